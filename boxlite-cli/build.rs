@@ -1,7 +1,8 @@
 //! Build script for boxlite-cli.
 //!
 //! Copies runtime to ~/.local/share/boxlite/ and sets rpath.
-//! Requires: Run `./scripts/build/build-runtime.sh` first.
+//! Requires: Run `./scripts/build/build-runtime.sh` first (not needed for
+//! remote-only builds, which skip this step).
 
 use std::path::{Path, PathBuf};
 use std::{env, fs};
@@ -17,6 +18,11 @@ fn main() {
         project_root.join("target/boxlite-runtime").display()
     );
 
+    // Remote-only builds run no boxes locally, so there is no runtime to ship
+    if env::var_os("CARGO_FEATURE_LOCAL").is_none() {
+        return;
+    }
+
     // Find runtime directory (may be None for clippy/check)
     let Some(runtime_src) = find_runtime_dir(project_root) else {
         return;
@@ -2,28 +2,41 @@
 //! This module contains all CLI-related code including the main CLI structure,
 //! subcommands, and flag definitions.
 
-use boxlite::runtime::options::{PortProtocol, PortSpec, VolumeSpec};
-use boxlite::{BoxCommand, BoxOptions, BoxliteOptions, BoxliteRuntime};
-use clap::{Args, Command, Parser, Subcommand, ValueEnum};
+use crate::error::{EXIT_CODES_HELP, ErrorFormat};
+use crate::reporter::{ColorChoice, Reporter};
+use boxlite::audit::{JsonlAuditSink, audit_dir};
+use boxlite::policy::{Severity, SeverityPolicy};
+use boxlite::runtime::constants::envs;
+use boxlite::runtime::options::{BoxPriority, NetworkMode, PortProtocol, PortSpec};
+use boxlite::{
+    BoxCommand, BoxOptionsBuilder, BoxStatus, BoxliteOptions, BoxliteRestOptions, BoxliteRuntime,
+    ListFilter,
+};
+use clap::{Args, Command, Parser, Subcommand, ValueEnum, ValueHint};
 use clap_complete::shells::{Bash, Fish, Zsh};
 use std::io::{IsTerminal, Write};
 use std::path::Path;
+use std::sync::Arc;
 
-/// Helper to parse CLI environment variables and apply them to BoxOptions
-pub fn apply_env_vars(env: &[String], opts: &mut BoxOptions) {
-    apply_env_vars_with_lookup(env, opts, |k| std::env::var(k).ok())
+/// Helper to parse CLI environment variables and apply them to the box options
+pub fn apply_env_vars<R>(env: &[String], builder: BoxOptionsBuilder<R>) -> BoxOptionsBuilder<R> {
+    apply_env_vars_with_lookup(env, builder, |k| std::env::var(k).ok())
 }
 
 /// Helper to parse CLI environment variables with custom lookup for host variables
-pub fn apply_env_vars_with_lookup<F>(env: &[String], opts: &mut BoxOptions, lookup: F)
+pub fn apply_env_vars_with_lookup<R, F>(
+    env: &[String],
+    mut builder: BoxOptionsBuilder<R>,
+    lookup: F,
+) -> BoxOptionsBuilder<R>
 where
     F: Fn(&str) -> Option<String>,
 {
     for env_str in env {
         if let Some((k, v)) = env_str.split_once('=') {
-            opts.env.push((k.to_string(), v.to_string()));
+            builder = builder.env(k, v);
         } else if let Some(val) = lookup(env_str) {
-            opts.env.push((env_str.to_string(), val));
+            builder = builder.env(env_str.as_str(), val);
         } else {
             tracing::warn!(
                 "Environment variable '{}' not found on host, skipping",
@@ -31,6 +44,7 @@ where
             );
         }
     }
+    builder
 }
 
 // ============================================================================
@@ -38,7 +52,13 @@ where
 // ============================================================================
 
 #[derive(Parser, Debug)]
-#[command(name = "boxlite", author, version, about = "BoxLite CLI")]
+#[command(
+    name = "boxlite",
+    author,
+    version,
+    about = "BoxLite CLI",
+    after_long_help = EXIT_CODES_HELP
+)]
 pub struct Cli {
     #[command(flatten)]
     pub global: GlobalFlags,
@@ -53,6 +73,10 @@ pub enum Commands {
     Run(crate::commands::run::RunArgs),
     /// Execute a command in a running box
     Exec(crate::commands::exec::ExecArgs),
+    /// Execute a command in every running box matching --filter
+    ExecAll(crate::commands::exec_all::ExecAllArgs),
+    /// Show the output of a detached exec
+    ExecLogs(crate::commands::exec_logs::ExecLogsArgs),
     /// Create a new box
     Create(crate::commands::create::CreateArgs),
 
@@ -63,6 +87,9 @@ pub enum Commands {
     /// Remove one or more boxes
     Rm(crate::commands::rm::RmArgs),
 
+    /// Remove all exited boxes
+    Prune(crate::commands::prune::PruneArgs),
+
     /// Start one or more stopped boxes
     Start(crate::commands::start::StartArgs),
 
@@ -72,9 +99,15 @@ pub enum Commands {
     /// Restart one or more boxes
     Restart(crate::commands::restart::RestartArgs),
 
+    /// Flush a running box's writes to its disk images
+    Sync(crate::commands::sync::SyncArgs),
+
     /// Pull an image from a registry
     Pull(crate::commands::pull::PullArgs),
 
+    /// Build an image from a Containerfile (minimal subset)
+    Build(crate::commands::build::BuildArgs),
+
     /// List images
     Images(crate::commands::images::ImagesArgs),
 
@@ -84,6 +117,17 @@ pub enum Commands {
     /// Copy files/folders between host and box
     Cp(crate::commands::cp::CpArgs),
 
+    /// Forward a local TCP port to a port in a running box
+    PortForward(crate::commands::port_forward::PortForwardArgs),
+
+    /// Mount a stopped box's filesystem read-only on the host
+    #[cfg(feature = "fuse")]
+    Mount(crate::commands::mount::MountArgs),
+
+    /// Unmount a box mounted with `boxlite mount`
+    #[cfg(feature = "fuse")]
+    Umount(crate::commands::umount::UmountArgs),
+
     /// Display system-wide runtime information
     Info(crate::commands::info::InfoArgs),
 
@@ -92,10 +136,94 @@ pub enum Commands {
 
     /// Display resource usage statistics for a box
     Stats(crate::commands::stats::StatsArgs),
+    /// Check host capabilities, and diagnose why a box failed to start or runs degraded
+    /// Diagnose why a box failed to start or runs degraded
+    Doctor(crate::commands::doctor::DoctorArgs),
+
+    /// Low-level diagnostics for a running box
+    Debug(crate::commands::debug::DebugArgs),
+
+    /// Reclaim unused space in stopped boxes' disks
+    Compact(crate::commands::compact::CompactArgs),
+
+    /// Export a stopped box to a portable archive
+    Export(crate::commands::export::ExportArgs),
+
+    /// Import a box from an exported archive
+    Import(crate::commands::import::ImportArgs),
+
+    /// Manage box snapshots
+    Snapshot(crate::commands::snapshot::SnapshotArgs),
+
+    /// Manage supervised services running next to a box's entrypoint
+    Service(crate::commands::service::ServiceArgs),
+
+    /// Bring a set of boxes up and down together
+    Compose(crate::commands::compose::ComposeArgs),
+
+    /// Manage removed boxes kept in the trash
+    Trash(crate::commands::trash::TrashArgs),
+
+    /// Manage box templates
+    Template(crate::commands::template::TemplateArgs),
 
-    /// Generate shell completion script (hidden from help)
-    #[command(hide = true)]
-    Completion(CompletionArgs),
+    /// Inspect runtime-wide disk usage
+    System(crate::commands::system::SystemArgs),
+
+    /// Inspect the audit log
+    Audit(crate::commands::audit::AuditArgs),
+
+    /// Measure box lifecycle latency (cold start, exec, copy, snapshot)
+    Bench(crate::commands::bench::BenchArgs),
+
+    /// Show version information (--verbose for engine components)
+    Version(crate::commands::version::VersionArgs),
+
+    /// Generate a shell completion script, completing box names dynamically
+    #[command(alias = "completion")]
+    Completions(CompletionArgs),
+
+    /// Generate man pages
+    Man(ManArgs),
+}
+
+impl Commands {
+    /// Whether the command honors `--dry-run`.
+    pub fn supports_dry_run(&self) -> bool {
+        use crate::commands::snapshot::SnapshotCommand;
+        match self {
+            Commands::Rm(_) | Commands::Prune(_) | Commands::Import(_) => true,
+            Commands::Snapshot(args) => matches!(
+                args.command,
+                SnapshotCommand::Restore(_) | SnapshotCommand::Prune(_)
+            ),
+            _ => false,
+        }
+    }
+
+    /// Name of the command if it works on this machine rather than through
+    /// the runtime (images, host files, the VM engine), so it cannot reach
+    /// boxes on a remote host.
+    pub fn local_only(&self) -> Option<&'static str> {
+        Some(match self {
+            Commands::Pull(_) => "pull",
+            Commands::Build(_) => "build",
+            Commands::Images(_) => "images",
+            #[cfg(feature = "fuse")]
+            Commands::Mount(_) => "mount",
+            #[cfg(feature = "fuse")]
+            Commands::Umount(_) => "umount",
+            Commands::Info(_) => "info",
+            Commands::Logs(_) => "logs",
+            Commands::Doctor(_) => "doctor",
+            Commands::Debug(_) => "debug",
+            Commands::Compact(_) => "compact",
+            Commands::System(_) => "system",
+            Commands::Audit(_) => "audit",
+            Commands::Bench(_) => "bench",
+            _ => return None,
+        })
+    }
 }
 
 /// Shell for which to generate completion script.
@@ -107,7 +235,7 @@ pub enum Shell {
     Fish,
 }
 
-/// Arguments for the completion subcommand.
+/// Arguments for the completions subcommand.
 #[derive(Args, Debug)]
 pub struct CompletionArgs {
     /// Shell to generate completion for (bash, zsh, fish).
@@ -115,12 +243,31 @@ pub struct CompletionArgs {
 }
 
 /// Writes a completion script for the given shell to `out`.
+///
+/// Box names are completed by running `boxlite ps` (see [`crate::completion`]).
 pub fn generate_completion(shell: &Shell, cmd: &mut Command, name: &str, out: &mut dyn Write) {
+    let mut script = Vec::new();
     match shell {
-        Shell::Bash => clap_complete::generate(Bash, cmd, name, out),
-        Shell::Zsh => clap_complete::generate(Zsh, cmd, name, out),
-        Shell::Fish => clap_complete::generate(Fish, cmd, name, out),
+        Shell::Bash => clap_complete::generate(Bash, cmd, name, &mut script),
+        Shell::Zsh => clap_complete::generate(Zsh, cmd, name, &mut script),
+        Shell::Fish => clap_complete::generate(Fish, cmd, name, &mut script),
     }
+    let script = String::from_utf8(script).expect("completion script is UTF-8");
+    let script = crate::completion::with_box_names(shell, cmd, name, script);
+    out.write_all(script.as_bytes())
+        .expect("failed to write completion script");
+}
+
+/// Arguments for the man subcommand.
+#[derive(Args, Debug)]
+pub struct ManArgs {
+    /// Command whose page to print, e.g. `snapshot restore` (default: boxlite)
+    #[arg(value_name = "COMMAND", conflicts_with = "dir")]
+    pub command: Vec<String>,
+
+    /// Write the pages of all commands into this directory instead
+    #[arg(long, value_name = "DIR", value_hint = ValueHint::DirPath)]
+    pub dir: Option<std::path::PathBuf>,
 }
 
 // ============================================================================
@@ -134,9 +281,20 @@ pub struct GlobalFlags {
     pub debug: bool,
 
     /// BoxLite home directory
-    #[arg(long, global = true, env = "BOXLITE_HOME")]
+    #[arg(long, global = true, env = "BOXLITE_HOME", value_hint = ValueHint::DirPath)]
     pub home: Option<std::path::PathBuf>,
 
+    /// Manage boxes on a remote BoxLite REST server instead of this machine
+    /// (required in remote-only builds)
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        env = "BOXLITE_HOST",
+        value_hint = ValueHint::Url
+    )]
+    pub host: Option<String>,
+
     /// Image registry to use (can be specified multiple times)
     #[arg(long, global = true, value_name = "REGISTRY")]
     pub registry: Vec<String>,
@@ -145,12 +303,73 @@ pub struct GlobalFlags {
     ///
     /// Specifies the JSON configuration file containing BoxLite options such as image_registries.
     /// If not provided, uses default options (no config file is loaded from $BOXLITE_HOME).
-    #[arg(long, global = true)]
+    #[arg(long, global = true, value_hint = ValueHint::FilePath)]
     pub config: Option<String>,
+
+    /// Air-gap mode: never contact a registry, use only locally cached images
+    #[arg(long, global = true, env = "BOXLITE_OFFLINE")]
+    pub offline: bool,
+
+    /// Record mutating operations to the audit log ($BOXLITE_HOME/audit)
+    #[arg(long, global = true, env = "BOXLITE_AUDIT")]
+    pub audit: bool,
+
+    /// When to use colors and progress spinners (NO_COLOR is honored in auto mode)
+    #[arg(long, global = true, value_enum, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// How to report a failure on stderr (json: one object with code, variant,
+    /// message and box_id; see --help for exit codes)
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+
+    /// Refuse to create boxes from images with vulnerabilities above this severity
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "SEVERITY",
+        env = "BOXLITE_MAX_SEVERITY"
+    )]
+    pub max_severity: Option<MaxSeverity>,
+
+    /// Image manifest digest exempt from --max-severity (can be specified multiple times)
+    #[arg(long, global = true, value_name = "DIGEST")]
+    pub allow_image_digest: Vec<String>,
+
+    /// Print what a destructive command would do without changing anything
+    /// (rm, prune, import, snapshot restore, snapshot prune)
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Wait up to SECS for another boxlite process using the home directory
+    /// to finish, instead of failing right away
+    #[arg(long, global = true, value_name = "SECS", env = "BOXLITE_WAIT_LOCK")]
+    pub wait_lock: Option<u64>,
+}
+
+/// Highest vulnerability severity an image may have to be run.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum MaxSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl From<MaxSeverity> for Severity {
+    fn from(max: MaxSeverity) -> Self {
+        match max {
+            MaxSeverity::Low => Severity::Low,
+            MaxSeverity::Medium => Severity::Medium,
+            MaxSeverity::High => Severity::High,
+        }
+    }
 }
 
 impl GlobalFlags {
-    /// Resolve runtime options from config file and CLI overrides (--home, --registry).
+    /// Resolve runtime options from config file and CLI overrides (--home, --registry, --offline,
+    /// --audit, --wait-lock).
     pub fn resolve_runtime_options(&self) -> anyhow::Result<BoxliteOptions> {
         let mut options = if let Some(config_path) = &self.config {
             crate::config::load_config(Path::new(config_path))?
@@ -171,21 +390,108 @@ impl GlobalFlags {
                 .collect();
         }
 
+        if self.offline {
+            options.offline = true;
+        }
+
+        if self.audit {
+            options.audit = true;
+        }
+
+        if let Some(secs) = self.wait_lock {
+            options.home_lock_wait = Some(std::time::Duration::from_secs(secs));
+        }
+
         Ok(options)
     }
 
     /// Create a runtime from pre-resolved options (avoids resolving twice when caller already has options).
+    ///
+    /// With `--host` the runtime talks to that REST server; only `--audit` and
+    /// `--home` (for the audit log) apply to it.
     pub fn create_runtime_with_options(
         &self,
-        options: BoxliteOptions,
+        mut options: BoxliteOptions,
     ) -> anyhow::Result<BoxliteRuntime> {
-        BoxliteRuntime::new(options).map_err(Into::into)
+        if let Some(rest) = self.rest_options()? {
+            let mut runtime = BoxliteRuntime::rest(rest)?;
+            if let Some(policy) = self.create_policy() {
+                runtime = runtime.with_create_policy(Arc::new(policy))?;
+            }
+            if options.audit {
+                let sink = JsonlAuditSink::new(audit_dir(&options.home_dir))?;
+                runtime = runtime.with_audit_sink(Arc::new(sink));
+            }
+            return Ok(runtime);
+        }
+
+        let Some(policy) = self.create_policy() else {
+            return BoxliteRuntime::new(options).map_err(Into::into);
+        };
+
+        // Attach the audit sink on top of the policy so denials are audited
+        let audit_dir = std::mem::take(&mut options.audit).then(|| audit_dir(&options.home_dir));
+        let mut runtime = BoxliteRuntime::new(options)?.with_create_policy(Arc::new(policy))?;
+        if let Some(dir) = audit_dir {
+            runtime = runtime.with_audit_sink(Arc::new(JsonlAuditSink::new(dir)?));
+        }
+        Ok(runtime)
+    }
+
+    /// REST options for `--host`, with credentials and API prefix from
+    /// BOXLITE_REST_CLIENT_ID, BOXLITE_REST_CLIENT_SECRET and BOXLITE_REST_PREFIX.
+    ///
+    /// `None` selects the local runtime, which remote-only builds lack.
+    fn rest_options(&self) -> anyhow::Result<Option<BoxliteRestOptions>> {
+        let Some(host) = &self.host else {
+            if cfg!(feature = "local") {
+                return Ok(None);
+            }
+            anyhow::bail!("BOXLITE_HOST (or --host) is required: this is a remote-only build");
+        };
+
+        let mut options = BoxliteRestOptions::new(host.clone());
+        if let (Ok(id), Ok(secret)) = (
+            std::env::var(envs::BOXLITE_REST_CLIENT_ID),
+            std::env::var(envs::BOXLITE_REST_CLIENT_SECRET),
+        ) {
+            options = options.with_credentials(id, secret);
+        }
+        if let Ok(prefix) = std::env::var(envs::BOXLITE_REST_PREFIX) {
+            options = options.with_prefix(prefix);
+        }
+        Ok(Some(options))
+    }
+
+    /// Why `local_only` commands are refused, if boxes are managed remotely.
+    pub fn remote_restriction(&self) -> Option<&'static str> {
+        if !cfg!(feature = "local") {
+            Some("not available in remote-only build")
+        } else if self.host.is_some() {
+            Some("not available with --host")
+        } else {
+            None
+        }
+    }
+
+    /// Create policy from --max-severity and --allow-image-digest, if set.
+    fn create_policy(&self) -> Option<SeverityPolicy> {
+        let mut policy = SeverityPolicy::new(self.max_severity?.into());
+        for digest in &self.allow_image_digest {
+            policy = policy.allow_digest(digest.clone());
+        }
+        Some(policy)
     }
 
     pub fn create_runtime(&self) -> anyhow::Result<BoxliteRuntime> {
         let options = self.resolve_runtime_options()?;
         self.create_runtime_with_options(options)
     }
+
+    /// Output sink honoring `--color`, `NO_COLOR` and the attached terminals.
+    pub fn reporter(&self) -> Reporter {
+        Reporter::new(self.color)
+    }
 }
 
 // ============================================================================
@@ -206,25 +512,38 @@ pub struct ProcessFlags {
     #[arg(short = 'e', long = "env")]
     pub env: Vec<String>,
 
+    /// Redact the value of this environment variable from the output
+    #[arg(long = "redact-env", value_name = "KEY")]
+    pub redact_env: Vec<String>,
+
     /// Working directory inside the box
     #[arg(short = 'w', long = "workdir")]
     pub workdir: Option<String>,
 }
 
 impl ProcessFlags {
-    /// Apply process configuration to BoxOptions
-    pub fn apply_to(&self, opts: &mut BoxOptions) -> anyhow::Result<()> {
-        self.apply_to_with_lookup(opts, |k| std::env::var(k).ok())
+    /// Apply process configuration to the box options
+    pub fn apply_to<R>(&self, builder: BoxOptionsBuilder<R>) -> BoxOptionsBuilder<R> {
+        self.apply_to_with_lookup(builder, |k| std::env::var(k).ok())
     }
 
     /// Internal helper for dependency injection of environment variables
-    fn apply_to_with_lookup<F>(&self, opts: &mut BoxOptions, lookup: F) -> anyhow::Result<()>
+    fn apply_to_with_lookup<R, F>(
+        &self,
+        mut builder: BoxOptionsBuilder<R>,
+        lookup: F,
+    ) -> BoxOptionsBuilder<R>
     where
         F: Fn(&str) -> Option<String>,
     {
-        opts.working_dir = self.workdir.clone();
-        apply_env_vars_with_lookup(&self.env, opts, lookup);
-        Ok(())
+        if let Some(workdir) = &self.workdir {
+            builder = builder.working_dir(workdir.as_str());
+        }
+        builder = apply_env_vars_with_lookup(&self.env, builder, lookup);
+        for key in &self.redact_env {
+            builder = builder.redact_env(key.as_str());
+        }
+        builder
     }
 
     /// Validate process flags
@@ -247,6 +566,10 @@ impl ProcessFlags {
             }
         }
 
+        for key in &self.redact_env {
+            cmd = cmd.redact_env(key);
+        }
+
         if let Some(ref w) = self.workdir {
             cmd = cmd.working_dir(w);
         }
@@ -272,19 +595,63 @@ pub struct ResourceFlags {
     /// Memory limit (in MiB)
     #[arg(long)]
     pub memory: Option<u32>,
+
+    /// Swap inside the box (in MiB, 0 for none); the swap file takes space
+    /// on the box disk
+    #[arg(long, value_name = "MIB")]
+    pub memory_swap: Option<u32>,
+
+    /// CPU priority relative to other boxes; low priority boxes are also
+    /// throttled under host CPU pressure when the runtime has a
+    /// `cpu_pressure` policy
+    #[arg(long, value_name = "PRIORITY")]
+    pub priority: Option<PriorityArg>,
+
+    /// CPU weight (1-10000, default 100), overriding --priority's
+    #[arg(long, value_name = "WEIGHT")]
+    pub cpu_shares: Option<u32>,
+}
+
+/// CPU priority of a box.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum PriorityArg {
+    Low,
+    Normal,
+    High,
+}
+
+impl From<PriorityArg> for BoxPriority {
+    fn from(priority: PriorityArg) -> Self {
+        match priority {
+            PriorityArg::Low => BoxPriority::Low,
+            PriorityArg::Normal => BoxPriority::Normal,
+            PriorityArg::High => BoxPriority::High,
+        }
+    }
 }
 
 impl ResourceFlags {
-    pub fn apply_to(&self, opts: &mut BoxOptions) {
+    pub fn apply_to<R>(&self, mut builder: BoxOptionsBuilder<R>) -> BoxOptionsBuilder<R> {
         if let Some(cpus) = self.cpus {
             if cpus > 255 {
                 tracing::warn!("CPU limit capped at 255 (requested {})", cpus);
             }
-            opts.cpus = Some(cpus.min(255) as u8);
+            builder = builder.cpus(cpus.min(255) as u8);
         }
         if let Some(mem) = self.memory {
-            opts.memory_mib = Some(mem);
+            builder = builder.memory_mib(mem);
+        }
+        if let Some(swap) = self.memory_swap {
+            builder = builder.swap_mib(swap);
+        }
+        if let Some(priority) = self.priority {
+            builder = builder.priority(priority.into());
         }
+        if let Some(shares) = self.cpu_shares {
+            builder = builder.cpu_shares(shares);
+        }
+        builder
     }
 }
 
@@ -297,10 +664,34 @@ pub struct PublishFlags {
     /// Publish a box port to the host (format: [hostPort:]boxPort[/tcp|udp], e.g. 18789:18789)
     #[arg(short = 'p', long = "publish", value_name = "PORT")]
     pub publish: Vec<String>,
+
+    /// Network access of the box; `none` leaves only loopback (default, none)
+    #[arg(long = "network", value_name = "MODE")]
+    pub network: Option<NetworkArg>,
+}
+
+/// Network access of a box.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum NetworkArg {
+    Default,
+    None,
+}
+
+impl From<NetworkArg> for NetworkMode {
+    fn from(network: NetworkArg) -> Self {
+        match network {
+            NetworkArg::Default => NetworkMode::Default,
+            NetworkArg::None => NetworkMode::None,
+        }
+    }
 }
 
 impl PublishFlags {
-    pub fn apply_to(&self, opts: &mut BoxOptions) -> anyhow::Result<()> {
+    pub fn apply_to<R>(
+        &self,
+        mut builder: BoxOptionsBuilder<R>,
+    ) -> anyhow::Result<BoxOptionsBuilder<R>> {
         for s in &self.publish {
             let spec = parse_publish_spec(s)?;
             if matches!(spec.protocol, PortProtocol::Udp) {
@@ -309,67 +700,21 @@ impl PublishFlags {
                     s
                 );
             }
-            opts.ports.push(spec);
+            builder = builder.port(spec);
         }
-        Ok(())
+        if let Some(network) = self.network {
+            builder = builder.network(network.into());
+        }
+        Ok(builder)
     }
 }
 
-/// Parse a single publish spec: `[hostPort:]boxPort[/tcp|udp]`.
-/// - `boxPort` → host_port=None, guest_port=boxPort
-/// - `hostPort:boxPort` → host_port=Some(hostPort), guest_port=boxPort
+/// Parse a single publish spec: `[hostPort:]boxPort[/tcp|udp]`, see
+/// [`boxlite::validate::port_spec`].
 ///
 /// Only TCP is forwarded by the runtime today; UDP is accepted but not yet implemented.
 fn parse_publish_spec(s: &str) -> anyhow::Result<PortSpec> {
-    let s = s.trim();
-    if s.is_empty() {
-        anyhow::bail!("empty port spec");
-    }
-    let (rest, protocol) = match s.split_once('/') {
-        Some((r, proto)) => {
-            let p = if proto.eq_ignore_ascii_case("tcp") {
-                PortProtocol::Tcp
-            } else if proto.eq_ignore_ascii_case("udp") {
-                PortProtocol::Udp
-            } else {
-                anyhow::bail!("invalid protocol {:?}; use tcp or udp", proto)
-            };
-            (r.trim(), p)
-        }
-        None => (s, PortProtocol::Tcp),
-    };
-    let parts: Vec<&str> = rest.splitn(2, ':').map(str::trim).collect();
-    let (host_port, guest_port) = match parts.as_slice() {
-        [guest] => {
-            let g = parse_port(guest)?;
-            (None, g)
-        }
-        [host, guest] => {
-            let h = parse_port(host)?;
-            let g = parse_port(guest)?;
-            (Some(h), g)
-        }
-        _ => anyhow::bail!(
-            "invalid port spec {:?}; use hostPort:boxPort or boxPort[/tcp]",
-            s
-        ),
-    };
-    Ok(PortSpec {
-        host_port,
-        guest_port,
-        protocol,
-        host_ip: None,
-    })
-}
-
-fn parse_port(s: &str) -> anyhow::Result<u16> {
-    let n: u16 = s
-        .parse()
-        .map_err(|_| anyhow::anyhow!("invalid port number {:?}", s))?;
-    if n == 0 {
-        anyhow::bail!("port must be 1-65535");
-    }
-    Ok(n)
+    Ok(boxlite::validate::port_spec(s)?)
 }
 
 // ============================================================================
@@ -516,11 +861,11 @@ fn anonymous_volume_base(home: Option<&std::path::Path>) -> std::path::PathBuf {
 
 impl VolumeFlags {
     /// Apply volume flags to options. Pass `home` for anonymous volume storage (e.g. from GlobalFlags).
-    pub fn apply_to(
+    pub fn apply_to<R>(
         &self,
-        opts: &mut BoxOptions,
+        mut builder: BoxOptionsBuilder<R>,
         home: Option<&std::path::Path>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<BoxOptionsBuilder<R>> {
         let base = anonymous_volume_base(home);
         for s in self.volume.iter() {
             let spec = parse_volume_spec(s)?;
@@ -546,13 +891,36 @@ impl VolumeFlags {
                     dir.to_string_lossy().into_owned()
                 }
             };
-            opts.volumes.push(VolumeSpec {
-                host_path,
-                guest_path: spec.guest_path,
-                read_only: spec.read_only,
-            });
+            builder = builder.volume(host_path, spec.guest_path, spec.read_only);
         }
-        Ok(())
+        Ok(builder)
+    }
+}
+
+// ============================================================================
+// FILTER FLAGS
+// ============================================================================
+
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterFlags {
+    /// Select boxes by label=<key>[=<value>], status=<status> or name=<name>
+    /// (repeatable; a box must match all)
+    #[arg(long = "filter", value_name = "FILTER")]
+    pub filter: Vec<String>,
+}
+
+impl FilterFlags {
+    pub fn is_set(&self) -> bool {
+        !self.filter.is_empty()
+    }
+
+    /// Parse the filters, requiring `default_status` when none selects a status.
+    pub fn to_filter(&self, default_status: Option<BoxStatus>) -> anyhow::Result<ListFilter> {
+        let mut filter = ListFilter::from_exprs(&self.filter)?;
+        if filter.status.is_none() {
+            filter.status = default_status;
+        }
+        Ok(filter)
     }
 }
 
@@ -573,35 +941,198 @@ pub struct ManagementFlags {
     /// Automatically remove the box when it exits
     #[arg(long)]
     pub rm: bool,
+
+    /// Run an init inside the box that forwards signals and reaps processes
+    #[arg(long)]
+    pub init: bool,
+
+    /// Shell script to run in place of the entrypoint; it receives the
+    /// entrypoint and command as arguments (end it with `exec "$@"`)
+    #[arg(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    pub entrypoint_script: Option<std::path::PathBuf>,
+
+    /// Capture core dumps of crashing processes (see `boxlite debug cores`)
+    #[arg(long)]
+    pub capture_core_dumps: bool,
+
+    /// Mount the container's root filesystem read-only (/tmp and /run stay
+    /// writable)
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Mount an empty, writable tmpfs at PATH in the container (repeatable)
+    #[arg(long = "tmpfs", value_name = "PATH")]
+    pub tmpfs: Vec<String>,
+
+    /// Seconds the box gets to exit after SIGTERM before it is killed
+    /// (default: 3)
+    #[arg(long, value_name = "SECS")]
+    pub stop_timeout: Option<u64>,
+
+    /// Stop the box once it has been idle this long, e.g. 30m or 2h: no
+    /// exec running and no files copied in or out
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub auto_stop: Option<std::time::Duration>,
+
+    /// Time zone of the box: an IANA name like Europe/Berlin, or `host`
+    #[arg(long, value_name = "ZONE")]
+    pub timezone: Option<String>,
+
+    /// Locale of the box, e.g. en_US.UTF-8 (sets LANG)
+    #[arg(long, value_name = "LOCALE")]
+    pub locale: Option<String>,
+
+    /// Set a label on the box, for selecting it with --filter
+    #[arg(short = 'l', long = "label", value_name = "KEY=VALUE")]
+    pub labels: Vec<String>,
+}
+
+/// Parse a duration in seconds, or with an `s`, `m`, `h` or `d` suffix.
+fn parse_duration(s: &str) -> Result<std::time::Duration, String> {
+    let (digits, factor) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 60 * 60),
+        Some((i, 'd')) => (&s[..i], 24 * 60 * 60),
+        _ => (s, 1),
+    };
+    digits
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(factor))
+        .map(std::time::Duration::from_secs)
+        .ok_or_else(|| format!("invalid duration '{}', expected e.g. 90s, 30m or 2h", s))
 }
 
 impl ManagementFlags {
-    pub fn apply_to(&self, opts: &mut BoxOptions) {
-        opts.detach = self.detach;
-        opts.auto_remove = self.rm;
+    pub fn apply_to<R>(
+        &self,
+        builder: BoxOptionsBuilder<R>,
+    ) -> anyhow::Result<BoxOptionsBuilder<R>> {
+        let mut builder = builder
+            .detach(self.detach)
+            .auto_remove(self.rm)
+            .init(self.init)
+            .capture_core_dumps(self.capture_core_dumps)
+            .read_only_rootfs(self.read_only);
+        for path in &self.tmpfs {
+            builder = builder.writable_path(path.as_str());
+        }
+        if let Some(secs) = self.stop_timeout {
+            builder = builder.shutdown_grace_period(std::time::Duration::from_secs(secs));
+        }
+        if let Some(window) = self.auto_stop {
+            builder = builder.auto_stop_after_idle(window);
+        }
+        if let Some(zone) = &self.timezone {
+            builder = builder.timezone(zone.as_str());
+        }
+        if let Some(locale) = &self.locale {
+            builder = builder.locale(locale.as_str());
+        }
+        for label in &self.labels {
+            // A bare key gets an empty value, like `docker run --label`
+            let (key, value) = label.split_once('=').unwrap_or((label, ""));
+            builder = builder.label(key, value);
+        }
+        if let Some(path) = &self.entrypoint_script {
+            let script = std::fs::read_to_string(path)
+                .map_err(|e| anyhow::anyhow!("entrypoint script {}: {}", path.display(), e))?;
+            builder = builder.entrypoint_script(script);
+        }
+        Ok(builder)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use boxlite::BoxOptions;
+
+    fn build(builder: BoxOptionsBuilder) -> BoxOptions {
+        builder.image("alpine:latest").build().unwrap()
+    }
+
+    #[test]
+    fn test_create_policy_only_with_max_severity() {
+        let cli = Cli::try_parse_from(["boxlite", "list"]).unwrap();
+        assert!(cli.global.create_policy().is_none());
+
+        let cli = Cli::try_parse_from([
+            "boxlite",
+            "--max-severity",
+            "high",
+            "--allow-image-digest",
+            "sha256:aaaa",
+            "list",
+        ])
+        .unwrap();
+        assert_eq!(cli.global.max_severity, Some(MaxSeverity::High));
+        assert_eq!(cli.global.allow_image_digest, vec!["sha256:aaaa"]);
+        assert!(cli.global.create_policy().is_some());
+
+        assert!(Cli::try_parse_from(["boxlite", "--max-severity", "critical", "list"]).is_err());
+    }
+
+    #[test]
+    fn test_host_selects_rest_runtime() {
+        let cli = Cli::try_parse_from(["boxlite", "list"]).unwrap();
+        if cfg!(feature = "local") {
+            assert!(cli.global.rest_options().unwrap().is_none());
+            assert_eq!(cli.global.remote_restriction(), None);
+        } else {
+            assert!(cli.global.rest_options().is_err());
+        }
+
+        let cli = Cli::try_parse_from(["boxlite", "--host", "https://boxes.example.com", "list"])
+            .unwrap();
+        let rest = cli.global.rest_options().unwrap().unwrap();
+        assert_eq!(rest.url, "https://boxes.example.com");
+        assert!(cli.global.remote_restriction().is_some());
+    }
+
+    #[test]
+    fn test_local_only_commands() {
+        let local_only = |args: &[&str]| Cli::try_parse_from(args).unwrap().command.local_only();
+        assert_eq!(local_only(&["boxlite", "images"]), Some("images"));
+        assert_eq!(local_only(&["boxlite", "logs", "web"]), Some("logs"));
+        assert_eq!(local_only(&["boxlite", "list"]), None);
+        assert_eq!(local_only(&["boxlite", "exec", "web", "ls"]), None);
+    }
+
+    #[test]
+    fn test_wait_lock_sets_home_lock_wait() {
+        let cli = Cli::try_parse_from(["boxlite", "list"]).unwrap();
+        let options = cli.global.resolve_runtime_options().unwrap();
+        assert_eq!(options.home_lock_wait, None);
+
+        let cli = Cli::try_parse_from(["boxlite", "list", "--wait-lock", "30"]).unwrap();
+        let options = cli.global.resolve_runtime_options().unwrap();
+        assert_eq!(
+            options.home_lock_wait,
+            Some(std::time::Duration::from_secs(30))
+        );
+    }
 
     #[test]
     fn test_apply_env_vars_with_lookup() {
-        let mut opts = BoxOptions::default();
         let current_env = vec![
             "TEST_VAR=test_value".to_string(),
             "TEST_HOST_VAR".to_string(),
             "NON_EXISTENT_VAR".to_string(),
         ];
 
-        apply_env_vars_with_lookup(&current_env, &mut opts, |k| {
-            if k == "TEST_HOST_VAR" {
-                Some("host_value".to_string())
-            } else {
-                None
-            }
-        });
+        let opts = build(apply_env_vars_with_lookup(
+            &current_env,
+            BoxOptions::builder(),
+            |k| {
+                if k == "TEST_HOST_VAR" {
+                    Some("host_value".to_string())
+                } else {
+                    None
+                }
+            },
+        ));
 
         assert!(
             opts.env
@@ -621,14 +1152,174 @@ mod tests {
         let flags = ResourceFlags {
             cpus: Some(1000),
             memory: None,
+            memory_swap: None,
+            priority: None,
+            cpu_shares: None,
         };
 
-        let mut opts = BoxOptions::default();
-        flags.apply_to(&mut opts);
+        let opts = build(flags.apply_to(BoxOptions::builder()));
 
         assert_eq!(opts.cpus, Some(255));
     }
 
+    #[test]
+    fn test_resource_flags_memory_swap() {
+        let flags = ResourceFlags {
+            cpus: None,
+            memory: Some(512),
+            memory_swap: Some(1024),
+            priority: None,
+            cpu_shares: None,
+        };
+
+        let opts = build(flags.apply_to(BoxOptions::builder()));
+
+        assert_eq!(opts.memory_mib, Some(512));
+        assert_eq!(opts.swap_mib, Some(1024));
+    }
+
+    #[test]
+    fn test_resource_flags_priority() {
+        let flags = ResourceFlags {
+            cpus: None,
+            memory: None,
+            memory_swap: None,
+            priority: Some(PriorityArg::Low),
+            cpu_shares: Some(50),
+        };
+
+        let opts = build(flags.apply_to(BoxOptions::builder()));
+
+        assert_eq!(opts.priority, BoxPriority::Low);
+        assert_eq!(opts.cpu_shares, Some(50));
+    }
+
+    #[test]
+    fn test_management_flags_read_entrypoint_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wrap.sh");
+        std::fs::write(&path, "#!/bin/sh\nexec \"$@\"\n").unwrap();
+        let mut flags = ManagementFlags {
+            name: None,
+            detach: false,
+            rm: true,
+            init: false,
+            entrypoint_script: Some(path),
+            capture_core_dumps: false,
+            read_only: false,
+            tmpfs: vec![],
+            stop_timeout: None,
+            auto_stop: None,
+            timezone: None,
+            locale: None,
+            labels: vec![],
+        };
+
+        let opts = build(flags.apply_to(BoxOptions::builder()).unwrap());
+        assert_eq!(
+            opts.entrypoint_script.as_deref(),
+            Some("#!/bin/sh\nexec \"$@\"\n")
+        );
+
+        flags.entrypoint_script = Some(dir.path().join("missing.sh"));
+        let err = flags.apply_to(BoxOptions::builder()).unwrap_err();
+        assert!(err.to_string().contains("missing.sh"), "{err}");
+    }
+
+    #[test]
+    fn test_management_flags_labels() {
+        let cli = Cli::try_parse_from([
+            "boxlite", "create", "--label", "tier=web", "-l", "canary", "alpine",
+        ])
+        .unwrap();
+        let Commands::Create(args) = cli.command else {
+            panic!("expected create");
+        };
+        let opts = build(args.management.apply_to(BoxOptions::builder()).unwrap());
+
+        assert_eq!(opts.labels["tier"], "web");
+        assert_eq!(opts.labels["canary"], "");
+    }
+
+    #[test]
+    fn test_management_flags_stop_timeout() {
+        let cli =
+            Cli::try_parse_from(["boxlite", "create", "--stop-timeout", "30", "alpine"]).unwrap();
+        let Commands::Create(args) = cli.command else {
+            panic!("expected create");
+        };
+        let opts = build(args.management.apply_to(BoxOptions::builder()).unwrap());
+        assert_eq!(
+            opts.shutdown_grace_period,
+            Some(std::time::Duration::from_secs(30))
+        );
+
+        let cli =
+            Cli::try_parse_from(["boxlite", "create", "--stop-timeout", "0", "alpine"]).unwrap();
+        let Commands::Create(args) = cli.command else {
+            panic!("expected create");
+        };
+        let builder = args.management.apply_to(BoxOptions::builder()).unwrap();
+        assert!(builder.image("alpine").build().is_err());
+    }
+
+    #[test]
+    fn test_management_flags_auto_stop() {
+        let cli =
+            Cli::try_parse_from(["boxlite", "create", "--auto-stop", "2h", "alpine"]).unwrap();
+        let Commands::Create(args) = cli.command else {
+            panic!("expected create");
+        };
+        let opts = build(args.management.apply_to(BoxOptions::builder()).unwrap());
+        assert_eq!(
+            opts.auto_stop_after_idle,
+            Some(std::time::Duration::from_secs(7200))
+        );
+
+        assert!(Cli::try_parse_from(["boxlite", "create", "--auto-stop", "2w", "alpine"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        let secs = |n| Ok(std::time::Duration::from_secs(n));
+        assert_eq!(parse_duration("45"), secs(45));
+        assert_eq!(parse_duration("90s"), secs(90));
+        assert_eq!(parse_duration("30m"), secs(1800));
+        assert_eq!(parse_duration("2h"), secs(7200));
+        assert_eq!(parse_duration("1d"), secs(86400));
+        for spec in ["", "h", "-5m", "1.5h", "2h30m", "99999999999999999999d"] {
+            assert!(parse_duration(spec).is_err(), "{spec}");
+        }
+    }
+
+    #[test]
+    fn test_filter_flags() {
+        let flags = FilterFlags {
+            filter: vec!["label=tier=web".into()],
+        };
+        assert_eq!(
+            flags.to_filter(None).unwrap(),
+            ListFilter::new().label("tier", "web")
+        );
+        // The default status applies only when no status filter is given
+        assert_eq!(
+            flags.to_filter(Some(BoxStatus::Running)).unwrap().status,
+            Some(BoxStatus::Running)
+        );
+        let flags = FilterFlags {
+            filter: vec!["status=stopped".into()],
+        };
+        assert_eq!(
+            flags.to_filter(Some(BoxStatus::Running)).unwrap().status,
+            Some(BoxStatus::Exited)
+        );
+
+        let flags = FilterFlags {
+            filter: vec!["tier=web".into()],
+        };
+        assert!(flags.to_filter(None).is_err());
+    }
+
     #[test]
     fn test_parse_publish_spec_host_box() {
         let spec = super::parse_publish_spec("18789:18789").unwrap();
@@ -675,14 +1366,32 @@ mod tests {
     fn test_publish_flags_apply_to() {
         let flags = PublishFlags {
             publish: vec!["18789:18789".to_string(), "8080:80/tcp".to_string()],
+            network: None,
         };
-        let mut opts = BoxOptions::default();
-        flags.apply_to(&mut opts).unwrap();
+        let opts = build(flags.apply_to(BoxOptions::builder()).unwrap());
         assert_eq!(opts.ports.len(), 2);
         assert_eq!(opts.ports[0].host_port, Some(18789));
         assert_eq!(opts.ports[0].guest_port, 18789);
         assert_eq!(opts.ports[1].host_port, Some(8080));
         assert_eq!(opts.ports[1].guest_port, 80);
+        assert_eq!(opts.network, NetworkMode::Default);
+    }
+
+    #[test]
+    fn test_publish_flags_network_none() {
+        let flags = PublishFlags {
+            publish: vec![],
+            network: Some(NetworkArg::None),
+        };
+        let opts = build(flags.apply_to(BoxOptions::builder()).unwrap());
+        assert_eq!(opts.network, NetworkMode::None);
+
+        let flags = PublishFlags {
+            publish: vec!["8080:80".to_string()],
+            network: Some(NetworkArg::None),
+        };
+        let builder = flags.apply_to(BoxOptions::builder()).unwrap();
+        assert!(builder.image("alpine:latest").build().is_err());
     }
 
     #[test]
@@ -798,8 +1507,7 @@ mod tests {
                 "/readonly:/ro:ro".to_string(),
             ],
         };
-        let mut opts = BoxOptions::default();
-        flags.apply_to(&mut opts, None).unwrap();
+        let opts = build(flags.apply_to(BoxOptions::builder(), None).unwrap());
         assert_eq!(opts.volumes.len(), 2);
         assert_eq!(opts.volumes[0].host_path, "/host/data");
         assert_eq!(opts.volumes[0].guest_path, "/guest/data");
@@ -817,8 +1525,7 @@ mod tests {
                 r"D:\readonly:/ro:ro".to_string(),
             ],
         };
-        let mut opts = BoxOptions::default();
-        flags.apply_to(&mut opts, None).unwrap();
+        let opts = build(flags.apply_to(BoxOptions::builder(), None).unwrap());
         assert_eq!(opts.volumes.len(), 2);
         assert_eq!(opts.volumes[0].host_path, r"C:\host\data");
         assert_eq!(opts.volumes[0].guest_path, "/guest/data");
@@ -834,8 +1541,7 @@ mod tests {
         let flags = VolumeFlags {
             volume: vec!["/data".to_string(), "/cache:ro".to_string()],
         };
-        let mut opts = BoxOptions::default();
-        flags.apply_to(&mut opts, Some(&base)).unwrap();
+        let opts = build(flags.apply_to(BoxOptions::builder(), Some(&base)).unwrap());
         assert_eq!(opts.volumes.len(), 2);
         assert_eq!(opts.volumes[0].guest_path, "/data");
         assert!(
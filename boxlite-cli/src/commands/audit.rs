@@ -0,0 +1,75 @@
+//! Inspect the audit log.
+
+use crate::cli::GlobalFlags;
+use crate::formatter;
+use boxlite::audit::{AuditOutcome, JsonlAuditSink};
+use clap::{Args, Subcommand};
+
+#[derive(Args, Debug)]
+pub struct AuditArgs {
+    #[command(subcommand)]
+    pub command: AuditCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AuditCommand {
+    /// Show the most recent audit events
+    Tail(TailArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct TailArgs {
+    /// Number of events to show
+    #[arg(short = 'n', long, default_value_t = 20)]
+    pub lines: usize,
+
+    /// Print raw JSON lines instead of a summary
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: AuditArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        AuditCommand::Tail(args) => tail(args, global),
+    }
+}
+
+fn tail(args: TailArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    // Read the files directly: no runtime (and no home lock) is needed
+    let options = global.resolve_runtime_options()?;
+    let dir = boxlite::audit::audit_dir(&options.home_dir);
+    let events = JsonlAuditSink::tail(&dir, args.lines)?;
+    let reporter = global.reporter().machine_readable(args.json);
+
+    for event in events {
+        if args.json {
+            reporter.println(serde_json::to_string(&event)?);
+            continue;
+        }
+
+        let outcome = match &event.outcome {
+            AuditOutcome::Success => "ok".to_string(),
+            AuditOutcome::Failure { error } => {
+                format!("failed: {}", error.lines().next().unwrap_or_default())
+            }
+        };
+        let summary: Vec<String> = event.args.iter().map(|(k, v)| format!("{k}={v}")).collect();
+
+        reporter.println(format!(
+            "{}  {:<9} {:<26} {}  [{}]{}",
+            formatter::format_time(&event.timestamp),
+            event.operation,
+            event.box_id.as_deref().unwrap_or("-"),
+            summary.join(" "),
+            event.caller,
+            event
+                .peer
+                .as_ref()
+                .map(|peer| format!(" via {peer}"))
+                .unwrap_or_default(),
+        ));
+        reporter.println(format!("    {outcome}"));
+    }
+
+    Ok(())
+}
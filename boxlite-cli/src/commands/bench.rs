@@ -0,0 +1,201 @@
+//! Measure end-to-end sandbox latency with the `boxlite::bench` scenarios.
+
+use crate::cli::GlobalFlags;
+use crate::commands::stats::format_bytes;
+use crate::formatter;
+use boxlite::bench::{self, BenchOptions, BenchReport, BenchResult, Scenario};
+use clap::{Args, ValueEnum};
+use tabled::Tabled;
+
+/// Benchmark box lifecycle latency.
+#[derive(Args, Debug)]
+pub struct BenchArgs {
+    /// Scenarios to run, in order (default: all)
+    #[arg(long = "scenario", value_enum, value_delimiter = ',')]
+    pub scenarios: Vec<ScenarioArg>,
+
+    /// Image of the benchmarked boxes
+    #[arg(long, default_value = bench::DEFAULT_IMAGE)]
+    pub image: String,
+
+    /// Timed iterations per scenario, after one untimed warm-up
+    #[arg(short = 'n', long, default_value_t = 10, value_parser = clap::value_parser!(u64).range(1..))]
+    pub iterations: u64,
+
+    /// Files in the tree copied by the copy-in scenario
+    #[arg(long, default_value_t = 1000)]
+    pub copy_files: usize,
+
+    /// Size in bytes of each file copied by the copy-in scenario
+    #[arg(long, default_value_t = 4096)]
+    pub copy_file_size: usize,
+
+    /// Output format (text, json)
+    #[arg(long, default_value_t = BenchOutput::Text, value_enum)]
+    pub output: BenchOutput,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScenarioArg {
+    /// Create, start, exec in and remove a new box
+    ColdStart,
+    /// Exec in a running box
+    WarmExec,
+    /// Copy a generated file tree into a running box
+    CopyIn,
+    /// Snapshot a stopped box and restore it
+    Snapshot,
+}
+
+impl From<ScenarioArg> for Scenario {
+    fn from(arg: ScenarioArg) -> Self {
+        match arg {
+            ScenarioArg::ColdStart => Scenario::ColdStart,
+            ScenarioArg::WarmExec => Scenario::WarmExec,
+            ScenarioArg::CopyIn => Scenario::CopyIn,
+            ScenarioArg::Snapshot => Scenario::Snapshot,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum BenchOutput {
+    #[default]
+    Text,
+    Json,
+}
+
+#[derive(Tabled)]
+struct ResultPresenter {
+    #[tabled(rename = "SCENARIO")]
+    scenario: String,
+
+    #[tabled(rename = "OPERATION")]
+    operation: String,
+
+    #[tabled(rename = "P50")]
+    p50: String,
+
+    #[tabled(rename = "P95")]
+    p95: String,
+
+    #[tabled(rename = "MAX")]
+    max: String,
+
+    #[tabled(rename = "THROUGHPUT")]
+    throughput: String,
+}
+
+impl From<&BenchResult> for ResultPresenter {
+    fn from(result: &BenchResult) -> Self {
+        Self {
+            scenario: result.scenario.to_string(),
+            operation: result.operation.clone(),
+            p50: format_ms(result.stats.p50_ms),
+            p95: format_ms(result.stats.p95_ms),
+            max: format_ms(result.stats.max_ms),
+            throughput: result.throughput_bytes_per_sec.map_or_else(
+                || "-".to_string(),
+                |rate| format!("{}/s", format_bytes(Some(rate as u64))),
+            ),
+        }
+    }
+}
+
+pub async fn execute(args: BenchArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let scenarios: Vec<Scenario> = if args.scenarios.is_empty() {
+        Scenario::ALL.to_vec()
+    } else {
+        args.scenarios.into_iter().map(Into::into).collect()
+    };
+    let options = BenchOptions {
+        image: args.image,
+        iterations: args.iterations as usize,
+        copy_files: args.copy_files,
+        copy_file_size: args.copy_file_size,
+    };
+
+    let spinner = (args.output == BenchOutput::Text).then(|| {
+        reporter.spinner(format!(
+            "Benchmarking {} ({} iterations per scenario)",
+            options.image, options.iterations
+        ))
+    });
+    let report = bench::run(&rt, &options, &scenarios).await;
+    drop(spinner);
+    let report = report?;
+
+    match args.output {
+        BenchOutput::Text => {
+            let presenters: Vec<_> = report.results.iter().map(ResultPresenter::from).collect();
+            if !presenters.is_empty() {
+                reporter.println(formatter::create_table(presenters));
+            }
+        }
+        BenchOutput::Json => reporter.println(formatter::format_json(&report)?),
+    }
+
+    if report.interrupted {
+        anyhow::bail!(interrupted_message(&report));
+    }
+    Ok(())
+}
+
+fn interrupted_message(report: &BenchReport) -> String {
+    format!(
+        "benchmark interrupted after {} result(s); its boxes were removed",
+        report.results.len()
+    )
+}
+
+/// Milliseconds with one decimal, e.g. `12.3ms`.
+fn format_ms(ms: f64) -> String {
+    format!("{:.1}ms", ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boxlite::bench::LatencyStats;
+
+    #[test]
+    fn test_result_presenter() {
+        let result = BenchResult {
+            scenario: Scenario::CopyIn,
+            operation: "copy_in".to_string(),
+            stats: LatencyStats {
+                samples: 10,
+                p50_ms: 12.34,
+                p95_ms: 20.0,
+                max_ms: 31.0,
+            },
+            throughput_bytes_per_sec: Some(4.0 * 1024.0 * 1024.0),
+        };
+        let presenter = ResultPresenter::from(&result);
+        assert_eq!(presenter.scenario, "copy-in");
+        assert_eq!(presenter.p50, "12.3ms");
+        assert_eq!(presenter.max, "31.0ms");
+        assert_eq!(presenter.throughput, "4.0 MiB/s");
+
+        let exec = BenchResult {
+            scenario: Scenario::WarmExec,
+            operation: "exec".to_string(),
+            throughput_bytes_per_sec: None,
+            ..result
+        };
+        assert_eq!(ResultPresenter::from(&exec).throughput, "-");
+    }
+
+    #[test]
+    fn test_every_scenario_has_an_argument() {
+        let names: Vec<_> = ScenarioArg::value_variants()
+            .iter()
+            .map(|arg| arg.to_possible_value().unwrap().get_name().to_string())
+            .collect();
+        let expected: Vec<_> = Scenario::ALL.iter().map(|s| s.to_string()).collect();
+        assert_eq!(names, expected);
+    }
+}
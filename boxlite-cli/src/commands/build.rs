@@ -0,0 +1,138 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use boxlite::{BuildEvent, BuildObserver, BuildSpec};
+use clap::Args;
+
+use crate::cli::GlobalFlags;
+use crate::reporter::Reporter;
+
+/// File names looked for in the context when `--file` is not given.
+const DEFAULT_FILES: &[&str] = &["Containerfile", "Dockerfile"];
+
+#[derive(Args, Debug)]
+pub struct BuildArgs {
+    /// Build context: the directory COPY sources are relative to
+    #[arg(default_value = ".")]
+    pub context: PathBuf,
+
+    /// Name of the image, e.g. local/myimg or local/myimg:v1
+    #[arg(short, long)]
+    pub tag: String,
+
+    /// Containerfile to build (default: Containerfile, then Dockerfile, in
+    /// the context)
+    #[arg(short, long)]
+    pub file: Option<PathBuf>,
+
+    /// Run every step instead of reusing cached results
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// Quiet mode - only show the image digest
+    #[arg(short, long)]
+    pub quiet: bool,
+}
+
+pub async fn execute(args: BuildArgs, global: &GlobalFlags) -> Result<()> {
+    let file = match &args.file {
+        Some(file) => file.clone(),
+        None => default_file(&args.context)?,
+    };
+    let text = std::fs::read_to_string(&file)
+        .with_context(|| format!("failed to read {}", file.display()))?;
+    // Parsed before the runtime starts, so unsupported instructions fail fast
+    let spec = BuildSpec::from_containerfile(&text, &args.context)?.no_cache(args.no_cache);
+
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter().machine_readable(args.quiet);
+    let image = runtime
+        .build_image_with(&args.tag, spec, step_printer(reporter.clone(), args.quiet))
+        .await?;
+
+    if args.quiet {
+        reporter.println(&image.digest);
+    } else {
+        reporter.println(format!("Built: {}", image.reference));
+        reporter.println(format!("Digest: {}", image.digest));
+    }
+    Ok(())
+}
+
+fn default_file(context: &Path) -> Result<PathBuf> {
+    DEFAULT_FILES
+        .iter()
+        .map(|name| context.join(name))
+        .find(|path| path.is_file())
+        .with_context(|| {
+            format!(
+                "no {} in {}; pass one with --file",
+                DEFAULT_FILES.join(" or "),
+                context.display()
+            )
+        })
+}
+
+/// Print each step as it starts and `RUN` output as it arrives, to stderr.
+fn step_printer(reporter: Reporter, quiet: bool) -> BuildObserver {
+    Arc::new(move |event: &BuildEvent| match event {
+        BuildEvent::Step {
+            index,
+            total,
+            step,
+            cached,
+        } => reporter.status(step_line(*index, *total, step, *cached)),
+        BuildEvent::Output { text, .. } => {
+            if !quiet {
+                let mut stderr = std::io::stderr().lock();
+                let _ = stderr.write_all(text.as_bytes());
+                let _ = stderr.flush();
+            }
+        }
+        BuildEvent::Committing => reporter.status("--> Committing"),
+    })
+}
+
+fn step_line(index: usize, total: usize, step: &str, cached: bool) -> String {
+    if cached {
+        format!("STEP {index}/{total}: {step} (cached)")
+    } else {
+        format!("STEP {index}/{total}: {step}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_line() {
+        assert_eq!(
+            step_line(2, 4, "RUN apk add curl", false),
+            "STEP 2/4: RUN apk add curl"
+        );
+        assert_eq!(
+            step_line(3, 4, "COPY . /app", true),
+            "STEP 3/4: COPY . /app (cached)"
+        );
+    }
+
+    #[test]
+    fn test_default_file_prefers_containerfile() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(default_file(dir.path()).is_err());
+
+        std::fs::write(dir.path().join("Dockerfile"), "FROM alpine\n").unwrap();
+        assert_eq!(
+            default_file(dir.path()).unwrap(),
+            dir.path().join("Dockerfile")
+        );
+        std::fs::write(dir.path().join("Containerfile"), "FROM alpine\n").unwrap();
+        assert_eq!(
+            default_file(dir.path()).unwrap(),
+            dir.path().join("Containerfile")
+        );
+    }
+}
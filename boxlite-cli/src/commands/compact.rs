@@ -0,0 +1,57 @@
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct CompactArgs {
+    /// Name or ID of the stopped box(es) to compact
+    #[arg(required = true, num_args = 1.., value_name = "BOX")]
+    pub targets: Vec<String>,
+}
+
+pub async fn execute(args: CompactArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let mut errors = Vec::new();
+    let mut success_count = 0;
+
+    for target in args.targets {
+        let litebox = match runtime.get(&target).await? {
+            Some(b) => b,
+            None => {
+                reporter.error(format!("No such box: {}", target));
+                errors.push(format!("{}: not found", target));
+                continue;
+            }
+        };
+
+        let spinner = reporter.spinner(format!("Compacting {}", target));
+        let result = litebox.compact_disk().await;
+        drop(spinner);
+
+        match result {
+            Ok(reclaimed) => {
+                reporter.println(format!("{}: reclaimed {} bytes", target, reclaimed));
+                success_count += 1;
+            }
+            Err(e) => {
+                reporter.error_for(format!("compacting box '{}'", target), &e);
+                errors.push(format!("{}: {}", target, e));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        let error_summary = if success_count > 0 {
+            format!(
+                "Failed to compact {} of {} box(es)",
+                errors.len(),
+                errors.len() + success_count
+            )
+        } else {
+            format!("Failed to compact all {} box(es)", errors.len())
+        };
+
+        anyhow::bail!("{}\nErrors:\n  {}", error_summary, errors.join("\n  "));
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+//! Bring a set of boxes up and down together.
+
+use std::path::{Path, PathBuf};
+
+use crate::cli::GlobalFlags;
+use crate::util::bulk_failure_summary;
+use boxlite::{ComposedBox, UpOptions, UpOutcome};
+use clap::{Args, Subcommand, ValueHint};
+use serde::Deserialize;
+
+#[derive(Args, Debug)]
+pub struct ComposeArgs {
+    #[command(subcommand)]
+    pub command: ComposeCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ComposeCommand {
+    /// Create and start the boxes of a compose file in dependency order
+    Up(UpArgs),
+
+    /// Stop the boxes of a compose file, dependents first
+    Down(DownArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct FileArgs {
+    /// Compose file (YAML or JSON)
+    #[arg(short, long, default_value = "boxes.yaml", value_hint = ValueHint::FilePath)]
+    pub file: PathBuf,
+}
+
+#[derive(Args, Debug)]
+pub struct UpArgs {
+    #[command(flatten)]
+    pub file: FileArgs,
+
+    /// Leave the boxes that came up running when another box fails
+    #[arg(long)]
+    pub no_rollback: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct DownArgs {
+    #[command(flatten)]
+    pub file: FileArgs,
+
+    /// Remove the boxes after stopping them
+    #[arg(long)]
+    pub rm: bool,
+}
+
+/// A compose file: the boxes, each a name, `BoxOptions` as in a template
+/// of the config file, `depends_on` and an optional `readiness` probe.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ComposeFile {
+    boxes: Vec<ComposedBox>,
+}
+
+fn load(path: &Path) -> anyhow::Result<Vec<ComposedBox>> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
+    let file: ComposeFile = serde_yaml::from_str(&text)
+        .map_err(|e| anyhow::anyhow!("Invalid compose file {}: {}", path.display(), e))?;
+    Ok(file.boxes)
+}
+
+pub async fn execute(args: ComposeArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        ComposeCommand::Up(args) => up(args, global).await,
+        ComposeCommand::Down(args) => down(args, global).await,
+    }
+}
+
+async fn up(args: UpArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let mut boxes = load(&args.file.file)?;
+    // The boxes outlive this command
+    for b in &mut boxes {
+        b.options.detach = true;
+        b.options.auto_remove = false;
+    }
+    let total = boxes.len();
+
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+    let spinner = reporter.spinner(format!("Bringing up {} box(es)", total));
+    let report = rt
+        .up_with(boxes, UpOptions::default().rollback(!args.no_rollback))
+        .await?;
+    drop(spinner);
+
+    let mut errors = Vec::new();
+    for (name, outcome) in &report.boxes {
+        match outcome {
+            UpOutcome::Created | UpOutcome::Started | UpOutcome::Running => reporter.println(name),
+            UpOutcome::Failed(e) => {
+                reporter.error_for(format!("bringing up box '{}'", name), e);
+                errors.push(format!("{}: {}", name, e));
+            }
+            UpOutcome::Skipped => {
+                reporter.warn(format!("Skipped box '{}'", name));
+                errors.push(format!("{}: skipped", name));
+            }
+            UpOutcome::RolledBack => {
+                reporter.warn(format!("Rolled back box '{}'", name));
+                errors.push(format!("{}: rolled back", name));
+            }
+        }
+    }
+
+    bulk_failure_summary("bring up", &errors, total)
+}
+
+async fn down(args: DownArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let names: Vec<String> = load(&args.file.file)?.into_iter().map(|b| b.name).collect();
+
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+    let spinner = reporter.spinner(format!("Stopping {} box(es)", names.len()));
+    let results = rt.down(&names, args.rm).await?;
+    drop(spinner);
+
+    let verb = if args.rm { "remove" } else { "stop" };
+    let mut errors = Vec::new();
+    for (name, result) in &results {
+        match result {
+            Ok(()) => reporter.println(name),
+            Err(e) => {
+                reporter.error_for(format!("taking down box '{}'", name), e);
+                errors.push(format!("{}: {}", name, e));
+            }
+        }
+    }
+
+    bulk_failure_summary(verb, &errors, results.len())
+}
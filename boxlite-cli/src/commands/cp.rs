@@ -1,7 +1,8 @@
 use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
 use anyhow::{Result, anyhow};
 use boxlite::{CopyOptions, LiteBox};
-use clap::Args;
+use clap::{Args, ValueHint};
 use std::path::PathBuf;
 
 #[derive(Args, Debug)]
@@ -18,17 +19,22 @@ pub struct CpArgs {
     #[arg(long, default_value_t = true)]
     pub include_parent: bool,
 
+    /// Continue an interrupted copy into a box, skipping files already sent
+    #[arg(long, default_value_t = false)]
+    pub resume: bool,
+
     /// Source path (host path or BOX:PATH)
-    #[arg(index = 1)]
+    #[arg(index = 1, value_name = "[BOX:]SRC", value_hint = ValueHint::AnyPath)]
     pub src: String,
 
     /// Destination path (host path or BOX:PATH)
-    #[arg(index = 2)]
+    #[arg(index = 2, value_name = "[BOX:]DST", value_hint = ValueHint::AnyPath)]
     pub dst: String,
 }
 
 pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
     let rt = global.create_runtime()?;
+    let reporter = global.reporter();
 
     let direction = parse_direction(&args.src, &args.dst)?;
 
@@ -36,6 +42,7 @@ pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
         follow_symlinks: args.follow_symlinks,
         overwrite: !args.no_overwrite,
         include_parent: args.include_parent,
+        resume: args.resume,
         ..Default::default()
     };
 
@@ -46,6 +53,12 @@ pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
             box_path,
         } => {
             let handle = require_box(&rt, &box_name).await?;
+            let _spinner = reporter.spinner(format!(
+                "Copying {} to {}:{}",
+                host.display(),
+                box_name,
+                box_path
+            ));
             let was_running = handle.info().status == boxlite::BoxStatus::Running;
             if !was_running {
                 handle.start().await?;
@@ -65,6 +78,12 @@ pub async fn execute(args: CpArgs, global: &GlobalFlags) -> Result<()> {
             host,
         } => {
             let handle = require_box(&rt, &box_name).await?;
+            let _spinner = reporter.spinner(format!(
+                "Copying {}:{} to {}",
+                box_name,
+                box_path,
+                host.display()
+            ));
             let was_running = handle.info().status == boxlite::BoxStatus::Running;
             if !was_running {
                 handle.start().await?;
@@ -139,7 +158,7 @@ fn non_empty(path: &str, role: &str) -> Result<String> {
 async fn require_box(rt: &boxlite::BoxliteRuntime, name: &str) -> Result<LiteBox> {
     match rt.get(name).await? {
         Some(b) => Ok(b),
-        None => Err(anyhow!("box '{}' not found", name)),
+        None => Err(no_such_box(name)),
     }
 }
 
@@ -1,5 +1,10 @@
+use std::sync::Arc;
+
+use super::pull::{LayerTotals, pulling_message};
+use super::stats::format_bytes;
 use crate::cli::{GlobalFlags, PublishFlags, ResourceFlags, VolumeFlags};
-use boxlite::{BoxOptions, RootfsSpec};
+use crate::reporter::{Reporter, Spinner};
+use boxlite::{BoxOptions, CreateEvent, CreatePhase};
 use clap::Args;
 
 /// Create a new box
@@ -16,6 +21,10 @@ pub struct CreateArgs {
     #[arg(short = 'e', long = "env")]
     pub env: Vec<String>,
 
+    /// Redact the value of this environment variable from exec output
+    #[arg(long = "redact-env", value_name = "KEY")]
+    pub redact_env: Vec<String>,
+
     /// Working directory inside the box
     #[arg(short = 'w', long = "workdir")]
     pub workdir: Option<String>,
@@ -34,22 +43,77 @@ pub async fn execute(args: CreateArgs, global: &GlobalFlags) -> anyhow::Result<(
     let rt = global.create_runtime()?;
     let box_options = args.to_box_options(global)?;
 
-    let litebox = rt.create(box_options, args.management.name.clone()).await?;
-    println!("{}", litebox.id());
+    let reporter = global.reporter();
+
+    let spinner = Arc::new(reporter.spinner(format!("Creating box from {}", args.image)));
+    let progress = phase_reporter(&args.image, Arc::clone(&spinner), reporter.clone());
+    let litebox = rt
+        .create_with_observer(box_options, args.management.name.clone(), progress)
+        .await?;
+    drop(spinner);
+
+    reporter.println(litebox.id());
 
     Ok(())
 }
 
+/// Show each create phase on the spinner, with bytes pulled across all
+/// layers while the image downloads, and warn when disk space is low.
+pub(super) fn phase_reporter(
+    image: &str,
+    spinner: Arc<Spinner>,
+    reporter: Reporter,
+) -> impl Fn(&CreateEvent) + Send + Sync + 'static {
+    let image = image.to_string();
+    let layers = LayerTotals::default();
+
+    move |event: &CreateEvent| {
+        let message = match &event.phase {
+            CreatePhase::ResolvingImage => format!("Resolving {}", image),
+            CreatePhase::PullingLayer {
+                digest,
+                done,
+                total,
+            } => {
+                let (downloaded, total) = layers.update(digest, *done, *total);
+                pulling_message(&image, downloaded, total)
+            }
+            CreatePhase::BuildingImageDisk => format!("Building disk for {}", image),
+            CreatePhase::InjectingGuest => "Preparing guest".to_string(),
+            CreatePhase::PreparingOverlays => "Creating box".to_string(),
+            CreatePhase::Done => return,
+            CreatePhase::LowSpace {
+                available_bytes,
+                threshold_bytes,
+            } => {
+                spinner.suspend(|| {
+                    reporter.warn(format!(
+                        "low disk space: {} free, below the {} threshold",
+                        format_bytes(Some(*available_bytes)),
+                        format_bytes(Some(*threshold_bytes))
+                    ))
+                });
+                return;
+            }
+        };
+        spinner.set_message(message);
+    }
+}
+
 impl CreateArgs {
     fn to_box_options(&self, global: &GlobalFlags) -> anyhow::Result<BoxOptions> {
-        let mut options = BoxOptions::default();
-        self.resource.apply_to(&mut options);
-        self.management.apply_to(&mut options);
-        self.publish.apply_to(&mut options)?;
-        self.volume.apply_to(&mut options, global.home.as_deref())?;
-        options.working_dir = self.workdir.clone();
-        crate::cli::apply_env_vars(&self.env, &mut options);
-        options.rootfs = RootfsSpec::Image(self.image.clone());
-        Ok(options)
+        let mut builder = BoxOptions::builder().image(self.image.as_str());
+        builder = self.resource.apply_to(builder);
+        builder = self.management.apply_to(builder)?;
+        builder = self.publish.apply_to(builder)?;
+        builder = self.volume.apply_to(builder, global.home.as_deref())?;
+        if let Some(workdir) = &self.workdir {
+            builder = builder.working_dir(workdir.as_str());
+        }
+        builder = crate::cli::apply_env_vars(&self.env, builder);
+        for key in &self.redact_env {
+            builder = builder.redact_env(key.as_str());
+        }
+        Ok(builder.build()?)
     }
 }
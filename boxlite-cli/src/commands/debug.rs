@@ -0,0 +1,142 @@
+//! Low-level diagnostics for a running box.
+
+use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use crate::formatter::{self, OutputFormat};
+use anyhow::Result;
+use boxlite::CoreDump;
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use std::io::Write;
+use std::path::PathBuf;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct DebugArgs {
+    #[command(subcommand)]
+    pub command: DebugCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DebugCommand {
+    /// Print the guest kernel log of a running box
+    Dmesg(DmesgArgs),
+    /// List or fetch the core dumps captured in a running box
+    Cores(CoresArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DmesgArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Number of lines to show from the end (at most 10000)
+    #[arg(short = 'n', long = "tail", default_value = "100")]
+    pub tail: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct CoresArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Copy this core dump to the host instead of listing
+    #[arg(long, value_name = "NAME")]
+    pub fetch: Option<String>,
+
+    /// Host destination for --fetch
+    #[arg(short, long, default_value = ".", requires = "fetch")]
+    pub output: PathBuf,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
+    pub format: String,
+}
+
+/// Presenter for core dump output, used by both table and JSON/YAML formats.
+#[derive(Tabled, Serialize)]
+struct CoreDumpPresenter {
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+    #[tabled(rename = "PID")]
+    #[serde(rename = "Pid")]
+    pid: u32,
+    #[tabled(rename = "SIGNAL")]
+    #[serde(rename = "Signal")]
+    signal: i32,
+    #[tabled(rename = "SIZE")]
+    #[serde(rename = "SizeBytes")]
+    size_bytes: u64,
+    #[tabled(rename = "TIME")]
+    #[serde(rename = "Time")]
+    time: String,
+}
+
+impl From<&CoreDump> for CoreDumpPresenter {
+    fn from(core: &CoreDump) -> Self {
+        Self {
+            name: core.name.clone(),
+            pid: core.pid,
+            signal: core.signal,
+            size_bytes: core.size_bytes,
+            time: formatter::format_time(&core.time),
+        }
+    }
+}
+
+pub async fn execute(args: DebugArgs, global: &GlobalFlags) -> Result<()> {
+    match args.command {
+        DebugCommand::Dmesg(args) => dmesg(args, global).await,
+        DebugCommand::Cores(args) => cores(args, global).await,
+    }
+}
+
+async fn dmesg(args: DmesgArgs, global: &GlobalFlags) -> Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+
+    for line in litebox.guest_dmesg(args.tail).await? {
+        reporter.println(line);
+    }
+    Ok(())
+}
+
+async fn cores(args: CoresArgs, global: &GlobalFlags) -> Result<()> {
+    let rt = global.create_runtime()?;
+
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+
+    if let Some(name) = &args.fetch {
+        litebox.fetch_core_dump(name, &args.output).await?;
+        return Ok(());
+    }
+
+    let presenters: Vec<CoreDumpPresenter> = litebox
+        .list_core_dumps()
+        .await?
+        .iter()
+        .map(Into::into)
+        .collect();
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            writeln!(writer, "{}", formatter::create_table(data))?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
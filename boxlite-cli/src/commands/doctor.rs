@@ -0,0 +1,140 @@
+//! Diagnose why a box failed to start, host limits that degrade it, and
+//! shims left behind by boxes that no longer exist.
+
+use crate::cli::GlobalFlags;
+use crate::commands::system::orphan_line;
+use crate::commands::version;
+use crate::error::no_such_box;
+use crate::formatter;
+use boxlite::{BoxliteError, CapabilityStatus, RegistryStatus};
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct DoctorArgs {
+    /// Box ID or name (omit to check only the host)
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: Option<String>,
+}
+
+pub async fn execute(args: DoctorArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let litebox = match &args.target {
+        Some(target) => Some(rt.get(target).await?.ok_or_else(|| no_such_box(target))?),
+        None => None,
+    };
+
+    let reporter = global.reporter();
+
+    if let Some(litebox) = &litebox {
+        let info = litebox.info();
+        reporter.println(format!("Box:    {}", info.id));
+        reporter.println(format!("Status: {}", info.status));
+        reporter.println("");
+    }
+
+    reporter.println("Host capabilities:");
+    for (capability, status) in rt.capabilities().await?.iter() {
+        reporter.println(format!("  {}: {}", capability, capability_state(status)));
+    }
+
+    reporter.println("");
+    reporter.println("Versions:");
+    for line in version::version_lines(&rt.version_info().await?) {
+        reporter.println(format!("  {}", line));
+    }
+
+    reporter.println("");
+    reporter.println("Registries:");
+    let registries = rt.registry_status()?;
+    if registries.is_empty() {
+        reporter.println("  none configured (unqualified images use docker.io)");
+    }
+    for status in &registries {
+        reporter.println(format!("  {}", registry_line(status)));
+    }
+
+    // Remote runtimes don't expose their shim processes
+    match rt.list_orphans().await {
+        Ok(orphans) => {
+            reporter.println("");
+            reporter.println("Orphaned shims:");
+            if orphans.is_empty() {
+                reporter.println("  none");
+            }
+            for orphan in &orphans {
+                reporter.println(format!("  {}", orphan_line(orphan)));
+            }
+            if !orphans.is_empty() {
+                reporter.println("  (run `boxlite system prune --orphans` to kill them)");
+            }
+        }
+        Err(BoxliteError::Unsupported(_)) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let Some(litebox) = litebox else {
+        return Ok(());
+    };
+    let info = litebox.info();
+
+    for degradation in &info.degradations {
+        reporter.warn(format!("isolation degraded, {}", degradation));
+    }
+
+    if info.resource_limits.has_disk_io_limits()
+        && let Some(reason) = boxlite::jailer::disk_io_limits_unsupported_reason()
+    {
+        reporter.warn(format!(
+            "disk I/O limits are configured but not enforced: {}",
+            reason
+        ));
+    }
+
+    let Some(failure) = litebox.last_start_failure() else {
+        reporter.println("");
+        reporter.println("No start failure recorded for this box.");
+        return Ok(());
+    };
+
+    reporter.println("");
+    reporter.println(format!(
+        "Last start failed at {}:",
+        formatter::format_time(&failure.failed_at)
+    ));
+    for line in failure.error.lines() {
+        reporter.println(format!("  {}", line));
+    }
+    reporter.println("");
+    reporter.println(failure.render());
+
+    Ok(())
+}
+
+/// State of one host capability, e.g. `missing (/dev/kvm not found)`.
+fn capability_state(status: &CapabilityStatus) -> String {
+    let state = if status.available { "ok" } else { "missing" };
+    format!("{} ({})", state, status.detail)
+}
+
+/// One line of registry health, e.g. `ghcr.io: ok`.
+fn registry_line(status: &RegistryStatus) -> String {
+    let last = status.last_error.as_deref().unwrap_or("unknown error");
+    if let Some(until) = &status.cooldown_until {
+        format!(
+            "{}: skipped until {} after {} consecutive failures (last: {})",
+            status.registry,
+            formatter::format_time(until),
+            status.consecutive_failures,
+            last
+        )
+    } else if status.consecutive_failures > 0 {
+        format!(
+            "{}: {} consecutive failures (last: {})",
+            status.registry, status.consecutive_failures, last
+        )
+    } else if status.last_success.is_some() {
+        format!("{}: ok", status.registry)
+    } else {
+        format!("{}: not contacted yet", status.registry)
+    }
+}
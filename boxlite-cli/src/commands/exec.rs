@@ -1,7 +1,8 @@
 use crate::cli::{GlobalFlags, ProcessFlags};
+use crate::error::no_such_box;
 use crate::terminal::StreamManager;
 use crate::util::to_shell_exit_code;
-use boxlite::{BoxCommand, BoxliteRuntime, LiteBox};
+use boxlite::{BoxCommand, BoxliteRuntime, LiteBox, ScratchSpec};
 use clap::Args;
 
 #[derive(Args, Debug)]
@@ -9,10 +10,26 @@ pub struct ExecArgs {
     #[command(flatten)]
     pub process: ProcessFlags,
 
-    /// Run command in the background (detached mode)
+    /// Run command in the background and print its execution ID
+    /// (output is kept for `boxlite exec-logs`)
     #[arg(short = 'd', long)]
     pub detach: bool,
 
+    /// Start from the current environment and working directory of the
+    /// box's main process, like `docker exec` (-e and -w still override)
+    #[arg(long)]
+    pub inherit_env: bool,
+
+    /// Mount a private tmpfs of SIZE (e.g. 256m, 1g) at PATH for this exec
+    /// only; its path is in $BOXLITE_SCRATCH_DIR
+    #[arg(long, value_name = "SIZE:PATH", value_parser = parse_scratch)]
+    pub scratch: Option<ScratchSpec>,
+
+    /// Put VALUE in front of the variable's value in the box, joined with
+    /// ':' (e.g. PATH=/custom/bin); applied after -e
+    #[arg(long, value_name = "KEY=VALUE", value_parser = parse_env_prepend)]
+    pub env_prepend: Vec<(String, String)>,
+
     /// Box ID or name
     #[arg(index = 1, value_name = "BOX")]
     pub target_box: String,
@@ -45,8 +62,9 @@ impl BoxExecutor {
         let cmd = self.prepare_command();
         let mut execution = litebox.exec(cmd).await?;
 
-        // Detach mode: Exit immediately without waiting
+        // Detach mode: Print the execution ID for `exec-logs` and exit
         if self.args.detach {
+            println!("{}", execution.id());
             return Ok(());
         }
 
@@ -74,11 +92,99 @@ impl BoxExecutor {
         self.rt
             .get(&self.args.target_box)
             .await?
-            .ok_or_else(|| anyhow::anyhow!("No such box: {}", self.args.target_box))
+            .ok_or_else(|| no_such_box(&self.args.target_box))
     }
 
     fn prepare_command(&self) -> BoxCommand {
-        let cmd = BoxCommand::new(&self.args.command[0]).args(&self.args.command[1..]);
-        self.args.process.configure_command(cmd)
+        let cmd = BoxCommand::new(&self.args.command[0])
+            .args(&self.args.command[1..])
+            .detach(self.args.detach)
+            .inherit_runtime_env(self.args.inherit_env);
+        let cmd = match &self.args.scratch {
+            Some(scratch) => cmd.scratch_dir(scratch.clone()),
+            None => cmd,
+        };
+        // After -e, which would drop earlier edits of the same variable
+        self.args
+            .env_prepend
+            .iter()
+            .fold(self.args.process.configure_command(cmd), |cmd, (k, v)| {
+                cmd.env_prepend_path(k, v, ":")
+            })
+    }
+}
+
+/// Parse `KEY=VALUE` for `--env-prepend`.
+fn parse_env_prepend(spec: &str) -> Result<(String, String), String> {
+    match spec.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got '{}'", spec)),
+    }
+}
+
+/// Parse `SIZE:PATH`; SIZE is in MiB, or with an `m` or `g` suffix.
+fn parse_scratch(spec: &str) -> Result<ScratchSpec, String> {
+    let (size, path) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("expected SIZE:PATH, got '{}'", spec))?;
+    let lower = size.to_ascii_lowercase();
+    let (digits, factor) = match lower.strip_suffix(['m', 'g']) {
+        Some(digits) if lower.ends_with('g') => (digits, 1024),
+        Some(digits) => (digits, 1),
+        None => (lower.as_str(), 1),
+    };
+    let size_mib = digits
+        .parse::<u32>()
+        .ok()
+        .and_then(|n| n.checked_mul(factor))
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("invalid scratch size '{}'", size))?;
+    boxlite::validate::guest_path(path).map_err(|e| e.to_string())?;
+    Ok(ScratchSpec::new(size_mib, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scratch_sizes() {
+        assert_eq!(
+            parse_scratch("256m:/scratch").unwrap(),
+            ScratchSpec::new(256, "/scratch")
+        );
+        assert_eq!(
+            parse_scratch("1G:/work/tmp").unwrap(),
+            ScratchSpec::new(1024, "/work/tmp")
+        );
+        assert_eq!(parse_scratch("64:/s").unwrap(), ScratchSpec::new(64, "/s"));
+    }
+
+    #[test]
+    fn parse_env_prepend_specs() {
+        assert_eq!(
+            parse_env_prepend("PATH=/custom/bin").unwrap(),
+            ("PATH".to_string(), "/custom/bin".to_string())
+        );
+        assert_eq!(
+            parse_env_prepend("A=b=c").unwrap(),
+            ("A".to_string(), "b=c".to_string())
+        );
+        assert!(parse_env_prepend("PATH").is_err());
+        assert!(parse_env_prepend("=/bin").is_err());
+    }
+
+    #[test]
+    fn parse_scratch_rejects_invalid_specs() {
+        for spec in [
+            "256m",
+            "0m:/s",
+            "big:/s",
+            "256k:/s",
+            "256m:scratch",
+            "9999999g:/s",
+        ] {
+            assert!(parse_scratch(spec).is_err(), "{spec}");
+        }
     }
 }
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use crate::cli::{FilterFlags, GlobalFlags};
+use crate::util::bulk_failure_summary;
+use boxlite::{BoxCommand, BoxStatus};
+use clap::Args;
+
+#[derive(Args, Debug)]
+pub struct ExecAllArgs {
+    #[command(flatten)]
+    pub filter: FilterFlags,
+
+    /// Set environment variables
+    #[arg(short = 'e', long = "env")]
+    pub env: Vec<String>,
+
+    /// Working directory inside the boxes
+    #[arg(short = 'w', long = "workdir")]
+    pub workdir: Option<String>,
+
+    /// Command to execute inside each box
+    #[arg(last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+/// Run the command in every matching running box and print each box's
+/// output, failing if the command failed anywhere.
+pub async fn execute(args: ExecAllArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
+    // Stopped boxes are only started when asked for with status=...
+    let filter = args.filter.to_filter(Some(BoxStatus::Running))?;
+
+    let mut command = BoxCommand::new(&args.command[0]).args(&args.command[1..]);
+    for env_str in &args.env {
+        if let Some((k, v)) = env_str.split_once('=') {
+            command = command.env(k, v);
+        } else if let Ok(val) = std::env::var(env_str) {
+            command = command.env(env_str, val);
+        }
+    }
+    if let Some(workdir) = &args.workdir {
+        command = command.working_dir(workdir);
+    }
+
+    let names: HashMap<_, _> = runtime
+        .list_info()
+        .await?
+        .into_iter()
+        .filter_map(|info| Some((info.id, info.name?)))
+        .collect();
+
+    let spinner = reporter.spinner(format!("Running {}", args.command.join(" ")));
+    let results = runtime.exec_matching(filter, command).await?;
+    drop(spinner);
+
+    // Stop non-detached boxes this run started, as `exec` does
+    let _ = runtime.shutdown(None).await;
+
+    if results.is_empty() {
+        reporter.status("No boxes match");
+        return Ok(());
+    }
+
+    let mut errors = Vec::new();
+    for (id, result) in &results {
+        let label = names.get(id).map_or_else(|| id.to_string(), Clone::clone);
+        let output = match result {
+            Ok(output) => output,
+            Err(e) => {
+                reporter.error_for(format!("running in box '{}'", label), e);
+                errors.push(format!("{}: {}", label, e));
+                continue;
+            }
+        };
+
+        reporter.println(format!("==> {} <==", label));
+        print!("{}", output.stdout);
+        eprint!("{}", output.stderr);
+        if output.stdout_truncated || output.stderr_truncated {
+            reporter.warn(format!("output of box '{}' was truncated", label));
+        }
+        if !output.result.success() {
+            errors.push(format!(
+                "{}: exited with code {}",
+                label, output.result.exit_code
+            ));
+        }
+    }
+
+    reporter.status(format!(
+        "Succeeded in {} of {} box(es)",
+        results.len() - errors.len(),
+        results.len()
+    ));
+    bulk_failure_summary("run the command in", &errors, results.len())
+}
@@ -0,0 +1,34 @@
+//! Show the captured output of a detached exec.
+
+use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use clap::Args;
+use std::io::Write;
+
+#[derive(Args, Debug)]
+pub struct ExecLogsArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Execution ID printed by `boxlite exec -d`
+    #[arg(index = 2, value_name = "EXEC_ID")]
+    pub exec_id: String,
+}
+
+pub async fn execute(args: ExecLogsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+
+    let output = litebox.exec_output(&args.exec_id).await?;
+
+    // Replay each stream on the matching host stream
+    std::io::stdout().write_all(output.stdout.as_bytes())?;
+    std::io::stderr().write_all(output.stderr.as_bytes())?;
+
+    Ok(())
+}
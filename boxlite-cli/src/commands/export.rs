@@ -0,0 +1,124 @@
+//! Write a box to a portable `.boxsnap` archive, on disk or at an
+//! `https://` / `s3://` URL.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use boxlite::ExportOptions;
+use clap::Args;
+
+use super::import::{is_url, parse_header, transfer_options};
+use crate::cli::GlobalFlags;
+
+/// Export a stopped box to an archive.
+#[derive(Args, Debug)]
+pub struct ExportArgs {
+    /// Name or ID of the box to export
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Archive to write: a path, or an https:// or s3:// URL
+    #[arg(value_name = "DEST")]
+    pub dest: String,
+
+    /// Write a plain tar instead of a zstd-compressed one
+    #[arg(long, conflicts_with = "seekable")]
+    pub no_compress: bool,
+
+    /// Write seekable zstd frames, so single members can be extracted
+    #[arg(long)]
+    pub seekable: bool,
+
+    /// Extra HTTP header for a URL destination (repeatable)
+    #[arg(long = "header", value_name = "NAME:VALUE", value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+}
+
+impl ExportArgs {
+    fn to_options(&self) -> ExportOptions {
+        let mut options = ExportOptions::default();
+        options.compress(!self.no_compress).seekable(self.seekable);
+        options
+    }
+}
+
+pub async fn execute(args: ExportArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No such box: {}", args.target))?;
+
+    if is_url(&args.dest) {
+        let spinner = Arc::new(reporter.spinner(format!("Exporting {}", args.target)));
+        let transfer = transfer_options(&args.headers, &spinner, "Uploading");
+        let result = litebox
+            .export_to_url(&args.dest, args.to_options(), transfer)
+            .await;
+        drop(spinner);
+        result?;
+        reporter.println(&args.dest);
+    } else {
+        if !args.headers.is_empty() {
+            anyhow::bail!("--header only applies to URL destinations");
+        }
+        let spinner = reporter.spinner(format!("Exporting {}", args.target));
+        let result = litebox
+            .export(Path::new(&args.dest), args.to_options())
+            .await;
+        drop(spinner);
+        reporter.println(result?.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        args: ExportArgs,
+    }
+
+    fn parse(argv: &[&str]) -> Result<ExportArgs, clap::Error> {
+        let argv = std::iter::once("export").chain(argv.iter().copied());
+        TestCli::try_parse_from(argv).map(|cli| cli.args)
+    }
+
+    #[test]
+    fn test_archive_flags() {
+        let options = parse(&["web", "web.boxsnap"]).unwrap().to_options();
+        assert!(options.compress);
+        assert!(!options.seekable);
+
+        let options = parse(&["web", "web.boxsnap", "--no-compress"]).unwrap();
+        assert!(!options.to_options().compress);
+
+        let options = parse(&["web", "web.boxsnap", "--seekable"]).unwrap();
+        assert!(options.to_options().seekable);
+
+        assert!(parse(&["web", "web.boxsnap", "--no-compress", "--seekable"]).is_err());
+        assert!(parse(&["web"]).is_err());
+    }
+
+    #[test]
+    fn test_url_destination() {
+        let args = parse(&[
+            "web",
+            "https://example.com/web.boxsnap",
+            "--header",
+            "Content-Type: application/zstd",
+        ])
+        .unwrap();
+        assert!(is_url(&args.dest));
+        assert_eq!(
+            args.headers,
+            vec![("Content-Type".to_string(), "application/zstd".to_string())]
+        );
+    }
+}
@@ -17,7 +17,7 @@ pub struct ImagesArgs {
     pub quiet: bool,
 
     /// Output format (table, json, yaml)
-    #[arg(long, default_value = "table")]
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
     pub format: String,
 }
 
@@ -59,8 +59,9 @@ pub async fn execute(args: ImagesArgs, global: &GlobalFlags) -> anyhow::Result<(
     let images = image_handle.list().await?;
 
     if args.quiet {
+        let reporter = global.reporter();
         for info in images {
-            println!("{}", info.id);
+            reporter.println(&info.id);
         }
         return Ok(());
     }
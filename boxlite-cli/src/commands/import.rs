@@ -0,0 +1,250 @@
+//! Recreate a box from an exported `.boxsnap` or `.boxlite` archive, on
+//! disk or at an `https://` / `s3://` URL.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use boxlite::{
+    ImportConflict, ImportOptions, ImportPlan, TransferObserver, TransferOptions, TransferProgress,
+};
+use clap::Args;
+
+use super::rm::describe_removal;
+use super::stats::format_bytes;
+use crate::cli::GlobalFlags;
+use crate::reporter::Spinner;
+
+/// Import a box from an archive.
+#[derive(Args, Debug)]
+pub struct ImportArgs {
+    /// Archive to import: a path, or an https:// or s3:// URL
+    #[arg(value_name = "ARCHIVE")]
+    pub archive: String,
+
+    /// Name of the imported box (default: unnamed)
+    #[arg(value_name = "NAME")]
+    pub name: Option<String>,
+
+    /// Replace a stopped box that already has NAME
+    #[arg(long, requires = "name", conflicts_with = "auto_name")]
+    pub overwrite: bool,
+
+    /// If NAME is taken, use the first free NAME-1, NAME-2, ...
+    #[arg(long, requires = "name")]
+    pub auto_name: bool,
+
+    /// Expected SHA-256 of a downloaded archive (sha256:HEX or HEX)
+    #[arg(long, value_name = "DIGEST")]
+    pub sha256: Option<String>,
+
+    /// Extra HTTP header for a URL archive (repeatable)
+    #[arg(long = "header", value_name = "NAME:VALUE", value_parser = parse_header)]
+    pub headers: Vec<(String, String)>,
+}
+
+impl ImportArgs {
+    fn to_options(&self) -> ImportOptions {
+        let on_conflict = if self.overwrite {
+            ImportConflict::Overwrite
+        } else if self.auto_name {
+            ImportConflict::Suffix
+        } else {
+            ImportConflict::Fail
+        };
+        ImportOptions {
+            name: self.name.clone(),
+            on_conflict,
+        }
+    }
+}
+
+pub async fn execute(args: ImportArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    if global.dry_run {
+        if is_url(&args.archive) {
+            anyhow::bail!("--dry-run only supports archives on disk");
+        }
+        let plan = rt
+            .plan_import(Path::new(&args.archive), &args.to_options())
+            .await?;
+        for line in describe_import(&args.archive, &plan) {
+            reporter.println(line);
+        }
+        return Ok(());
+    }
+
+    let result = if is_url(&args.archive) {
+        let spinner = Arc::new(reporter.spinner(format!("Downloading {}", args.archive)));
+        let mut transfer = transfer_options(&args.headers, &spinner, "Downloading");
+        if let Some(digest) = &args.sha256 {
+            transfer.expected_digest(digest);
+        }
+        rt.import_from_url(&args.archive, args.to_options(), transfer)
+            .await
+    } else {
+        if args.sha256.is_some() || !args.headers.is_empty() {
+            anyhow::bail!("--sha256 and --header only apply to URL archives");
+        }
+        let _spinner = reporter.spinner(format!("Importing {}", args.archive));
+        rt.import_with(Path::new(&args.archive), args.to_options())
+            .await
+    };
+    let litebox = result?;
+
+    // The name may differ from the requested one with --auto-name
+    match litebox.name() {
+        Some(name) => reporter.println(format!("{} {}", litebox.id(), name)),
+        None => reporter.println(litebox.id()),
+    }
+    Ok(())
+}
+
+/// What an import would do, e.g. `Would import web.boxsnap as web (1.2 GiB)`,
+/// preceded by the removal of the box it replaces.
+fn describe_import(archive: &str, plan: &ImportPlan) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let Some(replaced) = &plan.replaces {
+        let target = replaced.name.as_deref().unwrap_or(replaced.id.as_str());
+        lines.push(describe_removal(target, replaced));
+    }
+    let name = match &plan.name {
+        Some(name) => format!("as {}", name),
+        None => "as an unnamed box".to_string(),
+    };
+    lines.push(format!(
+        "Would import {} {} ({})",
+        archive,
+        name,
+        format_bytes(Some(plan.archive_bytes))
+    ));
+    lines
+}
+
+/// Whether an archive argument is a URL rather than a path.
+pub(super) fn is_url(archive: &str) -> bool {
+    archive.contains("://")
+}
+
+/// Parse a `NAME:VALUE` HTTP header.
+pub(super) fn parse_header(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        }
+        _ => Err(format!("expected NAME:VALUE, got '{}'", s)),
+    }
+}
+
+/// Transfer options sending `headers` and showing progress on `spinner`
+/// as `<verb> (<done> / <total>)`.
+pub(super) fn transfer_options(
+    headers: &[(String, String)],
+    spinner: &Arc<Spinner>,
+    verb: &'static str,
+) -> TransferOptions {
+    let mut transfer = TransferOptions::default();
+    for (name, value) in headers {
+        transfer.header(name, value);
+    }
+    let spinner = Arc::clone(spinner);
+    transfer.observer(TransferObserver::new(move |p: &TransferProgress| {
+        let done = format_bytes(Some(p.bytes_done));
+        spinner.set_message(match p.bytes_total {
+            Some(total) => format!("{} ({} / {})", verb, done, format_bytes(Some(total))),
+            None => format!("{} ({})", verb, done),
+        });
+    }));
+    transfer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        args: ImportArgs,
+    }
+
+    fn parse(argv: &[&str]) -> Result<ImportArgs, clap::Error> {
+        let argv = std::iter::once("import").chain(argv.iter().copied());
+        TestCli::try_parse_from(argv).map(|cli| cli.args)
+    }
+
+    #[test]
+    fn test_conflict_flags() {
+        let options = parse(&["box.boxsnap", "web"]).unwrap().to_options();
+        assert_eq!(options.name.as_deref(), Some("web"));
+        assert_eq!(options.on_conflict, ImportConflict::Fail);
+
+        let options = parse(&["box.boxsnap", "web", "--overwrite"]).unwrap();
+        assert_eq!(options.to_options().on_conflict, ImportConflict::Overwrite);
+
+        let options = parse(&["box.boxsnap", "web", "--auto-name"]).unwrap();
+        assert_eq!(options.to_options().on_conflict, ImportConflict::Suffix);
+
+        let unnamed = parse(&["box.boxsnap"]).unwrap().to_options();
+        assert_eq!(unnamed.name, None);
+
+        assert!(parse(&["box.boxsnap", "web", "--overwrite", "--auto-name"]).is_err());
+        assert!(parse(&["box.boxsnap", "--overwrite"]).is_err());
+    }
+
+    #[test]
+    fn test_describe_import() {
+        let mut plan = ImportPlan {
+            name: None,
+            archive_bytes: 2 * 1024 * 1024,
+            replaces: None,
+        };
+        assert_eq!(
+            describe_import("web.boxsnap", &plan),
+            vec!["Would import web.boxsnap as an unnamed box (2.0 MiB)"]
+        );
+
+        plan.name = Some("web".to_string());
+        plan.replaces = Some(boxlite::RemovePlan {
+            id: boxlite::BoxID::parse("01HZY0000000000000000000AB").unwrap(),
+            name: Some("web".to_string()),
+            status: boxlite::BoxStatus::Exited,
+            stops_box: false,
+            to_trash: false,
+            disk_bytes: 1024,
+            reclaimed_bytes: 1024,
+        });
+        assert_eq!(
+            describe_import("web.boxsnap", &plan),
+            vec![
+                "Would delete web (1.0 KiB)",
+                "Would import web.boxsnap as web (2.0 MiB)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_url_flags() {
+        let args = parse(&[
+            "https://example.com/web.boxsnap",
+            "web",
+            "--sha256",
+            "sha256:abc",
+            "--header",
+            "x-amz-acl: private",
+        ])
+        .unwrap();
+        assert!(is_url(&args.archive));
+        assert_eq!(args.sha256.as_deref(), Some("sha256:abc"));
+        assert_eq!(
+            args.headers,
+            vec![("x-amz-acl".to_string(), "private".to_string())]
+        );
+
+        assert!(!is_url("./web.boxsnap"));
+        assert!(parse(&["s3://bucket/web.boxsnap", "--header", "novalue"]).is_err());
+        assert!(parse(&["s3://bucket/web.boxsnap", "--header", ":x"]).is_err());
+    }
+}
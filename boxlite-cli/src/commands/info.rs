@@ -18,6 +18,7 @@ struct SystemInfo {
     boxes_running: u32,
     boxes_stopped: u32,
     boxes_configured: u32,
+    boxes_dead: u32,
     images_count: u32,
 }
 
@@ -53,12 +54,13 @@ pub async fn execute(args: InfoArgs, global: &GlobalFlags) -> anyhow::Result<()>
     let boxes_running = boxes_list.iter().filter(|b| b.status.is_active()).count() as u32;
     let boxes_stopped = boxes_list
         .iter()
-        .filter(|b| b.status == BoxStatus::Stopped)
+        .filter(|b| b.status == BoxStatus::Exited)
         .count() as u32;
     let boxes_configured = boxes_list
         .iter()
-        .filter(|b| b.status == BoxStatus::Configured)
+        .filter(|b| b.status == BoxStatus::Created)
         .count() as u32;
+    let boxes_dead = boxes_list.iter().filter(|b| b.status.is_dead()).count() as u32;
 
     let images_count = rt.images()?.list().await?.len() as u32;
 
@@ -72,6 +74,7 @@ pub async fn execute(args: InfoArgs, global: &GlobalFlags) -> anyhow::Result<()>
         boxes_running,
         boxes_stopped,
         boxes_configured,
+        boxes_dead,
         images_count,
     };
 
@@ -79,6 +82,6 @@ pub async fn execute(args: InfoArgs, global: &GlobalFlags) -> anyhow::Result<()>
         InfoFormat::Yaml => formatter::format_yaml(&info)?,
         InfoFormat::Json => formatter::format_json(&info)?,
     };
-    println!("{}", out);
+    global.reporter().println(out);
     Ok(())
 }
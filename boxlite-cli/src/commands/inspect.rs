@@ -2,7 +2,7 @@
 
 use crate::cli::GlobalFlags;
 use crate::formatter::{self, GtmplWithJson, OutputFormat, value_from_serde_json};
-use boxlite::{BoxInfo, BoxStateInfo};
+use boxlite::{BoxInfo, BoxStateInfo, EnvironmentReport, NetworkMode};
 use clap::Args;
 use serde::Serialize;
 
@@ -20,6 +20,12 @@ pub struct InspectArgs {
     /// Output format: json, yaml, or a Go template (e.g. '{{.State}}', '{{.State.Status}}')
     #[arg(short, long, default_value = "json")]
     pub format: String,
+
+    /// Print the environment each box last started with (image digest,
+    /// process, engine, resources, volumes, versions) instead of its
+    /// configuration. Supports json and yaml formats.
+    #[arg(long)]
+    pub report: bool,
 }
 
 /// Single view for inspect: JSON/YAML
@@ -31,6 +37,9 @@ struct InspectPresenter {
     name: String,
     #[serde(rename = "Image")]
     image: String,
+    /// Digest the image is pinned to; empty when pulled by tag.
+    #[serde(rename = "ImageDigest")]
+    image_digest: String,
     #[serde(rename = "Created")]
     created: String,
     #[serde(rename = "Status")]
@@ -41,6 +50,38 @@ struct InspectPresenter {
     cpus: u8,
     #[serde(rename = "Memory")]
     memory: u64,
+    /// Swap inside the VM in bytes; 0 without swap.
+    #[serde(rename = "Swap")]
+    swap: u64,
+    /// Configured time zone (`host` or an IANA name); empty for the image's.
+    #[serde(rename = "Timezone")]
+    timezone: String,
+    /// Configured locale; empty for the image's.
+    #[serde(rename = "Locale")]
+    locale: String,
+    /// Whether the container's root filesystem is read-only.
+    #[serde(rename = "ReadOnlyRootfs")]
+    read_only_rootfs: bool,
+    /// Container paths backed by a writable tmpfs.
+    #[serde(rename = "Tmpfs")]
+    tmpfs: Vec<String>,
+    /// Seconds the box gets to exit after SIGTERM before it is killed.
+    #[serde(rename = "StopTimeout")]
+    stop_timeout: f64,
+    /// Idle seconds after which the box is stopped; null without idle
+    /// auto-stop.
+    #[serde(rename = "AutoStop")]
+    auto_stop: Option<f64>,
+    /// Idle seconds left before the box is stopped, while it runs.
+    #[serde(rename = "IdleRemaining")]
+    idle_remaining: Option<f64>,
+    #[serde(rename = "NetworkSettings")]
+    network_settings: InspectNetworkPresenter,
+    #[serde(rename = "DiskIo")]
+    disk_io: InspectDiskIoPresenter,
+    /// Isolation the box runs without, e.g. `userns unavailable: ...`.
+    #[serde(rename = "Degradations")]
+    degradations: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -51,24 +92,91 @@ struct InspectStatePresenter {
     running: bool,
     #[serde(rename = "Pid")]
     pid: u32,
+    /// Exit code of the last run; 0 unless the box exited.
+    #[serde(rename = "ExitCode")]
+    exit_code: i32,
+    /// Whether the box lost its storage and can't start again.
+    #[serde(rename = "Dead")]
+    dead: bool,
+}
+
+/// Guest network identity; empty strings until the box is first started.
+#[derive(Debug, Serialize)]
+struct InspectNetworkPresenter {
+    /// `default` or `none`.
+    #[serde(rename = "NetworkMode")]
+    network_mode: NetworkMode,
+    #[serde(rename = "IPAddress")]
+    ip_address: String,
+    #[serde(rename = "MacAddress")]
+    mac_address: String,
+    #[serde(rename = "Gateway")]
+    gateway: String,
+    /// Extra `/etc/hosts` entries as `name:ip`, like Docker's `ExtraHosts`.
+    #[serde(rename = "ExtraHosts")]
+    extra_hosts: Vec<String>,
+}
+
+/// Disk I/O limits; 0 means unlimited.
+#[derive(Debug, Serialize)]
+struct InspectDiskIoPresenter {
+    #[serde(rename = "ReadBps")]
+    read_bps: u64,
+    #[serde(rename = "WriteBps")]
+    write_bps: u64,
+    #[serde(rename = "ReadIops")]
+    read_iops: u64,
+    #[serde(rename = "WriteIops")]
+    write_iops: u64,
 }
 
 impl From<&BoxInfo> for InspectPresenter {
     fn from(info: &BoxInfo) -> Self {
         let state = BoxStateInfo::from(info);
+        let network = info.network.as_ref();
+        let limits = &info.resource_limits;
         Self {
             id: info.id.to_string(),
             name: info.name.as_deref().unwrap_or("").to_string(),
             image: info.image.clone(),
+            image_digest: info.image_digest.clone().unwrap_or_default(),
             created: info.created_at.to_rfc3339(),
             status: info.status.as_str().to_string(),
             state: InspectStatePresenter {
                 status: state.status.as_str().to_string(),
                 running: state.running,
                 pid: state.pid.unwrap_or(0),
+                exit_code: state.exit_code.unwrap_or(0),
+                dead: state.status.is_dead(),
             },
             cpus: info.cpus,
             memory: info.memory_mib as u64 * 1024 * 1024,
+            swap: info.swap_mib as u64 * 1024 * 1024,
+            timezone: info.timezone.clone().unwrap_or_default(),
+            locale: info.locale.clone().unwrap_or_default(),
+            read_only_rootfs: info.read_only_rootfs,
+            tmpfs: info.writable_paths.clone(),
+            stop_timeout: info.shutdown_grace_period.as_secs_f64(),
+            auto_stop: info.auto_stop_after_idle.map(|d| d.as_secs_f64()),
+            idle_remaining: info.idle_remaining.map(|d| d.as_secs_f64()),
+            network_settings: InspectNetworkPresenter {
+                network_mode: info.network_mode,
+                ip_address: network.map(|n| n.guest_ip.clone()).unwrap_or_default(),
+                mac_address: network.map(|n| n.guest_mac.clone()).unwrap_or_default(),
+                gateway: network.map(|n| n.gateway_ip.clone()).unwrap_or_default(),
+                extra_hosts: info
+                    .hosts
+                    .iter()
+                    .flat_map(|e| e.names.iter().map(move |name| format!("{}:{}", name, e.ip)))
+                    .collect(),
+            },
+            disk_io: InspectDiskIoPresenter {
+                read_bps: limits.disk_read_bps.unwrap_or(0),
+                write_bps: limits.disk_write_bps.unwrap_or(0),
+                read_iops: limits.disk_read_iops.unwrap_or(0),
+                write_iops: limits.disk_write_iops.unwrap_or(0),
+            },
+            degradations: info.degradations.iter().map(|d| d.to_string()).collect(),
         }
     }
 }
@@ -83,21 +191,45 @@ pub async fn execute(args: InspectArgs, global: &GlobalFlags) -> anyhow::Result<
         ));
     }
 
+    let report_format = if args.report {
+        match OutputFormat::from_str(&args.format) {
+            Ok(fmt @ (OutputFormat::Json | OutputFormat::Yaml)) => Some(fmt),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "--report supports only json and yaml formats"
+                ));
+            }
+        }
+    } else {
+        None
+    };
+
     let rt = global.create_runtime()?;
-    let (infos, errs) = resolve_inspect_infos(&rt, &args).await?;
+    let (infos, mut errs) = resolve_inspect_infos(&rt, &args).await?;
 
-    if infos.is_empty() {
-        println!("[]");
-        return Err(errs.into_iter().next().unwrap());
-    }
+    if let Some(fmt) = report_format {
+        let reports = resolve_environment_reports(&rt, &infos, &mut errs).await?;
+        if reports.is_empty() {
+            global.reporter().println("[]");
+            return Err(errs.into_iter().next().unwrap());
+        }
+        let mut stdout = std::io::stdout().lock();
+        formatter::print_output(&mut stdout, &reports, fmt, |_, _| Ok(()))?;
+    } else {
+        if infos.is_empty() {
+            global.reporter().println("[]");
+            return Err(errs.into_iter().next().unwrap());
+        }
 
-    let presenters: Vec<InspectPresenter> = infos.iter().map(InspectPresenter::from).collect();
-    let mut stdout = std::io::stdout().lock();
-    write_inspect_output(&presenters, &args.format, &mut stdout)?;
+        let presenters: Vec<InspectPresenter> = infos.iter().map(InspectPresenter::from).collect();
+        let mut stdout = std::io::stdout().lock();
+        write_inspect_output(&presenters, &args.format, &mut stdout)?;
+    }
 
     if !errs.is_empty() {
+        let reporter = global.reporter();
         for e in &errs {
-            eprintln!("Error: {}", e);
+            reporter.error(e);
         }
         return Err(errs.into_iter().next().unwrap());
     }
@@ -173,6 +305,33 @@ async fn resolve_inspect_infos(
     }
 }
 
+/// Latest environment report of each box; boxes that never started become errors.
+async fn resolve_environment_reports(
+    rt: &boxlite::BoxliteRuntime,
+    infos: &[boxlite::BoxInfo],
+    errs: &mut Vec<anyhow::Error>,
+) -> anyhow::Result<Vec<EnvironmentReport>> {
+    let mut reports = Vec::new();
+    for info in infos {
+        let name_or_id = info.name.clone().unwrap_or_else(|| info.id.to_string());
+        let report = match rt.get(info.id.as_str()).await? {
+            Some(litebox) => litebox.environment_report().await?,
+            None => {
+                errs.push(anyhow::anyhow!("no such box: {}", name_or_id));
+                continue;
+            }
+        };
+        match report {
+            Some(report) => reports.push(report),
+            None => errs.push(anyhow::anyhow!(
+                "box {} has no environment report: it has not been started",
+                name_or_id
+            )),
+        }
+    }
+    Ok(reports)
+}
+
 /// Write inspect presenters to the given writer in the requested format.
 fn write_inspect_output<W: std::io::Write>(
     presenters: &Vec<InspectPresenter>,
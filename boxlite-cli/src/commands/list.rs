@@ -16,11 +16,18 @@ pub struct ListArgs {
     #[arg(short, long)]
     pub quiet: bool,
 
-    /// Output format (table, json, yaml)
-    #[arg(long, default_value = "table")]
+    /// Output format (table, json, yaml, or names: one name per line, the ID of
+    /// unnamed boxes)
+    #[arg(long, default_value = "table", value_parser = FORMATS, ignore_case = true)]
     pub format: String,
 }
 
+/// Values of `--format`: the table formats plus `names`.
+const FORMATS: [&str; 4] = ["table", "json", "yaml", NAMES];
+
+/// `--format` printing box names, which shell completion reads.
+const NAMES: &str = "names";
+
 #[derive(Tabled, Serialize)]
 struct BoxPresenter {
     #[tabled(rename = "ID")]
@@ -49,13 +56,21 @@ impl From<BoxInfo> for BoxPresenter {
         Self {
             id: info.id.to_string(),
             image: info.image,
-            status: format!("{:?}", info.status),
+            status: status_label(&info),
             created: formatter::format_time(&info.created_at),
             names: info.name.unwrap_or_default(),
         }
     }
 }
 
+/// Status column: the status, with the exit code of exited boxes.
+fn status_label(info: &BoxInfo) -> String {
+    match info.exit_code {
+        Some(code) if info.status.is_stopped() => format!("{:?} ({})", info.status, code),
+        _ => format!("{:?}", info.status),
+    }
+}
+
 pub async fn execute(args: ListArgs, global: &GlobalFlags) -> anyhow::Result<()> {
     let rt = global.create_runtime()?;
     let boxes = rt.list_info().await?;
@@ -65,9 +80,18 @@ pub async fn execute(args: ListArgs, global: &GlobalFlags) -> anyhow::Result<()>
         .filter(|info| args.all || info.status.is_active())
         .collect();
 
+    if args.format.eq_ignore_ascii_case(NAMES) {
+        let reporter = global.reporter();
+        for info in boxes {
+            reporter.println(info.name.as_deref().unwrap_or(info.id.as_str()));
+        }
+        return Ok(());
+    }
+
     if args.quiet {
+        let reporter = global.reporter();
         for info in boxes {
-            println!("{}", info.id);
+            reporter.println(&info.id);
         }
         return Ok(());
     }
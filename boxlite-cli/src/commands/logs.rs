@@ -1,6 +1,8 @@
 //! Display logs from a box.
 
 use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use crate::reporter::Reporter;
 use boxlite::runtime::layout::{FilesystemLayout, FsLayoutConfig};
 use clap::Args;
 use std::fs::File;
@@ -27,11 +29,12 @@ pub async fn execute(args: LogsArgs, global: &GlobalFlags) -> anyhow::Result<()>
     let options = global.resolve_runtime_options()?;
     let home_dir = options.home_dir.clone();
     let rt = global.create_runtime_with_options(options)?;
+    let reporter = global.reporter();
 
     let litebox = rt
         .get(&args.target)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("No such box: {}", args.target))?;
+        .ok_or_else(|| no_such_box(&args.target))?;
 
     // Construct console.log path: {home_dir}/boxes/{box_id}/logs/console.log
     let box_id = litebox.id();
@@ -41,21 +44,21 @@ pub async fn execute(args: LogsArgs, global: &GlobalFlags) -> anyhow::Result<()>
         .console_output_path();
 
     if !log_path.exists() {
-        eprintln!("No log file found for box '{}'", args.target);
-        eprintln!("The box may not have been started yet.");
-        eprintln!("Log path: {}", log_path.display());
+        reporter.status(format!("No log file found for box '{}'", args.target));
+        reporter.status("The box may not have been started yet.");
+        reporter.status(format!("Log path: {}", log_path.display()));
         return Ok(());
     }
 
     // Read initial logs (with --tail if specified)
     let initial_logs = read_logs(&log_path, args.tail)?;
     for line in initial_logs {
-        println!("{}", line);
+        reporter.println(line);
     }
 
     // Follow mode if requested
     if args.follow {
-        follow_logs(&log_path).await?;
+        follow_logs(&log_path, &reporter).await?;
     }
 
     Ok(())
@@ -118,11 +121,11 @@ fn find_start_offset(file: &mut File, tail_lines: usize) -> anyhow::Result<u64>
     Ok(0)
 }
 
-async fn follow_logs(path: &PathBuf) -> anyhow::Result<()> {
+async fn follow_logs(path: &PathBuf, reporter: &Reporter) -> anyhow::Result<()> {
     use notify::{RecursiveMode, Watcher};
     use tokio::signal;
 
-    eprintln!("\nFollowing log output (Ctrl+C to stop)...\n");
+    reporter.status("\nFollowing log output (Ctrl+C to stop)...\n");
 
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
     let mut watcher = notify::recommended_watcher(move |res| {
@@ -139,7 +142,7 @@ async fn follow_logs(path: &PathBuf) -> anyhow::Result<()> {
     loop {
         tokio::select! {
             _ = signal::ctrl_c() => {
-                eprintln!("\nStopped following logs.");
+                reporter.status("\nStopped following logs.");
                 break;
             }
             Some(event) = rx.recv() => {
@@ -147,14 +150,14 @@ async fn follow_logs(path: &PathBuf) -> anyhow::Result<()> {
                     match read_new_lines(path, last_pos) {
                         Ok(new_lines) => {
                             for line in new_lines {
-                                println!("{}", line);
+                                reporter.println(line);
                             }
                             if let Ok(metadata) = std::fs::metadata(path) {
                                 last_pos = metadata.len();
                             }
                         }
                         Err(e) => {
-                            eprintln!("Warning: failed to read new log lines: {}", e);
+                            reporter.warn(format!("failed to read new log lines: {}", e));
                         }
                     }
                 }
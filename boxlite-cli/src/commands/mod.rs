@@ -1,15 +1,39 @@
+pub mod audit;
+pub mod bench;
+pub mod build;
+pub mod compact;
+pub mod compose;
 pub mod cp;
 pub mod create;
+pub mod debug;
+pub mod doctor;
 pub mod exec;
+pub mod exec_all;
+pub mod exec_logs;
+pub mod export;
 pub mod images;
+pub mod import;
 pub mod info;
 pub mod inspect;
 pub mod list;
 pub mod logs;
+#[cfg(feature = "fuse")]
+pub mod mount;
+pub mod port_forward;
+pub mod prune;
 pub mod pull;
 pub mod restart;
 pub mod rm;
 pub mod run;
+pub mod service;
+pub mod snapshot;
 pub mod start;
 pub mod stats;
 pub mod stop;
+pub mod sync;
+pub mod system;
+pub mod template;
+pub mod trash;
+#[cfg(feature = "fuse")]
+pub mod umount;
+pub mod version;
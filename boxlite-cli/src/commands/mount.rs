@@ -0,0 +1,91 @@
+//! Mount a stopped box's filesystem read-only on the host.
+//!
+//! `boxlite mount` serves the mount until it is interrupted or
+//! `boxlite umount` signals it. It records itself under
+//! `<home>/mounts/<box_id>.json` so `umount` can find it; the runtime is
+//! released once mounted, so other commands keep working meanwhile.
+
+use std::path::{Path, PathBuf};
+
+use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use anyhow::{Context, Result};
+use clap::{Args, ValueHint};
+use serde::{Deserialize, Serialize};
+use tokio::signal::unix::{SignalKind, signal};
+
+#[derive(Args, Debug)]
+pub struct MountArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Existing directory to mount the box's filesystem on
+    #[arg(index = 2, value_name = "MOUNTPOINT", value_hint = ValueHint::DirPath)]
+    pub mountpoint: PathBuf,
+}
+
+/// A running `boxlite mount`, as recorded for `boxlite umount`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct MountRecord {
+    pub pid: u32,
+    pub mountpoint: PathBuf,
+}
+
+/// Where the mount of `box_id` is recorded.
+pub(crate) fn record_path(home: &Path, box_id: &str) -> PathBuf {
+    home.join("mounts").join(format!("{}.json", box_id))
+}
+
+pub async fn execute(args: MountArgs, global: &GlobalFlags) -> Result<()> {
+    let reporter = global.reporter();
+    let home = global.resolve_runtime_options()?.home_dir;
+
+    // Release the runtime once mounted; the handle keeps the box locked
+    let (box_id, mut handle) = {
+        let rt = global.create_runtime()?;
+        let litebox = rt
+            .get(&args.target)
+            .await?
+            .ok_or_else(|| no_such_box(&args.target))?;
+        let handle = litebox.mount_readonly(&args.mountpoint).await?;
+        (litebox.id().to_string(), handle)
+    };
+
+    let record = record_path(&home, &box_id);
+    std::fs::create_dir_all(record.parent().unwrap())
+        .with_context(|| format!("Failed to create {}", record.display()))?;
+    let mountpoint = handle.mountpoint().to_path_buf();
+    std::fs::write(
+        &record,
+        serde_json::to_vec(&MountRecord {
+            pid: std::process::id(),
+            mountpoint: mountpoint.clone(),
+        })?,
+    )
+    .with_context(|| format!("Failed to write {}", record.display()))?;
+
+    reporter.println(mountpoint.display());
+    reporter.status(format!(
+        "Mounted {} read-only at {} (Ctrl-C or `boxlite umount {}` to unmount)",
+        args.target,
+        mountpoint.display(),
+        args.target
+    ));
+
+    // Keep serving if unmounting fails, e.g. while a shell is inside
+    let mut terminate = signal(SignalKind::terminate())?;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+        match handle.unmount() {
+            Ok(()) => break,
+            Err(e) => reporter.error(e),
+        }
+    }
+
+    let _ = std::fs::remove_file(&record);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+//! Forward a local TCP port into a running box.
+
+use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use anyhow::{Context, Result};
+use clap::Args;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+
+#[derive(Args, Debug)]
+pub struct PortForwardArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Port in the box, optionally prefixed with the local port
+    /// (e.g. `8080`, `9000:8080`). Without one a free local port is picked.
+    #[arg(index = 2, value_name = "[LOCAL:]PORT")]
+    pub ports: String,
+
+    /// Local address to listen on
+    #[arg(long, default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    pub address: IpAddr,
+}
+
+pub async fn execute(args: PortForwardArgs, global: &GlobalFlags) -> Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let (local_port, guest_port) = parse_ports(&args.ports)?;
+
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+
+    let tunnel = litebox
+        .tunnel_on(guest_port, SocketAddr::new(args.address, local_port))
+        .await?;
+
+    reporter.println(tunnel.local_addr());
+    reporter.status(format!(
+        "Forwarding {} -> {}:{} (Ctrl-C to stop)",
+        tunnel.local_addr(),
+        args.target,
+        guest_port
+    ));
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = tunnel.closed() => {
+            anyhow::bail!("Box '{}' stopped; tunnel closed", args.target);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse `PORT` or `LOCAL:PORT` into (local, guest); local 0 means any.
+fn parse_ports(spec: &str) -> Result<(u16, u16)> {
+    let (local, guest) = match spec.split_once(':') {
+        Some((local, guest)) => (
+            local
+                .parse()
+                .with_context(|| format!("Invalid local port '{}'", local))?,
+            guest,
+        ),
+        None => (0, spec),
+    };
+    let guest: u16 = guest
+        .parse()
+        .with_context(|| format!("Invalid box port '{}'", guest))?;
+    if guest == 0 {
+        anyhow::bail!("Box port must not be 0");
+    }
+    Ok((local, guest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_guest_port_only() {
+        assert_eq!(parse_ports("8080").unwrap(), (0, 8080));
+    }
+
+    #[test]
+    fn parse_local_and_guest_port() {
+        assert_eq!(parse_ports("9000:8080").unwrap(), (9000, 8080));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_ports() {
+        assert!(parse_ports("http").is_err());
+        assert!(parse_ports("0").is_err());
+        assert!(parse_ports("70000").is_err());
+        assert!(parse_ports("x:80").is_err());
+    }
+}
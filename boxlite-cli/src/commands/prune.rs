@@ -0,0 +1,78 @@
+//! Remove every exited box at once.
+
+use boxlite::{BoxID, BoxliteResult, RemovePlan};
+use clap::Args;
+
+use crate::cli::{FilterFlags, GlobalFlags};
+use crate::commands::rm::describe_removal;
+use crate::commands::stats::format_bytes;
+use crate::util::bulk_failure_summary;
+
+/// Remove all exited boxes.
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub force: bool,
+
+    #[command(flatten)]
+    pub filter: FilterFlags,
+}
+
+pub async fn execute(args: PruneArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
+    let filter = args.filter.to_filter(None)?;
+
+    if global.dry_run {
+        let plans = runtime.plan_prune(filter).await?;
+        return report(global, &plans, "Would remove");
+    }
+
+    if !args.force
+        && reporter.is_interactive()
+        && !reporter.confirm("WARNING! This will remove all exited boxes. Are you sure?")?
+    {
+        return Ok(());
+    }
+
+    let spinner = reporter.spinner("Removing exited boxes");
+    let results = runtime.prune(filter).await;
+    drop(spinner);
+    report(global, &results?, "Removed")
+}
+
+/// Print one line per box and a total; fail if any box failed.
+fn report(
+    global: &GlobalFlags,
+    results: &[(BoxID, BoxliteResult<RemovePlan>)],
+    verb: &str,
+) -> anyhow::Result<()> {
+    let reporter = global.reporter();
+    let mut errors = Vec::new();
+    let mut reclaimed_bytes = 0;
+    for (id, result) in results {
+        match result {
+            Ok(plan) if global.dry_run => {
+                let target = plan.name.as_deref().unwrap_or(id.as_str());
+                reporter.println(describe_removal(target, plan));
+                reclaimed_bytes += plan.reclaimed_bytes;
+            }
+            Ok(plan) => {
+                reporter.println(id);
+                reclaimed_bytes += plan.reclaimed_bytes;
+            }
+            Err(e) => {
+                reporter.error_for(format!("removing box '{}'", id), e);
+                errors.push(format!("{}: {}", id, e));
+            }
+        }
+    }
+    reporter.status(format!(
+        "{} {} box(es), reclaiming {}",
+        verb,
+        results.len() - errors.len(),
+        format_bytes(Some(reclaimed_bytes))
+    ));
+    bulk_failure_summary("remove", &errors, results.len())
+}
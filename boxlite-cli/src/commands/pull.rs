@@ -1,7 +1,13 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
 use anyhow::Result;
+use boxlite::{BlobCacheLookup, PullProgress};
 use clap::Args;
 
+use super::stats::format_bytes;
 use crate::cli::GlobalFlags;
+use crate::reporter::{Reporter, Spinner};
 
 #[derive(Args, Debug)]
 pub struct PullArgs {
@@ -17,14 +23,129 @@ pub async fn execute(args: PullArgs, global: &GlobalFlags) -> Result<()> {
     let runtime = global.create_runtime()?;
     let images = runtime.images()?;
 
-    let image = images.pull(&args.image).await?;
+    let reporter = global.reporter().machine_readable(args.quiet);
+
+    let spinner = Arc::new(reporter.spinner(format!("Pulling {}", args.image)));
+    let progress = progress_reporter(&args.image, reporter.clone(), Arc::clone(&spinner));
+    let image = images.pull_with_progress(&args.image, progress).await?;
+    drop(spinner);
+
     if args.quiet {
-        println!("{}", image.config_digest());
+        reporter.println(image.config_digest());
     } else {
-        println!("Pulled: {}", image.reference());
-        println!("Digest: {}", image.config_digest());
-        println!("Layers: {}", image.layer_count());
+        reporter.println(format!("Pulled: {}", image.reference()));
+        reporter.println(format!("Digest: {}", image.config_digest()));
+        reporter.println(format!("Layers: {}", image.layer_count()));
     }
 
     Ok(())
 }
+
+/// Show bytes downloaded across all layers on the spinner, and announce
+/// layers resumed from an interrupted pull and shared blob cache lookups.
+fn progress_reporter(
+    image: &str,
+    reporter: Reporter,
+    spinner: Arc<Spinner>,
+) -> impl Fn(&PullProgress) + Send + Sync + 'static {
+    let image = image.to_string();
+    let layers = LayerTotals::default();
+    let looked_up: Mutex<HashSet<String>> = Mutex::default();
+
+    move |p: &PullProgress| {
+        if let Some(lookup) = p.cache
+            && looked_up
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .insert(p.digest.clone())
+        {
+            spinner.suspend(|| reporter.status(cache_message(&p.digest, lookup)));
+        }
+
+        if p.resumed > 0
+            && p.downloaded == p.resumed
+            && let Some(percent) = p.resumed_percent()
+        {
+            spinner.suspend(|| {
+                reporter.status(format!(
+                    "Resuming {} at {}%",
+                    short_digest(&p.digest),
+                    percent
+                ))
+            });
+        }
+
+        let (downloaded, total) = layers.update(&p.digest, p.downloaded, p.total);
+        spinner.set_message(pulling_message(&image, downloaded, total));
+    }
+}
+
+/// Latest progress of every layer of one pull.
+#[derive(Default)]
+pub(super) struct LayerTotals(Mutex<HashMap<String, (u64, u64)>>);
+
+impl LayerTotals {
+    /// Record one layer's progress and return `(downloaded, total)` bytes
+    /// across all layers seen so far.
+    pub(super) fn update(&self, digest: &str, downloaded: u64, total: u64) -> (u64, u64) {
+        let mut layers = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        layers.insert(digest.to_string(), (downloaded, total));
+        layers
+            .values()
+            .fold((0, 0), |(d, t), (ld, lt)| (d + ld, t + lt))
+    }
+}
+
+pub(super) fn pulling_message(image: &str, downloaded: u64, total: u64) -> String {
+    format!(
+        "Pulling {} ({} / {})",
+        image,
+        format_bytes(Some(downloaded)),
+        format_bytes(Some(total))
+    )
+}
+
+fn cache_message(digest: &str, lookup: BlobCacheLookup) -> String {
+    let outcome = match lookup {
+        BlobCacheLookup::Hit => "copied from shared cache",
+        BlobCacheLookup::Miss => "not in shared cache, downloading",
+    };
+    format!("{}: {}", short_digest(digest), outcome)
+}
+
+/// First 12 hex digits of a digest, as `docker pull` prints layers.
+fn short_digest(digest: &str) -> &str {
+    let hex = digest.split_once(':').map_or(digest, |(_, hex)| hex);
+    &hex[..hex.len().min(12)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_short_digest() {
+        assert_eq!(short_digest("sha256:0123456789abcdef0123"), "0123456789ab");
+        assert_eq!(short_digest("abc"), "abc");
+    }
+
+    #[test]
+    fn test_cache_message() {
+        assert_eq!(
+            cache_message("sha256:0123456789abcdef", BlobCacheLookup::Hit),
+            "0123456789ab: copied from shared cache"
+        );
+        assert_eq!(
+            cache_message("sha256:0123456789abcdef", BlobCacheLookup::Miss),
+            "0123456789ab: not in shared cache, downloading"
+        );
+    }
+
+    #[test]
+    fn test_layer_totals_sum_latest_progress() {
+        let layers = LayerTotals::default();
+        assert_eq!(layers.update("sha256:a", 10, 100), (10, 100));
+        assert_eq!(layers.update("sha256:b", 5, 50), (15, 150));
+        assert_eq!(layers.update("sha256:a", 100, 100), (105, 150));
+    }
+}
@@ -3,12 +3,13 @@ use clap::Args;
 #[derive(Args, Debug)]
 pub struct RestartArgs {
     /// Name or ID of the box(es) to restart
-    #[arg(required = true, num_args = 1..)]
+    #[arg(required = true, num_args = 1.., value_name = "BOX")]
     pub targets: Vec<String>,
 }
 
 pub async fn execute(args: RestartArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
     let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
 
     let mut errors = Vec::new();
     let mut success_count = 0;
@@ -17,15 +18,18 @@ pub async fn execute(args: RestartArgs, global: &crate::cli::GlobalFlags) -> any
         let litebox = match runtime.get(&target).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: No such box: {}", target);
+                reporter.error(format!("No such box: {}", target));
                 errors.push(format!("{}: not found", target));
                 continue;
             }
         };
 
+        let spinner = reporter.spinner(format!("Restarting {}", target));
+
         if let Err(e) = litebox.stop().await {
             // If stop fails, we should NOT proceed to start, because resources might still be locked.
-            eprintln!("Error restarting box '{}': {}", target, e);
+            drop(spinner);
+            reporter.error_for(format!("restarting box '{}'", target), &e);
             errors.push(format!("{}: {}", target, e));
             continue;
         }
@@ -35,17 +39,21 @@ pub async fn execute(args: RestartArgs, global: &crate::cli::GlobalFlags) -> any
         let litebox = match runtime.get(&target).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: Box disappeared after stop: {}", target);
+                drop(spinner);
+                reporter.error(format!("Box disappeared after stop: {}", target));
                 errors.push(format!("{}: disappeared after stop", target));
                 continue;
             }
         };
 
-        if let Err(e) = litebox.start().await {
-            eprintln!("Error restarting box '{}': {}", target, e);
+        let result = litebox.start().await;
+        drop(spinner);
+
+        if let Err(e) = result {
+            reporter.error_for(format!("restarting box '{}'", target), &e);
             errors.push(format!("{}: {}", target, e));
         } else {
-            println!("{}", target);
+            reporter.println(&target);
             success_count += 1;
         }
     }
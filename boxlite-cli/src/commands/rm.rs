@@ -1,3 +1,7 @@
+use crate::cli::FilterFlags;
+use crate::commands::stats::format_bytes;
+use crate::util::bulk_failure_summary;
+use boxlite::{BoxliteResult, BoxliteRuntime, RemovePlan};
 use clap::Args;
 
 #[derive(Args, Debug)]
@@ -7,25 +11,40 @@ pub struct RmArgs {
     pub force: bool,
 
     /// Remove all boxes
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "targets")]
     pub all: bool,
 
+    #[command(flatten)]
+    pub filter: FilterFlags,
+
+    /// Delete disks immediately instead of moving the box to the trash
+    #[arg(long)]
+    pub purge: bool,
+
     /// Name or ID of the box(es) to remove
-    #[arg(required_unless_present = "all", num_args = 1..)]
+    #[arg(
+        required_unless_present_any = ["all", "filter"],
+        conflicts_with = "filter",
+        num_args = 1..,
+        value_name = "BOX"
+    )]
     pub targets: Vec<String>,
 }
 
 pub async fn execute(args: RmArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    if global.dry_run {
+        return dry_run(args, global).await;
+    }
+    if args.filter.is_set() {
+        return remove_matching(args, global).await;
+    }
+
     let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
 
     // Require confirmation for --all unless --force is specified
     if args.all && !args.force {
-        use std::io::{self, Write};
-        eprint!("WARNING! This will remove all boxes. Are you sure? [y/N] ");
-        io::stderr().flush()?;
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        if !input.trim().eq_ignore_ascii_case("y") {
+        if !reporter.confirm("WARNING! This will remove all boxes. Are you sure?")? {
             return Ok(());
         }
     }
@@ -41,13 +60,43 @@ pub async fn execute(args: RmArgs, global: &crate::cli::GlobalFlags) -> anyhow::
         args.targets
     };
 
+    // Deleting disks outright cannot be undone from the trash
+    if !args.all && !args.force && reporter.is_interactive() {
+        let mut permanent = 0;
+        for target in &targets {
+            if plan(&runtime, target, args.force, args.purge)
+                .await
+                .is_ok_and(|plan| !plan.to_trash)
+            {
+                permanent += 1;
+            }
+        }
+        if permanent > 0 {
+            let question = format!(
+                "WARNING! This will permanently delete {} box(es). Are you sure?",
+                permanent
+            );
+            if !reporter.confirm(&question)? {
+                return Ok(());
+            }
+        }
+    }
+
     let mut active_error = false;
     for target in targets {
-        if let Err(e) = runtime.remove(&target, args.force).await {
-            eprintln!("Error removing box '{}': {}", target, e);
+        let spinner = reporter.spinner(format!("Removing {}", target));
+        let result = if args.purge {
+            runtime.remove_permanently(&target, args.force).await
+        } else {
+            runtime.remove(&target, args.force).await
+        };
+        drop(spinner);
+
+        if let Err(e) = result {
+            reporter.error_for(format!("removing box '{}'", target), &e);
             active_error = true;
         } else {
-            println!("{}", target);
+            reporter.println(&target);
         }
     }
 
@@ -56,3 +105,139 @@ pub async fn execute(args: RmArgs, global: &crate::cli::GlobalFlags) -> anyhow::
     }
     Ok(())
 }
+
+/// Remove the boxes selected by `--filter`.
+async fn remove_matching(args: RmArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
+    let filter = args.filter.to_filter(None)?;
+
+    if !args.force
+        && !reporter.confirm("WARNING! This will remove all matching boxes. Are you sure?")?
+    {
+        return Ok(());
+    }
+
+    let spinner = reporter.spinner("Removing matching boxes");
+    let force = args.force;
+    let results = if args.purge {
+        let runtime = &runtime;
+        runtime
+            .for_each_matching(filter, |info| async move {
+                runtime.remove_permanently(info.id.as_str(), force).await
+            })
+            .await?
+    } else {
+        runtime.remove_matching(filter, force).await?
+    };
+    drop(spinner);
+
+    let mut errors = Vec::new();
+    for (id, result) in &results {
+        match result {
+            Ok(()) => reporter.println(id),
+            Err(e) => {
+                reporter.error_for(format!("removing box '{}'", id), e);
+                errors.push(format!("{}: {}", id, e));
+            }
+        }
+    }
+    reporter.status(format!(
+        "Removed {} of {} box(es)",
+        results.len() - errors.len(),
+        results.len()
+    ));
+    bulk_failure_summary("remove", &errors, results.len())
+}
+
+/// Print what the removal would do, without removing anything.
+async fn dry_run(args: RmArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
+    let (force, purge) = (args.force, args.purge);
+
+    let results: Vec<(String, BoxliteResult<RemovePlan>)> = if args.filter.is_set() {
+        let runtime = &runtime;
+        runtime
+            .for_each_matching(args.filter.to_filter(None)?, |info| async move {
+                plan(runtime, info.id.as_str(), force, purge).await
+            })
+            .await?
+            .into_iter()
+            .map(|(id, result)| (id.to_string(), result))
+            .collect()
+    } else {
+        let targets = if args.all {
+            runtime
+                .list_info()
+                .await?
+                .into_iter()
+                .map(|info| info.id.to_string())
+                .collect()
+        } else {
+            args.targets
+        };
+        let mut results = Vec::new();
+        for target in targets {
+            let result = plan(&runtime, &target, force, purge).await;
+            results.push((target, result));
+        }
+        results
+    };
+
+    let mut failed = false;
+    let mut planned = 0;
+    let mut reclaimed_bytes = 0;
+    for (target, result) in results {
+        match result {
+            Ok(plan) => {
+                reporter.println(describe_removal(&target, &plan));
+                planned += 1;
+                reclaimed_bytes += plan.reclaimed_bytes;
+            }
+            Err(e) => {
+                reporter.error_for(format!("removing box '{}'", target), &e);
+                failed = true;
+            }
+        }
+    }
+    reporter.status(format!(
+        "Would remove {} box(es), reclaiming {}",
+        planned,
+        format_bytes(Some(reclaimed_bytes))
+    ));
+
+    if failed {
+        anyhow::bail!("Some boxes could not be removed");
+    }
+    Ok(())
+}
+
+async fn plan(
+    runtime: &BoxliteRuntime,
+    target: &str,
+    force: bool,
+    purge: bool,
+) -> BoxliteResult<RemovePlan> {
+    if purge {
+        runtime.plan_remove_permanently(target, force).await
+    } else {
+        runtime.plan_remove(target, force).await
+    }
+}
+
+pub(crate) fn describe_removal(target: &str, plan: &RemovePlan) -> String {
+    let stop = if plan.stops_box { "stop and " } else { "" };
+    let action = if plan.to_trash {
+        "move to the trash"
+    } else {
+        "delete"
+    };
+    format!(
+        "Would {}{} {} ({})",
+        stop,
+        action,
+        target,
+        format_bytes(Some(plan.disk_bytes))
+    )
+}
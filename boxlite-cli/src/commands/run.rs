@@ -1,12 +1,21 @@
+use super::create::phase_reporter;
 use crate::cli::{
     GlobalFlags, ManagementFlags, ProcessFlags, PublishFlags, ResourceFlags, VolumeFlags,
 };
+use crate::reporter::{Reporter, Spinner};
 use crate::terminal::StreamManager;
 use crate::util::to_shell_exit_code;
 use boxlite::BoxCommand;
-use boxlite::{BoxOptions, BoxliteRuntime, LiteBox, RootfsSpec};
+use boxlite::{
+    BoxOptions, BoxOptionsPatch, BoxliteRuntime, GetOrCreateOutcome, GetOrCreatePolicy, LiteBox,
+    RunOnceOptions,
+};
 use clap::Args;
-use std::io::{self, IsTerminal};
+use std::io::{self, IsTerminal, Write};
+use std::sync::Arc;
+
+/// Per-stream capture limit for `--once`.
+const RUN_ONCE_MAX_OUTPUT_BYTES: usize = 64 * 1024 * 1024;
 
 #[derive(Args, Debug)]
 pub struct RunArgs {
@@ -25,8 +34,27 @@ pub struct RunArgs {
     #[command(flatten)]
     pub management: ManagementFlags,
 
-    #[arg(index = 1)]
-    pub image: String,
+    /// Create the box from this template (see `boxlite template`); flags
+    /// override the template's settings and every argument is the command
+    #[arg(long, value_name = "NAME")]
+    pub template: Option<String>,
+
+    /// Reuse the box named by --name, recreating it first if it was created
+    /// with other options
+    #[arg(long, requires = "name")]
+    pub recreate_on_change: bool,
+
+    /// Run as a one-shot box: output is captured (up to 64 MiB per stream)
+    /// and printed when the command exits, instead of streamed
+    #[arg(
+        long,
+        requires = "rm",
+        conflicts_with_all = ["name", "detach", "interactive", "tty"]
+    )]
+    pub once: bool,
+
+    #[arg(index = 1, required_unless_present = "template")]
+    pub image: Option<String>,
 
     /// Command to run inside the image
     #[arg(index = 2, trailing_var_arg = true)]
@@ -43,29 +71,52 @@ struct BoxRunner {
     args: RunArgs,
     rt: BoxliteRuntime,
     home: Option<std::path::PathBuf>,
+    reporter: Reporter,
 }
 
 impl BoxRunner {
-    fn new(args: RunArgs, global: &GlobalFlags) -> anyhow::Result<Self> {
+    fn new(mut args: RunArgs, global: &GlobalFlags) -> anyhow::Result<Self> {
+        // With a template there is no image argument: the first positional
+        // is the command
+        if args.template.is_some()
+            && let Some(program) = args.image.take()
+        {
+            args.command.insert(0, program);
+        }
+
         let rt = global.create_runtime()?;
         let home = global.home.clone();
 
-        Ok(Self { args, rt, home })
+        let reporter = global.reporter();
+
+        Ok(Self {
+            args,
+            rt,
+            home,
+            reporter,
+        })
     }
 
     async fn run(&mut self) -> anyhow::Result<()> {
         // Validate flags and environment
         self.validate_flags()?;
 
-        let litebox = self.create_box().await?;
+        if self.args.once {
+            return self.run_once().await;
+        }
+
+        let spinner = Arc::new(self.reporter.spinner(format!("Starting {}", self.source())));
+        let litebox = self.create_box(&spinner).await?;
+        spinner.set_message(format!("Starting {}", self.source()));
 
         // Start execution
         let cmd = self.prepare_command();
         let mut execution = litebox.exec(cmd).await?;
+        drop(spinner);
 
         // Detach mode: Print ID and exit
         if self.args.management.detach {
-            println!("{}", litebox.id());
+            self.reporter.println(litebox.id());
             return Ok(());
         }
 
@@ -85,31 +136,154 @@ impl BoxRunner {
         Ok(())
     }
 
-    async fn create_box(&self) -> anyhow::Result<LiteBox> {
-        let mut options = BoxOptions::default();
-        self.args.resource.apply_to(&mut options);
-        self.args.management.apply_to(&mut options);
-        self.args.publish.apply_to(&mut options)?;
-        self.args
-            .volume
-            .apply_to(&mut options, self.home.as_deref())?;
-        self.args.process.apply_to(&mut options)?;
+    /// Run via `BoxliteRuntime::run_once`, then replay the captured output.
+    async fn run_once(&self) -> anyhow::Result<()> {
+        let options = self.box_options().await?;
+        let mut run_once_options = RunOnceOptions::default();
+        run_once_options.max_output_bytes(RUN_ONCE_MAX_OUTPUT_BYTES);
 
-        // Runtime requires detached boxes to have manual lifecycle control (auto_remove=false)
-        if self.args.management.detach {
-            options.auto_remove = false;
+        let spinner = self.reporter.spinner(format!("Running {}", self.source()));
+        let result = self
+            .rt
+            .run_once(options, self.prepare_command(), run_once_options)
+            .await?;
+        drop(spinner);
+
+        io::stdout().write_all(result.stdout.as_bytes())?;
+        io::stdout().flush()?;
+        io::stderr().write_all(result.stderr.as_bytes())?;
+        if result.stdout_truncated || result.stderr_truncated {
+            self.reporter.warn(format!(
+                "output truncated at {RUN_ONCE_MAX_OUTPUT_BYTES} bytes"
+            ));
+        }
+
+        if result.exit_code != 0 {
+            std::process::exit(to_shell_exit_code(result.exit_code));
+        }
+
+        Ok(())
+    }
+
+    async fn create_box(&self, spinner: &Arc<Spinner>) -> anyhow::Result<LiteBox> {
+        let options = self.box_options().await?;
+        if self.args.recreate_on_change {
+            let name = self.args.management.name.clone();
+            let (litebox, outcome) = self
+                .rt
+                .get_or_create_with(options, name, GetOrCreatePolicy::RecreateOnMismatch)
+                .await?;
+            if let GetOrCreateOutcome::Recreated { changed } = outcome {
+                spinner.suspend(|| {
+                    self.reporter
+                        .warn(format!("Recreated box: changed {}", changed.join(", ")))
+                });
+            }
+            return Ok(litebox);
         }
 
-        options.rootfs = RootfsSpec::Image(self.args.image.clone());
+        let progress = phase_reporter(self.source(), Arc::clone(spinner), self.reporter.clone());
 
         let litebox = self
             .rt
-            .create(options, self.args.management.name.clone())
+            .create_with_observer(options, self.args.management.name.clone(), progress)
             .await?;
 
         Ok(litebox)
     }
 
+    /// The template or image the box is created from, for progress messages.
+    fn source(&self) -> &str {
+        self.args
+            .template
+            .as_deref()
+            .or(self.args.image.as_deref())
+            .unwrap_or_default()
+    }
+
+    async fn box_options(&self) -> anyhow::Result<BoxOptions> {
+        match &self.args.template {
+            Some(template) => {
+                let overrides = self.template_overrides()?;
+                Ok(self.rt.template_options(template, &overrides).await?)
+            }
+            None => self.flag_options(),
+        }
+    }
+
+    /// The flags as overrides of a template's options. Only flags that were
+    /// given override the template, except `--rm` and `-d`, which always do.
+    fn template_overrides(&self) -> anyhow::Result<BoxOptionsPatch> {
+        let management = &self.args.management;
+        if management.init
+            || management.entrypoint_script.is_some()
+            || management.capture_core_dumps
+            || management.read_only
+            || !management.tmpfs.is_empty()
+            || management.stop_timeout.is_some()
+            || management.auto_stop.is_some()
+            || management.timezone.is_some()
+            || management.locale.is_some()
+            || !self.args.process.redact_env.is_empty()
+        {
+            anyhow::bail!(
+                "--init, --entrypoint-script, --capture-core-dumps, --read-only, --tmpfs, --stop-timeout, --auto-stop, --timezone, --locale and --redact-env cannot be used with --template"
+            );
+        }
+
+        let options = self.flag_options()?;
+        let mut overrides = BoxOptionsPatch::default();
+        for (key, value) in options.env {
+            overrides.env(key, value);
+        }
+        for (key, value) in options.labels {
+            overrides.label(key, value);
+        }
+        if let Some(cpus) = options.cpus {
+            overrides.cpus(cpus);
+        }
+        if let Some(memory_mib) = options.memory_mib {
+            overrides.memory_mib(memory_mib);
+        }
+        if let Some(swap_mib) = options.swap_mib {
+            overrides.swap_mib(swap_mib);
+        }
+        if let Some(working_dir) = options.working_dir {
+            overrides.working_dir(working_dir);
+        }
+        if !options.volumes.is_empty() {
+            overrides.volumes(options.volumes);
+        }
+        if !options.ports.is_empty() {
+            overrides.ports(options.ports);
+        }
+        if self.args.publish.network.is_some() {
+            overrides.network(options.network);
+        }
+        overrides
+            .auto_remove(options.auto_remove)
+            .detach(options.detach);
+        Ok(overrides)
+    }
+
+    /// Options built from the flags alone. With `--template` the image is a
+    /// placeholder; only the fields `template_overrides` copies are used.
+    fn flag_options(&self) -> anyhow::Result<BoxOptions> {
+        let mut builder = BoxOptions::builder().image(self.source());
+        builder = self.args.resource.apply_to(builder);
+        builder = self.args.management.apply_to(builder)?;
+        builder = self.args.publish.apply_to(builder)?;
+        builder = self.args.volume.apply_to(builder, self.home.as_deref())?;
+        builder = self.args.process.apply_to(builder);
+
+        // Runtime requires detached boxes to have manual lifecycle control (auto_remove=false)
+        if self.args.management.detach {
+            builder = builder.auto_remove(false);
+        }
+
+        Ok(builder.build()?)
+    }
+
     fn prepare_command(&self) -> BoxCommand {
         let (program, args) = parse_command_args(&self.args.command);
         BoxCommand::new(program)
@@ -0,0 +1,261 @@
+//! Manage the supervised services of a box.
+
+use crate::cli::{GlobalFlags, ProcessFlags};
+use crate::error::no_such_box;
+use crate::formatter::{self, OutputFormat};
+use boxlite::{BoxCommand, RestartPolicy, ServiceInfo, ServicePolicy};
+use clap::{Args, Subcommand, ValueEnum};
+use serde::Serialize;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct ServiceArgs {
+    #[command(subcommand)]
+    pub command: ServiceCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ServiceCommand {
+    /// Run a command as a supervised service, started with the box
+    Add(AddArgs),
+
+    /// List a box's services
+    #[command(visible_alias = "list")]
+    Ls(LsArgs),
+
+    /// Stop services until the box next starts
+    Stop(NamesArgs),
+
+    /// Stop services and remove them from the box
+    Rm(NamesArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    #[command(flatten)]
+    pub process: ProcessFlags,
+
+    /// When to restart the service after its process exits
+    #[arg(long, default_value_t = RestartArg::No, value_enum)]
+    pub restart: RestartArg,
+
+    /// Service that must start before this one (repeatable)
+    #[arg(long = "depends-on", value_name = "SERVICE")]
+    pub depends_on: Vec<String>,
+
+    /// Start from the current environment and working directory of the
+    /// box's main process (-e and -w still override)
+    #[arg(long)]
+    pub inherit_env: bool,
+
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Service name
+    #[arg(index = 2, value_name = "SERVICE")]
+    pub name: String,
+
+    /// Command the service runs
+    #[arg(index = 3, last = true, required = true)]
+    pub command: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct LsArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct NamesArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Service name(s)
+    #[arg(index = 2, required = true, num_args = 1.., value_name = "SERVICE")]
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum RestartArg {
+    No,
+    OnFailure,
+    Always,
+}
+
+impl From<RestartArg> for RestartPolicy {
+    fn from(arg: RestartArg) -> Self {
+        match arg {
+            RestartArg::No => RestartPolicy::No,
+            RestartArg::OnFailure => RestartPolicy::OnFailure,
+            RestartArg::Always => RestartPolicy::Always,
+        }
+    }
+}
+
+#[derive(Tabled, Serialize)]
+struct ServicePresenter {
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[tabled(rename = "STATUS")]
+    #[serde(rename = "Status")]
+    status: String,
+
+    #[tabled(rename = "PID")]
+    #[serde(rename = "Pid")]
+    pid: String,
+
+    #[tabled(rename = "RESTARTS")]
+    #[serde(rename = "Restarts")]
+    restarts: u32,
+
+    #[tabled(rename = "RESTART")]
+    #[serde(rename = "RestartPolicy")]
+    restart: String,
+
+    #[tabled(rename = "DEPENDS ON")]
+    #[serde(rename = "DependsOn")]
+    depends_on: String,
+
+    #[tabled(rename = "EXEC ID")]
+    #[serde(rename = "ExecId")]
+    execution_id: String,
+}
+
+impl From<ServiceInfo> for ServicePresenter {
+    fn from(info: ServiceInfo) -> Self {
+        let restart = match info.policy.restart {
+            RestartPolicy::No => "no",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Always => "always",
+        };
+        let status = match (&info.exit_code, &info.error) {
+            (_, Some(error)) => format!("{} ({})", info.status, error),
+            (Some(code), None) if info.pid.is_none() => format!("{} ({})", info.status, code),
+            _ => info.status.to_string(),
+        };
+        Self {
+            name: info.name,
+            status,
+            pid: info
+                .pid
+                .map_or_else(|| "-".to_string(), |pid| pid.to_string()),
+            restarts: info.restarts,
+            restart: restart.to_string(),
+            depends_on: info.policy.depends_on.join(","),
+            execution_id: info.execution_id.unwrap_or_default(),
+        }
+    }
+}
+
+pub async fn execute(args: ServiceArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        ServiceCommand::Add(args) => add(args, global).await,
+        ServiceCommand::Ls(args) => ls(args, global).await,
+        ServiceCommand::Stop(args) => stop(args, global, false).await,
+        ServiceCommand::Rm(args) => stop(args, global, true).await,
+    }
+}
+
+async fn add(args: AddArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    if args.process.tty || args.process.interactive {
+        anyhow::bail!("services run without a terminal or stdin (drop -t/-i)");
+    }
+    let rt = global.create_runtime()?;
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+
+    let command = BoxCommand::new(&args.command[0])
+        .args(&args.command[1..])
+        .inherit_runtime_env(args.inherit_env);
+    let command = args.process.configure_command(command);
+    let policy = ServicePolicy {
+        restart: args.restart.into(),
+        depends_on: args.depends_on,
+    };
+
+    litebox.spawn_service(&args.name, command, policy).await?;
+    global.reporter().println(&args.name);
+    Ok(())
+}
+
+async fn ls(args: LsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+    let presenters: Vec<ServicePresenter> = litebox
+        .list_services()
+        .await?
+        .into_iter()
+        .map(ServicePresenter::from)
+        .collect();
+
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            print_services(writer, data)?;
+            Ok(())
+        },
+    )?;
+    Ok(())
+}
+
+fn print_services(
+    writer: &mut dyn std::io::Write,
+    services: &[ServicePresenter],
+) -> anyhow::Result<()> {
+    let table = formatter::create_table(services).to_string();
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+async fn stop(args: NamesArgs, global: &GlobalFlags, remove: bool) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+    let reporter = global.reporter();
+    let (verb, done) = if remove {
+        ("removing", "removed")
+    } else {
+        ("stopping", "stopped")
+    };
+
+    let mut failed = false;
+    for name in &args.names {
+        let result = if remove {
+            litebox.remove_service(name).await
+        } else {
+            litebox.stop_service(name).await
+        };
+        match result {
+            Ok(()) => reporter.println(name),
+            Err(e) => {
+                reporter.error_for(format!("{} service '{}'", verb, name), &e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("Some services could not be {}", done);
+    }
+    Ok(())
+}
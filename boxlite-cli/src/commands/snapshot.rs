@@ -0,0 +1,415 @@
+use std::io::IsTerminal;
+
+use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use crate::formatter::{self, OutputFormat};
+use boxlite::{LiteBox, RestoreOptions, SnapshotInfo, SnapshotOptions, SnapshotRetention};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+use super::stats::format_bytes;
+
+#[derive(Args, Debug)]
+pub struct SnapshotArgs {
+    #[command(subcommand)]
+    pub command: SnapshotCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommand {
+    /// Snapshot a stopped box's disks
+    Create(CreateArgs),
+
+    /// List a box's snapshots
+    #[command(visible_alias = "list")]
+    Ls(LsArgs),
+
+    /// Remove one or more snapshots
+    Rm(RmArgs),
+
+    /// Reset a stopped box's disks to a snapshot, discarding current disk state
+    Restore(RestoreArgs),
+
+    /// Create a new box from a snapshot, leaving the source box untouched
+    Branch(BranchArgs),
+
+    /// Remove a stopped box's snapshots outside its retention policy
+    Prune(PruneArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct CreateArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Name of the new snapshot
+    pub snapshot: String,
+
+    /// Keep only the newest N snapshots afterwards, overriding the box's retention policy
+    #[arg(long, value_name = "N")]
+    pub keep: Option<usize>,
+
+    /// Also capture the box's metadata: options, env, labels and services
+    #[arg(long)]
+    pub metadata: bool,
+
+    /// Capture only the box's metadata, without its disks
+    #[arg(long, conflicts_with = "metadata")]
+    pub metadata_only: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct LsArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Only show snapshot names
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct RmArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Name of the snapshot(s) to remove
+    #[arg(required = true, num_args = 1..)]
+    pub snapshots: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct RestoreArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Name of the snapshot to restore
+    pub snapshot: String,
+
+    /// Also restore the box metadata captured with the snapshot
+    #[arg(long)]
+    pub metadata: bool,
+
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct BranchArgs {
+    /// Name or ID of the source box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Name of the snapshot to branch from
+    pub snapshot: String,
+
+    /// Name for the new box
+    #[arg(long)]
+    pub name: String,
+}
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// Name or ID of the box
+    #[arg(value_name = "BOX")]
+    pub target: String,
+
+    /// Keep only the newest N snapshots, overriding the box's retention policy
+    #[arg(long, value_name = "N")]
+    pub keep: Option<usize>,
+
+    /// Do not ask for confirmation
+    #[arg(short, long)]
+    pub force: bool,
+}
+
+#[derive(Tabled, Serialize)]
+struct SnapshotPresenter {
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[tabled(rename = "CREATED")]
+    #[serde(rename = "CreatedAt")]
+    created: String,
+
+    #[tabled(rename = "SIZE")]
+    #[serde(rename = "Size")]
+    size: String,
+
+    #[tabled(rename = "CONTENTS")]
+    #[serde(rename = "Contents")]
+    contents: String,
+}
+
+impl From<SnapshotInfo> for SnapshotPresenter {
+    fn from(info: SnapshotInfo) -> Self {
+        Self {
+            name: info.name,
+            created: chrono::DateTime::from_timestamp(info.created_at, 0)
+                .map(|t| formatter::format_time(&t))
+                .unwrap_or_default(),
+            size: format_bytes(Some(info.size_bytes)),
+            contents: contents(&info).to_string(),
+        }
+    }
+}
+
+pub async fn execute(args: SnapshotArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        SnapshotCommand::Create(args) => create(args, global).await,
+        SnapshotCommand::Ls(args) => ls(args, global).await,
+        SnapshotCommand::Rm(args) => rm(args, global).await,
+        SnapshotCommand::Restore(args) => restore(args, global).await,
+        SnapshotCommand::Branch(args) => branch(args, global).await,
+        SnapshotCommand::Prune(args) => prune(args, global).await,
+    }
+}
+
+async fn create(args: CreateArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    if args.keep == Some(0) {
+        anyhow::bail!("--keep must be at least 1");
+    }
+
+    let litebox = get_box(global, &args.target).await?;
+
+    let mut opts = SnapshotOptions::default();
+    if let Some(retention) = keep_retention(args.keep) {
+        opts.retention(retention);
+    }
+    opts.metadata(args.metadata)
+        .metadata_only(args.metadata_only);
+
+    let reporter = global.reporter();
+
+    let spinner = reporter.spinner(format!("Creating snapshot {}", args.snapshot));
+    let info = litebox.snapshot().create(&args.snapshot, opts).await?;
+    drop(spinner);
+
+    reporter.println(&info.name);
+    if let Some(pruned) = info.pruned.filter(|p| !p.names.is_empty()) {
+        reporter.status(format!(
+            "Pruned {} snapshot(s), reclaimed {} bytes: {}",
+            pruned.names.len(),
+            pruned.reclaimed_bytes,
+            pruned.names.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+async fn branch(args: BranchArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let litebox = get_box(global, &args.target).await?;
+
+    let reporter = global.reporter();
+
+    let spinner = reporter.spinner(format!("Branching {} from {}", args.name, args.snapshot));
+    let branched = litebox
+        .snapshot()
+        .restore_to_new(&args.snapshot, &args.name)
+        .await?;
+    drop(spinner);
+
+    reporter.println(branched.id());
+    Ok(())
+}
+
+async fn ls(args: LsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let litebox = get_box(global, &args.target).await?;
+    let snapshots = litebox.snapshot().list().await?;
+
+    if args.quiet {
+        let reporter = global.reporter();
+        for info in snapshots {
+            reporter.println(&info.name);
+        }
+        return Ok(());
+    }
+
+    let presenters: Vec<SnapshotPresenter> =
+        snapshots.into_iter().map(SnapshotPresenter::from).collect();
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            let table = formatter::create_table(data).to_string();
+            writeln!(writer, "{}", table)?;
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+async fn rm(args: RmArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let litebox = get_box(global, &args.target).await?;
+    let reporter = global.reporter();
+
+    let mut failed = false;
+    for name in args.snapshots {
+        match litebox.snapshot().remove(&name).await {
+            Ok(()) => reporter.println(&name),
+            Err(e) => {
+                reporter.error_for(format!("removing snapshot '{}'", name), &e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("Some snapshots could not be removed");
+    }
+    Ok(())
+}
+
+async fn restore(args: RestoreArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let litebox = get_box(global, &args.target).await?;
+    let reporter = global.reporter();
+
+    let mut opts = RestoreOptions::default();
+    opts.restore_metadata(args.metadata);
+
+    if global.dry_run {
+        let plan = litebox
+            .snapshot()
+            .plan_restore(&args.snapshot, opts)
+            .await?;
+        for disk in &plan.overwritten_disks {
+            reporter.println(format!("Would overwrite {}", disk.display()));
+        }
+        for path in &plan.metadata_changes {
+            reporter.println(format!("Would change {}", path));
+        }
+        reporter.status(format!(
+            "Would restore snapshot {} on box {}, discarding {} of changes",
+            plan.snapshot,
+            args.target,
+            format_bytes(Some(plan.discarded_bytes))
+        ));
+        return Ok(());
+    }
+
+    if !args.force {
+        if !std::io::stdin().is_terminal() {
+            anyhow::bail!(
+                "Restoring discards the current disk state of box {}; pass --force to confirm",
+                args.target
+            );
+        }
+        let mut question = format!(
+            "WARNING! This will discard the current disk state of box {}",
+            args.target
+        );
+        if args.metadata {
+            let plan = litebox
+                .snapshot()
+                .plan_restore(&args.snapshot, opts.clone())
+                .await?;
+            for path in &plan.metadata_changes {
+                reporter.status(format!("Will change {}", path));
+            }
+            question.push_str(" and replace its metadata");
+        }
+        question.push_str(". Are you sure?");
+        if !reporter.confirm(&question)? {
+            return Ok(());
+        }
+    }
+
+    let spinner = reporter.spinner(format!("Restoring snapshot {}", args.snapshot));
+    litebox.snapshot().restore(&args.snapshot, opts).await?;
+    drop(spinner);
+
+    reporter.println(&args.snapshot);
+    Ok(())
+}
+
+async fn prune(args: PruneArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    if args.keep == Some(0) {
+        anyhow::bail!("--keep must be at least 1");
+    }
+
+    let litebox = get_box(global, &args.target).await?;
+    let reporter = global.reporter();
+    let retention = keep_retention(args.keep);
+
+    let plan = litebox.snapshot().plan_prune(retention.clone()).await?;
+    if global.dry_run {
+        for name in &plan.names {
+            reporter.println(format!("Would remove snapshot {}", name));
+        }
+        reporter.status(format!(
+            "Would prune {} snapshot(s), reclaiming {}",
+            plan.names.len(),
+            format_bytes(Some(plan.reclaimed_bytes))
+        ));
+        return Ok(());
+    }
+    if plan.names.is_empty() {
+        return Ok(());
+    }
+
+    if !args.force && reporter.is_interactive() {
+        let question = format!(
+            "WARNING! This will remove {} snapshot(s) of box {}: {}. Are you sure?",
+            plan.names.len(),
+            args.target,
+            plan.names.join(", ")
+        );
+        if !reporter.confirm(&question)? {
+            return Ok(());
+        }
+    }
+
+    let spinner = reporter.spinner(format!("Pruning snapshots of {}", args.target));
+    let pruned = litebox.snapshot().prune(retention).await?;
+    drop(spinner);
+
+    for name in &pruned.names {
+        reporter.println(name);
+    }
+    reporter.status(format!(
+        "Pruned {} snapshot(s), reclaimed {}",
+        pruned.names.len(),
+        format_bytes(Some(pruned.reclaimed_bytes))
+    ));
+    Ok(())
+}
+
+/// What a snapshot holds, for the CONTENTS column.
+fn contents(info: &SnapshotInfo) -> &'static str {
+    match (info.metadata_only, info.has_metadata) {
+        (true, _) => "metadata",
+        (false, true) => "disks+metadata",
+        (false, false) => "disks",
+    }
+}
+
+/// Retention keeping only the newest `keep` snapshots, if given.
+fn keep_retention(keep: Option<usize>) -> Option<SnapshotRetention> {
+    keep.map(|keep| {
+        let mut retention = SnapshotRetention::default();
+        retention.max_count(keep);
+        retention
+    })
+}
+
+async fn get_box(global: &GlobalFlags, target: &str) -> anyhow::Result<LiteBox> {
+    let runtime = global.create_runtime()?;
+    runtime
+        .get(target)
+        .await?
+        .ok_or_else(|| no_such_box(&target))
+}
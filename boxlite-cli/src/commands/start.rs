@@ -3,12 +3,13 @@ use clap::Args;
 #[derive(Args, Debug)]
 pub struct StartArgs {
     /// Name or ID of the box(es) to start
-    #[arg(required = true, num_args = 1..)]
+    #[arg(required = true, num_args = 1.., value_name = "BOX")]
     pub targets: Vec<String>,
 }
 
 pub async fn execute(args: StartArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
     let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
 
     let mut errors = Vec::new();
     let mut success_count = 0;
@@ -17,17 +18,21 @@ pub async fn execute(args: StartArgs, global: &crate::cli::GlobalFlags) -> anyho
         let litebox = match runtime.get(&target).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: No such box: {}", target);
+                reporter.error(format!("No such box: {}", target));
                 errors.push(format!("{}: not found", target));
                 continue;
             }
         };
 
-        if let Err(e) = litebox.start().await {
-            eprintln!("Error starting box '{}': {}", target, e);
+        let spinner = reporter.spinner(format!("Starting {}", target));
+        let result = litebox.start().await;
+        drop(spinner);
+
+        if let Err(e) = result {
+            reporter.error_for(format!("starting box '{}'", target), &e);
             errors.push(format!("{}: {}", target, e));
         } else {
-            println!("{}", target);
+            reporter.println(&target);
             success_count += 1;
         }
     }
@@ -1,6 +1,7 @@
 //! Display resource usage statistics for a box.
 
 use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
 use crate::formatter::{self, OutputFormat};
 use boxlite::BoxMetrics;
 use clap::Args;
@@ -15,7 +16,7 @@ pub struct StatsArgs {
     pub target: String,
 
     /// Output format (table, json, yaml)
-    #[arg(long, default_value = "table")]
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
     pub format: String,
 
     /// Stream stats in real-time
@@ -39,15 +40,17 @@ pub async fn execute(args: StatsArgs, global: &GlobalFlags) -> anyhow::Result<()
     let litebox = rt
         .get(&args.target)
         .await?
-        .ok_or_else(|| anyhow::anyhow!("No such box: {}", args.target))?;
+        .ok_or_else(|| no_such_box(&args.target))?;
 
     let format = OutputFormat::from_str(&args.format)?;
+    let reporter = global
+        .reporter()
+        .machine_readable(format != OutputFormat::Table);
 
     if args.stream {
         loop {
             // Clear screen and move cursor to top-left
-            print!("\x1B[2J\x1B[1;1H");
-            std::io::stdout().flush()?;
+            reporter.clear_screen()?;
 
             let metrics = litebox.metrics().await?;
             let presenters = format_metrics(metrics);
@@ -98,6 +101,10 @@ fn format_metrics(metrics: BoxMetrics) -> Vec<StatsPresenter> {
             metric: "Memory".to_string(),
             value: format_bytes(metrics.memory_bytes),
         },
+        StatsPresenter {
+            metric: "Swap".to_string(),
+            value: format_bytes(metrics.swap_bytes),
+        },
         StatsPresenter {
             metric: "Commands".to_string(),
             value: metrics.commands_executed_total.to_string(),
@@ -137,7 +144,7 @@ fn format_percent(value: Option<f32>) -> String {
     }
 }
 
-fn format_bytes(value: Option<u64>) -> String {
+pub(crate) fn format_bytes(value: Option<u64>) -> String {
     match value {
         Some(bytes) => {
             const KB: u64 = 1024;
@@ -1,50 +1,90 @@
+use crate::cli::FilterFlags;
+use crate::util::bulk_failure_summary;
+use boxlite::{BoxStatus, StopOptions};
 use clap::Args;
 
 #[derive(Args, Debug)]
 pub struct StopArgs {
+    /// Stop all running boxes
+    #[arg(short, long, conflicts_with = "targets")]
+    pub all: bool,
+
+    #[command(flatten)]
+    pub filter: FilterFlags,
+
     /// Name or ID of the box(es) to stop
-    #[arg(required = true, num_args = 1..)]
+    #[arg(
+        required_unless_present_any = ["all", "filter"],
+        conflicts_with = "filter",
+        num_args = 1..,
+        value_name = "BOX"
+    )]
     pub targets: Vec<String>,
 }
 
 pub async fn execute(args: StopArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    if args.all || args.filter.is_set() {
+        return stop_matching(args, global).await;
+    }
+
     let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
 
     let mut errors = Vec::new();
-    let mut success_count = 0;
+    let total = args.targets.len();
 
     for target in args.targets {
         // Get the box first
         let litebox = match runtime.get(&target).await? {
             Some(b) => b,
             None => {
-                eprintln!("Error: No such box: {}", target);
+                reporter.error(format!("No such box: {}", target));
                 errors.push(format!("{}: not found", target));
                 continue;
             }
         };
 
-        if let Err(e) = litebox.stop().await {
-            eprintln!("Error stopping box '{}': {}", target, e);
+        let spinner = reporter.spinner(format!("Stopping {}", target));
+        let result = litebox.stop().await;
+        drop(spinner);
+
+        if let Err(e) = result {
+            reporter.error_for(format!("stopping box '{}'", target), &e);
             errors.push(format!("{}: {}", target, e));
         } else {
-            println!("{}", target);
-            success_count += 1;
+            reporter.println(&target);
         }
     }
 
-    if !errors.is_empty() {
-        let error_summary = if success_count > 0 {
-            format!(
-                "Failed to stop {} of {} box(es)",
-                errors.len(),
-                errors.len() + success_count
-            )
-        } else {
-            format!("Failed to stop all {} box(es)", errors.len())
-        };
+    bulk_failure_summary("stop", &errors, total)
+}
+
+/// Stop the running boxes selected by `--all` or `--filter`.
+async fn stop_matching(args: StopArgs, global: &crate::cli::GlobalFlags) -> anyhow::Result<()> {
+    let runtime = global.create_runtime()?;
+    let reporter = global.reporter();
+    let filter = args.filter.to_filter(Some(BoxStatus::Running))?;
+
+    let spinner = reporter.spinner("Stopping matching boxes");
+    let results = runtime
+        .stop_matching(filter, StopOptions::default())
+        .await?;
+    drop(spinner);
 
-        anyhow::bail!("{}\nErrors:\n  {}", error_summary, errors.join("\n  "));
+    let mut errors = Vec::new();
+    for (id, result) in &results {
+        match result {
+            Ok(()) => reporter.println(id),
+            Err(e) => {
+                reporter.error_for(format!("stopping box '{}'", id), e);
+                errors.push(format!("{}: {}", id, e));
+            }
+        }
     }
-    Ok(())
+    reporter.status(format!(
+        "Stopped {} of {} box(es)",
+        results.len() - errors.len(),
+        results.len()
+    ));
+    bulk_failure_summary("stop", &errors, results.len())
 }
@@ -0,0 +1,37 @@
+//! Flush a running box's writes to its disk images.
+
+use crate::cli::GlobalFlags;
+use crate::error::no_such_box;
+use anyhow::Result;
+use clap::Args;
+use std::time::Duration;
+
+#[derive(Args, Debug)]
+pub struct SyncArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+
+    /// Longest the container filesystem freeze may take, in milliseconds
+    /// (at most 10000; 0 only flushes)
+    #[arg(long = "freeze-ms", default_value = "500")]
+    pub freeze_ms: u64,
+}
+
+pub async fn execute(args: SyncArgs, global: &GlobalFlags) -> Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let litebox = rt
+        .get(&args.target)
+        .await?
+        .ok_or_else(|| no_such_box(&args.target))?;
+
+    let frozen = litebox.sync(Duration::from_millis(args.freeze_ms)).await?;
+    reporter.println(format!(
+        "{}: synced, frozen for {:.1} ms",
+        args.target,
+        frozen.as_secs_f64() * 1000.0
+    ));
+    Ok(())
+}
@@ -0,0 +1,284 @@
+use crate::cli::GlobalFlags;
+use crate::commands::stats::format_bytes;
+use crate::formatter::{self, OutputFormat};
+use boxlite::{BoxliteError, CacheStats, CompactionEstimate, OrphanShim};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct SystemArgs {
+    #[command(subcommand)]
+    pub command: SystemCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SystemCommand {
+    /// Show disk usage of the image disk and guest rootfs caches
+    ///
+    /// With --reclaimable, also estimate what `boxlite compact` would free.
+    Df(DfArgs),
+    /// Kill leftover processes of boxes that no longer exist
+    Prune(PruneArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct DfArgs {
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
+    pub format: String,
+
+    /// Add a row estimating the space `boxlite compact` would reclaim from box disks
+    #[arg(long)]
+    pub reclaimable: bool,
+}
+
+#[derive(Args, Debug)]
+pub struct PruneArgs {
+    /// Kill shim processes whose box record or home directory is gone
+    #[arg(long, required = true)]
+    pub orphans: bool,
+
+    /// Seconds an orphaned shim gets to exit after SIGTERM before SIGKILL
+    #[arg(long, value_name = "SECS", default_value_t = 10)]
+    pub grace: u64,
+}
+
+#[derive(Tabled, Serialize)]
+struct CachePresenter {
+    #[tabled(rename = "CACHE")]
+    #[serde(rename = "Cache")]
+    cache: String,
+
+    #[tabled(rename = "ENTRIES")]
+    #[serde(rename = "Entries")]
+    entries: usize,
+
+    #[tabled(rename = "IN USE")]
+    #[serde(rename = "InUse")]
+    in_use: usize,
+
+    #[tabled(rename = "SIZE")]
+    #[serde(rename = "Size")]
+    size: String,
+
+    #[tabled(rename = "RECLAIMABLE")]
+    #[serde(rename = "Reclaimable")]
+    reclaimable: String,
+
+    #[tabled(rename = "LIMIT")]
+    #[serde(rename = "Limit")]
+    limit: String,
+
+    #[tabled(rename = "EVICTED")]
+    #[serde(rename = "Evicted")]
+    evicted: String,
+}
+
+impl CachePresenter {
+    fn new(cache: &str, stats: CacheStats) -> Self {
+        Self {
+            cache: cache.to_string(),
+            entries: stats.entries,
+            in_use: stats.referenced_entries,
+            size: format_bytes(Some(stats.size_bytes)),
+            reclaimable: format_bytes(Some(stats.reclaimable_bytes)),
+            limit: stats
+                .max_bytes
+                .map_or_else(|| "none".to_string(), |max| format_bytes(Some(max))),
+            evicted: format!(
+                "{} ({})",
+                stats.evicted_entries,
+                format_bytes(Some(stats.evicted_bytes))
+            ),
+        }
+    }
+
+    /// Box disks as one row: ENTRIES counts qcow2 boxes, IN USE the running ones.
+    fn box_disks(boxes: usize, running: usize, estimate: CompactionEstimate) -> Self {
+        Self {
+            cache: "box disks (compact)".to_string(),
+            entries: boxes,
+            in_use: running,
+            size: format_bytes(Some(estimate.allocated_bytes)),
+            reclaimable: format_bytes(Some(estimate.reclaimable_bytes)),
+            limit: "none".to_string(),
+            evicted: "-".to_string(),
+        }
+    }
+}
+
+pub async fn execute(args: SystemArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        SystemCommand::Df(args) => df(args, global).await,
+        SystemCommand::Prune(args) => prune(args, global).await,
+    }
+}
+
+/// Creating the runtime applies the cache caps, so EVICTED shows what this
+/// invocation evicted.
+async fn df(args: DfArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let usage = rt.cache_usage()?;
+
+    let mut presenters = vec![
+        CachePresenter::new("image disks", usage.image_disks),
+        CachePresenter::new("guest rootfs", usage.guest_rootfs),
+    ];
+    if args.reclaimable {
+        presenters.push(box_disks_row(&rt, global).await?);
+    }
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            print_caches(writer, data)?;
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Sum the compaction estimates of all qcow2 boxes. Boxes on other storage
+/// drivers are skipped; a box whose disks can't be read is reported and skipped.
+async fn box_disks_row(
+    rt: &boxlite::BoxliteRuntime,
+    global: &GlobalFlags,
+) -> anyhow::Result<CachePresenter> {
+    let reporter = global.reporter();
+    let spinner = reporter.spinner("Estimating reclaimable box disk space");
+
+    let mut boxes = 0;
+    let mut running = 0;
+    let mut total = CompactionEstimate::default();
+    for info in rt.list_info().await? {
+        let Some(litebox) = rt.get(info.id.as_str()).await? else {
+            continue;
+        };
+        match litebox.compaction_estimate().await {
+            Ok(estimate) => {
+                boxes += 1;
+                if info.status.is_running() {
+                    running += 1;
+                }
+                total.allocated_bytes += estimate.allocated_bytes;
+                total.reclaimable_bytes += estimate.reclaimable_bytes;
+            }
+            Err(BoxliteError::Unsupported(_)) => {}
+            Err(e) => spinner.suspend(|| reporter.warn(format!("box {}: {}", info.id, e))),
+        }
+    }
+    drop(spinner);
+
+    Ok(CachePresenter::box_disks(boxes, running, total))
+}
+
+fn print_caches(writer: &mut dyn std::io::Write, caches: &[CachePresenter]) -> anyhow::Result<()> {
+    let table = formatter::create_table(caches).to_string();
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+async fn prune(args: PruneArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    if args.orphans {
+        let spinner = reporter.spinner("Killing orphaned shims");
+        let killed = rt
+            .kill_orphans(std::time::Duration::from_secs(args.grace))
+            .await;
+        drop(spinner);
+
+        let killed = killed?;
+        for orphan in &killed {
+            reporter.println(orphan_line(orphan));
+        }
+        let reclaimed: u64 = killed.iter().map(|orphan| orphan.rss_bytes).sum();
+        reporter.println(format!(
+            "Killed {} orphaned shim(s), reclaimed {}",
+            killed.len(),
+            format_bytes(Some(reclaimed))
+        ));
+    }
+
+    Ok(())
+}
+
+/// One orphaned shim, e.g. `pid 4242, box 01HZ... (no box record), 512.0 MiB`.
+pub(crate) fn orphan_line(orphan: &OrphanShim) -> String {
+    let mut line = format!(
+        "pid {}, box {} ({}), {}",
+        orphan.pid,
+        orphan.box_id,
+        orphan.reason,
+        format_bytes(Some(orphan.rss_bytes))
+    );
+    if let Some(started_at) = &orphan.started_at {
+        line.push_str(&format!(", started {}", formatter::format_time(started_at)));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_presenter() {
+        let stats = CacheStats {
+            entries: 3,
+            size_bytes: 3 * 1024 * 1024,
+            referenced_entries: 1,
+            reclaimable_bytes: 2 * 1024 * 1024,
+            max_bytes: None,
+            evicted_entries: 2,
+            evicted_bytes: 1024,
+        };
+        let presenter = CachePresenter::new("image disks", stats);
+        assert_eq!(presenter.size, "3.0 MiB");
+        assert_eq!(presenter.reclaimable, "2.0 MiB");
+        assert_eq!(presenter.limit, "none");
+        assert_eq!(presenter.evicted, "2 (1.0 KiB)");
+    }
+
+    #[test]
+    fn test_box_disks_presenter() {
+        let estimate = CompactionEstimate {
+            allocated_bytes: 4 * 1024 * 1024,
+            reclaimable_bytes: 3 * 1024 * 1024,
+        };
+        let presenter = CachePresenter::box_disks(2, 1, estimate);
+        assert_eq!(presenter.entries, 2);
+        assert_eq!(presenter.in_use, 1);
+        assert_eq!(presenter.size, "4.0 MiB");
+        assert_eq!(presenter.reclaimable, "3.0 MiB");
+        assert_eq!(presenter.evicted, "-");
+    }
+
+    #[test]
+    fn test_orphan_line() {
+        let mut orphan = OrphanShim {
+            pid: 4242,
+            box_id: "01HZY0000000000000000000AB".to_string(),
+            started_at: None,
+            rss_bytes: 512 * 1024 * 1024,
+            reason: boxlite::OrphanReason::NoRecord,
+        };
+        assert_eq!(
+            orphan_line(&orphan),
+            "pid 4242, box 01HZY0000000000000000000AB (no box record), 512.0 MiB"
+        );
+
+        orphan.reason = boxlite::OrphanReason::HomeMissing;
+        orphan.started_at = chrono::DateTime::from_timestamp(0, 0);
+        assert_eq!(
+            orphan_line(&orphan),
+            "pid 4242, box 01HZY0000000000000000000AB (box home missing), 512.0 MiB, \
+             started 1970-01-01 00:00:00 UTC"
+        );
+    }
+}
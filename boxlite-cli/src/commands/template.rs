@@ -0,0 +1,208 @@
+use crate::cli::{GlobalFlags, PublishFlags, ResourceFlags, VolumeFlags};
+use crate::formatter::{self, OutputFormat};
+use boxlite::{BoxOptions, BoxTemplate, RootfsSpec};
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct TemplateArgs {
+    #[command(subcommand)]
+    pub command: TemplateCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TemplateCommand {
+    /// Register a template that `boxlite run --template` creates boxes from
+    Add(AddArgs),
+
+    /// List templates
+    #[command(visible_alias = "list")]
+    Ls(LsArgs),
+
+    /// Remove one or more templates
+    Rm(RmArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct AddArgs {
+    /// Template name
+    pub name: String,
+
+    /// Image boxes created from the template use
+    pub image: String,
+
+    /// Set environment variables
+    #[arg(short = 'e', long = "env")]
+    pub env: Vec<String>,
+
+    /// Working directory inside the box
+    #[arg(short = 'w', long = "workdir")]
+    pub workdir: Option<String>,
+
+    /// Set a label on boxes created from the template
+    #[arg(short = 'l', long = "label", value_name = "KEY=VALUE")]
+    pub labels: Vec<String>,
+
+    #[command(flatten)]
+    pub resource: ResourceFlags,
+
+    #[command(flatten)]
+    pub publish: PublishFlags,
+
+    #[command(flatten)]
+    pub volume: VolumeFlags,
+}
+
+#[derive(Args, Debug)]
+pub struct LsArgs {
+    /// Only show names
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct RmArgs {
+    /// Name of the template(s)
+    #[arg(required = true, num_args = 1..)]
+    pub names: Vec<String>,
+}
+
+#[derive(Tabled, Serialize)]
+struct TemplatePresenter {
+    #[tabled(rename = "NAME")]
+    #[serde(rename = "Name")]
+    name: String,
+
+    #[tabled(rename = "IMAGE")]
+    #[serde(rename = "Image")]
+    image: String,
+
+    #[tabled(rename = "CPUS")]
+    #[serde(rename = "Cpus")]
+    cpus: String,
+
+    #[tabled(rename = "MEMORY")]
+    #[serde(rename = "MemoryMiB")]
+    memory: String,
+
+    #[tabled(rename = "CREATED")]
+    #[serde(rename = "CreatedAt")]
+    created: String,
+}
+
+impl From<BoxTemplate> for TemplatePresenter {
+    fn from(template: BoxTemplate) -> Self {
+        let (RootfsSpec::Image(image) | RootfsSpec::RootfsPath(image)) = template.options.rootfs;
+        Self {
+            name: template.name,
+            image,
+            cpus: template
+                .options
+                .cpus
+                .map_or_else(|| "default".to_string(), |cpus| cpus.to_string()),
+            memory: template
+                .options
+                .memory_mib
+                .map_or_else(|| "default".to_string(), |mib| format!("{} MiB", mib)),
+            created: formatter::format_time(&template.created_at),
+        }
+    }
+}
+
+pub async fn execute(args: TemplateArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        TemplateCommand::Add(args) => add(args, global).await,
+        TemplateCommand::Ls(args) => ls(args, global).await,
+        TemplateCommand::Rm(args) => rm(args, global).await,
+    }
+}
+
+async fn add(args: AddArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let options = args.to_box_options(global)?;
+    let template = rt.register_template(&args.name, options).await?;
+    global.reporter().println(&template.name);
+    Ok(())
+}
+
+impl AddArgs {
+    fn to_box_options(&self, global: &GlobalFlags) -> anyhow::Result<BoxOptions> {
+        let mut builder = BoxOptions::builder().image(self.image.as_str());
+        builder = self.resource.apply_to(builder);
+        builder = self.publish.apply_to(builder)?;
+        builder = self.volume.apply_to(builder, global.home.as_deref())?;
+        if let Some(workdir) = &self.workdir {
+            builder = builder.working_dir(workdir.as_str());
+        }
+        builder = crate::cli::apply_env_vars(&self.env, builder);
+        for label in &self.labels {
+            let (key, value) = label.split_once('=').unwrap_or((label, ""));
+            builder = builder.label(key, value);
+        }
+        // `run --template` decides per box whether it is removed on exit
+        Ok(builder.auto_remove(false).build()?)
+    }
+}
+
+async fn ls(args: LsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let templates = rt.list_templates().await?;
+
+    if args.quiet {
+        let reporter = global.reporter();
+        for template in templates {
+            reporter.println(&template.name);
+        }
+        return Ok(());
+    }
+
+    let presenters: Vec<TemplatePresenter> =
+        templates.into_iter().map(TemplatePresenter::from).collect();
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            print_templates(writer, data)?;
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+fn print_templates(
+    writer: &mut dyn std::io::Write,
+    templates: &[TemplatePresenter],
+) -> anyhow::Result<()> {
+    let table = formatter::create_table(templates).to_string();
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+async fn rm(args: RmArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let mut failed = false;
+    for name in args.names {
+        match rt.remove_template(&name).await {
+            Ok(()) => reporter.println(&name),
+            Err(e) => {
+                reporter.error_for(format!("removing template '{}'", name), &e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("Some templates could not be removed");
+    }
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use crate::cli::GlobalFlags;
+use crate::formatter::{self, OutputFormat};
+use boxlite::TrashedBox;
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use tabled::Tabled;
+
+#[derive(Args, Debug)]
+pub struct TrashArgs {
+    #[command(subcommand)]
+    pub command: TrashCommand,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum TrashCommand {
+    /// List removed boxes that can still be restored
+    #[command(visible_alias = "list")]
+    Ls(LsArgs),
+
+    /// Restore one or more boxes from the trash
+    Restore(TargetsArgs),
+
+    /// Permanently delete one or more boxes from the trash
+    Purge(PurgeArgs),
+}
+
+#[derive(Args, Debug)]
+pub struct LsArgs {
+    /// Only show IDs
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Output format (table, json, yaml)
+    #[arg(long, default_value = "table", value_parser = formatter::FORMATS, ignore_case = true)]
+    pub format: String,
+}
+
+#[derive(Args, Debug)]
+pub struct TargetsArgs {
+    /// Name or ID of the trashed box(es)
+    #[arg(required = true, num_args = 1..)]
+    pub targets: Vec<String>,
+}
+
+#[derive(Args, Debug)]
+pub struct PurgeArgs {
+    /// Purge every box in the trash
+    #[arg(short, long)]
+    pub all: bool,
+
+    /// Do not ask for confirmation with --all
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Name or ID of the trashed box(es)
+    #[arg(required_unless_present = "all", num_args = 1..)]
+    pub targets: Vec<String>,
+}
+
+#[derive(Tabled, Serialize)]
+struct TrashedPresenter {
+    #[tabled(rename = "ID")]
+    #[serde(rename = "ID")]
+    id: String,
+
+    #[tabled(rename = "IMAGE")]
+    #[serde(rename = "Image")]
+    image: String,
+
+    #[tabled(rename = "REMOVED")]
+    #[serde(rename = "TrashedAt")]
+    trashed: String,
+
+    #[tabled(rename = "EXPIRES")]
+    #[serde(rename = "ExpiresAt")]
+    expires: String,
+
+    #[tabled(rename = "NAMES")]
+    #[serde(rename = "Names")]
+    names: String,
+}
+
+impl From<TrashedBox> for TrashedPresenter {
+    fn from(trashed: TrashedBox) -> Self {
+        Self {
+            id: trashed.id.to_string(),
+            image: trashed.image,
+            trashed: formatter::format_time(&trashed.trashed_at),
+            expires: trashed
+                .expires_at
+                .map(|t| formatter::format_time(&t))
+                .unwrap_or_else(|| "never".to_string()),
+            names: trashed.name.unwrap_or_default(),
+        }
+    }
+}
+
+pub async fn execute(args: TrashArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    match args.command {
+        TrashCommand::Ls(args) => ls(args, global).await,
+        TrashCommand::Restore(args) => restore(args, global).await,
+        TrashCommand::Purge(args) => purge(args, global).await,
+    }
+}
+
+async fn ls(args: LsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let trashed = rt.list_trashed().await?;
+
+    if args.quiet {
+        let reporter = global.reporter();
+        for entry in trashed {
+            reporter.println(&entry.id);
+        }
+        return Ok(());
+    }
+
+    let presenters: Vec<TrashedPresenter> =
+        trashed.into_iter().map(TrashedPresenter::from).collect();
+    let format = OutputFormat::from_str(&args.format)?;
+    formatter::print_output(
+        &mut std::io::stdout().lock(),
+        &presenters,
+        format,
+        |writer, data| {
+            print_trashed(writer, data)?;
+            Ok(())
+        },
+    )?;
+
+    Ok(())
+}
+
+fn print_trashed(
+    writer: &mut dyn std::io::Write,
+    trashed: &[TrashedPresenter],
+) -> anyhow::Result<()> {
+    let table = formatter::create_table(trashed).to_string();
+    writeln!(writer, "{}", table)?;
+    Ok(())
+}
+
+async fn restore(args: TargetsArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    let mut failed = false;
+    for target in args.targets {
+        match rt.restore_trashed(&target).await {
+            Ok(litebox) => reporter.println(litebox.id()),
+            Err(e) => {
+                reporter.error_for(format!("restoring box '{}'", target), &e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("Some boxes could not be restored");
+    }
+    Ok(())
+}
+
+async fn purge(args: PurgeArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let rt = global.create_runtime()?;
+    let reporter = global.reporter();
+
+    if args.all
+        && !args.force
+        && !reporter
+            .confirm("WARNING! This will permanently delete all trashed boxes. Are you sure?")?
+    {
+        return Ok(());
+    }
+
+    let targets = if args.all {
+        rt.list_trashed()
+            .await?
+            .into_iter()
+            .map(|entry| entry.id.to_string())
+            .collect()
+    } else {
+        args.targets
+    };
+
+    let mut failed = false;
+    for target in targets {
+        let spinner = reporter.spinner(format!("Purging {}", target));
+        let result = rt.purge_trashed(&target).await;
+        drop(spinner);
+
+        match result {
+            Ok(()) => reporter.println(&target),
+            Err(e) => {
+                reporter.error_for(format!("purging box '{}'", target), &e);
+                failed = true;
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("Some boxes could not be purged");
+    }
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Unmount a box mounted with `boxlite mount`.
+
+use std::time::{Duration, Instant};
+
+use crate::cli::GlobalFlags;
+use crate::commands::mount::{MountRecord, record_path};
+use crate::error::no_such_box;
+use anyhow::{Context, Result};
+use boxlite::BoxliteError;
+use clap::Args;
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+
+/// How long to wait for the mount process to unmount and exit.
+const UNMOUNT_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Args, Debug)]
+pub struct UmountArgs {
+    /// Box ID or name
+    #[arg(index = 1, value_name = "BOX")]
+    pub target: String,
+}
+
+pub async fn execute(args: UmountArgs, global: &GlobalFlags) -> Result<()> {
+    let home = global.resolve_runtime_options()?.home_dir;
+    let box_id = {
+        let rt = global.create_runtime()?;
+        let litebox = rt
+            .get(&args.target)
+            .await?
+            .ok_or_else(|| no_such_box(&args.target))?;
+        litebox.id().to_string()
+    };
+
+    let path = record_path(&home, &box_id);
+    let record: MountRecord = match std::fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .with_context(|| format!("Invalid mount record {}", path.display()))?,
+        Err(_) => {
+            return Err(BoxliteError::InvalidState(format!(
+                "box '{}' is not mounted",
+                args.target
+            ))
+            .into());
+        }
+    };
+
+    // The mount process unmounts on SIGTERM and removes its record
+    if kill(Pid::from_raw(record.pid as i32), Signal::SIGTERM).is_err() {
+        let _ = std::fs::remove_file(&path);
+        return Err(BoxliteError::InvalidState(format!(
+            "the process that mounted box '{}' at {} is gone; unmount it with `umount {}`",
+            args.target,
+            record.mountpoint.display(),
+            record.mountpoint.display()
+        ))
+        .into());
+    }
+
+    let deadline = Instant::now() + UNMOUNT_TIMEOUT;
+    while path.exists() {
+        if Instant::now() >= deadline {
+            return Err(BoxliteError::Timeout(format!(
+                "box '{}' is still mounted at {} after {:?}; is the mount point in use?",
+                args.target,
+                record.mountpoint.display(),
+                UNMOUNT_TIMEOUT
+            ))
+            .into());
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    global.reporter().println(record.mountpoint.display());
+    Ok(())
+}
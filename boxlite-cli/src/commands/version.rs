@@ -0,0 +1,77 @@
+//! Show the boxlite version and, with `--verbose`, the engine component matrix.
+
+use crate::cli::GlobalFlags;
+use crate::formatter;
+use boxlite::VersionInfo;
+use clap::{Args, ValueEnum};
+
+/// Show version information.
+#[derive(Args, Debug)]
+pub struct VersionArgs {
+    /// Include engine components (libkrun, guest and shim binaries, bwrap, gvproxy)
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Output format (text, json, yaml). json and yaml always include all components.
+    #[arg(long, default_value_t = VersionFormat::Text, value_enum)]
+    pub format: VersionFormat,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum VersionFormat {
+    #[default]
+    Text,
+    Json,
+    Yaml,
+}
+
+pub async fn execute(args: VersionArgs, global: &GlobalFlags) -> anyhow::Result<()> {
+    let reporter = global.reporter();
+
+    if !args.verbose && args.format == VersionFormat::Text {
+        reporter.println(format!("boxlite {}", boxlite::VERSION));
+        return Ok(());
+    }
+
+    let rt = global.create_runtime()?;
+    let info = rt.version_info().await?;
+
+    match args.format {
+        VersionFormat::Text => {
+            for line in version_lines(&info) {
+                reporter.println(line);
+            }
+        }
+        VersionFormat::Json => reporter.println(formatter::format_json(&info)?),
+        VersionFormat::Yaml => reporter.println(formatter::format_yaml(&info)?),
+    }
+    Ok(())
+}
+
+/// Render `info` as aligned `Component: value` lines.
+pub fn version_lines(info: &VersionInfo) -> Vec<String> {
+    let unknown = || "unknown".to_string();
+    let shim_path = info.shim_path.as_ref().map(|p| p.display().to_string());
+
+    [
+        ("Version", Some(info.version.clone())),
+        ("Git commit", info.git_commit.clone()),
+        ("OS/Arch", Some(format!("{}/{}", info.os, info.arch))),
+        ("libkrun", info.libkrun.clone()),
+        ("libkrunfw", info.libkrunfw.clone()),
+        ("Guest hash", info.guest_hash.clone()),
+        ("Shim", shim_path),
+        ("Shim hash", info.shim_hash.clone()),
+        ("bwrap", info.bwrap.clone()),
+        ("gvproxy", info.gvproxy.clone()),
+    ]
+    .into_iter()
+    .map(|(label, value)| {
+        format!(
+            "{:<12}{}",
+            format!("{}:", label),
+            value.unwrap_or_else(unknown)
+        )
+    })
+    .collect()
+}
@@ -0,0 +1,500 @@
+//! Dynamic box-name completion layered on the clap_complete scripts.
+//!
+//! clap_complete only knows what the clap definitions declare, so box names
+//! can't come from it. Each shell script gets a wrapper that finds the
+//! subcommand and positional being completed and, where a box goes, asks
+//! `boxlite ps -a -q --format names`; anywhere else it defers to the
+//! generated completion.
+//!
+//! Box positions come from the clap definitions: positionals whose value
+//! name is `BOX` take a box name, and those starting with `[BOX:]` (as in
+//! `boxlite cp`) a box name followed by `:`.
+
+use crate::cli::Shell;
+use clap::Command;
+use std::fmt::Write as _;
+
+/// Value name of positionals that take a box name.
+const BOX: &str = "BOX";
+
+/// Value name prefix of positionals that take a host path or `BOX:PATH`.
+const BOX_PATH_PREFIX: &str = "[BOX:]";
+
+/// What the positional being completed takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BoxKind {
+    /// A box name
+    Name,
+    /// A host path or `BOX:PATH`
+    Path,
+}
+
+impl BoxKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            BoxKind::Name => "name",
+            BoxKind::Path => "path",
+        }
+    }
+}
+
+/// Positionals of a subcommand that take a box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BoxSlot {
+    /// 1-based index of the positional
+    index: usize,
+    /// Whether every positional from `index` on takes one
+    repeated: bool,
+    kind: BoxKind,
+}
+
+/// Completion facts about one (sub)command.
+#[derive(Debug)]
+struct Entry {
+    /// Space-separated path from the root, once per alias combination;
+    /// empty for the root
+    paths: Vec<String>,
+    /// Whether the first positional names a nested subcommand
+    group: bool,
+    /// Options that take a value, so the word after them isn't positional
+    value_options: Vec<String>,
+    slots: Vec<BoxSlot>,
+}
+
+/// Append box-name completion to the clap_complete `script` for `shell`.
+pub fn with_box_names(shell: &Shell, cmd: &Command, name: &str, script: String) -> String {
+    let entries = entries(cmd);
+    match shell {
+        Shell::Bash => script + &bash(&entries, name),
+        Shell::Zsh => zsh(&entries, name, script),
+        Shell::Fish => script + &fish(&entries, name),
+    }
+}
+
+/// Entries for `cmd` and its visible subcommands; `cmd` must be built.
+fn entries(cmd: &Command) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    collect(cmd, vec![String::new()], &mut entries);
+    entries
+}
+
+fn collect(cmd: &Command, paths: Vec<String>, entries: &mut Vec<Entry>) {
+    let value_options = cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_positional() && arg.get_action().takes_values())
+        .flat_map(|arg| {
+            let shorts = arg.get_short_and_visible_aliases().unwrap_or_default();
+            let longs = arg.get_long_and_visible_aliases().unwrap_or_default();
+            shorts
+                .into_iter()
+                .map(|s| format!("-{s}"))
+                .chain(longs.into_iter().map(|l| format!("--{l}")))
+        })
+        .collect();
+    let slots = cmd
+        .get_positionals()
+        .filter_map(|arg| {
+            let value_name = arg.get_value_names()?.first()?.as_str();
+            let kind = if value_name == BOX {
+                BoxKind::Name
+            } else if value_name.starts_with(BOX_PATH_PREFIX) {
+                BoxKind::Path
+            } else {
+                return None;
+            };
+            Some(BoxSlot {
+                index: arg.get_index()?,
+                repeated: arg.get_num_args()?.max_values() > 1,
+                kind,
+            })
+        })
+        .collect();
+
+    let subcommands: Vec<&Command> = cmd
+        .get_subcommands()
+        .filter(|sub| !sub.is_hide_set())
+        .collect();
+    entries.push(Entry {
+        paths: paths.clone(),
+        group: !subcommands.is_empty(),
+        value_options,
+        slots,
+    });
+
+    for sub in subcommands {
+        let sub_paths = paths
+            .iter()
+            .flat_map(|path| {
+                sub.get_name_and_visible_aliases()
+                    .into_iter()
+                    .map(move |name| match path.as_str() {
+                        "" => name.to_string(),
+                        _ => format!("{path} {name}"),
+                    })
+            })
+            .collect();
+        collect(sub, sub_paths, entries);
+    }
+}
+
+/// `case` label matching any of `paths` in bash and zsh.
+fn sh_label(paths: &[String]) -> String {
+    let quoted: Vec<String> = paths.iter().map(|p| format!("\"{p}\"")).collect();
+    quoted.join("|")
+}
+
+/// Shell functions shared by bash and zsh, which agree on this syntax.
+fn sh_helpers(entries: &[Entry], name: &str) -> String {
+    let fn_name = name.replace('-', "_");
+    let mut groups = String::new();
+    let mut values = String::new();
+    let mut slots = String::new();
+    for entry in entries {
+        let label = sh_label(&entry.paths);
+        if entry.group {
+            let _ = writeln!(groups, "        {label}) return 0 ;;");
+        }
+        if !entry.value_options.is_empty() {
+            let _ = writeln!(
+                values,
+                "        {label})\n            case \"$2\" in\n                {}) return 0 ;;\n            esac\n            ;;",
+                entry.value_options.join("|")
+            );
+        }
+        if !entry.slots.is_empty() {
+            let _ = writeln!(slots, "        {label})");
+            for slot in &entry.slots {
+                let test = if slot.repeated { "-ge" } else { "-eq" };
+                let _ = writeln!(
+                    slots,
+                    "            [[ $2 {test} {} ]] && echo {}",
+                    slot.index,
+                    slot.kind.as_str()
+                );
+            }
+            let _ = writeln!(slots, "            ;;");
+        }
+    }
+
+    format!(
+        r#"
+# Box names, from the home directory given on the command line if any
+__{fn_name}_boxes() {{
+    command {name} "$@" ps -a -q --format names 2>/dev/null
+}}
+
+# Whether the subcommand path $1 takes a nested subcommand
+__{fn_name}_group() {{
+    case "$1" in
+{groups}    esac
+    return 1
+}}
+
+# Whether option $2 of the subcommand path $1 takes a value
+__{fn_name}_takes_value() {{
+    case "$1" in
+{values}    esac
+    return 1
+}}
+
+# Print "name" or "path" if positional $2 of the subcommand path $1 takes a box
+__{fn_name}_box_slot() {{
+    case "$1" in
+{slots}    esac
+}}
+"#
+    )
+}
+
+/// The loop both bash and zsh run over the words before the cursor, setting
+/// `sub`, `pos`, `dashdash` and `home`.
+fn sh_scan(fn_name: &str, words: &str, first: usize, current: &str) -> String {
+    format!(
+        r#"    local sub="" pos=0 skip=0 dashdash=0 joined=0 prev="" word i
+    local -a home=()
+    for ((i = {first}; i < {current}; i++)); do
+        word="{words}[i]}}"
+        if ((skip)); then
+            # bash splits --opt=value into three words
+            if [[ $word != "=" ]]; then
+                skip=0
+                [[ $prev == --home ]] && home=(--home "$word")
+            fi
+            continue
+        fi
+        if ((joined)); then
+            joined=0
+            continue
+        fi
+        case "$word" in
+            --) dashdash=1 ;;
+            # bash splits BOX:PATH into three words
+            :) joined=1 ;;
+            -*)
+                prev="$word"
+                __{fn_name}_takes_value "$sub" "$word" && skip=1
+                ;;
+            *)
+                if [[ -z $sub ]] || __{fn_name}_group "$sub"; then
+                    sub="${{sub:+$sub }}$word"
+                else
+                    pos=$((pos + 1))
+                fi
+                ;;
+        esac
+    done
+"#
+    )
+}
+
+fn bash(entries: &[Entry], name: &str) -> String {
+    let fn_name = name.replace('-', "_");
+    let scan = sh_scan(&fn_name, "${COMP_WORDS", 1, "COMP_CWORD");
+    format!(
+        r#"{helpers}
+_{fn_name}_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}"
+{scan}
+    if ((!dashdash)) && [[ $cur != -* && $cur != : && ${{COMP_WORDS[COMP_CWORD-1]}} != : ]]; then
+        case "$(__{fn_name}_box_slot "$sub" $((pos + 1)))" in
+            name)
+                COMPREPLY=($(compgen -W "$(__{fn_name}_boxes "${{home[@]}}")" -- "$cur"))
+                return 0
+                ;;
+            path)
+                COMPREPLY=($(compgen -S : -W "$(__{fn_name}_boxes "${{home[@]}}")" -- "$cur") $(compgen -f -- "$cur"))
+                compopt -o nospace 2>/dev/null
+                return 0
+                ;;
+        esac
+    fi
+    _{fn_name} "$@"
+}}
+
+if [[ "${{BASH_VERSINFO[0]}}" -eq 4 && "${{BASH_VERSINFO[1]}}" -ge 4 || "${{BASH_VERSINFO[0]}}" -gt 4 ]]; then
+    complete -F _{fn_name}_dynamic -o nosort -o bashdefault -o default {name}
+else
+    complete -F _{fn_name}_dynamic -o bashdefault -o default {name}
+fi
+"#,
+        helpers = sh_helpers(entries, name),
+    )
+}
+
+/// The zsh script registers `_<name>` itself, possibly by calling it right
+/// away when autoloaded, so the wrapper takes that name before then.
+fn zsh(entries: &[Entry], name: &str, script: String) -> String {
+    let fn_name = name.replace('-', "_");
+    let scan = sh_scan(&fn_name, "${words", 2, "CURRENT");
+    let wrapper = format!(
+        r#"{helpers}
+_{fn_name}_dynamic() {{
+    local cur="${{words[CURRENT]}}"
+    local -a boxes
+{scan}
+    if ((!dashdash)) && [[ $cur != -* && $cur != *:* ]]; then
+        case "$(__{fn_name}_box_slot "$sub" $((pos + 1)))" in
+            name)
+                boxes=(${{(f)"$(__{fn_name}_boxes "${{home[@]}}")"}})
+                compadd -a boxes
+                return
+                ;;
+            path)
+                boxes=(${{(f)"$(__{fn_name}_boxes "${{home[@]}}")"}})
+                compadd -S : -a boxes
+                _files
+                return
+                ;;
+        esac
+    fi
+    _{fn_name}_static "$@"
+}}
+
+# Keep the generated completion as _{fn_name}_static, behind the wrapper
+functions[_{fn_name}_static]=$functions[_{fn_name}]
+_{fn_name}() {{
+    _{fn_name}_dynamic "$@"
+}}
+
+"#,
+        helpers = sh_helpers(entries, name),
+    );
+
+    let register = format!("if [ \"$funcstack[1]\" = \"_{fn_name}\" ]; then");
+    match script.rfind(&register) {
+        Some(at) => {
+            let (generated, registration) = script.split_at(at);
+            format!("{generated}{wrapper}{registration}")
+        }
+        None => script + &wrapper,
+    }
+}
+
+fn fish(entries: &[Entry], name: &str) -> String {
+    let fn_name = name.replace('-', "_");
+    let label = |paths: &[String]| {
+        let quoted: Vec<String> = paths.iter().map(|p| format!("'{p}'")).collect();
+        quoted.join(" ")
+    };
+    let mut groups = String::new();
+    let mut values = String::new();
+    let mut slots = String::new();
+    for entry in entries {
+        if entry.group {
+            let _ = writeln!(
+                groups,
+                "        case {}\n            return 0",
+                label(&entry.paths)
+            );
+        }
+        if !entry.value_options.is_empty() {
+            let _ = writeln!(
+                values,
+                "        case {}\n            contains -- $opt {}; and return 0",
+                label(&entry.paths),
+                entry.value_options.join(" ")
+            );
+        }
+        if !entry.slots.is_empty() {
+            let _ = writeln!(slots, "        case {}", label(&entry.paths));
+            for slot in &entry.slots {
+                let test = if slot.repeated { "-ge" } else { "-eq" };
+                let _ = writeln!(
+                    slots,
+                    "            test $pos {test} {}; and echo {}",
+                    slot.index,
+                    slot.kind.as_str()
+                );
+            }
+        }
+    }
+
+    format!(
+        r#"
+function __{fn_name}_group --argument-names sub
+    switch "$sub"
+{groups}    end
+    return 1
+end
+
+function __{fn_name}_takes_value --argument-names sub opt
+    switch "$sub"
+{values}    end
+    return 1
+end
+
+function __{fn_name}_slot_of --argument-names sub pos
+    switch "$sub"
+{slots}    end
+end
+
+# Whether the token being completed takes a box of kind $argv[1]
+function __{fn_name}_box_slot
+    set -l sub ''
+    set -l pos 0
+    set -l skip 0
+    set -l words (commandline -opc)
+    set -e words[1]
+    for word in $words
+        if test $skip -eq 1
+            set skip 0
+            continue
+        end
+        switch $word
+            case --
+                return 1
+            case '-*'
+                __{fn_name}_takes_value "$sub" (string split -m 1 = -- $word)[1]
+                and not string match -q -- '*=*' $word
+                and set skip 1
+            case '*'
+                if test -z "$sub"; or __{fn_name}_group "$sub"
+                    set sub (string trim -- "$sub $word")
+                else
+                    set pos (math $pos + 1)
+                end
+        end
+    end
+    set -l slot (__{fn_name}_slot_of "$sub" (math $pos + 1))
+    test "$slot" = "$argv[1]"
+end
+
+# Box names, from the home directory given on the command line if any
+function __{fn_name}_boxes
+    set -l tokens (commandline -opc)
+    set -l home
+    set -l at (contains -i -- --home $tokens)
+    and set home --home $tokens[(math $at + 1)]
+    command {name} $home ps -a -q --format names 2>/dev/null
+end
+
+complete -c {name} -n '__{fn_name}_box_slot name' -f -a '(__{fn_name}_boxes)'
+complete -c {name} -n '__{fn_name}_box_slot path; and not string match -q -- "*:*" (commandline -ct)' -a '(__{fn_name}_boxes | string replace -r \$ :)'
+"#
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    fn built() -> Command {
+        let mut cmd = Cli::command();
+        cmd.build();
+        cmd
+    }
+
+    fn entry<'a>(entries: &'a [Entry], path: &str) -> &'a Entry {
+        entries
+            .iter()
+            .find(|e| e.paths.iter().any(|p| p == path))
+            .unwrap_or_else(|| panic!("no entry for {path:?}"))
+    }
+
+    #[test]
+    fn test_box_slots_follow_value_names() {
+        let entries = entries(&built());
+
+        let exec = entry(&entries, "exec");
+        assert_eq!(
+            exec.slots,
+            [BoxSlot {
+                index: 1,
+                repeated: false,
+                kind: BoxKind::Name
+            }]
+        );
+        let stop = entry(&entries, "stop");
+        assert!(stop.slots[0].repeated);
+        let cp = entry(&entries, "cp");
+        assert_eq!(cp.slots.len(), 2);
+        assert!(cp.slots.iter().all(|s| s.kind == BoxKind::Path));
+        assert!(entry(&entries, "images").slots.is_empty());
+    }
+
+    #[test]
+    fn test_entries_cover_aliases_groups_and_value_options() {
+        let entries = entries(&built());
+
+        assert!(std::ptr::eq(entry(&entries, "ps"), entry(&entries, "list")));
+        assert!(entry(&entries, "snapshot").group);
+        assert!(!entry(&entries, "snapshot create").slots.is_empty());
+
+        let exec = entry(&entries, "exec");
+        assert!(exec.value_options.contains(&"-e".to_string()));
+        // Global options are accepted after the subcommand too
+        assert!(exec.value_options.contains(&"--home".to_string()));
+        assert!(!exec.value_options.contains(&"--detach".to_string()));
+    }
+
+    #[test]
+    fn test_zsh_wrapper_precedes_registration() {
+        let script = "_boxlite() {}\nif [ \"$funcstack[1]\" = \"_boxlite\" ]; then\nfi\n";
+        let out = with_box_names(&Shell::Zsh, &built(), "boxlite", script.to_string());
+        let wrapper = out.find("functions[_boxlite_static]").unwrap();
+        let register = out.find("if [ \"$funcstack[1]\"").unwrap();
+        assert!(wrapper < register);
+    }
+}
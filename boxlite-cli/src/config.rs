@@ -61,6 +61,55 @@ mod tests {
         assert_eq!(config.image_registries, vec!["docker.io"]);
     }
 
+    #[test]
+    fn test_load_config_with_registry_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{
+            "image_registries": ["mirror.internal:5000", "docker.io"],
+            "registry_settings": {
+                "mirror.internal:5000": {
+                    "timeout": {"secs": 5, "nanos": 0},
+                    "ca_cert": "/etc/boxlite/mirror-ca.pem"
+                },
+                "localhost:5000": {"insecure": true}
+            }
+        }"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        let mirror = &config.registry_settings["mirror.internal:5000"];
+        assert_eq!(mirror.timeout, Some(std::time::Duration::from_secs(5)));
+        assert_eq!(
+            mirror.ca_cert,
+            Some(PathBuf::from("/etc/boxlite/mirror-ca.pem"))
+        );
+        assert!(!mirror.insecure);
+        assert!(config.registry_settings["localhost:5000"].insecure);
+    }
+
+    #[test]
+    fn test_load_config_with_templates() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("config.json");
+        let config_content = r#"{
+            "templates": [{
+                "name": "web",
+                "options": {
+                    "rootfs": {"Image": "nginx:latest"},
+                    "memory_mib": 512,
+                    "env": [["TIER", "web"]]
+                }
+            }]
+        }"#;
+        fs::write(&config_path, config_content).unwrap();
+
+        let config = load_config(&config_path).unwrap();
+        assert_eq!(config.templates.len(), 1);
+        assert_eq!(config.templates[0].name, "web");
+        assert_eq!(config.templates[0].options.memory_mib, Some(512));
+    }
+
     #[test]
     fn test_load_empty_config() {
         let temp_dir = TempDir::new().unwrap();
@@ -0,0 +1,222 @@
+//! Exit codes and error output of failed commands.
+//!
+//! A command that fails exits with a code from [`exit_code`], chosen from
+//! the [`BoxliteError`] behind the failure by [`ErrorReport::new`]. With
+//! `--error-format json` the error is also written to stderr as one JSON
+//! object, so scripts don't have to match on messages.
+
+use boxlite::BoxliteError;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Stable exit codes. `exec` and `run` exit with the command's own status
+/// once it has started.
+pub mod exit_code {
+    /// Any other failure, e.g. some boxes of a multi-box command failed
+    pub const FAILURE: i32 = 1;
+    /// Invalid arguments, options or config file
+    pub const USAGE: i32 = 2;
+    /// No such box, image, snapshot or template
+    pub const NOT_FOUND: i32 = 3;
+    /// A box or other resource with that name already exists
+    pub const ALREADY_EXISTS: i32 = 4;
+    /// Wrong state for the operation: box stopped or running, busy, or the
+    /// home directory in use by another process
+    pub const INVALID_STATE: i32 = 5;
+    /// Network failure, image pull failure or offline mode
+    pub const NETWORK: i32 = 6;
+    /// The operation timed out
+    pub const TIMEOUT: i32 = 7;
+    /// Internal error in BoxLite, the VM engine or the guest
+    pub const INTERNAL: i32 = 125;
+}
+
+/// Long help section listing the exit codes.
+pub const EXIT_CODES_HELP: &str = "\
+Exit codes:
+  0    Success
+  1    Other failure (e.g. some boxes of a multi-box command failed)
+  2    Invalid arguments, options or config file
+  3    Not found (box, image, snapshot, template)
+  4    Already exists
+  5    Invalid state (box stopped/running, busy, home directory in use)
+  6    Network or image pull failure, offline mode
+  7    Timed out
+  125  Internal error
+`exec` and `run` exit with the command's status once it has started.
+With --error-format json, errors are written to stderr as one JSON object
+with code, variant, message and, when known, box_id.";
+
+/// How a failed command reports its error on stderr.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum ErrorFormat {
+    /// `Error: <message>`
+    #[default]
+    Text,
+    /// One JSON object: code, variant, message, box_id
+    Json,
+}
+
+/// A failed command's error, as written with `--error-format json`.
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct ErrorReport {
+    /// Process exit code
+    pub code: i32,
+    /// `BoxliteError` variant, `Usage` or `Other`
+    pub variant: &'static str,
+    pub message: String,
+    /// Box the error is about, for "not found" errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub box_id: Option<String>,
+}
+
+impl ErrorReport {
+    /// Classify `error` by the first [`BoxliteError`] in its chain.
+    pub fn new(error: &anyhow::Error) -> Self {
+        let message = error.to_string();
+        let Some(boxlite) = error.chain().find_map(|e| e.downcast_ref::<BoxliteError>()) else {
+            return Self {
+                code: exit_code::FAILURE,
+                variant: "Other",
+                message,
+                box_id: None,
+            };
+        };
+        let (code, variant) = classify(boxlite);
+        let box_id = match boxlite {
+            BoxliteError::NotFound(target) if error.is::<NoSuchBox>() => Some(target.clone()),
+            _ => None,
+        };
+        Self {
+            code,
+            variant,
+            message,
+            box_id,
+        }
+    }
+
+    /// A usage error found by the CLI itself.
+    pub fn usage(message: impl Into<String>) -> Self {
+        Self {
+            code: exit_code::USAGE,
+            variant: "Usage",
+            message: message.into(),
+            box_id: None,
+        }
+    }
+
+    /// Write the report to stderr as one line of JSON.
+    pub fn print_json(&self) {
+        match serde_json::to_string(self) {
+            Ok(json) => eprintln!("{json}"),
+            Err(_) => eprintln!("Error: {}", self.message),
+        }
+    }
+}
+
+/// Exit code and variant name for a `BoxliteError`.
+fn classify(error: &BoxliteError) -> (i32, &'static str) {
+    use exit_code::*;
+
+    match error {
+        BoxliteError::InvalidArgument(_) => (USAGE, "InvalidArgument"),
+        BoxliteError::Config(_) => (USAGE, "Config"),
+        BoxliteError::NotFound(_) => (NOT_FOUND, "NotFound"),
+        BoxliteError::AlreadyExists(_) => (ALREADY_EXISTS, "AlreadyExists"),
+        BoxliteError::InvalidState(_) => (INVALID_STATE, "InvalidState"),
+        BoxliteError::Busy { .. } => (INVALID_STATE, "Busy"),
+        BoxliteError::ConfigDrift { .. } => (INVALID_STATE, "ConfigDrift"),
+        BoxliteError::Stopped(_) => (INVALID_STATE, "Stopped"),
+        BoxliteError::ShuttingDown(_) => (INVALID_STATE, "ShuttingDown"),
+        BoxliteError::Network(_) => (NETWORK, "Network"),
+        BoxliteError::Image(_) => (NETWORK, "Image"),
+        BoxliteError::OfflineMode(_) => (NETWORK, "OfflineMode"),
+        BoxliteError::Timeout(_) => (TIMEOUT, "Timeout"),
+        BoxliteError::Unsupported(_) => (FAILURE, "Unsupported"),
+        BoxliteError::PolicyDenied(_) => (FAILURE, "PolicyDenied"),
+        BoxliteError::PermissionDenied(_) => (FAILURE, "PermissionDenied"),
+        BoxliteError::Execution(_) => (FAILURE, "Execution"),
+        BoxliteError::PartialTransfer(_) => (FAILURE, "PartialTransfer"),
+        BoxliteError::UnsafeArchive(_) => (FAILURE, "UnsafeArchive"),
+        BoxliteError::UnsafeCopy(_) => (FAILURE, "UnsafeCopy"),
+        BoxliteError::UnsupportedEngine => (INTERNAL, "UnsupportedEngine"),
+        BoxliteError::Engine(_) => (INTERNAL, "Engine"),
+        BoxliteError::Storage(_) => (INTERNAL, "Storage"),
+        BoxliteError::Portal(_) => (INTERNAL, "Portal"),
+        BoxliteError::Rpc(_) => (INTERNAL, "Rpc"),
+        BoxliteError::RpcTransport(_) => (INTERNAL, "RpcTransport"),
+        BoxliteError::Internal(_) => (INTERNAL, "Internal"),
+        BoxliteError::Database(_) => (INTERNAL, "Database"),
+        BoxliteError::MetadataError(_) => (INTERNAL, "MetadataError"),
+    }
+}
+
+/// Context of [`no_such_box`] errors, printed as `No such box: <target>`.
+#[derive(Debug)]
+struct NoSuchBox(String);
+
+impl std::fmt::Display for NoSuchBox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "No such box: {}", self.0)
+    }
+}
+
+/// Error for a box name or ID that matched no box.
+pub fn no_such_box(target: &str) -> anyhow::Error {
+    anyhow::Error::new(BoxliteError::NotFound(target.to_string()))
+        .context(NoSuchBox(target.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_such_box_reports_not_found_with_box_id() {
+        let error = no_such_box("web");
+        assert_eq!(error.to_string(), "No such box: web");
+        assert_eq!(
+            ErrorReport::new(&error),
+            ErrorReport {
+                code: exit_code::NOT_FOUND,
+                variant: "NotFound",
+                message: "No such box: web".to_string(),
+                box_id: Some("web".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_boxlite_errors_map_through_context() {
+        let error = anyhow::Error::new(BoxliteError::Image("pull failed".to_string()))
+            .context("pulling alpine");
+        let report = ErrorReport::new(&error);
+        assert_eq!(report.code, exit_code::NETWORK);
+        assert_eq!(report.variant, "Image");
+        assert_eq!(report.message, "pulling alpine");
+        assert_eq!(report.box_id, None);
+
+        let busy = anyhow::Error::new(BoxliteError::Busy {
+            operation: "start".to_string(),
+            pid: 42,
+        });
+        assert_eq!(ErrorReport::new(&busy).code, exit_code::INVALID_STATE);
+    }
+
+    #[test]
+    fn test_other_errors_are_general_failures() {
+        let report = ErrorReport::new(&anyhow::anyhow!("Some templates could not be removed"));
+        assert_eq!(report.code, exit_code::FAILURE);
+        assert_eq!(report.variant, "Other");
+    }
+
+    #[test]
+    fn test_json_report_omits_missing_box_id() {
+        let json = serde_json::to_value(ErrorReport::usage("bad flag")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"code": 2, "variant": "Usage", "message": "bad flag"})
+        );
+    }
+}
@@ -8,6 +8,9 @@ use gtmpl_value::{FuncError, Value as GtmplValue};
 use serde::Serialize;
 use tabled::{Table, Tabled, settings::Style};
 
+/// Values of `--format` for commands that print a table.
+pub const FORMATS: [&str; 3] = ["table", "json", "yaml"];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OutputFormat {
     Table,
@@ -1,7 +1,11 @@
 mod cli;
 mod commands;
+mod completion;
 mod config;
+mod error;
 mod formatter;
+mod man;
+mod reporter;
 pub mod terminal;
 pub mod util;
 
@@ -10,6 +14,7 @@ use std::process;
 use clap::CommandFactory;
 use clap::Parser;
 use cli::Cli;
+use error::{ErrorFormat, ErrorReport};
 use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt};
 
 fn main() {
@@ -23,14 +28,24 @@ fn main() {
         }
     }
 
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => exit_parse_error(e),
+    };
 
-    // Handle shell completion before starting tokio or tracing
-    if let cli::Commands::Completion(args) = &cli.command {
+    // Handle shell completion and man pages before starting tokio or tracing
+    if let cli::Commands::Completions(args) = &cli.command {
         let mut cmd = Cli::command();
         cli::generate_completion(&args.shell, &mut cmd, "boxlite", &mut std::io::stdout());
         process::exit(0);
     }
+    if let cli::Commands::Man(args) = &cli.command {
+        if let Err(e) = man::execute(args, Cli::command()) {
+            cli.global.reporter().error(format!("{e:#}"));
+            process::exit(1);
+        }
+        process::exit(0);
+    }
 
     // Start tokio runtime manually to ensure environment is set up safely
     let rt = tokio::runtime::Builder::new_multi_thread()
@@ -54,32 +69,121 @@ async fn run_cli(cli: Cli) -> anyhow::Result<()> {
         .init();
 
     let global = cli.global;
+    if global.dry_run && !cli.command.supports_dry_run() {
+        let report = ErrorReport::usage(
+            "--dry-run is only supported by rm, prune, import, snapshot restore and snapshot prune",
+        );
+        match global.error_format {
+            ErrorFormat::Json => report.print_json(),
+            ErrorFormat::Text => global.reporter().error(&report.message),
+        }
+        process::exit(report.code);
+    }
+    if let Some(command) = cli.command.local_only()
+        && let Some(reason) = global.remote_restriction()
+    {
+        let error = anyhow::Error::from(boxlite::BoxliteError::Unsupported(format!(
+            "`boxlite {command}` is {reason}"
+        )));
+        let report = ErrorReport::new(&error);
+        match global.error_format {
+            ErrorFormat::Json => report.print_json(),
+            ErrorFormat::Text => global.reporter().error(&report.message),
+        }
+        process::exit(report.code);
+    }
+
     let result = match cli.command {
         cli::Commands::Run(args) => commands::run::execute(args, &global).await,
         cli::Commands::Exec(args) => commands::exec::execute(args, &global).await,
+        cli::Commands::ExecAll(args) => commands::exec_all::execute(args, &global).await,
+        cli::Commands::ExecLogs(args) => commands::exec_logs::execute(args, &global).await,
         cli::Commands::Create(args) => commands::create::execute(args, &global).await,
         cli::Commands::List(args) => commands::list::execute(args, &global).await,
         cli::Commands::Rm(args) => commands::rm::execute(args, &global).await,
+        cli::Commands::Prune(args) => commands::prune::execute(args, &global).await,
         cli::Commands::Start(args) => commands::start::execute(args, &global).await,
         cli::Commands::Stop(args) => commands::stop::execute(args, &global).await,
         cli::Commands::Restart(args) => commands::restart::execute(args, &global).await,
+        cli::Commands::Sync(args) => commands::sync::execute(args, &global).await,
         cli::Commands::Pull(args) => commands::pull::execute(args, &global).await,
+        cli::Commands::Build(args) => commands::build::execute(args, &global).await,
         cli::Commands::Images(args) => commands::images::execute(args, &global).await,
         cli::Commands::Inspect(args) => commands::inspect::execute(args, &global).await,
         cli::Commands::Cp(args) => commands::cp::execute(args, &global).await,
+        cli::Commands::PortForward(args) => commands::port_forward::execute(args, &global).await,
+        #[cfg(feature = "fuse")]
+        cli::Commands::Mount(args) => commands::mount::execute(args, &global).await,
+        #[cfg(feature = "fuse")]
+        cli::Commands::Umount(args) => commands::umount::execute(args, &global).await,
         cli::Commands::Info(args) => commands::info::execute(args, &global).await,
         cli::Commands::Logs(args) => commands::logs::execute(args, &global).await,
         cli::Commands::Stats(args) => commands::stats::execute(args, &global).await,
+        cli::Commands::Doctor(args) => commands::doctor::execute(args, &global).await,
+        cli::Commands::Debug(args) => commands::debug::execute(args, &global).await,
+        cli::Commands::Compact(args) => commands::compact::execute(args, &global).await,
+        cli::Commands::Export(args) => commands::export::execute(args, &global).await,
+        cli::Commands::Import(args) => commands::import::execute(args, &global).await,
+        cli::Commands::Snapshot(args) => commands::snapshot::execute(args, &global).await,
+        cli::Commands::Service(args) => commands::service::execute(args, &global).await,
+        cli::Commands::Compose(args) => commands::compose::execute(args, &global).await,
+        cli::Commands::Audit(args) => commands::audit::execute(args, &global).await,
+        cli::Commands::Bench(args) => commands::bench::execute(args, &global).await,
+        cli::Commands::Version(args) => commands::version::execute(args, &global).await,
+        cli::Commands::Trash(args) => commands::trash::execute(args, &global).await,
+        cli::Commands::Template(args) => commands::template::execute(args, &global).await,
+        cli::Commands::System(args) => commands::system::execute(args, &global).await,
         // Handled in main() before tokio; never reaches run_cli
-        cli::Commands::Completion(_) => {
-            unreachable!("completion subcommand is handled before tokio in main()")
+        cli::Commands::Completions(_) | cli::Commands::Man(_) => {
+            unreachable!("completions and man are handled before tokio in main()")
         }
     };
 
     if let Err(error) = result {
-        eprintln!("Error: {}", error);
-        process::exit(1);
+        let report = ErrorReport::new(&error);
+        match (
+            global.error_format,
+            error.downcast_ref::<boxlite::BoxliteError>(),
+        ) {
+            (ErrorFormat::Json, _) => report.print_json(),
+            (_, Some(boxlite::BoxliteError::PolicyDenied(reason))) => global
+                .reporter()
+                .error_for("creating box", format!("denied by policy: {reason}")),
+            _ => global.reporter().error(error),
+        }
+        process::exit(report.code);
     }
 
     Ok(())
 }
+
+/// Exit on an argument error. clap exits with 2 on its own; with
+/// `--error-format json` the error is reported as JSON instead.
+fn exit_parse_error(e: clap::Error) -> ! {
+    use clap::error::ErrorKind;
+
+    let json = std::env::args()
+        .collect::<Vec<_>>()
+        .windows(2)
+        .any(|w| w[0] == "--error-format" && w[1] == "json")
+        || std::env::args().any(|arg| arg == "--error-format=json");
+    let help = matches!(
+        e.kind(),
+        ErrorKind::DisplayHelp
+            | ErrorKind::DisplayVersion
+            | ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+    );
+    if !json || help {
+        e.exit();
+    }
+
+    let rendered = e.to_string();
+    let message = rendered
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches("error: ");
+    let report = ErrorReport::usage(message);
+    report.print_json();
+    process::exit(report.code);
+}
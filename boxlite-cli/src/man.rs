@@ -0,0 +1,316 @@
+//! Man pages rendered from the clap definitions (`boxlite man`).
+//!
+//! Every visible command gets a section 1 page: `boxlite(1)` for the root
+//! and `boxlite-<path>(1)` for subcommands, e.g. `boxlite-snapshot-restore`.
+
+use crate::cli::ManArgs;
+use anyhow::{Context, Result, anyhow};
+use clap::{Arg, Command};
+use std::fmt::Write as _;
+use std::io::Write as _;
+
+/// Manual section of the pages: user commands.
+const SECTION: &str = "1";
+
+/// A rendered man page.
+#[derive(Debug)]
+pub struct Page {
+    /// Page name, e.g. `boxlite-snapshot-restore`
+    pub name: String,
+    /// troff source
+    pub source: String,
+}
+
+impl Page {
+    /// File name of the page, e.g. `boxlite-snapshot-restore.1`.
+    pub fn file_name(&self) -> String {
+        format!("{}.{SECTION}", self.name)
+    }
+}
+
+/// Print the page of `args.command`, or write every page into `args.dir`.
+pub fn execute(args: &ManArgs, mut cmd: Command) -> Result<()> {
+    cmd.build();
+
+    let Some(dir) = &args.dir else {
+        let page = page(&cmd, &args.command)?;
+        std::io::stdout().write_all(page.source.as_bytes())?;
+        return Ok(());
+    };
+
+    std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+    for page in pages(&cmd) {
+        let path = dir.join(page.file_name());
+        std::fs::write(&path, page.source)
+            .with_context(|| format!("writing {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Pages of `cmd` and its visible subcommands, root first; `cmd` must be built.
+pub fn pages(cmd: &Command) -> Vec<Page> {
+    let mut pages = Vec::new();
+    collect(cmd, version(cmd), &mut pages);
+    pages
+}
+
+/// The page of the subcommand at `path` (names or aliases); `cmd` must be built.
+pub fn page(cmd: &Command, path: &[String]) -> Result<Page> {
+    let mut sub = cmd;
+    for name in path {
+        sub = sub
+            .find_subcommand(name)
+            .filter(|s| !s.is_hide_set())
+            .ok_or_else(|| anyhow!("no such command: {}", path.join(" ")))?;
+    }
+    Ok(render(sub, version(cmd)))
+}
+
+fn version(cmd: &Command) -> &str {
+    cmd.get_version().unwrap_or_default()
+}
+
+fn collect(cmd: &Command, version: &str, pages: &mut Vec<Page>) {
+    pages.push(render(cmd, version));
+    for sub in visible_subcommands(cmd) {
+        collect(sub, version, pages);
+    }
+}
+
+fn visible_subcommands(cmd: &Command) -> impl Iterator<Item = &Command> {
+    cmd.get_subcommands()
+        .filter(|sub| !sub.is_hide_set() && sub.get_name() != "help")
+}
+
+fn page_name(cmd: &Command) -> String {
+    cmd.get_bin_name()
+        .unwrap_or(cmd.get_name())
+        .replace(' ', "-")
+}
+
+fn render(cmd: &Command, version: &str) -> Page {
+    let name = page_name(cmd);
+    let bin = cmd.get_bin_name().unwrap_or(cmd.get_name());
+    let root = bin.split(' ').next().unwrap_or(bin);
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        ".TH \"{}\" \"{SECTION}\" \"\" \"{} {}\" \"BoxLite Manual\"",
+        escape(&name.to_uppercase()),
+        escape(root),
+        escape(version)
+    );
+
+    let _ = writeln!(out, ".SH NAME");
+    match cmd.get_about() {
+        Some(about) => {
+            let _ = writeln!(out, "{} \\- {}", escape(&name), escape(&about.to_string()));
+        }
+        None => {
+            let _ = writeln!(out, "{}", escape(&name));
+        }
+    }
+
+    let usage = cmd.clone().render_usage().to_string();
+    let usage = usage.strip_prefix("Usage:").unwrap_or(&usage);
+    let _ = writeln!(out, ".SH SYNOPSIS\n.nf");
+    for line in usage.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let _ = writeln!(out, "{}", escape(line));
+    }
+    let _ = writeln!(out, ".fi");
+
+    if let Some(about) = cmd.get_long_about().or(cmd.get_about()) {
+        let _ = writeln!(out, ".SH DESCRIPTION");
+        paragraphs(&mut out, &about.to_string());
+    }
+
+    let visible = |arg: &&Arg| !arg.is_hide_set();
+    let positionals: Vec<&Arg> = cmd.get_positionals().filter(visible).collect();
+    if !positionals.is_empty() {
+        let _ = writeln!(out, ".SH ARGUMENTS");
+        for arg in positionals {
+            let _ = writeln!(out, ".TP\n\\fI{}\\fR", escape(&value_names(arg)));
+            arg_help(&mut out, arg);
+        }
+    }
+
+    let options: Vec<&Arg> = cmd
+        .get_arguments()
+        .filter(|arg| !arg.is_positional())
+        .filter(visible)
+        .collect();
+    if !options.is_empty() {
+        let _ = writeln!(out, ".SH OPTIONS");
+        for arg in options {
+            let _ = writeln!(out, ".TP\n{}", option_header(arg));
+            arg_help(&mut out, arg);
+        }
+    }
+
+    let subcommands: Vec<&Command> = visible_subcommands(cmd).collect();
+    if !subcommands.is_empty() {
+        let _ = writeln!(out, ".SH COMMANDS");
+        for sub in &subcommands {
+            let _ = writeln!(out, ".TP\n\\fB{}\\fR({SECTION})", escape(&page_name(sub)));
+            if let Some(about) = sub.get_about() {
+                let _ = writeln!(out, "{}", escape(&about.to_string()));
+            }
+        }
+    }
+
+    if let Some(after) = cmd.get_after_long_help().or(cmd.get_after_help()) {
+        let _ = writeln!(out, ".SH NOTES\n.nf");
+        for line in after.to_string().lines() {
+            let _ = writeln!(out, "{}", escape(line));
+        }
+        let _ = writeln!(out, ".fi");
+    }
+
+    if let Some((parent, _)) = bin.rsplit_once(' ') {
+        let _ = writeln!(out, ".SH SEE ALSO");
+        let _ = writeln!(
+            out,
+            "\\fB{}\\fR({SECTION})",
+            escape(&parent.replace(' ', "-"))
+        );
+    }
+
+    Page { name, source: out }
+}
+
+/// `-s, --long <VALUE>` in bold and italics.
+fn option_header(arg: &Arg) -> String {
+    let mut flags = Vec::new();
+    if let Some(short) = arg.get_short() {
+        flags.push(format!("\\fB\\-{}\\fR", escape(&short.to_string())));
+    }
+    if let Some(long) = arg.get_long() {
+        flags.push(format!("\\fB\\-\\-{}\\fR", escape(long)));
+    }
+    let mut header = flags.join(", ");
+    if arg.get_action().takes_values() {
+        let _ = write!(header, " \\fI{}\\fR", escape(&value_names(arg)));
+    }
+    header
+}
+
+/// `<NAME>`, with `...` when the argument repeats.
+fn value_names(arg: &Arg) -> String {
+    let names: Vec<String> = match arg.get_value_names() {
+        Some(names) => names.iter().map(|n| format!("<{n}>")).collect(),
+        None => vec![format!("<{}>", arg.get_id().as_str().to_uppercase())],
+    };
+    let repeats = arg.get_num_args().is_some_and(|n| n.max_values() > 1);
+    let mut out = names.join(" ");
+    if repeats {
+        out.push_str("...");
+    }
+    out
+}
+
+/// Help of `arg`, followed by its possible values, default and variable.
+fn arg_help(out: &mut String, arg: &Arg) {
+    if let Some(help) = arg.get_long_help().or(arg.get_help()) {
+        paragraphs(out, &help.to_string());
+    }
+
+    let mut notes = Vec::new();
+    if arg.get_action().takes_values() {
+        let possible: Vec<String> = arg
+            .get_possible_values()
+            .iter()
+            .filter(|v| !v.is_hide_set())
+            .map(|v| v.get_name().to_string())
+            .collect();
+        if !possible.is_empty() {
+            notes.push(format!("possible values: {}", possible.join(", ")));
+        }
+        let defaults: Vec<String> = arg
+            .get_default_values()
+            .iter()
+            .map(|v| v.to_string_lossy().into_owned())
+            .collect();
+        if !defaults.is_empty() && !arg.is_hide_default_value_set() {
+            notes.push(format!("default: {}", defaults.join(", ")));
+        }
+    }
+    if let Some(env) = arg.get_env()
+        && !arg.is_hide_env_set()
+    {
+        notes.push(format!("env: {}", env.to_string_lossy()));
+    }
+    if !notes.is_empty() {
+        let _ = writeln!(out, "[{}]", escape(&notes.join("; ")));
+    }
+}
+
+/// Write `text` as troff paragraphs, split at blank lines.
+fn paragraphs(out: &mut String, text: &str) {
+    for (i, paragraph) in text.split("\n\n").enumerate() {
+        if i > 0 {
+            let _ = writeln!(out, ".PP");
+        }
+        for line in paragraph.lines() {
+            let _ = writeln!(out, "{}", escape(line.trim_start()));
+        }
+    }
+}
+
+/// Escape `text` for troff: backslashes and dashes, and control characters
+/// at the start of a line.
+fn escape(text: &str) -> String {
+    let escaped = text.replace('\\', "\\e").replace('-', "\\-");
+    if escaped.starts_with('.') || escaped.starts_with('\'') {
+        format!("\\&{escaped}")
+    } else {
+        escaped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::CommandFactory;
+
+    fn built() -> Command {
+        let mut cmd = Cli::command();
+        cmd.build();
+        cmd
+    }
+
+    #[test]
+    fn test_pages_cover_nested_subcommands() {
+        let names: Vec<String> = pages(&built()).into_iter().map(|p| p.name).collect();
+        assert_eq!(names[0], "boxlite");
+        assert!(names.contains(&"boxlite-run".to_string()));
+        assert!(names.contains(&"boxlite-snapshot-restore".to_string()));
+        // Hidden and help commands get no page
+        assert!(!names.iter().any(|n| n.ends_with("-help")));
+    }
+
+    #[test]
+    fn test_page_resolves_aliases() {
+        let cmd = built();
+        let page = page(&cmd, &["ps".to_string()]).unwrap();
+        assert_eq!(page.file_name(), "boxlite-list.1");
+        assert!(page.source.starts_with(".TH \"BOXLITE\\-LIST\" \"1\""));
+        assert!(page.source.contains("\\fB\\-a\\fR, \\fB\\-\\-all\\fR"));
+        assert!(
+            page.source
+                .contains("possible values: table, json, yaml, names")
+        );
+        assert!(page.source.contains(".SH SEE ALSO\n\\fBboxlite\\fR(1)"));
+
+        assert!(page(&cmd, &["nope".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("--all"), "\\-\\-all");
+        assert_eq!(escape("a\\b"), "a\\eb");
+        assert_eq!(escape(".hidden"), "\\&.hidden");
+        assert_eq!(escape("'quoted'"), "\\&'quoted'");
+    }
+}
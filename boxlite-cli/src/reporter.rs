@@ -0,0 +1,221 @@
+//! User-facing output for CLI commands.
+//!
+//! Commands print through a [`Reporter`] instead of `println!`/`eprintln!`:
+//! - Results (ids, names, digests) go to stdout, never decorated, so they stay
+//!   pipeable.
+//! - Status lines, warnings, errors and spinners go to stderr.
+//!
+//! Colors follow `--color` and the `NO_COLOR` convention (<https://no-color.org>).
+//! Spinners are drawn only when both stdout and stderr are terminals, and never
+//! in machine-readable (`--format json|yaml`) mode.
+
+use std::borrow::Cow;
+use std::io::{IsTerminal, Write};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// When to use colors and other terminal decorations.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[value(rename_all = "lower")]
+pub enum ColorChoice {
+    /// Decorate when writing to a terminal and `NO_COLOR` is unset
+    #[default]
+    Auto,
+    /// Always colorize stderr (spinners still require a terminal)
+    Always,
+    /// Never decorate
+    Never,
+}
+
+const RED: &str = "31";
+const YELLOW: &str = "33";
+
+/// Output sink shared by all commands.
+#[derive(Clone, Debug)]
+pub struct Reporter {
+    color: bool,
+    decorate: bool,
+    machine_readable: bool,
+}
+
+impl Reporter {
+    /// Build a reporter for the current process's terminals and environment.
+    pub fn new(choice: ColorChoice) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        let stdout_tty = std::io::stdout().is_terminal();
+        let stderr_tty = std::io::stderr().is_terminal();
+        Self::resolve(choice, no_color, stdout_tty, stderr_tty)
+    }
+
+    /// Decide decorations from explicit inputs.
+    fn resolve(choice: ColorChoice, no_color: bool, stdout_tty: bool, stderr_tty: bool) -> Self {
+        let color = match choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => !no_color && stderr_tty,
+        };
+        let decorate = choice != ColorChoice::Never && !no_color && stdout_tty && stderr_tty;
+        Self {
+            color,
+            decorate,
+            machine_readable: false,
+        }
+    }
+
+    /// Suppress status lines and spinners, e.g. for `--format json`.
+    ///
+    /// Results and errors are still printed.
+    pub fn machine_readable(mut self, enabled: bool) -> Self {
+        self.machine_readable = enabled;
+        self
+    }
+
+    /// Print a result line to stdout.
+    pub fn println(&self, line: impl std::fmt::Display) {
+        println!("{line}");
+    }
+
+    /// Print an informational line to stderr (hidden in machine-readable mode).
+    pub fn status(&self, line: impl std::fmt::Display) {
+        if !self.machine_readable {
+            eprintln!("{line}");
+        }
+    }
+
+    /// Print `Warning: <msg>` to stderr.
+    pub fn warn(&self, msg: impl std::fmt::Display) {
+        eprintln!("{} {msg}", self.paint(YELLOW, "Warning:"));
+    }
+
+    /// Print `Error: <msg>` to stderr.
+    pub fn error(&self, msg: impl std::fmt::Display) {
+        eprintln!("{} {msg}", self.paint(RED, "Error:"));
+    }
+
+    /// Print `Error <doing what>: <msg>` to stderr.
+    pub fn error_for(&self, context: impl std::fmt::Display, msg: impl std::fmt::Display) {
+        eprintln!("{} {context}: {msg}", self.paint(RED, "Error"));
+    }
+
+    /// Start a spinner with `message`; a no-op when decorations are off.
+    ///
+    /// The spinner is cleared when finished or dropped, leaving stdout and
+    /// stderr free of progress artifacts.
+    pub fn spinner(&self, message: impl Into<Cow<'static, str>>) -> Spinner {
+        if !self.decorate || self.machine_readable {
+            return Spinner(ProgressBar::hidden());
+        }
+
+        let bar = ProgressBar::with_draw_target(None, ProgressDrawTarget::stderr());
+        bar.set_style(
+            ProgressStyle::with_template("{spinner} {msg} {elapsed:.dim}")
+                .expect("valid spinner template"),
+        );
+        bar.set_message(message);
+        bar.enable_steady_tick(Duration::from_millis(100));
+        Spinner(bar)
+    }
+
+    /// Clear the terminal before redrawing a live view; a no-op when decorations are off.
+    pub fn clear_screen(&self) -> std::io::Result<()> {
+        if self.decorate && !self.machine_readable {
+            let mut stdout = std::io::stdout();
+            write!(stdout, "\x1B[2J\x1B[1;1H")?;
+            stdout.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Ask a yes/no question on stderr. Only an explicit "y" confirms.
+    pub fn confirm(&self, question: &str) -> std::io::Result<bool> {
+        eprint!("{} [y/N] ", self.paint(YELLOW, question));
+        std::io::stderr().flush()?;
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        Ok(input.trim().eq_ignore_ascii_case("y"))
+    }
+
+    /// Whether a question can be asked: stdin and stderr are terminals.
+    pub fn is_interactive(&self) -> bool {
+        std::io::stdin().is_terminal() && std::io::stderr().is_terminal()
+    }
+
+    fn paint<'a>(&self, code: &str, text: &'a str) -> Cow<'a, str> {
+        if self.color {
+            Cow::Owned(format!("\x1b[{code}m{text}\x1b[0m"))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+}
+
+/// Handle to a running spinner. Cleared on drop.
+pub struct Spinner(ProgressBar);
+
+impl Spinner {
+    /// Replace the spinner's message.
+    pub fn set_message(&self, message: impl Into<Cow<'static, str>>) {
+        self.0.set_message(message);
+    }
+
+    /// Hide the spinner while `f` writes to the terminal.
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.0.suspend(f)
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.0.finish_and_clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_decorates_only_on_terminal() {
+        let tty = Reporter::resolve(ColorChoice::Auto, false, true, true);
+        assert!(tty.color);
+        assert!(tty.decorate);
+
+        let piped = Reporter::resolve(ColorChoice::Auto, false, false, false);
+        assert!(!piped.color);
+        assert!(!piped.decorate);
+
+        // stdout piped, stderr on terminal: color stderr, but no spinners
+        let stdout_piped = Reporter::resolve(ColorChoice::Auto, false, false, true);
+        assert!(stdout_piped.color);
+        assert!(!stdout_piped.decorate);
+    }
+
+    #[test]
+    fn no_color_disables_auto_decorations() {
+        let reporter = Reporter::resolve(ColorChoice::Auto, true, true, true);
+        assert!(!reporter.color);
+        assert!(!reporter.decorate);
+    }
+
+    #[test]
+    fn explicit_choice_overrides_environment() {
+        let always = Reporter::resolve(ColorChoice::Always, true, false, false);
+        assert!(always.color);
+        assert!(!always.decorate);
+
+        let never = Reporter::resolve(ColorChoice::Never, false, true, true);
+        assert!(!never.color);
+        assert!(!never.decorate);
+    }
+
+    #[test]
+    fn paint_is_plain_without_color() {
+        let plain = Reporter::resolve(ColorChoice::Never, false, true, true);
+        assert_eq!(plain.paint(RED, "Error:"), "Error:");
+
+        let colored = Reporter::resolve(ColorChoice::Always, false, true, true);
+        assert_eq!(colored.paint(RED, "Error:"), "\x1b[31mError:\x1b[0m");
+    }
+}
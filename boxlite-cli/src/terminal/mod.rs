@@ -1,13 +1,12 @@
 use anyhow::Result;
 use boxlite::Execution;
-use futures::StreamExt;
 use nix::sys::signal::Signal;
 use nix::sys::termios::{
     InputFlags, LocalFlags, OutputFlags, SetArg, Termios, tcgetattr, tcsetattr,
 };
 use std::io::IsTerminal;
 use std::os::fd::{AsFd, AsRawFd};
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::io::AsyncReadExt;
 use tokio::select;
 use tokio::signal::unix::{SignalKind, signal};
 
@@ -96,51 +95,27 @@ impl<'a> StreamManager<'a> {
             None
         };
 
-        // stdout
-        let stdout_stream = self.execution.stdout();
+        // stdout, paced so a slow terminal or pipe holds the process back
+        let stdout_pipe = self.execution.pipe_stdout(tokio::io::stdout());
         let stdout_handle = tokio::spawn(async move {
-            if let Some(mut stream) = stdout_stream {
-                let mut stdout = tokio::io::stdout();
-                while let Some(chunk) = stream.next().await {
-                    if let Err(e) = stdout.write_all(chunk.as_bytes()).await {
-                        if e.kind() != std::io::ErrorKind::BrokenPipe {
-                            tracing::debug!("stdout write error: {}", e);
-                        }
-                        break;
-                    }
-                    let _ = stdout.flush().await;
-                }
+            if let Ok(Err(e)) = stdout_pipe.await
+                && e.kind() != std::io::ErrorKind::BrokenPipe
+            {
+                tracing::debug!("stdout write error: {}", e);
             }
         });
 
-        // stderr
-        let stderr_stream = self.execution.stderr();
-        let tty_mode = self.tty;
+        // stderr, merged into stdout in TTY mode
+        let stderr_pipe = if self.tty {
+            self.execution.pipe_stderr(tokio::io::stdout())
+        } else {
+            self.execution.pipe_stderr(tokio::io::stderr())
+        };
         let stderr_handle = tokio::spawn(async move {
-            if let Some(mut stream) = stderr_stream {
-                let mut stderr = tokio::io::stderr();
-                let mut stdout = tokio::io::stdout();
-
-                while let Some(chunk) = stream.next().await {
-                    let res = if tty_mode {
-                        stdout.write_all(chunk.as_bytes()).await
-                    } else {
-                        stderr.write_all(chunk.as_bytes()).await
-                    };
-
-                    if let Err(e) = res {
-                        if e.kind() != std::io::ErrorKind::BrokenPipe {
-                            tracing::debug!("stderr write error: {}", e);
-                        }
-                        break;
-                    }
-
-                    if tty_mode {
-                        let _ = stdout.flush().await;
-                    } else {
-                        let _ = stderr.flush().await;
-                    }
-                }
+            if let Ok(Err(e)) = stderr_pipe.await
+                && e.kind() != std::io::ErrorKind::BrokenPipe
+            {
+                tracing::debug!("stderr write error: {}", e);
             }
         });
 
@@ -236,6 +211,8 @@ impl<'a> StreamManager<'a> {
     }
 }
 
+/// Forward local stdin to the process until EOF (end of a pipe, or Ctrl-D
+/// without `-t`), then close the remote stdin so the process sees EOF too.
 async fn stream_stdin(mut stdin_tx: boxlite::ExecStdin) {
     let mut stdin = tokio::io::stdin();
     let mut buf = [0u8; 8192];
@@ -244,9 +221,11 @@ async fn stream_stdin(mut stdin_tx: boxlite::ExecStdin) {
         match stdin.read(&mut buf).await {
             Ok(0) => break,
             Ok(n) => {
+                // Fails once the process has closed its stdin; the rest of
+                // the input is not wanted
                 if let Err(e) = stdin_tx.write(&buf[..n]).await {
-                    tracing::debug!("failed to forward stdin: {}", e);
-                    break;
+                    tracing::debug!("remote stdin closed: {}", e);
+                    return;
                 }
             }
             Err(e) => {
@@ -255,4 +234,5 @@ async fn stream_stdin(mut stdin_tx: boxlite::ExecStdin) {
             }
         }
     }
+    stdin_tx.close();
 }
@@ -21,10 +21,39 @@ pub fn to_shell_exit_code(boxlite_code: i32) -> i32 {
     }
 }
 
+/// Fail with a summary when any box of a multi-box command failed.
+///
+/// `errors` holds one `<box>: <error>` line per failed box, out of `total`.
+pub fn bulk_failure_summary(verb: &str, errors: &[String], total: usize) -> anyhow::Result<()> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    let summary = if errors.len() < total {
+        format!("Failed to {} {} of {} box(es)", verb, errors.len(), total)
+    } else {
+        format!("Failed to {} all {} box(es)", verb, errors.len())
+    };
+    anyhow::bail!("{}\nErrors:\n  {}", summary, errors.join("\n  "))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bulk_failure_summary() {
+        assert!(bulk_failure_summary("stop", &[], 3).is_ok());
+
+        let errors = vec!["web-1: not found".to_string()];
+        let err = bulk_failure_summary("stop", &errors, 3).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to stop 1 of 3 box(es)\nErrors:\n  web-1: not found"
+        );
+        let err = bulk_failure_summary("stop", &errors, 1).unwrap_err();
+        assert!(err.to_string().starts_with("Failed to stop all 1 box(es)"));
+    }
+
     #[test]
     fn test_to_shell_exit_code_success() {
         assert_eq!(to_shell_exit_code(0), 0);
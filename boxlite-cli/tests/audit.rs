@@ -0,0 +1,39 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_audit_tail_succeeds_without_log() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["audit", "tail", "-n", "5"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_audit_records_create_and_remove() {
+    let mut ctx = common::boxlite();
+    let name = "audit-create-remove";
+    ctx.cmd
+        .args(["--audit", "create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--audit", "rm", name])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["audit", "tail", "--json", "-n", "50"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("\"name\":\"{name}\"")))
+        .stdout(predicate::str::contains("\"operation\":\"create\""))
+        .stdout(predicate::str::contains(format!(
+            "\"id_or_name\":\"{name}\""
+        )));
+
+    ctx.cleanup_box(name);
+}
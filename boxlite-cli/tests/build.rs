@@ -0,0 +1,69 @@
+use predicates::prelude::*;
+
+mod common;
+
+fn context(containerfile: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("Containerfile"), containerfile).unwrap();
+    dir
+}
+
+#[test]
+fn test_build_unsupported_instruction_fails_upfront() {
+    let dir = context("FROM alpine:latest\nADD app.tgz /app\nUSER nobody\n");
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["build", "-t", "local/unsupported"])
+        .arg(dir.path());
+    ctx.cmd.assert().failure().stderr(
+        predicate::str::contains("line 2: ADD")
+            .and(predicate::str::contains("line 3: USER"))
+            .and(predicate::str::contains(
+                "FROM, RUN, COPY, ENV, WORKDIR, ENTRYPOINT, CMD",
+            ))
+            .and(predicate::str::contains("STEP").not()),
+    );
+}
+
+#[test]
+fn test_build_and_run() {
+    let dir = context(
+        "FROM alpine:latest\n\
+         ENV GREETING=hello\n\
+         WORKDIR /app\n\
+         COPY message.txt .\n\
+         RUN echo \"$GREETING from $(pwd)\" >> message.txt && cat message.txt\n\
+         CMD [\"cat\", \"/app/message.txt\"]\n",
+    );
+    std::fs::write(dir.path().join("message.txt"), "copied\n").unwrap();
+
+    let mut ctx = common::boxlite();
+    ctx.cmd.timeout(std::time::Duration::from_secs(300));
+    ctx.cmd
+        .args(["build", "-t", "local/cli-build"])
+        .arg(dir.path());
+    ctx.cmd
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("STEP 5/6: RUN"))
+        .stderr(predicate::str::contains("hello from /app"))
+        .stdout(predicate::str::contains("Built: local/cli-build:latest"));
+
+    // Every step is cached the second time
+    let mut rebuild = ctx.new_cmd();
+    rebuild.timeout(std::time::Duration::from_secs(300));
+    rebuild
+        .args(["build", "-t", "local/cli-build"])
+        .arg(dir.path());
+    rebuild
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("RUN echo").and(predicate::str::contains("(cached)")))
+        .stderr(predicate::str::contains("hello from /app").not());
+
+    let mut run = ctx.new_cmd();
+    run.args(["run", "--rm", "local/cli-build", "cat", "/app/message.txt"]);
+    run.assert()
+        .success()
+        .stdout(predicate::str::diff("copied\nhello from /app\n"));
+}
@@ -0,0 +1,73 @@
+use predicates::prelude::*;
+
+mod common;
+
+const ESC: &str = "\x1b[";
+
+#[test]
+fn test_piped_output_is_undecorated() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["stop", "color-nonexistent"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("Error: No such box"))
+        .stderr(predicate::str::contains(ESC).not());
+}
+
+#[test]
+fn test_color_always_colors_errors_when_piped() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["--color", "always", "stop", "color-nonexistent"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "\x1b[31mError:\x1b[0m No such box",
+        ));
+}
+
+#[test]
+fn test_no_color_env_is_honored() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .env("NO_COLOR", "1")
+        .args(["stop", "color-nonexistent"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(ESC).not());
+}
+
+#[test]
+fn test_color_never_overrides_everything() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["--color", "never", "stop", "color-nonexistent"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(ESC).not());
+}
+
+#[test]
+fn test_invalid_color_choice() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["--color", "sometimes", "list"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("invalid value"));
+}
+
+#[test]
+fn test_piped_pull_prints_only_results() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["pull", "--quiet", "alpine:latest"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"^sha256:[0-9a-f]+\n$").unwrap())
+        .stderr(predicate::str::contains('\r').not());
+}
@@ -1,12 +1,13 @@
 #![allow(dead_code)]
 
 use assert_cmd::Command;
-use std::path::PathBuf;
+use boxlite::testing::{ALPINE_IMAGE, SHORT_TEMP_ROOT};
+use std::path::{Path, PathBuf};
 use std::sync::{Mutex, MutexGuard, OnceLock};
 use std::time::Duration;
 
 /// images pre-pull (manually maintained to avoid Docker Hub rate limits)
-const TEST_IMAGES: &[&str] = &["alpine:latest", "python:alpine"];
+const TEST_IMAGES: &[&str] = &[ALPINE_IMAGE, "python:alpine"];
 
 // Prevents "Failed to acquire runtime lock" errors since all tests share the same home dir
 static TEST_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
@@ -51,8 +52,8 @@ pub fn boxlite() -> TestContext {
 
         // Use a very short path in /tmp to avoid SUN_LEN limits for Unix sockets (104-108 chars)
         // Project folders can be very deep, exceeding this limit when appended with /boxes/.../sockets/ready.sock
-        let test_home = PathBuf::from("/tmp/bl");
-        std::fs::create_dir_all(&test_home).expect("Failed to create /tmp/bl directory");
+        let test_home = Path::new(SHORT_TEMP_ROOT).join("bl");
+        std::fs::create_dir_all(&test_home).expect("Failed to create shared test home");
 
         let home = test_home;
         let bin_path = env!("CARGO_BIN_EXE_boxlite");
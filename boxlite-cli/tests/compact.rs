@@ -0,0 +1,39 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_compact_requires_target() {
+    let ctx = common::boxlite();
+    ctx.new_cmd().args(["compact"]).assert().failure();
+}
+
+#[test]
+fn test_compact_nonexistent_box() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["compact", "no-such-box-123"])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("No such box"));
+}
+
+#[test]
+fn test_compact_running_box_fails() {
+    let mut ctx = common::boxlite();
+    let name = "compact-running";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    ctx.new_cmd()
+        .args(["compact", name])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("must be stopped"));
+
+    ctx.cleanup_box(name);
+}
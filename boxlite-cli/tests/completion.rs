@@ -1,11 +1,15 @@
-//! Tests for shell completion generation (`boxlite completion bash|zsh|fish`).
-//! Verifies that each shell gets a non-empty script containing all visible subcommands.
+//! Tests for shell completion generation (`boxlite completions bash|zsh|fish`).
+//! Verifies that each shell gets a non-empty script containing all visible subcommands,
+//! and that the bash script completes box names.
 //! Expected subcommands are derived from `boxlite --help` so adding/removing commands
 //! does not require updating this file.
 
 use assert_cmd::Command;
 use predicates::prelude::*;
 use rstest::rstest;
+use std::path::Path;
+
+mod common;
 
 /// Minimum number of visible subcommands in `boxlite --help`. Bump when adding a new visible subcommand.
 const MIN_VISIBLE_SUBCOMMANDS: usize = 13;
@@ -15,7 +19,8 @@ fn boxlite_cmd() -> Command {
 }
 
 /// Returns visible subcommand names by parsing `boxlite --help` (Commands: section).
-/// Skips "help". Hidden subcommands (e.g. completion) do not appear in help, so they are not included.
+/// Skips "help". Hidden subcommands and aliases (e.g. completion) do not appear in help, so they
+/// are not included.
 /// This test assumes clap's help output keeps the "Commands:" section format stable.
 fn visible_subcommand_names() -> Vec<String> {
     let assert = boxlite_cmd().arg("--help").assert().success();
@@ -103,15 +108,90 @@ fn completion_invalid_shell_fails() {
         .args(["completion", "invalid"])
         .assert()
         .failure()
+        .code(2)
         .stderr(predicate::str::contains("invalid"));
 }
 
 #[test]
-fn completion_not_shown_in_help() {
-    let assert = boxlite_cmd().arg("--help").assert().success();
-    let stdout = std::str::from_utf8(&assert.get_output().stdout).unwrap();
+fn completions_shown_in_help_without_alias() {
+    let names = visible_subcommand_names();
+    assert!(names.contains(&"completions".to_string()));
     assert!(
-        !stdout.contains("completion"),
-        "completion subcommand should be hidden from --help"
+        !names.contains(&"completion".to_string()),
+        "the completion alias should be hidden from --help"
     );
 }
+
+#[rstest]
+#[case("bash", "_boxlite_dynamic")]
+#[case("zsh", "_boxlite_dynamic")]
+#[case("fish", "__boxlite_boxes")]
+fn completions_complete_box_names(#[case] shell: &str, #[case] function: &str) {
+    let assert = boxlite_cmd()
+        .args(["completions", shell])
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout).unwrap();
+    assert!(stdout.contains(function), "{shell}: no {function}");
+    assert!(stdout.contains("ps -a -q --format names"), "{shell}");
+}
+
+/// Completes the last of `words` with the generated bash script, as bash
+/// does on TAB, and returns the candidates.
+fn bash_complete(words: &[&str]) -> Vec<String> {
+    let bin = env!("CARGO_BIN_EXE_boxlite");
+    let bin_dir = Path::new(bin).parent().unwrap();
+    let path = format!(
+        "{}:{}",
+        bin_dir.display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+    let script = r#"
+        source <(boxlite completions bash)
+        COMP_WORDS=("$@")
+        COMP_CWORD=$((${#COMP_WORDS[@]} - 1))
+        complete -p boxlite | grep -q _boxlite_dynamic || exit 3
+        _boxlite_dynamic boxlite "${COMP_WORDS[COMP_CWORD]}" "${COMP_WORDS[COMP_CWORD - 1]}"
+        printf '%s
+' "${COMPREPLY[@]}"
+    "#;
+    let output = std::process::Command::new("bash")
+        .args(["-c", script, "bash"])
+        .args(words)
+        .env("PATH", path)
+        .output()
+        .expect("bash");
+    assert!(output.status.success(), "{output:?}");
+    String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn bash_completes_created_box_names() {
+    let mut ctx = common::boxlite();
+    let name = "completion-box";
+    ctx.cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+    let home = ctx.home.to_str().unwrap();
+
+    // The box position of exec, and every position of stop
+    let exec = bash_complete(&["boxlite", "--home", home, "exec", "completion-"]);
+    assert_eq!(exec, [name]);
+    let stop = bash_complete(&["boxlite", "--home", home, "stop", name, ""]);
+    assert!(stop.contains(&name.to_string()), "{stop:?}");
+
+    // cp completes BOX: prefixes besides host paths
+    let cp = bash_complete(&["boxlite", "--home", home, "cp", "completion-"]);
+    assert!(cp.contains(&format!("{name}:")), "{cp:?}");
+
+    // The command of exec is not a box
+    let command = bash_complete(&["boxlite", "--home", home, "exec", name, "completion-"]);
+    assert!(!command.contains(&name.to_string()), "{command:?}");
+
+    ctx.cleanup_box(name);
+}
@@ -0,0 +1,83 @@
+use predicates::prelude::*;
+
+mod common;
+
+fn compose_file(dir: &std::path::Path, yaml: &str) -> std::path::PathBuf {
+    let path = dir.join("boxes.yaml");
+    std::fs::write(&path, yaml).unwrap();
+    path
+}
+
+#[test]
+fn test_compose_up_down_in_dependency_order() {
+    let mut ctx = common::boxlite();
+    let dir = tempfile::tempdir().unwrap();
+    let file = compose_file(
+        dir.path(),
+        r#"
+boxes:
+  - name: compose-app
+    depends_on: [compose-db]
+    options:
+      cmd: [sleep, "300"]
+  - name: compose-db
+    options:
+      cmd: [sleep, "300"]
+    readiness:
+      command: ["true"]
+"#,
+    );
+
+    ctx.cmd
+        .args(["compose", "up", "-f"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout("compose-db\ncompose-app\n");
+
+    ctx.new_cmd()
+        .args(["compose", "down", "--rm", "-f"])
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout("compose-app\ncompose-db\n");
+
+    ctx.new_cmd()
+        .args(["inspect", "compose-db"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_compose_up_rejects_dependency_cycle() {
+    let mut ctx = common::boxlite();
+    let dir = tempfile::tempdir().unwrap();
+    let file = compose_file(
+        dir.path(),
+        r#"
+boxes:
+  - name: compose-a
+    depends_on: [compose-b]
+  - name: compose-b
+    depends_on: [compose-a]
+"#,
+    );
+
+    ctx.cmd
+        .args(["compose", "up", "-f"])
+        .arg(&file)
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("cycle"));
+}
+
+#[test]
+fn test_compose_missing_file() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["compose", "down", "-f", "/nonexistent/boxes.yaml"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Failed to read"));
+}
@@ -33,6 +33,7 @@ fn test_create_named() {
         .arg("alpine:latest")
         .assert()
         .failure()
+        .code(4)
         .stderr(predicate::str::contains("already exists"));
 
     ctx.cleanup_box(name);
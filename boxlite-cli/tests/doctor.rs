@@ -0,0 +1,102 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_doctor_nonexistent_box() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["doctor", "no-such-box-123"])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("No such box"));
+}
+
+#[test]
+fn test_doctor_box_without_failure() {
+    let mut ctx = common::boxlite();
+    let name = "doctor-no-failure";
+    let _ = ctx
+        .cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .output();
+
+    ctx.new_cmd()
+        .args(["doctor", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No start failure recorded"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_doctor_reports_registry_health() {
+    let mut ctx = common::boxlite();
+    let name = "doctor-registries";
+    let _ = ctx
+        .cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .output();
+
+    ctx.new_cmd()
+        .args(["--registry", "mirror.invalid", "doctor", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Registries:"))
+        .stdout(predicate::str::contains(
+            "mirror.invalid: not contacted yet",
+        ));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_doctor_without_box_reports_host_capabilities() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["doctor"])
+        .env("BOXLITE_DISABLE_CAPABILITIES", "userns")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Host capabilities:"))
+        .stdout(predicate::str::contains(
+            "userns: missing (disabled by BOXLITE_DISABLE_CAPABILITIES)",
+        ))
+        .stdout(predicate::str::contains("Box:").not());
+}
+
+#[test]
+fn test_doctor_reports_orphaned_shims() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["doctor"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Orphaned shims:"));
+}
+
+#[test]
+fn test_create_without_kvm_fails_up_front() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["create", "--name", "doctor-no-kvm", "alpine:latest"])
+        .env("BOXLITE_DISABLE_CAPABILITIES", "kvm")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "hardware virtualization is unavailable",
+        ));
+}
+
+#[test]
+fn test_unknown_disabled_capability_is_rejected() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["doctor"])
+        .env("BOXLITE_DISABLE_CAPABILITIES", "gpu")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("BOXLITE_DISABLE_CAPABILITIES"));
+}
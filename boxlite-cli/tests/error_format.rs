@@ -0,0 +1,52 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_json_error_for_missing_box() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["--error-format", "json", "doctor", "no-such-box-123"])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains(
+            r#"{"code":3,"variant":"NotFound","message":"No such box: no-such-box-123","box_id":"no-such-box-123"}"#,
+        ))
+        .stderr(predicate::str::contains("Error:").not());
+}
+
+#[test]
+fn test_json_error_for_invalid_flag_value() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["--error-format=json", "--color", "sometimes", "list"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains(
+            r#"{"code":2,"variant":"Usage","message":"invalid value 'sometimes'"#,
+        ));
+}
+
+#[test]
+fn test_json_error_for_unsupported_dry_run() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["--error-format", "json", "--dry-run", "stop", "some-box"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains(r#""variant":"Usage""#));
+}
+
+#[test]
+fn test_long_help_lists_exit_codes() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exit codes:"))
+        .stdout(predicate::str::contains("125  Internal error"));
+}
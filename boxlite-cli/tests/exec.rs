@@ -133,6 +133,51 @@ fn test_exec_env_override() {
     cleanup(&ctx, &box_id);
 }
 
+#[test]
+fn test_exec_redacts_env_values() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args([
+        "run",
+        "-d",
+        "-e",
+        "API_TOKEN=tok-12345",
+        "--redact-env",
+        "API_TOKEN",
+        "alpine:latest",
+        "sleep",
+        "300",
+    ]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    ctx.new_cmd()
+        .args(["exec", &box_id, "--", "sh", "-c", "echo token=$API_TOKEN"])
+        .assert()
+        .success()
+        .stdout("token=[REDACTED]\n");
+
+    ctx.new_cmd()
+        .args([
+            "exec",
+            "-e",
+            "PASSWORD=hunter2",
+            "--redact-env",
+            "PASSWORD",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "echo $PASSWORD >&2",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("[REDACTED]"))
+        .stderr(predicate::str::contains("hunter2").not());
+
+    cleanup(&ctx, &box_id);
+}
+
 #[test]
 fn test_exec_inherits_box_workdir() {
     let mut ctx = common::boxlite();
@@ -174,6 +219,115 @@ fn test_exec_workdir_override() {
     cleanup(&ctx, &box_id);
 }
 
+#[test]
+fn test_exec_inherit_env_sees_entrypoint_changes() {
+    let mut ctx = common::boxlite();
+
+    // Like sourcing an SDK env file, then handing over to the main process
+    let script = ctx.home.join("entrypoint.sh");
+    std::fs::write(&script, "export SDK_MARKER=sourced\ncd /srv\nexec \"$@\"\n").unwrap();
+
+    ctx.cmd.args([
+        "run",
+        "-d",
+        "--entrypoint-script",
+        script.to_str().unwrap(),
+        "alpine:latest",
+        "sleep",
+        "300",
+    ]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let show = "echo \"$SDK_MARKER $(pwd)\"";
+    ctx.new_cmd()
+        .args(["exec", &box_id, "--", "sh", "-c", show])
+        .assert()
+        .success()
+        .stdout(" /\n");
+    ctx.new_cmd()
+        .args(["exec", "--inherit-env", &box_id, "--", "sh", "-c", show])
+        .assert()
+        .success()
+        .stdout("sourced /srv\n");
+    // Explicit env and workdir still win
+    ctx.new_cmd()
+        .args([
+            "exec",
+            "--inherit-env",
+            "-e",
+            "SDK_MARKER=override",
+            "-w",
+            "/etc",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            show,
+        ])
+        .assert()
+        .success()
+        .stdout("override /etc\n");
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_scratch_is_private_tmpfs() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args([
+        "run",
+        "-d",
+        "--memory",
+        "512",
+        "alpine:latest",
+        "sleep",
+        "300",
+    ]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let script = "touch \"$BOXLITE_SCRATCH_DIR/file\" && ls \"$BOXLITE_SCRATCH_DIR\" \
+                  && awk '$2 == \"/scratch\" { print $3 }' /proc/mounts";
+    ctx.new_cmd()
+        .args([
+            "exec",
+            "--scratch",
+            "16m:/scratch",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            script,
+        ])
+        .assert()
+        .success()
+        .stdout("file\ntmpfs\n");
+    // Gone for the next exec, and never mounted outside the first one
+    ctx.new_cmd()
+        .args([
+            "exec",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "ls -A /scratch; grep -c ' /scratch ' /proc/mounts || true",
+        ])
+        .assert()
+        .success()
+        .stdout("0\n");
+
+    // Larger than the box's memory
+    ctx.new_cmd()
+        .args(["exec", "--scratch", "1g:/scratch", &box_id, "--", "true"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("memory"));
+
+    cleanup(&ctx, &box_id);
+}
+
 #[test]
 fn test_exec_basic_command() {
     let mut ctx = common::boxlite();
@@ -241,6 +395,36 @@ fn test_exec_stdin_redirect() {
     cleanup(&ctx, &box_id);
 }
 
+#[test]
+fn test_exec_stdin_eof_reaches_process() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // `wc` only prints once it reads EOF
+    ctx.new_cmd()
+        .args(["exec", "-i", &box_id, "--", "wc", "-c"])
+        .write_stdin("twelve bytes")
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("12"));
+
+    // The process closes stdin before all input is sent
+    ctx.new_cmd()
+        .args(["exec", "-i", &box_id, "--", "head", "-c", "5"])
+        .write_stdin("x".repeat(1024 * 1024))
+        .timeout(std::time::Duration::from_secs(60))
+        .assert()
+        .success()
+        .stdout("xxxxx")
+        .stderr("");
+
+    cleanup(&ctx, &box_id);
+}
+
 #[test]
 fn test_exec_command_not_found() {
     let mut ctx = common::boxlite();
@@ -335,7 +519,7 @@ fn test_exec_detach() {
         .args(["exec", "-d", &box_id, "--", "sleep", "10"])
         .assert()
         .success()
-        .stdout(""); // Detach mode produces no output
+        .stdout(predicate::str::is_match(r"^\S+\n$").unwrap()); // Only the execution ID
 
     cleanup(&ctx, &box_id);
 }
@@ -352,7 +536,102 @@ fn test_exec_detach_with_tty_allowed() {
         .args(["exec", "-d", "-t", &box_id, "--", "echo", "test"])
         .assert()
         .success()
-        .stdout("");
+        .stdout(predicate::str::contains("test").not());
 
     cleanup(&ctx, &box_id);
 }
+
+#[test]
+fn test_exec_logs_after_detach() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    let output = ctx
+        .new_cmd()
+        .args([
+            "exec",
+            "-d",
+            &box_id,
+            "--",
+            "sh",
+            "-c",
+            "echo to-stdout; echo to-stderr >&2",
+        ])
+        .assert()
+        .success()
+        .get_output()
+        .clone();
+    let exec_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    // Give the command a moment to run; the exec-logs process is a new one
+    std::thread::sleep(std::time::Duration::from_secs(2));
+    ctx.new_cmd()
+        .args(["exec-logs", &box_id, &exec_id])
+        .assert()
+        .success()
+        .stdout("to-stdout\n")
+        .stderr("to-stderr\n");
+
+    ctx.new_cmd()
+        .args(["exec-logs", &box_id, "no-such-exec"])
+        .assert()
+        .failure();
+
+    cleanup(&ctx, &box_id);
+}
+
+#[test]
+fn test_exec_all_reports_each_box() {
+    let ctx = common::boxlite();
+    let web = ["exec-all-web-1", "exec-all-web-2"];
+    let db = "exec-all-db";
+
+    for (name, label) in [(web[0], "tier=web"), (web[1], "tier=web"), (db, "tier=db")] {
+        ctx.new_cmd()
+            .args(["run", "-d", "--name", name, "-l", label])
+            .args(["alpine:latest", "sleep", "300"])
+            .assert()
+            .success();
+    }
+
+    ctx.new_cmd()
+        .args(["exec-all", "--filter", "label=tier=web", "--", "echo", "hi"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "==> {} <==\nhi\n",
+            web[0]
+        )))
+        .stdout(predicate::str::contains(format!(
+            "==> {} <==\nhi\n",
+            web[1]
+        )))
+        .stdout(predicate::str::contains(db).not())
+        .stderr(predicate::str::contains("Succeeded in 2 of 2 box(es)"));
+
+    // A failure in one box doesn't stop the others, but fails the command
+    ctx.new_cmd()
+        .args(["exec", web[0], "--", "touch", "/marker"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args(["exec-all", "--filter", "label=tier=web", "--"])
+        .args(["sh", "-c", "test -f /marker && echo found"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("found"))
+        .stderr(predicate::str::contains(
+            "Failed to run the command in 1 of 2 box(es)",
+        ))
+        .stderr(predicate::str::contains(format!(
+            "{}: exited with code 1",
+            web[1]
+        )));
+
+    for name in [web[0], web[1], db] {
+        cleanup(&ctx, name);
+    }
+}
@@ -0,0 +1,60 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_import_missing_archive() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["import", "/nonexistent/box.boxsnap", "imported"])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("Archive not found"));
+}
+
+#[test]
+fn test_import_conflict_flags_are_exclusive() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["import", "--overwrite", "--auto-name", "box.boxsnap", "web"])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn test_import_overwrite_requires_name() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["import", "--overwrite", "box.boxsnap"])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn test_import_dry_run_missing_archive() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args([
+            "--dry-run",
+            "import",
+            "/nonexistent/box.boxsnap",
+            "imported",
+        ])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("Archive not found"));
+}
+
+#[test]
+fn test_import_dry_run_rejects_url() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["--dry-run", "import", "https://example.com/box.boxsnap"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only supports archives on disk"));
+}
@@ -96,9 +96,13 @@ fn test_info_box_counts() {
         .get("boxesConfigured")
         .and_then(|n| n.as_u64())
         .unwrap_or(0);
+    let boxes_dead = obj
+        .get("boxesDead")
+        .and_then(|n| n.as_u64())
+        .expect("boxesDead");
     assert!(boxes_total >= 1, "expected at least one box after create");
     assert_eq!(
-        boxes_configured + boxes_stopped + boxes_running,
+        boxes_configured + boxes_stopped + boxes_running + boxes_dead,
         boxes_total,
         "box count breakdown must sum to total"
     );
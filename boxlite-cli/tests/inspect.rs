@@ -155,7 +155,7 @@ fn test_inspect_format_template_state() {
     let stdout = String::from_utf8(output.stdout).unwrap();
     // State is an object with status, running, pid; output may be JSON or single line
     assert!(
-        stdout.contains("running") || stdout.contains("configured") || stdout.contains("stopped"),
+        stdout.contains("running") || stdout.contains("created") || stdout.contains("exited"),
         "template {{.State}} output should contain status value; got: {}",
         stdout.trim()
     );
@@ -317,6 +317,223 @@ fn test_inspect_format_template_single_field_id() {
     ctx.cleanup_box(name);
 }
 
+/// `--memory-swap` is reported as Swap in bytes.
+#[test]
+fn test_inspect_reports_swap() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-swap";
+    let create_out = ctx
+        .cmd
+        .args([
+            "create",
+            "--name",
+            name,
+            "--memory-swap",
+            "256",
+            "alpine:latest",
+        ])
+        .output()
+        .unwrap();
+    assert!(create_out.status.success());
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--format", "{{.Swap}}", name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let got = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    assert_eq!(got, (256u64 * 1024 * 1024).to_string());
+
+    ctx.cleanup_box(name);
+}
+
+/// `--timezone` and `--locale` are reported as configured.
+#[test]
+fn test_inspect_reports_timezone_and_locale() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-tz";
+    let create_out = ctx
+        .cmd
+        .args([
+            "create",
+            "--name",
+            name,
+            "--timezone",
+            "Asia/Tokyo",
+            "--locale",
+            "ja_JP.UTF-8",
+            "alpine:latest",
+        ])
+        .output()
+        .unwrap();
+    assert!(create_out.status.success());
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--format", "{{.Timezone}} {{.Locale}}", name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let got = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    assert_eq!(got, "Asia/Tokyo ja_JP.UTF-8");
+
+    ctx.cleanup_box(name);
+}
+
+/// `--read-only` and `--tmpfs` are reported as configured.
+#[test]
+fn test_inspect_reports_read_only_rootfs() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-read-only";
+    let create_out = ctx
+        .cmd
+        .args([
+            "create",
+            "--name",
+            name,
+            "--read-only",
+            "--tmpfs",
+            "/var/cache",
+            "alpine:latest",
+        ])
+        .output()
+        .unwrap();
+    assert!(create_out.status.success());
+
+    let output = ctx
+        .new_cmd()
+        .args([
+            "inspect",
+            "--format",
+            "{{.ReadOnlyRootfs}} {{json .Tmpfs}}",
+            name,
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let got = String::from_utf8(output.stdout).unwrap().trim().to_string();
+    assert_eq!(got, "true [\"/var/cache\"]");
+
+    ctx.cleanup_box(name);
+}
+
+/// `--stop-timeout` is reported as StopTimeout in seconds, 3 by default.
+#[test]
+fn test_inspect_reports_stop_timeout() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-stop-timeout";
+    let create_out = ctx
+        .cmd
+        .args([
+            "create",
+            "--name",
+            name,
+            "--stop-timeout",
+            "30",
+            "alpine:latest",
+        ])
+        .output()
+        .unwrap();
+    assert!(create_out.status.success());
+    let default_name = "inspect-stop-timeout-default";
+    let create_out = ctx
+        .new_cmd()
+        .args(["create", "--name", default_name, "alpine:latest"])
+        .output()
+        .unwrap();
+    assert!(create_out.status.success());
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--format", "json", name, default_name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let boxes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(boxes[0]["StopTimeout"].as_f64(), Some(30.0));
+    assert_eq!(boxes[1]["StopTimeout"].as_f64(), Some(3.0));
+
+    ctx.cleanup_box(name);
+    ctx.cleanup_box(default_name);
+}
+
+/// `--auto-stop` is reported as AutoStop in seconds; IdleRemaining is only
+/// set while the box runs.
+#[test]
+fn test_inspect_reports_auto_stop() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-auto-stop";
+    let create_out = ctx
+        .cmd
+        .args([
+            "create",
+            "--name",
+            name,
+            "--auto-stop",
+            "2h",
+            "alpine:latest",
+        ])
+        .output()
+        .unwrap();
+    assert!(create_out.status.success());
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--format", "json", name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let boxes: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(boxes[0]["AutoStop"].as_f64(), Some(7200.0));
+    assert!(boxes[0]["IdleRemaining"].is_null());
+
+    ctx.cleanup_box(name);
+}
+
+/// `--stop-timeout 0` is rejected.
+#[test]
+fn test_create_rejects_zero_stop_timeout() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["create", "--stop-timeout", "0", "alpine:latest"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("shutdown_grace_period"));
+}
+
+/// A misspelled `--timezone` is a usage error suggesting the right zone.
+#[test]
+fn test_create_rejects_unknown_timezone() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["create", "--timezone", "Europe/Berln", "alpine:latest"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("did you mean Europe/Berlin"));
+}
+
 /// Template alias {{.ID}} and {{.ImageID}} work like {{.Id}} / {{.Image}}.
 #[test]
 fn test_inspect_format_template_alias_id_and_image() {
@@ -514,3 +731,95 @@ fn test_inspect_latest_and_ref_fail() {
         stderr
     );
 }
+
+/// Guest IP/MAC are assigned on first start and survive restarts.
+#[test]
+fn test_inspect_network_settings_stable_across_restart() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-network-stable";
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    let network_settings = |ctx: &common::TestContext| {
+        let output = ctx
+            .new_cmd()
+            .args(["inspect", "--format", "{{json .NetworkSettings}}", name])
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "stderr: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        serde_json::from_str::<serde_json::Value>(stdout.trim()).unwrap()
+    };
+
+    let before = network_settings(&ctx);
+    assert!(!before["IPAddress"].as_str().unwrap().is_empty());
+    assert!(!before["MacAddress"].as_str().unwrap().is_empty());
+    assert_eq!(
+        before["ExtraHosts"][0],
+        "host.boxlite.internal:192.168.127.254"
+    );
+
+    ctx.new_cmd().args(["restart", name]).assert().success();
+
+    let after = network_settings(&ctx);
+    assert_eq!(before, after);
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_inspect_report_requires_start() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-report-created";
+    let _ = ctx
+        .cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .output();
+
+    ctx.new_cmd()
+        .args(["inspect", "--report", name])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has not been started"))
+        .stdout(predicate::str::contains("[]"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_inspect_report_after_run() {
+    let mut ctx = common::boxlite();
+    let name = "inspect-report-run";
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+
+    let output = ctx
+        .new_cmd()
+        .args(["inspect", "--report", name])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "stderr: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let v: serde_json::Value = serde_json::from_str(stdout.trim()).unwrap();
+    let report = &v.as_array().expect("report output should be a JSON array")[0];
+    assert_eq!(report["image"]["reference"], "alpine:latest");
+    assert!(
+        report["image"]["manifest_digest"]
+            .as_str()
+            .unwrap()
+            .starts_with("sha256:")
+    );
+    assert_eq!(report["content_sha256"].as_str().unwrap().len(), 64);
+
+    ctx.cleanup_box(name);
+}
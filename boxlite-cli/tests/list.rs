@@ -48,7 +48,7 @@ fn test_list_json_format() {
 
     // Verify content
     assert!(stdout.contains("alpine:latest"));
-    assert!(stdout.contains("Configured"));
+    assert!(stdout.contains("Created"));
 
     ctx.cleanup_box(name);
 }
@@ -103,7 +103,27 @@ fn test_list_lifecycle() {
         .assert()
         .success()
         .stdout(predicate::str::contains(name))
-        .stdout(predicate::str::contains("Configured"));
+        .stdout(predicate::str::contains("Created"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_list_shows_exit_code_of_stopped_box() {
+    let mut ctx = common::boxlite();
+    let name = "list-exited";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"]);
+    ctx.cmd.assert().success();
+    ctx.new_cmd().args(["stop", name]).assert().success();
+
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name))
+        .stdout(predicate::str::contains("Exited (0)"));
 
     ctx.cleanup_box(name);
 }
@@ -113,3 +133,26 @@ fn test_list_alias_ls() {
     let mut ctx = common::boxlite();
     ctx.cmd.arg("ls").assert().success();
 }
+
+#[test]
+fn test_list_names_format() {
+    let mut ctx = common::boxlite();
+    let name = "list-names-test";
+
+    ctx.cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+
+    // As run by shell completion
+    let output = ctx
+        .new_cmd()
+        .args(["ps", "-a", "-q", "--format", "names"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.lines().any(|line| line == name), "{stdout}");
+    assert!(!stdout.contains("NAMES"));
+
+    ctx.cleanup_box(name);
+}
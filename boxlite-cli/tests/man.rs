@@ -0,0 +1,57 @@
+//! Tests for man page generation (`boxlite man`).
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+fn boxlite_cmd() -> Command {
+    Command::new(assert_cmd::cargo::cargo_bin!("boxlite"))
+}
+
+#[test]
+fn man_prints_root_page() {
+    boxlite_cmd()
+        .arg("man")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(".TH \"BOXLITE\" \"1\""))
+        .stdout(predicate::str::contains(".SH COMMANDS"))
+        .stdout(predicate::str::contains("\\fBboxlite\\-run\\fR(1)"));
+}
+
+#[test]
+fn man_prints_subcommand_page() {
+    boxlite_cmd()
+        .args(["man", "snapshot", "restore"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            ".TH \"BOXLITE\\-SNAPSHOT\\-RESTORE\" \"1\"",
+        ))
+        .stdout(predicate::str::contains("\\fI<BOX>\\fR"))
+        .stdout(predicate::str::contains("\\fBboxlite\\-snapshot\\fR(1)"));
+}
+
+#[test]
+fn man_unknown_command_fails() {
+    boxlite_cmd()
+        .args(["man", "nope"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no such command: nope"));
+}
+
+#[test]
+fn man_dir_writes_a_page_per_command() {
+    let dir = tempfile::tempdir().unwrap();
+    boxlite_cmd()
+        .args(["man", "--dir"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    for page in ["boxlite.1", "boxlite-exec.1", "boxlite-snapshot-create.1"] {
+        let source = std::fs::read_to_string(dir.path().join(page)).unwrap();
+        assert!(source.starts_with(".TH "), "{page}");
+    }
+}
@@ -0,0 +1,54 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_prune_removes_exited_boxes_only() {
+    let mut ctx = common::boxlite();
+    let exited = "prune-boxlite-exited";
+    let running = "prune-boxlite-running";
+
+    ctx.cmd
+        .args(["run", "--name", exited, "alpine:latest", "true"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args([
+            "run",
+            "-d",
+            "--name",
+            running,
+            "alpine:latest",
+            "sleep",
+            "300",
+        ])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--dry-run", "prune"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(exited))
+        .stdout(predicate::str::contains(running).not())
+        .stderr(predicate::str::contains("Would remove 1 box(es)"));
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(exited));
+
+    ctx.new_cmd()
+        .args(["prune", "--force"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Removed 1 box(es)"));
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(exited).not())
+        .stdout(predicate::str::contains(running));
+
+    ctx.cleanup_box(running);
+}
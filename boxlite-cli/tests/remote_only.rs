@@ -0,0 +1,59 @@
+//! Tests for the remote-only build (REST client, no local VM engine).
+//!
+//! Run with `cargo test -p boxlite-cli --no-default-features --features remote-only --test remote_only`;
+//! add `--release` for the size check.
+#![cfg(feature = "remote-only")]
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Ceiling for the release binary. A remote-only build that grows past this has
+/// most likely started pulling in the local engine again.
+const MAX_RELEASE_BINARY_BYTES: u64 = 48 * 1024 * 1024;
+
+fn boxlite_cmd() -> Command {
+    let mut cmd = Command::new(assert_cmd::cargo::cargo_bin!("boxlite"));
+    cmd.env_remove("BOXLITE_HOST");
+    cmd
+}
+
+#[test]
+#[cfg_attr(debug_assertions, ignore = "size ceiling applies to release builds")]
+fn test_binary_size_within_ceiling() {
+    let path = assert_cmd::cargo::cargo_bin!("boxlite");
+    let size = std::fs::metadata(path).unwrap().len();
+    assert!(
+        size <= MAX_RELEASE_BINARY_BYTES,
+        "remote-only binary is {size} bytes, ceiling is {MAX_RELEASE_BINARY_BYTES}"
+    );
+}
+
+#[test]
+fn test_binary_does_not_link_libkrun() {
+    let binary = std::fs::read(assert_cmd::cargo::cargo_bin!("boxlite")).unwrap();
+    let needle = b"krun_create_ctx";
+    assert!(
+        !binary.windows(needle.len()).any(|w| w == needle),
+        "remote-only binary references libkrun"
+    );
+}
+
+#[test]
+fn test_requires_host() {
+    boxlite_cmd()
+        .arg("list")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("BOXLITE_HOST"));
+}
+
+#[test]
+fn test_local_only_command_refused() {
+    boxlite_cmd()
+        .args(["--host", "http://127.0.0.1:9", "images"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "not available in remote-only build",
+        ));
+}
@@ -136,3 +136,119 @@ fn test_rm_unknown() {
         .failure()
         .stderr(predicate::str::contains("not found"));
 }
+
+#[test]
+fn test_rm_filter_force() {
+    let mut ctx = common::boxlite();
+    let tenant_a = ["rm-filter-a-1", "rm-filter-a-2"];
+    let tenant_b = "rm-filter-b";
+
+    for name in tenant_a {
+        ctx.new_cmd()
+            .args(["create", "--name", name, "-l", "tenant=a", "alpine:latest"])
+            .assert()
+            .success();
+    }
+    ctx.cmd
+        .args([
+            "create",
+            "--name",
+            tenant_b,
+            "-l",
+            "tenant=b",
+            "alpine:latest",
+        ])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["rm", "--force", "--filter", "label=tenant=a"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Are you sure").not())
+        .stderr(predicate::str::contains("Removed 2 of 2 box(es)"));
+
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(tenant_b))
+        .stdout(predicate::str::contains(tenant_a[0]).not())
+        .stdout(predicate::str::contains(tenant_a[1]).not());
+
+    ctx.cleanup_box(tenant_b);
+}
+
+#[test]
+fn test_rm_filter_rejects_bad_expression() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["rm", "--force", "--filter", "tier"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("Invalid filter 'tier'"));
+}
+
+#[test]
+fn test_rm_dry_run_keeps_box() {
+    let mut ctx = common::boxlite();
+    let name = "rm-boxlite-dry-run";
+
+    ctx.cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--dry-run", "rm", "--purge", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Would delete {}", name)))
+        .stderr(predicate::str::contains("Would remove 1 box(es)"));
+
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_rm_dry_run_rejects_running_box_without_force() {
+    let mut ctx = common::boxlite();
+    let name = "rm-boxlite-dry-run-running";
+
+    ctx.cmd
+        .args(["run", "-d", "--name", name, "alpine:latest", "sleep", "300"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--dry-run", "rm", name])
+        .assert()
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("cannot remove active box"));
+
+    ctx.new_cmd()
+        .args(["--dry-run", "rm", "--force", name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would stop and"));
+
+    ctx.cleanup_box(name);
+}
+
+#[test]
+fn test_dry_run_rejected_by_unsupported_command() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["--dry-run", "stop", "some-box"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--dry-run is only supported by"));
+}
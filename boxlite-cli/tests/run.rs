@@ -30,6 +30,32 @@ fn test_run_exit_code_125() {
     ctx.cmd.assert().code(125);
 }
 
+#[test]
+fn test_run_once_prints_output_and_exit_code() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.args([
+        "run",
+        "--rm",
+        "--once",
+        "alpine:latest",
+        "sh",
+        "-c",
+        "echo out; echo err >&2; exit 3",
+    ]);
+    ctx.cmd
+        .assert()
+        .code(3)
+        .stdout(predicate::str::contains("out"))
+        .stderr(predicate::str::contains("err"));
+}
+
+#[test]
+fn test_run_once_requires_rm() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.args(["run", "--once", "alpine:latest", "true"]);
+    ctx.cmd.assert().failure();
+}
+
 // ============================================================================
 // Command Execution Error Tests
 // ============================================================================
@@ -237,6 +263,49 @@ fn test_run_with_name() {
     ctx.cmd.assert().success().stdout("helloboxlite\n");
 }
 
+#[test]
+fn test_run_recreate_on_change() {
+    let mut ctx = common::boxlite();
+    let name = "test-recreate-on-change";
+    let run = |ctx: &common::TestContext, mode: &str| {
+        let mut cmd = ctx.new_cmd();
+        cmd.args([
+            "run",
+            "-d",
+            "--recreate-on-change",
+            "--name",
+            name,
+            "-e",
+            &format!("MODE={mode}"),
+            "alpine:latest",
+            "sleep",
+            "300",
+        ]);
+        cmd.assert().success().get_output().clone()
+    };
+
+    let first = run(&ctx, "a");
+    let reused = run(&ctx, "a");
+    assert_eq!(first.stdout, reused.stdout, "same options reuse the box");
+    let recreated = run(&ctx, "b");
+    assert_ne!(first.stdout, recreated.stdout, "changed env recreates it");
+    assert!(String::from_utf8_lossy(&recreated.stderr).contains("env.MODE"));
+
+    ctx.cmd.args(["rm", "--force", name]);
+    ctx.cmd.assert().success();
+}
+
+#[test]
+fn test_run_recreate_on_change_requires_name() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["run", "--recreate-on-change", "alpine:latest", "true"]);
+    ctx.cmd
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--name"));
+}
+
 // ============================================================================
 // Resource Limit Tests
 // ============================================================================
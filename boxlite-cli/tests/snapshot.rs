@@ -0,0 +1,309 @@
+use predicates::prelude::*;
+
+mod common;
+
+/// Run a box and stop it, so it has disks to snapshot.
+fn stopped_box(ctx: &mut common::TestContext) -> String {
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    ctx.new_cmd().args(["stop", &box_id]).assert().success();
+    box_id
+}
+
+#[test]
+fn test_snapshot_branch_requires_name() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["snapshot", "branch", "some-box", "snap1"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--name"));
+}
+
+#[test]
+fn test_snapshot_branch_nonexistent_box() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args([
+            "snapshot",
+            "branch",
+            "no-such-box-123",
+            "snap1",
+            "--name",
+            "branched",
+        ])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("No such box"));
+}
+
+#[test]
+fn test_snapshot_create_nonexistent_box() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args([
+            "snapshot",
+            "create",
+            "no-such-box-123",
+            "snap1",
+            "--keep",
+            "3",
+        ])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("No such box"));
+}
+
+#[test]
+fn test_snapshot_create_rejects_zero_keep() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["snapshot", "create", "some-box", "snap1", "--keep", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--keep must be at least 1"));
+}
+
+#[test]
+fn test_snapshot_create_metadata_flags_conflict() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args([
+            "snapshot",
+            "create",
+            "some-box",
+            "snap1",
+            "--metadata",
+            "--metadata-only",
+        ])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn test_snapshot_ls_nonexistent_box() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["snapshot", "ls", "no-such-box-123"])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("No such box"));
+}
+
+#[test]
+fn test_snapshot_rm_requires_name() {
+    let ctx = common::boxlite();
+    ctx.new_cmd()
+        .args(["snapshot", "rm", "some-box"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("<SNAPSHOTS>"));
+}
+
+#[test]
+fn test_snapshot_lifecycle() {
+    let mut ctx = common::boxlite();
+
+    let box_id = stopped_box(&mut ctx);
+
+    ctx.new_cmd()
+        .args(["snapshot", "create", &box_id, "snap1"])
+        .assert()
+        .success()
+        .stdout("snap1\n");
+
+    ctx.new_cmd()
+        .args(["snapshot", "ls", &box_id])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("NAME")
+                .and(predicate::str::contains("CREATED"))
+                .and(predicate::str::contains("SIZE"))
+                .and(predicate::str::contains("snap1")),
+        );
+
+    ctx.new_cmd()
+        .args(["snapshot", "ls", "-q", &box_id])
+        .assert()
+        .success()
+        .stdout("snap1\n");
+
+    ctx.new_cmd()
+        .args(["snapshot", "restore", "-f", &box_id, "snap1"])
+        .assert()
+        .success()
+        .stdout("snap1\n");
+
+    // The restored disk depends on the snapshot
+    ctx.new_cmd()
+        .args(["snapshot", "rm", &box_id, "snap1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("depends on this snapshot"));
+
+    ctx.cleanup_box(&box_id);
+}
+
+#[test]
+fn test_snapshot_restore_requires_confirmation() {
+    let mut ctx = common::boxlite();
+
+    let box_id = stopped_box(&mut ctx);
+
+    ctx.new_cmd()
+        .args(["snapshot", "create", &box_id, "snap1"])
+        .assert()
+        .success();
+
+    // stdin is not a terminal, so without -f there is no way to confirm
+    ctx.new_cmd()
+        .args(["snapshot", "restore", &box_id, "snap1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+
+    ctx.cleanup_box(&box_id);
+}
+
+#[test]
+fn test_snapshot_restore_dry_run() {
+    let mut ctx = common::boxlite();
+
+    let box_id = stopped_box(&mut ctx);
+
+    ctx.new_cmd()
+        .args(["snapshot", "create", &box_id, "snap1"])
+        .assert()
+        .success();
+
+    // No confirmation is needed because nothing changes
+    ctx.new_cmd()
+        .args(["--dry-run", "snapshot", "restore", &box_id, "snap1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would overwrite"))
+        .stderr(predicate::str::contains("Would restore snapshot snap1"));
+
+    ctx.new_cmd()
+        .args(["--dry-run", "snapshot", "restore", &box_id, "no-such-snap"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+
+    ctx.cleanup_box(&box_id);
+}
+
+#[test]
+fn test_snapshot_restore_metadata_dry_run() {
+    let mut ctx = common::boxlite();
+
+    let box_id = stopped_box(&mut ctx);
+
+    ctx.new_cmd()
+        .args(["snapshot", "create", &box_id, "disks"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args(["snapshot", "create", "--metadata-only", &box_id, "meta"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args(["snapshot", "ls", "--format", "json", &box_id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""Contents": "metadata""#));
+
+    // Nothing changed since, and a metadata-only snapshot overwrites no disks
+    ctx.new_cmd()
+        .args([
+            "--dry-run",
+            "snapshot",
+            "restore",
+            "--metadata",
+            &box_id,
+            "meta",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+
+    ctx.new_cmd()
+        .args([
+            "--dry-run",
+            "snapshot",
+            "restore",
+            "--metadata",
+            &box_id,
+            "disks",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("holds no metadata"));
+
+    ctx.cleanup_box(&box_id);
+}
+
+#[test]
+fn test_snapshot_prune_dry_run() {
+    let mut ctx = common::boxlite();
+
+    let box_id = stopped_box(&mut ctx);
+
+    for snapshot in ["snap1", "snap2"] {
+        ctx.new_cmd()
+            .args(["snapshot", "create", &box_id, snapshot])
+            .assert()
+            .success();
+    }
+
+    // Without --keep or a box policy there is nothing to prune by
+    ctx.new_cmd()
+        .args(["snapshot", "prune", &box_id])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no snapshot retention policy"));
+
+    // snap1 backs snap2's disks, so prune would keep it
+    ctx.new_cmd()
+        .args(["--dry-run", "snapshot", "prune", &box_id, "--keep", "1"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("Would prune 0 snapshot(s)"));
+
+    ctx.new_cmd()
+        .args(["snapshot", "ls", "-q", &box_id])
+        .assert()
+        .success()
+        .stdout("snap2\nsnap1\n");
+
+    ctx.cleanup_box(&box_id);
+}
+
+#[test]
+fn test_snapshot_rejects_running_box() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd.args(["run", "-d", "alpine:latest", "sleep", "300"]);
+    let output = ctx.cmd.assert().success().get_output().clone();
+    let box_id = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    ctx.new_cmd()
+        .args(["snapshot", "create", &box_id, "snap1"])
+        .assert()
+        .failure()
+        .code(5)
+        .stderr(predicate::str::contains("must be stopped"));
+
+    ctx.cleanup_box(&box_id);
+}
@@ -71,3 +71,48 @@ fn test_stop_unknown() {
         .failure()
         .stderr(predicate::str::contains("not found"));
 }
+
+#[test]
+fn test_stop_filter_by_label() {
+    let ctx = common::boxlite();
+    let web = ["stop-filter-web-1", "stop-filter-web-2"];
+    let db = "stop-filter-db";
+
+    for (name, label) in [(web[0], "tier=web"), (web[1], "tier=web"), (db, "tier=db")] {
+        ctx.new_cmd()
+            .args(["run", "-d", "--name", name, "-l", label])
+            .args(["alpine:latest", "sleep", "300"])
+            .assert()
+            .success();
+    }
+
+    ctx.new_cmd()
+        .args(["stop", "--filter", "label=tier=web"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Stopped 2 of 2 box(es)"));
+
+    // Only the db box is still running
+    ctx.new_cmd()
+        .args(["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(db))
+        .stdout(predicate::str::contains(web[0]).not())
+        .stdout(predicate::str::contains(web[1]).not());
+
+    ctx.cleanup_boxes(&[web[0], web[1], db]);
+}
+
+#[test]
+fn test_stop_requires_target_or_selector() {
+    let mut ctx = common::boxlite();
+    ctx.cmd.args(["stop"]).assert().failure();
+
+    ctx.new_cmd()
+        .args(["stop", "--all", "some-box"])
+        .assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
@@ -0,0 +1,66 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_system_df_lists_both_caches() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["system", "df"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("image disks"))
+        .stdout(predicate::str::contains("guest rootfs"))
+        .stdout(predicate::str::contains("RECLAIMABLE"));
+}
+
+#[test]
+fn test_system_df_reports_configured_cap() {
+    let mut ctx = common::boxlite();
+    // Large enough that the shared home's caches are never evicted
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        r#"{"rootfs_cache_max_bytes": 1099511627776}"#,
+    )
+    .unwrap();
+
+    let output = ctx
+        .cmd
+        .args(["--config", config.path().to_str().unwrap()])
+        .args(["system", "df", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let caches: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let caches = caches
+        .as_array()
+        .expect("df --format json should be an array");
+    assert_eq!(caches.len(), 2);
+    assert_eq!(caches[0]["Cache"], "image disks");
+    assert_eq!(caches[0]["Limit"], "none");
+    assert_eq!(caches[1]["Cache"], "guest rootfs");
+    assert_eq!(caches[1]["Limit"], "1024.0 GiB");
+    assert!(caches[1]["Evicted"].as_str().unwrap().starts_with("0 "));
+}
+
+#[test]
+fn test_system_prune_requires_what_to_prune() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["system", "prune"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--orphans"));
+}
+
+#[test]
+fn test_system_prune_orphans() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["system", "prune", "--orphans", "--grace", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("orphaned shim(s), reclaimed"));
+}
@@ -0,0 +1,132 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_template_add_ls_rm() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd
+        .args([
+            "template",
+            "add",
+            "web",
+            "alpine:latest",
+            "-e",
+            "TIER=web",
+            "--memory",
+            "256",
+        ])
+        .assert()
+        .success()
+        .stdout("web\n");
+
+    ctx.new_cmd()
+        .args(["template", "add", "web", "alpine:latest"])
+        .assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("already exists"));
+
+    ctx.new_cmd()
+        .args(["template", "ls"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("web").and(predicate::str::contains("256 MiB")));
+
+    ctx.new_cmd()
+        .args(["template", "rm", "web"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["template", "ls", "-q"])
+        .assert()
+        .success()
+        .stdout("");
+
+    ctx.new_cmd()
+        .args(["template", "rm", "web"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not found"));
+}
+
+#[test]
+fn test_template_add_rejects_invalid_name() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["template", "add", "my web", "alpine:latest"])
+        .assert()
+        .failure()
+        .code(2);
+}
+
+#[test]
+fn test_run_from_template_with_env_override() {
+    let mut ctx = common::boxlite();
+
+    ctx.cmd
+        .args([
+            "template",
+            "add",
+            "greeter",
+            "alpine:latest",
+            "-e",
+            "TIER=web",
+            "-e",
+            "TENANT=none",
+        ])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args([
+            "run",
+            "--rm",
+            "--template",
+            "greeter",
+            "-e",
+            "TENANT=acme",
+            "sh",
+            "-c",
+            "echo $TIER-$TENANT",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("web-acme"));
+}
+
+#[test]
+fn test_run_from_unknown_template_fails() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["run", "--rm", "--template", "missing", "true"])
+        .assert()
+        .failure()
+        .code(3)
+        .stderr(predicate::str::contains("not found"));
+}
+
+#[test]
+fn test_templates_imported_from_config() {
+    let mut ctx = common::boxlite();
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        r#"{"templates": [{"name": "from-config", "options": {"rootfs": {"Image": "alpine:latest"}}}]}"#,
+    )
+    .unwrap();
+
+    ctx.cmd
+        .args([
+            "--config",
+            config.path().to_str().unwrap(),
+            "template",
+            "ls",
+            "-q",
+        ])
+        .assert()
+        .success()
+        .stdout("from-config\n");
+}
@@ -0,0 +1,97 @@
+use predicates::prelude::*;
+
+mod common;
+
+/// Write a config enabling a one-hour trash retention.
+fn trash_config() -> tempfile::NamedTempFile {
+    let config = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(
+        config.path(),
+        r#"{"trash_retention": {"secs": 3600, "nanos": 0}}"#,
+    )
+    .unwrap();
+    config
+}
+
+#[test]
+fn test_rm_moves_box_to_trash_and_restore_brings_it_back() {
+    let mut ctx = common::boxlite();
+    let config = trash_config();
+    let config = config.path().to_str().unwrap();
+    let name = "trash-restore";
+
+    ctx.cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--config", config, "rm", name])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--config", config, "trash", "ls"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name));
+
+    // The name is free while the box is in the trash
+    ctx.new_cmd()
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+    ctx.new_cmd()
+        .args(["--config", config, "trash", "restore", name])
+        .assert()
+        .failure();
+    ctx.new_cmd()
+        .args(["--config", config, "rm", "--purge", name])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--config", config, "trash", "restore", name])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["list", "-a"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name));
+
+    ctx.new_cmd()
+        .args(["rm", "--purge", "--force", name])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_trash_purge_deletes_entry() {
+    let mut ctx = common::boxlite();
+    let config = trash_config();
+    let config = config.path().to_str().unwrap();
+    let name = "trash-purge";
+
+    ctx.cmd
+        .args(["create", "--name", name, "alpine:latest"])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--config", config, "rm", name])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--config", config, "trash", "purge", name])
+        .assert()
+        .success();
+
+    ctx.new_cmd()
+        .args(["--config", config, "trash", "ls"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(name).not());
+}
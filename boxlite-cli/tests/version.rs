@@ -0,0 +1,48 @@
+use predicates::prelude::*;
+
+mod common;
+
+#[test]
+fn test_version_prints_crate_version() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .arg("version")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("boxlite "));
+}
+
+#[test]
+fn test_version_verbose_lists_components() {
+    let mut ctx = common::boxlite();
+    ctx.cmd
+        .args(["version", "--verbose"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Version:"))
+        .stdout(predicate::str::contains("libkrun:"))
+        .stdout(predicate::str::contains("Guest hash:"))
+        .stdout(predicate::str::contains("Shim:"));
+}
+
+#[test]
+fn test_version_json() {
+    let mut ctx = common::boxlite();
+    let assert = ctx
+        .cmd
+        .args(["version", "--format", "json"])
+        .assert()
+        .success();
+    let stdout = std::str::from_utf8(&assert.get_output().stdout).unwrap();
+    let json: serde_json::Value = serde_json::from_str(stdout).expect("valid JSON");
+    for key in [
+        "version",
+        "git_commit",
+        "libkrun",
+        "shim_path",
+        "os",
+        "arch",
+    ] {
+        assert!(json.get(key).is_some(), "missing key {key}");
+    }
+}
@@ -0,0 +1,651 @@
+//! Checks for unpacking untrusted tar archives.
+//!
+//! Archives reach the runtime from outside its control: copies into and out
+//! of a box, imported boxes, image layers. [`ArchiveGuard`] vets every entry
+//! before it is unpacked:
+//!
+//! - paths must be relative and must not contain `..`
+//! - symlinks must not point above the extraction root, and no entry may be
+//!   unpacked through a symlink the archive itself created
+//! - hardlinks must name a path inside the extraction root
+//! - block and character devices are skipped unless explicitly allowed
+//! - the total size of the entries (sparse files at their full size) and
+//!   their number are capped
+//!
+//! A violation fails with [`BoxliteError::UnsafeArchive`] naming the entry.
+
+use std::collections::HashSet;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tar::{Archive, Entry, EntryType};
+
+use crate::errors::{BoxliteError, BoxliteResult};
+use crate::generated::UploadLimits;
+
+/// Prefix of the display form of [`BoxliteError::UnsafeArchive`].
+const UNSAFE_ARCHIVE_PREFIX: &str = "unsafe archive: ";
+
+/// Most symlinks followed while resolving one path in [`resolve_in_root`].
+const MAX_SYMLINK_HOPS: usize = 40;
+
+/// Caps applied while unpacking an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ArchiveLimits {
+    /// Total size of the entries, in bytes. Sparse files count at their
+    /// full size, not the size they take in the archive.
+    pub max_bytes: u64,
+    /// Number of entries, directories included.
+    pub max_entries: u64,
+    /// Create block and character devices instead of skipping them.
+    pub allow_devices: bool,
+}
+
+impl ArchiveLimits {
+    /// Default cap on the total size of the entries (64 GiB).
+    pub const DEFAULT_MAX_BYTES: u64 = 64 << 30;
+    /// Default cap on the number of entries.
+    pub const DEFAULT_MAX_ENTRIES: u64 = 1_000_000;
+
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn max_entries(mut self, max_entries: u64) -> Self {
+        self.max_entries = max_entries;
+        self
+    }
+
+    pub fn allow_devices(mut self, allow: bool) -> Self {
+        self.allow_devices = allow;
+        self
+    }
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+            max_entries: Self::DEFAULT_MAX_ENTRIES,
+            allow_devices: false,
+        }
+    }
+}
+
+impl From<ArchiveLimits> for UploadLimits {
+    fn from(limits: ArchiveLimits) -> Self {
+        Self {
+            max_bytes: limits.max_bytes,
+            max_entries: limits.max_entries,
+            allow_devices: limits.allow_devices,
+        }
+    }
+}
+
+impl From<UploadLimits> for ArchiveLimits {
+    fn from(limits: UploadLimits) -> Self {
+        Self {
+            max_bytes: limits.max_bytes,
+            max_entries: limits.max_entries,
+            allow_devices: limits.allow_devices,
+        }
+    }
+}
+
+/// What to do with an entry that passed [`ArchiveGuard::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryAction {
+    Unpack,
+    /// A device node the limits do not allow.
+    Skip,
+}
+
+/// Vets the entries of one archive, in order, against [`ArchiveLimits`].
+#[derive(Debug)]
+pub struct ArchiveGuard {
+    limits: ArchiveLimits,
+    bytes: u64,
+    entries: u64,
+    /// Symlinks created by earlier entries, relative to the root.
+    symlinks: HashSet<PathBuf>,
+}
+
+impl ArchiveGuard {
+    pub fn new(limits: ArchiveLimits) -> Self {
+        Self {
+            limits,
+            bytes: 0,
+            entries: 0,
+            symlinks: HashSet::new(),
+        }
+    }
+
+    /// Check the next entry of the archive.
+    pub fn check<R: Read>(&mut self, entry: &Entry<'_, R>) -> BoxliteResult<EntryAction> {
+        let path = entry.path().map_err(|e| {
+            unsafe_entry(
+                &String::from_utf8_lossy(&entry.path_bytes()),
+                &format!("has an unreadable path: {}", e),
+            )
+        })?;
+        let header = entry.header();
+        let kind = header.entry_type();
+        // For sparse files this is the size once expanded
+        let size = header
+            .size()
+            .map_err(|e| unsafe_entry(&path.display(), &format!("has an invalid size: {}", e)))?;
+        let link = match kind {
+            EntryType::Link | EntryType::Symlink => entry.link_name().map_err(|e| {
+                unsafe_entry(&path.display(), &format!("has an unreadable link: {}", e))
+            })?,
+            _ => None,
+        };
+        self.check_entry(&path, kind, size, link.as_deref())
+    }
+
+    fn check_entry(
+        &mut self,
+        path: &Path,
+        kind: EntryType,
+        size: u64,
+        link: Option<&Path>,
+    ) -> BoxliteResult<EntryAction> {
+        self.count(path, size)?;
+
+        let name = path.display();
+        let relative = relative_path(path)
+            .ok_or_else(|| unsafe_entry(&name, "is absolute or contains '..'"))?;
+        self.check_not_through_symlink(&name, &relative)?;
+
+        match kind {
+            EntryType::Symlink => {
+                let target =
+                    link.ok_or_else(|| unsafe_entry(&name, "is a symlink without target"))?;
+                // Absolute targets resolve inside the box, or the image,
+                // whose root the archive is unpacked at
+                let parent = relative.parent().unwrap_or(Path::new(""));
+                if !target.is_absolute() && !stays_inside(parent, target) {
+                    return Err(unsafe_entry(
+                        &name,
+                        &format!(
+                            "is a symlink to {}, outside the extraction root",
+                            target.display()
+                        ),
+                    ));
+                }
+                self.symlinks.insert(relative);
+            }
+            EntryType::Link => {
+                let target =
+                    link.ok_or_else(|| unsafe_entry(&name, "is a hardlink without target"))?;
+                let target = relative_path(target).ok_or_else(|| {
+                    unsafe_entry(
+                        &name,
+                        &format!(
+                            "is a hardlink to {}, outside the extraction root",
+                            target.display()
+                        ),
+                    )
+                })?;
+                self.check_not_through_symlink(&name, &target)?;
+            }
+            EntryType::Block | EntryType::Char if !self.limits.allow_devices => {
+                return Ok(EntryAction::Skip);
+            }
+            _ => {}
+        }
+        Ok(EntryAction::Unpack)
+    }
+
+    /// Count an entry of `size` bytes against the caps only, for callers
+    /// that sanitize paths and links on their own.
+    pub fn count(&mut self, path: &Path, size: u64) -> BoxliteResult<()> {
+        self.entries += 1;
+        if self.entries > self.limits.max_entries {
+            return Err(unsafe_entry(
+                &path.display(),
+                &format!("exceeds the limit of {} entries", self.limits.max_entries),
+            ));
+        }
+        self.bytes = self.bytes.saturating_add(size);
+        if self.bytes > self.limits.max_bytes {
+            return Err(unsafe_entry(
+                &path.display(),
+                &format!(
+                    "exceeds the limit of {} bytes of content",
+                    self.limits.max_bytes
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Refuse a path below a symlink an earlier entry created: unpacking it
+    /// would write wherever the link points.
+    fn check_not_through_symlink(
+        &self,
+        name: &impl std::fmt::Display,
+        relative: &Path,
+    ) -> BoxliteResult<()> {
+        match relative
+            .ancestors()
+            .skip(1)
+            .find(|dir| self.symlinks.contains(*dir))
+        {
+            Some(link) => Err(unsafe_entry(
+                name,
+                &format!("is unpacked through the symlink {}", link.display()),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Unpack `archive` into the directory `dest` like `tar::Archive::unpack`,
+/// checking every entry with an [`ArchiveGuard`] first.
+///
+/// Directories are finished last, so their permissions do not block the
+/// entries inside them.
+pub fn unpack<R: Read>(
+    archive: &mut Archive<R>,
+    dest: &Path,
+    limits: ArchiveLimits,
+) -> BoxliteResult<()> {
+    let io_err =
+        |e: std::io::Error| BoxliteError::Storage(format!("failed to extract archive: {}", e));
+    std::fs::create_dir_all(dest).map_err(io_err)?;
+
+    let mut guard = ArchiveGuard::new(limits);
+    let mut dirs = Vec::new();
+    for entry in archive.entries().map_err(io_err)? {
+        let mut entry = entry.map_err(io_err)?;
+        if guard.check(&entry)? == EntryAction::Skip {
+            continue;
+        }
+        if entry.header().entry_type() == EntryType::Directory {
+            dirs.push(entry);
+            continue;
+        }
+        entry.unpack_in(dest).map_err(io_err)?;
+    }
+    for mut dir in dirs {
+        dir.unpack_in(dest).map_err(io_err)?;
+    }
+    Ok(())
+}
+
+/// Resolve `relative` under `root` as if `root` were `/`: symlinks met on
+/// the way are followed, absolute targets restart at `root`, and `..`
+/// never climbs above it.
+///
+/// Only symlinks to directories are followed. The rest of the path is
+/// appended as is from the first component that does not exist or that is
+/// a symlink to something else, so the caller can create or replace it.
+pub fn resolve_in_root(root: &Path, relative: &Path) -> BoxliteResult<PathBuf> {
+    let mut resolved = PathBuf::new();
+    let mut pending: Vec<PathBuf> = vec![relative.to_path_buf()];
+    let mut hops = 0;
+    let mut literal = false;
+
+    while let Some(path) = pending.pop() {
+        let mut components = path.components();
+        while let Some(component) = components.next() {
+            match component {
+                Component::RootDir | Component::Prefix(_) => resolved.clear(),
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                Component::Normal(name) => {
+                    let next = resolved.join(name);
+                    if literal {
+                        resolved = next;
+                        continue;
+                    }
+                    let on_disk = root.join(&next);
+                    let is_link = std::fs::symlink_metadata(&on_disk)
+                        .map(|m| m.file_type().is_symlink())
+                        .unwrap_or(false);
+                    if !is_link {
+                        literal = !on_disk.exists();
+                        resolved = next;
+                        continue;
+                    }
+
+                    hops += 1;
+                    if hops > MAX_SYMLINK_HOPS {
+                        return Err(unsafe_entry(
+                            &relative.display(),
+                            "resolves through too many symlinks",
+                        ));
+                    }
+                    let target = std::fs::read_link(&on_disk)?;
+                    let mut through = resolved.clone();
+                    if target.is_absolute() {
+                        through.clear();
+                    }
+                    let through = normalize_in_root(&through.join(&target));
+                    if root.join(&through).is_dir() {
+                        // Continue from the target with what is left
+                        pending.push(components.as_path().to_path_buf());
+                        pending.push(target);
+                        break;
+                    }
+                    literal = true;
+                    resolved = next;
+                }
+            }
+        }
+    }
+    Ok(root.join(resolved))
+}
+
+/// `path` without `.`, with `..` applied and clamped at the root.
+fn normalize_in_root(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::RootDir | Component::Prefix(_) => normalized.clear(),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::Normal(name) => normalized.push(name),
+        }
+    }
+    normalized
+}
+
+/// Recover [`BoxliteError::UnsafeArchive`] from its display form, as it
+/// arrives from the guest or a REST server.
+pub fn parse_unsafe_archive(message: &str) -> Option<BoxliteError> {
+    message
+        .strip_prefix(UNSAFE_ARCHIVE_PREFIX)
+        .map(|reason| BoxliteError::UnsafeArchive(reason.to_string()))
+}
+
+/// `path` without `.` components, or `None` if it is absolute or has `..`.
+fn relative_path(path: &Path) -> Option<PathBuf> {
+    let mut relative = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => relative.push(name),
+            Component::CurDir => {}
+            Component::RootDir | Component::Prefix(_) | Component::ParentDir => return None,
+        }
+    }
+    Some(relative)
+}
+
+/// Whether the relative symlink `target`, placed in the directory `dir`
+/// under the root, points inside the root.
+fn stays_inside(dir: &Path, target: &Path) -> bool {
+    let mut depth = dir.components().count();
+    for component in target.components() {
+        match component {
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+            Component::ParentDir => match depth.checked_sub(1) {
+                Some(up) => depth = up,
+                None => return false,
+            },
+            Component::RootDir | Component::Prefix(_) => return true,
+        }
+    }
+    true
+}
+
+fn unsafe_entry(name: &impl std::fmt::Display, reason: &str) -> BoxliteError {
+    BoxliteError::UnsafeArchive(format!("entry '{}' {}", name, reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tar::{Builder, Header};
+
+    /// A hand-built malicious archive.
+    struct Corpus(Builder<Vec<u8>>);
+
+    impl Corpus {
+        fn new() -> Self {
+            Self(Builder::new(Vec::new()))
+        }
+
+        fn header(kind: EntryType, size: u64) -> Header {
+            let mut header = Header::new_gnu();
+            header.set_entry_type(kind);
+            header.set_size(size);
+            header.set_mode(0o644);
+            header
+        }
+
+        /// Append an entry with a raw name, bypassing the builder's own
+        /// refusal of `..` and absolute paths.
+        fn raw(mut self, name: &str, mut header: Header, data: &[u8]) -> Self {
+            let bytes = name.as_bytes();
+            header.as_old_mut().name[..bytes.len()].copy_from_slice(bytes);
+            header.set_cksum();
+            self.0.append(&header, data).unwrap();
+            self
+        }
+
+        fn file(self, name: &str, data: &[u8]) -> Self {
+            let header = Self::header(EntryType::Regular, data.len() as u64);
+            self.raw(name, header, data)
+        }
+
+        fn dir(self, name: &str) -> Self {
+            self.raw(name, Self::header(EntryType::Directory, 0), &[])
+        }
+
+        fn link(self, kind: EntryType, name: &str, target: &str) -> Self {
+            let mut header = Self::header(kind, 0);
+            let bytes = target.as_bytes();
+            header.as_old_mut().linkname[..bytes.len()].copy_from_slice(bytes);
+            self.raw(name, header, &[])
+        }
+
+        fn device(self, name: &str) -> Self {
+            let mut header = Self::header(EntryType::Char, 0);
+            header.set_device_major(1).unwrap();
+            header.set_device_minor(3).unwrap();
+            self.raw(name, header, &[])
+        }
+
+        /// A GNU sparse file of `real_size` bytes stored in one block.
+        fn sparse(self, name: &str, real_size: u64) -> Self {
+            let mut header = Self::header(EntryType::GNUSparse, 512);
+            let gnu = header.as_gnu_mut().unwrap();
+            gnu.set_real_size(real_size);
+            gnu.sparse[0].set_offset(real_size - 512);
+            gnu.sparse[0].set_length(512);
+            self.raw(name, header, &[0u8; 512])
+        }
+
+        fn unpack(self, dest: &Path, limits: ArchiveLimits) -> BoxliteResult<()> {
+            let bytes = self.0.into_inner().unwrap();
+            unpack(&mut Archive::new(bytes.as_slice()), dest, limits)
+        }
+    }
+
+    fn assert_unsafe(result: BoxliteResult<()>, entry: &str) {
+        match result {
+            Err(BoxliteError::UnsafeArchive(reason)) => {
+                assert!(reason.contains(&format!("'{}'", entry)), "{reason}")
+            }
+            other => panic!("expected UnsafeArchive for {entry}, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_unpacks_benign_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        Corpus::new()
+            .dir("app/")
+            .file("app/main.py", b"print(1)")
+            .file("./app/./data.txt", b"data")
+            .link(EntryType::Symlink, "app/current", "../app/main.py")
+            .link(EntryType::Symlink, "app/python", "/usr/bin/python3")
+            .link(EntryType::Link, "app/copy.py", "app/main.py")
+            .unpack(dir.path(), ArchiveLimits::default())
+            .unwrap();
+
+        let app = dir.path().join("app");
+        assert_eq!(std::fs::read(app.join("main.py")).unwrap(), b"print(1)");
+        assert_eq!(std::fs::read(app.join("data.txt")).unwrap(), b"data");
+        assert_eq!(std::fs::read(app.join("copy.py")).unwrap(), b"print(1)");
+        assert_eq!(
+            std::fs::read_link(app.join("current")).unwrap(),
+            Path::new("../app/main.py")
+        );
+    }
+
+    #[test]
+    fn test_rejects_traversal() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("root");
+        let result = Corpus::new()
+            .file("../escaped", b"x")
+            .unpack(&root, ArchiveLimits::default());
+        assert_unsafe(result, "../escaped");
+        assert!(!dir.path().join("escaped").exists());
+
+        let result = Corpus::new()
+            .file("a/../../escaped", b"x")
+            .unpack(&root, ArchiveLimits::default());
+        assert_unsafe(result, "a/../../escaped");
+    }
+
+    #[test]
+    fn test_rejects_absolute_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = Corpus::new()
+            .file("/tmp/boxlite-absolute", b"x")
+            .unpack(dir.path(), ArchiveLimits::default());
+        assert_unsafe(result, "/tmp/boxlite-absolute");
+    }
+
+    #[test]
+    fn test_rejects_symlink_swap() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        std::fs::create_dir(&outside).unwrap();
+
+        let result = Corpus::new()
+            .link(EntryType::Symlink, "evil", outside.to_str().unwrap())
+            .file("evil/planted", b"x")
+            .unpack(&dir.path().join("root"), ArchiveLimits::default());
+        assert_unsafe(result, "evil/planted");
+        assert!(!outside.join("planted").exists());
+    }
+
+    #[test]
+    fn test_rejects_escaping_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = Corpus::new()
+            .link(EntryType::Symlink, "app/up", "../../etc")
+            .unpack(dir.path(), ArchiveLimits::default());
+        assert_unsafe(result, "app/up");
+    }
+
+    #[test]
+    fn test_rejects_escaping_hardlink() {
+        let dir = tempfile::tempdir().unwrap();
+        for target in ["../../etc/passwd", "/etc/passwd"] {
+            let result = Corpus::new()
+                .link(EntryType::Link, "passwd", target)
+                .unpack(dir.path(), ArchiveLimits::default());
+            assert_unsafe(result, "passwd");
+        }
+    }
+
+    #[test]
+    fn test_rejects_hardlink_through_symlink() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = Corpus::new()
+            .link(EntryType::Symlink, "etc", "/etc")
+            .link(EntryType::Link, "shadow", "etc/shadow")
+            .unpack(dir.path(), ArchiveLimits::default());
+        assert_unsafe(result, "shadow");
+    }
+
+    #[test]
+    fn test_rejects_huge_sparse_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = Corpus::new()
+            .sparse("bomb", 1 << 40)
+            .unpack(dir.path(), ArchiveLimits::default());
+        assert_unsafe(result, "bomb");
+        assert!(!dir.path().join("bomb").exists());
+    }
+
+    #[test]
+    fn test_caps_total_size_and_entry_count() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = Corpus::new()
+            .file("a", &[0u8; 600])
+            .file("b", &[0u8; 600])
+            .unpack(dir.path(), ArchiveLimits::default().max_bytes(1000));
+        assert_unsafe(result, "b");
+
+        let dir = tempfile::tempdir().unwrap();
+        let result = Corpus::new()
+            .file("a", b"")
+            .file("b", b"")
+            .file("c", b"")
+            .unpack(dir.path(), ArchiveLimits::default().max_entries(2));
+        assert_unsafe(result, "c");
+    }
+
+    #[test]
+    fn test_skips_devices_unless_allowed() {
+        let dir = tempfile::tempdir().unwrap();
+        Corpus::new()
+            .device("null")
+            .file("after", b"x")
+            .unpack(dir.path(), ArchiveLimits::default())
+            .unwrap();
+        assert!(std::fs::symlink_metadata(dir.path().join("null")).is_err());
+        assert!(dir.path().join("after").exists());
+
+        let mut guard = ArchiveGuard::new(ArchiveLimits::default().allow_devices(true));
+        let action = guard
+            .check_entry(Path::new("null"), EntryType::Char, 0, None)
+            .unwrap();
+        assert_eq!(action, EntryAction::Unpack);
+    }
+
+    #[test]
+    fn test_resolve_in_root_stays_in_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("run")).unwrap();
+        std::fs::create_dir_all(root.join("usr/lib")).unwrap();
+        std::fs::create_dir(root.join("var")).unwrap();
+        std::os::unix::fs::symlink("/run", root.join("var/run")).unwrap();
+        std::os::unix::fs::symlink("usr/lib", root.join("lib")).unwrap();
+        std::os::unix::fs::symlink("../../../..", root.join("usr/up")).unwrap();
+        std::fs::write(root.join("file"), b"x").unwrap();
+        std::os::unix::fs::symlink("/file", root.join("to-file")).unwrap();
+
+        let resolve = |path: &str| resolve_in_root(root, Path::new(path)).unwrap();
+        assert_eq!(resolve("var/run/app.pid"), root.join("run/app.pid"));
+        assert_eq!(resolve("lib/x/y.so"), root.join("usr/lib/x/y.so"));
+        assert_eq!(resolve("usr/up/etc/passwd"), root.join("etc/passwd"));
+        assert_eq!(resolve("new/dir/file"), root.join("new/dir/file"));
+        // Not followed: the caller replaces the link
+        assert_eq!(resolve("to-file/x"), root.join("to-file/x"));
+    }
+
+    #[test]
+    fn test_parse_unsafe_archive() {
+        let err = unsafe_entry(&"../x", "is absolute or contains '..'");
+        let parsed = parse_unsafe_archive(&err.to_string()).unwrap();
+        assert_eq!(parsed.to_string(), err.to_string());
+        assert!(parse_unsafe_archive("storage error: disk full").is_none());
+    }
+}
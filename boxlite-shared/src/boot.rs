@@ -0,0 +1,119 @@
+//! Guest boot phase markers.
+//!
+//! The guest agent writes one marker line to its console (stderr) as it
+//! reaches each boot phase. The host captures the console in `console.log`
+//! and, when start fails, scans it for the last marker to report how far
+//! boot progressed.
+
+use serde::{Deserialize, Serialize};
+
+/// Prefix of a boot phase marker line: `[BOXLITE-PHASE] <phase>`.
+pub const PHASE_MARKER: &str = "[BOXLITE-PHASE]";
+
+/// Prefix of a line echoing the container init process's early stderr.
+pub const CONTAINER_STDERR_MARKER: &str = "[BOXLITE-CONTAINER-STDERR]";
+
+/// Guest boot phases, in the order they are reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BootPhase {
+    /// Kernel handed control to the guest agent (first userspace code).
+    KernelHandoff,
+    /// Guest agent gRPC server is up and about to notify the host.
+    AgentStarted,
+    /// Container rootfs is mounted into the OCI bundle.
+    RootfsMounted,
+    /// Container init process (entrypoint) was spawned.
+    ContainerSpawned,
+}
+
+impl BootPhase {
+    /// All phases in boot order.
+    pub const ALL: [BootPhase; 4] = [
+        BootPhase::KernelHandoff,
+        BootPhase::AgentStarted,
+        BootPhase::RootfsMounted,
+        BootPhase::ContainerSpawned,
+    ];
+
+    /// Stable string form used in marker lines.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BootPhase::KernelHandoff => "kernel-handoff",
+            BootPhase::AgentStarted => "agent-started",
+            BootPhase::RootfsMounted => "rootfs-mounted",
+            BootPhase::ContainerSpawned => "container-spawned",
+        }
+    }
+
+    /// Parse the string form produced by [`BootPhase::as_str`].
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+
+    /// The phase expected after this one, if any.
+    pub fn next(&self) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p > self)
+    }
+
+    /// Console line announcing this phase.
+    pub fn marker_line(&self) -> String {
+        format!("{} {}", PHASE_MARKER, self.as_str())
+    }
+
+    /// Extract a phase from a console line.
+    ///
+    /// The marker may appear anywhere in the line, since the console can
+    /// prefix output (timestamps, kernel log levels).
+    pub fn from_marker_line(line: &str) -> Option<Self> {
+        let (_, rest) = line.split_once(PHASE_MARKER)?;
+        Self::parse(rest.trim())
+    }
+}
+
+impl std::fmt::Display for BootPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_line_roundtrip() {
+        for phase in BootPhase::ALL {
+            assert_eq!(
+                BootPhase::from_marker_line(&phase.marker_line()),
+                Some(phase)
+            );
+        }
+    }
+
+    #[test]
+    fn test_from_marker_line_with_console_prefix() {
+        assert_eq!(
+            BootPhase::from_marker_line("[    0.512] [BOXLITE-PHASE] agent-started\r"),
+            Some(BootPhase::AgentStarted)
+        );
+        assert_eq!(BootPhase::from_marker_line("[BOOT] starting"), None);
+        assert_eq!(BootPhase::from_marker_line("[BOXLITE-PHASE] bogus"), None);
+    }
+
+    #[test]
+    fn test_phase_order_and_next() {
+        assert!(BootPhase::KernelHandoff < BootPhase::ContainerSpawned);
+        assert_eq!(
+            BootPhase::KernelHandoff.next(),
+            Some(BootPhase::AgentStarted)
+        );
+        assert_eq!(BootPhase::ContainerSpawned.next(), None);
+    }
+
+    #[test]
+    fn test_serde_kebab_case() {
+        let json = serde_json::to_string(&BootPhase::RootfsMounted).unwrap();
+        assert_eq!(json, "\"rootfs-mounted\"");
+    }
+}
@@ -12,6 +12,15 @@ pub mod container {
 
     /// Default RLIMIT_NOFILE hard limit
     pub const RLIMIT_NOFILE_HARD: u64 = 1024;
+
+    /// Container path of the entrypoint wrapper script, when the box has one
+    ///
+    /// Like the built-in init it lives on the per-container `/dev` tmpfs, so
+    /// nothing is written to the image rootfs.
+    pub const ENTRYPOINT_SCRIPT_PATH: &str = "/dev/boxlite-entrypoint.sh";
+
+    /// Env var set to the path of an exec's scratch directory, if it has one
+    pub const SCRATCH_DIR_ENV: &str = "BOXLITE_SCRATCH_DIR";
 }
 
 /// Network constants
@@ -40,6 +49,97 @@ pub mod executor {
     pub const CONTAINER_KEY: &str = "container";
 }
 
+/// Prepared command constants
+pub mod prepared {
+    /// `ExecError.reason` returned by ExecPrepared for unknown templates
+    /// (e.g. after the guest agent restarted). Clients re-register and retry.
+    pub const NOT_FOUND_REASON: &str = "prepared_not_found";
+
+    /// `ExecError.reason` returned by Prepare when the guest already holds
+    /// `MAX_TEMPLATES` templates. Nothing is registered.
+    pub const LIMIT_REASON: &str = "prepared_limit";
+
+    /// Most templates a guest agent holds at once
+    pub const MAX_TEMPLATES: usize = 1024;
+}
+
+/// Guest log retrieval limits
+pub mod guest_logs {
+    /// Most kernel log lines returned by `Guest.Dmesg`
+    pub const MAX_DMESG_LINES: u32 = 10_000;
+
+    /// Size of the agent's in-memory log buffer, and the most bytes
+    /// returned by `Guest.AgentLog`
+    pub const MAX_AGENT_LOG_BYTES: u32 = 1024 * 1024;
+}
+
+/// Filesystem freeze of `Guest.Sync` (`LiteBox::sync()`)
+pub mod freeze {
+    /// Longest freeze cap the agent accepts
+    pub const MAX_CAP_MS: u32 = 10_000;
+}
+
+/// Core dump capture (`BoxOptions::capture_core_dumps`)
+pub mod core_dumps {
+    /// Container directory the guest writes core files into
+    pub const DIR: &str = "/var/crash/boxlite";
+
+    /// Largest core file kept; longer dumps are truncated (also the
+    /// container's RLIMIT_CORE)
+    pub const MAX_BYTES: u64 = 256 * 1024 * 1024;
+
+    /// Most core files kept; the oldest are removed first
+    pub const MAX_COUNT: usize = 5;
+}
+
+/// Swap inside the VM (`BoxOptions::swap_mib`)
+pub mod swap {
+    /// Swap file on the container disk, relative to the container root
+    pub const FILE: &str = "/.boxlite-swap";
+}
+
+/// Guest agent protocol versioning
+///
+/// The agent advertises `VERSION` in `PingResponse.protocol_version`. The host
+/// accepts any agent in `MIN_SUPPORTED..=VERSION`, so a guest binary can be
+/// updated without rebuilding the host as long as it speaks a supported protocol.
+/// Bump `VERSION` when the agent RPC surface changes; raise `MIN_SUPPORTED` when
+/// the host drops compatibility with older agents.
+pub mod agent_protocol {
+    /// Protocol version spoken by this build
+    ///
+    /// 2: `Upload` applies `UploadChunk.ownership` (v1 agents leave files root-owned)
+    /// 3: `ExecRequest.capture` tees output to the execs share (v2 agents ignore it)
+    /// 4: `ContainerInitRequest.entrypoint_script` wraps the entrypoint (v3 agents ignore it)
+    /// 5: `Guest.Dmesg` / `Guest.AgentLog` (v4 agents return Unimplemented)
+    /// 6: `ContainerInitRequest.capture_core_dumps` and `Container.ListCoreDumps`
+    ///    (v5 agents ignore the flag)
+    /// 7: `ContainerInitRequest.swap_mib` and `Guest.SwapUsage` (v6 agents
+    ///    ignore the size and return Unimplemented)
+    /// 8: `Guest.Sync` (v7 agents return Unimplemented)
+    /// 9: `ContainerInitRequest.time_zone` (v8 agents ignore it)
+    /// 10: `ContainerInitRequest.read_only_rootfs` and `writable_paths` (v9
+    ///     agents ignore them)
+    /// 11: `ShutdownRequest.timeout_ms` (v10 agents keep their fixed 3s)
+    /// 12: `ContainerInitRequest.extra_hosts` and `Container.UpdateHosts`
+    ///     (v11 agents ignore the entries and return Unimplemented)
+    /// 13: `Guest.Activity` (v12 agents return Unimplemented)
+    /// 14: `ExecRequest.env_edits` (v13 agents ignore them)
+    /// 15: `Execution.Unprepare` and the prepared template limit (v14 agents
+    ///     return Unimplemented and keep every template)
+    pub const VERSION: u32 = 15;
+
+    /// Oldest agent protocol version the host still accepts
+    pub const MIN_SUPPORTED: u32 = 1;
+
+    /// Whether the host accepts an agent advertising `version`.
+    ///
+    /// Agents predating the handshake report 0 and are rejected.
+    pub const fn is_supported(version: u32) -> bool {
+        version >= MIN_SUPPORTED && version <= VERSION
+    }
+}
+
 /// Virtiofs mount tags
 ///
 /// These tags identify shared filesystems mounted via virtiofs.
@@ -53,4 +153,7 @@ pub mod mount_tags {
 
     /// Tag for shared container directory (contains overlayfs/ and rootfs/)
     pub const SHARED: &str = "BoxLiteShared";
+
+    /// Tag for captured output of detached executions (writable by the guest)
+    pub const EXECS: &str = "BoxLiteExecs";
 }
@@ -2,6 +2,8 @@
 
 use thiserror::Error;
 
+use crate::transfer::TransferManifest;
+
 /// Result type for Boxlite operations.
 pub type BoxliteResult<T> = Result<T, BoxliteError>;
 
@@ -70,6 +72,64 @@ pub enum BoxliteError {
     /// Resource (box or runtime) has been stopped/shutdown.
     #[error("stopped: {0}")]
     Stopped(String),
+
+    /// The runtime is shutting down and no longer accepts the operation.
+    ///
+    /// Carries what was refused, e.g. "cannot create box".
+    #[error("runtime is shutting down: {0}")]
+    ShuttingDown(String),
+
+    /// Network access attempted while the runtime is in offline mode.
+    ///
+    /// Carries the host that would have been contacted.
+    #[error("offline mode: refusing network access to {0}")]
+    OfflineMode(String),
+
+    /// Operation did not complete within the caller-supplied deadline.
+    #[error("operation timed out: {0}")]
+    Timeout(String),
+
+    /// A create policy refused the operation.
+    ///
+    /// Carries the policy's reason.
+    #[error("policy denied: {0}")]
+    PolicyDenied(String),
+
+    /// Another handle is running a mutating operation on the box.
+    ///
+    /// Carries the holder's operation and process ID (0 if unknown).
+    #[error("busy: {operation} in progress by pid {pid}")]
+    Busy { operation: String, pid: u32 },
+
+    /// A copy reached its deadline before every file was transferred.
+    ///
+    /// Carries what was transferred; copying again with `resume` continues
+    /// from there.
+    #[error("partial transfer: {0}")]
+    PartialTransfer(Box<TransferManifest>),
+
+    /// An archive being unpacked broke the rules of `archive::ArchiveGuard`.
+    ///
+    /// Carries the offending entry and what is wrong with it.
+    #[error("unsafe archive: {0}")]
+    UnsafeArchive(String),
+
+    /// A copy out of a box would write outside its destination on the host.
+    ///
+    /// Carries the offending entry and why it was refused.
+    #[error("unsafe copy: {0}")]
+    UnsafeCopy(String),
+
+    /// `get_or_create` found the named box created with other options.
+    ///
+    /// Carries the box name and the options that differ.
+    #[error("config drift: box '{name}' differs in {}", .fields.join(", "))]
+    ConfigDrift { name: String, fields: Vec<String> },
+
+    /// The caller is authenticated but its credentials do not allow the
+    /// operation (e.g. a read-only REST token asked to remove a box).
+    #[error("permission denied: {0}")]
+    PermissionDenied(String),
 }
 
 // Implement From for common error types to enable `?` operator
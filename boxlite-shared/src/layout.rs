@@ -43,6 +43,10 @@ pub mod dirs {
 
     /// Volumes directory name (contains user volumes)
     pub const VOLUMES: &str = "volumes";
+
+    /// Captured output of detached executions (box dir on the host,
+    /// execs share mount in the guest)
+    pub const EXECS: &str = "execs";
 }
 
 /// Guest base path (FHS-compliant).
@@ -3,9 +3,12 @@
 //! This crate contains common types, protocols, and utilities
 //! used by both the host-side runtime (boxlite) and guest agent.
 
+pub mod archive;
+pub mod boot;
 pub mod constants;
 pub mod errors;
 pub mod layout;
+pub mod transfer;
 pub mod transport;
 
 // Generated protobuf types
@@ -14,7 +17,9 @@ pub mod generated {
     tonic::include_proto!("boxlite.v1");
 }
 
+pub use archive::ArchiveLimits;
 pub use errors::{BoxliteError, BoxliteResult};
+pub use transfer::{TransferManifest, TransferredFile};
 pub use transport::Transport;
 
 // Container service
@@ -33,5 +38,13 @@ pub use generated::execution_server::{Execution, ExecutionServer};
 pub use generated::files_client::FilesClient;
 pub use generated::files_server::{Files, FilesServer};
 
+// Tunnel service
+pub use generated::tunnel_client::TunnelClient;
+pub use generated::tunnel_server::{Tunnel, TunnelServer};
+
+// Supervisor service
+pub use generated::supervisor_client::SupervisorClient;
+pub use generated::supervisor_server::{Supervisor, SupervisorServer};
+
 // All generated types
 pub use generated::*;
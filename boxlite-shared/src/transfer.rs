@@ -0,0 +1,61 @@
+//! Progress record of a resumable copy into a box.
+
+use std::fmt;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// Files a resumable copy has transferred so far.
+///
+/// Returned inside `BoxliteError::PartialTransfer` when a copy stops at its
+/// deadline; a later copy of the same source and destination with `resume`
+/// skips the files listed here.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferManifest {
+    /// Host path being copied.
+    pub source: PathBuf,
+    /// Destination path inside the box.
+    pub destination: String,
+    /// Files already in the box, by archive path.
+    pub completed: Vec<TransferredFile>,
+    /// Number of files in the source.
+    pub total_files: u64,
+    /// Size of all files in the source, in bytes.
+    pub total_bytes: u64,
+}
+
+/// A file a resumable copy has transferred.
+///
+/// Size and modification time are those of the host file when it was sent;
+/// a file that changed since is sent again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransferredFile {
+    /// Path of the entry in the archive, relative to the destination.
+    pub path: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// Modification time in nanoseconds since the Unix epoch.
+    pub mtime_ns: i64,
+}
+
+impl TransferManifest {
+    /// Bytes of the files already transferred.
+    pub fn transferred_bytes(&self) -> u64 {
+        self.completed.iter().map(|f| f.size).sum()
+    }
+}
+
+impl fmt::Display for TransferManifest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "copied {} of {} files ({} of {} bytes) from {} to {}",
+            self.completed.len(),
+            self.total_files,
+            self.transferred_bytes(),
+            self.total_bytes,
+            self.source.display(),
+            self.destination
+        )
+    }
+}
@@ -0,0 +1,142 @@
+//! Disk image checksums: wall-clock time and async worker starvation.
+//!
+//! Run with `cargo bench -p boxlite --bench checksum`. Set
+//! `BOXLITE_BENCH_CHECKSUM_MIB` to change the file size (default 2048).
+//!
+//! Compares the former export checksum (64 KiB reads on the calling thread)
+//! with `sha256_file`, the tree digest and both side by side as export
+//! computes them. Then hashes on a one-worker tokio runtime while a task
+//! ticks every 10ms, inline as export used to and through
+//! `file_digests_async`, and reports the longest tick delay.
+
+use std::hint::black_box;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use boxlite::util::checksum;
+use sha2::{Digest, Sha256};
+
+const TICK: Duration = Duration::from_millis(10);
+
+fn file_mib() -> u64 {
+    std::env::var("BOXLITE_BENCH_CHECKSUM_MIB")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(2048)
+}
+
+/// Pseudo-random contents, so the page cache holds what a disk would.
+fn write_file(path: &Path, mib: u64) {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path).unwrap());
+    let mut block = vec![0u8; 1024 * 1024];
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    for _ in 0..mib {
+        for byte in block.iter_mut() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            *byte = state as u8;
+        }
+        file.write_all(&block).unwrap();
+    }
+    file.flush().unwrap();
+}
+
+/// The checksum export computed before, for comparison.
+fn sha256_small_reads(path: &Path) -> String {
+    let mut file = std::fs::File::open(path).unwrap();
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+fn measure<T>(name: &str, mib: u64, run: impl FnOnce() -> T) -> Duration {
+    let started = Instant::now();
+    black_box(run());
+    let elapsed = started.elapsed();
+    let mib_per_sec = mib as f64 / elapsed.as_secs_f64();
+    println!("{name:<28} {elapsed:>10.2?} {mib_per_sec:>10.0} MiB/s");
+    elapsed
+}
+
+/// Longest delay of a 10ms ticker on a one-worker runtime while `hash`
+/// runs on that same runtime.
+fn worst_tick_delay<F>(hash: F) -> Duration
+where
+    F: std::future::Future<Output = ()> + Send + 'static,
+{
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_time()
+        .build()
+        .unwrap();
+    runtime.block_on(async move {
+        let done = Arc::new(AtomicBool::new(false));
+        let ticker = tokio::spawn({
+            let done = Arc::clone(&done);
+            async move {
+                let mut worst = Duration::ZERO;
+                while !done.load(Ordering::Relaxed) {
+                    let started = Instant::now();
+                    tokio::time::sleep(TICK).await;
+                    worst = worst.max(started.elapsed().saturating_sub(TICK));
+                }
+                worst
+            }
+        });
+        // Let the ticker start before hashing takes the worker
+        tokio::time::sleep(TICK * 3).await;
+        tokio::spawn(hash).await.unwrap();
+        done.store(true, Ordering::Relaxed);
+        ticker.await.unwrap()
+    })
+}
+
+fn main() {
+    let mib = file_mib();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("disk.img");
+    write_file(&path, mib);
+    println!("file: {mib} MiB");
+
+    // Warm the page cache so every run reads from memory
+    sha256_small_reads(&path);
+
+    let baseline = measure("64 KiB reads (before)", mib, || sha256_small_reads(&path));
+    measure("sha256_file", mib, || checksum::sha256_file(&path).unwrap());
+    let tree = measure("tree_sha256_file", mib, || {
+        checksum::tree_sha256_file(&path).unwrap()
+    });
+    let both = measure("file_digests (export)", mib, || {
+        checksum::file_digests(&path).unwrap()
+    });
+    println!(
+        "tree digest: {:.2}x faster than before",
+        baseline.as_secs_f64() / tree.as_secs_f64()
+    );
+    println!(
+        "export checksum step: {:.2}x the time of before, tree digest included",
+        both.as_secs_f64() / baseline.as_secs_f64()
+    );
+
+    let inline_path = path.clone();
+    let inline = worst_tick_delay(async move {
+        black_box(checksum::file_digests(&inline_path).unwrap());
+    });
+    let blocking_path = path.clone();
+    let offloaded = worst_tick_delay(async move {
+        black_box(checksum::file_digests_async(blocking_path).await.unwrap());
+    });
+    println!("worst tick delay, hashing on the worker:    {inline:>10.2?}");
+    println!("worst tick delay, hashing on blocking pool: {offloaded:>10.2?}");
+}
@@ -0,0 +1,47 @@
+//! Overhead of host-side exec output filtering.
+//!
+//! Run with `cargo bench -p boxlite --bench output_filter`. Compares a
+//! plain copy of each chunk, an upper bound for the no-filter path (which
+//! forwards chunks untouched, without an extra task), with a `Redactor`
+//! holding a few secrets.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use boxlite::{OutputFilter, Redactor};
+
+const CHUNK_SIZE: usize = 4096;
+const TOTAL_BYTES: usize = 256 * 1024 * 1024;
+
+/// A chunk of log-like output without any secret in it.
+fn chunk() -> String {
+    let line = "2026-01-01T00:00:00Z INFO worker: processed job 1234 in 5ms status=ok\n";
+    line.repeat(CHUNK_SIZE / line.len() + 1)[..CHUNK_SIZE].to_string()
+}
+
+fn measure(name: &str, chunk: &str, mut run: impl FnMut(&str) -> String) -> Duration {
+    let started = Instant::now();
+    for _ in 0..TOTAL_BYTES / CHUNK_SIZE {
+        black_box(run(black_box(chunk)));
+    }
+    let elapsed = started.elapsed();
+    let mib_per_sec = (TOTAL_BYTES as f64 / (1024.0 * 1024.0)) / elapsed.as_secs_f64();
+    println!("{name:<24} {elapsed:>10.2?} {mib_per_sec:>10.0} MiB/s");
+    elapsed
+}
+
+fn main() {
+    let chunk = chunk();
+
+    let baseline = measure("no filter", &chunk, str::to_string);
+
+    let mut redactor = Redactor::new(["sk-live-0123456789abcdef", "hunter2", "db-password-42"]);
+    let redacted = measure("redactor (3 secrets)", &chunk, |chunk| {
+        redactor.filter(chunk)
+    });
+
+    println!(
+        "redactor overhead: {:.2}x",
+        redacted.as_secs_f64() / baseline.as_secs_f64()
+    );
+}
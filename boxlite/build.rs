@@ -476,14 +476,20 @@ fn download_prebuilt_runtime(runtime_dir: &Path) {
 /// - unset  → `Source`:   build -sys crates from source, bundle outputs
 /// - `1`    → `Stub`:     skip everything, for CI `cargo check`/`cargo clippy`
 /// - `2`    → `Prebuilt`: skip -sys builds, download prebuilt from GitHub Releases
+///
+/// Without the `local` feature there is no engine to bundle (`Remote`).
 enum DepsMode {
     Source,
     Stub,
     Prebuilt,
+    Remote,
 }
 
 impl DepsMode {
     fn from_env() -> Self {
+        if env::var_os("CARGO_FEATURE_LOCAL").is_none() {
+            return Self::Remote;
+        }
         match env::var("BOXLITE_DEPS_STUB").ok().as_deref() {
             Some("2") => Self::Prebuilt,
             Some(_) => Self::Stub,
@@ -504,6 +510,55 @@ fn auto_detect_registry() {
     }
 }
 
+/// Embed the git commit as `BOXLITE_GIT_COMMIT` (read by `VersionInfo`).
+///
+/// Sources, in order: the `BOXLITE_GIT_COMMIT` env var (for builds outside a
+/// checkout), `git rev-parse HEAD`, then `.cargo_vcs_info.json` for crates.io
+/// packages. Skipped silently if none is available.
+fn embed_git_commit() {
+    println!("cargo:rerun-if-env-changed=BOXLITE_GIT_COMMIT");
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+
+    let git = |args: &[&str]| -> Option<String> {
+        let output = Command::new("git")
+            .args(args)
+            .current_dir(&manifest_dir)
+            .output()
+            .ok()?;
+        output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let commit = env::var("BOXLITE_GIT_COMMIT")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .or_else(|| {
+            let commit = git(&["rev-parse", "HEAD"])?;
+            // Rebuild when HEAD moves (commit, checkout, reset)
+            if let Some(git_dir) = git(&["rev-parse", "--absolute-git-dir"]) {
+                let git_dir = PathBuf::from(git_dir);
+                println!("cargo:rerun-if-changed={}", git_dir.join("HEAD").display());
+                println!(
+                    "cargo:rerun-if-changed={}",
+                    git_dir.join("logs/HEAD").display()
+                );
+            }
+            Some(commit)
+        })
+        .or_else(|| {
+            let vcs_info = fs::read_to_string(manifest_dir.join(".cargo_vcs_info.json")).ok()?;
+            let re = Regex::new(r#""sha1"\s*:\s*"([0-9a-f]+)""#).unwrap();
+            re.captures(&vcs_info).map(|c| c[1].to_string())
+        });
+
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=BOXLITE_GIT_COMMIT={}", commit);
+    }
+}
+
 /// Collects all FFI dependencies into a single runtime directory.
 /// This directory can be used by downstream crates (e.g., Python SDK) to
 /// bundle all required libraries and binaries together.
@@ -513,6 +568,8 @@ fn main() {
 
     auto_detect_registry();
 
+    embed_git_commit();
+
     // Compile seccomp filters at build time (fast, required for include_bytes!())
     compile_seccomp_filters();
 
@@ -528,6 +585,11 @@ fn main() {
             println!("cargo:runtime_dir=/nonexistent");
             return;
         }
+        DepsMode::Remote => {
+            // REST-only build: no libkrun, shim or guest to bundle
+            println!("cargo:runtime_dir=/nonexistent");
+            return;
+        }
         DepsMode::Prebuilt => {
             // Download prebuilt runtime from GitHub Releases
             println!("cargo:warning=BOXLITE_DEPS_STUB=2: downloading prebuilt runtime");
@@ -178,6 +178,7 @@ type GvproxyConfig struct {
 	Subnet           string        `json:"subnet"`
 	GatewayIP        string        `json:"gateway_ip"`
 	GatewayMac       string        `json:"gateway_mac"`
+	HostIP           string        `json:"host_ip"`
 	GuestIP          string        `json:"guest_ip"`
 	GuestMac         string        `json:"guest_mac"`
 	MTU              uint16        `json:"mtu"`
@@ -251,6 +252,16 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 		}
 	}
 
+	// The host IP is a gateway virtual IP NATed to the host's loopback
+	nat := map[string]string{
+		config.GuestIP: "127.0.0.1",
+	}
+	gatewayVirtualIPs := []string{config.GatewayIP}
+	if config.HostIP != "" {
+		nat[config.HostIP] = "127.0.0.1"
+		gatewayVirtualIPs = append(gatewayVirtualIPs, config.HostIP)
+	}
+
 	// Create gvisor-tap-vsock configuration from provided config
 	tapConfig := &types.Configuration{
 		Debug:             config.Debug,
@@ -262,10 +273,8 @@ func gvproxy_create(configJSON *C.char) C.longlong {
 			config.GuestIP: config.GuestMac,
 		},
 		Forwards: make(map[string]string),
-		NAT: map[string]string{
-			config.GuestIP: "127.0.0.1",
-		},
-		GatewayVirtualIPs: []string{config.GatewayIP},
+		NAT:               nat,
+		GatewayVirtualIPs: gatewayVirtualIPs,
 		Protocol:          protocol,
 		DNS:               dnsZones,
 		DNSSearchDomains:  config.DNSSearchDomains,
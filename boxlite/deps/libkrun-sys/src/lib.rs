@@ -57,6 +57,11 @@ extern "C" {
     pub fn krun_set_gpu_options(ctx_id: u32, virgl_flags: u32) -> i32;
     pub fn krun_set_rlimits(ctx_id: u32, rlimits: *const *const c_char) -> i32;
     pub fn krun_set_port_map(ctx_id: u32, port_map: *const *const c_char) -> i32;
+    /// Drop the vsock device libkrun adds by default, along with its
+    /// implicit TSI networking. Must be called before `krun_add_vsock`.
+    pub fn krun_disable_implicit_vsock(ctx_id: u32) -> i32;
+    /// Add a vsock device with the given TSI features (0 for none).
+    pub fn krun_add_vsock(ctx_id: u32, tsi_features: u32) -> i32;
     pub fn krun_add_vsock_port2(
         ctx_id: u32,
         port: u32,
@@ -0,0 +1,79 @@
+//! Benchmark: first start of a box, cold and warm image cache
+//!
+//! For each image (default: `alpine:latest` and the much larger
+//! `python:3.12`), starts a box in a fresh BoxLite home, so the image pull,
+//! ext4 build and guest rootfs are all part of the measurement, then a
+//! second box in the same home, where only per-box work is left. Prints the
+//! total and the per-stage times from `BoxMetrics`.
+//!
+//! For a per-task breakdown, including the tasks that run in parallel, run
+//! with `RUST_LOG=boxlite::pipeline=debug`.
+//! Run with: cargo run --release --example cold_start_bench [-- <image>...]
+
+use std::time::{Duration, Instant};
+
+use boxlite::{BoxCommand, BoxOptions, BoxliteOptions, BoxliteRuntime};
+
+type BenchResult<T = ()> = Result<T, Box<dyn std::error::Error>>;
+
+const DEFAULT_IMAGES: &[&str] = &["alpine:latest", "python:3.12"];
+
+/// Create and start a box, run `true` in it, and report the timings.
+async fn start_box(runtime: &BoxliteRuntime, image: &str, label: &str) -> BenchResult<Duration> {
+    let options = BoxOptions::builder().image(image).build()?;
+
+    let start = Instant::now();
+    let litebox = runtime.create(options, None).await?;
+    litebox.start().await?;
+    let started = start.elapsed();
+
+    let mut execution = litebox.exec(BoxCommand::new("true")).await?;
+    execution.wait().await?;
+
+    let metrics = litebox.metrics().await?;
+    let stage = |ms: Option<u128>| ms.map_or_else(|| "-".to_string(), |ms| format!("{ms}ms"));
+    println!(
+        "  {:<6} start {:>9.2?}  image {:>8}  guest rootfs {:>8}  spawn {:>8}  init {:>8}",
+        label,
+        started,
+        stage(metrics.stage_image_prepare_ms()),
+        stage(metrics.stage_guest_rootfs_ms()),
+        stage(metrics.stage_box_spawn_ms()),
+        stage(metrics.stage_container_init_ms()),
+    );
+
+    litebox.stop().await?;
+    runtime.remove(litebox.id().as_str(), true).await?;
+    Ok(started)
+}
+
+#[tokio::main]
+async fn main() -> BenchResult {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let images: Vec<&str> = if args.is_empty() {
+        DEFAULT_IMAGES.to_vec()
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
+
+    println!("=== Cold start benchmark ===");
+
+    for image in images {
+        println!("\n{image}");
+
+        // A fresh home has no cached images, guest rootfs or base disks
+        let home = tempfile::tempdir()?;
+        let runtime = BoxliteRuntime::new(BoxliteOptions {
+            home_dir: home.path().to_path_buf(),
+            ..Default::default()
+        })?;
+
+        let cold = start_box(&runtime, image, "cold").await?;
+        let warm = start_box(&runtime, image, "warm").await?;
+        println!("  cache saves {:.2?}", cold.saturating_sub(warm));
+
+        runtime.shutdown(None).await?;
+    }
+
+    Ok(())
+}
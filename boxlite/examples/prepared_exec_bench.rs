@@ -0,0 +1,79 @@
+//! Benchmark: repeated execs, prepared vs unprepared
+//!
+//! Runs the same command N times (default 1000) through `LiteBox::exec` and
+//! through a `PreparedExec`, and prints per-exec latency for both.
+//! Run with: cargo run --release --example prepared_exec_bench [-- <iterations>]
+
+use std::time::{Duration, Instant};
+
+use boxlite::{BoxCommand, BoxOptions, BoxliteRuntime, LiteBox, PreparedExec};
+
+type BenchResult = Result<(), Box<dyn std::error::Error>>;
+
+async fn run_unprepared(litebox: &LiteBox, job: usize) -> BenchResult {
+    let command = BoxCommand::new("true").args(["--job".to_string(), job.to_string()]);
+    let mut execution = litebox.exec(command).await?;
+    execution.wait().await?;
+    Ok(())
+}
+
+async fn run_prepared(prepared: &PreparedExec, job: usize) -> BenchResult {
+    let mut execution = prepared.run(["--job".to_string(), job.to_string()]).await?;
+    execution.wait().await?;
+    Ok(())
+}
+
+fn report(label: &str, iterations: usize, elapsed: Duration) {
+    let per_exec = elapsed / iterations as u32;
+    println!(
+        "  {:<11} total {:>8.2?}  per exec {:>8.2?}  ({:.0} execs/s)",
+        label,
+        elapsed,
+        per_exec,
+        iterations as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[tokio::main]
+async fn main() -> BenchResult {
+    let iterations: usize = std::env::args()
+        .nth(1)
+        .map(|n| n.parse())
+        .transpose()?
+        .unwrap_or(1000);
+
+    let runtime = BoxliteRuntime::with_defaults()?;
+    let options = BoxOptions::builder().image("alpine:latest").build()?;
+    let litebox = runtime.create(options, None).await?;
+    litebox.start().await?;
+
+    println!("=== Prepared exec benchmark ({iterations} execs) ===\n");
+
+    // Warm up both paths so the first-exec connection cost is not measured
+    run_unprepared(&litebox, 0).await?;
+    let prepared = litebox.prepare_exec(BoxCommand::new("true")).await?;
+    run_prepared(&prepared, 0).await?;
+
+    let start = Instant::now();
+    for job in 0..iterations {
+        run_unprepared(&litebox, job).await?;
+    }
+    let unprepared = start.elapsed();
+    report("unprepared", iterations, unprepared);
+
+    let start = Instant::now();
+    for job in 0..iterations {
+        run_prepared(&prepared, job).await?;
+    }
+    let prepared_elapsed = start.elapsed();
+    report("prepared", iterations, prepared_elapsed);
+
+    println!(
+        "\n  speedup: {:.2}x",
+        unprepared.as_secs_f64() / prepared_elapsed.as_secs_f64()
+    );
+
+    litebox.stop().await?;
+    runtime.remove(litebox.id().as_str(), true).await?;
+    Ok(())
+}
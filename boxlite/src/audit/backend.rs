@@ -0,0 +1,1012 @@
+//! Auditing decorators for the runtime and box backends.
+//!
+//! `AuditedRuntime` wraps any `RuntimeBackend` and every `LiteBox` it hands
+//! out, so the local and REST backends are audited the same way.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::panic::AssertUnwindSafe;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+
+use super::{AuditEvent, AuditOperation, AuditOutcome, AuditSink};
+use crate::db::compositions::ComposedMember;
+use crate::db::templates::BoxTemplate;
+use crate::db::trash::TrashedBox;
+use crate::litebox::copy::CopyOptions;
+use crate::litebox::snapshot_types::{SnapshotMetadata, SnapshotRetention};
+use crate::litebox::{
+    BoxCommand, CapturedOutput, CoreDump, EnvironmentReport, Execution, GuestAgentLog, LiteBox,
+    ServiceInfo, ServicePolicy, StartFailure, TunnelHandle,
+};
+use crate::metrics::{BoxMetrics, RuntimeMetrics};
+use crate::runtime::BulkResults;
+use crate::runtime::OrphanShim;
+use crate::runtime::WarmSelector;
+use crate::runtime::advanced_options::ResourceLimits;
+use crate::runtime::backend::{BoxBackend, RuntimeBackend};
+use crate::runtime::capabilities::RuntimeCapabilities;
+use crate::runtime::create_progress::CreateObserver;
+use crate::runtime::drift::{GetOrCreateOutcome, GetOrCreatePolicy};
+use crate::runtime::options::{BoxOptions, RootfsSpec};
+use crate::runtime::types::{BoxID, BoxInfo, ListOptions, RemovePlan};
+#[cfg(feature = "url-transfer")]
+use crate::runtime::url_transfer::TransferOptions;
+use crate::runtime::version::VersionInfo;
+use crate::runtime::{ImportOptions, ImportPlan};
+use crate::vmm::ShimExit;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Shared event emitter for a runtime and its boxes.
+#[derive(Clone)]
+struct Auditor {
+    sink: Arc<dyn AuditSink>,
+    caller: String,
+    peer: Option<String>,
+}
+
+impl Auditor {
+    fn emit<T>(
+        &self,
+        operation: AuditOperation,
+        box_id: Option<String>,
+        args: BTreeMap<String, String>,
+        result: &BoxliteResult<T>,
+    ) {
+        let event = AuditEvent {
+            timestamp: Utc::now(),
+            operation,
+            box_id,
+            args,
+            outcome: AuditOutcome::from_result(result),
+            caller: self.caller.clone(),
+            peer: self.peer.clone(),
+        };
+
+        // A misbehaving sink must never take the operation down with it
+        if std::panic::catch_unwind(AssertUnwindSafe(|| self.sink.record(event))).is_err() {
+            tracing::warn!(operation = %operation, "Audit sink panicked; event dropped");
+        }
+    }
+
+    fn wrap(&self, litebox: LiteBox) -> LiteBox {
+        litebox.map_backend(|inner| {
+            Arc::new(AuditedBox {
+                inner,
+                auditor: self.clone(),
+            })
+        })
+    }
+}
+
+/// Local identity of the current process: `"{user} (uid {uid})"`.
+fn caller_identity() -> String {
+    let user = std::env::var("USER").unwrap_or_else(|_| "unknown".to_string());
+    #[cfg(unix)]
+    {
+        let uid = unsafe { libc::getuid() };
+        format!("{user} (uid {uid})")
+    }
+    #[cfg(not(unix))]
+    {
+        user
+    }
+}
+
+/// Runtime backend that records mutating calls to an [`AuditSink`].
+pub(crate) struct AuditedRuntime {
+    inner: Arc<dyn RuntimeBackend>,
+    auditor: Auditor,
+}
+
+impl AuditedRuntime {
+    pub(crate) fn new(inner: Arc<dyn RuntimeBackend>, sink: Arc<dyn AuditSink>) -> Self {
+        let auditor = Auditor {
+            sink,
+            caller: caller_identity(),
+            peer: inner.audit_peer(),
+        };
+        Self { inner, auditor }
+    }
+}
+
+impl AuditedRuntime {
+    /// Record a bulk ownership change (`detach_all` / `adopt_all`): one
+    /// event per box, or one failed event when the call itself failed.
+    fn emit_owner_changes(&self, result: &BoxliteResult<BulkResults<()>>) {
+        let owner_args = || BTreeMap::from([("field".to_string(), "owner".to_string())]);
+        match result {
+            Ok(results) => {
+                for (box_id, outcome) in results {
+                    self.auditor.emit(
+                        AuditOperation::Update,
+                        Some(box_id.to_string()),
+                        owner_args(),
+                        outcome,
+                    );
+                }
+            }
+            Err(_) => self
+                .auditor
+                .emit(AuditOperation::Update, None, owner_args(), result),
+        }
+    }
+}
+
+/// Summarize create options without leaking env values or volume contents.
+fn create_args(options: &BoxOptions, name: Option<&str>) -> BTreeMap<String, String> {
+    let mut args = BTreeMap::new();
+    match &options.rootfs {
+        RootfsSpec::Image(image) => args.insert("image".to_string(), image.clone()),
+        RootfsSpec::RootfsPath(path) => args.insert("rootfs_path".to_string(), path.clone()),
+    };
+    if let Some(name) = name {
+        args.insert("name".to_string(), name.to_string());
+    }
+    if let Some(cpus) = options.cpus {
+        args.insert("cpus".to_string(), cpus.to_string());
+    }
+    if let Some(memory_mib) = options.memory_mib {
+        args.insert("memory_mib".to_string(), memory_mib.to_string());
+    }
+    if !options.volumes.is_empty() {
+        args.insert("volumes".to_string(), options.volumes.len().to_string());
+    }
+    if !options.ports.is_empty() {
+        args.insert("ports".to_string(), options.ports.len().to_string());
+    }
+    args
+}
+
+/// Summarize import options; `archive` is the path or URL imported from.
+fn import_args(archive: String, options: &ImportOptions) -> BTreeMap<String, String> {
+    let mut args = BTreeMap::from([
+        ("archive".to_string(), archive),
+        (
+            "on_conflict".to_string(),
+            format!("{:?}", options.on_conflict).to_lowercase(),
+        ),
+    ]);
+    if let Some(name) = &options.name {
+        args.insert("name".to_string(), name.clone());
+    }
+    args
+}
+
+#[async_trait]
+impl RuntimeBackend for AuditedRuntime {
+    async fn create(&self, options: BoxOptions, name: Option<String>) -> BoxliteResult<LiteBox> {
+        let args = create_args(&options, name.as_deref());
+        let result = self.inner.create(options, name).await;
+        let box_id = result.as_ref().ok().map(|b| b.id().to_string());
+        self.auditor
+            .emit(AuditOperation::Create, box_id, args, &result);
+        result.map(|litebox| self.auditor.wrap(litebox))
+    }
+
+    async fn create_with_observer(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: CreateObserver,
+    ) -> BoxliteResult<LiteBox> {
+        let args = create_args(&options, name.as_deref());
+        let result = self
+            .inner
+            .create_with_observer(options, name, observer)
+            .await;
+        let box_id = result.as_ref().ok().map(|b| b.id().to_string());
+        self.auditor
+            .emit(AuditOperation::Create, box_id, args, &result);
+        result.map(|litebox| self.auditor.wrap(litebox))
+    }
+
+    async fn get_or_create(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+    ) -> BoxliteResult<(LiteBox, bool)> {
+        let args = create_args(&options, name.as_deref());
+        let result = self.inner.get_or_create(options, name).await;
+        match &result {
+            // Returning an existing box mutates nothing
+            Ok((_, false)) => {}
+            Ok((litebox, true)) => {
+                let box_id = Some(litebox.id().to_string());
+                self.auditor
+                    .emit(AuditOperation::Create, box_id, args, &result);
+            }
+            Err(_) => self
+                .auditor
+                .emit(AuditOperation::Create, None, args, &result),
+        }
+        result.map(|(litebox, created)| (self.auditor.wrap(litebox), created))
+    }
+
+    async fn get_or_create_with(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        policy: GetOrCreatePolicy,
+    ) -> BoxliteResult<(LiteBox, GetOrCreateOutcome)> {
+        let args = create_args(&options, name.as_deref());
+        let result = self.inner.get_or_create_with(options, name, policy).await;
+        match &result {
+            Ok((_, GetOrCreateOutcome::Reused)) => {}
+            Ok((litebox, _)) => {
+                let box_id = Some(litebox.id().to_string());
+                self.auditor
+                    .emit(AuditOperation::Create, box_id, args, &result);
+            }
+            Err(_) => self
+                .auditor
+                .emit(AuditOperation::Create, None, args, &result),
+        }
+        result.map(|(litebox, outcome)| (self.auditor.wrap(litebox), outcome))
+    }
+
+    async fn get(&self, id_or_name: &str) -> BoxliteResult<Option<LiteBox>> {
+        let litebox = self.inner.get(id_or_name).await?;
+        Ok(litebox.map(|litebox| self.auditor.wrap(litebox)))
+    }
+
+    async fn get_info(&self, id_or_name: &str) -> BoxliteResult<Option<BoxInfo>> {
+        self.inner.get_info(id_or_name).await
+    }
+
+    async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
+        self.inner.list_info().await
+    }
+
+    async fn list_info_with(&self, options: ListOptions) -> BoxliteResult<Vec<BoxInfo>> {
+        self.inner.list_info_with(options).await
+    }
+
+    async fn acquire_warm(&self, selector: WarmSelector) -> BoxliteResult<LiteBox> {
+        let args = BTreeMap::from([("warm_pool".to_string(), selector.pool().to_string())]);
+        let result = self.inner.acquire_warm(selector).await;
+        let box_id = result.as_ref().ok().map(|b| b.id().to_string());
+        self.auditor
+            .emit(AuditOperation::Create, box_id, args, &result);
+        result.map(|litebox| self.auditor.wrap(litebox))
+    }
+
+    async fn exists(&self, id_or_name: &str) -> BoxliteResult<bool> {
+        self.inner.exists(id_or_name).await
+    }
+
+    async fn metrics(&self) -> BoxliteResult<RuntimeMetrics> {
+        self.inner.metrics().await
+    }
+
+    async fn version_info(&self) -> BoxliteResult<VersionInfo> {
+        self.inner.version_info().await
+    }
+
+    async fn capabilities(&self) -> BoxliteResult<RuntimeCapabilities> {
+        self.inner.capabilities().await
+    }
+
+    async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        let result = self.inner.remove(id_or_name, force).await;
+        let args = BTreeMap::from([
+            ("id_or_name".to_string(), id_or_name.to_string()),
+            ("force".to_string(), force.to_string()),
+        ]);
+        self.auditor
+            .emit(AuditOperation::Remove, None, args, &result);
+        result
+    }
+
+    async fn remove_permanently(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        let result = self.inner.remove_permanently(id_or_name, force).await;
+        let args = BTreeMap::from([
+            ("id_or_name".to_string(), id_or_name.to_string()),
+            ("force".to_string(), force.to_string()),
+            ("purge".to_string(), "true".to_string()),
+        ]);
+        self.auditor
+            .emit(AuditOperation::Remove, None, args, &result);
+        result
+    }
+
+    async fn plan_remove(
+        &self,
+        id_or_name: &str,
+        force: bool,
+        permanently: bool,
+    ) -> BoxliteResult<RemovePlan> {
+        self.inner.plan_remove(id_or_name, force, permanently).await
+    }
+
+    async fn list_trashed(&self) -> BoxliteResult<Vec<TrashedBox>> {
+        self.inner.list_trashed().await
+    }
+
+    async fn register_template(
+        &self,
+        name: &str,
+        options: BoxOptions,
+    ) -> BoxliteResult<BoxTemplate> {
+        let mut args = create_args(&options, None);
+        args.insert("template".to_string(), name.to_string());
+        let result = self.inner.register_template(name, options).await;
+        self.auditor
+            .emit(AuditOperation::Create, None, args, &result);
+        result
+    }
+
+    async fn get_template(&self, name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        self.inner.get_template(name).await
+    }
+
+    async fn list_templates(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        self.inner.list_templates().await
+    }
+
+    async fn remove_template(&self, name: &str) -> BoxliteResult<()> {
+        let result = self.inner.remove_template(name).await;
+        let args = BTreeMap::from([("template".to_string(), name.to_string())]);
+        self.auditor
+            .emit(AuditOperation::Remove, None, args, &result);
+        result
+    }
+
+    async fn record_composition(&self, members: &[ComposedMember]) -> BoxliteResult<()> {
+        let result = self.inner.record_composition(members).await;
+        let args = BTreeMap::from([
+            ("field".to_string(), "composition".to_string()),
+            ("members".to_string(), members.len().to_string()),
+        ]);
+        self.auditor
+            .emit(AuditOperation::Update, None, args, &result);
+        result
+    }
+
+    async fn list_composition(&self) -> BoxliteResult<Vec<ComposedMember>> {
+        self.inner.list_composition().await
+    }
+
+    async fn forget_composition(&self, names: &[String]) -> BoxliteResult<()> {
+        let result = self.inner.forget_composition(names).await;
+        let args = BTreeMap::from([
+            ("field".to_string(), "composition".to_string()),
+            ("forget".to_string(), names.join(",")),
+        ]);
+        self.auditor
+            .emit(AuditOperation::Update, None, args, &result);
+        result
+    }
+
+    async fn restore_trashed(&self, id_or_name: &str) -> BoxliteResult<LiteBox> {
+        let result = self.inner.restore_trashed(id_or_name).await;
+        let box_id = result.as_ref().ok().map(|b| b.id().to_string());
+        let args = BTreeMap::from([("id_or_name".to_string(), id_or_name.to_string())]);
+        self.auditor
+            .emit(AuditOperation::Restore, box_id, args, &result);
+        result.map(|litebox| self.auditor.wrap(litebox))
+    }
+
+    async fn purge_trashed(&self, id_or_name: &str) -> BoxliteResult<()> {
+        let result = self.inner.purge_trashed(id_or_name).await;
+        let args = BTreeMap::from([("id_or_name".to_string(), id_or_name.to_string())]);
+        self.auditor
+            .emit(AuditOperation::Purge, None, args, &result);
+        result
+    }
+
+    async fn list_orphans(&self) -> BoxliteResult<Vec<OrphanShim>> {
+        self.inner.list_orphans().await
+    }
+
+    async fn kill_orphans(&self, grace: Duration) -> BoxliteResult<Vec<OrphanShim>> {
+        let result = self.inner.kill_orphans(grace).await;
+        // One event per killed shim, so each lands under its box
+        if let Ok(killed) = &result {
+            for orphan in killed {
+                let args = BTreeMap::from([
+                    ("orphan".to_string(), "true".to_string()),
+                    ("pid".to_string(), orphan.pid.to_string()),
+                    ("grace_ms".to_string(), grace.as_millis().to_string()),
+                ]);
+                self.auditor.emit(
+                    AuditOperation::Stop,
+                    Some(orphan.box_id.clone()),
+                    args,
+                    &result,
+                );
+            }
+        } else {
+            let args = BTreeMap::from([
+                ("orphan".to_string(), "true".to_string()),
+                ("grace_ms".to_string(), grace.as_millis().to_string()),
+            ]);
+            self.auditor.emit(AuditOperation::Stop, None, args, &result);
+        }
+        result
+    }
+
+    async fn detach_all(&self) -> BoxliteResult<BulkResults<()>> {
+        let result = self.inner.detach_all().await;
+        self.emit_owner_changes(&result);
+        result
+    }
+
+    async fn adopt_all(&self) -> BoxliteResult<BulkResults<()>> {
+        let result = self.inner.adopt_all().await;
+        self.emit_owner_changes(&result);
+        result
+    }
+
+    async fn import(&self, archive_path: &Path, options: ImportOptions) -> BoxliteResult<LiteBox> {
+        let args = import_args(archive_path.display().to_string(), &options);
+        let result = self.inner.import(archive_path, options).await;
+        let box_id = result.as_ref().ok().map(|b| b.id().to_string());
+        self.auditor
+            .emit(AuditOperation::Create, box_id, args, &result);
+        result.map(|litebox| self.auditor.wrap(litebox))
+    }
+
+    async fn plan_import(
+        &self,
+        archive_path: &Path,
+        options: &ImportOptions,
+    ) -> BoxliteResult<ImportPlan> {
+        self.inner.plan_import(archive_path, options).await
+    }
+
+    #[cfg(feature = "url-transfer")]
+    async fn import_from_url(
+        &self,
+        url: &str,
+        options: ImportOptions,
+        transfer: TransferOptions,
+    ) -> BoxliteResult<LiteBox> {
+        // Presigned URLs carry their signature in the query string
+        let archive = url.split('?').next().unwrap_or(url).to_string();
+        let args = import_args(archive, &options);
+        let result = self.inner.import_from_url(url, options, transfer).await;
+        let box_id = result.as_ref().ok().map(|b| b.id().to_string());
+        self.auditor
+            .emit(AuditOperation::Create, box_id, args, &result);
+        result.map(|litebox| self.auditor.wrap(litebox))
+    }
+
+    async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<()> {
+        let result = self.inner.shutdown(timeout).await;
+        let mut args = BTreeMap::new();
+        if let Some(timeout) = timeout {
+            args.insert("timeout".to_string(), timeout.to_string());
+        }
+        self.auditor
+            .emit(AuditOperation::Shutdown, None, args, &result);
+        result
+    }
+
+    fn shutdown_sync(&self) {
+        self.inner.shutdown_sync()
+    }
+
+    #[cfg(feature = "rest-server")]
+    fn shutdown_token(&self) -> Option<tokio_util::sync::CancellationToken> {
+        self.inner.shutdown_token()
+    }
+
+    fn audit_peer(&self) -> Option<String> {
+        self.auditor.peer.clone()
+    }
+}
+
+/// Box backend that records mutating calls to an [`AuditSink`].
+struct AuditedBox {
+    inner: Arc<dyn BoxBackend>,
+    auditor: Auditor,
+}
+
+impl AuditedBox {
+    fn emit<T>(
+        &self,
+        operation: AuditOperation,
+        args: BTreeMap<String, String>,
+        result: &BoxliteResult<T>,
+    ) {
+        let box_id = Some(self.inner.id().to_string());
+        self.auditor.emit(operation, box_id, args, result);
+    }
+}
+
+/// Summarize an exec: program, argument count and env keys (never values).
+fn exec_args(command: &BoxCommand) -> BTreeMap<String, String> {
+    let mut args = BTreeMap::new();
+    args.insert("command".to_string(), command.command.clone());
+    args.insert("args".to_string(), command.args.len().to_string());
+    if let Some(env) = &command.env {
+        let keys: Vec<&str> = env.iter().map(|(key, _)| key.as_str()).collect();
+        args.insert("env_keys".to_string(), keys.join(","));
+    }
+    if let Some(working_dir) = &command.working_dir {
+        args.insert("working_dir".to_string(), working_dir.clone());
+    }
+    if command.tty {
+        args.insert("tty".to_string(), "true".to_string());
+    }
+    args
+}
+
+fn copy_args(host: &Path, container: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([
+        ("host_path".to_string(), host.display().to_string()),
+        ("container_path".to_string(), container.to_string()),
+    ])
+}
+
+#[async_trait]
+impl BoxBackend for AuditedBox {
+    fn id(&self) -> &BoxID {
+        self.inner.id()
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    fn info(&self) -> BoxInfo {
+        self.inner.info()
+    }
+
+    async fn start(&self) -> BoxliteResult<()> {
+        let result = self.inner.start().await;
+        self.emit(AuditOperation::Start, BTreeMap::new(), &result);
+        result
+    }
+
+    async fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution> {
+        let args = exec_args(&command);
+        let result = self.inner.exec(command).await;
+        self.emit(AuditOperation::Exec, args, &result);
+        result
+    }
+
+    async fn prepare_exec(&self, command: BoxCommand) -> BoxliteResult<Option<String>> {
+        self.inner.prepare_exec(command).await
+    }
+
+    async fn exec_prepared(
+        &self,
+        prepared_id: &str,
+        args: Vec<String>,
+    ) -> BoxliteResult<Execution> {
+        let summary = BTreeMap::from([
+            ("prepared_id".to_string(), prepared_id.to_string()),
+            ("args".to_string(), args.len().to_string()),
+        ]);
+        let result = self.inner.exec_prepared(prepared_id, args).await;
+        // The template going missing is an internal retry signal, not a run
+        if !matches!(result, Err(BoxliteError::NotFound(_))) {
+            self.emit(AuditOperation::Exec, summary, &result);
+        }
+        result
+    }
+
+    async fn unprepare_exec(&self, prepared_id: &str) -> BoxliteResult<()> {
+        self.inner.unprepare_exec(prepared_id).await
+    }
+
+    async fn metrics(&self) -> BoxliteResult<BoxMetrics> {
+        self.inner.metrics().await
+    }
+
+    async fn stop(&self) -> BoxliteResult<()> {
+        let result = self.inner.stop().await;
+        self.emit(AuditOperation::Stop, BTreeMap::new(), &result);
+        result
+    }
+
+    async fn copy_into(
+        &self,
+        host_src: &Path,
+        container_dst: &str,
+        opts: CopyOptions,
+    ) -> BoxliteResult<()> {
+        let result = self.inner.copy_into(host_src, container_dst, opts).await;
+        self.emit(
+            AuditOperation::CopyIn,
+            copy_args(host_src, container_dst),
+            &result,
+        );
+        result
+    }
+
+    async fn copy_out(
+        &self,
+        container_src: &str,
+        host_dst: &Path,
+        opts: CopyOptions,
+    ) -> BoxliteResult<()> {
+        let result = self.inner.copy_out(container_src, host_dst, opts).await;
+        self.emit(
+            AuditOperation::CopyOut,
+            copy_args(host_dst, container_src),
+            &result,
+        );
+        result
+    }
+
+    async fn update_resource_limits(&self, limits: ResourceLimits) -> BoxliteResult<()> {
+        let args = BTreeMap::from([("field".to_string(), "resource_limits".to_string())]);
+        let result = self.inner.update_resource_limits(limits).await;
+        self.emit(AuditOperation::Update, args, &result);
+        result
+    }
+
+    fn snapshot_retention(&self) -> Option<SnapshotRetention> {
+        self.inner.snapshot_retention()
+    }
+
+    async fn set_snapshot_retention(
+        &self,
+        retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<()> {
+        let args = BTreeMap::from([("field".to_string(), "snapshot_retention".to_string())]);
+        let result = self.inner.set_snapshot_retention(retention).await;
+        self.emit(AuditOperation::Update, args, &result);
+        result
+    }
+
+    fn snapshot_metadata(&self) -> BoxliteResult<SnapshotMetadata> {
+        self.inner.snapshot_metadata()
+    }
+
+    fn restore_metadata(&self, metadata: SnapshotMetadata) -> BoxliteResult<()> {
+        self.inner.restore_metadata(metadata)
+    }
+
+    fn last_start_failure(&self) -> Option<StartFailure> {
+        self.inner.last_start_failure()
+    }
+
+    fn last_exit(&self) -> Option<ShimExit> {
+        self.inner.last_exit()
+    }
+
+    async fn reprovision(&self) -> BoxliteResult<()> {
+        let args = BTreeMap::from([("field".to_string(), "provisioned".to_string())]);
+        let result = self.inner.reprovision().await;
+        self.emit(AuditOperation::Update, args, &result);
+        result
+    }
+
+    async fn adopt(&self) -> BoxliteResult<()> {
+        let args = BTreeMap::from([("field".to_string(), "owner".to_string())]);
+        let result = self.inner.adopt().await;
+        self.emit(AuditOperation::Update, args, &result);
+        result
+    }
+
+    async fn detach(&self) -> BoxliteResult<()> {
+        let args = BTreeMap::from([("field".to_string(), "owner".to_string())]);
+        let result = self.inner.detach().await;
+        self.emit(AuditOperation::Update, args, &result);
+        result
+    }
+
+    async fn tunnel(&self, guest_port: u16, bind: SocketAddr) -> BoxliteResult<TunnelHandle> {
+        let args = BTreeMap::from([
+            ("guest_port".to_string(), guest_port.to_string()),
+            ("bind".to_string(), bind.to_string()),
+        ]);
+        let result = self.inner.tunnel(guest_port, bind).await;
+        self.emit(AuditOperation::Tunnel, args, &result);
+        result
+    }
+
+    async fn guest_dmesg(&self, tail_lines: usize) -> BoxliteResult<Vec<String>> {
+        self.inner.guest_dmesg(tail_lines).await
+    }
+
+    async fn guest_agent_log(&self, tail_bytes: usize) -> BoxliteResult<GuestAgentLog> {
+        self.inner.guest_agent_log(tail_bytes).await
+    }
+
+    async fn list_core_dumps(&self) -> BoxliteResult<Vec<CoreDump>> {
+        self.inner.list_core_dumps().await
+    }
+
+    async fn sync(&self, freeze_cap: Duration) -> BoxliteResult<Duration> {
+        self.inner.sync(freeze_cap).await
+    }
+
+    async fn exec_output(&self, exec_id: &str) -> BoxliteResult<CapturedOutput> {
+        self.inner.exec_output(exec_id).await
+    }
+
+    async fn environment_reports(&self) -> BoxliteResult<Vec<EnvironmentReport>> {
+        self.inner.environment_reports().await
+    }
+
+    async fn spawn_service(
+        &self,
+        name: &str,
+        command: BoxCommand,
+        policy: ServicePolicy,
+    ) -> BoxliteResult<()> {
+        let mut args = exec_args(&command);
+        args.insert("service".to_string(), name.to_string());
+        let result = self.inner.spawn_service(name, command, policy).await;
+        self.emit(AuditOperation::Exec, args, &result);
+        result
+    }
+
+    async fn stop_service(&self, name: &str) -> BoxliteResult<()> {
+        let args = BTreeMap::from([("service".to_string(), name.to_string())]);
+        let result = self.inner.stop_service(name).await;
+        self.emit(AuditOperation::Stop, args, &result);
+        result
+    }
+
+    async fn remove_service(&self, name: &str) -> BoxliteResult<()> {
+        let args = BTreeMap::from([
+            ("field".to_string(), "services".to_string()),
+            ("service".to_string(), name.to_string()),
+        ]);
+        let result = self.inner.remove_service(name).await;
+        self.emit(AuditOperation::Update, args, &result);
+        result
+    }
+
+    async fn list_services(&self) -> BoxliteResult<Vec<ServiceInfo>> {
+        self.inner.list_services().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use parking_lot::Mutex;
+
+    #[derive(Default)]
+    struct MemorySink(Mutex<Vec<AuditEvent>>);
+
+    impl AuditSink for MemorySink {
+        fn record(&self, event: AuditEvent) {
+            self.0.lock().push(event);
+        }
+    }
+
+    struct PanickingSink;
+
+    impl AuditSink for PanickingSink {
+        fn record(&self, _event: AuditEvent) {
+            panic!("sink exploded");
+        }
+    }
+
+    fn auditor(sink: Arc<dyn AuditSink>) -> Auditor {
+        Auditor {
+            sink,
+            caller: "tester (uid 1000)".to_string(),
+            peer: Some("http://peer".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_emit_records_outcome_and_peer() {
+        let sink = Arc::new(MemorySink::default());
+        let auditor = auditor(sink.clone());
+
+        let err: BoxliteResult<()> = Err(BoxliteError::NotFound("b1".into()));
+        auditor.emit(AuditOperation::Remove, None, BTreeMap::new(), &err);
+
+        let events = sink.0.lock();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].operation, AuditOperation::Remove);
+        assert!(!events[0].outcome.is_success());
+        assert_eq!(events[0].peer.as_deref(), Some("http://peer"));
+    }
+
+    #[test]
+    fn test_emit_survives_panicking_sink() {
+        let auditor = auditor(Arc::new(PanickingSink));
+        let ok: BoxliteResult<()> = Ok(());
+        auditor.emit(
+            AuditOperation::Stop,
+            Some("b1".into()),
+            BTreeMap::new(),
+            &ok,
+        );
+    }
+
+    /// Runtime whose bulk, orphan and template calls succeed; everything
+    /// else is unsupported.
+    struct StubRuntime {
+        boxes: Vec<BoxID>,
+    }
+
+    fn unsupported<T>() -> BoxliteResult<T> {
+        Err(BoxliteError::Unsupported("stub".into()))
+    }
+
+    #[async_trait]
+    impl RuntimeBackend for StubRuntime {
+        async fn create(&self, _: BoxOptions, _: Option<String>) -> BoxliteResult<LiteBox> {
+            unsupported()
+        }
+
+        async fn get_or_create(
+            &self,
+            _: BoxOptions,
+            _: Option<String>,
+        ) -> BoxliteResult<(LiteBox, bool)> {
+            unsupported()
+        }
+
+        async fn get(&self, _: &str) -> BoxliteResult<Option<LiteBox>> {
+            unsupported()
+        }
+
+        async fn get_info(&self, _: &str) -> BoxliteResult<Option<BoxInfo>> {
+            unsupported()
+        }
+
+        async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
+            unsupported()
+        }
+
+        async fn exists(&self, _: &str) -> BoxliteResult<bool> {
+            unsupported()
+        }
+
+        async fn metrics(&self) -> BoxliteResult<RuntimeMetrics> {
+            unsupported()
+        }
+
+        async fn version_info(&self) -> BoxliteResult<VersionInfo> {
+            unsupported()
+        }
+
+        async fn remove(&self, _: &str, _: bool) -> BoxliteResult<()> {
+            unsupported()
+        }
+
+        async fn register_template(
+            &self,
+            name: &str,
+            options: BoxOptions,
+        ) -> BoxliteResult<BoxTemplate> {
+            Ok(BoxTemplate {
+                name: name.to_string(),
+                options,
+                created_at: Utc::now(),
+            })
+        }
+
+        async fn remove_template(&self, _: &str) -> BoxliteResult<()> {
+            Ok(())
+        }
+
+        async fn kill_orphans(&self, _: Duration) -> BoxliteResult<Vec<OrphanShim>> {
+            Ok(self
+                .boxes
+                .iter()
+                .enumerate()
+                .map(|(i, id)| OrphanShim {
+                    pid: 100 + i as u32,
+                    box_id: id.to_string(),
+                    started_at: None,
+                    rss_bytes: 0,
+                    reason: crate::runtime::OrphanReason::NoRecord,
+                })
+                .collect())
+        }
+
+        async fn detach_all(&self) -> BoxliteResult<BulkResults<()>> {
+            Ok(self.boxes.iter().map(|id| (id.clone(), Ok(()))).collect())
+        }
+
+        async fn adopt_all(&self) -> BoxliteResult<BulkResults<()>> {
+            Ok(vec![(
+                self.boxes[0].clone(),
+                Err(BoxliteError::InvalidState("owned".into())),
+            )])
+        }
+
+        async fn shutdown(&self, _: Option<i32>) -> BoxliteResult<()> {
+            Ok(())
+        }
+    }
+
+    fn audited(boxes: Vec<BoxID>) -> (AuditedRuntime, Arc<MemorySink>) {
+        let sink = Arc::new(MemorySink::default());
+        let runtime = AuditedRuntime::new(Arc::new(StubRuntime { boxes }), sink.clone());
+        (runtime, sink)
+    }
+
+    #[tokio::test]
+    async fn test_bulk_ownership_changes_audited_per_box() {
+        let boxes = vec![BoxID::new(), BoxID::new()];
+        let (runtime, sink) = audited(boxes.clone());
+
+        runtime.detach_all().await.unwrap();
+        runtime.adopt_all().await.unwrap();
+
+        let events = sink.0.lock();
+        assert_eq!(events.len(), 3);
+        assert!(events.iter().all(|e| e.operation == AuditOperation::Update));
+        assert!(events.iter().all(|e| e.args["field"] == "owner"));
+        assert_eq!(events[0].box_id.as_deref(), Some(boxes[0].as_str()));
+        assert_eq!(events[1].box_id.as_deref(), Some(boxes[1].as_str()));
+        assert!(events[1].outcome.is_success());
+        // adopt_all refused the first box
+        assert!(!events[2].outcome.is_success());
+    }
+
+    #[tokio::test]
+    async fn test_kill_orphans_audited_per_shim() {
+        let boxes = vec![BoxID::new(), BoxID::new()];
+        let (runtime, sink) = audited(boxes.clone());
+
+        runtime.kill_orphans(Duration::from_secs(2)).await.unwrap();
+
+        let events = sink.0.lock();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].operation, AuditOperation::Stop);
+        assert_eq!(events[0].box_id.as_deref(), Some(boxes[0].as_str()));
+        assert_eq!(events[0].args["pid"], "100");
+        assert_eq!(events[0].args["grace_ms"], "2000");
+    }
+
+    #[tokio::test]
+    async fn test_template_changes_audited() {
+        let (runtime, sink) = audited(Vec::new());
+        let options = BoxOptions {
+            rootfs: RootfsSpec::Image("alpine:latest".into()),
+            ..Default::default()
+        };
+
+        runtime.register_template("web", options).await.unwrap();
+        runtime.remove_template("web").await.unwrap();
+
+        let events = sink.0.lock();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].operation, AuditOperation::Create);
+        assert_eq!(events[0].args["template"], "web");
+        assert_eq!(events[0].args["image"], "alpine:latest");
+        assert_eq!(events[1].operation, AuditOperation::Remove);
+        assert_eq!(events[1].args["template"], "web");
+    }
+
+    #[tokio::test]
+    async fn test_failed_runtime_call_still_audited() {
+        let (runtime, sink) = audited(Vec::new());
+
+        // The stub has no compositions: the failure is recorded too
+        assert!(runtime.forget_composition(&["db".into()]).await.is_err());
+
+        let events = sink.0.lock();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].operation, AuditOperation::Update);
+        assert_eq!(events[0].args["forget"], "db");
+        assert!(!events[0].outcome.is_success());
+    }
+
+    #[test]
+    fn test_exec_args_hide_values() {
+        let command = BoxCommand::new("sh")
+            .args(["-c", "echo $TOKEN"])
+            .env("TOKEN", "s3cret");
+        let args = exec_args(&command);
+
+        assert_eq!(args["command"], "sh");
+        assert_eq!(args["args"], "2");
+        assert_eq!(args["env_keys"], "TOKEN");
+        assert!(
+            !args
+                .values()
+                .any(|v| v.contains("s3cret") || v.contains("echo"))
+        );
+    }
+}
@@ -0,0 +1,137 @@
+//! Audit event types.
+
+use std::collections::BTreeMap;
+
+use boxlite_shared::errors::BoxliteResult;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Operation recorded in the audit log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Create,
+    Remove,
+    Start,
+    Stop,
+    Exec,
+    CopyIn,
+    CopyOut,
+    Tunnel,
+    Update,
+    Shutdown,
+    Restore,
+    Purge,
+}
+
+impl AuditOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOperation::Create => "create",
+            AuditOperation::Remove => "remove",
+            AuditOperation::Start => "start",
+            AuditOperation::Stop => "stop",
+            AuditOperation::Exec => "exec",
+            AuditOperation::CopyIn => "copy_in",
+            AuditOperation::CopyOut => "copy_out",
+            AuditOperation::Tunnel => "tunnel",
+            AuditOperation::Update => "update",
+            AuditOperation::Shutdown => "shutdown",
+            AuditOperation::Restore => "restore",
+            AuditOperation::Purge => "purge",
+        }
+    }
+}
+
+impl std::fmt::Display for AuditOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Result of an audited operation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure { error: String },
+}
+
+impl AuditOutcome {
+    pub(crate) fn from_result<T>(result: &BoxliteResult<T>) -> Self {
+        match result {
+            Ok(_) => AuditOutcome::Success,
+            Err(e) => AuditOutcome::Failure {
+                error: e.to_string(),
+            },
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, AuditOutcome::Success)
+    }
+}
+
+/// A single audit record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// When the operation completed (UTC).
+    pub timestamp: DateTime<Utc>,
+    /// What was done.
+    pub operation: AuditOperation,
+    /// Box the operation targeted (None for runtime-wide operations, or
+    /// when a create failed before an ID was assigned).
+    pub box_id: Option<String>,
+    /// Summarized arguments. Values that may carry secrets (command
+    /// arguments, environment values) are reduced to counts or keys.
+    #[serde(default)]
+    pub args: BTreeMap<String, String>,
+    /// Whether the operation succeeded.
+    pub outcome: AuditOutcome,
+    /// Local identity of the caller (user name and uid).
+    pub caller: String,
+    /// Remote peer identity (REST runtimes only).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub peer: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boxlite_shared::errors::BoxliteError;
+
+    #[test]
+    fn test_outcome_from_result() {
+        let ok: BoxliteResult<()> = Ok(());
+        assert_eq!(AuditOutcome::from_result(&ok), AuditOutcome::Success);
+
+        let err: BoxliteResult<()> = Err(BoxliteError::NotFound("box-1".into()));
+        let outcome = AuditOutcome::from_result(&err);
+        assert!(!outcome.is_success());
+        assert!(matches!(outcome, AuditOutcome::Failure { ref error } if error.contains("box-1")));
+    }
+
+    #[test]
+    fn test_event_json_shape() {
+        let event = AuditEvent {
+            timestamp: Utc::now(),
+            operation: AuditOperation::CopyIn,
+            box_id: Some("01HJK4TNRPQSXYZ8WM6NCVT9R1".into()),
+            args: BTreeMap::from([("container_dst".to_string(), "/app".to_string())]),
+            outcome: AuditOutcome::Failure {
+                error: "boom".into(),
+            },
+            caller: "alice (uid 1000)".into(),
+            peer: None,
+        };
+
+        let json: serde_json::Value = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["operation"], "copy_in");
+        assert_eq!(json["outcome"]["status"], "failure");
+        assert_eq!(json["outcome"]["error"], "boom");
+        assert!(json.get("peer").is_none());
+
+        let back: AuditEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back, event);
+    }
+}
@@ -0,0 +1,280 @@
+//! Append-only JSONL audit sink with size-based rotation.
+//!
+//! Events are appended to `{dir}/audit.jsonl`, one JSON object per line.
+//! When the active file would exceed `max_file_bytes`, it is rotated to
+//! `audit.jsonl.1` (older files shift to `.2`, `.3`, ...) and files beyond
+//! `max_files` are deleted.
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use parking_lot::Mutex;
+
+use super::{AuditEvent, AuditSink};
+
+/// Name of the active audit log file.
+const AUDIT_FILE: &str = "audit.jsonl";
+
+/// Default size at which the active file is rotated (10 MiB).
+const DEFAULT_MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// Default number of rotated files kept next to the active one.
+const DEFAULT_MAX_FILES: usize = 5;
+
+/// JSONL file sink (the default audit sink).
+pub struct JsonlAuditSink {
+    dir: PathBuf,
+    max_file_bytes: u64,
+    max_files: usize,
+    writer: Mutex<Option<ActiveFile>>,
+}
+
+struct ActiveFile {
+    file: File,
+    size: u64,
+}
+
+impl JsonlAuditSink {
+    /// Create a sink writing to `dir`, creating the directory if needed.
+    pub fn new(dir: impl Into<PathBuf>) -> BoxliteResult<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to create audit directory {}: {}",
+                dir.display(),
+                e
+            ))
+        })?;
+
+        Ok(Self {
+            dir,
+            max_file_bytes: DEFAULT_MAX_FILE_BYTES,
+            max_files: DEFAULT_MAX_FILES,
+            writer: Mutex::new(None),
+        })
+    }
+
+    /// Set the size at which the active file is rotated.
+    pub fn with_max_file_bytes(mut self, bytes: u64) -> Self {
+        self.max_file_bytes = bytes;
+        self
+    }
+
+    /// Set how many rotated files are kept.
+    pub fn with_max_files(mut self, files: usize) -> Self {
+        self.max_files = files;
+        self
+    }
+
+    /// Path of the active log file.
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(AUDIT_FILE)
+    }
+
+    /// Read the last `n` events from the logs in `dir`, oldest first.
+    ///
+    /// Reads the active file and, if it holds fewer than `n` events, the
+    /// rotated files before it. Lines that fail to parse are skipped.
+    pub fn tail(dir: &Path, n: usize) -> BoxliteResult<Vec<AuditEvent>> {
+        let mut events = Vec::new();
+
+        for index in 0.. {
+            if events.len() >= n {
+                break;
+            }
+            let path = log_path(dir, index);
+            let file = match File::open(&path) {
+                Ok(file) => file,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => break,
+                Err(e) => {
+                    return Err(BoxliteError::Storage(format!(
+                        "Failed to read audit log {}: {}",
+                        path.display(),
+                        e
+                    )));
+                }
+            };
+
+            let mut file_events: Vec<AuditEvent> = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .filter_map(|line| serde_json::from_str(&line).ok())
+                .collect();
+            // Older file goes in front of what we already collected
+            file_events.append(&mut events);
+            events = file_events;
+        }
+
+        let skip = events.len().saturating_sub(n);
+        Ok(events.split_off(skip))
+    }
+
+    fn write_line(&self, line: &[u8]) -> std::io::Result<()> {
+        let mut writer = self.writer.lock();
+
+        let needs_rotation = writer.as_ref().is_some_and(|active| {
+            active.size > 0 && active.size + line.len() as u64 > self.max_file_bytes
+        });
+        if needs_rotation {
+            *writer = None;
+            self.rotate()?;
+        }
+
+        if writer.is_none() {
+            *writer = Some(self.open_active()?);
+        }
+        let active = writer.as_mut().expect("active audit file opened above");
+
+        active.file.write_all(line)?;
+        active.file.flush()?;
+        active.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn open_active(&self) -> std::io::Result<ActiveFile> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+
+        let file = options.open(self.path())?;
+        let size = file.metadata()?.len();
+        Ok(ActiveFile { file, size })
+    }
+
+    /// Shift `audit.jsonl.{i}` to `.{i+1}`, dropping files past `max_files`.
+    fn rotate(&self) -> std::io::Result<()> {
+        let _ = std::fs::remove_file(log_path(&self.dir, self.max_files));
+
+        for index in (0..self.max_files).rev() {
+            let from = log_path(&self.dir, index);
+            if from.exists() {
+                std::fs::rename(&from, log_path(&self.dir, index + 1))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AuditSink for JsonlAuditSink {
+    fn record(&self, event: AuditEvent) {
+        let mut line = match serde_json::to_vec(&event) {
+            Ok(line) => line,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to serialize audit event");
+                return;
+            }
+        };
+        line.push(b'\n');
+
+        if let Err(e) = self.write_line(&line) {
+            tracing::warn!(
+                path = %self.path().display(),
+                operation = %event.operation,
+                error = %e,
+                "Failed to write audit event"
+            );
+        }
+    }
+}
+
+/// Path of the active file (`index == 0`) or a rotated one.
+fn log_path(dir: &Path, index: usize) -> PathBuf {
+    if index == 0 {
+        dir.join(AUDIT_FILE)
+    } else {
+        dir.join(format!("{AUDIT_FILE}.{index}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audit::{AuditOperation, AuditOutcome};
+    use chrono::Utc;
+    use std::collections::BTreeMap;
+
+    fn event(box_id: &str) -> AuditEvent {
+        AuditEvent {
+            timestamp: Utc::now(),
+            operation: AuditOperation::Exec,
+            box_id: Some(box_id.to_string()),
+            args: BTreeMap::new(),
+            outcome: AuditOutcome::Success,
+            caller: "tester (uid 1000)".to_string(),
+            peer: None,
+        }
+    }
+
+    #[test]
+    fn test_record_appends_jsonl() {
+        let dir = tempfile::tempdir().unwrap();
+        let sink = JsonlAuditSink::new(dir.path()).unwrap();
+
+        sink.record(event("a"));
+        sink.record(event("b"));
+
+        let content = std::fs::read_to_string(sink.path()).unwrap();
+        assert_eq!(content.lines().count(), 2);
+
+        let events = JsonlAuditSink::tail(dir.path(), 10).unwrap();
+        let ids: Vec<_> = events
+            .iter()
+            .map(|e| e.box_id.as_deref().unwrap())
+            .collect();
+        assert_eq!(ids, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_rotation_keeps_max_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let line_len = serde_json::to_vec(&event("0")).unwrap().len() as u64 + 1;
+        // Room for two events per file, keep two rotated files
+        let sink = JsonlAuditSink::new(dir.path())
+            .unwrap()
+            .with_max_file_bytes(line_len * 2)
+            .with_max_files(2);
+
+        for i in 0..8 {
+            sink.record(event(&i.to_string()));
+        }
+
+        assert!(log_path(dir.path(), 1).exists());
+        assert!(log_path(dir.path(), 2).exists());
+        assert!(!log_path(dir.path(), 3).exists());
+
+        // Oldest events were dropped with the deleted file
+        let events = JsonlAuditSink::tail(dir.path(), 100).unwrap();
+        let ids: Vec<_> = events.iter().map(|e| e.box_id.clone().unwrap()).collect();
+        assert_eq!(ids, vec!["2", "3", "4", "5", "6", "7"]);
+    }
+
+    #[test]
+    fn test_tail_limits_and_spans_rotated_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let line_len = serde_json::to_vec(&event("0")).unwrap().len() as u64 + 1;
+        let sink = JsonlAuditSink::new(dir.path())
+            .unwrap()
+            .with_max_file_bytes(line_len * 2);
+
+        for i in 0..5 {
+            sink.record(event(&i.to_string()));
+        }
+
+        let events = JsonlAuditSink::tail(dir.path(), 3).unwrap();
+        let ids: Vec<_> = events.iter().map(|e| e.box_id.clone().unwrap()).collect();
+        assert_eq!(ids, vec!["2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_tail_missing_dir_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = JsonlAuditSink::tail(&dir.path().join("missing"), 5).unwrap();
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,40 @@
+//! Audit log of mutating runtime operations.
+//!
+//! When enabled, every mutating call that reaches a runtime or box backend
+//! (create, remove, start, stop, exec, copy, shutdown) emits an [`AuditEvent`]
+//! to an [`AuditSink`]. Events carry the caller identity and, for REST
+//! runtimes, the remote peer.
+//!
+//! Auditing is off by default. Turn it on with `BoxliteOptions::audit`
+//! (JSONL files under `~/.boxlite/audit/`) or plug in a custom sink with
+//! `BoxliteRuntime::with_audit_sink()`.
+//!
+//! Sinks never fail the audited operation: write errors are logged and the
+//! operation continues.
+
+mod backend;
+mod event;
+mod jsonl;
+
+pub(crate) use backend::AuditedRuntime;
+pub use event::{AuditEvent, AuditOperation, AuditOutcome};
+pub use jsonl::JsonlAuditSink;
+
+use std::path::{Path, PathBuf};
+
+/// Subdirectory of the BoxLite home holding the JSONL audit log.
+pub const AUDIT_DIR: &str = "audit";
+
+/// Destination for audit events.
+///
+/// `record` must not block for long and must not fail the caller; report
+/// problems through `tracing` instead.
+pub trait AuditSink: Send + Sync {
+    /// Record a single event.
+    fn record(&self, event: AuditEvent);
+}
+
+/// Default audit directory for a BoxLite home: `{home_dir}/audit`.
+pub fn audit_dir(home_dir: &Path) -> PathBuf {
+    home_dir.join(AUDIT_DIR)
+}
@@ -0,0 +1,525 @@
+//! End-to-end latency benchmarks of the sandbox lifecycle.
+//!
+//! Enabled by the `bench` feature. `boxlite bench` runs the same scenarios:
+//!
+//! - [`Scenario::ColdStart`]: create, start, exec and remove a fresh box
+//! - [`Scenario::WarmExec`]: exec a trivial command in a running box
+//! - [`Scenario::CopyIn`]: copy a generated file tree into a running box
+//! - [`Scenario::Snapshot`]: create and restore a snapshot of a stopped box
+//!
+//! Every scenario runs once untimed first, so image pulls and disk builds
+//! are not measured. Boxes created by a run are removed when it ends,
+//! including when SIGINT or SIGTERM stops it early.
+//!
+//! ```no_run
+//! use boxlite::bench::{self, BenchOptions, Scenario};
+//! use boxlite::BoxliteRuntime;
+//!
+//! # async fn example() -> boxlite::BoxliteResult<()> {
+//! let runtime = BoxliteRuntime::with_defaults()?;
+//! let report = bench::run(&runtime, &BenchOptions::default(), &Scenario::ALL).await?;
+//! for result in &report.results {
+//!     println!("{} {}: p50 {:.1}ms", result.scenario, result.operation, result.stats.p50_ms);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::litebox::snapshot_types::{RestoreOptions, SnapshotOptions};
+use crate::litebox::{BoxCommand, CopyOptions, LiteBox};
+use crate::runtime::BoxliteRuntime;
+use crate::runtime::options::{BoxOptions, RootfsSpec};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Image benchmarked by default.
+pub const DEFAULT_IMAGE: &str = "alpine:latest";
+
+/// Prefix of the names of boxes created by a run.
+pub const BOX_NAME_PREFIX: &str = "boxlite-bench-";
+
+/// Destination of [`Scenario::CopyIn`] copies inside the box.
+const COPY_DEST: &str = "/tmp/boxlite-bench";
+
+/// Files per directory of the generated copy tree.
+const FILES_PER_DIR: usize = 100;
+
+/// A benchmark scenario.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Scenario {
+    /// Create, start, exec `true` in and force-remove a new box, timed as one.
+    ColdStart,
+    /// Exec `true` in one running box, each exec timed to its exit.
+    WarmExec,
+    /// Copy the generated file tree into one running box.
+    CopyIn,
+    /// Snapshot a stopped box, then restore that snapshot, timed apart.
+    Snapshot,
+}
+
+impl Scenario {
+    /// Every scenario, in the order `boxlite bench` runs them.
+    pub const ALL: [Scenario; 4] = [
+        Scenario::ColdStart,
+        Scenario::WarmExec,
+        Scenario::CopyIn,
+        Scenario::Snapshot,
+    ];
+
+    /// Kebab-case name, as on the command line.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scenario::ColdStart => "cold-start",
+            Scenario::WarmExec => "warm-exec",
+            Scenario::CopyIn => "copy-in",
+            Scenario::Snapshot => "snapshot",
+        }
+    }
+}
+
+impl std::fmt::Display for Scenario {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Scenario {
+    type Err = BoxliteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Scenario::ALL
+            .into_iter()
+            .find(|scenario| scenario.as_str() == s)
+            .ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!(
+                    "invalid scenario '{}' (expected cold-start, warm-exec, copy-in or snapshot)",
+                    s
+                ))
+            })
+    }
+}
+
+/// What to benchmark with.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Image of the benchmarked boxes (default: [`DEFAULT_IMAGE`]).
+    pub image: String,
+    /// Timed iterations per scenario (default: 10).
+    pub iterations: usize,
+    /// Files in the tree copied by [`Scenario::CopyIn`] (default: 1000).
+    pub copy_files: usize,
+    /// Size of each of those files in bytes (default: 4096).
+    pub copy_file_size: usize,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        Self {
+            image: DEFAULT_IMAGE.to_string(),
+            iterations: 10,
+            copy_files: 1000,
+            copy_file_size: 4096,
+        }
+    }
+}
+
+/// Latency distribution of one timed operation, in milliseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl LatencyStats {
+    /// Nearest-rank percentiles of `samples`, or None if there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+        let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+        let percentile = |p: usize| ms(sorted[(sorted.len() * p).div_ceil(100).max(1) - 1]);
+        Some(Self {
+            samples: sorted.len(),
+            p50_ms: percentile(50),
+            p95_ms: percentile(95),
+            max_ms: ms(*sorted.last()?),
+        })
+    }
+}
+
+/// Timings of one operation of a scenario.
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchResult {
+    pub scenario: Scenario,
+    /// What was timed: `cold_start`, `exec`, `copy_in`, `snapshot_create`
+    /// or `snapshot_restore`.
+    pub operation: String,
+    pub stats: LatencyStats,
+    /// Bytes per second at the median latency, for operations moving data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub throughput_bytes_per_sec: Option<f64>,
+}
+
+/// Outcome of [`run`].
+#[derive(Debug, Clone, Serialize)]
+pub struct BenchReport {
+    pub image: String,
+    pub iterations: usize,
+    /// Results of the scenarios that finished, in the order they ran.
+    pub results: Vec<BenchResult>,
+    /// Whether a signal stopped the run before every scenario finished.
+    pub interrupted: bool,
+}
+
+/// Run `scenarios` on `runtime`, stopping early on SIGINT or SIGTERM.
+///
+/// The boxes the run created are removed before this returns, whether the
+/// run finished, failed or was interrupted. An interrupted run reports the
+/// scenarios that finished, with [`BenchReport::interrupted`] set.
+pub async fn run(
+    runtime: &BoxliteRuntime,
+    options: &BenchOptions,
+    scenarios: &[Scenario],
+) -> BoxliteResult<BenchReport> {
+    run_until(runtime, options, scenarios, shutdown_signal()).await
+}
+
+/// [`run`], stopping early when `stop` completes instead of on a signal.
+pub async fn run_until(
+    runtime: &BoxliteRuntime,
+    options: &BenchOptions,
+    scenarios: &[Scenario],
+    stop: impl Future<Output = ()>,
+) -> BoxliteResult<BenchReport> {
+    if options.iterations == 0 {
+        return Err(BoxliteError::InvalidArgument(
+            "iterations must be at least 1".into(),
+        ));
+    }
+
+    let harness = Harness::new(runtime, options);
+    let outcome = tokio::select! {
+        result = harness.run_all(scenarios) => result.map(|()| false),
+        () = stop => Ok(true),
+    };
+    // The scenario future is dropped by now, so no box is being created
+    harness.cleanup().await;
+
+    let interrupted = outcome?;
+    if interrupted {
+        tracing::info!("Benchmark interrupted, boxes removed");
+    }
+    Ok(BenchReport {
+        image: options.image.clone(),
+        iterations: options.iterations,
+        results: harness.results.into_inner().unwrap_or_default(),
+        interrupted,
+    })
+}
+
+/// Completes on the first SIGINT or SIGTERM.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to listen for SIGTERM: {}", e);
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+/// State of one run: the boxes to remove and the results so far.
+struct Harness<'a> {
+    runtime: &'a BoxliteRuntime,
+    options: &'a BenchOptions,
+    run_id: String,
+    /// Names of every box the run asked for, recorded before creating it
+    boxes: Mutex<Vec<String>>,
+    results: Mutex<Vec<BenchResult>>,
+}
+
+impl<'a> Harness<'a> {
+    fn new(runtime: &'a BoxliteRuntime, options: &'a BenchOptions) -> Self {
+        Self {
+            runtime,
+            options,
+            run_id: ulid::Ulid::new().to_string().to_lowercase(),
+            boxes: Mutex::new(Vec::new()),
+            results: Mutex::new(Vec::new()),
+        }
+    }
+
+    async fn run_all(&self, scenarios: &[Scenario]) -> BoxliteResult<()> {
+        for &scenario in scenarios {
+            tracing::info!(%scenario, iterations = self.options.iterations, "Running benchmark");
+            match scenario {
+                Scenario::ColdStart => self.cold_start().await?,
+                Scenario::WarmExec => self.warm_exec().await?,
+                Scenario::CopyIn => self.copy_in().await?,
+                Scenario::Snapshot => self.snapshot().await?,
+            }
+        }
+        Ok(())
+    }
+
+    async fn cold_start(&self) -> BoxliteResult<()> {
+        // Untimed warm-up pulls the image and builds its disk
+        self.cold_start_once().await?;
+        let mut samples = Vec::with_capacity(self.options.iterations);
+        for _ in 0..self.options.iterations {
+            samples.push(self.cold_start_once().await?);
+        }
+        self.record(Scenario::ColdStart, "cold_start", &samples, None);
+        Ok(())
+    }
+
+    async fn cold_start_once(&self) -> BoxliteResult<Duration> {
+        let started = Instant::now();
+        let litebox = self.create_box().await?;
+        litebox.start().await?;
+        exec_true(&litebox).await?;
+        self.runtime
+            .remove_permanently(litebox.id().as_str(), true)
+            .await?;
+        Ok(started.elapsed())
+    }
+
+    async fn warm_exec(&self) -> BoxliteResult<()> {
+        let litebox = self.create_box().await?;
+        litebox.start().await?;
+        exec_true(&litebox).await?;
+
+        let mut samples = Vec::with_capacity(self.options.iterations);
+        for _ in 0..self.options.iterations {
+            let started = Instant::now();
+            exec_true(&litebox).await?;
+            samples.push(started.elapsed());
+        }
+        self.record(Scenario::WarmExec, "exec", &samples, None);
+        self.remove(&litebox).await
+    }
+
+    async fn copy_in(&self) -> BoxliteResult<()> {
+        let tree = tempfile::tempdir().map_err(|e| {
+            BoxliteError::Storage(format!("Failed to create benchmark directory: {}", e))
+        })?;
+        let bytes = write_tree(
+            tree.path(),
+            self.options.copy_files,
+            self.options.copy_file_size,
+        )?;
+
+        let litebox = self.create_box().await?;
+        litebox.start().await?;
+        let copy = |i: usize| {
+            let opts = CopyOptions {
+                recursive: true,
+                overwrite: true,
+                ..Default::default()
+            };
+            litebox.copy_into(tree.path(), format!("{}/{}", COPY_DEST, i), opts)
+        };
+        copy(0).await?;
+
+        let mut samples = Vec::with_capacity(self.options.iterations);
+        for i in 1..=self.options.iterations {
+            let started = Instant::now();
+            copy(i).await?;
+            samples.push(started.elapsed());
+        }
+        self.record(Scenario::CopyIn, "copy_in", &samples, Some(bytes));
+        self.remove(&litebox).await
+    }
+
+    async fn snapshot(&self) -> BoxliteResult<()> {
+        // Snapshots need a stopped box whose disks exist
+        let litebox = self.create_box().await?;
+        litebox.start().await?;
+        litebox.stop().await?;
+
+        let snapshots = litebox.snapshot();
+        let mut created = Vec::with_capacity(self.options.iterations);
+        let mut restored = Vec::with_capacity(self.options.iterations);
+        for i in 0..=self.options.iterations {
+            let name = format!("bench-{}", i);
+            let started = Instant::now();
+            snapshots.create(&name, SnapshotOptions::default()).await?;
+            let create = started.elapsed();
+
+            let started = Instant::now();
+            snapshots.restore(&name, RestoreOptions::default()).await?;
+            let restore = started.elapsed();
+
+            // The first round is the warm-up
+            if i > 0 {
+                created.push(create);
+                restored.push(restore);
+            }
+        }
+        self.record(Scenario::Snapshot, "snapshot_create", &created, None);
+        self.record(Scenario::Snapshot, "snapshot_restore", &restored, None);
+        self.remove(&litebox).await
+    }
+
+    /// Create a box of the benchmarked image, named so cleanup finds it.
+    async fn create_box(&self) -> BoxliteResult<LiteBox> {
+        let name = {
+            let mut boxes = self.boxes.lock().unwrap();
+            let name = format!("{}{}-{}", BOX_NAME_PREFIX, self.run_id, boxes.len());
+            boxes.push(name.clone());
+            name
+        };
+        let options = BoxOptions {
+            rootfs: RootfsSpec::Image(self.options.image.clone()),
+            auto_remove: false,
+            ..Default::default()
+        };
+        self.runtime.create(options, Some(name)).await
+    }
+
+    async fn remove(&self, litebox: &LiteBox) -> BoxliteResult<()> {
+        self.runtime
+            .remove_permanently(litebox.id().as_str(), true)
+            .await
+    }
+
+    fn record(
+        &self,
+        scenario: Scenario,
+        operation: &str,
+        samples: &[Duration],
+        bytes: Option<u64>,
+    ) {
+        let Some(stats) = LatencyStats::from_samples(samples) else {
+            return;
+        };
+        let throughput_bytes_per_sec = bytes
+            .filter(|_| stats.p50_ms > 0.0)
+            .map(|bytes| bytes as f64 * 1000.0 / stats.p50_ms);
+        self.results.lock().unwrap().push(BenchResult {
+            scenario,
+            operation: operation.to_string(),
+            stats,
+            throughput_bytes_per_sec,
+        });
+    }
+
+    /// Force-remove every box the run created that still exists.
+    async fn cleanup(&self) {
+        let boxes = std::mem::take(&mut *self.boxes.lock().unwrap());
+        for name in boxes {
+            match self.runtime.remove_permanently(&name, true).await {
+                Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+                Err(e) => {
+                    tracing::warn!(box_name = %name, error = %e, "Failed to remove benchmark box")
+                }
+            }
+        }
+    }
+}
+
+/// Run `true` in `litebox` to completion.
+async fn exec_true(litebox: &LiteBox) -> BoxliteResult<()> {
+    let result = litebox.exec(BoxCommand::new("true")).await?.wait().await?;
+    if result.exit_code != 0 {
+        return Err(BoxliteError::Execution(format!(
+            "benchmark command exited with code {}",
+            result.exit_code
+        )));
+    }
+    Ok(())
+}
+
+/// Write `files` files of `file_size` bytes under `dir`, at most
+/// [`FILES_PER_DIR`] per directory. Returns the bytes written.
+///
+/// The contents are pseudo-random so compression does not flatter copies.
+fn write_tree(dir: &Path, files: usize, file_size: usize) -> BoxliteResult<u64> {
+    let write_error =
+        |e: std::io::Error| BoxliteError::Storage(format!("Failed to write benchmark tree: {}", e));
+    let mut state = 0x9e37_79b9_7f4a_7c15u64;
+    let mut data = vec![0u8; file_size];
+    for i in 0..files {
+        for byte in data.iter_mut() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            *byte = state as u8;
+        }
+        let subdir = dir.join(format!("d{}", i / FILES_PER_DIR));
+        if i % FILES_PER_DIR == 0 {
+            std::fs::create_dir_all(&subdir).map_err(write_error)?;
+        }
+        std::fs::write(subdir.join(format!("f{}", i)), &data).map_err(write_error)?;
+    }
+    Ok((files * file_size) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_latency_stats_use_nearest_rank() {
+        let samples: Vec<_> = (1..=20).rev().map(Duration::from_millis).collect();
+        let stats = LatencyStats::from_samples(&samples).unwrap();
+        assert_eq!(stats.samples, 20);
+        assert_eq!(stats.p50_ms, 10.0);
+        assert_eq!(stats.p95_ms, 19.0);
+        assert_eq!(stats.max_ms, 20.0);
+
+        let one = LatencyStats::from_samples(&[Duration::from_millis(7)]).unwrap();
+        assert_eq!((one.p50_ms, one.p95_ms, one.max_ms), (7.0, 7.0, 7.0));
+        assert!(LatencyStats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_scenario_names_round_trip() {
+        for scenario in Scenario::ALL {
+            assert_eq!(scenario.as_str().parse::<Scenario>().unwrap(), scenario);
+            assert_eq!(
+                serde_json::to_value(scenario).unwrap(),
+                serde_json::json!(scenario.as_str())
+            );
+        }
+        assert!(matches!(
+            "warm".parse::<Scenario>(),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_write_tree_spreads_files_over_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let bytes = write_tree(dir.path(), 250, 64).unwrap();
+        assert_eq!(bytes, 250 * 64);
+
+        let dirs = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(dirs, 3);
+        let last = std::fs::read(dir.path().join("d2/f249")).unwrap();
+        assert_eq!(last.len(), 64);
+        assert_ne!(last, std::fs::read(dir.path().join("d0/f0")).unwrap());
+    }
+}
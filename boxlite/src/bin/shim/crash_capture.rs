@@ -3,13 +3,25 @@
 //! Captures crash information (panics, signals) to an exit file for diagnostics.
 //! Signal handlers can't capture closures, so we use global statics for paths.
 //!
+//! Alongside the exit file, a [`DiagnosticsCollector`] writes the shim's
+//! recent operations and open fd count (see [`boxlite::vmm::crash_diagnostics`]).
+//! In signal context everything is written with async-signal-safe
+//! primitives: paths are converted to C strings at install time and JSON is
+//! rendered into stack buffers.
+//!
 //! Note: Stderr content is captured separately by the parent process (to shim.stderr).
 //! CrashReport reads it directly from file - we don't embed it in the exit file.
 //!
 //! Uses [`boxlite::vmm::ExitInfo`] for the JSON format.
 
 use boxlite::vmm::ExitInfo;
-use std::path::PathBuf;
+use boxlite::vmm::crash_diagnostics::{
+    DiagnosticsCollector, MAX_DIAGNOSTICS_BYTES, diagnostics_path, write_file,
+};
+use std::ffi::CString;
+use std::io::Write as _;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 /// Unix convention: exit code for signal-terminated process = 128 + signal number.
@@ -18,8 +30,15 @@ const SIGNAL_EXIT_CODE_BASE: i32 = 128;
 /// Exit code for Rust panics.
 const PANIC_EXIT_CODE: i32 = 101;
 
-/// Global exit file path for signal handlers.
-static EXIT_FILE_PATH: OnceLock<PathBuf> = OnceLock::new();
+/// Everything the signal handler needs, prepared at install time.
+struct SignalContext {
+    exit_file: CString,
+    diagnostics_file: CString,
+    collector: DiagnosticsCollector,
+}
+
+/// Global crash context for signal handlers.
+static SIGNAL_CONTEXT: OnceLock<SignalContext> = OnceLock::new();
 
 /// Crash capture installer.
 ///
@@ -30,14 +49,25 @@ impl CrashCapture {
     /// Install crash capture mechanisms (panic hook + signal handlers).
     ///
     /// - `exit_file`: Where to write crash info (JSON format)
-    pub fn install(exit_file: PathBuf) {
-        install_panic_hook(exit_file.clone());
-        install_signal_handlers(exit_file);
+    /// - `collector`: Snapshots diagnostics into the file next to `exit_file`
+    pub fn install(exit_file: PathBuf, collector: DiagnosticsCollector) {
+        // Diagnostics left by an earlier run would be attributed to this one
+        let diagnostics_file = diagnostics_path(&exit_file);
+        if diagnostics_file.exists() {
+            let _ = std::fs::File::create(&diagnostics_file);
+        }
+
+        install_panic_hook(exit_file.clone(), diagnostics_file.clone(), collector);
+        install_signal_handlers(&exit_file, &diagnostics_file, collector);
     }
 }
 
 /// Install panic hook that writes JSON to exit file AND log.
-fn install_panic_hook(exit_file: PathBuf) {
+fn install_panic_hook(
+    exit_file: PathBuf,
+    diagnostics_file: PathBuf,
+    collector: DiagnosticsCollector,
+) {
     let default_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
         let message = panic_info
@@ -63,13 +93,32 @@ fn install_panic_hook(exit_file: PathBuf) {
             let _ = std::fs::write(&exit_file, json);
         }
 
+        let mut buf = vec![0u8; MAX_DIAGNOSTICS_BYTES];
+        let len = collector.render(&mut buf);
+        let _ = std::fs::write(&diagnostics_file, &buf[..len]);
+
         default_hook(panic_info);
     }));
 }
 
 /// Install Unix signal handlers to catch C library crashes.
-fn install_signal_handlers(exit_file: PathBuf) {
-    let _ = EXIT_FILE_PATH.set(exit_file);
+fn install_signal_handlers(
+    exit_file: &Path,
+    diagnostics_file: &Path,
+    collector: DiagnosticsCollector,
+) {
+    let (Ok(exit_file), Ok(diagnostics_file)) = (
+        CString::new(exit_file.as_os_str().as_bytes()),
+        CString::new(diagnostics_file.as_os_str().as_bytes()),
+    ) else {
+        tracing::warn!("Crash file path contains a NUL byte, signal capture disabled");
+        return;
+    };
+    let _ = SIGNAL_CONTEXT.set(SignalContext {
+        exit_file,
+        diagnostics_file,
+        collector,
+    });
 
     unsafe {
         libc::signal(libc::SIGABRT, crash_signal_handler as *const () as usize);
@@ -80,11 +129,11 @@ fn install_signal_handlers(exit_file: PathBuf) {
     }
 }
 
-/// Signal handler that writes JSON crash info to exit file.
+/// Signal handler that writes JSON crash info and diagnostics.
 ///
-/// Note: We intentionally don't read stderr here. Signal handlers should be
-/// minimal and avoid async-signal-unsafe operations. CrashReport reads stderr
-/// directly from the file when formatting the error message.
+/// Only async-signal-safe operations: no allocation, no locks, raw
+/// `open`/`write` syscalls. We intentionally don't read stderr here;
+/// CrashReport reads it directly from the file when formatting the error.
 extern "C" fn crash_signal_handler(sig: libc::c_int) {
     let signal = match sig {
         libc::SIGABRT => "SIGABRT",
@@ -95,14 +144,8 @@ extern "C" fn crash_signal_handler(sig: libc::c_int) {
         _ => "UNKNOWN",
     };
 
-    if let Some(exit_file) = EXIT_FILE_PATH.get() {
-        let info = ExitInfo::Signal {
-            exit_code: SIGNAL_EXIT_CODE_BASE + sig,
-            signal: signal.to_string(),
-        };
-        if let Ok(json) = serde_json::to_string(&info) {
-            let _ = std::fs::write(exit_file, json);
-        }
+    if let Some(ctx) = SIGNAL_CONTEXT.get() {
+        write_signal_exit(ctx, sig, signal);
     }
 
     unsafe {
@@ -110,3 +153,51 @@ extern "C" fn crash_signal_handler(sig: libc::c_int) {
         libc::raise(sig);
     }
 }
+
+/// Write the exit file and diagnostics for a fatal signal.
+///
+/// Produces the same JSON as serializing [`ExitInfo::Signal`], without allocating.
+fn write_signal_exit(ctx: &SignalContext, sig: libc::c_int, signal: &str) {
+    let mut buf = [0u8; 128];
+    let mut cursor = std::io::Cursor::new(&mut buf[..]);
+    let written = write!(
+        cursor,
+        r#"{{"type":"signal","exit_code":{},"signal":"{}"}}"#,
+        SIGNAL_EXIT_CODE_BASE + sig,
+        signal
+    );
+    if written.is_ok() {
+        let len = cursor.position() as usize;
+        write_file(&ctx.exit_file, &buf[..len]);
+    }
+
+    ctx.collector.write_to(&ctx.diagnostics_file);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boxlite::vmm::OperationLog;
+
+    #[test]
+    fn test_signal_exit_matches_exit_info() {
+        static OPERATIONS: OperationLog = OperationLog::new();
+        OPERATIONS.record("instance.enter");
+
+        let dir = tempfile::tempdir().unwrap();
+        let exit_file = dir.path().join("exit");
+        install_signal_handlers(
+            &exit_file,
+            &diagnostics_path(&exit_file),
+            DiagnosticsCollector::new(&OPERATIONS),
+        );
+
+        write_signal_exit(SIGNAL_CONTEXT.get().unwrap(), libc::SIGSEGV, "SIGSEGV");
+
+        let exit = boxlite::vmm::ShimExit::from_exit_file(&exit_file).unwrap();
+        assert_eq!(exit.info.exit_code(), 139);
+        assert_eq!(exit.info.signal_name(), Some("SIGSEGV"));
+        let diagnostics = exit.diagnostics.unwrap();
+        assert_eq!(diagnostics.recent_operations[0].name, "instance.enter");
+    }
+}
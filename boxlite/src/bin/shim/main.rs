@@ -14,13 +14,20 @@
 
 mod crash_capture;
 
-use std::path::Path;
+use std::os::fd::IntoRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
+#[cfg(target_os = "macos")]
+use boxlite::BoxPriority;
 use boxlite::{
     util,
-    vmm::{self, ExitInfo, InstanceSpec, VmmConfig, VmmKind, controller::watchdog},
+    vmm::{
+        self, DiagnosticsCollector, ExitInfo, IdlePolicy, IdleSample, IdleTracker, InstanceSpec,
+        OperationLog, VmmConfig, VmmKind, controller::watchdog, idle,
+    },
 };
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use clap::Parser;
@@ -31,6 +38,17 @@ use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 #[cfg(feature = "gvproxy-backend")]
 use boxlite::net::{ConnectionType, NetworkBackendEndpoint, gvproxy::GvproxyInstance};
 
+/// Recent shim operations, included in crash diagnostics.
+static OPERATIONS: OperationLog = OperationLog::new();
+
+/// Bumped when the owner releases the box. A parent watchdog started under
+/// an earlier value ignores the hangup of its pipe.
+static OWNER_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Whether a host process owns the box. The owner applies idle auto-stop
+/// itself; the shim does while this is false.
+static OWNED: AtomicBool = AtomicBool::new(false);
+
 /// Universal Box runner binary - subprocess that executes isolated Boxes
 #[derive(Parser, Debug)]
 #[command(
@@ -80,6 +98,28 @@ fn init_logging(box_dir: &Path) -> tracing_appender::non_blocking::WorkerGuard {
     guard
 }
 
+/// Move the shim to the QoS class of the box priority.
+///
+/// New threads take the class of the thread that creates them, so this runs
+/// before gvproxy and the VMM start their threads.
+#[cfg(target_os = "macos")]
+fn apply_qos_class(priority: BoxPriority) {
+    use libc::qos_class_t::{QOS_CLASS_USER_INITIATED, QOS_CLASS_UTILITY};
+
+    let class = match priority {
+        BoxPriority::Low => QOS_CLASS_UTILITY,
+        BoxPriority::Normal => return,
+        BoxPriority::High => QOS_CLASS_USER_INITIATED,
+    };
+    // SAFETY: only changes the scheduling class of the calling thread
+    let rc = unsafe { libc::pthread_set_qos_class_self_np(class, 0) };
+    if rc == 0 {
+        tracing::info!(%priority, "QoS class set");
+    } else {
+        tracing::warn!(%priority, error = rc, "Failed to set QoS class");
+    }
+}
+
 fn main() -> BoxliteResult<()> {
     // Parse command line arguments with clap
     // VmmKind parsed via FromStr trait automatically
@@ -101,7 +141,11 @@ fn main() -> BoxliteResult<()> {
     // Install crash capture (panic hook, signal handlers).
     // Note: stderr is already redirected to file by parent process (spawn.rs).
     // CrashReport reads stderr content directly from shim.stderr when needed.
-    CrashCapture::install(config.exit_file.clone());
+    CrashCapture::install(
+        config.exit_file.clone(),
+        DiagnosticsCollector::new(&OPERATIONS),
+    );
+    OPERATIONS.record("shim.start");
 
     tracing::info!(
         engine = ?args.engine,
@@ -135,6 +179,9 @@ fn run_shim(args: ShimArgs, mut config: InstanceSpec) -> BoxliteResult<()> {
         "Guest entrypoint configured"
     );
 
+    #[cfg(target_os = "macos")]
+    apply_qos_class(config.priority);
+
     // =========================================================================
     // Network backend (gvproxy) + Seccomp
     // =========================================================================
@@ -145,17 +192,23 @@ fn run_shim(args: ShimArgs, mut config: InstanceSpec) -> BoxliteResult<()> {
     // duration of the VM. When the shim process exits, OS cleans up all resources.
     #[cfg(feature = "gvproxy-backend")]
     if let Some(ref net_config) = config.network_config {
+        OPERATIONS.record("network.create");
         tracing::info!(
             port_mappings = ?net_config.port_mappings,
             "Creating network backend (gvproxy) from config"
         );
 
-        // Create gvproxy instance with caller-provided socket path
-        let gvproxy =
-            GvproxyInstance::new(net_config.socket_path.clone(), &net_config.port_mappings)?;
+        // Create gvproxy instance with caller-provided socket path and static lease
+        let gvproxy = GvproxyInstance::new(
+            net_config.socket_path.clone(),
+            &net_config.port_mappings,
+            &net_config.guest,
+        )?;
 
         tracing::info!(
             socket_path = ?net_config.socket_path,
+            guest_ip = %net_config.guest.guest_ip,
+            guest_mac = %net_config.guest.guest_mac,
             "Network backend created"
         );
 
@@ -169,13 +222,11 @@ fn run_shim(args: ShimArgs, mut config: InstanceSpec) -> BoxliteResult<()> {
             ConnectionType::UnixStream
         };
 
-        // Use GUEST_MAC constant - must match DHCP static lease in gvproxy config
-        use boxlite::net::constants::GUEST_MAC;
-
+        // Guest MAC must match the DHCP static lease in gvproxy config
         config.network_backend_endpoint = Some(NetworkBackendEndpoint::UnixSocket {
             path: net_config.socket_path.clone(),
             connection_type,
-            mac_address: GUEST_MAC,
+            mac_address: net_config.guest.mac_address()?,
         });
 
         // Leak the gvproxy instance to keep it alive for VM lifetime.
@@ -215,20 +266,31 @@ fn run_shim(args: ShimArgs, mut config: InstanceSpec) -> BoxliteResult<()> {
         }
     }
 
-    // Save detach/transport before config is moved into engine.create()
+    // Save detach/transport/grace/idle policy/box dir before config is moved
+    // into engine.create()
     let detach = config.detach;
     let transport = config.transport.clone();
+    let grace = config.shutdown_grace_period;
+    let idle_policy = config.idle_policy;
+    let exit_file = config.exit_file.clone();
+    let box_dir = config
+        .exit_file
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
 
     // Initialize engine options with defaults
     let options = VmmConfig::default();
 
     // Create engine using inventory pattern (no match statement needed!)
     // Engines auto-register themselves at compile time
+    OPERATIONS.record("engine.create");
     let mut engine = vmm::create_engine(args.engine, options)?;
 
     tracing::info!("Engine created, creating Box instance");
 
     // Create Box instance with the provided configuration
+    OPERATIONS.record("instance.create");
     let instance = match engine.create(config) {
         Ok(instance) => instance,
         Err(e) => {
@@ -241,21 +303,31 @@ fn run_shim(args: ShimArgs, mut config: InstanceSpec) -> BoxliteResult<()> {
 
     // Install SIGTERM handler for graceful shutdown (all boxes, detached or not).
     // When SIGTERM is received: Guest.Shutdown() RPC (flush qcow2) → re-raise SIGTERM.
-    install_graceful_shutdown_handler(transport);
+    install_graceful_shutdown_handler(transport.clone(), grace);
 
     // Start parent watchdog if detach=false.
     // The parent holds the write end of a pipe (fd 3 in this process).
     // When parent dies or drops the keepalive, kernel closes the write end,
     // delivering POLLHUP to our watchdog thread → SIGTERM → graceful shutdown.
+    OWNED.store(!detach, Ordering::SeqCst);
     if !detach {
-        start_parent_watchdog();
+        start_parent_watchdog(watchdog::PIPE_FD, grace);
         tracing::info!("Parent watchdog started via pipe POLLHUP (detach=false)");
     } else {
         tracing::info!("Running in detached mode (detach=true)");
     }
 
+    // A later host process may adopt the box (`LiteBox::adopt()`), tying it
+    // to that process the same way, and the owner may release it
+    // (`LiteBox::detach()`).
+    if let Some(policy) = idle_policy {
+        start_idle_watch(transport, box_dir.clone(), exit_file, policy);
+    }
+    install_owner_handler(box_dir, grace);
+
     // Hand over process control to Box instance
     // This may never return (process takeover)
+    OPERATIONS.record("instance.enter");
     match instance.enter() {
         Ok(()) => {
             tracing::info!("Box execution completed successfully");
@@ -268,22 +340,22 @@ fn run_shim(args: ShimArgs, mut config: InstanceSpec) -> BoxliteResult<()> {
     }
 }
 
-/// Timeout for graceful shutdown before force kill (in seconds).
+/// Time the watchdog allows on top of the grace period before force kill
+/// (in seconds).
 const GRACEFUL_SHUTDOWN_TIMEOUT_SECS: u64 = 5;
 
-/// Timeout for guest RPC shutdown (filesystem sync) in seconds.
-const GUEST_SHUTDOWN_TIMEOUT_SECS: u64 = 3;
-
 /// Install SIGTERM handler for graceful VM shutdown.
 ///
 /// Uses `signal-hook` to catch SIGTERM in a dedicated thread.
 /// When received: Guest.Shutdown() RPC (flush qcow2) → re-raise SIGTERM.
+/// The RPC is bounded by the box's shutdown grace period, which the guest
+/// also uses as the window its processes get between SIGTERM and SIGKILL.
 ///
 /// This ensures any SIGTERM source (runtime shutdown, watchdog, systemd, manual kill)
 /// triggers a graceful guest shutdown with filesystem sync. Without this handler,
 /// SIGTERM would immediately kill the process, risking qcow2 COW disk buffer loss
 /// and ext4 filesystem corruption on next restart.
-fn install_graceful_shutdown_handler(transport: boxlite_shared::Transport) {
+fn install_graceful_shutdown_handler(transport: boxlite_shared::Transport, grace: Duration) {
     use signal_hook::consts::signal::SIGTERM;
     use signal_hook::iterator::Signals;
 
@@ -299,6 +371,7 @@ fn install_graceful_shutdown_handler(transport: boxlite_shared::Transport) {
         // Block until SIGTERM received
         for sig in signals.forever() {
             if sig == SIGTERM {
+                OPERATIONS.record("sigterm");
                 tracing::info!("SIGTERM received, initiating graceful guest shutdown");
                 break;
             }
@@ -312,10 +385,11 @@ fn install_graceful_shutdown_handler(transport: boxlite_shared::Transport) {
             Ok(rt) => {
                 let session = boxlite::GuestSession::new(transport);
                 let result = rt.block_on(async {
-                    tokio::time::timeout(Duration::from_secs(GUEST_SHUTDOWN_TIMEOUT_SECS), async {
+                    tokio::time::timeout(grace, async {
                         match session.guest().await {
                             Ok(mut guest) => {
-                                let _ = guest.shutdown().await;
+                                OPERATIONS.record("guest.shutdown");
+                                let _ = guest.shutdown(grace).await;
                             }
                             Err(e) => {
                                 tracing::debug!("Could not connect to guest for shutdown: {e}");
@@ -327,7 +401,7 @@ fn install_graceful_shutdown_handler(transport: boxlite_shared::Transport) {
                 match result {
                     Ok(()) => tracing::info!("Guest shutdown completed (filesystems synced)"),
                     Err(_) => tracing::warn!(
-                        timeout_secs = GUEST_SHUTDOWN_TIMEOUT_SECS,
+                        timeout_ms = grace.as_millis() as u64,
                         "Guest shutdown timed out"
                     ),
                 }
@@ -343,20 +417,148 @@ fn install_graceful_shutdown_handler(transport: boxlite_shared::Transport) {
     });
 }
 
+/// Stop the box once idle for `policy.window` while no host process owns
+/// it (see [`idle`]).
+///
+/// Samples the guest's activity over `transport` and the CPU usage of this
+/// process, which runs the VM. Once the box has been idle for the window,
+/// records the idle stop in `exit_file` and sends SIGTERM to self, which
+/// shuts the guest down gracefully ([`install_graceful_shutdown_handler`]).
+/// Idle time counts from when the box was last released.
+fn start_idle_watch(
+    transport: boxlite_shared::Transport,
+    box_dir: PathBuf,
+    exit_file: PathBuf,
+    policy: IdlePolicy,
+) {
+    thread::spawn(move || {
+        let rt = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(rt) => rt,
+            Err(e) => {
+                tracing::warn!("Failed to build tokio runtime for idle auto-stop: {e}");
+                return;
+            }
+        };
+        let session = boxlite::GuestSession::new(transport);
+        let pid = sysinfo::Pid::from_u32(std::process::id());
+        let mut sys = sysinfo::System::new();
+        let mut tracker: Option<IdleTracker> = None;
+
+        loop {
+            thread::sleep(policy.sample_interval());
+            if OWNED.load(Ordering::SeqCst) {
+                tracker = None;
+                continue;
+            }
+            let tracker = tracker.get_or_insert_with(|| IdleTracker::new(policy, Instant::now()));
+
+            sys.refresh_process(pid);
+            let cpu_percent = sys.process(pid).map(|p| f64::from(p.cpu_usage()));
+            let activity = rt.block_on(async { session.guest().await?.activity().await });
+            let sample = match activity {
+                Ok((running_execs, since_activity)) => Some(IdleSample {
+                    running_execs,
+                    since_activity,
+                    cpu_percent,
+                }),
+                Err(e) => {
+                    tracing::debug!("Failed to read guest activity: {e}");
+                    None
+                }
+            };
+
+            let now = Instant::now();
+            let remaining = tracker.observe(now, sample.as_ref());
+            let idle = tracker.idle_for(now);
+            if let Err(e) = idle::record_last_active(&box_dir, idle) {
+                tracing::debug!("Failed to record idle time: {e}");
+            }
+            if remaining.is_zero() {
+                OPERATIONS.record("idle_stop");
+                tracing::info!(idle_secs = idle.as_secs(), "Box idle, stopping it");
+                if let Err(e) = idle::record_idle_stop(&exit_file, idle) {
+                    tracing::warn!("Failed to record idle stop: {e}");
+                }
+                // SIGTERM triggers the graceful shutdown handler
+                unsafe {
+                    libc::kill(std::process::id() as i32, libc::SIGTERM);
+                }
+                return;
+            }
+        }
+    });
+}
+
+/// Act on ownership requests from host processes when signalled.
+///
+/// On [`watchdog::OWNER_SIGNAL`], with a release file in the box directory,
+/// disarms the running parent watchdogs and removes the file, so the box
+/// outlives its owner as if started with `detach = true`. Otherwise opens the
+/// owner FIFO the adopting process created in the box directory and watches
+/// it with [`start_parent_watchdog`], so the box stops when that process
+/// exits.
+fn install_owner_handler(box_dir: PathBuf, grace: Duration) {
+    use signal_hook::iterator::Signals;
+
+    let mut signals = match Signals::new([watchdog::OWNER_SIGNAL]) {
+        Ok(s) => s,
+        Err(e) => {
+            tracing::warn!("Failed to install ownership handler: {e}");
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        for _ in signals.forever() {
+            let release_file = box_dir.join(watchdog::RELEASE_FILE);
+            if release_file.exists() {
+                OPERATIONS.record("release");
+                OWNER_GENERATION.fetch_add(1, Ordering::SeqCst);
+                OWNED.store(false, Ordering::SeqCst);
+                match std::fs::remove_file(&release_file) {
+                    Ok(()) => tracing::info!("Released by the parent process, now detached"),
+                    Err(e) => tracing::warn!("Failed to acknowledge release: {e}"),
+                }
+                continue;
+            }
+
+            OPERATIONS.record("adopt");
+            match watchdog::open_owner_fifo(&box_dir.join(watchdog::OWNER_FIFO)) {
+                Ok(fd) => {
+                    start_parent_watchdog(fd.into_raw_fd(), grace);
+                    OWNED.store(true, Ordering::SeqCst);
+                    tracing::info!("Adopted by a new parent process");
+                }
+                Err(e) => tracing::warn!("Adoption failed: {e}"),
+            }
+        }
+    });
+}
+
 /// Start a watchdog thread that detects parent death via pipe POLLHUP.
 ///
-/// The parent holds the write end of a pipe; the read end is fd 3 in this process
-/// (dup2'd by the pre_exec hook). When the parent dies or drops its keepalive,
+/// The parent holds the write end of a pipe whose read end is `fd`: fd 3
+/// (dup2'd by the pre_exec hook) for the process that spawned us, or the owner
+/// FIFO for one that adopted us. When the parent dies or drops its keepalive,
 /// the kernel closes the write end, delivering POLLHUP immediately — zero latency,
 /// works across PID/mount namespaces.
 ///
+/// A watchdog started before the owner released the box exits on POLLHUP
+/// without stopping anything.
+///
 /// On POLLHUP: sends SIGTERM to self. The SIGTERM handler
 /// ([`install_graceful_shutdown_handler`]) does the actual graceful shutdown
-/// (Guest.Shutdown() RPC → qcow2 flush → exit).
-fn start_parent_watchdog() {
-    thread::spawn(|| {
+/// (Guest.Shutdown() RPC → qcow2 flush → exit); the watchdog force-kills
+/// the shim if that takes longer than `grace` plus
+/// [`GRACEFUL_SHUTDOWN_TIMEOUT_SECS`].
+fn start_parent_watchdog(fd: std::os::fd::RawFd, grace: Duration) {
+    let generation = OWNER_GENERATION.load(Ordering::SeqCst);
+    thread::spawn(move || {
         let mut pollfd = libc::pollfd {
-            fd: watchdog::PIPE_FD,
+            fd,
             events: libc::POLLIN, // POLLIN for macOS compatibility; POLLHUP is reported in revents
             revents: 0,
         };
@@ -364,6 +566,16 @@ fn start_parent_watchdog() {
         // Block until write end is closed (parent death or keepalive drop)
         let ret = unsafe { libc::poll(&mut pollfd, 1, -1) };
 
+        if OWNER_GENERATION.load(Ordering::SeqCst) != generation {
+            tracing::info!("Released parent went away, watchdog stopped");
+            // SAFETY: closing the pipe read end this thread owns.
+            unsafe {
+                libc::close(fd);
+            }
+            return;
+        }
+
+        OPERATIONS.record("watchdog.parent_hangup");
         if ret > 0 && (pollfd.revents & libc::POLLHUP) != 0 {
             tracing::info!("Parent death detected (POLLHUP on watchdog pipe)");
         } else {
@@ -381,9 +593,7 @@ fn start_parent_watchdog() {
         }
 
         // Safety net: wait for handler to complete, then force kill
-        thread::sleep(Duration::from_secs(
-            GUEST_SHUTDOWN_TIMEOUT_SECS + GRACEFUL_SHUTDOWN_TIMEOUT_SECS,
-        ));
+        thread::sleep(grace + Duration::from_secs(GRACEFUL_SHUTDOWN_TIMEOUT_SECS));
 
         tracing::warn!("Graceful shutdown timed out, forcing exit with SIGKILL");
         unsafe {
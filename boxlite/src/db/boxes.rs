@@ -36,7 +36,7 @@ impl BoxStore {
     }
 
     // ========================================================================
-    // BoxConfig operations (immutable after creation)
+    // BoxConfig operations (immutable after creation, except resource limits)
     // ========================================================================
 
     /// Load box configuration by ID.
@@ -64,6 +64,28 @@ impl BoxStore {
         }
     }
 
+    /// Replace the stored box configuration.
+    ///
+    /// Only used for settings that can change after creation (resource
+    /// limits). Returns error if box doesn't exist.
+    pub fn update_config(&self, config: &BoxConfig) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+
+        let json = serde_json::to_string(config)
+            .map_err(|e| BoxliteError::Database(format!("Failed to serialize config: {}", e)))?;
+
+        let rows_affected = db_err!(conn.execute(
+            "UPDATE box_config SET json = ?1 WHERE id = ?2",
+            params![json, config.id.as_str()],
+        ))?;
+
+        if rows_affected == 0 {
+            return Err(BoxliteError::NotFound(config.id.to_string()));
+        }
+
+        Ok(())
+    }
+
     /// Delete box configuration (and state via CASCADE).
     pub fn delete(&self, box_id: &str) -> BoxliteResult<bool> {
         let conn = self.db.conn();
@@ -111,8 +133,14 @@ impl BoxStore {
             .map_err(|e| BoxliteError::Database(format!("Failed to serialize state: {}", e)))?;
 
         let rows_affected = db_err!(conn.execute(
-            "UPDATE box_state SET status = ?1, pid = ?2, json = ?3 WHERE id = ?4",
-            params![state.status.as_str(), state.pid, json, box_id],
+            "UPDATE box_state SET status = ?1, pid = ?2, json = ?3, updated_at = ?4 WHERE id = ?5",
+            params![
+                state.status.as_str(),
+                state.pid,
+                json,
+                state.last_updated.timestamp(),
+                box_id
+            ],
         ))?;
 
         // Podman pattern: verify rows were actually updated
@@ -134,35 +162,24 @@ impl BoxStore {
     pub fn save(&self, config: &BoxConfig, state: &BoxState) -> BoxliteResult<()> {
         let mut conn = self.db.conn();
         let tx = db_err!(conn.transaction())?;
-
-        // Serialize config
-        let config_json = serde_json::to_string(config)
-            .map_err(|e| BoxliteError::Database(format!("Failed to serialize config: {}", e)))?;
-
-        // Serialize state
-        let state_json = serde_json::to_string(state)
-            .map_err(|e| BoxliteError::Database(format!("Failed to serialize state: {}", e)))?;
-
-        // Insert config (name has UNIQUE constraint, will fail on duplicate)
-        db_err!(tx.execute(
-            "INSERT INTO box_config (id, name, created_at, json) VALUES (?1, ?2, ?3, ?4)",
-            params![
-                config.id,
-                config.name.as_deref(),
-                config.created_at.timestamp(),
-                config_json
-            ],
-        ))?;
-
-        // Insert state
-        db_err!(tx.execute(
-            "INSERT INTO box_state (id, status, pid, json) VALUES (?1, ?2, ?3, ?4)",
-            params![config.id, state.status.as_str(), state.pid, state_json],
-        ))?;
-
-        // Commit transaction
+        insert(&tx, config, state)?;
         db_err!(tx.commit())?;
+        Ok(())
+    }
 
+    /// Delete box `old_id` and save `config` and `state` in one transaction,
+    /// so either the old box or the new one is stored, never neither.
+    ///
+    /// The new box may take the old one's name.
+    pub fn replace(&self, old_id: &str, config: &BoxConfig, state: &BoxState) -> BoxliteResult<()> {
+        let mut conn = self.db.conn();
+        let tx = db_err!(conn.transaction())?;
+        let deleted = db_err!(tx.execute("DELETE FROM box_config WHERE id = ?1", params![old_id]))?;
+        if deleted == 0 {
+            return Err(BoxliteError::NotFound(old_id.to_string()));
+        }
+        insert(&tx, config, state)?;
+        db_err!(tx.commit())?;
         Ok(())
     }
 
@@ -339,6 +356,45 @@ fn get_boot_id() -> String {
     }
 }
 
+/// Insert a new box's config and state rows within `tx`.
+fn insert(
+    tx: &rusqlite::Transaction<'_>,
+    config: &BoxConfig,
+    state: &BoxState,
+) -> BoxliteResult<()> {
+    // Serialize config
+    let config_json = serde_json::to_string(config)
+        .map_err(|e| BoxliteError::Database(format!("Failed to serialize config: {}", e)))?;
+
+    // Serialize state
+    let state_json = serde_json::to_string(state)
+        .map_err(|e| BoxliteError::Database(format!("Failed to serialize state: {}", e)))?;
+
+    // Insert config (name has UNIQUE constraint, will fail on duplicate)
+    db_err!(tx.execute(
+        "INSERT INTO box_config (id, name, created_at, json) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            config.id,
+            config.name.as_deref(),
+            config.created_at.timestamp(),
+            config_json
+        ],
+    ))?;
+
+    // Insert state
+    db_err!(tx.execute(
+        "INSERT INTO box_state (id, status, pid, json, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            config.id,
+            state.status.as_str(),
+            state.pid,
+            state_json,
+            state.last_updated.timestamp()
+        ],
+    ))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -376,6 +432,9 @@ mod tests {
             transport: Transport::unix(PathBuf::from("/tmp/test.sock")),
             box_home: PathBuf::from("/tmp/boxes/test"),
             ready_socket_path: PathBuf::from("/tmp/ready.sock"),
+            disk_driver: Default::default(),
+            lineage: None,
+            degradations: Vec::new(),
         }
     }
 
@@ -407,7 +466,7 @@ mod tests {
 
         let loaded = store.load_state(config.id.as_str()).unwrap();
         assert!(loaded.is_some());
-        assert_eq!(loaded.unwrap().status, BoxStatus::Configured);
+        assert_eq!(loaded.unwrap().status, BoxStatus::Created);
     }
 
     #[test]
@@ -429,6 +488,28 @@ mod tests {
         assert_eq!(loaded.pid, Some(12345));
     }
 
+    #[test]
+    fn test_update_config() {
+        let (store, _dir) = create_test_db();
+        let mut config = create_test_config(TEST_ID_1);
+        store.save(&config, &BoxState::new()).unwrap();
+
+        config
+            .options
+            .advanced
+            .security
+            .resource_limits
+            .disk_write_bps = Some(1024 * 1024);
+        store.update_config(&config).unwrap();
+
+        let loaded = store.load_config(config.id.as_str()).unwrap().unwrap();
+        let limits = loaded.options.advanced.security.resource_limits;
+        assert_eq!(limits.disk_write_bps, Some(1024 * 1024));
+
+        let missing = create_test_config(TEST_ID_2);
+        assert!(store.update_config(&missing).is_err());
+    }
+
     #[test]
     fn test_delete() {
         let (store, _dir) = create_test_db();
@@ -442,6 +523,38 @@ mod tests {
         assert!(store.load(config.id.as_str()).unwrap().is_none());
     }
 
+    #[test]
+    fn test_replace_is_all_or_nothing() {
+        let (store, _dir) = create_test_db();
+        let named = |id: &str, name: &str| BoxConfig {
+            name: Some(name.to_string()),
+            ..create_test_config(id)
+        };
+        store
+            .save(&named(TEST_ID_1, "web"), &BoxState::new())
+            .unwrap();
+        store
+            .save(&named(TEST_ID_2, "db"), &BoxState::new())
+            .unwrap();
+
+        // The insert fails on the taken name, so the delete is rolled back
+        let clash = named(TEST_ID_3, "db");
+        assert!(store.replace(TEST_ID_1, &clash, &BoxState::new()).is_err());
+        assert!(store.load(TEST_ID_1).unwrap().is_some());
+        assert!(store.load(TEST_ID_3).unwrap().is_none());
+
+        let replacement = named(TEST_ID_3, "web");
+        store
+            .replace(TEST_ID_1, &replacement, &BoxState::new())
+            .unwrap();
+        assert!(store.load(TEST_ID_1).unwrap().is_none());
+        let (config, _) = store.load(TEST_ID_3).unwrap().unwrap();
+        assert_eq!(config.name.as_deref(), Some("web"));
+
+        let missing = store.replace(TEST_ID_1, &create_test_config(TEST_ID_1), &BoxState::new());
+        assert!(matches!(missing, Err(BoxliteError::NotFound(_))));
+    }
+
     #[test]
     fn test_list_all() {
         let (store, _dir) = create_test_db();
@@ -471,7 +584,7 @@ mod tests {
         // Create stopped box
         let config2 = create_test_config(TEST_ID_2);
         let mut state2 = BoxState::new();
-        state2.set_status(BoxStatus::Stopped);
+        state2.set_status(BoxStatus::Exited);
         store.save(&config2, &state2).unwrap();
 
         let active = store.list_active().unwrap();
@@ -510,7 +623,7 @@ mod tests {
 
         // Verify state changed to Stopped (not Crashed - rootfs preserved)
         let loaded = store.load_state(config.id.as_str()).unwrap().unwrap();
-        assert_eq!(loaded.status, BoxStatus::Stopped);
+        assert_eq!(loaded.status, BoxStatus::Exited);
         assert_eq!(loaded.pid, None);
     }
 }
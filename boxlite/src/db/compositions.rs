@@ -0,0 +1,135 @@
+//! Box composition persistence.
+//!
+//! Records which boxes `BoxliteRuntime::up` brought up and what each depends
+//! on, so `down` can stop them in reverse dependency order from any process.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+
+use super::{Database, db_err};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// A box brought up as part of a composition.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComposedMember {
+    /// Box name.
+    pub name: String,
+    /// Names of the boxes started before this one.
+    pub depends_on: Vec<String>,
+    /// When the box was last brought up by `up`.
+    pub composed_at: DateTime<Utc>,
+}
+
+/// Store for composition members.
+pub struct CompositionStore {
+    db: Database,
+}
+
+impl CompositionStore {
+    /// Create a new CompositionStore wrapping the given database.
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Record members, replacing any with the same name.
+    pub(crate) fn upsert(&self, members: &[ComposedMember]) -> BoxliteResult<()> {
+        let mut conn = self.db.conn();
+        let tx = db_err!(conn.transaction())?;
+        for member in members {
+            let json = serde_json::to_string(&member.depends_on).map_err(|e| {
+                BoxliteError::Database(format!("Failed to serialize composition: {}", e))
+            })?;
+            db_err!(tx.execute(
+                "INSERT OR REPLACE INTO box_composition (name, composed_at, json) \
+                 VALUES (?1, ?2, ?3)",
+                params![member.name, member.composed_at.timestamp(), json],
+            ))?;
+        }
+        db_err!(tx.commit())
+    }
+
+    /// All recorded members, by name.
+    pub(crate) fn list(&self) -> BoxliteResult<Vec<ComposedMember>> {
+        let conn = self.db.conn();
+        let mut stmt = db_err!(
+            conn.prepare("SELECT name, composed_at, json FROM box_composition ORDER BY name")
+        )?;
+        let rows = db_err!(stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, String>(2)?,
+            ))
+        }))?;
+
+        let mut members = Vec::new();
+        for row in rows {
+            let (name, composed_at, json) = db_err!(row)?;
+            let depends_on = serde_json::from_str(&json).map_err(|e| {
+                BoxliteError::Database(format!(
+                    "Failed to deserialize composition of '{}': {}",
+                    name, e
+                ))
+            })?;
+            members.push(ComposedMember {
+                name,
+                depends_on,
+                composed_at: Utc
+                    .timestamp_opt(composed_at, 0)
+                    .single()
+                    .unwrap_or_default(),
+            });
+        }
+        Ok(members)
+    }
+
+    /// Forget members. Names that are not recorded are ignored.
+    pub(crate) fn remove(&self, names: &[String]) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+        for name in names {
+            db_err!(conn.execute("DELETE FROM box_composition WHERE name = ?1", params![name]))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_store() -> (CompositionStore, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        (CompositionStore::new(db), dir)
+    }
+
+    fn member(name: &str, depends_on: &[&str]) -> ComposedMember {
+        ComposedMember {
+            name: name.to_string(),
+            depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            composed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_upsert_list_and_remove() {
+        let (store, _dir) = test_store();
+        store
+            .upsert(&[member("db", &[]), member("app", &["db"])])
+            .unwrap();
+        store.upsert(&[member("app", &["db", "cache"])]).unwrap();
+
+        let members = store.list().unwrap();
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].name, "app");
+        assert_eq!(members[0].depends_on, vec!["db", "cache"]);
+
+        store
+            .remove(&["app".to_string(), "missing".to_string()])
+            .unwrap();
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|m| m.name).collect();
+        assert_eq!(names, vec!["db"]);
+    }
+}
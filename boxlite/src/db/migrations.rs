@@ -0,0 +1,234 @@
+//! Ordered schema migrations.
+//!
+//! Each [`Migration`] moves the schema up by one version. When an existing
+//! database is older than [`SCHEMA_VERSION`], `Database::open` copies the
+//! file aside (see [`backup`]) and then applies every pending migration in
+//! order inside a single transaction, so a failed upgrade leaves the
+//! database exactly as it was.
+//!
+//! To change the schema: bump [`SCHEMA_VERSION`], update the table
+//! definitions in `schema.rs` (used for new databases), and append a
+//! migration here that brings existing databases to the same shape.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use rusqlite::Connection;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use super::db_err;
+use super::schema::{self, SCHEMA_VERSION};
+
+/// One schema upgrade step.
+pub(super) struct Migration {
+    /// Version the schema is at after this migration.
+    pub to: i32,
+    /// What the migration does, for logs.
+    pub description: &'static str,
+    pub apply: fn(&Connection) -> BoxliteResult<()>,
+}
+
+/// All migrations, ordered by `to`. Versions 1 and 2 predate migrations.
+pub(super) const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 3,
+        description: "add name column to box_config",
+        apply: add_box_name,
+    },
+    Migration {
+        to: 4,
+        description: "add image_index table",
+        apply: |conn| db_err!(conn.execute_batch(schema::IMAGE_INDEX_TABLE)),
+    },
+    Migration {
+        to: 5,
+        description: "add snapshots table",
+        apply: add_legacy_snapshots,
+    },
+    Migration {
+        to: 6,
+        description: "replace snapshots with box_snapshot",
+        apply: |conn| {
+            db_err!(conn.execute_batch("DROP TABLE IF EXISTS snapshots;"))?;
+            db_err!(conn.execute_batch(schema::BOX_SNAPSHOT_TABLE))
+        },
+    },
+    Migration {
+        to: 7,
+        description: "add box_trash table",
+        apply: |conn| db_err!(conn.execute_batch(schema::BOX_TRASH_TABLE)),
+    },
+    Migration {
+        to: 8,
+        description: "add updated_at column to box_state",
+        apply: add_state_updated_at,
+    },
+    Migration {
+        to: 9,
+        description: "add box_template table",
+        apply: |conn| db_err!(conn.execute_batch(schema::BOX_TEMPLATE_TABLE)),
+    },
+    Migration {
+        to: 10,
+        description: "add box_composition table",
+        apply: |conn| db_err!(conn.execute_batch(schema::BOX_COMPOSITION_TABLE)),
+    },
+    Migration {
+        to: 11,
+        description: "add metadata columns to box_snapshot",
+        apply: add_snapshot_metadata,
+    },
+];
+
+/// Oldest version that can be migrated.
+pub(super) fn oldest_supported() -> i32 {
+    MIGRATIONS[0].to - 1
+}
+
+/// Run the migrations from `from_version` up to [`SCHEMA_VERSION`].
+///
+/// Runs in one transaction, together with the version bump.
+pub(super) fn run(conn: &Connection, from_version: i32) -> BoxliteResult<()> {
+    if from_version < oldest_supported() {
+        return Err(BoxliteError::Database(format!(
+            "Database schema v{} is too old to migrate (oldest supported: v{})",
+            from_version,
+            oldest_supported()
+        )));
+    }
+
+    let tx = db_err!(conn.unchecked_transaction())?;
+    for migration in MIGRATIONS.iter().filter(|m| m.to > from_version) {
+        tracing::info!(
+            "Running migration {} -> {}: {}",
+            migration.to - 1,
+            migration.to,
+            migration.description
+        );
+        (migration.apply)(&tx).map_err(|e| {
+            BoxliteError::Database(format!(
+                "Migration to schema v{} ({}) failed: {}",
+                migration.to, migration.description, e
+            ))
+        })?;
+    }
+
+    let now = Utc::now().to_rfc3339();
+    db_err!(tx.execute(
+        "UPDATE schema_version SET version = ?1, updated_at = ?2 WHERE id = 1",
+        rusqlite::params![SCHEMA_VERSION, now],
+    ))?;
+    db_err!(tx.commit())?;
+
+    tracing::info!(
+        "Database migration complete, now at version {}",
+        SCHEMA_VERSION
+    );
+    Ok(())
+}
+
+/// Copy the database at `db_path` to `<db_path>.v<version>.bak`.
+///
+/// Uses `VACUUM INTO`, which produces a consistent copy including pages
+/// still in the WAL. An older backup of the same version is replaced.
+pub(super) fn backup(conn: &Connection, db_path: &Path, version: i32) -> BoxliteResult<PathBuf> {
+    let mut name = db_path.as_os_str().to_owned();
+    name.push(format!(".v{version}.bak"));
+    let backup_path = PathBuf::from(name);
+
+    if backup_path.exists() {
+        std::fs::remove_file(&backup_path)?;
+    }
+    db_err!(conn.execute(
+        "VACUUM INTO ?1",
+        rusqlite::params![backup_path.to_string_lossy()]
+    ))?;
+
+    tracing::info!(
+        "Backed up schema v{} database to {}",
+        version,
+        backup_path.display()
+    );
+    Ok(backup_path)
+}
+
+fn add_box_name(conn: &Connection) -> BoxliteResult<()> {
+    db_err!(conn.execute_batch("ALTER TABLE box_config ADD COLUMN name TEXT;"))?;
+
+    // Unique index enforces uniqueness, allows multiple NULLs
+    db_err!(conn.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_box_config_name_unique ON box_config(name);"
+    ))?;
+
+    // Populate name from JSON for existing rows
+    db_err!(conn.execute_batch(
+        "UPDATE box_config SET name = json_extract(json, '$.name') WHERE name IS NULL;"
+    ))
+}
+
+/// Legacy table, only created so the next migration can drop it.
+fn add_legacy_snapshots(conn: &Connection) -> BoxliteResult<()> {
+    db_err!(conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS snapshots (
+            id TEXT PRIMARY KEY NOT NULL,
+            box_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            description TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL,
+            FOREIGN KEY (box_id) REFERENCES box_config(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_snapshots_box_id ON snapshots(box_id);
+        CREATE UNIQUE INDEX IF NOT EXISTS idx_snapshots_box_name ON snapshots(box_id, name);
+        "#
+    ))
+}
+
+fn add_state_updated_at(conn: &Connection) -> BoxliteResult<()> {
+    if !has_column(conn, "box_state", "updated_at")? {
+        db_err!(conn.execute_batch("ALTER TABLE box_state ADD COLUMN updated_at INTEGER;"))?;
+    }
+
+    // Backfill from the JSON blob's RFC 3339 `last_updated`
+    db_err!(conn.execute_batch(
+        r#"
+        UPDATE box_state
+        SET updated_at = CAST(strftime('%s', json_extract(json, '$.last_updated')) AS INTEGER)
+        WHERE updated_at IS NULL;
+        CREATE INDEX IF NOT EXISTS idx_box_state_updated_at ON box_state(updated_at);
+        "#
+    ))
+}
+
+fn add_snapshot_metadata(conn: &Connection) -> BoxliteResult<()> {
+    for column in ["has_metadata", "metadata_only"] {
+        if !has_column(conn, "box_snapshot", column)? {
+            db_err!(conn.execute_batch(&format!(
+                "ALTER TABLE box_snapshot ADD COLUMN {column} INTEGER NOT NULL DEFAULT 0;"
+            )))?;
+        }
+    }
+    Ok(())
+}
+
+fn has_column(conn: &Connection, table: &str, column: &str) -> BoxliteResult<bool> {
+    db_err!(conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        rusqlite::params![table, column],
+        |row| row.get(0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrations_are_contiguous_and_reach_current_version() {
+        for pair in MIGRATIONS.windows(2) {
+            assert_eq!(pair[1].to, pair[0].to + 1);
+        }
+        assert_eq!(MIGRATIONS.last().unwrap().to, SCHEMA_VERSION);
+    }
+}
@@ -7,9 +7,13 @@
 //! Uses JSON blob pattern for flexibility with queryable columns for performance.
 
 mod boxes;
+pub(crate) mod compositions;
 mod images;
+mod migrations;
 mod schema;
 pub(crate) mod snapshots;
+pub(crate) mod templates;
+pub(crate) mod trash;
 
 use std::path::Path;
 use std::sync::Arc;
@@ -21,8 +25,11 @@ use rusqlite::{Connection, OptionalExtension};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 pub use boxes::BoxStore;
+pub use compositions::CompositionStore;
 pub use images::{CachedImage, ImageIndexStore};
 pub use snapshots::SnapshotStore;
+pub use templates::TemplateStore;
+pub use trash::TrashStore;
 
 /// Helper macro to convert rusqlite errors to BoxliteError.
 macro_rules! db_err {
@@ -65,7 +72,7 @@ impl Database {
             "
         ))?;
 
-        Self::init_schema(&conn)?;
+        Self::init_schema(&conn, db_path)?;
 
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
@@ -83,10 +90,10 @@ impl Database {
     /// 1. Create schema_version table (safe, no dependencies)
     /// 2. Check current version
     /// 3. New DB: apply full schema
-    ///    Existing DB with older version: run migrations automatically
+    ///    Existing DB with older version: back up the file, then migrate
     ///    Existing DB with newer version: error (need newer boxlite)
     ///    Existing DB with same version: nothing to do
-    fn init_schema(conn: &Connection) -> BoxliteResult<()> {
+    fn init_schema(conn: &Connection, db_path: &Path) -> BoxliteResult<()> {
         // Step 1: Create schema_version table first (always safe)
         db_err!(conn.execute_batch(schema::SCHEMA_VERSION_TABLE))?;
 
@@ -118,13 +125,14 @@ impl Database {
                 )));
             }
             Some(v) => {
-                // Older database - run migrations automatically
+                // Older database - keep a copy, then run migrations automatically
                 tracing::info!(
                     "Database schema v{} is older than expected v{}, running migrations",
                     v,
                     schema::SCHEMA_VERSION
                 );
-                Self::run_migrations(conn, v)?;
+                migrations::backup(conn, db_path, v)?;
+                migrations::run(conn, v)?;
             }
         }
 
@@ -149,83 +157,6 @@ impl Database {
         );
         Ok(())
     }
-
-    /// Run migrations from `from_version` to current schema version.
-    fn run_migrations(conn: &Connection, from_version: i32) -> BoxliteResult<()> {
-        let mut current = from_version;
-
-        // Migration 2 -> 3: Add name column with UNIQUE constraint
-        if current == 2 {
-            tracing::info!("Running migration 2 -> 3: Adding name column to box_config");
-
-            // Add name column
-            db_err!(conn.execute_batch("ALTER TABLE box_config ADD COLUMN name TEXT;"))?;
-
-            // Create unique index (enforces uniqueness, allows multiple NULLs)
-            db_err!(conn.execute_batch(
-                "CREATE UNIQUE INDEX IF NOT EXISTS idx_box_config_name_unique ON box_config(name);"
-            ))?;
-
-            // Populate name from JSON for existing rows
-            db_err!(conn.execute_batch(
-                "UPDATE box_config SET name = json_extract(json, '$.name') WHERE name IS NULL;"
-            ))?;
-
-            current = 3;
-        }
-
-        // Migration 3 -> 4: Add image_index table
-        if current == 3 {
-            tracing::info!("Running migration 3 -> 4: Adding image_index table");
-
-            db_err!(conn.execute_batch(schema::IMAGE_INDEX_TABLE))?;
-
-            current = 4;
-        }
-
-        // Migration 4 -> 5: Add snapshots table (legacy, now replaced by box_snapshot in v6)
-        if current == 4 {
-            tracing::info!("Running migration 4 -> 5: Adding snapshots table");
-
-            // Create the old snapshots table so migration 5->6 can drop it
-            db_err!(conn.execute_batch(
-                r#"
-                CREATE TABLE IF NOT EXISTS snapshots (
-                    id TEXT PRIMARY KEY NOT NULL,
-                    box_id TEXT NOT NULL,
-                    name TEXT NOT NULL,
-                    description TEXT NOT NULL DEFAULT '',
-                    created_at TEXT NOT NULL,
-                    FOREIGN KEY (box_id) REFERENCES box_config(id) ON DELETE CASCADE
-                );
-                CREATE INDEX IF NOT EXISTS idx_snapshots_box_id ON snapshots(box_id);
-                CREATE UNIQUE INDEX IF NOT EXISTS idx_snapshots_box_name ON snapshots(box_id, name);
-                "#
-            ))?;
-
-            current = 5;
-        }
-
-        // Migration 5 -> 6: Replace snapshots table with box_snapshot
-        if current == 5 {
-            tracing::info!("Running migration 5 -> 6: Replacing snapshots with box_snapshot");
-
-            db_err!(conn.execute_batch("DROP TABLE IF EXISTS snapshots;"))?;
-            db_err!(conn.execute_batch(schema::BOX_SNAPSHOT_TABLE))?;
-
-            current = 6;
-        }
-
-        // Update schema version
-        let now = Utc::now().to_rfc3339();
-        db_err!(conn.execute(
-            "UPDATE schema_version SET version = ?1, updated_at = ?2 WHERE id = 1",
-            rusqlite::params![current, now],
-        ))?;
-
-        tracing::info!("Database migration complete, now at version {}", current);
-        Ok(())
-    }
 }
 
 #[cfg(test)]
@@ -265,10 +196,13 @@ mod tests {
         assert!(tables.contains(&"alive".to_string()));
         assert!(tables.contains(&"image_index".to_string()));
         assert!(tables.contains(&"box_snapshot".to_string()));
+        assert!(tables.contains(&"box_trash".to_string()));
+        assert!(tables.contains(&"box_template".to_string()));
+        assert!(tables.contains(&"box_composition".to_string()));
     }
 
     #[test]
-    fn test_db_migration_v4_to_v6() {
+    fn test_db_migration_v4_to_latest() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
 
@@ -289,7 +223,7 @@ mod tests {
             .unwrap();
         }
 
-        // Open with current code - should auto-migrate to v6
+        // Open with current code - should auto-migrate to the latest version
         let db = Database::open(&db_path).unwrap();
         let conn = db.conn();
 
@@ -301,7 +235,7 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(version, 6);
+        assert_eq!(version, schema::SCHEMA_VERSION);
 
         // Verify box_snapshot table exists
         let table_exists: bool = conn
@@ -331,7 +265,7 @@ mod tests {
     }
 
     #[test]
-    fn test_db_migration_v5_to_v6() {
+    fn test_db_migration_v5_to_latest() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("test.db");
 
@@ -365,7 +299,7 @@ mod tests {
             .unwrap();
         }
 
-        // Open with current code - should auto-migrate to v6
+        // Open with current code - should auto-migrate to the latest version
         let db = Database::open(&db_path).unwrap();
         let conn = db.conn();
 
@@ -376,7 +310,7 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(version, 6);
+        assert_eq!(version, schema::SCHEMA_VERSION);
 
         // box_snapshot should exist
         let table_exists: bool = conn
@@ -418,4 +352,190 @@ mod tests {
             Ok(_) => panic!("expected error"),
         }
     }
+
+    // ========================================================================
+    // Homes created by earlier releases
+    // ========================================================================
+
+    /// `box_state` as created by schema v7 and earlier (no `updated_at`).
+    const V7_BOX_STATE_TABLE: &str = r#"
+        CREATE TABLE IF NOT EXISTS box_state (
+            id TEXT PRIMARY KEY NOT NULL,
+            status TEXT NOT NULL,
+            pid INTEGER,
+            json TEXT NOT NULL,
+            FOREIGN KEY (id) REFERENCES box_config(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_box_state_status ON box_state(status);
+        CREATE INDEX IF NOT EXISTS idx_box_state_pid ON box_state(pid);
+    "#;
+
+    /// `box_snapshot` as created by schema v10 and earlier (no metadata columns).
+    const V10_BOX_SNAPSHOT_TABLE: &str = r#"
+        CREATE TABLE IF NOT EXISTS box_snapshot (
+            id TEXT PRIMARY KEY NOT NULL,
+            box_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            created_at INTEGER NOT NULL,
+            snapshot_dir TEXT NOT NULL,
+            guest_disk_bytes INTEGER NOT NULL,
+            container_disk_bytes INTEGER NOT NULL,
+            size_bytes INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY (box_id) REFERENCES box_config(id) ON DELETE CASCADE,
+            UNIQUE(box_id, name)
+        );
+    "#;
+
+    /// Create a database the way schema `version` (6 or 7) left it, with one
+    /// box and one snapshot of it.
+    fn create_old_home(db_path: &Path, version: i32) {
+        let conn = Connection::open(db_path).unwrap();
+        conn.execute_batch("PRAGMA journal_mode=WAL;").unwrap();
+        conn.execute_batch(schema::SCHEMA_VERSION_TABLE).unwrap();
+        conn.execute_batch(schema::BOX_CONFIG_TABLE).unwrap();
+        conn.execute_batch(V7_BOX_STATE_TABLE).unwrap();
+        conn.execute_batch(schema::ALIVE_TABLE).unwrap();
+        conn.execute_batch(schema::IMAGE_INDEX_TABLE).unwrap();
+        conn.execute_batch(V10_BOX_SNAPSHOT_TABLE).unwrap();
+        if version >= 7 {
+            conn.execute_batch(schema::BOX_TRASH_TABLE).unwrap();
+        }
+
+        conn.execute(
+            "INSERT INTO box_config (id, name, created_at, json) VALUES ('box1', 'web', 0, '{}')",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO box_state (id, status, pid, json) VALUES ('box1', 'stopped', NULL, ?1)",
+            rusqlite::params![
+                r#"{"status":"stopped","pid":null,"container_id":null,"last_updated":"2024-05-01T12:00:00.123456789Z","lock_id":null}"#
+            ],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO box_snapshot (id, box_id, name, created_at, snapshot_dir, \
+             guest_disk_bytes, container_disk_bytes, size_bytes) \
+             VALUES ('snap1', 'box1', 'base', 0, '/snapshots/base', 0, 1024, 512)",
+            [],
+        )
+        .unwrap();
+
+        let now = Utc::now().to_rfc3339();
+        conn.execute(
+            "INSERT INTO schema_version (id, version, updated_at) VALUES (1, ?1, ?2)",
+            rusqlite::params![version, now],
+        )
+        .unwrap();
+    }
+
+    fn schema_version_of(conn: &Connection) -> i32 {
+        conn.query_row(
+            "SELECT version FROM schema_version WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn table_exists(conn: &Connection, table: &str) -> bool {
+        conn.query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type='table' AND name=?1",
+            rusqlite::params![table],
+            |row| row.get(0),
+        )
+        .unwrap()
+    }
+
+    fn assert_old_home_migrates(version: i32) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("boxlite.db");
+        create_old_home(&db_path, version);
+
+        let db = Database::open(&db_path).unwrap();
+        let conn = db.conn();
+        assert_eq!(schema_version_of(&conn), schema::SCHEMA_VERSION);
+        assert!(table_exists(&conn, "box_trash"));
+
+        // Existing rows survive, and the new column is backfilled from JSON
+        let (name, updated_at): (String, Option<i64>) = conn
+            .query_row(
+                "SELECT c.name, s.updated_at FROM box_config c \
+                 JOIN box_state s ON c.id = s.id WHERE c.id = 'box1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(name, "web");
+        assert_eq!(updated_at, Some(1714564800));
+
+        // Old snapshots hold disks only
+        let (has_metadata, metadata_only): (bool, bool) = conn
+            .query_row(
+                "SELECT has_metadata, metadata_only FROM box_snapshot WHERE id = 'snap1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert!(!has_metadata && !metadata_only);
+
+        // The pre-migration copy is still at the old version
+        let backup = temp_dir.path().join(format!("boxlite.db.v{version}.bak"));
+        let backup_conn = Connection::open(&backup).unwrap();
+        assert_eq!(schema_version_of(&backup_conn), version);
+    }
+
+    #[test]
+    fn test_db_opens_v6_home() {
+        assert_old_home_migrates(6);
+    }
+
+    #[test]
+    fn test_db_opens_v7_home() {
+        assert_old_home_migrates(7);
+    }
+
+    #[test]
+    fn test_db_failed_migration_rolls_back() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("boxlite.db");
+        create_old_home(&db_path, 6);
+
+        // Break the v8 migration: it has no box_state to add a column to
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch("DROP TABLE box_state;").unwrap();
+        }
+
+        let err = Database::open(&db_path)
+            .err()
+            .expect("migration should fail");
+        assert!(err.to_string().contains("Migration to schema v8"), "{err}");
+
+        // Earlier migrations in the same run were rolled back too
+        let conn = Connection::open(&db_path).unwrap();
+        assert_eq!(schema_version_of(&conn), 6);
+        assert!(!table_exists(&conn, "box_trash"));
+    }
+
+    #[test]
+    fn test_db_rejects_version_without_migration_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("boxlite.db");
+        {
+            let conn = Connection::open(&db_path).unwrap();
+            conn.execute_batch(schema::SCHEMA_VERSION_TABLE).unwrap();
+            let now = Utc::now().to_rfc3339();
+            conn.execute(
+                "INSERT INTO schema_version (id, version, updated_at) VALUES (1, 1, ?1)",
+                rusqlite::params![now],
+            )
+            .unwrap();
+        }
+
+        let err = Database::open(&db_path)
+            .err()
+            .expect("v1 cannot be migrated");
+        assert!(err.to_string().contains("too old to migrate"), "{err}");
+    }
 }
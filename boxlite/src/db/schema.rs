@@ -7,7 +7,7 @@
 //! Each table has queryable columns for efficient filtering + JSON blob for full data.
 
 /// Current schema version.
-pub const SCHEMA_VERSION: i32 = 6;
+pub const SCHEMA_VERSION: i32 = 11;
 
 /// Schema version tracking table.
 pub const SCHEMA_VERSION_TABLE: &str = r#"
@@ -38,18 +38,21 @@ CREATE INDEX IF NOT EXISTS idx_box_config_name ON box_config(name);
 /// BoxState table schema.
 ///
 /// Stores mutable box state. JSON blob contains full BoxState struct.
-/// Queryable columns: id, status, pid (for filtering active boxes).
+/// Queryable columns: id, status, pid (for filtering active boxes) and
+/// updated_at (last state change, unix seconds; added in v8).
 pub const BOX_STATE_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS box_state (
     id TEXT PRIMARY KEY NOT NULL,
     status TEXT NOT NULL,
     pid INTEGER,
     json TEXT NOT NULL,
+    updated_at INTEGER,
     FOREIGN KEY (id) REFERENCES box_config(id) ON DELETE CASCADE
 );
 
 CREATE INDEX IF NOT EXISTS idx_box_state_status ON box_state(status);
 CREATE INDEX IF NOT EXISTS idx_box_state_pid ON box_state(pid);
+CREATE INDEX IF NOT EXISTS idx_box_state_updated_at ON box_state(updated_at);
 "#;
 
 /// Alive file table schema.
@@ -84,7 +87,8 @@ CREATE INDEX IF NOT EXISTS idx_image_index_manifest_digest ON image_index(manife
 ///
 /// Stores snapshot metadata for box state persistence.
 /// Each snapshot captures the disk state of a stopped box at a point in time
-/// using external COW files stored in the snapshot directory.
+/// using external COW files stored in the snapshot directory. Since v11 it
+/// may also, or only, capture the box's options and services there.
 pub const BOX_SNAPSHOT_TABLE: &str = r#"
 CREATE TABLE IF NOT EXISTS box_snapshot (
     id TEXT PRIMARY KEY NOT NULL,
@@ -95,11 +99,58 @@ CREATE TABLE IF NOT EXISTS box_snapshot (
     guest_disk_bytes INTEGER NOT NULL,
     container_disk_bytes INTEGER NOT NULL,
     size_bytes INTEGER NOT NULL DEFAULT 0,
+    has_metadata INTEGER NOT NULL DEFAULT 0,
+    metadata_only INTEGER NOT NULL DEFAULT 0,
     FOREIGN KEY (box_id) REFERENCES box_config(id) ON DELETE CASCADE,
     UNIQUE(box_id, name)
 );
 "#;
 
+/// Box trash table schema (added in v7).
+///
+/// Holds soft-deleted boxes. Trashing moves the box's config, state and
+/// snapshot rows here as JSON and deletes them from `box_config`, so trashed
+/// boxes never collide with live ones on name and stay invisible to normal
+/// queries. `trash_dir` is where the box home was moved to.
+pub const BOX_TRASH_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS box_trash (
+    id TEXT PRIMARY KEY NOT NULL,
+    name TEXT,
+    trashed_at INTEGER NOT NULL,
+    trash_dir TEXT NOT NULL,
+    config_json TEXT NOT NULL,
+    state_json TEXT NOT NULL,
+    snapshots_json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_box_trash_trashed_at ON box_trash(trashed_at);
+"#;
+
+/// Box template table schema (added in v9).
+///
+/// Named box options for `create_from_template`. JSON blob contains the
+/// template's `BoxOptions`.
+pub const BOX_TEMPLATE_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS box_template (
+    name TEXT PRIMARY KEY NOT NULL,
+    created_at INTEGER NOT NULL,
+    json TEXT NOT NULL
+);
+"#;
+
+/// Box composition table schema (added in v10).
+///
+/// One row per box brought up by `BoxliteRuntime::up`, keyed by box name, so
+/// `down` can order the teardown from another process. JSON blob contains
+/// the names of the boxes it depends on.
+pub const BOX_COMPOSITION_TABLE: &str = r#"
+CREATE TABLE IF NOT EXISTS box_composition (
+    name TEXT PRIMARY KEY NOT NULL,
+    composed_at INTEGER NOT NULL,
+    json TEXT NOT NULL
+);
+"#;
+
 /// Get all schema creation statements.
 pub fn all_schemas() -> Vec<&'static str> {
     vec![
@@ -109,5 +160,8 @@ pub fn all_schemas() -> Vec<&'static str> {
         ALIVE_TABLE,
         IMAGE_INDEX_TABLE,
         BOX_SNAPSHOT_TABLE,
+        BOX_TRASH_TABLE,
+        BOX_TEMPLATE_TABLE,
+        BOX_COMPOSITION_TABLE,
     ]
 }
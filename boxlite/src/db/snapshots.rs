@@ -28,6 +28,26 @@ pub struct SnapshotInfo {
     pub container_disk_bytes: u64,
     /// Total on-disk size in bytes of all snapshot files.
     pub size_bytes: u64,
+    /// Whether box metadata (options and services) was captured with the
+    /// disks; see `SnapshotOptions::metadata`.
+    #[serde(default)]
+    pub has_metadata: bool,
+    /// Whether only metadata was captured, leaving the disks untouched.
+    #[serde(default)]
+    pub metadata_only: bool,
+    /// Snapshots removed by the retention policy when this snapshot was
+    /// created. Only set on the value returned by create; not persisted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruned: Option<PrunedSnapshots>,
+}
+
+/// Snapshots removed by a retention policy.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrunedSnapshots {
+    /// Names of the removed snapshots, oldest first.
+    pub names: Vec<String>,
+    /// Total on-disk bytes freed.
+    pub reclaimed_bytes: u64,
 }
 
 /// Store for snapshot metadata operations.
@@ -44,51 +64,13 @@ impl SnapshotStore {
     /// Save a snapshot record to the database.
     pub fn save(&self, record: &SnapshotInfo) -> BoxliteResult<()> {
         let conn = self.db.conn();
-        db_err!(conn.execute(
-            "INSERT INTO box_snapshot (id, box_id, name, created_at, snapshot_dir, \
-             guest_disk_bytes, container_disk_bytes, size_bytes) \
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            rusqlite::params![
-                record.id,
-                record.box_id,
-                record.name,
-                record.created_at,
-                record.snapshot_dir,
-                record.guest_disk_bytes as i64,
-                record.container_disk_bytes as i64,
-                record.size_bytes as i64,
-            ],
-        ))?;
-        Ok(())
+        insert_with(&conn, record)
     }
 
     /// List all snapshots for a given box, ordered by creation time (newest first).
     pub fn list(&self, box_id: &str) -> BoxliteResult<Vec<SnapshotInfo>> {
         let conn = self.db.conn();
-        let mut stmt = db_err!(conn.prepare(
-            "SELECT id, box_id, name, created_at, snapshot_dir, \
-             guest_disk_bytes, container_disk_bytes, size_bytes \
-             FROM box_snapshot WHERE box_id = ?1 ORDER BY created_at DESC"
-        ))?;
-
-        let rows = db_err!(stmt.query_map(rusqlite::params![box_id], |row| {
-            Ok(SnapshotInfo {
-                id: row.get(0)?,
-                box_id: row.get(1)?,
-                name: row.get(2)?,
-                created_at: row.get(3)?,
-                snapshot_dir: row.get(4)?,
-                guest_disk_bytes: row.get::<_, i64>(5)? as u64,
-                container_disk_bytes: row.get::<_, i64>(6)? as u64,
-                size_bytes: row.get::<_, i64>(7)? as u64,
-            })
-        }))?;
-
-        let mut snapshots = Vec::new();
-        for row in rows {
-            snapshots.push(db_err!(row)?);
-        }
-        Ok(snapshots)
+        list_with(&conn, box_id)
     }
 
     /// Get a snapshot by box ID and snapshot name.
@@ -96,22 +78,9 @@ impl SnapshotStore {
         let conn = self.db.conn();
         let result = db_err!(
             conn.query_row(
-                "SELECT id, box_id, name, created_at, snapshot_dir, \
-                 guest_disk_bytes, container_disk_bytes, size_bytes \
-                 FROM box_snapshot WHERE box_id = ?1 AND name = ?2",
+                &format!("SELECT {COLUMNS} FROM box_snapshot WHERE box_id = ?1 AND name = ?2"),
                 rusqlite::params![box_id, name],
-                |row| {
-                    Ok(SnapshotInfo {
-                        id: row.get(0)?,
-                        box_id: row.get(1)?,
-                        name: row.get(2)?,
-                        created_at: row.get(3)?,
-                        snapshot_dir: row.get(4)?,
-                        guest_disk_bytes: row.get::<_, i64>(5)? as u64,
-                        container_disk_bytes: row.get::<_, i64>(6)? as u64,
-                        size_bytes: row.get::<_, i64>(7)? as u64,
-                    })
-                },
+                from_row,
             )
             .optional()
         )?;
@@ -124,22 +93,9 @@ impl SnapshotStore {
         let conn = self.db.conn();
         let result = db_err!(
             conn.query_row(
-                "SELECT id, box_id, name, created_at, snapshot_dir, \
-                 guest_disk_bytes, container_disk_bytes, size_bytes \
-                 FROM box_snapshot WHERE id = ?1",
+                &format!("SELECT {COLUMNS} FROM box_snapshot WHERE id = ?1"),
                 rusqlite::params![snapshot_id],
-                |row| {
-                    Ok(SnapshotInfo {
-                        id: row.get(0)?,
-                        box_id: row.get(1)?,
-                        name: row.get(2)?,
-                        created_at: row.get(3)?,
-                        snapshot_dir: row.get(4)?,
-                        guest_disk_bytes: row.get::<_, i64>(5)? as u64,
-                        container_disk_bytes: row.get::<_, i64>(6)? as u64,
-                        size_bytes: row.get::<_, i64>(7)? as u64,
-                    })
-                },
+                from_row,
             )
             .optional()
         )?;
@@ -168,6 +124,66 @@ impl SnapshotStore {
     }
 }
 
+/// Columns read by [`from_row`], in order.
+const COLUMNS: &str = "id, box_id, name, created_at, snapshot_dir, guest_disk_bytes, \
+                       container_disk_bytes, size_bytes, has_metadata, metadata_only";
+
+fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<SnapshotInfo> {
+    Ok(SnapshotInfo {
+        id: row.get(0)?,
+        box_id: row.get(1)?,
+        name: row.get(2)?,
+        created_at: row.get(3)?,
+        snapshot_dir: row.get(4)?,
+        guest_disk_bytes: row.get::<_, i64>(5)? as u64,
+        container_disk_bytes: row.get::<_, i64>(6)? as u64,
+        size_bytes: row.get::<_, i64>(7)? as u64,
+        has_metadata: row.get(8)?,
+        metadata_only: row.get(9)?,
+        pruned: None,
+    })
+}
+
+/// Insert a snapshot record on `conn` (which may be a transaction).
+pub(super) fn insert_with(conn: &rusqlite::Connection, record: &SnapshotInfo) -> BoxliteResult<()> {
+    db_err!(conn.execute(
+        "INSERT INTO box_snapshot (id, box_id, name, created_at, snapshot_dir, \
+         guest_disk_bytes, container_disk_bytes, size_bytes, has_metadata, metadata_only) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            record.id,
+            record.box_id,
+            record.name,
+            record.created_at,
+            record.snapshot_dir,
+            record.guest_disk_bytes as i64,
+            record.container_disk_bytes as i64,
+            record.size_bytes as i64,
+            record.has_metadata,
+            record.metadata_only,
+        ],
+    ))?;
+    Ok(())
+}
+
+/// List a box's snapshots on `conn` (which may be a transaction), newest first.
+pub(super) fn list_with(
+    conn: &rusqlite::Connection,
+    box_id: &str,
+) -> BoxliteResult<Vec<SnapshotInfo>> {
+    let mut stmt = db_err!(conn.prepare(&format!(
+        "SELECT {COLUMNS} FROM box_snapshot WHERE box_id = ?1 ORDER BY created_at DESC"
+    )))?;
+
+    let rows = db_err!(stmt.query_map(rusqlite::params![box_id], from_row))?;
+
+    let mut snapshots = Vec::new();
+    for row in rows {
+        snapshots.push(db_err!(row)?);
+    }
+    Ok(snapshots)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -190,6 +206,9 @@ mod tests {
             guest_disk_bytes: 1024,
             container_disk_bytes: 10 * 1024 * 1024 * 1024,
             size_bytes: 512,
+            has_metadata: false,
+            metadata_only: false,
+            pruned: None,
         }
     }
 
@@ -218,6 +237,27 @@ mod tests {
         assert_eq!(snapshots[0].container_disk_bytes, 10 * 1024 * 1024 * 1024);
     }
 
+    #[test]
+    fn test_snapshot_metadata_flags_round_trip() {
+        let db = test_db();
+        insert_box(&db, "box1");
+
+        let store = SnapshotStore::new(db);
+
+        let record = SnapshotInfo {
+            has_metadata: true,
+            metadata_only: true,
+            ..make_snapshot("box1", "config")
+        };
+        store.save(&record).unwrap();
+        store.save(&make_snapshot("box1", "disks")).unwrap();
+
+        let found = store.get_by_name("box1", "config").unwrap().unwrap();
+        assert!(found.has_metadata && found.metadata_only);
+        let found = store.get_by_name("box1", "disks").unwrap().unwrap();
+        assert!(!found.has_metadata && !found.metadata_only);
+    }
+
     #[test]
     fn test_snapshot_get_by_name() {
         let db = test_db();
@@ -0,0 +1,216 @@
+//! Box template persistence.
+//!
+//! A template is a named `BoxOptions` blob. Boxes created from a template
+//! copy its options, so removing or replacing a template never affects
+//! existing boxes.
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+
+use super::{Database, db_err};
+use crate::runtime::options::BoxOptions;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Named box options, from `BoxliteRuntime::register_template`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BoxTemplate {
+    /// Name boxes are created from.
+    pub name: String,
+    /// Options every box created from the template starts with.
+    pub options: BoxOptions,
+    /// When the template was registered.
+    pub created_at: DateTime<Utc>,
+}
+
+/// Store for box templates.
+pub struct TemplateStore {
+    db: Database,
+}
+
+impl TemplateStore {
+    /// Create a new TemplateStore wrapping the given database.
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Add a template. Fails with `AlreadyExists` if the name is taken.
+    pub(crate) fn insert(&self, template: &BoxTemplate) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+        let inserted = db_err!(conn.execute(
+            "INSERT OR IGNORE INTO box_template (name, created_at, json) VALUES (?1, ?2, ?3)",
+            params![
+                template.name,
+                template.created_at.timestamp(),
+                to_json(&template.options)?
+            ],
+        ))?;
+        if inserted == 0 {
+            return Err(BoxliteError::AlreadyExists(format!(
+                "template '{}' already exists",
+                template.name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Add a template, replacing any with the same name.
+    pub(crate) fn upsert(&self, template: &BoxTemplate) -> BoxliteResult<()> {
+        let conn = self.db.conn();
+        db_err!(conn.execute(
+            "INSERT OR REPLACE INTO box_template (name, created_at, json) VALUES (?1, ?2, ?3)",
+            params![
+                template.name,
+                template.created_at.timestamp(),
+                to_json(&template.options)?
+            ],
+        ))?;
+        Ok(())
+    }
+
+    /// Get a template by name.
+    pub(crate) fn get(&self, name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        let conn = self.db.conn();
+        let row = db_err!(
+            conn.query_row(
+                "SELECT name, created_at, json FROM box_template WHERE name = ?1",
+                params![name],
+                RawRow::from_row,
+            )
+            .optional()
+        )?;
+        row.map(RawRow::into_template).transpose()
+    }
+
+    /// List templates by name.
+    pub(crate) fn list(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        let conn = self.db.conn();
+        let mut stmt =
+            db_err!(conn.prepare("SELECT name, created_at, json FROM box_template ORDER BY name"))?;
+        let rows = db_err!(stmt.query_map([], RawRow::from_row))?;
+
+        let mut templates = Vec::new();
+        for row in rows {
+            templates.push(db_err!(row)?.into_template()?);
+        }
+        Ok(templates)
+    }
+
+    /// Delete a template. Returns false if there was none by that name.
+    pub(crate) fn remove(&self, name: &str) -> BoxliteResult<bool> {
+        let conn = self.db.conn();
+        let rows_affected =
+            db_err!(conn.execute("DELETE FROM box_template WHERE name = ?1", params![name]))?;
+        Ok(rows_affected > 0)
+    }
+}
+
+/// Undecoded `box_template` row.
+struct RawRow {
+    name: String,
+    created_at: i64,
+    json: String,
+}
+
+impl RawRow {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            name: row.get(0)?,
+            created_at: row.get(1)?,
+            json: row.get(2)?,
+        })
+    }
+
+    fn into_template(self) -> BoxliteResult<BoxTemplate> {
+        let options = serde_json::from_str(&self.json).map_err(|e| {
+            BoxliteError::Database(format!(
+                "Failed to deserialize template '{}': {}",
+                self.name, e
+            ))
+        })?;
+        Ok(BoxTemplate {
+            name: self.name,
+            options,
+            created_at: Utc
+                .timestamp_opt(self.created_at, 0)
+                .single()
+                .unwrap_or_default(),
+        })
+    }
+}
+
+fn to_json(options: &BoxOptions) -> BoxliteResult<String> {
+    serde_json::to_string(options)
+        .map_err(|e| BoxliteError::Database(format!("Failed to serialize template: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::RootfsSpec;
+    use tempfile::TempDir;
+
+    fn test_store() -> (TemplateStore, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        (TemplateStore::new(db), dir)
+    }
+
+    fn test_template(name: &str, image: &str) -> BoxTemplate {
+        BoxTemplate {
+            name: name.to_string(),
+            options: BoxOptions {
+                rootfs: RootfsSpec::Image(image.to_string()),
+                env: vec![("TIER".to_string(), "web".to_string())],
+                ..Default::default()
+            },
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_insert_and_get_round_trips_options() {
+        let (store, _dir) = test_store();
+        store.insert(&test_template("web", "nginx:latest")).unwrap();
+
+        let template = store.get("web").unwrap().unwrap();
+        assert!(matches!(template.options.rootfs, RootfsSpec::Image(ref i) if i == "nginx:latest"));
+        assert_eq!(template.options.env, vec![("TIER".into(), "web".into())]);
+        assert!(store.get("api").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_insert_rejects_duplicate_name() {
+        let (store, _dir) = test_store();
+        store.insert(&test_template("web", "nginx:latest")).unwrap();
+
+        let err = store
+            .insert(&test_template("web", "caddy:latest"))
+            .unwrap_err();
+        assert!(matches!(err, BoxliteError::AlreadyExists(_)));
+    }
+
+    #[test]
+    fn test_upsert_replaces() {
+        let (store, _dir) = test_store();
+        store.insert(&test_template("web", "nginx:latest")).unwrap();
+        store.upsert(&test_template("web", "caddy:latest")).unwrap();
+
+        let template = store.get("web").unwrap().unwrap();
+        assert!(matches!(template.options.rootfs, RootfsSpec::Image(ref i) if i == "caddy:latest"));
+    }
+
+    #[test]
+    fn test_list_and_remove() {
+        let (store, _dir) = test_store();
+        store.insert(&test_template("web", "nginx:latest")).unwrap();
+        store.insert(&test_template("api", "python:3")).unwrap();
+
+        let names: Vec<String> = store.list().unwrap().into_iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["api", "web"]);
+
+        assert!(store.remove("api").unwrap());
+        assert!(!store.remove("api").unwrap());
+        assert_eq!(store.list().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,380 @@
+//! Trash (soft-delete) persistence.
+//!
+//! Trashing a box moves its `box_config`, `box_state` and `box_snapshot`
+//! rows into a single `box_trash` row in one transaction; restoring moves
+//! them back. Live-box queries never see trashed boxes.
+
+use std::path::PathBuf;
+
+use chrono::{DateTime, TimeZone, Utc};
+use rusqlite::{OptionalExtension, params};
+use serde::{Deserialize, Serialize};
+
+use super::snapshots::{self, SnapshotInfo};
+use super::{Database, db_err};
+use crate::litebox::config::BoxConfig;
+use crate::runtime::types::{BoxID, BoxInfo, BoxState};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Summary of a box in the trash.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrashedBox {
+    /// ID the box had (and gets back on restore).
+    pub id: BoxID,
+    /// Name the box had, if any.
+    pub name: Option<String>,
+    /// Image reference or rootfs path.
+    pub image: String,
+    /// When the box was originally created.
+    pub created_at: DateTime<Utc>,
+    /// When the box was moved to the trash.
+    pub trashed_at: DateTime<Utc>,
+    /// When the background sweep will purge it (None without a retention).
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// A trashed box as stored in the database.
+#[derive(Debug, Clone)]
+pub(crate) struct TrashRecord {
+    pub config: BoxConfig,
+    pub state: BoxState,
+    pub snapshots: Vec<SnapshotInfo>,
+    pub trashed_at: DateTime<Utc>,
+    /// Where the box home was moved to.
+    pub trash_dir: PathBuf,
+}
+
+impl TrashRecord {
+    /// Public summary, with expiry computed from the runtime's retention.
+    pub(crate) fn summary(&self, retention: Option<std::time::Duration>) -> TrashedBox {
+        let info = BoxInfo::new(&self.config, &self.state);
+        TrashedBox {
+            id: info.id,
+            name: info.name,
+            image: info.image,
+            created_at: info.created_at,
+            trashed_at: self.trashed_at,
+            expires_at: retention
+                .and_then(|r| chrono::Duration::from_std(r).ok())
+                .map(|r| self.trashed_at + r),
+        }
+    }
+}
+
+/// Store for trashed boxes.
+pub struct TrashStore {
+    db: Database,
+}
+
+impl TrashStore {
+    /// Create a new TrashStore wrapping the given database.
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    /// Move a live box into the trash.
+    ///
+    /// `state` is stored as given (callers clear runtime-only fields such as
+    /// the lock ID first). Fails with `NotFound` if the box is not live.
+    pub(crate) fn trash(
+        &self,
+        config: &BoxConfig,
+        state: &BoxState,
+        trash_dir: &std::path::Path,
+        trashed_at: DateTime<Utc>,
+    ) -> BoxliteResult<()> {
+        let mut conn = self.db.conn();
+        let tx = db_err!(conn.transaction())?;
+
+        let snapshots = snapshots::list_with(&tx, config.id.as_str())?;
+
+        db_err!(tx.execute(
+            "INSERT INTO box_trash (id, name, trashed_at, trash_dir, config_json, state_json, \
+             snapshots_json) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                config.id,
+                config.name.as_deref(),
+                trashed_at.timestamp(),
+                trash_dir.to_string_lossy(),
+                to_json(config, "config")?,
+                to_json(state, "state")?,
+                to_json(&snapshots, "snapshots")?,
+            ],
+        ))?;
+
+        // Cascades to box_state and box_snapshot
+        let deleted =
+            db_err!(tx.execute("DELETE FROM box_config WHERE id = ?1", params![config.id]))?;
+        if deleted == 0 {
+            return Err(BoxliteError::NotFound(format!("box {}", config.id)));
+        }
+
+        db_err!(tx.commit())?;
+        Ok(())
+    }
+
+    /// Move a trashed box back to the live tables with `state`.
+    ///
+    /// Fails if a live box already uses the same ID or name.
+    pub(crate) fn restore(&self, record: &TrashRecord, state: &BoxState) -> BoxliteResult<()> {
+        let mut conn = self.db.conn();
+        let tx = db_err!(conn.transaction())?;
+        let config = &record.config;
+
+        db_err!(tx.execute(
+            "INSERT INTO box_config (id, name, created_at, json) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                config.id,
+                config.name.as_deref(),
+                config.created_at.timestamp(),
+                to_json(config, "config")?
+            ],
+        ))?;
+        db_err!(tx.execute(
+            "INSERT INTO box_state (id, status, pid, json, updated_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                config.id,
+                state.status.as_str(),
+                state.pid,
+                to_json(state, "state")?,
+                state.last_updated.timestamp()
+            ],
+        ))?;
+        for snapshot in &record.snapshots {
+            snapshots::insert_with(&tx, snapshot)?;
+        }
+        db_err!(tx.execute("DELETE FROM box_trash WHERE id = ?1", params![config.id]))?;
+
+        db_err!(tx.commit())?;
+        Ok(())
+    }
+
+    /// List trashed boxes, most recently trashed first.
+    pub(crate) fn list(&self) -> BoxliteResult<Vec<TrashRecord>> {
+        let conn = self.db.conn();
+        let mut stmt = db_err!(conn.prepare(
+            "SELECT trashed_at, trash_dir, config_json, state_json, snapshots_json \
+             FROM box_trash ORDER BY trashed_at DESC"
+        ))?;
+        let rows = db_err!(stmt.query_map([], RawRow::from_row))?;
+
+        let mut records = Vec::new();
+        for row in rows {
+            records.push(db_err!(row)?.into_record()?);
+        }
+        Ok(records)
+    }
+
+    /// Get a trashed box by exact ID.
+    pub(crate) fn get(&self, box_id: &str) -> BoxliteResult<Option<TrashRecord>> {
+        let conn = self.db.conn();
+        let row = db_err!(
+            conn.query_row(
+                "SELECT trashed_at, trash_dir, config_json, state_json, snapshots_json \
+                 FROM box_trash WHERE id = ?1",
+                params![box_id],
+                RawRow::from_row,
+            )
+            .optional()
+        )?;
+        row.map(RawRow::into_record).transpose()
+    }
+
+    /// Delete a trashed box's record. Returns false if it was not trashed.
+    pub(crate) fn remove(&self, box_id: &str) -> BoxliteResult<bool> {
+        let conn = self.db.conn();
+        let rows_affected =
+            db_err!(conn.execute("DELETE FROM box_trash WHERE id = ?1", params![box_id]))?;
+        Ok(rows_affected > 0)
+    }
+}
+
+/// Undecoded `box_trash` row.
+struct RawRow {
+    trashed_at: i64,
+    trash_dir: String,
+    config_json: String,
+    state_json: String,
+    snapshots_json: String,
+}
+
+impl RawRow {
+    fn from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Self> {
+        Ok(Self {
+            trashed_at: row.get(0)?,
+            trash_dir: row.get(1)?,
+            config_json: row.get(2)?,
+            state_json: row.get(3)?,
+            snapshots_json: row.get(4)?,
+        })
+    }
+
+    fn into_record(self) -> BoxliteResult<TrashRecord> {
+        Ok(TrashRecord {
+            config: from_json(&self.config_json, "config")?,
+            state: from_json(&self.state_json, "state")?,
+            snapshots: from_json(&self.snapshots_json, "snapshots")?,
+            trashed_at: Utc
+                .timestamp_opt(self.trashed_at, 0)
+                .single()
+                .unwrap_or_default(),
+            trash_dir: PathBuf::from(self.trash_dir),
+        })
+    }
+}
+
+fn to_json<T: serde::Serialize + ?Sized>(value: &T, what: &str) -> BoxliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|e| BoxliteError::Database(format!("Failed to serialize {}: {}", what, e)))
+}
+
+fn from_json<T: serde::de::DeserializeOwned>(json: &str, what: &str) -> BoxliteResult<T> {
+    serde_json::from_str(json)
+        .map_err(|e| BoxliteError::Database(format!("Failed to deserialize {}: {}", what, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{BoxStore, SnapshotStore};
+    use crate::litebox::config::ContainerRuntimeConfig;
+    use crate::runtime::options::{BoxOptions, RootfsSpec};
+    use crate::runtime::types::ContainerID;
+    use crate::vmm::VmmKind;
+    use boxlite_shared::Transport;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    const TEST_ID_1: &str = "01HJK4TNRPQSXYZ8WM6NCVT9R1";
+    const TEST_ID_2: &str = "01HJK4TNRPQSXYZ8WM6NCVT9R2";
+
+    fn test_db() -> (Database, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let db = Database::open(&dir.path().join("test.db")).unwrap();
+        (db, dir)
+    }
+
+    fn test_config(id: &str, name: &str) -> BoxConfig {
+        BoxConfig {
+            id: BoxID::parse(id).unwrap(),
+            name: Some(name.to_string()),
+            created_at: Utc::now(),
+            container: ContainerRuntimeConfig {
+                id: ContainerID::new(),
+            },
+            options: BoxOptions {
+                rootfs: RootfsSpec::Image("test:latest".to_string()),
+                ..Default::default()
+            },
+            engine_kind: VmmKind::Libkrun,
+            transport: Transport::unix(PathBuf::from("/tmp/test.sock")),
+            box_home: PathBuf::from("/tmp/boxes/test"),
+            ready_socket_path: PathBuf::from("/tmp/ready.sock"),
+            disk_driver: Default::default(),
+            lineage: None,
+            degradations: Vec::new(),
+        }
+    }
+
+    fn test_snapshot(box_id: &str) -> SnapshotInfo {
+        SnapshotInfo {
+            id: ulid::Ulid::new().to_string(),
+            box_id: box_id.to_string(),
+            name: "snap1".to_string(),
+            created_at: Utc::now().timestamp(),
+            snapshot_dir: "/tmp/boxes/test/snapshots/snap1".to_string(),
+            guest_disk_bytes: 0,
+            container_disk_bytes: 1024,
+            size_bytes: 512,
+            has_metadata: true,
+            metadata_only: false,
+            pruned: None,
+        }
+    }
+
+    #[test]
+    fn test_trash_hides_box_and_frees_name() {
+        let (db, _dir) = test_db();
+        let boxes = BoxStore::new(db.clone());
+        let trash = TrashStore::new(db);
+
+        let config = test_config(TEST_ID_1, "web");
+        boxes.save(&config, &BoxState::new()).unwrap();
+        trash
+            .trash(&config, &BoxState::new(), Path::new("/trash/1"), Utc::now())
+            .unwrap();
+
+        assert!(boxes.load(TEST_ID_1).unwrap().is_none());
+        assert_eq!(trash.list().unwrap().len(), 1);
+
+        // A new live box may reuse the name
+        boxes
+            .save(&test_config(TEST_ID_2, "web"), &BoxState::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn test_restore_round_trips_snapshots() {
+        let (db, _dir) = test_db();
+        let boxes = BoxStore::new(db.clone());
+        let snapshots = SnapshotStore::new(db.clone());
+        let trash = TrashStore::new(db);
+
+        let config = test_config(TEST_ID_1, "web");
+        boxes.save(&config, &BoxState::new()).unwrap();
+        snapshots.save(&test_snapshot(TEST_ID_1)).unwrap();
+
+        trash
+            .trash(&config, &BoxState::new(), Path::new("/trash/1"), Utc::now())
+            .unwrap();
+        assert!(snapshots.list(TEST_ID_1).unwrap().is_empty());
+
+        let record = trash.get(TEST_ID_1).unwrap().unwrap();
+        assert_eq!(record.trash_dir, PathBuf::from("/trash/1"));
+        trash.restore(&record, &record.state).unwrap();
+
+        assert!(boxes.load(TEST_ID_1).unwrap().is_some());
+        let restored = snapshots.list(TEST_ID_1).unwrap();
+        assert_eq!(restored.len(), 1);
+        assert!(restored[0].has_metadata);
+        assert!(trash.get(TEST_ID_1).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_restore_fails_on_name_conflict() {
+        let (db, _dir) = test_db();
+        let boxes = BoxStore::new(db.clone());
+        let trash = TrashStore::new(db);
+
+        let config = test_config(TEST_ID_1, "web");
+        boxes.save(&config, &BoxState::new()).unwrap();
+        trash
+            .trash(&config, &BoxState::new(), Path::new("/trash/1"), Utc::now())
+            .unwrap();
+        boxes
+            .save(&test_config(TEST_ID_2, "web"), &BoxState::new())
+            .unwrap();
+
+        let record = trash.get(TEST_ID_1).unwrap().unwrap();
+        assert!(trash.restore(&record, &record.state).is_err());
+        // Still in the trash after the failed restore
+        assert!(trash.get(TEST_ID_1).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_remove() {
+        let (db, _dir) = test_db();
+        let boxes = BoxStore::new(db.clone());
+        let trash = TrashStore::new(db);
+
+        let config = test_config(TEST_ID_1, "web");
+        boxes.save(&config, &BoxState::new()).unwrap();
+        trash
+            .trash(&config, &BoxState::new(), Path::new("/trash/1"), Utc::now())
+            .unwrap();
+
+        assert!(trash.remove(TEST_ID_1).unwrap());
+        assert!(!trash.remove(TEST_ID_1).unwrap());
+        assert!(trash.list().unwrap().is_empty());
+    }
+}
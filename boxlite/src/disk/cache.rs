@@ -0,0 +1,425 @@
+//! Size-bounded disk caches.
+//!
+//! The image disk and guest rootfs caches keep one `.ext4` file per entry.
+//! Every hit touches a `{entry}.used` marker whose mtime records the entry's
+//! last use. When a cache grows past its cap, entries are evicted least
+//! recently used first, skipping those a box disk is backed by.
+
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::{Deserialize, Serialize};
+
+use super::constants::dirs::SNAPSHOTS_DIR;
+use super::read_backing_file_path;
+
+/// Suffix of the marker recording when an entry was last used.
+const USED_SUFFIX: &str = ".used";
+
+/// Entries used more recently than this are never evicted: a box being
+/// created may hold one it has not yet put a disk on top of.
+const EVICTION_GRACE: Duration = Duration::from_secs(5 * 60);
+
+/// Usage of the size-bounded disk caches, see `BoxliteRuntime::cache_usage()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheUsage {
+    /// Ext4 disks built from OCI images (`~/.boxlite/images/disk-images`).
+    pub image_disks: CacheStats,
+    /// Image disks with the guest agent injected (`~/.boxlite/rootfs`).
+    pub guest_rootfs: CacheStats,
+}
+
+/// Usage of one disk cache.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Cached disks.
+    pub entries: usize,
+    /// Bytes allocated on the host by the cached disks.
+    pub size_bytes: u64,
+    /// Disks that existing boxes (including trashed ones) are backed by.
+    pub referenced_entries: usize,
+    /// Bytes of the disks no box is backed by.
+    pub reclaimable_bytes: u64,
+    /// Configured cap (`None` = unbounded).
+    pub max_bytes: Option<u64>,
+    /// Disks evicted since the runtime was created.
+    pub evicted_entries: u64,
+    /// Bytes freed by those evictions.
+    pub evicted_bytes: u64,
+}
+
+/// One cached disk.
+#[derive(Debug, Clone)]
+pub(crate) struct CacheEntry {
+    pub path: PathBuf,
+    pub size: u64,
+    pub last_used: SystemTime,
+}
+
+/// Size cap of a disk cache and the evictions it caused.
+#[derive(Debug, Default)]
+pub(crate) struct CacheCap {
+    max_bytes: Option<u64>,
+    /// Directories whose subdirectories are box homes (boxes, trash).
+    box_roots: Vec<PathBuf>,
+    evicted_entries: AtomicU64,
+    evicted_bytes: AtomicU64,
+}
+
+impl CacheCap {
+    pub fn new(max_bytes: Option<u64>, box_roots: Vec<PathBuf>) -> Self {
+        Self {
+            max_bytes,
+            box_roots,
+            ..Default::default()
+        }
+    }
+
+    /// Evict least recently used entries of `cache_dir` until it fits the cap.
+    ///
+    /// `cache` names the cache in logs. Returns the evicted entries.
+    pub fn enforce(&self, cache: &str, cache_dir: &Path) -> BoxliteResult<Vec<CacheEntry>> {
+        let Some(max_bytes) = self.max_bytes else {
+            return Ok(Vec::new());
+        };
+        let entries = list_entries(cache_dir)?;
+        let referenced = referenced_backing_files(&self.box_roots)?;
+        let evicted = evict_lru(cache, entries, max_bytes, &referenced, SystemTime::now());
+
+        for entry in &evicted {
+            self.evicted_entries.fetch_add(1, Ordering::Relaxed);
+            self.evicted_bytes.fetch_add(entry.size, Ordering::Relaxed);
+        }
+        Ok(evicted)
+    }
+
+    /// Current usage of `cache_dir`.
+    pub fn stats(&self, cache_dir: &Path) -> BoxliteResult<CacheStats> {
+        let referenced = referenced_backing_files(&self.box_roots)?;
+        let mut stats = CacheStats {
+            max_bytes: self.max_bytes,
+            evicted_entries: self.evicted_entries.load(Ordering::Relaxed),
+            evicted_bytes: self.evicted_bytes.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        for entry in list_entries(cache_dir)? {
+            stats.entries += 1;
+            stats.size_bytes += entry.size;
+            if referenced.contains(&entry.path) {
+                stats.referenced_entries += 1;
+            } else {
+                stats.reclaimable_bytes += entry.size;
+            }
+        }
+        Ok(stats)
+    }
+}
+
+/// Pick entries to remove, least recently used first, until the rest fit in
+/// `max_bytes`, and remove them.
+///
+/// Referenced entries and entries used within [`EVICTION_GRACE`] of `now`
+/// are kept even if the cache stays over its cap.
+fn evict_lru(
+    cache: &str,
+    mut entries: Vec<CacheEntry>,
+    max_bytes: u64,
+    referenced: &HashSet<PathBuf>,
+    now: SystemTime,
+) -> Vec<CacheEntry> {
+    let mut total: u64 = entries.iter().map(|entry| entry.size).sum();
+    if total <= max_bytes {
+        return Vec::new();
+    }
+
+    entries.sort_by_key(|entry| entry.last_used);
+    let mut evicted = Vec::new();
+    for entry in entries {
+        if total <= max_bytes {
+            break;
+        }
+        if referenced.contains(&entry.path) {
+            continue;
+        }
+        let idle = now.duration_since(entry.last_used).unwrap_or_default();
+        if idle < EVICTION_GRACE {
+            continue;
+        }
+
+        if let Err(e) = remove_entry(&entry.path) {
+            tracing::warn!("Failed to evict {}: {}", entry.path.display(), e);
+            continue;
+        }
+        tracing::info!(
+            cache,
+            entry = %entry.path.display(),
+            size_bytes = entry.size,
+            idle_secs = idle.as_secs(),
+            cache_bytes = total,
+            max_bytes,
+            "Evicted least recently used cache entry: cache over its cap"
+        );
+        total -= entry.size;
+        evicted.push(entry);
+    }
+
+    if total > max_bytes {
+        tracing::warn!(
+            cache,
+            cache_bytes = total,
+            max_bytes,
+            "Cache stays over its cap: remaining entries are in use"
+        );
+    }
+    evicted
+}
+
+/// Record that `entry` was just used.
+pub(crate) fn touch(entry: &Path) {
+    let marker = used_marker(entry);
+    let result = fs::File::create(&marker).and_then(|file| file.set_modified(SystemTime::now()));
+    if let Err(e) = result {
+        tracing::debug!("Failed to touch {}: {}", marker.display(), e);
+    }
+}
+
+/// Remove a cached disk and its last-use marker.
+pub(crate) fn remove_entry(entry: &Path) -> std::io::Result<()> {
+    fs::remove_file(entry)?;
+    let _ = fs::remove_file(used_marker(entry));
+    Ok(())
+}
+
+/// Cached `.ext4` disks in `cache_dir`. Markers left without a disk are
+/// removed on the way.
+pub(crate) fn list_entries(cache_dir: &Path) -> BoxliteResult<Vec<CacheEntry>> {
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let dir_entries = fs::read_dir(cache_dir).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to read cache directory {}: {}",
+            cache_dir.display(),
+            e
+        ))
+    })?;
+
+    let mut entries = Vec::new();
+    for path in dir_entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        if let Some(disk) = path
+            .to_str()
+            .and_then(|p| p.strip_suffix(USED_SUFFIX))
+            .map(Path::new)
+        {
+            if !disk.exists() {
+                let _ = fs::remove_file(&path);
+            }
+            continue;
+        }
+        if path.extension().is_none_or(|ext| ext != "ext4") {
+            continue;
+        }
+        let Ok(metadata) = path.metadata() else {
+            continue;
+        };
+        let last_used = used_marker(&path)
+            .metadata()
+            .and_then(|m| m.modified())
+            .or_else(|_| metadata.modified())
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        entries.push(CacheEntry {
+            path,
+            size: metadata.blocks() * 512,
+            last_used,
+        });
+    }
+    Ok(entries)
+}
+
+/// Backing files of the qcow2 disks of every box home under `box_roots`,
+/// including their snapshots.
+pub(crate) fn referenced_backing_files(box_roots: &[PathBuf]) -> BoxliteResult<HashSet<PathBuf>> {
+    let mut referenced = HashSet::new();
+
+    for root in box_roots.iter().filter(|root| root.exists()) {
+        let entries = fs::read_dir(root).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to read boxes directory {}: {}",
+                root.display(),
+                e
+            ))
+        })?;
+
+        for entry in entries {
+            let entry = entry.map_err(|e| {
+                BoxliteError::Storage(format!("Failed to read box directory entry: {}", e))
+            })?;
+            let box_dir = entry.path();
+            collect_backing_files(&box_dir, &mut referenced);
+
+            let Ok(snapshots) = fs::read_dir(box_dir.join(SNAPSHOTS_DIR)) else {
+                continue;
+            };
+            for snapshot in snapshots.filter_map(|entry| entry.ok()) {
+                collect_backing_files(&snapshot.path(), &mut referenced);
+            }
+        }
+    }
+
+    Ok(referenced)
+}
+
+/// Add the backing files of the qcow2 disks directly in `dir`.
+fn collect_backing_files(dir: &Path, referenced: &mut HashSet<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for qcow2_path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "qcow2"))
+    {
+        match read_backing_file_path(&qcow2_path) {
+            Ok(Some(backing)) => {
+                // Relative backing paths resolve against the overlay's directory
+                referenced.insert(dir.join(backing));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to read backing file from {}: {}",
+                    qcow2_path.display(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+fn used_marker(entry: &Path) -> PathBuf {
+    let mut name = entry.as_os_str().to_owned();
+    name.push(USED_SUFFIX);
+    PathBuf::from(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(dir: &Path, name: &str, size: u64, idle: Duration, now: SystemTime) -> CacheEntry {
+        let path = dir.join(name);
+        fs::write(&path, name).unwrap();
+        CacheEntry {
+            path,
+            size,
+            last_used: now - idle,
+        }
+    }
+
+    #[test]
+    fn test_evict_lru_removes_oldest_until_under_cap() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let entries = vec![
+            entry(dir.path(), "new.ext4", 100, hour, now),
+            entry(dir.path(), "oldest.ext4", 100, 3 * hour, now),
+            entry(dir.path(), "old.ext4", 100, 2 * hour, now),
+        ];
+
+        let evicted = evict_lru("test", entries, 150, &HashSet::new(), now);
+
+        let names: Vec<_> = evicted
+            .iter()
+            .map(|e| e.path.file_name().unwrap())
+            .collect();
+        assert_eq!(names, ["oldest.ext4", "old.ext4"]);
+        assert!(dir.path().join("new.ext4").exists());
+        assert!(!dir.path().join("oldest.ext4").exists());
+    }
+
+    #[test]
+    fn test_evict_lru_skips_referenced_and_recent_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let entries = vec![
+            entry(dir.path(), "referenced.ext4", 100, 3 * hour, now),
+            entry(dir.path(), "recent.ext4", 100, Duration::from_secs(10), now),
+            entry(dir.path(), "idle.ext4", 100, hour, now),
+        ];
+        let referenced = HashSet::from([dir.path().join("referenced.ext4")]);
+
+        let evicted = evict_lru("test", entries, 0, &referenced, now);
+
+        assert_eq!(evicted.len(), 1);
+        assert_eq!(evicted[0].path, dir.path().join("idle.ext4"));
+        assert!(dir.path().join("referenced.ext4").exists());
+        assert!(dir.path().join("recent.ext4").exists());
+    }
+
+    #[test]
+    fn test_evict_lru_under_cap_keeps_everything() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(3600);
+        let entries = vec![entry(dir.path(), "a.ext4", 100, hour, now)];
+
+        assert!(evict_lru("test", entries, 100, &HashSet::new(), now).is_empty());
+        assert!(dir.path().join("a.ext4").exists());
+    }
+
+    #[test]
+    fn test_touch_records_last_use() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let disk = dir.path().join("a.ext4");
+        fs::write(&disk, "disk").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(&disk)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let entries = list_entries(dir.path()).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].last_used <= old + Duration::from_secs(1));
+
+        touch(&disk);
+        let entries = list_entries(dir.path()).unwrap();
+        assert_eq!(entries.len(), 1, "marker is not an entry");
+        assert!(entries[0].last_used > old + Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_list_entries_removes_orphan_markers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let orphan = dir.path().join("gone.ext4.used");
+        fs::write(&orphan, "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        assert!(list_entries(dir.path()).unwrap().is_empty());
+        assert!(!orphan.exists());
+    }
+
+    #[test]
+    fn test_remove_entry_removes_marker() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let disk = dir.path().join("a.ext4");
+        fs::write(&disk, "disk").unwrap();
+        touch(&disk);
+
+        remove_entry(&disk).unwrap();
+        assert!(!disk.exists());
+        assert!(!used_marker(&disk).exists());
+    }
+}
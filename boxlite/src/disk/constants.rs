@@ -9,6 +9,12 @@ pub mod filenames {
 
     /// Guest bootstrap COW disk: `~/.boxlite/boxes/{box_id}/guest-rootfs.qcow2`
     pub const GUEST_ROOTFS_DISK: &str = "guest-rootfs.qcow2";
+
+    /// Container rootfs disk of the reflink driver: `~/.boxlite/boxes/{box_id}/disk.raw`
+    pub const RAW_CONTAINER_DISK: &str = "disk.raw";
+
+    /// Guest bootstrap disk of the reflink driver: `~/.boxlite/boxes/{box_id}/guest-rootfs.raw`
+    pub const RAW_GUEST_ROOTFS_DISK: &str = "guest-rootfs.raw";
 }
 
 /// Directory names within a box home.
@@ -0,0 +1,420 @@
+//! Disk drivers: how a box's writable disks are provisioned.
+//!
+//! A box's container and guest disks start from shared raw base images.
+//! - `Qcow2` (default): qcow2 overlays backed by the base image. Works on
+//!   any filesystem.
+//! - `Reflink`: raw disks cloned from the base with `FICLONE` (or the
+//!   platform equivalent). On Btrfs and ZFS this avoids layering qcow2 COW
+//!   on top of the filesystem's own COW.
+//!
+//! The driver is chosen per runtime (`BoxliteOptions::storage_driver`) and
+//! recorded per box, so one home can hold boxes of both kinds. Snapshot,
+//! restore and clone go through the box's driver; export always flattens
+//! to a standalone qcow2.
+
+use std::path::Path;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::{Deserialize, Serialize};
+
+use super::constants::filenames;
+use super::{BackingFormat, Disk, DiskFormat, Qcow2Helper, qemu_img};
+use crate::runtime::options::StorageDriver;
+
+/// `statfs` magic of Btrfs.
+#[cfg(target_os = "linux")]
+const BTRFS_SUPER_MAGIC: i64 = 0x9123_683E;
+/// `statfs` magic of ZFS.
+#[cfg(target_os = "linux")]
+const ZFS_SUPER_MAGIC: i64 = 0x2FC1_2FC1;
+
+/// Disk driver a box was created with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskDriverKind {
+    /// qcow2 overlays over the base images.
+    #[default]
+    Qcow2,
+    /// Raw disks reflinked from the base images.
+    Reflink,
+}
+
+impl DiskDriverKind {
+    /// The driver implementing this kind.
+    pub(crate) fn driver(self) -> &'static dyn DiskDriver {
+        match self {
+            DiskDriverKind::Qcow2 => &Qcow2Driver,
+            DiskDriverKind::Reflink => &ReflinkDriver,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DiskDriverKind::Qcow2 => "qcow2",
+            DiskDriverKind::Reflink => "reflink",
+        }
+    }
+
+    /// Resolve the requested storage driver for disks under `boxes_dir`.
+    ///
+    /// `Auto` picks `Reflink` on Btrfs and ZFS when a reflink probe succeeds,
+    /// and `Qcow2` otherwise. `Reflink` fails if the probe does.
+    pub(crate) fn resolve(requested: StorageDriver, boxes_dir: &Path) -> BoxliteResult<Self> {
+        match requested {
+            StorageDriver::Qcow2 => Ok(DiskDriverKind::Qcow2),
+            StorageDriver::Reflink => {
+                probe_reflink(boxes_dir).map_err(|e| {
+                    BoxliteError::Unsupported(format!(
+                        "storage driver 'reflink' needs a filesystem with reflink support \
+                         under {}: {}",
+                        boxes_dir.display(),
+                        e
+                    ))
+                })?;
+                Ok(DiskDriverKind::Reflink)
+            }
+            StorageDriver::Auto => {
+                if is_cow_filesystem(boxes_dir) && probe_reflink(boxes_dir).is_ok() {
+                    Ok(DiskDriverKind::Reflink)
+                } else {
+                    Ok(DiskDriverKind::Qcow2)
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for DiskDriverKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Provisioning strategy for a box's writable disks.
+///
+/// "Frozen" disks are the read-only copies kept in snapshot directories.
+pub(crate) trait DiskDriver: Send + Sync {
+    fn kind(&self) -> DiskDriverKind;
+
+    /// Format of the disks this driver produces.
+    fn format(&self) -> DiskFormat;
+
+    /// File name of the container disk in a box home or snapshot dir.
+    fn container_disk_name(&self) -> &'static str;
+
+    /// File name of the guest rootfs disk in a box home or snapshot dir.
+    fn guest_disk_name(&self) -> &'static str;
+
+    /// Create a persistent writable disk at `dst` from the raw `base`,
+    /// at least `size` bytes large.
+    fn provision(&self, base: &Path, dst: &Path, size: u64) -> BoxliteResult<Disk>;
+
+    /// Size of the disk as seen by the guest.
+    fn virtual_size(&self, disk: &Path) -> BoxliteResult<u64>;
+
+    /// Preserve the current contents of `disk` at `frozen`; `disk` stays
+    /// writable.
+    fn freeze(&self, disk: &Path, frozen: &Path) -> BoxliteResult<()>;
+
+    /// Undo a successful [`freeze`](DiskDriver::freeze).
+    fn unfreeze(&self, disk: &Path, frozen: &Path) -> BoxliteResult<()>;
+
+    /// Create a writable disk at `dst` sharing blocks with `src` (restore and
+    /// COW clone).
+    fn branch(&self, src: &Path, dst: &Path, size: u64) -> BoxliteResult<()>;
+
+    /// Create an independent copy of `src` at `dst` (full clone).
+    fn copy(&self, src: &Path, dst: &Path) -> BoxliteResult<()>;
+
+    /// Write `src` as a standalone qcow2 at `dst` (export).
+    fn flatten(&self, src: &Path, dst: &Path) -> BoxliteResult<()>;
+}
+
+/// qcow2 overlays: COW happens in the image format.
+pub(crate) struct Qcow2Driver;
+
+impl DiskDriver for Qcow2Driver {
+    fn kind(&self) -> DiskDriverKind {
+        DiskDriverKind::Qcow2
+    }
+
+    fn format(&self) -> DiskFormat {
+        DiskFormat::Qcow2
+    }
+
+    fn container_disk_name(&self) -> &'static str {
+        filenames::CONTAINER_DISK
+    }
+
+    fn guest_disk_name(&self) -> &'static str {
+        filenames::GUEST_ROOTFS_DISK
+    }
+
+    fn provision(&self, base: &Path, dst: &Path, size: u64) -> BoxliteResult<Disk> {
+        let path = Qcow2Helper::new()
+            .create_cow_child_disk(base, BackingFormat::Raw, dst, size)?
+            .leak();
+        Ok(Disk::new(path, DiskFormat::Qcow2, true))
+    }
+
+    fn virtual_size(&self, disk: &Path) -> BoxliteResult<u64> {
+        Qcow2Helper::qcow2_virtual_size(disk)
+    }
+
+    fn freeze(&self, disk: &Path, frozen: &Path) -> BoxliteResult<()> {
+        let size = self.virtual_size(disk)?;
+        std::fs::rename(disk, frozen).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to move {} to {}: {}",
+                disk.display(),
+                frozen.display(),
+                e
+            ))
+        })?;
+        if let Err(e) = self.branch(frozen, disk, size) {
+            let _ = std::fs::rename(frozen, disk);
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    fn unfreeze(&self, disk: &Path, frozen: &Path) -> BoxliteResult<()> {
+        remove_disk(disk)?;
+        std::fs::rename(frozen, disk).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to move {} back to {}: {}",
+                frozen.display(),
+                disk.display(),
+                e
+            ))
+        })
+    }
+
+    fn branch(&self, src: &Path, dst: &Path, size: u64) -> BoxliteResult<()> {
+        Qcow2Helper::new()
+            .create_cow_child_disk(src, BackingFormat::Qcow2, dst, size)?
+            .leak();
+        Ok(())
+    }
+
+    fn copy(&self, src: &Path, dst: &Path) -> BoxliteResult<()> {
+        qemu_img::full_copy(src, dst)
+    }
+
+    fn flatten(&self, src: &Path, dst: &Path) -> BoxliteResult<()> {
+        qemu_img::convert(src, BackingFormat::Qcow2, dst)
+    }
+}
+
+/// Raw disks cloned with reflinks: COW happens in the filesystem.
+pub(crate) struct ReflinkDriver;
+
+impl DiskDriver for ReflinkDriver {
+    fn kind(&self) -> DiskDriverKind {
+        DiskDriverKind::Reflink
+    }
+
+    fn format(&self) -> DiskFormat {
+        // The base images are raw ext4, and so are their clones.
+        DiskFormat::Ext4
+    }
+
+    fn container_disk_name(&self) -> &'static str {
+        filenames::RAW_CONTAINER_DISK
+    }
+
+    fn guest_disk_name(&self) -> &'static str {
+        filenames::RAW_GUEST_ROOTFS_DISK
+    }
+
+    fn provision(&self, base: &Path, dst: &Path, size: u64) -> BoxliteResult<Disk> {
+        self.branch(base, dst, size)?;
+        Ok(Disk::new(dst.to_path_buf(), DiskFormat::Ext4, true))
+    }
+
+    fn virtual_size(&self, disk: &Path) -> BoxliteResult<u64> {
+        std::fs::metadata(disk)
+            .map(|m| m.len())
+            .map_err(|e| BoxliteError::Storage(format!("Failed to stat {}: {}", disk.display(), e)))
+    }
+
+    fn freeze(&self, disk: &Path, frozen: &Path) -> BoxliteResult<()> {
+        reflink(disk, frozen)
+    }
+
+    fn unfreeze(&self, _disk: &Path, frozen: &Path) -> BoxliteResult<()> {
+        remove_disk(frozen)
+    }
+
+    fn branch(&self, src: &Path, dst: &Path, size: u64) -> BoxliteResult<()> {
+        reflink(src, dst)?;
+        // Growing a raw disk is a sparse extension; the guest resizes the
+        // filesystem on first boot, as it does for larger qcow2 overlays.
+        let result = std::fs::OpenOptions::new()
+            .write(true)
+            .open(dst)
+            .and_then(|file| {
+                if file.metadata()?.len() < size {
+                    file.set_len(size)?;
+                }
+                Ok(())
+            });
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(dst);
+            return Err(BoxliteError::Storage(format!(
+                "Failed to resize {}: {}",
+                dst.display(),
+                e
+            )));
+        }
+        Ok(())
+    }
+
+    fn copy(&self, src: &Path, dst: &Path) -> BoxliteResult<()> {
+        std::fs::copy(src, dst).map(|_| ()).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to copy {} to {}: {}",
+                src.display(),
+                dst.display(),
+                e
+            ))
+        })
+    }
+
+    fn flatten(&self, src: &Path, dst: &Path) -> BoxliteResult<()> {
+        qemu_img::convert(src, BackingFormat::Raw, dst)
+    }
+}
+
+fn reflink(src: &Path, dst: &Path) -> BoxliteResult<()> {
+    reflink_copy::reflink(src, dst).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to reflink {} to {}: {}",
+            src.display(),
+            dst.display(),
+            e
+        ))
+    })
+}
+
+fn remove_disk(path: &Path) -> BoxliteResult<()> {
+    match std::fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(BoxliteError::Storage(format!(
+            "Failed to remove {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Check that files under `dir` can be reflinked.
+fn probe_reflink(dir: &Path) -> std::io::Result<()> {
+    use std::io::Write;
+
+    std::fs::create_dir_all(dir)?;
+    let probe_dir = tempfile::Builder::new()
+        .prefix(".reflink-probe")
+        .tempdir_in(dir)?;
+    let src = probe_dir.path().join("src");
+    std::fs::File::create(&src)?.write_all(b"boxlite")?;
+    reflink_copy::reflink(&src, probe_dir.path().join("dst"))
+}
+
+/// Whether `dir` lives on Btrfs or ZFS.
+#[cfg(target_os = "linux")]
+fn is_cow_filesystem(dir: &Path) -> bool {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(path) = std::ffi::CString::new(dir.as_os_str().as_bytes()) else {
+        return false;
+    };
+    let mut stat: libc::statfs = unsafe { std::mem::zeroed() };
+    // SAFETY: `path` is NUL-terminated and `stat` is a valid out pointer.
+    if unsafe { libc::statfs(path.as_ptr(), &mut stat) } != 0 {
+        return false;
+    }
+    #[allow(clippy::unnecessary_cast)]
+    let magic = stat.f_type as i64;
+    magic == BTRFS_SUPER_MAGIC || magic == ZFS_SUPER_MAGIC
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_cow_filesystem(_dir: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_disk_driver_kind_defaults_to_qcow2() {
+        assert_eq!(DiskDriverKind::default(), DiskDriverKind::Qcow2);
+        let kind: DiskDriverKind = serde_json::from_str("\"reflink\"").unwrap();
+        assert_eq!(kind, DiskDriverKind::Reflink);
+        assert_eq!(kind.driver().kind(), DiskDriverKind::Reflink);
+    }
+
+    #[test]
+    fn test_drivers_use_distinct_disk_names() {
+        let qcow2 = DiskDriverKind::Qcow2.driver();
+        let reflink = DiskDriverKind::Reflink.driver();
+        assert_ne!(qcow2.container_disk_name(), reflink.container_disk_name());
+        assert_ne!(qcow2.guest_disk_name(), reflink.guest_disk_name());
+    }
+
+    #[test]
+    fn test_resolve_qcow2_skips_probe() {
+        let dir = TempDir::new().unwrap();
+        let kind = DiskDriverKind::resolve(StorageDriver::Qcow2, dir.path()).unwrap();
+        assert_eq!(kind, DiskDriverKind::Qcow2);
+    }
+
+    #[test]
+    fn test_resolve_follows_reflink_support() {
+        let dir = TempDir::new().unwrap();
+        let supported = probe_reflink(dir.path()).is_ok();
+
+        let reflink = DiskDriverKind::resolve(StorageDriver::Reflink, dir.path());
+        assert_eq!(reflink.is_ok(), supported);
+        if !supported {
+            assert!(matches!(reflink, Err(BoxliteError::Unsupported(_))));
+        }
+
+        let auto = DiskDriverKind::resolve(StorageDriver::Auto, dir.path()).unwrap();
+        if !supported {
+            assert_eq!(auto, DiskDriverKind::Qcow2);
+        }
+        // The probe cleans up after itself.
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_reflink_branch_grows_and_freeze_keeps_contents() {
+        let dir = TempDir::new().unwrap();
+        if probe_reflink(dir.path()).is_err() {
+            return;
+        }
+        let driver = DiskDriverKind::Reflink.driver();
+        let base = dir.path().join("base");
+        std::fs::write(&base, vec![7u8; 4096]).unwrap();
+
+        let disk = dir.path().join(driver.container_disk_name());
+        driver.provision(&base, &disk, 8192).unwrap();
+        assert_eq!(driver.virtual_size(&disk).unwrap(), 8192);
+
+        let frozen = dir.path().join("frozen");
+        driver.freeze(&disk, &frozen).unwrap();
+        std::fs::write(&disk, b"changed").unwrap();
+        let frozen_bytes = std::fs::read(&frozen).unwrap();
+        assert_eq!(frozen_bytes.len(), 8192);
+        assert!(frozen_bytes[..4096].iter().all(|&b| b == 7));
+
+        driver.unfreeze(&disk, &frozen).unwrap();
+        assert!(!frozen.exists());
+        assert!(disk.exists());
+    }
+}
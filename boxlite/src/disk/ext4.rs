@@ -274,7 +274,32 @@ pub fn inject_file_into_ext4(
     })?;
 
     let commands = build_inject_commands(host_file_str, guest_path);
+    run_inject_commands(image_path, host_file, guest_path, &commands)
+}
+
+/// Replace a file inside an ext4 disk image using debugfs.
+///
+/// Like [`inject_file_into_ext4`], but first unlinks any existing file at
+/// `guest_path` (debugfs `write` refuses to overwrite).
+pub fn replace_file_in_ext4(
+    image_path: &Path,
+    host_file: &Path,
+    guest_path: &str,
+) -> BoxliteResult<()> {
+    let host_file_str = host_file.to_str().ok_or_else(|| {
+        BoxliteError::Storage(format!("Invalid host file path: {}", host_file.display()))
+    })?;
+
+    let commands = build_replace_commands(host_file_str, guest_path);
+    run_inject_commands(image_path, host_file, guest_path, &commands)
+}
 
+fn run_inject_commands(
+    image_path: &Path,
+    host_file: &Path,
+    guest_path: &str,
+    commands: &str,
+) -> BoxliteResult<()> {
     let debugfs = get_debugfs_path();
 
     let mut child = Command::new(&debugfs)
@@ -317,6 +342,18 @@ pub fn inject_file_into_ext4(
     Ok(())
 }
 
+/// Build debugfs commands for replacing a file in an ext4 image.
+///
+/// `rm` fails harmlessly when the file doesn't exist, so this also works
+/// on images that never had it.
+fn build_replace_commands(host_file_str: &str, guest_path: &str) -> String {
+    format!(
+        "rm /{}\n{}",
+        guest_path,
+        build_inject_commands(host_file_str, guest_path)
+    )
+}
+
 /// Build debugfs commands for injecting a file into an ext4 image.
 ///
 /// Creates parent directories, writes the file, and sets ownership/mode.
@@ -408,6 +445,18 @@ mod tests {
         assert!(cmds.contains("sif /file mode 0100555\n"));
     }
 
+    #[test]
+    fn test_build_replace_commands_removes_before_write() {
+        let cmds = build_replace_commands("/host/boxlite-guest", "boxlite/bin/boxlite-guest");
+
+        let rm = cmds.find("rm /boxlite/bin/boxlite-guest\n").unwrap();
+        let write = cmds
+            .find("write /host/boxlite-guest /boxlite/bin/boxlite-guest\n")
+            .unwrap();
+        assert!(rm < write);
+        assert!(cmds.contains("sif /boxlite/bin/boxlite-guest mode 0100555\n"));
+    }
+
     #[test]
     fn test_build_inject_commands_deeply_nested() {
         let cmds = build_inject_commands("/src/bin", "a/b/c/d/bin");
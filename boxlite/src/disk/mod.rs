@@ -5,13 +5,22 @@
 //! - `DiskFormat` - Disk format types (Ext4, Qcow2)
 //! - `create_ext4_from_dir` - Create ext4 filesystem from directory
 //! - `Qcow2Helper` - QCOW2 copy-on-write disk creation
+//! - `DiskDriver` - Per-box disk provisioning (qcow2 overlays or reflinks)
+//! - `CacheCap` - Size caps with LRU eviction for the disk caches
+//! - `DiskReader` - Userspace reads of a raw or qcow2 disk chain (`fuse` feature)
 
+pub(crate) mod cache;
 pub mod constants;
+pub(crate) mod driver;
 pub(crate) mod ext4;
 mod image;
 mod qcow2;
 pub(crate) mod qemu_img;
+#[cfg(feature = "fuse")]
+pub(crate) mod reader;
 
-pub use ext4::{create_ext4_from_dir, inject_file_into_ext4};
+pub use cache::{CacheStats, CacheUsage};
+pub use driver::DiskDriverKind;
+pub use ext4::{create_ext4_from_dir, inject_file_into_ext4, replace_file_in_ext4};
 pub use image::{Disk, DiskFormat};
 pub use qcow2::{BackingFormat, Qcow2Helper, read_backing_file_path};
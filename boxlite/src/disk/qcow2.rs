@@ -651,6 +651,22 @@ impl BackingFormat {
             BackingFormat::Qcow2 => "qcow2",
         }
     }
+
+    /// Detect the format of an existing image from its magic bytes.
+    pub fn detect(path: &Path) -> BoxliteResult<Self> {
+        use std::io::Read;
+
+        let mut file = std::fs::File::open(path).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to open {}: {}", path.display(), e))
+        })?;
+
+        let mut magic = [0u8; 4];
+        if file.read_exact(&mut magic).is_ok() && u32::from_be_bytes(magic) == 0x514649fb {
+            Ok(BackingFormat::Qcow2)
+        } else {
+            Ok(BackingFormat::Raw)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -745,4 +761,23 @@ mod tests {
         assert_eq!(BackingFormat::Raw.as_str(), "raw");
         assert_eq!(BackingFormat::Qcow2.as_str(), "qcow2");
     }
+
+    #[test]
+    fn test_backing_format_detect() {
+        let dir = TempDir::new().unwrap();
+        let qcow2_path = dir.path().join("disk.qcow2");
+        write_qcow2_with_backing(&qcow2_path, None);
+        let raw_path = dir.path().join("rootfs.ext4");
+        std::fs::write(&raw_path, [0u8; 1024]).unwrap();
+
+        assert_eq!(
+            BackingFormat::detect(&qcow2_path).unwrap(),
+            BackingFormat::Qcow2
+        );
+        assert_eq!(
+            BackingFormat::detect(&raw_path).unwrap(),
+            BackingFormat::Raw
+        );
+        assert!(BackingFormat::detect(&dir.path().join("missing")).is_err());
+    }
 }
@@ -9,6 +9,8 @@ use std::process::Command;
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
+use super::BackingFormat;
+
 /// Check if `qemu-img` is available on the system.
 pub fn is_available() -> bool {
     Command::new("qemu-img")
@@ -31,23 +33,71 @@ fn require_qemu_img() -> BoxliteResult<()> {
     Ok(())
 }
 
-/// Convert a QCOW2 disk image to a standalone file (flatten COW chain).
+/// Convert a disk image to a standalone QCOW2 file (flatten COW chain).
 ///
-/// This flattens a QCOW2 file with a backing chain into a single standalone
-/// file with no backing reference. The output format is QCOW2 by default.
+/// This flattens a QCOW2 file with a backing chain, or a raw image, into a
+/// single standalone file with no backing reference. The source format is
+/// given rather than probed, so guest-written data in a raw image is never
+/// interpreted as an image header.
 ///
-/// Equivalent to: `qemu-img convert -O qcow2 <src> <dst>`
-pub fn convert(src: &Path, dst: &Path) -> BoxliteResult<()> {
+/// Equivalent to: `qemu-img convert -f <src_format> -O qcow2 <src> <dst>`
+pub fn convert(src: &Path, src_format: BackingFormat, dst: &Path) -> BoxliteResult<()> {
     require_qemu_img()?;
 
     tracing::info!(
         src = %src.display(),
         dst = %dst.display(),
-        "Flattening QCOW2 disk image"
+        format = src_format.as_str(),
+        "Flattening disk image"
     );
 
     let output = Command::new("qemu-img")
-        .args(["convert", "-O", "qcow2"])
+        .args(["convert", "-f", src_format.as_str(), "-O", "qcow2"])
+        .arg(src)
+        .arg(dst)
+        .output()
+        .map_err(|e| BoxliteError::Storage(format!("Failed to run qemu-img convert: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(BoxliteError::Storage(format!(
+            "qemu-img convert failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rewrite a QCOW2 disk into a freshly allocated file, keeping its backing file.
+///
+/// Unallocated and zeroed clusters are not written, so space the guest has
+/// freed and discarded is dropped. When `backing` is set, only data not already
+/// provided by the backing chain is written and `dst` references the same
+/// backing file as `src`.
+///
+/// Equivalent to: `qemu-img convert -O qcow2 [-B <backing> -F <fmt>] <src> <dst>`
+pub fn compact(
+    src: &Path,
+    dst: &Path,
+    backing: Option<(&str, BackingFormat)>,
+) -> BoxliteResult<()> {
+    require_qemu_img()?;
+
+    tracing::info!(
+        src = %src.display(),
+        dst = %dst.display(),
+        backing = ?backing.map(|(path, _)| path),
+        "Compacting QCOW2 disk image"
+    );
+
+    let mut cmd = Command::new("qemu-img");
+    cmd.args(["convert", "-O", "qcow2"]);
+    if let Some((backing_path, backing_format)) = backing {
+        cmd.arg("-B")
+            .arg(backing_path)
+            .args(["-F", backing_format.as_str()]);
+    }
+    let output = cmd
         .arg(src)
         .arg(dst)
         .output()
@@ -63,12 +113,78 @@ pub fn convert(src: &Path, dst: &Path) -> BoxliteResult<()> {
     Ok(())
 }
 
+/// Bytes of guest data stored in a QCOW2 disk's own layer.
+///
+/// Sums the extents `qemu-img map` reports as data at depth 0, i.e. clusters
+/// the image itself holds rather than its backing chain. This is roughly what
+/// [`compact`] has to write. The image is opened with shared locks, so this
+/// also works while a box is running.
+///
+/// Equivalent to: `qemu-img map --output=json -U <disk>`
+pub fn data_bytes(disk: &Path) -> BoxliteResult<u64> {
+    require_qemu_img()?;
+
+    let output = Command::new("qemu-img")
+        .args(["map", "--output=json", "-U"])
+        .arg(disk)
+        .output()
+        .map_err(|e| BoxliteError::Storage(format!("Failed to run qemu-img map: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(BoxliteError::Storage(format!(
+            "qemu-img map failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    parse_map_data_bytes(&output.stdout)
+}
+
+#[derive(serde::Deserialize)]
+struct MapExtent {
+    length: u64,
+    depth: u32,
+    data: bool,
+}
+
+fn parse_map_data_bytes(json: &[u8]) -> BoxliteResult<u64> {
+    let extents: Vec<MapExtent> = serde_json::from_slice(json).map_err(|e| {
+        BoxliteError::Storage(format!("Failed to parse qemu-img map output: {}", e))
+    })?;
+    Ok(extents
+        .iter()
+        .filter(|extent| extent.depth == 0 && extent.data)
+        .map(|extent| extent.length)
+        .sum())
+}
+
 /// Create a full copy of a disk image (no COW, standalone).
 ///
 /// Used for clone and export operations. Produces a completely independent copy
 /// with no backing file references.
 ///
-/// Equivalent to: `qemu-img convert -O qcow2 <src> <dst>`
+/// Equivalent to: `qemu-img convert -f qcow2 -O qcow2 <src> <dst>`
 pub fn full_copy(src: &Path, dst: &Path) -> BoxliteResult<()> {
-    convert(src, dst)
+    convert(src, BackingFormat::Qcow2, dst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_map_counts_only_own_data() {
+        let json = br#"[
+            {"start": 0, "length": 65536, "depth": 0, "present": true, "zero": false, "data": true, "offset": 327680},
+            {"start": 65536, "length": 131072, "depth": 1, "present": true, "zero": false, "data": true},
+            {"start": 196608, "length": 65536, "depth": 0, "present": true, "zero": true, "data": false},
+            {"start": 262144, "length": 4096, "depth": 0, "present": true, "zero": false, "data": true, "offset": 393216}
+        ]"#;
+        assert_eq!(parse_map_data_bytes(json).unwrap(), 65536 + 4096);
+    }
+
+    #[test]
+    fn test_parse_map_rejects_garbage() {
+        assert!(parse_map_data_bytes(b"not json").is_err());
+    }
 }
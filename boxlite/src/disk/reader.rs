@@ -0,0 +1,344 @@
+//! Read-only random access to a box disk.
+//!
+//! [`DiskReader`] reads a raw image, or a qcow2 image together with its
+//! backing chain, in userspace: no qemu-img, no VM and no kernel mount.
+//! Unallocated qcow2 clusters fall through to the backing file and read as
+//! zeros past its end. Compressed clusters, encryption and external data
+//! files are not supported; box disks use none of them.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use parking_lot::Mutex;
+
+use super::qcow2::{BackingFormat, read_backing_file_path};
+
+/// Backing files followed before a chain is considered circular.
+const MAX_CHAIN_DEPTH: usize = 32;
+
+/// Host offset bits of an L1 or L2 entry.
+const OFFSET_MASK: u64 = 0x00ff_ffff_ffff_fe00;
+/// L2 entry flag: compressed cluster.
+const L2_COMPRESSED: u64 = 1 << 62;
+/// L2 entry flag: the cluster reads as zeros.
+const L2_ZERO: u64 = 1;
+/// Incompatible features that don't affect reading: dirty, compression type.
+const READABLE_INCOMPAT: u64 = (1 << 0) | (1 << 3);
+
+/// A raw or qcow2 disk image, opened for reading.
+pub(crate) struct DiskReader {
+    file: File,
+    path: PathBuf,
+    /// Virtual size in bytes.
+    size: u64,
+    /// Cluster map, for qcow2 images.
+    qcow2: Option<Qcow2Map>,
+}
+
+struct Qcow2Map {
+    cluster_bits: u32,
+    l1: Vec<u64>,
+    /// L2 tables by host offset. A box disk has a few dozen at most.
+    l2_cache: Mutex<HashMap<u64, Arc<[u64]>>>,
+    backing: Option<Box<DiskReader>>,
+}
+
+enum Cluster {
+    Data(u64),
+    Zero,
+    Unallocated,
+}
+
+impl DiskReader {
+    /// Open `path` and, for qcow2, its whole backing chain.
+    pub(crate) fn open(path: &Path) -> BoxliteResult<Self> {
+        Self::open_layer(path, 0)
+    }
+
+    /// Virtual size of the disk in bytes.
+    pub(crate) fn size(&self) -> u64 {
+        self.size
+    }
+
+    fn open_layer(path: &Path, depth: usize) -> BoxliteResult<Self> {
+        if depth > MAX_CHAIN_DEPTH {
+            return Err(BoxliteError::Storage(format!(
+                "Backing chain of {} is deeper than {} images",
+                path.display(),
+                MAX_CHAIN_DEPTH
+            )));
+        }
+        let file = File::open(path).map_err(|e| {
+            BoxliteError::Storage(format!("Failed to open {}: {}", path.display(), e))
+        })?;
+        let storage_err = |e: io::Error| {
+            BoxliteError::Storage(format!("Failed to read {}: {}", path.display(), e))
+        };
+
+        if BackingFormat::detect(path)? == BackingFormat::Raw {
+            let size = file.metadata().map_err(storage_err)?.len();
+            return Ok(Self {
+                file,
+                path: path.to_path_buf(),
+                size,
+                qcow2: None,
+            });
+        }
+
+        // v2 headers end at byte 72; v3 adds the feature bits and more
+        let mut header = [0u8; 104];
+        file.read_exact_at(&mut header[..72], 0)
+            .map_err(storage_err)?;
+        let version = u32::from_be_bytes(header[4..8].try_into().unwrap());
+        if version >= 3 {
+            file.read_exact_at(&mut header[72..], 72)
+                .map_err(storage_err)?;
+        }
+        let be32 = |at: usize| u32::from_be_bytes(header[at..at + 4].try_into().unwrap());
+        let be64 = |at: usize| u64::from_be_bytes(header[at..at + 8].try_into().unwrap());
+
+        let cluster_bits = be32(20);
+        let size = be64(24);
+        let crypt_method = be32(32);
+        let l1_size = be32(36) as usize;
+        let l1_offset = be64(40);
+        let incompatible = be64(72);
+
+        if !(9..=21).contains(&cluster_bits) {
+            return Err(BoxliteError::Storage(format!(
+                "Invalid qcow2 cluster size 2^{} in {}",
+                cluster_bits,
+                path.display()
+            )));
+        }
+        if crypt_method != 0 || incompatible & !READABLE_INCOMPAT != 0 {
+            return Err(BoxliteError::Unsupported(format!(
+                "{} uses qcow2 features that can't be read in userspace \
+                 (encryption {}, incompatible features 0x{:x})",
+                path.display(),
+                crypt_method,
+                incompatible
+            )));
+        }
+
+        let mut raw_l1 = vec![0u8; l1_size * 8];
+        file.read_exact_at(&mut raw_l1, l1_offset)
+            .map_err(storage_err)?;
+        let l1 = be_u64s(&raw_l1);
+
+        let backing = match read_backing_file_path(path)? {
+            Some(backing) => {
+                let backing = Path::new(&backing);
+                let backing = match path.parent() {
+                    Some(dir) if backing.is_relative() => dir.join(backing),
+                    _ => backing.to_path_buf(),
+                };
+                Some(Box::new(Self::open_layer(&backing, depth + 1)?))
+            }
+            None => None,
+        };
+
+        Ok(Self {
+            file,
+            path: path.to_path_buf(),
+            size,
+            qcow2: Some(Qcow2Map {
+                cluster_bits,
+                l1,
+                l2_cache: Mutex::new(HashMap::new()),
+                backing,
+            }),
+        })
+    }
+
+    /// Fill `buf` from virtual offset `offset`. Bytes past the end of the
+    /// disk read as zeros.
+    pub(crate) fn read_at(&self, mut offset: u64, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            if offset >= self.size {
+                buf.fill(0);
+                return Ok(());
+            }
+            let remaining = (self.size - offset).min(buf.len() as u64) as usize;
+            let n = match &self.qcow2 {
+                None => {
+                    self.file.read_exact_at(&mut buf[..remaining], offset)?;
+                    remaining
+                }
+                Some(map) => {
+                    let cluster_size = 1u64 << map.cluster_bits;
+                    let in_cluster = offset & (cluster_size - 1);
+                    let n = remaining.min((cluster_size - in_cluster) as usize);
+                    match map.lookup(&self.file, offset)? {
+                        Cluster::Data(host) => {
+                            self.file.read_exact_at(&mut buf[..n], host + in_cluster)?
+                        }
+                        Cluster::Zero => buf[..n].fill(0),
+                        Cluster::Unallocated => match &map.backing {
+                            Some(backing) => backing.read_at(offset, &mut buf[..n])?,
+                            None => buf[..n].fill(0),
+                        },
+                    }
+                    n
+                }
+            };
+            offset += n as u64;
+            buf = &mut std::mem::take(&mut buf)[n..];
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for DiskReader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiskReader")
+            .field("path", &self.path)
+            .field("size", &self.size)
+            .field("qcow2", &self.qcow2.is_some())
+            .finish()
+    }
+}
+
+impl Qcow2Map {
+    /// Where the cluster holding virtual offset `offset` is stored.
+    fn lookup(&self, file: &File, offset: u64) -> io::Result<Cluster> {
+        let l2_bits = self.cluster_bits - 3;
+        let cluster = offset >> self.cluster_bits;
+        let l1_index = (cluster >> l2_bits) as usize;
+        let l2_index = (cluster & ((1 << l2_bits) - 1)) as usize;
+
+        let l2_offset = match self.l1.get(l1_index) {
+            Some(entry) => entry & OFFSET_MASK,
+            None => 0,
+        };
+        if l2_offset == 0 {
+            return Ok(Cluster::Unallocated);
+        }
+
+        let entry = self.l2_table(file, l2_offset)?[l2_index];
+        if entry & L2_COMPRESSED != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "compressed qcow2 clusters are not supported",
+            ));
+        }
+        if entry & L2_ZERO != 0 {
+            return Ok(Cluster::Zero);
+        }
+        Ok(match entry & OFFSET_MASK {
+            0 => Cluster::Unallocated,
+            host => Cluster::Data(host),
+        })
+    }
+
+    fn l2_table(&self, file: &File, l2_offset: u64) -> io::Result<Arc<[u64]>> {
+        if let Some(table) = self.l2_cache.lock().get(&l2_offset) {
+            return Ok(table.clone());
+        }
+        let mut raw = vec![0u8; 1 << self.cluster_bits];
+        file.read_exact_at(&mut raw, l2_offset)?;
+        let table: Arc<[u64]> = be_u64s(&raw).into();
+        self.l2_cache.lock().insert(l2_offset, table.clone());
+        Ok(table)
+    }
+}
+
+fn be_u64s(raw: &[u8]) -> Vec<u64> {
+    raw.chunks_exact(8)
+        .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CLUSTER: u64 = 1 << 16;
+
+    /// A qcow2 of three clusters over `backing`: cluster 0 holds 0xAB,
+    /// cluster 1 is unallocated and cluster 2 is a zero cluster.
+    fn write_overlay(path: &Path, backing: &str) {
+        let mut buf = vec![0u8; 4 * CLUSTER as usize];
+        buf[0..4].copy_from_slice(&0x514649fbu32.to_be_bytes());
+        buf[4..8].copy_from_slice(&3u32.to_be_bytes());
+        buf[8..16].copy_from_slice(&512u64.to_be_bytes());
+        buf[16..20].copy_from_slice(&(backing.len() as u32).to_be_bytes());
+        buf[20..24].copy_from_slice(&16u32.to_be_bytes());
+        buf[24..32].copy_from_slice(&(3 * CLUSTER).to_be_bytes());
+        buf[36..40].copy_from_slice(&1u32.to_be_bytes());
+        buf[40..48].copy_from_slice(&CLUSTER.to_be_bytes());
+        buf[100..104].copy_from_slice(&104u32.to_be_bytes());
+        buf[512..512 + backing.len()].copy_from_slice(backing.as_bytes());
+
+        // L1 at cluster 1, L2 at cluster 2, data at cluster 3
+        let l1 = CLUSTER as usize;
+        buf[l1..l1 + 8].copy_from_slice(&((2 * CLUSTER) | (1 << 63)).to_be_bytes());
+        let l2 = 2 * CLUSTER as usize;
+        buf[l2..l2 + 8].copy_from_slice(&((3 * CLUSTER) | (1 << 63)).to_be_bytes());
+        buf[l2 + 16..l2 + 24].copy_from_slice(&L2_ZERO.to_be_bytes());
+        buf[3 * CLUSTER as usize..].fill(0xAB);
+
+        std::fs::write(path, buf).unwrap();
+    }
+
+    #[test]
+    fn test_raw_reads_and_zero_fills_past_end() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("disk.raw");
+        std::fs::write(&path, b"hello world").unwrap();
+
+        let reader = DiskReader::open(&path).unwrap();
+        assert_eq!(reader.size(), 11);
+        let mut buf = [0xFFu8; 8];
+        reader.read_at(6, &mut buf).unwrap();
+        assert_eq!(&buf, b"world\0\0\0");
+    }
+
+    #[test]
+    fn test_qcow2_reads_through_backing_chain() {
+        let dir = TempDir::new().unwrap();
+        // The backing file covers cluster 0 and half of cluster 1
+        std::fs::write(
+            dir.path().join("base.raw"),
+            vec![0xCD; (CLUSTER + CLUSTER / 2) as usize],
+        )
+        .unwrap();
+        let overlay = dir.path().join("disk.qcow2");
+        write_overlay(&overlay, "base.raw");
+
+        let reader = DiskReader::open(&overlay).unwrap();
+        assert_eq!(reader.size(), 3 * CLUSTER);
+
+        // Spans the allocated cluster 0 and the backing data of cluster 1
+        let mut buf = vec![0u8; 8];
+        reader.read_at(CLUSTER - 4, &mut buf).unwrap();
+        assert_eq!(buf, [0xAB, 0xAB, 0xAB, 0xAB, 0xCD, 0xCD, 0xCD, 0xCD]);
+
+        // Past the end of the backing file
+        reader.read_at(CLUSTER + CLUSTER / 2, &mut buf).unwrap();
+        assert_eq!(buf, [0; 8]);
+
+        // Zero cluster
+        let mut buf = vec![0xFFu8; CLUSTER as usize];
+        reader.read_at(2 * CLUSTER, &mut buf).unwrap();
+        assert!(buf.iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_qcow2_missing_backing_file_fails() {
+        let dir = TempDir::new().unwrap();
+        let overlay = dir.path().join("disk.qcow2");
+        write_overlay(&overlay, "gone.raw");
+
+        assert!(matches!(
+            DiskReader::open(&overlay),
+            Err(BoxliteError::Storage(_))
+        ));
+    }
+}
@@ -0,0 +1,557 @@
+//! Read-only FUSE view of an ext4 disk image.
+//!
+//! The image is read through [`DiskReader`] and parsed with `ext4-view`,
+//! so nothing is mounted by the kernel except the FUSE filesystem itself.
+//! Inode numbers are assigned as paths are looked up. The mount is
+//! read-only, and every request that would modify the filesystem also
+//! fails with `EROFS` here.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use ext4_view::{Ext4, Ext4Error, Metadata};
+use fuser::{
+    FileAttr, FileType, Filesystem, MountOption, ReplyAttr, ReplyCreate, ReplyData, ReplyDirectory,
+    ReplyEmpty, ReplyEntry, ReplyOpen, ReplyStatfs, ReplyWrite, Request, Session, SessionUnmounter,
+    TimeOrNow,
+};
+
+use crate::disk::reader::DiskReader;
+
+/// Attribute and entry cache timeout; the image never changes while mounted.
+const TTL: Duration = Duration::from_secs(60);
+const BLOCK_SIZE: u32 = 4096;
+
+struct DiskImage(DiskReader);
+
+impl ext4_view::Ext4Read for DiskImage {
+    fn read(
+        &mut self,
+        start_byte: u64,
+        dst: &mut [u8],
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.0.read_at(start_byte, dst).map_err(Into::into)
+    }
+}
+
+/// A mounted image. Requests are served on a thread of its own.
+pub(crate) struct FuseMount {
+    unmounter: SessionUnmounter,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl FuseMount {
+    /// Unmount and wait for the serving thread to exit.
+    pub(crate) fn unmount(&mut self) -> std::io::Result<()> {
+        self.unmounter.unmount()?;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        Ok(())
+    }
+}
+
+/// Mount the ext4 filesystem on `disk` read-only at `mountpoint`.
+///
+/// Blocks until the filesystem is mounted. Only the calling user can
+/// access the mount; file permissions inside it are not enforced.
+pub(crate) fn mount(disk: &Path, mountpoint: &Path, fsname: String) -> BoxliteResult<FuseMount> {
+    let disk = disk.to_path_buf();
+    let mountpoint = mountpoint.to_path_buf();
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+    // ext4-view handles are not Send, so the filesystem is built and
+    // served on the same thread
+    let thread = std::thread::Builder::new()
+        .name("boxlite-fuse".to_string())
+        .spawn(move || {
+            let session = DiskReader::open(&disk)
+                .and_then(|reader| {
+                    Ext4::load(Box::new(DiskImage(reader))).map_err(|e| {
+                        BoxliteError::Storage(format!(
+                            "{} does not hold a readable ext4 filesystem: {}",
+                            disk.display(),
+                            e
+                        ))
+                    })
+                })
+                .and_then(|fs| {
+                    let time = std::fs::metadata(&disk)
+                        .and_then(|m| m.modified())
+                        .unwrap_or(SystemTime::UNIX_EPOCH);
+                    let options = [
+                        MountOption::RO,
+                        MountOption::NoDev,
+                        MountOption::NoSuid,
+                        MountOption::FSName(fsname),
+                        MountOption::Subtype("boxlite".to_string()),
+                    ];
+                    Session::new(Ext4Fuse::new(fs, time), &mountpoint, &options).map_err(|e| {
+                        BoxliteError::Storage(format!(
+                            "Failed to mount {}: {}",
+                            mountpoint.display(),
+                            e
+                        ))
+                    })
+                });
+            let mut session = match session {
+                Ok(mut session) => {
+                    let _ = ready_tx.send(Ok(session.unmount_callable()));
+                    session
+                }
+                Err(e) => {
+                    let _ = ready_tx.send(Err(e));
+                    return;
+                }
+            };
+            if let Err(e) = session.run() {
+                tracing::warn!(mountpoint = %mountpoint.display(), error = %e, "FUSE session failed");
+            }
+        })
+        .map_err(|e| BoxliteError::Internal(format!("Failed to spawn FUSE thread: {}", e)))?;
+
+    match ready_rx.recv() {
+        Ok(Ok(unmounter)) => Ok(FuseMount {
+            unmounter,
+            thread: Some(thread),
+        }),
+        Ok(Err(e)) => {
+            let _ = thread.join();
+            Err(e)
+        }
+        Err(_) => Err(BoxliteError::Internal(
+            "FUSE thread exited before mounting".to_string(),
+        )),
+    }
+}
+
+struct Ext4Fuse {
+    fs: Ext4,
+    /// Path of each inode; inode `n` is at index `n - 1`, the root is 1.
+    paths: Vec<Vec<u8>>,
+    inodes: HashMap<Vec<u8>, u64>,
+    files: HashMap<u64, ext4_view::File>,
+    next_fh: u64,
+    /// Every file's timestamps: when the disk was last written.
+    time: SystemTime,
+}
+
+impl Ext4Fuse {
+    fn new(fs: Ext4, time: SystemTime) -> Self {
+        Self {
+            fs,
+            paths: vec![b"/".to_vec()],
+            inodes: HashMap::from([(b"/".to_vec(), fuser::FUSE_ROOT_ID)]),
+            files: HashMap::new(),
+            next_fh: 1,
+            time,
+        }
+    }
+
+    fn path(&self, ino: u64) -> Result<&[u8], i32> {
+        ino.checked_sub(1)
+            .and_then(|index| self.paths.get(index as usize))
+            .map(Vec::as_slice)
+            .ok_or(libc::ENOENT)
+    }
+
+    fn child_path(&self, parent: u64, name: &[u8]) -> Result<Vec<u8>, i32> {
+        let mut path = self.path(parent)?.to_vec();
+        if path.len() > 1 {
+            path.push(b'/');
+        }
+        path.extend_from_slice(name);
+        Ok(path)
+    }
+
+    fn parent_path(path: &[u8]) -> &[u8] {
+        match path.iter().rposition(|&b| b == b'/') {
+            Some(0) | None => &b"/"[..],
+            Some(slash) => &path[..slash],
+        }
+    }
+
+    fn inode(&mut self, path: Vec<u8>) -> u64 {
+        if let Some(&ino) = self.inodes.get(&path) {
+            return ino;
+        }
+        self.paths.push(path.clone());
+        let ino = self.paths.len() as u64;
+        self.inodes.insert(path, ino);
+        ino
+    }
+
+    fn metadata(&self, path: &[u8]) -> Result<Metadata, i32> {
+        let path = ext4_view::Path::try_from(path).map_err(|_| libc::EINVAL)?;
+        self.fs.symlink_metadata(path).map_err(errno)
+    }
+
+    fn attr(&self, ino: u64, metadata: &Metadata) -> FileAttr {
+        let kind = file_type(metadata.file_type());
+        let size = metadata.len();
+        FileAttr {
+            ino,
+            size,
+            blocks: size.div_ceil(512),
+            atime: self.time,
+            mtime: self.time,
+            ctime: self.time,
+            crtime: self.time,
+            kind,
+            perm: metadata.mode() & 0o7777,
+            nlink: if kind == FileType::Directory { 2 } else { 1 },
+            uid: metadata.uid(),
+            gid: metadata.gid(),
+            rdev: 0,
+            blksize: BLOCK_SIZE,
+            flags: 0,
+        }
+    }
+
+    fn lookup_path(&mut self, path: Vec<u8>) -> Result<FileAttr, i32> {
+        let metadata = self.metadata(&path)?;
+        let ino = self.inode(path);
+        Ok(self.attr(ino, &metadata))
+    }
+
+    /// Entries of a directory, `.` and `..` first.
+    fn entries(&mut self, ino: u64) -> Result<Vec<(u64, FileType, Vec<u8>)>, i32> {
+        let path = self.path(ino)?.to_vec();
+        let parent = self.inode(Self::parent_path(&path).to_vec());
+        let mut entries = vec![
+            (ino, FileType::Directory, b".".to_vec()),
+            (parent, FileType::Directory, b"..".to_vec()),
+        ];
+
+        let dir = ext4_view::Path::try_from(path.as_slice()).map_err(|_| libc::EINVAL)?;
+        let mut children = Vec::new();
+        for entry in self.fs.read_dir(dir).map_err(errno)? {
+            let entry = entry.map_err(errno)?;
+            let name = entry.file_name().as_ref().to_vec();
+            if name == b"." || name == b".." {
+                continue;
+            }
+            let kind = file_type(entry.file_type().map_err(errno)?);
+            children.push((name, kind));
+        }
+        for (name, kind) in children {
+            let child = self.child_path(ino, &name)?;
+            entries.push((self.inode(child), kind, name));
+        }
+        Ok(entries)
+    }
+}
+
+impl Filesystem for Ext4Fuse {
+    fn lookup(&mut self, _req: &Request<'_>, parent: u64, name: &OsStr, reply: ReplyEntry) {
+        match self
+            .child_path(parent, name.as_bytes())
+            .and_then(|path| self.lookup_path(path))
+        {
+            Ok(attr) => reply.entry(&TTL, &attr, 0),
+            Err(e) => reply.error(e),
+        }
+    }
+
+    fn getattr(&mut self, _req: &Request<'_>, ino: u64, _fh: Option<u64>, reply: ReplyAttr) {
+        match self
+            .path(ino)
+            .and_then(|path| self.metadata(path))
+            .map(|metadata| self.attr(ino, &metadata))
+        {
+            Ok(attr) => reply.attr(&TTL, &attr),
+            Err(e) => reply.error(e),
+        }
+    }
+
+    fn readlink(&mut self, _req: &Request<'_>, ino: u64, reply: ReplyData) {
+        let target = self.path(ino).and_then(|path| {
+            let path = ext4_view::Path::try_from(path).map_err(|_| libc::EINVAL)?;
+            self.fs.read_link(path).map_err(errno)
+        });
+        match target {
+            Ok(target) => reply.data(AsRef::<[u8]>::as_ref(&target)),
+            Err(e) => reply.error(e),
+        }
+    }
+
+    fn open(&mut self, _req: &Request<'_>, ino: u64, flags: i32, reply: ReplyOpen) {
+        if flags & libc::O_ACCMODE != libc::O_RDONLY || flags & libc::O_TRUNC != 0 {
+            return reply.error(libc::EROFS);
+        }
+        let file = self.path(ino).and_then(|path| {
+            let path = ext4_view::Path::try_from(path).map_err(|_| libc::EINVAL)?;
+            self.fs.open(path).map_err(errno)
+        });
+        match file {
+            Ok(file) => {
+                let fh = self.next_fh;
+                self.next_fh += 1;
+                self.files.insert(fh, file);
+                reply.opened(fh, 0);
+            }
+            Err(e) => reply.error(e),
+        }
+    }
+
+    fn read(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        fh: u64,
+        offset: i64,
+        size: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyData,
+    ) {
+        let Some(file) = self.files.get_mut(&fh) else {
+            return reply.error(libc::EBADF);
+        };
+        let mut buf = vec![0u8; size as usize];
+        let mut filled = 0;
+        let result = file.seek_to(offset.max(0) as u64).and_then(|()| {
+            while filled < buf.len() {
+                match file.read_bytes(&mut buf[filled..])? {
+                    0 => break,
+                    n => filled += n,
+                }
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => reply.data(&buf[..filled]),
+            Err(e) => reply.error(errno(e)),
+        }
+    }
+
+    fn release(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        fh: u64,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        _flush: bool,
+        reply: ReplyEmpty,
+    ) {
+        self.files.remove(&fh);
+        reply.ok();
+    }
+
+    fn readdir(
+        &mut self,
+        _req: &Request<'_>,
+        ino: u64,
+        _fh: u64,
+        offset: i64,
+        mut reply: ReplyDirectory,
+    ) {
+        let entries = match self.entries(ino) {
+            Ok(entries) => entries,
+            Err(e) => return reply.error(e),
+        };
+        for (i, (ino, kind, name)) in entries.iter().enumerate().skip(offset.max(0) as usize) {
+            if reply.add(*ino, (i + 1) as i64, *kind, OsStr::from_bytes(name)) {
+                break;
+            }
+        }
+        reply.ok();
+    }
+
+    fn statfs(&mut self, _req: &Request<'_>, _ino: u64, reply: ReplyStatfs) {
+        reply.statfs(
+            0,
+            0,
+            0,
+            self.paths.len() as u64,
+            0,
+            BLOCK_SIZE,
+            255,
+            BLOCK_SIZE,
+        );
+    }
+
+    // Everything below would modify the filesystem
+
+    fn setattr(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        _mode: Option<u32>,
+        _uid: Option<u32>,
+        _gid: Option<u32>,
+        _size: Option<u64>,
+        _atime: Option<TimeOrNow>,
+        _mtime: Option<TimeOrNow>,
+        _ctime: Option<SystemTime>,
+        _fh: Option<u64>,
+        _crtime: Option<SystemTime>,
+        _chgtime: Option<SystemTime>,
+        _bkuptime: Option<SystemTime>,
+        _flags: Option<u32>,
+        reply: ReplyAttr,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn mknod(
+        &mut self,
+        _req: &Request<'_>,
+        _parent: u64,
+        _name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _rdev: u32,
+        reply: ReplyEntry,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn mkdir(
+        &mut self,
+        _req: &Request<'_>,
+        _parent: u64,
+        _name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        reply: ReplyEntry,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn unlink(&mut self, _req: &Request<'_>, _parent: u64, _name: &OsStr, reply: ReplyEmpty) {
+        reply.error(libc::EROFS);
+    }
+
+    fn rmdir(&mut self, _req: &Request<'_>, _parent: u64, _name: &OsStr, reply: ReplyEmpty) {
+        reply.error(libc::EROFS);
+    }
+
+    fn symlink(
+        &mut self,
+        _req: &Request<'_>,
+        _parent: u64,
+        _link_name: &OsStr,
+        _target: &Path,
+        reply: ReplyEntry,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn rename(
+        &mut self,
+        _req: &Request<'_>,
+        _parent: u64,
+        _name: &OsStr,
+        _newparent: u64,
+        _newname: &OsStr,
+        _flags: u32,
+        reply: ReplyEmpty,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn link(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        _newparent: u64,
+        _newname: &OsStr,
+        reply: ReplyEntry,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn write(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        _fh: u64,
+        _offset: i64,
+        _data: &[u8],
+        _write_flags: u32,
+        _flags: i32,
+        _lock_owner: Option<u64>,
+        reply: ReplyWrite,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn create(
+        &mut self,
+        _req: &Request<'_>,
+        _parent: u64,
+        _name: &OsStr,
+        _mode: u32,
+        _umask: u32,
+        _flags: i32,
+        reply: ReplyCreate,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn setxattr(
+        &mut self,
+        _req: &Request<'_>,
+        _ino: u64,
+        _name: &OsStr,
+        _value: &[u8],
+        _flags: i32,
+        _position: u32,
+        reply: ReplyEmpty,
+    ) {
+        reply.error(libc::EROFS);
+    }
+
+    fn removexattr(&mut self, _req: &Request<'_>, _ino: u64, _name: &OsStr, reply: ReplyEmpty) {
+        reply.error(libc::EROFS);
+    }
+}
+
+fn file_type(kind: ext4_view::FileType) -> FileType {
+    if kind.is_dir() {
+        FileType::Directory
+    } else if kind.is_symlink() {
+        FileType::Symlink
+    } else if kind.is_block_dev() {
+        FileType::BlockDevice
+    } else if kind.is_char_dev() {
+        FileType::CharDevice
+    } else if kind.is_fifo() {
+        FileType::NamedPipe
+    } else if kind.is_socket() {
+        FileType::Socket
+    } else {
+        FileType::RegularFile
+    }
+}
+
+fn errno(e: Ext4Error) -> i32 {
+    match e {
+        Ext4Error::NotFound => libc::ENOENT,
+        Ext4Error::NotADirectory => libc::ENOTDIR,
+        Ext4Error::IsADirectory => libc::EISDIR,
+        e => {
+            tracing::debug!(error = %e, "ext4 read failed");
+            libc::EIO
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parent_path() {
+        assert_eq!(Ext4Fuse::parent_path(b"/"), b"/");
+        assert_eq!(Ext4Fuse::parent_path(b"/etc"), b"/");
+        assert_eq!(Ext4Fuse::parent_path(b"/etc/apk/world"), b"/etc/apk");
+    }
+}
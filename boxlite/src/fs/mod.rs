@@ -1,6 +1,8 @@
 //! Filesystem utilities for host-side operations.
 
 mod bind_mount;
+#[cfg(feature = "fuse")]
+pub(crate) mod ext4_fuse;
 
 #[cfg(target_os = "linux")]
 pub use bind_mount::{BindMountConfig, BindMountHandle, create_bind_mount};
@@ -1,5 +1,6 @@
 //! Streaming OCI tar layer applier (containerd-style).
 
+use boxlite_shared::archive::{ArchiveGuard, ArchiveLimits, resolve_in_root};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use filetime::{FileTime, set_file_times, set_symlink_file_times};
 use flate2::read::GzDecoder;
@@ -193,6 +194,12 @@ struct DeferredHardlink {
 }
 
 /// Apply an OCI layer tar stream into `dest`, handling whiteouts inline.
+///
+/// Entry paths are sanitized rather than refused: they are normalized, and
+/// resolved inside `dest` so symlinks from this or earlier layers cannot
+/// lead writes out of it. Device nodes are created as the image describes
+/// them. The size and entry caps of `ArchiveLimits` apply as for any
+/// archive.
 pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
     fs::create_dir_all(dest).map_err(|e| {
         BoxliteError::Storage(format!(
@@ -208,6 +215,7 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
     let mut total_size = 0u64;
     let mut deferred_dirs: Vec<DirMeta> = Vec::new();
     let mut deferred_hardlinks: Vec<DeferredHardlink> = Vec::new();
+    let mut guard = ArchiveGuard::new(ArchiveLimits::default());
 
     for entry_result in archive
         .entries()
@@ -232,7 +240,11 @@ pub fn apply_oci_layer<R: Read>(reader: R, dest: &Path) -> BoxliteResult<u64> {
             continue;
         }
 
-        let full_path = dest.join(&normalized);
+        guard.count(&raw_path, entry.header().size().unwrap_or(0))?;
+        let full_path = match (normalized.parent(), normalized.file_name()) {
+            (Some(parent), Some(name)) => resolve_in_root(dest, parent)?.join(name),
+            _ => dest.join(&normalized),
+        };
         let entry_type = entry.header().entry_type();
         let mode = entry.header().mode().unwrap_or(0o755);
         let uid = entry.header().uid().unwrap_or(0);
@@ -815,11 +827,10 @@ fn resolve_hardlink_target(root: &Path, linkname: &Path) -> BoxliteResult<PathBu
         ))
     })?;
 
-    let target = root.join(cleaned);
-    if target.starts_with(root) {
-        Ok(target)
-    } else {
-        Ok(root.to_path_buf())
+    // Symlinks on the way must not lead outside the root either
+    match (cleaned.parent(), cleaned.file_name()) {
+        (Some(parent), Some(name)) => Ok(resolve_in_root(root, parent)?.join(name)),
+        _ => Ok(root.to_path_buf()),
     }
 }
 
@@ -1607,4 +1618,36 @@ mod tests {
         let target = std::fs::read_link(&link_path).unwrap();
         assert_eq!(target, PathBuf::from("target.txt"));
     }
+
+    #[test]
+    fn test_apply_oci_layer_writes_through_symlink_stay_in_root() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let outside = temp_dir.path().join("outside");
+        std::fs::create_dir(&outside).unwrap();
+        let dest_dir = temp_dir.path().join("extracted");
+        std::fs::create_dir_all(dest_dir.join(outside.strip_prefix("/").unwrap())).unwrap();
+
+        // An absolute symlink to a host directory, then a file below it
+        let entries = vec![
+            TestEntry {
+                path: "evil".to_string(),
+                entry_type: TestEntryType::Symlink {
+                    target: outside.display().to_string(),
+                },
+            },
+            TestEntry {
+                path: "evil/planted".to_string(),
+                entry_type: TestEntryType::File {
+                    content: b"x".to_vec(),
+                },
+            },
+        ];
+        apply_oci_layer(create_test_tar(entries).as_slice(), &dest_dir).unwrap();
+
+        assert!(!outside.join("planted").exists());
+        let inside = dest_dir
+            .join(outside.strip_prefix("/").unwrap())
+            .join("planted");
+        assert_eq!(std::fs::read(inside).unwrap(), b"x");
+    }
 }
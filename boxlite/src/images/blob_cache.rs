@@ -0,0 +1,149 @@
+//! Layer cache shared between machines (`BoxliteOptions::blob_cache_dir`).
+//!
+//! Blobs live at `{dir}/sha256/{hex}`. Readers never trust an entry: the
+//! copy they take is verified against its digest before use, so a truncated
+//! or tampered entry only costs a download. Writers copy into a temp file of
+//! their own, flush it and rename it into place, so readers on any machine
+//! see the whole blob or nothing. A rename within one directory is atomic on
+//! NFS too, which is all the cache relies on.
+
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Directory of blobs shared between machines.
+#[derive(Debug, Clone)]
+pub(crate) struct SharedBlobCache {
+    dir: PathBuf,
+}
+
+impl SharedBlobCache {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Path of the entry for `digest`, or None for anything but a sha256
+    /// digest, so a digest can never name a path outside the cache.
+    fn entry_path(&self, digest: &str) -> Option<PathBuf> {
+        let hex = digest.strip_prefix("sha256:")?;
+        if hex.len() != 64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        Some(self.dir.join("sha256").join(hex))
+    }
+
+    /// Open the cached blob for `digest`, or None if it is not cached.
+    ///
+    /// The content is unverified; callers check it against the digest.
+    pub(crate) async fn open(&self, digest: &str) -> BoxliteResult<Option<tokio::fs::File>> {
+        let Some(path) = self.entry_path(digest) else {
+            return Ok(None);
+        };
+        match tokio::fs::File::open(&path).await {
+            Ok(file) => Ok(Some(file)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(BoxliteError::Storage(format!(
+                "Failed to open cached blob {}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Store a copy of `src`, already verified against `digest`, replacing
+    /// any entry for it.
+    pub(crate) async fn publish(&self, digest: &str, src: &Path) -> BoxliteResult<()> {
+        let Some(path) = self.entry_path(digest) else {
+            return Ok(());
+        };
+        let dir = path.parent().expect("cache entries live in a directory");
+        tokio::fs::create_dir_all(dir).await.map_err(|e| {
+            BoxliteError::Storage(format!("Failed to create {}: {}", dir.display(), e))
+        })?;
+
+        // Unique across machines sharing the directory
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp = dir.join(format!(".{}.{}.tmp", name, ulid::Ulid::new()));
+        let result = copy_durably(src, &temp, &path).await;
+        if result.is_err() {
+            let _ = tokio::fs::remove_file(&temp).await;
+        }
+        result
+    }
+}
+
+/// Copy `src` to `temp`, flush it to stable storage, then rename it to `dest`.
+async fn copy_durably(src: &Path, temp: &Path, dest: &Path) -> BoxliteResult<()> {
+    let write_error = |e: std::io::Error| {
+        BoxliteError::Storage(format!("Failed to write {}: {}", temp.display(), e))
+    };
+    tokio::fs::copy(src, temp).await.map_err(write_error)?;
+    tokio::fs::File::open(temp)
+        .await
+        .map_err(write_error)?
+        .sync_all()
+        .await
+        .map_err(write_error)?;
+    tokio::fs::rename(temp, dest).await.map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to move {} to {}: {}",
+            temp.display(),
+            dest.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncReadExt;
+
+    const DIGEST: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    async fn read(cache: &SharedBlobCache, digest: &str) -> Option<Vec<u8>> {
+        let mut file = cache.open(digest).await.unwrap()?;
+        let mut data = Vec::new();
+        file.read_to_end(&mut data).await.unwrap();
+        Some(data)
+    }
+
+    #[tokio::test]
+    async fn test_publish_then_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SharedBlobCache::new(dir.path().join("cache"));
+        assert_eq!(read(&cache, DIGEST).await, None);
+
+        let src = dir.path().join("layer");
+        std::fs::write(&src, b"first").unwrap();
+        cache.publish(DIGEST, &src).await.unwrap();
+        assert_eq!(read(&cache, DIGEST).await.as_deref(), Some(&b"first"[..]));
+
+        // A later publish replaces the entry, leaving no temp files behind
+        std::fs::write(&src, b"second").unwrap();
+        cache.publish(DIGEST, &src).await.unwrap();
+        assert_eq!(read(&cache, DIGEST).await.as_deref(), Some(&b"second"[..]));
+        let entries = std::fs::read_dir(dir.path().join("cache/sha256")).unwrap();
+        assert_eq!(entries.count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_digests_cannot_leave_the_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = SharedBlobCache::new(dir.path().join("cache"));
+        let src = dir.path().join("layer");
+        std::fs::write(&src, b"data").unwrap();
+
+        for digest in [
+            "sha256:../../escape",
+            "sha512:abcd",
+            "sha256:abc",
+            DIGEST.trim_end_matches('5'),
+        ] {
+            assert!(cache.entry_path(digest).is_none(), "{digest}");
+            cache.publish(digest, &src).await.unwrap();
+            assert_eq!(read(&cache, digest).await, None);
+        }
+        assert!(!dir.path().join("cache").exists());
+    }
+}
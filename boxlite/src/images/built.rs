@@ -0,0 +1,428 @@
+//! Images built by `BoxliteRuntime::build_image`.
+//!
+//! A built image is one flattened, uncompressed layer plus a config. Each
+//! one is kept as an OCI layout bundle that `ImageManager::load_from_local`
+//! reads, named by its manifest digest:
+//!
+//! ```text
+//! {images}/built/
+//!   blobs/sha256/{hex}       layers, configs and manifests, by digest
+//!   bundles/{manifest hex}/  oci-layout, index.json and hard links into blobs/
+//!   refs.json                tags and build cache entries -> manifest digest
+//! ```
+//!
+//! Bundles share blobs, so an image that only changes the config of another
+//! one costs a config and a manifest.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use oci_spec::image::ImageConfiguration;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::util::checksum;
+
+const LAYER_MEDIA_TYPE: &str = "application/vnd.oci.image.layer.v1.tar";
+const CONFIG_MEDIA_TYPE: &str = "application/vnd.oci.image.config.v1+json";
+const MANIFEST_MEDIA_TYPE: &str = "application/vnd.oci.image.manifest.v1+json";
+const REFS_FILE: &str = "refs.json";
+
+/// Tag and build cache entries.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Refs {
+    /// Normalized tag -> manifest digest.
+    #[serde(default)]
+    tags: BTreeMap<String, String>,
+    /// Build step key -> manifest digest of the image after the step.
+    #[serde(default)]
+    steps: BTreeMap<String, String>,
+}
+
+/// A built image read back from the store.
+#[derive(Debug, Clone)]
+pub(crate) struct BuiltManifest {
+    pub(crate) manifest_digest: String,
+    pub(crate) layer_digest: String,
+}
+
+/// Store of built images. Cheap to clone; clones share the refs lock.
+#[derive(Debug, Clone)]
+pub(crate) struct BuiltImageStore {
+    root: PathBuf,
+    /// Serializes read-modify-write of `refs.json` within the process.
+    refs_lock: Arc<Mutex<()>>,
+}
+
+impl BuiltImageStore {
+    pub(crate) fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            refs_lock: Arc::default(),
+        }
+    }
+
+    /// Directory for files on their way into the store (same filesystem, so
+    /// they can be renamed in).
+    pub(crate) fn staging_dir(&self) -> BoxliteResult<PathBuf> {
+        let dir = self.root.join("staging");
+        create_dir(&dir)?;
+        Ok(dir)
+    }
+
+    /// Bundle directory of the image with `manifest_digest`.
+    pub(crate) fn bundle_path(&self, manifest_digest: &str) -> PathBuf {
+        self.root.join("bundles").join(hex_part(manifest_digest))
+    }
+
+    /// Bundle of the image tagged `reference`, if one was built.
+    pub(crate) fn resolve(&self, reference: &str) -> Option<PathBuf> {
+        let refs = self.read_refs().ok()?;
+        let digest = refs.tags.get(&normalize_tag(reference))?;
+        let bundle = self.bundle_path(digest);
+        bundle.join("index.json").exists().then_some(bundle)
+    }
+
+    /// Point `reference` at the image with `manifest_digest`.
+    pub(crate) fn tag(&self, reference: &str, manifest_digest: &str) -> BoxliteResult<()> {
+        self.update_refs(|refs| {
+            refs.tags
+                .insert(normalize_tag(reference), manifest_digest.to_string());
+        })
+    }
+
+    /// Image recorded for the build step `key`, if its bundle still exists.
+    pub(crate) fn cached_step(&self, key: &str) -> BoxliteResult<Option<BuiltManifest>> {
+        let refs = self.read_refs()?;
+        match refs.steps.get(key) {
+            Some(digest) if self.bundle_path(digest).exists() => self.manifest(digest).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Record the image produced by the build step `key`.
+    pub(crate) fn record_step(&self, key: &str, manifest_digest: &str) -> BoxliteResult<()> {
+        self.update_refs(|refs| {
+            refs.steps
+                .insert(key.to_string(), manifest_digest.to_string());
+        })
+    }
+
+    /// Move the tar at `path` into the store as a layer; returns its digest.
+    pub(crate) fn add_layer(&self, path: &Path) -> BoxliteResult<String> {
+        let digest = format!("sha256:{}", checksum::sha256_file(path)?);
+        let blob = self.blob_path(&digest);
+        if blob.exists() {
+            let _ = std::fs::remove_file(path);
+        } else {
+            create_dir(blob.parent().expect("blob path has a parent"))?;
+            std::fs::rename(path, &blob).map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "failed to move layer into {}: {}",
+                    blob.display(),
+                    e
+                ))
+            })?;
+        }
+        Ok(digest)
+    }
+
+    /// Write the image made of the layer `layer_digest` (already added) and
+    /// `config`; returns its manifest digest. The config's rootfs is set to
+    /// the layer.
+    pub(crate) fn write_image(
+        &self,
+        layer_digest: &str,
+        mut config: ImageConfiguration,
+    ) -> BoxliteResult<String> {
+        let layer_size = file_size(&self.blob_path(layer_digest))?;
+        config
+            .rootfs_mut()
+            .set_diff_ids(vec![layer_digest.to_string()]);
+        config.set_history(None);
+        let config = serde_json::to_vec(&config)
+            .map_err(|e| BoxliteError::Internal(format!("failed to encode image config: {e}")))?;
+        let config_digest = self.write_blob(&config)?;
+
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": MANIFEST_MEDIA_TYPE,
+            "config": {
+                "mediaType": CONFIG_MEDIA_TYPE,
+                "digest": config_digest,
+                "size": config.len(),
+            },
+            "layers": [{
+                "mediaType": LAYER_MEDIA_TYPE,
+                "digest": layer_digest,
+                "size": layer_size,
+            }],
+        });
+        let manifest = serde_json::to_vec(&manifest)
+            .map_err(|e| BoxliteError::Internal(format!("failed to encode manifest: {e}")))?;
+        let manifest_digest = self.write_blob(&manifest)?;
+
+        let bundle = self.bundle_path(&manifest_digest);
+        if !bundle.join("index.json").exists() {
+            self.write_bundle(
+                &bundle,
+                &[&manifest_digest, &config_digest, layer_digest],
+                &manifest_digest,
+                manifest.len(),
+            )?;
+        }
+        Ok(manifest_digest)
+    }
+
+    /// Read back the image with `manifest_digest`.
+    pub(crate) fn manifest(&self, manifest_digest: &str) -> BoxliteResult<BuiltManifest> {
+        let manifest: serde_json::Value = read_json(&self.blob_path(manifest_digest))?;
+        let digest_at = |pointer: &str| {
+            manifest
+                .pointer(pointer)
+                .and_then(|v| v.as_str())
+                .map(str::to_string)
+                .ok_or_else(|| {
+                    BoxliteError::Storage(format!(
+                        "built image {manifest_digest} has no {pointer} digest"
+                    ))
+                })
+        };
+        Ok(BuiltManifest {
+            manifest_digest: manifest_digest.to_string(),
+            layer_digest: digest_at("/layers/0/digest")?,
+        })
+    }
+
+    /// Assemble the OCI layout for one image, then rename it into place.
+    fn write_bundle(
+        &self,
+        bundle: &Path,
+        digests: &[&str],
+        manifest_digest: &str,
+        manifest_size: usize,
+    ) -> BoxliteResult<()> {
+        let temp = self
+            .staging_dir()?
+            .join(format!("bundle-{}", uuid::Uuid::new_v4().simple()));
+        let result = (|| {
+            let blobs = temp.join("blobs").join("sha256");
+            create_dir(&blobs)?;
+            for digest in digests {
+                let dest = blobs.join(hex_part(digest));
+                if dest.exists() {
+                    continue;
+                }
+                let src = self.blob_path(digest);
+                if std::fs::hard_link(&src, &dest).is_err() {
+                    std::fs::copy(&src, &dest).map_err(|e| {
+                        BoxliteError::Storage(format!(
+                            "failed to copy {} into bundle: {}",
+                            src.display(),
+                            e
+                        ))
+                    })?;
+                }
+            }
+            write_file(
+                &temp.join("oci-layout"),
+                br#"{"imageLayoutVersion":"1.0.0"}"#,
+            )?;
+            let index = serde_json::json!({
+                "schemaVersion": 2,
+                "manifests": [{
+                    "mediaType": MANIFEST_MEDIA_TYPE,
+                    "digest": manifest_digest,
+                    "size": manifest_size,
+                }],
+            });
+            write_file(&temp.join("index.json"), index.to_string().as_bytes())?;
+
+            create_dir(bundle.parent().expect("bundle path has a parent"))?;
+            match std::fs::rename(&temp, bundle) {
+                Ok(()) => Ok(()),
+                // Written by a concurrent build of the same image
+                Err(_) if bundle.join("index.json").exists() => Ok(()),
+                Err(e) => Err(BoxliteError::Storage(format!(
+                    "failed to move bundle into {}: {}",
+                    bundle.display(),
+                    e
+                ))),
+            }
+        })();
+        if temp.exists() {
+            let _ = std::fs::remove_dir_all(&temp);
+        }
+        result
+    }
+
+    fn blob_path(&self, digest: &str) -> PathBuf {
+        self.root.join("blobs").join(digest.replace(':', "/"))
+    }
+
+    fn write_blob(&self, bytes: &[u8]) -> BoxliteResult<String> {
+        let digest = format!("sha256:{}", hex::encode(Sha256::digest(bytes)));
+        let blob = self.blob_path(&digest);
+        if !blob.exists() {
+            create_dir(blob.parent().expect("blob path has a parent"))?;
+            self.write_atomic(&blob, bytes)?;
+        }
+        Ok(digest)
+    }
+
+    fn read_refs(&self) -> BoxliteResult<Refs> {
+        let path = self.root.join(REFS_FILE);
+        if !path.exists() {
+            return Ok(Refs::default());
+        }
+        read_json(&path)
+    }
+
+    fn update_refs(&self, update: impl FnOnce(&mut Refs)) -> BoxliteResult<()> {
+        let _guard = self.refs_lock.lock();
+        let mut refs = self.read_refs()?;
+        update(&mut refs);
+        let json = serde_json::to_vec_pretty(&refs)
+            .map_err(|e| BoxliteError::Internal(format!("failed to encode {REFS_FILE}: {e}")))?;
+        create_dir(&self.root)?;
+        self.write_atomic(&self.root.join(REFS_FILE), &json)
+    }
+
+    fn write_atomic(&self, path: &Path, bytes: &[u8]) -> BoxliteResult<()> {
+        let temp = self
+            .staging_dir()?
+            .join(format!("write-{}", uuid::Uuid::new_v4().simple()));
+        write_file(&temp, bytes)?;
+        std::fs::rename(&temp, path).map_err(|e| {
+            let _ = std::fs::remove_file(&temp);
+            BoxliteError::Storage(format!("failed to write {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// `name` with `:latest` added if it has neither a tag nor a digest.
+pub(crate) fn normalize_tag(reference: &str) -> String {
+    let last = reference.rsplit('/').next().unwrap_or(reference);
+    if last.contains(':') || last.contains('@') {
+        reference.to_string()
+    } else {
+        format!("{reference}:latest")
+    }
+}
+
+fn hex_part(digest: &str) -> &str {
+    digest.split_once(':').map_or(digest, |(_, hex)| hex)
+}
+
+fn file_size(path: &Path) -> BoxliteResult<u64> {
+    std::fs::metadata(path)
+        .map(|m| m.len())
+        .map_err(|e| BoxliteError::Storage(format!("failed to stat {}: {}", path.display(), e)))
+}
+
+fn create_dir(path: &Path) -> BoxliteResult<()> {
+    std::fs::create_dir_all(path)
+        .map_err(|e| BoxliteError::Storage(format!("failed to create {}: {}", path.display(), e)))
+}
+
+fn write_file(path: &Path, bytes: &[u8]) -> BoxliteResult<()> {
+    let mut file = std::fs::File::create(path).map_err(|e| {
+        BoxliteError::Storage(format!("failed to create {}: {}", path.display(), e))
+    })?;
+    file.write_all(bytes)
+        .and_then(|()| file.sync_all())
+        .map_err(|e| BoxliteError::Storage(format!("failed to write {}: {}", path.display(), e)))
+}
+
+fn read_json<T: serde::de::DeserializeOwned>(path: &Path) -> BoxliteResult<T> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| BoxliteError::Storage(format!("failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_slice(&bytes)
+        .map_err(|e| BoxliteError::Storage(format!("failed to parse {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(store: &BuiltImageStore, content: &[u8]) -> String {
+        let path = store.staging_dir().unwrap().join("layer.tar");
+        std::fs::write(&path, content).unwrap();
+        store.add_layer(&path).unwrap()
+    }
+
+    #[test]
+    fn test_normalize_tag() {
+        assert_eq!(normalize_tag("local/app"), "local/app:latest");
+        assert_eq!(normalize_tag("local/app:v1"), "local/app:v1");
+        assert_eq!(
+            normalize_tag("localhost:5000/app"),
+            "localhost:5000/app:latest"
+        );
+    }
+
+    #[test]
+    fn test_write_and_resolve_image() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BuiltImageStore::new(dir.path().join("built"));
+
+        let layer_digest = layer(&store, b"layer");
+        let digest = store
+            .write_image(&layer_digest, ImageConfiguration::default())
+            .unwrap();
+        let bundle = store.bundle_path(&digest);
+        assert!(bundle.join("oci-layout").exists());
+        assert!(
+            bundle
+                .join("blobs")
+                .join(layer_digest.replace(':', "/"))
+                .exists()
+        );
+
+        assert_eq!(store.manifest(&digest).unwrap().layer_digest, layer_digest);
+
+        assert!(store.resolve("local/app").is_none());
+        store.tag("local/app", &digest).unwrap();
+        assert_eq!(store.resolve("local/app:latest"), Some(bundle));
+    }
+
+    #[test]
+    fn test_same_content_same_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BuiltImageStore::new(dir.path().join("built"));
+
+        let first = layer(&store, b"same");
+        let second = layer(&store, b"same");
+        assert_eq!(first, second);
+        let a = store
+            .write_image(&first, ImageConfiguration::default())
+            .unwrap();
+        let b = store
+            .write_image(&second, ImageConfiguration::default())
+            .unwrap();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn test_step_cache_needs_the_bundle() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = BuiltImageStore::new(dir.path().join("built"));
+
+        let layer_digest = layer(&store, b"layer");
+        let digest = store
+            .write_image(&layer_digest, ImageConfiguration::default())
+            .unwrap();
+        store.record_step("key", &digest).unwrap();
+        assert_eq!(
+            store.cached_step("key").unwrap().unwrap().manifest_digest,
+            digest
+        );
+
+        std::fs::remove_dir_all(store.bundle_path(&digest)).unwrap();
+        assert!(store.cached_step("key").unwrap().is_none());
+    }
+}
@@ -0,0 +1,233 @@
+//! Registry client construction with offline-mode enforcement.
+//!
+//! Every registry request goes through `RegistryClient::for_reference()`.
+//! In offline mode no `oci_client::Client` is ever constructed, so there is
+//! no handle that could open a connection — callers get
+//! `BoxliteError::OfflineMode` naming the registry host instead.
+//!
+//! Registries with `RegistrySettings` get a client of their own, built with
+//! their timeout, protocol and CA certificates.
+//!
+//! With `BoxliteOptions::blob_cache_proxy` set, every registry also gets a
+//! second client sending its requests through the proxy, for blobs only.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use oci_client::Reference;
+use oci_client::client::{Certificate, CertificateEncoding, ClientConfig, ClientProtocol};
+use url::Url;
+
+use crate::runtime::options::RegistrySettings;
+
+/// Connect timeout of proxied clients whose registry sets none, so a proxy
+/// that is down fails over to the registry quickly.
+const PROXY_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// OCI registry client gate.
+///
+/// Holds the underlying HTTP clients only when network access is allowed.
+pub(crate) struct RegistryClient {
+    inner: Option<Clients>,
+    /// Clients going through the blob cache proxy, if one is set.
+    proxied: Option<Clients>,
+}
+
+struct Clients {
+    /// Client for registries without settings.
+    default: oci_client::Client,
+    /// Clients for registries with settings, keyed by registry.
+    configured: HashMap<String, oci_client::Client>,
+}
+
+impl Clients {
+    /// Clients for `settings`, sending requests through `proxy` if given.
+    fn new(
+        settings: &HashMap<String, RegistrySettings>,
+        proxy: Option<&Url>,
+    ) -> BoxliteResult<Self> {
+        let configured = settings
+            .iter()
+            .map(|(registry, settings)| {
+                let config = client_config(registry, settings)?;
+                Ok((registry.clone(), build_client(config, proxy)?))
+            })
+            .collect::<BoxliteResult<_>>()?;
+        Ok(Self {
+            default: build_client(ClientConfig::default(), proxy)?,
+            configured,
+        })
+    }
+
+    fn get(&self, reference: &Reference) -> &oci_client::Client {
+        self.configured
+            .get(reference.registry())
+            .unwrap_or(&self.default)
+    }
+}
+
+impl RegistryClient {
+    /// Create a registry client.
+    ///
+    /// When `offline` is true, the HTTP clients are not constructed at all.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BoxliteError::Config` if a `ca_cert` file cannot be read or
+    /// `blob_cache_proxy` is not a usable proxy URL.
+    pub(crate) fn new(
+        offline: bool,
+        settings: &HashMap<String, RegistrySettings>,
+        blob_cache_proxy: Option<&Url>,
+    ) -> BoxliteResult<Self> {
+        if offline {
+            tracing::info!("Offline mode enabled: registry access disabled");
+            return Ok(Self {
+                inner: None,
+                proxied: None,
+            });
+        }
+
+        Ok(Self {
+            inner: Some(Clients::new(settings, None)?),
+            proxied: blob_cache_proxy
+                .map(|proxy| Clients::new(settings, Some(proxy)))
+                .transpose()?,
+        })
+    }
+
+    /// Whether this client refuses all network access.
+    pub(crate) fn is_offline(&self) -> bool {
+        self.inner.is_none()
+    }
+
+    /// Get the HTTP client for talking to the registry of `reference`.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BoxliteError::OfflineMode` with the registry host when offline.
+    pub(crate) fn for_reference(
+        &self,
+        reference: &Reference,
+    ) -> BoxliteResult<&oci_client::Client> {
+        let Some(clients) = &self.inner else {
+            tracing::warn!(
+                registry = %reference.resolve_registry(),
+                reference = %reference.whole(),
+                "Blocked registry access in offline mode"
+            );
+            return Err(BoxliteError::OfflineMode(
+                reference.resolve_registry().to_string(),
+            ));
+        };
+        Ok(clients.get(reference))
+    }
+
+    /// Get the client sending blob requests for `reference` through the blob
+    /// cache proxy, or None without a proxy (or offline).
+    pub(crate) fn proxied_for(&self, reference: &Reference) -> Option<&oci_client::Client> {
+        self.proxied.as_ref().map(|clients| clients.get(reference))
+    }
+}
+
+/// Build a client from `config`, sending its requests through `proxy` if
+/// given.
+fn build_client(
+    mut config: ClientConfig,
+    proxy: Option<&Url>,
+) -> BoxliteResult<oci_client::Client> {
+    let Some(proxy) = proxy else {
+        return Ok(oci_client::Client::new(config));
+    };
+    config.http_proxy = Some(proxy.to_string());
+    config.https_proxy = Some(proxy.to_string());
+    config.connect_timeout.get_or_insert(PROXY_CONNECT_TIMEOUT);
+    // Client::new() would quietly drop a proxy it can't use
+    oci_client::Client::try_from(config)
+        .map_err(|e| BoxliteError::Config(format!("Invalid blob_cache_proxy {}: {}", proxy, e)))
+}
+
+/// Client configuration for `registry` with `settings` applied.
+fn client_config(registry: &str, settings: &RegistrySettings) -> BoxliteResult<ClientConfig> {
+    let mut config = ClientConfig {
+        connect_timeout: settings.timeout,
+        read_timeout: settings.timeout,
+        ..Default::default()
+    };
+    if settings.insecure {
+        config.protocol = ClientProtocol::HttpsExcept(vec![registry.to_string()]);
+    }
+    if let Some(path) = &settings.ca_cert {
+        let data = std::fs::read(path).map_err(|e| {
+            BoxliteError::Config(format!(
+                "Failed to read CA certificate {} for registry {}: {}",
+                path.display(),
+                registry,
+                e
+            ))
+        })?;
+        config.extra_root_certificates.push(Certificate {
+            encoding: CertificateEncoding::Pem,
+            data,
+        });
+    }
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offline_client_names_registry_host() {
+        let client = RegistryClient::new(true, &HashMap::new(), None).unwrap();
+        assert!(client.is_offline());
+
+        let reference: Reference = "ghcr.io/foo/bar:v1".parse().unwrap();
+        match client.for_reference(&reference) {
+            Err(BoxliteError::OfflineMode(host)) => assert_eq!(host, "ghcr.io"),
+            other => panic!("expected OfflineMode, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_online_client_is_available() {
+        let client = RegistryClient::new(false, &HashMap::new(), None).unwrap();
+        assert!(!client.is_offline());
+
+        let reference: Reference = "alpine:latest".parse().unwrap();
+        assert!(client.for_reference(&reference).is_ok());
+        assert!(client.proxied_for(&reference).is_none());
+    }
+
+    #[test]
+    fn test_proxied_clients_only_with_proxy_and_online() {
+        let proxy: Url = "http://cache.internal:3128".parse().unwrap();
+        let reference: Reference = "alpine:latest".parse().unwrap();
+
+        let client = RegistryClient::new(false, &HashMap::new(), Some(&proxy)).unwrap();
+        assert!(client.proxied_for(&reference).is_some());
+
+        let offline = RegistryClient::new(true, &HashMap::new(), Some(&proxy)).unwrap();
+        assert!(offline.proxied_for(&reference).is_none());
+    }
+
+    #[test]
+    fn test_missing_ca_cert_is_config_error() {
+        let settings = HashMap::from([(
+            "registry.local".to_string(),
+            RegistrySettings {
+                ca_cert: Some("/nonexistent/ca.pem".into()),
+                ..Default::default()
+            },
+        )]);
+        match RegistryClient::new(false, &settings, None) {
+            Err(BoxliteError::Config(msg)) => assert!(msg.contains("registry.local"), "got {msg}"),
+            other => panic!("expected Config error, got {:?}", other.map(|_| ())),
+        }
+
+        // Offline runtimes never build clients, so the file is not read
+        assert!(RegistryClient::new(true, &settings, None).is_ok());
+    }
+}
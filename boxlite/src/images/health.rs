@@ -0,0 +1,225 @@
+//! Registry health tracking for pull failover.
+//!
+//! Every manifest request records whether the registry answered. After
+//! `FAILURE_THRESHOLD` consecutive failures a registry cools down for
+//! `COOLDOWN`: pulls skip it in favour of the next candidate, then try it
+//! again once the cooldown is over. Answers such as "manifest unknown"
+//! count as healthy, since a mirror without the image is still up.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+/// Consecutive failures after which a registry cools down.
+pub(crate) const FAILURE_THRESHOLD: u32 = 3;
+
+/// How long a registry is skipped once it reaches `FAILURE_THRESHOLD`.
+pub(crate) const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Health of one registry, see `BoxliteRuntime::registry_status()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegistryStatus {
+    /// Registry as written in `image_registries` or an image reference.
+    pub registry: String,
+    /// Failed requests since the registry last answered.
+    pub consecutive_failures: u32,
+    /// Error of the most recent failed request.
+    pub last_error: Option<String>,
+    /// When the registry last answered a request.
+    pub last_success: Option<DateTime<Utc>>,
+    /// When a request to the registry last failed.
+    pub last_failure: Option<DateTime<Utc>>,
+    /// End of the current cooldown; `None` when pulls use the registry.
+    pub cooldown_until: Option<DateTime<Utc>>,
+}
+
+impl RegistryStatus {
+    /// Whether pulls currently try this registry.
+    pub fn is_available(&self) -> bool {
+        self.cooldown_until.is_none()
+    }
+}
+
+#[derive(Default)]
+struct Entry {
+    consecutive_failures: u32,
+    last_error: Option<String>,
+    last_success: Option<DateTime<Utc>>,
+    last_failure: Option<(Instant, DateTime<Utc>)>,
+}
+
+impl Entry {
+    /// Time left in the cooldown at `now`, if any.
+    fn cooldown_left(&self, now: Instant) -> Option<Duration> {
+        if self.consecutive_failures < FAILURE_THRESHOLD {
+            return None;
+        }
+        let (failed_at, _) = self.last_failure?;
+        COOLDOWN
+            .checked_sub(now.saturating_duration_since(failed_at))
+            .filter(|left| !left.is_zero())
+    }
+}
+
+/// Per-registry failure counts, shared by all pulls of a store.
+#[derive(Default)]
+pub(crate) struct RegistryHealth {
+    entries: Mutex<HashMap<String, Entry>>,
+}
+
+impl RegistryHealth {
+    /// The registry answered a request.
+    pub(crate) fn record_success(&self, registry: &str) {
+        let mut entries = self.lock();
+        let entry = entries.entry(registry.to_string()).or_default();
+        entry.consecutive_failures = 0;
+        entry.last_success = Some(Utc::now());
+    }
+
+    /// A request to the registry failed without an answer, or with a server
+    /// error.
+    pub(crate) fn record_failure(&self, registry: &str, error: &str) {
+        self.record_failure_at(registry, error, Instant::now());
+    }
+
+    fn record_failure_at(&self, registry: &str, error: &str, now: Instant) {
+        let mut entries = self.lock();
+        let entry = entries.entry(registry.to_string()).or_default();
+        entry.consecutive_failures += 1;
+        entry.last_error = Some(error.to_string());
+        entry.last_failure = Some((now, Utc::now()));
+        if entry.consecutive_failures == FAILURE_THRESHOLD {
+            tracing::warn!(
+                registry = %registry,
+                failures = entry.consecutive_failures,
+                error = %error,
+                "Registry unhealthy, skipping it for {}s",
+                COOLDOWN.as_secs()
+            );
+        }
+    }
+
+    /// Time left before pulls try `registry` again; `None` if it is usable.
+    pub(crate) fn cooldown_left(&self, registry: &str, now: Instant) -> Option<Duration> {
+        self.lock().get(registry)?.cooldown_left(now)
+    }
+
+    /// Why `registry` is being skipped, for aggregated pull errors.
+    pub(crate) fn skip_reason(&self, registry: &str, now: Instant) -> Option<String> {
+        let entries = self.lock();
+        let entry = entries.get(registry)?;
+        let left = entry.cooldown_left(now)?;
+        Some(format!(
+            "skipped for another {}s after {} consecutive failures (last: {})",
+            left.as_secs().max(1),
+            entry.consecutive_failures,
+            entry.last_error.as_deref().unwrap_or("unknown error")
+        ))
+    }
+
+    /// Status of `configured` registries in order, then of every other
+    /// registry contacted so far, by name.
+    pub(crate) fn status(&self, configured: &[String]) -> Vec<RegistryStatus> {
+        let entries = self.lock();
+        let now = Instant::now();
+        let mut others: Vec<&String> = entries
+            .keys()
+            .filter(|name| !configured.contains(name))
+            .collect();
+        others.sort();
+
+        configured
+            .iter()
+            .chain(others)
+            .map(|name| match entries.get(name) {
+                Some(entry) => RegistryStatus {
+                    registry: name.clone(),
+                    consecutive_failures: entry.consecutive_failures,
+                    last_error: entry.last_error.clone(),
+                    last_success: entry.last_success,
+                    last_failure: entry.last_failure.map(|(_, at)| at),
+                    cooldown_until: entry.cooldown_left(now).and_then(|left| {
+                        chrono::Duration::from_std(left)
+                            .ok()
+                            .map(|d| Utc::now() + d)
+                    }),
+                },
+                None => RegistryStatus {
+                    registry: name.clone(),
+                    consecutive_failures: 0,
+                    last_error: None,
+                    last_success: None,
+                    last_failure: None,
+                    cooldown_until: None,
+                },
+            })
+            .collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Entry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cooldown_after_threshold_and_expiry() {
+        let health = RegistryHealth::default();
+        let start = Instant::now();
+        for _ in 0..FAILURE_THRESHOLD - 1 {
+            health.record_failure_at("mirror.local", "connection refused", start);
+        }
+        assert_eq!(health.cooldown_left("mirror.local", start), None);
+
+        health.record_failure_at("mirror.local", "connection refused", start);
+        assert_eq!(health.cooldown_left("mirror.local", start), Some(COOLDOWN));
+        let reason = health.skip_reason("mirror.local", start).unwrap();
+        assert!(reason.contains("3 consecutive failures"), "got {reason}");
+        assert!(reason.contains("connection refused"), "got {reason}");
+
+        let later = start + COOLDOWN;
+        assert_eq!(health.cooldown_left("mirror.local", later), None);
+        assert_eq!(health.skip_reason("mirror.local", later), None);
+    }
+
+    #[test]
+    fn test_success_resets_failures() {
+        let health = RegistryHealth::default();
+        let now = Instant::now();
+        for _ in 0..FAILURE_THRESHOLD {
+            health.record_failure_at("mirror.local", "timed out", now);
+        }
+        health.record_success("mirror.local");
+
+        assert_eq!(health.cooldown_left("mirror.local", now), None);
+        let status = health.status(&[]);
+        assert_eq!(status.len(), 1);
+        assert_eq!(status[0].consecutive_failures, 0);
+        assert_eq!(status[0].last_error.as_deref(), Some("timed out"));
+        assert!(status[0].last_success.is_some());
+        assert!(status[0].is_available());
+    }
+
+    #[test]
+    fn test_status_lists_configured_first() {
+        let health = RegistryHealth::default();
+        health.record_success("quay.io");
+        for _ in 0..FAILURE_THRESHOLD {
+            health.record_failure("mirror.local", "503 Service Unavailable");
+        }
+
+        let configured = vec!["mirror.local".to_string(), "docker.io".to_string()];
+        let status = health.status(&configured);
+        let names: Vec<&str> = status.iter().map(|s| s.registry.as_str()).collect();
+        assert_eq!(names, vec!["mirror.local", "docker.io", "quay.io"]);
+        assert!(!status[0].is_available());
+        assert!(status[0].cooldown_until.is_some());
+        assert_eq!(status[1].last_success, None);
+        assert!(status[1].is_available());
+    }
+}
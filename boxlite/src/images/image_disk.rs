@@ -8,8 +8,10 @@ use std::path::PathBuf;
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
-use crate::disk::{Disk, DiskFormat, create_ext4_from_dir};
+use crate::disk::cache::{self, CacheCap};
+use crate::disk::{CacheStats, Disk, DiskFormat, create_ext4_from_dir};
 use crate::rootfs::RootfsBuilder;
+use crate::runtime::disk_space::DiskSpace;
 
 use super::ImageObject;
 
@@ -29,10 +31,17 @@ use super::ImageObject;
 ///
 /// No internal locking is needed.
 ///
+/// # Size cap
+///
+/// With a cap set via [`with_cap`](Self::with_cap), least recently used
+/// disks no box is backed by are evicted after each install and on startup.
+///
 /// Cache location: `~/.boxlite/images/disk-images/`
 pub struct ImageDiskManager {
     cache_dir: PathBuf,
     temp_dir: PathBuf,
+    cap: CacheCap,
+    space: DiskSpace,
 }
 
 impl ImageDiskManager {
@@ -40,9 +49,24 @@ impl ImageDiskManager {
         Self {
             cache_dir,
             temp_dir,
+            cap: CacheCap::default(),
+            space: DiskSpace::default(),
         }
     }
 
+    /// Cap the cache at `max_bytes`, protecting disks that the boxes under
+    /// `box_roots` are backed by.
+    pub fn with_cap(mut self, max_bytes: Option<u64>, box_roots: Vec<PathBuf>) -> Self {
+        self.cap = CacheCap::new(max_bytes, box_roots);
+        self
+    }
+
+    /// Check free space with `space` before building a disk.
+    pub(crate) fn with_disk_space(mut self, space: DiskSpace) -> Self {
+        self.space = space;
+        self
+    }
+
     /// Get or create an ext4 disk image for the given OCI image.
     ///
     /// Returns a persistent `Disk` (won't be cleaned up on drop).
@@ -57,19 +81,51 @@ impl ImageDiskManager {
         }
 
         tracing::info!("Building image disk for {} (first time)", digest);
-        self.build_and_install(image, &digest).await
+        let disk = self.build_and_install(image, &digest).await?;
+        if let Err(e) = self.evict() {
+            tracing::warn!("Image disk cache eviction failed: {}", e);
+        }
+        Ok(disk)
     }
 
-    /// Look up a cached disk by image digest.
+    /// Evict least recently used disks until the cache fits its cap.
+    ///
+    /// Returns the number of disks removed.
+    pub fn evict(&self) -> BoxliteResult<usize> {
+        Ok(self.cap.enforce("image disks", &self.cache_dir)?.len())
+    }
+
+    /// Current usage of the cache.
+    pub fn stats(&self) -> BoxliteResult<CacheStats> {
+        self.cap.stats(&self.cache_dir)
+    }
+
+    /// Look up a cached disk by image digest, recording the use.
     fn find(&self, digest: &str) -> Option<Disk> {
         let path = self.disk_path(digest);
-        path.exists()
-            .then(|| Disk::new(path, DiskFormat::Ext4, true))
+        if !path.exists() {
+            return None;
+        }
+        cache::touch(&path);
+        Some(Disk::new(path, DiskFormat::Ext4, true))
     }
 
     /// Build ext4 from image layers and atomically install to cache.
+    ///
+    /// The merged tree and the disk each take at least as much as the
+    /// compressed layers, so twice their size must be free to start.
     async fn build_and_install(&self, image: &ImageObject, digest: &str) -> BoxliteResult<Disk> {
-        // All work happens in a temp directory (staged)
+        let layers_bytes: u64 = image
+            .layer_tarballs()
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        self.space
+            .ensure(&self.temp_dir, layers_bytes * 2, "build image disk")?;
+
+        // All work happens in a temp directory (staged); it is removed on
+        // any failure, including running out of space midway
         let temp = tempfile::tempdir_in(&self.temp_dir).map_err(|e| {
             BoxliteError::Storage(format!(
                 "Failed to create temp directory in {}: {}",
@@ -133,6 +189,7 @@ impl ImageDiskManager {
         // Prevent staged_disk from cleaning up the now-moved file
         let _ = staged_disk.leak();
 
+        cache::touch(&target);
         tracing::info!("Installed image disk to cache: {}", target.display());
         Ok(Disk::new(target, DiskFormat::Ext4, true))
     }
@@ -232,4 +289,44 @@ mod tests {
         assert_eq!(std::fs::read_to_string(result.path()).unwrap(), "first");
         let _ = result.leak();
     }
+
+    #[test]
+    fn test_evict_removes_least_recently_used_disk() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mgr = ImageDiskManager::new(dir.path().to_path_buf(), dir.path().to_path_buf())
+            .with_cap(Some(1), vec![dir.path().join("boxes")]);
+
+        let two_hours_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(7200);
+        for name in ["sha256-used.ext4", "sha256-idle.ext4"] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, vec![1u8; 8192]).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(two_hours_ago)
+                .unwrap();
+        }
+
+        // A hit marks the disk as recently used
+        let _ = mgr.find("sha256:used").unwrap().leak();
+        assert_eq!(mgr.evict().unwrap(), 1);
+
+        assert!(dir.path().join("sha256-used.ext4").exists());
+        assert!(!dir.path().join("sha256-idle.ext4").exists());
+        let stats = mgr.stats().unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.evicted_entries, 1);
+        assert_eq!(stats.max_bytes, Some(1));
+    }
+
+    #[test]
+    fn test_evict_without_cap_keeps_everything() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mgr = ImageDiskManager::new(dir.path().to_path_buf(), dir.path().to_path_buf());
+        std::fs::write(dir.path().join("sha256-a.ext4"), "disk").unwrap();
+
+        assert_eq!(mgr.evict().unwrap(), 0);
+        assert!(dir.path().join("sha256-a.ext4").exists());
+    }
 }
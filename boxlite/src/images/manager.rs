@@ -9,19 +9,29 @@
 //! - `ImageStore` handles all locking internally
 //! - `ImageObject` uses `BlobSource` for blob access
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 
 use chrono::{DateTime, Utc};
 
 use super::blob_source::{BlobSource, LocalBundleBlobSource, StoreBlobSource};
+use super::built::BuiltImageStore;
+use super::health::RegistryStatus;
 use super::object::ImageObject;
+use super::progress::{BlobCacheLookup, PullProgress, PullProgressFn};
+use super::vulnerability::VulnerabilityReport;
 use crate::db::Database;
 use crate::images::store::{ImageStore, SharedImageStore};
+use crate::metrics::RuntimeMetricsStorage;
+use crate::runtime::options::RegistrySettings;
 use crate::runtime::types::ImageInfo;
 use boxlite_shared::errors::BoxliteResult;
 use oci_client::Reference;
+use parking_lot::Mutex;
 use std::str::FromStr;
+use std::sync::atomic::Ordering;
+use url::Url;
 
 // ============================================================================
 // INTERNAL TYPES
@@ -39,6 +49,8 @@ pub(super) struct ImageManifest {
 pub(super) struct LayerInfo {
     pub(super) digest: String,
     pub(super) media_type: String,
+    /// Size in bytes from the manifest (0 if unknown)
+    pub(super) size: u64,
 }
 
 // ============================================================================
@@ -62,7 +74,15 @@ pub(super) struct LayerInfo {
 ///
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// let db = Database::open(&PathBuf::from("/tmp/boxlite.db"))?;
-/// let manager = ImageManager::new(PathBuf::from("/tmp/images"), db, vec![])?;
+/// let manager = ImageManager::new(
+///     PathBuf::from("/tmp/images"),
+///     db,
+///     vec![],
+///     &HashMap::new(),
+///     false,
+///     None,
+///     None,
+/// )?;
 ///
 /// // Pull an image
 /// let image = manager.pull("python:alpine").await?;
@@ -76,6 +96,10 @@ pub(super) struct LayerInfo {
 #[derive(Clone)]
 pub struct ImageManager {
     store: SharedImageStore,
+    /// Images made by `BoxliteRuntime::build_image`, resolved before pulling
+    built: BuiltImageStore,
+    /// Receives the bytes each pull downloads
+    metrics: RuntimeMetricsStorage,
 }
 
 impl std::fmt::Debug for ImageManager {
@@ -91,20 +115,84 @@ impl ImageManager {
     /// * `images_dir` - Directory for image cache
     /// * `db` - Database for image index
     /// * `registries` - Registries to search for unqualified images (tried in order)
-    pub fn new(images_dir: PathBuf, db: Database, registries: Vec<String>) -> BoxliteResult<Self> {
-        let store = Arc::new(ImageStore::new(images_dir, db, registries)?);
-        Ok(Self { store })
+    /// * `registry_settings` - Timeout, protocol and CA certificates per registry
+    /// * `offline` - Refuse all registry access; serve from cache only
+    /// * `blob_cache_proxy` - HTTP caching proxy for blob downloads
+    /// * `blob_cache_dir` - Layer cache shared with other machines
+    pub fn new(
+        images_dir: PathBuf,
+        db: Database,
+        registries: Vec<String>,
+        registry_settings: &HashMap<String, RegistrySettings>,
+        offline: bool,
+        blob_cache_proxy: Option<&Url>,
+        blob_cache_dir: Option<PathBuf>,
+    ) -> BoxliteResult<Self> {
+        let built = BuiltImageStore::new(images_dir.join("built"));
+        let store = Arc::new(ImageStore::with_blob_cache(
+            images_dir,
+            db,
+            registries,
+            registry_settings,
+            offline,
+            blob_cache_proxy,
+            blob_cache_dir,
+        )?);
+        Ok(Self {
+            store,
+            built,
+            metrics: RuntimeMetricsStorage::default(),
+        })
+    }
+
+    /// Count downloaded bytes in `metrics`, per image, and shared blob cache
+    /// lookups.
+    pub(crate) fn with_metrics(mut self, metrics: RuntimeMetricsStorage) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Health of the configured registries and of every other registry
+    /// contacted so far.
+    pub fn registry_status(&self) -> Vec<RegistryStatus> {
+        self.store.registry_status()
+    }
+
+    /// Store of the images built by `BoxliteRuntime::build_image`.
+    pub(crate) fn built_images(&self) -> &BuiltImageStore {
+        &self.built
     }
 
     /// Pull an OCI image from a registry.
     ///
-    /// Checks local cache first. If the image is already cached and complete,
+    /// A tag of a built image resolves to that image, without registry
+    /// access. Otherwise checks local cache first. If the image is already cached and complete,
     /// returns immediately without network access. Otherwise pulls from registry.
+    /// In offline mode, an uncached image fails with `BoxliteError::OfflineMode`.
     ///
     /// Thread Safety: `ImageStore` handles locking internally. Multiple
     /// concurrent pulls of the same image will only download once.
     pub async fn pull(&self, image_ref: &str) -> BoxliteResult<ImageObject> {
-        let manifest = self.store.pull(image_ref).await?;
+        self.pull_with_progress(image_ref, None).await
+    }
+
+    /// Pull an OCI image, reporting layer download progress to `progress`.
+    ///
+    /// Dropping the returned future aborts in-flight downloads; partially
+    /// downloaded layers are kept and resumed by the next pull.
+    pub async fn pull_with_progress(
+        &self,
+        image_ref: &str,
+        progress: Option<PullProgressFn>,
+    ) -> BoxliteResult<ImageObject> {
+        if let Some(bundle) = self.built.resolve(image_ref) {
+            return self.load_from_local(bundle, image_ref.to_string()).await;
+        }
+        let progress = self.count_pulled(image_ref, progress);
+        let manifest = self
+            .store
+            .pull_with_progress(image_ref, Some(progress))
+            .await?;
         let storage = self.store.storage().await;
         let blob_source = BlobSource::Store(StoreBlobSource::new(storage));
 
@@ -115,6 +203,58 @@ impl ImageManager {
         ))
     }
 
+    /// Wrap `progress` to add the layer bytes actually downloaded (not
+    /// resumed or copied from the shared blob cache) to the per-image metrics
+    /// of `image_ref`, and count shared blob cache lookups.
+    fn count_pulled(&self, image_ref: &str, progress: Option<PullProgressFn>) -> PullProgressFn {
+        let metrics = self.metrics.clone();
+        let image = image_ref.to_string();
+        // Bytes of each layer counted so far in its current attempt
+        let counted: Mutex<HashMap<String, u64>> = Mutex::default();
+        Arc::new(move |p: &PullProgress| {
+            {
+                let mut counted = counted.lock();
+                match p.cache {
+                    // Copied, not downloaded: no bytes to count
+                    Some(BlobCacheLookup::Hit) => {
+                        metrics.blob_cache_hits.fetch_add(1, Ordering::Relaxed);
+                    }
+                    // Once per layer, however many attempts its download takes
+                    Some(BlobCacheLookup::Miss) if !counted.contains_key(&p.digest) => {
+                        metrics.blob_cache_misses.fetch_add(1, Ordering::Relaxed);
+                    }
+                    _ => {}
+                }
+                if p.cache != Some(BlobCacheLookup::Hit) {
+                    let seen = counted.entry(p.digest.clone()).or_insert(p.resumed);
+                    if p.downloaded == p.resumed {
+                        // An attempt starts (again) after the bytes already on disk
+                        *seen = p.resumed;
+                    } else if p.downloaded > *seen {
+                        metrics.per_image.bytes_pulled(&image, p.downloaded - *seen);
+                        *seen = p.downloaded;
+                    }
+                }
+            }
+            if let Some(progress) = &progress {
+                progress(p);
+            }
+        })
+    }
+
+    /// Fetch the vulnerability report the registry holds for `image`.
+    ///
+    /// Returns `Ok(None)` if there is none. See
+    /// `ImageStore::vulnerability_report` for the formats understood.
+    pub async fn vulnerability_report(
+        &self,
+        image: &ImageObject,
+    ) -> BoxliteResult<Option<VulnerabilityReport>> {
+        self.store
+            .vulnerability_report(image.reference(), image.manifest_digest())
+            .await
+    }
+
     /// List all cached images.
     pub async fn list(&self) -> BoxliteResult<Vec<ImageInfo>> {
         let raw_images = self.store.list().await?;
@@ -1,20 +1,56 @@
 mod archive;
+mod blob_cache;
 mod blob_source;
+pub(crate) mod built;
+mod client;
 mod config;
+mod health;
 mod image_disk;
 mod manager;
 mod object;
+mod progress;
 mod storage;
 mod store;
+mod vulnerability;
 
 pub use archive::extract_layer_tarball_streaming;
 pub use config::ContainerImageConfig;
+pub use health::RegistryStatus;
 pub use image_disk::ImageDiskManager;
 pub use manager::ImageManager;
 pub use object::ImageObject;
+pub use progress::{BlobCacheLookup, PullProgress, PullProgressFn};
+pub use vulnerability::{Severity, Vulnerability, VulnerabilityReport};
 
 use oci_client::Reference;
 
+// ============================================================================
+// Digest References
+// ============================================================================
+
+/// Parse an image reference, dropping the tag of `name:tag@digest`.
+///
+/// Like docker, the digest wins: a pinned reference is never resolved
+/// through its tag, so the tag is informational only.
+pub(crate) fn parse_reference(image_ref: &str) -> Result<Reference, oci_client::ParseError> {
+    let reference: Reference = image_ref.parse()?;
+    Ok(match reference.digest() {
+        Some(digest) if reference.tag().is_some() => Reference::with_digest(
+            reference.registry().to_string(),
+            reference.repository().to_string(),
+            digest.to_string(),
+        ),
+        _ => reference,
+    })
+}
+
+/// The digest `image_ref` is pinned to (`name@sha256:...`), if any.
+pub(crate) fn pinned_digest(image_ref: &str) -> Option<String> {
+    parse_reference(image_ref)
+        .ok()
+        .and_then(|r| r.digest().map(str::to_string))
+}
+
 // ============================================================================
 // Registry Resolution (Reference Iterator)
 // ============================================================================
@@ -23,6 +59,7 @@ use oci_client::Reference;
 ///
 /// For qualified images (e.g., `"ghcr.io/foo/bar"`), yields only the original.
 /// For unqualified images (e.g., `"alpine"`), yields one `Reference` per registry.
+/// A digest reference keeps its digest on every candidate, so no tag is resolved.
 ///
 /// # Examples
 ///
@@ -66,7 +103,7 @@ impl<'a> ReferenceIter<'a> {
     ///
     /// Returns an error if the image reference cannot be parsed by oci_client.
     pub fn new(image_ref: &str, registries: &'a [String]) -> Result<Self, oci_client::ParseError> {
-        let base_ref: Reference = image_ref.parse()?;
+        let base_ref = parse_reference(image_ref)?;
         let is_qualified = is_fully_qualified(image_ref);
 
         tracing::debug!(
@@ -116,12 +153,16 @@ impl Iterator for ReferenceIter<'_> {
         let registry = &self.registries[self.index];
         self.index += 1;
 
+        let repository = self.base_ref.repository().to_string();
+        if let Some(digest) = self.base_ref.digest() {
+            return Some(Reference::with_digest(
+                registry.clone(),
+                repository,
+                digest.to_string(),
+            ));
+        }
         let tag = self.base_ref.tag().unwrap_or("latest").to_string();
-        Some(Reference::with_tag(
-            registry.clone(),
-            self.base_ref.repository().to_string(),
-            tag,
-        ))
+        Some(Reference::with_tag(registry.clone(), repository, tag))
     }
 }
 
@@ -237,6 +278,36 @@ mod tests {
         assert!(refs[0].1.contains("library"));
     }
 
+    const DIGEST: &str = "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+
+    #[test]
+    fn test_digest_kept_for_every_registry() {
+        let registries = vec!["ghcr.io".to_string(), "quay.io".to_string()];
+        let iter = ReferenceIter::new(&format!("alpine@{DIGEST}"), &registries).unwrap();
+        let refs: Vec<Reference> = iter.collect();
+
+        assert_eq!(refs.len(), 2);
+        for r in &refs {
+            assert_eq!(r.digest(), Some(DIGEST));
+            assert_eq!(r.tag(), None);
+        }
+        assert_eq!(refs[0].whole(), format!("ghcr.io/library/alpine@{DIGEST}"));
+    }
+
+    #[test]
+    fn test_tag_and_digest_digest_wins() {
+        let reference = parse_reference(&format!("ghcr.io/foo/bar:v1@{DIGEST}")).unwrap();
+        assert_eq!(reference.tag(), None);
+        assert_eq!(reference.digest(), Some(DIGEST));
+        assert_eq!(reference.whole(), format!("ghcr.io/foo/bar@{DIGEST}"));
+
+        assert_eq!(
+            pinned_digest(&format!("alpine:3.20@{DIGEST}")).as_deref(),
+            Some(DIGEST)
+        );
+        assert_eq!(pinned_digest("alpine:3.20"), None);
+    }
+
     #[test]
     fn test_is_fully_qualified() {
         // Qualified (has registry)
@@ -8,6 +8,7 @@ use std::path::PathBuf;
 
 use super::blob_source::BlobSource;
 use super::manager::ImageManifest;
+use super::vulnerability::VulnerabilityReport;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 // ============================================================================
@@ -35,6 +36,9 @@ pub struct ImageObject {
 
     /// Source of blobs with source-specific caching
     blob_source: BlobSource,
+
+    /// Vulnerability report from the registry, when one was fetched
+    vulnerability_report: Option<VulnerabilityReport>,
 }
 
 impl ImageObject {
@@ -44,9 +48,16 @@ impl ImageObject {
             reference,
             manifest,
             blob_source,
+            vulnerability_report: None,
         }
     }
 
+    /// Attach a vulnerability report fetched for this image.
+    pub(crate) fn with_vulnerability_report(mut self, report: Option<VulnerabilityReport>) -> Self {
+        self.vulnerability_report = report;
+        self
+    }
+
     // ========================================================================
     // METADATA OPERATIONS
     // ========================================================================
@@ -67,6 +78,20 @@ impl ImageObject {
             .collect()
     }
 
+    /// Get the manifest digest (platform-specific for multi-platform images)
+    pub fn manifest_digest(&self) -> &str {
+        &self.manifest.manifest_digest
+    }
+
+    /// Vulnerability report attached to the image in its registry.
+    ///
+    /// Only fetched when a create policy asks for it
+    /// (`CreatePolicy::needs_vulnerability_report`); `None` otherwise, and
+    /// when the registry has no report for the image.
+    pub fn vulnerability_report(&self) -> Option<&VulnerabilityReport> {
+        self.vulnerability_report.as_ref()
+    }
+
     /// Get config digest
     #[allow(dead_code)]
     pub fn config_digest(&self) -> &str {
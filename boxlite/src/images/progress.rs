@@ -0,0 +1,43 @@
+//! Progress reporting for image pulls.
+
+use std::sync::Arc;
+
+/// Download progress of one image layer.
+///
+/// Reported when a layer download starts and after every chunk written.
+/// Layers download in parallel, so events for different layers interleave.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullProgress {
+    /// Layer digest, e.g. `sha256:...`.
+    pub digest: String,
+    /// Bytes of the layer on disk, including resumed bytes.
+    pub downloaded: u64,
+    /// Layer size from the manifest (0 if the manifest does not say).
+    pub total: u64,
+    /// Bytes kept from an interrupted earlier download and not fetched again.
+    pub resumed: u64,
+    /// Outcome of the lookup in `BoxliteOptions::blob_cache_dir`, or None
+    /// without a shared cache. A hit reports the whole layer at once.
+    pub cache: Option<BlobCacheLookup>,
+}
+
+/// Whether a layer was found in the shared blob cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlobCacheLookup {
+    /// Copied from the cache, digest verified.
+    Hit,
+    /// Not cached, or the cached copy was unreadable or corrupt; downloaded.
+    Miss,
+}
+
+impl PullProgress {
+    /// Percentage of the layer that was resumed rather than downloaded, if
+    /// the size is known.
+    pub fn resumed_percent(&self) -> Option<u8> {
+        (self.total > 0).then(|| (self.resumed.min(self.total) * 100 / self.total) as u8)
+    }
+}
+
+/// Callback invoked with pull progress. Called from download tasks, so it
+/// should return quickly.
+pub type PullProgressFn = Arc<dyn Fn(&PullProgress) + Send + Sync>;
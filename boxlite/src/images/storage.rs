@@ -268,12 +268,23 @@ impl ImageStorage {
         Ok(())
     }
 
-    /// Start a staged download for a layer blob.
+    /// Get path to the partial download of a layer tarball.
     ///
-    /// **Mutability**: Atomic - creates unique temp file with random suffix.
-    /// Safe for concurrent access; each caller gets its own temp file.
+    /// **Mutability**: Immutable - pure path computation, no I/O.
+    pub fn layer_partial_path(&self, digest: &str) -> PathBuf {
+        let mut path = self.layer_tarball_path(digest).into_os_string();
+        path.push(".part");
+        PathBuf::from(path)
+    }
+
+    /// Start or resume a staged download for a layer blob.
     ///
-    /// Returns a StagedDownload handle that manages the temp file lifecycle.
+    /// **Mutability**: Mutating - opens the layer's `.part` file for appending.
+    /// The path is content-addressed, so a download interrupted by an error,
+    /// a dropped future or a crash resumes from `staged.offset()` on the next
+    /// attempt. Callers must ensure only one download per digest at a time.
+    ///
+    /// Returns a StagedDownload handle that manages the partial file lifecycle.
     /// Use `staged.file()` to get the file for writing.
     pub async fn stage_layer_download(&self, digest: &str) -> BoxliteResult<StagedDownload> {
         // Extract expected hash from digest
@@ -282,28 +293,75 @@ impl ImageStorage {
             .ok_or_else(|| BoxliteError::Storage("Invalid digest format, expected sha256:".into()))?
             .to_string();
 
-        // Generate random suffix to prevent collision in parallel downloads
-        let random_suffix = uuid::Uuid::new_v4().simple();
-        let filename = digest.replace(':', "-");
-        let staged_path = self
-            .layout
-            .layers_dir()
-            .join(format!("{}.{}.downloading", filename, random_suffix));
-
-        let file = tokio::fs::File::create(&staged_path).await.map_err(|e| {
-            BoxliteError::Storage(format!(
-                "Failed to create temp file {}: {}",
-                staged_path.display(),
-                e
-            ))
-        })?;
+        let staged_path = self.layer_partial_path(digest);
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&staged_path)
+            .await
+            .map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to open partial file {}: {}",
+                    staged_path.display(),
+                    e
+                ))
+            })?;
+        let offset = file
+            .metadata()
+            .await
+            .map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to stat partial file {}: {}",
+                    staged_path.display(),
+                    e
+                ))
+            })?
+            .len();
 
-        Ok(StagedDownload::new(
+        let mut staged = StagedDownload::new(
             staged_path,
             self.layer_tarball_path(digest),
             expected_hash,
             file,
-        ))
+        );
+        staged.offset = offset;
+        Ok(staged)
+    }
+
+    /// Remove abandoned downloads from the layers directory.
+    ///
+    /// **Mutability**: Mutating - deletes `.part` files and leftover
+    /// `.downloading` temp files not modified within `max_age`. Recent
+    /// partials are kept so the next pull can resume them.
+    ///
+    /// Returns the number of files removed.
+    pub fn remove_stale_partials(&self, max_age: std::time::Duration) -> usize {
+        let Ok(entries) = std::fs::read_dir(self.layout.layers_dir()) else {
+            return 0;
+        };
+
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_partial = path
+                .extension()
+                .is_some_and(|ext| ext == "part" || ext == "downloading");
+            if !is_partial {
+                continue;
+            }
+
+            let stale = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > max_age);
+            if stale && std::fs::remove_file(&path).is_ok() {
+                tracing::debug!("Removed stale partial download: {}", path.display());
+                removed += 1;
+            }
+        }
+        removed
     }
 
     // ========================================================================
@@ -453,8 +511,13 @@ impl ImageStorage {
 /// Handle for an in-progress download with atomic commit semantics
 ///
 /// Downloads to a temp file first, then verifies integrity and atomically
-/// moves to the final location. Temp file uses random suffix to prevent
-/// collision in parallel downloads.
+/// moves to the final location. Config temp files use a random suffix to
+/// prevent collision in parallel downloads; layers use a content-addressed
+/// `.part` file that may already hold `offset()` bytes from an earlier
+/// attempt.
+///
+/// Dropping the handle without `commit()` or `abort()` keeps the staged file
+/// on disk, which is what lets an interrupted layer download resume.
 ///
 /// # Example
 /// ```ignore
@@ -472,6 +535,8 @@ pub struct StagedDownload {
     final_path: PathBuf,
     expected_hash: String,
     file: Option<tokio::fs::File>,
+    /// Bytes already in the staged file when it was opened.
+    offset: u64,
 }
 
 impl StagedDownload {
@@ -487,9 +552,28 @@ impl StagedDownload {
             final_path,
             expected_hash,
             file: Some(file),
+            offset: 0,
         }
     }
 
+    /// Bytes already staged by an earlier attempt; new data is appended.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Discard the staged bytes and start over from an empty file.
+    pub async fn restart(&mut self) -> BoxliteResult<()> {
+        self.file().set_len(0).await.map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to truncate {}: {}",
+                self.staged_path.display(),
+                e
+            ))
+        })?;
+        self.offset = 0;
+        Ok(())
+    }
+
     /// Get mutable reference to the file for writing
     pub fn file(&mut self) -> &mut tokio::fs::File {
         self.file.as_mut().expect("file already consumed")
@@ -582,6 +666,7 @@ impl StagedDownload {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sha2::Digest;
 
     #[test]
     fn test_store_new_creates_directories() {
@@ -695,4 +780,87 @@ mod tests {
         std::fs::write(store.layer_tarball_path(&layer2), b"data2").unwrap();
         assert!(store.verify_blobs_exist(&[layer1, layer2]));
     }
+
+    #[test]
+    fn test_layer_partial_path() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ImageStorage::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let path = store.layer_partial_path("sha256:layer1");
+        assert_eq!(
+            path,
+            temp_dir.path().join("layers/sha256-layer1.tar.gz.part")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stage_layer_download_resumes_partial() {
+        use tokio::io::AsyncWriteExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ImageStorage::new(temp_dir.path().to_path_buf()).unwrap();
+        let digest = format!("sha256:{:x}", sha2::Sha256::digest(b"hello world"));
+
+        let mut staged = store.stage_layer_download(&digest).await.unwrap();
+        assert_eq!(staged.offset(), 0);
+        staged.file().write_all(b"hello ").await.unwrap();
+        staged.file().flush().await.unwrap();
+        drop(staged);
+
+        let mut staged = store.stage_layer_download(&digest).await.unwrap();
+        assert_eq!(staged.offset(), 6);
+        staged.file().write_all(b"world").await.unwrap();
+        staged.file().flush().await.unwrap();
+        assert!(staged.commit().await.unwrap());
+
+        assert!(!store.layer_partial_path(&digest).exists());
+        assert_eq!(
+            std::fs::read(store.layer_tarball_path(&digest)).unwrap(),
+            b"hello world"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_staged_download_restart_discards_partial() {
+        use tokio::io::AsyncWriteExt;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ImageStorage::new(temp_dir.path().to_path_buf()).unwrap();
+        let digest = format!("sha256:{:x}", sha2::Sha256::digest(b"fresh"));
+        std::fs::write(store.layer_partial_path(&digest), b"stale bytes").unwrap();
+
+        let mut staged = store.stage_layer_download(&digest).await.unwrap();
+        assert_eq!(staged.offset(), 11);
+        staged.restart().await.unwrap();
+        assert_eq!(staged.offset(), 0);
+        staged.file().write_all(b"fresh").await.unwrap();
+        staged.file().flush().await.unwrap();
+        assert!(staged.commit().await.unwrap());
+    }
+
+    #[test]
+    fn test_remove_stale_partials_keeps_recent() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = ImageStorage::new(temp_dir.path().to_path_buf()).unwrap();
+
+        let old = store.layer_partial_path("sha256:old");
+        let recent = store.layer_partial_path("sha256:recent");
+        let legacy = store.layer_dir().join("sha256-legacy.1234.downloading");
+        let layer = store.layer_tarball_path("sha256:done");
+        for path in [&old, &recent, &legacy, &layer] {
+            std::fs::write(path, b"data").unwrap();
+        }
+        let long_ago = filetime::FileTime::from_unix_time(0, 0);
+        filetime::set_file_mtime(&old, long_ago).unwrap();
+        filetime::set_file_mtime(&legacy, long_ago).unwrap();
+        filetime::set_file_mtime(&layer, long_ago).unwrap();
+
+        let removed = store.remove_stale_partials(std::time::Duration::from_secs(3600));
+
+        assert_eq!(removed, 2);
+        assert!(!old.exists());
+        assert!(!legacy.exists());
+        assert!(recent.exists());
+        assert!(layer.exists());
+    }
 }
@@ -14,18 +14,104 @@
 //! - `layer_extracted()` - Get extracted layer path (extracts if needed)
 
 use crate::db::{CachedImage, Database, ImageIndexStore};
+use crate::images::RegistryStatus;
+use crate::images::blob_cache::SharedBlobCache;
+use crate::images::client::RegistryClient;
+use crate::images::health::RegistryHealth;
 use crate::images::manager::{ImageManifest, LayerInfo};
-use crate::images::storage::ImageStorage;
+use crate::images::progress::{BlobCacheLookup, PullProgress, PullProgressFn};
+use crate::images::storage::{ImageStorage, StagedDownload};
+use crate::images::vulnerability::VulnerabilityReport;
+use crate::runtime::disk_space;
+use crate::runtime::options::RegistrySettings;
 use boxlite_shared::{BoxliteError, BoxliteResult};
-use oci_client::Reference;
+use oci_client::client::BlobResponse;
+use oci_client::errors::{DigestError, OciDistributionError};
 use oci_client::manifest::{
     ImageIndexEntry, OciDescriptor, OciImageIndex, OciImageManifest as ClientOciImageManifest,
 };
 use oci_client::secrets::RegistryAuth;
+use oci_client::{Client, Reference, RegistryOperation};
 use oci_spec::image::MediaType;
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
+use url::Url;
+
+/// Partial layer downloads untouched for this long are deleted, not resumed.
+const PARTIAL_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Referrers inspected when looking for a vulnerability report.
+const MAX_REFERRERS: usize = 16;
+
+/// Report blobs larger than this are skipped.
+const MAX_REPORT_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Artifact types (matched as substrings) that may hold a vulnerability report.
+const REPORT_ARTIFACT_HINTS: &[&str] = &["cyclonedx", "trivy", "grype", "vuln", "in-toto", "dsse"];
+
+fn is_report_artifact(artifact_type: &str) -> bool {
+    let artifact_type = artifact_type.to_ascii_lowercase();
+    REPORT_ARTIFACT_HINTS
+        .iter()
+        .any(|hint| artifact_type.contains(hint))
+}
+
+/// Whether a failed request points at the registry itself (unreachable,
+/// timed out, server error) rather than at the image.
+fn is_registry_fault(error: &OciDistributionError) -> bool {
+    match error {
+        OciDistributionError::RequestError(_) => true,
+        OciDistributionError::ServerError { code, .. } => *code >= 500,
+        _ => false,
+    }
+}
+
+/// Error for content that does not match the digest it was requested by.
+///
+/// Pulls fail on it instead of trying another registry: the content is
+/// pinned, so no registry may serve anything else.
+fn integrity_error(detail: impl std::fmt::Display) -> BoxliteError {
+    BoxliteError::Image(format!("image integrity check failed: {detail}"))
+}
+
+/// Map a failed manifest pull, surfacing a digest mismatch as an integrity error.
+fn manifest_error(what: &str, error: OciDistributionError) -> BoxliteError {
+    match error {
+        OciDistributionError::DigestError(DigestError::VerificationError { expected, actual }) => {
+            integrity_error(format!("{what} expected {expected}, got {actual}"))
+        }
+        e => BoxliteError::Storage(format!("failed to pull {what}: {e}")),
+    }
+}
+
+/// Give `proxy` a pull token for `reference` obtained from the registry
+/// directly, so authentication never goes through the blob cache proxy.
+async fn authorize_proxy(
+    direct: &Client,
+    proxy: &Client,
+    reference: &Reference,
+) -> BoxliteResult<()> {
+    let auth_error = |e| BoxliteError::Storage(format!("failed to authenticate: {e}"));
+    let token = direct
+        .auth(reference, &RegistryAuth::Anonymous, RegistryOperation::Pull)
+        .await
+        .map_err(auth_error)?;
+    if let Some(token) = token {
+        // A bearer token is handed over as is, without a request
+        proxy
+            .auth(
+                reference,
+                &RegistryAuth::Bearer(token),
+                RegistryOperation::Pull,
+            )
+            .await
+            .map_err(auth_error)?;
+    }
+    Ok(())
+}
 
 // ============================================================================
 // INNER STATE (no locking awareness)
@@ -76,13 +162,22 @@ impl ImageStoreInner {
 /// let blob_source = BlobSource::Store(StoreBlobSource::new(storage));
 /// ```
 pub struct ImageStore {
-    /// OCI registry client (immutable, outside lock)
-    client: oci_client::Client,
+    /// OCI registry client gate (immutable, outside lock).
+    /// Refuses every request when the runtime is offline.
+    client: RegistryClient,
     /// Mutable state protected by RwLock
     inner: RwLock<ImageStoreInner>,
     /// Registries to search for unqualified image references.
     /// Tried in order; first successful pull wins.
     registries: Vec<String>,
+    /// Consecutive failures per registry; registries cooling down are
+    /// skipped.
+    health: RegistryHealth,
+    /// One lock per layer digest being downloaded, so concurrent pulls never
+    /// append to the same partial file.
+    layer_locks: std::sync::Mutex<HashMap<String, Weak<Mutex<()>>>>,
+    /// Layer cache shared with other machines, consulted before registries.
+    blob_cache: Option<SharedBlobCache>,
 }
 
 impl std::fmt::Debug for ImageStore {
@@ -98,15 +193,54 @@ impl ImageStore {
     /// * `images_dir` - Directory for image cache
     /// * `db` - Database for image index
     /// * `registries` - Registries to search for unqualified images (tried in order)
-    pub fn new(images_dir: PathBuf, db: Database, registries: Vec<String>) -> BoxliteResult<Self> {
+    /// * `registry_settings` - Timeout, protocol and CA certificates per registry
+    /// * `offline` - Refuse all registry access; serve from cache only
+    pub fn new(
+        images_dir: PathBuf,
+        db: Database,
+        registries: Vec<String>,
+        registry_settings: &HashMap<String, RegistrySettings>,
+        offline: bool,
+    ) -> BoxliteResult<Self> {
+        Self::with_blob_cache(
+            images_dir,
+            db,
+            registries,
+            registry_settings,
+            offline,
+            None,
+            None,
+        )
+    }
+
+    /// Like [`new`](Self::new), fetching blobs through `blob_cache_proxy` and
+    /// sharing layers through `blob_cache_dir` (see `BoxliteOptions`).
+    pub fn with_blob_cache(
+        images_dir: PathBuf,
+        db: Database,
+        registries: Vec<String>,
+        registry_settings: &HashMap<String, RegistrySettings>,
+        offline: bool,
+        blob_cache_proxy: Option<&Url>,
+        blob_cache_dir: Option<PathBuf>,
+    ) -> BoxliteResult<Self> {
         let inner = ImageStoreInner::new(images_dir, db)?;
         Ok(Self {
-            client: oci_client::Client::new(Default::default()),
+            client: RegistryClient::new(offline, registry_settings, blob_cache_proxy)?,
             inner: RwLock::new(inner),
             registries,
+            health: RegistryHealth::default(),
+            layer_locks: Default::default(),
+            blob_cache: blob_cache_dir.map(SharedBlobCache::new),
         })
     }
 
+    /// Health of the configured registries and of every other registry
+    /// contacted so far.
+    pub fn registry_status(&self) -> Vec<RegistryStatus> {
+        self.health.status(&self.registries)
+    }
+
     /// Get shared reference to image storage for BlobSource creation.
     ///
     /// This allows creating `StoreBlobSource` that can outlive the lock.
@@ -145,6 +279,18 @@ impl ImageStore {
     /// Thread-safe: Multiple concurrent pulls of the same image will only
     /// download once; others will get the cached result.
     pub async fn pull(&self, image_ref: &str) -> BoxliteResult<ImageManifest> {
+        self.pull_with_progress(image_ref, None).await
+    }
+
+    /// Pull an image, reporting layer download progress to `progress`.
+    ///
+    /// Cancellation-safe: dropping the future aborts in-flight requests and
+    /// keeps partially downloaded layers, which the next pull resumes.
+    pub async fn pull_with_progress(
+        &self,
+        image_ref: &str,
+        progress: Option<PullProgressFn>,
+    ) -> BoxliteResult<ImageManifest> {
         use super::ReferenceIter;
 
         tracing::debug!(
@@ -153,9 +299,17 @@ impl ImageStore {
             "Starting image pull with registry fallback"
         );
 
-        // Parse image reference and create iterator over registry candidates
-        let candidates = ReferenceIter::new(image_ref, &self.registries)
-            .map_err(|e| BoxliteError::Storage(format!("invalid image reference: {e}")))?;
+        // Parse image reference and resolve registry candidates
+        let candidates: Vec<Reference> = ReferenceIter::new(image_ref, &self.registries)
+            .map_err(|e| BoxliteError::Storage(format!("invalid image reference: {e}")))?
+            .collect();
+
+        // Registries cooling down are skipped, unless all of them are: a
+        // pull never fails without contacting anything
+        let now = std::time::Instant::now();
+        let skip_unhealthy = candidates
+            .iter()
+            .any(|r| self.health.cooldown_left(r.registry(), now).is_none());
 
         let mut errors: Vec<(String, BoxliteError)> = Vec::new();
 
@@ -171,9 +325,17 @@ impl ImageStore {
                 }
             } // Read lock released
 
+            if skip_unhealthy
+                && let Some(reason) = self.health.skip_reason(reference.registry(), now)
+            {
+                tracing::debug!(reference = %ref_str, "Skipping registry: {}", reason);
+                errors.push((ref_str, BoxliteError::Storage(reason)));
+                continue;
+            }
+
             // Slow path: pull from registry
             tracing::info!("Pulling image from registry: {}", ref_str);
-            match self.pull_from_registry(&reference).await {
+            match self.pull_from_registry(&reference, progress.as_ref()).await {
                 Ok(manifest) => {
                     if !errors.is_empty() {
                         tracing::info!(
@@ -185,6 +347,8 @@ impl ImageStore {
                     }
                     return Ok(manifest);
                 }
+                // Only integrity failures are image errors here
+                Err(e @ BoxliteError::Image(_)) => return Err(e),
                 Err(e) => {
                     tracing::debug!(
                         reference = %ref_str,
@@ -196,6 +360,26 @@ impl ImageStore {
             }
         }
 
+        // Offline and nothing cached: surface the dedicated error, not a storage error
+        if !errors.is_empty()
+            && errors
+                .iter()
+                .all(|(_, e)| matches!(e, BoxliteError::OfflineMode(_)))
+        {
+            let hosts: Vec<String> = errors
+                .into_iter()
+                .filter_map(|(_, e)| match e {
+                    BoxliteError::OfflineMode(host) => Some(host),
+                    _ => None,
+                })
+                .collect();
+            return Err(BoxliteError::OfflineMode(format!(
+                "{} (image '{}' is not cached)",
+                hosts.join(", "),
+                image_ref
+            )));
+        }
+
         // All candidates failed - format comprehensive error message
         if errors.is_empty() {
             Err(BoxliteError::Storage(format!(
@@ -209,7 +393,7 @@ impl ImageStore {
                 .collect();
 
             Err(BoxliteError::Storage(format!(
-                "Failed to pull image '{}' after trying {} {}:\n{}",
+                "Failed to pull image '{}' from {} {}:\n{}",
                 image_ref,
                 errors.len(),
                 if errors.len() == 1 {
@@ -313,6 +497,52 @@ impl ImageStore {
         })
     }
 
+    /// Fetch the vulnerability report attached to an image as an OCI referrer.
+    ///
+    /// Looks at the referrers of `manifest_digest` whose artifact type names a
+    /// scanner format (CycloneDX, Trivy, in-toto/DSSE attestations, ...) and
+    /// returns the first one that parses. Reports are not cached: a scanner
+    /// may publish a newer one at any time.
+    ///
+    /// Returns `Ok(None)` if the registry has no report for the image.
+    ///
+    /// # Errors
+    /// - `OfflineMode` when registry access is disabled
+    /// - `Storage` if the registry cannot be queried
+    pub async fn vulnerability_report(
+        &self,
+        image_ref: &str,
+        manifest_digest: &str,
+    ) -> BoxliteResult<Option<VulnerabilityReport>> {
+        use super::ReferenceIter;
+
+        let candidates = ReferenceIter::new(image_ref, &self.registries)
+            .map_err(|e| BoxliteError::Storage(format!("invalid image reference: {e}")))?;
+
+        // The image came from one of the candidates; the first registry that
+        // answers the referrers query is taken to be it
+        let mut last_error = None;
+        for reference in candidates {
+            let subject = reference.clone_with_digest(manifest_digest.to_string());
+            match self.referrer_report(&subject).await {
+                Ok(report) => return Ok(report),
+                Err(e) => {
+                    tracing::debug!(
+                        reference = %subject.whole(),
+                        error = %e,
+                        "Failed to query referrers, trying next"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        match last_error {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+
     /// Get an ImageManifest digest from the descriptor.
     ///
     /// Handles at most two levels (like containerd):
@@ -499,13 +729,33 @@ impl ImageStore {
     ///
     /// This method handles the actual network I/O - manifest pull, layer download, etc.
     /// Lock is released during network I/O to allow other operations.
-    async fn pull_from_registry(&self, reference: &Reference) -> BoxliteResult<ImageManifest> {
+    async fn pull_from_registry(
+        &self,
+        reference: &Reference,
+        progress: Option<&PullProgressFn>,
+    ) -> BoxliteResult<ImageManifest> {
         // Step 1: Pull manifest (no lock needed - uses self.client)
-        let (manifest, manifest_digest_str) = self
+        let registry = reference.registry();
+        let (manifest, manifest_digest_str) = match self
             .client
+            .for_reference(reference)?
             .pull_manifest(reference, &RegistryAuth::Anonymous)
             .await
-            .map_err(|e| BoxliteError::Storage(format!("failed to pull manifest: {e}")))?;
+        {
+            Ok(pulled) => {
+                self.health.record_success(registry);
+                pulled
+            }
+            Err(e) => {
+                if is_registry_fault(&e) {
+                    self.health.record_failure(registry, &e.to_string());
+                } else {
+                    // The registry answered, it just cannot serve this image
+                    self.health.record_success(registry);
+                }
+                return Err(manifest_error("manifest", e));
+            }
+        };
 
         // Step 2: Save manifest (quick write lock)
         {
@@ -521,7 +771,7 @@ impl ImageStore {
             .await?;
 
         // Step 4: Download layers (no lock during download, atomic file writes)
-        self.download_layers(reference, &image_manifest.layers)
+        self.download_layers(reference, &image_manifest.layers, progress)
             .await?;
 
         // Step 5: Download config (no lock during download)
@@ -586,6 +836,7 @@ impl ImageStore {
             .map(|layer| LayerInfo {
                 digest: layer.digest.clone(),
                 media_type: layer.media_type.clone(),
+                size: u64::try_from(layer.size).unwrap_or(0),
             })
             .collect()
     }
@@ -606,10 +857,8 @@ impl ImageStore {
 
         let platform_manifest = self.select_platform_manifest(index, platform_os, platform_arch)?;
 
-        let platform_ref = format!("{}@{}", reference.whole(), platform_manifest.digest);
-        let platform_reference: Reference = platform_ref
-            .parse()
-            .map_err(|e| BoxliteError::Storage(format!("invalid platform reference: {e}")))?;
+        // Pinned by the index, whether the index itself came by tag or digest
+        let platform_reference = reference.clone_with_digest(platform_manifest.digest.clone());
 
         tracing::info!(
             "Pulling platform-specific manifest: {}",
@@ -617,9 +866,10 @@ impl ImageStore {
         );
         let (platform_image, platform_digest) = self
             .client
+            .for_reference(&platform_reference)?
             .pull_manifest(&platform_reference, &RegistryAuth::Anonymous)
             .await
-            .map_err(|e| BoxliteError::Storage(format!("failed to pull platform manifest: {e}")))?;
+            .map_err(|e| manifest_error("platform manifest", e))?;
 
         // Save platform manifest (quick lock)
         {
@@ -699,12 +949,14 @@ impl ImageStore {
         &self,
         reference: &Reference,
         layers: &[LayerInfo],
+        progress: Option<&PullProgressFn>,
     ) -> BoxliteResult<()> {
         use futures::future::join_all;
 
         // Check which layers need downloading (quick read lock)
         let layers_to_download: Vec<_> = {
             let inner = self.inner.read().await;
+            inner.storage.remove_stale_partials(PARTIAL_MAX_AGE);
             let mut to_download = Vec::new();
             for layer in layers {
                 if !inner.storage.has_layer(&layer.digest) {
@@ -743,7 +995,7 @@ impl ImageStore {
         // Download in parallel (no lock held)
         let download_futures = layers_to_download
             .iter()
-            .map(|layer| self.download_layer(reference, layer));
+            .map(|layer| self.download_layer(reference, layer, progress));
 
         let results = join_all(download_futures).await;
 
@@ -754,9 +1006,56 @@ impl ImageStore {
         Ok(())
     }
 
-    async fn download_layer(&self, reference: &Reference, layer: &LayerInfo) -> BoxliteResult<()> {
+    async fn download_layer(
+        &self,
+        reference: &Reference,
+        layer: &LayerInfo,
+        progress: Option<&PullProgressFn>,
+    ) -> BoxliteResult<()> {
         const MAX_RETRIES: u32 = 3;
 
+        let client = self.client.for_reference(reference)?;
+
+        // A concurrent pull of the same layer finishes first; reuse its result
+        let lock = self.layer_lock(&layer.digest);
+        let _guard = lock.lock().await;
+        if self.inner.read().await.storage.has_layer(&layer.digest) {
+            tracing::debug!("Layer downloaded by a concurrent pull: {}", layer.digest);
+            return Ok(());
+        }
+
+        let cache = match &self.blob_cache {
+            Some(blob_cache) => {
+                if self.copy_from_cache(blob_cache, layer, progress).await {
+                    return Ok(());
+                }
+                Some(BlobCacheLookup::Miss)
+            }
+            None => None,
+        };
+
+        // Through the caching proxy first; any failure there falls back to
+        // the registry
+        if let Some(proxy) = self.client.proxied_for(reference) {
+            let proxied = async {
+                authorize_proxy(client, proxy, reference).await?;
+                self.download_attempt(proxy, reference, layer, progress, cache)
+                    .await
+            };
+            match proxied.await {
+                Ok(()) => {
+                    tracing::info!("Downloaded and verified layer via proxy: {}", layer.digest);
+                    self.publish_to_cache(layer).await;
+                    return Ok(());
+                }
+                Err(e) => tracing::warn!(
+                    digest = %layer.digest,
+                    error = %e,
+                    "Blob cache proxy failed, downloading layer from the registry"
+                ),
+            }
+        }
+
         tracing::info!("Downloading layer: {}", layer.digest);
 
         let mut last_error = None;
@@ -771,67 +1070,308 @@ impl ImageStore {
                 );
             }
 
-            // Stage download (quick read lock for path computation)
-            let mut staged = {
-                let inner = self.inner.read().await;
-                match inner.storage.stage_layer_download(&layer.digest).await {
-                    Ok(result) => result,
-                    Err(e) => {
-                        last_error = Some(format!(
-                            "Failed to stage layer {} download: {e}",
-                            layer.digest
-                        ));
-                        continue;
-                    }
+            match self
+                .download_attempt(client, reference, layer, progress, cache)
+                .await
+            {
+                Ok(()) => {
+                    tracing::info!("Downloaded and verified layer: {}", layer.digest);
+                    self.publish_to_cache(layer).await;
+                    return Ok(());
                 }
-            };
+                Err(e) => {
+                    // A failed fetch keeps the partial file: the next attempt
+                    // resumes from it
+                    tracing::warn!("Layer download failed (attempt {}): {}", attempt, e);
+                    last_error = Some(e);
+                }
+            }
+        }
 
-            // Download (no lock)
-            match self
-                .client
-                .pull_blob(
-                    reference,
-                    &OciDescriptor {
+        Err(last_error
+            .unwrap_or_else(|| BoxliteError::Storage("download failed after retries".to_string())))
+    }
+
+    /// Stage, fetch and commit `layer` once with `client`.
+    async fn download_attempt(
+        &self,
+        client: &Client,
+        reference: &Reference,
+        layer: &LayerInfo,
+        progress: Option<&PullProgressFn>,
+        cache: Option<BlobCacheLookup>,
+    ) -> BoxliteResult<()> {
+        // Stage download (quick read lock for path computation)
+        let mut staged = {
+            let inner = self.inner.read().await;
+            inner
+                .storage
+                .stage_layer_download(&layer.digest)
+                .await
+                .map_err(|e| {
+                    BoxliteError::Storage(format!(
+                        "Failed to stage layer {} download: {e}",
+                        layer.digest
+                    ))
+                })?
+        };
+
+        // Download (no lock)
+        Self::fetch_layer(client, reference, layer, &mut staged, progress, cache)
+            .await
+            .map_err(|e| {
+                BoxliteError::Storage(format!("failed to pull layer {}: {e}", layer.digest))
+            })?;
+
+        match staged.commit().await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(integrity_error(format!(
+                "layer content does not match {}",
+                layer.digest
+            ))),
+            Err(e) => Err(BoxliteError::Storage(format!("layer commit error: {e}"))),
+        }
+    }
+
+    /// Copy `layer` from the shared blob cache into the store.
+    ///
+    /// Returns whether an intact copy was found. A cache that fails is
+    /// skipped with a warning, like a miss.
+    async fn copy_from_cache(
+        &self,
+        cache: &SharedBlobCache,
+        layer: &LayerInfo,
+        progress: Option<&PullProgressFn>,
+    ) -> bool {
+        match self.try_copy_from_cache(cache, layer).await {
+            Ok(Some(copied)) => {
+                tracing::info!("Copied layer from shared blob cache: {}", layer.digest);
+                if let Some(progress) = progress {
+                    progress(&PullProgress {
                         digest: layer.digest.clone(),
-                        media_type: layer.media_type.clone(),
-                        size: 0,
-                        urls: None,
-                        annotations: None,
-                    },
-                    staged.file(),
-                )
+                        downloaded: copied,
+                        total: layer.size,
+                        resumed: 0,
+                        cache: Some(BlobCacheLookup::Hit),
+                    });
+                }
+                true
+            }
+            Ok(None) => false,
+            Err(e) => {
+                tracing::warn!(
+                    digest = %layer.digest,
+                    error = %e,
+                    "Shared blob cache failed, downloading layer from the registry"
+                );
+                false
+            }
+        }
+    }
+
+    /// Bytes of `layer` copied from `cache`, or None if it holds no intact
+    /// copy.
+    async fn try_copy_from_cache(
+        &self,
+        cache: &SharedBlobCache,
+        layer: &LayerInfo,
+    ) -> BoxliteResult<Option<u64>> {
+        use tokio::io::AsyncWriteExt;
+
+        let Some(mut cached) = cache.open(&layer.digest).await? else {
+            return Ok(None);
+        };
+        let mut staged = {
+            let inner = self.inner.read().await;
+            inner.storage.stage_layer_download(&layer.digest).await?
+        };
+        // The cached copy supersedes any partial download
+        staged.restart().await?;
+
+        let copied = match tokio::io::copy(&mut cached, staged.file()).await {
+            Ok(copied) => staged.file().flush().await.map(|()| copied),
+            Err(e) => Err(e),
+        };
+        let copied = match copied {
+            Ok(copied) => copied,
+            Err(e) => {
+                staged.abort().await;
+                return Err(BoxliteError::Storage(format!(
+                    "failed to copy cached layer {}: {e}",
+                    layer.digest
+                )));
+            }
+        };
+
+        if !staged.commit().await? {
+            // Replaced by the download that follows
+            tracing::warn!(
+                "Cached layer does not match its digest, ignoring it: {}",
+                layer.digest
+            );
+            return Ok(None);
+        }
+        Ok(Some(copied))
+    }
+
+    /// Share a layer just downloaded through the shared blob cache, if any.
+    /// A failure only costs other machines a download.
+    async fn publish_to_cache(&self, layer: &LayerInfo) {
+        let Some(cache) = &self.blob_cache else {
+            return;
+        };
+        let path = self
+            .inner
+            .read()
+            .await
+            .storage
+            .layer_tarball_path(&layer.digest);
+        if let Err(e) = cache.publish(&layer.digest, &path).await {
+            tracing::warn!(
+                digest = %layer.digest,
+                error = %e,
+                "Failed to publish layer to shared blob cache"
+            );
+        }
+    }
+
+    /// Get the download lock for a layer digest, creating it if needed.
+    fn layer_lock(&self, digest: &str) -> Arc<Mutex<()>> {
+        let mut locks = self
+            .layer_locks
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        locks.retain(|_, lock| lock.strong_count() > 0);
+        if let Some(lock) = locks.get(digest).and_then(Weak::upgrade) {
+            return lock;
+        }
+        let lock = Arc::new(Mutex::new(()));
+        locks.insert(digest.to_string(), Arc::downgrade(&lock));
+        lock
+    }
+
+    /// Stream a layer blob into `staged`, resuming after the bytes it holds.
+    ///
+    /// Asks the registry for the remaining range; a registry that answers with
+    /// the whole blob restarts the download from zero.
+    async fn fetch_layer(
+        client: &Client,
+        reference: &Reference,
+        layer: &LayerInfo,
+        staged: &mut StagedDownload,
+        progress: Option<&PullProgressFn>,
+        cache: Option<BlobCacheLookup>,
+    ) -> BoxliteResult<()> {
+        use futures::StreamExt;
+        use tokio::io::AsyncWriteExt;
+
+        let total = layer.size;
+        if total > 0 && staged.offset() > total {
+            tracing::warn!(
+                "Partial download of {} is larger than the layer, discarding",
+                layer.digest
+            );
+            staged.restart().await?;
+        }
+
+        let report = |downloaded: u64, resumed: u64| {
+            if let Some(progress) = progress {
+                progress(&PullProgress {
+                    digest: layer.digest.clone(),
+                    downloaded,
+                    total,
+                    resumed,
+                    cache,
+                });
+            }
+        };
+
+        if total > 0 && staged.offset() == total {
+            // Fully downloaded before an interruption; only commit is left
+            report(total, total);
+            return Ok(());
+        }
+
+        let descriptor = OciDescriptor {
+            digest: layer.digest.clone(),
+            media_type: layer.media_type.clone(),
+            size: i64::try_from(total).unwrap_or(0),
+            urls: None,
+            annotations: None,
+        };
+        let pull_err = |e| BoxliteError::Storage(format!("failed to pull layer: {e}"));
+
+        let mut stream = if staged.offset() == 0 {
+            client
+                .pull_blob_stream(reference, &descriptor)
                 .await
+                .map_err(pull_err)?
+        } else {
+            match client
+                .pull_blob_stream_partial(reference, &descriptor, staged.offset(), None)
+                .await
+                .map_err(pull_err)?
             {
-                Ok(_) => match staged.commit().await {
-                    Ok(true) => {
-                        tracing::info!("Downloaded and verified layer: {}", layer.digest);
-                        return Ok(());
-                    }
-                    Ok(false) => {
-                        tracing::warn!(
-                            "Layer integrity check failed (attempt {}): hash mismatch for {}",
-                            attempt,
-                            layer.digest
-                        );
-                        last_error =
-                            Some("layer integrity verification failed: hash mismatch".to_string());
-                    }
-                    Err(e) => {
-                        tracing::warn!("Layer commit error (attempt {}): {}", attempt, e);
-                        last_error = Some(format!("layer commit error: {e}"));
-                    }
-                },
+                BlobResponse::Partial(stream) => {
+                    tracing::info!(
+                        "Resuming layer {} at byte {}",
+                        layer.digest,
+                        staged.offset()
+                    );
+                    stream
+                }
+                BlobResponse::Full(stream) => {
+                    tracing::info!(
+                        "Registry does not support range requests, restarting layer {}",
+                        layer.digest
+                    );
+                    staged.restart().await?;
+                    stream
+                }
+            }
+        };
+
+        let resumed = staged.offset();
+        let mut downloaded = resumed;
+        report(downloaded, resumed);
+
+        let mut streamed = Ok(());
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
                 Err(e) => {
-                    tracing::warn!("Layer download failed (attempt {}): {}", attempt, e);
-                    last_error = Some(format!("failed to pull layer {}: {e}", layer.digest));
-                    staged.abort().await;
+                    streamed = Err(BoxliteError::Storage(format!(
+                        "connection lost after {} bytes: {e}",
+                        downloaded
+                    )));
+                    break;
                 }
+            };
+            if let Err(e) = staged.file().write_all(&chunk).await {
+                if disk_space::is_out_of_space(&e) {
+                    // A partial file that filled the disk can't be resumed; free it
+                    let _ = staged.restart().await;
+                    return Err(BoxliteError::Storage(format!(
+                        "no space left to download layer {}: {e}",
+                        layer.digest
+                    )));
+                }
+                streamed = Err(BoxliteError::Storage(format!(
+                    "failed to write layer {}: {e}",
+                    layer.digest
+                )));
+                break;
             }
+            downloaded += chunk.len() as u64;
+            report(downloaded, resumed);
         }
 
-        Err(BoxliteError::Storage(last_error.unwrap_or_else(|| {
-            "download failed after retries".to_string()
-        })))
+        // Settle buffered writes so the partial file length matches what was
+        // received, whether or not the stream completed
+        staged.file().flush().await.map_err(|e| {
+            BoxliteError::Storage(format!("failed to write layer {}: {e}", layer.digest))
+        })?;
+        streamed
     }
 
     async fn download_config(
@@ -850,6 +1390,33 @@ impl ImageStore {
 
         tracing::debug!("Downloading config blob: {}", config_digest);
 
+        let client = self.client.for_reference(reference)?;
+
+        if let Some(proxy) = self.client.proxied_for(reference) {
+            let proxied = async {
+                authorize_proxy(client, proxy, reference).await?;
+                self.fetch_config(proxy, reference, config_digest).await
+            };
+            match proxied.await {
+                Ok(()) => return Ok(()),
+                Err(e) => tracing::warn!(
+                    digest = %config_digest,
+                    error = %e,
+                    "Blob cache proxy failed, downloading config from the registry"
+                ),
+            }
+        }
+
+        self.fetch_config(client, reference, config_digest).await
+    }
+
+    /// Download, verify and commit the config blob with `client`.
+    async fn fetch_config(
+        &self,
+        client: &Client,
+        reference: &Reference,
+        config_digest: &str,
+    ) -> BoxliteResult<()> {
         // Start staged download (quick read lock)
         let mut staged = {
             let inner = self.inner.read().await;
@@ -857,8 +1424,7 @@ impl ImageStore {
         };
 
         // Download to temp file (no lock)
-        if let Err(e) = self
-            .client
+        if let Err(e) = client
             .pull_blob(
                 reference,
                 &OciDescriptor {
@@ -878,15 +1444,70 @@ impl ImageStore {
 
         // Verify and commit (atomic move to final location)
         if !staged.commit().await? {
-            return Err(BoxliteError::Storage(format!(
-                "Config blob verification failed for {}",
-                config_digest
+            return Err(integrity_error(format!(
+                "config content does not match {config_digest}"
             )));
         }
 
         Ok(())
     }
 
+    /// First parseable vulnerability report among the referrers of `subject`.
+    async fn referrer_report(
+        &self,
+        subject: &Reference,
+    ) -> BoxliteResult<Option<VulnerabilityReport>> {
+        let client = self.client.for_reference(subject)?;
+        let referrers = client
+            .pull_referrers(subject, None)
+            .await
+            .map_err(|e| BoxliteError::Storage(format!("failed to pull referrers: {e}")))?;
+
+        for entry in referrers.manifests.iter().take(MAX_REFERRERS) {
+            let artifact = subject.clone_with_digest(entry.digest.clone());
+            let (manifest, _) = match client
+                .pull_image_manifest(&artifact, &RegistryAuth::Anonymous)
+                .await
+            {
+                Ok(pulled) => pulled,
+                Err(e) => {
+                    tracing::debug!(digest = %entry.digest, error = %e, "Skipping referrer");
+                    continue;
+                }
+            };
+
+            let artifact_type = manifest
+                .artifact_type
+                .as_deref()
+                .unwrap_or(&manifest.config.media_type);
+            if !is_report_artifact(artifact_type) {
+                continue;
+            }
+
+            for layer in &manifest.layers {
+                if u64::try_from(layer.size).unwrap_or(u64::MAX) > MAX_REPORT_BYTES {
+                    continue;
+                }
+                let mut blob = Vec::new();
+                if let Err(e) = client.pull_blob(&artifact, layer, &mut blob).await {
+                    tracing::debug!(digest = %layer.digest, error = %e, "Skipping report blob");
+                    continue;
+                }
+                if let Some(report) = VulnerabilityReport::parse(&blob) {
+                    tracing::debug!(
+                        subject = %subject.whole(),
+                        artifact_type,
+                        findings = report.vulnerabilities.len(),
+                        "Found vulnerability report"
+                    );
+                    return Ok(Some(report));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Parse OCI image manifest from file path.
     ///
     /// Reads an OCI ImageManifest from the given path and extracts
@@ -917,6 +1538,7 @@ impl ImageStore {
             .map(|layer| LayerInfo {
                 digest: layer.digest.clone(),
                 media_type: layer.media_type.clone(),
+                size: u64::try_from(layer.size).unwrap_or(0),
             })
             .collect();
 
@@ -1071,7 +1693,8 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store =
+            ImageStore::new(images_dir.clone(), db, vec![], &HashMap::new(), false).unwrap();
 
         // Load from local
         let manifest = store.load_from_local(bundle_dir.clone()).await.unwrap();
@@ -1095,7 +1718,8 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store =
+            ImageStore::new(images_dir.clone(), db, vec![], &HashMap::new(), false).unwrap();
 
         // Load from local
         let _manifest = store.load_from_local(bundle_dir.clone()).await.unwrap();
@@ -1130,7 +1754,8 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store =
+            ImageStore::new(images_dir.clone(), db, vec![], &HashMap::new(), false).unwrap();
 
         // Load should fail
         let result = store.load_from_local(bundle_dir).await;
@@ -1156,7 +1781,8 @@ mod tests {
 
         // Create store
         let db = Database::open(&db_path).unwrap();
-        let store = ImageStore::new(images_dir.clone(), db, vec![]).unwrap();
+        let store =
+            ImageStore::new(images_dir.clone(), db, vec![], &HashMap::new(), false).unwrap();
 
         // Load should fail
         let result = store.load_from_local(bundle_dir).await;
@@ -1164,4 +1790,706 @@ mod tests {
         let err = result.unwrap_err().to_string();
         assert!(err.contains("index.json"));
     }
+
+    /// Seed the store with a cached image under `reference`, as a previous pull would.
+    async fn seed_cached_image(store: &ImageStore, reference: &str) {
+        let layer_digest =
+            "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+        let config_digest =
+            "sha256:2222222222222222222222222222222222222222222222222222222222222222";
+        let manifest_digest =
+            "sha256:3333333333333333333333333333333333333333333333333333333333333333";
+
+        let manifest_json = format!(
+            r#"{{
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "config": {{
+                "mediaType": "application/vnd.oci.image.config.v1+json",
+                "digest": "{}",
+                "size": 2
+            }},
+            "layers": [
+                {{
+                    "mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+                    "digest": "{}",
+                    "size": 4
+                }}
+            ]
+        }}"#,
+            config_digest, layer_digest
+        );
+        let manifest: oci_client::manifest::OciManifest =
+            serde_json::from_str(&manifest_json).unwrap();
+
+        let inner = store.inner.read().await;
+        inner
+            .storage
+            .save_manifest(&manifest, manifest_digest)
+            .unwrap();
+        let layer_path = inner.storage.layer_tarball_path(layer_digest);
+        std::fs::create_dir_all(layer_path.parent().unwrap()).unwrap();
+        std::fs::write(&layer_path, b"data").unwrap();
+        let config_path = inner.storage.config_path(config_digest);
+        std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        std::fs::write(&config_path, b"{}").unwrap();
+        inner
+            .index
+            .upsert(
+                reference,
+                &CachedImage {
+                    manifest_digest: manifest_digest.to_string(),
+                    config_digest: config_digest.to_string(),
+                    layers: vec![layer_digest.to_string()],
+                    cached_at: chrono::Utc::now().to_rfc3339(),
+                    complete: true,
+                },
+            )
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_offline_pull_uncached_fails_with_offline_mode() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let store = ImageStore::new(
+            temp_dir.path().join("images"),
+            db,
+            vec![],
+            &HashMap::new(),
+            true,
+        )
+        .unwrap();
+
+        let err = store.pull("ghcr.io/acme/tool:v1").await.unwrap_err();
+        match err {
+            BoxliteError::OfflineMode(msg) => {
+                assert!(msg.contains("ghcr.io"), "should name the host: {msg}");
+                assert!(msg.contains("ghcr.io/acme/tool:v1"));
+            }
+            other => panic!("expected OfflineMode, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_offline_pull_cached_image_succeeds() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let store = ImageStore::new(
+            temp_dir.path().join("images"),
+            db,
+            vec![],
+            &HashMap::new(),
+            true,
+        )
+        .unwrap();
+
+        seed_cached_image(&store, "docker.io/library/alpine:latest").await;
+
+        let manifest = store.pull("alpine:latest").await.unwrap();
+        assert_eq!(manifest.layers.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_offline_pull_falls_through_registries_to_cache() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db = Database::open(&temp_dir.path().join("test.db")).unwrap();
+        let registries = vec!["ghcr.io".to_string(), "docker.io".to_string()];
+        let store = ImageStore::new(
+            temp_dir.path().join("images"),
+            db,
+            registries,
+            &HashMap::new(),
+            true,
+        )
+        .unwrap();
+
+        // Only the second candidate is cached; the first must not abort resolution.
+        seed_cached_image(&store, "docker.io/library/alpine:latest").await;
+
+        assert!(store.pull("alpine:latest").await.is_ok());
+    }
+
+    // ========================================================================
+    // Resumable downloads against a mock registry
+    // ========================================================================
+
+    const LAYER_SIZE: usize = 256 * 1024;
+    /// Where an interrupted layer response stops: 62.5% of the layer.
+    const CUT_AT: usize = LAYER_SIZE * 5 / 8;
+
+    /// What happens to the first full-layer response.
+    #[derive(Clone, Copy)]
+    enum Interrupt {
+        /// Close the connection after `CUT_AT` bytes.
+        Drop,
+        /// Send `CUT_AT` bytes, then hang without closing.
+        Stall,
+    }
+
+    struct MockState {
+        manifest: Vec<u8>,
+        manifest_digest: String,
+        layer_digest: String,
+        blobs: HashMap<String, Vec<u8>>,
+        supports_range: bool,
+        interrupt: std::sync::Mutex<Option<Interrupt>>,
+        /// Start offsets of the range requests received for the layer.
+        range_starts: std::sync::Mutex<Vec<u64>>,
+        /// Request targets received, absolute URLs when acting as a proxy.
+        requests: std::sync::Mutex<Vec<String>>,
+    }
+
+    /// Minimal OCI registry serving `test/resume:v1` over plain HTTP.
+    struct MockRegistry {
+        addr: std::net::SocketAddr,
+        state: Arc<MockState>,
+    }
+
+    fn sha256_digest(data: &[u8]) -> String {
+        use sha2::Digest;
+        format!("sha256:{:x}", sha2::Sha256::digest(data))
+    }
+
+    impl MockRegistry {
+        async fn start(supports_range: bool, interrupt: Option<Interrupt>) -> Self {
+            let layer: Vec<u8> = (0..LAYER_SIZE).map(|i| (i * 7 % 251) as u8).collect();
+            let config = br#"{"architecture":"amd64","os":"linux","rootfs":{"type":"layers","diff_ids":[]}}"#.to_vec();
+            let layer_digest = sha256_digest(&layer);
+            let config_digest = sha256_digest(&config);
+
+            let manifest = serde_json::json!({
+                "schemaVersion": 2,
+                "mediaType": "application/vnd.oci.image.manifest.v1+json",
+                "config": {
+                    "mediaType": "application/vnd.oci.image.config.v1+json",
+                    "digest": config_digest,
+                    "size": config.len(),
+                },
+                "layers": [{
+                    "mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+                    "digest": layer_digest,
+                    "size": layer.len(),
+                }],
+            })
+            .to_string()
+            .into_bytes();
+
+            let state = Arc::new(MockState {
+                manifest_digest: sha256_digest(&manifest),
+                manifest,
+                layer_digest: layer_digest.clone(),
+                blobs: HashMap::from([(layer_digest, layer), (config_digest, config)]),
+                supports_range,
+                interrupt: std::sync::Mutex::new(interrupt),
+                range_starts: Default::default(),
+                requests: Default::default(),
+            });
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let server_state = Arc::clone(&state);
+            tokio::spawn(async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    tokio::spawn(Self::serve(socket, Arc::clone(&server_state)));
+                }
+            });
+            Self { addr, state }
+        }
+
+        fn image(&self) -> String {
+            format!("{}/test/resume:v1", self.addr)
+        }
+
+        fn range_starts(&self) -> Vec<u64> {
+            self.state.range_starts.lock().unwrap().clone()
+        }
+
+        /// Requests received for the layer blob.
+        fn layer_requests(&self) -> Vec<String> {
+            let requests = self.state.requests.lock().unwrap();
+            requests
+                .iter()
+                .filter(|target| target.ends_with(&self.state.layer_digest))
+                .cloned()
+                .collect()
+        }
+
+        /// Answer one request, then close the connection.
+        async fn serve(mut socket: tokio::net::TcpStream, state: Arc<MockState>) {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let request = String::from_utf8_lossy(&request).into_owned();
+            let path = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+            state.requests.lock().unwrap().push(path.clone());
+            let range_start = request.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                if !name.eq_ignore_ascii_case("range") {
+                    return None;
+                }
+                value
+                    .trim()
+                    .strip_prefix("bytes=")?
+                    .strip_suffix('-')?
+                    .parse::<u64>()
+                    .ok()
+            });
+
+            let respond = |status: &str, headers: &[(&str, String)], len: usize| {
+                let mut head =
+                    format!("HTTP/1.1 {status}\r\nContent-Length: {len}\r\nConnection: close\r\n");
+                for (name, value) in headers {
+                    head.push_str(&format!("{name}: {value}\r\n"));
+                }
+                head.push_str("\r\n");
+                head.into_bytes()
+            };
+
+            let (head, body, interrupt): (Vec<u8>, &[u8], Option<Interrupt>) = if path == "/v2/" {
+                (respond("200 OK", &[], 2), &b"{}"[..], None)
+            } else if path.ends_with("/manifests/v1") {
+                let headers = [
+                    (
+                        "Content-Type",
+                        "application/vnd.oci.image.manifest.v1+json".to_string(),
+                    ),
+                    ("Docker-Content-Digest", state.manifest_digest.clone()),
+                ];
+                let head = respond("200 OK", &headers, state.manifest.len());
+                (head, state.manifest.as_slice(), None)
+            } else if let Some(blob) = path
+                .rsplit_once("/blobs/")
+                .and_then(|(_, digest)| state.blobs.get(digest))
+            {
+                let is_layer = path.ends_with(&state.layer_digest);
+                match range_start {
+                    Some(start) if is_layer && state.supports_range => {
+                        state.range_starts.lock().unwrap().push(start);
+                        let body = &blob[start as usize..];
+                        let content_range =
+                            format!("bytes {}-{}/{}", start, blob.len() - 1, blob.len());
+                        let head = respond(
+                            "206 Partial Content",
+                            &[("Content-Range", content_range)],
+                            body.len(),
+                        );
+                        (head, body, None)
+                    }
+                    _ => {
+                        let interrupt = if is_layer {
+                            state.interrupt.lock().unwrap().take()
+                        } else {
+                            None
+                        };
+                        (
+                            respond("200 OK", &[], blob.len()),
+                            blob.as_slice(),
+                            interrupt,
+                        )
+                    }
+                }
+            } else {
+                (respond("404 Not Found", &[], 0), &b""[..], None)
+            };
+
+            if socket.write_all(&head).await.is_err() {
+                return;
+            }
+            match interrupt {
+                None => {
+                    let _ = socket.write_all(body).await;
+                }
+                Some(Interrupt::Drop) => {
+                    let _ = socket.write_all(&body[..CUT_AT]).await;
+                }
+                Some(Interrupt::Stall) => {
+                    let _ = socket.write_all(&body[..CUT_AT]).await;
+                    std::future::pending::<()>().await;
+                }
+            }
+        }
+    }
+
+    fn mock_store(dir: &Path, registry: &MockRegistry) -> ImageStore {
+        plain_http_store(dir, vec![], &[registry.addr.to_string()])
+    }
+
+    /// Store searching `registries` that talks plain HTTP to `hosts`.
+    fn plain_http_store(dir: &Path, registries: Vec<String>, hosts: &[String]) -> ImageStore {
+        let db = Database::open(&dir.join("test.db")).unwrap();
+        ImageStore::new(
+            dir.join("images"),
+            db,
+            registries,
+            &plain_http_settings(hosts),
+            false,
+        )
+        .unwrap()
+    }
+
+    /// Store for `registry` fetching blobs through `proxy` and sharing
+    /// layers through `cache_dir`.
+    fn caching_store(
+        dir: &Path,
+        registry: &MockRegistry,
+        proxy: Option<&Url>,
+        cache_dir: Option<&Path>,
+    ) -> ImageStore {
+        let db = Database::open(&dir.join("test.db")).unwrap();
+        ImageStore::with_blob_cache(
+            dir.join("images"),
+            db,
+            vec![],
+            &plain_http_settings(&[registry.addr.to_string()]),
+            false,
+            proxy,
+            cache_dir.map(Path::to_path_buf),
+        )
+        .unwrap()
+    }
+
+    fn plain_http_settings(hosts: &[String]) -> HashMap<String, RegistrySettings> {
+        hosts
+            .iter()
+            .map(|host| {
+                let settings = RegistrySettings {
+                    insecure: true,
+                    timeout: Some(Duration::from_secs(5)),
+                    ..Default::default()
+                };
+                (host.clone(), settings)
+            })
+            .collect()
+    }
+
+    /// Address nothing listens on, so connections are refused.
+    async fn dead_registry() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().to_string()
+    }
+
+    /// Progress callback that records every event.
+    fn recording_progress() -> (PullProgressFn, Arc<std::sync::Mutex<Vec<PullProgress>>>) {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        let progress: PullProgressFn = Arc::new(move |p: &PullProgress| {
+            sink.lock().unwrap().push(p.clone());
+        });
+        (progress, events)
+    }
+
+    async fn assert_layer_committed(store: &ImageStore, registry: &MockRegistry) {
+        let digest = &registry.state.layer_digest;
+        let storage = store.storage().await;
+        assert!(storage.verify_layer(digest).await.unwrap());
+        assert!(!storage.layer_partial_path(digest).exists());
+    }
+
+    #[tokio::test]
+    async fn test_pull_resumes_layer_after_connection_drop() {
+        let registry = MockRegistry::start(true, Some(Interrupt::Drop)).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = mock_store(temp_dir.path(), &registry);
+        let (progress, events) = recording_progress();
+
+        store
+            .pull_with_progress(&registry.image(), Some(progress))
+            .await
+            .unwrap();
+
+        assert_layer_committed(&store, &registry).await;
+        assert_eq!(registry.range_starts(), vec![CUT_AT as u64]);
+
+        let events = events.lock().unwrap();
+        let resumed = events
+            .iter()
+            .find(|p| p.resumed > 0)
+            .expect("no resume reported");
+        assert_eq!(resumed.resumed, CUT_AT as u64);
+        assert_eq!(resumed.total, LAYER_SIZE as u64);
+        assert_eq!(resumed.resumed_percent(), Some(62));
+        let last = events.last().unwrap();
+        assert_eq!(last.downloaded, LAYER_SIZE as u64);
+    }
+
+    #[tokio::test]
+    async fn test_pull_restarts_layer_when_range_unsupported() {
+        let registry = MockRegistry::start(false, Some(Interrupt::Drop)).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = mock_store(temp_dir.path(), &registry);
+        let (progress, events) = recording_progress();
+
+        store
+            .pull_with_progress(&registry.image(), Some(progress))
+            .await
+            .unwrap();
+
+        assert_layer_committed(&store, &registry).await;
+        assert!(registry.range_starts().is_empty());
+        assert!(events.lock().unwrap().iter().all(|p| p.resumed == 0));
+    }
+
+    #[tokio::test]
+    async fn test_pull_discards_corrupt_partial() {
+        let registry = MockRegistry::start(true, None).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = mock_store(temp_dir.path(), &registry);
+        let partial = store
+            .storage()
+            .await
+            .layer_partial_path(&registry.state.layer_digest);
+        std::fs::write(&partial, b"not a prefix of the layer").unwrap();
+
+        store.pull(&registry.image()).await.unwrap();
+
+        assert_layer_committed(&store, &registry).await;
+    }
+
+    #[tokio::test]
+    async fn test_dropped_pull_keeps_partial_for_next_pull() {
+        let registry = MockRegistry::start(true, Some(Interrupt::Stall)).await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = mock_store(temp_dir.path(), &registry);
+        let partial = store
+            .storage()
+            .await
+            .layer_partial_path(&registry.state.layer_digest);
+
+        // Cancel the pull once the stalled layer has received CUT_AT bytes
+        let (tx, mut rx) = tokio::sync::watch::channel(0u64);
+        let progress: PullProgressFn = Arc::new(move |p: &PullProgress| {
+            let _ = tx.send(p.downloaded);
+        });
+        let image = registry.image();
+        tokio::select! {
+            result = store.pull_with_progress(&image, Some(progress)) => {
+                panic!("pull finished despite the stalled layer: {:?}", result.map(|_| ()));
+            }
+            _ = rx.wait_for(|downloaded| *downloaded >= CUT_AT as u64) => {}
+        }
+
+        // The write of the last chunk may still be settling
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(5);
+        while std::fs::metadata(&partial).map(|m| m.len()).unwrap_or(0) < CUT_AT as u64 {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "partial file not kept"
+            );
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(
+            !store
+                .storage()
+                .await
+                .has_layer(&registry.state.layer_digest)
+        );
+
+        let (progress, events) = recording_progress();
+        store
+            .pull_with_progress(&registry.image(), Some(progress))
+            .await
+            .unwrap();
+
+        assert_layer_committed(&store, &registry).await;
+        assert_eq!(registry.range_starts(), vec![CUT_AT as u64]);
+        assert!(
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .all(|p| p.resumed == CUT_AT as u64)
+        );
+    }
+
+    // ========================================================================
+    // Registry failover
+    // ========================================================================
+
+    fn status_of(store: &ImageStore, registry: &str) -> RegistryStatus {
+        store
+            .registry_status()
+            .into_iter()
+            .find(|s| s.registry == registry)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_pull_fails_over_and_cools_down_dead_mirror() {
+        use crate::images::health::FAILURE_THRESHOLD;
+
+        let registry = MockRegistry::start(true, None).await;
+        let dead = dead_registry().await;
+        let live = registry.addr.to_string();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = plain_http_store(
+            temp_dir.path(),
+            vec![dead.clone(), live.clone()],
+            &[dead.clone(), live.clone()],
+        );
+
+        for _ in 0..FAILURE_THRESHOLD {
+            store.pull("test/resume:v1").await.unwrap();
+        }
+        let status = status_of(&store, &dead);
+        assert_eq!(status.consecutive_failures, FAILURE_THRESHOLD);
+        assert!(!status.is_available());
+        assert!(status.last_error.is_some());
+        assert!(status_of(&store, &live).last_success.is_some());
+
+        // Cooling down: the next pull does not contact the dead mirror
+        store.pull("test/resume:v1").await.unwrap();
+        assert_eq!(
+            status_of(&store, &dead).consecutive_failures,
+            FAILURE_THRESHOLD
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_error_lists_every_registry() {
+        use crate::images::health::FAILURE_THRESHOLD;
+
+        let first = dead_registry().await;
+        let second = dead_registry().await;
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = plain_http_store(
+            temp_dir.path(),
+            vec![first.clone(), second.clone()],
+            &[first.clone(), second.clone()],
+        );
+
+        let err = store.pull("test/resume:v1").await.unwrap_err().to_string();
+        assert!(err.contains("from 2 registries"), "got {err}");
+        assert!(err.contains(&first) && err.contains(&second), "got {err}");
+
+        // With every registry cooling down, pulls still try them all
+        for _ in 1..=FAILURE_THRESHOLD {
+            store.pull("test/resume:v1").await.unwrap_err();
+        }
+        assert_eq!(
+            status_of(&store, &first).consecutive_failures,
+            FAILURE_THRESHOLD + 1
+        );
+        assert_eq!(
+            status_of(&store, &second).consecutive_failures,
+            FAILURE_THRESHOLD + 1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_fetches_blobs_through_proxy() {
+        let registry = MockRegistry::start(true, None).await;
+        let proxy = MockRegistry::start(true, None).await;
+        let proxy_url: Url = format!("http://{}", proxy.addr).parse().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = caching_store(temp_dir.path(), &registry, Some(&proxy_url), None);
+
+        store.pull(&registry.image()).await.unwrap();
+        assert_layer_committed(&store, &registry).await;
+
+        // Blobs went through the proxy, addressed to the registry
+        let proxied = proxy.layer_requests();
+        assert_eq!(proxied.len(), 1);
+        assert!(
+            proxied[0].contains(&registry.addr.to_string()),
+            "got {proxied:?}"
+        );
+        assert!(registry.layer_requests().is_empty());
+        // Manifests did not
+        let proxy_requests = proxy.state.requests.lock().unwrap().clone();
+        assert!(
+            proxy_requests.iter().all(|r| r.contains("/blobs/")),
+            "got {proxy_requests:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_falls_back_to_registry_when_proxy_is_down() {
+        let registry = MockRegistry::start(true, None).await;
+        let proxy_url: Url = format!("http://{}", dead_registry().await).parse().unwrap();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = caching_store(temp_dir.path(), &registry, Some(&proxy_url), None);
+
+        store.pull(&registry.image()).await.unwrap();
+        assert_layer_committed(&store, &registry).await;
+        assert_eq!(registry.layer_requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_shared_cache_serves_layers_to_other_machines() {
+        let registry = MockRegistry::start(true, None).await;
+        let shared = tempfile::tempdir().unwrap();
+        let first_dir = tempfile::tempdir().unwrap();
+        let second_dir = tempfile::tempdir().unwrap();
+
+        let first = caching_store(first_dir.path(), &registry, None, Some(shared.path()));
+        let (progress, events) = recording_progress();
+        first
+            .pull_with_progress(&registry.image(), Some(progress))
+            .await
+            .unwrap();
+        assert!(
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .all(|p| p.cache == Some(BlobCacheLookup::Miss))
+        );
+
+        let second = caching_store(second_dir.path(), &registry, None, Some(shared.path()));
+        let (progress, events) = recording_progress();
+        second
+            .pull_with_progress(&registry.image(), Some(progress))
+            .await
+            .unwrap();
+        assert_layer_committed(&second, &registry).await;
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].cache, Some(BlobCacheLookup::Hit));
+        assert_eq!(events[0].downloaded, LAYER_SIZE as u64);
+
+        // Only the first machine downloaded the layer
+        assert_eq!(registry.layer_requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_shared_cache_entry_is_downloaded_and_replaced() {
+        let registry = MockRegistry::start(true, None).await;
+        let shared = tempfile::tempdir().unwrap();
+        let digest = registry.state.layer_digest.clone();
+        let entry = shared
+            .path()
+            .join("sha256")
+            .join(digest.strip_prefix("sha256:").unwrap());
+        std::fs::create_dir_all(entry.parent().unwrap()).unwrap();
+        std::fs::write(&entry, b"not the layer").unwrap();
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = caching_store(temp_dir.path(), &registry, None, Some(shared.path()));
+        let (progress, events) = recording_progress();
+        store
+            .pull_with_progress(&registry.image(), Some(progress))
+            .await
+            .unwrap();
+
+        assert_layer_committed(&store, &registry).await;
+        assert!(
+            events
+                .lock()
+                .unwrap()
+                .iter()
+                .all(|p| p.cache == Some(BlobCacheLookup::Miss))
+        );
+        assert_eq!(
+            std::fs::read(&entry).unwrap(),
+            registry.state.blobs[&digest]
+        );
+    }
 }
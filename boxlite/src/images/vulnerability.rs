@@ -0,0 +1,268 @@
+//! Vulnerability reports attached to images.
+//!
+//! Scanners publish their results next to an image as OCI referrers. This
+//! module turns the formats commonly found there into a
+//! [`VulnerabilityReport`]:
+//!
+//! - CycloneDX JSON (`vulnerabilities[].ratings[].severity`)
+//! - Trivy JSON (`Results[].Vulnerabilities[]`)
+//! - Grype JSON (`matches[].vulnerability`)
+//! - in-toto statements wrapping any of the above, such as cosign `vuln`
+//!   attestations, optionally inside a DSSE envelope
+//!
+//! Signatures on attestations are not verified; the report is taken as found.
+
+use base64::Engine;
+use serde_json::Value;
+
+/// Severity of a vulnerability, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// Severity not rated, or rated negligible/informational.
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Parse a scanner's severity label (case-insensitive).
+    ///
+    /// Unrecognized labels parse as `Unknown`.
+    pub fn parse(label: &str) -> Self {
+        match label.trim().to_ascii_lowercase().as_str() {
+            "critical" => Self::Critical,
+            "high" | "important" => Self::High,
+            "medium" | "moderate" => Self::Medium,
+            "low" => Self::Low,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Unknown => "unknown",
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One finding of a vulnerability report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vulnerability {
+    /// Identifier, e.g. `CVE-2024-1234`.
+    pub id: String,
+    pub severity: Severity,
+}
+
+/// Vulnerabilities found in an image by a scanner.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VulnerabilityReport {
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+impl VulnerabilityReport {
+    /// Parse a report in any supported format.
+    ///
+    /// Returns `None` if the document is not a recognized report.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let doc: Value = serde_json::from_slice(bytes).ok()?;
+        parse_value(&doc).map(|vulnerabilities| Self { vulnerabilities })
+    }
+
+    /// Most severe finding, or `None` for a clean report.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.vulnerabilities.iter().map(|v| v.severity).max()
+    }
+
+    /// Findings at `severity` or worse.
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Vulnerability> {
+        self.vulnerabilities
+            .iter()
+            .filter(move |v| v.severity >= severity)
+    }
+}
+
+fn parse_value(doc: &Value) -> Option<Vec<Vulnerability>> {
+    // DSSE envelope: base64 payload holding an in-toto statement
+    if let Some(payload) = doc.get("payload").and_then(Value::as_str) {
+        let decoded = base64::engine::general_purpose::STANDARD
+            .decode(payload)
+            .ok()?;
+        return parse_value(&serde_json::from_slice(&decoded).ok()?);
+    }
+
+    // in-toto statement: the predicate is the report, or wraps the
+    // scanner output (cosign vuln attestations)
+    if let Some(predicate) = doc.get("predicate") {
+        return predicate
+            .pointer("/scanner/result")
+            .and_then(parse_value)
+            .or_else(|| parse_value(predicate));
+    }
+
+    if doc.get("bomFormat").and_then(Value::as_str) == Some("CycloneDX") {
+        return Some(parse_cyclonedx(doc));
+    }
+    if let Some(results) = doc.get("Results") {
+        return Some(parse_trivy(results));
+    }
+    if let Some(matches) = doc.get("matches").and_then(Value::as_array) {
+        return Some(parse_grype(matches));
+    }
+    None
+}
+
+fn parse_cyclonedx(doc: &Value) -> Vec<Vulnerability> {
+    let entries = doc.get("vulnerabilities").and_then(Value::as_array);
+    entries
+        .into_iter()
+        .flatten()
+        .map(|entry| {
+            let severity = entry
+                .get("ratings")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(|rating| rating.get("severity").and_then(Value::as_str))
+                .map(Severity::parse)
+                .max()
+                .unwrap_or(Severity::Unknown);
+            Vulnerability {
+                id: string_field(entry, "id"),
+                severity,
+            }
+        })
+        .collect()
+}
+
+fn parse_trivy(results: &Value) -> Vec<Vulnerability> {
+    results
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|result| result.get("Vulnerabilities").and_then(Value::as_array))
+        .flatten()
+        .map(|entry| Vulnerability {
+            id: string_field(entry, "VulnerabilityID"),
+            severity: Severity::parse(entry.get("Severity").and_then(Value::as_str).unwrap_or("")),
+        })
+        .collect()
+}
+
+fn parse_grype(matches: &[Value]) -> Vec<Vulnerability> {
+    matches
+        .iter()
+        .filter_map(|m| m.get("vulnerability"))
+        .map(|entry| Vulnerability {
+            id: string_field(entry, "id"),
+            severity: Severity::parse(entry.get("severity").and_then(Value::as_str).unwrap_or("")),
+        })
+        .collect()
+}
+
+fn string_field(entry: &Value, key: &str) -> String {
+    entry
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn ids(report: &VulnerabilityReport) -> Vec<(&str, Severity)> {
+        report
+            .vulnerabilities
+            .iter()
+            .map(|v| (v.id.as_str(), v.severity))
+            .collect()
+    }
+
+    #[test]
+    fn test_severity_labels_and_order() {
+        assert_eq!(Severity::parse("CRITICAL"), Severity::Critical);
+        assert_eq!(Severity::parse("Moderate"), Severity::Medium);
+        assert_eq!(Severity::parse("negligible"), Severity::Unknown);
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::Low > Severity::Unknown);
+    }
+
+    #[test]
+    fn test_parse_cyclonedx_takes_highest_rating() {
+        let doc = json!({
+            "bomFormat": "CycloneDX",
+            "vulnerabilities": [
+                {"id": "CVE-1", "ratings": [{"severity": "medium"}, {"severity": "critical"}]},
+                {"id": "CVE-2", "ratings": []}
+            ]
+        });
+        let report = VulnerabilityReport::parse(doc.to_string().as_bytes()).unwrap();
+        assert_eq!(
+            ids(&report),
+            vec![("CVE-1", Severity::Critical), ("CVE-2", Severity::Unknown)]
+        );
+        assert_eq!(report.max_severity(), Some(Severity::Critical));
+    }
+
+    #[test]
+    fn test_parse_trivy_and_grype() {
+        let trivy = json!({
+            "Results": [
+                {"Target": "alpine", "Vulnerabilities": [
+                    {"VulnerabilityID": "CVE-3", "Severity": "HIGH"}
+                ]},
+                {"Target": "app", "Vulnerabilities": null}
+            ]
+        });
+        let report = VulnerabilityReport::parse(trivy.to_string().as_bytes()).unwrap();
+        assert_eq!(ids(&report), vec![("CVE-3", Severity::High)]);
+
+        let grype = json!({"matches": [{"vulnerability": {"id": "CVE-4", "severity": "Low"}}]});
+        let report = VulnerabilityReport::parse(grype.to_string().as_bytes()).unwrap();
+        assert_eq!(ids(&report), vec![("CVE-4", Severity::Low)]);
+    }
+
+    #[test]
+    fn test_parse_cosign_attestation_in_dsse_envelope() {
+        let statement = json!({
+            "_type": "https://in-toto.io/Statement/v0.1",
+            "predicateType": "https://cosign.sigstore.dev/attestation/vuln/v1",
+            "predicate": {
+                "scanner": {"result": {"Results": [{"Vulnerabilities": [
+                    {"VulnerabilityID": "CVE-5", "Severity": "CRITICAL"}
+                ]}]}}
+            }
+        });
+        let envelope = json!({
+            "payloadType": "application/vnd.in-toto+json",
+            "payload": base64::engine::general_purpose::STANDARD.encode(statement.to_string()),
+            "signatures": []
+        });
+        let report = VulnerabilityReport::parse(envelope.to_string().as_bytes()).unwrap();
+        assert_eq!(ids(&report), vec![("CVE-5", Severity::Critical)]);
+    }
+
+    #[test]
+    fn test_clean_and_unrecognized_documents() {
+        let clean = json!({"bomFormat": "CycloneDX", "components": []});
+        let report = VulnerabilityReport::parse(clean.to_string().as_bytes()).unwrap();
+        assert_eq!(report.max_severity(), None);
+
+        assert!(VulnerabilityReport::parse(br#"{"critical": true}"#).is_none());
+        assert!(VulnerabilityReport::parse(b"not json").is_none());
+    }
+}
@@ -27,6 +27,7 @@ pub struct JailerBuilder {
     volumes: Vec<VolumeSpec>,
     box_id: Option<String>,
     layout: Option<BoxFilesystemLayout>,
+    cpu_weight: Option<u32>,
     preserved_fds: Vec<(RawFd, i32)>,
 }
 
@@ -44,6 +45,7 @@ impl JailerBuilder {
             volumes: Vec::new(),
             box_id: None,
             layout: None,
+            cpu_weight: None,
             preserved_fds: Vec::new(),
         }
     }
@@ -81,6 +83,13 @@ impl JailerBuilder {
         self
     }
 
+    /// Set the CPU weight of the box's cgroup (Linux only; default: the
+    /// cgroup default of 100).
+    pub fn with_cpu_weight(mut self, weight: Option<u32>) -> Self {
+        self.cpu_weight = weight;
+        self
+    }
+
     /// Enable or disable jailer isolation.
     pub fn with_jailer_enabled(mut self, enabled: bool) -> Self {
         self.security.jailer_enabled = enabled;
@@ -143,6 +152,7 @@ impl JailerBuilder {
             volumes: self.volumes,
             box_id,
             layout,
+            cpu_weight: self.cpu_weight,
             preserved_fds: self.preserved_fds,
         })
     }
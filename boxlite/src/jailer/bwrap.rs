@@ -274,7 +274,6 @@ fn read_sysctl(name: &str) -> Option<String> {
 }
 
 /// Get the bwrap version string.
-#[allow(dead_code)]
 pub fn version() -> Option<String> {
     let bwrap_path = get_bwrap_path()?;
     Command::new(bwrap_path)
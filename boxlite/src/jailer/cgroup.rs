@@ -1,7 +1,7 @@
 //! Cgroup v2 setup for resource limiting.
 //!
 //! This module sets up cgroup v2 limits for the boxlite-shim process.
-//! Cgroups are used to limit CPU, memory, and process count.
+//! Cgroups are used to limit CPU, memory, process count, and disk I/O.
 //!
 //! ## Why Cgroups?
 //!
@@ -27,6 +27,7 @@
 //!         ├── memory.max        # Memory limit
 //!         ├── memory.high       # Memory throttle threshold
 //!         ├── pids.max          # Max processes
+//!         ├── io.max            # Disk bandwidth/IOPS limits (if io is delegated)
 //!         └── cgroup.procs      # Add process here
 //! ```
 
@@ -114,6 +115,53 @@ pub struct CgroupConfig {
 
     /// Maximum number of processes (pids.max).
     pub pids_max: Option<u64>,
+
+    /// Disk I/O limits (io.max).
+    pub io_max: Option<IoMax>,
+}
+
+/// Disk I/O limits for one block device (a line of `io.max`).
+///
+/// Unset limits are written as `max`, so writing an `IoMax` always replaces
+/// the previous limits for the device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IoMax {
+    /// Block device major number.
+    pub major: u32,
+    /// Block device minor number.
+    pub minor: u32,
+    pub read_bps: Option<u64>,
+    pub write_bps: Option<u64>,
+    pub read_iops: Option<u64>,
+    pub write_iops: Option<u64>,
+}
+
+impl IoMax {
+    /// Build io.max limits for `device` from resource limits.
+    pub fn new((major, minor): (u32, u32), limits: &ResourceLimits) -> Self {
+        Self {
+            major,
+            minor,
+            read_bps: limits.disk_read_bps,
+            write_bps: limits.disk_write_bps,
+            read_iops: limits.disk_read_iops,
+            write_iops: limits.disk_write_iops,
+        }
+    }
+
+    /// Format as an `io.max` line, e.g. `8:0 rbps=1048576 wbps=max riops=max wiops=max`.
+    pub fn to_line(&self) -> String {
+        let fmt = |v: Option<u64>| v.map_or_else(|| "max".to_string(), |v| v.to_string());
+        format!(
+            "{}:{} rbps={} wbps={} riops={} wiops={}",
+            self.major,
+            self.minor,
+            fmt(self.read_bps),
+            fmt(self.write_bps),
+            fmt(self.read_iops),
+            fmt(self.write_iops)
+        )
+    }
 }
 
 /// Check if cgroup v2 is available and unified hierarchy is used.
@@ -129,6 +177,81 @@ pub fn is_cgroup_v2_available() -> bool {
     controllers.exists()
 }
 
+/// Check if the io controller is delegated to the cgroup base.
+///
+/// Rootless hosts often only delegate cpu, memory and pids to the user
+/// service; io.max cannot be set without the io controller.
+pub fn is_io_controller_available() -> bool {
+    has_controller(&get_cgroup_base(), "io")
+}
+
+/// Why boxes can't get their own cgroup on this host, or None if they can.
+///
+/// Needs cgroup v2, a cgroup base this process may create children in, and
+/// the cpu, memory and pids controllers delegated to it.
+pub fn delegation_unavailable_reason() -> Option<String> {
+    if !is_cgroup_v2_available() {
+        return Some("cgroup v2 is not available".to_string());
+    }
+    let base = get_cgroup_base();
+    let parent = base.join(BOXLITE_CGROUP);
+    let writable = if parent.exists() { &parent } else { &base };
+    let path = std::ffi::CString::new(writable.to_string_lossy().as_bytes()).ok()?;
+    // SAFETY: access() only reads the NUL-terminated path
+    if unsafe { libc::access(path.as_ptr(), libc::W_OK) } != 0 {
+        return Some(format!(
+            "{} is not writable by this user (cgroup not delegated)",
+            writable.display()
+        ));
+    }
+    let missing: Vec<_> = ["cpu", "memory", "pids"]
+        .into_iter()
+        .filter(|controller| !has_controller(&base, controller))
+        .collect();
+    if !missing.is_empty() {
+        return Some(format!(
+            "controllers not delegated to {}: {} (add `Delegate=cpu memory pids` \
+             to user@.service)",
+            base.display(),
+            missing.join(", ")
+        ));
+    }
+    None
+}
+
+/// Check if `controller` is listed in a cgroup's `cgroup.controllers`.
+fn has_controller(cgroup_path: &Path, controller: &str) -> bool {
+    fs::read_to_string(cgroup_path.join("cgroup.controllers"))
+        .map(|c| c.split_whitespace().any(|name| name == controller))
+        .unwrap_or(false)
+}
+
+/// Find the whole-disk block device backing `path`.
+///
+/// io.max only accepts whole disks, so a partition is resolved to its parent
+/// disk through sysfs. Returns None for filesystems without a backing block
+/// device (tmpfs, overlay, network filesystems).
+pub fn block_device_of(path: &Path) -> Option<(u32, u32)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = fs::metadata(path).ok()?.dev();
+    let (major, minor) = (libc::major(dev), libc::minor(dev));
+
+    let sys_dev = PathBuf::from(format!("/sys/dev/block/{}:{}", major, minor));
+    if !sys_dev.exists() {
+        return None;
+    }
+    if !sys_dev.join("partition").exists() {
+        return Some((major, minor));
+    }
+
+    // Partition: the parent directory in sysfs is the whole disk
+    let parent = fs::canonicalize(&sys_dev).ok()?.parent()?.join("dev");
+    let content = fs::read_to_string(parent).ok()?;
+    let (major, minor) = content.trim().split_once(':')?;
+    Some((major.parse().ok()?, minor.parse().ok()?))
+}
+
 /// Get the path to a box's cgroup directory.
 ///
 /// The base path depends on whether running as root or regular user:
@@ -210,6 +333,13 @@ fn enable_controllers(cgroup_path: &Path) -> Result<(), JailerError> {
     // Enable cpu, memory, and pids controllers
     write_file(&subtree_control, "+cpu +memory +pids")?;
 
+    // io is optional: rootless hosts often don't delegate it
+    if has_controller(cgroup_path, "io")
+        && let Err(e) = write_file(&subtree_control, "+io")
+    {
+        tracing::warn!(error = %e, "Failed to enable io controller");
+    }
+
     Ok(())
 }
 
@@ -243,6 +373,86 @@ fn apply_limits(cgroup_path: &Path, config: &CgroupConfig) -> Result<(), JailerE
         write_file(&cgroup_path.join("pids.max"), &pids_max.to_string())?;
     }
 
+    // Disk I/O (degrades to a warning when io is not delegated)
+    if let Some(io_max) = &config.io_max {
+        if has_controller(cgroup_path, "io") {
+            write_file(&cgroup_path.join("io.max"), &io_max.to_line())?;
+        } else {
+            tracing::warn!(
+                path = %cgroup_path.display(),
+                "io controller not available, disk I/O limits not enforced"
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Replace the disk I/O limits of a running box.
+///
+/// Writes io.max on the existing cgroup, so the change applies immediately.
+/// Unset limits are reset to `max`.
+///
+/// # Errors
+///
+/// Returns [`JailerError::Cgroup`] if the box has no cgroup, the io
+/// controller is not available for it, or the write fails.
+pub fn update_io_limits(box_id: &str, io_max: &IoMax) -> Result<(), JailerError> {
+    let box_cgroup = cgroup_path(box_id);
+    if !box_cgroup.exists() {
+        return Err(JailerError::Cgroup(format!(
+            "No cgroup for box {} (disk I/O limits require the jailer)",
+            box_id
+        )));
+    }
+    if !has_controller(&box_cgroup, "io") {
+        return Err(JailerError::Cgroup(format!(
+            "io controller not available in {}",
+            box_cgroup.display()
+        )));
+    }
+
+    write_file(&box_cgroup.join("io.max"), &io_max.to_line())?;
+
+    tracing::debug!(
+        box_id = %box_id,
+        io_max = %io_max.to_line(),
+        "Disk I/O limits updated"
+    );
+
+    Ok(())
+}
+
+/// Replace the CPU bandwidth limit of a running box.
+///
+/// Writes cpu.max on the existing cgroup as `quota period`, or `max` for
+/// `None`, so the change applies immediately.
+///
+/// # Errors
+///
+/// Returns [`JailerError::Cgroup`] if the box has no cgroup or the write
+/// fails.
+pub fn update_cpu_max(box_id: &str, cpu_max: Option<(u64, u64)>) -> Result<(), JailerError> {
+    let box_cgroup = cgroup_path(box_id);
+    if !box_cgroup.exists() {
+        return Err(JailerError::Cgroup(format!(
+            "No cgroup for box {} (CPU limits require the jailer)",
+            box_id
+        )));
+    }
+
+    let line = match cpu_max {
+        Some((quota, period)) => format!("{} {}", quota, period),
+        None => "max".to_string(),
+    };
+    write_file(&box_cgroup.join("cpu.max"), &line)?;
+
+    tracing::debug!(
+        box_id = %box_id,
+        cpu_max = %line,
+        "CPU limit updated"
+    );
+
     Ok(())
 }
 
@@ -302,13 +512,14 @@ impl From<&ResourceLimits> for CgroupConfig {
         Self {
             memory_max: limits.max_memory,
             memory_high: limits.max_memory.map(|m| m * 9 / 10), // 90% of max
-            cpu_weight: None,                                   // Could add to ResourceLimits
+            cpu_weight: None, // From BoxOptions::priority, see SandboxContext
             cpu_max: limits.max_cpu_time.map(|t| {
                 // Convert seconds to quota/period
                 // 1 CPU = 100000/100000
                 (t * 1_000_000, 1_000_000)
             }),
             pids_max: limits.max_processes,
+            io_max: None, // Needs the backing device, see IoMax::new
         }
     }
 }
@@ -439,5 +650,36 @@ mod tests {
         assert_eq!(config.memory_max, Some(1024 * 1024 * 1024));
         assert_eq!(config.pids_max, Some(100));
         assert!(config.cpu_max.is_some());
+        assert!(config.io_max.is_none());
+    }
+
+    #[test]
+    fn test_io_max_line() {
+        let limits = ResourceLimits {
+            disk_read_bps: Some(10 * 1024 * 1024),
+            disk_write_iops: Some(500),
+            ..Default::default()
+        };
+
+        let io_max = IoMax::new((8, 0), &limits);
+        assert_eq!(
+            io_max.to_line(),
+            "8:0 rbps=10485760 wbps=max riops=max wiops=500"
+        );
+
+        // No limits resets the device to unthrottled
+        let io_max = IoMax::new((259, 0), &ResourceLimits::default());
+        assert_eq!(
+            io_max.to_line(),
+            "259:0 rbps=max wbps=max riops=max wiops=max"
+        );
+    }
+
+    #[test]
+    fn test_block_device_of() {
+        // Result depends on the host filesystem; must not panic
+        let device = block_device_of(Path::new("/"));
+        println!("Block device of /: {:?}", device);
+        assert!(block_device_of(Path::new("/nonexistent/path")).is_none());
     }
 }
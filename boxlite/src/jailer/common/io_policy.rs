@@ -0,0 +1,60 @@
+//! Disk I/O throttling for hosts without an io cgroup (macOS).
+//!
+//! The VMM reads and writes a box's disk images from the shim process, so
+//! putting that process's disk I/O in the throttled tier is the closest the
+//! host gets to disk I/O limits. It does not hold the box to the configured
+//! rates: its I/O is delayed only while other processes' I/O contends for
+//! the same device, which is what keeps one box from starving the others.
+//!
+//! Only the async-signal-safe `throttle_disk_io_raw()` is used, called from
+//! the `pre_exec` hook before exec(). The policy is inherited across exec.
+
+/// `IOPOL_TYPE_DISK` from `<sys/resource.h>`.
+const IOPOL_TYPE_DISK: libc::c_int = 0;
+/// `IOPOL_SCOPE_PROCESS` from `<sys/resource.h>`.
+const IOPOL_SCOPE_PROCESS: libc::c_int = 0;
+/// `IOPOL_THROTTLE` from `<sys/resource.h>`.
+const IOPOL_THROTTLE: libc::c_int = 3;
+
+unsafe extern "C" {
+    fn setiopolicy_np(iotype: libc::c_int, scope: libc::c_int, policy: libc::c_int) -> libc::c_int;
+}
+
+/// Throttle the calling process's disk I/O - async-signal-safe version for pre_exec.
+///
+/// # Safety
+///
+/// This function only makes one system call (`setiopolicy_np`).
+/// Do NOT add:
+/// - Logging (tracing, println)
+/// - Memory allocation (Box, Vec, String)
+/// - Mutex operations
+///
+/// # Returns
+/// * `Ok(())` - Disk I/O policy set
+/// * `Err(errno)` - Failed to set it (returns raw errno)
+pub fn throttle_disk_io_raw() -> Result<(), i32> {
+    // SAFETY: setiopolicy_np only changes the calling process's I/O policy
+    let result = unsafe { setiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_PROCESS, IOPOL_THROTTLE) };
+    if result != 0 {
+        return Err(super::get_errno());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe extern "C" {
+        fn getiopolicy_np(iotype: libc::c_int, scope: libc::c_int) -> libc::c_int;
+    }
+
+    #[test]
+    fn test_throttle_disk_io_raw() {
+        throttle_disk_io_raw().expect("Should throttle disk I/O");
+
+        let policy = unsafe { getiopolicy_np(IOPOL_TYPE_DISK, IOPOL_SCOPE_PROCESS) };
+        assert_eq!(policy, IOPOL_THROTTLE);
+    }
+}
@@ -4,12 +4,15 @@
 //! - [`fd`]: File descriptor cleanup (async-signal-safe for pre_exec)
 //! - [`rlimit`]: Resource limit management (async-signal-safe for pre_exec)
 //! - [`pid`]: PID file writing (async-signal-safe for pre_exec)
+//! - [`io_policy`]: Disk I/O throttling, macOS only (async-signal-safe for pre_exec)
 //! - [`fs`]: Filesystem utilities (copy-if-newer, etc.)
 //!
 //! Note: Environment sanitization is handled by bwrap/sandbox-exec at spawn time.
 
 pub mod fd;
 pub mod fs;
+#[cfg(target_os = "macos")]
+pub mod io_policy;
 pub mod pid;
 pub mod rlimit;
 
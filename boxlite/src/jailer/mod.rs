@@ -103,6 +103,80 @@ pub use sandbox::seatbelt::{
     SANDBOX_EXEC_PATH, get_base_policy, get_network_policy, is_sandbox_available,
 };
 
+// ============================================================================
+// Disk I/O limits
+// ============================================================================
+
+/// Why disk I/O limits can't be enforced on this host, or None if they can.
+///
+/// Used by `boxlite doctor` to explain limits that are silently degraded.
+#[cfg(target_os = "linux")]
+pub fn disk_io_limits_unsupported_reason() -> Option<String> {
+    if !cgroup::is_cgroup_v2_available() {
+        return Some("cgroup v2 is not available".to_string());
+    }
+    if !cgroup::is_io_controller_available() {
+        return Some(
+            "the io cgroup controller is not delegated to this user \
+             (add `Delegate=cpu memory pids io` to user@.service)"
+                .to_string(),
+        );
+    }
+    None
+}
+
+/// macOS has no io cgroup: the shim's disk I/O is throttled instead (see
+/// [`common::io_policy`]), which is not held to the configured rates.
+#[cfg(target_os = "macos")]
+pub fn disk_io_limits_unsupported_reason() -> Option<String> {
+    Some(
+        "disk I/O rates are only enforced on Linux hosts; \
+         here the box's disk I/O is throttled behind other processes'"
+            .to_string(),
+    )
+}
+
+/// Elsewhere there is no io cgroup, and the VMM's disk backend has no rate
+/// limiting of its own, so limits are recorded but never applied.
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn disk_io_limits_unsupported_reason() -> Option<String> {
+    Some(
+        "disk I/O limits are only enforced on Linux hosts \
+         (the VM's disk backend has no rate limiting)"
+            .to_string(),
+    )
+}
+
+/// Apply disk I/O limits to a running box's cgroup.
+#[cfg(target_os = "linux")]
+pub(crate) fn update_disk_io_limits(
+    box_id: &str,
+    box_dir: &Path,
+    limits: &ResourceLimits,
+) -> BoxliteResult<()> {
+    let device = cgroup::block_device_of(box_dir).ok_or_else(|| {
+        boxlite_shared::errors::BoxliteError::Unsupported(format!(
+            "no block device found for {}",
+            box_dir.display()
+        ))
+    })?;
+    cgroup::update_io_limits(box_id, &cgroup::IoMax::new(device, limits))?;
+    Ok(())
+}
+
+/// Fails with `Unsupported`: see [`disk_io_limits_unsupported_reason`].
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn update_disk_io_limits(
+    _box_id: &str,
+    _box_dir: &Path,
+    _limits: &ResourceLimits,
+) -> BoxliteResult<()> {
+    Err(boxlite_shared::errors::BoxliteError::Unsupported(format!(
+        "cannot apply disk I/O limits to a running box: {}",
+        disk_io_limits_unsupported_reason().unwrap_or_default()
+    )))
+}
+
 // ============================================================================
 // Jail trait — public contract
 // ============================================================================
@@ -140,6 +214,7 @@ pub trait Jail: Send + Sync {
 // Jailer<S: Sandbox> — implements Jail
 // ============================================================================
 
+use crate::disk::constants::filenames as disk_filenames;
 use crate::runtime::layout::BoxFilesystemLayout;
 use std::path::PathBuf;
 
@@ -163,9 +238,12 @@ use std::path::PathBuf;
 /// ├── logs/                       [RW]  # shim logging + VM console output
 /// │   ├── boxlite-shim.log                # tracing_appender daily log
 /// │   └── console.log                     # libkrun serial console (krun_set_console_output)
+/// ├── execs/                    [RW]  # detached exec output (guest writes via virtio-fs)
 /// ├── exit                        [RW]  # crash_capture ExitInfo JSON
+/// ├── exit.diagnostics            [RW]  # crash_capture diagnostics JSON
 /// ├── root.qcow2                  [RW]  # VM root disk image
 /// ├── guest-rootfs.qcow2          [RW]  # guest rootfs COW overlay
+/// ├── disk.raw, guest-rootfs.raw  [RW]  # reflink storage driver disks
 /// ├── mounts/                     [--]  # EXCLUDED: host writes, shim reads via shared/
 /// ├── shim.pid                    [--]  # EXCLUDED: written by pre_exec (before sandbox)
 /// └── shim.stderr                 [--]  # EXCLUDED: host creates before spawn
@@ -181,7 +259,7 @@ fn build_path_access(layout: &BoxFilesystemLayout, volumes: &[VolumeSpec]) -> Ve
 
     // Writable directories (shim creates files inside these at runtime)
     // Note: mounts_dir not included — host writes before spawn, shim accesses via shared_dir
-    for dir in [layout.sockets_dir(), layout.logs_dir()] {
+    for dir in [layout.sockets_dir(), layout.logs_dir(), layout.execs_dir()] {
         if dir.exists() {
             paths.push(PathAccess {
                 path: dir,
@@ -194,8 +272,12 @@ fn build_path_access(layout: &BoxFilesystemLayout, volumes: &[VolumeSpec]) -> Ve
     // Note: console_output_path() not listed — lives inside logs/ [RW subpath]
     for file in [
         layout.exit_file_path(),
+        layout.exit_diagnostics_path(),
         layout.disk_path(),
         layout.guest_rootfs_disk_path(),
+        // Disks of the reflink storage driver
+        layout.root().join(disk_filenames::RAW_CONTAINER_DISK),
+        layout.root().join(disk_filenames::RAW_GUEST_ROOTFS_DISK),
     ] {
         if file.exists() {
             paths.push(PathAccess {
@@ -282,6 +364,8 @@ pub struct Jailer<S: Sandbox> {
     pub(crate) box_id: String,
     /// Box filesystem layout (provides typed path accessors).
     pub(crate) layout: BoxFilesystemLayout,
+    /// CPU weight for the box's cgroup (Linux only).
+    pub(crate) cpu_weight: Option<u32>,
     /// FDs to preserve through pre_exec: each (source_fd, target_fd) is dup2'd
     /// before FD cleanup. Used for watchdog pipe inheritance across fork.
     pub(crate) preserved_fds: Vec<(std::os::fd::RawFd, i32)>,
@@ -301,6 +385,7 @@ impl<S: Sandbox> Jail for Jailer<S> {
             let _ = std::fs::create_dir_all(self.layout.logs_dir());
             for path in [
                 self.layout.exit_file_path(),
+                self.layout.exit_diagnostics_path(),
                 self.layout.console_output_path(),
             ] {
                 if !path.exists() {
@@ -332,11 +417,14 @@ impl<S: Sandbox> Jail for Jailer<S> {
             (binary.to_path_buf(), None)
         };
 
-        let mut cmd = if self.security.jailer_enabled && self.sandbox.is_available() {
+        let sandboxed = ctx.namespaces && self.sandbox.is_available();
+        let mut cmd = if self.security.jailer_enabled && sandboxed {
             tracing::info!(sandbox = self.sandbox.name(), "Building confined command");
             self.sandbox.wrap(&ctx, &effective_binary, args)
         } else {
-            if self.security.jailer_enabled {
+            if self.security.jailer_enabled && !ctx.namespaces {
+                tracing::warn!("Host has no user namespaces, running shim without the sandbox");
+            } else if self.security.jailer_enabled {
                 tracing::warn!("Sandbox not available, falling back to direct command");
             } else {
                 tracing::info!("Jailer disabled, running shim without sandbox isolation");
@@ -410,9 +498,26 @@ impl<S: Sandbox> Jailer<S> {
             id: &self.box_id,
             paths: build_path_access(&self.layout, &self.volumes),
             resource_limits: &self.security.resource_limits,
+            cpu_weight: self.cpu_weight,
+            io_device: self.io_device(),
             network_enabled: self.security.network_enabled,
             sandbox_profile: self.security.sandbox_profile.as_deref(),
+            namespaces: self.security.namespaces_enabled || !cfg!(target_os = "linux"),
+        }
+    }
+
+    /// Block device backing the box directory, when disk I/O limits are set.
+    #[cfg(target_os = "linux")]
+    fn io_device(&self) -> Option<(u32, u32)> {
+        if !self.security.resource_limits.has_disk_io_limits() {
+            return None;
         }
+        cgroup::block_device_of(self.layout.root())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn io_device(&self) -> Option<(u32, u32)> {
+        None
     }
 
     /// Build the PID file path as a CString for the pre_exec hook.
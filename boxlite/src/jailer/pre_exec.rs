@@ -8,7 +8,8 @@
 //! 1. **Close inherited FDs** - Prevents information leakage
 //! 2. **Apply rlimits** - Resource limits (max files, memory, CPU time, etc.)
 //! 3. **Add to cgroup** - Linux only, for cgroup resource limits
-//! 4. **Write PID file** - Single source of truth for process tracking
+//! 4. **Throttle disk I/O** - macOS only, best effort for disk I/O limits
+//! 5. **Write PID file** - Single source of truth for process tracking
 //!
 //! # Safety
 //!
@@ -29,7 +30,7 @@ use std::process::Command;
 ///
 /// Runs after fork() but before the new program starts in the child process.
 /// Applies: FD preservation (dup2), FD cleanup, rlimits, cgroup membership (Linux),
-/// PID file writing.
+/// disk I/O throttling (macOS), PID file writing.
 ///
 /// # Arguments
 ///
@@ -47,6 +48,7 @@ use std::process::Command;
 /// only uses async-signal-safe operations:
 /// - `dup2()` / `close()` / `close_range()` syscalls
 /// - `setrlimit()` syscall
+/// - `setiopolicy_np()` (macOS)
 /// - `open()` / `write()` / `close()` syscalls (for cgroup and PID file)
 /// - `getpid()` syscall
 ///
@@ -95,7 +97,14 @@ pub fn add_pre_exec_hook(
                 let _ = crate::jailer::cgroup::add_self_to_cgroup_raw(path);
             }
 
-            // 4. Write PID file
+            // 4. Throttle disk I/O (macOS only): there is no io cgroup to
+            // hold the box to its disk I/O limits
+            #[cfg(target_os = "macos")]
+            if resource_limits.has_disk_io_limits() {
+                let _ = common::io_policy::throttle_disk_io_raw();
+            }
+
+            // 5. Write PID file
             if let Some(ref path) = pid_file_path {
                 common::pid::write_pid_file_raw(path).map_err(std::io::Error::from_raw_os_error)?;
             }
@@ -34,7 +34,8 @@ impl Sandbox for BwrapSandbox {
         // Preflight: verify bwrap can create user namespaces before proceeding.
         // Uses Chrome-style clone(CLONE_NEWUSER) probe for diagnosis + bwrap
         // probe for actual capability (handles AppArmor per-binary profiles).
-        if bwrap::is_available()
+        if ctx.namespaces
+            && bwrap::is_available()
             && let Err(diagnostic) = bwrap::can_create_user_namespace()
         {
             return Err(BoxliteError::Config(format!(
@@ -45,7 +46,19 @@ impl Sandbox for BwrapSandbox {
             )));
         }
 
-        let cgroup_config = cgroup::CgroupConfig::from(ctx.resource_limits);
+        let mut cgroup_config = cgroup::CgroupConfig::from(ctx.resource_limits);
+        cgroup_config.cpu_weight = ctx.cpu_weight;
+        if ctx.resource_limits.has_disk_io_limits() {
+            match ctx.io_device {
+                Some(device) => {
+                    cgroup_config.io_max = Some(cgroup::IoMax::new(device, ctx.resource_limits));
+                }
+                None => tracing::warn!(
+                    id = %ctx.id,
+                    "No block device found for box disks, disk I/O limits not enforced"
+                ),
+            }
+        }
 
         match cgroup::setup_cgroup(ctx.id, &cgroup_config) {
             Ok(path) => {
@@ -103,10 +103,17 @@ pub struct SandboxContext<'a> {
     pub paths: Vec<PathAccess>,
     /// Resource limits (for cgroup configuration).
     pub resource_limits: &'a ResourceLimits,
+    /// CPU weight for cpu.weight, None for the cgroup default.
+    pub cpu_weight: Option<u32>,
+    /// Block device (major, minor) holding the box's disk images, for io.max.
+    pub io_device: Option<(u32, u32)>,
     /// Whether network access is enabled.
     pub network_enabled: bool,
     /// Custom sandbox profile path (macOS only).
     pub sandbox_profile: Option<&'a Path>,
+    /// Whether to isolate with namespaces (Linux only). False for boxes
+    /// created on a host without user namespaces.
+    pub namespaces: bool,
 }
 
 impl SandboxContext<'_> {
@@ -553,8 +553,11 @@ mod tests {
             id: "test",
             paths: vec![],
             resource_limits: &ResourceLimits::default(),
+            cpu_weight: None,
+            io_device: None,
             network_enabled: false,
             sandbox_profile: None,
+            namespaces: true,
         };
         assert!(sandbox.cgroup_procs_path(&ctx).is_none());
     }
@@ -8,14 +8,21 @@ use tracing_subscriber::EnvFilter;
 // Global guard for tracing-appender to keep the writer thread alive
 static LOG_GUARD: OnceLock<tracing_appender::non_blocking::WorkerGuard> = OnceLock::new();
 
+pub mod audit;
+#[cfg(feature = "bench")]
+pub mod bench;
 pub mod jailer;
 pub mod litebox;
 pub mod lock;
 pub mod metrics;
 pub mod net;
 pub mod pipeline;
+pub mod policy;
 pub mod runtime;
+#[cfg(feature = "test-util")]
+pub mod testing;
 pub mod util;
+pub mod validate;
 pub mod vmm;
 
 mod db;
@@ -24,33 +31,77 @@ mod fs;
 mod images;
 mod portal;
 #[cfg(feature = "rest")]
-mod rest;
+pub mod rest;
 mod rootfs;
 mod volumes;
 
+pub use audit::{AuditEvent, AuditSink};
 pub use litebox::LiteBox;
 pub use portal::GuestSession;
-pub use runtime::{BoxliteRuntime, ImageHandle};
+pub use runtime::{
+    BoxOptionsPatch, BoxliteRuntime, BuildEvent, BuildObserver, BuildSpec, BuildStep, BuiltImage,
+    BulkExecResult, BulkResults, Capability, CapabilityStatus, ComposedBox, CreateEvent,
+    CreateObserver, CreatePhase, Degradation, GetOrCreateOutcome, GetOrCreatePolicy, ImageHandle,
+    OrphanReason, OrphanShim, ReadinessProbe, RunOnceOptions, RunOnceResult, RuntimeCapabilities,
+    StopOptions, UpOptions, UpOutcome, UpReport, VersionInfo, WarmSelector,
+};
 
+pub use boxlite_shared::archive::ArchiveLimits;
+pub use boxlite_shared::boot::BootPhase;
 pub use boxlite_shared::errors::{BoxliteError, BoxliteResult};
-pub use db::snapshots::SnapshotInfo;
+pub use db::snapshots::{PrunedSnapshots, SnapshotInfo};
+pub use db::templates::BoxTemplate;
+pub use db::trash::TrashedBox;
+pub use disk::{CacheStats, CacheUsage};
+pub use images::{BlobCacheLookup, ImageObject, PullProgress, RegistryStatus};
+pub use litebox::CompactionEstimate;
+#[cfg(feature = "fuse")]
+pub use litebox::MountHandle;
+pub use litebox::PreparedExec;
 pub use litebox::SnapshotHandle;
-pub use litebox::snapshot_types::{CloneOptions, ExportOptions, SnapshotOptions};
+pub use litebox::StartFailure;
+pub use litebox::TunnelHandle;
+pub use litebox::snapshot_types::{
+    CloneOptions, ExportOptions, RestoreOptions, RestorePlan, SnapshotOptions, SnapshotRetention,
+};
 pub use litebox::{
-    BoxCommand, CopyOptions, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, ExecutionId,
+    BoxCommand, CapturedOutput, CopyObserver, CopyOptions, CopyOwnership, CopyProgress,
+    ExecOutputBytes, ExecOutputPaths, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution,
+    ExecutionId, normalize_host_path, validate_container_path,
+};
+pub use litebox::{CoreDump, GuestAgentLog};
+pub use litebox::{EnvironmentContent, EnvironmentReport};
+pub use litebox::{InvalidUtf8, JsonLineError, LineOptions, OutputEncoding, Timestamped};
+pub use litebox::{OutputFilter, Redactor};
+pub use litebox::{RestartPolicy, ServiceInfo, ServicePolicy, ServiceSpec, ServiceStatus};
+pub use litebox::{SCRATCH_DIR_ENV, ScratchSpec};
+pub use metrics::{
+    BoxMetrics, ImageUsage, RuntimeMetrics, RuntimeMetricsDelta, RuntimeMetricsSnapshot,
 };
-pub use metrics::{BoxMetrics, RuntimeMetrics};
-pub use runtime::ArchiveManifest;
 pub use runtime::advanced_options::{AdvancedBoxOptions, ResourceLimits, SecurityOptions};
 use runtime::layout::FilesystemLayout;
-pub use runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+pub use runtime::options::{
+    BoxOptions, BoxOptionsBuilder, BoxPriority, BoxliteOptions, CpuPressurePolicy, GuestUpdateMode,
+    IdMapping, NetworkMode, RegistrySettings, RootfsSpec, SetupFailurePolicy, StorageDriver,
+    TemplateSpec, UserNsMode, WarmPoolSpec,
+};
+#[cfg(feature = "url-transfer")]
+pub use runtime::url_transfer::{
+    AwsCredentials, EnvAuth, StaticAuth, TransferAuth, TransferCredentials, TransferObserver,
+    TransferOptions, TransferProgress,
+};
+pub use runtime::{ArchiveEntry, ArchiveManifest, ImportConflict, ImportOptions, ImportPlan};
 /// Boxlite library version (from CARGO_PKG_VERSION at compile time).
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 pub use runtime::types::ContainerID;
-pub use runtime::types::{BoxID, BoxInfo, BoxState, BoxStateInfo, BoxStatus};
+pub use runtime::types::{
+    BoxID, BoxInfo, BoxState, BoxStateInfo, BoxStatus, ListFilter, ListOptions, RemovePlan,
+};
 
 #[cfg(feature = "rest")]
 pub use rest::options::BoxliteRestOptions;
+#[cfg(feature = "rest-server")]
+pub use rest::server::{RestServer, RestServerAuth, RestServerBuilder, ScopedToken, TokenScope};
 
 /// Initialize tracing for Boxlite using the provided filesystem layout.
 ///
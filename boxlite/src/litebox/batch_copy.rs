@@ -0,0 +1,318 @@
+//! Batched copy of a directory into a box.
+//!
+//! The source is archived on a blocking thread and streamed to the guest,
+//! which unpacks each entry as it arrives. Nothing is staged on either side,
+//! so a tree of many small files costs one upload and one pass over the
+//! files instead of an archive written, read back and extracted.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use super::copy::{CopyObserver, CopyOptions, CopyProgress};
+use crate::portal::interfaces::FilesInterface;
+
+/// Directories with at least this many entries are batched without asking.
+pub(crate) const BATCH_THRESHOLD_FILES: usize = 1000;
+
+/// Size of the archive chunks handed to the guest.
+const CHUNK_SIZE: usize = 1 << 20; // 1 MiB
+
+/// Chunks archived ahead of the upload.
+const CHUNKS_IN_FLIGHT: usize = 4;
+
+/// Whether copying `src` into a box should be batched.
+pub(crate) fn should_batch(src: &Path, opts: &CopyOptions) -> bool {
+    if !src.is_dir() || opts.is_resumable() {
+        return false;
+    }
+    opts.batch
+        || walkdir::WalkDir::new(src)
+            .follow_links(opts.follow_symlinks)
+            .into_iter()
+            .take(BATCH_THRESHOLD_FILES)
+            .count()
+            >= BATCH_THRESHOLD_FILES
+}
+
+/// Stream the directory `src` into the box at `destination`.
+pub(crate) async fn copy_batched(
+    files: &mut FilesInterface,
+    src: &Path,
+    destination: &str,
+    container_id: &str,
+    opts: &CopyOptions,
+) -> BoxliteResult<()> {
+    let (tx, rx) = mpsc::channel(CHUNKS_IN_FLIGHT);
+    let archiver = tokio::task::spawn_blocking({
+        let src = src.to_path_buf();
+        let opts = opts.clone();
+        move || archive_dir(&src, &opts, tx)
+    });
+
+    let uploaded = files
+        .upload_tar_stream(
+            ReceiverStream::new(rx),
+            destination,
+            Some(container_id),
+            opts.overwrite,
+            opts.ownership,
+            opts.archive_limits,
+        )
+        .await;
+    let archived = archiver
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("archive task failed: {}", e)))?;
+
+    // A source that could not be read explains the truncated upload
+    archived?;
+    uploaded
+}
+
+/// One entry of the source, in archive order.
+struct Entry {
+    host_path: PathBuf,
+    name: String,
+    /// Bytes of file data, 0 for directories and symlinks
+    size: u64,
+    is_dir: bool,
+}
+
+/// Archive `src` into `tx`, reporting progress to `opts.progress`.
+///
+/// Returns `Ok` if the receiver went away first: the upload has failed and
+/// reports why.
+fn archive_dir(src: &Path, opts: &CopyOptions, tx: mpsc::Sender<Vec<u8>>) -> BoxliteResult<()> {
+    let entries = scan(src, opts)?;
+    let progress = CopyProgress {
+        files_total: entries.iter().filter(|e| !e.is_dir).count() as u64,
+        bytes_total: entries.iter().map(|e| e.size).sum(),
+        ..Default::default()
+    };
+
+    let mut builder = tar::Builder::new(ChunkWriter {
+        tx,
+        buf: Vec::with_capacity(CHUNK_SIZE),
+        progress,
+        observer: opts.progress.clone(),
+        closed: false,
+    });
+    builder.follow_symlinks(opts.follow_symlinks);
+
+    let result = (|| {
+        for entry in &entries {
+            builder
+                .append_path_with_name(&entry.host_path, &entry.name)
+                .map_err(|e| archive_err(&entry.host_path, e))?;
+            if !entry.is_dir {
+                let progress = &mut builder.get_mut().progress;
+                progress.files_sent += 1;
+                progress.bytes_sent += entry.size;
+            }
+        }
+        builder.finish().map_err(|e| archive_err(src, e))?;
+        builder
+            .get_mut()
+            .send_chunk()
+            .map_err(|e| archive_err(src, e))
+    })();
+
+    match result {
+        Err(_) if builder.get_ref().closed => Ok(()),
+        result => result,
+    }
+}
+
+/// Entries of `src` with the names `build_tar_from_host` would give them.
+fn scan(src: &Path, opts: &CopyOptions) -> BoxliteResult<Vec<Entry>> {
+    let base = if opts.include_parent {
+        src.file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "root".to_string())
+    } else {
+        ".".to_string()
+    };
+
+    let mut entries = Vec::new();
+    for entry in walkdir::WalkDir::new(src)
+        .follow_links(opts.follow_symlinks)
+        .sort_by_file_name()
+    {
+        let entry = entry.map_err(|e| {
+            BoxliteError::Storage(format!("failed to scan {}: {}", src.display(), e))
+        })?;
+        let meta = entry
+            .metadata()
+            .map_err(|e| archive_err(entry.path(), e.into()))?;
+        let rel = entry.path().strip_prefix(src).unwrap_or(entry.path());
+        let name = if rel.as_os_str().is_empty() {
+            base.clone()
+        } else {
+            format!("{}/{}", base, rel.to_string_lossy())
+        };
+        entries.push(Entry {
+            host_path: entry.path().to_path_buf(),
+            name,
+            size: if meta.is_file() { meta.len() } else { 0 },
+            is_dir: meta.is_dir(),
+        });
+    }
+    Ok(entries)
+}
+
+fn archive_err(path: &Path, e: io::Error) -> BoxliteError {
+    BoxliteError::Storage(format!("failed to archive {}: {}", path.display(), e))
+}
+
+/// Collects archive bytes into chunks and sends each to the upload.
+struct ChunkWriter {
+    tx: mpsc::Sender<Vec<u8>>,
+    buf: Vec<u8>,
+    progress: CopyProgress,
+    observer: Option<CopyObserver>,
+    /// The upload stopped taking chunks
+    closed: bool,
+}
+
+impl ChunkWriter {
+    fn send_chunk(&mut self) -> io::Result<()> {
+        if !self.buf.is_empty() {
+            let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+            if self.tx.blocking_send(chunk).is_err() {
+                self.closed = true;
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "upload stopped"));
+            }
+        }
+        if let Some(observer) = &self.observer {
+            observer.report(&self.progress);
+        }
+        Ok(())
+    }
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == CHUNK_SIZE {
+            self.send_chunk()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    fn tree(dir: &Path, files: usize) -> PathBuf {
+        let src = dir.join("src");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        for i in 0..files {
+            std::fs::write(src.join("sub").join(format!("f{i}")), b"data").unwrap();
+        }
+        src
+    }
+
+    /// Archive `src` the way `copy_batched` does and return the tar bytes.
+    fn archive(src: &Path, opts: &CopyOptions) -> BoxliteResult<Vec<u8>> {
+        let (tx, mut rx) = mpsc::channel(CHUNKS_IN_FLIGHT);
+        let src = src.to_path_buf();
+        let opts = opts.clone();
+        let archiver = std::thread::spawn(move || archive_dir(&src, &opts, tx));
+        let mut tar = Vec::new();
+        while let Some(chunk) = rx.blocking_recv() {
+            tar.extend(chunk);
+        }
+        archiver.join().unwrap().map(|()| tar)
+    }
+
+    #[test]
+    fn test_should_batch_many_files_or_when_asked() {
+        let dir = tempfile::tempdir().unwrap();
+        let small = tree(&dir.path().join("small"), 3);
+        let large = tree(&dir.path().join("large"), BATCH_THRESHOLD_FILES);
+
+        assert!(!should_batch(&small, &CopyOptions::default()));
+        assert!(should_batch(&small, &CopyOptions::default().batch(true)));
+        assert!(should_batch(&large, &CopyOptions::default()));
+        // Resumable copies and single files are never batched
+        assert!(!should_batch(&large, &CopyOptions::default().resume(true)));
+        assert!(!should_batch(
+            &small.join("sub/f0"),
+            &CopyOptions::default().batch(true)
+        ));
+    }
+
+    #[test]
+    fn test_archive_names_follow_include_parent() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = tree(dir.path(), 2);
+
+        for (include_parent, expected) in [
+            (true, ["src/sub/f0", "src/sub/f1"]),
+            (false, ["sub/f0", "sub/f1"]),
+        ] {
+            let opts = CopyOptions::default().include_parent(include_parent);
+            let tar = archive(&src, &opts).unwrap();
+            let files: Vec<PathBuf> = tar::Archive::new(&tar[..])
+                .entries()
+                .unwrap()
+                .map(|e| e.unwrap())
+                .filter(|e| e.header().entry_type().is_file())
+                .map(|e| {
+                    let path = e.path().unwrap();
+                    path.components()
+                        .filter(|c| matches!(c, std::path::Component::Normal(_)))
+                        .collect()
+                })
+                .collect();
+            assert_eq!(files, expected.map(PathBuf::from));
+        }
+    }
+
+    #[test]
+    fn test_archive_reports_aggregate_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = tree(dir.path(), 10);
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&reports);
+        let opts = CopyOptions::default().on_progress(move |p| seen.lock().unwrap().push(*p));
+
+        archive(&src, &opts).unwrap();
+
+        let reports = reports.lock().unwrap();
+        let last = reports.last().unwrap();
+        assert_eq!(
+            *last,
+            CopyProgress {
+                files_sent: 10,
+                files_total: 10,
+                bytes_sent: 40,
+                bytes_total: 40,
+            }
+        );
+        // Progress is reported per chunk, not per file
+        assert!(reports.len() < 10, "{}", reports.len());
+    }
+
+    #[test]
+    fn test_archive_stops_quietly_when_upload_goes_away() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        std::fs::create_dir(&src).unwrap();
+        std::fs::write(src.join("big"), vec![0u8; 4 * CHUNK_SIZE]).unwrap();
+
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+        archive_dir(&src, &CopyOptions::default(), tx).unwrap();
+    }
+}
@@ -4,31 +4,62 @@
 // IMPORTS
 // ============================================================================
 
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::sync::atomic::Ordering;
+use std::time::Duration;
 
 use parking_lot::RwLock;
 use tar;
 use tokio::sync::OnceCell;
 use tokio_util::sync::CancellationToken;
 
+use boxlite_shared::archive::{ArchiveGuard, ArchiveLimits, EntryAction};
+use boxlite_shared::constants::{freeze, guest_logs};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
+use super::batch_copy;
+use super::capture::{self, CapturedOutput, ExecOutputPaths};
 use super::config::BoxConfig;
-use super::exec::{BoxCommand, ExecStderr, ExecStdin, ExecStdout, Execution};
+use super::core_dump::CoreDump;
+use super::encoding::DecodeStatus;
+use super::environment::EnvironmentReport;
+use super::exec::{BoxCommand, ExecStdin, Execution};
+use super::guest_log::GuestAgentLog;
+use super::output_filter::OutputFilters;
+use super::provision::{self, ProvisionLog, SetupOutput};
+use super::service::{self, ServiceInfo, ServicePolicy, ServiceSpec};
+use super::snapshot_types::{SnapshotMetadata, SnapshotRetention};
+use super::start_failure::StartFailure;
 use super::state::BoxState;
+use super::transfer::{BatchUploader, ResumableCopy};
+use super::tunnel::TunnelHandle;
 use crate::disk::Disk;
 #[cfg(target_os = "linux")]
 use crate::fs::BindMountHandle;
-use crate::litebox::copy::CopyOptions;
-use crate::lock::LockGuard;
+use crate::litebox::copy::{self, CopyOptions, CopyOwnership};
+use crate::lock::BoxOperation;
 use crate::metrics::{BoxMetrics, BoxMetricsStorage};
+use crate::net::{BoxNetwork, HostEntry};
 use crate::portal::GuestSession;
+use crate::portal::interfaces::ExecutionInterface;
+use crate::portal::interfaces::exec::ExecComponents;
+use crate::runtime::advanced_options::ResourceLimits;
+use crate::runtime::in_flight::InFlightGuard;
+use crate::runtime::options::SetupFailurePolicy;
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use crate::runtime::types::BoxStatus;
-use crate::vmm::controller::VmmHandler;
+use crate::vmm::controller::{VmmHandler, watchdog};
+use crate::vmm::{IdleSample, ShimExit, idle};
 use crate::{BoxID, BoxInfo};
 
+/// How long `adopt()` and `detach()` wait for the shim to acknowledge.
+const OWNERSHIP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a stopping service gets between SIGTERM and SIGKILL.
+const SERVICE_STOP_GRACE: Duration = Duration::from_secs(5);
+
 // ============================================================================
 // TYPE ALIASES
 // ============================================================================
@@ -96,6 +127,12 @@ pub(crate) struct BoxImpl {
     // --- Always available ---
     pub(crate) config: BoxConfig,
     pub(crate) state: RwLock<BoxState>,
+    /// Current resource limits (may differ from `config` after an update).
+    resource_limits: RwLock<ResourceLimits>,
+    /// Current snapshot retention policy (may differ from `config` after an update).
+    snapshot_retention: RwLock<Option<SnapshotRetention>>,
+    /// Output filters of registered command templates, by prepared ID.
+    prepared_filters: RwLock<HashMap<String, OutputFilters>>,
     pub(crate) runtime: SharedRuntimeImpl,
     /// Cancellation token for this box (child of runtime's token).
     /// When cancelled (via stop() or runtime shutdown), all operations abort gracefully.
@@ -125,9 +162,14 @@ impl BoxImpl {
         runtime: SharedRuntimeImpl,
         shutdown_token: CancellationToken,
     ) -> Self {
+        let resource_limits = config.options.advanced.security.resource_limits.clone();
+        let snapshot_retention = config.options.snapshot_retention.clone();
         Self {
             config,
             state: RwLock::new(state),
+            resource_limits: RwLock::new(resource_limits),
+            snapshot_retention: RwLock::new(snapshot_retention),
+            prepared_filters: RwLock::new(HashMap::new()),
             runtime,
             shutdown_token,
             live: OnceCell::new(),
@@ -148,7 +190,24 @@ impl BoxImpl {
 
     pub(crate) fn info(&self) -> BoxInfo {
         let state = self.state.read();
-        BoxInfo::new(&self.config, &state)
+        let mut info = BoxInfo::new(&self.config, &state);
+        info.resource_limits = self.resource_limits.read().clone();
+        info
+    }
+
+    /// Admit a mutating operation on this handle.
+    ///
+    /// Fails with `ShuttingDown` once the runtime began shutting down and
+    /// with `Stopped` after `stop()`. Shutdown waits for admitted operations
+    /// (up to its timeout) before stopping boxes.
+    fn admit(&self, operation: &str) -> BoxliteResult<InFlightGuard<'_>> {
+        let op = self.runtime.in_flight.enter(operation)?;
+        if self.shutdown_token.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "Handle invalidated after stop(). Use runtime.get() to get a new handle.".into(),
+            ));
+        }
+        Ok(op)
     }
 
     // ========================================================================
@@ -157,17 +216,12 @@ impl BoxImpl {
 
     /// Start the box (initialize VM).
     ///
-    /// For Configured boxes: full pipeline (filesystem, rootfs, spawn, connect, init)
-    /// For Stopped boxes: restart pipeline (reuse rootfs, spawn, connect, init)
+    /// For Created boxes: full pipeline (filesystem, rootfs, spawn, connect, init)
+    /// For Exited boxes: restart pipeline (reuse rootfs, spawn, connect, init)
     ///
     /// This is idempotent - calling start() on a Running box is a no-op.
     pub(crate) async fn start(&self) -> BoxliteResult<()> {
-        // Check if already shutdown (via stop() or runtime shutdown)
-        if self.shutdown_token.is_cancelled() {
-            return Err(BoxliteError::Stopped(
-                "Handle invalidated after stop(). Use runtime.get() to get a new handle.".into(),
-            ));
-        }
+        let _op = self.admit("start box")?;
 
         // Check current status
         let status = self.state.read().status;
@@ -188,23 +242,156 @@ impl BoxImpl {
         // Trigger lazy initialization (this does the actual work)
         let _ = self.live_state().await?;
 
+        self.runtime.refresh_box_dns(&self.config).await;
         Ok(())
     }
 
     pub(crate) async fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution> {
-        use boxlite_shared::constants::executor as executor_const;
+        let _op = self.admit("exec in box")?;
 
-        // Check if box is stopped before proceeding (via stop() or runtime shutdown)
-        if self.shutdown_token.is_cancelled() {
-            return Err(BoxliteError::Stopped(
-                "Handle invalidated after stop(). Use runtime.get() to get a new handle.".into(),
-            ));
+        let info = self.info();
+        command.validate_scratch(info.memory_mib)?;
+        let command = command.render_for_exec(&info)?;
+        let live = self.live_state().await?;
+        self.exec_on(live, command).await
+    }
+
+    /// Execute a rendered command on an already initialized VM.
+    async fn exec_on(&self, live: &LiveState, command: BoxCommand) -> BoxliteResult<Execution> {
+        let command = self.resolve_command(command);
+        let detach = command.detach;
+        if detach {
+            capture::sweep_captured_output(&self.execs_dir(), capture::CAPTURE_MAX_AGE);
+        }
+        let options = &self.config.options;
+        let filters = OutputFilters::for_command(&command, &options.env, &options.redact_env);
+        filters.validate()?;
+
+        let mut exec_interface = live.guest_session.execution().await?;
+        let result = exec_interface
+            .exec(command, self.shutdown_token.clone())
+            .await;
+
+        let execution = self.finish_exec(live, exec_interface, result, &filters)?;
+        if !detach {
+            return Ok(execution);
         }
+        let paths = ExecOutputPaths::new(&self.execs_dir(), execution.id())?;
+        Ok(execution.with_output_paths(paths))
+    }
+
+    /// Read the captured output of a detached execution.
+    ///
+    /// Reads files on the host, so it works whether or not the box is running.
+    ///
+    /// The guest writes the capture files, so only the box's env redaction
+    /// applies; per-command filters do not.
+    pub(crate) fn exec_output(&self, exec_id: &str) -> BoxliteResult<CapturedOutput> {
+        let output = capture::read_captured_output(&self.execs_dir(), exec_id)?;
+        let options = &self.config.options;
+        let filters = OutputFilters::for_box(&options.env, &options.redact_env);
+        Ok(CapturedOutput {
+            stdout: filters.redact(&output.stdout),
+            stderr: filters.redact(&output.stderr),
+        })
+    }
+
+    fn execs_dir(&self) -> std::path::PathBuf {
+        use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+
+        BoxFilesystemLayout::new(
+            self.config.box_home.clone(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        )
+        .execs_dir()
+    }
+
+    fn socket_paths(&self) -> Vec<std::path::PathBuf> {
+        use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+
+        BoxFilesystemLayout::new(
+            self.config.box_home.clone(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        )
+        .socket_paths()
+    }
+
+    /// Register a command template with the guest; returns the prepared ID.
+    pub(crate) async fn prepare_exec(&self, command: BoxCommand) -> BoxliteResult<String> {
+        let _op = self.admit("prepare command in box")?;
+
+        let info = self.info();
+        command.validate_scratch(info.memory_mib)?;
+        let command = command.render_for_prepare(&info)?;
+        let live = self.live_state().await?;
+        let command = self.resolve_command(command);
+        let options = &self.config.options;
+        let filters = OutputFilters::for_command(&command, &options.env, &options.redact_env);
+        filters.validate()?;
+
+        let mut exec_interface = live.guest_session.execution().await?;
+        let prepared_id = exec_interface.prepare(&command).await?;
+        self.prepared_filters
+            .write()
+            .insert(prepared_id.clone(), filters);
+        Ok(prepared_id)
+    }
+
+    /// Execute a prepared template with extra args.
+    ///
+    /// Fails with `NotFound` (not counted as an exec error) when the guest no
+    /// longer knows the template.
+    pub(crate) async fn exec_prepared(
+        &self,
+        prepared_id: &str,
+        args: Vec<String>,
+    ) -> BoxliteResult<Execution> {
+        let _op = self.admit("exec in box")?;
 
         let live = self.live_state().await?;
 
+        let mut exec_interface = live.guest_session.execution().await?;
+        let result = exec_interface
+            .exec_prepared(prepared_id, args, self.shutdown_token.clone())
+            .await;
+        if let Err(BoxliteError::NotFound(msg)) = result {
+            return Err(BoxliteError::NotFound(msg));
+        }
+
+        let filters = self
+            .prepared_filters
+            .read()
+            .get(prepared_id)
+            .cloned()
+            .unwrap_or_else(|| {
+                let options = &self.config.options;
+                OutputFilters::for_box(&options.env, &options.redact_env)
+            });
+        self.finish_exec(live, exec_interface, result, &filters)
+    }
+
+    /// Drop a prepared template from the guest.
+    ///
+    /// Does not start the box: a stopped box's guest holds no templates.
+    pub(crate) async fn unprepare_exec(&self, prepared_id: &str) -> BoxliteResult<()> {
+        self.prepared_filters.write().remove(prepared_id);
+
+        let Some(live) = self.live.get() else {
+            return Ok(());
+        };
+        let mut exec_interface = live.guest_session.execution().await?;
+        exec_interface.unprepare(prepared_id).await
+    }
+
+    /// Apply box defaults to a command: executor routing, exec env and
+    /// working dir.
+    fn resolve_command(&self, command: BoxCommand) -> BoxCommand {
+        use boxlite_shared::constants::executor as executor_const;
+
         // Inject container ID into environment if not already set
-        let command = if command
+        let mut command = if command
             .env
             .as_ref()
             .map(|env| env.iter().any(|(k, _)| k == executor_const::ENV_VAR))
@@ -218,17 +405,32 @@ impl BoxImpl {
             )
         };
 
-        // Set working directory from BoxOptions if not set in command
-        let command = match (&command.working_dir, &self.config.options.working_dir) {
-            (None, Some(dir)) => command.working_dir(dir),
-            _ => command,
-        };
+        // Environment added after start, under the command's own variables.
+        // Pushed directly: the command's edits of these keys still apply.
+        let exec_env = self.state.read().exec_env.clone();
+        let env = command.env.get_or_insert_with(Vec::new);
+        for (key, value) in exec_env {
+            if !env.iter().any(|(k, _)| *k == key) {
+                env.push((key, value));
+            }
+        }
 
-        let mut exec_interface = live.guest_session.execution().await?;
-        let result = exec_interface
-            .exec(command, self.shutdown_token.clone())
-            .await;
+        // Set working directory from BoxOptions if not set in command,
+        // unless the command takes the init process's
+        match (&command.working_dir, &self.config.options.working_dir) {
+            (None, Some(dir)) if !command.inherit_runtime_env => command.working_dir(dir),
+            _ => command,
+        }
+    }
 
+    /// Record exec metrics and wrap the started execution.
+    fn finish_exec(
+        &self,
+        live: &LiveState,
+        exec_interface: ExecutionInterface,
+        result: BoxliteResult<ExecComponents>,
+        filters: &OutputFilters,
+    ) -> BoxliteResult<Execution> {
         // Instrument metrics
         live.metrics.increment_commands_executed();
         self.runtime
@@ -236,6 +438,13 @@ impl BoxImpl {
             .total_commands
             .fetch_add(1, Ordering::Relaxed);
 
+        if let Some(image) = self.config.options.rootfs.image() {
+            self.runtime
+                .runtime_metrics
+                .per_image
+                .command_executed(image);
+        }
+
         if result.is_err() {
             live.metrics.increment_exec_errors();
             self.runtime
@@ -245,14 +454,16 @@ impl BoxImpl {
         }
 
         let components = result?;
+        let stdin = ExecStdin::new(components.stdin_tx).stopped_by(self.shutdown_token.clone());
         Ok(Execution::new(
             components.execution_id,
             Box::new(exec_interface),
             components.result_rx,
-            Some(ExecStdin::new(components.stdin_tx)),
-            Some(ExecStdout::new(components.stdout_rx)),
-            Some(ExecStderr::new(components.stderr_rx)),
-        ))
+            Some(stdin),
+            filters.output(components.stdout_rx, DecodeStatus::default()),
+            filters.output(components.stderr_rx, DecodeStatus::default()),
+        )
+        .stopped_by(self.shutdown_token.clone()))
     }
 
     pub(crate) async fn metrics(&self) -> BoxliteResult<BoxMetrics> {
@@ -264,16 +475,22 @@ impl BoxImpl {
         }
 
         let live = self.live_state().await?;
-        let handler = live
+        let raw = live
             .handler
             .lock()
-            .map_err(|e| BoxliteError::Internal(format!("handler lock poisoned: {}", e)))?;
-        let raw = handler.metrics()?;
+            .map_err(|e| BoxliteError::Internal(format!("handler lock poisoned: {}", e)))?
+            .metrics()?;
+        let swap_bytes = if self.config.options.swap_mib.unwrap_or(0) > 0 {
+            self.swap_used_bytes(&live).await
+        } else {
+            None
+        };
 
         Ok(BoxMetrics::from_storage(
             &live.metrics,
             raw.cpu_percent,
             raw.memory_bytes,
+            swap_bytes,
             None,
             None,
             None,
@@ -281,11 +498,95 @@ impl BoxImpl {
         ))
     }
 
+    /// What the box is doing, for idle auto-stop.
+    ///
+    /// `None`, which counts as activity, while the box idles in a warm pool
+    /// or when the guest can't tell.
+    pub(crate) async fn idle_sample(&self) -> Option<IdleSample> {
+        if self.state.read().warm_pool.is_some() {
+            return None;
+        }
+        let live = self.live.get()?;
+        let activity = async { live.guest_session.guest().await?.activity().await };
+        let (running_execs, since_activity) = match activity.await {
+            Ok(activity) => activity,
+            Err(e) => {
+                tracing::debug!(box_id = %self.id(), error = %e, "Failed to read guest activity");
+                return None;
+            }
+        };
+        let cpu_percent = live
+            .handler
+            .lock()
+            .ok()
+            .and_then(|handler| handler.metrics().ok())
+            .and_then(|metrics| metrics.cpu_percent)
+            .map(f64::from);
+        Some(IdleSample {
+            running_execs,
+            since_activity,
+            cpu_percent,
+        })
+    }
+
+    /// Stop the box after `idle` without activity, recording why in its
+    /// exit file.
+    pub(crate) async fn stop_idle(&self, idle: Duration) -> BoxliteResult<()> {
+        use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+
+        tracing::info!(
+            box_id = %self.id(),
+            idle_secs = idle.as_secs(),
+            "Box idle, stopping it"
+        );
+        self.stop().await?;
+
+        // Written once stopped: an exit file newer than the PID file marks
+        // the shim as gone
+        let exit_file = BoxFilesystemLayout::new(
+            self.config.box_home.clone(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        )
+        .exit_file_path();
+        if let Err(e) = idle::record_idle_stop(&exit_file, idle) {
+            tracing::warn!(box_id = %self.id(), error = %e, "Failed to record idle stop");
+        }
+        Ok(())
+    }
+
     pub(crate) async fn stop(&self) -> BoxliteResult<()> {
+        self.stop_waiting(self.runtime.lock_wait, self.shutdown_grace_period())
+            .await?;
+        self.runtime.refresh_box_dns(&self.config).await;
+        Ok(())
+    }
+
+    /// The box's shutdown grace period.
+    pub(crate) fn shutdown_grace_period(&self) -> Duration {
+        self.config.options.effective_shutdown_grace_period()
+    }
+
+    /// Stop, waiting up to `lock_wait` for a concurrent operation to finish
+    /// and giving the guest up to `grace` to shut down before it is killed.
+    pub(crate) async fn stop_waiting(
+        &self,
+        lock_wait: Option<Duration>,
+        grace: Duration,
+    ) -> BoxliteResult<()> {
         // Early exit if already stopped (idempotent, prevents double-counting)
         // Note: We check status, not shutdown_token, because the token may be cancelled
         // by runtime.shutdown() before stop() is called on each box.
-        if self.state.read().status == BoxStatus::Stopped {
+        if self.state.read().status == BoxStatus::Exited {
+            return Ok(());
+        }
+
+        let _lock = self
+            .runtime
+            .lock_box_waiting(self.id(), BoxOperation::Stop, lock_wait)
+            .await?;
+        // Another handle may have stopped the box while we waited
+        if self.state.read().status == BoxStatus::Exited {
             return Ok(());
         }
 
@@ -294,9 +595,21 @@ impl BoxImpl {
 
         // Only try to stop VM if LiveState exists
         if let Some(live) = self.live.get() {
-            // Gracefully shut down guest
-            if let Ok(mut guest) = live.guest_session.guest().await {
-                let _ = guest.shutdown().await;
+            // Services go before the entrypoint, dependents first
+            self.stop_services(live).await;
+
+            // Gracefully shut down guest, giving up after the grace period
+            if !grace.is_zero()
+                && let Ok(mut guest) = live.guest_session.guest().await
+                && tokio::time::timeout(grace, guest.shutdown(grace))
+                    .await
+                    .is_err()
+            {
+                tracing::warn!(
+                    box_id = %self.config.id,
+                    grace_ms = grace.as_millis() as u64,
+                    "Guest shutdown exceeded the grace period, killing the box"
+                );
             }
 
             // Stop handler
@@ -305,6 +618,11 @@ impl BoxImpl {
             }
         }
 
+        self.runtime
+            .runtime_metrics
+            .per_image
+            .box_stopped(self.config.id.as_str());
+
         // Clean up PID file (single source of truth)
         let pid_file = self
             .runtime
@@ -330,11 +648,11 @@ impl BoxImpl {
         {
             let mut state = self.state.write();
 
-            // Only transition to Stopped if we were Running (or other active state).
-            // If we were Configured (never started), stay Configured so next start()
+            // Only transition to Exited if we were Running (or other active state).
+            // If we were Created (never started), stay Created so next start()
             // triggers full initialization (creating disks).
-            if !state.status.is_configured() {
-                state.mark_stop();
+            if !state.status.is_created() {
+                state.mark_exited(Some(0));
             }
 
             if was_persisted {
@@ -395,12 +713,7 @@ impl BoxImpl {
         container_dst: &str,
         opts: CopyOptions,
     ) -> BoxliteResult<()> {
-        // Check if box is stopped before proceeding
-        if self.shutdown_token.is_cancelled() {
-            return Err(BoxliteError::Stopped(
-                "Handle invalidated after stop(). Use runtime.get() to get a new handle.".into(),
-            ));
-        }
+        let _op = self.admit("copy into box")?;
 
         // Ensure box is running
         let live = self.live_state().await?;
@@ -409,75 +722,861 @@ impl BoxImpl {
             opts.validate_for_dir()?;
         }
 
-        if container_dst.is_empty() {
-            return Err(BoxliteError::Config(
-                "destination path cannot be empty".into(),
-            ));
+        crate::validate::guest_path(container_dst)?;
+
+        if opts.is_resumable() {
+            let copy = ResumableCopy::new(
+                host_src,
+                container_dst,
+                opts.clone(),
+                &self.config.box_home,
+                &self.runtime.layout.temp_dir(),
+            );
+            let mut uploader = GuestUploader {
+                session: &live.guest_session,
+                destination: container_dst,
+                container_id: self.container_id(),
+                ownership: opts.ownership,
+                limits: opts.archive_limits,
+            };
+            return copy.run(&mut uploader).await;
         }
 
+        if batch_copy::should_batch(host_src, &opts) {
+            let mut files_iface = live.guest_session.files().await?;
+            return batch_copy::copy_batched(
+                &mut files_iface,
+                host_src,
+                container_dst,
+                self.container_id(),
+                &opts,
+            )
+            .await;
+        }
+
+        // The tar holds at least the source's file contents
+        let temp_root = self.runtime.layout.temp_dir();
+        self.runtime
+            .disk_space
+            .ensure(&temp_root, super::dir_size(host_src), "copy into box")?;
+        let temp_tar = temp_root.join(format!("cp-in-{}.tar", self.config.id.as_str()));
+
+        // Remove the tar whether or not it was completed and sent
+        let result = async {
+            build_tar_from_host(host_src, &temp_tar, &opts)?;
+
+            let mut files_iface = live.guest_session.files().await?;
+            files_iface
+                .upload_tar(
+                    &temp_tar,
+                    container_dst,
+                    Some(self.container_id()),
+                    true,
+                    opts.overwrite,
+                    opts.ownership,
+                    opts.archive_limits,
+                )
+                .await
+        }
+        .await;
+
+        let _ = tokio::fs::remove_file(&temp_tar).await;
+        result
+    }
+
+    pub(crate) async fn copy_out(
+        &self,
+        container_src: &str,
+        host_dst: &std::path::Path,
+        opts: CopyOptions,
+    ) -> BoxliteResult<()> {
+        let _op = self.admit("copy out of box")?;
+
+        // Ensure box is running
+        let live = self.live_state().await?;
+
+        crate::validate::guest_path(container_src)?;
+        copy::check_dest_allowed(&self.runtime.allowed_dest_roots, host_dst)?;
+
         let temp_tar = self
             .runtime
             .layout
             .temp_dir()
-            .join(format!("cp-in-{}.tar", self.config.id.as_str()));
-
-        build_tar_from_host(host_src, &temp_tar, &opts)?;
+            .join(format!("cp-out-{}.tar", self.config.id.as_str()));
 
-        let mut files_iface = live.guest_session.files().await?;
-        files_iface
-            .upload_tar(
+        let result = async {
+            let mut files_iface = live.guest_session.files().await?;
+            files_iface
+                .download_tar(
+                    container_src,
+                    Some(self.container_id()),
+                    opts.include_parent,
+                    opts.follow_symlinks,
+                    &temp_tar,
+                )
+                .await?;
+
+            extract_tar_to_host(
                 &temp_tar,
-                container_dst,
-                Some(self.container_id()),
-                true,
+                host_dst,
                 opts.overwrite,
+                opts.chown_to_caller,
+                opts.follow_symlinks,
+                opts.archive_limits,
             )
-            .await?;
+        }
+        .await;
 
         let _ = tokio::fs::remove_file(&temp_tar).await;
+        result
+    }
+
+    /// Relay TCP connections accepted on `bind` to `127.0.0.1:guest_port`
+    /// inside the box. The tunnel closes when the handle is dropped or the
+    /// box stops.
+    pub(crate) async fn tunnel(
+        &self,
+        guest_port: u16,
+        bind: SocketAddr,
+    ) -> BoxliteResult<TunnelHandle> {
+        let _op = self.admit("open tunnel")?;
+
+        if guest_port == 0 {
+            return Err(BoxliteError::InvalidArgument(
+                "guest port must not be 0".into(),
+            ));
+        }
+
+        // Ensure box is running
+        let live = self.live_state().await?;
+
+        let listener = tokio::net::TcpListener::bind(bind)
+            .await
+            .map_err(|e| BoxliteError::Network(format!("Failed to listen on {}: {}", bind, e)))?;
+        let iface = live.guest_session.tunnel().await?;
+        TunnelHandle::open(
+            listener,
+            guest_port,
+            iface,
+            self.shutdown_token.child_token(),
+        )
+        .await
+    }
+
+    /// Last `tail_lines` lines of the guest kernel log.
+    pub(crate) async fn guest_dmesg(&self, tail_lines: usize) -> BoxliteResult<Vec<String>> {
+        let _op = self.admit("read guest kernel log")?;
+        let live = self.running_live_state("kernel log").await?;
+        let tail_lines = tail_lines.min(guest_logs::MAX_DMESG_LINES as usize) as u32;
+        live.guest_session.guest().await?.dmesg(tail_lines).await
+    }
+
+    /// Last `tail_bytes` bytes of the guest agent's log.
+    pub(crate) async fn guest_agent_log(&self, tail_bytes: usize) -> BoxliteResult<GuestAgentLog> {
+        let _op = self.admit("read guest agent log")?;
+        let live = self.running_live_state("guest agent log").await?;
+        let tail_bytes = tail_bytes.min(guest_logs::MAX_AGENT_LOG_BYTES as usize) as u32;
+        let mut guest = live.guest_session.guest().await?;
+        let (data, truncated) = guest.agent_log(tail_bytes).await?;
+        Ok(GuestAgentLog {
+            text: String::from_utf8_lossy(&data).into_owned(),
+            truncated,
+        })
+    }
+
+    /// Swap in use inside the VM, or None if the guest can't tell.
+    async fn swap_used_bytes(&self, live: &LiveState) -> Option<u64> {
+        let usage = async { live.guest_session.guest().await?.swap_usage().await };
+        match usage.await {
+            Ok((_total, used)) => Some(used),
+            Err(e) => {
+                tracing::debug!(box_id = %self.id(), error = %e, "Failed to read swap usage");
+                None
+            }
+        }
+    }
+
+    /// Core dumps captured in the container, oldest first.
+    pub(crate) async fn list_core_dumps(&self) -> BoxliteResult<Vec<CoreDump>> {
+        let _op = self.admit("list core dumps")?;
+        let live = self.running_live_state("core dumps").await?;
+        let mut container = live.guest_session.container().await?;
+        let dumps = container.list_core_dumps(self.container_id()).await?;
+        Ok(dumps.into_iter().map(CoreDump::from).collect())
+    }
+
+    /// Rewrite the extra entries of the guest's `/etc/hosts`.
+    ///
+    /// No-op unless the box is running and the entries changed.
+    pub(crate) async fn update_hosts(&self, hosts: Vec<HostEntry>) -> BoxliteResult<()> {
+        let _op = self.admit("update box hosts")?;
+        {
+            let state = self.state.read();
+            if !state.status.is_running() || state.hosts == hosts {
+                return Ok(());
+            }
+        }
+
+        let live = self.live_state().await?;
+        let mut container = live.guest_session.container().await?;
+        container
+            .update_hosts(self.container_id(), hosts.clone())
+            .await?;
+
+        let mut state = self.state.write();
+        state.set_hosts(hosts);
+        self.runtime.box_manager.save_box(&self.config.id, &state)
+    }
+
+    /// Flush the box's filesystems and freeze/thaw its container disk.
+    pub(crate) async fn sync(&self, freeze_cap: Duration) -> BoxliteResult<Duration> {
+        let freeze_ms = u32::try_from(freeze_cap.as_millis())
+            .ok()
+            .filter(|ms| *ms <= freeze::MAX_CAP_MS)
+            .ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!(
+                    "freeze cap {:?} exceeds the maximum of {} ms",
+                    freeze_cap,
+                    freeze::MAX_CAP_MS
+                ))
+            })?;
+
+        let _op = self.admit("sync box")?;
+        let status = self.state.read().status;
+        if !status.is_running() {
+            return Err(BoxliteError::InvalidState(format!(
+                "Cannot sync box {}: box is {}",
+                self.id(),
+                status
+            )));
+        }
+        let live = self.live_state().await?;
+        let frozen = live
+            .guest_session
+            .guest()
+            .await?
+            .sync(self.container_id(), freeze_ms)
+            .await?;
+        tracing::info!(box_id = %self.id(), ?frozen, "Synced box filesystems");
+        Ok(frozen)
+    }
+
+    /// Live state of a running box, without starting a stopped one.
+    ///
+    /// `what` names the log being read, for the error pointing at the
+    /// console log instead.
+    async fn running_live_state(&self, what: &str) -> BoxliteResult<&LiveState> {
+        if self.runtime.box_manager.box_by_id(self.id())?.is_none() {
+            return Err(BoxliteError::NotFound(self.id().to_string()));
+        }
+
+        let status = self.state.read().status;
+        if !status.is_running() {
+            return Err(BoxliteError::InvalidState(format!(
+                "Cannot read the {} of box {}: box is {}. The console log of its last boot is at {}",
+                what,
+                self.id(),
+                status,
+                self.console_log_path().display()
+            )));
+        }
+
+        self.live_state().await
+    }
+
+    /// Diagnostics from the most recent failed start, if any.
+    ///
+    /// Cleared when the box next starts successfully.
+    /// Replace the box's resource limits.
+    ///
+    /// The limits are persisted and used from the next start. On a running
+    /// box, disk I/O limits are also applied immediately through its cgroup;
+    /// if that fails the error is returned, but the persisted limits stay.
+    pub(crate) async fn update_resource_limits(&self, limits: ResourceLimits) -> BoxliteResult<()> {
+        let _lock = self
+            .runtime
+            .lock_box(self.id(), BoxOperation::Resize)
+            .await?;
+
+        let mut config = self.current_config();
+        config.options.advanced.security.resource_limits = limits.clone();
+        self.runtime.box_manager.update_config(&config)?;
+
+        let previous = std::mem::replace(&mut *self.resource_limits.write(), limits.clone());
+        tracing::info!(box_id = %self.config.id, ?limits, "Resource limits updated");
+
+        let running = self.state.read().status == BoxStatus::Running;
+        if running && (limits.has_disk_io_limits() || previous.has_disk_io_limits()) {
+            crate::jailer::update_disk_io_limits(
+                self.config.id.as_str(),
+                &self.config.box_home,
+                &limits,
+            )?;
+        }
+
         Ok(())
     }
 
-    pub(crate) async fn copy_out(
+    pub(crate) fn snapshot_retention(&self) -> Option<SnapshotRetention> {
+        self.snapshot_retention.read().clone()
+    }
+
+    /// Replace the box's snapshot retention policy and persist it.
+    pub(crate) fn set_snapshot_retention(
         &self,
-        container_src: &str,
-        host_dst: &std::path::Path,
-        opts: CopyOptions,
+        retention: Option<SnapshotRetention>,
     ) -> BoxliteResult<()> {
-        // Check if box is stopped before proceeding
-        if self.shutdown_token.is_cancelled() {
-            return Err(BoxliteError::Stopped(
-                "Handle invalidated after stop(). Use runtime.get() to get a new handle.".into(),
-            ));
+        if let Some(retention) = &retention {
+            retention.validate()?;
+        }
+
+        let mut config = self.current_config();
+        config.options.snapshot_retention = retention.clone();
+        self.runtime.box_manager.update_config(&config)?;
+
+        tracing::info!(box_id = %self.config.id, ?retention, "Snapshot retention updated");
+        *self.snapshot_retention.write() = retention;
+        Ok(())
+    }
+
+    /// `config` with live updates applied, for persisting further changes.
+    fn current_config(&self) -> BoxConfig {
+        let mut config = self.config.clone();
+        config.options.advanced.security.resource_limits = self.resource_limits.read().clone();
+        config.options.snapshot_retention = self.snapshot_retention.read().clone();
+        config
+    }
+
+    /// The options and services a snapshot captures as its metadata.
+    pub(crate) fn snapshot_metadata(&self) -> SnapshotMetadata {
+        SnapshotMetadata {
+            options: self.current_config().options,
+            services: self.state.read().services.clone(),
+        }
+    }
+
+    /// Put back options and services captured by a snapshot, and persist them.
+    ///
+    /// The snapshot retention policy is kept. The caller holds the box's
+    /// operation lock and has checked that the box is stopped.
+    pub(crate) fn restore_metadata(&self, metadata: SnapshotMetadata) -> BoxliteResult<()> {
+        let mut config = self.current_config();
+        let retention = config.options.snapshot_retention.take();
+        config.options = metadata.options;
+        config.options.snapshot_retention = retention;
+        self.runtime.box_manager.update_config(&config)?;
+        *self.resource_limits.write() = config.options.advanced.security.resource_limits;
+
+        let mut state = self.state.write();
+        state.services = metadata.services;
+        self.runtime.box_manager.save_box(&self.config.id, &state)?;
+        tracing::info!(box_id = %self.config.id, "Box metadata restored from snapshot");
+        Ok(())
+    }
+
+    /// Refuse to start a box whose rootfs was shifted for another user
+    /// namespace mapping than the one it is configured with now.
+    fn check_userns_shift(&self, state: &BoxState) -> BoxliteResult<()> {
+        let Some(applied) = &state.userns_shift else {
+            return Ok(());
+        };
+        if self.config.options.userns.as_ref() == Some(applied) {
+            return Ok(());
+        }
+        Err(BoxliteError::InvalidState(format!(
+            "box '{}' has its rootfs shifted for user namespace mapping {:?}, but is \
+             configured with {:?}; the shift can't be undone, so start it with the \
+             mapping it was first started with",
+            self.config.id, applied, self.config.options.userns
+        )))
+    }
+
+    /// Fail if the box can't run again: it is Dead, or it ran before and
+    /// its storage is gone, which makes it Dead.
+    fn check_not_dead(&self, status: BoxStatus) -> BoxliteResult<()> {
+        let missing = match status {
+            BoxStatus::Dead => None,
+            BoxStatus::Exited => match self.config.missing_storage() {
+                Some(missing) => Some(missing),
+                None => return Ok(()),
+            },
+            _ => return Ok(()),
+        };
+        if let Some(missing) = &missing {
+            let mut state = self.state.write();
+            state.transition_to(BoxStatus::Dead)?;
+            self.runtime.box_manager.save_box(&self.config.id, &state)?;
+            tracing::warn!(
+                box_id = %self.config.id,
+                missing = %missing.display(),
+                "Box storage is missing, marked as dead"
+            );
+        }
+        Err(BoxliteError::InvalidState(format!(
+            "Box {} is dead{}: restore a disk snapshot or remove it",
+            self.id(),
+            missing
+                .map(|m| format!(" ({} is missing)", m.display()))
+                .unwrap_or_default()
+        )))
+    }
+
+    pub(crate) fn last_start_failure(&self) -> Option<StartFailure> {
+        StartFailure::load(&self.config.box_home)
+    }
+
+    pub(crate) fn last_exit(&self) -> Option<ShimExit> {
+        use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+
+        let exit_file = BoxFilesystemLayout::new(
+            self.config.box_home.clone(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        )
+        .exit_file_path();
+        ShimExit::from_exit_file(&exit_file)
+    }
+
+    /// Whether this process owns the box: it started the box with
+    /// `detach = false`, or adopted it.
+    pub(crate) fn is_owned_here(&self) -> bool {
+        self.state.read().owner_pid == Some(std::process::id())
+    }
+
+    /// Mark the box as idling in warm pool `pool`.
+    pub(crate) fn mark_warm(&self, pool: &str) -> BoxliteResult<()> {
+        let mut state = self.state.write();
+        state.set_warm_pool(Some(pool.to_string()));
+        self.runtime.box_manager.save_box(&self.config.id, &state)
+    }
+
+    /// Hand the box out of its warm pool, adding `env` to every later exec.
+    pub(crate) fn claim_warm(&self, env: &[(String, String)]) -> BoxliteResult<()> {
+        let mut state = self.state.write();
+        state.set_warm_pool(None);
+        state.add_exec_env(env);
+        self.runtime.box_manager.save_box(&self.config.id, &state)
+    }
+
+    /// Make this process the owner of a running box.
+    ///
+    /// The shim is asked to watch an owner FIFO whose write end this
+    /// process holds, so the box stops when this process exits (or the
+    /// handle is dropped), as if it had been started here with
+    /// `detach = false`. Fails if another live process owns the box.
+    pub(crate) async fn adopt(&self) -> BoxliteResult<()> {
+        let _op = self.admit("adopt box")?;
+
+        if !self.state.read().status.is_running() {
+            return Err(BoxliteError::InvalidState(format!(
+                "Cannot adopt box {}: box is not running",
+                self.id()
+            )));
+        }
+        let live = self.live_state().await?;
+        let _lock = self
+            .runtime
+            .lock_box(self.id(), BoxOperation::Adopt)
+            .await?;
+
+        // Another process may have adopted the box since this handle was made
+        let owner = self
+            .runtime
+            .box_manager
+            .box_by_id(&self.config.id)?
+            .and_then(|(_, state)| state.owner_pid);
+        match owner {
+            Some(pid) if pid == std::process::id() => return Ok(()),
+            Some(pid) if crate::util::is_process_alive(pid) => {
+                return Err(BoxliteError::InvalidState(format!(
+                    "Cannot adopt box {}: owned by process {}",
+                    self.id(),
+                    pid
+                )));
+            }
+            _ => {}
+        }
+
+        let fifo = self.box_dir().join(watchdog::OWNER_FIFO);
+        let keepalive = watchdog::create_owner_fifo(&fifo)?;
+        // The shim removes the FIFO once it is watching the read end
+        let shim_pid = self.request_ownership(live, &fifo, "adoption").await?;
+        live.handler
+            .lock()
+            .map_err(|e| BoxliteError::Internal(format!("handler lock poisoned: {}", e)))?
+            .set_keepalive(keepalive);
+
+        let mut state = self.state.write();
+        state.set_owner_pid(Some(std::process::id()));
+        self.runtime.box_manager.save_box(&self.config.id, &state)?;
+
+        tracing::info!(box_id = %self.config.id, shim_pid, "Adopted box");
+        if let Some(policy) = self.config.options.idle_policy() {
+            self.runtime.watch_idle(self.id(), policy);
+        }
+        Ok(())
+    }
+
+    /// Stop owning a running box, leaving it running as if it had been
+    /// started with `detach = true`.
+    ///
+    /// The shim is asked to ignore the hangup of its parent watchdog, so the
+    /// box outlives this process. A no-op if no live process owns the box.
+    /// Fails if another live process owns it, or if the box has
+    /// `auto_remove`: the next runtime on this home would remove it.
+    pub(crate) async fn detach(&self) -> BoxliteResult<()> {
+        let _op = self.admit("detach box")?;
+
+        if !self.state.read().status.is_running() {
+            return Err(BoxliteError::InvalidState(format!(
+                "Cannot detach box {}: box is not running",
+                self.id()
+            )));
+        }
+        if self.config.options.auto_remove {
+            return Err(BoxliteError::InvalidState(format!(
+                "Cannot detach box {}: auto_remove boxes do not outlive their runtime",
+                self.id()
+            )));
+        }
+        let live = self.live_state().await?;
+        let _lock = self
+            .runtime
+            .lock_box(self.id(), BoxOperation::Detach)
+            .await?;
+
+        let owner = self
+            .runtime
+            .box_manager
+            .box_by_id(&self.config.id)?
+            .and_then(|(_, state)| state.owner_pid);
+        match owner {
+            None => return Ok(()),
+            Some(pid) if pid == std::process::id() => {
+                let release = self.box_dir().join(watchdog::RELEASE_FILE);
+                std::fs::write(&release, b"").map_err(|e| {
+                    BoxliteError::Engine(format!(
+                        "Failed to create release file {}: {}",
+                        release.display(),
+                        e
+                    ))
+                })?;
+                // The shim removes the file once its watchdogs are disarmed
+                let shim_pid = self.request_ownership(live, &release, "release").await?;
+                tracing::info!(box_id = %self.config.id, shim_pid, "Detached box");
+            }
+            Some(pid) if crate::util::is_process_alive(pid) => {
+                return Err(BoxliteError::InvalidState(format!(
+                    "Cannot detach box {}: owned by process {}",
+                    self.id(),
+                    pid
+                )));
+            }
+            // The owner is gone, only the record is left
+            Some(_) => {}
+        }
+
+        let mut state = self.state.write();
+        state.set_owner_pid(None);
+        self.runtime.box_manager.save_box(&self.config.id, &state)
+    }
+
+    fn box_dir(&self) -> std::path::PathBuf {
+        self.runtime
+            .layout
+            .boxes_dir()
+            .join(self.config.id.as_str())
+    }
+
+    /// Send the shim an ownership request described by the file at
+    /// `marker`, and wait for the shim to remove it. Returns the shim PID.
+    async fn request_ownership(
+        &self,
+        live: &LiveState,
+        marker: &std::path::Path,
+        what: &str,
+    ) -> BoxliteResult<u32> {
+        let shim_pid = live
+            .handler
+            .lock()
+            .map_err(|e| BoxliteError::Internal(format!("handler lock poisoned: {}", e)))?
+            .pid();
+        if unsafe { libc::kill(shim_pid as i32, watchdog::OWNER_SIGNAL) } != 0 {
+            let _ = std::fs::remove_file(marker);
+            return Err(BoxliteError::Engine(format!(
+                "Failed to signal shim {} for {}: {}",
+                shim_pid,
+                what,
+                std::io::Error::last_os_error()
+            )));
+        }
+
+        let deadline = std::time::Instant::now() + OWNERSHIP_TIMEOUT;
+        while marker.exists() {
+            if std::time::Instant::now() >= deadline {
+                let _ = std::fs::remove_file(marker);
+                return Err(BoxliteError::Engine(format!(
+                    "Shim {} did not accept {} within {:?}",
+                    shim_pid, what, OWNERSHIP_TIMEOUT
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        Ok(shim_pid)
+    }
+
+    /// Run the setup commands again, starting the box if needed.
+    ///
+    /// On a running box the commands run right away; a `FailBox` failure is
+    /// returned but leaves the box running (unprovisioned).
+    pub(crate) async fn reprovision(&self) -> BoxliteResult<()> {
+        let _op = self.admit("reprovision box")?;
+
+        {
+            let mut state = self.state.write();
+            state.set_provisioned(false);
+            self.runtime.box_manager.save_box(&self.config.id, &state)?;
+        }
+
+        if let Some(live) = self.live.get() {
+            return self.provision(live).await;
+        }
+
+        // Initializing the VM provisions the box, as on first start
+        if self.state.read().status == BoxStatus::Running {
+            self.live_state().await.map(|_| ())
+        } else {
+            self.start().await
+        }
+    }
+
+    // ========================================================================
+    // SERVICES
+    // ========================================================================
+
+    /// Record service `name` and start it, starting the box if needed.
+    ///
+    /// The service is only recorded once its first run was spawned.
+    pub(crate) async fn spawn_service(
+        &self,
+        name: &str,
+        command: BoxCommand,
+        policy: ServicePolicy,
+    ) -> BoxliteResult<()> {
+        let _op = self.admit("spawn service in box")?;
+
+        service::validate_new(&self.state.read().services, name, &command, &policy)?;
+        command.validate_scratch(self.info().memory_mib)?;
+        let live = self.live_state().await?;
+
+        let spec = ServiceSpec {
+            name: name.to_string(),
+            command,
+            policy,
+        };
+        self.start_service(live, &spec).await?;
+
+        let mut state = self.state.write();
+        state.add_service(spec);
+        self.runtime.box_manager.save_box(&self.config.id, &state)?;
+        tracing::info!(box_id = %self.config.id, service = name, "Service added");
+        Ok(())
+    }
+
+    /// Stop service `name` until the box next starts.
+    pub(crate) async fn stop_service(&self, name: &str) -> BoxliteResult<()> {
+        let _op = self.admit("stop service in box")?;
+        self.require_service(name)?;
+        self.stop_running_service(name).await
+    }
+
+    /// Stop service `name` and forget it.
+    ///
+    /// Fails with `InvalidState` while other services depend on it.
+    pub(crate) async fn remove_service(&self, name: &str) -> BoxliteResult<()> {
+        let _op = self.admit("remove service from box")?;
+        self.require_service(name)?;
+        service::validate_remove(&self.state.read().services, name)?;
+        self.stop_running_service(name).await?;
+
+        let mut state = self.state.write();
+        state.remove_service(name);
+        self.runtime.box_manager.save_box(&self.config.id, &state)?;
+        tracing::info!(box_id = %self.config.id, service = name, "Service removed");
+        Ok(())
+    }
+
+    /// The box's services in registration order, with their current runs.
+    pub(crate) async fn list_services(&self) -> BoxliteResult<Vec<ServiceInfo>> {
+        let services = self.state.read().services.clone();
+        let mut statuses = HashMap::new();
+        if !services.is_empty() && self.state.read().status.is_running() {
+            let live = self.live_state().await?;
+            let mut supervisor = live.guest_session.supervisor().await?;
+            for status in supervisor.list_services().await? {
+                statuses.insert(status.name.clone(), status);
+            }
+        }
+
+        Ok(services
+            .iter()
+            .map(|spec| ServiceInfo::new(spec, statuses.remove(&spec.name)))
+            .collect())
+    }
+
+    fn require_service(&self, name: &str) -> BoxliteResult<()> {
+        if self.state.read().services.iter().any(|s| s.name == name) {
+            Ok(())
+        } else {
+            Err(BoxliteError::NotFound(format!(
+                "service {} in box {}",
+                name,
+                self.id()
+            )))
+        }
+    }
+
+    /// Stop the run of service `name`, if the box is running.
+    async fn stop_running_service(&self, name: &str) -> BoxliteResult<()> {
+        if !self.state.read().status.is_running() {
+            return Ok(());
         }
 
-        // Ensure box is running
         let live = self.live_state().await?;
+        let mut supervisor = live.guest_session.supervisor().await?;
+        match supervisor.stop_service(name, SERVICE_STOP_GRACE).await {
+            // Failed to start on this boot
+            Err(BoxliteError::NotFound(_)) => Ok(()),
+            result => result,
+        }
+    }
 
-        if container_src.is_empty() {
-            return Err(BoxliteError::Config("source path cannot be empty".into()));
+    /// Start the recorded services in order.
+    ///
+    /// Failures are logged, not returned: the box runs without the service
+    /// and without the services depending on it.
+    async fn start_services(&self, live: &LiveState) {
+        let services = self.state.read().services.clone();
+        let mut failed = HashSet::new();
+
+        for spec in &services {
+            if let Some(dep) = spec.policy.depends_on.iter().find(|d| failed.contains(*d)) {
+                tracing::warn!(
+                    box_id = %self.config.id,
+                    service = %spec.name,
+                    dependency = %dep,
+                    "Not starting service: its dependency did not start"
+                );
+                failed.insert(spec.name.clone());
+                continue;
+            }
+            if let Err(e) = self.start_service(live, spec).await {
+                tracing::warn!(
+                    box_id = %self.config.id,
+                    service = %spec.name,
+                    error = %e,
+                    "Service failed to start"
+                );
+                failed.insert(spec.name.clone());
+            }
         }
+    }
 
-        let temp_tar = self
-            .runtime
-            .layout
-            .temp_dir()
-            .join(format!("cp-out-{}.tar", self.config.id.as_str()));
+    /// Stop the recorded services in reverse order, so each stops before
+    /// the services it depends on.
+    async fn stop_services(&self, live: &LiveState) {
+        let services = self.state.read().services.clone();
+        if services.is_empty() {
+            return;
+        }
+        let Ok(mut supervisor) = live.guest_session.supervisor().await else {
+            return;
+        };
 
-        let mut files_iface = live.guest_session.files().await?;
-        files_iface
-            .download_tar(
-                container_src,
-                Some(self.container_id()),
-                opts.include_parent,
-                opts.follow_symlinks,
-                &temp_tar,
-            )
-            .await?;
+        for spec in services.iter().rev() {
+            match supervisor
+                .stop_service(&spec.name, SERVICE_STOP_GRACE)
+                .await
+            {
+                Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+                Err(e) => tracing::warn!(
+                    box_id = %self.config.id,
+                    service = %spec.name,
+                    error = %e,
+                    "Failed to stop service"
+                ),
+            }
+        }
+    }
 
-        extract_tar_to_host(&temp_tar, host_dst, opts.overwrite)?;
-        let _ = tokio::fs::remove_file(&temp_tar).await;
-        Ok(())
+    /// Hand one service to the guest supervisor; its output is captured
+    /// like a detached exec's.
+    async fn start_service(&self, live: &LiveState, spec: &ServiceSpec) -> BoxliteResult<()> {
+        let command = spec.command.clone().render_for_exec(&self.info())?;
+        let command = self.resolve_command(command).detach(true);
+        let mut supervisor = live.guest_session.supervisor().await?;
+        supervisor
+            .start_service(&spec.name, &command, spec.policy.restart)
+            .await
+    }
+
+    // ========================================================================
+    // PROVISIONING (internal)
+    // ========================================================================
+
+    /// Run `setup_commands` in order and mark the box provisioned.
+    ///
+    /// Output goes to the provisioning log. Under `FailBox` the first
+    /// failing command aborts the run and the box stays unprovisioned;
+    /// under `WarnAndContinue` failures are logged and the run goes on.
+    async fn provision(&self, live: &LiveState) -> BoxliteResult<()> {
+        let options = &self.config.options;
+        let mut log = ProvisionLog::create(&self.config.box_home)?;
+
+        tracing::info!(
+            box_id = %self.config.id,
+            commands = options.setup_commands.len(),
+            "Provisioning box"
+        );
+
+        for command in &options.setup_commands {
+            let result = self.run_setup_command(live, command.clone()).await;
+            log.record(command, &result);
+
+            if let Err(e) = provision::check(command, result) {
+                match options.setup_failure {
+                    SetupFailurePolicy::FailBox => {
+                        return Err(BoxliteError::Execution(format!(
+                            "Provisioning failed: {} (log: {})",
+                            e,
+                            log.path().display()
+                        )));
+                    }
+                    SetupFailurePolicy::WarnAndContinue => {
+                        tracing::warn!(
+                            box_id = %self.config.id,
+                            error = %e,
+                            log = %log.path().display(),
+                            "Setup command failed, continuing"
+                        );
+                    }
+                }
+            }
+        }
+
+        let mut state = self.state.write();
+        state.set_provisioned(true);
+        self.runtime.box_manager.save_box(&self.config.id, &state)
+    }
+
+    async fn run_setup_command(
+        &self,
+        live: &LiveState,
+        command: BoxCommand,
+    ) -> BoxliteResult<SetupOutput> {
+        let command = command.render_for_exec(&self.info())?;
+        let execution = self.exec_on(live, command).await?;
+        SetupOutput::collect(execution).await
     }
 
     // ========================================================================
@@ -492,8 +1591,8 @@ impl BoxImpl {
     /// Initialize LiveState via BoxBuilder.
     ///
     /// BoxBuilder handles all status types with different execution plans:
-    /// - Configured: full pipeline (filesystem, rootfs, spawn, connect, init)
-    /// - Stopped: restart pipeline (reuse rootfs, spawn, connect, init)
+    /// - Created: full pipeline (filesystem, rootfs, spawn, connect, init)
+    /// - Exited: restart pipeline (reuse rootfs, spawn, connect, init)
     /// - Running: attach pipeline (attach, connect)
     ///
     /// Note: Lock is allocated in create(), not here. DB persistence also
@@ -503,34 +1602,53 @@ impl BoxImpl {
         use crate::util::read_pid_file;
         use std::sync::Arc;
 
-        let state = self.state.read().clone();
-        let is_first_start = state.status == BoxStatus::Configured;
+        // Hold the box's operation lock (allocated in create()) for the
+        // duration of build operations; released on drop.
+        let _lock = self
+            .runtime
+            .lock_box(self.id(), BoxOperation::Start)
+            .await?;
 
-        // Retrieve the lock (allocated in create())
-        let lock_id = state.lock_id.ok_or_else(|| {
-            BoxliteError::Internal(format!(
-                "box {} is missing lock_id (status: {:?})",
-                self.config.id, state.status
-            ))
-        })?;
-        let locker = self.runtime.lock_manager.retrieve(lock_id)?;
+        // Read state under the lock: another handle may have changed it
+        let mut state = self.state.read().clone();
+        self.check_not_dead(state.status)?;
+        self.check_userns_shift(&state)?;
+        let is_first_start = state.status == BoxStatus::Created;
+        let is_reattach = state.status == BoxStatus::Running;
         tracing::debug!(
             box_id = %self.config.id,
-            lock_id = %lock_id,
-            "Acquired lock for box (first_start={})",
+            "Acquired operation lock for box (first_start={})",
             is_first_start
         );
 
-        // Hold the lock for the duration of build operations.
-        // LockGuard acquires lock on creation and releases on drop.
-        let _guard = LockGuard::new(&*locker);
-
         // Build the box (lock is held)
         // The returned cleanup_guard stays armed until we disarm it after all
         // operations succeed. If any operation fails, the guard's Drop will
         // cleanup the VM process and directory.
+        self.ensure_network(&mut state)?;
+        // A reattached guest keeps the hosts file written when it started
+        if !is_reattach {
+            state.hosts = self.runtime.guest_hosts(&self.config)?;
+        }
+        let hosts = state.hosts.clone();
+
         let builder = BoxBuilder::new(Arc::clone(&self.runtime), self.config.clone(), state)?;
-        let (live_state, mut cleanup_guard) = builder.build().await?;
+        let (live_state, mut cleanup_guard) = match builder.build().await {
+            Ok(built) => built,
+            Err(e) => return Err(self.record_start_failure(e.error, e.dmesg_tail)),
+        };
+
+        // Provision before the box is reported running. On failure the
+        // armed cleanup guard tears the VM down.
+        let needs_setup = !self.config.options.setup_commands.is_empty();
+        if needs_setup && !self.state.read().provisioned {
+            self.provision(&live_state).await?;
+        }
+
+        // Services may rely on provisioning; a reattached box runs them already
+        if !is_reattach {
+            self.start_services(&live_state).await;
+        }
 
         // Read PID from file (single source of truth) and update state.
         //
@@ -553,6 +1671,15 @@ impl BoxImpl {
             let mut state = self.state.write();
             state.set_pid(Some(pid));
             state.set_status(BoxStatus::Running);
+            state.set_sockets(self.socket_paths());
+            state.set_hosts(hosts);
+            if state.userns_shift.is_none() && self.config.options.userns.is_some() {
+                state.set_userns_shift(self.config.options.userns.clone());
+            }
+            // A reattached box keeps the owner it was started or adopted by
+            if !is_reattach {
+                state.set_owner_pid((!self.config.options.detach).then(std::process::id));
+            }
 
             // Save to DB (cache for queries and recovery)
             self.runtime.box_manager.save_box(&self.config.id, &state)?;
@@ -566,16 +1693,84 @@ impl BoxImpl {
 
         // All operations succeeded - disarm the cleanup guard
         cleanup_guard.disarm();
+        StartFailure::clear(&self.config.box_home);
+        if let Some(image) = self.config.options.rootfs.image() {
+            self.runtime
+                .runtime_metrics
+                .per_image
+                .box_running(self.config.id.as_str(), image);
+        }
 
         tracing::info!(
             box_id = %self.config.id,
             "Box started successfully (first_start={})",
             is_first_start
         );
+        // The shim watches boxes no process owns
+        if let Some(policy) = self.config.options.idle_policy()
+            && self.is_owned_here()
+        {
+            self.runtime.watch_idle(self.id(), policy);
+        }
 
-        // Lock is automatically released when _guard drops
+        // Lock is automatically released when _lock drops
         Ok(live_state)
     }
+
+    /// Host-side log of the VM console (kernel and guest agent output).
+    fn console_log_path(&self) -> std::path::PathBuf {
+        use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+
+        BoxFilesystemLayout::new(
+            self.config.box_home.clone(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        )
+        .console_output_path()
+    }
+
+    /// Make sure the box has a persisted guest network identity.
+    ///
+    /// Boxes get their IP/MAC on first start and reuse it afterwards. A box
+    /// that is already running without one was booted by an older version
+    /// with the legacy defaults, so those are recorded instead.
+    fn ensure_network(&self, state: &mut BoxState) -> BoxliteResult<()> {
+        if state.network.is_some() {
+            return Ok(());
+        }
+
+        if state.status == BoxStatus::Running {
+            state.set_network(BoxNetwork::default());
+            self.runtime.box_manager.save_box(&self.config.id, state)?;
+        } else {
+            self.runtime
+                .box_manager
+                .assign_network(&self.config.id, state)?;
+        }
+
+        self.state.write().network = state.network.clone();
+        Ok(())
+    }
+
+    /// Capture boot diagnostics for a failed start and attach them to `err`.
+    fn record_start_failure(&self, err: BoxliteError, dmesg_tail: Vec<String>) -> BoxliteError {
+        let failure = StartFailure::collect(&err, &self.console_log_path(), dmesg_tail);
+
+        tracing::error!(
+            box_id = %self.config.id,
+            last_phase = ?failure.last_phase,
+            "Box failed to start"
+        );
+        if let Err(e) = failure.save(&self.config.box_home) {
+            tracing::warn!(
+                box_id = %self.config.id,
+                error = %e,
+                "Failed to persist start failure"
+            );
+        }
+
+        failure.attach_to(err)
+    }
 }
 
 // ============================================================================
@@ -604,6 +1799,22 @@ impl crate::runtime::backend::BoxBackend for BoxImpl {
         self.exec(command).await
     }
 
+    async fn prepare_exec(&self, command: BoxCommand) -> BoxliteResult<Option<String>> {
+        self.prepare_exec(command).await.map(Some)
+    }
+
+    async fn exec_prepared(
+        &self,
+        prepared_id: &str,
+        args: Vec<String>,
+    ) -> BoxliteResult<Execution> {
+        self.exec_prepared(prepared_id, args).await
+    }
+
+    async fn unprepare_exec(&self, prepared_id: &str) -> BoxliteResult<()> {
+        self.unprepare_exec(prepared_id).await
+    }
+
     async fn metrics(&self) -> BoxliteResult<BoxMetrics> {
         self.metrics().await
     }
@@ -629,6 +1840,98 @@ impl crate::runtime::backend::BoxBackend for BoxImpl {
     ) -> BoxliteResult<()> {
         self.copy_out(container_src, host_dst, opts).await
     }
+
+    async fn update_resource_limits(&self, limits: ResourceLimits) -> BoxliteResult<()> {
+        self.update_resource_limits(limits).await
+    }
+
+    fn snapshot_retention(&self) -> Option<SnapshotRetention> {
+        self.snapshot_retention()
+    }
+
+    async fn set_snapshot_retention(
+        &self,
+        retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<()> {
+        self.set_snapshot_retention(retention)
+    }
+
+    fn snapshot_metadata(&self) -> BoxliteResult<SnapshotMetadata> {
+        Ok(self.snapshot_metadata())
+    }
+
+    fn restore_metadata(&self, metadata: SnapshotMetadata) -> BoxliteResult<()> {
+        self.restore_metadata(metadata)
+    }
+
+    fn last_start_failure(&self) -> Option<StartFailure> {
+        self.last_start_failure()
+    }
+
+    fn last_exit(&self) -> Option<ShimExit> {
+        self.last_exit()
+    }
+
+    async fn reprovision(&self) -> BoxliteResult<()> {
+        self.reprovision().await
+    }
+
+    async fn adopt(&self) -> BoxliteResult<()> {
+        self.adopt().await
+    }
+
+    async fn detach(&self) -> BoxliteResult<()> {
+        self.detach().await
+    }
+
+    async fn tunnel(&self, guest_port: u16, bind: SocketAddr) -> BoxliteResult<TunnelHandle> {
+        self.tunnel(guest_port, bind).await
+    }
+
+    async fn guest_dmesg(&self, tail_lines: usize) -> BoxliteResult<Vec<String>> {
+        self.guest_dmesg(tail_lines).await
+    }
+
+    async fn guest_agent_log(&self, tail_bytes: usize) -> BoxliteResult<GuestAgentLog> {
+        self.guest_agent_log(tail_bytes).await
+    }
+
+    async fn list_core_dumps(&self) -> BoxliteResult<Vec<CoreDump>> {
+        self.list_core_dumps().await
+    }
+
+    async fn sync(&self, freeze_cap: Duration) -> BoxliteResult<Duration> {
+        self.sync(freeze_cap).await
+    }
+
+    async fn exec_output(&self, exec_id: &str) -> BoxliteResult<CapturedOutput> {
+        self.exec_output(exec_id)
+    }
+
+    async fn spawn_service(
+        &self,
+        name: &str,
+        command: BoxCommand,
+        policy: ServicePolicy,
+    ) -> BoxliteResult<()> {
+        self.spawn_service(name, command, policy).await
+    }
+
+    async fn stop_service(&self, name: &str) -> BoxliteResult<()> {
+        self.stop_service(name).await
+    }
+
+    async fn remove_service(&self, name: &str) -> BoxliteResult<()> {
+        self.remove_service(name).await
+    }
+
+    async fn list_services(&self) -> BoxliteResult<Vec<ServiceInfo>> {
+        self.list_services().await
+    }
+
+    async fn environment_reports(&self) -> BoxliteResult<Vec<EnvironmentReport>> {
+        EnvironmentReport::load_all(&self.config.box_home)
+    }
 }
 
 fn build_tar_from_host(
@@ -678,6 +1981,33 @@ fn build_tar_from_host(
     })
 }
 
+/// Sends the batches of a resumable copy through the guest files service.
+struct GuestUploader<'a> {
+    session: &'a GuestSession,
+    destination: &'a str,
+    container_id: &'a str,
+    ownership: CopyOwnership,
+    limits: ArchiveLimits,
+}
+
+#[async_trait::async_trait]
+impl BatchUploader for GuestUploader<'_> {
+    async fn upload(&mut self, tar_path: &std::path::Path, overwrite: bool) -> BoxliteResult<()> {
+        let mut files_iface = self.session.files().await?;
+        files_iface
+            .upload_tar(
+                tar_path,
+                self.destination,
+                Some(self.container_id),
+                true,
+                overwrite,
+                self.ownership,
+                self.limits,
+            )
+            .await
+    }
+}
+
 /// Whether to extract as a single file or into a directory.
 enum ExtractionMode {
     /// Destination is a file path — extract the single tar entry directly to it.
@@ -726,10 +2056,18 @@ fn determine_extraction_mode(
     Ok(ExtractionMode::IntoDirectory)
 }
 
+/// Unpack a downloaded archive at `dest`, within `limits`.
+///
+/// Unless `chown_to_caller`, entries keep the uid/gid recorded by the guest.
+/// Unless `follow_symlinks`, entries must not be written through host
+/// symlinks out of `dest` (see [`copy::unpack_to_host`]).
 fn extract_tar_to_host(
     tar_path: &std::path::Path,
     dest: &std::path::Path,
     overwrite: bool,
+    chown_to_caller: bool,
+    follow_symlinks: bool,
+    limits: ArchiveLimits,
 ) -> BoxliteResult<()> {
     tokio::task::block_in_place(|| {
         let mode = determine_extraction_mode(dest, tar_path)?;
@@ -761,6 +2099,7 @@ fn extract_tar_to_host(
                     ))
                 })?;
                 let mut archive = tar::Archive::new(tar_file);
+                archive.set_preserve_ownerships(!chown_to_caller);
                 let mut entries = archive.entries().map_err(|e| {
                     BoxliteError::Storage(format!("failed to read tar entries: {}", e))
                 })?;
@@ -768,6 +2107,9 @@ fn extract_tar_to_host(
                     let mut entry = entry.map_err(|e| {
                         BoxliteError::Storage(format!("failed to read tar entry: {}", e))
                     })?;
+                    if ArchiveGuard::new(limits).check(&entry)? == EntryAction::Skip {
+                        return Ok(());
+                    }
                     entry.unpack(dest).map_err(|e| {
                         BoxliteError::Storage(format!(
                             "failed to unpack file to {}: {}",
@@ -793,9 +2135,8 @@ fn extract_tar_to_host(
                     ))
                 })?;
                 let mut archive = tar::Archive::new(tar_file);
-                archive
-                    .unpack(dest)
-                    .map_err(|e| BoxliteError::Storage(format!("failed to extract archive: {}", e)))
+                archive.set_preserve_ownerships(!chown_to_caller);
+                copy::unpack_to_host(&mut archive, dest, limits, follow_symlinks)
             }
         }
     })
@@ -831,7 +2172,15 @@ mod tests {
 
             let dest_dir = tmp.path().join("dest");
             std::fs::create_dir(&dest_dir).unwrap();
-            extract_tar_to_host(&tar_path, &dest_dir, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest_dir,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
 
             let extracted = dest_dir.join("src").join("hello.txt");
             let data = std::fs::read_to_string(extracted).unwrap();
@@ -947,7 +2296,15 @@ mod tests {
 
             // Extract to a file path (not a directory)
             let dest_file = tmp.path().join("dest_dir").join("script.py");
-            extract_tar_to_host(&tar_path, &dest_file, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest_file,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
 
             // Verify it's a file, not a directory
             assert!(dest_file.is_file(), "dest should be a regular file");
@@ -977,7 +2334,15 @@ mod tests {
             // Extract to an existing directory — should copy INTO the dir
             let dest_dir = tmp.path().join("workspace");
             std::fs::create_dir(&dest_dir).unwrap();
-            extract_tar_to_host(&tar_path, &dest_dir, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest_dir,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
 
             // File should be inside the directory with its original name
             let extracted = dest_dir.join("source.py");
@@ -1012,7 +2377,15 @@ mod tests {
             let workspace = tmp.path().join("workspace");
             std::fs::create_dir(&workspace).unwrap();
             let dest_file = workspace.join("script.py");
-            extract_tar_to_host(&tar_path, &dest_file, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest_file,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
 
             // MUST be a regular file, NOT a directory
             assert!(
@@ -1043,7 +2416,15 @@ mod tests {
 
             // Deep nested path — parent dirs should be created automatically
             let dest = tmp.path().join("a").join("b").join("c").join("data.txt");
-            extract_tar_to_host(&tar_path, &dest, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
 
             assert!(dest.is_file());
             assert_eq!(std::fs::read_to_string(&dest).unwrap(), "content");
@@ -1068,7 +2449,14 @@ mod tests {
             std::fs::write(&dest, b"old content").unwrap();
 
             // overwrite=false should fail
-            let result = extract_tar_to_host(&tar_path, &dest, false);
+            let result = extract_tar_to_host(
+                &tar_path,
+                &dest,
+                false,
+                true,
+                false,
+                ArchiveLimits::default(),
+            );
             assert!(
                 result.is_err(),
                 "should reject overwrite when overwrite=false"
@@ -1097,11 +2485,112 @@ mod tests {
             std::fs::write(&dest, b"old content").unwrap();
 
             // overwrite=true should succeed
-            extract_tar_to_host(&tar_path, &dest, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
             assert_eq!(std::fs::read_to_string(&dest).unwrap(), "new content");
         });
     }
 
+    #[test]
+    fn extract_rejects_entry_escaping_destination() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let tmp = TempDir::new().unwrap();
+            let tar_path = tmp.path().join("evil.tar");
+            let mut builder = tar::Builder::new(std::fs::File::create(&tar_path).unwrap());
+            for name in ["dir/ok.txt", "../escaped.txt"] {
+                let mut header = tar::Header::new_gnu();
+                header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+                header.set_size(1);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append(&header, &b"x"[..]).unwrap();
+            }
+            builder.finish().unwrap();
+
+            let dest = tmp.path().join("dest/");
+            let err = extract_tar_to_host(&tar_path, &dest, true, true, false, ArchiveLimits::default())
+                .unwrap_err();
+            assert!(
+                matches!(&err, BoxliteError::UnsafeArchive(reason) if reason.contains("../escaped.txt")),
+                "{err}"
+            );
+            assert!(!tmp.path().join("escaped.txt").exists());
+        });
+    }
+
+    #[test]
+    fn extract_refuses_host_symlink_out_of_destination() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let tmp = TempDir::new().unwrap();
+            let tar_path = tmp.path().join("dir.tar");
+            create_dir_tar(&tar_path);
+
+            // dest/mydir already exists on the host, as a link out of dest
+            let outside = tmp.path().join("outside");
+            std::fs::create_dir(&outside).unwrap();
+            let dest = tmp.path().join("output");
+            std::fs::create_dir(&dest).unwrap();
+            std::os::unix::fs::symlink(&outside, dest.join("mydir")).unwrap();
+
+            let err = extract_tar_to_host(&tar_path, &dest, true, true, false, ArchiveLimits::default())
+                .unwrap_err();
+            assert!(
+                matches!(&err, BoxliteError::UnsafeCopy(reason) if reason.contains("mydir/file.txt")),
+                "{err}"
+            );
+            assert!(!outside.join("file.txt").exists());
+
+            // Explicitly following host symlinks writes through the link
+            extract_tar_to_host(&tar_path, &dest, true, true, true, ArchiveLimits::default()).unwrap();
+            assert_eq!(
+                std::fs::read_to_string(outside.join("file.txt")).unwrap(),
+                "inside dir"
+            );
+        });
+    }
+
+    #[test]
+    fn allowed_dest_roots_fence_copy_out() {
+        let tmp = TempDir::new().unwrap();
+        let allowed = tmp.path().join("allowed");
+        std::fs::create_dir(&allowed).unwrap();
+        std::os::unix::fs::symlink(tmp.path(), allowed.join("up")).unwrap();
+        let roots = vec![allowed.clone()];
+
+        assert!(copy::check_dest_allowed(&[], tmp.path()).is_ok());
+        assert!(copy::check_dest_allowed(&roots, &allowed.join("new/dir")).is_ok());
+        for dest in [
+            tmp.path().join("elsewhere"),
+            allowed.join("up/elsewhere"),
+            allowed.join("new/../../elsewhere"),
+        ] {
+            let err = copy::check_dest_allowed(&roots, &dest).unwrap_err();
+            assert!(
+                matches!(err, BoxliteError::UnsafeCopy(_)),
+                "{dest:?}: {err}"
+            );
+        }
+    }
+
     #[test]
     fn extract_dir_tar_into_directory() {
         let rt = tokio::runtime::Builder::new_multi_thread()
@@ -1118,7 +2607,15 @@ mod tests {
             // Extract multi-entry tar to a directory — should use directory mode
             let dest = tmp.path().join("output");
             std::fs::create_dir(&dest).unwrap();
-            extract_tar_to_host(&tar_path, &dest, true).unwrap();
+            extract_tar_to_host(
+                &tar_path,
+                &dest,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
 
             let extracted = dest.join("mydir").join("file.txt");
             assert!(extracted.is_file());
@@ -1165,4 +2662,64 @@ mod tests {
         // Empty tar → directory mode (fallback)
         assert!(matches!(mode, ExtractionMode::IntoDirectory));
     }
+
+    #[test]
+    fn extract_chown_to_caller_controls_owner() {
+        use std::os::unix::fs::MetadataExt;
+
+        // Keeping a foreign owner needs root
+        if unsafe { libc::geteuid() } != 0 {
+            eprintln!("skipping: chown requires root");
+            return;
+        }
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .enable_all()
+            .build()
+            .unwrap();
+
+        rt.block_on(async {
+            let tmp = TempDir::new().unwrap();
+            let tar_path = tmp.path().join("owned.tar");
+            let mut builder = tar::Builder::new(std::fs::File::create(&tar_path).unwrap());
+            for (path, data) in [("app/a.txt", "a"), ("app/sub/b.txt", "b")] {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(data.len() as u64);
+                header.set_mode(0o644);
+                header.set_uid(1000);
+                header.set_gid(1000);
+                header.set_cksum();
+                builder
+                    .append_data(&mut header, path, data.as_bytes())
+                    .unwrap();
+            }
+            builder.finish().unwrap();
+
+            let kept = tmp.path().join("kept");
+            extract_tar_to_host(
+                &tar_path,
+                &kept,
+                true,
+                false,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
+            let meta = std::fs::metadata(kept.join("app/sub/b.txt")).unwrap();
+            assert_eq!((meta.uid(), meta.gid()), (1000, 1000));
+
+            let mine = tmp.path().join("mine");
+            extract_tar_to_host(
+                &tar_path,
+                &mine,
+                true,
+                true,
+                false,
+                ArchiveLimits::default(),
+            )
+            .unwrap();
+            let meta = std::fs::metadata(mine.join("app/sub/b.txt")).unwrap();
+            assert_eq!((meta.uid(), meta.gid()), (0, 0));
+        });
+    }
 }
@@ -0,0 +1,201 @@
+//! Captured output of detached executions.
+//!
+//! A command started with `BoxCommand::detach(true)` has its stdout/stderr
+//! written by the guest to `{box_home}/execs/{exec_id}/{stdout,stderr}`,
+//! each capped at [`DETACHED_OUTPUT_MAX_BYTES`]. The files outlive the
+//! process that started the execution and are read back with
+//! `LiteBox::exec_output()`. They are removed with the box, or by the sweep
+//! once older than [`CAPTURE_MAX_AGE`].
+
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Per-stream size cap of captured output. Output past it is discarded.
+pub(crate) const DETACHED_OUTPUT_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Captures not written to for this long are removed by the sweep.
+pub(crate) const CAPTURE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Host paths of a detached execution's captured output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExecOutputPaths {
+    /// Captured standard output.
+    pub stdout: PathBuf,
+    /// Captured standard error (empty in TTY mode, where it is merged).
+    pub stderr: PathBuf,
+}
+
+impl ExecOutputPaths {
+    /// Paths for `exec_id` under `execs_dir`.
+    ///
+    /// Fails with `InvalidArgument` unless the ID is a single plain path
+    /// component.
+    pub(crate) fn new(execs_dir: &Path, exec_id: &str) -> BoxliteResult<Self> {
+        let mut components = Path::new(exec_id).components();
+        let (Some(Component::Normal(_)), None) = (components.next(), components.next()) else {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "invalid execution id: {:?}",
+                exec_id
+            )));
+        };
+
+        let dir = execs_dir.join(exec_id);
+        Ok(Self {
+            stdout: dir.join("stdout"),
+            stderr: dir.join("stderr"),
+        })
+    }
+}
+
+/// Output of a detached execution, read from its capture files.
+///
+/// Reflects what has been written so far if the execution is still running.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CapturedOutput {
+    /// Standard output (invalid UTF-8 replaced).
+    pub stdout: String,
+    /// Standard error (invalid UTF-8 replaced).
+    pub stderr: String,
+}
+
+/// Read the captured output of `exec_id`.
+///
+/// Fails with `NotFound` if nothing was captured for it: the ID is unknown,
+/// the execution was not detached, or its capture was swept.
+pub(crate) fn read_captured_output(
+    execs_dir: &Path,
+    exec_id: &str,
+) -> BoxliteResult<CapturedOutput> {
+    let paths = ExecOutputPaths::new(execs_dir, exec_id)?;
+    if !paths.stdout.exists() {
+        return Err(BoxliteError::NotFound(format!(
+            "no captured output for execution {}",
+            exec_id
+        )));
+    }
+
+    Ok(CapturedOutput {
+        stdout: read_lossy(&paths.stdout)?,
+        stderr: read_lossy(&paths.stderr)?,
+    })
+}
+
+fn read_lossy(path: &Path) -> BoxliteResult<String> {
+    match std::fs::read(path) {
+        Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(BoxliteError::Storage(format!(
+            "Failed to read captured output {}: {}",
+            path.display(),
+            e
+        ))),
+    }
+}
+
+/// Remove captures in `execs_dir` not written to within `max_age`.
+///
+/// Returns the number of captures removed.
+pub(crate) fn sweep_captured_output(execs_dir: &Path, max_age: Duration) -> usize {
+    let Ok(entries) = std::fs::read_dir(execs_dir) else {
+        return 0;
+    };
+
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        let stale = last_modified(&path)
+            .and_then(|modified| modified.elapsed().ok())
+            .is_some_and(|age| age > max_age);
+        if stale && std::fs::remove_dir_all(&path).is_ok() {
+            tracing::debug!("Removed stale exec output: {}", path.display());
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Latest modification time of a capture directory and the files in it.
+fn last_modified(dir: &Path) -> Option<SystemTime> {
+    let dir_modified = std::fs::metadata(dir).and_then(|m| m.modified()).ok()?;
+    let files_modified = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| entry.metadata().and_then(|m| m.modified()).ok());
+    files_modified.chain(std::iter::once(dir_modified)).max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_capture(execs_dir: &Path, exec_id: &str, stdout: &[u8], stderr: &[u8]) {
+        let paths = ExecOutputPaths::new(execs_dir, exec_id).unwrap();
+        std::fs::create_dir_all(paths.stdout.parent().unwrap()).unwrap();
+        std::fs::write(&paths.stdout, stdout).unwrap();
+        std::fs::write(&paths.stderr, stderr).unwrap();
+    }
+
+    #[test]
+    fn test_paths_reject_non_component_ids() {
+        let execs = Path::new("/boxes/b1/execs");
+        let paths = ExecOutputPaths::new(execs, "abc-123").unwrap();
+        assert_eq!(
+            paths.stdout,
+            PathBuf::from("/boxes/b1/execs/abc-123/stdout")
+        );
+        assert_eq!(
+            paths.stderr,
+            PathBuf::from("/boxes/b1/execs/abc-123/stderr")
+        );
+
+        for bad in ["", ".", "..", "a/b", "/abs", "../escape"] {
+            let err = ExecOutputPaths::new(execs, bad).unwrap_err();
+            assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn test_read_captured_output() {
+        let dir = tempfile::tempdir().unwrap();
+        write_capture(dir.path(), "e1", b"out\n", b"bad \xff\n");
+
+        let output = read_captured_output(dir.path(), "e1").unwrap();
+        assert_eq!(output.stdout, "out\n");
+        assert_eq!(output.stderr, "bad \u{fffd}\n");
+
+        let err = read_captured_output(dir.path(), "missing").unwrap_err();
+        assert!(matches!(err, BoxliteError::NotFound(_)));
+    }
+
+    #[test]
+    fn test_sweep_removes_only_stale_captures() {
+        let dir = tempfile::tempdir().unwrap();
+        write_capture(dir.path(), "old", b"", b"");
+        write_capture(dir.path(), "new", b"", b"");
+
+        let old = dir.path().join("old");
+        let long_ago = SystemTime::now() - Duration::from_secs(3600);
+        for path in [old.join("stdout"), old.join("stderr"), old.clone()] {
+            let file = std::fs::File::open(&path).unwrap();
+            file.set_modified(long_ago).unwrap();
+        }
+
+        assert_eq!(
+            sweep_captured_output(dir.path(), Duration::from_secs(60)),
+            1
+        );
+        assert!(!old.exists());
+        assert!(dir.path().join("new").exists());
+        assert_eq!(
+            sweep_captured_output(&dir.path().join("absent"), Duration::ZERO),
+            0
+        );
+    }
+}
@@ -1,29 +1,33 @@
 //! Box clone operations.
 //!
-//! Clone creates a new box from an existing stopped box.
+//! Clone creates a new box from an existing stopped box, or from one of
+//! its snapshots whatever the box's state.
 //! COW (copy-on-write) by default for fast, space-efficient clones.
 //! Full-copy mode available for independent lifecycle.
 
+use std::path::Path;
+
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::Utc;
 
 use crate::disk::constants::dirs as disk_dirs;
-use crate::disk::constants::filenames as disk_filenames;
-use crate::disk::qemu_img;
-use crate::disk::{BackingFormat, Qcow2Helper};
-use crate::litebox::config::{BoxConfig, ContainerRuntimeConfig};
-use crate::litebox::snapshot_types::CloneOptions;
+use crate::disk::driver::DiskDriver;
+use crate::litebox::config::{BoxConfig, BoxLineage, ContainerRuntimeConfig};
+use crate::litebox::snapshot_types::{CloneOptions, SnapshotDiskState};
+use crate::lock::BoxOperation;
 use crate::runtime::constants::filenames as rt_filenames;
 use crate::runtime::types::{BoxID, BoxState, BoxStatus, ContainerID};
 use crate::vmm::VmmKind;
 
 use super::LiteBox;
+use super::snapshot::read_disk_state;
 
 impl LiteBox {
     /// Clone this box, creating a new box with a copy of its disks.
     ///
     /// By default uses COW (copy-on-write) for fast, space-efficient clones.
-    /// The source box must be stopped.
+    /// The source box must be stopped, unless `opts.from_snapshot` is set:
+    /// snapshot disks never change, so they can be cloned while it runs.
     ///
     /// # Arguments
     ///
@@ -34,8 +38,15 @@ impl LiteBox {
     ///
     /// A LiteBox handle for the newly created clone.
     pub async fn clone(&self, name: &str, opts: CloneOptions) -> BoxliteResult<LiteBox> {
-        // Verify stopped
-        {
+        crate::validate::box_name(name)?;
+        let _lock = self
+            .inner
+            .runtime
+            .lock_box(self.id(), BoxOperation::Clone)
+            .await?;
+
+        // Verify stopped; a snapshot's disks are at rest whatever the box does
+        if opts.from_snapshot.is_none() {
             let state = self.inner.state.read();
             if !state.status.is_stopped() {
                 return Err(BoxliteError::InvalidState(format!(
@@ -48,6 +59,8 @@ impl LiteBox {
 
         let rt = &self.inner.runtime;
         let src_home = &self.inner.config.box_home;
+        // The clone keeps the source's driver: its disks are in that format.
+        let driver = self.inner.config.disk_driver.driver();
 
         // Determine source disks (current state or from a named snapshot)
         let (src_container, src_guest) = if let Some(ref snap_name) = opts.from_snapshot {
@@ -59,17 +72,32 @@ impl LiteBox {
                     self.id()
                 )));
             }
-            (
-                snap_dir.join(disk_filenames::CONTAINER_DISK),
-                snap_dir.join(disk_filenames::GUEST_ROOTFS_DISK),
-            )
+            let snap_container = snap_dir.join(driver.container_disk_name());
+            if !snap_container.exists() {
+                return Err(BoxliteError::InvalidArgument(format!(
+                    "snapshot '{}' of box '{}' holds only metadata, no disks to clone",
+                    snap_name,
+                    self.id()
+                )));
+            }
+            (snap_container, snap_dir.join(driver.guest_disk_name()))
         } else {
             (
-                src_home.join(disk_filenames::CONTAINER_DISK),
-                src_home.join(disk_filenames::GUEST_ROOTFS_DISK),
+                src_home.join(driver.container_disk_name()),
+                src_home.join(driver.guest_disk_name()),
             )
         };
 
+        // The setup the disks carry: the snapshot's, or the current one for
+        // snapshots taken before it was recorded
+        let disk_state = match &opts.from_snapshot {
+            Some(snap_name) => {
+                read_disk_state(&src_home.join(disk_dirs::SNAPSHOTS_DIR).join(snap_name))?
+            }
+            None => None,
+        }
+        .unwrap_or_else(|| SnapshotDiskState::of(&self.inner.state.read()));
+
         if !src_container.exists() {
             return Err(BoxliteError::Storage(format!(
                 "Container disk not found at {}",
@@ -96,11 +124,24 @@ impl LiteBox {
         })?;
 
         // Clone disks
-        let dst_container = box_home.join(disk_filenames::CONTAINER_DISK);
+        let dst_container = box_home.join(driver.container_disk_name());
+        let dst_guest = box_home.join(driver.guest_disk_name());
         let clone_result = if opts.cow {
-            self.clone_cow(&src_container, &dst_container, &src_guest, &box_home)
+            clone_cow(
+                driver,
+                &src_container,
+                &dst_container,
+                &src_guest,
+                &dst_guest,
+            )
         } else {
-            self.clone_full_copy(&src_container, &dst_container, &src_guest, &box_home)
+            clone_full_copy(
+                driver,
+                &src_container,
+                &dst_container,
+                &src_guest,
+                &dst_guest,
+            )
         };
 
         if let Err(e) = clone_result {
@@ -119,11 +160,19 @@ impl LiteBox {
             transport: boxlite_shared::Transport::unix(socket_path),
             box_home,
             ready_socket_path,
+            disk_driver: self.inner.config.disk_driver,
+            lineage: Some(BoxLineage {
+                source_box_id: self.id().clone(),
+                snapshot: opts.from_snapshot.clone(),
+            }),
+            degradations: self.inner.config.degradations.clone(),
         };
 
-        // Create state as Stopped
+        // Create state as Exited
         let mut state = BoxState::new();
-        state.set_status(BoxStatus::Stopped);
+        state.set_status(BoxStatus::Exited);
+        // The disks already carry the source's setup
+        disk_state.apply(&mut state);
 
         // Allocate lock
         let lock_id = rt.lock_manager.allocate()?;
@@ -139,6 +188,7 @@ impl LiteBox {
         tracing::info!(
             box_id = %config.id,
             source_id = %self.id(),
+            snapshot = ?opts.from_snapshot,
             cow = %opts.cow,
             "Cloned box"
         );
@@ -155,49 +205,40 @@ impl LiteBox {
 
         Ok(litebox)
     }
+}
 
-    /// COW clone: create child disks backed by source disks.
-    fn clone_cow(
-        &self,
-        src_container: &std::path::Path,
-        dst_container: &std::path::Path,
-        src_guest: &std::path::Path,
-        box_home: &std::path::Path,
-    ) -> BoxliteResult<()> {
-        let qcow2 = Qcow2Helper::new();
-        let container_size = Qcow2Helper::qcow2_virtual_size(src_container)?;
-
-        qcow2.create_cow_child_disk(
-            src_container,
-            BackingFormat::Qcow2,
-            dst_container,
-            container_size,
-        )?;
-
-        if src_guest.exists() {
-            let guest_size = Qcow2Helper::qcow2_virtual_size(src_guest)?;
-            let dst_guest = box_home.join(disk_filenames::GUEST_ROOTFS_DISK);
-            qcow2.create_cow_child_disk(src_guest, BackingFormat::Qcow2, &dst_guest, guest_size)?;
-        }
-
-        Ok(())
+/// COW clone: create disks sharing blocks with the source disks.
+fn clone_cow(
+    driver: &dyn DiskDriver,
+    src_container: &Path,
+    dst_container: &Path,
+    src_guest: &Path,
+    dst_guest: &Path,
+) -> BoxliteResult<()> {
+    let container_size = driver.virtual_size(src_container)?;
+    driver.branch(src_container, dst_container, container_size)?;
+
+    if src_guest.exists() {
+        let guest_size = driver.virtual_size(src_guest)?;
+        driver.branch(src_guest, dst_guest, guest_size)?;
     }
 
-    /// Full-copy clone: flatten COW chains into standalone disks.
-    fn clone_full_copy(
-        &self,
-        src_container: &std::path::Path,
-        dst_container: &std::path::Path,
-        src_guest: &std::path::Path,
-        box_home: &std::path::Path,
-    ) -> BoxliteResult<()> {
-        qemu_img::full_copy(src_container, dst_container)?;
-
-        if src_guest.exists() {
-            let dst_guest = box_home.join(disk_filenames::GUEST_ROOTFS_DISK);
-            qemu_img::full_copy(src_guest, &dst_guest)?;
-        }
+    Ok(())
+}
 
-        Ok(())
+/// Full-copy clone: standalone disks with no shared blocks or backing files.
+fn clone_full_copy(
+    driver: &dyn DiskDriver,
+    src_container: &Path,
+    dst_container: &Path,
+    src_guest: &Path,
+    dst_guest: &Path,
+) -> BoxliteResult<()> {
+    driver.copy(src_container, dst_container)?;
+
+    if src_guest.exists() {
+        driver.copy(src_guest, dst_guest)?;
     }
+
+    Ok(())
 }
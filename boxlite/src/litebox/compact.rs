@@ -0,0 +1,297 @@
+//! Offline disk compaction.
+//!
+//! Rewrites each box disk with `qemu-img convert` into a freshly allocated
+//! file that references the same backing file, dropping unallocated and
+//! zeroed clusters, then swaps it into place.
+//!
+//! Crash safety follows the staged install pattern: the rewrite is staged at
+//! `<disk>.compact` next to the disk, fsynced, and moved over the original
+//! with `rename(2)`. An interrupted compaction leaves either the old or the
+//! new disk in place; a leftover staged file is removed by the next run.
+
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::disk::constants::filenames as disk_filenames;
+use crate::disk::{BackingFormat, DiskDriverKind, qemu_img, read_backing_file_path};
+use crate::litebox::state::BoxStatus;
+use crate::lock::BoxOperation;
+
+use super::LiteBox;
+
+/// Suffix of the staged rewrite, next to the disk being compacted.
+const STAGED_SUFFIX: &str = "compact";
+
+impl LiteBox {
+    /// Compact the box's disks, returning the number of bytes reclaimed.
+    ///
+    /// The box must be stopped. Only space the guest has released to the
+    /// disk (trimmed or zeroed blocks) can be reclaimed; deleted files whose
+    /// blocks were never discarded still occupy the image. Backing files are
+    /// left untouched, so snapshots and COW clones remain valid.
+    ///
+    /// Requires `qemu-img`. Boxes on the reflink storage driver have raw
+    /// disks and cannot be compacted.
+    pub async fn compact_disk(&self) -> BoxliteResult<u64> {
+        let _lock = self
+            .inner
+            .runtime
+            .lock_box(self.id(), BoxOperation::Compact)
+            .await?;
+
+        let driver = self.inner.config.disk_driver;
+        if driver != DiskDriverKind::Qcow2 {
+            return Err(BoxliteError::Unsupported(format!(
+                "box '{}' uses the {} storage driver; only qcow2 disks can be compacted",
+                self.id(),
+                driver
+            )));
+        }
+
+        // Verify stopped
+        {
+            let state = self.inner.state.read();
+            if !state.status.is_stopped() {
+                return Err(BoxliteError::InvalidState(format!(
+                    "box '{}' must be stopped for compaction (current status: {})",
+                    self.id(),
+                    state.status
+                )));
+            }
+        }
+
+        // Transition to Compacting
+        {
+            let mut state = self.inner.state.write();
+            state.transition_to(BoxStatus::Compacting)?;
+            self.inner
+                .runtime
+                .box_manager
+                .save_box(self.inner.id(), &state)?;
+        }
+
+        let box_home = self.inner.config.box_home.clone();
+        let result = tokio::task::spawn_blocking(move || compact_box_disks(&box_home))
+            .await
+            .unwrap_or_else(|e| {
+                Err(BoxliteError::Internal(format!(
+                    "disk compaction task failed: {}",
+                    e
+                )))
+            });
+        if let Ok(reclaimed) = result {
+            tracing::info!(
+                box_id = %self.id(),
+                reclaimed_bytes = reclaimed,
+                "Compacted box disks"
+            );
+        }
+
+        // Transition back to Exited
+        {
+            let mut state = self.inner.state.write();
+            state.force_status(BoxStatus::Exited);
+            let _ = self
+                .inner
+                .runtime
+                .box_manager
+                .save_box(self.inner.id(), &state);
+        }
+
+        result
+    }
+
+    /// Estimate how much [`compact_disk()`](Self::compact_disk) would reclaim.
+    ///
+    /// Compares the space the box's disks occupy on the host with the data
+    /// their own layers hold; the difference is what a rewrite drops. The
+    /// estimate ignores QCOW2 metadata, so the actual gain is slightly lower.
+    /// Works on running boxes too, but the figure moves as the guest writes.
+    ///
+    /// Requires `qemu-img` and a box on the qcow2 storage driver.
+    pub async fn compaction_estimate(&self) -> BoxliteResult<CompactionEstimate> {
+        let driver = self.inner.config.disk_driver;
+        if driver != DiskDriverKind::Qcow2 {
+            return Err(BoxliteError::Unsupported(format!(
+                "box '{}' uses the {} storage driver; only qcow2 disks can be compacted",
+                self.id(),
+                driver
+            )));
+        }
+
+        let box_home = self.inner.config.box_home.clone();
+        tokio::task::spawn_blocking(move || estimate_box_disks(&box_home))
+            .await
+            .map_err(|e| {
+                BoxliteError::Internal(format!("compaction estimate task failed: {}", e))
+            })?
+    }
+}
+
+/// Space a box's disks occupy and how much of it compaction would drop.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CompactionEstimate {
+    /// Bytes the disks occupy on the host filesystem.
+    pub allocated_bytes: u64,
+    /// Bytes `compact_disk()` is expected to reclaim.
+    pub reclaimable_bytes: u64,
+}
+
+fn box_disks(box_home: &Path) -> BoxliteResult<Vec<PathBuf>> {
+    let container_disk = box_home.join(disk_filenames::CONTAINER_DISK);
+    let guest_disk = box_home.join(disk_filenames::GUEST_ROOTFS_DISK);
+
+    if !container_disk.exists() {
+        return Err(BoxliteError::Storage(format!(
+            "Container disk not found at {}",
+            container_disk.display()
+        )));
+    }
+
+    let mut disks = vec![container_disk];
+    if guest_disk.exists() {
+        disks.push(guest_disk);
+    }
+    Ok(disks)
+}
+
+/// Compact every disk of a box. Blocking; runs `qemu-img` to completion.
+fn compact_box_disks(box_home: &Path) -> BoxliteResult<u64> {
+    let mut reclaimed = 0;
+    for disk in box_disks(box_home)? {
+        reclaimed += compact_disk_file(&disk)?;
+    }
+    Ok(reclaimed)
+}
+
+/// Blocking counterpart of [`LiteBox::compaction_estimate`].
+fn estimate_box_disks(box_home: &Path) -> BoxliteResult<CompactionEstimate> {
+    let mut estimate = CompactionEstimate::default();
+    for disk in box_disks(box_home)? {
+        let allocated = allocated_bytes(&disk)?;
+        estimate.allocated_bytes += allocated;
+        estimate.reclaimable_bytes += allocated.saturating_sub(qemu_img::data_bytes(&disk)?);
+    }
+    Ok(estimate)
+}
+
+/// Compact a single QCOW2 disk in place, returning the bytes reclaimed.
+fn compact_disk_file(disk: &Path) -> BoxliteResult<u64> {
+    let staged = staged_path(disk);
+    if staged.exists() {
+        tracing::warn!(
+            staged = %staged.display(),
+            "Removing leftover staged disk from an interrupted compaction"
+        );
+        remove_staged(&staged)?;
+    }
+
+    let backing = read_backing_file_path(disk)?;
+    let backing_format = match &backing {
+        Some(backing) => Some(BackingFormat::detect(&resolve_backing(disk, backing))?),
+        None => None,
+    };
+    let allocated_before = allocated_bytes(disk)?;
+
+    let converted = qemu_img::compact(disk, &staged, backing.as_deref().zip(backing_format))
+        .and_then(|()| sync_file(&staged));
+    if let Err(e) = converted {
+        let _ = std::fs::remove_file(&staged);
+        return Err(e);
+    }
+
+    // Atomic swap: the disk path holds either the old or the new image.
+    std::fs::rename(&staged, disk).map_err(|e| {
+        let _ = std::fs::remove_file(&staged);
+        BoxliteError::Storage(format!(
+            "Failed to move compacted disk {} into place: {}",
+            staged.display(),
+            e
+        ))
+    })?;
+    if let Some(parent) = disk.parent() {
+        sync_file(parent)?;
+    }
+
+    let allocated_after = allocated_bytes(disk)?;
+    Ok(allocated_before.saturating_sub(allocated_after))
+}
+
+fn staged_path(disk: &Path) -> PathBuf {
+    let mut name = disk.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(STAGED_SUFFIX);
+    disk.with_file_name(name)
+}
+
+fn remove_staged(staged: &Path) -> BoxliteResult<()> {
+    std::fs::remove_file(staged).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to remove staged disk {}: {}",
+            staged.display(),
+            e
+        ))
+    })
+}
+
+/// Resolve a backing path the way qemu does: relative to the overlay's directory.
+fn resolve_backing(disk: &Path, backing: &str) -> PathBuf {
+    let backing = Path::new(backing);
+    match disk.parent() {
+        Some(parent) if backing.is_relative() => parent.join(backing),
+        _ => backing.to_path_buf(),
+    }
+}
+
+/// Bytes actually allocated on the host filesystem (sparse-aware).
+fn allocated_bytes(path: &Path) -> BoxliteResult<u64> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| BoxliteError::Storage(format!("Failed to stat {}: {}", path.display(), e)))?;
+    Ok(metadata.blocks() * 512)
+}
+
+fn sync_file(path: &Path) -> BoxliteResult<()> {
+    std::fs::File::open(path)
+        .and_then(|f| f.sync_all())
+        .map_err(|e| BoxliteError::Storage(format!("Failed to sync {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staged_path_is_sibling_of_disk() {
+        let disk = Path::new("/boxes/abc/disk.qcow2");
+        assert_eq!(
+            staged_path(disk),
+            PathBuf::from("/boxes/abc/disk.qcow2.compact")
+        );
+    }
+
+    #[test]
+    fn test_resolve_backing_relative_to_disk_dir() {
+        let disk = Path::new("/boxes/abc/disk.qcow2");
+        assert_eq!(
+            resolve_backing(disk, "snapshots/s1/disk.qcow2"),
+            PathBuf::from("/boxes/abc/snapshots/s1/disk.qcow2")
+        );
+        assert_eq!(
+            resolve_backing(disk, "/images/rootfs.ext4"),
+            PathBuf::from("/images/rootfs.ext4")
+        );
+    }
+
+    #[test]
+    fn test_allocated_bytes_is_sparse_aware() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("sparse.img");
+        let file = std::fs::File::create(&path).unwrap();
+        file.set_len(64 * 1024 * 1024).unwrap();
+
+        assert!(allocated_bytes(&path).unwrap() < 64 * 1024 * 1024);
+    }
+}
@@ -15,6 +15,16 @@ pub struct ContainerRuntimeConfig {
     pub id: ContainerID,
 }
 
+/// Provenance of a box derived from another box.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoxLineage {
+    /// ID of the box this box was derived from.
+    pub source_box_id: BoxID,
+    /// Snapshot of the source box whose disks this box branched from.
+    /// None when cloned from the source's current state.
+    pub snapshot: Option<String>,
+}
+
 /// Static box configuration (set once at creation, never changes).
 ///
 /// This is persisted to database and remains immutable throughout the box lifecycle.
@@ -47,4 +57,36 @@ pub struct BoxConfig {
     pub box_home: PathBuf,
     /// Ready signal socket path.
     pub ready_socket_path: PathBuf,
+    /// Driver that provisions this box's disks.
+    ///
+    /// Boxes created before storage drivers existed use qcow2.
+    #[serde(default)]
+    pub disk_driver: crate::disk::DiskDriverKind,
+
+    // === Provenance ===
+    /// Box this one was cloned or branched from (None for boxes created from scratch).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<BoxLineage>,
+
+    // === Host ===
+    /// Isolation the box runs without because the host lacked a capability
+    /// when it was created. `options.advanced.security` already reflects it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degradations: Vec<crate::runtime::capabilities::Degradation>,
+}
+
+impl BoxConfig {
+    /// What the box needs to restart but no longer has: its home directory,
+    /// or the container disk holding its data.
+    ///
+    /// Only meaningful once the box has run; Created boxes have no disk yet.
+    pub(crate) fn missing_storage(&self) -> Option<PathBuf> {
+        if !self.box_home.exists() {
+            return Some(self.box_home.clone());
+        }
+        let disk = self
+            .box_home
+            .join(self.disk_driver.driver().container_disk_name());
+        (!disk.exists()).then_some(disk)
+    }
 }
@@ -1,4 +1,13 @@
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
 use crate::BoxliteError;
+use boxlite_shared::archive::{ArchiveGuard, ArchiveLimits, EntryAction};
+use boxlite_shared::errors::BoxliteResult;
+use tar::EntryType;
 
 /// Options controlling copy behavior.
 #[derive(Debug, Clone)]
@@ -8,9 +17,34 @@ pub struct CopyOptions {
     /// Overwrite existing files/directories at destination.
     pub overwrite: bool,
     /// Follow symlinks when archiving (otherwise include symlinks as links).
+    /// When copying out, also let entries be written through symlinks that
+    /// already exist on the host and point outside the destination, which
+    /// otherwise fails with `BoxliteError::UnsafeCopy`.
     pub follow_symlinks: bool,
     /// When copying out, include the parent directory in the archive (docker cp semantics).
     pub include_parent: bool,
+    /// When copying in, the owner of the files written into the box.
+    pub ownership: CopyOwnership,
+    /// When copying out, make the extracted files owned by the calling host
+    /// user. With `false` they keep the box's uid/gid, which needs privileges
+    /// to chown on the host.
+    pub chown_to_caller: bool,
+    /// When copying in, continue an earlier copy of the same source and
+    /// destination that did not finish, skipping files it already sent.
+    pub resume: bool,
+    /// When copying in, stop after this long and fail with
+    /// `BoxliteError::PartialTransfer` describing what was sent.
+    pub deadline: Option<Duration>,
+    /// When copying a directory in, stream it as one archive the guest
+    /// unpacks as it arrives. Used anyway for directories with many files;
+    /// ignored by resumable copies, which batch on their own.
+    pub batch: bool,
+    /// Called with aggregate progress while a batched copy is sent.
+    pub progress: Option<CopyObserver>,
+    /// Caps on the archive unpacked at the destination, in the box when
+    /// copying in and on the host when copying out. Entries breaking them
+    /// fail the copy with `BoxliteError::UnsafeArchive`.
+    pub archive_limits: ArchiveLimits,
 }
 
 impl Default for CopyOptions {
@@ -20,10 +54,67 @@ impl Default for CopyOptions {
             overwrite: true,
             follow_symlinks: false,
             include_parent: true,
+            ownership: CopyOwnership::default(),
+            chown_to_caller: true,
+            resume: false,
+            deadline: None,
+            batch: false,
+            progress: None,
+            archive_limits: ArchiveLimits::default(),
         }
     }
 }
 
+/// Aggregate progress of a batched copy into a box.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CopyProgress {
+    /// Files and symlinks sent so far
+    pub files_sent: u64,
+    /// Files and symlinks in the source
+    pub files_total: u64,
+    /// File data sent so far, in bytes
+    pub bytes_sent: u64,
+    /// File data in the source, in bytes
+    pub bytes_total: u64,
+}
+
+/// Callback for [`CopyProgress`]. Called from a blocking runtime thread as
+/// each chunk of the archive is handed to the guest, so it should return
+/// quickly.
+#[derive(Clone)]
+pub struct CopyObserver(Arc<dyn Fn(&CopyProgress) + Send + Sync>);
+
+impl CopyObserver {
+    pub fn new(observer: impl Fn(&CopyProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(observer))
+    }
+
+    pub(crate) fn report(&self, progress: &CopyProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for CopyObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CopyObserver")
+    }
+}
+
+/// Owner of files copied into a box.
+///
+/// Applied by the guest while unpacking, so it does not depend on how host
+/// IDs map into the VM. Directories the copy creates get the same owner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CopyOwnership {
+    /// Keep the uid/gid the files have on the host.
+    PreserveHost,
+    /// The box's default user and group (`BoxOptions::user`, else the image's USER).
+    #[default]
+    ContainerUser,
+    /// A specific uid/gid, as seen inside the box.
+    Explicit { uid: u32, gid: u32 },
+}
+
 impl CopyOptions {
     pub fn no_overwrite(mut self) -> Self {
         self.overwrite = false;
@@ -45,6 +136,47 @@ impl CopyOptions {
         self
     }
 
+    pub fn ownership(mut self, ownership: CopyOwnership) -> Self {
+        self.ownership = ownership;
+        self
+    }
+
+    pub fn chown_to_caller(mut self, chown: bool) -> Self {
+        self.chown_to_caller = chown;
+        self
+    }
+
+    pub fn resume(mut self, resume: bool) -> Self {
+        self.resume = resume;
+        self
+    }
+
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn batch(mut self, batch: bool) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    pub fn archive_limits(mut self, limits: ArchiveLimits) -> Self {
+        self.archive_limits = limits;
+        self
+    }
+
+    pub fn on_progress(mut self, observer: impl Fn(&CopyProgress) + Send + Sync + 'static) -> Self {
+        self.progress = Some(CopyObserver::new(observer));
+        self
+    }
+
+    /// Whether the copy is sent in batches with progress recorded between
+    /// them, so it can stop early and be resumed.
+    pub(crate) fn is_resumable(&self) -> bool {
+        self.resume || self.deadline.is_some()
+    }
+
     pub fn validate_for_dir(&self) -> Result<(), BoxliteError> {
         if !self.recursive {
             return Err(BoxliteError::Config(
@@ -54,3 +186,232 @@ impl CopyOptions {
         Ok(())
     }
 }
+
+/// Validate a guest-side path used as a copy source or destination.
+///
+/// Same as [`crate::validate::guest_path`]: absolute, unix-style, no `..`
+/// components. Errors name the offending component so callers can surface it
+/// directly.
+pub fn validate_container_path(path: &str) -> Result<(), BoxliteError> {
+    crate::validate::guest_path(path)
+}
+
+/// Convert a host path string received from an SDK into a native `PathBuf`.
+///
+/// On Windows, forward slashes are rewritten to `\\` so mixed-separator input
+/// (common from Java/JS callers) resolves the same as native paths. Other
+/// hosts use the string unchanged.
+pub fn normalize_host_path(path: &str) -> PathBuf {
+    #[cfg(windows)]
+    {
+        PathBuf::from(path.replace('/', "\\"))
+    }
+    #[cfg(not(windows))]
+    {
+        PathBuf::from(path)
+    }
+}
+
+/// Check that the host destination `dest` lies under one of `roots`
+/// (`BoxliteOptions::allowed_dest_roots`), once symlinks are resolved.
+/// No roots means no restriction.
+pub(crate) fn check_dest_allowed(roots: &[PathBuf], dest: &Path) -> BoxliteResult<()> {
+    if roots.is_empty() {
+        return Ok(());
+    }
+    let resolved = resolve_host_path(dest);
+    let allowed = resolved.as_ref().is_some_and(|resolved| {
+        roots
+            .iter()
+            .filter_map(|root| root.canonicalize().ok())
+            .any(|root| resolved.starts_with(root))
+    });
+    if allowed {
+        Ok(())
+    } else {
+        Err(BoxliteError::UnsafeCopy(format!(
+            "destination {} is outside the allowed destination roots",
+            dest.display()
+        )))
+    }
+}
+
+/// Unpack a copied-out archive into the host directory `dest`, within
+/// `limits`, like `boxlite_shared::archive::unpack`.
+///
+/// Every entry must also land under `dest` once the symlinks already on the
+/// host are resolved, unless `follow_symlinks`. An entry that would escape
+/// fails with `BoxliteError::UnsafeCopy` before anything is written for it.
+pub(crate) fn unpack_to_host<R: Read>(
+    archive: &mut tar::Archive<R>,
+    dest: &Path,
+    limits: ArchiveLimits,
+    follow_symlinks: bool,
+) -> BoxliteResult<()> {
+    let io_err =
+        |e: std::io::Error| BoxliteError::Storage(format!("failed to extract archive: {}", e));
+    std::fs::create_dir_all(dest).map_err(io_err)?;
+    let root = dest.canonicalize().map_err(io_err)?;
+
+    let mut guard = ArchiveGuard::new(limits);
+    let mut dirs = Vec::new();
+    for entry in archive.entries().map_err(io_err)? {
+        let mut entry = entry.map_err(io_err)?;
+        if guard.check(&entry)? == EntryAction::Skip {
+            continue;
+        }
+        if entry.header().entry_type() == EntryType::Directory {
+            dirs.push(entry);
+            continue;
+        }
+        let path = entry.path().map_err(io_err)?.into_owned();
+        let escapes = check_confined(&root, dest, &path, false, follow_symlinks)?;
+        unpack_entry(&mut entry, &root, &path, escapes).map_err(io_err)?;
+    }
+    // Checked again here: later entries may have put a symlink in their way
+    for mut dir in dirs {
+        let path = dir.path().map_err(io_err)?.into_owned();
+        let escapes = check_confined(&root, dest, &path, true, follow_symlinks)?;
+        unpack_entry(&mut dir, &root, &path, escapes).map_err(io_err)?;
+    }
+    Ok(())
+}
+
+/// Whether the entry at `path` would be written outside `root`, the
+/// canonical `dest`; an error if so and not `follow_symlinks`. An existing
+/// file at the entry's own path is replaced rather than followed, except
+/// when the entry is a directory.
+fn check_confined(
+    root: &Path,
+    dest: &Path,
+    path: &Path,
+    is_dir: bool,
+    follow_symlinks: bool,
+) -> BoxliteResult<bool> {
+    let target = root.join(path);
+    let written_in = match target.parent() {
+        Some(parent) if !is_dir => parent,
+        _ => &target,
+    };
+    let escapes = resolve_host_path(written_in).is_none_or(|resolved| !resolved.starts_with(root));
+    if escapes && !follow_symlinks {
+        return Err(BoxliteError::UnsafeCopy(format!(
+            "entry '{}' would be written outside {} through a symlink on the host",
+            path.display(),
+            dest.display()
+        )));
+    }
+    Ok(escapes)
+}
+
+/// Unpack one vetted entry at `root/path`. `unpack_in` refuses anything
+/// resolving outside `root`, so entries allowed to follow a host symlink out
+/// are unpacked at their path directly. Hardlinks always name a path inside
+/// `root`, which only `unpack_in` resolves.
+fn unpack_entry<R: Read>(
+    entry: &mut tar::Entry<'_, R>,
+    root: &Path,
+    path: &Path,
+    escapes: bool,
+) -> std::io::Result<()> {
+    if !escapes || entry.header().entry_type() == EntryType::Link {
+        entry.unpack_in(root)?;
+        return Ok(());
+    }
+    let target = root.join(path);
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    entry.unpack(&target)?;
+    Ok(())
+}
+
+/// Resolve the symlinks in the existing part of `path`, appending the rest
+/// as is. `None` if the rest climbs with `..` or the existing part cannot
+/// be resolved, such as through a dangling symlink.
+fn resolve_host_path(path: &Path) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    while std::fs::symlink_metadata(existing).is_err() {
+        rest.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+    let mut resolved = existing.canonicalize().ok()?;
+    resolved.extend(rest.into_iter().rev());
+    Some(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invalid_message(path: &str) -> String {
+        match validate_container_path(path) {
+            Err(BoxliteError::InvalidArgument(msg)) => msg,
+            other => panic!("expected InvalidArgument for {:?}, got {:?}", path, other),
+        }
+    }
+
+    #[test]
+    fn test_valid_container_paths() {
+        for path in [
+            "/",
+            "/data",
+            "/data/",
+            "/app/results/out.log",
+            "/tmp/./x",
+            "/a..b/c",
+        ] {
+            assert!(
+                validate_container_path(path).is_ok(),
+                "{path} should be valid"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_traversal() {
+        for path in ["/data/../../etc/passwd", "/..", "/data/..", "/a/b/../c"] {
+            assert!(invalid_message(path).contains("\"..\""), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_relative_paths() {
+        for path in ["data/file", "./data", "../etc/passwd", "C:/data"] {
+            assert!(invalid_message(path).contains("must be absolute"), "{path}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_backslashes_with_component() {
+        let msg = invalid_message(r"/data\sub\file");
+        assert!(msg.contains("backslash"));
+        assert!(msg.contains(r#""data\\sub\\file""#), "{msg}");
+
+        let msg = invalid_message(r"C:\Users\me");
+        assert!(msg.contains("backslash"));
+    }
+
+    #[test]
+    fn test_rejects_empty_and_nul() {
+        assert!(invalid_message("").contains("empty"));
+        assert!(invalid_message("/data\0/x").contains("NUL"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn test_normalize_host_path_unix_passthrough() {
+        assert_eq!(normalize_host_path("/tmp/a/b"), PathBuf::from("/tmp/a/b"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_normalize_host_path_windows_separators() {
+        assert_eq!(
+            normalize_host_path("C:/Users/me/data"),
+            PathBuf::from(r"C:\Users\me\data")
+        );
+    }
+}
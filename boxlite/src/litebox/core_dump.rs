@@ -0,0 +1,65 @@
+//! Core dumps captured in a box (`BoxOptions::capture_core_dumps`).
+
+use boxlite_shared::constants::core_dumps;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::{DateTime, Utc};
+
+/// A core file captured in the box, see `LiteBox::list_core_dumps()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreDump {
+    /// File name, as passed to `LiteBox::fetch_core_dump()`.
+    pub name: String,
+    /// PID of the crashed process, as seen in the container.
+    pub pid: u32,
+    /// Signal that caused the dump (e.g. 11 for SIGSEGV).
+    pub signal: i32,
+    /// Size of the core file (dumps are truncated at 256 MiB).
+    pub size_bytes: u64,
+    /// When the process crashed.
+    pub time: DateTime<Utc>,
+}
+
+impl From<boxlite_shared::CoreDump> for CoreDump {
+    fn from(core: boxlite_shared::CoreDump) -> Self {
+        Self {
+            name: core.name,
+            pid: core.pid,
+            signal: core.signal,
+            size_bytes: core.size_bytes,
+            time: DateTime::from_timestamp(core.time_unix, 0).unwrap_or_default(),
+        }
+    }
+}
+
+/// Container path of the core file `name`.
+///
+/// Only plain `core.*` names from `list_core_dumps()` are accepted, so the
+/// name cannot reach outside the core directory.
+pub(crate) fn container_path(name: &str) -> BoxliteResult<String> {
+    if !name.starts_with("core.") || name.contains('/') {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "{:?} is not a core dump name (see list_core_dumps)",
+            name
+        )));
+    }
+    Ok(format!("{}/{}", core_dumps::DIR, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_path_accepts_only_core_names() {
+        assert_eq!(
+            container_path("core.app.7.11.1700000000").unwrap(),
+            "/var/crash/boxlite/core.app.7.11.1700000000"
+        );
+        for name in ["", "app.core", "core./../../etc/passwd", "../core.x"] {
+            assert!(
+                matches!(container_path(name), Err(BoxliteError::InvalidArgument(_))),
+                "{name:?} should be rejected"
+            );
+        }
+    }
+}
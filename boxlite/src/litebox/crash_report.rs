@@ -134,6 +134,14 @@ impl CrashReport {
                  • Console: {console_display}\n\
                  • Stderr:  {stderr_display}"
             ),
+            // Left by an earlier run; this start failed without a record
+            ExitInfo::IdleStopped { .. } => format!(
+                "Box {box_id} failed to start\n\n\
+                 The VM exited unexpectedly during startup.\n\n\
+                 Debug files:\n\
+                 • Console: {console_display}\n\
+                 • Stderr:  {stderr_display}"
+            ),
         };
 
         // Include brief debug info if available (first 5 lines)
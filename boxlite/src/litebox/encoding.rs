@@ -0,0 +1,377 @@
+//! Decoding of exec output (`BoxCommand::output_encoding`).
+//!
+//! stdout and stderr reach the host as byte chunks, from the guest agent or
+//! the REST server. Both runtimes pass them through [`decode_stream`]
+//! before the output filters, so the streams, the line views, the pipes and
+//! the SDKs all see the same text. Chunks can split UTF-8 sequences;
+//! [`Utf8Decoder`] carries an incomplete sequence over to the next chunk.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, OnceLock};
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use super::pipe::{self, PacedReceiver, Pacing};
+
+/// How the output streams of a command are decoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputEncoding {
+    /// UTF-8; invalid bytes become U+FFFD.
+    #[default]
+    Utf8Lossy,
+    /// UTF-8; the stream ends at the first invalid sequence, which is
+    /// reported by `ExecStdout::decode_error` and the line and pipe APIs.
+    Strict,
+    /// No decoding: the output is read as bytes with
+    /// `Execution::stdout_bytes`/`stderr_bytes` or the pipes.
+    Raw,
+}
+
+/// An invalid UTF-8 sequence in a stream decoded with
+/// [`OutputEncoding::Strict`].
+#[derive(Clone, Debug, PartialEq, Eq, thiserror::Error)]
+#[error("invalid UTF-8 at byte {offset}: {bytes:02x?}")]
+pub struct InvalidUtf8 {
+    /// Offset of the sequence from the start of the stream.
+    pub offset: u64,
+    /// The invalid bytes (at most 3).
+    pub bytes: Vec<u8>,
+}
+
+/// Decodes a byte stream as UTF-8 chunk by chunk, holding back a sequence
+/// split across chunks until the rest arrives.
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Decoder {
+    pending: Vec<u8>,
+    /// Bytes decoded so far.
+    offset: u64,
+}
+
+impl Utf8Decoder {
+    /// Text of `bytes` after what the previous chunk left incomplete.
+    /// Invalid bytes become U+FFFD.
+    pub(crate) fn decode(&mut self, bytes: &[u8]) -> String {
+        let complete = self.take_complete(bytes);
+        String::from_utf8_lossy(&complete).into_owned()
+    }
+
+    /// Like [`decode`](Self::decode), but stops at the first invalid
+    /// sequence: returns the text before it and the error. Once an error is
+    /// returned, the decoder must not be used again.
+    pub(crate) fn decode_strict(&mut self, bytes: &[u8]) -> (String, Option<InvalidUtf8>) {
+        let start = self.offset;
+        let complete = self.take_complete(bytes);
+        match std::str::from_utf8(&complete) {
+            Ok(text) => (text.to_string(), None),
+            Err(e) => {
+                let valid = e.valid_up_to();
+                let len = e.error_len().unwrap_or(complete.len() - valid);
+                let text = String::from_utf8_lossy(&complete[..valid]).into_owned();
+                let error = InvalidUtf8 {
+                    offset: start + valid as u64,
+                    bytes: complete[valid..valid + len].to_vec(),
+                };
+                (text, Some(error))
+            }
+        }
+    }
+
+    /// Whatever is still held back, at the end of the stream.
+    pub(crate) fn finish(&mut self) -> String {
+        let text = String::from_utf8_lossy(&self.pending).into_owned();
+        self.pending.clear();
+        text
+    }
+
+    /// Like [`finish`](Self::finish): a sequence cut off by the end of the
+    /// stream is an error.
+    pub(crate) fn finish_strict(&mut self) -> Result<(), InvalidUtf8> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        Err(InvalidUtf8 {
+            offset: self.offset,
+            bytes: std::mem::take(&mut self.pending),
+        })
+    }
+
+    /// The complete sequences of what is pending plus `bytes`.
+    fn take_complete(&mut self, bytes: &[u8]) -> Vec<u8> {
+        self.pending.extend_from_slice(bytes);
+        let tail = self
+            .pending
+            .split_off(self.pending.len() - incomplete_tail(&self.pending));
+        let complete = std::mem::replace(&mut self.pending, tail);
+        self.offset += complete.len() as u64;
+        complete
+    }
+}
+
+/// Length of the incomplete UTF-8 sequence `bytes` ends with, if any.
+fn incomplete_tail(bytes: &[u8]) -> usize {
+    let start = bytes.len().saturating_sub(3);
+    for i in (start..bytes.len()).rev() {
+        let width = match bytes[i] {
+            0x80..=0xBF => continue,
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => return 0,
+        };
+        let have = bytes.len() - i;
+        return if have < width { have } else { 0 };
+    }
+    0
+}
+
+/// Where a strictly decoded stream records the sequence that ended it.
+/// Clones share the record.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DecodeStatus(Arc<OnceLock<InvalidUtf8>>);
+
+impl DecodeStatus {
+    /// Record `error`; only the first one is kept.
+    pub(crate) fn fail(&self, error: InvalidUtf8) {
+        let _ = self.0.set(error);
+    }
+
+    pub(crate) fn error(&self) -> Option<InvalidUtf8> {
+        self.0.get().cloned()
+    }
+}
+
+/// Decode the byte chunks of `rx` as text on a new task.
+///
+/// With [`OutputEncoding::Strict`], the text stream ends at the first
+/// invalid sequence and `status` records it; the rest of `rx` is drained so
+/// the source is not held back. Empty chunks are not forwarded. The text
+/// stream shares `rx`'s pacing, like the output filters.
+pub(crate) fn decode_stream(
+    mut rx: PacedReceiver<Vec<u8>>,
+    encoding: OutputEncoding,
+    status: DecodeStatus,
+) -> PacedReceiver<String> {
+    let strict = encoding == OutputEncoding::Strict;
+    let (tx, text_rx) = pipe::channel(rx.pacing());
+    tokio::spawn(async move {
+        let mut decoder = Utf8Decoder::default();
+        while let Some(chunk) = rx.recv().await {
+            let (text, error) = if strict {
+                decoder.decode_strict(&chunk)
+            } else {
+                (decoder.decode(&chunk), None)
+            };
+            if !text.is_empty() && tx.send(text).await.is_err() {
+                return;
+            }
+            if let Some(error) = error {
+                status.fail(error);
+                drop(tx);
+                while rx.recv().await.is_some() {}
+                return;
+            }
+        }
+        if strict {
+            if let Err(error) = decoder.finish_strict() {
+                status.fail(error);
+            }
+        } else {
+            let rest = decoder.finish();
+            if !rest.is_empty() {
+                let _ = tx.send_now(rest);
+            }
+        }
+    });
+    text_rx
+}
+
+/// One output stream of an execution, before it is handed out as
+/// `ExecStdout`/`ExecStderr` (text) or `ExecOutputBytes` (raw).
+pub(crate) enum OutputStream {
+    Text {
+        receiver: PacedReceiver<String>,
+        status: DecodeStatus,
+    },
+    Raw(PacedReceiver<Vec<u8>>),
+}
+
+impl OutputStream {
+    pub(crate) fn pacing(&self) -> &Pacing {
+        match self {
+            Self::Text { receiver, .. } => receiver.pacing(),
+            Self::Raw(receiver) => receiver.pacing(),
+        }
+    }
+
+    pub(crate) fn is_raw(&self) -> bool {
+        matches!(self, Self::Raw(_))
+    }
+
+    /// The sequence that ended a strictly decoded stream early.
+    pub(crate) fn decode_error(&self) -> Option<InvalidUtf8> {
+        match self {
+            Self::Text { status, .. } => status.error(),
+            Self::Raw(_) => None,
+        }
+    }
+
+    /// Fail with `InvalidData` if strict decoding ended the stream early.
+    pub(crate) fn check_decoded(&self) -> io::Result<()> {
+        match self.decode_error() {
+            Some(e) => Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The bytes of either kind of stream, for the pipes.
+impl Stream for OutputStream {
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match &mut *self {
+            Self::Text { receiver, .. } => receiver
+                .poll_recv(cx)
+                .map(|chunk| chunk.map(String::into_bytes)),
+            Self::Raw(receiver) => receiver.poll_recv(cx),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode `chunks` strictly, as `decode_stream` does.
+    fn strict(chunks: &[&[u8]]) -> (String, Option<InvalidUtf8>) {
+        let mut decoder = Utf8Decoder::default();
+        let mut text = String::new();
+        for chunk in chunks {
+            let (decoded, error) = decoder.decode_strict(chunk);
+            text.push_str(&decoded);
+            if error.is_some() {
+                return (text, error);
+            }
+        }
+        (text, decoder.finish_strict().err())
+    }
+
+    async fn decode_all(chunks: &[&[u8]], encoding: OutputEncoding) -> (String, DecodeStatus) {
+        let (tx, rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        for chunk in chunks {
+            tx.send_now(chunk.to_vec()).unwrap();
+        }
+        drop(tx);
+
+        let status = DecodeStatus::default();
+        let mut text_rx = decode_stream(rx, encoding, status.clone());
+        let mut text = String::new();
+        while let Some(chunk) = text_rx.recv().await {
+            assert!(!chunk.is_empty());
+            text.push_str(&chunk);
+        }
+        (text, status)
+    }
+
+    #[test]
+    fn test_decoder_joins_split_sequences() {
+        let text = "héllo 世界 🦀";
+        let bytes = text.as_bytes();
+        for split in 0..=bytes.len() {
+            let mut decoder = Utf8Decoder::default();
+            let mut decoded = decoder.decode(&bytes[..split]);
+            decoded.push_str(&decoder.decode(&bytes[split..]));
+            decoded.push_str(&decoder.finish());
+            assert_eq!(decoded, text, "split at {split}");
+
+            assert_eq!(
+                strict(&[&bytes[..split], &bytes[split..]]),
+                (text.to_string(), None),
+                "split at {split}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decoder_byte_by_byte() {
+        let text = "a€b🦀c";
+        let mut decoder = Utf8Decoder::default();
+        let decoded: String = text
+            .as_bytes()
+            .iter()
+            .map(|b| decoder.decode(std::slice::from_ref(b)))
+            .collect();
+        assert_eq!(decoded, text);
+    }
+
+    #[test]
+    fn test_decoder_replaces_invalid_and_truncated_bytes() {
+        let mut decoder = Utf8Decoder::default();
+        assert_eq!(decoder.decode(b"a\xffb\xe4\xb8"), "a\u{fffd}b");
+        assert_eq!(decoder.finish(), "\u{fffd}");
+    }
+
+    #[test]
+    fn test_strict_reports_offset_across_chunks() {
+        // "añ" is 3 bytes; the Latin-1 "é" (0xe9) follows in the next chunk
+        // after a sequence split across the boundary
+        let (text, error) = strict(&[b"a\xc3", b"\xb1\xe2\x82", b"\xac\xe9tail"]);
+        assert_eq!(text, "añ€");
+        assert_eq!(
+            error,
+            Some(InvalidUtf8 {
+                offset: 6,
+                bytes: vec![0xe9],
+            })
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_bad_continuation_held_back() {
+        // 0xe4 0xb8 looks like the start of a 3-byte sequence until 'x'
+        let (text, error) = strict(&[b"ok\xe4\xb8", b"x"]);
+        assert_eq!(text, "ok");
+        assert_eq!(
+            error,
+            Some(InvalidUtf8 {
+                offset: 2,
+                bytes: vec![0xe4, 0xb8],
+            })
+        );
+    }
+
+    #[test]
+    fn test_strict_rejects_truncated_end() {
+        let (text, error) = strict(&[b"end \xf0\x9f"]);
+        assert_eq!(text, "end ");
+        assert_eq!(
+            error,
+            Some(InvalidUtf8 {
+                offset: 4,
+                bytes: vec![0xf0, 0x9f],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_decode_stream_modes() {
+        let chunks: &[&[u8]] = &[b"caf\xc3", b"\xa9 \xff ", b"\xe4\xb8", b"\x96"];
+
+        let (text, status) = decode_all(chunks, OutputEncoding::Utf8Lossy).await;
+        assert_eq!(text, "café \u{fffd} 世");
+        assert_eq!(status.error(), None);
+
+        let (text, status) = decode_all(chunks, OutputEncoding::Strict).await;
+        assert_eq!(text, "café ");
+        assert_eq!(
+            status.error(),
+            Some(InvalidUtf8 {
+                offset: 6,
+                bytes: vec![0xff],
+            })
+        );
+    }
+}
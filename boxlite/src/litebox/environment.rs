@@ -0,0 +1,364 @@
+//! Reproducibility records of the environment a box ran with.
+//!
+//! Every start that boots the VM resolves an [`EnvironmentReport`]: the image
+//! digest actually used, the resolved process, the engine and its guest
+//! command line, resources, volumes and component versions. A report is
+//! appended to `{box_home}/environment.jsonl` when its content differs from
+//! the previous one, so the file holds one entry per distinct environment,
+//! oldest first, and a retagged image or upgraded engine shows up as a new
+//! entry rather than overwriting history.
+//!
+//! `content_sha256` covers everything but the timestamp, making reports of
+//! identical environments byte-for-byte comparable. It detects accidental
+//! edits, not forgery: nothing is signed.
+
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::runtime::advanced_options::ResourceLimits;
+use crate::runtime::version::VersionInfo;
+use crate::vmm::VmmKind;
+
+/// File name of the report history inside the box directory.
+const ENVIRONMENT_REPORTS_FILE: &str = "environment.jsonl";
+
+/// Machine-readable record of what a box ran with.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    /// SHA256 (hex) of the canonical JSON of `content`.
+    pub content_sha256: String,
+    /// Start that first produced this content.
+    pub generated_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub content: EnvironmentContent,
+}
+
+/// The hashed part of an [`EnvironmentReport`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentContent {
+    pub box_id: String,
+    pub image: ImageRecord,
+    pub process: ProcessRecord,
+    pub engine: EngineRecord,
+    pub resources: ResourceRecord,
+    pub volumes: Vec<VolumeRecord>,
+    /// boxlite and engine component versions, including the guest binary hash.
+    pub versions: VersionInfo,
+}
+
+/// Image the box's container configuration was resolved from.
+///
+/// On restarts the rootfs disk still holds the files of the first start's
+/// image; a differing digest here means the reference now resolves elsewhere.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImageRecord {
+    /// Reference as requested (`local:{path}` for rootfs paths).
+    pub reference: String,
+    pub manifest_digest: String,
+    pub config_digest: String,
+}
+
+/// Container process after applying box options to the image config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProcessRecord {
+    pub entrypoint: Vec<String>,
+    pub cmd: Vec<String>,
+    /// `KEY=VALUE` entries.
+    pub env: Vec<String>,
+    pub working_dir: String,
+    pub user: String,
+    /// Script run in place of `entrypoint` + `cmd`, which it receives as
+    /// arguments (`BoxOptions::entrypoint_script`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub entrypoint_script: Option<String>,
+}
+
+/// Engine that ran the VM and how it booted the guest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineRecord {
+    pub kind: VmmKind,
+    /// Guest init executable and arguments.
+    pub guest_init: Vec<String>,
+    /// Guest init environment (`KEY=VALUE`).
+    ///
+    /// The engine renders `guest_init` and `guest_env` into the kernel
+    /// command line after its built-in defaults.
+    pub guest_env: Vec<String>,
+}
+
+/// Resources requested for the VM.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceRecord {
+    /// `None` means the engine default.
+    pub cpus: Option<u8>,
+    /// `None` means the engine default.
+    pub memory_mib: Option<u32>,
+    pub disk_size_gb: Option<u64>,
+    pub limits: ResourceLimits,
+}
+
+/// A host directory mounted into the box.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeRecord {
+    /// Canonical host path.
+    pub host_path: PathBuf,
+    pub guest_path: String,
+    pub read_only: bool,
+    /// SHA256 of the directory tree at start, when
+    /// `AdvancedBoxOptions::report_volume_hashes` is set.
+    pub content_sha256: Option<String>,
+}
+
+impl EnvironmentReport {
+    /// Stamp `content` with its hash and the current time.
+    pub(crate) fn new(content: EnvironmentContent) -> BoxliteResult<Self> {
+        Ok(Self {
+            content_sha256: content_hash(&content)?,
+            generated_at: Utc::now(),
+            content,
+        })
+    }
+
+    /// Whether `content_sha256` still matches the content.
+    pub fn verify(&self) -> bool {
+        content_hash(&self.content).is_ok_and(|hash| hash == self.content_sha256)
+    }
+
+    /// Append to the box's history unless the last entry has the same content.
+    ///
+    /// Returns whether the report was appended.
+    pub(crate) fn record(&self, box_home: &Path) -> BoxliteResult<bool> {
+        let unchanged = Self::load_all(box_home)?
+            .last()
+            .is_some_and(|last| last.content_sha256 == self.content_sha256);
+        if unchanged {
+            return Ok(false);
+        }
+
+        let path = box_home.join(ENVIRONMENT_REPORTS_FILE);
+        let mut line = serde_json::to_vec(self)?;
+        line.push(b'\n');
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(&line))
+            .map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to write environment report to {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?;
+        Ok(true)
+    }
+
+    /// All reports of a box, oldest first. Empty if it never started.
+    ///
+    /// Unparseable lines (e.g. from an interrupted write) are skipped.
+    pub(crate) fn load_all(box_home: &Path) -> BoxliteResult<Vec<Self>> {
+        let path = box_home.join(ENVIRONMENT_REPORTS_FILE);
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(BoxliteError::Storage(format!(
+                    "Failed to read environment reports from {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+        };
+
+        let mut reports = Vec::new();
+        for line in std::io::BufReader::new(file).lines() {
+            let line = line.map_err(|e| {
+                BoxliteError::Storage(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(&line) {
+                Ok(report) => reports.push(report),
+                Err(e) => tracing::warn!(
+                    path = %path.display(),
+                    error = %e,
+                    "Skipping unreadable environment report"
+                ),
+            }
+        }
+        Ok(reports)
+    }
+}
+
+fn content_hash(content: &EnvironmentContent) -> BoxliteResult<String> {
+    let json = serde_json::to_vec(content)?;
+    Ok(format!("{:x}", Sha256::digest(&json)))
+}
+
+/// SHA256 of a directory tree: names, file contents and symlink targets.
+///
+/// Entries are visited in name order and symlinks are not followed, so the
+/// hash is independent of timestamps and traversal order. Blocking; reads
+/// every file.
+pub(crate) fn hash_tree(root: &Path) -> std::io::Result<String> {
+    let mut hasher = Sha256::new();
+    hash_dir(root, Path::new(""), &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn hash_dir(root: &Path, rel: &Path, hasher: &mut Sha256) -> std::io::Result<()> {
+    let mut entries = std::fs::read_dir(root.join(rel))?.collect::<Result<Vec<_>, _>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let rel = rel.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        // Length-prefixed path so names can't run into content
+        let name = rel.to_string_lossy();
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name.as_bytes());
+
+        if file_type.is_dir() {
+            hasher.update(b"d");
+            hash_dir(root, &rel, hasher)?;
+        } else if file_type.is_symlink() {
+            hasher.update(b"l");
+            hasher.update(
+                std::fs::read_link(entry.path())?
+                    .to_string_lossy()
+                    .as_bytes(),
+            );
+        } else if file_type.is_file() {
+            hasher.update(b"f");
+            hasher.update(entry.metadata()?.len().to_le_bytes());
+            let mut file = std::fs::File::open(entry.path())?;
+            let mut buffer = vec![0u8; 64 * 1024];
+            loop {
+                let n = file.read(&mut buffer)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..n]);
+            }
+        } else {
+            // Sockets, FIFOs and devices contribute their name only
+            hasher.update(b"o");
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn content(image_digest: &str) -> EnvironmentContent {
+        EnvironmentContent {
+            box_id: "01HBOX".to_string(),
+            image: ImageRecord {
+                reference: "alpine:latest".to_string(),
+                manifest_digest: image_digest.to_string(),
+                config_digest: "sha256:cfg".to_string(),
+            },
+            process: ProcessRecord {
+                entrypoint: vec![],
+                cmd: vec!["/bin/sh".to_string()],
+                env: vec!["PATH=/bin".to_string()],
+                working_dir: "/".to_string(),
+                user: "0:0".to_string(),
+                entrypoint_script: None,
+            },
+            engine: EngineRecord {
+                kind: VmmKind::Libkrun,
+                guest_init: vec!["/boxlite/bin/boxlite-guest".to_string()],
+                guest_env: vec![],
+            },
+            resources: ResourceRecord {
+                cpus: Some(2),
+                memory_mib: None,
+                disk_size_gb: None,
+                limits: ResourceLimits::default(),
+            },
+            volumes: vec![],
+            versions: VersionInfo::default(),
+        }
+    }
+
+    #[test]
+    fn test_hash_covers_content_not_timestamp() {
+        let a = EnvironmentReport::new(content("sha256:aaa")).unwrap();
+        let mut b = EnvironmentReport::new(content("sha256:aaa")).unwrap();
+        b.generated_at = a.generated_at + chrono::Duration::hours(1);
+        assert_eq!(a.content_sha256, b.content_sha256);
+        assert!(a.verify() && b.verify());
+
+        let c = EnvironmentReport::new(content("sha256:bbb")).unwrap();
+        assert_ne!(a.content_sha256, c.content_sha256);
+
+        let mut tampered = a.clone();
+        tampered.content.process.user = "1000".to_string();
+        assert!(!tampered.verify());
+    }
+
+    #[test]
+    fn test_entrypoint_script_only_serialized_when_set() {
+        let plain = content("sha256:aaa");
+        let json = serde_json::to_value(&plain.process).unwrap();
+        assert!(json.get("entrypoint_script").is_none());
+
+        let mut wrapped = plain.clone();
+        wrapped.process.entrypoint_script = Some("#!/bin/sh\nexec \"$@\"\n".to_string());
+        let json = serde_json::to_value(&wrapped.process).unwrap();
+        assert_eq!(json["entrypoint_script"], "#!/bin/sh\nexec \"$@\"\n");
+        assert_ne!(
+            EnvironmentReport::new(plain).unwrap().content_sha256,
+            EnvironmentReport::new(wrapped).unwrap().content_sha256
+        );
+    }
+
+    #[test]
+    fn test_record_appends_only_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(EnvironmentReport::load_all(dir.path()).unwrap().is_empty());
+
+        let first = EnvironmentReport::new(content("sha256:aaa")).unwrap();
+        assert!(first.record(dir.path()).unwrap());
+        let again = EnvironmentReport::new(content("sha256:aaa")).unwrap();
+        assert!(!again.record(dir.path()).unwrap());
+        let retagged = EnvironmentReport::new(content("sha256:bbb")).unwrap();
+        assert!(retagged.record(dir.path()).unwrap());
+
+        let history = EnvironmentReport::load_all(dir.path()).unwrap();
+        let digests: Vec<_> = history
+            .iter()
+            .map(|r| r.content.image.manifest_digest.as_str())
+            .collect();
+        assert_eq!(digests, vec!["sha256:aaa", "sha256:bbb"]);
+        assert!(history.iter().all(EnvironmentReport::verify));
+    }
+
+    #[test]
+    fn test_hash_tree_tracks_names_and_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("sub")).unwrap();
+        std::fs::write(dir.path().join("sub/data.txt"), b"one").unwrap();
+        std::fs::write(dir.path().join("top.txt"), b"").unwrap();
+
+        let base = hash_tree(dir.path()).unwrap();
+        assert_eq!(hash_tree(dir.path()).unwrap(), base);
+
+        std::fs::write(dir.path().join("sub/data.txt"), b"two").unwrap();
+        let edited = hash_tree(dir.path()).unwrap();
+        assert_ne!(edited, base);
+
+        std::fs::rename(dir.path().join("top.txt"), dir.path().join("renamed.txt")).unwrap();
+        assert_ne!(hash_tree(dir.path()).unwrap(), edited);
+    }
+}
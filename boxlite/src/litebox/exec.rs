@@ -3,18 +3,48 @@
 //! Type definitions for executing commands in a box.
 //! The actual execution logic is in BoxImpl::exec().
 
-use crate::runtime::backend::ExecBackend;
-use boxlite_shared::errors::BoxliteResult;
+use super::capture::ExecOutputPaths;
+use super::encoding::{DecodeStatus, InvalidUtf8, OutputEncoding, OutputStream};
+use super::lines::{self, JsonLineError, LineOptions, Timestamped};
+use super::output_filter::{OutputFilter, OutputFilterFactory};
+use super::pipe::{self, PacedReceiver, PacedSender};
+use super::scratch::ScratchSpec;
+use crate::runtime::backend::{BoxBackend, ExecBackend};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use futures::Stream;
+use serde::de::DeserializeOwned;
+use std::io;
 use std::pin::Pin;
+use std::sync::Arc;
 use std::task::{Context, Poll};
 use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
 
 /// Command builder for executing programs in a box.
 ///
 /// Provides a builder API similar to `std::process::Command`.
 ///
+/// # Environment
+///
+/// The env a command runs with is built in layers, each over the last:
+///
+/// 1. The image's `ENV`, then `BoxOptions::env` (with
+///    [`inherit_runtime_env`](Self::inherit_runtime_env), the container
+///    init process's live environ replaces both).
+/// 2. Env a warm pool claim added (`WarmSelector::env`).
+/// 3. [`env`](Self::env) / [`env_set`](Self::env_set), replacing a value.
+/// 4. [`env_prepend_path`](Self::env_prepend_path),
+///    [`env_append_path`](Self::env_append_path) and
+///    [`env_unset`](Self::env_unset), in the order they were called. They
+///    are applied by the guest at exec time, so a prepend to `PATH` keeps
+///    the `PATH` of the layers below.
+///
+/// Setting a variable drops the earlier edits of it. Local and REST
+/// runtimes build the env the same way.
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -26,14 +56,46 @@ use tokio::sync::mpsc;
 ///     .timeout(Duration::from_secs(30))
 ///     .working_dir("/workspace");
 /// ```
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
 pub struct BoxCommand {
     pub(crate) command: String,
+    #[serde(default)]
     pub(crate) args: Vec<String>,
+    #[serde(default)]
     pub(crate) env: Option<Vec<(String, String)>>,
+    /// Merges and removals applied by the guest after `env`, in order.
+    #[serde(default)]
+    pub(crate) env_edits: Vec<EnvEdit>,
+    #[serde(default)]
     pub(crate) timeout: Option<Duration>,
+    #[serde(default)]
     pub(crate) working_dir: Option<String>,
+    #[serde(default)]
     pub(crate) tty: bool,
+    /// Expand `{box.*}` / `{exec.id}` placeholders at exec time.
+    #[serde(default)]
+    pub(crate) templating: bool,
+    /// Capture output to files under `{box_home}/execs/{exec_id}/`.
+    #[serde(default)]
+    pub(crate) detach: bool,
+    /// Env keys whose values are redacted from the output.
+    #[serde(default)]
+    pub(crate) redact_env: Vec<String>,
+    /// Start from the container init process's live env and cwd.
+    #[serde(default)]
+    pub(crate) inherit_runtime_env: bool,
+    /// Private tmpfs mounted for this exec only.
+    #[serde(default)]
+    pub(crate) scratch_dir: Option<ScratchSpec>,
+    /// How stdout and stderr are decoded on the host.
+    #[serde(default)]
+    pub(crate) output_encoding: OutputEncoding,
+    /// Host-side transform of output chunks (not serialized).
+    #[serde(skip)]
+    pub(crate) output_filter: Option<OutputFilterFactory>,
+    /// Pre-assigned execution ID (set when `{exec.id}` is used).
+    #[serde(skip)]
+    pub(crate) execution_id: Option<ExecutionId>,
 }
 
 impl BoxCommand {
@@ -43,9 +105,18 @@ impl BoxCommand {
             command: command.into(),
             args: vec![],
             env: None,
+            env_edits: Vec::new(),
             timeout: None,
             working_dir: None,
             tty: false,
+            templating: false,
+            detach: false,
+            redact_env: Vec::new(),
+            inherit_runtime_env: false,
+            scratch_dir: None,
+            output_encoding: OutputEncoding::default(),
+            output_filter: None,
+            execution_id: None,
         }
     }
 
@@ -65,11 +136,63 @@ impl BoxCommand {
         self
     }
 
-    /// Set an environment variable.
+    /// Set an environment variable, replacing its value.
+    ///
+    /// The same as [`env_set`](Self::env_set). See [Environment](#environment)
+    /// for how this combines with the box's env.
     pub fn env(mut self, key: impl Into<String>, val: impl Into<String>) -> Self {
+        let key = key.into();
+        self.env_edits.retain(|edit| edit.key() != key);
         self.env
             .get_or_insert_with(Vec::new)
-            .push((key.into(), val.into()));
+            .push((key, val.into()));
+        self
+    }
+
+    /// Set an environment variable, replacing its value.
+    pub fn env_set(self, key: impl Into<String>, val: impl Into<String>) -> Self {
+        self.env(key, val)
+    }
+
+    /// Put `val` in front of the current value of `key`, joined with `sep`.
+    ///
+    /// Meant for list variables: `env_prepend_path("PATH", "/custom/bin",
+    /// ":")` runs the command with `/custom/bin:` followed by the `PATH` it
+    /// would otherwise get. A `key` that is unset or empty becomes `val`.
+    pub fn env_prepend_path(
+        mut self,
+        key: impl Into<String>,
+        val: impl Into<String>,
+        sep: impl Into<String>,
+    ) -> Self {
+        self.env_edits.push(EnvEdit::Prepend {
+            key: key.into(),
+            value: val.into(),
+            separator: sep.into(),
+        });
+        self
+    }
+
+    /// Put `val` after the current value of `key`, joined with `sep`.
+    ///
+    /// See [`env_prepend_path`](Self::env_prepend_path).
+    pub fn env_append_path(
+        mut self,
+        key: impl Into<String>,
+        val: impl Into<String>,
+        sep: impl Into<String>,
+    ) -> Self {
+        self.env_edits.push(EnvEdit::Append {
+            key: key.into(),
+            value: val.into(),
+            separator: sep.into(),
+        });
+        self
+    }
+
+    /// Remove `key` from the command's environment, whichever layer set it.
+    pub fn env_unset(mut self, key: impl Into<String>) -> Self {
+        self.env_edits.push(EnvEdit::Unset { key: key.into() });
         self
     }
 
@@ -92,6 +215,145 @@ impl BoxCommand {
         self.tty = enable;
         self
     }
+
+    /// Expand placeholders in args, env values and working directory.
+    ///
+    /// Supported: `{box.id}`, `{box.name}`, `{box.image}` and `{exec.id}`.
+    /// Use `{{` / `}}` for literal braces. Unknown placeholders fail the exec
+    /// with `InvalidArgument`. Off by default.
+    pub fn enable_templating(mut self, enable: bool) -> Self {
+        self.templating = enable;
+        self
+    }
+
+    /// Capture output for reading after the caller has gone.
+    ///
+    /// The guest writes stdout and stderr to
+    /// `{box_home}/execs/{exec_id}/{stdout,stderr}` from the moment the
+    /// command starts, capped at 16 MiB per stream, whether or not anyone
+    /// reads the output streams. Read them back with
+    /// [`Execution::output_paths`] or `LiteBox::exec_output()`. The output
+    /// streams still work but may drop chunks nobody reads in time; the
+    /// files never do.
+    pub fn detach(mut self, enable: bool) -> Self {
+        self.detach = enable;
+        self
+    }
+
+    /// Start from the environment and working directory the container's
+    /// init process has now, like `docker exec`.
+    ///
+    /// By default an exec gets the env and working directory the box was
+    /// created with, so changes made by the entrypoint are not seen. With
+    /// this set, the guest reads the init process's environ and cwd from
+    /// `/proc` at exec time. The environ is what the init process was last
+    /// exec'd with: an entrypoint that exports variables and then `exec`s
+    /// its main process passes them on. Env set with [`env`](Self::env) and
+    /// [`working_dir`](Self::working_dir) still override; the box's
+    /// `working_dir` does not. Off by default.
+    pub fn inherit_runtime_env(mut self, enable: bool) -> Self {
+        self.inherit_runtime_env = enable;
+        self
+    }
+
+    /// Give the command a private scratch directory.
+    ///
+    /// The guest mounts a fresh tmpfs of `size_mib` at `mount_at` in a mount
+    /// namespace of the command's own, so concurrent execs never see each
+    /// other's files, and sets `BOXLITE_SCRATCH_DIR` to its path. The tmpfs
+    /// goes away when the command and every process it started have exited,
+    /// whatever the outcome. Its pages count against box memory, so the size
+    /// may not exceed `BoxOptions::memory_mib`; the exec fails with
+    /// `InvalidArgument` otherwise.
+    pub fn scratch_dir(mut self, scratch: ScratchSpec) -> Self {
+        self.scratch_dir = Some(scratch);
+        self
+    }
+
+    /// Redact the value of environment variable `key` from the output.
+    ///
+    /// The value is looked up in this command's env, then in the box's
+    /// `BoxOptions::env`, and replaced with `[REDACTED]` wherever it appears
+    /// in stdout or stderr. Adds to `BoxOptions::redact_env`.
+    pub fn redact_env(mut self, key: impl Into<String>) -> Self {
+        self.redact_env.push(key.into());
+        self
+    }
+
+    /// Transform output chunks on the host before they reach the streams.
+    ///
+    /// Each of stdout and stderr gets its own clone of `filter`. It runs
+    /// after env redaction. Detached capture files are written by the guest
+    /// and only get env redaction, not this filter.
+    pub fn output_filter<F>(mut self, filter: F) -> Self
+    where
+        F: OutputFilter + Clone + Sync + 'static,
+    {
+        self.output_filter = Some(OutputFilterFactory::new(filter));
+        self
+    }
+
+    /// Choose how stdout and stderr are decoded on the host.
+    ///
+    /// [`OutputEncoding::Utf8Lossy`] (the default) replaces invalid UTF-8
+    /// with U+FFFD. [`OutputEncoding::Strict`] ends a stream at its first
+    /// invalid sequence: [`ExecStdout::decode_error`] gives its offset, the
+    /// line streams yield [`JsonLineError::InvalidUtf8`] and the pipes fail
+    /// with `InvalidData`. [`OutputEncoding::Raw`] skips decoding: read the
+    /// output with [`Execution::stdout_bytes`]/[`Execution::stderr_bytes`]
+    /// or the pipes; `stdout()`/`stderr()` return `None`. Raw output cannot
+    /// be redacted or filtered, so the exec fails with `InvalidArgument` if
+    /// the command or box redacts env or the command has an output filter.
+    /// Sequences split across chunks are joined in every mode, and local and
+    /// REST runtimes decode the same way.
+    pub fn output_encoding(mut self, encoding: OutputEncoding) -> Self {
+        self.output_encoding = encoding;
+        self
+    }
+}
+
+/// Change to one variable of a command's env that depends on the value
+/// it has in the guest (see [`BoxCommand`]'s environment layers).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub(crate) enum EnvEdit {
+    Prepend {
+        key: String,
+        value: String,
+        separator: String,
+    },
+    Append {
+        key: String,
+        value: String,
+        separator: String,
+    },
+    Unset {
+        key: String,
+    },
+}
+
+impl EnvEdit {
+    pub(crate) fn key(&self) -> &str {
+        match self {
+            Self::Prepend { key, .. } | Self::Append { key, .. } | Self::Unset { key } => key,
+        }
+    }
+
+    /// The value merged into the variable; `None` for an unset.
+    pub(crate) fn value(&self) -> Option<&str> {
+        match self {
+            Self::Prepend { value, .. } | Self::Append { value, .. } => Some(value),
+            Self::Unset { .. } => None,
+        }
+    }
+
+    /// Mutable access to the merged value, if any (for templating).
+    pub(crate) fn value_mut(&mut self) -> Option<&mut String> {
+        match self {
+            Self::Prepend { value, .. } | Self::Append { value, .. } => Some(value),
+            Self::Unset { .. } => None,
+        }
+    }
 }
 
 /// Handle to a running command execution.
@@ -120,11 +382,28 @@ impl BoxCommand {
 /// # Ok(())
 /// # }
 /// ```
+///
+/// # Lifetime
+///
+/// An execution does not depend on the [`LiteBox`](crate::LiteBox) handle
+/// it came from: dropping that handle (or every handle to the box) leaves
+/// the command running and its streams, `wait()` and `kill()` working.
+///
+/// Stopping or removing the box, or shutting down the runtime, ends the
+/// command. Output received before that can still be read and the streams
+/// then end; `wait()`, `kill()`, `signal()`, `resize_tty()` and stdin
+/// writes fail with `BoxliteError::Stopped`, unless the command had already
+/// exited, in which case `wait()` returns its result.
 #[derive(Clone)]
 pub struct Execution {
     id: ExecutionId,
+    output_paths: Option<ExecOutputPaths>,
     control: std::sync::Arc<tokio::sync::Mutex<ExecutionControl>>,
     completion: std::sync::Arc<tokio::sync::Mutex<ExecutionCompletion>>,
+    /// Cancelled when the box is stopped or removed.
+    stopped: CancellationToken,
+    /// The box the command runs in, held until the last clone is dropped.
+    _backend: Option<Arc<dyn BoxBackend>>,
 }
 
 pub(crate) struct ExecutionInner {
@@ -136,10 +415,10 @@ pub(crate) struct ExecutionInner {
     stdin: Option<ExecStdin>,
 
     /// Standard output stream (read-only).
-    stdout: Option<ExecStdout>,
+    stdout: Option<OutputStream>,
 
     /// Standard error stream (read-only).
-    stderr: Option<ExecStderr>,
+    stderr: Option<OutputStream>,
 }
 
 pub(crate) struct ExecutionCompletion {
@@ -157,14 +436,14 @@ impl Execution {
         interface: Box<dyn ExecBackend>,
         result_rx: mpsc::UnboundedReceiver<ExecResult>,
         stdin: Option<ExecStdin>,
-        stdout: Option<ExecStdout>,
-        stderr: Option<ExecStderr>,
+        stdout: OutputStream,
+        stderr: OutputStream,
     ) -> Self {
         let control = ExecutionControl {
             interface,
             stdin,
-            stdout,
-            stderr,
+            stdout: Some(stdout),
+            stderr: Some(stderr),
         };
         let completion = ExecutionCompletion {
             result_rx,
@@ -173,16 +452,56 @@ impl Execution {
 
         Self {
             id: execution_id,
+            output_paths: None,
             control: std::sync::Arc::new(tokio::sync::Mutex::new(control)),
             completion: std::sync::Arc::new(tokio::sync::Mutex::new(completion)),
+            stopped: CancellationToken::new(),
+            _backend: None,
         }
     }
 
+    /// Set where the guest captures this execution's output.
+    pub(crate) fn with_output_paths(mut self, paths: ExecOutputPaths) -> Self {
+        self.output_paths = Some(paths);
+        self
+    }
+
+    /// Report `Stopped` once `token` (the box's) is cancelled.
+    pub(crate) fn stopped_by(mut self, token: CancellationToken) -> Self {
+        self.stopped = token;
+        self
+    }
+
+    /// Keep `backend` (and the box's resources) alive for as long as the
+    /// execution exists, so dropping the last `LiteBox` does not tear down
+    /// what the command still uses.
+    pub(crate) fn keep_alive(mut self, backend: Arc<dyn BoxBackend>) -> Self {
+        self._backend = Some(backend);
+        self
+    }
+
+    /// Fail with `Stopped` if the box was stopped or removed.
+    fn ensure_running(&self) -> BoxliteResult<()> {
+        if self.stopped.is_cancelled() {
+            return Err(box_stopped(&self.id));
+        }
+        Ok(())
+    }
+
     /// Get the execution ID.
     pub fn id(&self) -> &ExecutionId {
         &self.id
     }
 
+    /// Host paths of the captured output, for detached commands.
+    ///
+    /// `None` unless the command was started with
+    /// [`BoxCommand::detach`]. The files may not exist when the guest agent
+    /// predates output capture.
+    pub fn output_paths(&self) -> Option<&ExecOutputPaths> {
+        self.output_paths.as_ref()
+    }
+
     /// Take the stdin stream (can only be called once).
     pub fn stdin(&mut self) -> Option<ExecStdin> {
         futures::executor::block_on(async {
@@ -192,18 +511,260 @@ impl Execution {
     }
 
     /// Take the stdout stream (can only be called once).
+    ///
+    /// `None` once taken, or if the command uses [`OutputEncoding::Raw`];
+    /// see [`stdout_bytes`](Self::stdout_bytes).
     pub fn stdout(&mut self) -> Option<ExecStdout> {
+        let OutputStream::Text { receiver, status } = self.take_stdout(|s| !s.is_raw())? else {
+            return None;
+        };
+        Some(ExecStdout { receiver, status })
+    }
+
+    /// Take the stderr stream (can only be called once).
+    ///
+    /// `None` once taken, or if the command uses [`OutputEncoding::Raw`].
+    pub fn stderr(&mut self) -> Option<ExecStderr> {
+        let OutputStream::Text { receiver, status } = self.take_stderr(|s| !s.is_raw())? else {
+            return None;
+        };
+        Some(ExecStderr { receiver, status })
+    }
+
+    /// Take stdout as bytes, for a command using [`OutputEncoding::Raw`].
+    ///
+    /// `None` once taken, or if the command decodes its output; use
+    /// [`stdout`](Self::stdout) then.
+    pub fn stdout_bytes(&mut self) -> Option<ExecOutputBytes> {
+        let OutputStream::Raw(receiver) = self.take_stdout(OutputStream::is_raw)? else {
+            return None;
+        };
+        Some(ExecOutputBytes { receiver })
+    }
+
+    /// Take stderr as bytes, like [`stdout_bytes`](Self::stdout_bytes).
+    pub fn stderr_bytes(&mut self) -> Option<ExecOutputBytes> {
+        let OutputStream::Raw(receiver) = self.take_stderr(OutputStream::is_raw)? else {
+            return None;
+        };
+        Some(ExecOutputBytes { receiver })
+    }
+
+    /// Take stdout if it is there and `wanted` accepts it.
+    pub(crate) fn take_stdout(
+        &mut self,
+        wanted: fn(&OutputStream) -> bool,
+    ) -> Option<OutputStream> {
         futures::executor::block_on(async {
             let mut control = self.control.lock().await;
-            control.stdout.take()
+            control.stdout.take_if(|stream| wanted(stream))
         })
     }
 
-    /// Take the stderr stream (can only be called once).
-    pub fn stderr(&mut self) -> Option<ExecStderr> {
+    /// Take stderr if it is there and `wanted` accepts it.
+    pub(crate) fn take_stderr(
+        &mut self,
+        wanted: fn(&OutputStream) -> bool,
+    ) -> Option<OutputStream> {
         futures::executor::block_on(async {
             let mut control = self.control.lock().await;
-            control.stderr.take()
+            control.stderr.take_if(|stream| wanted(stream))
+        })
+    }
+
+    /// Copy stdout into `sink` on a new task until the stream ends.
+    ///
+    /// The copy is paced: while `sink` is slow, output backs up to the
+    /// process, which blocks on its next write instead of buffering on the
+    /// host. stdout and stderr share one stream from the guest, so a stalled
+    /// pipe also holds back stderr. Each chunk is flushed as it is written.
+    /// Against a REST runtime, pacing stops at the client: the server keeps
+    /// reading the guest.
+    ///
+    /// The task resolves to the bytes written; it fails if stdout was
+    /// already taken or `sink` fails. With [`OutputEncoding::Strict`], it
+    /// fails with `InvalidData` after writing the text before the first
+    /// invalid sequence. [`OutputEncoding::Raw`] output is copied as is.
+    /// Must be called within a Tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(litebox: &boxlite::LiteBox) -> Result<(), Box<dyn std::error::Error>> {
+    /// use boxlite::BoxCommand;
+    ///
+    /// let mut execution = litebox.exec(BoxCommand::new("ls").arg("-la")).await?;
+    /// let copied = execution.pipe_stdout(tokio::io::stdout());
+    /// let status = execution.wait().await?;
+    /// println!("{} bytes, exit code {}", copied.await??, status.exit_code);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn pipe_stdout<W>(&mut self, sink: W) -> JoinHandle<io::Result<u64>>
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let stdout = self.take_stdout(|_| true);
+        tokio::spawn(async move {
+            let mut stdout = stdout.ok_or_else(|| pipe::taken("stdout"))?;
+            stdout.pacing().start();
+            let written = pipe::pipe_output(&mut stdout, sink).await?;
+            stdout.check_decoded()?;
+            Ok(written)
+        })
+    }
+
+    /// Copy stderr into `sink`, like [`pipe_stdout`](Self::pipe_stdout).
+    pub fn pipe_stderr<W>(&mut self, sink: W) -> JoinHandle<io::Result<u64>>
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let stderr = self.take_stderr(|_| true);
+        tokio::spawn(async move {
+            let mut stderr = stderr.ok_or_else(|| pipe::taken("stderr"))?;
+            stderr.pacing().start();
+            let written = pipe::pipe_output(&mut stderr, sink).await?;
+            stderr.check_decoded()?;
+            Ok(written)
+        })
+    }
+
+    /// Copy stdout and stderr into one `sink`, like `2>&1`.
+    ///
+    /// Chunks keep their order within each stream; chunks of the two streams
+    /// interleave in roughly the order they arrive. Fails if either stream
+    /// was already taken.
+    pub fn pipe_combined<W>(&mut self, sink: W) -> JoinHandle<io::Result<u64>>
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let stdout = self.take_stdout(|_| true);
+        let stderr = self.take_stderr(|_| true);
+        tokio::spawn(async move {
+            let mut stdout = stdout.ok_or_else(|| pipe::taken("stdout"))?;
+            let mut stderr = stderr.ok_or_else(|| pipe::taken("stderr"))?;
+            stdout.pacing().start();
+            stderr.pacing().start();
+            let combined = futures::stream::select(&mut stdout, &mut stderr);
+            let written = pipe::pipe_output(combined, sink).await?;
+            stdout.check_decoded()?;
+            stderr.check_decoded()?;
+            Ok(written)
+        })
+    }
+
+    /// Stream the lines of stdout, with [`LineOptions::default`].
+    ///
+    /// See [`raw_lines_with`](Self::raw_lines_with).
+    pub fn raw_lines(
+        &mut self,
+    ) -> impl Stream<Item = Result<Timestamped<String>, JsonLineError>> + Send + use<> {
+        self.raw_lines_with(LineOptions::default())
+    }
+
+    /// Stream the lines of stdout, each with the time it was received.
+    ///
+    /// Takes stdout like [`stdout`](Self::stdout) and is paced like
+    /// [`pipe_stdout`](Self::pipe_stdout). Lines split across chunks are
+    /// joined, the newline and a trailing `\r` are stripped, and a last line
+    /// without a newline is delivered when stdout ends. A line longer than
+    /// `options.max_line_bytes` is dropped with [`JsonLineError::TooLong`]
+    /// and the stream goes on. If stdout was already taken, the stream
+    /// yields [`JsonLineError::StdoutTaken`] and ends; if the command uses
+    /// [`OutputEncoding::Raw`], [`JsonLineError::RawOutput`]. With
+    /// [`OutputEncoding::Strict`], invalid UTF-8 yields
+    /// [`JsonLineError::InvalidUtf8`] and ends the stream. Local and REST
+    /// runtimes behave the same.
+    pub fn raw_lines_with(
+        &mut self,
+        options: LineOptions,
+    ) -> impl Stream<Item = Result<Timestamped<String>, JsonLineError>> + Send + use<> {
+        lines::raw_lines(self.line_stdout(), options)
+    }
+
+    /// Stream stdout as newline-delimited JSON, with [`LineOptions::default`].
+    ///
+    /// See [`json_lines_with`](Self::json_lines_with).
+    pub fn json_lines<T: DeserializeOwned>(
+        &mut self,
+    ) -> impl Stream<Item = Result<Timestamped<T>, JsonLineError>> + Send + use<T> {
+        self.json_lines_with(LineOptions::default())
+    }
+
+    /// Stream stdout as newline-delimited JSON, one `T` per line.
+    ///
+    /// Lines are read as in [`raw_lines_with`](Self::raw_lines_with); blank
+    /// lines are skipped. A line that doesn't parse as `T` yields
+    /// [`JsonLineError::Parse`] with the line, and the stream goes on.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// # async fn example(litebox: &boxlite::LiteBox) -> Result<(), Box<dyn std::error::Error>> {
+    /// use boxlite::{BoxCommand, Timestamped};
+    /// use futures::StreamExt;
+    ///
+    /// #[derive(serde::Deserialize)]
+    /// struct Event {
+    ///     level: String,
+    ///     msg: String,
+    /// }
+    ///
+    /// let mut execution = litebox.exec(BoxCommand::new("./agent")).await?;
+    /// let mut events = execution.json_lines::<Event>();
+    /// while let Some(event) = events.next().await {
+    ///     match event {
+    ///         Ok(Timestamped { received_at, value }) => {
+    ///             println!("{received_at} [{}] {}", value.level, value.msg)
+    ///         }
+    ///         Err(e) => eprintln!("skipped: {e}"),
+    ///     }
+    /// }
+    /// execution.wait().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn json_lines_with<T: DeserializeOwned>(
+        &mut self,
+        options: LineOptions,
+    ) -> impl Stream<Item = Result<Timestamped<T>, JsonLineError>> + Send + use<T> {
+        lines::json_lines(self.line_stdout(), options)
+    }
+
+    /// Take stdout for the line streams, paced like a pipe. Raw stdout is
+    /// left in place.
+    fn line_stdout(&mut self) -> Result<ExecStdout, JsonLineError> {
+        let stdout = futures::executor::block_on(async {
+            let mut control = self.control.lock().await;
+            match control.stdout.take() {
+                Some(OutputStream::Text { receiver, status }) => {
+                    Ok(ExecStdout { receiver, status })
+                }
+                Some(raw) => {
+                    control.stdout = Some(raw);
+                    Err(JsonLineError::RawOutput)
+                }
+                None => Err(JsonLineError::StdoutTaken),
+            }
+        })?;
+        stdout.receiver.pacing().start();
+        Ok(stdout)
+    }
+
+    /// Copy `source` into stdin on a new task, closing stdin at EOF.
+    ///
+    /// Paced like [`pipe_stdout`](Self::pipe_stdout): `source` is read only
+    /// as fast as the process consumes its input. The task resolves to the
+    /// bytes copied; it fails if stdin was already taken, `source` fails, or
+    /// the process closes its stdin first.
+    pub fn pipe_stdin<R>(&mut self, source: R) -> JoinHandle<io::Result<u64>>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        let stdin = self.stdin();
+        tokio::spawn(async move {
+            let stdin = stdin.ok_or_else(|| pipe::taken("stdin"))?;
+            pipe::pipe_input(source, stdin).await
         })
     }
 
@@ -211,6 +772,9 @@ impl Execution {
     ///
     /// Returns the exit status once the execution finishes. If the result is
     /// already cached, returns immediately. Otherwise, waits for result from channel.
+    ///
+    /// Fails with `Stopped` if the box is stopped or removed before the
+    /// command exits.
     pub async fn wait(&mut self) -> BoxliteResult<ExecResult> {
         let mut completion = self.completion.lock().await;
 
@@ -225,10 +789,12 @@ impl Execution {
             return Ok(status);
         }
 
-        // Await next result
-        let status = completion.result_rx.recv().await.ok_or_else(|| {
-            boxlite_shared::BoxliteError::Internal("Result channel closed".into())
-        })?;
+        // Await next result; the channel closes without one when the box stops
+        let status = match completion.result_rx.recv().await {
+            Some(status) => status,
+            None if self.stopped.is_cancelled() => return Err(box_stopped(&self.id)),
+            None => return Err(BoxliteError::Internal("Result channel closed".into())),
+        };
         completion.cached_result = Some(status.clone());
         Ok(status)
     }
@@ -240,6 +806,7 @@ impl Execution {
 
     /// Send a signal to the execution.
     pub async fn signal(&self, signal: i32) -> BoxliteResult<()> {
+        self.ensure_running()?;
         let mut control = self.control.lock().await;
         control.interface.kill(&self.id, signal).await
     }
@@ -248,6 +815,7 @@ impl Execution {
     ///
     /// Only works for executions started with TTY enabled.
     pub async fn resize_tty(&self, rows: u32, cols: u32) -> BoxliteResult<()> {
+        self.ensure_running()?;
         let mut control = self.control.lock().await;
         control
             .interface
@@ -256,6 +824,13 @@ impl Execution {
     }
 }
 
+fn box_stopped(execution_id: &str) -> BoxliteError {
+    BoxliteError::Stopped(format!(
+        "box was stopped while execution {} was running",
+        execution_id
+    ))
+}
+
 /// Exit status of a process.
 #[derive(Clone, Debug)]
 pub struct ExecResult {
@@ -280,20 +855,38 @@ impl ExecResult {
 
 /// Standard input stream (write-only).
 pub struct ExecStdin {
-    sender: Option<mpsc::UnboundedSender<Vec<u8>>>,
+    sender: Option<PacedSender<Vec<u8>>>,
+    /// Cancelled when the box is stopped or removed.
+    stopped: CancellationToken,
 }
 
 impl ExecStdin {
-    pub(crate) fn new(sender: mpsc::UnboundedSender<Vec<u8>>) -> Self {
+    pub(crate) fn new(sender: PacedSender<Vec<u8>>) -> Self {
         Self {
             sender: Some(sender),
+            stopped: CancellationToken::new(),
         }
     }
 
+    /// Report `Stopped` from writes once `token` (the box's) is cancelled.
+    pub(crate) fn stopped_by(mut self, token: CancellationToken) -> Self {
+        self.stopped = token;
+        self
+    }
+
     /// Write data to stdin.
+    ///
+    /// Fails once stdin is closed, by [`close`](Self::close) or by the
+    /// process closing its end (or exiting); input written up to then is
+    /// delivered. Fails with `Stopped` once the box is stopped or removed.
     pub async fn write(&mut self, data: &[u8]) -> BoxliteResult<()> {
+        if self.stopped.is_cancelled() {
+            return Err(BoxliteError::Stopped(
+                "box was stopped; stdin is closed".to_string(),
+            ));
+        }
         match &self.sender {
-            Some(sender) => sender.send(data.to_vec()).map_err(|_| {
+            Some(sender) => sender.send_now(data.to_vec()).map_err(|_| {
                 boxlite_shared::BoxliteError::Internal("stdin channel closed".to_string())
             }),
             None => Err(boxlite_shared::BoxliteError::Internal(
@@ -308,24 +901,58 @@ impl ExecStdin {
     }
 
     /// Close stdin stream, signaling EOF to the process.
+    ///
+    /// Input already written is delivered first; the process then reads EOF.
+    /// Dropping `ExecStdin` does the same.
     pub fn close(&mut self) {
         self.sender = None;
     }
 
-    /// Check if stdin is closed.
+    /// Check if stdin is closed, locally or because the process stopped
+    /// reading it.
     pub fn is_closed(&self) -> bool {
-        self.sender.is_none()
+        self.sender.as_ref().is_none_or(|sender| sender.is_closed())
+    }
+
+    /// Hold [`send`](Self::send) back while the guest is behind.
+    pub(crate) fn pace(&self) {
+        if let Some(sender) = &self.sender {
+            sender.pacing().start();
+        }
+    }
+
+    /// Write `data`, waiting for room if stdin is paced.
+    pub(crate) async fn send(&mut self, data: Vec<u8>) -> io::Result<()> {
+        match &self.sender {
+            Some(sender) => sender
+                .send(data)
+                .await
+                .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "stdin channel closed")),
+            None => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "stdin already closed",
+            )),
+        }
     }
 }
 
 /// Standard output stream (read-only).
 pub struct ExecStdout {
-    receiver: mpsc::UnboundedReceiver<String>,
+    receiver: PacedReceiver<String>,
+    status: DecodeStatus,
 }
 
 impl ExecStdout {
-    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<String>) -> Self {
-        Self { receiver }
+    #[cfg(test)]
+    pub(crate) fn new(receiver: PacedReceiver<String>, status: DecodeStatus) -> Self {
+        Self { receiver, status }
+    }
+
+    /// The invalid UTF-8 sequence that ended the stream early, for a
+    /// command using [`OutputEncoding::Strict`]. Set by the time the stream
+    /// ends.
+    pub fn decode_error(&self) -> Option<InvalidUtf8> {
+        self.status.error()
     }
 }
 
@@ -339,12 +966,15 @@ impl Stream for ExecStdout {
 
 /// Standard error stream (read-only).
 pub struct ExecStderr {
-    receiver: mpsc::UnboundedReceiver<String>,
+    receiver: PacedReceiver<String>,
+    status: DecodeStatus,
 }
 
 impl ExecStderr {
-    pub(crate) fn new(receiver: mpsc::UnboundedReceiver<String>) -> Self {
-        Self { receiver }
+    /// The invalid UTF-8 sequence that ended the stream early, like
+    /// [`ExecStdout::decode_error`].
+    pub fn decode_error(&self) -> Option<InvalidUtf8> {
+        self.status.error()
     }
 }
 
@@ -355,3 +985,56 @@ impl Stream for ExecStderr {
         self.receiver.poll_recv(cx)
     }
 }
+
+/// Raw stdout or stderr of a command using [`OutputEncoding::Raw`]
+/// (read-only). Yields the chunks as they arrive.
+pub struct ExecOutputBytes {
+    receiver: PacedReceiver<Vec<u8>>,
+}
+
+impl Stream for ExecOutputBytes {
+    type Item = Vec<u8>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_env_edits_keep_call_order() {
+        let cmd = BoxCommand::new("env")
+            .env_prepend_path("PATH", "/a", ":")
+            .env_unset("DEBUG")
+            .env_append_path("PATH", "/b", ":");
+        let keys: Vec<_> = cmd.env_edits.iter().map(EnvEdit::key).collect();
+        assert_eq!(keys, ["PATH", "DEBUG", "PATH"]);
+        assert!(cmd.env.is_none());
+    }
+
+    #[test]
+    fn test_env_set_drops_earlier_edits_of_key() {
+        let cmd = BoxCommand::new("env")
+            .env_prepend_path("PATH", "/a", ":")
+            .env_unset("DEBUG")
+            .env_set("PATH", "/bin")
+            .env_append_path("PATH", "/b", ":");
+        assert_eq!(cmd.env, Some(vec![("PATH".into(), "/bin".into())]));
+        assert_eq!(
+            cmd.env_edits,
+            vec![
+                EnvEdit::Unset {
+                    key: "DEBUG".into()
+                },
+                EnvEdit::Append {
+                    key: "PATH".into(),
+                    value: "/b".into(),
+                    separator: ":".into(),
+                },
+            ]
+        );
+    }
+}
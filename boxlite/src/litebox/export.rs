@@ -1,25 +1,39 @@
 //! Box export to portable archive.
 //!
 //! Creates a `.boxsnap` archive containing flattened disk images,
-//! optionally compressed with zstd, with SHA-256 checksums.
+//! optionally compressed with zstd, with SHA-256 checksums. Seekable
+//! archives also list each member's offset in the manifest.
+//!
+//! The manifest carries each disk's plain SHA-256 and, alongside it, its
+//! tree digest (see `util::checksum`), which can be verified in parallel.
+//!
+//! With the `url-transfer` feature, the archive can go to a URL instead of
+//! a file (see `runtime::url_transfer`).
 
+use std::future::Future;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::Utc;
-use sha2::{Digest, Sha256};
 
 use crate::disk::constants::filenames as disk_filenames;
-use crate::disk::qemu_img;
+use crate::disk::driver::DiskDriver;
 use crate::litebox::snapshot_types::ExportOptions;
 use crate::litebox::state::BoxStatus;
-use crate::runtime::portability::ArchiveManifest;
+use crate::lock::BoxOperation;
+use crate::runtime::disk_space::DiskSpace;
+use crate::runtime::portability::{ArchiveEntry, ArchiveManifest};
+use crate::runtime::seekable::SeekableWriter;
+#[cfg(feature = "url-transfer")]
+use crate::runtime::url_transfer::{ArchiveUrl, TransferOptions, UploadPipe};
+use crate::util::checksum::{self, FileDigests};
 
 use super::LiteBox;
 
 const ARCHIVE_VERSION: u32 = 2;
 const MANIFEST_FILENAME: &str = "manifest.json";
+const TAR_BLOCK: u64 = 512;
 
 impl LiteBox {
     /// Export this box as a portable `.boxsnap` archive.
@@ -29,6 +43,41 @@ impl LiteBox {
     ///
     /// Returns the path to the created archive.
     pub async fn export(&self, dest: &Path, opts: ExportOptions) -> BoxliteResult<PathBuf> {
+        self.exporting(self.do_export(dest, &opts)).await
+    }
+
+    /// Export this box as a `.boxsnap` archive uploaded to `url`.
+    ///
+    /// `https://` (or `http://`) URLs get a PUT streaming the archive while
+    /// it is built, with no local copy. `s3://bucket/key` URLs (`s3`
+    /// feature) go to S3, staging the archive in the runtime's temp
+    /// directory first since S3 needs its length up front. Credentials and
+    /// extra headers come from `transfer`. Fails with `OfflineMode` on an
+    /// offline runtime.
+    #[cfg(feature = "url-transfer")]
+    pub async fn export_to_url(
+        &self,
+        url: &str,
+        opts: ExportOptions,
+        transfer: TransferOptions,
+    ) -> BoxliteResult<()> {
+        let url = ArchiveUrl::parse(url)?;
+        self.inner.runtime.check_online(&url.host())?;
+        self.exporting(self.do_export_to_url(&url, &opts, &transfer))
+            .await
+    }
+
+    /// Run `export` with the box locked and marked as exporting.
+    async fn exporting<T>(
+        &self,
+        export: impl Future<Output = BoxliteResult<T>>,
+    ) -> BoxliteResult<T> {
+        let _lock = self
+            .inner
+            .runtime
+            .lock_box(self.id(), BoxOperation::Export)
+            .await?;
+
         // Verify stopped
         {
             let state = self.inner.state.read();
@@ -51,12 +100,12 @@ impl LiteBox {
                 .save_box(self.inner.id(), &state)?;
         }
 
-        let result = self.do_export(dest, &opts);
+        let result = export.await;
 
-        // Transition back to Stopped
+        // Transition back to Exited
         {
             let mut state = self.inner.state.write();
-            state.force_status(BoxStatus::Stopped);
+            state.force_status(BoxStatus::Exited);
             let _ = self
                 .inner
                 .runtime
@@ -67,10 +116,72 @@ impl LiteBox {
         result
     }
 
-    fn do_export(&self, dest: &Path, opts: &ExportOptions) -> BoxliteResult<PathBuf> {
+    async fn do_export(&self, dest: &Path, opts: &ExportOptions) -> BoxliteResult<PathBuf> {
+        // Determine output path
+        let output_path = if dest.is_dir() {
+            let name = self.name().unwrap_or("box");
+            dest.join(format!("{}.boxsnap", name))
+        } else {
+            dest.to_path_buf()
+        };
+
+        self.build_archive(ArchiveOutput::File(output_path.clone()), opts)
+            .await?;
+
+        tracing::info!(
+            box_id = %self.id(),
+            output = %output_path.display(),
+            compressed = %(opts.compress || opts.seekable),
+            seekable = %opts.seekable,
+            "Exported box to archive"
+        );
+
+        Ok(output_path)
+    }
+
+    #[cfg(feature = "url-transfer")]
+    async fn do_export_to_url(
+        &self,
+        url: &ArchiveUrl,
+        opts: &ExportOptions,
+        transfer: &TransferOptions,
+    ) -> BoxliteResult<()> {
+        if url.streams_uploads() {
+            url.upload(None, transfer, |pipe| {
+                self.build_archive(ArchiveOutput::Upload(pipe), opts)
+            })
+            .await?;
+        } else {
+            let staging =
+                tempfile::tempdir_in(self.inner.runtime.layout.temp_dir()).map_err(|e| {
+                    BoxliteError::Storage(format!("Failed to create temp directory: {}", e))
+                })?;
+            let archive = staging.path().join("box.boxsnap");
+            self.build_archive(ArchiveOutput::File(archive.clone()), opts)
+                .await?;
+            url.upload_file(archive, transfer).await?;
+        }
+
+        tracing::info!(
+            box_id = %self.id(),
+            url = %url.url(),
+            compressed = %(opts.compress || opts.seekable),
+            seekable = %opts.seekable,
+            "Exported box to URL"
+        );
+        Ok(())
+    }
+
+    /// Flatten the box's disks and write them as an archive to `output`.
+    async fn build_archive(
+        &self,
+        output: ArchiveOutput,
+        opts: &ExportOptions,
+    ) -> BoxliteResult<()> {
         let box_home = &self.inner.config.box_home;
-        let container_disk = box_home.join(disk_filenames::CONTAINER_DISK);
-        let guest_disk = box_home.join(disk_filenames::GUEST_ROOTFS_DISK);
+        let driver = self.inner.config.disk_driver.driver();
+        let container_disk = box_home.join(driver.container_disk_name());
+        let guest_disk = box_home.join(driver.guest_disk_name());
 
         if !container_disk.exists() {
             return Err(BoxliteError::Storage(format!(
@@ -79,142 +190,362 @@ impl LiteBox {
             )));
         }
 
-        // Determine output path
-        let output_path = if dest.is_dir() {
-            let name = self.name().unwrap_or("box");
-            dest.join(format!("{}.boxsnap", name))
-        } else {
-            dest.to_path_buf()
-        };
+        // Flattened disks hold at least the data the box's own disks do
+        let space = &self.inner.runtime.disk_space;
+        let temp_root = self.inner.runtime.layout.temp_dir();
+        let disks_bytes = allocated_bytes(&container_disk) + allocated_bytes(&guest_disk);
+        space.ensure(&temp_root, disks_bytes, "export box")?;
 
         // Create temp directory for flattened disks
-        let temp_dir = tempfile::tempdir_in(self.inner.runtime.layout.temp_dir()).map_err(|e| {
+        let temp_dir = tempfile::tempdir_in(&temp_root).map_err(|e| {
             BoxliteError::Storage(format!("Failed to create temp directory: {}", e))
         })?;
 
-        // Flatten COW disks to standalone images
-        let flat_container = temp_dir.path().join(disk_filenames::CONTAINER_DISK);
-        qemu_img::convert(&container_disk, &flat_container)?;
-
-        let flat_guest = if guest_disk.exists() {
-            let flat = temp_dir.path().join(disk_filenames::GUEST_ROOTFS_DISK);
-            qemu_img::convert(&guest_disk, &flat)?;
-            Some(flat)
-        } else {
-            None
-        };
-
-        // Compute checksums
-        let container_disk_checksum = sha256_file(&flat_container)?;
-        let guest_disk_checksum = match flat_guest {
-            Some(ref fg) => sha256_file(fg)?,
-            None => String::new(),
-        };
-
         // Extract image reference from rootfs spec
         let image = match &self.inner.config.options.rootfs {
             crate::runtime::options::RootfsSpec::Image(img) => img.clone(),
             crate::runtime::options::RootfsSpec::RootfsPath(path) => path.clone(),
         };
 
-        // Create manifest
-        let manifest = ArchiveManifest {
-            version: ARCHIVE_VERSION,
+        let job = ExportJob {
+            driver,
+            container_disk,
+            guest_disk: guest_disk.exists().then_some(guest_disk),
+            work_dir: temp_dir.path().to_path_buf(),
+            output,
             box_name: self.inner.config.name.clone(),
             image,
-            guest_disk_checksum,
-            container_disk_checksum,
+            opts: opts.clone(),
+            space: space.clone(),
+        };
+        // Flattening, hashing and compressing take seconds to minutes for
+        // large disks; keep them off the async workers
+        tokio::task::spawn_blocking(move || job.run())
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("export task failed: {}", e)))?
+    }
+}
+
+/// Where an export writes its archive.
+enum ArchiveOutput {
+    File(PathBuf),
+    /// Streamed to an upload while it is built.
+    #[cfg(feature = "url-transfer")]
+    Upload(UploadPipe),
+}
+
+/// Writer of an [`ArchiveOutput`].
+enum ArchiveWriter {
+    File(std::fs::File),
+    #[cfg(feature = "url-transfer")]
+    Upload(UploadPipe),
+}
+
+impl ArchiveWriter {
+    /// Complete the archive; an upload only succeeds once this is called.
+    fn finish(self) -> std::io::Result<()> {
+        match self {
+            Self::File(_) => Ok(()),
+            #[cfg(feature = "url-transfer")]
+            Self::Upload(pipe) => pipe.finish(),
+        }
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Self::File(file) => file.write(buf),
+            #[cfg(feature = "url-transfer")]
+            Self::Upload(pipe) => pipe.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Self::File(file) => file.flush(),
+            #[cfg(feature = "url-transfer")]
+            Self::Upload(pipe) => pipe.flush(),
+        }
+    }
+}
+
+/// The blocking part of an export, from the box's disks to the archive.
+struct ExportJob {
+    driver: &'static dyn DiskDriver,
+    container_disk: PathBuf,
+    guest_disk: Option<PathBuf>,
+    /// Where the flattened disks and the manifest are staged.
+    work_dir: PathBuf,
+    output: ArchiveOutput,
+    box_name: Option<String>,
+    image: String,
+    opts: ExportOptions,
+    space: DiskSpace,
+}
+
+impl ExportJob {
+    fn run(self) -> BoxliteResult<()> {
+        let opts = &self.opts;
+
+        // Flatten disks to standalone qcow2 images, whatever the box's driver
+        let flat_container = self.work_dir.join(disk_filenames::CONTAINER_DISK);
+        self.driver.flatten(&self.container_disk, &flat_container)?;
+
+        // Hash the container disk while the guest disk is being flattened
+        let (container_digests, guest) = std::thread::scope(|scope| {
+            let container = scope.spawn(|| checksum::file_digests(&flat_container));
+            let guest = self.flatten_guest_disk().and_then(|flat| match flat {
+                Some(flat) => checksum::file_digests(&flat).map(|digests| Some((flat, digests))),
+                None => Ok(None),
+            });
+            let container = container.join().map_err(|_| {
+                BoxliteError::Internal("container disk checksum thread panicked".to_string())
+            })?;
+            Ok::<_, BoxliteError>((container?, guest?))
+        })?;
+        let flat_guest = guest.as_ref().map(|(flat, _)| flat.clone());
+
+        // Create manifest
+        let mut manifest = ArchiveManifest {
+            version: ARCHIVE_VERSION,
+            box_name: self.box_name.clone(),
+            image: self.image.clone(),
+            guest_disk_checksum: guest
+                .as_ref()
+                .map_or_else(String::new, |(_, digests)| plain_checksum(digests)),
+            container_disk_checksum: plain_checksum(&container_digests),
+            guest_disk_tree_checksum: guest.as_ref().map(|(_, digests)| tree_checksum(digests)),
+            container_disk_tree_checksum: Some(tree_checksum(&container_digests)),
             exported_at: Utc::now().to_rfc3339(),
+            entries: Vec::new(),
         };
 
-        let manifest_json = serde_json::to_string_pretty(&manifest)
-            .map_err(|e| BoxliteError::Internal(format!("Failed to serialize manifest: {}", e)))?;
-        let manifest_path = temp_dir.path().join(MANIFEST_FILENAME);
+        let manifest_json = if opts.seekable {
+            let mut members = vec![(
+                disk_filenames::CONTAINER_DISK,
+                std::fs::metadata(&flat_container)?.len(),
+            )];
+            if let Some(ref fg) = flat_guest {
+                members.push((
+                    disk_filenames::GUEST_ROOTFS_DISK,
+                    std::fs::metadata(fg)?.len(),
+                ));
+            }
+            manifest_with_index(&mut manifest, &members)?
+        } else {
+            manifest_to_json(&manifest)?
+        };
+        let manifest_path = self.work_dir.join(MANIFEST_FILENAME);
         std::fs::write(&manifest_path, manifest_json)?;
 
+        // Only an uncompressed archive's size is known up front
+        if let ArchiveOutput::File(output_path) = &self.output
+            && !opts.compress
+            && !opts.seekable
+        {
+            let flat_bytes = std::fs::metadata(&flat_container)?.len()
+                + flat_guest
+                    .as_deref()
+                    .map_or(Ok(0), |fg| std::fs::metadata(fg).map(|m| m.len()))?;
+            self.space
+                .ensure(output_path, flat_bytes, "write archive")?;
+        }
+
         // Build archive
-        if opts.compress {
+        if opts.seekable {
+            build_seekable_tar_archive(
+                self.output,
+                &manifest_path,
+                &flat_container,
+                flat_guest.as_deref(),
+                opts.compression_level,
+            )
+        } else if opts.compress {
             build_zstd_tar_archive(
-                &output_path,
+                self.output,
                 &manifest_path,
                 &flat_container,
                 flat_guest.as_deref(),
                 opts.compression_level,
-            )?;
+            )
         } else {
             build_tar_archive(
-                &output_path,
+                self.output,
                 &manifest_path,
                 &flat_container,
                 flat_guest.as_deref(),
-            )?;
+            )
         }
+    }
 
-        tracing::info!(
-            box_id = %self.id(),
-            output = %output_path.display(),
-            compressed = %opts.compress,
-            "Exported box to archive"
-        );
-
-        Ok(output_path)
+    fn flatten_guest_disk(&self) -> BoxliteResult<Option<PathBuf>> {
+        let Some(guest_disk) = &self.guest_disk else {
+            return Ok(None);
+        };
+        let flat = self.work_dir.join(disk_filenames::GUEST_ROOTFS_DISK);
+        self.driver.flatten(guest_disk, &flat)?;
+        Ok(Some(flat))
     }
 }
 
+/// Manifest form of a disk's plain SHA-256.
+fn plain_checksum(digests: &FileDigests) -> String {
+    format!("sha256:{}", digests.sha256)
+}
+
+/// Manifest form of a disk's tree digest (`util::checksum`).
+fn tree_checksum(digests: &FileDigests) -> String {
+    format!("sha256-tree:{}", digests.tree_sha256)
+}
+
+/// Bytes a disk file occupies on its filesystem (0 if missing).
+fn allocated_bytes(path: &Path) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    std::fs::metadata(path).map_or(0, |m| m.blocks() * 512)
+}
+
 /// Build a plain tar archive.
 fn build_tar_archive(
-    output_path: &Path,
+    output: ArchiveOutput,
     manifest_path: &Path,
     container_disk: &Path,
     guest_disk: Option<&Path>,
 ) -> BoxliteResult<()> {
-    let file = std::fs::File::create(output_path).map_err(|e| {
-        BoxliteError::Storage(format!(
-            "Failed to create archive file {}: {}",
-            output_path.display(),
-            e
-        ))
-    })?;
-
-    let mut builder = tar::Builder::new(file);
-    append_archive_files(&mut builder, manifest_path, container_disk, guest_disk)?;
-    builder
-        .finish()
-        .map_err(|e| BoxliteError::Storage(format!("Failed to finalize archive: {}", e)))?;
-
-    Ok(())
+    write_archive(output, |writer| {
+        let mut builder = tar::Builder::new(writer);
+        append_archive_files(&mut builder, manifest_path, container_disk, guest_disk)?;
+        builder
+            .into_inner()
+            .map_err(|e| BoxliteError::Storage(format!("Failed to finalize archive: {}", e)))
+    })
 }
 
 /// Build a zstd-compressed tar archive.
 fn build_zstd_tar_archive(
-    output_path: &Path,
+    output: ArchiveOutput,
     manifest_path: &Path,
     container_disk: &Path,
     guest_disk: Option<&Path>,
     compression_level: i32,
 ) -> BoxliteResult<()> {
-    let file = std::fs::File::create(output_path).map_err(|e| {
-        BoxliteError::Storage(format!(
-            "Failed to create archive file {}: {}",
-            output_path.display(),
-            e
-        ))
-    })?;
-
-    let encoder = zstd::Encoder::new(file, compression_level)
-        .map_err(|e| BoxliteError::Storage(format!("Failed to create zstd encoder: {}", e)))?;
-
-    let mut builder = tar::Builder::new(encoder);
-    append_archive_files(&mut builder, manifest_path, container_disk, guest_disk)?;
-
-    let encoder = builder
-        .into_inner()
-        .map_err(|e| BoxliteError::Storage(format!("Failed to finalize tar: {}", e)))?;
-    encoder
-        .finish()
-        .map_err(|e| BoxliteError::Storage(format!("Failed to finish zstd compression: {}", e)))?;
+    write_archive(output, |writer| {
+        let encoder = zstd::Encoder::new(writer, compression_level)
+            .map_err(|e| BoxliteError::Storage(format!("Failed to create zstd encoder: {}", e)))?;
+
+        let mut builder = tar::Builder::new(encoder);
+        append_archive_files(&mut builder, manifest_path, container_disk, guest_disk)?;
+
+        let encoder = builder
+            .into_inner()
+            .map_err(|e| BoxliteError::Storage(format!("Failed to finalize tar: {}", e)))?;
+        encoder
+            .finish()
+            .map_err(|e| BoxliteError::Storage(format!("Failed to finish zstd compression: {}", e)))
+    })
+}
 
-    Ok(())
+/// Build a zstd-compressed tar archive in the seekable format.
+fn build_seekable_tar_archive(
+    output: ArchiveOutput,
+    manifest_path: &Path,
+    container_disk: &Path,
+    guest_disk: Option<&Path>,
+    compression_level: i32,
+) -> BoxliteResult<()> {
+    write_archive(output, |writer| {
+        let writer = SeekableWriter::new(std::io::BufWriter::new(writer), compression_level);
+        let mut builder = tar::Builder::new(writer);
+        append_archive_files(&mut builder, manifest_path, container_disk, guest_disk)?;
+
+        let writer = builder
+            .into_inner()
+            .map_err(|e| BoxliteError::Storage(format!("Failed to finalize tar: {}", e)))?;
+        writer
+            .finish()
+            .map_err(|e| BoxliteError::Storage(format!("Failed to write seek table: {}", e)))?
+            .into_inner()
+            .map_err(|e| BoxliteError::Storage(format!("Failed to write archive: {}", e)))
+    })
+}
+
+/// Open `output` and fill it with `write`, which hands the writer back
+/// once done. A partial archive file is removed if that fails (e.g. when
+/// the disk fills up); a partial upload is aborted.
+fn write_archive(
+    output: ArchiveOutput,
+    write: impl FnOnce(ArchiveWriter) -> BoxliteResult<ArchiveWriter>,
+) -> BoxliteResult<()> {
+    let (writer, output_path) = match output {
+        ArchiveOutput::File(output_path) => {
+            let file = std::fs::File::create(&output_path).map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to create archive file {}: {}",
+                    output_path.display(),
+                    e
+                ))
+            })?;
+            (ArchiveWriter::File(file), Some(output_path))
+        }
+        #[cfg(feature = "url-transfer")]
+        ArchiveOutput::Upload(pipe) => (ArchiveWriter::Upload(pipe), None),
+    };
+    write(writer)
+        .and_then(|writer| {
+            writer
+                .finish()
+                .map_err(|e| BoxliteError::Storage(format!("Failed to write archive: {}", e)))
+        })
+        .inspect_err(|_| {
+            if let Some(output_path) = &output_path {
+                let _ = std::fs::remove_file(output_path);
+            }
+        })
+}
+
+fn manifest_to_json(manifest: &ArchiveManifest) -> BoxliteResult<String> {
+    serde_json::to_string_pretty(manifest)
+        .map_err(|e| BoxliteError::Internal(format!("Failed to serialize manifest: {}", e)))
+}
+
+/// Serialize `manifest` with the tar offsets of every member, `members`
+/// being the files appended after it.
+///
+/// The offsets depend on the manifest's own padded length, so this
+/// re-serializes until they stop changing.
+fn manifest_with_index(
+    manifest: &mut ArchiveManifest,
+    members: &[(&str, u64)],
+) -> BoxliteResult<String> {
+    let mut json = manifest_to_json(manifest)?;
+    loop {
+        let entries = tar_entries(json.len() as u64, members);
+        if entries == manifest.entries {
+            return Ok(json);
+        }
+        manifest.entries = entries;
+        json = manifest_to_json(manifest)?;
+    }
+}
+
+/// Where `tar::Builder` places each member's data: a 512-byte header per
+/// member, data padded to 512 bytes. Member names are short enough to
+/// never need a long-name header.
+fn tar_entries(manifest_len: u64, members: &[(&str, u64)]) -> Vec<ArchiveEntry> {
+    let mut offset = 0;
+    std::iter::once((MANIFEST_FILENAME, manifest_len))
+        .chain(members.iter().copied())
+        .map(|(path, size)| {
+            let entry = ArchiveEntry {
+                path: path.to_string(),
+                offset: offset + TAR_BLOCK,
+                size,
+            };
+            offset = entry.offset + size.div_ceil(TAR_BLOCK) * TAR_BLOCK;
+            entry
+        })
+        .collect()
 }
 
 /// Append standard files to a tar builder.
@@ -245,33 +576,124 @@ fn append_archive_files<W: Write>(
     Ok(())
 }
 
-/// Compute SHA-256 of a file, returning hex string.
-fn sha256_file(path: &Path) -> BoxliteResult<String> {
-    use std::io::Read;
-
-    let mut file = std::fs::File::open(path).map_err(|e| {
-        BoxliteError::Storage(format!(
-            "Failed to open {} for checksum: {}",
-            path.display(),
-            e
-        ))
-    })?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::portability::extract_member;
 
-    let mut hasher = Sha256::new();
-    let mut buf = [0u8; 64 * 1024];
-    loop {
-        let n = file.read(&mut buf).map_err(|e| {
-            BoxliteError::Storage(format!(
-                "Failed to read {} for checksum: {}",
-                path.display(),
-                e
-            ))
-        })?;
-        if n == 0 {
-            break;
+    fn manifest() -> ArchiveManifest {
+        ArchiveManifest {
+            version: ARCHIVE_VERSION,
+            box_name: Some("web".into()),
+            image: "alpine:latest".into(),
+            guest_disk_checksum: String::new(),
+            container_disk_checksum: "sha256:00".into(),
+            guest_disk_tree_checksum: None,
+            container_disk_tree_checksum: None,
+            exported_at: "2026-01-01T00:00:00Z".into(),
+            entries: Vec::new(),
         }
-        hasher.update(&buf[..n]);
     }
 
-    Ok(format!("sha256:{:x}", hasher.finalize()))
+    #[test]
+    fn test_tree_checksums_are_optional_in_manifest() {
+        let json = manifest_to_json(&manifest()).unwrap();
+        assert!(!json.contains("tree_checksum"));
+
+        let mut with_trees = manifest();
+        with_trees.container_disk_tree_checksum = Some("sha256-tree:01".into());
+        let parsed: ArchiveManifest =
+            serde_json::from_str(&manifest_to_json(&with_trees).unwrap()).unwrap();
+        assert_eq!(parsed.container_disk_checksum, "sha256:00");
+        assert_eq!(
+            parsed.container_disk_tree_checksum.as_deref(),
+            Some("sha256-tree:01")
+        );
+        assert_eq!(parsed.guest_disk_tree_checksum, None);
+    }
+
+    #[test]
+    fn test_index_matches_tar_builder_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let disk = dir.path().join("disk");
+        std::fs::write(&disk, vec![7u8; 1000]).unwrap();
+
+        let mut manifest = manifest();
+        let json =
+            manifest_with_index(&mut manifest, &[(disk_filenames::CONTAINER_DISK, 1000)]).unwrap();
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+        std::fs::write(&manifest_path, &json).unwrap();
+
+        let mut builder = tar::Builder::new(Vec::new());
+        append_archive_files(&mut builder, &manifest_path, &disk, None).unwrap();
+        let tar = builder.into_inner().unwrap();
+
+        let mut archive = tar::Archive::new(tar.as_slice());
+        let actual: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                (
+                    entry.path().unwrap().to_string_lossy().into_owned(),
+                    entry.raw_file_position(),
+                    entry.size(),
+                )
+            })
+            .collect();
+        let indexed: Vec<_> = manifest
+            .entries
+            .iter()
+            .map(|e| (e.path.clone(), e.offset, e.size))
+            .collect();
+        assert_eq!(indexed, actual);
+        assert_eq!(manifest.entries[0].size, json.len() as u64);
+    }
+
+    #[test]
+    fn test_seekable_archive_extracts_single_member() {
+        let dir = tempfile::tempdir().unwrap();
+        let container = dir.path().join("container");
+        let guest = dir.path().join("guest");
+        let container_data: Vec<u8> = (0..6_000_000u32).map(|i| (i % 253) as u8).collect();
+        std::fs::write(&container, &container_data).unwrap();
+        std::fs::write(&guest, b"guest").unwrap();
+
+        let mut manifest = manifest();
+        let json = manifest_with_index(
+            &mut manifest,
+            &[
+                (disk_filenames::CONTAINER_DISK, container_data.len() as u64),
+                (disk_filenames::GUEST_ROOTFS_DISK, 5),
+            ],
+        )
+        .unwrap();
+        let manifest_path = dir.path().join(MANIFEST_FILENAME);
+        std::fs::write(&manifest_path, &json).unwrap();
+
+        let archive = dir.path().join("box.boxsnap");
+        build_seekable_tar_archive(
+            ArchiveOutput::File(archive.clone()),
+            &manifest_path,
+            &container,
+            Some(&guest),
+            3,
+        )
+        .unwrap();
+
+        let out = dir.path().join("out");
+        extract_member(&archive, disk_filenames::GUEST_ROOTFS_DISK, &out).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), b"guest");
+        extract_member(&archive, disk_filenames::CONTAINER_DISK, &out).unwrap();
+        assert_eq!(std::fs::read(&out).unwrap(), container_data);
+
+        // Still a regular tar.zst for import
+        let unpacked = dir.path().join("unpacked");
+        let decoder = zstd::Decoder::new(std::fs::File::open(&archive).unwrap()).unwrap();
+        tar::Archive::new(decoder).unpack(&unpacked).unwrap();
+        assert_eq!(
+            std::fs::read(unpacked.join(MANIFEST_FILENAME)).unwrap(),
+            json.as_bytes()
+        );
+    }
 }
@@ -0,0 +1,11 @@
+//! Logs fetched from inside a running box.
+
+/// Tail of the guest agent's own log, see `LiteBox::guest_agent_log()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GuestAgentLog {
+    /// Log text (invalid UTF-8 replaced).
+    pub text: String,
+    /// Earlier output was left out, by the requested size or because the
+    /// agent's log buffer wrapped.
+    pub truncated: bool,
+}
@@ -8,24 +8,32 @@
 //! Starting (new box):
 //!   1. Filesystem           (create layout)
 //!   2. ContainerRootfs ─┬─  (pull image, create COW disk)
-//!      GuestRootfs     ─┘   (prepare guest, create COW disk)
+//!      GuestRootfs     ─┤   (prepare guest, create COW disk)
+//!      VmmPrepare      ─┘   (sandbox preflight, cgroup, shim copy)
 //!   3. VmmSpawn             (build config + spawn VM)
 //!   4. GuestConnect         (wait for guest ready)
 //!   5. GuestInit            (initialize container)
+//!   6. EnvironmentReport    (record image digest, engine, versions)
 //!
-//! Stopped (restart):
+//! Exited (restart):
 //!   1. Filesystem           (load existing layout)
 //!   2. ContainerRootfs ─┬─  (reuse existing COW disk - preserves user data)
-//!      GuestRootfs     ─┘   (reuse existing COW disk)
+//!      GuestRootfs     ─┤   (reuse existing COW disk)
+//!      VmmPrepare      ─┘   (sandbox preflight, cgroup, shim copy)
 //!   3. VmmSpawn             (build config + spawn NEW VM)
 //!   4. GuestConnect         (wait for guest ready)
 //!   5. GuestInit            (re-initialize container in new VM)
+//!   6. EnvironmentReport    (record a new entry if anything changed)
 //!
 //! Running (reattach):
 //!   1. VmmAttach            (attach to running VM)
 //!   2. GuestConnect         (reconnect to guest)
 //! ```
 //!
+//! Each stage and task runs in an `pipeline_stage` / `pipeline_task`
+//! tracing span,
+//! so per-stage timings show up in traces as well as in `BoxMetrics`.
+//!
 //! `CleanupGuard` provides RAII cleanup on failure.
 
 mod tasks;
@@ -33,12 +41,16 @@ mod types;
 
 pub(crate) use crate::litebox::box_impl::LiveState;
 
+use crate::images::PullProgress;
 use crate::litebox::BoxStatus;
 use crate::litebox::config::BoxConfig;
+use crate::litebox::start_failure::fetch_dmesg_tail;
 use crate::metrics::BoxMetricsStorage;
 use crate::pipeline::{
     BoxedTask, ExecutionPlan, PipelineBuilder, PipelineExecutor, PipelineMetrics, Stage,
 };
+use crate::runtime::create_progress::{CreatePhase, CreateProgress};
+use crate::runtime::options::RootfsSpec;
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use crate::runtime::types::BoxState;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
@@ -46,10 +58,57 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use tasks::{
-    ContainerRootfsTask, FilesystemTask, GuestConnectTask, GuestInitTask, GuestRootfsTask, InitCtx,
-    VmmAttachTask, VmmSpawnTask,
+    ContainerRootfsTask, EnvironmentReportTask, FilesystemTask, GuestConnectTask, GuestInitTask,
+    GuestRootfsTask, InitCtx, VmmAttachTask, VmmPrepareTask, VmmSpawnTask,
 };
-use types::InitPipelineContext;
+use types::{InitPipelineContext, USE_DISK_ROOTFS};
+
+// ============================================================================
+// CREATE-TIME WARMUP
+// ============================================================================
+
+/// Pull the image and build the shared disks a box's first start needs.
+///
+/// Everything prepared here is cached runtime-wide (image store, image disk
+/// cache, bootstrap guest rootfs), so the start pipeline finds it ready and
+/// only creates the per-box COW overlays.
+pub(crate) async fn prepare_shared_rootfs(
+    runtime: &SharedRuntimeImpl,
+    rootfs_spec: &RootfsSpec,
+    progress: &CreateProgress,
+) -> BoxliteResult<()> {
+    if let Some(low) = runtime.disk_space.low_space(runtime.layout.home_dir()) {
+        progress.emit(CreatePhase::LowSpace {
+            available_bytes: low.available_bytes,
+            threshold_bytes: low.threshold_bytes,
+        });
+    }
+
+    progress.emit(CreatePhase::ResolvingImage);
+    let layer_progress = progress.clone();
+    let image = tasks::load_image(
+        runtime,
+        rootfs_spec,
+        Some(Arc::new(move |p: &PullProgress| {
+            layer_progress.emit(CreatePhase::PullingLayer {
+                digest: p.digest.clone(),
+                done: p.downloaded,
+                total: p.total,
+            })
+        })),
+    )
+    .await?;
+
+    progress.emit(CreatePhase::BuildingImageDisk);
+    if USE_DISK_ROOTFS {
+        tasks::prepare_disk_rootfs(&runtime.image_disk_mgr, &image).await?;
+    }
+
+    progress.emit(CreatePhase::InjectingGuest);
+    tasks::shared_guest_rootfs(runtime).await?;
+
+    Ok(())
+}
 
 // ============================================================================
 // EXECUTION PLAN
@@ -58,33 +117,40 @@ use types::InitPipelineContext;
 /// Get execution plan based on BoxStatus.
 fn get_execution_plan(status: BoxStatus) -> ExecutionPlan<InitCtx> {
     let stages: Vec<Stage<BoxedTask<InitCtx>>> = match status {
-        BoxStatus::Configured => vec![
+        BoxStatus::Created => vec![
             // First start: Full pipeline
             // Phase 1: Setup filesystem layout first
             Stage::sequential(vec![Box::new(FilesystemTask)]),
             // Phase 2: Prepare rootfs (now has access to layout for disk paths)
+            // while getting the shim ready to spawn
             Stage::parallel(vec![
                 Box::new(ContainerRootfsTask),
                 Box::new(GuestRootfsTask),
+                Box::new(VmmPrepareTask),
             ]),
             // Phase 3: Build config and spawn VM
             Stage::sequential(vec![Box::new(VmmSpawnTask)]),
             // Phase 4: Connect to guest and initialize container
             Stage::sequential(vec![Box::new(GuestConnectTask)]),
             Stage::sequential(vec![Box::new(GuestInitTask)]),
+            // Phase 5: Record what the box started with
+            Stage::sequential(vec![Box::new(EnvironmentReportTask)]),
         ],
-        BoxStatus::Stopped => vec![
+        BoxStatus::Exited => vec![
             // Restart: Same flow but rootfs tasks reuse existing COW disks
             // (preserves user modifications from previous run)
             Stage::sequential(vec![Box::new(FilesystemTask)]),
             Stage::parallel(vec![
                 Box::new(ContainerRootfsTask),
                 Box::new(GuestRootfsTask),
+                Box::new(VmmPrepareTask),
             ]),
             Stage::sequential(vec![Box::new(VmmSpawnTask)]),
             Stage::sequential(vec![Box::new(GuestConnectTask)]),
             // GuestInit must run - new VM process has fresh guest daemon
             Stage::sequential(vec![Box::new(GuestInitTask)]),
+            // The image tag or boxlite may have changed since the last start
+            Stage::sequential(vec![Box::new(EnvironmentReportTask)]),
         ],
         BoxStatus::Running => vec![
             // Reattach: Attach to existing VM process and connect to guest
@@ -126,6 +192,22 @@ fn box_metrics_from_pipeline(pipeline_metrics: &PipelineMetrics) -> BoxMetricsSt
     metrics
 }
 
+/// A failed [`BoxBuilder::build`].
+pub(crate) struct BuildError {
+    pub(crate) error: BoxliteError,
+    /// Kernel log tail, if the guest agent still answered.
+    pub(crate) dmesg_tail: Vec<String>,
+}
+
+impl From<BoxliteError> for BuildError {
+    fn from(error: BoxliteError) -> Self {
+        Self {
+            error,
+            dmesg_tail: Vec::new(),
+        }
+    }
+}
+
 /// Builds and initializes box components.
 ///
 /// # Example
@@ -146,7 +228,7 @@ impl BoxBuilder {
     ///
     /// The state determines initialization mode:
     /// - `Starting`: normal init (pull image or use rootfs path)
-    /// - `Stopped`: restart (reuse existing rootfs at box_home/rootfs)
+    /// - `Exited`: restart (reuse existing rootfs at box_home/rootfs)
     ///
     /// # Arguments
     ///
@@ -173,8 +255,9 @@ impl BoxBuilder {
     ///
     /// Executes all initialization stages with automatic cleanup on failure.
     /// Returns (LiveState, CleanupGuard) - caller must disarm guard after all
-    /// operations succeed (including DB persist).
-    pub(crate) async fn build(self) -> BoxliteResult<(LiveState, types::CleanupGuard)> {
+    /// operations succeed (including DB persist). On failure the guest kernel
+    /// log tail is fetched first, if the guest agent was reached.
+    pub(crate) async fn build(self) -> Result<(LiveState, types::CleanupGuard), BuildError> {
         use std::time::Instant;
 
         let total_start = Instant::now();
@@ -186,10 +269,19 @@ impl BoxBuilder {
         } = self;
 
         let status = state.status;
-        let reuse_rootfs = status == BoxStatus::Stopped;
+        let reuse_rootfs = status == BoxStatus::Exited;
         let skip_guest_wait = status == BoxStatus::Running;
 
-        let ctx = InitPipelineContext::new(config, runtime.clone(), reuse_rootfs, skip_guest_wait);
+        let network = state.network.clone().unwrap_or_default();
+
+        let mut ctx = InitPipelineContext::new(
+            config,
+            runtime.clone(),
+            reuse_rootfs,
+            skip_guest_wait,
+            network,
+        );
+        ctx.extra_hosts = state.hosts;
         let ctx = Arc::new(Mutex::new(ctx));
 
         // Note: Guard stays armed until caller disarms it after DB persist succeeds.
@@ -197,7 +289,18 @@ impl BoxBuilder {
 
         let plan = get_execution_plan(status);
         let pipeline = PipelineBuilder::from_plan(plan);
-        let pipeline_metrics = PipelineExecutor::execute(pipeline, Arc::clone(&ctx)).await?;
+        let pipeline_metrics = match PipelineExecutor::execute(pipeline, Arc::clone(&ctx)).await {
+            Ok(metrics) => metrics,
+            Err(error) => {
+                // A stage after GuestConnect failed: the agent may still answer
+                let guest_session = ctx.lock().await.guest_session.clone();
+                let dmesg_tail = match guest_session {
+                    Some(session) => fetch_dmesg_tail(&session).await,
+                    None => Vec::new(),
+                };
+                return Err(BuildError { error, dmesg_tail });
+            }
+        };
 
         let mut ctx = ctx.lock().await;
         let total_create_duration_ms = total_start.elapsed().as_millis();
@@ -222,17 +325,16 @@ impl BoxBuilder {
 
         // Get disks from context (for Running, create disk reference directly)
         let (container_disk, guest_disk) = if status == BoxStatus::Running {
-            // Reattach: create disk reference to existing qcow2
-            use crate::disk::DiskFormat;
-            use crate::disk::constants::filenames;
+            // Reattach: create disk reference to the existing disk
+            let driver = ctx.config.disk_driver.driver();
             let disk = crate::disk::Disk::new(
-                ctx.config.box_home.join(filenames::CONTAINER_DISK),
-                DiskFormat::Qcow2,
+                ctx.config.box_home.join(driver.container_disk_name()),
+                driver.format(),
                 true,
             );
             (disk, None)
         } else {
-            // Starting/Stopped: get disks from rootfs tasks
+            // Created/Exited: get disks from rootfs tasks
             let container_disk = ctx
                 .container_disk
                 .take()
@@ -5,13 +5,17 @@
 //! - Overlayfs: Extracts layers for guest-side overlayfs (flexible)
 //!
 //! For restart (reuse_rootfs=true), opens existing COW disk instead of creating new.
+//! The box's disk driver decides whether that disk is a qcow2 overlay or a
+//! reflinked raw copy of the base.
 
 use super::{InitCtx, log_task_error, task_start};
-use crate::disk::{BackingFormat, Disk, DiskFormat, Qcow2Helper};
-use crate::images::{ContainerImageConfig, ImageDiskManager};
+use crate::disk::Disk;
+use crate::disk::driver::DiskDriver;
+use crate::images::{ContainerImageConfig, ImageDiskManager, ImageObject, PullProgressFn};
 use crate::litebox::init::types::{ContainerRootfsPrepResult, USE_DISK_ROOTFS, USE_OVERLAYFS};
 use crate::pipeline::PipelineTask;
 use crate::runtime::layout::BoxFilesystemLayout;
+use crate::runtime::locale;
 use crate::runtime::options::RootfsSpec;
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use async_trait::async_trait;
@@ -27,6 +31,8 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
 
         let (
             rootfs_spec,
+            timezone,
+            locale,
             env,
             runtime,
             layout,
@@ -35,6 +41,7 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
             entrypoint_override,
             cmd_override,
             user_override,
+            driver,
         ) = {
             let ctx = ctx.lock().await;
             let layout = ctx
@@ -43,6 +50,8 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
                 .ok_or_else(|| BoxliteError::Internal("filesystem task must run first".into()))?;
             (
                 ctx.config.options.rootfs.clone(),
+                ctx.config.options.timezone.clone(),
+                ctx.config.options.locale.clone(),
                 ctx.config.options.env.clone(),
                 ctx.runtime.clone(),
                 layout,
@@ -51,14 +60,26 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
                 ctx.config.options.entrypoint.clone(),
                 ctx.config.options.cmd.clone(),
                 ctx.config.options.user.clone(),
+                ctx.config.disk_driver.driver(),
             )
         };
 
-        let (container_image_config, disk) = run_container_rootfs(
+        // `host` follows the host's zone, so it is resolved at every start
+        let time_zone = timezone
+            .as_deref()
+            .map(locale::resolve_timezone)
+            .transpose()
+            .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+        // TZ / LANG defaults go first so the box's own env overrides them
+        let mut env_with_defaults = locale::env_defaults(time_zone.as_ref(), locale.as_deref());
+        env_with_defaults.extend(env);
+
+        let (container_image_config, disk, image) = run_container_rootfs(
             &rootfs_spec,
-            &env,
+            &env_with_defaults,
             &runtime,
             &layout,
+            driver,
             reuse_rootfs,
             disk_size_gb,
             entrypoint_override.as_deref(),
@@ -70,7 +91,9 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
 
         let mut ctx = ctx.lock().await;
         ctx.container_image_config = Some(container_image_config);
+        ctx.time_zone = time_zone;
         ctx.container_disk = Some(disk);
+        ctx.image = Some(image);
 
         Ok(())
     }
@@ -81,19 +104,22 @@ impl PipelineTask<InitCtx> for ContainerRootfsTask {
 }
 
 /// Pull image and prepare rootfs, then create or reuse COW disk.
+///
+/// Also returns the image the container config was resolved from.
 #[allow(clippy::too_many_arguments)]
 async fn run_container_rootfs(
     rootfs_spec: &RootfsSpec,
     env: &[(String, String)],
     runtime: &SharedRuntimeImpl,
     layout: &BoxFilesystemLayout,
+    driver: &dyn DiskDriver,
     reuse_rootfs: bool,
     disk_size_gb: Option<u64>,
     entrypoint_override: Option<&[String]>,
     cmd_override: Option<&[String]>,
     user_override: Option<&str>,
-) -> BoxliteResult<(ContainerImageConfig, Disk)> {
-    let disk_path = layout.disk_path();
+) -> BoxliteResult<(ContainerImageConfig, Disk, ImageObject)> {
+    let disk_path = layout.root().join(driver.container_disk_name());
 
     // For restart, reuse existing COW disk
     if reuse_rootfs {
@@ -109,27 +135,10 @@ async fn run_container_rootfs(
             )));
         }
 
-        let disk = Disk::new(disk_path.clone(), DiskFormat::Qcow2, true);
+        let disk = Disk::new(disk_path.clone(), driver.format(), true);
 
         // Load container config
-        let image = match rootfs_spec {
-            RootfsSpec::Image(r) => pull_image(runtime, r).await?,
-            RootfsSpec::RootfsPath(path) => {
-                let bundle_dir = std::path::Path::new(path);
-
-                if !bundle_dir.exists() {
-                    return Err(BoxliteError::Storage(format!(
-                        "Rootfs path does not exist: {}",
-                        path
-                    )));
-                }
-
-                runtime
-                    .image_manager
-                    .load_from_local(bundle_dir.to_path_buf(), format!("local:{}", path))
-                    .await?
-            }
-        };
+        let image = load_image(runtime, rootfs_spec, None).await?;
         let image_config = image.load_config().await?;
         let mut container_image_config = ContainerImageConfig::from_oci_config(&image_config)?;
         if !env.is_empty() {
@@ -142,28 +151,11 @@ async fn run_container_rootfs(
             user_override,
         );
 
-        return Ok((container_image_config, disk));
+        return Ok((container_image_config, disk, image));
     }
 
     // Fresh start: pull or load image
-    let image = match rootfs_spec {
-        RootfsSpec::Image(r) => pull_image(runtime, r).await?,
-        RootfsSpec::RootfsPath(path) => {
-            let bundle_dir = std::path::Path::new(path);
-
-            if !bundle_dir.exists() {
-                return Err(BoxliteError::Storage(format!(
-                    "Rootfs path does not exist: {}",
-                    path
-                )));
-            }
-
-            runtime
-                .image_manager
-                .load_from_local(bundle_dir.to_path_buf(), format!("local:{}", path))
-                .await?
-        }
-    };
+    let image = load_image(runtime, rootfs_spec, None).await?;
 
     // Prepare rootfs from image
     let rootfs_result = if USE_DISK_ROOTFS {
@@ -189,21 +181,23 @@ async fn run_container_rootfs(
         user_override,
     );
 
-    let disk = create_cow_disk(&rootfs_result, layout, disk_size_gb)?;
+    let disk = create_cow_disk(&rootfs_result, &disk_path, driver, disk_size_gb)?;
 
-    Ok((container_image_config, disk))
+    Ok((container_image_config, disk, image))
 }
 
 /// Create COW disk from base rootfs.
 ///
 /// # Arguments
 /// * `rootfs_result` - Result of rootfs preparation (disk image or layers)
-/// * `layout` - Box filesystem layout for disk paths
+/// * `cow_disk_path` - Where the box's container disk goes
+/// * `driver` - The box's disk driver
 /// * `disk_size_gb` - Optional user-specified disk size in GB. If set, the COW disk
 ///   will have this virtual size (or the base disk size, whichever is larger).
 fn create_cow_disk(
     rootfs_result: &ContainerRootfsPrepResult,
-    layout: &crate::runtime::layout::BoxFilesystemLayout,
+    cow_disk_path: &std::path::Path,
+    driver: &dyn DiskDriver,
     disk_size_gb: Option<u64>,
 ) -> BoxliteResult<Disk> {
     match rootfs_result {
@@ -219,26 +213,15 @@ fn create_cow_disk(
                 *base_disk_size
             };
 
-            let qcow2_helper = Qcow2Helper::new();
-            let cow_disk_path = layout.disk_path();
-            let temp_disk = qcow2_helper.create_cow_child_disk(
-                base_disk_path,
-                BackingFormat::Raw,
-                &cow_disk_path,
-                target_disk_size,
-            )?;
-
-            // Make disk persistent so it survives stop/restart
-            // create_cow_child_disk returns non-persistent disk, but we want to preserve
-            // COW disks across box restarts (only delete on remove)
-            let disk_path = temp_disk.leak(); // Prevent cleanup
-            let disk = Disk::new(disk_path, DiskFormat::Qcow2, true); // persistent=true
+            // Persistent: COW disks survive stop/restart (only deleted on remove)
+            let disk = driver.provision(base_disk_path, cow_disk_path, target_disk_size)?;
 
             tracing::info!(
                 cow_disk = %cow_disk_path.display(),
                 base_disk = %base_disk_path.display(),
                 virtual_size_mb = target_disk_size / (1024 * 1024),
-                "Created container rootfs COW overlay (persistent)"
+                driver = %driver.kind(),
+                "Created container rootfs COW disk (persistent)"
             );
 
             Ok(disk)
@@ -270,17 +253,34 @@ fn apply_user_overrides(
     }
 }
 
-async fn pull_image(
-    runtime: &crate::runtime::SharedRuntimeImpl,
-    image_ref: &str,
-) -> BoxliteResult<crate::images::ImageObject> {
-    // ImageManager has internal locking - direct access
-    runtime.image_manager.pull(image_ref).await
+/// Pull the image or load the local rootfs bundle.
+pub(crate) async fn load_image(
+    runtime: &SharedRuntimeImpl,
+    rootfs_spec: &RootfsSpec,
+    progress: Option<PullProgressFn>,
+) -> BoxliteResult<ImageObject> {
+    match rootfs_spec {
+        // ImageManager has internal locking - direct access
+        RootfsSpec::Image(r) => runtime.image_manager.pull_with_progress(r, progress).await,
+        RootfsSpec::RootfsPath(path) => {
+            let bundle_dir = std::path::Path::new(path);
+
+            if !bundle_dir.exists() {
+                return Err(BoxliteError::Storage(format!(
+                    "Rootfs path does not exist: {}",
+                    path
+                )));
+            }
+
+            runtime
+                .image_manager
+                .load_from_local(bundle_dir.to_path_buf(), format!("local:{}", path))
+                .await
+        }
+    }
 }
 
-async fn prepare_overlayfs_layers(
-    image: &crate::images::ImageObject,
-) -> BoxliteResult<ContainerRootfsPrepResult> {
+async fn prepare_overlayfs_layers(image: &ImageObject) -> BoxliteResult<ContainerRootfsPrepResult> {
     let layer_paths = image.layer_extracted().await?;
 
     if layer_paths.is_empty() {
@@ -319,9 +319,9 @@ async fn prepare_overlayfs_layers(
 ///
 /// Delegates to ImageDiskManager which handles caching, layer merging,
 /// and ext4 creation with staged atomic install.
-async fn prepare_disk_rootfs(
+pub(crate) async fn prepare_disk_rootfs(
     image_disk_mgr: &ImageDiskManager,
-    image: &crate::images::ImageObject,
+    image: &ImageObject,
 ) -> BoxliteResult<ContainerRootfsPrepResult> {
     let disk = image_disk_mgr.get_or_create(image).await?;
 
@@ -0,0 +1,131 @@
+//! Task: Environment report.
+//!
+//! Records what the box started with (image digest, resolved process,
+//! engine, resources, volumes, versions) to the box's environment history.
+//! Runs last, so only successful starts are recorded. Never fails the start:
+//! errors are logged and the box keeps running without a new report.
+
+use super::{InitCtx, task_start};
+use crate::images::{ContainerImageConfig, ImageObject};
+use crate::litebox::config::BoxConfig;
+use crate::litebox::environment::{
+    self, EngineRecord, EnvironmentContent, EnvironmentReport, ImageRecord, ProcessRecord,
+    ResourceRecord, VolumeRecord,
+};
+use crate::litebox::init::types::resolve_user_volumes;
+use crate::pipeline::PipelineTask;
+use crate::runtime::version::VersionInfo;
+use async_trait::async_trait;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+pub struct EnvironmentReportTask;
+
+#[async_trait]
+impl PipelineTask<InitCtx> for EnvironmentReportTask {
+    async fn run(self: Box<Self>, ctx: InitCtx) -> BoxliteResult<()> {
+        let task_name = self.name();
+        let box_id = task_start(&ctx, task_name).await;
+
+        let (config, image, container_image_config, engine) = {
+            let ctx = ctx.lock().await;
+            (
+                ctx.config.clone(),
+                ctx.image.clone(),
+                ctx.container_image_config.clone(),
+                ctx.engine.clone(),
+            )
+        };
+        let (Some(image), Some(container_image_config), Some(engine)) =
+            (image, container_image_config, engine)
+        else {
+            tracing::warn!(box_id = %box_id, "Start outputs missing, skipping environment report");
+            return Ok(());
+        };
+
+        let result = match tokio::task::spawn_blocking(move || {
+            let content = collect(&config, &image, &container_image_config, engine)?;
+            EnvironmentReport::new(content)?.record(&config.box_home)
+        })
+        .await
+        {
+            Ok(result) => result,
+            Err(e) => Err(BoxliteError::Internal(format!(
+                "environment report task panicked: {e}"
+            ))),
+        };
+
+        match result {
+            Ok(true) => tracing::info!(box_id = %box_id, "Recorded new box environment"),
+            Ok(false) => tracing::debug!(box_id = %box_id, "Box environment unchanged"),
+            Err(e) => tracing::warn!(
+                box_id = %box_id,
+                error = %e,
+                "Failed to record environment report"
+            ),
+        }
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "environment_report"
+    }
+}
+
+/// Assemble the report content. Blocking: collects versions and may hash volumes.
+fn collect(
+    config: &BoxConfig,
+    image: &ImageObject,
+    container_image_config: &ContainerImageConfig,
+    engine: EngineRecord,
+) -> BoxliteResult<EnvironmentContent> {
+    let options = &config.options;
+
+    let volumes = resolve_user_volumes(&options.volumes)?
+        .into_iter()
+        .map(|volume| -> BoxliteResult<VolumeRecord> {
+            let content_sha256 = if options.advanced.report_volume_hashes {
+                Some(environment::hash_tree(&volume.host_path).map_err(|e| {
+                    BoxliteError::Storage(format!(
+                        "Failed to hash volume {}: {}",
+                        volume.host_path.display(),
+                        e
+                    ))
+                })?)
+            } else {
+                None
+            };
+            Ok(VolumeRecord {
+                host_path: volume.host_path,
+                guest_path: volume.guest_path,
+                read_only: volume.read_only,
+                content_sha256,
+            })
+        })
+        .collect::<BoxliteResult<Vec<_>>>()?;
+
+    Ok(EnvironmentContent {
+        box_id: config.id.to_string(),
+        image: ImageRecord {
+            reference: image.reference().to_string(),
+            manifest_digest: image.manifest_digest().to_string(),
+            config_digest: image.config_digest().to_string(),
+        },
+        process: ProcessRecord {
+            entrypoint: container_image_config.entrypoint.clone(),
+            cmd: container_image_config.cmd.clone(),
+            env: container_image_config.env.clone(),
+            working_dir: container_image_config.working_dir.clone(),
+            user: container_image_config.user.clone(),
+            entrypoint_script: options.entrypoint_script.clone(),
+        },
+        engine,
+        resources: ResourceRecord {
+            cpus: options.cpus,
+            memory_mib: options.memory_mib,
+            disk_size_gb: options.disk_size_gb,
+            limits: options.advanced.security.resource_limits.clone(),
+        },
+        volumes,
+        versions: VersionInfo::collect(),
+    })
+}
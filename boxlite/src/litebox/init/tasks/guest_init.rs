@@ -5,14 +5,36 @@
 
 use super::{InitCtx, log_task_error, task_start};
 use crate::images::ContainerImageConfig;
+use crate::net::{BoxNetwork, HostEntry};
 use crate::pipeline::PipelineTask;
 use crate::portal::GuestSession;
 use crate::portal::interfaces::{ContainerRootfsInitConfig, GuestInitConfig, NetworkInitConfig};
+use crate::runtime::locale::TimeZone;
+use crate::runtime::options::{NetworkMode, UserNsMode};
 use crate::runtime::types::ContainerID;
 use crate::volumes::{ContainerMount, GuestVolumeManager};
 use async_trait::async_trait;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
+/// First agent protocol version that honors `entrypoint_script`.
+const ENTRYPOINT_SCRIPT_PROTOCOL: u32 = 4;
+
+/// First agent protocol version that honors `capture_core_dumps`.
+const CORE_DUMPS_PROTOCOL: u32 = 6;
+
+/// First agent protocol version that honors `swap_mib`.
+const SWAP_PROTOCOL: u32 = 7;
+
+/// First agent protocol version that honors `timezone`.
+const TIME_ZONE_PROTOCOL: u32 = 9;
+
+/// First agent protocol version that honors `read_only_rootfs` and
+/// `writable_paths`.
+const READ_ONLY_ROOTFS_PROTOCOL: u32 = 10;
+
+/// First agent protocol version that honors `extra_hosts`.
+const HOSTS_PROTOCOL: u32 = 12;
+
 pub struct GuestInitTask;
 
 #[async_trait]
@@ -28,6 +50,16 @@ impl PipelineTask<InitCtx> for GuestInitTask {
             volume_mgr,
             rootfs_init,
             container_mounts,
+            network,
+            userns,
+            init,
+            entrypoint_script,
+            capture_core_dumps,
+            swap_mib,
+            time_zone,
+            read_only_rootfs,
+            writable_paths,
+            extra_hosts,
         ) =
             {
                 let mut ctx = ctx.lock().await;
@@ -55,22 +87,43 @@ impl PipelineTask<InitCtx> for GuestInitTask {
                     volume_mgr,
                     rootfs_init,
                     container_mounts,
+                    (ctx.config.options.network != NetworkMode::None).then(|| ctx.network.clone()),
+                    ctx.config.options.userns.clone(),
+                    ctx.config.options.init,
+                    ctx.config.options.entrypoint_script.clone(),
+                    ctx.config.options.capture_core_dumps,
+                    ctx.config.options.swap_mib.unwrap_or(0),
+                    ctx.time_zone.clone(),
+                    ctx.config.options.read_only_rootfs,
+                    ctx.config.options.writable_paths.clone(),
+                    ctx.extra_hosts.clone(),
                 )
             };
 
-        run_guest_init(
+        let result = run_guest_init(
             guest_session.clone(),
             &container_image_config,
             &container_id,
             &volume_mgr,
             &rootfs_init,
             &container_mounts,
+            network.as_ref(),
+            userns,
+            init,
+            entrypoint_script,
+            capture_core_dumps,
+            swap_mib,
+            time_zone,
+            read_only_rootfs,
+            writable_paths,
+            extra_hosts,
         )
-        .await
-        .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+        .await;
 
         let mut ctx = ctx.lock().await;
+        // Kept on failure too, so the kernel log can go into the diagnostics
         ctx.guest_session = Some(guest_session);
+        result.inspect_err(|e| log_task_error(&box_id, task_name, e))?;
         ctx.volume_mgr = Some(volume_mgr);
         ctx.rootfs_init = Some(rootfs_init);
         ctx.container_mounts = Some(container_mounts);
@@ -84,6 +137,7 @@ impl PipelineTask<InitCtx> for GuestInitTask {
 }
 
 /// Initialize guest and start container.
+#[allow(clippy::too_many_arguments)]
 async fn run_guest_init(
     guest_session: GuestSession,
     container_image_config: &ContainerImageConfig,
@@ -91,6 +145,16 @@ async fn run_guest_init(
     volume_mgr: &GuestVolumeManager,
     rootfs_init: &ContainerRootfsInitConfig,
     container_mounts: &[ContainerMount],
+    network: Option<&BoxNetwork>,
+    userns: Option<UserNsMode>,
+    init: bool,
+    entrypoint_script: Option<String>,
+    capture_core_dumps: bool,
+    swap_mib: u32,
+    time_zone: Option<TimeZone>,
+    read_only_rootfs: bool,
+    writable_paths: Vec<String>,
+    extra_hosts: Vec<HostEntry>,
 ) -> BoxliteResult<()> {
     let container_id_str = container_id.as_str();
 
@@ -99,16 +163,70 @@ async fn run_guest_init(
 
     let guest_init_config = GuestInitConfig {
         volumes: guest_volumes,
-        network: Some(NetworkInitConfig {
-            interface: "eth0".to_string(),
-            ip: Some("192.168.127.2/24".to_string()),
-            gateway: Some("192.168.127.1".to_string()),
+        network: Some(match network {
+            Some(network) => NetworkInitConfig {
+                interface: "eth0".to_string(),
+                ip: Some(network.guest_cidr()),
+                gateway: Some(network.gateway_ip.clone()),
+            },
+            // No network device: only bring up loopback
+            None => NetworkInitConfig {
+                interface: "lo".to_string(),
+                ip: None,
+                gateway: None,
+            },
         }),
     };
 
-    // Step 1: Guest Init (volumes + network)
-    tracing::info!("Sending guest initialization request");
+    // Step 1: Handshake, then Guest Init (volumes + network)
     let mut guest_interface = guest_session.guest().await?;
+    let agent = guest_interface.handshake().await?;
+    tracing::info!(
+        agent_version = %agent.version,
+        protocol_version = agent.protocol_version,
+        "Sending guest initialization request"
+    );
+    // Older agents ignore the script and would run the bare entrypoint
+    if entrypoint_script.is_some() && agent.protocol_version < ENTRYPOINT_SCRIPT_PROTOCOL {
+        return Err(BoxliteError::Unsupported(format!(
+            "Guest agent {} speaks protocol {}; entrypoint_script needs {}",
+            agent.version, agent.protocol_version, ENTRYPOINT_SCRIPT_PROTOCOL
+        )));
+    }
+    if capture_core_dumps && agent.protocol_version < CORE_DUMPS_PROTOCOL {
+        return Err(BoxliteError::Unsupported(format!(
+            "Guest agent {} speaks protocol {}; capture_core_dumps needs {}",
+            agent.version, agent.protocol_version, CORE_DUMPS_PROTOCOL
+        )));
+    }
+    if swap_mib > 0 && agent.protocol_version < SWAP_PROTOCOL {
+        return Err(BoxliteError::Unsupported(format!(
+            "Guest agent {} speaks protocol {}; swap_mib needs {}",
+            agent.version, agent.protocol_version, SWAP_PROTOCOL
+        )));
+    }
+    if time_zone.is_some() && agent.protocol_version < TIME_ZONE_PROTOCOL {
+        return Err(BoxliteError::Unsupported(format!(
+            "Guest agent {} speaks protocol {}; timezone needs {}",
+            agent.version, agent.protocol_version, TIME_ZONE_PROTOCOL
+        )));
+    }
+    // Older agents would leave the rootfs writable and the paths on disk
+    if (read_only_rootfs || !writable_paths.is_empty())
+        && agent.protocol_version < READ_ONLY_ROOTFS_PROTOCOL
+    {
+        return Err(BoxliteError::Unsupported(format!(
+            "Guest agent {} speaks protocol {}; read_only_rootfs and writable_paths need {}",
+            agent.version, agent.protocol_version, READ_ONLY_ROOTFS_PROTOCOL
+        )));
+    }
+    // Hosts entries are a convenience: older agents just go without them
+    if !extra_hosts.is_empty() && agent.protocol_version < HOSTS_PROTOCOL {
+        tracing::debug!(
+            protocol_version = agent.protocol_version,
+            "Guest agent predates extra hosts entries, skipping them"
+        );
+    }
     guest_interface.init(guest_init_config).await?;
     tracing::info!("Guest initialized successfully");
 
@@ -121,6 +239,15 @@ async fn run_guest_init(
             container_image_config.clone(),
             rootfs_init.clone(),
             container_mounts.to_vec(),
+            userns,
+            init,
+            entrypoint_script,
+            capture_core_dumps,
+            swap_mib,
+            time_zone,
+            read_only_rootfs,
+            writable_paths,
+            extra_hosts,
         )
         .await?;
     tracing::info!(container_id = %returned_id, "Container initialized");
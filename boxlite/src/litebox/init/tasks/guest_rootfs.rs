@@ -4,7 +4,8 @@
 //! Then creates or reuses per-box COW overlay disk.
 
 use super::{InitCtx, log_task_error, task_start};
-use crate::disk::{BackingFormat, Disk, DiskFormat, Qcow2Helper};
+use crate::disk::driver::DiskDriver;
+use crate::disk::{Disk, DiskDriverKind};
 use crate::images::ImageDiskManager;
 use crate::pipeline::PipelineTask;
 use crate::runtime::constants::images;
@@ -23,16 +24,21 @@ impl PipelineTask<InitCtx> for GuestRootfsTask {
         let task_name = self.name();
         let box_id = task_start(&ctx, task_name).await;
 
-        let (runtime, layout, reuse_rootfs) = {
+        let (runtime, layout, driver, reuse_rootfs) = {
             let ctx = ctx.lock().await;
             let layout = ctx
                 .layout
                 .clone()
                 .ok_or_else(|| BoxliteError::Internal("filesystem task must run first".into()))?;
-            (ctx.runtime.clone(), layout, ctx.reuse_rootfs)
+            (
+                ctx.runtime.clone(),
+                layout,
+                ctx.config.disk_driver.driver(),
+                ctx.reuse_rootfs,
+            )
         };
 
-        let disk = run_guest_rootfs(&runtime, &layout, reuse_rootfs)
+        let disk = run_guest_rootfs(&runtime, &layout, driver, reuse_rootfs)
             .await
             .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
 
@@ -51,9 +57,20 @@ impl PipelineTask<InitCtx> for GuestRootfsTask {
 async fn run_guest_rootfs(
     runtime: &SharedRuntimeImpl,
     layout: &BoxFilesystemLayout,
+    driver: &dyn DiskDriver,
     reuse_rootfs: bool,
 ) -> BoxliteResult<Option<Disk>> {
-    // First, get or create the shared base guest rootfs
+    let guest_rootfs = shared_guest_rootfs(runtime).await?;
+
+    // Now create or reuse the per-box COW disk
+    let (_updated_guest_rootfs, disk) =
+        create_or_reuse_cow_disk(&guest_rootfs, layout, driver, reuse_rootfs)?;
+
+    Ok(disk)
+}
+
+/// Get or create the bootstrap guest rootfs shared by all boxes.
+pub(crate) async fn shared_guest_rootfs(runtime: &SharedRuntimeImpl) -> BoxliteResult<GuestRootfs> {
     let guest_rootfs = runtime
         .guest_rootfs
         .get_or_try_init(|| async {
@@ -68,6 +85,7 @@ async fn run_guest_rootfs(
                 &runtime.guest_rootfs_mgr,
                 &runtime.image_disk_mgr,
                 &base_image,
+                &runtime.layout.boxes_dir(),
                 env,
             )
             .await?;
@@ -79,20 +97,17 @@ async fn run_guest_rootfs(
         .await?
         .clone();
 
-    // Now create or reuse the per-box COW disk
-    let (_updated_guest_rootfs, disk) =
-        create_or_reuse_cow_disk(&guest_rootfs, layout, reuse_rootfs)?;
-
-    Ok(disk)
+    Ok(guest_rootfs)
 }
 
 /// Create new COW disk or reuse existing one for restart.
 fn create_or_reuse_cow_disk(
     guest_rootfs: &GuestRootfs,
     layout: &BoxFilesystemLayout,
+    driver: &dyn DiskDriver,
     reuse_rootfs: bool,
 ) -> BoxliteResult<(GuestRootfs, Option<Disk>)> {
-    let guest_rootfs_disk_path = layout.guest_rootfs_disk_path();
+    let guest_rootfs_disk_path = layout.root().join(driver.guest_disk_name());
 
     if reuse_rootfs {
         // Restart: reuse existing COW disk
@@ -109,7 +124,7 @@ fn create_or_reuse_cow_disk(
         }
 
         // Open existing disk as persistent
-        let disk = Disk::new(guest_rootfs_disk_path.clone(), DiskFormat::Qcow2, true);
+        let disk = Disk::new(guest_rootfs_disk_path.clone(), driver.format(), true);
 
         // Update guest_rootfs with the COW disk path
         let mut updated = guest_rootfs.clone();
@@ -136,25 +151,21 @@ fn create_or_reuse_cow_disk(
         // a box-local path, eliminating the sandbox dependency on home_dir/rootfs/.
         // Reflink is instant on CoW filesystems (APFS, btrfs, xfs); falls back
         // to the external path on ext4/tmpfs where reflink isn't supported.
-        let effective_base = reflink_rootfs_base(base_disk_path, layout);
-
-        // Create COW child disk
-        let qcow2_helper = Qcow2Helper::new();
-        let temp_disk = qcow2_helper.create_cow_child_disk(
-            &effective_base,
-            BackingFormat::Raw,
-            &guest_rootfs_disk_path,
-            base_size,
-        )?;
+        // Reflinked raw disks have no backing file, so they skip this step.
+        let effective_base = if driver.kind() == DiskDriverKind::Qcow2 {
+            reflink_rootfs_base(base_disk_path, layout)
+        } else {
+            base_disk_path.clone()
+        };
 
-        // Make disk persistent so it survives stop/restart
-        let disk_path_owned = temp_disk.leak();
-        let disk = Disk::new(disk_path_owned, DiskFormat::Qcow2, true);
+        // Create COW disk (persistent, so it survives stop/restart)
+        let disk = driver.provision(&effective_base, &guest_rootfs_disk_path, base_size)?;
 
         tracing::info!(
             cow_disk = %guest_rootfs_disk_path.display(),
             base_disk = %base_disk_path.display(),
-            "Created guest rootfs COW overlay (persistent)"
+            driver = %driver.kind(),
+            "Created guest rootfs COW disk (persistent)"
         );
 
         // Update guest_rootfs with COW disk path
@@ -180,10 +191,11 @@ async fn prepare_guest_rootfs(
     guest_rootfs_mgr: &GuestRootfsManager,
     image_disk_mgr: &ImageDiskManager,
     base_image: &crate::images::ImageObject,
+    boxes_dir: &std::path::Path,
     env: Vec<(String, String)>,
 ) -> BoxliteResult<GuestRootfs> {
     let rootfs_disk = guest_rootfs_mgr
-        .get_or_create(base_image, image_disk_mgr)
+        .get_or_create(base_image, image_disk_mgr, boxes_dir)
         .await?;
 
     let disk_path = rootfs_disk.path().to_path_buf();
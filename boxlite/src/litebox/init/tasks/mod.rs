@@ -3,33 +3,35 @@
 //! ## Dependency Graph
 //!
 //! ```text
-//! Filesystem ─────┐
-//!                 │
-//! ContainerRootfs ┼──→ VmmSpawn ──→ GuestConnect ──→ GuestInit
-//!                 │
-//! GuestRootfs ────┘
+//!              ┌─→ ContainerRootfs ─┐
+//!              │                    │
+//! Filesystem ──┼─→ GuestRootfs ─────┼──→ VmmSpawn ──→ GuestConnect ──→ GuestInit ──→ EnvironmentReport
+//!              │                    │
+//!              └─→ VmmPrepare ──────┘
 //!
 //! Starting (new box):
 //! - Stage 1 (sequential): [Filesystem]
-//! - Stage 2 (parallel):   [ContainerRootfs, GuestRootfs]
-//! - Stage 3 (sequential): [VmmSpawn, GuestConnect, GuestInit]
+//! - Stage 2 (parallel):   [ContainerRootfs, GuestRootfs, VmmPrepare]
+//! - Stage 3 (sequential): [VmmSpawn, GuestConnect, GuestInit, EnvironmentReport]
 //!
-//! Stopped (restart):
+//! Exited (restart):
 //! - Stage 1 (sequential): [Filesystem]
-//! - Stage 2 (parallel):   [ContainerRootfs, GuestRootfs]
-//! - Stage 3 (sequential): [VmmSpawn, GuestConnect]
+//! - Stage 2 (parallel):   [ContainerRootfs, GuestRootfs, VmmPrepare]
+//! - Stage 3 (sequential): [VmmSpawn, GuestConnect, GuestInit, EnvironmentReport]
 //!
 //! Running (reattach):
 //! - Stage 1 (sequential): [VmmAttach, GuestConnect]
 //! ```
 
 mod container_rootfs;
+mod environment_report;
 mod filesystem;
 mod guest_connect;
 mod guest_entrypoint;
 mod guest_init;
 mod guest_rootfs;
 mod vmm_attach;
+mod vmm_prepare;
 mod vmm_spawn;
 
 use super::types::InitPipelineContext;
@@ -51,9 +53,13 @@ fn log_task_error(box_id: &BoxID, task_name: &str, err: &BoxliteError) {
 }
 
 pub use container_rootfs::ContainerRootfsTask;
+pub(crate) use container_rootfs::{load_image, prepare_disk_rootfs};
+pub use environment_report::EnvironmentReportTask;
 pub use filesystem::FilesystemTask;
 pub use guest_connect::GuestConnectTask;
 pub use guest_init::GuestInitTask;
 pub use guest_rootfs::GuestRootfsTask;
+pub(crate) use guest_rootfs::shared_guest_rootfs;
 pub use vmm_attach::VmmAttachTask;
+pub use vmm_prepare::VmmPrepareTask;
 pub use vmm_spawn::VmmSpawnTask;
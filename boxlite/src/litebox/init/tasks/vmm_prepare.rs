@@ -0,0 +1,57 @@
+//! Task: VMM Prepare - Get the shim ready to spawn while the rootfs builds.
+//!
+//! Locates the shim binary and runs the pre-spawn setup that needs only the
+//! box layout: sandbox preflight, cgroup creation and the shim copy into
+//! the box. Runs in parallel with the rootfs tasks; `VmmSpawnTask` picks up
+//! the prepared controller.
+
+use super::vmm_spawn::ENGINE;
+use super::{InitCtx, log_task_error, task_start};
+use crate::pipeline::PipelineTask;
+use crate::util::find_binary;
+use crate::vmm::controller::ShimController;
+use async_trait::async_trait;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+pub struct VmmPrepareTask;
+
+#[async_trait]
+impl PipelineTask<InitCtx> for VmmPrepareTask {
+    async fn run(self: Box<Self>, ctx: InitCtx) -> BoxliteResult<()> {
+        let task_name = self.name();
+        let box_id = task_start(&ctx, task_name).await;
+
+        let (options, layout) = {
+            let ctx = ctx.lock().await;
+            let layout = ctx
+                .layout
+                .clone()
+                .ok_or_else(|| BoxliteError::Internal("filesystem task must run first".into()))?;
+            (ctx.config.options.clone(), layout)
+        };
+
+        let controller_box_id = box_id.clone();
+        let controller = tokio::task::spawn_blocking(move || {
+            let mut controller = ShimController::new(
+                find_binary("boxlite-shim")?,
+                ENGINE,
+                controller_box_id,
+                options,
+                layout,
+            )?;
+            controller.prepare()?;
+            Ok::<_, BoxliteError>(controller)
+        })
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
+        .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
+
+        let mut ctx = ctx.lock().await;
+        ctx.vmm_controller = Some(controller);
+        Ok(())
+    }
+
+    fn name(&self) -> &str {
+        "vmm_prepare"
+    }
+}
@@ -7,13 +7,14 @@ use super::guest_entrypoint::GuestEntrypointBuilder;
 use super::{InitCtx, log_task_error, task_start};
 use crate::disk::DiskFormat;
 use crate::images::ContainerImageConfig;
+use crate::litebox::environment::EngineRecord;
 use crate::litebox::init::types::resolve_user_volumes;
-use crate::net::NetworkBackendConfig;
+use crate::net::{BoxNetwork, NetworkBackendConfig};
 use crate::pipeline::PipelineTask;
 use crate::runtime::constants::{guest_paths, mount_tags};
 use crate::runtime::guest_rootfs::{GuestRootfs, Strategy};
 use crate::runtime::layout::BoxFilesystemLayout;
-use crate::runtime::options::BoxOptions;
+use crate::runtime::options::{BoxOptions, NetworkMode};
 use crate::runtime::rt_impl::SharedRuntimeImpl;
 use crate::runtime::types::{BoxID, ContainerID};
 use crate::util::find_binary;
@@ -26,6 +27,9 @@ use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
+/// Engine the VM subprocess runs on.
+pub(super) const ENGINE: VmmKind = VmmKind::Libkrun;
+
 pub struct VmmSpawnTask;
 
 #[async_trait]
@@ -40,12 +44,15 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
             layout,
             container_image_config,
             container_disk_path,
-            guest_disk_path,
+            container_disk_format,
+            guest_disk,
             container_id,
             runtime,
             reuse_rootfs,
+            network,
+            controller,
         ) = {
-            let ctx = ctx.lock().await;
+            let mut ctx = ctx.lock().await;
             let layout = ctx
                 .layout
                 .clone()
@@ -54,22 +61,28 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
                 .container_image_config
                 .clone()
                 .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?;
-            let container_disk_path = ctx
+            let container_disk = ctx
                 .container_disk
                 .as_ref()
-                .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?
-                .path()
-                .to_path_buf();
-            let guest_disk_path = ctx.guest_disk.as_ref().map(|d| d.path().to_path_buf());
+                .ok_or_else(|| BoxliteError::Internal("rootfs task must run first".into()))?;
+            let container_disk_path = container_disk.path().to_path_buf();
+            let container_disk_format = container_disk.format();
+            let guest_disk = ctx
+                .guest_disk
+                .as_ref()
+                .map(|d| (d.path().to_path_buf(), d.format()));
             (
                 ctx.config.options.clone(),
                 layout,
                 container_image_config,
                 container_disk_path,
-                guest_disk_path,
+                container_disk_format,
+                guest_disk,
                 ctx.config.container.id.clone(),
                 ctx.runtime.clone(),
                 ctx.reuse_rootfs,
+                ctx.network.clone(),
+                ctx.vmm_controller.take(),
             )
         };
 
@@ -80,16 +93,20 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
             &layout,
             &container_image_config,
             &container_disk_path,
-            guest_disk_path.as_deref(),
+            container_disk_format,
+            guest_disk
+                .as_ref()
+                .map(|(path, format)| (path.as_path(), *format)),
             &container_id,
             &runtime,
             reuse_rootfs,
+            &network,
         )
         .await
         .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
 
         // Spawn VM
-        let handler = spawn_vm(&box_id, &instance_spec, &options, &layout)
+        let handler = spawn_vm(&box_id, &instance_spec, &options, &layout, controller)
             .await
             .inspect_err(|e| log_task_error(&box_id, task_name, e))?;
 
@@ -98,6 +115,7 @@ impl PipelineTask<InitCtx> for VmmSpawnTask {
         ctx.volume_mgr = Some(volume_mgr);
         ctx.rootfs_init = Some(rootfs_init);
         ctx.container_mounts = Some(container_mounts);
+        ctx.engine = Some(engine_record(&instance_spec.guest_entrypoint));
         Ok(())
     }
 
@@ -114,10 +132,12 @@ async fn build_config(
     layout: &BoxFilesystemLayout,
     container_image_config: &ContainerImageConfig,
     container_disk_path: &Path,
-    guest_disk_path: Option<&Path>,
+    container_disk_format: DiskFormat,
+    guest_disk: Option<(&Path, DiskFormat)>,
     container_id: &ContainerID,
     runtime: &SharedRuntimeImpl,
     reuse_rootfs: bool,
+    network: &BoxNetwork,
 ) -> BoxliteResult<(
     InstanceSpec,
     GuestVolumeManager,
@@ -140,9 +160,12 @@ async fn build_config(
     // SHARED virtiofs - needed by all strategies
     volume_mgr.add_fs_share(mount_tags::SHARED, layout.shared_dir(), None, false, None);
 
+    // EXECS virtiofs - guest writes detached exec output here
+    volume_mgr.add_fs_share(mount_tags::EXECS, layout.execs_dir(), None, false, None);
+
     // Add container rootfs disk (COW overlay workflow):
     // 1. Base disk: Pre-built ext4 image with container layers merged
-    // 2. COW disk: QCOW2 overlay with copy-on-write semantics
+    // 2. COW disk: QCOW2 overlay or reflinked raw copy, per the box's disk driver
     //    - Inherits formatted ext4 from base (need_format=false)
     //    - May have larger virtual size if disk_size_gb specified
     // 3. Guest mount: Only resize on fresh start, not restart
@@ -151,7 +174,7 @@ async fn build_config(
     let need_resize = options.disk_size_gb.is_some() && !reuse_rootfs;
     let rootfs_device = volume_mgr.add_block_device(
         container_disk_path,
-        DiskFormat::Qcow2,
+        container_disk_format,
         false,
         None,
         false,       // need_format: COW child inherits formatted base
@@ -186,7 +209,7 @@ async fn build_config(
         .ok_or_else(|| BoxliteError::Internal("guest_rootfs not initialized".into()))?
         .clone();
 
-    let guest_rootfs = configure_guest_rootfs(guest_rootfs, guest_disk_path, &mut volume_mgr)?;
+    let guest_rootfs = configure_guest_rootfs(guest_rootfs, guest_disk, &mut volume_mgr)?;
 
     // Build VMM config from volume manager
     let vmm_config = volume_mgr.build_vmm_config();
@@ -196,7 +219,7 @@ async fn build_config(
         build_guest_entrypoint(&transport, &ready_transport, &guest_rootfs, options)?;
 
     // Network configuration
-    let network_config = build_network_config(container_image_config, options, layout);
+    let network_config = build_network_config(container_image_config, options, layout, network);
 
     // Assemble VMM instance spec
     let instance_spec = InstanceSpec {
@@ -206,6 +229,7 @@ async fn build_config(
         // VM resources
         cpus: options.cpus,
         memory_mib: options.memory_mib,
+        priority: options.priority,
         // Filesystem and devices
         fs_shares: vmm_config.fs_shares,
         block_devices: vmm_config.block_devices,
@@ -213,6 +237,7 @@ async fn build_config(
         transport: transport.clone(),
         ready_transport: ready_transport.clone(),
         guest_rootfs,
+        network_mode: options.network,
         network_config,
         network_backend_endpoint: None,
         home_dir: runtime.layout.home_dir().to_path_buf(),
@@ -220,6 +245,8 @@ async fn build_config(
         console_output: Some(layout.console_output_path()),
         exit_file: layout.exit_file_path(),
         detach: options.detach,
+        shutdown_grace_period: options.effective_shutdown_grace_period(),
+        idle_policy: options.idle_policy(),
     };
 
     Ok((instance_spec, volume_mgr, rootfs_init, container_mounts))
@@ -228,16 +255,16 @@ async fn build_config(
 /// Configure guest rootfs with device path from volume manager.
 fn configure_guest_rootfs(
     mut guest_rootfs: GuestRootfs,
-    guest_disk_path: Option<&Path>,
+    guest_disk: Option<(&Path, DiskFormat)>,
     volume_mgr: &mut GuestVolumeManager,
 ) -> BoxliteResult<GuestRootfs> {
-    if let Some(disk_path_input) = guest_disk_path
+    if let Some((disk_path_input, disk_format)) = guest_disk
         && let Strategy::Disk { ref disk_path, .. } = guest_rootfs.strategy
     {
         // Add disk to volume manager (guest rootfs - no format/resize needed)
         let device_path = volume_mgr.add_block_device(
             disk_path_input,
-            DiskFormat::Qcow2,
+            disk_format,
             false,
             None,
             false, // need_format
@@ -290,11 +317,19 @@ fn build_guest_entrypoint(
 }
 
 /// Build network configuration from container image config and options.
+///
+/// Returns None for `NetworkMode::None`: the box gets no network backend.
 fn build_network_config(
     container_image_config: &crate::images::ContainerImageConfig,
     options: &crate::runtime::options::BoxOptions,
     layout: &BoxFilesystemLayout,
+    network: &BoxNetwork,
 ) -> Option<NetworkBackendConfig> {
+    if options.network == NetworkMode::None {
+        tracing::info!("Network mode none: no network backend");
+        return None;
+    }
+
     let mut port_map: HashMap<u16, u16> = HashMap::new();
 
     // Step 1: Collect guest ports that user wants to customize
@@ -325,27 +360,48 @@ fn build_network_config(
             .count()
     );
 
-    // Always return Some - gvproxy provides virtio-net (eth0) even without port mappings
-    Some(NetworkBackendConfig::new(
-        final_mappings,
-        layout.net_backend_socket_path(),
-    ))
+    // gvproxy provides virtio-net (eth0) even without port mappings
+    Some(
+        NetworkBackendConfig::new(final_mappings, layout.net_backend_socket_path())
+            .with_guest(network.clone()),
+    )
 }
 
 /// Spawn VM subprocess and return handler.
+///
+/// Uses the controller prepared by `VmmPrepareTask` when there is one.
 async fn spawn_vm(
     box_id: &BoxID,
     config: &InstanceSpec,
     options: &BoxOptions,
     layout: &BoxFilesystemLayout,
+    controller: Option<ShimController>,
 ) -> BoxliteResult<Box<dyn VmmHandler>> {
-    let mut controller = ShimController::new(
-        find_binary("boxlite-shim")?,
-        VmmKind::Libkrun,
-        box_id.clone(),
-        options.clone(),
-        layout.clone(),
-    )?;
+    let mut controller = match controller {
+        Some(controller) => controller,
+        None => ShimController::new(
+            find_binary("boxlite-shim")?,
+            ENGINE,
+            box_id.clone(),
+            options.clone(),
+            layout.clone(),
+        )?,
+    };
 
     controller.start(config).await
 }
+
+/// What the environment report records about the spawned engine.
+fn engine_record(entrypoint: &Entrypoint) -> EngineRecord {
+    let mut guest_init = vec![entrypoint.executable.clone()];
+    guest_init.extend(entrypoint.args.iter().cloned());
+    EngineRecord {
+        kind: ENGINE,
+        guest_init,
+        guest_env: entrypoint
+            .env
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect(),
+    }
+}
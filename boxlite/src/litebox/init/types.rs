@@ -4,14 +4,17 @@ use crate::BoxID;
 use crate::disk::Disk;
 #[cfg(target_os = "linux")]
 use crate::fs::BindMountHandle;
-use crate::images::ContainerImageConfig;
+use crate::images::{ContainerImageConfig, ImageObject};
 use crate::litebox::config::BoxConfig;
+use crate::litebox::environment::EngineRecord;
+use crate::net::{BoxNetwork, HostEntry};
 use crate::portal::GuestSession;
 use crate::portal::interfaces::ContainerRootfsInitConfig;
 use crate::runtime::layout::BoxFilesystemLayout;
+use crate::runtime::locale::TimeZone;
 use crate::runtime::options::VolumeSpec;
 use crate::runtime::rt_impl::SharedRuntimeImpl;
-use crate::vmm::controller::VmmHandler;
+use crate::vmm::controller::{ShimController, VmmHandler};
 use crate::volumes::{ContainerMount, GuestVolumeManager};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use std::path::PathBuf;
@@ -191,7 +194,7 @@ impl Drop for CleanupGuard {
         // First mark as crashed so remove_box() doesn't fail the active check
         // TODO(@DorianZheng) Check if this is necessary
         if let Ok(mut state) = self.runtime.box_manager.update_box(&self.box_id) {
-            state.mark_stop();
+            state.mark_exited(None);
             let _ = self.runtime.box_manager.save_box(&self.box_id, &state);
         }
         if let Err(e) = self.runtime.box_manager.remove_box(&self.box_id) {
@@ -217,15 +220,27 @@ pub struct InitPipelineContext {
     pub reuse_rootfs: bool,
     /// Skip waiting for guest ready signal (for reattach to running box).
     pub skip_guest_wait: bool,
+    /// Persisted guest network identity (IP/MAC).
+    pub network: BoxNetwork,
+    /// Extra entries for the container's `/etc/hosts`.
+    pub extra_hosts: Vec<HostEntry>,
 
     pub layout: Option<BoxFilesystemLayout>,
     pub container_image_config: Option<ContainerImageConfig>,
+    /// Resolved `BoxOptions::timezone`, placed in the container by guest init.
+    pub time_zone: Option<TimeZone>,
+    /// Image the container config was resolved from.
+    pub image: Option<ImageObject>,
     pub container_disk: Option<Disk>,
     pub guest_disk: Option<Disk>,
     pub volume_mgr: Option<GuestVolumeManager>,
     pub rootfs_init: Option<ContainerRootfsInitConfig>,
     pub container_mounts: Option<Vec<ContainerMount>>,
     pub guest_session: Option<GuestSession>,
+    /// Engine and guest command line the VM was spawned with.
+    pub engine: Option<EngineRecord>,
+    /// Shim controller with the pre-spawn setup done (see `VmmPrepareTask`).
+    pub vmm_controller: Option<ShimController>,
 
     #[cfg(target_os = "linux")]
     pub bind_mount: Option<BindMountHandle>,
@@ -237,6 +252,7 @@ impl InitPipelineContext {
         runtime: SharedRuntimeImpl,
         reuse_rootfs: bool,
         skip_guest_wait: bool,
+        network: BoxNetwork,
     ) -> Self {
         let guard = CleanupGuard::new(runtime.clone(), config.id.clone());
         Self {
@@ -245,14 +261,20 @@ impl InitPipelineContext {
             guard,
             reuse_rootfs,
             skip_guest_wait,
+            network,
+            extra_hosts: Vec::new(),
             layout: None,
             container_image_config: None,
+            time_zone: None,
+            image: None,
             container_disk: None,
             guest_disk: None,
             volume_mgr: None,
             rootfs_init: None,
             container_mounts: None,
             guest_session: None,
+            engine: None,
+            vmm_controller: None,
             #[cfg(target_os = "linux")]
             bind_mount: None,
         }
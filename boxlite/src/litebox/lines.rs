@@ -0,0 +1,353 @@
+//! Line-oriented views of exec stdout (`Execution::raw_lines`,
+//! `Execution::json_lines`).
+//!
+//! Output reaches the host in chunks that split lines. The text is decoded
+//! before it enters the exec streams (see `encoding`); the line streams
+//! then carry a partial line over until its newline arrives. Each line is
+//! stamped with the time its last chunk was received on the host.
+
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use serde::de::DeserializeOwned;
+
+use super::encoding::InvalidUtf8;
+use super::exec::ExecStdout;
+
+/// Default limit on the length of one line (1 MiB).
+pub const DEFAULT_MAX_LINE_BYTES: usize = 1024 * 1024;
+
+/// Options for [`Execution::raw_lines_with`](super::Execution::raw_lines_with)
+/// and [`Execution::json_lines_with`](super::Execution::json_lines_with).
+#[derive(Clone, Debug)]
+pub struct LineOptions {
+    /// Longest line kept, in bytes, without the newline (default: 1 MiB).
+    /// Longer lines are dropped and reported as [`JsonLineError::TooLong`];
+    /// only the limit is ever buffered.
+    pub max_line_bytes: usize,
+}
+
+impl Default for LineOptions {
+    fn default() -> Self {
+        Self {
+            max_line_bytes: DEFAULT_MAX_LINE_BYTES,
+        }
+    }
+}
+
+impl LineOptions {
+    /// Set the line length limit.
+    pub fn max_line_bytes(mut self, bytes: usize) -> Self {
+        self.max_line_bytes = bytes;
+        self
+    }
+}
+
+/// A line, or the value parsed from it, with the time it was received.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Timestamped<T> {
+    /// When the chunk completing the line reached the host.
+    pub received_at: DateTime<Utc>,
+    pub value: T,
+}
+
+/// A line the line streams could not deliver. The stream goes on with the
+/// next line.
+#[derive(Clone, Debug, PartialEq, thiserror::Error)]
+pub enum JsonLineError {
+    /// The line was longer than `LineOptions::max_line_bytes` and dropped.
+    #[error("line of {len} bytes exceeds the {limit} byte limit")]
+    TooLong { len: usize, limit: usize },
+
+    /// The line is not valid JSON for the requested type.
+    #[error("invalid JSON line: {error}")]
+    Parse { line: String, error: String },
+
+    /// Stdout was taken before the stream was created. Ends the stream.
+    #[error("stdout was already taken")]
+    StdoutTaken,
+
+    /// Stdout is not text: the command uses `OutputEncoding::Raw`. Ends the
+    /// stream.
+    #[error("stdout is raw bytes")]
+    RawOutput,
+
+    /// Stdout holds invalid UTF-8 and the command uses
+    /// `OutputEncoding::Strict`. The line holding it is dropped. Ends the
+    /// stream.
+    #[error(transparent)]
+    InvalidUtf8(InvalidUtf8),
+}
+
+type LineResult = Result<Timestamped<String>, JsonLineError>;
+
+/// Splits text chunks into lines, dropping the ones over the limit.
+struct LineSplitter {
+    line: String,
+    limit: usize,
+    /// Length so far of a line being dropped.
+    dropping: Option<usize>,
+}
+
+impl LineSplitter {
+    fn new(limit: usize) -> Self {
+        Self {
+            line: String::new(),
+            limit,
+            dropping: None,
+        }
+    }
+
+    /// Queue the lines `chunk` completes.
+    fn push(&mut self, chunk: &str, at: DateTime<Utc>, out: &mut VecDeque<LineResult>) {
+        let mut rest = chunk;
+        while let Some(newline) = rest.find('\n') {
+            self.append(&rest[..newline]);
+            out.push_back(self.take(at));
+            rest = &rest[newline + 1..];
+        }
+        self.append(rest);
+    }
+
+    /// Queue a last line without a newline.
+    fn finish(&mut self, at: DateTime<Utc>, out: &mut VecDeque<LineResult>) {
+        if !self.line.is_empty() || self.dropping.is_some() {
+            out.push_back(self.take(at));
+        }
+    }
+
+    fn append(&mut self, text: &str) {
+        if let Some(len) = &mut self.dropping {
+            *len += text.len();
+        } else if self.line.len() + text.len() > self.limit {
+            self.dropping = Some(self.line.len() + text.len());
+            self.line = String::new();
+        } else {
+            self.line.push_str(text);
+        }
+    }
+
+    fn take(&mut self, at: DateTime<Utc>) -> LineResult {
+        if let Some(len) = self.dropping.take() {
+            return Err(JsonLineError::TooLong {
+                len,
+                limit: self.limit,
+            });
+        }
+        let mut line = std::mem::take(&mut self.line);
+        if line.ends_with('\r') {
+            line.pop();
+        }
+        Ok(Timestamped {
+            received_at: at,
+            value: line,
+        })
+    }
+}
+
+struct Lines {
+    stdout: Result<ExecStdout, JsonLineError>,
+    splitter: LineSplitter,
+    ready: VecDeque<LineResult>,
+    done: bool,
+}
+
+/// Lines of `stdout`, or just the error why there are none; see
+/// `Execution::raw_lines_with`.
+pub(crate) fn raw_lines(
+    stdout: Result<ExecStdout, JsonLineError>,
+    options: LineOptions,
+) -> impl Stream<Item = LineResult> + Send {
+    let lines = Lines {
+        stdout,
+        splitter: LineSplitter::new(options.max_line_bytes),
+        ready: VecDeque::new(),
+        done: false,
+    };
+    futures::stream::unfold(lines, |mut lines| async move {
+        loop {
+            if let Some(line) = lines.ready.pop_front() {
+                return Some((line, lines));
+            }
+            if lines.done {
+                return None;
+            }
+            let stdout = match &mut lines.stdout {
+                Ok(stdout) => stdout,
+                Err(e) => {
+                    lines.done = true;
+                    lines.ready.push_back(Err(e.clone()));
+                    continue;
+                }
+            };
+            let chunk = stdout.next().await;
+            let at = Utc::now();
+            match (chunk, stdout.decode_error()) {
+                (Some(chunk), _) => lines.splitter.push(&chunk, at, &mut lines.ready),
+                (None, Some(e)) => {
+                    lines.ready.push_back(Err(JsonLineError::InvalidUtf8(e)));
+                    lines.done = true;
+                }
+                (None, None) => {
+                    lines.splitter.finish(at, &mut lines.ready);
+                    lines.done = true;
+                }
+            }
+        }
+    })
+}
+
+/// Non-blank lines of `stdout` parsed as `T`; see `Execution::json_lines_with`.
+pub(crate) fn json_lines<T: DeserializeOwned>(
+    stdout: Result<ExecStdout, JsonLineError>,
+    options: LineOptions,
+) -> impl Stream<Item = Result<Timestamped<T>, JsonLineError>> + Send {
+    raw_lines(stdout, options)
+        .filter(|line| {
+            let blank = matches!(line, Ok(line) if line.value.trim().is_empty());
+            futures::future::ready(!blank)
+        })
+        .map(|line| {
+            let line = line?;
+            match serde_json::from_str(&line.value) {
+                Ok(value) => Ok(Timestamped {
+                    received_at: line.received_at,
+                    value,
+                }),
+                Err(e) => Err(JsonLineError::Parse {
+                    line: line.value,
+                    error: e.to_string(),
+                }),
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::litebox::encoding::{DecodeStatus, OutputEncoding, decode_stream};
+    use crate::litebox::pipe::{self, Pacing};
+
+    fn stdout_of(chunks: &[&str]) -> ExecStdout {
+        let (tx, rx) = pipe::channel::<String>(&Pacing::default());
+        for chunk in chunks {
+            tx.send_now(chunk.to_string()).unwrap();
+        }
+        ExecStdout::new(rx, DecodeStatus::default())
+    }
+
+    /// Stdout decoded from raw `chunks`, as the exec streams are.
+    fn decoded_stdout(chunks: &[&[u8]], encoding: OutputEncoding) -> ExecStdout {
+        let (tx, rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        for chunk in chunks {
+            tx.send_now(chunk.to_vec()).unwrap();
+        }
+        let status = DecodeStatus::default();
+        ExecStdout::new(decode_stream(rx, encoding, status.clone()), status)
+    }
+
+    async fn collect_raw(chunks: &[&str], limit: usize) -> Vec<Result<String, JsonLineError>> {
+        let options = LineOptions::default().max_line_bytes(limit);
+        raw_lines(Ok(stdout_of(chunks)), options)
+            .map(|line| line.map(|line| line.value))
+            .collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_raw_lines_across_chunks() {
+        let lines = collect_raw(&["one\ntw", "o\r\n", "", "thr", "ee"], 1024).await;
+        assert_eq!(
+            lines,
+            [Ok("one".into()), Ok("two".into()), Ok("three".into())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_raw_lines_drop_long_lines() {
+        let lines = collect_raw(&["short\n0123", "456789\nok\n", "0123456789"], 8).await;
+        assert_eq!(
+            lines,
+            [
+                Ok("short".into()),
+                Err(JsonLineError::TooLong { len: 10, limit: 8 }),
+                Ok("ok".into()),
+                Err(JsonLineError::TooLong { len: 10, limit: 8 }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_survive_bad_lines() {
+        #[derive(Debug, PartialEq, serde::Deserialize)]
+        struct Event {
+            n: u32,
+        }
+
+        let stdout = stdout_of(&["{\"n\":1}\n\nnot json\n{\"n\"", ":2}\n"]);
+        let events: Vec<_> = json_lines::<Event>(Ok(stdout), LineOptions::default())
+            .map(|event| event.map(|event| event.value))
+            .collect()
+            .await;
+        assert_eq!(events.len(), 3, "{events:?}");
+        assert_eq!(events[0], Ok(Event { n: 1 }));
+        assert!(
+            matches!(&events[1], Err(JsonLineError::Parse { line, .. }) if line == "not json"),
+            "{events:?}"
+        );
+        assert_eq!(events[2], Ok(Event { n: 2 }));
+    }
+
+    #[tokio::test]
+    async fn test_json_lines_decode_split_utf8() {
+        let line = "{\"msg\":\"日本語 ✓\"}\n".as_bytes();
+        // Every chunk boundary falls inside a multi-byte sequence
+        let chunks: Vec<&[u8]> = line.chunks(2).collect();
+        let stdout = decoded_stdout(&chunks, OutputEncoding::Utf8Lossy);
+
+        let values: Vec<_> = json_lines::<serde_json::Value>(Ok(stdout), LineOptions::default())
+            .collect()
+            .await;
+        assert_eq!(values.len(), 1);
+        assert_eq!(values[0].as_ref().unwrap().value["msg"], "日本語 ✓");
+    }
+
+    #[tokio::test]
+    async fn test_raw_lines_invalid_utf8_by_encoding() {
+        // "ü" is split across chunks; the Latin-1 "é" (0xe9) is invalid
+        let chunks: &[&[u8]] = &[b"gr\xc3", b"\xbcn\ncaf\xe9\n", b"last\n"];
+        let lines = |encoding| {
+            raw_lines(Ok(decoded_stdout(chunks, encoding)), LineOptions::default())
+                .map(|line| line.map(|line| line.value))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            lines(OutputEncoding::Utf8Lossy).await,
+            [
+                Ok("grün".into()),
+                Ok("caf\u{fffd}".into()),
+                Ok("last".into()),
+            ]
+        );
+        assert_eq!(
+            lines(OutputEncoding::Strict).await,
+            [
+                Ok("grün".into()),
+                Err(JsonLineError::InvalidUtf8(InvalidUtf8 {
+                    offset: 9,
+                    bytes: vec![0xe9],
+                })),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_taken_stdout() {
+        let lines: Vec<_> = raw_lines(Err(JsonLineError::StdoutTaken), LineOptions::default())
+            .collect()
+            .await;
+        assert_eq!(lines, [Err(JsonLineError::StdoutTaken)]);
+    }
+}
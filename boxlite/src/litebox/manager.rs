@@ -3,12 +3,14 @@
 //! Pure database access layer for box persistence.
 //! No in-memory cache - queries go directly to database.
 
+use std::collections::HashSet;
 use std::sync::Arc;
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 use crate::db::BoxStore;
 use crate::litebox::config::BoxConfig;
+use crate::net::BoxNetwork;
 use crate::runtime::types::{BoxID, BoxState};
 
 /// State backend for box persistence.
@@ -75,6 +77,35 @@ impl BoxManager {
         Ok(())
     }
 
+    /// Replace box `old_id` with a new box in one transaction.
+    ///
+    /// The new box may take the old one's name; on failure the old box is
+    /// left as it was.
+    pub fn replace_box(
+        &self,
+        old_id: &BoxID,
+        config: &BoxConfig,
+        state: &BoxState,
+    ) -> BoxliteResult<()> {
+        if self.has_box(&config.id)? {
+            return Err(BoxliteError::InvalidState(format!(
+                "box {} already exists",
+                config.id
+            )));
+        }
+
+        self.store.replace(old_id.as_str(), config, state)?;
+
+        tracing::debug!(
+            old_box_id = %old_id,
+            box_id = %config.id,
+            name = ?config.name,
+            "Replaced box in state"
+        );
+
+        Ok(())
+    }
+
     /// Remove a box from the database.
     pub fn remove_box(&self, id: &BoxID) -> BoxliteResult<()> {
         // Check if box exists
@@ -164,6 +195,47 @@ impl BoxManager {
         Ok(())
     }
 
+    /// Persist an updated box configuration.
+    pub fn update_config(&self, config: &BoxConfig) -> BoxliteResult<()> {
+        self.store.update_config(config)?;
+
+        tracing::trace!(box_id = %config.id, "Saved box config to database");
+
+        Ok(())
+    }
+
+    /// Assign the guest network identity for a box, if not assigned yet.
+    ///
+    /// The MAC is derived from the box ID and checked against every MAC
+    /// already persisted, so no two boxes share one. The assignment is saved
+    /// to the database before returning.
+    pub fn assign_network(&self, id: &BoxID, state: &mut BoxState) -> BoxliteResult<BoxNetwork> {
+        if let Some(ref network) = state.network {
+            return Ok(network.clone());
+        }
+
+        let taken: HashSet<String> = self
+            .store
+            .list_all()?
+            .into_iter()
+            .filter(|(config, _)| &config.id != id)
+            .filter_map(|(_, state)| state.network.map(|n| n.guest_mac))
+            .collect();
+
+        let network = BoxNetwork::allocate(id.as_str(), &taken);
+        state.set_network(network.clone());
+        self.save_box(id, state)?;
+
+        tracing::debug!(
+            box_id = %id,
+            guest_ip = %network.guest_ip,
+            guest_mac = %network.guest_mac,
+            "Assigned guest network"
+        );
+
+        Ok(network)
+    }
+
     /// Load box state from the database.
     ///
     /// Returns the latest state from DB.
@@ -233,6 +305,9 @@ mod tests {
             transport: Transport::unix(PathBuf::from("/tmp/test.sock")),
             box_home: PathBuf::from("/tmp/box"),
             ready_socket_path: PathBuf::from("/tmp/ready"),
+            disk_driver: Default::default(),
+            lineage: None,
+            degradations: Vec::new(),
         }
     }
 
@@ -258,7 +333,7 @@ mod tests {
 
         let (retrieved_config, retrieved_state) = manager.box_by_id(&config.id).unwrap().unwrap();
         assert_eq!(retrieved_config.id, config.id);
-        assert_eq!(retrieved_state.status, BoxStatus::Configured);
+        assert_eq!(retrieved_state.status, BoxStatus::Created);
     }
 
     #[test]
@@ -376,7 +451,7 @@ mod tests {
         manager
             .add_box(
                 &create_test_config(TEST_ID_2),
-                &create_test_state(BoxStatus::Stopped),
+                &create_test_state(BoxStatus::Exited),
             )
             .unwrap();
         manager
@@ -423,4 +498,43 @@ mod tests {
         assert_eq!(loaded_state.status, BoxStatus::Running);
         assert_eq!(loaded_state.pid, Some(12345));
     }
+
+    #[test]
+    fn test_assign_network_is_persisted_and_stable() {
+        let store = create_test_store();
+        let manager = BoxManager::new(store);
+        let config = create_test_config(TEST_ID_1);
+        let mut state = BoxState::new();
+        manager.add_box(&config, &state).unwrap();
+
+        let network = manager.assign_network(&config.id, &mut state).unwrap();
+        assert_eq!(state.network.as_ref(), Some(&network));
+
+        // Persisted, and reused on the next assignment
+        let mut loaded = manager.update_box(&config.id).unwrap();
+        assert_eq!(loaded.network.as_ref(), Some(&network));
+        assert_eq!(
+            manager.assign_network(&config.id, &mut loaded).unwrap(),
+            network
+        );
+    }
+
+    #[test]
+    fn test_assign_network_never_reuses_mac() {
+        let store = create_test_store();
+        let manager = BoxManager::new(store);
+        let config_a = create_test_config(TEST_ID_1);
+        let config_b = create_test_config(TEST_ID_2);
+
+        // Box A already holds the MAC that box B would derive first
+        let mut state_a = BoxState::new();
+        state_a.set_network(BoxNetwork::allocate(TEST_ID_2, &HashSet::new()));
+        manager.add_box(&config_a, &state_a).unwrap();
+
+        let mut state_b = BoxState::new();
+        manager.add_box(&config_b, &state_b).unwrap();
+        let network_b = manager.assign_network(&config_b.id, &mut state_b).unwrap();
+
+        assert_ne!(state_a.network.unwrap().guest_mac, network_b.guest_mac);
+    }
 }
@@ -2,37 +2,89 @@
 //!
 //! Provides lazy initialization and execution capabilities for isolated boxes.
 
+mod batch_copy;
 pub(crate) mod box_impl;
+pub(crate) mod capture;
 mod clone;
+mod compact;
 pub(crate) mod config;
 pub mod copy;
+mod core_dump;
 mod crash_report;
+pub(crate) mod encoding;
+mod environment;
 mod exec;
 mod export;
+mod guest_log;
 mod init;
+pub(crate) mod lines;
 mod manager;
+#[cfg(feature = "fuse")]
+mod mount;
+pub(crate) mod output_filter;
+pub(crate) mod pipe;
+mod prepared;
+mod provision;
+mod scratch;
+mod service;
 mod snapshot;
 pub mod snapshot_types;
+mod start_failure;
 mod state;
+mod template;
+mod transfer;
+mod tunnel;
 
-pub use copy::CopyOptions;
+pub use capture::{CapturedOutput, ExecOutputPaths};
+pub use compact::CompactionEstimate;
+pub use copy::{
+    CopyObserver, CopyOptions, CopyOwnership, CopyProgress, normalize_host_path,
+    validate_container_path,
+};
+pub use core_dump::CoreDump;
 pub(crate) use crash_report::CrashReport;
-pub use exec::{BoxCommand, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, ExecutionId};
+pub use encoding::{InvalidUtf8, OutputEncoding};
+pub use environment::{
+    EngineRecord, EnvironmentContent, EnvironmentReport, ImageRecord, ProcessRecord,
+    ResourceRecord, VolumeRecord,
+};
+pub(crate) use exec::EnvEdit;
+pub use exec::{
+    BoxCommand, ExecOutputBytes, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution,
+    ExecutionId,
+};
+pub use guest_log::GuestAgentLog;
+pub(crate) use init::prepare_shared_rootfs;
+pub use lines::{JsonLineError, LineOptions, Timestamped};
 pub(crate) use manager::BoxManager;
+#[cfg(feature = "fuse")]
+pub use mount::MountHandle;
+pub use output_filter::{OutputFilter, Redactor};
+pub use prepared::PreparedExec;
+pub use scratch::{SCRATCH_DIR_ENV, ScratchSpec};
+pub use service::{RestartPolicy, ServiceInfo, ServicePolicy, ServiceSpec, ServiceStatus};
 pub use snapshot::SnapshotHandle;
+pub(crate) use snapshot::{allocated_size, dir_size, find_dependent_disk};
+pub use start_failure::StartFailure;
 pub use state::{BoxState, BoxStatus};
+pub use tunnel::TunnelHandle;
 
 pub(crate) use box_impl::SharedBoxImpl;
 pub(crate) use init::BoxBuilder;
 
+use std::net::{Ipv4Addr, SocketAddr};
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
+use crate::litebox::snapshot_types::SnapshotRetention;
 use crate::metrics::BoxMetrics;
+use crate::runtime::advanced_options::ResourceLimits;
 use crate::runtime::backend::BoxBackend;
+use crate::vmm::ShimExit;
 use crate::{BoxID, BoxInfo};
 use boxlite_shared::errors::BoxliteResult;
-pub use config::BoxConfig;
+pub use config::{BoxConfig, BoxLineage};
 
 /// LiteBox - Handle to a box.
 ///
@@ -57,6 +109,17 @@ impl LiteBox {
         Self { id, name, inner }
     }
 
+    /// Replace the backend with a wrapper around it (e.g. auditing).
+    pub(crate) fn map_backend(
+        self,
+        f: impl FnOnce(Arc<dyn BoxBackend>) -> Arc<dyn BoxBackend>,
+    ) -> Self {
+        Self {
+            inner: f(self.inner),
+            ..self
+        }
+    }
+
     pub fn id(&self) -> &BoxID {
         &self.id
     }
@@ -70,10 +133,17 @@ impl LiteBox {
         self.inner.info()
     }
 
+    /// Guest IP address, stable across restarts.
+    ///
+    /// Returns `None` until the box has been started for the first time.
+    pub fn guest_ip(&self) -> Option<String> {
+        self.info().network.map(|network| network.guest_ip)
+    }
+
     /// Start the box (initialize VM).
     ///
-    /// For Configured boxes: initializes VM for the first time.
-    /// For Stopped boxes: restarts the VM.
+    /// For Created boxes: initializes VM for the first time.
+    /// For Exited boxes: restarts the VM.
     ///
     /// This is idempotent - calling start() on a Running box is a no-op.
     /// Also called implicitly by exec() if the box is not running.
@@ -81,8 +151,22 @@ impl LiteBox {
         self.inner.start().await
     }
 
+    /// Start `command` in the box.
+    ///
+    /// The execution holds its own reference to the box: dropping this
+    /// handle does not affect it. See [`Execution`] for what stopping or
+    /// removing the box does to it.
     pub async fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution> {
-        self.inner.exec(command).await
+        let execution = self.inner.exec(command).await?;
+        Ok(execution.keep_alive(Arc::clone(&self.inner)))
+    }
+
+    /// Register a command template for repeated, low-latency execution.
+    ///
+    /// The guest resolves the template once; each [`PreparedExec::run`] then
+    /// only sends the extra arguments. Starts the box if it is not running.
+    pub async fn prepare_exec(&self, command: BoxCommand) -> BoxliteResult<PreparedExec> {
+        PreparedExec::prepare(Arc::clone(&self.inner), command).await
     }
 
     pub async fn metrics(&self) -> BoxliteResult<BoxMetrics> {
@@ -105,6 +189,197 @@ impl LiteBox {
             .await
     }
 
+    /// Replace the box's resource limits.
+    ///
+    /// The new limits are persisted and apply from the next start. Disk I/O
+    /// limits (`disk_*` fields) also apply immediately to a running box on
+    /// Linux; an error is returned if they could not be applied.
+    pub async fn update_resource_limits(&self, limits: ResourceLimits) -> BoxliteResult<()> {
+        self.inner.update_resource_limits(limits).await
+    }
+
+    /// Replace the box's snapshot retention policy (`None` disables pruning).
+    ///
+    /// The policy is persisted and enforced after every subsequent
+    /// successful snapshot.
+    pub async fn set_snapshot_retention(
+        &self,
+        retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<()> {
+        self.inner.set_snapshot_retention(retention).await
+    }
+
+    /// Diagnostics from the most recent failed `start()`, if any.
+    ///
+    /// Includes the last guest boot phase reached, the console log tail,
+    /// early container stderr and, if the guest agent was reached, the last
+    /// 100 kernel log lines. Cleared by the next successful start.
+    pub fn last_start_failure(&self) -> Option<StartFailure> {
+        self.inner.last_start_failure()
+    }
+
+    /// Last `tail_lines` lines of the guest kernel log (`dmesg`), oldest
+    /// first, e.g. to see OOM kills or filesystem errors inside the VM.
+    ///
+    /// At most 10,000 lines are returned. Only available while the box is
+    /// running; for a stopped box the error points at its console log,
+    /// which holds the kernel output of its last boot.
+    pub async fn guest_dmesg(&self, tail_lines: usize) -> BoxliteResult<Vec<String>> {
+        self.inner.guest_dmesg(tail_lines).await
+    }
+
+    /// Last `tail_bytes` bytes of the guest agent's own log.
+    ///
+    /// The agent keeps its most recent 1 MiB of log output; larger requests
+    /// are capped. Only available while the box is running, like
+    /// [`guest_dmesg`](Self::guest_dmesg).
+    pub async fn guest_agent_log(&self, tail_bytes: usize) -> BoxliteResult<GuestAgentLog> {
+        self.inner.guest_agent_log(tail_bytes).await
+    }
+
+    /// Core dumps captured in the box, oldest first.
+    ///
+    /// Empty unless the box was created with
+    /// `BoxOptions::capture_core_dumps`. Only the newest 5 are kept. Only
+    /// available while the box is running.
+    pub async fn list_core_dumps(&self) -> BoxliteResult<Vec<CoreDump>> {
+        self.inner.list_core_dumps().await
+    }
+
+    /// Copy the core dump `name` (from [`list_core_dumps`](Self::list_core_dumps))
+    /// to `host_dst`, like [`copy_out`](Self::copy_out).
+    pub async fn fetch_core_dump(
+        &self,
+        name: &str,
+        host_dst: impl AsRef<Path>,
+    ) -> BoxliteResult<()> {
+        let container_src = core_dump::container_path(name)?;
+        self.copy_out(container_src, host_dst, CopyOptions::default())
+            .await
+    }
+
+    /// Flush the box's writes to its disk images, e.g. before backing up
+    /// the qcow2 files of a running box.
+    ///
+    /// Runs `sync()` in the guest, then freezes and thaws the container
+    /// filesystem so its journal is checkpointed. Container writes block
+    /// while it is frozen. `freeze_cap` (at most 10 s) bounds the wait for
+    /// the freeze; past it the call fails with `Timeout` and the guest
+    /// thaws as soon as the freeze completes, even if this process is
+    /// gone. A zero cap only runs `sync()`. Fails with `InvalidState` if
+    /// another freeze is in progress or the box is not running.
+    ///
+    /// Returns how long the container filesystem was frozen.
+    pub async fn sync(&self, freeze_cap: Duration) -> BoxliteResult<Duration> {
+        self.inner.sync(freeze_cap).await
+    }
+
+    /// How the shim last exited abnormally (panic, fatal signal or error).
+    ///
+    /// For crashes this carries the shim's recent operations and open fd
+    /// count. Overwritten on the next abnormal exit.
+    pub fn last_exit(&self) -> Option<ShimExit> {
+        self.inner.last_exit()
+    }
+
+    /// Run `BoxOptions::setup_commands` again.
+    ///
+    /// Clears the provisioned marker and provisions the box right away,
+    /// starting it first if it is not running. The failure policy applies
+    /// as on first start.
+    pub async fn reprovision(&self) -> BoxliteResult<()> {
+        self.inner.reprovision().await
+    }
+
+    /// Make this process the owner of a running box.
+    ///
+    /// Afterwards the box stops when this process exits or its last handle
+    /// is dropped, and `runtime.shutdown()` stops it, exactly as for a box
+    /// started here with `detach = false`. Use it to take back control of a
+    /// detached box after a restart. A no-op if this process already owns
+    /// the box; fails with `InvalidState` if the box is not running or
+    /// another live process owns it.
+    pub async fn adopt(&self) -> BoxliteResult<()> {
+        self.inner.adopt().await
+    }
+
+    /// Stop owning a running box, the reverse of [`adopt`](Self::adopt).
+    ///
+    /// Afterwards the box keeps running when this process exits, as if it
+    /// had been started with `detach = true`, and `runtime.shutdown()`
+    /// leaves it alone. A no-op if no live process owns the box; fails with
+    /// `InvalidState` if the box is not running, another live process owns
+    /// it, or it has `auto_remove` (the next runtime would remove it).
+    pub async fn detach(&self) -> BoxliteResult<()> {
+        self.inner.detach().await
+    }
+
+    /// Read the captured output of a detached execution.
+    ///
+    /// `exec_id` is the [`Execution::id`] of a command started with
+    /// [`BoxCommand::detach`]. Works from any process, including after the
+    /// one that started the command has exited, and whether or not the box
+    /// is running. Fails with `NotFound` if nothing was captured.
+    pub async fn exec_output(&self, exec_id: &str) -> BoxliteResult<CapturedOutput> {
+        self.inner.exec_output(exec_id).await
+    }
+
+    /// Environment reports recorded at start, oldest first.
+    ///
+    /// A report is added whenever a start resolves a different environment
+    /// than the previous one (e.g. the image tag moved or boxlite was
+    /// upgraded). Empty if the box never started.
+    pub async fn environment_reports(&self) -> BoxliteResult<Vec<EnvironmentReport>> {
+        self.inner.environment_reports().await
+    }
+
+    /// The most recent environment report, if the box ever started.
+    pub async fn environment_report(&self) -> BoxliteResult<Option<EnvironmentReport>> {
+        Ok(self.inner.environment_reports().await?.pop())
+    }
+
+    /// Run `command` as a supervised service named `name`.
+    ///
+    /// The guest agent runs the service next to the entrypoint and restarts
+    /// it per `policy.restart`. Services are recorded with the box: every
+    /// start runs them in the order they were added, after provisioning,
+    /// and `stop()` stops them in reverse order before the entrypoint, so
+    /// each stops before the services it depends on. `policy.depends_on`
+    /// may only name services added earlier.
+    ///
+    /// Starts the box if it is not running. Each run's output is captured
+    /// like a detached exec's; [`ServiceInfo::execution_id`] names the run
+    /// for [`exec_output`](Self::exec_output). Fails with `AlreadyExists`
+    /// for a taken name, and without recording the service if its first
+    /// run cannot be spawned.
+    pub async fn spawn_service(
+        &self,
+        name: &str,
+        command: BoxCommand,
+        policy: ServicePolicy,
+    ) -> BoxliteResult<()> {
+        self.inner.spawn_service(name, command, policy).await
+    }
+
+    /// Stop service `name` without restarting it: SIGTERM, then SIGKILL
+    /// after 5 seconds. It starts again with the box.
+    pub async fn stop_service(&self, name: &str) -> BoxliteResult<()> {
+        self.inner.stop_service(name).await
+    }
+
+    /// Stop service `name` and remove it from the box.
+    ///
+    /// Fails with `InvalidState` while other services depend on it.
+    pub async fn remove_service(&self, name: &str) -> BoxliteResult<()> {
+        self.inner.remove_service(name).await
+    }
+
+    /// The box's services in the order they were added, with status, PID
+    /// and restart count. All are `Stopped` while the box is not running.
+    pub async fn list_services(&self) -> BoxliteResult<Vec<ServiceInfo>> {
+        self.inner.list_services().await
+    }
+
     /// Get a snapshot handle for snapshot operations.
     pub fn snapshot(&self) -> SnapshotHandle<'_> {
         SnapshotHandle::new(self)
@@ -121,6 +396,26 @@ impl LiteBox {
             .copy_out(container_src.as_ref(), host_dst.as_ref(), opts)
             .await
     }
+
+    /// Forward a TCP port into the box without a port mapping.
+    ///
+    /// Listens on an ephemeral port on `127.0.0.1` and relays each accepted
+    /// connection to `127.0.0.1:guest_port` inside the box through the guest
+    /// agent. The bound address is `handle.local_addr()`; dropping the
+    /// handle closes the listener and its connections.
+    pub async fn tunnel(&self, guest_port: u16) -> BoxliteResult<TunnelHandle> {
+        self.tunnel_on(guest_port, SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+            .await
+    }
+
+    /// Like [`tunnel`](Self::tunnel), listening on `bind` instead.
+    pub async fn tunnel_on(
+        &self,
+        guest_port: u16,
+        bind: SocketAddr,
+    ) -> BoxliteResult<TunnelHandle> {
+        self.inner.tunnel(guest_port, bind).await
+    }
 }
 
 // ============================================================================
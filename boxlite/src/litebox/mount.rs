@@ -0,0 +1,143 @@
+//! Read-only host mounts of a stopped box's filesystem.
+//!
+//! The container disk is read in userspace, qcow2 chain and ext4 alike,
+//! and served over FUSE, so a box's files can be inspected without
+//! starting or exporting it. The mount holds the box's operation lock:
+//! starting, snapshotting, restoring, compacting or removing the box fails
+//! with `Busy` until it is unmounted.
+
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::fs::ext4_fuse::{self, FuseMount};
+use crate::lock::{BoxOperation, OperationLock};
+
+use super::LiteBox;
+
+/// A box filesystem mounted read-only on the host.
+///
+/// Dropping the handle unmounts it and releases the box.
+pub struct MountHandle {
+    mountpoint: PathBuf,
+    mount: FuseMount,
+    unmounted: bool,
+    _lock: OperationLock,
+}
+
+impl MountHandle {
+    /// Where the filesystem is mounted.
+    pub fn mountpoint(&self) -> &Path {
+        &self.mountpoint
+    }
+
+    /// Unmount now, reporting failures such as the mount point being in
+    /// use; the mount stays up and can be unmounted again. Dropping the
+    /// handle unmounts too, but only logs failures.
+    pub fn unmount(&mut self) -> BoxliteResult<()> {
+        if self.unmounted {
+            return Ok(());
+        }
+        self.mount.unmount().map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to unmount {}: {}",
+                self.mountpoint.display(),
+                e
+            ))
+        })?;
+        self.unmounted = true;
+        Ok(())
+    }
+}
+
+impl Drop for MountHandle {
+    fn drop(&mut self) {
+        if self.unmounted {
+            return;
+        }
+        if let Err(e) = self.mount.unmount() {
+            tracing::warn!(
+                mountpoint = %self.mountpoint.display(),
+                error = %e,
+                "Failed to unmount box filesystem"
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for MountHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MountHandle")
+            .field("mountpoint", &self.mountpoint)
+            .finish()
+    }
+}
+
+impl LiteBox {
+    /// Mount the box's container filesystem read-only at `mountpoint`.
+    ///
+    /// The box must be stopped, and must have been started once so that it
+    /// has a disk. While the handle is alive, starting the box or any other
+    /// operation that changes it fails with `BoxliteError::Busy` naming the
+    /// `mount` operation. Writes through the mount fail with `EROFS`.
+    ///
+    /// Only the calling user can access the mount, and file permissions
+    /// inside it are not enforced. Needs FUSE: libfuse on Linux, macFUSE
+    /// on macOS.
+    pub async fn mount_readonly(&self, mountpoint: impl AsRef<Path>) -> BoxliteResult<MountHandle> {
+        let mountpoint = mountpoint.as_ref().to_path_buf();
+        if !mountpoint.is_dir() {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "mount point {} is not a directory",
+                mountpoint.display()
+            )));
+        }
+
+        let lock = self
+            .inner
+            .runtime
+            .lock_box(self.id(), BoxOperation::Mount)
+            .await?;
+
+        {
+            let state = self.inner.state.read();
+            if !state.status.is_stopped() {
+                return Err(BoxliteError::InvalidState(format!(
+                    "box '{}' must be stopped to be mounted (current status: {})",
+                    self.id(),
+                    state.status
+                )));
+            }
+        }
+
+        let disk = self
+            .inner
+            .config
+            .box_home
+            .join(self.inner.config.disk_driver.driver().container_disk_name());
+        if !disk.exists() {
+            return Err(BoxliteError::InvalidState(format!(
+                "box '{}' has no disk yet; start it once before mounting",
+                self.id()
+            )));
+        }
+
+        let fsname = format!("boxlite:{}", self.id());
+        let target = mountpoint.clone();
+        let mount = tokio::task::spawn_blocking(move || ext4_fuse::mount(&disk, &target, fsname))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("mount task failed: {}", e)))??;
+
+        tracing::info!(
+            box_id = %self.id(),
+            mountpoint = %mountpoint.display(),
+            "Mounted box filesystem read-only"
+        );
+        Ok(MountHandle {
+            mountpoint,
+            mount,
+            unmounted: false,
+            _lock: lock,
+        })
+    }
+}
@@ -0,0 +1,420 @@
+//! Host-side filtering of exec output.
+//!
+//! An [`OutputFilter`] transforms stdout/stderr chunks before they reach
+//! `ExecStdout`/`ExecStderr` consumers. [`Redactor`] is the built-in filter:
+//! it replaces known secret values, such as those of environment variables
+//! named in `BoxOptions::redact_env` or `BoxCommand::redact_env`.
+//!
+//! Output arrives in arbitrary chunks, so a secret may be split across two
+//! of them. `Redactor` holds back the tail of a chunk when it could be the
+//! start of a secret and prepends it to the next chunk. The carry-over is
+//! bounded by the length of the longest secret.
+
+use std::fmt;
+use std::sync::Arc;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use super::encoding::{self, DecodeStatus, OutputEncoding, OutputStream};
+use super::exec::BoxCommand;
+use super::pipe::{self, PacedReceiver};
+
+/// Replacement text for redacted values.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Transforms one output stream, chunk by chunk.
+///
+/// Each stream gets its own instance, so implementations may keep state
+/// between chunks (e.g. a carry-over buffer for matches split across them).
+pub trait OutputFilter: Send {
+    /// Transform a chunk. May hold back part of it for the next call.
+    fn filter(&mut self, chunk: &str) -> String;
+
+    /// Release whatever is held back once the stream ends.
+    fn finish(&mut self) -> String {
+        String::new()
+    }
+}
+
+/// Creates a fresh [`OutputFilter`] for each stream of an execution.
+#[derive(Clone)]
+pub(crate) struct OutputFilterFactory(Arc<dyn Fn() -> Box<dyn OutputFilter> + Send + Sync>);
+
+impl OutputFilterFactory {
+    /// Factory cloning `filter` for each stream.
+    pub(crate) fn new<F>(filter: F) -> Self
+    where
+        F: OutputFilter + Clone + Sync + 'static,
+    {
+        Self(Arc::new(move || Box::new(filter.clone())))
+    }
+
+    pub(crate) fn make(&self) -> Box<dyn OutputFilter> {
+        (self.0)()
+    }
+}
+
+impl fmt::Debug for OutputFilterFactory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OutputFilterFactory(..)")
+    }
+}
+
+/// Replaces every occurrence of a set of secret strings with [`REDACTED`].
+///
+/// Overlapping secrets are resolved longest first. Matches split across
+/// chunks are caught; a partial secret left at the end of the stream is
+/// released unredacted by [`OutputFilter::finish`].
+///
+/// # Examples
+///
+/// ```rust
+/// use boxlite::{OutputFilter, Redactor};
+///
+/// let mut redactor = Redactor::new(["hunter2"]);
+/// let mut out = redactor.filter("password: hun");
+/// out += &redactor.filter("ter2\n");
+/// out += &redactor.finish();
+/// assert_eq!(out, "password: [REDACTED]\n");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Redactor {
+    /// Secrets, longest first.
+    secrets: Vec<String>,
+    /// Tail of the previous chunk that may start a secret.
+    carry: String,
+}
+
+impl Redactor {
+    /// Redactor for `secrets`. Empty strings are ignored.
+    pub fn new<I, S>(secrets: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut secrets: Vec<String> = secrets
+            .into_iter()
+            .map(Into::into)
+            .filter(|s| !s.is_empty())
+            .collect();
+        secrets.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        secrets.dedup();
+        Self {
+            secrets,
+            carry: String::new(),
+        }
+    }
+
+    /// Whether there is nothing to redact.
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Redact a complete text in one go.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        let mut redactor = Self {
+            secrets: self.secrets.clone(),
+            carry: String::new(),
+        };
+        let mut out = redactor.filter(text);
+        out.push_str(&redactor.finish());
+        out
+    }
+
+    /// Length of the secret starting at the beginning of `rest`, if any.
+    fn match_at(&self, rest: &str) -> Option<usize> {
+        self.secrets
+            .iter()
+            .find(|secret| rest.starts_with(secret.as_str()))
+            .map(String::len)
+    }
+
+    /// Whether `rest` is a proper prefix of some secret.
+    fn may_start_secret(&self, rest: &str) -> bool {
+        self.secrets
+            .iter()
+            .any(|secret| secret.len() > rest.len() && secret.starts_with(rest))
+    }
+}
+
+impl OutputFilter for Redactor {
+    fn filter(&mut self, chunk: &str) -> String {
+        if self.secrets.is_empty() {
+            return chunk.to_string();
+        }
+
+        let mut buf = std::mem::take(&mut self.carry);
+        buf.push_str(chunk);
+
+        // Only bytes that start a secret need a closer look; first bytes of
+        // UTF-8 strings never occur mid-character, so `start` is a boundary.
+        let mut firsts = [false; 256];
+        for secret in &self.secrets {
+            firsts[secret.as_bytes()[0] as usize] = true;
+        }
+        let mut out = String::with_capacity(buf.len());
+        let mut pos = 0;
+        while let Some(offset) = buf[pos..].bytes().position(|b| firsts[b as usize]) {
+            let start = pos + offset;
+            out.push_str(&buf[pos..start]);
+
+            let rest = &buf[start..];
+            if let Some(len) = self.match_at(rest) {
+                out.push_str(REDACTED);
+                pos = start + len;
+                continue;
+            }
+            if self.may_start_secret(rest) {
+                self.carry = rest.to_string();
+                return out;
+            }
+            let len = rest.chars().next().map_or(1, char::len_utf8);
+            out.push_str(&rest[..len]);
+            pos = start + len;
+        }
+        out.push_str(&buf[pos..]);
+        out
+    }
+
+    fn finish(&mut self) -> String {
+        std::mem::take(&mut self.carry)
+    }
+}
+
+/// Output handling of one execution: decoding, then env redaction, then a
+/// custom filter.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct OutputFilters {
+    encoding: OutputEncoding,
+    redactor: Redactor,
+    custom: Option<OutputFilterFactory>,
+}
+
+impl OutputFilters {
+    /// Filters for `command` in a box with `box_env` and `box_redact_env`.
+    ///
+    /// Redacts the values of every key in either redact list, taken from
+    /// both the box env and the command env.
+    pub(crate) fn for_command(
+        command: &BoxCommand,
+        box_env: &[(String, String)],
+        box_redact_env: &[String],
+    ) -> Self {
+        let keys: Vec<&String> = box_redact_env.iter().chain(&command.redact_env).collect();
+        let command_env = command.env.as_deref().unwrap_or_default();
+        Self {
+            encoding: command.output_encoding,
+            redactor: env_redactor(&keys, box_env.iter().chain(command_env)),
+            custom: command.output_filter.clone(),
+        }
+    }
+
+    /// Filters for output of any command in the box (e.g. capture files).
+    pub(crate) fn for_box(box_env: &[(String, String)], box_redact_env: &[String]) -> Self {
+        let keys: Vec<&String> = box_redact_env.iter().collect();
+        Self {
+            encoding: OutputEncoding::default(),
+            redactor: env_redactor(&keys, box_env),
+            custom: None,
+        }
+    }
+
+    /// Fail with `InvalidArgument` if raw output would have to be filtered.
+    pub(crate) fn validate(&self) -> BoxliteResult<()> {
+        if self.encoding == OutputEncoding::Raw
+            && (!self.redactor.is_empty() || self.custom.is_some())
+        {
+            return Err(BoxliteError::InvalidArgument(
+                "raw output cannot be redacted or filtered; use a text output encoding".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Decode and filter one raw output stream. `status` records where
+    /// strict decoding stopped.
+    pub(crate) fn output(&self, rx: PacedReceiver<Vec<u8>>, status: DecodeStatus) -> OutputStream {
+        if self.encoding == OutputEncoding::Raw {
+            return OutputStream::Raw(rx);
+        }
+        OutputStream::Text {
+            receiver: self.apply(encoding::decode_stream(rx, self.encoding, status.clone())),
+            status,
+        }
+    }
+
+    /// Filter a text stream. Returns `rx` untouched when there is nothing to
+    /// do.
+    fn apply(&self, rx: PacedReceiver<String>) -> PacedReceiver<String> {
+        let rx = if self.redactor.is_empty() {
+            rx
+        } else {
+            filter_stream(rx, Box::new(self.redactor.clone()))
+        };
+        match &self.custom {
+            Some(factory) => filter_stream(rx, factory.make()),
+            None => rx,
+        }
+    }
+
+    /// Redact a complete text with the env redactor.
+    pub(crate) fn redact(&self, text: &str) -> String {
+        if self.redactor.is_empty() {
+            return text.to_string();
+        }
+        self.redactor.redact(text)
+    }
+}
+
+/// Redactor for the values of `keys` found in `env`.
+fn env_redactor<'a>(
+    keys: &[&String],
+    env: impl IntoIterator<Item = &'a (String, String)>,
+) -> Redactor {
+    if keys.is_empty() {
+        return Redactor::default();
+    }
+    Redactor::new(
+        env.into_iter()
+            .filter(|(key, _)| keys.contains(&key))
+            .map(|(_, value)| value.clone()),
+    )
+}
+
+/// Route `rx` through `filter`, returning the receiver of the filtered chunks.
+///
+/// Chunks the filter fully holds back are not forwarded as empty strings.
+/// The filtered stream shares `rx`'s pacing, so a pipe draining it slows
+/// this task and, through it, the source.
+pub(crate) fn filter_stream(
+    mut rx: PacedReceiver<String>,
+    mut filter: Box<dyn OutputFilter>,
+) -> PacedReceiver<String> {
+    let (tx, filtered_rx) = pipe::channel(rx.pacing());
+    tokio::spawn(async move {
+        while let Some(chunk) = rx.recv().await {
+            let out = filter.filter(&chunk);
+            if !out.is_empty() && tx.send(out).await.is_err() {
+                return;
+            }
+        }
+        let rest = filter.finish();
+        if !rest.is_empty() {
+            let _ = tx.send_now(rest);
+        }
+    });
+    filtered_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(redactor: &mut Redactor, chunks: &[&str]) -> String {
+        let mut out: String = chunks.iter().map(|c| redactor.filter(c)).collect();
+        out.push_str(&redactor.finish());
+        out
+    }
+
+    #[test]
+    fn test_redacts_whole_and_repeated_matches() {
+        let mut redactor = Redactor::new(["s3cr3t"]);
+        assert_eq!(
+            run(&mut redactor, &["a s3cr3t b s3cr3t\n"]),
+            "a [REDACTED] b [REDACTED]\n"
+        );
+    }
+
+    #[test]
+    fn test_redacts_match_split_across_chunks() {
+        let mut redactor = Redactor::new(["s3cr3t"]);
+        assert_eq!(
+            run(&mut redactor, &["key=s", "3c", "r3t;"]),
+            "key=[REDACTED];"
+        );
+    }
+
+    #[test]
+    fn test_carry_is_released_when_no_match_follows() {
+        let mut redactor = Redactor::new(["s3cr3t"]);
+        assert_eq!(redactor.filter("abc s3c"), "abc ");
+        assert_eq!(redactor.filter("ond"), "s3cond");
+        assert_eq!(run(&mut redactor, &["tail s3"]), "tail s3");
+    }
+
+    #[test]
+    fn test_longest_secret_wins() {
+        let mut redactor = Redactor::new(["abc", "abcdef"]);
+        assert_eq!(
+            run(&mut redactor, &["xabcdefx abcx"]),
+            "x[REDACTED]x [REDACTED]x"
+        );
+    }
+
+    #[test]
+    fn test_multibyte_text_and_secrets() {
+        let mut redactor = Redactor::new(["pässwörd"]);
+        assert_eq!(run(&mut redactor, &["ü päss", "wörd ü"]), "ü [REDACTED] ü");
+    }
+
+    #[test]
+    fn test_empty_secrets_pass_through() {
+        let mut redactor = Redactor::new([""]);
+        assert!(redactor.is_empty());
+        assert_eq!(run(&mut redactor, &["anything"]), "anything");
+    }
+
+    #[test]
+    fn test_for_command_redacts_box_and_command_env() {
+        let box_env = vec![
+            ("API_KEY".to_string(), "box-key".to_string()),
+            ("PATH".to_string(), "/usr/bin".to_string()),
+        ];
+        let command = BoxCommand::new("env")
+            .env("DB_PASSWORD", "db-pass")
+            .redact_env("DB_PASSWORD");
+        let filters = OutputFilters::for_command(&command, &box_env, &["API_KEY".to_string()]);
+        assert_eq!(
+            filters.redact("API_KEY=box-key DB_PASSWORD=db-pass PATH=/usr/bin"),
+            "API_KEY=[REDACTED] DB_PASSWORD=[REDACTED] PATH=/usr/bin"
+        );
+
+        let filters = OutputFilters::for_box(&box_env, &[]);
+        assert_eq!(filters.redact("box-key"), "box-key");
+    }
+
+    #[test]
+    fn test_raw_output_cannot_be_filtered() {
+        let box_env = vec![("API_KEY".to_string(), "box-key".to_string())];
+        let redacted = ["API_KEY".to_string()];
+        let raw = BoxCommand::new("cat").output_encoding(OutputEncoding::Raw);
+        let filters = OutputFilters::for_command(&raw, &box_env, &[]);
+        assert!(filters.validate().is_ok());
+        let filters = OutputFilters::for_command(&raw, &box_env, &redacted);
+        assert!(matches!(
+            filters.validate(),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+
+        let filtered = raw.output_filter(Redactor::new(["x"]));
+        let filters = OutputFilters::for_command(&filtered, &[], &[]);
+        assert!(filters.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_filter_stream_forwards_filtered_chunks() {
+        let (tx, rx) = pipe::channel(&pipe::Pacing::default());
+        let mut out_rx = filter_stream(rx, Box::new(Redactor::new(["token"])));
+        for chunk in ["a to", "ken b", " to"] {
+            tx.send_now(chunk.to_string()).unwrap();
+        }
+        drop(tx);
+
+        let mut out = String::new();
+        while let Some(chunk) = out_rx.recv().await {
+            assert!(!chunk.is_empty());
+            out.push_str(&chunk);
+        }
+        assert_eq!(out, "a [REDACTED] b to");
+    }
+}
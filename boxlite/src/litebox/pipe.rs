@@ -0,0 +1,292 @@
+//! Paced exec stream channels and the `Execution::pipe_*` copy loops.
+//!
+//! Exec streams are unbounded, so output nobody reads never stalls the
+//! process. A pipe turns on pacing for its stream: every hop between the
+//! guest and the sink (the attach pump, output filters, the pipe itself)
+//! then holds at most [`PIPE_WINDOW`] chunks. A slow sink stops the pump
+//! reading the attach stream, gRPC flow control stops the guest agent, and
+//! the process blocks on its next write. Stdin works the same way in the
+//! other direction.
+
+use std::io;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::task::{Context, Poll, ready};
+
+use futures::{Stream, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::{Notify, mpsc};
+
+use super::exec::ExecStdin;
+
+/// Chunks one hop may hold while its stream is paced.
+pub(crate) const PIPE_WINDOW: usize = 16;
+
+/// Read size for [`pipe_input`].
+const STDIN_CHUNK: usize = 64 * 1024;
+
+/// Whether a stream is paced. Shared by every hop of one stream, so a pipe
+/// at the end turns on pacing all the way to the source.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Pacing(Arc<AtomicBool>);
+
+impl Pacing {
+    pub(crate) fn start(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+
+    fn is_on(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// Occupancy of one hop.
+#[derive(Default)]
+struct Queue {
+    len: AtomicUsize,
+    drained: Notify,
+}
+
+/// Unbounded channel that holds senders back while `pacing` is on and the
+/// channel has [`PIPE_WINDOW`] items queued.
+pub(crate) fn channel<T>(pacing: &Pacing) -> (PacedSender<T>, PacedReceiver<T>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let queue = Arc::new(Queue::default());
+    (
+        PacedSender {
+            tx,
+            queue: Arc::clone(&queue),
+            pacing: pacing.clone(),
+        },
+        PacedReceiver {
+            rx,
+            queue,
+            pacing: pacing.clone(),
+        },
+    )
+}
+
+pub(crate) struct PacedSender<T> {
+    tx: mpsc::UnboundedSender<T>,
+    queue: Arc<Queue>,
+    pacing: Pacing,
+}
+
+impl<T> Clone for PacedSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+            queue: Arc::clone(&self.queue),
+            pacing: self.pacing.clone(),
+        }
+    }
+}
+
+impl<T> PacedSender<T> {
+    /// Queue `item`, first waiting for room if the stream is paced.
+    pub(crate) async fn send(&self, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        loop {
+            let mut drained = pin!(self.queue.drained.notified());
+            drained.as_mut().enable();
+            if !self.pacing.is_on()
+                || self.queue.len.load(Ordering::Acquire) < PIPE_WINDOW
+                || self.tx.is_closed()
+            {
+                break;
+            }
+            drained.await;
+        }
+        self.send_now(item)
+    }
+
+    /// Queue `item` without waiting, for producers that cannot be held back.
+    pub(crate) fn send_now(&self, item: T) -> Result<(), mpsc::error::SendError<T>> {
+        // Count first: the receiver may take the item before `send` returns
+        self.queue.len.fetch_add(1, Ordering::AcqRel);
+        self.tx.send(item).inspect_err(|_| {
+            self.queue.len.fetch_sub(1, Ordering::AcqRel);
+        })
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    pub(crate) fn pacing(&self) -> &Pacing {
+        &self.pacing
+    }
+}
+
+pub(crate) struct PacedReceiver<T> {
+    rx: mpsc::UnboundedReceiver<T>,
+    queue: Arc<Queue>,
+    pacing: Pacing,
+}
+
+impl<T> PacedReceiver<T> {
+    pub(crate) fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let item = ready!(self.rx.poll_recv(cx));
+        if item.is_some() {
+            self.queue.len.fetch_sub(1, Ordering::AcqRel);
+            self.queue.drained.notify_waiters();
+        }
+        Poll::Ready(item)
+    }
+
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        std::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    pub(crate) fn pacing(&self) -> &Pacing {
+        &self.pacing
+    }
+}
+
+impl<T> Drop for PacedReceiver<T> {
+    fn drop(&mut self) {
+        // Close before waking, so waiting senders see the channel closed
+        self.rx.close();
+        self.queue.drained.notify_waiters();
+    }
+}
+
+/// Write every chunk of `output` to `sink`, flushing after each so an
+/// interactive consumer sees output as it arrives. Returns the bytes written.
+pub(crate) async fn pipe_output<S, T, W>(output: S, mut sink: W) -> io::Result<u64>
+where
+    S: Stream<Item = T>,
+    T: AsRef<[u8]>,
+    W: AsyncWrite + Unpin,
+{
+    let mut output = pin!(output);
+    let mut written = 0;
+    while let Some(chunk) = output.next().await {
+        let chunk = chunk.as_ref();
+        sink.write_all(chunk).await?;
+        sink.flush().await?;
+        written += chunk.len() as u64;
+    }
+    Ok(written)
+}
+
+/// Forward `source` to `stdin` until EOF, then close stdin. Returns the
+/// bytes forwarded.
+pub(crate) async fn pipe_input<R>(mut source: R, mut stdin: ExecStdin) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin,
+{
+    stdin.pace();
+    let mut buf = vec![0u8; STDIN_CHUNK];
+    let mut written = 0;
+    loop {
+        let n = source.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        stdin.send(buf[..n].to_vec()).await?;
+        written += n as u64;
+    }
+    stdin.close();
+    Ok(written)
+}
+
+/// Error for a pipe whose stream was already taken.
+pub(crate) fn taken(stream: &str) -> io::Error {
+    io::Error::other(format!("{stream} was already taken"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_unpaced_sender_never_waits() {
+        let (tx, mut rx) = channel(&Pacing::default());
+        for i in 0..PIPE_WINDOW * 4 {
+            tx.send(i).await.unwrap();
+        }
+        drop(tx);
+
+        let mut received = 0;
+        while rx.recv().await.is_some() {
+            received += 1;
+        }
+        assert_eq!(received, PIPE_WINDOW * 4);
+    }
+
+    #[tokio::test]
+    async fn test_paced_sender_waits_for_room() {
+        let pacing = Pacing::default();
+        pacing.start();
+        let (tx, mut rx) = channel(&pacing);
+        for i in 0..PIPE_WINDOW {
+            tx.send(i).await.unwrap();
+        }
+
+        let blocked = tokio::time::timeout(Duration::from_millis(50), tx.send(PIPE_WINDOW)).await;
+        assert!(
+            blocked.is_err(),
+            "send should wait while the window is full"
+        );
+
+        assert_eq!(rx.recv().await, Some(0));
+        tokio::time::timeout(Duration::from_secs(1), tx.send(PIPE_WINDOW))
+            .await
+            .expect("send should resume once an item is taken")
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_dropping_receiver_releases_waiting_sender() {
+        let pacing = Pacing::default();
+        pacing.start();
+        let (tx, rx) = channel(&pacing);
+        for i in 0..PIPE_WINDOW {
+            tx.send(i).await.unwrap();
+        }
+
+        let waiting = tokio::spawn(async move { tx.send(PIPE_WINDOW).await });
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        drop(rx);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), waiting)
+            .await
+            .expect("sender should wake when the receiver goes away")
+            .unwrap();
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_pipe_output_backpressure_reaches_the_producer() {
+        let pacing = Pacing::default();
+        pacing.start();
+        let (tx, rx) = channel(&pacing);
+        // A sink that holds at most one chunk until the reader catches up
+        let (sink, mut reader) = tokio::io::duplex(8);
+        let output = futures::stream::unfold(rx, |mut rx| async move {
+            rx.recv().await.map(|chunk| (chunk, rx))
+        });
+        let pipe = tokio::spawn(pipe_output(output, sink));
+
+        let producer = tokio::spawn(async move {
+            let mut sent = 0;
+            for _ in 0..PIPE_WINDOW * 8 {
+                tx.send("chunk 8b".to_string()).await.unwrap();
+                sent += 1;
+            }
+            sent
+        });
+
+        // Nobody reads the sink: the producer stalls a window ahead of it
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!producer.is_finished());
+
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).await.unwrap();
+        assert_eq!(producer.await.unwrap(), PIPE_WINDOW * 8);
+        assert_eq!(pipe.await.unwrap().unwrap(), (PIPE_WINDOW * 8 * 8) as u64);
+        assert_eq!(out.len(), PIPE_WINDOW * 8 * 8);
+    }
+}
@@ -0,0 +1,142 @@
+//! Prepared commands: register a command template once, run it many times.
+//!
+//! The guest resolves the template up front (container env merged, working
+//! directory validated), so each run only sends the template ID and the
+//! extra arguments. Templates live in the guest agent until the handle is
+//! dropped; when the agent restarts they are gone and the next run
+//! re-registers transparently.
+
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::exec::{BoxCommand, Execution};
+use crate::runtime::backend::BoxBackend;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Handle to a command template registered with a box.
+///
+/// Created by [`LiteBox::prepare_exec`](super::LiteBox::prepare_exec).
+/// Backends without prepared-exec support (e.g. REST) fall back to a full
+/// exec per run, so the handle works everywhere. Dropping the handle
+/// unregisters the template; a guest holds at most
+/// [`MAX_TEMPLATES`](boxlite_shared::constants::prepared::MAX_TEMPLATES)
+/// at once and refuses more with `InvalidState`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use boxlite::{BoxCommand, LiteBox};
+/// # async fn example(litebox: &LiteBox) -> Result<(), Box<dyn std::error::Error>> {
+/// let worker = litebox
+///     .prepare_exec(BoxCommand::new("python").arg("worker.py"))
+///     .await?;
+///
+/// for job in 0..1000 {
+///     let mut execution = worker.run(["--job".to_string(), job.to_string()]).await?;
+///     execution.wait().await?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct PreparedExec {
+    backend: Arc<dyn BoxBackend>,
+    command: BoxCommand,
+    /// `Some(id)` when registered with the guest, `None` when the backend
+    /// has no prepared-exec support.
+    prepared_id: Mutex<Option<String>>,
+}
+
+impl PreparedExec {
+    /// Register `command` with the box backend.
+    pub(crate) async fn prepare(
+        backend: Arc<dyn BoxBackend>,
+        command: BoxCommand,
+    ) -> BoxliteResult<Self> {
+        let prepared_id = backend.prepare_exec(command.clone()).await?;
+        Ok(Self {
+            backend,
+            command,
+            prepared_id: Mutex::new(prepared_id),
+        })
+    }
+
+    /// The command template.
+    pub fn command(&self) -> &BoxCommand {
+        &self.command
+    }
+
+    /// Run the template with `args` appended to its arguments.
+    pub async fn run<I, S>(&self, args: I) -> BoxliteResult<Execution>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let args: Vec<String> = args.into_iter().map(Into::into).collect();
+        let execution = self.start(args).await?;
+        Ok(execution.keep_alive(Arc::clone(&self.backend)))
+    }
+
+    async fn start(&self, args: Vec<String>) -> BoxliteResult<Execution> {
+        let Some(prepared_id) = self.prepared_id.lock().await.clone() else {
+            return self.backend.exec(self.command.clone().args(args)).await;
+        };
+
+        match self.backend.exec_prepared(&prepared_id, args.clone()).await {
+            Err(BoxliteError::NotFound(_)) => {
+                tracing::debug!(
+                    box_id = %self.backend.id(),
+                    prepared_id = %prepared_id,
+                    "Prepared command lost by guest, re-registering"
+                );
+                let prepared_id = self.reregister(&prepared_id).await?;
+                self.backend.exec_prepared(&prepared_id, args).await
+            }
+            result => result,
+        }
+    }
+
+    /// Register the template again, unless a concurrent run already did.
+    async fn reregister(&self, stale_id: &str) -> BoxliteResult<String> {
+        let mut prepared_id = self.prepared_id.lock().await;
+        if let Some(current) = prepared_id.as_deref()
+            && current != stale_id
+        {
+            return Ok(current.to_string());
+        }
+
+        let fresh = self
+            .backend
+            .prepare_exec(self.command.clone())
+            .await?
+            .ok_or_else(|| {
+                BoxliteError::Internal("backend stopped supporting prepared exec".to_string())
+            })?;
+        *prepared_id = Some(fresh.clone());
+        Ok(fresh)
+    }
+}
+
+impl Drop for PreparedExec {
+    fn drop(&mut self) {
+        let Some(prepared_id) = self.prepared_id.get_mut().take() else {
+            return;
+        };
+        // Drop can't await; without a runtime the template stays until the
+        // box stops, which also clears it
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let backend = Arc::clone(&self.backend);
+        handle.spawn(async move {
+            if let Err(e) = backend.unprepare_exec(&prepared_id).await {
+                tracing::debug!(
+                    box_id = %backend.id(),
+                    prepared_id = %prepared_id,
+                    error = %e,
+                    "Failed to unregister prepared command"
+                );
+            }
+        });
+    }
+}
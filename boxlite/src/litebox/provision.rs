@@ -0,0 +1,216 @@
+//! One-time box provisioning.
+//!
+//! `BoxOptions::setup_commands` run in order on the first start, before the
+//! box is reported running. Their output is written to
+//! `{box_home}/provision.log`, which each provisioning run replaces. The
+//! `provisioned` marker in the box state keeps restarts from running them
+//! again; `LiteBox::reprovision()` clears it.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use futures::StreamExt;
+
+use super::exec::{BoxCommand, Execution};
+
+/// File name of the provisioning log inside the box directory.
+pub(crate) const PROVISION_LOG_FILE: &str = "provision.log";
+
+/// Captured result of one setup command.
+#[derive(Debug)]
+pub(crate) struct SetupOutput {
+    pub(crate) stdout: String,
+    pub(crate) stderr: String,
+    pub(crate) exit_code: i32,
+    pub(crate) error_message: Option<String>,
+}
+
+impl SetupOutput {
+    /// Drain the output streams and wait for the command to exit.
+    pub(crate) async fn collect(mut execution: Execution) -> BoxliteResult<Self> {
+        let (stdout, stderr) = tokio::join!(drain(execution.stdout()), drain(execution.stderr()));
+        let result = execution.wait().await?;
+        Ok(Self {
+            stdout,
+            stderr,
+            exit_code: result.exit_code,
+            error_message: result.error_message,
+        })
+    }
+}
+
+async fn drain<S>(stream: Option<S>) -> String
+where
+    S: futures::Stream<Item = String> + Unpin,
+{
+    let mut out = String::new();
+    if let Some(mut stream) = stream {
+        while let Some(chunk) = stream.next().await {
+            out.push_str(&chunk);
+        }
+    }
+    out
+}
+
+/// Turn a setup command's result into an error unless it exited with 0.
+pub(crate) fn check(command: &BoxCommand, result: BoxliteResult<SetupOutput>) -> BoxliteResult<()> {
+    let output = result.map_err(|e| {
+        BoxliteError::Execution(format!(
+            "setup command `{}` failed to run: {}",
+            command_line(command),
+            e
+        ))
+    })?;
+    if output.exit_code == 0 {
+        return Ok(());
+    }
+
+    let mut msg = format!(
+        "setup command `{}` exited with code {}",
+        command_line(command),
+        output.exit_code
+    );
+    if let Some(detail) = output.error_message {
+        msg.push_str(&format!(" ({})", detail));
+    }
+    Err(BoxliteError::Execution(msg))
+}
+
+/// Log of one provisioning run.
+pub(crate) struct ProvisionLog {
+    path: PathBuf,
+    file: File,
+}
+
+impl ProvisionLog {
+    /// Start a new log at `{box_home}/provision.log`, replacing the last run's.
+    pub(crate) fn create(box_home: &Path) -> BoxliteResult<Self> {
+        let path = box_home.join(PROVISION_LOG_FILE);
+        let file = File::create(&path).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to create provisioning log {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(Self { path, file })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a command and its output, or the error that kept it from
+    /// finishing.
+    ///
+    /// Write errors are logged and otherwise ignored: a full disk should not
+    /// fail the box on top of whatever the command did.
+    pub(crate) fn record(&mut self, command: &BoxCommand, result: &BoxliteResult<SetupOutput>) {
+        let mut entry = format!("$ {}\n", command_line(command));
+        match result {
+            Ok(output) => {
+                push_block(&mut entry, &output.stdout);
+                push_block(&mut entry, &output.stderr);
+                entry.push_str(&format!("[exit code {}]\n", output.exit_code));
+                if let Some(detail) = &output.error_message {
+                    entry.push_str(&format!("[{}]\n", detail));
+                }
+            }
+            Err(e) => entry.push_str(&format!("[error: {}]\n", e)),
+        }
+        entry.push('\n');
+
+        if let Err(e) = self.file.write_all(entry.as_bytes()) {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %e,
+                "Failed to write provisioning log"
+            );
+        }
+    }
+}
+
+/// Append `text`, ending it with a newline if it has none.
+fn push_block(entry: &mut String, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    entry.push_str(text);
+    if !text.ends_with('\n') {
+        entry.push('\n');
+    }
+}
+
+/// The command as it would be typed in a shell (without quoting).
+fn command_line(command: &BoxCommand) -> String {
+    std::iter::once(command.command.as_str())
+        .chain(command.args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output(stdout: &str, stderr: &str, exit_code: i32) -> BoxliteResult<SetupOutput> {
+        Ok(SetupOutput {
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+            exit_code,
+            error_message: None,
+        })
+    }
+
+    #[test]
+    fn test_check_exit_codes() {
+        let cmd = BoxCommand::new("apk").args(["add", "curl"]);
+        assert!(check(&cmd, output("", "", 0)).is_ok());
+
+        let err = check(&cmd, output("", "", 2)).unwrap_err();
+        assert!(matches!(err, BoxliteError::Execution(_)));
+        assert!(
+            err.to_string()
+                .contains("`apk add curl` exited with code 2")
+        );
+
+        let err = check(&cmd, Err(BoxliteError::Stopped("gone".into()))).unwrap_err();
+        assert!(err.to_string().contains("failed to run"));
+    }
+
+    #[test]
+    fn test_log_records_each_command() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = ProvisionLog::create(dir.path()).unwrap();
+        log.record(&BoxCommand::new("echo").arg("hi"), &output("hi\n", "", 0));
+        log.record(
+            &BoxCommand::new("sh").args(["-c", "exit 3"]),
+            &output("", "oops", 3),
+        );
+        log.record(
+            &BoxCommand::new("true"),
+            &Err(BoxliteError::Stopped("box stopped".into())),
+        );
+
+        let text = std::fs::read_to_string(log.path()).unwrap();
+        assert_eq!(
+            text,
+            "$ echo hi\nhi\n[exit code 0]\n\n\
+             $ sh -c exit 3\noops\n[exit code 3]\n\n\
+             $ true\n[error: stopped: box stopped]\n\n"
+        );
+    }
+
+    #[test]
+    fn test_log_replaces_previous_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut log = ProvisionLog::create(dir.path()).unwrap();
+        log.record(&BoxCommand::new("first"), &output("", "", 0));
+        drop(log);
+
+        let log = ProvisionLog::create(dir.path()).unwrap();
+        assert_eq!(std::fs::read_to_string(log.path()).unwrap(), "");
+    }
+}
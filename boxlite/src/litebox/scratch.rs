@@ -0,0 +1,100 @@
+//! Per-exec scratch directories.
+//!
+//! A command with a [`ScratchSpec`] gets a fresh tmpfs mounted at
+//! `mount_at` in a mount namespace of its own: other execs and the
+//! entrypoint never see it, and the kernel frees it once the command and
+//! everything it started have exited, however they exit.
+
+use serde::{Deserialize, Serialize};
+
+use super::exec::BoxCommand;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Env var the guest sets to the scratch directory's path.
+pub const SCRATCH_DIR_ENV: &str = boxlite_shared::constants::container::SCRATCH_DIR_ENV;
+
+/// A scratch tmpfs for one exec, see [`BoxCommand::scratch_dir`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScratchSpec {
+    /// Size limit of the tmpfs. Its pages count against box memory.
+    pub size_mib: u32,
+    /// Absolute container path to mount it at; created if missing.
+    pub mount_at: String,
+}
+
+impl ScratchSpec {
+    pub fn new(size_mib: u32, mount_at: impl Into<String>) -> Self {
+        Self {
+            size_mib,
+            mount_at: mount_at.into(),
+        }
+    }
+
+    /// Check the spec for a box with `memory_mib` of memory.
+    pub(crate) fn validate(&self, memory_mib: u32) -> BoxliteResult<()> {
+        if self.size_mib == 0 {
+            return Err(BoxliteError::InvalidArgument(
+                "scratch directory size must be at least 1 MiB".into(),
+            ));
+        }
+        if self.size_mib > memory_mib {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "scratch directory of {} MiB exceeds the box's {} MiB of memory",
+                self.size_mib, memory_mib
+            )));
+        }
+        crate::validate::guest_path(&self.mount_at)?;
+        if self.mount_at.split('/').all(str::is_empty) {
+            return Err(BoxliteError::InvalidArgument(
+                "scratch directory cannot be mounted at /".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl BoxCommand {
+    /// Check the command's scratch directory, if any, against box memory.
+    pub(crate) fn validate_scratch(&self, memory_mib: u32) -> BoxliteResult<()> {
+        match &self.scratch_dir {
+            Some(scratch) => scratch.validate(memory_mib),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(ScratchSpec::new(256, "/scratch").validate(512).is_ok());
+        assert!(ScratchSpec::new(512, "/work/tmp").validate(512).is_ok());
+
+        for spec in [
+            ScratchSpec::new(0, "/scratch"),
+            ScratchSpec::new(1024, "/scratch"),
+            ScratchSpec::new(64, "scratch"),
+            ScratchSpec::new(64, "/work/../scratch"),
+            ScratchSpec::new(64, "/"),
+            ScratchSpec::new(64, "//"),
+        ] {
+            assert!(
+                matches!(spec.validate(512), Err(BoxliteError::InvalidArgument(_))),
+                "{spec:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_command_without_scratch_is_valid() {
+        assert!(BoxCommand::new("true").validate_scratch(0).is_ok());
+        assert!(
+            BoxCommand::new("true")
+                .scratch_dir(ScratchSpec::new(64, "/scratch"))
+                .validate_scratch(32)
+                .is_err()
+        );
+    }
+}
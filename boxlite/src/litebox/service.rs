@@ -0,0 +1,263 @@
+//! Supervised services: long-running processes next to the entrypoint.
+//!
+//! Services are recorded in the box state and run by the guest agent, which
+//! restarts them per their [`RestartPolicy`]. The host starts them in
+//! registration order on every box start and stops them in reverse order
+//! before the box stops. A service may only depend on services registered
+//! before it, so that order always satisfies `depends_on`.
+
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use super::exec::BoxCommand;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// When the guest starts a service again after its process exits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    /// Never restart.
+    #[default]
+    No,
+    /// Restart after a non-zero exit or a fatal signal.
+    OnFailure,
+    /// Restart whenever the process exits.
+    Always,
+}
+
+/// How a service is supervised.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServicePolicy {
+    pub restart: RestartPolicy,
+    /// Services started before this one and stopped after it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+/// A service as recorded in the box state.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServiceSpec {
+    pub name: String,
+    pub command: BoxCommand,
+    #[serde(default)]
+    pub policy: ServicePolicy,
+}
+
+/// Lifecycle status of a service.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceStatus {
+    Running,
+    /// Exited and waiting out the restart backoff.
+    Restarting,
+    /// Exited and not restarted by its policy.
+    Exited,
+    /// Could not be spawned.
+    Failed,
+    /// Stopped with `LiteBox::stop_service()`, or the box is not running.
+    Stopped,
+}
+
+impl std::fmt::Display for ServiceStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ServiceStatus::Running => "running",
+            ServiceStatus::Restarting => "restarting",
+            ServiceStatus::Exited => "exited",
+            ServiceStatus::Failed => "failed",
+            ServiceStatus::Stopped => "stopped",
+        })
+    }
+}
+
+/// A service and its current run, see `LiteBox::list_services()`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub status: ServiceStatus,
+    pub policy: ServicePolicy,
+    /// PID of the running process, as seen in the container.
+    pub pid: Option<u32>,
+    /// Restarts since the box started.
+    pub restarts: u32,
+    /// Exit code of the last run, if it exited normally.
+    pub exit_code: Option<i32>,
+    /// Execution of the current or last run; read its output with
+    /// `LiteBox::exec_output()`.
+    pub execution_id: Option<String>,
+    /// Why the last run could not be spawned.
+    pub error: Option<String>,
+}
+
+impl ServiceInfo {
+    /// Info of `spec` from the guest's status, `Stopped` if it has none.
+    pub(crate) fn new(spec: &ServiceSpec, status: Option<boxlite_shared::ServiceStatus>) -> Self {
+        use boxlite_shared::ServiceState;
+
+        let Some(status) = status else {
+            return Self {
+                name: spec.name.clone(),
+                status: ServiceStatus::Stopped,
+                policy: spec.policy.clone(),
+                pid: None,
+                restarts: 0,
+                exit_code: None,
+                execution_id: None,
+                error: None,
+            };
+        };
+
+        let state = match status.state() {
+            ServiceState::Running => ServiceStatus::Running,
+            ServiceState::Restarting => ServiceStatus::Restarting,
+            ServiceState::Exited => ServiceStatus::Exited,
+            ServiceState::Failed => ServiceStatus::Failed,
+            ServiceState::Stopped | ServiceState::Unspecified => ServiceStatus::Stopped,
+        };
+        Self {
+            name: spec.name.clone(),
+            status: state,
+            policy: spec.policy.clone(),
+            pid: (status.pid != 0).then_some(status.pid),
+            restarts: status.restarts,
+            exit_code: status.exit_code,
+            execution_id: (!status.execution_id.is_empty()).then_some(status.execution_id),
+            error: (!status.error.is_empty()).then_some(status.error),
+        }
+    }
+}
+
+/// Check that a service `name` with `policy` can be added to `services`.
+pub(crate) fn validate_new(
+    services: &[ServiceSpec],
+    name: &str,
+    command: &BoxCommand,
+    policy: &ServicePolicy,
+) -> BoxliteResult<()> {
+    let valid_name = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        && name.starts_with(|c: char| c.is_ascii_alphanumeric());
+    if !valid_name {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "invalid service name {:?}: use letters, digits, '_', '.' and '-', \
+             starting with a letter or digit",
+            name
+        )));
+    }
+    if command.tty {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "service {} cannot use a TTY",
+            name
+        )));
+    }
+    if services.iter().any(|s| s.name == name) {
+        return Err(BoxliteError::AlreadyExists(format!(
+            "service {} already exists",
+            name
+        )));
+    }
+
+    let known: HashSet<&str> = services.iter().map(|s| s.name.as_str()).collect();
+    if let Some(missing) = policy
+        .depends_on
+        .iter()
+        .find(|dep| !known.contains(dep.as_str()))
+    {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "service {} depends on unknown service {}",
+            name, missing
+        )));
+    }
+    Ok(())
+}
+
+/// Check that no other service depends on `name`.
+pub(crate) fn validate_remove(services: &[ServiceSpec], name: &str) -> BoxliteResult<()> {
+    match services
+        .iter()
+        .find(|s| s.policy.depends_on.iter().any(|dep| dep == name))
+    {
+        Some(dependent) => Err(BoxliteError::InvalidState(format!(
+            "service {} is a dependency of {}",
+            name, dependent.name
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(name: &str, depends_on: &[&str]) -> ServiceSpec {
+        ServiceSpec {
+            name: name.to_string(),
+            command: BoxCommand::new("sleep").arg("infinity"),
+            policy: ServicePolicy {
+                restart: RestartPolicy::Always,
+                depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_validate_new() {
+        let services = vec![spec("db", &[])];
+        let command = BoxCommand::new("server");
+        let policy = |deps: &[&str]| spec("x", deps).policy;
+
+        assert!(validate_new(&services, "web", &command, &policy(&["db"])).is_ok());
+        assert!(matches!(
+            validate_new(&services, "db", &command, &policy(&[])),
+            Err(BoxliteError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            validate_new(&services, "web", &command, &policy(&["cache"])),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+        // A service cannot depend on itself: it is not registered yet
+        assert!(validate_new(&services, "web", &command, &policy(&["web"])).is_err());
+        for name in ["", "-web", "../web", "web/1", "web app"] {
+            assert!(
+                validate_new(&services, name, &command, &policy(&[])).is_err(),
+                "{name:?}"
+            );
+        }
+        assert!(validate_new(&services, "web", &command.tty(true), &policy(&[])).is_err());
+    }
+
+    #[test]
+    fn test_validate_remove() {
+        let services = vec![spec("db", &[]), spec("web", &["db"])];
+        assert!(matches!(
+            validate_remove(&services, "db"),
+            Err(BoxliteError::InvalidState(_))
+        ));
+        assert!(validate_remove(&services, "web").is_ok());
+    }
+
+    #[test]
+    fn test_info_without_guest_status_is_stopped() {
+        let info = ServiceInfo::new(&spec("db", &[]), None);
+        assert_eq!(info.status, ServiceStatus::Stopped);
+        assert_eq!(info.pid, None);
+
+        let status = boxlite_shared::ServiceStatus {
+            name: "db".into(),
+            state: boxlite_shared::ServiceState::Running.into(),
+            pid: 42,
+            restarts: 3,
+            execution_id: "service-db-3".into(),
+            exit_code: Some(1),
+            error: String::new(),
+        };
+        let info = ServiceInfo::new(&spec("db", &[]), Some(status));
+        assert_eq!(info.status, ServiceStatus::Running);
+        assert_eq!(info.pid, Some(42));
+        assert_eq!(info.restarts, 3);
+        assert_eq!(info.execution_id.as_deref(), Some("service-db-3"));
+        assert_eq!(info.error, None);
+    }
+}
@@ -3,10 +3,19 @@
 //! Provides create, list, get, remove, and restore operations using
 //! external COW files instead of QCOW2 internal snapshots.
 //!
-//! Snapshot mechanics:
+//! Snapshot mechanics (qcow2 driver; the reflink driver reflinks disks instead,
+//! see [`DiskDriver`]):
 //! - Create: move current disks → snapshot dir, create COW children at original paths
 //! - Restore: delete current COW children, create new ones pointing at snapshot's disks
-//! - Remove: delete snapshot directory and DB record (error if current disk depends on it)
+//! - Restore to new: create a new box whose COW children point at snapshot's disks
+//! - Remove: delete snapshot directory and DB record (error if any box's disk depends on it)
+//! - Prune: after a create or on demand, remove snapshots outside the retention
+//!   policy (skipping any a disk depends on)
+//! - Plan: `plan_prune` / `plan_restore` report what prune / restore would do
+//!   without changing anything
+//! - Metadata: with `SnapshotOptions::metadata`, the box's options and services
+//!   are also written to `metadata.json` in the snapshot dir, and restored with
+//!   `RestoreOptions::restore_metadata`; `metadata_only` snapshots move no disks
 
 use std::path::{Path, PathBuf};
 
@@ -14,15 +23,25 @@ use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::Utc;
 
 use crate::db::SnapshotStore;
-use crate::db::snapshots::SnapshotInfo;
+use crate::db::snapshots::{PrunedSnapshots, SnapshotInfo};
 use crate::disk::constants::dirs as disk_dirs;
 use crate::disk::constants::filenames as disk_filenames;
-use crate::disk::{BackingFormat, Qcow2Helper};
-use crate::litebox::snapshot_types::SnapshotOptions;
+use crate::disk::driver::DiskDriver;
+use crate::litebox::snapshot_types::{
+    CloneOptions, RestoreOptions, RestorePlan, SnapshotDiskState, SnapshotMetadata,
+    SnapshotOptions, SnapshotRetention,
+};
 use crate::litebox::state::BoxStatus;
+use crate::lock::{BoxOperation, OperationLock};
 
 use super::LiteBox;
 
+/// Box metadata in a snapshot directory (`SnapshotOptions::metadata`).
+const METADATA_FILE: &str = "metadata.json";
+
+/// Box state the snapshot's disks were frozen in ([`SnapshotDiskState`]).
+const DISK_STATE_FILE: &str = "disk_state.json";
+
 /// Handle for snapshot operations on a LiteBox.
 ///
 /// Obtained via `litebox.snapshot()`. Borrows the LiteBox for the
@@ -38,14 +57,50 @@ impl<'a> SnapshotHandle<'a> {
 
     /// Create a snapshot of the box's current disk state.
     ///
-    /// The box must be stopped. Disks are atomically moved to the snapshot
-    /// directory and COW children are created at the original paths.
-    pub async fn create(&self, name: &str, _opts: SnapshotOptions) -> BoxliteResult<SnapshotInfo> {
+    /// The box must be stopped. The box's disk driver freezes the current
+    /// disks into the snapshot directory: qcow2 disks are moved there and COW
+    /// children are created at the original paths, raw disks are reflinked.
+    ///
+    /// With `opts.metadata`, the box's options and services are captured
+    /// too. With `opts.metadata_only`, only they are and the disks are left
+    /// alone.
+    ///
+    /// On success, the retention policy (`opts.retention`, else the box's
+    /// policy) is enforced and any pruned snapshots are reported in
+    /// `SnapshotInfo::pruned`. Pruning failures are logged, not returned.
+    pub async fn create(&self, name: &str, opts: SnapshotOptions) -> BoxliteResult<SnapshotInfo> {
+        let _lock = self.lock(BoxOperation::Snapshot).await?;
         self.require_stopped()?;
 
+        let metadata = (opts.metadata || opts.metadata_only)
+            .then(|| self.litebox.inner.snapshot_metadata())
+            .transpose()?;
+        let mut info = if opts.metadata_only {
+            self.require_unique_name(name)?;
+            self.do_create_metadata_only(name, metadata.as_ref())?
+        } else {
+            self.create_with_disks(name, metadata.as_ref())?
+        };
+
+        let retention = opts
+            .retention
+            .or_else(|| self.litebox.inner.snapshot_retention());
+        if let Some(retention) = retention {
+            info.pruned = Some(self.prune_expired(&retention));
+        }
+        Ok(info)
+    }
+
+    /// Freeze the disks (and write `metadata`, if any) into a new snapshot.
+    fn create_with_disks(
+        &self,
+        name: &str,
+        metadata: Option<&SnapshotMetadata>,
+    ) -> BoxliteResult<SnapshotInfo> {
         let box_home = self.box_home();
-        let container_disk = box_home.join(disk_filenames::CONTAINER_DISK);
-        let guest_disk = box_home.join(disk_filenames::GUEST_ROOTFS_DISK);
+        let driver = self.driver();
+        let container_disk = box_home.join(driver.container_disk_name());
+        let guest_disk = box_home.join(driver.guest_disk_name());
 
         // Validate container disk exists
         if !container_disk.exists() {
@@ -55,15 +110,15 @@ impl<'a> SnapshotHandle<'a> {
             )));
         }
 
-        // Check for duplicate snapshot name
-        let store = self.snapshot_store();
-        let box_id = self.litebox.id().as_str();
-        if store.get_by_name(box_id, name)?.is_some() {
-            return Err(BoxliteError::AlreadyExists(format!(
-                "snapshot '{}' already exists for box '{}'",
-                name, box_id
-            )));
-        }
+        // Freezing only writes disk metadata (new COW children or reflinks),
+        // covered by the reserve every check keeps
+        self.litebox
+            .inner
+            .runtime
+            .disk_space
+            .ensure(&box_home, 0, "snapshot box")?;
+
+        self.require_unique_name(name)?;
 
         // Transition to Snapshotting
         {
@@ -73,13 +128,13 @@ impl<'a> SnapshotHandle<'a> {
             inner.runtime.box_manager.save_box(inner.id(), &state)?;
         }
 
-        let result = self.do_create(name, &box_home, &container_disk, &guest_disk);
+        let result = self.do_create(name, &box_home, &container_disk, &guest_disk, metadata);
 
-        // Transition back to Stopped regardless of outcome
+        // Transition back to Exited regardless of outcome
         {
             let inner = &self.litebox.inner;
             let mut state = inner.state.write();
-            state.force_status(BoxStatus::Stopped);
+            state.force_status(BoxStatus::Exited);
             let _ = inner.runtime.box_manager.save_box(inner.id(), &state);
         }
 
@@ -92,62 +147,38 @@ impl<'a> SnapshotHandle<'a> {
         box_home: &Path,
         container_disk: &Path,
         guest_disk: &Path,
+        metadata: Option<&SnapshotMetadata>,
     ) -> BoxliteResult<SnapshotInfo> {
-        let snapshot_dir = self.snapshot_dir(box_home, name);
-        std::fs::create_dir_all(&snapshot_dir).map_err(|e| {
-            BoxliteError::Storage(format!(
-                "Failed to create snapshot directory {}: {}",
-                snapshot_dir.display(),
-                e
-            ))
-        })?;
+        let snapshot_dir = self.create_snapshot_dir(box_home, name, metadata)?;
+        let disk_state = SnapshotDiskState::of(&self.litebox.inner.state.read());
+        if let Err(e) = write_disk_state(&snapshot_dir, &disk_state) {
+            let _ = std::fs::remove_dir_all(&snapshot_dir);
+            return Err(e);
+        }
 
-        let qcow2 = Qcow2Helper::new();
+        let driver = self.driver();
 
-        // Get virtual sizes before moving
-        let container_virtual_size = Qcow2Helper::qcow2_virtual_size(container_disk)?;
+        // Get virtual sizes before freezing
+        let container_virtual_size = driver.virtual_size(container_disk)?;
         let guest_virtual_size = if guest_disk.exists() {
-            Qcow2Helper::qcow2_virtual_size(guest_disk)?
+            driver.virtual_size(guest_disk)?
         } else {
             0
         };
 
-        // Move container disk → snapshot dir
-        let snap_container = snapshot_dir.join(disk_filenames::CONTAINER_DISK);
-        std::fs::rename(container_disk, &snap_container).map_err(|e| {
-            BoxliteError::Storage(format!("Failed to move container disk to snapshot: {}", e))
-        })?;
-
-        // Create COW child at original path, backed by snapshot's copy
-        if let Err(e) = qcow2.create_cow_child_disk(
-            &snap_container,
-            BackingFormat::Qcow2,
-            container_disk,
-            container_virtual_size,
-        ) {
-            // Rollback: move snapshot disk back
-            let _ = std::fs::rename(&snap_container, container_disk);
+        // Freeze container disk into snapshot dir; the box keeps a writable disk
+        let snap_container = snapshot_dir.join(driver.container_disk_name());
+        if let Err(e) = driver.freeze(container_disk, &snap_container) {
             let _ = std::fs::remove_dir_all(&snapshot_dir);
             return Err(e);
         }
 
-        // Move guest disk → snapshot dir (if exists)
+        // Freeze guest disk (if exists)
         if guest_disk.exists() {
-            let snap_guest = snapshot_dir.join(disk_filenames::GUEST_ROOTFS_DISK);
-            std::fs::rename(guest_disk, &snap_guest).map_err(|e| {
-                BoxliteError::Storage(format!("Failed to move guest disk to snapshot: {}", e))
-            })?;
-
-            if let Err(e) = qcow2.create_cow_child_disk(
-                &snap_guest,
-                BackingFormat::Qcow2,
-                guest_disk,
-                guest_virtual_size,
-            ) {
-                // Rollback: move disks back, delete COW children
-                let _ = std::fs::remove_file(container_disk);
-                let _ = std::fs::rename(&snap_container, container_disk);
-                let _ = std::fs::rename(&snap_guest, guest_disk);
+            let snap_guest = snapshot_dir.join(driver.guest_disk_name());
+            if let Err(e) = driver.freeze(guest_disk, &snap_guest) {
+                // Rollback: give the container disk back its pre-snapshot state
+                let _ = driver.unfreeze(container_disk, &snap_container);
                 let _ = std::fs::remove_dir_all(&snapshot_dir);
                 return Err(e);
             }
@@ -166,18 +197,82 @@ impl<'a> SnapshotHandle<'a> {
             guest_disk_bytes: guest_virtual_size,
             container_disk_bytes: container_virtual_size,
             size_bytes,
+            has_metadata: metadata.is_some(),
+            metadata_only: false,
+            pruned: None,
         };
         self.snapshot_store().save(&record)?;
 
         tracing::info!(
             box_id = %self.litebox.id(),
             snapshot = %name,
-            "Created external COW snapshot"
+            driver = %driver.kind(),
+            metadata = metadata.is_some(),
+            "Created snapshot"
+        );
+
+        Ok(record)
+    }
+
+    /// Write `metadata` into a new snapshot that holds no disks.
+    fn do_create_metadata_only(
+        &self,
+        name: &str,
+        metadata: Option<&SnapshotMetadata>,
+    ) -> BoxliteResult<SnapshotInfo> {
+        let snapshot_dir = self.create_snapshot_dir(&self.box_home(), name, metadata)?;
+        let record = SnapshotInfo {
+            id: ulid::Ulid::new().to_string(),
+            box_id: self.litebox.id().as_str().to_string(),
+            name: name.to_string(),
+            created_at: Utc::now().timestamp(),
+            snapshot_dir: snapshot_dir.to_string_lossy().to_string(),
+            guest_disk_bytes: 0,
+            container_disk_bytes: 0,
+            size_bytes: dir_size(&snapshot_dir),
+            has_metadata: true,
+            metadata_only: true,
+            pruned: None,
+        };
+        if let Err(e) = self.snapshot_store().save(&record) {
+            let _ = std::fs::remove_dir_all(&snapshot_dir);
+            return Err(e);
+        }
+
+        tracing::info!(
+            box_id = %self.litebox.id(),
+            snapshot = %name,
+            "Created metadata-only snapshot"
         );
 
         Ok(record)
     }
 
+    /// Create the directory of snapshot `name`, with `metadata` in it if given.
+    fn create_snapshot_dir(
+        &self,
+        box_home: &Path,
+        name: &str,
+        metadata: Option<&SnapshotMetadata>,
+    ) -> BoxliteResult<PathBuf> {
+        let snapshot_dir = self.snapshot_dir(box_home, name);
+        std::fs::create_dir_all(&snapshot_dir).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to create snapshot directory {}: {}",
+                snapshot_dir.display(),
+                e
+            ))
+        })?;
+
+        if let Some(metadata) = metadata
+            && let Err(e) = write_metadata(&snapshot_dir, metadata)
+        {
+            let _ = std::fs::remove_dir_all(&snapshot_dir);
+            return Err(e);
+        }
+        Ok(snapshot_dir)
+    }
+
     /// List all snapshots for this box.
     pub async fn list(&self) -> BoxliteResult<Vec<SnapshotInfo>> {
         self.snapshot_store().list(self.litebox.id().as_str())
@@ -191,9 +286,10 @@ impl<'a> SnapshotHandle<'a> {
 
     /// Remove a snapshot by name.
     ///
-    /// Errors if the current disk's backing file is this snapshot
-    /// (restore a different snapshot first).
+    /// Errors if any box's disk is backed by this snapshot: the source box
+    /// after a restore, or a box created via `restore_to_new()`.
     pub async fn remove(&self, name: &str) -> BoxliteResult<()> {
+        let _lock = self.lock(BoxOperation::Snapshot).await?;
         self.require_stopped()?;
 
         let box_id = self.litebox.id().as_str();
@@ -206,24 +302,20 @@ impl<'a> SnapshotHandle<'a> {
             ))
         })?;
 
-        // Check if current disk depends on this snapshot
+        self.do_remove(&info)
+    }
+
+    fn do_remove(&self, info: &SnapshotInfo) -> BoxliteResult<()> {
+        // Check if any box's disk (this box, boxes branched from the snapshot,
+        // or their own snapshots) depends on this snapshot
         let snapshot_dir = PathBuf::from(&info.snapshot_dir);
-        let snap_container = snapshot_dir.join(disk_filenames::CONTAINER_DISK);
-        let container_disk = self.box_home().join(disk_filenames::CONTAINER_DISK);
-
-        if container_disk.exists() && snap_container.exists() {
-            // Read backing file from current disk to see if it points to this snapshot
-            if let (Ok(backing), Ok(snap_canonical)) = (
-                read_backing_file(&container_disk),
-                snap_container.canonicalize(),
-            ) && backing == snap_canonical
-            {
-                return Err(BoxliteError::InvalidState(
-                    "Cannot remove snapshot: current disk depends on this snapshot. \
-                     Restore a different snapshot first."
-                        .to_string(),
-                ));
-            }
+        let box_roots = self.litebox.inner.runtime.layout.box_roots();
+        if let Some(dependent) = find_dependent_disk(&box_roots, &[snapshot_dir.clone()]) {
+            return Err(BoxliteError::InvalidState(format!(
+                "Cannot remove snapshot: disk {} depends on this snapshot. \
+                 Restore a different snapshot or remove the dependent box first.",
+                dependent.display()
+            )));
         }
 
         // Delete snapshot directory
@@ -238,23 +330,112 @@ impl<'a> SnapshotHandle<'a> {
         }
 
         // Remove DB record
-        store.remove_by_name(box_id, name)?;
+        self.snapshot_store()
+            .remove_by_name(self.litebox.id().as_str(), &info.name)?;
 
         tracing::info!(
             box_id = %self.litebox.id(),
-            snapshot = %name,
+            snapshot = %info.name,
             "Removed snapshot"
         );
 
         Ok(())
     }
 
+    /// Remove snapshots outside `retention` (else the box's policy) now.
+    ///
+    /// The box must be stopped. Snapshots a disk depends on are kept. Errors
+    /// if neither `retention` nor a box policy is set.
+    pub async fn prune(
+        &self,
+        retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<PrunedSnapshots> {
+        let _lock = self.lock(BoxOperation::Snapshot).await?;
+        self.require_stopped()?;
+        let retention = self.resolve_retention(retention)?;
+        Ok(self.prune_expired(&retention))
+    }
+
+    /// Report what [`prune`](Self::prune) would remove, without removing anything.
+    pub async fn plan_prune(
+        &self,
+        retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<PrunedSnapshots> {
+        self.require_stopped()?;
+        let retention = self.resolve_retention(retention)?;
+
+        let snapshots = self.snapshot_store().list(self.litebox.id().as_str())?;
+        let box_roots = self.litebox.inner.runtime.layout.box_roots();
+        let mut plan = PrunedSnapshots::default();
+        for info in retention.expired(&snapshots, Utc::now().timestamp()) {
+            // prune skips these too
+            let snapshot_dir = PathBuf::from(&info.snapshot_dir);
+            if find_dependent_disk(&box_roots, &[snapshot_dir]).is_some() {
+                continue;
+            }
+            plan.names.push(info.name.clone());
+            plan.reclaimed_bytes += info.size_bytes;
+        }
+        Ok(plan)
+    }
+
+    /// Remove snapshots outside `retention`, oldest first.
+    ///
+    /// Snapshots a disk depends on, or that fail to be removed, are skipped
+    /// with a warning.
+    fn prune_expired(&self, retention: &SnapshotRetention) -> PrunedSnapshots {
+        let mut pruned = PrunedSnapshots::default();
+
+        let snapshots = match self.snapshot_store().list(self.litebox.id().as_str()) {
+            Ok(snapshots) => snapshots,
+            Err(e) => {
+                tracing::warn!(
+                    box_id = %self.litebox.id(),
+                    error = %e,
+                    "Failed to list snapshots for pruning"
+                );
+                return pruned;
+            }
+        };
+
+        for info in retention.expired(&snapshots, Utc::now().timestamp()) {
+            match self.do_remove(info) {
+                Ok(()) => {
+                    pruned.names.push(info.name.clone());
+                    pruned.reclaimed_bytes += info.size_bytes;
+                }
+                Err(e) => tracing::warn!(
+                    box_id = %self.litebox.id(),
+                    snapshot = %info.name,
+                    error = %e,
+                    "Skipped pruning snapshot"
+                ),
+            }
+        }
+
+        if !pruned.names.is_empty() {
+            tracing::info!(
+                box_id = %self.litebox.id(),
+                pruned = ?pruned.names,
+                reclaimed_bytes = pruned.reclaimed_bytes,
+                "Pruned snapshots by retention policy"
+            );
+        }
+        pruned
+    }
+
     /// Restore box disks from a snapshot.
     ///
     /// Deletes current COW child disks and creates new ones pointing at
-    /// the snapshot's disks. Box stays stopped after restore.
-    pub async fn restore(&self, name: &str) -> BoxliteResult<()> {
-        self.require_stopped()?;
+    /// the snapshot's disks. With `opts.restore_metadata`, the options and
+    /// services captured with the snapshot are put back too; a snapshot
+    /// holding only metadata restores just that. Box stays stopped after
+    /// restore.
+    ///
+    /// A dead box is revived by restoring a snapshot with disks, which
+    /// brings back the storage it lost.
+    pub async fn restore(&self, name: &str, opts: RestoreOptions) -> BoxliteResult<()> {
+        let _lock = self.lock(BoxOperation::Restore).await?;
 
         let box_id = self.litebox.id().as_str();
         let store = self.snapshot_store();
@@ -265,6 +446,14 @@ impl<'a> SnapshotHandle<'a> {
                 name, box_id
             ))
         })?;
+        let previous = self.require_restorable(&info)?;
+        // Read up front, so a bad metadata file leaves the disks alone
+        let metadata = self.metadata_to_restore(&info, &opts)?;
+        let disk_state = if info.metadata_only {
+            None
+        } else {
+            read_disk_state(Path::new(&info.snapshot_dir))?
+        };
 
         // Transition to Restoring
         {
@@ -274,26 +463,137 @@ impl<'a> SnapshotHandle<'a> {
             inner.runtime.box_manager.save_box(inner.id(), &state)?;
         }
 
-        let result = self.do_restore(&info);
+        let result = if info.metadata_only {
+            Ok(())
+        } else {
+            self.do_restore(&info)
+        }
+        .and_then(|()| match metadata {
+            Some(metadata) => self.litebox.inner.restore_metadata(metadata),
+            None => Ok(()),
+        });
 
-        // Transition back to Stopped
+        // Transition back to Exited, or Dead if the disks didn't come back
         {
             let inner = &self.litebox.inner;
             let mut state = inner.state.write();
-            state.force_status(BoxStatus::Stopped);
+            state.force_status(if result.is_ok() {
+                BoxStatus::Exited
+            } else {
+                previous
+            });
+            // The restored disks carry the snapshot's setup
+            if result.is_ok()
+                && let Some(disk_state) = &disk_state
+            {
+                disk_state.apply(&mut state);
+            }
             let _ = inner.runtime.box_manager.save_box(inner.id(), &state);
         }
 
         result
     }
 
+    /// Describe what [`restore`](Self::restore) would do, without changing anything.
+    ///
+    /// Fails the same way `restore` would (box not stopped, unknown snapshot,
+    /// no metadata to restore).
+    pub async fn plan_restore(
+        &self,
+        name: &str,
+        opts: RestoreOptions,
+    ) -> BoxliteResult<RestorePlan> {
+        let box_id = self.litebox.id().as_str();
+        let info = self
+            .snapshot_store()
+            .get_by_name(box_id, name)?
+            .ok_or_else(|| {
+                BoxliteError::NotFound(format!(
+                    "snapshot '{}' not found for box '{}'",
+                    name, box_id
+                ))
+            })?;
+        self.require_restorable(&info)?;
+
+        let metadata_changes = match self.metadata_to_restore(&info, &opts)? {
+            Some(metadata) => metadata.changes_from(&self.litebox.inner.snapshot_metadata()?),
+            None => Vec::new(),
+        };
+        if info.metadata_only {
+            return Ok(RestorePlan {
+                snapshot: info.name,
+                overwritten_disks: Vec::new(),
+                discarded_bytes: 0,
+                metadata_changes,
+            });
+        }
+
+        let box_home = self.box_home();
+        let snapshot_dir = PathBuf::from(&info.snapshot_dir);
+        let driver = self.driver();
+
+        let snap_container = snapshot_dir.join(driver.container_disk_name());
+        if !snap_container.exists() {
+            return Err(BoxliteError::Storage(format!(
+                "Snapshot container disk not found at {}",
+                snap_container.display()
+            )));
+        }
+
+        // The guest disk is only replaced when the snapshot has one
+        let mut disks = vec![box_home.join(driver.container_disk_name())];
+        if snapshot_dir.join(driver.guest_disk_name()).exists() {
+            disks.push(box_home.join(driver.guest_disk_name()));
+        }
+        disks.retain(|disk| disk.exists());
+
+        let discarded_bytes = disks.iter().map(|disk| allocated_size(disk)).sum();
+        Ok(RestorePlan {
+            snapshot: info.name,
+            overwritten_disks: disks,
+            discarded_bytes,
+            metadata_changes,
+        })
+    }
+
+    /// Branch a snapshot into a brand-new box, leaving this box untouched.
+    ///
+    /// The new box gets fresh IDs, the same options as this box, lineage
+    /// pointing back at this box and snapshot, and COW child disks backed
+    /// by the snapshot's disks. The new box is left stopped. This box may be
+    /// running: only the snapshot's disks are read.
+    pub async fn restore_to_new(&self, name: &str, new_box_name: &str) -> BoxliteResult<LiteBox> {
+        let box_id = self.litebox.id().as_str();
+        self.snapshot_store()
+            .get_by_name(box_id, name)?
+            .ok_or_else(|| {
+                BoxliteError::NotFound(format!(
+                    "snapshot '{}' not found for box '{}'",
+                    name, box_id
+                ))
+            })?;
+
+        let mut opts = CloneOptions::default();
+        opts.cow(true).from_snapshot(name);
+        let branched = self.litebox.clone(new_box_name, opts).await?;
+
+        tracing::info!(
+            box_id = %self.litebox.id(),
+            snapshot = %name,
+            new_box_id = %branched.id(),
+            "Branched snapshot into new box"
+        );
+
+        Ok(branched)
+    }
+
     fn do_restore(&self, info: &SnapshotInfo) -> BoxliteResult<()> {
         let box_home = self.box_home();
         let snapshot_dir = PathBuf::from(&info.snapshot_dir);
-        let qcow2 = Qcow2Helper::new();
+        let driver = self.driver();
 
-        let container_disk = box_home.join(disk_filenames::CONTAINER_DISK);
-        let snap_container = snapshot_dir.join(disk_filenames::CONTAINER_DISK);
+        let container_disk = box_home.join(driver.container_disk_name());
+        let snap_container = snapshot_dir.join(driver.container_disk_name());
 
         if !snap_container.exists() {
             return Err(BoxliteError::Storage(format!(
@@ -309,16 +609,11 @@ impl<'a> SnapshotHandle<'a> {
             })?;
         }
 
-        qcow2.create_cow_child_disk(
-            &snap_container,
-            BackingFormat::Qcow2,
-            &container_disk,
-            info.container_disk_bytes,
-        )?;
+        driver.branch(&snap_container, &container_disk, info.container_disk_bytes)?;
 
         // Handle guest disk
-        let guest_disk = box_home.join(disk_filenames::GUEST_ROOTFS_DISK);
-        let snap_guest = snapshot_dir.join(disk_filenames::GUEST_ROOTFS_DISK);
+        let guest_disk = box_home.join(driver.guest_disk_name());
+        let snap_guest = snapshot_dir.join(driver.guest_disk_name());
 
         if snap_guest.exists() {
             if guest_disk.exists() {
@@ -327,12 +622,7 @@ impl<'a> SnapshotHandle<'a> {
                 })?;
             }
 
-            qcow2.create_cow_child_disk(
-                &snap_guest,
-                BackingFormat::Qcow2,
-                &guest_disk,
-                info.guest_disk_bytes,
-            )?;
+            driver.branch(&snap_guest, &guest_disk, info.guest_disk_bytes)?;
         }
 
         tracing::info!(
@@ -348,6 +638,62 @@ impl<'a> SnapshotHandle<'a> {
     // Helpers
     // ========================================================================
 
+    /// Take the box's operation lock for the rest of the operation.
+    async fn lock(&self, operation: BoxOperation) -> BoxliteResult<OperationLock> {
+        self.litebox
+            .inner
+            .runtime
+            .lock_box(self.litebox.id(), operation)
+            .await
+    }
+
+    /// `retention` if given, else the box's policy.
+    fn resolve_retention(
+        &self,
+        retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<SnapshotRetention> {
+        let retention = retention
+            .or_else(|| self.litebox.inner.snapshot_retention())
+            .ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!(
+                    "box '{}' has no snapshot retention policy; pass one to prune",
+                    self.litebox.id()
+                ))
+            })?;
+        retention.validate()?;
+        Ok(retention)
+    }
+
+    /// The metadata [`restore`](Self::restore) puts back: the snapshot's, if
+    /// asked for or the snapshot holds nothing else.
+    fn metadata_to_restore(
+        &self,
+        info: &SnapshotInfo,
+        opts: &RestoreOptions,
+    ) -> BoxliteResult<Option<SnapshotMetadata>> {
+        if !opts.restore_metadata && !info.metadata_only {
+            return Ok(None);
+        }
+        if !info.has_metadata {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "snapshot '{}' holds no metadata to restore; take it with SnapshotOptions::metadata",
+                info.name
+            )));
+        }
+        read_metadata(Path::new(&info.snapshot_dir)).map(Some)
+    }
+
+    fn require_unique_name(&self, name: &str) -> BoxliteResult<()> {
+        let box_id = self.litebox.id().as_str();
+        if self.snapshot_store().get_by_name(box_id, name)?.is_some() {
+            return Err(BoxliteError::AlreadyExists(format!(
+                "snapshot '{}' already exists for box '{}'",
+                name, box_id
+            )));
+        }
+        Ok(())
+    }
+
     fn require_stopped(&self) -> BoxliteResult<()> {
         let state = self.litebox.inner.state.read();
         if !state.status.is_stopped() {
@@ -360,10 +706,37 @@ impl<'a> SnapshotHandle<'a> {
         Ok(())
     }
 
+    /// Check that `info` can be restored onto the box; returns its status.
+    ///
+    /// Exited boxes take any snapshot. Dead boxes only take one with
+    /// disks: metadata alone wouldn't give them back their storage.
+    fn require_restorable(&self, info: &SnapshotInfo) -> BoxliteResult<BoxStatus> {
+        let status = self.litebox.inner.state.read().status;
+        if status.is_dead() && info.metadata_only {
+            return Err(BoxliteError::InvalidState(format!(
+                "box '{}' is dead: snapshot '{}' holds no disks to restore",
+                self.litebox.id(),
+                info.name
+            )));
+        }
+        if !status.can_restore() {
+            return Err(BoxliteError::InvalidState(format!(
+                "box '{}' must be stopped for this operation (current status: {})",
+                self.litebox.id(),
+                status
+            )));
+        }
+        Ok(status)
+    }
+
     fn box_home(&self) -> PathBuf {
         self.litebox.inner.config.box_home.clone()
     }
 
+    fn driver(&self) -> &'static dyn DiskDriver {
+        self.litebox.inner.config.disk_driver.driver()
+    }
+
     fn snapshot_dir(&self, box_home: &Path, name: &str) -> PathBuf {
         box_home.join(disk_dirs::SNAPSHOTS_DIR).join(name)
     }
@@ -373,8 +746,62 @@ impl<'a> SnapshotHandle<'a> {
     }
 }
 
+fn write_metadata(snapshot_dir: &Path, metadata: &SnapshotMetadata) -> BoxliteResult<()> {
+    let path = snapshot_dir.join(METADATA_FILE);
+    let json = serde_json::to_vec_pretty(metadata)
+        .map_err(|e| BoxliteError::Internal(format!("Failed to serialize box metadata: {}", e)))?;
+    std::fs::write(&path, json)
+        .map_err(|e| BoxliteError::Storage(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+fn read_metadata(snapshot_dir: &Path) -> BoxliteResult<SnapshotMetadata> {
+    let path = snapshot_dir.join(METADATA_FILE);
+    let json = std::fs::read(&path)
+        .map_err(|e| BoxliteError::Storage(format!("Failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_slice(&json).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Invalid snapshot metadata {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn write_disk_state(snapshot_dir: &Path, disk_state: &SnapshotDiskState) -> BoxliteResult<()> {
+    let path = snapshot_dir.join(DISK_STATE_FILE);
+    let json = serde_json::to_vec_pretty(disk_state).map_err(|e| {
+        BoxliteError::Internal(format!("Failed to serialize snapshot disk state: {}", e))
+    })?;
+    std::fs::write(&path, json)
+        .map_err(|e| BoxliteError::Storage(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+/// Read the box state the disks of the snapshot in `snapshot_dir` were
+/// frozen in; `None` for snapshots taken before it was recorded.
+pub(super) fn read_disk_state(snapshot_dir: &Path) -> BoxliteResult<Option<SnapshotDiskState>> {
+    let path = snapshot_dir.join(DISK_STATE_FILE);
+    let json = match std::fs::read(&path) {
+        Ok(json) => json,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            return Err(BoxliteError::Storage(format!(
+                "Failed to read {}: {}",
+                path.display(),
+                e
+            )));
+        }
+    };
+    serde_json::from_slice(&json).map(Some).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Invalid snapshot disk state {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
 /// Calculate total size of files in a directory.
-fn dir_size(path: &Path) -> u64 {
+pub(crate) fn dir_size(path: &Path) -> u64 {
     walkdir::WalkDir::new(path)
         .into_iter()
         .filter_map(|e| e.ok())
@@ -384,6 +811,51 @@ fn dir_size(path: &Path) -> u64 {
         .sum()
 }
 
+/// Bytes the files under `path` occupy on the host filesystem.
+///
+/// Unlike [`dir_size`], sparse regions don't count, so a mostly empty qcow2
+/// disk is not reported at its full apparent size.
+pub(crate) fn allocated_size(path: &Path) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+
+    walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.blocks() * 512)
+        .sum()
+}
+
+/// Find a disk under `roots`, outside `dirs`, whose backing file lives in
+/// one of `dirs`.
+///
+/// Scans every box home (including each box's own snapshot directories) so
+/// that branched boxes and snapshots taken of them are also detected. Only
+/// qcow2 disks have backing files; reflinked disks never depend on a snapshot.
+/// `dirs` may also hold a whole box home, to find the boxes cloned from it or
+/// from its snapshots. Paths that no longer exist, like the home a trashed
+/// box was moved from, are compared as written.
+pub(crate) fn find_dependent_disk(roots: &[PathBuf], dirs: &[PathBuf]) -> Option<PathBuf> {
+    let resolve = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let dirs: Vec<PathBuf> = dirs.iter().map(|dir| resolve(dir)).collect();
+    let in_dirs = |path: &Path| dirs.iter().any(|dir| path.starts_with(dir));
+
+    roots
+        .iter()
+        .flat_map(|root| walkdir::WalkDir::new(root).into_iter())
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| {
+            let file_name = e.file_name();
+            file_name == disk_filenames::CONTAINER_DISK
+                || file_name == disk_filenames::GUEST_ROOTFS_DISK
+        })
+        .map(|e| e.into_path())
+        .filter(|disk| !in_dirs(&resolve(disk)))
+        .find(|disk| read_backing_file(disk).is_ok_and(|backing| in_dirs(&resolve(&backing))))
+}
+
 /// Read the backing file path from a QCOW2 disk header.
 fn read_backing_file(disk_path: &Path) -> BoxliteResult<PathBuf> {
     use std::io::Read;
@@ -445,3 +917,298 @@ fn read_backing_file(disk_path: &Path) -> BoxliteResult<PathBuf> {
 
     Ok(PathBuf::from(path_str))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    /// Build a minimal qcow2 file with optional backing file.
+    fn write_qcow2(path: &Path, backing: Option<&Path>) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let mut buf = vec![0u8; 1024];
+        buf[0..4].copy_from_slice(&0x514649fbu32.to_be_bytes());
+        buf[4..8].copy_from_slice(&3u32.to_be_bytes());
+
+        if let Some(backing) = backing {
+            let backing_bytes = backing.to_string_lossy().into_owned().into_bytes();
+            let backing_offset: u64 = 512;
+            buf[8..16].copy_from_slice(&backing_offset.to_be_bytes());
+            buf[16..20].copy_from_slice(&(backing_bytes.len() as u32).to_be_bytes());
+            buf[512..512 + backing_bytes.len()].copy_from_slice(&backing_bytes);
+        }
+
+        std::fs::File::create(path)
+            .unwrap()
+            .write_all(&buf)
+            .unwrap();
+    }
+
+    /// Lay out `boxes/src` with snapshot `snap1` and return (boxes_dir, snapshot_dir).
+    fn setup_snapshot(tmp: &TempDir) -> (PathBuf, PathBuf) {
+        let boxes_dir = tmp.path().join("boxes");
+        let snap_dir = boxes_dir
+            .join("src")
+            .join(disk_dirs::SNAPSHOTS_DIR)
+            .join("snap1");
+        write_qcow2(&snap_dir.join(disk_filenames::CONTAINER_DISK), None);
+        write_qcow2(&snap_dir.join(disk_filenames::GUEST_ROOTFS_DISK), None);
+        (boxes_dir, snap_dir)
+    }
+
+    #[test]
+    fn test_no_dependents_when_disks_are_independent() {
+        let tmp = TempDir::new().unwrap();
+        let (boxes_dir, snap_dir) = setup_snapshot(&tmp);
+        write_qcow2(
+            &boxes_dir.join("src").join(disk_filenames::CONTAINER_DISK),
+            None,
+        );
+
+        assert_eq!(find_dependent_disk(&[boxes_dir], &[snap_dir]), None);
+    }
+
+    #[test]
+    fn test_source_box_disk_depends_on_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let (boxes_dir, snap_dir) = setup_snapshot(&tmp);
+        let disk = boxes_dir.join("src").join(disk_filenames::CONTAINER_DISK);
+        write_qcow2(&disk, Some(&snap_dir.join(disk_filenames::CONTAINER_DISK)));
+
+        assert_eq!(find_dependent_disk(&[boxes_dir], &[snap_dir]), Some(disk));
+    }
+
+    #[test]
+    fn test_branched_box_disk_depends_on_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let (boxes_dir, snap_dir) = setup_snapshot(&tmp);
+        write_qcow2(
+            &boxes_dir.join("src").join(disk_filenames::CONTAINER_DISK),
+            None,
+        );
+        let branched = boxes_dir
+            .join("branched")
+            .join(disk_filenames::GUEST_ROOTFS_DISK);
+        write_qcow2(
+            &branched,
+            Some(&snap_dir.join(disk_filenames::GUEST_ROOTFS_DISK)),
+        );
+
+        assert_eq!(
+            find_dependent_disk(&[boxes_dir], &[snap_dir]),
+            Some(branched)
+        );
+    }
+
+    #[test]
+    fn test_snapshot_of_branched_box_depends_on_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let (boxes_dir, snap_dir) = setup_snapshot(&tmp);
+        let nested = boxes_dir
+            .join("branched")
+            .join(disk_dirs::SNAPSHOTS_DIR)
+            .join("snap2")
+            .join(disk_filenames::CONTAINER_DISK);
+        write_qcow2(
+            &nested,
+            Some(&snap_dir.join(disk_filenames::CONTAINER_DISK)),
+        );
+
+        assert_eq!(find_dependent_disk(&[boxes_dir], &[snap_dir]), Some(nested));
+    }
+
+    #[test]
+    fn test_box_home_has_dependent_clone() {
+        let tmp = TempDir::new().unwrap();
+        let (boxes_dir, snap_dir) = setup_snapshot(&tmp);
+        let src_home = boxes_dir.join("src");
+        // The box's own disk backing onto its snapshot is not a dependent
+        write_qcow2(
+            &src_home.join(disk_filenames::CONTAINER_DISK),
+            Some(&snap_dir.join(disk_filenames::CONTAINER_DISK)),
+        );
+        assert_eq!(
+            find_dependent_disk(&[boxes_dir.clone()], &[src_home.clone()]),
+            None
+        );
+
+        let clone = boxes_dir.join("clone").join(disk_filenames::CONTAINER_DISK);
+        write_qcow2(&clone, Some(&snap_dir.join(disk_filenames::CONTAINER_DISK)));
+        assert_eq!(find_dependent_disk(&[boxes_dir], &[src_home]), Some(clone));
+    }
+
+    #[test]
+    fn test_trashed_box_disk_depends_on_snapshot() {
+        let tmp = TempDir::new().unwrap();
+        let (boxes_dir, snap_dir) = setup_snapshot(&tmp);
+        let trash_dir = tmp.path().join("trash");
+        let trashed = trash_dir.join("clone").join(disk_filenames::CONTAINER_DISK);
+        write_qcow2(
+            &trashed,
+            Some(&snap_dir.join(disk_filenames::CONTAINER_DISK)),
+        );
+
+        assert_eq!(
+            find_dependent_disk(&[boxes_dir, trash_dir], &[snap_dir]),
+            Some(trashed)
+        );
+    }
+
+    #[test]
+    fn test_trashed_box_does_not_depend_on_itself() {
+        let tmp = TempDir::new().unwrap();
+        let boxes_dir = tmp.path().join("boxes");
+        let trash_dir = tmp.path().join("trash");
+        // Moved to the trash: its disk still names the home it left
+        let home = boxes_dir.join("src");
+        let trashed_home = trash_dir.join("src");
+        write_qcow2(
+            &trashed_home.join(disk_filenames::CONTAINER_DISK),
+            Some(
+                &home
+                    .join(disk_dirs::SNAPSHOTS_DIR)
+                    .join("snap1")
+                    .join(disk_filenames::CONTAINER_DISK),
+            ),
+        );
+        let roots = [boxes_dir.clone(), trash_dir];
+        assert_eq!(
+            find_dependent_disk(&roots, &[home.clone(), trashed_home.clone()]),
+            None
+        );
+
+        // A clone made before the move broke with it, and is still found
+        let clone = boxes_dir.join("clone").join(disk_filenames::CONTAINER_DISK);
+        write_qcow2(&clone, Some(&home.join(disk_filenames::CONTAINER_DISK)));
+        assert_eq!(
+            find_dependent_disk(&roots, &[home, trashed_home]),
+            Some(clone)
+        );
+    }
+
+    fn snapshot_at(name: &str, created_at: i64) -> SnapshotInfo {
+        SnapshotInfo {
+            id: name.to_string(),
+            box_id: "box1".to_string(),
+            name: name.to_string(),
+            created_at,
+            snapshot_dir: format!("/snapshots/{}", name),
+            guest_disk_bytes: 0,
+            container_disk_bytes: 0,
+            size_bytes: 100,
+            has_metadata: false,
+            metadata_only: false,
+            pruned: None,
+        }
+    }
+
+    fn names(expired: Vec<&SnapshotInfo>) -> Vec<&str> {
+        expired.iter().map(|info| info.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_allocated_size_skips_sparse_regions() {
+        let tmp = TempDir::new().unwrap();
+        let disk = tmp.path().join(disk_filenames::CONTAINER_DISK);
+        std::fs::File::create(&disk)
+            .unwrap()
+            .set_len(64 * 1024 * 1024)
+            .unwrap();
+
+        assert_eq!(dir_size(tmp.path()), 64 * 1024 * 1024);
+        assert!(allocated_size(tmp.path()) < 64 * 1024 * 1024);
+        assert_eq!(allocated_size(&disk), allocated_size(tmp.path()));
+    }
+
+    #[test]
+    fn test_retention_max_count_expires_oldest_first() {
+        // Newest first, as returned by SnapshotStore::list
+        let snapshots = vec![
+            snapshot_at("s4", 400),
+            snapshot_at("s3", 300),
+            snapshot_at("s2", 200),
+            snapshot_at("s1", 100),
+        ];
+        let mut retention = SnapshotRetention::default();
+        retention.max_count(2);
+
+        assert_eq!(names(retention.expired(&snapshots, 500)), vec!["s1", "s2"]);
+    }
+
+    #[test]
+    fn test_retention_max_age_expires_old_snapshots() {
+        let snapshots = vec![snapshot_at("new", 950), snapshot_at("old", 100)];
+        let mut retention = SnapshotRetention::default();
+        retention.max_age(std::time::Duration::from_secs(60));
+
+        assert_eq!(names(retention.expired(&snapshots, 1000)), vec!["old"]);
+    }
+
+    #[test]
+    fn test_retention_without_limits_keeps_everything() {
+        let snapshots = vec![snapshot_at("s2", 200), snapshot_at("s1", 100)];
+        let retention = SnapshotRetention::default();
+
+        assert!(retention.expired(&snapshots, i64::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_retention_rejects_zero_max_count() {
+        let mut retention = SnapshotRetention::default();
+        retention.max_count(0);
+
+        assert!(matches!(
+            retention.validate(),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_metadata_changes() {
+        use crate::litebox::exec::BoxCommand;
+        use crate::litebox::service::ServiceSpec;
+        use crate::runtime::options::BoxOptions;
+
+        let web = ServiceSpec {
+            name: "web".into(),
+            command: BoxCommand::new("nginx"),
+            policy: Default::default(),
+        };
+        let snapshot = SnapshotMetadata {
+            options: BoxOptions {
+                env: vec![("MODE".into(), "prod".into())],
+                ..Default::default()
+            },
+            services: vec![web.clone()],
+        };
+        assert!(snapshot.changes_from(&snapshot).is_empty());
+
+        let mut current = snapshot.clone();
+        current.options.env = vec![("MODE".into(), "dev".into())];
+        current.options.labels.insert("team".into(), "infra".into());
+        current.options.snapshot_retention = Some(SnapshotRetention::default());
+        current.services.clear();
+
+        assert_eq!(
+            snapshot.changes_from(&current),
+            vec!["options.env.MODE", "options.labels.team", "services.web"]
+        );
+    }
+
+    #[test]
+    fn test_disk_state_round_trips() {
+        use crate::runtime::options::UserNsMode;
+
+        let tmp = TempDir::new().unwrap();
+        // Snapshots from before the disk state was recorded have none
+        assert_eq!(read_disk_state(tmp.path()).unwrap(), None);
+
+        let disk_state = SnapshotDiskState {
+            provisioned: true,
+            userns_shift: Some(UserNsMode::Auto),
+        };
+        write_disk_state(tmp.path(), &disk_state).unwrap();
+        assert_eq!(read_disk_state(tmp.path()).unwrap(), Some(disk_state));
+    }
+}
@@ -1,5 +1,80 @@
 //! Options types for snapshot, export, and clone operations.
 
+use std::path::PathBuf;
+use std::time::Duration;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::{Deserialize, Serialize};
+
+use crate::db::snapshots::SnapshotInfo;
+use crate::litebox::BoxState;
+use crate::litebox::service::ServiceSpec;
+use crate::runtime::options::{BoxOptions, UserNsMode};
+
+/// Retention policy enforced after each successful snapshot.
+///
+/// Snapshots beyond `max_count` (newest are kept) or older than `max_age`
+/// are removed, oldest first. Snapshots that any disk still depends on are
+/// kept, so in a linear chain only snapshots no longer backing a newer
+/// snapshot or the live disks are reclaimed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRetention {
+    /// Keep at most this many snapshots, including the one just created.
+    #[serde(default)]
+    pub max_count: Option<usize>,
+    /// Remove snapshots older than this.
+    #[serde(default)]
+    pub max_age: Option<Duration>,
+}
+
+impl SnapshotRetention {
+    /// Keep at most `count` snapshots.
+    pub fn max_count(&mut self, count: usize) -> &mut Self {
+        self.max_count = Some(count);
+        self
+    }
+
+    /// Remove snapshots older than `age`.
+    pub fn max_age(&mut self, age: Duration) -> &mut Self {
+        self.max_age = Some(age);
+        self
+    }
+
+    pub(crate) fn validate(&self) -> BoxliteResult<()> {
+        if self.max_count == Some(0) {
+            return Err(BoxliteError::InvalidArgument(
+                "snapshot retention max_count must be at least 1".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Snapshots falling outside this policy, oldest first.
+    ///
+    /// `snapshots` must be ordered newest first, as returned by `SnapshotStore::list`.
+    pub(crate) fn expired<'a>(
+        &self,
+        snapshots: &'a [SnapshotInfo],
+        now: i64,
+    ) -> Vec<&'a SnapshotInfo> {
+        let max_age_secs = self
+            .max_age
+            .map(|age| i64::try_from(age.as_secs()).unwrap_or(i64::MAX));
+
+        let mut expired: Vec<&SnapshotInfo> = snapshots
+            .iter()
+            .enumerate()
+            .filter(|(index, info)| {
+                self.max_count.is_some_and(|max| *index >= max)
+                    || max_age_secs.is_some_and(|max| now.saturating_sub(info.created_at) > max)
+            })
+            .map(|(_, info)| info)
+            .collect();
+        expired.reverse();
+        expired
+    }
+}
+
 /// Options for creating a snapshot.
 #[derive(Debug, Clone)]
 pub struct SnapshotOptions {
@@ -10,6 +85,15 @@ pub struct SnapshotOptions {
     pub quiesce_timeout_secs: u64,
     /// Whether to abort the snapshot if quiesce fails (default: true).
     pub stop_on_quiesce_fail: bool,
+    /// Retention policy for this snapshot only, overriding the box's policy.
+    pub retention: Option<SnapshotRetention>,
+    /// Also capture the box's options (env, labels, resource limits, ...)
+    /// and services, for `RestoreOptions::restore_metadata` (default: false).
+    pub metadata: bool,
+    /// Capture only the metadata, leaving the disks in place (default:
+    /// false). A cheap checkpoint of the box's configuration; implies
+    /// `metadata`.
+    pub metadata_only: bool,
 }
 
 impl Default for SnapshotOptions {
@@ -18,6 +102,9 @@ impl Default for SnapshotOptions {
             quiesce: true,
             quiesce_timeout_secs: 30,
             stop_on_quiesce_fail: true,
+            retention: None,
+            metadata: false,
+            metadata_only: false,
         }
     }
 }
@@ -40,6 +127,131 @@ impl SnapshotOptions {
         self.stop_on_quiesce_fail = stop;
         self
     }
+
+    /// Override the box's retention policy for this snapshot.
+    pub fn retention(&mut self, retention: SnapshotRetention) -> &mut Self {
+        self.retention = Some(retention);
+        self
+    }
+
+    /// Set whether to capture the box's options and services too.
+    pub fn metadata(&mut self, metadata: bool) -> &mut Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Set whether to capture only the box's options and services.
+    pub fn metadata_only(&mut self, metadata_only: bool) -> &mut Self {
+        self.metadata_only = metadata_only;
+        self
+    }
+}
+
+/// Options for restoring a snapshot.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    /// Also put back the options and services captured with the snapshot
+    /// (default: false). The box's snapshot retention policy is kept.
+    /// Errors if the snapshot has no metadata. Snapshots holding only
+    /// metadata always restore it.
+    pub restore_metadata: bool,
+}
+
+impl RestoreOptions {
+    /// Set whether to restore the snapshot's metadata.
+    pub fn restore_metadata(&mut self, restore: bool) -> &mut Self {
+        self.restore_metadata = restore;
+        self
+    }
+}
+
+/// Box metadata captured by a snapshot, kept in its directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SnapshotMetadata {
+    /// The box's options when the snapshot was taken.
+    pub options: BoxOptions,
+    /// The box's services, in registration order.
+    #[serde(default)]
+    pub services: Vec<ServiceSpec>,
+}
+
+impl SnapshotMetadata {
+    /// Dotted paths of what restoring `self` over `current` would change,
+    /// e.g. `options.env` or `services.web`; sorted, empty if nothing.
+    ///
+    /// The snapshot retention policy is left out, as restores keep it.
+    pub(crate) fn changes_from(&self, current: &SnapshotMetadata) -> Vec<String> {
+        crate::runtime::drift::changed_values(&current.to_value(), &self.to_value())
+    }
+
+    fn to_value(&self) -> serde_json::Value {
+        let mut options = serde_json::to_value(&self.options).unwrap_or_default();
+        if let serde_json::Value::Object(fields) = &mut options {
+            fields.remove("snapshot_retention");
+            // Keyed by name so a changed variable reports its own path
+            let env: serde_json::Map<String, serde_json::Value> = self
+                .options
+                .env
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::Value::from(v.as_str())))
+                .collect();
+            fields.insert("env".to_string(), serde_json::Value::Object(env));
+        }
+        let services: serde_json::Map<String, serde_json::Value> = self
+            .services
+            .iter()
+            .map(|spec| {
+                let value = serde_json::to_value(spec).unwrap_or_default();
+                (spec.name.clone(), value)
+            })
+            .collect();
+        serde_json::json!({ "options": options, "services": services })
+    }
+}
+
+/// Box state the disks of a snapshot were frozen in, kept next to them.
+///
+/// Boxes restored or branched from the snapshot take it over, since their
+/// disks carry that setup rather than the source box's current one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct SnapshotDiskState {
+    /// Whether `BoxOptions::setup_commands` had run.
+    #[serde(default)]
+    pub provisioned: bool,
+    /// User namespace mapping the container rootfs was shifted for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userns_shift: Option<UserNsMode>,
+}
+
+impl SnapshotDiskState {
+    /// The disk state `state` describes.
+    pub(crate) fn of(state: &BoxState) -> Self {
+        Self {
+            provisioned: state.provisioned,
+            userns_shift: state.userns_shift.clone(),
+        }
+    }
+
+    /// Record `self` on `state`.
+    pub(crate) fn apply(&self, state: &mut BoxState) {
+        state.set_provisioned(self.provisioned);
+        state.set_userns_shift(self.userns_shift.clone());
+    }
+}
+
+/// What restoring a snapshot would do, from `SnapshotHandle::plan_restore`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RestorePlan {
+    /// Snapshot that would be restored.
+    pub snapshot: String,
+    /// Current disks that would be deleted and re-created from the snapshot.
+    pub overwritten_disks: Vec<PathBuf>,
+    /// Bytes held by those disks: changes made since the snapshot, which are lost.
+    pub discarded_bytes: u64,
+    /// Dotted paths of the box metadata that would change, e.g.
+    /// `options.env.PATH` or `services.web`; empty unless metadata is restored.
+    #[serde(default)]
+    pub metadata_changes: Vec<String>,
 }
 
 /// Options for exporting a box archive.
@@ -51,6 +263,10 @@ pub struct ExportOptions {
     pub compression_level: i32,
     /// Whether to include metadata in the archive (default: true).
     pub include_metadata: bool,
+    /// Write zstd seekable frames and an index of member offsets, so single
+    /// members can be pulled with `BoxliteRuntime::extract_from_archive`
+    /// (default: false). Implies `compress`.
+    pub seekable: bool,
 }
 
 impl Default for ExportOptions {
@@ -59,6 +275,7 @@ impl Default for ExportOptions {
             compress: true,
             compression_level: 3,
             include_metadata: true,
+            seekable: false,
         }
     }
 }
@@ -81,6 +298,12 @@ impl ExportOptions {
         self.include_metadata = include;
         self
     }
+
+    /// Set whether to write a seekable archive.
+    pub fn seekable(&mut self, seekable: bool) -> &mut Self {
+        self.seekable = seekable;
+        self
+    }
 }
 
 /// Options for cloning a box.
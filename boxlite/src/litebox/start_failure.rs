@@ -0,0 +1,350 @@
+//! Structured diagnostics for failed box starts.
+//!
+//! Layers on the exit-file/crash-report plumbing: when the start pipeline
+//! fails, the console log is scanned for guest boot phase markers (see
+//! [`boxlite_shared::boot`]) and echoed container stderr. If the guest agent
+//! was reached before the failure, the tail of the guest kernel log is
+//! fetched too. The result is appended to the returned error and persisted
+//! in the box directory so it can be inspected later via
+//! `LiteBox::last_start_failure()`.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use boxlite_shared::boot::{BootPhase, CONTAINER_STDERR_MARKER, PHASE_MARKER};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::portal::GuestSession;
+
+/// File name of the persisted start failure inside the box directory.
+pub(crate) const START_FAILURE_FILE: &str = "start-failure.json";
+
+/// Number of console lines kept in [`StartFailure::console_tail`].
+const CONSOLE_TAIL_LINES: usize = 20;
+
+/// Number of kernel log lines kept in [`StartFailure::dmesg_tail`].
+const DMESG_TAIL_LINES: u32 = 100;
+
+/// How long a failed start waits for the guest to return its kernel log.
+const DMESG_FETCH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Diagnostics captured when a box fails to start.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StartFailure {
+    /// Error returned by the start pipeline.
+    pub error: String,
+    /// Last boot phase the guest reported (None if the kernel never handed off).
+    pub last_phase: Option<BootPhase>,
+    /// Last lines of the console log from the failed boot (markers removed).
+    pub console_tail: Vec<String>,
+    /// Early stderr of the container init process, if it crashed.
+    pub container_stderr: Vec<String>,
+    /// Last lines of the guest kernel log (empty if the guest agent was
+    /// never reached).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dmesg_tail: Vec<String>,
+    /// Console log the tail was read from.
+    pub console_log: PathBuf,
+    /// When the failure was recorded.
+    pub failed_at: DateTime<Utc>,
+}
+
+impl StartFailure {
+    /// Collect diagnostics for `error` from the box's console log, with the
+    /// kernel log tail from [`fetch_dmesg_tail`].
+    pub(crate) fn collect(
+        error: &BoxliteError,
+        console_log: &Path,
+        dmesg_tail: Vec<String>,
+    ) -> Self {
+        let console = std::fs::read_to_string(console_log).unwrap_or_default();
+        let (last_phase, console_tail, container_stderr) = parse_console(&console);
+
+        Self {
+            error: error.to_string(),
+            last_phase,
+            console_tail,
+            container_stderr,
+            dmesg_tail,
+            console_log: console_log.to_path_buf(),
+            failed_at: Utc::now(),
+        }
+    }
+
+    /// Human-readable hint about where boot stopped.
+    pub fn phase_hint(&self) -> &'static str {
+        match self.last_phase {
+            None => "the kernel did not hand off to the guest agent",
+            Some(BootPhase::KernelHandoff) => "the guest agent did not finish starting",
+            Some(BootPhase::AgentStarted) => "the container rootfs was not mounted",
+            Some(BootPhase::RootfsMounted) => "the container entrypoint was not spawned",
+            Some(BootPhase::ContainerSpawned) => "the container entrypoint exited or failed",
+        }
+    }
+
+    /// Render the diagnostics as an indented multi-line report.
+    pub fn render(&self) -> String {
+        let mut out = String::from("Start diagnostics:\n");
+        match self.last_phase {
+            Some(phase) => out.push_str(&format!("  Last boot phase: {phase}\n")),
+            None => out.push_str("  Last boot phase: none\n"),
+        }
+        out.push_str(&format!("  Hint: {}\n", self.phase_hint()));
+
+        if !self.container_stderr.is_empty() {
+            out.push_str("  Container stderr:\n");
+            for line in &self.container_stderr {
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+
+        if self.console_tail.is_empty() {
+            out.push_str(&format!(
+                "  Console: no output ({})\n",
+                self.console_log.display()
+            ));
+        } else {
+            out.push_str(&format!(
+                "  Console tail ({}):\n",
+                self.console_log.display()
+            ));
+            for line in &self.console_tail {
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+
+        if !self.dmesg_tail.is_empty() {
+            out.push_str("  Kernel log tail:\n");
+            for line in &self.dmesg_tail {
+                out.push_str(&format!("    {line}\n"));
+            }
+        }
+
+        out.trim_end().to_string()
+    }
+
+    /// Append the rendered diagnostics to a boot-related error.
+    ///
+    /// Errors raised before the VM is involved (config, state, image) are
+    /// returned unchanged.
+    pub(crate) fn attach_to(&self, err: BoxliteError) -> BoxliteError {
+        let with_report = |msg: String| format!("{msg}\n\n{}", self.render());
+        match err {
+            BoxliteError::Engine(msg) => BoxliteError::Engine(with_report(msg)),
+            BoxliteError::Portal(msg) => BoxliteError::Portal(with_report(msg)),
+            BoxliteError::Rpc(msg) => BoxliteError::Rpc(with_report(msg)),
+            BoxliteError::RpcTransport(msg) => BoxliteError::RpcTransport(with_report(msg)),
+            BoxliteError::Internal(msg) => BoxliteError::Internal(with_report(msg)),
+            other => other,
+        }
+    }
+
+    /// Persist to `{box_home}/start-failure.json`.
+    pub(crate) fn save(&self, box_home: &Path) -> BoxliteResult<()> {
+        let path = box_home.join(START_FAILURE_FILE);
+        let json = serde_json::to_vec_pretty(self)?;
+        std::fs::write(&path, json).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to write start failure to {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+
+    /// Load the last persisted start failure, if any.
+    pub(crate) fn load(box_home: &Path) -> Option<Self> {
+        let data = std::fs::read(box_home.join(START_FAILURE_FILE)).ok()?;
+        serde_json::from_slice(&data).ok()
+    }
+
+    /// Remove the persisted start failure after a successful start.
+    pub(crate) fn clear(box_home: &Path) {
+        let _ = std::fs::remove_file(box_home.join(START_FAILURE_FILE));
+    }
+}
+
+impl std::fmt::Display for StartFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render())
+    }
+}
+
+/// Fetch the last [`DMESG_TAIL_LINES`] kernel log lines from the guest.
+///
+/// Best effort: returns nothing if the agent does not answer in time.
+pub(crate) async fn fetch_dmesg_tail(session: &GuestSession) -> Vec<String> {
+    let fetch = async { session.guest().await?.dmesg(DMESG_TAIL_LINES).await };
+    match tokio::time::timeout(DMESG_FETCH_TIMEOUT, fetch).await {
+        Ok(Ok(lines)) => lines,
+        Ok(Err(e)) => {
+            tracing::debug!(error = %e, "Failed to fetch guest kernel log");
+            Vec::new()
+        }
+        Err(_) => {
+            tracing::debug!("Timed out fetching guest kernel log");
+            Vec::new()
+        }
+    }
+}
+
+/// Split console output into (last phase, tail, container stderr).
+///
+/// Only the most recent boot is considered: everything before the last
+/// `kernel-handoff` marker belongs to an earlier start.
+fn parse_console(console: &str) -> (Option<BootPhase>, Vec<String>, Vec<String>) {
+    let lines: Vec<&str> = console.lines().collect();
+    let boot_start = lines
+        .iter()
+        .rposition(|l| BootPhase::from_marker_line(l) == Some(BootPhase::KernelHandoff))
+        .unwrap_or(0);
+    let boot = &lines[boot_start..];
+
+    let last_phase = boot
+        .iter()
+        .filter_map(|l| BootPhase::from_marker_line(l))
+        .max();
+
+    let container_stderr = boot
+        .iter()
+        .filter_map(|l| l.split_once(CONTAINER_STDERR_MARKER))
+        .map(|(_, rest)| rest.trim().to_string())
+        .collect();
+
+    let output: Vec<&str> = boot
+        .iter()
+        .copied()
+        .filter(|l| !l.contains(PHASE_MARKER) && !l.contains(CONTAINER_STDERR_MARKER))
+        .collect();
+    let console_tail = output[output.len().saturating_sub(CONSOLE_TAIL_LINES)..]
+        .iter()
+        .map(|l| l.trim_end().to_string())
+        .collect();
+
+    (last_phase, console_tail, container_stderr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_console_empty() {
+        let (phase, tail, stderr) = parse_console("");
+        assert_eq!(phase, None);
+        assert!(tail.is_empty());
+        assert!(stderr.is_empty());
+    }
+
+    #[test]
+    fn test_parse_console_reports_last_phase_and_stderr() {
+        let console = "\
+[    0.000] Linux version 6.x
+[BOXLITE-PHASE] kernel-handoff
+[BOOT] BoxLite guest agent starting
+[BOXLITE-PHASE] agent-started
+[BOXLITE-PHASE] rootfs-mounted
+[BOXLITE-PHASE] container-spawned
+[BOXLITE-CONTAINER-STDERR] exec /app/run: no such file or directory
+ERROR container init exited";
+        let (phase, tail, stderr) = parse_console(console);
+        assert_eq!(phase, Some(BootPhase::ContainerSpawned));
+        assert_eq!(stderr, vec!["exec /app/run: no such file or directory"]);
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[0], "[BOOT] BoxLite guest agent starting");
+        assert_eq!(tail[1], "ERROR container init exited");
+    }
+
+    #[test]
+    fn test_parse_console_ignores_previous_boots() {
+        let console = "\
+[BOXLITE-PHASE] kernel-handoff
+[BOXLITE-PHASE] agent-started
+[BOXLITE-PHASE] container-spawned
+old boot output
+[BOXLITE-PHASE] kernel-handoff
+new boot output";
+        let (phase, tail, _) = parse_console(console);
+        assert_eq!(phase, Some(BootPhase::KernelHandoff));
+        assert_eq!(tail, vec!["new boot output"]);
+    }
+
+    #[test]
+    fn test_parse_console_limits_tail() {
+        let console: String = (0..50).map(|i| format!("line {i}\n")).collect();
+        let (phase, tail, _) = parse_console(&console);
+        assert_eq!(phase, None);
+        assert_eq!(tail.len(), CONSOLE_TAIL_LINES);
+        assert_eq!(tail.last().map(String::as_str), Some("line 49"));
+    }
+
+    #[test]
+    fn test_attach_to_boot_errors_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let console_log = dir.path().join("console.log");
+        std::fs::write(&console_log, "[BOXLITE-PHASE] kernel-handoff\n").unwrap();
+
+        let err = BoxliteError::Engine("guest did not become ready".into());
+        let failure = StartFailure::collect(&err, &console_log, Vec::new());
+        assert_eq!(failure.last_phase, Some(BootPhase::KernelHandoff));
+
+        let attached = failure.attach_to(err).to_string();
+        assert!(attached.contains("guest did not become ready"));
+        assert!(attached.contains("Last boot phase: kernel-handoff"));
+
+        let config_err = failure.attach_to(BoxliteError::Config("bad".into()));
+        assert!(matches!(config_err, BoxliteError::Config(ref m) if m == "bad"));
+    }
+
+    #[test]
+    fn test_render_includes_kernel_log_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let console_log = dir.path().join("console.log");
+        let err = BoxliteError::Engine("container init failed".into());
+
+        let without = StartFailure::collect(&err, &console_log, Vec::new());
+        assert!(!without.render().contains("Kernel log tail"));
+
+        let failure = StartFailure::collect(
+            &err,
+            &console_log,
+            vec!["[    3.2] EXT4-fs error (device vda): bad inode".into()],
+        );
+        let rendered = failure.render();
+        assert!(rendered.contains("Kernel log tail:"));
+        assert!(rendered.contains("    [    3.2] EXT4-fs error (device vda): bad inode"));
+    }
+
+    #[test]
+    fn test_load_without_dmesg_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let failure = StartFailure::collect(
+            &BoxliteError::Engine("boom".into()),
+            &dir.path().join("console.log"),
+            Vec::new(),
+        );
+        failure.save(dir.path()).unwrap();
+        let json = std::fs::read_to_string(dir.path().join(START_FAILURE_FILE)).unwrap();
+        assert!(!json.contains("dmesg_tail"));
+        assert_eq!(StartFailure::load(dir.path()), Some(failure));
+    }
+
+    #[test]
+    fn test_save_load_clear_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let failure = StartFailure::collect(
+            &BoxliteError::Engine("boom".into()),
+            &dir.path().join("missing.log"),
+            vec!["Out of memory: Killed process 42 (app)".into()],
+        );
+        assert_eq!(failure.last_phase, None);
+
+        failure.save(dir.path()).unwrap();
+        assert_eq!(StartFailure::load(dir.path()), Some(failure));
+
+        StartFailure::clear(dir.path());
+        assert_eq!(StartFailure::load(dir.path()), None);
+    }
+}
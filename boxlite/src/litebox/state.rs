@@ -2,11 +2,15 @@
 //!
 //! Defines the possible states of a box and valid transitions between them.
 
+use super::service::ServiceSpec;
 use crate::ContainerID;
 use crate::lock::LockId;
+use crate::net::{BoxNetwork, HostEntry};
+use crate::runtime::options::UserNsMode;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Lifecycle status of a box.
 ///
@@ -15,9 +19,11 @@ use serde::{Deserialize, Serialize};
 ///
 /// State machine (Docker/Podman-style):
 /// ```text
-/// create() → Configured (persisted to DB, no VM)
+/// create() → Created (persisted to DB, no VM)
 /// start()  → Running (VM initialized)
-/// stop()   → Stopped (VM terminated, can restart)
+/// stop()   → Exited (VM terminated, can restart)
+/// crash    → Exited (with the shim's exit code)
+/// disks lost → Dead (can only be restored from a snapshot or removed)
 /// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -27,7 +33,9 @@ pub enum BoxStatus {
 
     /// Box is created and persisted, but VM not yet started.
     /// No VM process allocated. Call start() or exec() to initialize.
-    Configured,
+    // Records written before the rename say "configured" (or "starting")
+    #[serde(alias = "configured", alias = "starting")]
+    Created,
 
     /// Box is running and guest server is accepting commands.
     Running,
@@ -35,9 +43,15 @@ pub enum BoxStatus {
     /// Box is shutting down gracefully (transient state).
     Stopping,
 
-    /// Box is not running. VM process terminated.
+    /// Box is not running. VM process terminated, stopped or crashed.
     /// Rootfs is preserved, box can be restarted.
-    Stopped,
+    // Records written before the rename say "stopped"
+    #[serde(alias = "stopped")]
+    Exited,
+
+    /// Box can't be started again: its disks or home directory are gone.
+    /// Restoring a disk snapshot revives it; otherwise it can only be removed.
+    Dead,
 
     /// Box is being snapshotted (transient state).
     Snapshotting,
@@ -47,6 +61,9 @@ pub enum BoxStatus {
 
     /// Box is being exported (transient state).
     Exporting,
+
+    /// Box disks are being compacted (transient state).
+    Compacting,
 }
 
 impl BoxStatus {
@@ -59,12 +76,20 @@ impl BoxStatus {
         matches!(self, BoxStatus::Running)
     }
 
-    pub fn is_configured(&self) -> bool {
-        matches!(self, BoxStatus::Configured)
+    pub fn is_created(&self) -> bool {
+        matches!(self, BoxStatus::Created)
     }
 
+    /// Check if the box has run and its disks are at rest.
+    ///
+    /// Operations on the disks (snapshot, export, clone, compact, mount)
+    /// require this: Created boxes have no disks yet, Dead boxes lost them.
     pub fn is_stopped(&self) -> bool {
-        matches!(self, BoxStatus::Stopped)
+        matches!(self, BoxStatus::Exited)
+    }
+
+    pub fn is_dead(&self) -> bool {
+        matches!(self, BoxStatus::Dead)
     }
 
     /// Check if this status represents a transient state.
@@ -75,13 +100,14 @@ impl BoxStatus {
                 | BoxStatus::Snapshotting
                 | BoxStatus::Restoring
                 | BoxStatus::Exporting
+                | BoxStatus::Compacting
         )
     }
 
     /// Check if start() can be called from this state.
-    /// Configured boxes need first start, Stopped boxes can restart.
+    /// Created boxes need first start, Exited boxes can restart.
     pub fn can_start(&self) -> bool {
-        matches!(self, BoxStatus::Configured | BoxStatus::Stopped)
+        matches!(self, BoxStatus::Created | BoxStatus::Exited)
     }
 
     /// Check if stop() can be called from this state.
@@ -91,23 +117,29 @@ impl BoxStatus {
     }
 
     /// Check if remove() can be called from this state.
-    /// Configured, Stopped, and Unknown boxes can be removed.
+    /// Created, Exited, Dead, and Unknown boxes can be removed.
     pub fn can_remove(&self) -> bool {
         matches!(
             self,
-            BoxStatus::Configured | BoxStatus::Stopped | BoxStatus::Unknown
+            BoxStatus::Created | BoxStatus::Exited | BoxStatus::Dead | BoxStatus::Unknown
         )
     }
 
     /// Check if exec() can be called from this state.
-    /// Configured and Stopped will trigger implicit start().
+    /// Created and Exited will trigger implicit start().
     pub fn can_exec(&self) -> bool {
         matches!(
             self,
-            BoxStatus::Configured | BoxStatus::Running | BoxStatus::Stopped
+            BoxStatus::Created | BoxStatus::Running | BoxStatus::Exited
         )
     }
 
+    /// Check if a snapshot can be restored onto the box from this state.
+    /// Exited boxes, and Dead boxes whose disks the snapshot brings back.
+    pub fn can_restore(&self) -> bool {
+        matches!(self, BoxStatus::Exited | BoxStatus::Dead)
+    }
+
     /// Check if transition to target state is valid.
     pub fn can_transition_to(&self, target: BoxStatus) -> bool {
         use BoxStatus::*;
@@ -115,30 +147,38 @@ impl BoxStatus {
             (self, target),
             // Unknown can transition to any state (recovery)
             (Unknown, _) |
-            // Configured → Running (start success) or Stopped (start failed)
-            (Configured, Running) |
-            (Configured, Stopped) |
-            (Configured, Unknown) |
-            // Running → Stopping (graceful) or Stopped (crash)
+            // Created → Running (start success) or Exited (start failed)
+            (Created, Running) |
+            (Created, Exited) |
+            (Created, Unknown) |
+            // Running → Stopping (graceful), Exited (crash) or Dead (disks lost)
             (Running, Stopping) |
-            (Running, Stopped) |
+            (Running, Exited) |
+            (Running, Dead) |
             (Running, Unknown) |
-            // Stopping → Stopped (complete) or Unknown (error)
-            (Stopping, Stopped) |
+            // Stopping → Exited (complete) or Unknown (error)
+            (Stopping, Exited) |
             (Stopping, Unknown) |
-            // Stopped → Running (restart) or transient operation states
-            (Stopped, Running) |
-            (Stopped, Snapshotting) |
-            (Stopped, Restoring) |
-            (Stopped, Exporting) |
-            (Stopped, Unknown) |
-            // Transient operation states → Stopped (on completion or error)
-            (Snapshotting, Stopped) |
+            // Exited → Running (restart), transient operation states or Dead
+            (Exited, Running) |
+            (Exited, Snapshotting) |
+            (Exited, Restoring) |
+            (Exited, Exporting) |
+            (Exited, Compacting) |
+            (Exited, Dead) |
+            (Exited, Unknown) |
+            // Dead → Restoring (disk snapshot brings the disks back)
+            (Dead, Restoring) |
+            // Transient operation states → Exited (on completion or error)
+            (Snapshotting, Exited) |
             (Snapshotting, Unknown) |
-            (Restoring, Stopped) |
+            (Restoring, Exited) |
+            (Restoring, Dead) |
             (Restoring, Unknown) |
-            (Exporting, Stopped) |
-            (Exporting, Unknown)
+            (Exporting, Exited) |
+            (Exporting, Unknown) |
+            (Compacting, Exited) |
+            (Compacting, Unknown)
         )
     }
 
@@ -146,13 +186,15 @@ impl BoxStatus {
     pub fn as_str(&self) -> &'static str {
         match self {
             BoxStatus::Unknown => "unknown",
-            BoxStatus::Configured => "configured",
+            BoxStatus::Created => "created",
             BoxStatus::Running => "running",
             BoxStatus::Stopping => "stopping",
-            BoxStatus::Stopped => "stopped",
+            BoxStatus::Exited => "exited",
+            BoxStatus::Dead => "dead",
             BoxStatus::Snapshotting => "snapshotting",
             BoxStatus::Restoring => "restoring",
             BoxStatus::Exporting => "exporting",
+            BoxStatus::Compacting => "compacting",
         }
     }
 }
@@ -163,15 +205,19 @@ impl std::str::FromStr for BoxStatus {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "unknown" => Ok(BoxStatus::Unknown),
-            "configured" => Ok(BoxStatus::Configured),
-            // Legacy: support "starting" for backward compatibility with existing databases
-            "starting" => Ok(BoxStatus::Configured),
+            "created" => Ok(BoxStatus::Created),
+            // Legacy: "configured" and "starting" from existing databases
+            "configured" | "starting" => Ok(BoxStatus::Created),
             "running" => Ok(BoxStatus::Running),
             "stopping" => Ok(BoxStatus::Stopping),
-            "stopped" => Ok(BoxStatus::Stopped),
+            "exited" => Ok(BoxStatus::Exited),
+            // Legacy: "stopped" from existing databases
+            "stopped" => Ok(BoxStatus::Exited),
+            "dead" => Ok(BoxStatus::Dead),
             "snapshotting" => Ok(BoxStatus::Snapshotting),
             "restoring" => Ok(BoxStatus::Restoring),
             "exporting" => Ok(BoxStatus::Exporting),
+            "compacting" => Ok(BoxStatus::Compacting),
             _ => Err(()),
         }
     }
@@ -200,18 +246,86 @@ pub struct BoxState {
     /// Allocated when the box is first initialized (not at creation time).
     /// Used to retrieve the lock across process restarts.
     pub lock_id: Option<LockId>,
+    /// Guest network identity (IP/MAC).
+    ///
+    /// Assigned on first start and reused on every later start so the
+    /// guest address stays stable across restarts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<BoxNetwork>,
+    /// Extra entries written to the guest's `/etc/hosts`.
+    ///
+    /// Resolved on every start and rewritten while running as other boxes
+    /// start and stop (see `BoxliteOptions::box_dns`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<HostEntry>,
+    /// Whether `BoxOptions::setup_commands` have run for this box.
+    #[serde(default)]
+    pub provisioned: bool,
+    /// Unix sockets the box binds on the host.
+    ///
+    /// Recorded on start so `remove()` and recovery can clean up what a
+    /// killed shim left behind, wherever the box layout put them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sockets: Vec<PathBuf>,
+    /// Host process the running box stops with.
+    ///
+    /// Set when a box starts with `detach = false` or is adopted with
+    /// `LiteBox::adopt()`; `None` while the box runs detached.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner_pid: Option<u32>,
+    /// Warm pool the box is idling in, waiting to be acquired.
+    ///
+    /// Pooled boxes are hidden from `list_info()`; cleared when the box is
+    /// handed out by `BoxliteRuntime::acquire_warm()`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warm_pool: Option<String>,
+    /// Environment added after the container started.
+    ///
+    /// Set when a warm box is acquired; merged into every exec, under the
+    /// command's own variables.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exec_env: Vec<(String, String)>,
+    /// Supervised services, in registration order.
+    ///
+    /// Started in this order on every box start and stopped in reverse;
+    /// a service only depends on services before it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub services: Vec<ServiceSpec>,
+    /// Exit code of the box's last run, while it is Exited.
+    ///
+    /// 0 after `stop()`; the shim's exit code after a crash, when it
+    /// recorded one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// User namespace mapping the container rootfs ownership was shifted for.
+    ///
+    /// Recorded on the first start with `BoxOptions::userns`. The shift is
+    /// not undone, so starting the box with another mapping, or none, is
+    /// refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub userns_shift: Option<UserNsMode>,
 }
 
 impl BoxState {
     /// Create initial state for a new box.
-    /// Box starts in Configured status (persisted, no VM yet).
+    /// Box starts in Created status (persisted, no VM yet).
     pub fn new() -> Self {
         Self {
-            status: BoxStatus::Configured,
+            status: BoxStatus::Created,
             pid: None,
             container_id: None,
             last_updated: Utc::now(),
             lock_id: None,
+            network: None,
+            hosts: Vec::new(),
+            provisioned: false,
+            sockets: Vec::new(),
+            owner_pid: None,
+            warm_pool: None,
+            exec_env: Vec::new(),
+            services: Vec::new(),
+            exit_code: None,
+            userns_shift: None,
         }
     }
 
@@ -248,32 +362,108 @@ impl BoxState {
         self.force_status(status);
     }
 
+    /// Set guest network identity and update timestamp.
+    pub fn set_network(&mut self, network: BoxNetwork) {
+        self.network = Some(network);
+        self.last_updated = Utc::now();
+    }
+
+    /// Record the guest's extra hosts entries and update timestamp.
+    pub fn set_hosts(&mut self, hosts: Vec<HostEntry>) {
+        self.hosts = hosts;
+        self.last_updated = Utc::now();
+    }
+
+    /// Record the box's host sockets and update timestamp.
+    pub fn set_sockets(&mut self, sockets: Vec<PathBuf>) {
+        self.sockets = sockets;
+        self.last_updated = Utc::now();
+    }
+
+    /// Set the provisioning marker and update timestamp.
+    pub fn set_provisioned(&mut self, provisioned: bool) {
+        self.provisioned = provisioned;
+        self.last_updated = Utc::now();
+    }
+
+    /// Record the user namespace mapping the rootfs was shifted for.
+    pub fn set_userns_shift(&mut self, userns_shift: Option<UserNsMode>) {
+        self.userns_shift = userns_shift;
+        self.last_updated = Utc::now();
+    }
+
     /// Set PID and update timestamp.
     pub fn set_pid(&mut self, pid: Option<u32>) {
         self.pid = pid;
         self.last_updated = Utc::now();
     }
 
-    /// Mark box as crashed (sets status to Stopped since VM is no longer running).
+    /// Set the owning host process and update timestamp.
+    pub fn set_owner_pid(&mut self, owner_pid: Option<u32>) {
+        self.owner_pid = owner_pid;
+        self.last_updated = Utc::now();
+    }
+
+    /// Set or clear the warm pool the box idles in.
+    pub fn set_warm_pool(&mut self, pool: Option<String>) {
+        self.warm_pool = pool;
+        self.last_updated = Utc::now();
+    }
+
+    /// Add exec environment variables, replacing earlier values of a key.
+    pub fn add_exec_env(&mut self, env: &[(String, String)]) {
+        for (key, value) in env {
+            self.exec_env.retain(|(k, _)| k != key);
+            self.exec_env.push((key.clone(), value.clone()));
+        }
+        self.last_updated = Utc::now();
+    }
+
+    /// Record a service after the ones registered before it.
+    pub fn add_service(&mut self, service: ServiceSpec) {
+        self.services.push(service);
+        self.last_updated = Utc::now();
+    }
+
+    /// Forget service `name`.
+    pub fn remove_service(&mut self, name: &str) {
+        self.services.retain(|s| s.name != name);
+        self.last_updated = Utc::now();
+    }
+
+    /// Mark box as exited with `exit_code` (0 for a clean stop, the shim's
+    /// code after a crash, None if unknown).
     ///
-    /// In our simplified state model, crashed VMs become Stopped
-    /// since the rootfs is preserved and can be restarted.
+    /// The rootfs is preserved, so the box can be restarted.
     /// PID is cleared since the process is no longer alive.
-    pub fn mark_stop(&mut self) {
-        self.status = BoxStatus::Stopped;
+    pub fn mark_exited(&mut self, exit_code: Option<i32>) {
+        self.status = BoxStatus::Exited;
+        self.exit_code = exit_code;
         self.pid = None;
+        self.owner_pid = None;
+        self.last_updated = Utc::now();
+    }
+
+    /// Mark box as dead: it can't run again without its disks.
+    pub fn mark_dead(&mut self) {
+        self.status = BoxStatus::Dead;
+        self.pid = None;
+        self.owner_pid = None;
         self.last_updated = Utc::now();
     }
 
     /// Reset state after system reboot.
     ///
-    /// Active boxes become Stopped since VM rootfs is preserved.
-    /// PID is cleared since all processes are gone after reboot.
+    /// Active boxes become Exited since VM rootfs is preserved; their exit
+    /// code is unknown. PID is cleared since all processes are gone after
+    /// reboot.
     pub fn reset_for_reboot(&mut self) {
         if self.status.is_active() {
-            self.status = BoxStatus::Stopped;
+            self.status = BoxStatus::Exited;
+            self.exit_code = None;
         }
         self.pid = None;
+        self.owner_pid = None;
         self.last_updated = Utc::now();
     }
 }
@@ -288,100 +478,168 @@ impl Default for BoxState {
 mod tests {
     use super::*;
 
+    const ALL: [BoxStatus; 10] = [
+        BoxStatus::Unknown,
+        BoxStatus::Created,
+        BoxStatus::Running,
+        BoxStatus::Stopping,
+        BoxStatus::Exited,
+        BoxStatus::Dead,
+        BoxStatus::Snapshotting,
+        BoxStatus::Restoring,
+        BoxStatus::Exporting,
+        BoxStatus::Compacting,
+    ];
+
     #[test]
     fn test_status_is_active() {
         // Only Running is active (VM process running)
-        assert!(!BoxStatus::Configured.is_active());
+        assert!(!BoxStatus::Created.is_active());
         assert!(BoxStatus::Running.is_active());
         assert!(!BoxStatus::Stopping.is_active());
-        assert!(!BoxStatus::Stopped.is_active());
+        assert!(!BoxStatus::Exited.is_active());
+        assert!(!BoxStatus::Dead.is_active());
         assert!(!BoxStatus::Unknown.is_active());
     }
 
     #[test]
-    fn test_status_is_configured() {
-        assert!(BoxStatus::Configured.is_configured());
-        assert!(!BoxStatus::Running.is_configured());
-        assert!(!BoxStatus::Stopping.is_configured());
-        assert!(!BoxStatus::Stopped.is_configured());
-        assert!(!BoxStatus::Unknown.is_configured());
+    fn test_status_is_created() {
+        assert!(BoxStatus::Created.is_created());
+        assert!(!BoxStatus::Running.is_created());
+        assert!(!BoxStatus::Stopping.is_created());
+        assert!(!BoxStatus::Exited.is_created());
+        assert!(!BoxStatus::Dead.is_created());
+        assert!(!BoxStatus::Unknown.is_created());
+    }
+
+    #[test]
+    fn test_status_is_stopped() {
+        // Only Exited boxes have disks at rest to operate on
+        for status in ALL {
+            assert_eq!(status.is_stopped(), status == BoxStatus::Exited, "{status}");
+        }
     }
 
     #[test]
     fn test_status_can_start() {
-        // Configured and Stopped can be started
-        assert!(BoxStatus::Configured.can_start());
+        // Created and Exited can be started
+        assert!(BoxStatus::Created.can_start());
         assert!(!BoxStatus::Running.can_start());
         assert!(!BoxStatus::Stopping.can_start());
-        assert!(BoxStatus::Stopped.can_start());
+        assert!(BoxStatus::Exited.can_start());
+        assert!(!BoxStatus::Dead.can_start());
         assert!(!BoxStatus::Unknown.can_start());
     }
 
     #[test]
     fn test_status_can_stop() {
         // Only Running boxes can be stopped
-        assert!(!BoxStatus::Configured.can_stop());
+        assert!(!BoxStatus::Created.can_stop());
         assert!(BoxStatus::Running.can_stop());
         assert!(!BoxStatus::Stopping.can_stop());
-        assert!(!BoxStatus::Stopped.can_stop());
+        assert!(!BoxStatus::Exited.can_stop());
+        assert!(!BoxStatus::Dead.can_stop());
         assert!(!BoxStatus::Unknown.can_stop());
     }
 
+    #[test]
+    fn test_status_can_remove() {
+        assert!(BoxStatus::Created.can_remove());
+        assert!(!BoxStatus::Running.can_remove());
+        assert!(!BoxStatus::Stopping.can_remove());
+        assert!(BoxStatus::Exited.can_remove());
+        assert!(BoxStatus::Dead.can_remove());
+        assert!(BoxStatus::Unknown.can_remove());
+        assert!(!BoxStatus::Snapshotting.can_remove());
+    }
+
     #[test]
     fn test_status_can_exec() {
-        // Configured and Stopped trigger implicit start
-        assert!(BoxStatus::Configured.can_exec());
+        // Created and Exited trigger implicit start
+        assert!(BoxStatus::Created.can_exec());
         assert!(BoxStatus::Running.can_exec());
         assert!(!BoxStatus::Stopping.can_exec());
-        assert!(BoxStatus::Stopped.can_exec());
+        assert!(BoxStatus::Exited.can_exec());
+        assert!(!BoxStatus::Dead.can_exec());
         assert!(!BoxStatus::Unknown.can_exec());
     }
 
+    #[test]
+    fn test_status_can_restore() {
+        for status in ALL {
+            assert_eq!(
+                status.can_restore(),
+                matches!(status, BoxStatus::Exited | BoxStatus::Dead),
+                "{status}"
+            );
+        }
+    }
+
     #[test]
     fn test_valid_transitions() {
-        // Configured transitions
-        assert!(BoxStatus::Configured.can_transition_to(BoxStatus::Running));
-        assert!(BoxStatus::Configured.can_transition_to(BoxStatus::Stopped));
-        assert!(!BoxStatus::Configured.can_transition_to(BoxStatus::Stopping));
-
-        // Running transitions
-        assert!(BoxStatus::Running.can_transition_to(BoxStatus::Stopping));
-        assert!(BoxStatus::Running.can_transition_to(BoxStatus::Stopped));
-        assert!(!BoxStatus::Running.can_transition_to(BoxStatus::Configured));
-
-        // Stopping transitions
-        assert!(BoxStatus::Stopping.can_transition_to(BoxStatus::Stopped));
-        assert!(!BoxStatus::Stopping.can_transition_to(BoxStatus::Running));
-        assert!(!BoxStatus::Stopping.can_transition_to(BoxStatus::Configured));
-
-        // Stopped transitions
-        assert!(BoxStatus::Stopped.can_transition_to(BoxStatus::Running));
-        assert!(BoxStatus::Stopped.can_transition_to(BoxStatus::Snapshotting));
-        assert!(BoxStatus::Stopped.can_transition_to(BoxStatus::Restoring));
-        assert!(BoxStatus::Stopped.can_transition_to(BoxStatus::Exporting));
-        assert!(!BoxStatus::Stopped.can_transition_to(BoxStatus::Configured));
-        assert!(!BoxStatus::Stopped.can_transition_to(BoxStatus::Stopping));
-
-        // Transient operation states → Stopped
-        assert!(BoxStatus::Snapshotting.can_transition_to(BoxStatus::Stopped));
-        assert!(!BoxStatus::Snapshotting.can_transition_to(BoxStatus::Running));
-        assert!(BoxStatus::Restoring.can_transition_to(BoxStatus::Stopped));
-        assert!(!BoxStatus::Restoring.can_transition_to(BoxStatus::Running));
-        assert!(BoxStatus::Exporting.can_transition_to(BoxStatus::Stopped));
-        assert!(!BoxStatus::Exporting.can_transition_to(BoxStatus::Running));
-
-        // Unknown can go anywhere (recovery)
-        assert!(BoxStatus::Unknown.can_transition_to(BoxStatus::Configured));
-        assert!(BoxStatus::Unknown.can_transition_to(BoxStatus::Running));
-        assert!(BoxStatus::Unknown.can_transition_to(BoxStatus::Stopped));
+        use BoxStatus::*;
+
+        // Every allowed edge; anything else is rejected
+        let allowed = [
+            (Created, Running),
+            (Created, Exited),
+            (Created, Unknown),
+            (Running, Stopping),
+            (Running, Exited),
+            (Running, Dead),
+            (Running, Unknown),
+            (Stopping, Exited),
+            (Stopping, Unknown),
+            (Exited, Running),
+            (Exited, Snapshotting),
+            (Exited, Restoring),
+            (Exited, Exporting),
+            (Exited, Compacting),
+            (Exited, Dead),
+            (Exited, Unknown),
+            (Dead, Restoring),
+            (Snapshotting, Exited),
+            (Snapshotting, Unknown),
+            (Restoring, Exited),
+            (Restoring, Dead),
+            (Restoring, Unknown),
+            (Exporting, Exited),
+            (Exporting, Unknown),
+            (Compacting, Exited),
+            (Compacting, Unknown),
+        ];
+        for from in ALL {
+            for to in ALL {
+                // Unknown can go anywhere (recovery)
+                let expected = from == Unknown || allowed.contains(&(from, to));
+                assert_eq!(
+                    from.can_transition_to(to),
+                    expected,
+                    "{from} -> {to} should be {}",
+                    if expected { "allowed" } else { "rejected" }
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_dead_is_terminal_except_restore() {
+        // A dead box can't run, be operated on, or come back on its own
+        for target in ALL {
+            assert_eq!(
+                BoxStatus::Dead.can_transition_to(target),
+                target == BoxStatus::Restoring,
+                "dead -> {target}"
+            );
+        }
     }
 
     #[test]
     fn test_state_transition() {
         let mut state = BoxState::new();
-        assert_eq!(state.status, BoxStatus::Configured);
+        assert_eq!(state.status, BoxStatus::Created);
 
-        // Valid: Configured → Running
+        // Valid: Created → Running
         assert!(state.transition_to(BoxStatus::Running).is_ok());
         assert_eq!(state.status, BoxStatus::Running);
 
@@ -389,24 +647,62 @@ mod tests {
         assert!(state.transition_to(BoxStatus::Stopping).is_ok());
         assert_eq!(state.status, BoxStatus::Stopping);
 
-        // Valid: Stopping → Stopped
-        assert!(state.transition_to(BoxStatus::Stopped).is_ok());
-        assert_eq!(state.status, BoxStatus::Stopped);
+        // Valid: Stopping → Exited
+        assert!(state.transition_to(BoxStatus::Exited).is_ok());
+        assert_eq!(state.status, BoxStatus::Exited);
 
-        // Valid: Stopped → Running (direct restart)
+        // Valid: Exited → Running (direct restart)
         assert!(state.transition_to(BoxStatus::Running).is_ok());
         assert_eq!(state.status, BoxStatus::Running);
+
+        // Valid: Running → Dead (disks lost under a running box)
+        assert!(state.transition_to(BoxStatus::Dead).is_ok());
+        assert_eq!(state.status, BoxStatus::Dead);
+
+        // Valid: Dead → Restoring → Exited (revived from a snapshot)
+        assert!(state.transition_to(BoxStatus::Restoring).is_ok());
+        assert!(state.transition_to(BoxStatus::Exited).is_ok());
+        assert_eq!(state.status, BoxStatus::Exited);
     }
 
     #[test]
     fn test_invalid_transition() {
         let mut state = BoxState::new();
-        state.status = BoxStatus::Configured;
+        state.status = BoxStatus::Created;
 
-        // Invalid: Configured → Stopping (must go through Running)
+        // Invalid: Created → Stopping (must go through Running)
         let result = state.transition_to(BoxStatus::Stopping);
         assert!(result.is_err());
-        assert_eq!(state.status, BoxStatus::Configured); // Unchanged
+        assert_eq!(state.status, BoxStatus::Created); // Unchanged
+
+        // Invalid: Dead → Running (disks are gone)
+        state.status = BoxStatus::Dead;
+        assert!(state.transition_to(BoxStatus::Running).is_err());
+        assert_eq!(state.status, BoxStatus::Dead);
+    }
+
+    #[test]
+    fn test_mark_exited_records_exit_code() {
+        let mut state = BoxState::new();
+        state.set_status(BoxStatus::Running);
+        state.set_pid(Some(4242));
+
+        state.mark_exited(Some(137));
+
+        assert_eq!(state.status, BoxStatus::Exited);
+        assert_eq!(state.exit_code, Some(137));
+        assert_eq!(state.pid, None);
+    }
+
+    #[test]
+    fn test_mark_dead() {
+        let mut state = BoxState::new();
+        state.mark_exited(Some(0));
+
+        state.mark_dead();
+
+        assert_eq!(state.status, BoxStatus::Dead);
+        assert!(!state.status.can_start());
     }
 
     #[test]
@@ -414,61 +710,157 @@ mod tests {
         let mut state = BoxState::new();
         state.status = BoxStatus::Running;
         state.pid = Some(12345);
+        state.exit_code = Some(0);
 
         state.reset_for_reboot();
 
-        assert_eq!(state.status, BoxStatus::Stopped);
+        assert_eq!(state.status, BoxStatus::Exited);
         assert_eq!(state.pid, None);
+        // How the VM went down with the host is unknown
+        assert_eq!(state.exit_code, None);
     }
 
     #[test]
-    fn test_reset_for_reboot_stopped() {
+    fn test_reset_for_reboot_exited() {
         let mut state = BoxState::new();
-        state.status = BoxStatus::Stopped;
-        state.pid = None;
+        state.mark_exited(Some(3));
 
         state.reset_for_reboot();
 
-        // Stopped stays stopped
-        assert_eq!(state.status, BoxStatus::Stopped);
+        // Exited stays exited, with its exit code
+        assert_eq!(state.status, BoxStatus::Exited);
+        assert_eq!(state.exit_code, Some(3));
     }
 
     #[test]
-    fn test_reset_for_reboot_configured() {
+    fn test_reset_for_reboot_created() {
         let mut state = BoxState::new();
-        // Configured is not active, should stay configured
-        assert_eq!(state.status, BoxStatus::Configured);
+        // Created is not active, should stay created
+        assert_eq!(state.status, BoxStatus::Created);
 
         state.reset_for_reboot();
 
-        // Configured stays configured (no VM was running)
-        assert_eq!(state.status, BoxStatus::Configured);
+        // Created stays created (no VM was running)
+        assert_eq!(state.status, BoxStatus::Created);
     }
 
     #[test]
     fn test_status_as_str() {
         assert_eq!(BoxStatus::Unknown.as_str(), "unknown");
-        assert_eq!(BoxStatus::Configured.as_str(), "configured");
+        assert_eq!(BoxStatus::Created.as_str(), "created");
         assert_eq!(BoxStatus::Running.as_str(), "running");
         assert_eq!(BoxStatus::Stopping.as_str(), "stopping");
-        assert_eq!(BoxStatus::Stopped.as_str(), "stopped");
+        assert_eq!(BoxStatus::Exited.as_str(), "exited");
+        assert_eq!(BoxStatus::Dead.as_str(), "dead");
         assert_eq!(BoxStatus::Snapshotting.as_str(), "snapshotting");
         assert_eq!(BoxStatus::Restoring.as_str(), "restoring");
         assert_eq!(BoxStatus::Exporting.as_str(), "exporting");
+        assert_eq!(BoxStatus::Compacting.as_str(), "compacting");
     }
 
     #[test]
     fn test_status_from_str() {
-        assert_eq!("unknown".parse(), Ok(BoxStatus::Unknown));
-        assert_eq!("configured".parse(), Ok(BoxStatus::Configured));
-        // Legacy support: "starting" maps to Configured
-        assert_eq!("starting".parse(), Ok(BoxStatus::Configured));
-        assert_eq!("running".parse(), Ok(BoxStatus::Running));
-        assert_eq!("stopping".parse(), Ok(BoxStatus::Stopping));
-        assert_eq!("stopped".parse(), Ok(BoxStatus::Stopped));
-        assert_eq!("snapshotting".parse(), Ok(BoxStatus::Snapshotting));
-        assert_eq!("restoring".parse(), Ok(BoxStatus::Restoring));
-        assert_eq!("exporting".parse(), Ok(BoxStatus::Exporting));
+        for status in ALL {
+            assert_eq!(status.as_str().parse(), Ok(status));
+        }
+        // Legacy names from existing databases
+        assert_eq!("configured".parse(), Ok(BoxStatus::Created));
+        assert_eq!("starting".parse(), Ok(BoxStatus::Created));
+        assert_eq!("stopped".parse(), Ok(BoxStatus::Exited));
         assert!("invalid".parse::<BoxStatus>().is_err());
     }
+
+    #[test]
+    fn test_status_serde_matches_as_str() {
+        for status in ALL {
+            let json = serde_json::to_value(status).unwrap();
+            assert_eq!(json, status.as_str());
+        }
+    }
+
+    #[test]
+    fn test_legacy_records_load_with_new_statuses() {
+        let stopped: BoxState = serde_json::from_str(
+            r#"{"status":"stopped","pid":null,"container_id":null,"last_updated":"2024-05-01T12:00:00Z","lock_id":null}"#,
+        )
+        .unwrap();
+        assert_eq!(stopped.status, BoxStatus::Exited);
+        assert_eq!(stopped.exit_code, None);
+
+        let configured: BoxState = serde_json::from_str(
+            r#"{"status":"configured","pid":null,"container_id":null,"last_updated":"2024-05-01T12:00:00Z","lock_id":null}"#,
+        )
+        .unwrap();
+        assert_eq!(configured.status, BoxStatus::Created);
+    }
+
+    #[test]
+    fn test_stop_clears_owner() {
+        let mut state = BoxState::new();
+        state.set_status(BoxStatus::Running);
+        state.set_pid(Some(4242));
+        state.set_owner_pid(Some(1234));
+
+        state.mark_exited(Some(0));
+
+        assert_eq!(state.owner_pid, None);
+    }
+
+    #[test]
+    fn test_owner_pid_defaults_for_old_records() {
+        let mut json = serde_json::to_value(BoxState::new()).unwrap();
+        json.as_object_mut().unwrap().remove("owner_pid");
+
+        let state: BoxState = serde_json::from_value(json).unwrap();
+        assert_eq!(state.owner_pid, None);
+    }
+
+    #[test]
+    fn test_add_exec_env_replaces_existing_keys() {
+        let mut state = BoxState::new();
+        state.add_exec_env(&[("A".into(), "1".into()), ("B".into(), "2".into())]);
+        state.add_exec_env(&[("A".into(), "3".into())]);
+
+        assert_eq!(
+            state.exec_env,
+            vec![
+                ("B".to_string(), "2".to_string()),
+                ("A".to_string(), "3".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_warm_pool_fields_default_for_old_records() {
+        let json = serde_json::to_value(BoxState::new()).unwrap();
+        assert!(json.get("warm_pool").is_none());
+        assert!(json.get("exec_env").is_none());
+
+        let state: BoxState = serde_json::from_value(json).unwrap();
+        assert_eq!(state.warm_pool, None);
+        assert!(state.exec_env.is_empty());
+    }
+
+    #[test]
+    fn test_services_default_for_old_records() {
+        let json = serde_json::to_value(BoxState::new()).unwrap();
+        assert!(json.get("services").is_none());
+
+        let state: BoxState = serde_json::from_value(json).unwrap();
+        assert!(state.services.is_empty());
+    }
+
+    #[test]
+    fn test_userns_shift_round_trips_and_defaults() {
+        let json = serde_json::to_value(BoxState::new()).unwrap();
+        assert!(json.get("userns_shift").is_none());
+        let state: BoxState = serde_json::from_value(json).unwrap();
+        assert_eq!(state.userns_shift, None);
+
+        let mut state = BoxState::new();
+        state.set_userns_shift(Some(UserNsMode::Auto));
+        let json = serde_json::to_value(&state).unwrap();
+        let state: BoxState = serde_json::from_value(json).unwrap();
+        assert_eq!(state.userns_shift, Some(UserNsMode::Auto));
+    }
 }
@@ -0,0 +1,266 @@
+//! Placeholder expansion for `BoxCommand` fields.
+//!
+//! Opt-in via [`BoxCommand::enable_templating`]. Expands `working_dir`, `args`
+//! and env values against the target box at exec time:
+//!
+//! | Placeholder   | Value                                   |
+//! |---------------|-----------------------------------------|
+//! | `{box.id}`    | Box ID                                  |
+//! | `{box.name}`  | Box name (error if the box is unnamed)  |
+//! | `{box.image}` | Image reference the box was created from |
+//! | `{exec.id}`   | ID of the execution being started       |
+//!
+//! `{{` and `}}` produce literal braces. Unknown placeholders are rejected
+//! with `InvalidArgument` so typos surface before the command runs.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use super::{BoxCommand, EnvEdit};
+use crate::BoxInfo;
+
+/// Values placeholders resolve to.
+struct TemplateContext<'a> {
+    box_id: String,
+    box_name: Option<&'a str>,
+    image: &'a str,
+    /// `None` for prepared commands, which are rendered once for many runs.
+    exec_id: Option<&'a str>,
+}
+
+impl<'a> TemplateContext<'a> {
+    fn new(info: &'a BoxInfo, exec_id: Option<&'a str>) -> Self {
+        Self {
+            box_id: info.id.to_string(),
+            box_name: info.name.as_deref(),
+            image: &info.image,
+            exec_id,
+        }
+    }
+
+    fn lookup(&self, key: &str) -> BoxliteResult<&str> {
+        match key {
+            "box.id" => Ok(&self.box_id),
+            "box.name" => self.box_name.ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!(
+                    "{{box.name}} used but box {} has no name",
+                    self.box_id
+                ))
+            }),
+            "box.image" => Ok(self.image),
+            "exec.id" => self.exec_id.ok_or_else(|| {
+                BoxliteError::InvalidArgument(
+                    "{exec.id} is not available for prepared commands".into(),
+                )
+            }),
+            _ => Err(BoxliteError::InvalidArgument(format!(
+                "unknown placeholder {{{key}}} (expected box.id, box.name, box.image or exec.id)"
+            ))),
+        }
+    }
+}
+
+/// Expand placeholders in `input`.
+fn expand(input: &str, ctx: &TemplateContext<'_>) -> BoxliteResult<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if let Some(after) = tail.strip_prefix("{{") {
+            out.push('{');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix("}}") {
+            out.push('}');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix('}') {
+            // A lone closing brace has no special meaning
+            out.push('}');
+            rest = after;
+        } else {
+            let end = tail.find('}').ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!("unterminated placeholder in {input:?}"))
+            })?;
+            out.push_str(ctx.lookup(&tail[1..end])?);
+            rest = &tail[end + 1..];
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Whether `input` references `{exec.id}` (ignoring escaped braces).
+fn references_exec_id(input: &str) -> bool {
+    input.replace("{{", "").contains("{exec.id}")
+}
+
+impl BoxCommand {
+    /// Render for a one-off exec against `info`.
+    ///
+    /// Pre-assigns the execution ID when `{exec.id}` is referenced, so the
+    /// backend must start the execution under `self.execution_id`.
+    pub(crate) fn render_for_exec(mut self, info: &BoxInfo) -> BoxliteResult<Self> {
+        if self.needs_exec_id() {
+            self.execution_id = Some(uuid::Uuid::new_v4().to_string());
+        }
+        let exec_id = self.execution_id.clone();
+        self.render(&TemplateContext::new(info, exec_id.as_deref()))
+    }
+
+    /// Render a prepared command template; `{exec.id}` is rejected.
+    pub(crate) fn render_for_prepare(self, info: &BoxInfo) -> BoxliteResult<Self> {
+        self.render(&TemplateContext::new(info, None))
+    }
+
+    /// Whether templating is on and some field uses `{exec.id}`.
+    fn needs_exec_id(&self) -> bool {
+        self.templating
+            && (self.args.iter().any(|a| references_exec_id(a))
+                || self.working_dir.as_deref().is_some_and(references_exec_id)
+                || self
+                    .env
+                    .iter()
+                    .flatten()
+                    .any(|(_, v)| references_exec_id(v))
+                || self
+                    .env_edits
+                    .iter()
+                    .filter_map(EnvEdit::value)
+                    .any(references_exec_id))
+    }
+
+    /// Expand placeholders in args, working dir, env values and the values
+    /// of env edits.
+    ///
+    /// Returns the command unchanged when templating is disabled.
+    fn render(mut self, ctx: &TemplateContext<'_>) -> BoxliteResult<Self> {
+        if !self.templating {
+            return Ok(self);
+        }
+
+        for arg in &mut self.args {
+            *arg = expand(arg, ctx)?;
+        }
+        if let Some(dir) = &mut self.working_dir {
+            *dir = expand(dir, ctx)?;
+        }
+        for (_, value) in self.env.iter_mut().flatten() {
+            *value = expand(value, ctx)?;
+        }
+        for value in self.env_edits.iter_mut().filter_map(EnvEdit::value_mut) {
+            *value = expand(value, ctx)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx(name: Option<&'static str>, exec_id: Option<&'static str>) -> TemplateContext<'static> {
+        TemplateContext {
+            box_id: "01HBOX".into(),
+            box_name: name,
+            image: "alpine:latest",
+            exec_id,
+        }
+    }
+
+    #[test]
+    fn test_expand_placeholders() {
+        let ctx = ctx(Some("web"), Some("exec-1"));
+        assert_eq!(
+            expand("/data/{box.name}/{exec.id}", &ctx).unwrap(),
+            "/data/web/exec-1"
+        );
+        assert_eq!(
+            expand("{box.id}@{box.image}", &ctx).unwrap(),
+            "01HBOX@alpine:latest"
+        );
+        assert_eq!(expand("plain", &ctx).unwrap(), "plain");
+    }
+
+    #[test]
+    fn test_expand_escapes() {
+        let ctx = ctx(Some("web"), None);
+        assert_eq!(expand("{{box.name}}", &ctx).unwrap(), "{box.name}");
+        assert_eq!(
+            expand("awk '{{print $1}}' {box.name}", &ctx).unwrap(),
+            "awk '{print $1}' web"
+        );
+        assert_eq!(expand("a } b", &ctx).unwrap(), "a } b");
+    }
+
+    #[test]
+    fn test_expand_rejects_unknown_and_unterminated() {
+        let ctx = ctx(Some("web"), None);
+        assert!(matches!(
+            expand("{box.nmae}", &ctx),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            expand("/data/{box.name", &ctx),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_expand_missing_values() {
+        let ctx = ctx(None, None);
+        assert!(matches!(
+            expand("{box.name}", &ctx),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            expand("{exec.id}", &ctx),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn test_render_is_opt_in() {
+        let ctx = ctx(Some("web"), None);
+        let cmd = BoxCommand::new("awk")
+            .arg("{print $1}")
+            .render(&ctx)
+            .unwrap();
+        assert_eq!(cmd.args, vec!["{print $1}"]);
+
+        let cmd = BoxCommand::new("ls")
+            .arg("/data/{box.name}")
+            .env("BOX", "{box.id}")
+            .env_prepend_path("PATH", "/srv/{box.name}/bin", ":")
+            .working_dir("/srv/{box.name}")
+            .enable_templating(true)
+            .render(&ctx)
+            .unwrap();
+        assert_eq!(cmd.args, vec!["/data/web"]);
+        assert_eq!(cmd.env, Some(vec![("BOX".into(), "01HBOX".into())]));
+        assert_eq!(
+            cmd.env_edits[0].value(),
+            Some("/srv/web/bin"),
+            "env edit values are templated too"
+        );
+        assert_eq!(cmd.working_dir.as_deref(), Some("/srv/web"));
+    }
+
+    #[test]
+    fn test_needs_exec_id() {
+        let cmd = BoxCommand::new("echo").arg("{exec.id}");
+        assert!(!cmd.needs_exec_id());
+        assert!(cmd.enable_templating(true).needs_exec_id());
+
+        let escaped = BoxCommand::new("echo")
+            .arg("{{exec.id}}")
+            .enable_templating(true);
+        assert!(!escaped.needs_exec_id());
+
+        let edit = BoxCommand::new("env")
+            .env_append_path("TAGS", "{exec.id}", ",")
+            .enable_templating(true);
+        assert!(edit.needs_exec_id());
+    }
+}
@@ -0,0 +1,572 @@
+//! Resumable copy into a box.
+//!
+//! The source is sent as a series of tar batches instead of one archive.
+//! After each batch lands, the files it carried are recorded in a manifest
+//! under the box's home directory. A copy that stops early, because its
+//! deadline passed, the caller dropped it, or the upload failed, can be
+//! started again with `resume` and sends only the files the manifest does
+//! not already list.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use async_trait::async_trait;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use boxlite_shared::{TransferManifest, TransferredFile};
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+
+use super::copy::CopyOptions;
+
+/// Directory under the box home holding manifests of unfinished copies.
+const TRANSFERS_DIR: &str = "transfers";
+
+/// A batch is closed once it carries this many bytes of file data...
+const BATCH_BYTES: u64 = 64 * 1024 * 1024;
+
+/// ...or this many files, whichever comes first.
+const BATCH_FILES: usize = 1024;
+
+/// Sends one tar batch into the box.
+#[async_trait]
+pub(crate) trait BatchUploader: Send {
+    /// Extract `tar_path` at the copy destination.
+    async fn upload(&mut self, tar_path: &Path, overwrite: bool) -> BoxliteResult<()>;
+}
+
+/// One entry of the source tree.
+#[derive(Debug, Clone)]
+struct SourceEntry {
+    host_path: PathBuf,
+    /// Path inside the archive.
+    name: String,
+    kind: EntryKind,
+    size: u64,
+    mtime_ns: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EntryKind {
+    Dir,
+    File,
+}
+
+impl SourceEntry {
+    fn matches(&self, done: &TransferredFile) -> bool {
+        self.size == done.size && self.mtime_ns == done.mtime_ns
+    }
+
+    fn record(&self) -> TransferredFile {
+        TransferredFile {
+            path: self.name.clone(),
+            size: self.size,
+            mtime_ns: self.mtime_ns,
+        }
+    }
+}
+
+/// A copy of one host path to one box path, sent in batches.
+pub(crate) struct ResumableCopy {
+    source: PathBuf,
+    destination: String,
+    opts: CopyOptions,
+    manifest_path: PathBuf,
+    temp_dir: PathBuf,
+    batch_bytes: u64,
+    batch_files: usize,
+}
+
+impl ResumableCopy {
+    /// `box_home` holds the progress manifest; batch archives are written
+    /// to `temp_dir` and removed once sent.
+    pub(crate) fn new(
+        source: &Path,
+        destination: &str,
+        opts: CopyOptions,
+        box_home: &Path,
+        temp_dir: &Path,
+    ) -> Self {
+        let source = std::fs::canonicalize(source).unwrap_or_else(|_| source.to_path_buf());
+        let manifest_path = box_home.join(TRANSFERS_DIR).join(format!(
+            "{}.json",
+            transfer_key(&source, destination, &opts)
+        ));
+        Self {
+            source,
+            destination: destination.to_string(),
+            opts,
+            manifest_path,
+            temp_dir: temp_dir.to_path_buf(),
+            batch_bytes: BATCH_BYTES,
+            batch_files: BATCH_FILES,
+        }
+    }
+
+    /// Send every file not yet in the box.
+    ///
+    /// Returns `BoxliteError::PartialTransfer` if the deadline passes first.
+    /// Progress is kept on any failure, and dropping the future between
+    /// batches loses at most the batch in flight.
+    pub(crate) async fn run(&self, uploader: &mut dyn BatchUploader) -> BoxliteResult<()> {
+        let deadline = self.opts.deadline.map(|d| Instant::now() + d);
+
+        let source = self.source.clone();
+        let (follow, include_parent) = (self.opts.follow_symlinks, self.opts.include_parent);
+        let entries =
+            tokio::task::spawn_blocking(move || scan_source(&source, follow, include_parent))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("source scan task failed: {}", e)))??;
+
+        let mut manifest = self.load_manifest();
+        let (dirs, files): (Vec<_>, Vec<_>) =
+            entries.into_iter().partition(|e| e.kind == EntryKind::Dir);
+
+        // Files that changed since they were sent go again.
+        let mut pending = Vec::new();
+        let mut kept = Vec::new();
+        {
+            let done: HashMap<&str, &TransferredFile> = manifest
+                .completed
+                .iter()
+                .map(|f| (f.path.as_str(), f))
+                .collect();
+            for file in &files {
+                match done.get(file.name.as_str()) {
+                    Some(d) if file.matches(d) => kept.push(file.record()),
+                    _ => pending.push(file.clone()),
+                }
+            }
+        }
+        let resuming = !kept.is_empty();
+        manifest.completed = kept;
+        manifest.total_files = files.len() as u64;
+        manifest.total_bytes = files.iter().map(|f| f.size).sum();
+
+        // Directories all go in the first batch so later batches always
+        // extract into an existing tree.
+        let mut batches = self.split_batches(pending);
+        if let Some(first) = batches.first_mut() {
+            first.splice(0..0, dirs);
+        } else if !dirs.is_empty() {
+            batches.push(dirs);
+        }
+
+        for (index, batch) in batches.into_iter().enumerate() {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                return Err(self.stop(manifest));
+            }
+
+            let tar_path = self.temp_dir.join(format!(
+                "cp-in-{}-{}.tar",
+                &transfer_key(&self.source, &self.destination, &self.opts)[..12],
+                index
+            ));
+            let archive = batch.clone();
+            let archive_path = tar_path.clone();
+            tokio::task::spawn_blocking(move || write_batch(&archive, &archive_path, follow))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("archive task failed: {}", e)))??;
+
+            // Only the first write into the destination is subject to the
+            // caller's overwrite choice; later batches add to it.
+            let overwrite = self.opts.overwrite || index > 0 || resuming;
+            let sent = match deadline {
+                Some(d) => tokio::time::timeout_at(d, uploader.upload(&tar_path, overwrite)).await,
+                None => Ok(uploader.upload(&tar_path, overwrite).await),
+            };
+            let _ = std::fs::remove_file(&tar_path);
+            match sent {
+                Ok(result) => result?,
+                Err(_) => return Err(self.stop(manifest)),
+            }
+
+            manifest.completed.extend(
+                batch
+                    .iter()
+                    .filter(|e| e.kind == EntryKind::File)
+                    .map(SourceEntry::record),
+            );
+            self.save_manifest(&manifest)?;
+        }
+
+        let _ = std::fs::remove_file(&self.manifest_path);
+        Ok(())
+    }
+
+    fn split_batches(&self, files: Vec<SourceEntry>) -> Vec<Vec<SourceEntry>> {
+        let mut batches = Vec::new();
+        let mut current = Vec::new();
+        let mut bytes = 0;
+        for file in files {
+            if !current.is_empty()
+                && (current.len() >= self.batch_files || bytes + file.size > self.batch_bytes)
+            {
+                batches.push(std::mem::take(&mut current));
+                bytes = 0;
+            }
+            bytes += file.size;
+            current.push(file);
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+        batches
+    }
+
+    /// The manifest to continue from: the saved one when resuming, else empty.
+    fn load_manifest(&self) -> TransferManifest {
+        let fresh = TransferManifest {
+            source: self.source.clone(),
+            destination: self.destination.clone(),
+            ..Default::default()
+        };
+        if !self.opts.resume {
+            return fresh;
+        }
+        std::fs::read(&self.manifest_path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<TransferManifest>(&data).ok())
+            .filter(|m| m.source == fresh.source && m.destination == fresh.destination)
+            .unwrap_or(fresh)
+    }
+
+    fn save_manifest(&self, manifest: &TransferManifest) -> BoxliteResult<()> {
+        let write = || -> std::io::Result<()> {
+            if let Some(dir) = self.manifest_path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            let tmp = self.manifest_path.with_extension("json.tmp");
+            std::fs::write(&tmp, serde_json::to_vec_pretty(manifest)?)?;
+            std::fs::rename(&tmp, &self.manifest_path)
+        };
+        write().map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to write transfer manifest to {}: {}",
+                self.manifest_path.display(),
+                e
+            ))
+        })
+    }
+
+    fn stop(&self, manifest: TransferManifest) -> BoxliteError {
+        tracing::info!(
+            manifest = %self.manifest_path.display(),
+            "Copy reached its deadline: {}",
+            manifest
+        );
+        BoxliteError::PartialTransfer(Box::new(manifest))
+    }
+}
+
+/// Name of the manifest for a source/destination pair.
+///
+/// Archive layout options are part of the key, since they change the
+/// archive paths recorded in the manifest.
+fn transfer_key(source: &Path, destination: &str, opts: &CopyOptions) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(source.as_os_str().as_encoded_bytes());
+    hasher.update(b"\0");
+    hasher.update(destination.as_bytes());
+    hasher.update([opts.include_parent as u8, opts.follow_symlinks as u8]);
+    format!("{:x}", hasher.finalize())[..32].to_string()
+}
+
+/// List the source tree with the archive names `build_tar_from_host` uses.
+fn scan_source(
+    src: &Path,
+    follow_symlinks: bool,
+    include_parent: bool,
+) -> BoxliteResult<Vec<SourceEntry>> {
+    let scan_err = |e: &dyn std::fmt::Display| {
+        BoxliteError::Storage(format!("failed to scan {}: {}", src.display(), e))
+    };
+
+    if !src.is_dir() {
+        let name = src
+            .file_name()
+            .ok_or_else(|| BoxliteError::Config("source file has no name".into()))?;
+        let meta = if follow_symlinks {
+            std::fs::metadata(src)
+        } else {
+            std::fs::symlink_metadata(src)
+        }
+        .map_err(|e| scan_err(&e))?;
+        return Ok(vec![SourceEntry {
+            host_path: src.to_path_buf(),
+            name: name.to_string_lossy().into_owned(),
+            kind: EntryKind::File,
+            size: meta.len(),
+            mtime_ns: mtime_ns(&meta),
+        }]);
+    }
+
+    let base = if include_parent {
+        src.file_name()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "root".to_string())
+    } else {
+        ".".to_string()
+    };
+
+    let mut entries = Vec::new();
+    for entry in walkdir::WalkDir::new(src)
+        .follow_links(follow_symlinks)
+        .sort_by_file_name()
+    {
+        let entry = entry.map_err(|e| scan_err(&e))?;
+        let meta = entry.metadata().map_err(|e| scan_err(&e))?;
+        let rel = entry.path().strip_prefix(src).unwrap_or(entry.path());
+        let name = if rel.as_os_str().is_empty() {
+            base.clone()
+        } else {
+            format!("{}/{}", base, rel.to_string_lossy())
+        };
+        let kind = if meta.is_dir() {
+            EntryKind::Dir
+        } else if meta.is_file() || meta.file_type().is_symlink() {
+            EntryKind::File
+        } else {
+            tracing::debug!(path = %entry.path().display(), "Skipping special file");
+            continue;
+        };
+        entries.push(SourceEntry {
+            host_path: entry.path().to_path_buf(),
+            name,
+            kind,
+            size: if kind == EntryKind::File {
+                meta.len()
+            } else {
+                0
+            },
+            mtime_ns: mtime_ns(&meta),
+        });
+    }
+    Ok(entries)
+}
+
+fn mtime_ns(meta: &std::fs::Metadata) -> i64 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+fn write_batch(batch: &[SourceEntry], tar_path: &Path, follow: bool) -> BoxliteResult<()> {
+    let tar_file = std::fs::File::create(tar_path).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "failed to create tar {}: {}",
+            tar_path.display(),
+            e
+        ))
+    })?;
+    let mut builder = tar::Builder::new(tar_file);
+    builder.follow_symlinks(follow);
+    for entry in batch {
+        let appended = match entry.kind {
+            EntryKind::Dir => builder.append_dir(&entry.name, &entry.host_path),
+            EntryKind::File => builder.append_path_with_name(&entry.host_path, &entry.name),
+        };
+        appended.map_err(|e| {
+            BoxliteError::Storage(format!(
+                "failed to archive {}: {}",
+                entry.host_path.display(),
+                e
+            ))
+        })?;
+    }
+    builder
+        .finish()
+        .map_err(|e| BoxliteError::Storage(format!("failed to finish tar: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// Unpacks batches into a local directory, optionally hanging on one.
+    struct LocalUploader {
+        dest: PathBuf,
+        calls: usize,
+        hang_on: Option<usize>,
+        files: Vec<String>,
+    }
+
+    impl LocalUploader {
+        fn new(dest: &Path, hang_on: Option<usize>) -> Self {
+            Self {
+                dest: dest.to_path_buf(),
+                calls: 0,
+                hang_on,
+                files: Vec::new(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BatchUploader for LocalUploader {
+        async fn upload(&mut self, tar_path: &Path, _overwrite: bool) -> BoxliteResult<()> {
+            self.calls += 1;
+            if self.hang_on == Some(self.calls) {
+                std::future::pending::<()>().await;
+            }
+            let mut archive = tar::Archive::new(std::fs::File::open(tar_path).unwrap());
+            for entry in archive.entries().unwrap() {
+                let mut entry = entry.unwrap();
+                if entry.header().entry_type().is_file() {
+                    self.files
+                        .push(entry.path().unwrap().to_string_lossy().into_owned());
+                }
+                entry.unpack_in(&self.dest).unwrap();
+            }
+            Ok(())
+        }
+    }
+
+    struct Fixture {
+        _tmp: TempDir,
+        src: PathBuf,
+        dest: PathBuf,
+        home: PathBuf,
+    }
+
+    fn fixture(files: usize) -> Fixture {
+        let tmp = TempDir::new().unwrap();
+        let src = tmp.path().join("data");
+        std::fs::create_dir_all(src.join("sub")).unwrap();
+        for i in 0..files {
+            let dir = if i % 2 == 0 {
+                src.clone()
+            } else {
+                src.join("sub")
+            };
+            std::fs::write(dir.join(format!("f{i}")), vec![b'x'; 100 + i]).unwrap();
+        }
+        let dest = tmp.path().join("dest");
+        let home = tmp.path().join("home");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::create_dir_all(&home).unwrap();
+        Fixture {
+            _tmp: tmp,
+            src,
+            dest,
+            home,
+        }
+    }
+
+    fn copy(fx: &Fixture, opts: CopyOptions) -> ResumableCopy {
+        let mut copy = ResumableCopy::new(&fx.src, "/dest", opts, &fx.home, &fx.home);
+        copy.batch_files = 2;
+        copy
+    }
+
+    fn dest_files(fx: &Fixture) -> usize {
+        walkdir::WalkDir::new(&fx.dest)
+            .into_iter()
+            .filter(|e| e.as_ref().unwrap().file_type().is_file())
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_dropped_copy_resumes_with_remainder() {
+        let fx = fixture(6);
+
+        let mut first = LocalUploader::new(&fx.dest, Some(2));
+        let interrupted = tokio::time::timeout(
+            Duration::from_millis(200),
+            copy(&fx, CopyOptions::default().resume(true)).run(&mut first),
+        )
+        .await;
+        assert!(interrupted.is_err(), "copy should still be in flight");
+        assert_eq!(first.files.len(), 2);
+
+        let mut second = LocalUploader::new(&fx.dest, None);
+        copy(&fx, CopyOptions::default().resume(true))
+            .run(&mut second)
+            .await
+            .unwrap();
+
+        assert_eq!(second.files.len(), 4);
+        assert!(second.files.iter().all(|f| !first.files.contains(f)));
+        assert_eq!(dest_files(&fx), 6);
+        assert!(
+            !fx.home
+                .join(TRANSFERS_DIR)
+                .read_dir()
+                .unwrap()
+                .any(|_| true)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deadline_returns_partial_transfer() {
+        let fx = fixture(6);
+
+        let mut uploader = LocalUploader::new(&fx.dest, Some(3));
+        let opts = CopyOptions::default().deadline(Duration::from_millis(200));
+        let err = copy(&fx, opts).run(&mut uploader).await.unwrap_err();
+
+        let manifest = match err {
+            BoxliteError::PartialTransfer(m) => m,
+            other => panic!("expected PartialTransfer, got {other:?}"),
+        };
+        assert_eq!(manifest.completed.len(), 4);
+        assert_eq!(manifest.total_files, 6);
+        assert_eq!(manifest.destination, "/dest");
+
+        let mut rest = LocalUploader::new(&fx.dest, None);
+        copy(&fx, CopyOptions::default().resume(true))
+            .run(&mut rest)
+            .await
+            .unwrap();
+        assert_eq!(rest.files.len(), 2);
+        assert_eq!(dest_files(&fx), 6);
+    }
+
+    #[tokio::test]
+    async fn test_changed_file_is_sent_again() {
+        let fx = fixture(4);
+
+        let mut first = LocalUploader::new(&fx.dest, Some(2));
+        let _ = tokio::time::timeout(
+            Duration::from_millis(200),
+            copy(&fx, CopyOptions::default().resume(true)).run(&mut first),
+        )
+        .await;
+        let changed = fx.src.join(&first.files[0]["data/".len()..]);
+        std::fs::write(&changed, b"changed").unwrap();
+
+        let mut second = LocalUploader::new(&fx.dest, None);
+        copy(&fx, CopyOptions::default().resume(true))
+            .run(&mut second)
+            .await
+            .unwrap();
+        assert_eq!(second.files.len(), 3);
+        assert!(second.files.contains(&first.files[0]));
+    }
+
+    #[tokio::test]
+    async fn test_without_resume_sends_everything() {
+        let fx = fixture(4);
+
+        let mut first = LocalUploader::new(&fx.dest, Some(2));
+        let _ = tokio::time::timeout(
+            Duration::from_millis(200),
+            copy(&fx, CopyOptions::default().resume(true)).run(&mut first),
+        )
+        .await;
+
+        let mut second = LocalUploader::new(&fx.dest, None);
+        copy(
+            &fx,
+            CopyOptions::default().deadline(Duration::from_secs(60)),
+        )
+        .run(&mut second)
+        .await
+        .unwrap();
+        assert_eq!(second.files.len(), 4);
+    }
+}
@@ -0,0 +1,296 @@
+//! Host-side TCP tunnels into a running box.
+//!
+//! A tunnel listens on a host address and relays every accepted connection
+//! to `127.0.0.1:<guest_port>` inside the box through the guest agent. All
+//! connections of one tunnel share a single relay stream and are told apart
+//! by a per-connection id, so no gvproxy port mapping or restart is needed.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use boxlite_shared::{BoxliteResult, TunnelClose, TunnelFrame, TunnelOpen, tunnel_frame::Kind};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tonic::Streaming;
+
+use crate::portal::interfaces::TunnelInterface;
+
+const READ_BUF_SIZE: usize = 32 * 1024;
+/// Frames buffered towards a single connection before the relay backs off.
+const CONN_QUEUE: usize = 64;
+/// Frames buffered towards the guest, shared by all connections.
+const OUTBOUND_QUEUE: usize = 256;
+
+/// A running tunnel from a host address into a box.
+///
+/// Dropping the handle closes the listener and every relayed connection.
+/// The tunnel also closes on its own when the box stops.
+#[derive(Debug)]
+pub struct TunnelHandle {
+    local_addr: SocketAddr,
+    guest_port: u16,
+    cancel: CancellationToken,
+}
+
+impl TunnelHandle {
+    /// Start relaying connections accepted on `listener` to `guest_port`.
+    ///
+    /// The tunnel stops when `cancel` is cancelled, the handle is dropped,
+    /// or the relay stream ends.
+    pub(crate) async fn open(
+        listener: TcpListener,
+        guest_port: u16,
+        mut iface: TunnelInterface,
+        cancel: CancellationToken,
+    ) -> BoxliteResult<Self> {
+        let local_addr = listener.local_addr()?;
+        let (outbound, outbound_rx) = mpsc::channel(OUTBOUND_QUEUE);
+        let inbound = iface.relay(outbound_rx).await?;
+
+        tokio::spawn(serve(
+            listener,
+            guest_port,
+            inbound,
+            outbound,
+            cancel.clone(),
+        ));
+        tracing::info!(%local_addr, guest_port, "Tunnel opened");
+
+        Ok(Self {
+            local_addr,
+            guest_port,
+            cancel,
+        })
+    }
+
+    /// Host address the tunnel is listening on.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Port inside the box that connections are relayed to.
+    pub fn guest_port(&self) -> u16 {
+        self.guest_port
+    }
+
+    /// Wait until the tunnel has closed, e.g. because the box stopped.
+    pub async fn closed(&self) {
+        self.cancel.cancelled().await
+    }
+}
+
+impl Drop for TunnelHandle {
+    fn drop(&mut self) {
+        self.cancel.cancel();
+    }
+}
+
+/// A relayed connection, as seen by the serve loop.
+struct Conn {
+    /// Data from the guest; `None` once the guest has closed its side.
+    to_host: Option<mpsc::Sender<Vec<u8>>>,
+    task: JoinHandle<()>,
+}
+
+/// Accept connections and route guest frames until the tunnel closes.
+async fn serve(
+    listener: TcpListener,
+    guest_port: u16,
+    mut inbound: Streaming<TunnelFrame>,
+    outbound: mpsc::Sender<TunnelFrame>,
+    cancel: CancellationToken,
+) {
+    let mut conns: HashMap<u64, Conn> = HashMap::new();
+    let mut next_id: u64 = 0;
+
+    loop {
+        tokio::select! {
+            _ = cancel.cancelled() => break,
+            accepted = listener.accept() => {
+                let (socket, peer) = match accepted {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Tunnel accept failed");
+                        continue;
+                    }
+                };
+                conns.retain(|_, conn| !conn.task.is_finished());
+                next_id += 1;
+                let open = TunnelFrame {
+                    conn_id: next_id,
+                    kind: Some(Kind::Open(TunnelOpen {
+                        port: guest_port.into(),
+                    })),
+                };
+                if outbound.send(open).await.is_err() {
+                    break;
+                }
+                tracing::debug!(conn_id = next_id, %peer, guest_port, "Tunnel connection");
+                let (tx, rx) = mpsc::channel(CONN_QUEUE);
+                let task = tokio::spawn(relay_conn(next_id, socket, rx, outbound.clone()));
+                conns.insert(next_id, Conn { to_host: Some(tx), task });
+            }
+            frame = inbound.message() => {
+                let frame = match frame {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::debug!(error = %e, "Tunnel relay stream ended");
+                        break;
+                    }
+                };
+                route(&mut conns, frame).await;
+            }
+        }
+    }
+
+    for conn in conns.into_values() {
+        conn.task.abort();
+    }
+    cancel.cancel();
+    tracing::info!(guest_port, "Tunnel closed");
+}
+
+/// Deliver one guest frame to its connection.
+async fn route(conns: &mut HashMap<u64, Conn>, frame: TunnelFrame) {
+    let conn_id = frame.conn_id;
+    match frame.kind {
+        Some(Kind::Data(data)) => {
+            if let Some(tx) = conns.get(&conn_id).and_then(|c| c.to_host.as_ref()) {
+                let _ = tx.send(data).await;
+            }
+        }
+        Some(Kind::Close(TunnelClose { error: Some(error) })) => {
+            tracing::debug!(conn_id, %error, "Tunnel connection failed in guest");
+            if let Some(conn) = conns.remove(&conn_id) {
+                conn.task.abort();
+            }
+        }
+        Some(Kind::Close(TunnelClose { error: None })) => {
+            if let Some(conn) = conns.get_mut(&conn_id) {
+                conn.to_host = None;
+            }
+        }
+        Some(Kind::Open(_)) | None => {}
+    }
+}
+
+/// Pump bytes between an accepted socket and the relay stream.
+///
+/// Each direction closes independently; an I/O error on either aborts the
+/// connection and is reported to the guest.
+async fn relay_conn(
+    conn_id: u64,
+    socket: TcpStream,
+    mut from_guest: mpsc::Receiver<Vec<u8>>,
+    outbound: mpsc::Sender<TunnelFrame>,
+) {
+    let (mut reader, mut writer) = socket.into_split();
+
+    let to_guest = async {
+        let mut buf = vec![0u8; READ_BUF_SIZE];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            let kind = if n == 0 {
+                Kind::Close(TunnelClose { error: None })
+            } else {
+                Kind::Data(buf[..n].to_vec())
+            };
+            let frame = TunnelFrame {
+                conn_id,
+                kind: Some(kind),
+            };
+            if outbound.send(frame).await.is_err() {
+                return Err(std::io::Error::other("tunnel relay stream closed"));
+            }
+            if n == 0 {
+                return Ok(());
+            }
+        }
+    };
+    let to_host = async {
+        while let Some(data) = from_guest.recv().await {
+            writer.write_all(&data).await?;
+        }
+        writer.shutdown().await
+    };
+
+    if let Err(e) = tokio::try_join!(to_guest, to_host) {
+        let frame = TunnelFrame {
+            conn_id,
+            kind: Some(Kind::Close(TunnelClose {
+                error: Some(e.to_string()),
+            })),
+        };
+        let _ = outbound.send(frame).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        (client, server)
+    }
+
+    #[tokio::test]
+    async fn test_relay_conn_frames_socket_data_and_half_close() {
+        let (mut client, accepted) = socket_pair().await;
+        let (to_conn, from_guest) = mpsc::channel(CONN_QUEUE);
+        let (outbound, mut rx) = mpsc::channel(OUTBOUND_QUEUE);
+        let task = tokio::spawn(relay_conn(3, accepted, from_guest, outbound));
+
+        client.write_all(b"GET /").await.unwrap();
+        client.shutdown().await.unwrap();
+        let frame = rx.recv().await.unwrap();
+        assert_eq!(frame.conn_id, 3);
+        assert_eq!(frame.kind, Some(Kind::Data(b"GET /".to_vec())));
+        let frame = rx.recv().await.unwrap();
+        assert_eq!(frame.kind, Some(Kind::Close(TunnelClose { error: None })));
+
+        // The guest can still answer after the client half-closed
+        to_conn.send(b"200 OK".to_vec()).await.unwrap();
+        drop(to_conn);
+        let mut response = Vec::new();
+        client.read_to_end(&mut response).await.unwrap();
+        assert_eq!(response, b"200 OK");
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_guest_error_aborts_connection() {
+        let (mut client, accepted) = socket_pair().await;
+        let (tx, rx) = mpsc::channel(CONN_QUEUE);
+        let (outbound, _outbound_rx) = mpsc::channel(OUTBOUND_QUEUE);
+        let task = tokio::spawn(relay_conn(5, accepted, rx, outbound));
+        let mut conns = HashMap::from([(
+            5,
+            Conn {
+                to_host: Some(tx),
+                task,
+            },
+        )]);
+
+        let refused = TunnelFrame {
+            conn_id: 5,
+            kind: Some(Kind::Close(TunnelClose {
+                error: Some("connection refused".into()),
+            })),
+        };
+        route(&mut conns, refused).await;
+
+        assert!(conns.is_empty());
+        let mut buf = Vec::new();
+        let read = client.read_to_end(&mut buf).await;
+        assert!(read.is_err() || buf.is_empty());
+    }
+}
@@ -7,12 +7,17 @@
 //! Two implementations are provided:
 //! - [`InMemoryLockManager`]: Single-process locks for testing
 //! - [`FileLockManager`]: Cross-process locks using flock(2)
+//!
+//! [`OperationLock`] layers non-blocking acquisition with holder reporting
+//! on top of a box's lock for mutating box operations.
 
 mod file;
 mod memory;
+mod operation;
 
 pub use file::FileLockManager;
 pub use memory::InMemoryLockManager;
+pub(crate) use operation::{BoxOperation, OperationLock};
 
 use std::sync::Arc;
 
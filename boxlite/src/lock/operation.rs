@@ -0,0 +1,242 @@
+//! Non-blocking per-box operation lock.
+//!
+//! Mutating box operations (start, stop, remove, resource limit updates,
+//! snapshot, restore, export, clone, compact, adopt) and read-only mounts
+//! hold the box's [`Locker`] for their whole duration, so two handles to
+//! the same box cannot interleave them, whether they live in one process
+//! or several. Read-only operations (info, metrics, exec on a running
+//! box) never take it.
+//!
+//! A contender does not block on the lock: it fails with
+//! [`BoxliteError::Busy`] naming the holder's operation and pid, or polls
+//! for up to the configured wait. The holder records itself in a small
+//! sidecar file next to the lock so contenders can report who they are
+//! waiting on. The kernel releases the lock when its holder exits, so a
+//! crashed process never leaves a box locked.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::{Deserialize, Serialize};
+
+use super::Locker;
+
+/// How often a waiting contender retries the lock.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// A mutating box operation, as recorded by the lock holder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BoxOperation {
+    Start,
+    Stop,
+    Remove,
+    Resize,
+    Snapshot,
+    Restore,
+    Export,
+    Clone,
+    Compact,
+    Adopt,
+    Detach,
+    /// Held for as long as the box's disk is mounted on the host.
+    #[cfg(feature = "fuse")]
+    Mount,
+}
+
+impl BoxOperation {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            Self::Start => "start",
+            Self::Stop => "stop",
+            Self::Remove => "remove",
+            Self::Resize => "resize",
+            Self::Snapshot => "snapshot",
+            Self::Restore => "restore",
+            Self::Export => "export",
+            Self::Clone => "clone",
+            Self::Compact => "compact",
+            Self::Adopt => "adopt",
+            Self::Detach => "detach",
+            #[cfg(feature = "fuse")]
+            Self::Mount => "mount",
+        }
+    }
+}
+
+/// Contents of the holder file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Holder {
+    pid: u32,
+    operation: String,
+}
+
+impl Holder {
+    /// Read the current holder. A holder that has not written yet (or a
+    /// missing file) reads as pid 0, operation "unknown".
+    fn read(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_else(|| Self {
+                pid: 0,
+                operation: "unknown".to_string(),
+            })
+    }
+
+    fn into_busy(self) -> BoxliteError {
+        BoxliteError::Busy {
+            operation: self.operation,
+            pid: self.pid,
+        }
+    }
+}
+
+/// Held operation lock. Releases the lock and clears the holder on drop.
+pub(crate) struct OperationLock {
+    locker: Arc<dyn Locker>,
+    holder_path: PathBuf,
+}
+
+impl OperationLock {
+    /// Acquire `locker` for `operation`.
+    ///
+    /// With `wait` of `None`, fails with `Busy` right away if the lock is
+    /// held; otherwise retries until `wait` has elapsed.
+    pub(crate) async fn acquire(
+        locker: Arc<dyn Locker>,
+        holder_path: PathBuf,
+        operation: BoxOperation,
+        wait: Option<Duration>,
+    ) -> BoxliteResult<Self> {
+        // checked_add: an effectively unbounded wait (Duration::MAX) has no deadline
+        let deadline = wait.map(|wait| Instant::now().checked_add(wait));
+        loop {
+            if locker.try_lock() {
+                let holder = Holder {
+                    pid: std::process::id(),
+                    operation: operation.as_str().to_string(),
+                };
+                if let Err(e) = std::fs::write(&holder_path, serde_json::to_vec(&holder)?) {
+                    // Only used for reporting, the lock itself is held
+                    tracing::debug!(
+                        path = %holder_path.display(),
+                        error = %e,
+                        "Failed to record operation lock holder"
+                    );
+                }
+                return Ok(Self {
+                    locker,
+                    holder_path,
+                });
+            }
+
+            let keep_waiting = match deadline {
+                None => false,
+                Some(None) => true,
+                Some(Some(deadline)) => Instant::now() < deadline,
+            };
+            if !keep_waiting {
+                return Err(Holder::read(&holder_path).into_busy());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+impl Drop for OperationLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.holder_path);
+        self.locker.unlock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lock::{FileLockManager, LockId, LockManager};
+    use tempfile::TempDir;
+
+    fn setup() -> (FileLockManager, LockId, PathBuf, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = FileLockManager::new(temp_dir.path()).unwrap();
+        let id = manager.allocate().unwrap();
+        let holder_path = temp_dir.path().join(format!("{id}.holder"));
+        (manager, id, holder_path, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn test_busy_reports_holder() {
+        let (manager, id, holder_path, _temp) = setup();
+        let locker = || manager.retrieve(id).unwrap();
+
+        let held = OperationLock::acquire(locker(), holder_path.clone(), BoxOperation::Stop, None)
+            .await
+            .unwrap();
+
+        let err = OperationLock::acquire(locker(), holder_path.clone(), BoxOperation::Start, None)
+            .await
+            .err()
+            .unwrap();
+        let BoxliteError::Busy { operation, pid } = err else {
+            panic!("expected Busy, got {err}");
+        };
+        assert_eq!(operation, "stop");
+        assert_eq!(pid, std::process::id());
+
+        drop(held);
+        assert!(!holder_path.exists());
+        OperationLock::acquire(locker(), holder_path, BoxOperation::Start, None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_acquires_after_release() {
+        let (manager, id, holder_path, _temp) = setup();
+        let locker = || manager.retrieve(id).unwrap();
+
+        let held = OperationLock::acquire(locker(), holder_path.clone(), BoxOperation::Stop, None)
+            .await
+            .unwrap();
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(150)).await;
+            drop(held);
+        });
+
+        OperationLock::acquire(
+            locker(),
+            holder_path,
+            BoxOperation::Start,
+            Some(Duration::from_secs(5)),
+        )
+        .await
+        .unwrap();
+        release.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_wait_times_out() {
+        let (manager, id, holder_path, _temp) = setup();
+        let locker = || manager.retrieve(id).unwrap();
+
+        let _held =
+            OperationLock::acquire(locker(), holder_path.clone(), BoxOperation::Export, None)
+                .await
+                .unwrap();
+
+        let started = Instant::now();
+        let err = OperationLock::acquire(
+            locker(),
+            holder_path,
+            BoxOperation::Remove,
+            Some(Duration::from_millis(200)),
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(matches!(err, BoxliteError::Busy { ref operation, .. } if operation == "export"));
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
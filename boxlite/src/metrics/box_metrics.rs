@@ -164,6 +164,8 @@ pub struct BoxMetrics {
     pub cpu_percent: Option<f32>,
     /// Memory usage in bytes
     pub memory_bytes: Option<u64>,
+    /// Swap in use inside the VM in bytes
+    pub swap_bytes: Option<u64>,
     /// Network bytes sent (host to guest)
     pub network_bytes_sent: Option<u64>,
     /// Network bytes received (guest to host)
@@ -194,6 +196,7 @@ impl BoxMetrics {
         storage: &BoxMetricsStorage,
         cpu_percent: Option<f32>,
         memory_bytes: Option<u64>,
+        swap_bytes: Option<u64>,
         network_bytes_sent: Option<u64>,
         network_bytes_received: Option<u64>,
         network_tcp_connections: Option<u64>,
@@ -208,6 +211,7 @@ impl BoxMetrics {
             guest_boot_duration_ms: storage.guest_boot_duration_ms,
             cpu_percent,
             memory_bytes,
+            swap_bytes,
             network_bytes_sent,
             network_bytes_received,
             network_tcp_connections,
@@ -282,6 +286,14 @@ impl BoxMetrics {
         self.memory_bytes
     }
 
+    /// Swap in use inside the VM in bytes.
+    ///
+    /// Returns None if the box has no swap (`BoxOptions::swap_mib`) or the
+    /// guest could not be asked.
+    pub fn swap_bytes(&self) -> Option<u64> {
+        self.swap_bytes
+    }
+
     /// Network bytes sent from host to guest.
     ///
     /// Returns None if network backend doesn't support metrics.
@@ -0,0 +1,265 @@
+//! Per-image breakdown of runtime metrics, for accounting by image.
+
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use oci_client::Reference;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Images with their own entry in [`RuntimeMetrics::per_image()`].
+///
+/// [`RuntimeMetrics::per_image()`]: super::RuntimeMetrics::per_image
+pub const MAX_TRACKED_IMAGES: usize = 64;
+
+/// Entry that sums images past [`MAX_TRACKED_IMAGES`].
+pub const OTHER_IMAGES: &str = "other";
+
+/// Usage attributed to one image.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ImageUsage {
+    /// Boxes created from the image
+    #[serde(default)]
+    pub boxes_created: u64,
+    /// Commands executed in those boxes
+    #[serde(default)]
+    pub commands_executed: u64,
+    /// Seconds those boxes have been running, up to now for running boxes
+    #[serde(default)]
+    pub box_seconds: f64,
+    /// Layer bytes downloaded for the image (resumed bytes count once)
+    #[serde(default)]
+    pub bytes_pulled: u64,
+}
+
+/// Storage for per-image counters, shared by `RuntimeMetricsStorage` clones.
+#[derive(Clone, Default)]
+pub(crate) struct ImageMetricsStorage(Arc<Mutex<ImageTable>>);
+
+#[derive(Default)]
+struct ImageTable {
+    /// Counters by image key, including [`OTHER_IMAGES`]
+    images: HashMap<String, Counters>,
+    /// Running boxes: box ID to image key and the time it started counting
+    running: HashMap<String, (String, Instant)>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Counters {
+    boxes_created: u64,
+    commands_executed: u64,
+    /// Runtime of boxes that have stopped
+    stopped_runtime: Duration,
+    bytes_pulled: u64,
+}
+
+impl ImageTable {
+    /// Key that counts `image`: its own while there is room, else [`OTHER_IMAGES`].
+    fn slot(&self, image: &str) -> String {
+        let key = image_key(image);
+        let tracked = self.images.len() - usize::from(self.images.contains_key(OTHER_IMAGES));
+        if self.images.contains_key(&key) || tracked < MAX_TRACKED_IMAGES {
+            key
+        } else {
+            OTHER_IMAGES.to_string()
+        }
+    }
+
+    fn counters(&mut self, image: &str) -> &mut Counters {
+        let slot = self.slot(image);
+        self.images.entry(slot).or_default()
+    }
+}
+
+impl ImageMetricsStorage {
+    pub(crate) fn box_created(&self, image: &str) {
+        self.0.lock().counters(image).boxes_created += 1;
+    }
+
+    pub(crate) fn command_executed(&self, image: &str) {
+        self.0.lock().counters(image).commands_executed += 1;
+    }
+
+    pub(crate) fn bytes_pulled(&self, image: &str, bytes: u64) {
+        self.0.lock().counters(image).bytes_pulled += bytes;
+    }
+
+    /// Start counting runtime for `box_id`. A box already counted keeps
+    /// its start time.
+    pub(crate) fn box_running(&self, box_id: &str, image: &str) {
+        let mut table = self.0.lock();
+        if table.running.contains_key(box_id) {
+            return;
+        }
+        let slot = table.slot(image);
+        table.images.entry(slot.clone()).or_default();
+        table
+            .running
+            .insert(box_id.to_string(), (slot, Instant::now()));
+    }
+
+    /// Stop counting runtime for `box_id`, if it was counted.
+    pub(crate) fn box_stopped(&self, box_id: &str) {
+        let mut table = self.0.lock();
+        if let Some((slot, since)) = table.running.remove(box_id) {
+            table.images.entry(slot).or_default().stopped_runtime += since.elapsed();
+        }
+    }
+
+    pub(crate) fn usage(&self) -> BTreeMap<String, ImageUsage> {
+        let table = self.0.lock();
+        let mut running: HashMap<&str, Duration> = HashMap::new();
+        for (slot, since) in table.running.values() {
+            *running.entry(slot.as_str()).or_default() += since.elapsed();
+        }
+        table
+            .images
+            .iter()
+            .map(|(key, counters)| {
+                let runtime = counters.stopped_runtime
+                    + running.get(key.as_str()).copied().unwrap_or_default();
+                let usage = ImageUsage {
+                    boxes_created: counters.boxes_created,
+                    commands_executed: counters.commands_executed,
+                    box_seconds: runtime.as_secs_f64(),
+                    bytes_pulled: counters.bytes_pulled,
+                };
+                (key.clone(), usage)
+            })
+            .collect()
+    }
+
+    /// Replace the counters with `usage` reported by a server.
+    pub(crate) fn load(&self, usage: BTreeMap<String, ImageUsage>) {
+        let mut table = self.0.lock();
+        table.running.clear();
+        table.images = usage
+            .into_iter()
+            .map(|(key, usage)| {
+                let counters = Counters {
+                    boxes_created: usage.boxes_created,
+                    commands_executed: usage.commands_executed,
+                    stopped_runtime: Duration::try_from_secs_f64(usage.box_seconds)
+                        .unwrap_or_default(),
+                    bytes_pulled: usage.bytes_pulled,
+                };
+                (key, counters)
+            })
+            .collect();
+    }
+
+    /// Zero the counters. Running boxes count from now, and images get
+    /// their own entries again in the order they are next seen.
+    #[cfg(feature = "metrics-reset")]
+    pub(crate) fn reset(&self) {
+        let mut table = self.0.lock();
+        let table = &mut *table;
+        table.images.clear();
+        let now = Instant::now();
+        for (slot, since) in table.running.values_mut() {
+            *since = now;
+            table.images.entry(slot.clone()).or_default();
+        }
+    }
+}
+
+/// Key for `image`: the reference as the registry client normalizes it
+/// (`alpine` becomes `docker.io/library/alpine:latest`), or `image` itself
+/// if it does not parse.
+pub(crate) fn image_key(image: &str) -> String {
+    Reference::from_str(image)
+        .map(|reference| reference.whole())
+        .unwrap_or_else(|_| image.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keys_are_normalized_references() {
+        let storage = ImageMetricsStorage::default();
+        storage.box_created("alpine");
+        storage.box_created("docker.io/library/alpine:latest");
+        storage.command_executed("alpine:latest");
+
+        let usage = storage.usage();
+        assert_eq!(usage.len(), 1);
+        let alpine = &usage["docker.io/library/alpine:latest"];
+        assert_eq!(alpine.boxes_created, 2);
+        assert_eq!(alpine.commands_executed, 1);
+    }
+
+    #[test]
+    fn test_images_past_the_cap_go_to_other() {
+        let storage = ImageMetricsStorage::default();
+        for i in 0..MAX_TRACKED_IMAGES + 3 {
+            storage.box_created(&format!("image{i}"));
+        }
+        // Already tracked images keep their entry once the cap is reached
+        storage.bytes_pulled("image0", 10);
+
+        let usage = storage.usage();
+        assert_eq!(usage.len(), MAX_TRACKED_IMAGES + 1);
+        assert_eq!(usage[OTHER_IMAGES].boxes_created, 3);
+        assert_eq!(usage["docker.io/library/image0:latest"].bytes_pulled, 10);
+    }
+
+    #[test]
+    fn test_box_seconds_include_running_boxes() {
+        let storage = ImageMetricsStorage::default();
+        storage.box_running("box1", "alpine");
+        // A second start of a running box (e.g. a reattach) keeps the clock
+        std::thread::sleep(Duration::from_millis(20));
+        storage.box_running("box1", "alpine");
+
+        let running = storage.usage()["docker.io/library/alpine:latest"].box_seconds;
+        assert!(running >= 0.02, "{running}");
+
+        storage.box_stopped("box1");
+        let stopped = storage.usage()["docker.io/library/alpine:latest"].box_seconds;
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            storage.usage()["docker.io/library/alpine:latest"].box_seconds,
+            stopped
+        );
+        assert!(stopped >= running);
+
+        // Stopping a box that was never counted changes nothing
+        storage.box_stopped("box2");
+        assert_eq!(storage.usage().len(), 1);
+    }
+
+    #[test]
+    fn test_load_replaces_counters() {
+        let storage = ImageMetricsStorage::default();
+        storage.box_running("box1", "alpine");
+        let reported = BTreeMap::from([(
+            "docker.io/library/python:3".to_string(),
+            ImageUsage {
+                boxes_created: 4,
+                box_seconds: 1.5,
+                ..Default::default()
+            },
+        )]);
+
+        storage.load(reported.clone());
+        assert_eq!(storage.usage(), reported);
+    }
+
+    #[cfg(feature = "metrics-reset")]
+    #[test]
+    fn test_reset_restarts_running_clocks() {
+        let storage = ImageMetricsStorage::default();
+        storage.box_created("alpine");
+        storage.box_running("box1", "alpine");
+        std::thread::sleep(Duration::from_millis(50));
+
+        storage.reset();
+        let alpine = &storage.usage()["docker.io/library/alpine:latest"];
+        assert_eq!(alpine.boxes_created, 0);
+        assert!(alpine.box_seconds < 0.05, "{}", alpine.box_seconds);
+    }
+}
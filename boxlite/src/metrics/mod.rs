@@ -6,8 +6,8 @@
 //!
 //! # Design
 //!
-//! All counters are monotonic (never decrease). Delta calculation is the caller's
-//! responsibility. Future `boxlite-metrics` crate may provide helpers (deferred).
+//! All counters are monotonic (never decrease). To measure activity over an
+//! interval, take a [`RuntimeMetricsSnapshot`] before and after and `diff()` them.
 //!
 //! # Example
 //!
@@ -31,7 +31,11 @@
 //! ```
 
 mod box_metrics;
+mod image_metrics;
 mod runtime_metrics;
 
 pub use box_metrics::{BoxMetrics, BoxMetricsStorage};
-pub use runtime_metrics::{RuntimeMetrics, RuntimeMetricsStorage};
+pub use image_metrics::{ImageUsage, MAX_TRACKED_IMAGES, OTHER_IMAGES};
+pub use runtime_metrics::{
+    RuntimeMetrics, RuntimeMetricsDelta, RuntimeMetricsSnapshot, RuntimeMetricsStorage,
+};
@@ -1,8 +1,21 @@
 //! Runtime-level metrics (aggregate across all boxes).
 
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
 
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use super::image_metrics::{ImageMetricsStorage, ImageUsage};
+
+/// Process-wide source of snapshot sequence numbers.
+///
+/// Global rather than per-runtime so snapshots from REST handles, which are
+/// rebuilt on every call, still order correctly.
+static SNAPSHOT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
 /// Storage for runtime-wide metrics.
 ///
 /// Stored in `RuntimeState`, shared across all operations.
@@ -19,6 +32,46 @@ pub struct RuntimeMetricsStorage {
     pub(crate) total_commands: Arc<AtomicU64>,
     /// Total command execution errors across all boxes
     pub(crate) total_exec_errors: Arc<AtomicU64>,
+    /// `acquire_warm()` calls served from an idle pool box
+    pub(crate) warm_pool_hits: Arc<AtomicU64>,
+    /// `acquire_warm()` calls that had to create and start a box
+    pub(crate) warm_pool_misses: Arc<AtomicU64>,
+    /// Total time spent in successful `acquire_warm()` calls (ms)
+    pub(crate) warm_acquire_ms: Arc<AtomicU64>,
+    /// Free space checks that found the disk below the low-space threshold
+    pub(crate) low_space_warnings: Arc<AtomicU64>,
+    /// Times low priority boxes were throttled for host CPU pressure
+    pub(crate) cpu_throttles: Arc<AtomicU64>,
+    /// Times that throttle was lifted
+    pub(crate) cpu_throttle_releases: Arc<AtomicU64>,
+    /// Layers copied from the shared blob cache
+    pub(crate) blob_cache_hits: Arc<AtomicU64>,
+    /// Layers the shared blob cache didn't hold intact, so were downloaded
+    pub(crate) blob_cache_misses: Arc<AtomicU64>,
+    /// Counters broken down by image
+    pub(crate) per_image: ImageMetricsStorage,
+    /// Raw counter values at the last reset; reported counters are relative to it
+    baseline: Arc<Mutex<CounterValues>>,
+    /// Number of resets so far
+    reset_epoch: Arc<AtomicU64>,
+}
+
+/// Raw values of the monotonic counters.
+#[derive(Clone, Copy, Default)]
+struct CounterValues {
+    boxes_created: u64,
+    boxes_failed: u64,
+    boxes_stopped: u64,
+    total_commands: u64,
+    total_exec_errors: u64,
+    warm_pool_hits: u64,
+    warm_pool_misses: u64,
+    warm_acquire_ms: u64,
+    low_space_warnings: u64,
+    cpu_throttles: u64,
+    cpu_throttle_releases: u64,
+    blob_cache_hits: u64,
+    blob_cache_misses: u64,
 }
 
 impl RuntimeMetricsStorage {
@@ -26,12 +79,56 @@ impl RuntimeMetricsStorage {
     pub fn new() -> Self {
         Self::default()
     }
+
+    fn raw(&self) -> CounterValues {
+        CounterValues {
+            boxes_created: self.boxes_created.load(Ordering::Relaxed),
+            boxes_failed: self.boxes_failed.load(Ordering::Relaxed),
+            boxes_stopped: self.boxes_stopped.load(Ordering::Relaxed),
+            total_commands: self.total_commands.load(Ordering::Relaxed),
+            total_exec_errors: self.total_exec_errors.load(Ordering::Relaxed),
+            warm_pool_hits: self.warm_pool_hits.load(Ordering::Relaxed),
+            warm_pool_misses: self.warm_pool_misses.load(Ordering::Relaxed),
+            warm_acquire_ms: self.warm_acquire_ms.load(Ordering::Relaxed),
+            low_space_warnings: self.low_space_warnings.load(Ordering::Relaxed),
+            cpu_throttles: self.cpu_throttles.load(Ordering::Relaxed),
+            cpu_throttle_releases: self.cpu_throttle_releases.load(Ordering::Relaxed),
+            blob_cache_hits: self.blob_cache_hits.load(Ordering::Relaxed),
+            blob_cache_misses: self.blob_cache_misses.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Counter values since the last reset.
+    fn current(&self) -> CounterValues {
+        let raw = self.raw();
+        let base = *self.baseline.lock();
+        CounterValues {
+            boxes_created: raw.boxes_created.saturating_sub(base.boxes_created),
+            boxes_failed: raw.boxes_failed.saturating_sub(base.boxes_failed),
+            boxes_stopped: raw.boxes_stopped.saturating_sub(base.boxes_stopped),
+            total_commands: raw.total_commands.saturating_sub(base.total_commands),
+            total_exec_errors: raw.total_exec_errors.saturating_sub(base.total_exec_errors),
+            warm_pool_hits: raw.warm_pool_hits.saturating_sub(base.warm_pool_hits),
+            warm_pool_misses: raw.warm_pool_misses.saturating_sub(base.warm_pool_misses),
+            warm_acquire_ms: raw.warm_acquire_ms.saturating_sub(base.warm_acquire_ms),
+            low_space_warnings: raw
+                .low_space_warnings
+                .saturating_sub(base.low_space_warnings),
+            cpu_throttles: raw.cpu_throttles.saturating_sub(base.cpu_throttles),
+            cpu_throttle_releases: raw
+                .cpu_throttle_releases
+                .saturating_sub(base.cpu_throttle_releases),
+            blob_cache_hits: raw.blob_cache_hits.saturating_sub(base.blob_cache_hits),
+            blob_cache_misses: raw.blob_cache_misses.saturating_sub(base.blob_cache_misses),
+        }
+    }
 }
 
 /// Handle for querying runtime-wide metrics.
 ///
 /// Cloneable, lightweight handle (only Arc pointers).
-/// All counters are monotonic and never reset.
+/// All counters are monotonic; they only go back to zero through [`reset()`](Self::reset)
+/// (`metrics-reset` feature).
 #[derive(Clone)]
 pub struct RuntimeMetrics {
     storage: RuntimeMetricsStorage,
@@ -48,7 +145,7 @@ impl RuntimeMetrics {
     /// Incremented when `BoxliteRuntime::create()` is called.
     /// Never decreases (monotonic counter).
     pub fn boxes_created_total(&self) -> u64 {
-        self.storage.boxes_created.load(Ordering::Relaxed)
+        self.storage.current().boxes_created
     }
 
     /// Total number of boxes that failed to start.
@@ -56,7 +153,7 @@ impl RuntimeMetrics {
     /// Incremented when box creation or initialization fails.
     /// Never decreases (monotonic counter).
     pub fn boxes_failed_total(&self) -> u64 {
-        self.storage.boxes_failed.load(Ordering::Relaxed)
+        self.storage.current().boxes_failed
     }
 
     /// Total number of boxes that have been stopped.
@@ -65,17 +162,18 @@ impl RuntimeMetrics {
     /// active box is force-removed.
     /// Never decreases (monotonic counter).
     pub fn boxes_stopped_total(&self) -> u64 {
-        self.storage.boxes_stopped.load(Ordering::Relaxed)
+        self.storage.current().boxes_stopped
     }
 
     /// Number of currently running boxes.
     ///
-    /// Calculated as: boxes_created - boxes_stopped - boxes_failed
+    /// Calculated as: boxes_created - boxes_stopped - boxes_failed, over the
+    /// lifetime of the runtime (unaffected by [`reset()`](Self::reset)).
     pub fn num_running_boxes(&self) -> u64 {
-        let created = self.boxes_created_total();
-        let stopped = self.boxes_stopped_total();
-        let failed = self.boxes_failed_total();
-        created.saturating_sub(stopped).saturating_sub(failed)
+        let raw = self.storage.raw();
+        raw.boxes_created
+            .saturating_sub(raw.boxes_stopped)
+            .saturating_sub(raw.boxes_failed)
     }
 
     /// Total commands executed across all boxes.
@@ -83,7 +181,7 @@ impl RuntimeMetrics {
     /// Incremented on every `LiteBox::exec()` call.
     /// Never decreases (monotonic counter).
     pub fn total_commands_executed(&self) -> u64 {
-        self.storage.total_commands.load(Ordering::Relaxed)
+        self.storage.current().total_commands
     }
 
     /// Total command execution errors across all boxes.
@@ -91,8 +189,198 @@ impl RuntimeMetrics {
     /// Incremented when `LiteBox::exec()` returns error.
     /// Never decreases (monotonic counter).
     pub fn total_exec_errors(&self) -> u64 {
-        self.storage.total_exec_errors.load(Ordering::Relaxed)
+        self.storage.current().total_exec_errors
+    }
+
+    /// Warm box acquisitions served from an idle pool box.
+    ///
+    /// Never decreases (monotonic counter).
+    pub fn warm_pool_hits_total(&self) -> u64 {
+        self.storage.current().warm_pool_hits
+    }
+
+    /// Warm box acquisitions that found the pool empty and started a box.
+    ///
+    /// Never decreases (monotonic counter).
+    pub fn warm_pool_misses_total(&self) -> u64 {
+        self.storage.current().warm_pool_misses
+    }
+
+    /// Mean latency of successful warm box acquisitions in milliseconds.
+    ///
+    /// None before the first acquisition.
+    pub fn warm_acquire_avg_ms(&self) -> Option<f64> {
+        let current = self.storage.current();
+        let acquisitions = current.warm_pool_hits + current.warm_pool_misses;
+        (acquisitions > 0).then(|| current.warm_acquire_ms as f64 / acquisitions as f64)
+    }
+
+    /// Free space checks that found the disk under
+    /// `BoxliteOptions::low_space_threshold_bytes`.
+    ///
+    /// Checked before image disk builds, snapshots, export, import and copies
+    /// into a box. Never decreases (monotonic counter).
+    pub fn low_space_warnings_total(&self) -> u64 {
+        self.storage.current().low_space_warnings
+    }
+
+    /// Times low priority boxes were throttled because host CPU pressure
+    /// crossed `CpuPressurePolicy::throttle_above`.
+    ///
+    /// Counts transitions, not boxes. Never decreases (monotonic counter).
+    pub fn cpu_throttles_total(&self) -> u64 {
+        self.storage.current().cpu_throttles
+    }
+
+    /// Times the CPU pressure throttle was lifted, after pressure fell to
+    /// `CpuPressurePolicy::release_below` or the runtime shut down.
+    ///
+    /// Never decreases (monotonic counter).
+    pub fn cpu_throttle_releases_total(&self) -> u64 {
+        self.storage.current().cpu_throttle_releases
+    }
+
+    /// Layers copied from `BoxliteOptions::blob_cache_dir` instead of
+    /// downloaded.
+    ///
+    /// Never decreases (monotonic counter).
+    pub fn blob_cache_hits_total(&self) -> u64 {
+        self.storage.current().blob_cache_hits
+    }
+
+    /// Layers downloaded because `BoxliteOptions::blob_cache_dir` held no
+    /// intact copy.
+    ///
+    /// Never decreases (monotonic counter).
+    pub fn blob_cache_misses_total(&self) -> u64 {
+        self.storage.current().blob_cache_misses
+    }
+
+    /// Usage broken down by image, keyed by normalized image reference
+    /// (`alpine` is counted as `docker.io/library/alpine:latest`).
+    ///
+    /// Counts boxes created, commands executed, bytes pulled and box-seconds
+    /// of runtime, which include running boxes up to now. A box recovered
+    /// from a previous run counts from the time this runtime recovered it.
+    /// Boxes started from a rootfs path are not broken down.
+    ///
+    /// The first [`MAX_TRACKED_IMAGES`](super::MAX_TRACKED_IMAGES) images
+    /// seen get their own entry; later ones are summed under
+    /// [`OTHER_IMAGES`](super::OTHER_IMAGES).
+    pub fn per_image(&self) -> BTreeMap<String, ImageUsage> {
+        self.storage.per_image.usage()
+    }
+
+    /// Take an immutable point-in-time copy of all metrics.
+    ///
+    /// Each snapshot gets a sequence number that is strictly greater than that
+    /// of any snapshot taken before it in this process.
+    pub fn snapshot(&self) -> RuntimeMetricsSnapshot {
+        let current = self.storage.current();
+        RuntimeMetricsSnapshot {
+            sequence: SNAPSHOT_SEQUENCE.fetch_add(1, Ordering::Relaxed) + 1,
+            taken_at: Utc::now(),
+            reset_epoch: self.storage.reset_epoch.load(Ordering::Relaxed),
+            boxes_created_total: current.boxes_created,
+            boxes_failed_total: current.boxes_failed,
+            boxes_stopped_total: current.boxes_stopped,
+            num_running_boxes: self.num_running_boxes(),
+            total_commands_executed: current.total_commands,
+            total_exec_errors: current.total_exec_errors,
+        }
     }
+
+    /// Changes since `earlier`, measured against the current values.
+    pub fn diff(&self, earlier: &RuntimeMetricsSnapshot) -> RuntimeMetricsDelta {
+        self.snapshot().diff(earlier)
+    }
+
+    /// Zero all counters.
+    ///
+    /// Gauges such as [`num_running_boxes()`](Self::num_running_boxes) are
+    /// preserved. Applies to every handle sharing this runtime's storage; for
+    /// REST runtimes it only affects this handle, not the server.
+    #[cfg(feature = "metrics-reset")]
+    pub fn reset(&self) {
+        let mut baseline = self.storage.baseline.lock();
+        *baseline = self.storage.raw();
+        self.storage.per_image.reset();
+        self.storage.reset_epoch.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Immutable point-in-time copy of [`RuntimeMetrics`].
+///
+/// Serializes to JSON with a stable field order, so snapshots can be archived
+/// and compared later.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeMetricsSnapshot {
+    /// Monotonic sequence number (process-wide)
+    pub sequence: u64,
+    /// When the snapshot was taken
+    pub taken_at: DateTime<Utc>,
+    /// Number of counter resets before this snapshot
+    pub reset_epoch: u64,
+    pub boxes_created_total: u64,
+    pub boxes_failed_total: u64,
+    pub boxes_stopped_total: u64,
+    pub num_running_boxes: u64,
+    pub total_commands_executed: u64,
+    pub total_exec_errors: u64,
+}
+
+impl RuntimeMetricsSnapshot {
+    /// Changes between `earlier` and this snapshot.
+    ///
+    /// If the counters were reset in between, the reported deltas are the
+    /// values accumulated since the reset (a lower bound on the true activity)
+    /// and `counters_reset` is set.
+    pub fn diff(&self, earlier: &RuntimeMetricsSnapshot) -> RuntimeMetricsDelta {
+        let counters_reset = self.reset_epoch != earlier.reset_epoch;
+        let delta = |later: u64, before: u64| {
+            if counters_reset {
+                later
+            } else {
+                later.saturating_sub(before)
+            }
+        };
+
+        RuntimeMetricsDelta {
+            from_sequence: earlier.sequence,
+            to_sequence: self.sequence,
+            elapsed_ms: (self.taken_at - earlier.taken_at).num_milliseconds(),
+            counters_reset,
+            boxes_created: delta(self.boxes_created_total, earlier.boxes_created_total),
+            boxes_failed: delta(self.boxes_failed_total, earlier.boxes_failed_total),
+            boxes_stopped: delta(self.boxes_stopped_total, earlier.boxes_stopped_total),
+            commands_executed: delta(
+                self.total_commands_executed,
+                earlier.total_commands_executed,
+            ),
+            exec_errors: delta(self.total_exec_errors, earlier.total_exec_errors),
+            running_boxes_change: self.num_running_boxes as i64 - earlier.num_running_boxes as i64,
+        }
+    }
+}
+
+/// Difference between two [`RuntimeMetricsSnapshot`]s.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeMetricsDelta {
+    /// Sequence number of the earlier snapshot
+    pub from_sequence: u64,
+    /// Sequence number of the later snapshot
+    pub to_sequence: u64,
+    /// Wall-clock time between the snapshots (negative if taken out of order)
+    pub elapsed_ms: i64,
+    /// Counters were reset between the snapshots
+    pub counters_reset: bool,
+    pub boxes_created: u64,
+    pub boxes_failed: u64,
+    pub boxes_stopped: u64,
+    pub commands_executed: u64,
+    pub exec_errors: u64,
+    /// Change in the number of running boxes
+    pub running_boxes_change: i64,
 }
 
 #[cfg(test)]
@@ -143,6 +431,90 @@ mod tests {
         assert_eq!(metrics.num_running_boxes(), 0);
     }
 
+    #[test]
+    fn test_snapshot_diff() {
+        let storage = RuntimeMetricsStorage::new();
+        let metrics = RuntimeMetrics::new(storage.clone());
+
+        storage.boxes_created.fetch_add(2, Ordering::Relaxed);
+        storage.total_commands.fetch_add(10, Ordering::Relaxed);
+        let earlier = metrics.snapshot();
+
+        storage.boxes_created.fetch_add(1, Ordering::Relaxed);
+        storage.boxes_stopped.fetch_add(2, Ordering::Relaxed);
+        storage.total_commands.fetch_add(5, Ordering::Relaxed);
+        storage.total_exec_errors.fetch_add(1, Ordering::Relaxed);
+        let later = metrics.snapshot();
+
+        assert!(later.sequence > earlier.sequence);
+
+        let delta = later.diff(&earlier);
+        assert_eq!(delta.from_sequence, earlier.sequence);
+        assert_eq!(delta.to_sequence, later.sequence);
+        assert!(!delta.counters_reset);
+        assert_eq!(delta.boxes_created, 1);
+        assert_eq!(delta.boxes_stopped, 2);
+        assert_eq!(delta.commands_executed, 5);
+        assert_eq!(delta.exec_errors, 1);
+        assert_eq!(delta.running_boxes_change, -1);
+    }
+
+    #[test]
+    fn test_snapshot_json_roundtrip_is_stable() {
+        let metrics = RuntimeMetrics::new(RuntimeMetricsStorage::new());
+        let snapshot = metrics.snapshot();
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.starts_with(r#"{"sequence":"#));
+        let fields: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(fields.len(), 9);
+
+        let parsed: RuntimeMetricsSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+    }
+
+    #[cfg(feature = "metrics-reset")]
+    #[test]
+    fn test_reset_zeroes_counters_but_keeps_running_gauge() {
+        let storage = RuntimeMetricsStorage::new();
+        let metrics = RuntimeMetrics::new(storage.clone());
+
+        storage.boxes_created.fetch_add(3, Ordering::Relaxed);
+        storage.boxes_stopped.fetch_add(1, Ordering::Relaxed);
+        storage.total_commands.fetch_add(7, Ordering::Relaxed);
+        let before = metrics.snapshot();
+
+        metrics.reset();
+        assert_eq!(metrics.boxes_created_total(), 0);
+        assert_eq!(metrics.boxes_stopped_total(), 0);
+        assert_eq!(metrics.total_commands_executed(), 0);
+        assert_eq!(metrics.num_running_boxes(), 2);
+
+        storage.total_commands.fetch_add(4, Ordering::Relaxed);
+        assert_eq!(metrics.total_commands_executed(), 4);
+
+        let delta = metrics.diff(&before);
+        assert!(delta.counters_reset);
+        assert_eq!(delta.commands_executed, 4);
+        assert_eq!(delta.running_boxes_change, 0);
+    }
+
+    #[test]
+    fn test_warm_acquire_avg_ms() {
+        let storage = RuntimeMetricsStorage::new();
+        let metrics = RuntimeMetrics::new(storage.clone());
+        assert_eq!(metrics.warm_acquire_avg_ms(), None);
+
+        storage.warm_pool_hits.fetch_add(3, Ordering::Relaxed);
+        storage.warm_pool_misses.fetch_add(1, Ordering::Relaxed);
+        storage.warm_acquire_ms.fetch_add(2_200, Ordering::Relaxed);
+        assert_eq!(metrics.warm_pool_hits_total(), 3);
+        assert_eq!(metrics.warm_pool_misses_total(), 1);
+        assert_eq!(metrics.warm_acquire_avg_ms(), Some(550.0));
+    }
+
     #[test]
     fn test_boxes_stopped_total() {
         let storage = RuntimeMetricsStorage::new();
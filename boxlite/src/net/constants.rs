@@ -13,17 +13,29 @@ pub const GATEWAY_IP: &str = "192.168.127.1";
 /// Guest IP address (assigned via DHCP static lease)
 pub const GUEST_IP: &str = "192.168.127.2";
 
+/// Host IP address as seen from the guest
+///
+/// A virtual IP on the gateway whose traffic is forwarded to the host's
+/// loopback, so guests can reach services listening on the host.
+pub const HOST_IP: &str = "192.168.127.254";
+
+/// Stable name for [`HOST_IP`] written to every networked guest's hosts file
+pub const HOST_NAME: &str = "host.boxlite.internal";
+
 /// Gateway MAC address
 ///
 /// This MAC is used by gvproxy's virtual network interface.
 /// Uses locally administered address space (bit 2 of first octet set).
 pub const GATEWAY_MAC: [u8; 6] = [0x5a, 0x94, 0xef, 0xe4, 0x0c, 0xdd];
 
-/// Guest MAC address
+/// Default guest MAC address
 ///
 /// This MAC must match the MAC address configured by the engine for the guest's network interface.
 /// Used for DHCP static lease to ensure the guest always receives GUEST_IP.
 /// Last byte differs from GATEWAY_MAC by 1 (0xdd vs 0xee).
+///
+/// Boxes get a per-box MAC from [`derive_guest_mac`]; this value is only used
+/// for boxes started before per-box MACs were persisted.
 pub const GUEST_MAC: [u8; 6] = [0x5a, 0x94, 0xef, 0xe4, 0x0c, 0xee];
 
 /// Guest MAC address as colon-separated string (for DHCP configuration)
@@ -42,6 +54,44 @@ pub const DNS_SERVER_IP: &str = GATEWAY_IP;
 /// DNS search domains
 pub const DNS_SEARCH_DOMAINS: &[&str] = &["local"];
 
+/// Derive a guest MAC address from a box ID.
+///
+/// The result is a locally administered unicast address (bit 1 of the first
+/// octet set, bit 0 clear). `attempt` salts the hash so callers can pick a
+/// different address when the first candidate is already taken.
+pub fn derive_guest_mac(box_id: &str, attempt: u32) -> [u8; 6] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(box_id.as_bytes());
+    if attempt > 0 {
+        hasher.update(attempt.to_le_bytes());
+    }
+    let hash = hasher.finalize();
+
+    let mut mac = [0u8; 6];
+    mac.copy_from_slice(&hash[..6]);
+    mac[0] = (mac[0] & 0xfc) | 0x02;
+    mac
+}
+
+/// Parse a colon-separated MAC address (e.g., "5a:94:ef:e4:0c:ee").
+pub fn parse_mac(s: &str) -> Option<[u8; 6]> {
+    let mut mac = [0u8; 6];
+    let mut parts = s.split(':');
+    for byte in mac.iter_mut() {
+        let part = parts.next()?;
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(mac)
+}
+
 /// Helper function to format MAC address as string
 pub fn mac_to_string(mac: &[u8; 6]) -> String {
     format!(
@@ -69,4 +119,25 @@ mod tests {
         assert_eq!(GUEST_MAC[5], 0xee);
         assert_eq!(GATEWAY_MAC[5], 0xdd);
     }
+
+    #[test]
+    fn test_derive_guest_mac_is_stable_and_local() {
+        let mac = derive_guest_mac("01HJK4TNRPQSXYZ8WM6NCVT9R5", 0);
+        assert_eq!(mac, derive_guest_mac("01HJK4TNRPQSXYZ8WM6NCVT9R5", 0));
+        // Locally administered, unicast
+        assert_eq!(mac[0] & 0x03, 0x02);
+
+        assert_ne!(mac, derive_guest_mac("01HJK4TNRPQSXYZ8WM6NCVT9R6", 0));
+        assert_ne!(mac, derive_guest_mac("01HJK4TNRPQSXYZ8WM6NCVT9R5", 1));
+    }
+
+    #[test]
+    fn test_parse_mac() {
+        assert_eq!(parse_mac(GUEST_MAC_STRING), Some(GUEST_MAC));
+        assert_eq!(parse_mac(&mac_to_string(&GATEWAY_MAC)), Some(GATEWAY_MAC));
+        assert_eq!(parse_mac("5a:94:ef:e4:0c"), None);
+        assert_eq!(parse_mac("5a:94:ef:e4:0c:ee:ff"), None);
+        assert_eq!(parse_mac("5a:94:ef:e4:0c:zz"), None);
+        assert_eq!(parse_mac("5a:94:ef:e4:0c:e"), None);
+    }
 }
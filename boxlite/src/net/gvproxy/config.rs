@@ -43,6 +43,9 @@ pub struct GvproxyConfig {
     /// Gateway MAC address
     pub gateway_mac: String,
 
+    /// Virtual IP forwarded to the host's loopback
+    pub host_ip: String,
+
     /// Guest IP address
     pub guest_ip: String,
 
@@ -80,6 +83,7 @@ fn defaults_with_socket_path(socket_path: PathBuf) -> GvproxyConfig {
         subnet: SUBNET.to_string(),
         gateway_ip: GATEWAY_IP.to_string(),
         gateway_mac: GATEWAY_MAC_STRING.to_string(),
+        host_ip: HOST_IP.to_string(),
         guest_ip: GUEST_IP.to_string(),
         guest_mac: GUEST_MAC_STRING.to_string(),
         mtu: DEFAULT_MTU,
@@ -144,6 +148,13 @@ impl GvproxyConfig {
         self
     }
 
+    /// Set the guest IP/MAC served as a DHCP static lease
+    pub fn with_guest(mut self, guest: &crate::net::BoxNetwork) -> Self {
+        self.guest_ip = guest.guest_ip.clone();
+        self.guest_mac = guest.guest_mac.clone();
+        self
+    }
+
     /// Set custom MTU
     pub fn with_mtu(mut self, mtu: u16) -> Self {
         self.mtu = mtu;
@@ -184,6 +195,7 @@ mod tests {
         assert_eq!(config.subnet, "192.168.127.0/24");
         assert_eq!(config.gateway_ip, "192.168.127.1");
         assert_eq!(config.guest_ip, "192.168.127.2");
+        assert_eq!(config.host_ip, "192.168.127.254");
         assert_eq!(config.mtu, 1500);
         assert!(!config.debug);
         assert!(config.dns_zones.is_empty());
@@ -217,6 +229,17 @@ mod tests {
         assert_eq!(config.port_mappings.len(), deserialized.port_mappings.len());
     }
 
+    #[test]
+    fn test_with_guest_sets_static_lease() {
+        let guest = crate::net::BoxNetwork {
+            guest_mac: "5a:00:00:00:00:01".to_string(),
+            ..Default::default()
+        };
+        let config = GvproxyConfig::new(test_socket_path(), vec![]).with_guest(&guest);
+        assert_eq!(config.guest_ip, "192.168.127.2");
+        assert_eq!(config.guest_mac, "5a:00:00:00:00:01");
+    }
+
     #[test]
     fn test_capture_file_builder() {
         let config = GvproxyConfig::new(test_socket_path(), vec![(8080, 80)])
@@ -11,6 +11,7 @@ use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use super::ffi;
 use super::logging;
 use super::stats::NetworkStats;
+use crate::net::BoxNetwork;
 
 /// Safe wrapper for gvproxy library with automatic resource management
 ///
@@ -39,12 +40,14 @@ use super::stats::NetworkStats;
 /// ## Example
 ///
 /// ```no_run
+/// use boxlite::net::BoxNetwork;
 /// use boxlite::net::gvproxy::GvproxyInstance;
 /// use std::path::PathBuf;
 ///
 /// // Create instance with caller-provided socket path
 /// let socket_path = PathBuf::from("/tmp/my-box/net.sock");
-/// let instance = GvproxyInstance::new(socket_path, &[(8080, 80), (8443, 443)])?;
+/// let guest = BoxNetwork::default();
+/// let instance = GvproxyInstance::new(socket_path, &[(8080, 80), (8443, 443)], &guest)?;
 ///
 /// // Socket path is known from creation — no FFI call needed
 /// println!("Socket: {:?}", instance.socket_path());
@@ -67,13 +70,19 @@ impl GvproxyInstance {
     ///
     /// * `socket_path` - Caller-provided Unix socket path (must be unique per box)
     /// * `port_mappings` - List of (host_port, guest_port) tuples for port forwarding
-    pub fn new(socket_path: PathBuf, port_mappings: &[(u16, u16)]) -> BoxliteResult<Self> {
+    /// * `guest` - Guest IP/MAC served as a DHCP static lease
+    pub fn new(
+        socket_path: PathBuf,
+        port_mappings: &[(u16, u16)],
+        guest: &BoxNetwork,
+    ) -> BoxliteResult<Self> {
         // Initialize logging callback (one-time setup)
         // This ensures all gvproxy logs are routed to Rust's tracing system
         logging::init_logging();
 
-        // Create config with caller-provided socket path + port mappings
-        let config = super::config::GvproxyConfig::new(socket_path.clone(), port_mappings.to_vec());
+        // Create config with caller-provided socket path + port mappings + static lease
+        let config = super::config::GvproxyConfig::new(socket_path.clone(), port_mappings.to_vec())
+            .with_guest(guest);
 
         // Create instance via FFI with full config
         let id = ffi::create_instance(&config)?;
@@ -265,8 +274,9 @@ mod tests {
     #[ignore] // Requires libgvproxy.dylib to be available
     fn test_gvproxy_create_destroy() {
         let socket_path = PathBuf::from("/tmp/test-gvproxy-instance.sock");
+        let guest = BoxNetwork::default();
         let instance =
-            GvproxyInstance::new(socket_path.clone(), &[(8080, 80), (8443, 443)]).unwrap();
+            GvproxyInstance::new(socket_path.clone(), &[(8080, 80), (8443, 443)], &guest).unwrap();
 
         // Socket path matches what we provided
         assert_eq!(instance.socket_path(), socket_path);
@@ -280,8 +290,11 @@ mod tests {
         let path1 = PathBuf::from("/tmp/test-gvproxy-1.sock");
         let path2 = PathBuf::from("/tmp/test-gvproxy-2.sock");
 
-        let instance1 = GvproxyInstance::new(path1.clone(), &[(8080, 80)]).unwrap();
-        let instance2 = GvproxyInstance::new(path2.clone(), &[(9090, 90)]).unwrap();
+        let guest1 = BoxNetwork::allocate("box-1", &Default::default());
+        let guest2 = BoxNetwork::allocate("box-2", &Default::default());
+
+        let instance1 = GvproxyInstance::new(path1.clone(), &[(8080, 80)], &guest1).unwrap();
+        let instance2 = GvproxyInstance::new(path2.clone(), &[(9090, 90)], &guest2).unwrap();
 
         assert_ne!(instance1.id(), instance2.id());
         assert_ne!(instance1.socket_path(), instance2.socket_path());
@@ -108,6 +108,8 @@ pub struct GvisorTapBackend {
     instance: Arc<GvproxyInstance>,
     /// Socket path for cross-process communication
     socket_path: PathBuf,
+    /// Guest MAC address (matches the DHCP static lease)
+    guest_mac: [u8; 6],
 }
 
 impl GvisorTapBackend {
@@ -146,9 +148,11 @@ impl GvisorTapBackend {
         );
 
         // Create gvproxy instance with caller-provided socket path
+        let guest_mac = config.guest.mac_address()?;
         let instance = Arc::new(GvproxyInstance::new(
             config.socket_path.clone(),
             &config.port_mappings,
+            &config.guest,
         )?);
 
         // Start background stats logging thread
@@ -165,6 +169,7 @@ impl GvisorTapBackend {
         Ok(Self {
             instance,
             socket_path,
+            guest_mac,
         })
     }
 
@@ -211,13 +216,11 @@ impl NetworkBackend for GvisorTapBackend {
             ConnectionType::UnixStream
         };
 
-        // Use GUEST_MAC constant - this must match the DHCP static lease in gvproxy config
-        use crate::net::constants::GUEST_MAC;
-
+        // Guest MAC must match the DHCP static lease in gvproxy config
         Ok(NetworkBackendEndpoint::UnixSocket {
             path: self.socket_path.clone(),
             connection_type,
-            mac_address: GUEST_MAC,
+            mac_address: self.guest_mac,
         })
     }
 
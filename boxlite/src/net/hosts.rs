@@ -0,0 +1,128 @@
+//! Extra entries for the guest's generated `/etc/hosts`.
+//!
+//! Every networked guest gets [`HOST_NAME`] for the host. With
+//! `BoxliteOptions::box_dns`, other running boxes are added under their
+//! names. Each box sits behind its own gateway, so another box is reached
+//! through the host: its name maps to [`HOST_IP`], where its published ports
+//! listen.
+
+use std::collections::HashSet;
+
+use super::constants::{HOST_IP, HOST_NAME};
+
+/// Names the guest's base hosts file already defines.
+const RESERVED_NAMES: &[&str] = &[
+    "localhost",
+    "ip6-localhost",
+    "ip6-loopback",
+    "ip6-localnet",
+    "ip6-mcastprefix",
+    "ip6-allnodes",
+    "ip6-allrouters",
+    "boxlite",
+    HOST_NAME,
+];
+
+/// One `/etc/hosts` line written into a guest.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HostEntry {
+    /// Address as seen from the guest
+    pub ip: String,
+    /// Names resolving to `ip`
+    pub names: Vec<String>,
+}
+
+/// Another box that may appear in a guest's hosts file.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Peer<'a> {
+    pub name: &'a str,
+    /// Whether the box has a network backend
+    pub networked: bool,
+}
+
+/// Resolve the extra hosts entries of a box.
+///
+/// A box without networking gets none. Peers without networking, or whose
+/// name collides with a reserved or earlier name (hosts lookups ignore
+/// case), are skipped with a debug log.
+pub(crate) fn resolve<'a>(
+    networked: bool,
+    peers: impl IntoIterator<Item = Peer<'a>>,
+) -> Vec<HostEntry> {
+    if !networked {
+        return Vec::new();
+    }
+
+    let mut entries = vec![HostEntry {
+        ip: HOST_IP.to_string(),
+        names: vec![HOST_NAME.to_string()],
+    }];
+    let mut taken: HashSet<String> = RESERVED_NAMES.iter().map(|n| n.to_string()).collect();
+    for peer in peers {
+        if !peer.networked {
+            tracing::debug!(
+                name = peer.name,
+                "Box has no networking, not adding to hosts"
+            );
+            continue;
+        }
+        if !taken.insert(peer.name.to_ascii_lowercase()) {
+            tracing::debug!(name = peer.name, "Box name collides in hosts, skipping");
+            continue;
+        }
+        entries.push(HostEntry {
+            ip: HOST_IP.to_string(),
+            names: vec![peer.name.to_string()],
+        });
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peer(name: &str, networked: bool) -> Peer<'_> {
+        Peer { name, networked }
+    }
+
+    fn names(entries: &[HostEntry]) -> Vec<&str> {
+        entries
+            .iter()
+            .flat_map(|e| e.names.iter().map(String::as_str))
+            .collect()
+    }
+
+    #[test]
+    fn test_host_entry_always_first() {
+        let entries = resolve(true, []);
+        assert_eq!(
+            entries,
+            vec![HostEntry {
+                ip: HOST_IP.to_string(),
+                names: vec![HOST_NAME.to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_no_entries_without_networking() {
+        assert!(resolve(false, [peer("web", true)]).is_empty());
+    }
+
+    #[test]
+    fn test_peers_skip_collisions_and_unnetworked() {
+        let entries = resolve(
+            true,
+            [
+                peer("web", true),
+                peer("db", false),
+                peer("localhost", true),
+                peer("Web", true),
+                peer("cache", true),
+            ],
+        );
+        assert_eq!(names(&entries), vec![HOST_NAME, "web", "cache"]);
+        assert!(entries.iter().all(|e| e.ip == HOST_IP));
+    }
+}
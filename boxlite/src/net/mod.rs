@@ -7,10 +7,12 @@
 //! When no backend is configured (None), the engine uses its default net
 //! implementation.
 
-use boxlite_shared::errors::BoxliteResult;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::collections::HashSet;
 use std::path::PathBuf;
 
 pub mod constants;
+mod hosts;
 
 #[cfg(feature = "libslirp-backend")]
 mod libslirp;
@@ -18,6 +20,9 @@ mod libslirp;
 #[cfg(feature = "gvproxy-backend")]
 pub mod gvproxy;
 
+pub use hosts::HostEntry;
+pub(crate) use hosts::{Peer, resolve as resolve_hosts};
+
 #[cfg(feature = "libslirp-backend")]
 pub use libslirp::LibslirpBackend;
 
@@ -42,6 +47,65 @@ pub enum NetworkBackendEndpoint {
     },
 }
 
+/// Guest network identity of a box.
+///
+/// Assigned on first start and persisted in the box state, so the guest keeps
+/// the same address and MAC across restarts. The network backend serves the
+/// address to the guest as a DHCP static lease for the MAC.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BoxNetwork {
+    /// Guest IP address (e.g., "192.168.127.2")
+    pub guest_ip: String,
+    /// Guest MAC address, colon-separated (e.g., "5a:94:ef:e4:0c:ee")
+    pub guest_mac: String,
+    /// Gateway IP address (also the DNS server)
+    pub gateway_ip: String,
+}
+
+impl Default for BoxNetwork {
+    /// Network identity used before per-box MACs were persisted.
+    fn default() -> Self {
+        Self {
+            guest_ip: constants::GUEST_IP.to_string(),
+            guest_mac: constants::GUEST_MAC_STRING.to_string(),
+            gateway_ip: constants::GATEWAY_IP.to_string(),
+        }
+    }
+}
+
+impl BoxNetwork {
+    /// Allocate a network identity for a box.
+    ///
+    /// The MAC is derived from the box ID. If it collides with a MAC in
+    /// `taken` (or with the gateway), the derivation is re-salted until a
+    /// free address is found.
+    pub fn allocate(box_id: &str, taken: &HashSet<String>) -> Self {
+        let guest_mac = (0u32..)
+            .map(|attempt| constants::derive_guest_mac(box_id, attempt))
+            .map(|mac| constants::mac_to_string(&mac))
+            .find(|mac| !taken.contains(mac) && mac != constants::GATEWAY_MAC_STRING)
+            .expect("MAC derivation space exhausted");
+
+        Self {
+            guest_mac,
+            ..Self::default()
+        }
+    }
+
+    /// Guest MAC address as raw bytes.
+    pub fn mac_address(&self) -> BoxliteResult<[u8; 6]> {
+        constants::parse_mac(&self.guest_mac).ok_or_else(|| {
+            BoxliteError::Network(format!("Invalid guest MAC address: {}", self.guest_mac))
+        })
+    }
+
+    /// Guest IP address with the subnet prefix (e.g., "192.168.127.2/24").
+    pub fn guest_cidr(&self) -> String {
+        let prefix = constants::SUBNET.rsplit_once('/').map_or("24", |(_, p)| p);
+        format!("{}/{}", self.guest_ip, prefix)
+    }
+}
+
 /// Configuration for network backend initialization.
 ///
 /// This is the only struct that callers need to know about - they don't need
@@ -53,6 +117,9 @@ pub struct NetworkBackendConfig {
     /// Unix socket path for the network backend.
     /// Each box must have its own unique path to prevent collisions.
     pub socket_path: PathBuf,
+    /// Guest network identity (static DHCP lease).
+    #[serde(default)]
+    pub guest: BoxNetwork,
 }
 
 impl NetworkBackendConfig {
@@ -60,8 +127,15 @@ impl NetworkBackendConfig {
         Self {
             port_mappings,
             socket_path,
+            guest: BoxNetwork::default(),
         }
     }
+
+    /// Set the guest network identity.
+    pub fn with_guest(mut self, guest: BoxNetwork) -> Self {
+        self.guest = guest;
+        self
+    }
 }
 
 /// Network metrics from a network backend.
@@ -156,3 +230,31 @@ impl NetworkBackendFactory {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocate_is_deterministic_per_box() {
+        let taken = HashSet::new();
+        let a = BoxNetwork::allocate("box-a", &taken);
+        assert_eq!(a, BoxNetwork::allocate("box-a", &taken));
+        assert_eq!(a.guest_ip, constants::GUEST_IP);
+        assert_ne!(a.guest_mac, BoxNetwork::allocate("box-b", &taken).guest_mac);
+        assert!(a.mac_address().is_ok());
+    }
+
+    #[test]
+    fn test_allocate_skips_taken_macs() {
+        let first = BoxNetwork::allocate("box-a", &HashSet::new());
+        let taken: HashSet<String> = [first.guest_mac.clone()].into_iter().collect();
+        let second = BoxNetwork::allocate("box-a", &taken);
+        assert_ne!(first.guest_mac, second.guest_mac);
+    }
+
+    #[test]
+    fn test_guest_cidr() {
+        assert_eq!(BoxNetwork::default().guest_cidr(), "192.168.127.2/24");
+    }
+}
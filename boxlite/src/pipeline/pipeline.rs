@@ -9,6 +9,7 @@ use super::task::BoxedTask;
 use boxlite_shared::errors::BoxliteResult;
 use futures::future::try_join_all;
 use std::time::Instant;
+use tracing::Instrument;
 
 pub struct ExecutionPlan<Ctx> {
     stages: Vec<Stage<BoxedTask<Ctx>>>,
@@ -54,6 +55,9 @@ impl PipelineExecutor {
     /// This is the core pipeline execution loop. It iterates through stages
     /// and executes their tasks according to the stage's execution mode.
     ///
+    /// Each stage runs in a `pipeline_stage` span and each task in a
+    /// `pipeline_task` span, so stage timings can be read from traces.
+    ///
     /// Generic over:
     /// - `Ctx`: Shared pipeline context (use interior mutability for writes)
     pub async fn execute<Ctx>(pipeline: Pipeline<Ctx>, ctx: Ctx) -> BoxliteResult<PipelineMetrics>
@@ -66,37 +70,28 @@ impl PipelineExecutor {
         for (index, stage) in pipeline.stages.into_iter().enumerate() {
             let execution = stage.execution;
             let stage_start = Instant::now();
+            let stage_span = tracing::info_span!("pipeline_stage", index, execution = ?execution);
 
-            let task_metrics = match execution {
-                ExecutionMode::Parallel => {
-                    let futures = stage.tasks.into_iter().map(|task| {
-                        let ctx = ctx.clone();
-                        async move {
-                            let name = task.name().to_string();
-                            let task_start = Instant::now();
-                            task.run(ctx).await?;
-                            Ok::<TaskMetrics, boxlite_shared::errors::BoxliteError>(TaskMetrics {
-                                name,
-                                duration_ms: task_start.elapsed().as_millis(),
-                            })
+            let task_metrics = async {
+                match execution {
+                    ExecutionMode::Parallel => {
+                        let futures = stage
+                            .tasks
+                            .into_iter()
+                            .map(|task| Self::run_task(task, ctx.clone()));
+                        try_join_all(futures).await
+                    }
+                    ExecutionMode::Sequential => {
+                        let mut task_metrics = Vec::new();
+                        for task in stage.tasks {
+                            task_metrics.push(Self::run_task(task, ctx.clone()).await?);
                         }
-                    });
-                    try_join_all(futures).await?
-                }
-                ExecutionMode::Sequential => {
-                    let mut task_metrics = Vec::new();
-                    for task in stage.tasks {
-                        let name = task.name().to_string();
-                        let task_start = Instant::now();
-                        task.run(ctx.clone()).await?;
-                        task_metrics.push(TaskMetrics {
-                            name,
-                            duration_ms: task_start.elapsed().as_millis(),
-                        });
+                        Ok(task_metrics)
                     }
-                    task_metrics
                 }
-            };
+            }
+            .instrument(stage_span)
+            .await?;
 
             stage_metrics.push(StageMetrics {
                 index,
@@ -111,4 +106,15 @@ impl PipelineExecutor {
             stages: stage_metrics,
         })
     }
+
+    /// Run one task in its own span and time it.
+    async fn run_task<Ctx>(task: BoxedTask<Ctx>, ctx: Ctx) -> BoxliteResult<TaskMetrics> {
+        let name = task.name().to_string();
+        let span = tracing::info_span!("pipeline_task", task = %name);
+        let task_start = Instant::now();
+        task.run(ctx).instrument(span).await?;
+        let duration_ms = task_start.elapsed().as_millis();
+        tracing::debug!(task = %name, duration_ms, "Pipeline task finished");
+        Ok(TaskMetrics { name, duration_ms })
+    }
 }
@@ -0,0 +1,57 @@
+//! Admission policies for box creation.
+//!
+//! A [`CreatePolicy`] attached with `BoxliteRuntime::with_create_policy()`
+//! sees the resolved image and the requested options before a box is
+//! created, and can refuse the request. Refusals fail `create()` and
+//! `get_or_create()` with `BoxliteError::PolicyDenied` carrying the reason.
+//!
+//! No policy is attached by default, so every request is admitted.
+//! [`SeverityPolicy`] is the built-in policy: it rejects images whose
+//! registry-attached vulnerability report has findings above a threshold.
+//!
+//! Only image-based boxes are evaluated; boxes created from a local rootfs
+//! path, restored from the trash, or cloned from a snapshot are not.
+
+mod runtime;
+mod severity;
+
+pub(crate) use runtime::PolicyRuntime;
+pub use severity::SeverityPolicy;
+
+pub use crate::images::{ImageObject, Severity, Vulnerability, VulnerabilityReport};
+use crate::runtime::options::BoxOptions;
+
+/// Outcome of a [`CreatePolicy`] evaluation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PolicyDecision {
+    /// Create the box.
+    Allow,
+    /// Refuse the request; the reason is returned to the caller.
+    Deny(String),
+}
+
+/// Decides whether a box may be created from an image.
+///
+/// `evaluate` runs on the request path and must not block for long.
+pub trait CreatePolicy: Send + Sync {
+    /// Admit or refuse creating a box from `image` with `options`.
+    fn evaluate(&self, image: &ImageObject, options: &BoxOptions) -> PolicyDecision;
+
+    /// Whether `evaluate` reads `ImageObject::vulnerability_report()`.
+    ///
+    /// When true, the report is fetched from the image's registry before
+    /// each evaluation. Defaults to false.
+    fn needs_vulnerability_report(&self) -> bool {
+        false
+    }
+}
+
+/// Policy admitting every request.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AllowAll;
+
+impl CreatePolicy for AllowAll {
+    fn evaluate(&self, _image: &ImageObject, _options: &BoxOptions) -> PolicyDecision {
+        PolicyDecision::Allow
+    }
+}
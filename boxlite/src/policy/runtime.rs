@@ -0,0 +1,291 @@
+//! Runtime decorator enforcing a create policy.
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use super::{CreatePolicy, PolicyDecision};
+use crate::db::compositions::ComposedMember;
+use crate::db::templates::BoxTemplate;
+use crate::db::trash::TrashedBox;
+use crate::litebox::LiteBox;
+use crate::metrics::RuntimeMetrics;
+use crate::runtime::BulkResults;
+use crate::runtime::OrphanShim;
+use crate::runtime::WarmSelector;
+use crate::runtime::backend::RuntimeBackend;
+use crate::runtime::capabilities::RuntimeCapabilities;
+use crate::runtime::create_progress::CreateObserver;
+use crate::runtime::drift::{GetOrCreateOutcome, GetOrCreatePolicy};
+use crate::runtime::images::ImageManager;
+use crate::runtime::options::{BoxOptions, RootfsSpec};
+use crate::runtime::types::{BoxInfo, ListOptions, RemovePlan};
+#[cfg(feature = "url-transfer")]
+use crate::runtime::url_transfer::TransferOptions;
+use crate::runtime::version::VersionInfo;
+use crate::runtime::{ImportOptions, ImportPlan};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Runtime backend that evaluates a [`CreatePolicy`] before creating boxes.
+pub(crate) struct PolicyRuntime {
+    inner: Arc<dyn RuntimeBackend>,
+    images: Arc<dyn ImageManager>,
+    policy: Arc<dyn CreatePolicy>,
+}
+
+impl PolicyRuntime {
+    pub(crate) fn new(
+        inner: Arc<dyn RuntimeBackend>,
+        images: Arc<dyn ImageManager>,
+        policy: Arc<dyn CreatePolicy>,
+    ) -> Self {
+        Self {
+            inner,
+            images,
+            policy,
+        }
+    }
+
+    /// Fail with `PolicyDenied` unless the policy admits `options`.
+    async fn admit(&self, options: &BoxOptions) -> BoxliteResult<()> {
+        let RootfsSpec::Image(image_ref) = &options.rootfs else {
+            return Ok(());
+        };
+
+        // Resolves to the cached image when present; creation reuses it
+        let mut image = self.images.pull_image(image_ref, None).await?;
+
+        if self.policy.needs_vulnerability_report() {
+            let report = match self.images.vulnerability_report(&image).await {
+                Ok(report) => report,
+                Err(e) => {
+                    tracing::warn!(
+                        image = %image_ref,
+                        error = %e,
+                        "Failed to fetch vulnerability report; evaluating without one"
+                    );
+                    None
+                }
+            };
+            image = image.with_vulnerability_report(report);
+        }
+
+        match self.policy.evaluate(&image, options) {
+            PolicyDecision::Allow => Ok(()),
+            PolicyDecision::Deny(reason) => {
+                tracing::info!(image = %image_ref, %reason, "Box creation denied by policy");
+                Err(BoxliteError::PolicyDenied(reason))
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl RuntimeBackend for PolicyRuntime {
+    async fn create(&self, options: BoxOptions, name: Option<String>) -> BoxliteResult<LiteBox> {
+        self.admit(&options).await?;
+        self.inner.create(options, name).await
+    }
+
+    async fn create_with_observer(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: CreateObserver,
+    ) -> BoxliteResult<LiteBox> {
+        self.admit(&options).await?;
+        self.inner
+            .create_with_observer(options, name, observer)
+            .await
+    }
+
+    async fn get_or_create(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+    ) -> BoxliteResult<(LiteBox, bool)> {
+        // Returning an existing box creates nothing
+        let existing = match &name {
+            Some(name) => self.inner.exists(name).await?,
+            None => false,
+        };
+        if !existing {
+            self.admit(&options).await?;
+        }
+        self.inner.get_or_create(options, name).await
+    }
+
+    async fn get_or_create_with(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        policy: GetOrCreatePolicy,
+    ) -> BoxliteResult<(LiteBox, GetOrCreateOutcome)> {
+        // Only recreating replaces an existing box
+        let existing = match &name {
+            Some(name) => self.inner.exists(name).await?,
+            None => false,
+        };
+        if !existing || policy == GetOrCreatePolicy::RecreateOnMismatch {
+            self.admit(&options).await?;
+        }
+        self.inner.get_or_create_with(options, name, policy).await
+    }
+
+    async fn get(&self, id_or_name: &str) -> BoxliteResult<Option<LiteBox>> {
+        self.inner.get(id_or_name).await
+    }
+
+    async fn get_info(&self, id_or_name: &str) -> BoxliteResult<Option<BoxInfo>> {
+        self.inner.get_info(id_or_name).await
+    }
+
+    async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
+        self.inner.list_info().await
+    }
+
+    async fn list_info_with(&self, options: ListOptions) -> BoxliteResult<Vec<BoxInfo>> {
+        self.inner.list_info_with(options).await
+    }
+
+    // Warm pools are part of the runtime's own configuration, not a
+    // caller's request, so their boxes are not evaluated
+    async fn acquire_warm(&self, selector: WarmSelector) -> BoxliteResult<LiteBox> {
+        self.inner.acquire_warm(selector).await
+    }
+
+    async fn exists(&self, id_or_name: &str) -> BoxliteResult<bool> {
+        self.inner.exists(id_or_name).await
+    }
+
+    async fn metrics(&self) -> BoxliteResult<RuntimeMetrics> {
+        self.inner.metrics().await
+    }
+
+    async fn version_info(&self) -> BoxliteResult<VersionInfo> {
+        self.inner.version_info().await
+    }
+
+    async fn capabilities(&self) -> BoxliteResult<RuntimeCapabilities> {
+        self.inner.capabilities().await
+    }
+
+    async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        self.inner.remove(id_or_name, force).await
+    }
+
+    async fn remove_permanently(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        self.inner.remove_permanently(id_or_name, force).await
+    }
+
+    async fn plan_remove(
+        &self,
+        id_or_name: &str,
+        force: bool,
+        permanently: bool,
+    ) -> BoxliteResult<RemovePlan> {
+        self.inner.plan_remove(id_or_name, force, permanently).await
+    }
+
+    async fn list_trashed(&self) -> BoxliteResult<Vec<TrashedBox>> {
+        self.inner.list_trashed().await
+    }
+
+    async fn register_template(
+        &self,
+        name: &str,
+        options: BoxOptions,
+    ) -> BoxliteResult<BoxTemplate> {
+        self.inner.register_template(name, options).await
+    }
+
+    async fn get_template(&self, name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        self.inner.get_template(name).await
+    }
+
+    async fn list_templates(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        self.inner.list_templates().await
+    }
+
+    async fn remove_template(&self, name: &str) -> BoxliteResult<()> {
+        self.inner.remove_template(name).await
+    }
+
+    async fn record_composition(&self, members: &[ComposedMember]) -> BoxliteResult<()> {
+        self.inner.record_composition(members).await
+    }
+
+    async fn list_composition(&self) -> BoxliteResult<Vec<ComposedMember>> {
+        self.inner.list_composition().await
+    }
+
+    async fn forget_composition(&self, names: &[String]) -> BoxliteResult<()> {
+        self.inner.forget_composition(names).await
+    }
+
+    async fn restore_trashed(&self, id_or_name: &str) -> BoxliteResult<LiteBox> {
+        self.inner.restore_trashed(id_or_name).await
+    }
+
+    async fn purge_trashed(&self, id_or_name: &str) -> BoxliteResult<()> {
+        self.inner.purge_trashed(id_or_name).await
+    }
+
+    async fn list_orphans(&self) -> BoxliteResult<Vec<OrphanShim>> {
+        self.inner.list_orphans().await
+    }
+
+    async fn kill_orphans(&self, grace: Duration) -> BoxliteResult<Vec<OrphanShim>> {
+        self.inner.kill_orphans(grace).await
+    }
+
+    async fn detach_all(&self) -> BoxliteResult<BulkResults<()>> {
+        self.inner.detach_all().await
+    }
+
+    async fn adopt_all(&self) -> BoxliteResult<BulkResults<()>> {
+        self.inner.adopt_all().await
+    }
+
+    // Archives carry their disks; there is no image to evaluate
+    async fn import(&self, archive_path: &Path, options: ImportOptions) -> BoxliteResult<LiteBox> {
+        self.inner.import(archive_path, options).await
+    }
+
+    async fn plan_import(
+        &self,
+        archive_path: &Path,
+        options: &ImportOptions,
+    ) -> BoxliteResult<ImportPlan> {
+        self.inner.plan_import(archive_path, options).await
+    }
+
+    #[cfg(feature = "url-transfer")]
+    async fn import_from_url(
+        &self,
+        url: &str,
+        options: ImportOptions,
+        transfer: TransferOptions,
+    ) -> BoxliteResult<LiteBox> {
+        self.inner.import_from_url(url, options, transfer).await
+    }
+
+    async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<()> {
+        self.inner.shutdown(timeout).await
+    }
+
+    fn shutdown_sync(&self) {
+        self.inner.shutdown_sync()
+    }
+
+    #[cfg(feature = "rest-server")]
+    fn shutdown_token(&self) -> Option<tokio_util::sync::CancellationToken> {
+        self.inner.shutdown_token()
+    }
+
+    fn audit_peer(&self) -> Option<String> {
+        self.inner.audit_peer()
+    }
+}
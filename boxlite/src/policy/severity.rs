@@ -0,0 +1,216 @@
+//! Built-in policy gating images on their vulnerability report.
+
+use std::collections::HashSet;
+
+use super::{CreatePolicy, ImageObject, PolicyDecision, Severity, VulnerabilityReport};
+use crate::runtime::options::BoxOptions;
+
+/// Finding IDs quoted in a denial reason.
+const MAX_REPORTED_IDS: usize = 5;
+
+/// Rejects images with vulnerabilities more severe than a threshold.
+///
+/// The report is the one a scanner attached to the image in its registry as
+/// an OCI referrer (CycloneDX, Trivy or Grype JSON, or a cosign `vuln`
+/// attestation). Images without a report are admitted unless
+/// [`deny_unscanned`](Self::deny_unscanned) is set.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use boxlite::policy::{Severity, SeverityPolicy};
+/// use boxlite::runtime::BoxliteRuntime;
+///
+/// // Refuse images with known critical CVEs, except one vetted build
+/// let policy = SeverityPolicy::new(Severity::High).allow_digest("sha256:4bcff6...");
+/// let runtime = BoxliteRuntime::with_defaults()?.with_create_policy(Arc::new(policy))?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct SeverityPolicy {
+    max_allowed: Severity,
+    allowed_digests: HashSet<String>,
+    deny_unscanned: bool,
+}
+
+impl SeverityPolicy {
+    /// Deny images with findings above `max_allowed`.
+    pub fn new(max_allowed: Severity) -> Self {
+        Self {
+            max_allowed,
+            allowed_digests: HashSet::new(),
+            deny_unscanned: false,
+        }
+    }
+
+    /// Always admit the image with this manifest digest (`sha256:...`).
+    ///
+    /// For multi-platform images this is the platform-specific manifest,
+    /// as shown by `ImageObject::manifest_digest()`.
+    pub fn allow_digest(mut self, digest: impl Into<String>) -> Self {
+        self.allowed_digests.insert(digest.into());
+        self
+    }
+
+    /// Deny images the registry has no vulnerability report for.
+    pub fn deny_unscanned(mut self, deny: bool) -> Self {
+        self.deny_unscanned = deny;
+        self
+    }
+
+    fn decide(
+        &self,
+        reference: &str,
+        digest: &str,
+        report: Option<&VulnerabilityReport>,
+    ) -> PolicyDecision {
+        if self.allowed_digests.contains(digest) {
+            return PolicyDecision::Allow;
+        }
+
+        let Some(report) = report else {
+            if self.deny_unscanned {
+                return PolicyDecision::Deny(format!(
+                    "image {reference} ({digest}) has no vulnerability report"
+                ));
+            }
+            return PolicyDecision::Allow;
+        };
+
+        let findings: Vec<_> = report
+            .vulnerabilities
+            .iter()
+            .filter(|v| v.severity > self.max_allowed)
+            .collect();
+        if findings.is_empty() {
+            return PolicyDecision::Allow;
+        }
+
+        let mut ids: Vec<&str> = findings
+            .iter()
+            .take(MAX_REPORTED_IDS)
+            .map(|v| v.id.as_str())
+            .collect();
+        if findings.len() > MAX_REPORTED_IDS {
+            ids.push("...");
+        }
+        let worst = report.max_severity().unwrap_or(Severity::Unknown);
+        PolicyDecision::Deny(format!(
+            "image {reference} ({digest}) has vulnerabilities above {} ({} found, worst {worst}): {}",
+            self.max_allowed,
+            findings.len(),
+            ids.join(", ")
+        ))
+    }
+}
+
+impl CreatePolicy for SeverityPolicy {
+    fn evaluate(&self, image: &ImageObject, _options: &BoxOptions) -> PolicyDecision {
+        self.decide(
+            image.reference(),
+            image.manifest_digest(),
+            image.vulnerability_report(),
+        )
+    }
+
+    fn needs_vulnerability_report(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::images::Vulnerability;
+
+    const DIGEST: &str = "sha256:aaaa";
+
+    fn report(findings: &[(&str, Severity)]) -> VulnerabilityReport {
+        VulnerabilityReport {
+            vulnerabilities: findings
+                .iter()
+                .map(|(id, severity)| Vulnerability {
+                    id: id.to_string(),
+                    severity: *severity,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_denies_findings_above_threshold() {
+        let policy = SeverityPolicy::new(Severity::High);
+
+        let ok = report(&[("CVE-1", Severity::High), ("CVE-2", Severity::Low)]);
+        assert_eq!(
+            policy.decide("alpine", DIGEST, Some(&ok)),
+            PolicyDecision::Allow
+        );
+
+        let bad = report(&[("CVE-1", Severity::High), ("CVE-9", Severity::Critical)]);
+        let PolicyDecision::Deny(reason) = policy.decide("alpine", DIGEST, Some(&bad)) else {
+            panic!("critical finding admitted");
+        };
+        assert_eq!(
+            reason,
+            "image alpine (sha256:aaaa) has vulnerabilities above high (1 found, worst critical): CVE-9"
+        );
+    }
+
+    #[test]
+    fn test_denial_reason_truncates_ids() {
+        let findings: Vec<(String, Severity)> = (0..7)
+            .map(|i| (format!("CVE-{i}"), Severity::Critical))
+            .collect();
+        let findings: Vec<(&str, Severity)> =
+            findings.iter().map(|(id, s)| (id.as_str(), *s)).collect();
+
+        let decision = SeverityPolicy::new(Severity::Medium).decide(
+            "alpine",
+            DIGEST,
+            Some(&report(&findings)),
+        );
+        let PolicyDecision::Deny(reason) = decision else {
+            panic!("critical findings admitted");
+        };
+        assert!(reason.contains("(7 found, worst critical)"), "{reason}");
+        assert!(
+            reason.ends_with("CVE-0, CVE-1, CVE-2, CVE-3, CVE-4, ..."),
+            "{reason}"
+        );
+    }
+
+    #[test]
+    fn test_allowlisted_digest_bypasses_report() {
+        let policy = SeverityPolicy::new(Severity::Low)
+            .allow_digest(DIGEST)
+            .deny_unscanned(true);
+        let bad = report(&[("CVE-1", Severity::Critical)]);
+
+        assert_eq!(
+            policy.decide("alpine", DIGEST, Some(&bad)),
+            PolicyDecision::Allow
+        );
+        assert_eq!(policy.decide("alpine", DIGEST, None), PolicyDecision::Allow);
+        assert!(matches!(
+            policy.decide("alpine", "sha256:bbbb", Some(&bad)),
+            PolicyDecision::Deny(_)
+        ));
+    }
+
+    #[test]
+    fn test_unscanned_images() {
+        let lenient = SeverityPolicy::new(Severity::High);
+        assert_eq!(
+            lenient.decide("alpine", DIGEST, None),
+            PolicyDecision::Allow
+        );
+
+        let strict = lenient.deny_unscanned(true);
+        let PolicyDecision::Deny(reason) = strict.decide("alpine", DIGEST, None) else {
+            panic!("unscanned image admitted");
+        };
+        assert!(reason.contains("no vulnerability report"), "{reason}");
+    }
+}
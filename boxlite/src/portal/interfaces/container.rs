@@ -2,11 +2,17 @@
 
 use boxlite_shared::{
     BindMount, BoxliteError, BoxliteResult, ContainerClient,
-    ContainerConfig as ProtoContainerConfig, ContainerInitRequest, DiskRootfs, MergedRootfs,
-    OverlayRootfs, RootfsInit, container_init_response,
+    ContainerConfig as ProtoContainerConfig, ContainerInitRequest, CoreDump as ProtoCoreDump,
+    DiskRootfs, HostEntry as ProtoHostEntry, IdMapping as ProtoIdMapping, ListCoreDumpsRequest,
+    MergedRootfs, OverlayRootfs, RootfsInit, TimeZone as ProtoTimeZone, UpdateHostsRequest,
+    UserNamespace, container_init_response,
 };
+use tonic::Code;
 use tonic::transport::Channel;
 
+use crate::net::HostEntry;
+use crate::runtime::locale::TimeZone;
+use crate::runtime::options::{IdMapping, UserNsMode};
 use crate::volumes::ContainerMount;
 
 /// Container rootfs initialization strategy.
@@ -69,6 +75,37 @@ impl ContainerRootfsInitConfig {
     }
 }
 
+/// Convert user namespace mode to proto (empty maps = auto).
+fn userns_to_proto(mode: UserNsMode) -> UserNamespace {
+    let convert = |map: Vec<IdMapping>| {
+        map.into_iter()
+            .map(|m| ProtoIdMapping {
+                container_id: m.container_id,
+                host_id: m.host_id,
+                size: m.size,
+            })
+            .collect()
+    };
+
+    match mode {
+        UserNsMode::Auto => UserNamespace::default(),
+        UserNsMode::Map { uid_map, gid_map } => UserNamespace {
+            uid_map: convert(uid_map),
+            gid_map: convert(gid_map),
+        },
+    }
+}
+
+fn hosts_to_proto(entries: Vec<HostEntry>) -> Vec<ProtoHostEntry> {
+    entries
+        .into_iter()
+        .map(|e| ProtoHostEntry {
+            ip: e.ip,
+            names: e.names,
+        })
+        .collect()
+}
+
 /// Container service interface.
 pub struct ContainerInterface {
     client: ContainerClient<Channel>,
@@ -89,15 +126,34 @@ impl ContainerInterface {
     /// * `image_config` - Image-derived container config (entrypoint, env, workdir)
     /// * `rootfs` - Rootfs initialization strategy
     /// * `mounts` - Bind mounts from guest VM paths into container
+    /// * `userns` - User namespace mode (None = share the guest's)
+    /// * `init` - Run the entrypoint under the guest's built-in init
+    /// * `entrypoint_script` - Script that wraps the entrypoint
+    /// * `capture_core_dumps` - Capture core dumps of container processes
+    /// * `swap_mib` - Swap file size on the container disk (0 = no swap)
+    /// * `time_zone` - Zone placed at `/etc/localtime` (None = the image's)
+    /// * `read_only_rootfs` - Mount the container rootfs read-only
+    /// * `writable_paths` - Container paths given a writable tmpfs
+    /// * `extra_hosts` - Entries appended to the container's `/etc/hosts`
     ///
     /// # Returns
     /// Container ID on success
+    #[allow(clippy::too_many_arguments)]
     pub async fn init(
         &mut self,
         container_id: &str,
         image_config: crate::images::ContainerImageConfig,
         rootfs: ContainerRootfsInitConfig,
         mounts: Vec<ContainerMount>,
+        userns: Option<UserNsMode>,
+        init: bool,
+        entrypoint_script: Option<String>,
+        capture_core_dumps: bool,
+        swap_mib: u32,
+        time_zone: Option<TimeZone>,
+        read_only_rootfs: bool,
+        writable_paths: Vec<String>,
+        extra_hosts: Vec<HostEntry>,
     ) -> BoxliteResult<String> {
         let proto_config = ProtoContainerConfig {
             entrypoint: image_config.final_cmd(),
@@ -127,6 +183,15 @@ impl ContainerInterface {
             env_count = image_config.env.len(),
             rootfs = ?rootfs,
             mounts_count = proto_mounts.len(),
+            userns = ?userns,
+            init,
+            entrypoint_script = entrypoint_script.is_some(),
+            capture_core_dumps,
+            swap_mib,
+            time_zone = ?time_zone.as_ref().map(|tz| &tz.name),
+            read_only_rootfs,
+            writable_paths = ?writable_paths,
+            extra_hosts = ?extra_hosts,
             "Container configuration"
         );
 
@@ -135,6 +200,18 @@ impl ContainerInterface {
             container_config: Some(proto_config),
             rootfs: Some(rootfs.into_proto()),
             mounts: proto_mounts,
+            userns: userns.map(userns_to_proto),
+            init,
+            entrypoint_script,
+            capture_core_dumps,
+            swap_mib,
+            time_zone: time_zone.map(|tz| ProtoTimeZone {
+                name: tz.name,
+                tzif: tz.tzif.to_vec(),
+            }),
+            read_only_rootfs,
+            writable_paths,
+            extra_hosts: hosts_to_proto(extra_hosts),
         };
 
         let response = self.client.init(request).await?.into_inner();
@@ -156,4 +233,35 @@ impl ContainerInterface {
             )),
         }
     }
+
+    /// List the core dumps captured in the container, oldest first.
+    pub async fn list_core_dumps(
+        &mut self,
+        container_id: &str,
+    ) -> BoxliteResult<Vec<ProtoCoreDump>> {
+        let request = ListCoreDumpsRequest {
+            container_id: container_id.to_string(),
+        };
+        let response = self.client.list_core_dumps(request).await?.into_inner();
+        Ok(response.dumps)
+    }
+
+    /// Replace the extra entries of the container's `/etc/hosts`.
+    pub async fn update_hosts(
+        &mut self,
+        container_id: &str,
+        entries: Vec<HostEntry>,
+    ) -> BoxliteResult<()> {
+        let request = UpdateHostsRequest {
+            container_id: container_id.to_string(),
+            entries: hosts_to_proto(entries),
+        };
+        match self.client.update_hosts(request).await {
+            Ok(_) => Ok(()),
+            Err(status) if status.code() == Code::Unimplemented => Err(BoxliteError::Unsupported(
+                "guest agent does not support hosts updates (needs protocol 12)".to_string(),
+            )),
+            Err(status) => Err(status.into()),
+        }
+    }
 }
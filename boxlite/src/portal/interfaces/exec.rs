@@ -3,10 +3,14 @@
 //! High-level API for execution operations (unary Exec + output-only Attach +
 //! blocking Wait).
 
-use crate::litebox::{BoxCommand, ExecResult};
+use crate::litebox::capture::DETACHED_OUTPUT_MAX_BYTES;
+use crate::litebox::pipe::{self, PacedReceiver, PacedSender, Pacing};
+use crate::litebox::{BoxCommand, EnvEdit, ExecResult};
+use boxlite_shared::constants::prepared as prepared_const;
 use boxlite_shared::{
-    AttachRequest, BoxliteError, BoxliteResult, ExecOutput, ExecRequest, ExecStdin,
-    ExecutionClient, KillRequest, WaitRequest, WaitResponse, exec_output,
+    AttachRequest, BoxliteError, BoxliteResult, ExecOutput, ExecPreparedRequest, ExecRequest,
+    ExecResponse, ExecStdin, ExecutionClient, KillRequest, OutputCapture, PrepareRequest,
+    ScratchDir, UnprepareRequest, WaitRequest, WaitResponse, exec_output,
 };
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
@@ -22,9 +26,10 @@ pub struct ExecutionInterface {
 /// Components for building an Execution.
 pub struct ExecComponents {
     pub execution_id: String,
-    pub stdin_tx: mpsc::UnboundedSender<Vec<u8>>,
-    pub stdout_rx: mpsc::UnboundedReceiver<String>,
-    pub stderr_rx: mpsc::UnboundedReceiver<String>,
+    pub stdin_tx: PacedSender<Vec<u8>>,
+    /// Raw output; decoded by the output filters of the execution.
+    pub stdout_rx: PacedReceiver<Vec<u8>>,
+    pub stderr_rx: PacedReceiver<Vec<u8>>,
     pub result_rx: mpsc::UnboundedReceiver<ExecResult>,
 }
 
@@ -46,12 +51,6 @@ impl ExecutionInterface {
         command: BoxCommand,
         shutdown_token: CancellationToken,
     ) -> BoxliteResult<ExecComponents> {
-        // Create channels
-        let (stdin_tx, stdin_rx) = mpsc::unbounded_channel::<Vec<u8>>();
-        let (stdout_tx, stdout_rx) = mpsc::unbounded_channel::<String>();
-        let (stderr_tx, stderr_rx) = mpsc::unbounded_channel::<String>();
-        let (result_tx, result_rx) = mpsc::unbounded_channel();
-
         // Build request
         let request = ExecProtocol::build_exec_request(&command);
 
@@ -59,6 +58,82 @@ impl ExecutionInterface {
 
         // Start execution
         let exec_response = self.client.exec(request).await?.into_inner();
+        self.attach_execution(exec_response, shutdown_token)
+    }
+
+    /// Register a command template with the guest and return its ID.
+    ///
+    /// The guest merges the container env and validates the working
+    /// directory once; later runs only send the prepared ID and extra args.
+    pub async fn prepare(&mut self, command: &BoxCommand) -> BoxliteResult<String> {
+        let request = PrepareRequest {
+            template: Some(ExecProtocol::build_exec_request(command)),
+        };
+
+        tracing::debug!(?command, "Preparing command");
+
+        let response = self.client.prepare(request).await?.into_inner();
+        match response.error {
+            Some(err) if err.reason == prepared_const::LIMIT_REASON => {
+                Err(BoxliteError::InvalidState(format!(
+                    "{}; drop unused prepared commands",
+                    err.detail
+                )))
+            }
+            Some(err) => Err(BoxliteError::InvalidArgument(format!(
+                "{}: {}",
+                err.reason, err.detail
+            ))),
+            None => Ok(response.prepared_id),
+        }
+    }
+
+    /// Drop a prepared command from the guest.
+    ///
+    /// Agents older than protocol 15 have no Unprepare and keep the
+    /// template; that is not an error.
+    pub async fn unprepare(&mut self, prepared_id: &str) -> BoxliteResult<()> {
+        let request = UnprepareRequest {
+            prepared_id: prepared_id.to_string(),
+        };
+        match self.client.unprepare(request).await {
+            Ok(_) => Ok(()),
+            Err(status) if status.code() == tonic::Code::Unimplemented => Ok(()),
+            Err(status) => Err(status.into()),
+        }
+    }
+
+    /// Execute a prepared command with extra args appended.
+    ///
+    /// Returns `BoxliteError::NotFound` if the guest no longer knows the
+    /// template (e.g. the box restarted); callers re-register and retry.
+    pub async fn exec_prepared(
+        &mut self,
+        prepared_id: &str,
+        args: Vec<String>,
+        shutdown_token: CancellationToken,
+    ) -> BoxliteResult<ExecComponents> {
+        let request = ExecPreparedRequest {
+            prepared_id: prepared_id.to_string(),
+            execution_id: None,
+            args,
+        };
+
+        let exec_response = self.client.exec_prepared(request).await?.into_inner();
+        if let Some(err) = &exec_response.error
+            && err.reason == prepared_const::NOT_FOUND_REASON
+        {
+            return Err(BoxliteError::NotFound(err.detail.clone()));
+        }
+        self.attach_execution(exec_response, shutdown_token)
+    }
+
+    /// Wire stdin/output/wait pumps to a started execution.
+    fn attach_execution(
+        &self,
+        exec_response: ExecResponse,
+        shutdown_token: CancellationToken,
+    ) -> BoxliteResult<ExecComponents> {
         if let Some(err) = exec_response.error {
             return Err(BoxliteError::Internal(format!(
                 "{}: {}",
@@ -66,7 +141,13 @@ impl ExecutionInterface {
             )));
         }
 
-        let execution_id = exec_response.execution_id.clone();
+        // Create channels
+        let (stdin_tx, stdin_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let (stdout_tx, stdout_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let (stderr_tx, stderr_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let (result_tx, result_rx) = mpsc::unbounded_channel();
+
+        let execution_id = exec_response.execution_id;
 
         // Spawn stdin pump (cancellable — exits cleanly during shutdown)
         ExecProtocol::spawn_stdin(
@@ -191,14 +272,14 @@ impl crate::runtime::backend::ExecBackend for ExecutionInterface {
 // Helper: Protocol wiring
 // ============================================================================
 
-struct ExecProtocol;
+pub(super) struct ExecProtocol;
 
 impl ExecProtocol {
-    fn build_exec_request(command: &BoxCommand) -> ExecRequest {
+    pub(super) fn build_exec_request(command: &BoxCommand) -> ExecRequest {
         use boxlite_shared::TtyConfig;
 
         ExecRequest {
-            execution_id: None,
+            execution_id: command.execution_id.clone(),
             program: command.command.clone(),
             args: command.args.clone(),
             env: command
@@ -220,6 +301,41 @@ impl ExecProtocol {
             } else {
                 None
             },
+            capture: command.detach.then_some(OutputCapture {
+                max_bytes: DETACHED_OUTPUT_MAX_BYTES,
+            }),
+            inherit_runtime_env: command.inherit_runtime_env,
+            scratch: command.scratch_dir.as_ref().map(|scratch| ScratchDir {
+                size_mib: scratch.size_mib,
+                mount_at: scratch.mount_at.clone(),
+            }),
+            env_edits: command.env_edits.iter().map(Self::env_edit).collect(),
+        }
+    }
+
+    fn env_edit(edit: &EnvEdit) -> boxlite_shared::EnvEdit {
+        use boxlite_shared::PathEdit;
+        use boxlite_shared::env_edit::Edit;
+
+        let key = edit.key();
+        let edit = match edit {
+            EnvEdit::Prepend {
+                value, separator, ..
+            } => Edit::Prepend(PathEdit {
+                value: value.clone(),
+                separator: separator.clone(),
+            }),
+            EnvEdit::Append {
+                value, separator, ..
+            } => Edit::Append(PathEdit {
+                value: value.clone(),
+                separator: separator.clone(),
+            }),
+            EnvEdit::Unset { .. } => Edit::Unset(true),
+        };
+        boxlite_shared::EnvEdit {
+            key: key.to_string(),
+            edit: Some(edit),
         }
     }
 
@@ -243,8 +359,8 @@ impl ExecProtocol {
     fn spawn_attach(
         mut client: ExecutionClient<Channel>,
         execution_id: String,
-        stdout_tx: mpsc::UnboundedSender<String>,
-        stderr_tx: mpsc::UnboundedSender<String>,
+        stdout_tx: PacedSender<Vec<u8>>,
+        stderr_tx: PacedSender<Vec<u8>>,
         shutdown_token: CancellationToken,
     ) {
         tokio::spawn(async move {
@@ -286,7 +402,13 @@ impl ExecProtocol {
                         match output.transpose() {
                             Some(Ok(output)) => {
                                 message_count += 1;
-                                Self::route_output(output, &stdout_tx, &stderr_tx);
+                                // Waits while a pipe is behind, which stops
+                                // reading and holds the guest back
+                                tokio::select! {
+                                    biased;
+                                    _ = shutdown_token.cancelled() => break,
+                                    _ = Self::route_output(output, &stdout_tx, &stderr_tx) => {}
+                                }
                             }
                             Some(Err(e)) => {
                                 tracing::debug!(
@@ -295,7 +417,8 @@ impl ExecProtocol {
                                     message_count,
                                     "Attach stream error, breaking"
                                 );
-                                let _ = stderr_tx.send(format!("Attach stream error: {}", e));
+                                let _ = stderr_tx
+                                    .send_now(format!("Attach stream error: {}", e).into_bytes());
                                 break;
                             }
                             None => {
@@ -313,27 +436,25 @@ impl ExecProtocol {
                 }
                 Err(e) => {
                     tracing::debug!(execution_id = %execution_id, error = %e, "Attach failed");
-                    let _ = stderr_tx.send(format!("Attach failed: {}", e));
+                    let _ = stderr_tx.send_now(format!("Attach failed: {}", e).into_bytes());
                 }
             }
         });
     }
 
-    fn route_output(
+    async fn route_output(
         output: ExecOutput,
-        stdout_tx: &mpsc::UnboundedSender<String>,
-        stderr_tx: &mpsc::UnboundedSender<String>,
+        stdout_tx: &PacedSender<Vec<u8>>,
+        stderr_tx: &PacedSender<Vec<u8>>,
     ) {
         match output.event {
             Some(exec_output::Event::Stdout(chunk)) => {
-                let stdout_data = String::from_utf8_lossy(&chunk.data).to_string();
-                tracing::trace!(?stdout_data, "Received exec stdout");
-                let _ = stdout_tx.send(stdout_data);
+                tracing::trace!(len = chunk.data.len(), "Received exec stdout");
+                let _ = stdout_tx.send(chunk.data).await;
             }
             Some(exec_output::Event::Stderr(chunk)) => {
-                let stderr_data = String::from_utf8_lossy(&chunk.data).to_string();
-                tracing::trace!(?stderr_data, "Received exec stderr");
-                let _ = stderr_tx.send(stderr_data);
+                tracing::trace!(len = chunk.data.len(), "Received exec stderr");
+                let _ = stderr_tx.send(chunk.data).await;
             }
             None => {}
         }
@@ -355,9 +476,8 @@ impl ExecProtocol {
                 biased;
                 _ = shutdown_token.cancelled() => {
                     tracing::debug!(execution_id = %execution_id, "Wait cancelled during shutdown");
-                    // Send a special result indicating cancellation
-                    // Using exit code -1 to indicate abnormal termination
-                    let _ = result_tx.send(ExecResult { exit_code: -1, error_message: None });
+                    // Closing the channel without a result makes
+                    // Execution::wait() report the box as stopped
                     return;
                 }
                 result = client.wait(request) => result,
@@ -368,6 +488,8 @@ impl ExecProtocol {
                     let mapped = Self::map_wait_response(resp.into_inner());
                     let _ = result_tx.send(mapped);
                 }
+                // The connection broke because the box went away
+                Err(_) if shutdown_token.is_cancelled() => {}
                 Err(e) => {
                     tracing::warn!(
                         execution_id = %execution_id,
@@ -386,15 +508,17 @@ impl ExecProtocol {
     fn spawn_stdin(
         mut client: ExecutionClient<Channel>,
         execution_id: String,
-        mut stdin_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+        mut stdin_rx: PacedReceiver<Vec<u8>>,
         shutdown_token: CancellationToken,
     ) {
         tokio::spawn(async move {
             let (tx, rx) = mpsc::channel::<ExecStdin>(8);
 
-            // Producer: forward stdin channel into tonic stream
+            // Producer: forward stdin channel into tonic stream. Once the
+            // channel ends (`ExecStdin::close` or drop) an explicit close
+            // message tells the guest to close the process's stdin.
             let exec_id_clone = execution_id.clone();
-            tokio::spawn(async move {
+            let producer = tokio::spawn(async move {
                 while let Some(data) = stdin_rx.recv().await {
                     let msg = ExecStdin {
                         execution_id: exec_id_clone.clone(),
@@ -432,6 +556,11 @@ impl ExecProtocol {
                     }
                 }
             }
+
+            // The guest stopped reading (the process closed its stdin or
+            // exited): drop the receiver so further writes fail fast instead
+            // of queueing.
+            producer.abort();
         });
     }
 }
@@ -562,22 +691,21 @@ mod tests {
     }
 
     /// Test simulating spawn_wait cancellation behavior.
-    /// When token is cancelled, the result channel should receive exit_code -1.
+    /// When token is cancelled, the result channel closes without a result.
     #[tokio::test]
-    async fn test_spawn_wait_cancellation_sends_result() {
+    async fn test_spawn_wait_cancellation_closes_channel() {
         let token = CancellationToken::new();
-        let (result_tx, mut result_rx) = mpsc::unbounded_channel();
+        let (result_tx, mut result_rx) = mpsc::unbounded_channel::<ExecResult>();
 
         // Simulate spawn_wait's cancellation handling
         let token_clone = token.clone();
         let handle = tokio::spawn(async move {
             tokio::select! {
                 biased;
-                _ = token_clone.cancelled() => {
-                    let _ = result_tx.send(ExecResult { exit_code: -1, error_message: None });
-                }
+                _ = token_clone.cancelled() => {}
                 _ = tokio::time::sleep(Duration::from_secs(3600)) => {
                     // Would normally wait for gRPC response
+                    let _ = result_tx.send(ExecResult { exit_code: 0, error_message: None });
                 }
             }
         });
@@ -589,10 +717,8 @@ mod tests {
         // Wait for task to complete
         handle.await.unwrap();
 
-        // Should have received cancellation result
-        let result = result_rx.recv().await;
-        assert!(result.is_some());
-        assert_eq!(result.unwrap().exit_code, -1);
+        // The sender was dropped without a result
+        assert!(result_rx.recv().await.is_none());
     }
 
     /// Test simulating spawn_attach cancellation behavior.
@@ -2,11 +2,18 @@
 //!
 //! Provides tar-based upload/download to the guest container rootfs.
 
-use boxlite_shared::{BoxliteError, BoxliteResult, DownloadRequest, FilesClient, UploadChunk};
+use boxlite_shared::archive::parse_unsafe_archive;
+use boxlite_shared::{
+    ArchiveLimits, BoxliteError, BoxliteResult, DownloadRequest, FilesClient, UploadChunk,
+    UploadOwnership, UploadResponse,
+};
+use futures::{Stream, StreamExt};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tonic::transport::Channel;
 
+use crate::litebox::CopyOwnership;
+
 const CHUNK_SIZE: usize = 1 << 20; // 1 MiB
 
 /// Files service interface.
@@ -23,6 +30,7 @@ impl FilesInterface {
     }
 
     /// Upload a tar file to the guest and extract at dest_path.
+    #[allow(clippy::too_many_arguments)]
     pub async fn upload_tar(
         &mut self,
         tar_path: &std::path::Path,
@@ -30,9 +38,12 @@ impl FilesInterface {
         container_id: Option<&str>,
         mkdir_parents: bool,
         overwrite: bool,
+        ownership: CopyOwnership,
+        limits: ArchiveLimits,
     ) -> BoxliteResult<()> {
         let dest = dest_path.to_string();
         let cid = container_id.unwrap_or_default().to_string();
+        let (mode, uid, gid) = upload_ownership(ownership);
 
         // Read entire tar file and build chunks
         // Note: For very large files, consider streaming with async_stream crate
@@ -54,6 +65,11 @@ impl FilesInterface {
                         data: buf[..n].to_vec(),
                         mkdir_parents,
                         overwrite,
+                        ownership: mode as i32,
+                        uid,
+                        gid,
+                        streamed: false,
+                        limits: first.then(|| limits.into()),
                     };
                     first = false;
                     chunks.push(chunk);
@@ -77,13 +93,52 @@ impl FilesInterface {
             .map_err(map_tonic_err)?
             .into_inner();
 
-        if response.success {
-            Ok(())
-        } else {
-            Err(BoxliteError::Internal(
-                response.error.unwrap_or_else(|| "Upload failed".into()),
-            ))
-        }
+        upload_result(response)
+    }
+
+    /// Upload a tar archive as `archive` yields it. The guest unpacks each
+    /// entry into the directory `dest_path` as it arrives, without staging
+    /// the archive.
+    pub async fn upload_tar_stream<S>(
+        &mut self,
+        archive: S,
+        dest_path: &str,
+        container_id: Option<&str>,
+        overwrite: bool,
+        ownership: CopyOwnership,
+        limits: ArchiveLimits,
+    ) -> BoxliteResult<()>
+    where
+        S: Stream<Item = Vec<u8>> + Send + 'static,
+    {
+        let (mode, uid, gid) = upload_ownership(ownership);
+        // The first chunk carries the request, later ones only data
+        let first = UploadChunk {
+            dest_path: dest_path.to_string(),
+            container_id: container_id.unwrap_or_default().to_string(),
+            data: Vec::new(),
+            mkdir_parents: true,
+            overwrite,
+            ownership: mode as i32,
+            uid,
+            gid,
+            streamed: true,
+            limits: Some(limits.into()),
+        };
+        let rest = archive.map(|data| UploadChunk {
+            data,
+            ..Default::default()
+        });
+        let chunks = futures::stream::once(async move { first }).chain(rest);
+
+        let response = self
+            .client
+            .upload(chunks)
+            .await
+            .map_err(map_tonic_err)?
+            .into_inner();
+
+        upload_result(response)
     }
 
     /// Download a path from guest into a local tar file.
@@ -134,6 +189,30 @@ impl FilesInterface {
     }
 }
 
+fn upload_ownership(ownership: CopyOwnership) -> (UploadOwnership, u32, u32) {
+    match ownership {
+        CopyOwnership::PreserveHost => (UploadOwnership::Preserve, 0, 0),
+        CopyOwnership::ContainerUser => (UploadOwnership::ContainerUser, 0, 0),
+        CopyOwnership::Explicit { uid, gid } => (UploadOwnership::Explicit, uid, gid),
+    }
+}
+
+fn upload_result(response: UploadResponse) -> BoxliteResult<()> {
+    if response.success {
+        Ok(())
+    } else {
+        Err(BoxliteError::Internal(
+            response.error.unwrap_or_else(|| "Upload failed".into()),
+        ))
+    }
+}
+
 fn map_tonic_err(err: tonic::Status) -> BoxliteError {
+    // The guest refuses unsafe archives as invalid arguments
+    if err.code() == tonic::Code::InvalidArgument
+        && let Some(unsafe_archive) = parse_unsafe_archive(err.message())
+    {
+        return unsafe_archive;
+    }
     BoxliteError::Internal(err.to_string())
 }
@@ -1,9 +1,13 @@
 //! Guest service interface.
 
+use boxlite_shared::constants::agent_protocol;
 use boxlite_shared::{
-    BlockDeviceSource, BoxliteError, BoxliteResult, Filesystem, GuestClient, GuestInitRequest,
-    NetworkInit, PingRequest, ShutdownRequest, VirtiofsSource, Volume, guest_init_response,
+    ActivityRequest, AgentLogRequest, BlockDeviceSource, BoxliteError, BoxliteResult, DmesgRequest,
+    Filesystem, GuestClient, GuestInitRequest, NetworkInit, PingRequest, ShutdownRequest,
+    SwapUsageRequest, SyncRequest, VirtiofsSource, Volume, guest_init_response,
 };
+use std::time::Duration;
+use tonic::Code;
 use tonic::transport::Channel;
 
 /// Guest service interface.
@@ -67,11 +71,128 @@ impl GuestInterface {
         Ok(())
     }
 
+    /// Check that the guest agent speaks a supported protocol version.
+    ///
+    /// The agent may come from a different build than the host (see
+    /// `GuestUpdateMode::InPlace`), so compatibility is decided by the
+    /// advertised protocol version rather than the binary hash.
+    pub async fn handshake(&mut self) -> BoxliteResult<AgentInfo> {
+        let response = self.client.ping(PingRequest {}).await?.into_inner();
+        let agent = AgentInfo {
+            version: response.version,
+            protocol_version: response.protocol_version,
+        };
+
+        if !agent_protocol::is_supported(agent.protocol_version) {
+            return Err(BoxliteError::Unsupported(format!(
+                "Guest agent {} speaks protocol {}, host supports {}..={}",
+                agent.version,
+                agent.protocol_version,
+                agent_protocol::MIN_SUPPORTED,
+                agent_protocol::VERSION
+            )));
+        }
+
+        tracing::debug!(
+            agent_version = %agent.version,
+            protocol_version = agent.protocol_version,
+            "Guest agent handshake succeeded"
+        );
+        Ok(agent)
+    }
+
     /// Shutdown the guest agent.
-    pub async fn shutdown(&mut self) -> BoxliteResult<()> {
-        let _response = self.client.shutdown(ShutdownRequest {}).await?;
+    ///
+    /// Running processes get `timeout` between SIGTERM and SIGKILL before the
+    /// agent syncs the filesystems.
+    pub async fn shutdown(&mut self, timeout: Duration) -> BoxliteResult<()> {
+        let _response = self
+            .client
+            .shutdown(ShutdownRequest {
+                timeout_ms: timeout.as_millis() as u64,
+            })
+            .await?;
         Ok(())
     }
+
+    /// Last `tail_lines` lines of the guest kernel log, oldest first.
+    ///
+    /// The agent caps the count at `guest_logs::MAX_DMESG_LINES`.
+    pub async fn dmesg(&mut self, tail_lines: u32) -> BoxliteResult<Vec<String>> {
+        let response = self
+            .client
+            .dmesg(DmesgRequest { tail_lines })
+            .await?
+            .into_inner();
+        Ok(response.lines)
+    }
+
+    /// Last `tail_bytes` bytes of the guest agent's log, and whether earlier
+    /// output was cut off.
+    ///
+    /// The agent caps the size at `guest_logs::MAX_AGENT_LOG_BYTES`.
+    pub async fn agent_log(&mut self, tail_bytes: u32) -> BoxliteResult<(Vec<u8>, bool)> {
+        let response = self
+            .client
+            .agent_log(AgentLogRequest { tail_bytes })
+            .await?
+            .into_inner();
+        Ok((response.data, response.truncated))
+    }
+
+    /// Swap size and usage of the guest in bytes, `(total, used)`.
+    pub async fn swap_usage(&mut self) -> BoxliteResult<(u64, u64)> {
+        let response = self
+            .client
+            .swap_usage(SwapUsageRequest {})
+            .await?
+            .into_inner();
+        Ok((response.total_bytes, response.used_bytes))
+    }
+
+    /// Executions still running in the guest, and the time since the last
+    /// exec or file transfer, `(running_execs, idle)`.
+    pub async fn activity(&mut self) -> BoxliteResult<(u32, Duration)> {
+        let response = self.client.activity(ActivityRequest {}).await?.into_inner();
+        Ok((
+            response.running_execs,
+            Duration::from_millis(response.idle_ms),
+        ))
+    }
+
+    /// Flush the guest's filesystems and freeze/thaw the container's,
+    /// allowing the freeze up to `freeze_ms`. Returns how long it was frozen.
+    pub async fn sync(&mut self, container_id: &str, freeze_ms: u32) -> BoxliteResult<Duration> {
+        let request = SyncRequest {
+            container_id: container_id.to_string(),
+            freeze_ms,
+        };
+        let response = match self.client.sync(request).await {
+            Ok(response) => response.into_inner(),
+            Err(status) => {
+                return Err(match status.code() {
+                    Code::FailedPrecondition => {
+                        BoxliteError::InvalidState(status.message().to_string())
+                    }
+                    Code::DeadlineExceeded => BoxliteError::Timeout(status.message().to_string()),
+                    Code::Unimplemented => BoxliteError::Unsupported(
+                        "guest agent does not support sync (needs protocol 8)".to_string(),
+                    ),
+                    _ => status.into(),
+                });
+            }
+        };
+        Ok(Duration::from_micros(response.frozen_micros))
+    }
+}
+
+/// Guest agent identity reported at handshake.
+#[derive(Debug, Clone)]
+pub struct AgentInfo {
+    /// Agent crate version
+    pub version: String,
+    /// Agent protocol version
+    pub protocol_version: u32,
 }
 
 /// Configuration for guest initialization.
@@ -6,8 +6,12 @@ pub mod container;
 pub mod exec;
 pub mod files;
 pub mod guest;
+pub mod supervisor;
+pub mod tunnel;
 
 pub use container::{ContainerInterface, ContainerRootfsInitConfig};
 pub use exec::ExecutionInterface;
 pub use files::FilesInterface;
 pub use guest::{GuestInitConfig, GuestInterface, NetworkInitConfig, VolumeConfig};
+pub use supervisor::SupervisorInterface;
+pub use tunnel::TunnelInterface;
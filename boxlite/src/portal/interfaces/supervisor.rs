@@ -0,0 +1,89 @@
+//! Supervisor service interface.
+//!
+//! Starts, stops and lists the services the guest agent supervises.
+
+use super::exec::ExecProtocol;
+use crate::litebox::{BoxCommand, RestartPolicy};
+use boxlite_shared::{
+    BoxliteError, BoxliteResult, ListServicesRequest, ServiceStatus, StartServiceRequest,
+    StopServiceRequest, SupervisorClient,
+};
+use std::time::Duration;
+use tonic::transport::Channel;
+
+/// Supervisor service interface.
+pub struct SupervisorInterface {
+    client: SupervisorClient<Channel>,
+}
+
+impl SupervisorInterface {
+    /// Create from a channel.
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            client: SupervisorClient::new(channel),
+        }
+    }
+
+    /// Start service `name` running `command`.
+    ///
+    /// Fails if the first run cannot be spawned; later runs are the guest's.
+    pub async fn start_service(
+        &mut self,
+        name: &str,
+        command: &BoxCommand,
+        restart: RestartPolicy,
+    ) -> BoxliteResult<()> {
+        let restart = match restart {
+            RestartPolicy::No => boxlite_shared::RestartPolicy::No,
+            RestartPolicy::OnFailure => boxlite_shared::RestartPolicy::OnFailure,
+            RestartPolicy::Always => boxlite_shared::RestartPolicy::Always,
+        };
+        let request = StartServiceRequest {
+            name: name.to_string(),
+            command: Some(ExecProtocol::build_exec_request(command)),
+            restart: restart.into(),
+        };
+
+        let response = self.client.start_service(request).await?.into_inner();
+        match response.error {
+            Some(err) if err.reason == "service_exists" => {
+                Err(BoxliteError::AlreadyExists(err.detail))
+            }
+            Some(err) => Err(BoxliteError::Execution(format!(
+                "Failed to start service {}: {}",
+                name, err.detail
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Stop service `name`: SIGTERM, then SIGKILL after `grace`.
+    pub async fn stop_service(&mut self, name: &str, grace: Duration) -> BoxliteResult<()> {
+        let request = StopServiceRequest {
+            name: name.to_string(),
+            timeout_ms: grace.as_millis() as u64,
+        };
+
+        let response = self.client.stop_service(request).await?.into_inner();
+        match response.error {
+            Some(err) if err.reason == "service_not_found" => {
+                Err(BoxliteError::NotFound(err.detail))
+            }
+            Some(err) => Err(BoxliteError::Internal(format!(
+                "{}: {}",
+                err.reason, err.detail
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Status of the services the guest knows, by name.
+    pub async fn list_services(&mut self) -> BoxliteResult<Vec<ServiceStatus>> {
+        let response = self
+            .client
+            .list_services(ListServicesRequest {})
+            .await?
+            .into_inner();
+        Ok(response.services)
+    }
+}
@@ -0,0 +1,39 @@
+//! Tunnel service interface.
+//!
+//! Opens the relay stream that carries TCP connections into the guest.
+
+use boxlite_shared::{BoxliteError, BoxliteResult, TunnelClient, TunnelFrame};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::Streaming;
+use tonic::transport::Channel;
+
+/// Tunnel service interface.
+pub struct TunnelInterface {
+    client: TunnelClient<Channel>,
+}
+
+impl TunnelInterface {
+    /// Create from a channel.
+    pub fn new(channel: Channel) -> Self {
+        Self {
+            client: TunnelClient::new(channel),
+        }
+    }
+
+    /// Open a relay stream.
+    ///
+    /// Frames sent on `outbound` go to the guest; the returned stream yields
+    /// the guest's frames. The relay ends when either side is dropped.
+    pub async fn relay(
+        &mut self,
+        outbound: mpsc::Receiver<TunnelFrame>,
+    ) -> BoxliteResult<Streaming<TunnelFrame>> {
+        let response = self
+            .client
+            .relay(ReceiverStream::new(outbound))
+            .await
+            .map_err(|e| BoxliteError::Internal(e.to_string()))?;
+        Ok(response.into_inner())
+    }
+}
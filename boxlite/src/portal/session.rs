@@ -4,6 +4,8 @@
 
 use crate::portal::connection::Connection;
 use crate::portal::interfaces::FilesInterface;
+use crate::portal::interfaces::SupervisorInterface;
+use crate::portal::interfaces::TunnelInterface;
 use crate::portal::interfaces::{ContainerInterface, ExecutionInterface, GuestInterface};
 use boxlite_shared::{BoxliteResult, Transport};
 
@@ -46,6 +48,18 @@ impl GuestSession {
         let channel = self.connection.channel().await?;
         Ok(FilesInterface::new(channel))
     }
+
+    /// Get supervisor interface.
+    pub async fn supervisor(&self) -> BoxliteResult<SupervisorInterface> {
+        let channel = self.connection.channel().await?;
+        Ok(SupervisorInterface::new(channel))
+    }
+
+    /// Get tunnel interface.
+    pub async fn tunnel(&self) -> BoxliteResult<TunnelInterface> {
+        let channel = self.connection.channel().await?;
+        Ok(TunnelInterface::new(channel))
+    }
 }
 
 // ============================================================================
@@ -7,6 +7,7 @@ use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::sync::RwLock;
 
+use crate::litebox::encoding::Utf8Decoder;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 use super::error::{map_http_error, map_http_status};
@@ -177,6 +178,32 @@ impl ApiClient {
         }
     }
 
+    /// POST a JSON body asking for an event stream, and call `on_event`
+    /// with the name and data of every event received.
+    pub async fn post_event_stream<B: Serialize>(
+        &self,
+        path: &str,
+        body: &B,
+        on_event: impl FnMut(&str, &str),
+    ) -> BoxliteResult<()> {
+        let builder = self
+            .http
+            .post(self.url(path))
+            .header("Accept", "text/event-stream")
+            .json(body);
+        let builder = self.authorize(builder).await?;
+        let resp = builder
+            .send()
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("HTTP request failed: {}", e)))?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            return self.handle_error(status, resp).await;
+        }
+        read_sse_events(resp, on_event).await
+    }
+
     /// Parse an error response body and map to BoxliteError.
     async fn handle_error<T>(
         &self,
@@ -200,6 +227,12 @@ impl ApiClient {
         self.send_json(builder).await
     }
 
+    /// GET a path outside the tenant prefix (e.g. `/version`).
+    pub async fn get_root<T: DeserializeOwned>(&self, path: &str) -> BoxliteResult<T> {
+        let builder = self.http.get(self.url_root(path));
+        self.send_json(builder).await
+    }
+
     pub async fn post<B: Serialize, T: DeserializeOwned>(
         &self,
         path: &str,
@@ -246,6 +279,15 @@ impl ApiClient {
         }
     }
 
+    /// Identity of the remote peer for audit events: `client_id@base_url`,
+    /// or just the base URL when no credentials are configured.
+    pub fn peer_identity(&self) -> String {
+        match &self.client_id {
+            Some(client_id) => format!("{}@{}", client_id, self.base_url),
+            None => self.base_url.clone(),
+        }
+    }
+
     /// Get the raw reqwest client (for SSE streaming).
     #[allow(dead_code)]
     pub fn raw_client(&self) -> &Client {
@@ -288,3 +330,50 @@ impl ApiClient {
         self.send_no_content(builder).await
     }
 }
+
+/// Read a server-sent event stream, calling `on_event(event, data)` for each
+/// event. Multi-line data is joined with `\n`; keepalive comments are skipped.
+pub(crate) async fn read_sse_events(
+    resp: reqwest::Response,
+    mut on_event: impl FnMut(&str, &str),
+) -> BoxliteResult<()> {
+    use futures::StreamExt;
+    let mut stream = resp.bytes_stream();
+    let mut decoder = Utf8Decoder::default();
+    let mut buffer = String::new();
+    let mut current_event = String::new();
+    let mut current_data = String::new();
+
+    while let Some(chunk) = stream.next().await {
+        let chunk =
+            chunk.map_err(|e| BoxliteError::Internal(format!("SSE stream read error: {}", e)))?;
+        buffer.push_str(&decoder.decode(&chunk));
+
+        // Process complete lines
+        while let Some(newline_pos) = buffer.find('\n') {
+            let line = buffer[..newline_pos].trim_end_matches('\r').to_string();
+            buffer = buffer[newline_pos + 1..].to_string();
+
+            if line.is_empty() {
+                // Empty line = end of event, dispatch
+                on_event(&current_event, &current_data);
+                current_event.clear();
+                current_data.clear();
+            } else if let Some(value) = line.strip_prefix("event: ") {
+                current_event = value.to_string();
+            } else if let Some(value) = line.strip_prefix("data: ") {
+                if !current_data.is_empty() {
+                    current_data.push('\n');
+                }
+                current_data.push_str(value);
+            }
+        }
+    }
+
+    // Dispatch any remaining event
+    if !current_event.is_empty() || !current_data.is_empty() {
+        on_event(&current_event, &current_data);
+    }
+
+    Ok(())
+}
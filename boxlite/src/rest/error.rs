@@ -1,5 +1,6 @@
 //! HTTP error → BoxliteError mapping.
 
+use boxlite_shared::archive::parse_unsafe_archive;
 use boxlite_shared::errors::BoxliteError;
 use reqwest::StatusCode;
 
@@ -12,19 +13,66 @@ pub(crate) fn map_http_error(status: StatusCode, body: &ErrorModel) -> BoxliteEr
         (409, "AlreadyExistsError") => BoxliteError::AlreadyExists(body.message.clone()),
         (409, "InvalidStateError") => BoxliteError::InvalidState(body.message.clone()),
         (409, "StoppedError") => BoxliteError::Stopped(body.message.clone()),
+        (503, "ShuttingDownError") => BoxliteError::ShuttingDown(body.message.clone()),
+        (409, "BusyError") => parse_busy(&body.message),
+        (409, "ConfigDriftError") => parse_config_drift(&body.message),
+        // The manifest stays on the server; resuming there picks it up.
+        (504, "PartialTransferError") => BoxliteError::Timeout(body.message.clone()),
         (400, _) => BoxliteError::InvalidArgument(body.message.clone()),
         (422, "ImageError") => BoxliteError::Image(body.message.clone()),
+        (422, "UnsafeArchiveError") => parse_unsafe_archive(&body.message)
+            .unwrap_or_else(|| BoxliteError::UnsafeArchive(body.message.clone())),
+        (422, "UnsafeCopyError") => BoxliteError::UnsafeCopy(
+            body.message
+                .strip_prefix("unsafe copy: ")
+                .unwrap_or(&body.message)
+                .to_string(),
+        ),
         (422, _) => BoxliteError::InvalidArgument(body.message.clone()),
-        (401 | 403, _) => BoxliteError::Config(format!("auth: {}", body.message)),
+        (403, "PolicyDeniedError") => BoxliteError::PolicyDenied(body.message.clone()),
+        // Authenticated, but the token's scope or selector rules it out
+        (403, _) => BoxliteError::PermissionDenied(body.message.clone()),
+        (401, _) => BoxliteError::Config(format!("auth: {}", body.message)),
         _ => BoxliteError::Internal(format!("HTTP {}: {}", status, body.message)),
     }
 }
 
+/// Recover `Busy` from its display form ("busy: {operation} in progress by pid {pid}").
+fn parse_busy(message: &str) -> BoxliteError {
+    let parsed = message
+        .strip_prefix("busy: ")
+        .and_then(|rest| rest.rsplit_once(" in progress by pid "))
+        .and_then(|(operation, pid)| Some((operation, pid.parse().ok()?)));
+    match parsed {
+        Some((operation, pid)) => BoxliteError::Busy {
+            operation: operation.to_string(),
+            pid,
+        },
+        None => BoxliteError::InvalidState(message.to_string()),
+    }
+}
+
+/// Recover `ConfigDrift` from its display form
+/// ("config drift: box '{name}' differs in {field}, {field}").
+fn parse_config_drift(message: &str) -> BoxliteError {
+    let parsed = message
+        .strip_prefix("config drift: box '")
+        .and_then(|rest| rest.rsplit_once("' differs in "));
+    match parsed {
+        Some((name, fields)) => BoxliteError::ConfigDrift {
+            name: name.to_string(),
+            fields: fields.split(", ").map(str::to_string).collect(),
+        },
+        None => BoxliteError::InvalidState(message.to_string()),
+    }
+}
+
 /// Map an HTTP error when we can't parse the body.
 pub(crate) fn map_http_status(status: StatusCode, text: &str) -> BoxliteError {
     match status.as_u16() {
         404 => BoxliteError::NotFound(text.to_string()),
-        401 | 403 => BoxliteError::Config(format!("auth: {}", text)),
+        401 => BoxliteError::Config(format!("auth: {}", text)),
+        403 => BoxliteError::PermissionDenied(text.to_string()),
         _ => BoxliteError::Internal(format!("HTTP {}: {}", status, text)),
     }
 }
@@ -77,6 +125,28 @@ mod tests {
         assert!(matches!(err, BoxliteError::Stopped(_)));
     }
 
+    #[test]
+    fn test_503_shutting_down() {
+        let err = map_http_error(
+            StatusCode::SERVICE_UNAVAILABLE,
+            &error_model("runtime is shutting down", "ShuttingDownError", 503),
+        );
+        assert!(matches!(err, BoxliteError::ShuttingDown(_)));
+    }
+
+    #[test]
+    fn test_504_partial_transfer() {
+        let err = map_http_error(
+            StatusCode::GATEWAY_TIMEOUT,
+            &error_model(
+                "partial transfer: copied 2 of 6 files",
+                "PartialTransferError",
+                504,
+            ),
+        );
+        assert!(matches!(err, BoxliteError::Timeout(_)));
+    }
+
     #[test]
     fn test_400_invalid_argument() {
         let err = map_http_error(
@@ -95,6 +165,19 @@ mod tests {
         assert!(matches!(err, BoxliteError::Image(_)));
     }
 
+    #[test]
+    fn test_422_unsafe_archive_round_trips() {
+        let sent = BoxliteError::UnsafeArchive("entry '../x' is absolute or contains '..'".into());
+        let err = map_http_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            &error_model(&sent.to_string(), "UnsafeArchiveError", 422),
+        );
+        let BoxliteError::UnsafeArchive(reason) = err else {
+            panic!("expected UnsafeArchive, got {err}");
+        };
+        assert_eq!(reason, "entry '../x' is absolute or contains '..'");
+    }
+
     #[test]
     fn test_401_auth_error() {
         let err = map_http_error(
@@ -104,6 +187,61 @@ mod tests {
         assert!(matches!(err, BoxliteError::Config(_)));
     }
 
+    #[test]
+    fn test_403_policy_denied() {
+        let err = map_http_error(
+            StatusCode::FORBIDDEN,
+            &error_model("critical vulnerabilities", "PolicyDeniedError", 403),
+        );
+        assert!(matches!(err, BoxliteError::PolicyDenied(_)));
+    }
+
+    #[test]
+    fn test_403_permission_denied() {
+        let err = map_http_error(
+            StatusCode::FORBIDDEN,
+            &error_model(
+                "token scope 'read-only' does not allow this request",
+                "PermissionDeniedError",
+                403,
+            ),
+        );
+        assert!(matches!(err, BoxliteError::PermissionDenied(_)));
+    }
+
+    #[test]
+    fn test_409_busy_round_trips() {
+        let sent = BoxliteError::Busy {
+            operation: "stop".to_string(),
+            pid: 4242,
+        };
+        let err = map_http_error(
+            StatusCode::CONFLICT,
+            &error_model(&sent.to_string(), "BusyError", 409),
+        );
+        let BoxliteError::Busy { operation, pid } = err else {
+            panic!("expected Busy, got {err}");
+        };
+        assert_eq!((operation.as_str(), pid), ("stop", 4242));
+    }
+
+    #[test]
+    fn test_409_config_drift_round_trips() {
+        let sent = BoxliteError::ConfigDrift {
+            name: "ci-runner".to_string(),
+            fields: vec!["cpus".to_string(), "env.PATH".to_string()],
+        };
+        let err = map_http_error(
+            StatusCode::CONFLICT,
+            &error_model(&sent.to_string(), "ConfigDriftError", 409),
+        );
+        let BoxliteError::ConfigDrift { name, fields } = err else {
+            panic!("expected ConfigDrift, got {err}");
+        };
+        assert_eq!(name, "ci-runner");
+        assert_eq!(fields, ["cpus", "env.PATH"]);
+    }
+
     #[test]
     fn test_500_internal_error() {
         let err = map_http_error(
@@ -118,9 +256,12 @@ mod tests {
         let err = map_http_status(StatusCode::NOT_FOUND, "not found");
         assert!(matches!(err, BoxliteError::NotFound(_)));
 
-        let err = map_http_status(StatusCode::FORBIDDEN, "forbidden");
+        let err = map_http_status(StatusCode::UNAUTHORIZED, "unauthorized");
         assert!(matches!(err, BoxliteError::Config(_)));
 
+        let err = map_http_status(StatusCode::FORBIDDEN, "forbidden");
+        assert!(matches!(err, BoxliteError::PermissionDenied(_)));
+
         let err = map_http_status(StatusCode::INTERNAL_SERVER_ERROR, "oops");
         assert!(matches!(err, BoxliteError::Internal(_)));
     }
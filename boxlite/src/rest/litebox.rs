@@ -7,16 +7,20 @@ use parking_lot::RwLock;
 use reqwest::Method;
 use tokio::sync::mpsc;
 
+use boxlite_shared::archive::ArchiveLimits;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 use crate::BoxInfo;
-use crate::litebox::copy::CopyOptions;
-use crate::litebox::{BoxCommand, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution};
+use crate::litebox::copy::{self, CopyOptions};
+use crate::litebox::encoding::{DecodeStatus, InvalidUtf8};
+use crate::litebox::output_filter::OutputFilters;
+use crate::litebox::pipe::{self, PacedReceiver, PacedSender, Pacing};
+use crate::litebox::{BoxCommand, EnvironmentReport, ExecResult, ExecStdin, Execution};
 use crate::metrics::BoxMetrics;
 use crate::runtime::backend::BoxBackend;
 use crate::runtime::types::BoxID;
 
-use super::client::ApiClient;
+use super::client::{ApiClient, read_sse_events};
 use super::exec::RestExecControl;
 use super::types::{BoxMetricsResponse, BoxResponse, ExecRequest, ExecResponse};
 
@@ -78,32 +82,52 @@ impl BoxBackend for RestBox {
     async fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution> {
         let box_id = self.box_id_str();
 
-        // 1. Create execution on remote server
+        if command.scratch_dir.is_some() {
+            return Err(BoxliteError::Unsupported(
+                "scratch directories are not supported by REST boxes".into(),
+            ));
+        }
+
+        // 1. Create execution on remote server. The server applies the box's
+        // env redaction; the command's own filters run here.
+        let command = command.render_for_exec(&self.info())?;
+        let filters = OutputFilters::for_command(&command, &[], &[]);
+        filters.validate()?;
         let path = format!("/boxes/{}/exec", box_id);
         let req = ExecRequest::from_command(&command);
         let resp: ExecResponse = self.client.post(&path, &req).await?;
         let execution_id = resp.execution_id;
+        if let Some(requested) = &command.execution_id
+            && *requested != execution_id
+        {
+            tracing::warn!(
+                requested = %requested,
+                assigned = %execution_id,
+                "Server ignored requested execution ID; {{exec.id}} will not match"
+            );
+        }
 
         // 2. Set up channels for stdout, stderr, stdin, and result
-        let (stdout_tx, stdout_rx) = mpsc::unbounded_channel::<String>();
-        let (stderr_tx, stderr_rx) = mpsc::unbounded_channel::<String>();
-        let (stdin_tx, stdin_rx) = mpsc::unbounded_channel::<Vec<u8>>();
+        let (stdout_tx, stdout_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let (stderr_tx, stderr_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let (stdin_tx, stdin_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
         let (result_tx, result_rx) = mpsc::unbounded_channel::<ExecResult>();
 
         // 3. Spawn SSE reader task for output streaming
         let sse_client = self.client.clone();
         let sse_box_id = box_id.clone();
         let sse_exec_id = execution_id.clone();
+        let output = SseOutput {
+            stdout_tx,
+            stderr_tx,
+            stdout_status: DecodeStatus::default(),
+            stderr_status: DecodeStatus::default(),
+        };
+        let stdout_status = output.stdout_status.clone();
+        let stderr_status = output.stderr_status.clone();
         tokio::spawn(async move {
-            let _ = read_sse_output(
-                &sse_client,
-                &sse_box_id,
-                &sse_exec_id,
-                stdout_tx,
-                stderr_tx,
-                result_tx,
-            )
-            .await;
+            let _ =
+                read_sse_output(&sse_client, &sse_box_id, &sse_exec_id, output, result_tx).await;
         });
 
         // 4. Spawn stdin writer task
@@ -116,8 +140,6 @@ impl BoxBackend for RestBox {
 
         // 5. Build Execution handle
         let control = RestExecControl::new(self.client.clone(), box_id);
-        let stdout = ExecStdout::new(stdout_rx);
-        let stderr = ExecStderr::new(stderr_rx);
         let stdin = ExecStdin::new(stdin_tx);
 
         Ok(Execution::new(
@@ -125,8 +147,8 @@ impl BoxBackend for RestBox {
             Box::new(control),
             result_rx,
             Some(stdin),
-            Some(stdout),
-            Some(stderr),
+            filters.output(stdout_rx, stdout_status),
+            filters.output(stderr_rx, stderr_status),
         ))
     }
 
@@ -152,6 +174,7 @@ impl BoxBackend for RestBox {
         container_dst: &str,
         _opts: CopyOptions,
     ) -> BoxliteResult<()> {
+        crate::validate::guest_path(container_dst)?;
         let box_id = self.box_id_str();
 
         // Create tar archive from host path
@@ -187,8 +210,9 @@ impl BoxBackend for RestBox {
         &self,
         container_src: &str,
         host_dst: &Path,
-        _opts: CopyOptions,
+        opts: CopyOptions,
     ) -> BoxliteResult<()> {
+        crate::validate::guest_path(container_src)?;
         let box_id = self.box_id_str();
 
         // Download tar from server
@@ -220,7 +244,18 @@ impl BoxBackend for RestBox {
             .map_err(|e| BoxliteError::Internal(format!("copy_out read body failed: {}", e)))?;
 
         // Extract tar to host path
-        extract_tar_to_path(&tar_bytes, host_dst)
+        extract_tar_to_path(
+            &tar_bytes,
+            host_dst,
+            opts.archive_limits,
+            opts.follow_symlinks,
+        )
+    }
+
+    async fn environment_reports(&self) -> BoxliteResult<Vec<EnvironmentReport>> {
+        let box_id = self.box_id_str();
+        let path = format!("/boxes/{}/environment", box_id);
+        self.client.get(&path).await
     }
 }
 
@@ -228,13 +263,21 @@ impl BoxBackend for RestBox {
 // SSE Output Streaming
 // ============================================================================
 
+/// Where the SSE reader delivers the output of an execution.
+struct SseOutput {
+    stdout_tx: PacedSender<Vec<u8>>,
+    stderr_tx: PacedSender<Vec<u8>>,
+    /// Set from `invalid_utf8` events, before the streams end.
+    stdout_status: DecodeStatus,
+    stderr_status: DecodeStatus,
+}
+
 /// Read SSE events from the execution output endpoint and forward to channels.
 async fn read_sse_output(
     client: &ApiClient,
     box_id: &str,
     execution_id: &str,
-    stdout_tx: mpsc::UnboundedSender<String>,
-    stderr_tx: mpsc::UnboundedSender<String>,
+    output: SseOutput,
     result_tx: mpsc::UnboundedSender<ExecResult>,
 ) -> BoxliteResult<()> {
     let path = format!("/boxes/{}/executions/{}/output", box_id, execution_id);
@@ -254,83 +297,48 @@ async fn read_sse_output(
         )));
     }
 
-    // Read SSE stream line by line
-    use futures::StreamExt;
-    let mut stream = resp.bytes_stream();
-    let mut buffer = String::new();
-    let mut current_event = String::new();
-    let mut current_data = String::new();
-
-    while let Some(chunk) = stream.next().await {
-        let chunk =
-            chunk.map_err(|e| BoxliteError::Internal(format!("SSE stream read error: {}", e)))?;
-        buffer.push_str(&String::from_utf8_lossy(&chunk));
-
-        // Process complete lines
-        while let Some(newline_pos) = buffer.find('\n') {
-            let line = buffer[..newline_pos].trim_end_matches('\r').to_string();
-            buffer = buffer[newline_pos + 1..].to_string();
-
-            if line.is_empty() {
-                // Empty line = end of event, dispatch
-                dispatch_sse_event(
-                    &current_event,
-                    &current_data,
-                    &stdout_tx,
-                    &stderr_tx,
-                    &result_tx,
-                );
-                current_event.clear();
-                current_data.clear();
-            } else if let Some(value) = line.strip_prefix("event: ") {
-                current_event = value.to_string();
-            } else if let Some(value) = line.strip_prefix("data: ") {
-                if !current_data.is_empty() {
-                    current_data.push('\n');
-                }
-                current_data.push_str(value);
-            }
-        }
-    }
+    read_sse_events(resp, |event, data| match event {
+        "stdout" => forward_output(data, &output.stdout_tx),
+        "stderr" => forward_output(data, &output.stderr_tx),
+        "invalid_utf8" => record_invalid_utf8(data, &output),
+        _ => dispatch_sse_event(event, data, &result_tx),
+    })
+    .await
+}
 
-    // Dispatch any remaining event
-    if !current_event.is_empty() || !current_data.is_empty() {
-        dispatch_sse_event(
-            &current_event,
-            &current_data,
-            &stdout_tx,
-            &stderr_tx,
-            &result_tx,
-        );
+/// Decode an output event and forward its bytes.
+fn forward_output(data: &str, tx: &PacedSender<Vec<u8>>) {
+    // SSE data is JSON: {"data":"<base64>"} per OpenAPI spec
+    if let Some(bytes) = extract_and_decode_b64(data)
+        && !bytes.is_empty()
+    {
+        let _ = tx.send_now(bytes);
     }
+}
 
-    Ok(())
+/// Record where the server's strict decoding stopped a stream.
+fn record_invalid_utf8(data: &str, output: &SseOutput) {
+    let Ok(event) = serde_json::from_str::<serde_json::Value>(data) else {
+        return;
+    };
+    let status = match event["stream"].as_str() {
+        Some("stdout") => &output.stdout_status,
+        Some("stderr") => &output.stderr_status,
+        _ => return,
+    };
+    status.fail(InvalidUtf8 {
+        offset: event["offset"].as_u64().unwrap_or_default(),
+        bytes: extract_and_decode_b64(data).unwrap_or_default(),
+    });
 }
 
-/// Dispatch a single SSE event to the appropriate channel.
-fn dispatch_sse_event(
-    event: &str,
-    data: &str,
-    stdout_tx: &mpsc::UnboundedSender<String>,
-    stderr_tx: &mpsc::UnboundedSender<String>,
-    result_tx: &mpsc::UnboundedSender<ExecResult>,
-) {
+/// Dispatch a single non-output SSE event.
+fn dispatch_sse_event(event: &str, data: &str, result_tx: &mpsc::UnboundedSender<ExecResult>) {
     if data.is_empty() {
         return;
     }
 
     match event {
-        "stdout" => {
-            // SSE data is JSON: {"data":"<base64>"} per OpenAPI spec
-            if let Some(decoded) = extract_and_decode_b64(data) {
-                let _ = stdout_tx.send(decoded);
-            }
-        }
-        "stderr" => {
-            if let Some(decoded) = extract_and_decode_b64(data) {
-                let _ = stderr_tx.send(decoded);
-            }
-        }
         "exit" => {
             // Parse exit code from JSON: {"exit_code": 0}
             if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(data) {
@@ -361,21 +369,19 @@ fn dispatch_sse_event(
     }
 }
 
-/// Extract base64 value from SSE JSON `{"data":"<base64>"}` and decode to UTF-8.
-fn extract_and_decode_b64(data: &str) -> Option<String> {
+/// Extract base64 value from SSE JSON `{"data":"<base64>"}` and decode it.
+fn extract_and_decode_b64(data: &str) -> Option<Vec<u8>> {
     let parsed: serde_json::Value = serde_json::from_str(data).ok()?;
     let b64 = parsed.get("data")?.as_str()?;
     base64_decode(b64).ok()
 }
 
-/// Decode base64-encoded SSE data to a UTF-8 string.
-fn base64_decode(data: &str) -> Result<String, BoxliteError> {
+/// Decode base64-encoded SSE data.
+fn base64_decode(data: &str) -> Result<Vec<u8>, BoxliteError> {
     use base64::Engine;
-    let bytes = base64::engine::general_purpose::STANDARD
+    base64::engine::general_purpose::STANDARD
         .decode(data.trim())
-        .map_err(|e| BoxliteError::Internal(format!("base64 decode error: {}", e)))?;
-    String::from_utf8(bytes)
-        .map_err(|e| BoxliteError::Internal(format!("UTF-8 decode error: {}", e)))
+        .map_err(|e| BoxliteError::Internal(format!("base64 decode error: {}", e)))
 }
 
 // ============================================================================
@@ -387,7 +393,7 @@ async fn forward_stdin(
     client: &ApiClient,
     box_id: &str,
     execution_id: &str,
-    mut stdin_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    mut stdin_rx: PacedReceiver<Vec<u8>>,
 ) {
     let path = format!("/boxes/{}/executions/{}/input", box_id, execution_id);
     while let Some(data) = stdin_rx.recv().await {
@@ -437,8 +443,14 @@ fn create_tar_from_path(host_src: &Path) -> BoxliteResult<Vec<u8>> {
         .map_err(|e| BoxliteError::Internal(format!("failed to finalize tar archive: {}", e)))
 }
 
-/// Extract a tar archive to a host directory.
-fn extract_tar_to_path(tar_bytes: &[u8], host_dst: &Path) -> BoxliteResult<()> {
+/// Extract a tar archive to a host directory, within `limits` and, unless
+/// `follow_symlinks`, confined to it.
+fn extract_tar_to_path(
+    tar_bytes: &[u8],
+    host_dst: &Path,
+    limits: ArchiveLimits,
+    follow_symlinks: bool,
+) -> BoxliteResult<()> {
     // Ensure parent directory exists
     if let Some(parent) = host_dst.parent() {
         std::fs::create_dir_all(parent).map_err(|e| {
@@ -450,14 +462,12 @@ fn extract_tar_to_path(tar_bytes: &[u8], host_dst: &Path) -> BoxliteResult<()> {
         })?;
     }
 
-    let mut archive = tar::Archive::new(tar_bytes);
-    archive.unpack(host_dst).map_err(|e| {
-        BoxliteError::Internal(format!(
-            "failed to extract tar to {}: {}",
-            host_dst.display(),
-            e
-        ))
-    })
+    copy::unpack_to_host(
+        &mut tar::Archive::new(tar_bytes),
+        host_dst,
+        limits,
+        follow_symlinks,
+    )
 }
 
 // ============================================================================
@@ -499,6 +509,7 @@ fn box_metrics_from_response(resp: &BoxMetricsResponse) -> BoxMetrics {
         guest_boot_duration_ms: guest_boot_ms,
         cpu_percent: resp.cpu_percent,
         memory_bytes: resp.memory_bytes,
+        swap_bytes: resp.swap_bytes,
         network_bytes_sent: resp.network_bytes_sent,
         network_bytes_received: resp.network_bytes_received,
         network_tcp_connections: resp.network_tcp_connections,
@@ -511,3 +522,56 @@ fn box_metrics_from_response(resp: &BoxMetricsResponse) -> BoxMetrics {
         stage_container_init_ms: container_init_ms,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::litebox::OutputEncoding;
+    use crate::litebox::encoding::decode_stream;
+    use base64::Engine;
+
+    fn output_event(bytes: &[u8]) -> String {
+        let b64 = base64::engine::general_purpose::STANDARD.encode(bytes);
+        serde_json::json!({ "data": b64 }).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_output_split_inside_utf8_sequence() {
+        let text = "größe: 世界\n";
+        let (tx, rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        for chunk in text.as_bytes().chunks(3) {
+            forward_output(&output_event(chunk), &tx);
+        }
+        drop(tx);
+
+        let mut rx = decode_stream(rx, OutputEncoding::Utf8Lossy, DecodeStatus::default());
+        let mut received = String::new();
+        while let Some(chunk) = rx.recv().await {
+            received.push_str(&chunk);
+        }
+        assert_eq!(received, text);
+    }
+
+    #[test]
+    fn test_invalid_utf8_event_sets_stream_status() {
+        let (stdout_tx, _stdout_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let (stderr_tx, _stderr_rx) = pipe::channel::<Vec<u8>>(&Pacing::default());
+        let output = SseOutput {
+            stdout_tx,
+            stderr_tx,
+            stdout_status: DecodeStatus::default(),
+            stderr_status: DecodeStatus::default(),
+        };
+        let event = serde_json::json!({ "stream": "stderr", "offset": 42, "data": "6Q==" });
+        record_invalid_utf8(&event.to_string(), &output);
+
+        assert_eq!(output.stdout_status.error(), None);
+        assert_eq!(
+            output.stderr_status.error(),
+            Some(InvalidUtf8 {
+                offset: 42,
+                bytes: vec![0xe9],
+            })
+        );
+    }
+}
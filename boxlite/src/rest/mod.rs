@@ -3,7 +3,8 @@
 //! Provides a REST-based `RuntimeBackend` and `BoxBackend` implementation
 //! that delegates all operations to a remote BoxLite REST API server.
 //!
-//! Enabled with the `rest` feature flag.
+//! Enabled with the `rest` feature flag. The `rest-server` feature adds
+//! [`server`], an embeddable server for the same API.
 
 pub(crate) mod client;
 pub(crate) mod error;
@@ -11,4 +12,6 @@ mod exec;
 pub(crate) mod litebox;
 pub mod options;
 pub(crate) mod runtime;
+#[cfg(feature = "rest-server")]
+pub mod server;
 pub(crate) mod types;
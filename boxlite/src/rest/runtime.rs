@@ -3,16 +3,23 @@
 use std::sync::Arc;
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use reqwest::StatusCode;
 
 use crate::metrics::RuntimeMetrics;
 use crate::runtime::backend::RuntimeBackend;
+use crate::runtime::create_progress::{CreateObserver, CreateProgress};
 use crate::runtime::options::BoxOptions;
+use crate::runtime::version::VersionInfo;
 use crate::{BoxInfo, LiteBox};
 
 use super::client::ApiClient;
+use super::error::map_http_error;
 use super::litebox::RestBox;
 use super::options::BoxliteRestOptions;
-use super::types::{BoxResponse, CreateBoxRequest, ListBoxesResponse, RuntimeMetricsResponse};
+use super::types::{
+    BoxResponse, CreateBoxRequest, CreateProgressEvent, ErrorResponse, ListBoxesResponse,
+    RuntimeMetricsResponse,
+};
 
 pub(crate) struct RestRuntime {
     client: ApiClient,
@@ -35,6 +42,48 @@ impl RuntimeBackend for RestRuntime {
         Ok(LiteBox::new(Arc::new(rest_box)))
     }
 
+    async fn create_with_observer(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: CreateObserver,
+    ) -> BoxliteResult<LiteBox> {
+        let progress = CreateProgress::new(observer);
+        let req = CreateBoxRequest::from_options(&options, name);
+        let mut outcome: Option<BoxliteResult<BoxResponse>> = None;
+        self.client
+            .post_event_stream("/boxes", &req, |event, data| match event {
+                "progress" => {
+                    if let Ok(wire) = serde_json::from_str::<CreateProgressEvent>(data) {
+                        progress.forward(&wire.to_event());
+                    }
+                }
+                "box" => {
+                    outcome = Some(serde_json::from_str(data).map_err(|e| {
+                        BoxliteError::Internal(format!("failed to parse box event: {}", e))
+                    }));
+                }
+                "error" => {
+                    outcome = Some(Err(match serde_json::from_str::<ErrorResponse>(data) {
+                        Ok(resp) => map_http_error(
+                            StatusCode::from_u16(resp.error.code)
+                                .unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+                            &resp.error,
+                        ),
+                        Err(_) => BoxliteError::Internal(data.to_string()),
+                    }));
+                }
+                _ => {}
+            })
+            .await?;
+
+        let resp = outcome.ok_or_else(|| {
+            BoxliteError::Internal("create stream ended without a result".into())
+        })??;
+        let rest_box = RestBox::new(self.client.clone(), resp.to_box_info());
+        Ok(LiteBox::new(Arc::new(rest_box)))
+    }
+
     async fn get_or_create(
         &self,
         options: BoxOptions,
@@ -88,6 +137,10 @@ impl RuntimeBackend for RestRuntime {
         Ok(runtime_metrics_from_response(&resp))
     }
 
+    async fn version_info(&self) -> BoxliteResult<VersionInfo> {
+        self.client.get_root("/version").await
+    }
+
     async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
         let path = format!("/boxes/{}", id_or_name);
         if force {
@@ -104,6 +157,10 @@ impl RuntimeBackend for RestRuntime {
         // The server manages its own lifecycle.
         Ok(())
     }
+
+    fn audit_peer(&self) -> Option<String> {
+        Some(self.client.peer_identity())
+    }
 }
 
 /// Convert REST metrics response to core RuntimeMetrics.
@@ -127,6 +184,7 @@ fn runtime_metrics_from_response(resp: &RuntimeMetricsResponse) -> RuntimeMetric
     storage
         .total_exec_errors
         .store(resp.total_exec_errors, Ordering::Relaxed);
+    storage.per_image.load(resp.per_image.clone());
 
     RuntimeMetrics::new(storage)
 }
@@ -0,0 +1,426 @@
+//! Request authentication, token scopes and the OAuth2 token endpoint.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Form, RawPathParams, Request, State};
+use axum::http::header;
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use serde::Deserialize;
+
+use super::ServerState;
+use super::error::ApiError;
+use crate::rest::types::TokenResponse;
+use crate::runtime::types::{BoxInfo, ListFilter};
+
+/// Lifetime advertised for issued tokens. Shared tokens never actually
+/// expire; the client just exchanges its credentials again.
+const TOKEN_LIFETIME_SECS: u64 = 3600;
+
+/// Token handed out when requests are not checked for one.
+const PLACEHOLDER_TOKEN: &str = "unused";
+
+/// How clients authenticate to a [`RestServer`](super::RestServer).
+#[derive(Clone, Default)]
+pub enum RestServerAuth {
+    /// No authentication: any client that can connect has full access.
+    #[default]
+    None,
+    /// Every API request carries `Authorization: Bearer <token>`.
+    ///
+    /// The bundled REST client obtains the token from the OAuth2 token
+    /// endpoint: configure it with any client ID and the token as the
+    /// client secret.
+    Token(String),
+    /// Every API request carries one of these bearer tokens and is limited
+    /// to what its scope and selector allow.
+    ///
+    /// The bundled REST client obtains a token the same way as with
+    /// [`Token`](Self::Token).
+    Tokens(Vec<ScopedToken>),
+    /// Every connection presents a TLS client certificate accepted by the
+    /// server's client verifier; connections without one are closed.
+    ClientCert,
+}
+
+impl std::fmt::Debug for RestServerAuth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => write!(f, "None"),
+            Self::Token(_) => write!(f, "Token(<redacted>)"),
+            Self::Tokens(tokens) => f.debug_tuple("Tokens").field(tokens).finish(),
+            Self::ClientCert => write!(f, "ClientCert"),
+        }
+    }
+}
+
+/// What a token allows, from least to most.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum TokenScope {
+    /// List boxes and read their info, metrics and environment.
+    ReadOnly,
+    /// Also run commands in boxes and transfer files to and from them.
+    Exec,
+    /// Everything: also create, start, stop and remove boxes.
+    Full,
+}
+
+impl std::fmt::Display for TokenScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ReadOnly => write!(f, "read-only"),
+            Self::Exec => write!(f, "exec"),
+            Self::Full => write!(f, "full"),
+        }
+    }
+}
+
+/// A bearer token for [`RestServerAuth::Tokens`], with its scope and
+/// optionally the boxes it is limited to.
+#[derive(Clone)]
+pub struct ScopedToken {
+    token: String,
+    scope: TokenScope,
+    selector: Option<ListFilter>,
+}
+
+impl ScopedToken {
+    /// Token `token` allowing `scope` on every box.
+    pub fn new(token: impl Into<String>, scope: TokenScope) -> Self {
+        Self {
+            token: token.into(),
+            scope,
+            selector: None,
+        }
+    }
+
+    /// Limit the token to boxes matching the name and labels of `selector`.
+    ///
+    /// Other boxes are hidden from the token: lists leave them out and
+    /// requests naming them fail as not found. Boxes created with the
+    /// token must match, and runtime-wide metrics are refused. Selectors
+    /// cannot require a status.
+    pub fn selector(mut self, selector: ListFilter) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    /// The access this token grants.
+    fn access(&self) -> Access {
+        Access {
+            scope: self.scope,
+            selector: self.selector.clone(),
+        }
+    }
+
+    /// Validate the token when the server is built.
+    pub(super) fn validate(&self) -> BoxliteResult<()> {
+        if self.token.is_empty() {
+            return Err(BoxliteError::Config("REST tokens must not be empty".into()));
+        }
+        if self.selector.as_ref().is_some_and(|s| s.status.is_some()) {
+            return Err(BoxliteError::Config(
+                "REST token selectors match box names and labels, not status".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ScopedToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ScopedToken")
+            .field("token", &"<redacted>")
+            .field("scope", &self.scope)
+            .field("selector", &self.selector)
+            .finish()
+    }
+}
+
+/// What the client of a request may do, attached to every authenticated
+/// request by [`require_auth`].
+#[derive(Debug, Clone)]
+pub(super) struct Access {
+    scope: TokenScope,
+    selector: Option<ListFilter>,
+}
+
+impl Access {
+    /// Access of clients not limited by a scoped token.
+    fn full() -> Self {
+        Self {
+            scope: TokenScope::Full,
+            selector: None,
+        }
+    }
+
+    /// Whether the box described by `info` is visible to the client.
+    pub(super) fn allows(&self, info: &BoxInfo) -> bool {
+        self.allows_identity(info.name.as_deref(), &info.labels)
+    }
+
+    fn allows_identity(&self, name: Option<&str>, labels: &HashMap<String, String>) -> bool {
+        self.selector
+            .as_ref()
+            .is_none_or(|selector| selector.matches_identity(name, labels))
+    }
+
+    /// Fail unless the client may create a box named `name` with `labels`.
+    pub(super) fn check_new_box(
+        &self,
+        name: Option<&str>,
+        labels: &HashMap<String, String>,
+    ) -> Result<(), ApiError> {
+        if self.allows_identity(name, labels) {
+            Ok(())
+        } else {
+            Err(ApiError::permission_denied(
+                "token may only create boxes matching its selector",
+            ))
+        }
+    }
+
+    /// Fail for requests covering every box when the client is limited to
+    /// some of them.
+    pub(super) fn check_unrestricted(&self) -> Result<(), ApiError> {
+        match self.selector {
+            None => Ok(()),
+            Some(_) => Err(ApiError::permission_denied(
+                "token limited to some boxes cannot read runtime-wide data",
+            )),
+        }
+    }
+
+    /// Fail unless the client's scope includes `required`.
+    fn check_scope(&self, required: TokenScope) -> Result<(), ApiError> {
+        if self.scope >= required {
+            Ok(())
+        } else {
+            Err(ApiError::permission_denied(format!(
+                "token scope '{}' does not allow this request (requires '{}')",
+                self.scope, required
+            )))
+        }
+    }
+}
+
+/// Reject requests without a configured token (other modes pass through;
+/// client certificates are checked per connection), and attach the
+/// client's [`Access`] to the rest.
+pub(super) async fn require_auth(
+    State(state): State<Arc<ServerState>>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    let access = match (&state.auth, provided) {
+        (RestServerAuth::None | RestServerAuth::ClientCert, _) => Access::full(),
+        (RestServerAuth::Token(_) | RestServerAuth::Tokens(_), None) => {
+            return ApiError::unauthorized("missing authorization header").into_response();
+        }
+        (RestServerAuth::Token(expected), Some(token)) => {
+            if !constant_time_eq(token.as_bytes(), expected.as_bytes()) {
+                return ApiError::unauthorized("invalid token").into_response();
+            }
+            Access::full()
+        }
+        (RestServerAuth::Tokens(tokens), Some(token)) => match find_token(tokens, token) {
+            Some(scoped) => scoped.access(),
+            None => return ApiError::unauthorized("invalid token").into_response(),
+        },
+    };
+    request.extensions_mut().insert(access);
+    next.run(request).await
+}
+
+/// Refuse requests the client's token does not allow: its scope must
+/// include `required`, and a box named in the path must match its
+/// selector.
+///
+/// Layered on each route's methods, inside [`require_auth`].
+pub(super) async fn authorize(
+    State((state, required)): State<(Arc<ServerState>, TokenScope)>,
+    params: RawPathParams,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(access) = request.extensions().get::<Access>().cloned() else {
+        return ApiError::from(BoxliteError::Internal(
+            "request reached authorization without authentication".into(),
+        ))
+        .into_response();
+    };
+    if let Err(err) = access.check_scope(required) {
+        return err.into_response();
+    }
+
+    let box_id = params
+        .iter()
+        .find(|(key, _)| *key == "box_id")
+        .map(|(_, value)| value.to_string());
+    if let (Some(_), Some(box_id)) = (&access.selector, box_id) {
+        match state.runtime.get_info(&box_id).await {
+            Ok(Some(info)) if access.allows(&info) => {}
+            // Hidden boxes look the same as missing ones
+            Ok(_) => {
+                return ApiError::not_found(format!("box not found: {}", box_id)).into_response();
+            }
+            Err(err) => return ApiError::from(err).into_response(),
+        }
+    }
+    next.run(request).await
+}
+
+/// `POST /oauth/tokens` form (client credentials grant).
+#[derive(Debug, Deserialize)]
+pub(super) struct TokenForm {
+    grant_type: String,
+    #[serde(default)]
+    client_secret: String,
+}
+
+/// Exchange client credentials for the shared token.
+pub(super) async fn issue_token(
+    State(state): State<Arc<ServerState>>,
+    Form(form): Form<TokenForm>,
+) -> Result<Json<TokenResponse>, ApiError> {
+    if form.grant_type != "client_credentials" {
+        return Err(ApiError::bad_request("unsupported grant_type"));
+    }
+
+    let access_token = match &state.auth {
+        RestServerAuth::Token(expected) => {
+            if !constant_time_eq(form.client_secret.as_bytes(), expected.as_bytes()) {
+                return Err(ApiError::unauthorized("invalid client credentials"));
+            }
+            expected.clone()
+        }
+        RestServerAuth::Tokens(tokens) => match find_token(tokens, &form.client_secret) {
+            Some(scoped) => scoped.token.clone(),
+            None => return Err(ApiError::unauthorized("invalid client credentials")),
+        },
+        RestServerAuth::None | RestServerAuth::ClientCert => PLACEHOLDER_TOKEN.to_string(),
+    };
+
+    Ok(Json(TokenResponse {
+        access_token,
+        token_type: "bearer".to_string(),
+        expires_in: TOKEN_LIFETIME_SECS,
+    }))
+}
+
+/// The configured token equal to `provided`.
+fn find_token<'a>(tokens: &'a [ScopedToken], provided: &str) -> Option<&'a ScopedToken> {
+    tokens
+        .iter()
+        .find(|scoped| constant_time_eq(provided.as_bytes(), scoped.token.as_bytes()))
+}
+
+/// Compare secrets without an early exit on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"x"));
+    }
+
+    #[test]
+    fn test_debug_redacts_token() {
+        let auth = RestServerAuth::Token("hunter2".into());
+        assert_eq!(format!("{:?}", auth), "Token(<redacted>)");
+
+        let auth = RestServerAuth::Tokens(vec![ScopedToken::new("hunter2", TokenScope::Exec)]);
+        let debug = format!("{:?}", auth);
+        assert!(!debug.contains("hunter2"), "{debug}");
+        assert!(debug.contains("Exec"), "{debug}");
+    }
+
+    #[test]
+    fn test_scopes_by_endpoint_class() {
+        let read_only = ScopedToken::new("r", TokenScope::ReadOnly).access();
+        let exec = ScopedToken::new("e", TokenScope::Exec).access();
+        let full = ScopedToken::new("f", TokenScope::Full).access();
+
+        // List and info
+        assert!(read_only.check_scope(TokenScope::ReadOnly).is_ok());
+        assert!(exec.check_scope(TokenScope::ReadOnly).is_ok());
+        assert!(full.check_scope(TokenScope::ReadOnly).is_ok());
+        // Exec and files
+        assert!(read_only.check_scope(TokenScope::Exec).is_err());
+        assert!(exec.check_scope(TokenScope::Exec).is_ok());
+        assert!(full.check_scope(TokenScope::Exec).is_ok());
+        // Lifecycle and remove
+        assert!(read_only.check_scope(TokenScope::Full).is_err());
+        assert!(exec.check_scope(TokenScope::Full).is_err());
+        assert!(full.check_scope(TokenScope::Full).is_ok());
+
+        let err = read_only.check_scope(TokenScope::Full).unwrap_err();
+        let model = err.into_error_response().error;
+        assert_eq!(model.code, 403);
+        assert_eq!(model.error_type, "PermissionDeniedError");
+        assert!(model.message.contains("'read-only'"), "{}", model.message);
+    }
+
+    #[test]
+    fn test_selector_limits_boxes() {
+        let access = ScopedToken::new("t", TokenScope::Full)
+            .selector(ListFilter::new().label("team", "web"))
+            .access();
+        let web: HashMap<String, String> = [("team".to_string(), "web".to_string())].into();
+        let db: HashMap<String, String> = [("team".to_string(), "db".to_string())].into();
+
+        assert!(access.allows_identity(Some("a"), &web));
+        assert!(!access.allows_identity(Some("a"), &db));
+        assert!(!access.allows_identity(None, &HashMap::new()));
+        assert!(access.check_new_box(Some("a"), &web).is_ok());
+        assert!(access.check_new_box(None, &db).is_err());
+        assert!(access.check_unrestricted().is_err());
+
+        let unlimited = ScopedToken::new("t", TokenScope::ReadOnly).access();
+        assert!(unlimited.allows_identity(None, &db));
+        assert!(unlimited.check_unrestricted().is_ok());
+    }
+
+    #[test]
+    fn test_find_token() {
+        let tokens = vec![
+            ScopedToken::new("reader", TokenScope::ReadOnly),
+            ScopedToken::new("admin", TokenScope::Full),
+        ];
+        assert_eq!(
+            find_token(&tokens, "admin").map(|t| t.scope),
+            Some(TokenScope::Full)
+        );
+        assert!(find_token(&tokens, "admi").is_none());
+        assert!(find_token(&tokens, "").is_none());
+    }
+
+    #[test]
+    fn test_validate_selector() {
+        assert!(ScopedToken::new("t", TokenScope::Exec).validate().is_ok());
+        assert!(ScopedToken::new("", TokenScope::Exec).validate().is_err());
+        let by_status = ListFilter::new().status(crate::runtime::types::BoxStatus::Running);
+        assert!(
+            ScopedToken::new("t", TokenScope::Exec)
+                .selector(by_status)
+                .validate()
+                .is_err()
+        );
+    }
+}
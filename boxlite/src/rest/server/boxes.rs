@@ -0,0 +1,561 @@
+//! Box lifecycle, metrics, environment and discovery endpoints.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::Json;
+use axum::extract::{Extension, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use boxlite_shared::errors::BoxliteResult;
+use futures::Stream;
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+
+use super::ServerState;
+use super::auth::Access;
+use super::error::ApiError;
+use super::files::TRANSFER_MAX_BYTES;
+use super::prometheus;
+use crate::litebox::{EnvironmentReport, LiteBox};
+use crate::metrics::{BoxMetrics, RuntimeMetrics};
+use crate::rest::types::{
+    BootTimingResponse, BoxMetricsResponse, BoxResponse, CreateBoxRequest, CreateProgressEvent,
+    ListBoxesResponse, RuntimeMetricsResponse,
+};
+use crate::runtime::create_progress::CreateEvent;
+use crate::runtime::options::{BoxOptions, RootfsSpec};
+use crate::runtime::types::BoxInfo;
+use crate::runtime::version::VersionInfo;
+
+/// Look up a box by ID or name.
+pub(super) async fn find_box(state: &ServerState, id_or_name: &str) -> Result<LiteBox, ApiError> {
+    state
+        .runtime
+        .get(id_or_name)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("box not found: {}", id_or_name)))
+}
+
+/// `GET /config`: capabilities of this server.
+pub(super) async fn config() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "capabilities": {
+            "tty_enabled": false,
+            "streaming_enabled": true,
+            "file_transfer_max_bytes": TRANSFER_MAX_BYTES,
+        }
+    }))
+}
+
+/// `GET /version`
+pub(super) async fn version(
+    State(state): State<Arc<ServerState>>,
+) -> Result<Json<VersionInfo>, ApiError> {
+    Ok(Json(state.runtime.version_info().await?))
+}
+
+/// `POST /boxes`
+///
+/// With `Accept: text/event-stream`, the box is created with progress: the
+/// image is pulled and the shared disks built before the box is registered,
+/// each phase is streamed as a `progress` event, and the stream ends with a
+/// `box` event carrying the created box or an `error` event.
+pub(super) async fn create(
+    State(state): State<Arc<ServerState>>,
+    Extension(access): Extension<Access>,
+    Path(workspace): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<CreateBoxRequest>,
+) -> Result<Response, ApiError> {
+    access.check_new_box(req.name.as_deref(), &req.labels)?;
+    let name = req.name.clone();
+    if accepts_event_stream(&headers) {
+        return Ok(create_with_progress(state, box_options(req), name).into_response());
+    }
+
+    let litebox = state.runtime.create(box_options(req), name).await?;
+    let info = litebox.info();
+    let location = format!("{}/boxes/{}", state.workspace_path(&workspace), info.id);
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(box_response(&info)),
+    )
+        .into_response())
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+/// Message from the create task to the event stream.
+enum CreateMessage {
+    Progress(CreateEvent),
+    Finished(BoxliteResult<BoxInfo>),
+}
+
+/// Run a create in the background and stream its phases.
+fn create_with_progress(
+    state: Arc<ServerState>,
+    options: BoxOptions,
+    name: Option<String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let progress_tx = tx.clone();
+    tokio::spawn(async move {
+        let result = state
+            .runtime
+            .create_with_observer(options, name, move |event| {
+                let _ = progress_tx.send(CreateMessage::Progress(event.clone()));
+            })
+            .await;
+        let info = result.map(|litebox| litebox.info());
+        let _ = tx.send(CreateMessage::Finished(info));
+    });
+
+    let events = async_stream::stream! {
+        while let Some(message) = rx.recv().await {
+            match message {
+                CreateMessage::Progress(event) => {
+                    yield Ok(json_event("progress", &CreateProgressEvent::from_event(&event)));
+                }
+                CreateMessage::Finished(Ok(info)) => {
+                    yield Ok(json_event("box", &box_response(&info)));
+                    break;
+                }
+                CreateMessage::Finished(Err(err)) => {
+                    yield Ok(json_event("error", &ApiError::from(err).into_error_response()));
+                    break;
+                }
+            }
+        }
+    };
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+fn json_event(name: &'static str, data: &impl Serialize) -> Event {
+    Event::default()
+        .event(name)
+        .data(serde_json::to_string(data).unwrap_or_default())
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct ListQuery {
+    status: Option<String>,
+}
+
+/// `GET /boxes`
+///
+/// Only boxes matching the token's selector are listed.
+pub(super) async fn list(
+    State(state): State<Arc<ServerState>>,
+    Extension(access): Extension<Access>,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<ListBoxesResponse>, ApiError> {
+    let boxes = state
+        .runtime
+        .list_info()
+        .await?
+        .iter()
+        .filter(|info| access.allows(info))
+        .filter(|info| {
+            query
+                .status
+                .as_deref()
+                .is_none_or(|status| info.status.as_str() == status)
+        })
+        .map(box_response)
+        .collect();
+    Ok(Json(ListBoxesResponse {
+        boxes,
+        next_page_token: None,
+    }))
+}
+
+/// `GET /boxes/{box_id}`
+pub(super) async fn get(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+) -> Result<Json<BoxResponse>, ApiError> {
+    let info = state
+        .runtime
+        .get_info(&box_id)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("box not found: {}", box_id)))?;
+    Ok(Json(box_response(&info)))
+}
+
+/// `HEAD /boxes/{box_id}`
+pub(super) async fn exists(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    if state.runtime.exists(&box_id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Ok(StatusCode::NOT_FOUND)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct RemoveQuery {
+    #[serde(default)]
+    force: bool,
+}
+
+/// `DELETE /boxes/{box_id}`
+pub(super) async fn remove(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+    Query(query): Query<RemoveQuery>,
+) -> Result<StatusCode, ApiError> {
+    state.runtime.remove(&box_id, query.force).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /boxes/{box_id}/start`
+pub(super) async fn start(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+) -> Result<Json<BoxResponse>, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+    litebox.start().await?;
+    Ok(Json(box_response(&litebox.info())))
+}
+
+/// `POST /boxes/{box_id}/stop`
+///
+/// The optional `timeout_seconds` body is ignored: boxes stop with the
+/// runtime's default timeout.
+pub(super) async fn stop(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+) -> Result<Json<BoxResponse>, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+    litebox.stop().await?;
+    Ok(Json(box_response(&litebox.info())))
+}
+
+/// `GET /metrics`
+///
+/// JSON by default; the Prometheus text format for clients that accept
+/// `text/plain` (or OpenMetrics) and not JSON. Refused to tokens limited
+/// to some boxes, since the totals cover every box.
+pub(super) async fn runtime_metrics(
+    State(state): State<Arc<ServerState>>,
+    Extension(access): Extension<Access>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    access.check_unrestricted()?;
+    let metrics = state.runtime.metrics().await?;
+    if accepts_prometheus_text(&headers) {
+        let text = prometheus::render(&metrics);
+        return Ok(([(header::CONTENT_TYPE, prometheus::CONTENT_TYPE)], text).into_response());
+    }
+    Ok(Json(runtime_metrics_response(&metrics)).into_response())
+}
+
+fn accepts_prometheus_text(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| {
+            (accept.contains("text/plain") || accept.contains("application/openmetrics-text"))
+                && !accept.contains("application/json")
+        })
+}
+
+/// `GET /boxes/{box_id}/metrics`
+pub(super) async fn box_metrics(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+) -> Result<Json<BoxMetricsResponse>, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+    let metrics = litebox.metrics().await?;
+    Ok(Json(box_metrics_response(&metrics)))
+}
+
+/// `GET /boxes/{box_id}/environment`
+pub(super) async fn environment(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+) -> Result<Json<Vec<EnvironmentReport>>, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+    Ok(Json(litebox.environment_reports().await?))
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+/// Convert a create request to box options; unset fields keep their defaults.
+///
+/// `security` is not mapped, matching the client, which never sends it.
+fn box_options(req: CreateBoxRequest) -> BoxOptions {
+    let defaults = BoxOptions::default();
+    let rootfs = match (req.rootfs_path, req.image) {
+        (Some(path), _) => RootfsSpec::RootfsPath(path),
+        (None, Some(image)) => RootfsSpec::Image(image),
+        (None, None) => defaults.rootfs.clone(),
+    };
+
+    BoxOptions {
+        rootfs,
+        cpus: req.cpus,
+        memory_mib: req.memory_mib,
+        priority: req.priority.unwrap_or(defaults.priority),
+        cpu_shares: req.cpu_shares,
+        disk_size_gb: req.disk_size_gb,
+        swap_mib: req.swap_mib,
+        working_dir: req.working_dir,
+        env: req
+            .env
+            .map(|env| env.into_iter().collect())
+            .unwrap_or_default(),
+        timezone: req.timezone,
+        locale: req.locale,
+        labels: req.labels,
+        entrypoint: req.entrypoint,
+        cmd: req.cmd,
+        user: req.user,
+        auto_remove: req.auto_remove.unwrap_or(defaults.auto_remove),
+        detach: req.detach.unwrap_or(defaults.detach),
+        init: req.init.unwrap_or(defaults.init),
+        entrypoint_script: req.entrypoint_script,
+        capture_core_dumps: req
+            .capture_core_dumps
+            .unwrap_or(defaults.capture_core_dumps),
+        read_only_rootfs: req.read_only_rootfs.unwrap_or(defaults.read_only_rootfs),
+        writable_paths: req.writable_paths.unwrap_or_default(),
+        shutdown_grace_period: req
+            .shutdown_grace_period_ms
+            .map(std::time::Duration::from_millis),
+        auto_stop_after_idle: req
+            .auto_stop_after_idle_ms
+            .map(std::time::Duration::from_millis),
+        idle_cpu_threshold: req.idle_cpu_threshold,
+        network: req.network.unwrap_or(defaults.network),
+        ..defaults
+    }
+}
+
+fn box_response(info: &BoxInfo) -> BoxResponse {
+    BoxResponse {
+        box_id: info.id.to_string(),
+        name: info.name.clone(),
+        status: info.status.as_str().to_string(),
+        created_at: info.created_at.to_rfc3339(),
+        updated_at: info.last_updated.to_rfc3339(),
+        pid: info.pid,
+        exit_code: info.exit_code,
+        image: info.image.clone(),
+        image_digest: info.image_digest.clone(),
+        cpus: info.cpus,
+        memory_mib: info.memory_mib,
+        swap_mib: info.swap_mib,
+        labels: info.labels.clone(),
+        network: info.network.clone(),
+        network_mode: info.network_mode,
+        timezone: info.timezone.clone(),
+        locale: info.locale.clone(),
+        read_only_rootfs: info.read_only_rootfs,
+        writable_paths: info.writable_paths.clone(),
+        hosts: info.hosts.clone(),
+        shutdown_grace_period_ms: Some(info.shutdown_grace_period.as_millis() as u64),
+        auto_stop_after_idle_ms: info
+            .auto_stop_after_idle
+            .map(|window| window.as_millis() as u64),
+        idle_remaining_ms: info.idle_remaining.map(|left| left.as_millis() as u64),
+        degradations: info.degradations.clone(),
+    }
+}
+
+fn runtime_metrics_response(metrics: &RuntimeMetrics) -> RuntimeMetricsResponse {
+    RuntimeMetricsResponse {
+        boxes_created_total: metrics.boxes_created_total(),
+        boxes_failed_total: metrics.boxes_failed_total(),
+        boxes_stopped_total: metrics.boxes_stopped_total(),
+        num_running_boxes: metrics.num_running_boxes(),
+        total_commands_executed: metrics.total_commands_executed(),
+        total_exec_errors: metrics.total_exec_errors(),
+        per_image: metrics.per_image(),
+    }
+}
+
+fn box_metrics_response(metrics: &BoxMetrics) -> BoxMetricsResponse {
+    let ms = |value: Option<u128>| value.map(|v| v as u64);
+    BoxMetricsResponse {
+        commands_executed_total: metrics.commands_executed_total,
+        exec_errors_total: metrics.exec_errors_total,
+        bytes_sent_total: metrics.bytes_sent_total,
+        bytes_received_total: metrics.bytes_received_total,
+        cpu_percent: metrics.cpu_percent,
+        memory_bytes: metrics.memory_bytes,
+        swap_bytes: metrics.swap_bytes,
+        network_bytes_sent: metrics.network_bytes_sent,
+        network_bytes_received: metrics.network_bytes_received,
+        network_tcp_connections: metrics.network_tcp_connections,
+        network_tcp_errors: metrics.network_tcp_errors,
+        boot_timing: Some(BootTimingResponse {
+            total_create_ms: ms(metrics.total_create_duration_ms),
+            guest_boot_ms: ms(metrics.guest_boot_duration_ms),
+            filesystem_setup_ms: ms(metrics.stage_filesystem_setup_ms),
+            image_prepare_ms: ms(metrics.stage_image_prepare_ms),
+            guest_rootfs_ms: ms(metrics.stage_guest_rootfs_ms),
+            box_config_ms: ms(metrics.stage_box_config_ms),
+            box_spawn_ms: ms(metrics.stage_box_spawn_ms),
+            container_init_ms: ms(metrics.stage_container_init_ms),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::NetworkMode;
+
+    #[test]
+    fn test_accepts_event_stream() {
+        let mut headers = HeaderMap::new();
+        assert!(!accepts_event_stream(&headers));
+        headers.insert(header::ACCEPT, "application/json".parse().unwrap());
+        assert!(!accepts_event_stream(&headers));
+        headers.insert(header::ACCEPT, "text/event-stream".parse().unwrap());
+        assert!(accepts_event_stream(&headers));
+    }
+
+    #[test]
+    fn test_accepts_prometheus_text() {
+        let mut headers = HeaderMap::new();
+        assert!(!accepts_prometheus_text(&headers));
+        headers.insert(header::ACCEPT, "text/plain;version=0.0.4".parse().unwrap());
+        assert!(accepts_prometheus_text(&headers));
+        headers.insert(
+            header::ACCEPT,
+            "application/openmetrics-text;version=1.0.0,text/plain;q=0.5"
+                .parse()
+                .unwrap(),
+        );
+        assert!(accepts_prometheus_text(&headers));
+        headers.insert(
+            header::ACCEPT,
+            "application/json, text/plain, */*".parse().unwrap(),
+        );
+        assert!(!accepts_prometheus_text(&headers));
+    }
+
+    #[test]
+    fn test_box_options_round_trip_from_client_request() {
+        let opts = BoxOptions {
+            rootfs: RootfsSpec::Image("python:3.11".into()),
+            cpus: Some(2),
+            memory_mib: Some(1024),
+            disk_size_gb: Some(4),
+            swap_mib: Some(512),
+            env: vec![("A".into(), "1".into())],
+            timezone: Some("host".into()),
+            locale: Some("C.UTF-8".into()),
+            labels: [("tier".to_string(), "web".to_string())].into(),
+            auto_remove: false,
+            init: true,
+            entrypoint_script: Some("#!/bin/sh\nexec \"$@\"\n".into()),
+            capture_core_dumps: true,
+            read_only_rootfs: true,
+            writable_paths: vec!["/var/cache".into()],
+            shutdown_grace_period: Some(std::time::Duration::from_secs(20)),
+            auto_stop_after_idle: Some(std::time::Duration::from_secs(7200)),
+            idle_cpu_threshold: Some(5.0),
+            network: NetworkMode::None,
+            ..Default::default()
+        };
+        let req = CreateBoxRequest::from_options(&opts, None);
+        let parsed = box_options(req);
+
+        assert!(matches!(parsed.rootfs, RootfsSpec::Image(ref i) if i == "python:3.11"));
+        assert_eq!(parsed.cpus, Some(2));
+        assert_eq!(parsed.memory_mib, Some(1024));
+        assert_eq!(parsed.swap_mib, Some(512));
+        assert_eq!(parsed.env, vec![("A".to_string(), "1".to_string())]);
+        assert_eq!(parsed.timezone.as_deref(), Some("host"));
+        assert_eq!(parsed.locale.as_deref(), Some("C.UTF-8"));
+        assert_eq!(parsed.labels, opts.labels);
+        assert!(!parsed.auto_remove);
+        assert!(parsed.init);
+        assert_eq!(parsed.entrypoint_script, opts.entrypoint_script);
+        assert!(parsed.capture_core_dumps);
+        assert!(parsed.read_only_rootfs);
+        assert_eq!(parsed.writable_paths, opts.writable_paths);
+        assert_eq!(parsed.shutdown_grace_period, opts.shutdown_grace_period);
+        assert_eq!(parsed.auto_stop_after_idle, opts.auto_stop_after_idle);
+        assert_eq!(parsed.idle_cpu_threshold, Some(5.0));
+        assert_eq!(parsed.network, NetworkMode::None);
+    }
+
+    #[test]
+    fn test_box_options_defaults_for_empty_request() {
+        let req: CreateBoxRequest = serde_json::from_str("{}").unwrap();
+        let parsed = box_options(req);
+        let defaults = BoxOptions::default();
+
+        assert_eq!(parsed.auto_remove, defaults.auto_remove);
+        assert_eq!(parsed.detach, defaults.detach);
+        assert!(!parsed.init);
+        assert_eq!(parsed.network, defaults.network);
+        assert!(parsed.env.is_empty());
+        assert_eq!(parsed.shutdown_grace_period, None);
+    }
+
+    #[test]
+    fn test_box_response_round_trips_through_client() {
+        let resp = BoxResponse {
+            box_id: "01J0000000000000000000000A".to_string(),
+            name: Some("mybox".to_string()),
+            status: "running".to_string(),
+            created_at: "2024-01-01T00:00:00+00:00".to_string(),
+            updated_at: "2024-01-01T00:01:00+00:00".to_string(),
+            pid: Some(1234),
+            exit_code: None,
+            image: "python:3.11".to_string(),
+            image_digest: Some("sha256:ab".to_string()),
+            cpus: 2,
+            memory_mib: 512,
+            swap_mib: 256,
+            labels: [("tier".to_string(), "web".to_string())].into(),
+            network: None,
+            network_mode: NetworkMode::None,
+            timezone: Some("Europe/Berlin".to_string()),
+            locale: None,
+            read_only_rootfs: true,
+            writable_paths: vec!["/var/cache".to_string()],
+            hosts: Vec::new(),
+            shutdown_grace_period_ms: Some(20_000),
+            auto_stop_after_idle_ms: Some(7_200_000),
+            idle_remaining_ms: Some(600_000),
+            degradations: Vec::new(),
+        };
+        let info = resp.to_box_info();
+        let again = box_response(&info);
+
+        assert_eq!(again.box_id, resp.box_id);
+        assert_eq!(again.status, resp.status);
+        assert_eq!(again.created_at, resp.created_at);
+        assert_eq!(again.updated_at, resp.updated_at);
+        assert_eq!(again.pid, resp.pid);
+        assert_eq!(again.network_mode, NetworkMode::None);
+        assert_eq!(again.swap_mib, resp.swap_mib);
+        assert_eq!(again.timezone, resp.timezone);
+        assert_eq!(again.labels, resp.labels);
+        assert_eq!(again.image_digest, resp.image_digest);
+        assert!(again.read_only_rootfs);
+        assert_eq!(again.writable_paths, resp.writable_paths);
+        assert_eq!(again.shutdown_grace_period_ms, Some(20_000));
+        assert_eq!(again.auto_stop_after_idle_ms, Some(7_200_000));
+        assert_eq!(again.idle_remaining_ms, Some(600_000));
+    }
+}
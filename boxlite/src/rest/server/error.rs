@@ -0,0 +1,178 @@
+//! BoxliteError → HTTP error response mapping.
+//!
+//! The inverse of the client's `rest::error` mapping, so errors survive a
+//! round trip through the bundled client with their variant intact.
+
+use axum::Json;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+
+use boxlite_shared::errors::BoxliteError;
+
+use crate::rest::types::{ErrorModel, ErrorResponse};
+
+/// Error returned by a request handler, rendered as an `ErrorResponse`.
+#[derive(Debug)]
+pub(super) struct ApiError {
+    status: StatusCode,
+    error_type: &'static str,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: StatusCode, error_type: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            error_type,
+            message: message.into(),
+        }
+    }
+
+    pub(super) fn not_found(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NotFoundError", message)
+    }
+
+    pub(super) fn invalid_state(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::CONFLICT, "InvalidStateError", message)
+    }
+
+    pub(super) fn bad_request(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "InvalidArgumentError", message)
+    }
+
+    pub(super) fn unauthorized(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::UNAUTHORIZED, "UnauthorizedError", message)
+    }
+
+    pub(super) fn permission_denied(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "PermissionDeniedError", message)
+    }
+
+    /// The response body, also sent as the `error` event of event streams.
+    pub(super) fn into_error_response(self) -> ErrorResponse {
+        ErrorResponse {
+            error: ErrorModel {
+                message: self.message,
+                error_type: self.error_type.to_string(),
+                code: self.status.as_u16(),
+            },
+        }
+    }
+}
+
+impl From<BoxliteError> for ApiError {
+    fn from(err: BoxliteError) -> Self {
+        let (status, error_type) = match &err {
+            BoxliteError::NotFound(_) => (StatusCode::NOT_FOUND, "NotFoundError"),
+            BoxliteError::AlreadyExists(_) => (StatusCode::CONFLICT, "AlreadyExistsError"),
+            BoxliteError::InvalidState(_) => (StatusCode::CONFLICT, "InvalidStateError"),
+            BoxliteError::Stopped(_) => (StatusCode::CONFLICT, "StoppedError"),
+            BoxliteError::ShuttingDown(_) => (StatusCode::SERVICE_UNAVAILABLE, "ShuttingDownError"),
+            BoxliteError::Busy { .. } => (StatusCode::CONFLICT, "BusyError"),
+            BoxliteError::ConfigDrift { .. } => (StatusCode::CONFLICT, "ConfigDriftError"),
+            BoxliteError::InvalidArgument(_) => (StatusCode::BAD_REQUEST, "InvalidArgumentError"),
+            BoxliteError::Config(_) => (StatusCode::BAD_REQUEST, "ConfigError"),
+            BoxliteError::Unsupported(_) | BoxliteError::UnsupportedEngine => {
+                (StatusCode::BAD_REQUEST, "UnsupportedError")
+            }
+            BoxliteError::PolicyDenied(_) => (StatusCode::FORBIDDEN, "PolicyDeniedError"),
+            BoxliteError::PermissionDenied(_) => (StatusCode::FORBIDDEN, "PermissionDeniedError"),
+            BoxliteError::Image(_) => (StatusCode::UNPROCESSABLE_ENTITY, "ImageError"),
+            BoxliteError::Execution(_) => (StatusCode::UNPROCESSABLE_ENTITY, "ExecutionError"),
+            BoxliteError::UnsafeArchive(_) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, "UnsafeArchiveError")
+            }
+            BoxliteError::UnsafeCopy(_) => (StatusCode::UNPROCESSABLE_ENTITY, "UnsafeCopyError"),
+            BoxliteError::Portal(_) => (StatusCode::BAD_GATEWAY, "PortalError"),
+            BoxliteError::Network(_) | BoxliteError::OfflineMode(_) => {
+                (StatusCode::BAD_GATEWAY, "NetworkError")
+            }
+            BoxliteError::Rpc(_) => (StatusCode::BAD_GATEWAY, "RpcError"),
+            BoxliteError::RpcTransport(_) => (StatusCode::BAD_GATEWAY, "RpcTransportError"),
+            BoxliteError::Timeout(_) => (StatusCode::GATEWAY_TIMEOUT, "InternalError"),
+            BoxliteError::PartialTransfer(_) => {
+                (StatusCode::GATEWAY_TIMEOUT, "PartialTransferError")
+            }
+            BoxliteError::Engine(_) => (StatusCode::INTERNAL_SERVER_ERROR, "EngineError"),
+            BoxliteError::Storage(_) => (StatusCode::INTERNAL_SERVER_ERROR, "StorageError"),
+            BoxliteError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "DatabaseError"),
+            BoxliteError::MetadataError(_) => (StatusCode::INTERNAL_SERVER_ERROR, "MetadataError"),
+            BoxliteError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "InternalError"),
+        };
+        Self::new(status, error_type, err.to_string())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if self.status.is_server_error() {
+            tracing::warn!(status = %self.status, error = %self.message, "REST request failed");
+        }
+        let status = self.status;
+        (status, Json(self.into_error_response())).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rest::error::map_http_error;
+
+    /// Map `err` to a response model and back through the client mapping.
+    fn round_trip(err: BoxliteError) -> BoxliteError {
+        let api = ApiError::from(err);
+        let model = ErrorModel {
+            message: api.message,
+            error_type: api.error_type.to_string(),
+            code: api.status.as_u16(),
+        };
+        map_http_error(api.status, &model)
+    }
+
+    #[test]
+    fn test_client_recovers_error_variant() {
+        assert!(matches!(
+            round_trip(BoxliteError::NotFound("x".into())),
+            BoxliteError::NotFound(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::AlreadyExists("x".into())),
+            BoxliteError::AlreadyExists(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::InvalidState("x".into())),
+            BoxliteError::InvalidState(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::Stopped("x".into())),
+            BoxliteError::Stopped(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::ShuttingDown("x".into())),
+            BoxliteError::ShuttingDown(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::InvalidArgument("x".into())),
+            BoxliteError::InvalidArgument(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::Image("x".into())),
+            BoxliteError::Image(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::PermissionDenied("x".into())),
+            BoxliteError::PermissionDenied(_)
+        ));
+        assert!(matches!(
+            round_trip(BoxliteError::UnsafeCopy("x".into())),
+            BoxliteError::UnsafeCopy(reason) if reason == "x"
+        ));
+    }
+
+    #[test]
+    fn test_message_keeps_error_context() {
+        let api = ApiError::from(BoxliteError::NotFound("abc".into()));
+        assert_eq!(api.status, StatusCode::NOT_FOUND);
+        assert_eq!(api.message, "box not found: abc");
+    }
+}
@@ -0,0 +1,415 @@
+//! Execution endpoints: start, status, SSE output, stdin, signal, resize.
+//!
+//! Executions live in a registry keyed by execution ID. Output can be
+//! streamed once; the exit status stays queryable for a while after the
+//! execution finishes.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::Json;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::IntoResponse;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use parking_lot::Mutex;
+use serde::Serialize;
+use tokio::sync::watch;
+
+use super::ServerState;
+use super::boxes::find_box;
+use super::error::ApiError;
+use crate::litebox::encoding::{InvalidUtf8, OutputStream};
+use crate::litebox::{BoxCommand, ExecResult, ExecStdin, Execution};
+use crate::rest::types::{ExecRequest, ExecResponse, ResizeRequestBody, SignalRequestBody};
+
+/// How long a finished execution stays in the registry.
+const FINISHED_RETENTION: Duration = Duration::from_secs(600);
+
+/// Executions started through the API, by execution ID.
+#[derive(Default)]
+pub(super) struct Executions {
+    active: Mutex<HashMap<String, Arc<ActiveExecution>>>,
+}
+
+impl Executions {
+    fn contains(&self, execution_id: &str) -> bool {
+        self.active.lock().contains_key(execution_id)
+    }
+
+    /// Register `execution`, dropping executions that finished long ago.
+    fn insert(&self, execution: Arc<ActiveExecution>) {
+        let mut active = self.active.lock();
+        active.retain(|_, entry| !entry.expired());
+        active.insert(execution.execution.id().clone(), execution);
+    }
+
+    /// Look up an execution of the box named by `box_id` (ID or name).
+    fn get(&self, box_id: &str, execution_id: &str) -> Result<Arc<ActiveExecution>, ApiError> {
+        self.active
+            .lock()
+            .get(execution_id)
+            .filter(|entry| entry.box_id == box_id || entry.box_name.as_deref() == Some(box_id))
+            .cloned()
+            .ok_or_else(|| ApiError::not_found(format!("execution not found: {}", execution_id)))
+    }
+}
+
+/// Final status of an execution.
+#[derive(Clone)]
+struct Finished {
+    result: ExecResult,
+    duration: Duration,
+    at: Instant,
+}
+
+struct ActiveExecution {
+    execution: Execution,
+    box_id: String,
+    box_name: Option<String>,
+    started_at: DateTime<Utc>,
+    stdin: tokio::sync::Mutex<Option<ExecStdin>>,
+    /// Output streams, text or raw, taken by the first output request.
+    output: Mutex<Option<(Option<OutputStream>, Option<OutputStream>)>>,
+    finished: watch::Receiver<Option<Finished>>,
+}
+
+impl ActiveExecution {
+    /// Register streams of `execution` and track its completion.
+    fn spawn(mut execution: Execution, box_id: String, box_name: Option<String>) -> Arc<Self> {
+        let stdin = execution.stdin();
+        let output = (
+            execution.take_stdout(|_| true),
+            execution.take_stderr(|_| true),
+        );
+
+        let (finished_tx, finished) = watch::channel(None);
+        let mut waiter = execution.clone();
+        let started = Instant::now();
+        tokio::spawn(async move {
+            let result = waiter.wait().await.unwrap_or_else(|e| ExecResult {
+                exit_code: -1,
+                error_message: Some(e.to_string()),
+            });
+            let _ = finished_tx.send(Some(Finished {
+                result,
+                duration: started.elapsed(),
+                at: Instant::now(),
+            }));
+        });
+
+        Arc::new(Self {
+            execution,
+            box_id,
+            box_name,
+            started_at: Utc::now(),
+            stdin: tokio::sync::Mutex::new(stdin),
+            output: Mutex::new(Some(output)),
+            finished,
+        })
+    }
+
+    fn finished_now(&self) -> Option<Finished> {
+        self.finished.borrow().clone()
+    }
+
+    fn expired(&self) -> bool {
+        self.finished_now()
+            .is_some_and(|finished| finished.at.elapsed() > FINISHED_RETENTION)
+    }
+
+    fn ensure_running(&self) -> Result<(), ApiError> {
+        match self.finished_now() {
+            Some(_) => Err(ApiError::invalid_state("execution is not running")),
+            None => Ok(()),
+        }
+    }
+
+    async fn wait(&self) -> Finished {
+        let mut finished = self.finished.clone();
+        match finished.wait_for(Option::is_some).await {
+            Ok(value) => value.clone().expect("waited for Some"),
+            // The waiter task never drops the sender without sending
+            Err(_) => unreachable!("execution waiter exited without a result"),
+        }
+    }
+}
+
+/// `POST /boxes/{box_id}/exec`
+pub(super) async fn start(
+    State(state): State<Arc<ServerState>>,
+    Path((workspace, box_id)): Path<(String, String)>,
+    Json(req): Json<ExecRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+
+    let mut command = box_command(req);
+    // A taken ID gets a generated one; the client warns about the mismatch
+    if let Some(requested) = &command.execution_id
+        && state.executions.contains(requested)
+    {
+        command.execution_id = None;
+    }
+
+    let execution = litebox.exec(command).await?;
+    let execution_id = execution.id().clone();
+    let active = ActiveExecution::spawn(
+        execution,
+        litebox.id().to_string(),
+        litebox.name().map(str::to_string),
+    );
+    state.executions.insert(active);
+
+    let location = format!(
+        "{}/boxes/{}/executions/{}",
+        state.workspace_path(&workspace),
+        box_id,
+        execution_id
+    );
+    Ok((
+        StatusCode::CREATED,
+        [(header::LOCATION, location)],
+        Json(ExecResponse { execution_id }),
+    ))
+}
+
+/// `GET /executions/{exec_id}` response.
+#[derive(Debug, Serialize)]
+pub(super) struct ExecutionInfo {
+    execution_id: String,
+    status: &'static str,
+    exit_code: Option<i32>,
+    started_at: String,
+    duration_ms: Option<u64>,
+    error_message: Option<String>,
+}
+
+/// `GET /boxes/{box_id}/executions/{exec_id}`
+pub(super) async fn status(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id, exec_id)): Path<(String, String, String)>,
+) -> Result<Json<ExecutionInfo>, ApiError> {
+    let active = state.executions.get(&box_id, &exec_id)?;
+    let finished = active.finished_now();
+    let status = match &finished {
+        None => "running",
+        Some(f) if f.result.error_message.is_some() || f.result.exit_code < 0 => "killed",
+        Some(_) => "completed",
+    };
+
+    Ok(Json(ExecutionInfo {
+        execution_id: exec_id,
+        status,
+        exit_code: finished.as_ref().map(|f| f.result.exit_code),
+        started_at: active.started_at.to_rfc3339(),
+        duration_ms: finished.as_ref().map(|f| f.duration.as_millis() as u64),
+        error_message: finished.and_then(|f| f.result.error_message),
+    }))
+}
+
+/// `GET /boxes/{box_id}/executions/{exec_id}/output`
+///
+/// Streams `stdout`/`stderr` events with base64 data, then one `exit` event.
+/// Output can be streamed by one request only.
+pub(super) async fn output(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id, exec_id)): Path<(String, String, String)>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let active = state.executions.get(&box_id, &exec_id)?;
+    let (stdout, stderr) = active.output.lock().take().ok_or_else(|| {
+        ApiError::invalid_state(format!("output of {} is already being streamed", exec_id))
+    })?;
+
+    let events = async_stream::stream! {
+        let mut output = std::pin::pin!(futures::stream::select(
+            output_events("stdout", stdout),
+            output_events("stderr", stderr),
+        ));
+        while let Some(event) = output.next().await {
+            yield Ok(event);
+        }
+        yield Ok(exit_event(&active.wait().await));
+    };
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// `POST /boxes/{box_id}/executions/{exec_id}/input`
+///
+/// Writes the body to stdin; `X-Close-Stdin: true` closes it afterwards.
+pub(super) async fn input(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id, exec_id)): Path<(String, String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let active = state.executions.get(&box_id, &exec_id)?;
+    let close = headers
+        .get("X-Close-Stdin")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"));
+
+    let mut stdin = active.stdin.lock().await;
+    let writer = stdin
+        .as_mut()
+        .ok_or_else(|| ApiError::invalid_state("stdin is closed"))?;
+    if !body.is_empty() {
+        writer.write_all(&body).await?;
+    }
+    if close {
+        writer.close();
+        *stdin = None;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /boxes/{box_id}/executions/{exec_id}/signal`
+pub(super) async fn signal(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id, exec_id)): Path<(String, String, String)>,
+    Json(req): Json<SignalRequestBody>,
+) -> Result<StatusCode, ApiError> {
+    let active = state.executions.get(&box_id, &exec_id)?;
+    active.ensure_running()?;
+    active.execution.signal(req.signal).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// `POST /boxes/{box_id}/executions/{exec_id}/resize`
+pub(super) async fn resize(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id, exec_id)): Path<(String, String, String)>,
+    Json(req): Json<ResizeRequestBody>,
+) -> Result<StatusCode, ApiError> {
+    let active = state.executions.get(&box_id, &exec_id)?;
+    active.ensure_running()?;
+    active.execution.resize_tty(req.rows, req.cols).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+/// Convert an exec request to a command.
+///
+/// Templating is left off: the client has already rendered the command.
+fn box_command(req: ExecRequest) -> BoxCommand {
+    let mut command = BoxCommand::new(req.command)
+        .args(req.args)
+        .tty(req.tty)
+        .inherit_runtime_env(req.inherit_env)
+        .output_encoding(req.output_encoding);
+    if let Some(env) = req.env {
+        command.env = Some(env.into_iter().collect());
+    }
+    command.env_edits = req.env_edits;
+    if let Some(seconds) = req.timeout_seconds.filter(|s| s.is_finite() && *s > 0.0) {
+        command = command.timeout(Duration::from_secs_f64(seconds));
+    }
+    if let Some(dir) = req.working_dir {
+        command = command.working_dir(dir);
+    }
+    command.execution_id = req.execution_id;
+    command
+}
+
+/// Events of one output stream: its chunks, then the sequence strict
+/// decoding stopped at, if any.
+fn output_events(stream: &'static str, output: Option<OutputStream>) -> impl Stream<Item = Event> {
+    async_stream::stream! {
+        if let Some(mut output) = output {
+            while let Some(chunk) = output.next().await {
+                yield output_event(stream, &chunk);
+            }
+            if let Some(error) = output.decode_error() {
+                yield invalid_utf8_event(stream, &error);
+            }
+        }
+    }
+}
+
+/// SSE event carrying an output chunk as `{"data":"<base64>"}`.
+fn output_event(stream: &'static str, chunk: &[u8]) -> Event {
+    let data = base64::engine::general_purpose::STANDARD.encode(chunk);
+    Event::default()
+        .event(stream)
+        .data(serde_json::json!({ "data": data }).to_string())
+}
+
+/// SSE event carrying the invalid sequence that ended a strictly decoded
+/// stream.
+fn invalid_utf8_event(stream: &'static str, error: &InvalidUtf8) -> Event {
+    let data = base64::engine::general_purpose::STANDARD.encode(&error.bytes);
+    Event::default().event("invalid_utf8").data(
+        serde_json::json!({ "stream": stream, "offset": error.offset, "data": data }).to_string(),
+    )
+}
+
+/// SSE event carrying the exit status.
+fn exit_event(finished: &Finished) -> Event {
+    let mut data = serde_json::json!({
+        "exit_code": finished.result.exit_code,
+        "duration_ms": finished.duration.as_millis() as u64,
+    });
+    if let Some(error) = &finished.result.error_message {
+        data["error"] = serde_json::Value::String(error.clone());
+    }
+    Event::default().event("exit").data(data.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_box_command_from_request() {
+        let req: ExecRequest = serde_json::from_str(
+            r#"{
+                "command": "python3",
+                "args": ["-c", "print(1)"],
+                "env": {"A": "1"},
+                "timeout_seconds": 1.5,
+                "working_dir": "/app",
+                "tty": true,
+                "execution_id": "exec-1",
+                "inherit_env": true,
+                "env_edits": [
+                    {"op": "append", "key": "PATH", "value": "/opt/bin", "separator": ":"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        let command = box_command(req);
+
+        assert_eq!(command.command, "python3");
+        assert_eq!(command.args, vec!["-c", "print(1)"]);
+        assert_eq!(command.env, Some(vec![("A".into(), "1".into())]));
+        assert_eq!(command.env_edits[0].value(), Some("/opt/bin"));
+        assert_eq!(command.timeout, Some(Duration::from_millis(1500)));
+        assert_eq!(command.working_dir.as_deref(), Some("/app"));
+        assert!(command.tty);
+        assert!(command.inherit_runtime_env);
+        assert!(!command.templating);
+        assert_eq!(command.execution_id.as_deref(), Some("exec-1"));
+    }
+
+    #[test]
+    fn test_box_command_minimal_request() {
+        let req: ExecRequest = serde_json::from_str(r#"{"command": "ls"}"#).unwrap();
+        let command = box_command(req);
+
+        assert!(command.args.is_empty());
+        assert!(command.env.is_none());
+        assert!(command.env_edits.is_empty());
+        assert!(command.timeout.is_none());
+        assert!(!command.tty);
+        assert!(!command.inherit_runtime_env);
+    }
+}
@@ -0,0 +1,167 @@
+//! File transfer endpoints: tar archives in and out of a box.
+
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::{Path, Query, State};
+use axum::http::{StatusCode, header};
+use axum::response::IntoResponse;
+use serde::Deserialize;
+
+use boxlite_shared::archive::{self, ArchiveLimits};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use super::ServerState;
+use super::boxes::find_box;
+use super::error::ApiError;
+use crate::litebox::CopyOptions;
+
+/// Largest upload body accepted, advertised in `GET /config`.
+pub(super) const TRANSFER_MAX_BYTES: usize = 1 << 30;
+
+#[derive(Debug, Deserialize)]
+pub(super) struct UploadQuery {
+    path: String,
+    #[serde(default = "default_overwrite")]
+    overwrite: bool,
+}
+
+fn default_overwrite() -> bool {
+    true
+}
+
+/// `PUT /boxes/{box_id}/files?path=`
+///
+/// The body is a tar archive; its entries land under `path`.
+pub(super) async fn upload(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> Result<StatusCode, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+    let tmp = temp_dir()?;
+    let extract_dir = tmp.path().join("extracted");
+
+    let dir = extract_dir.clone();
+    tokio::task::spawn_blocking(move || unpack(&body, &dir))
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("tar extraction task failed: {}", e)))??;
+
+    let opts = CopyOptions {
+        overwrite: query.overwrite,
+        include_parent: false,
+        ..Default::default()
+    };
+    litebox.copy_into(&extract_dir, &query.path, opts).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Deserialize)]
+pub(super) struct DownloadQuery {
+    path: String,
+    #[serde(default)]
+    follow_symlinks: bool,
+}
+
+/// `GET /boxes/{box_id}/files?path=`
+///
+/// Responds with a tar archive of `path`.
+pub(super) async fn download(
+    State(state): State<Arc<ServerState>>,
+    Path((_, box_id)): Path<(String, String)>,
+    Query(query): Query<DownloadQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let litebox = find_box(&state, &box_id).await?;
+    let tmp = temp_dir()?;
+    let out_dir = tmp.path().join("out");
+    std::fs::create_dir(&out_dir).map_err(|e| {
+        BoxliteError::Storage(format!("failed to create {}: {}", out_dir.display(), e))
+    })?;
+
+    let opts = CopyOptions::default().follow_symlinks(query.follow_symlinks);
+    litebox.copy_out(&query.path, &out_dir, opts).await?;
+
+    let tar_bytes = tokio::task::spawn_blocking(move || pack(&out_dir))
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("tar creation task failed: {}", e)))??;
+    drop(tmp);
+
+    Ok(([(header::CONTENT_TYPE, "application/x-tar")], tar_bytes))
+}
+
+fn temp_dir() -> BoxliteResult<tempfile::TempDir> {
+    tempfile::tempdir()
+        .map_err(|e| BoxliteError::Storage(format!("failed to create temp directory: {}", e)))
+}
+
+/// Unpack an uploaded archive into `dst`.
+fn unpack(tar_bytes: &[u8], dst: &FsPath) -> BoxliteResult<()> {
+    std::fs::create_dir_all(dst)
+        .map_err(|e| BoxliteError::Storage(format!("failed to create {}: {}", dst.display(), e)))?;
+    archive::unpack(
+        &mut tar::Archive::new(tar_bytes),
+        dst,
+        ArchiveLimits::default(),
+    )
+    .map_err(|e| match e {
+        BoxliteError::Storage(msg) => {
+            BoxliteError::InvalidArgument(format!("invalid tar archive: {}", msg))
+        }
+        other => other,
+    })
+}
+
+/// Archive the entries of `dir` under their own names.
+fn pack(dir: &FsPath) -> BoxliteResult<Vec<u8>> {
+    let to_err = |e: std::io::Error| {
+        BoxliteError::Storage(format!("failed to archive {}: {}", dir.display(), e))
+    };
+
+    let mut archive = tar::Builder::new(Vec::new());
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(to_err)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()
+        .map_err(to_err)?;
+    entries.sort();
+
+    for path in entries {
+        let name = path.file_name().expect("read_dir entries have names");
+        if path.is_dir() {
+            archive.append_dir_all(name, &path).map_err(to_err)?;
+        } else {
+            archive.append_path_with_name(&path, name).map_err(to_err)?;
+        }
+    }
+    archive.into_inner().map_err(to_err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack_round_trip() {
+        let src = tempfile::tempdir().unwrap();
+        std::fs::write(src.path().join("a.txt"), "a").unwrap();
+        std::fs::create_dir(src.path().join("sub")).unwrap();
+        std::fs::write(src.path().join("sub/b.txt"), "b").unwrap();
+
+        let tar_bytes = pack(src.path()).unwrap();
+        let dst = tempfile::tempdir().unwrap();
+        unpack(&tar_bytes, &dst.path().join("x")).unwrap();
+
+        let out = dst.path().join("x");
+        assert_eq!(std::fs::read_to_string(out.join("a.txt")).unwrap(), "a");
+        assert_eq!(std::fs::read_to_string(out.join("sub/b.txt")).unwrap(), "b");
+    }
+
+    #[test]
+    fn test_unpack_rejects_garbage() {
+        let dst = tempfile::tempdir().unwrap();
+        let err = unpack(&[b'x'; 1024], dst.path()).unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidArgument(_)));
+    }
+}
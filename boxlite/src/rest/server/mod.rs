@@ -0,0 +1,453 @@
+//! Embedded REST API server.
+//!
+//! Serves the REST API described in `openapi/rest-sandbox-open-api.yaml`
+//! from a [`BoxliteRuntime`], so an application can self-host it instead of
+//! running a separate server. The bundled REST client backend
+//! ([`BoxliteRuntime::rest`]) works against it.
+//!
+//! Enabled with the `rest-server` feature flag.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use boxlite::BoxliteRuntime;
+//! use boxlite::rest::server::{RestServer, ScopedToken, TokenScope};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let runtime = BoxliteRuntime::with_defaults()?;
+//! let server = RestServer::builder()
+//!     .runtime(runtime)
+//!     .bind("127.0.0.1:8080".parse()?)
+//!     .auth("shared-secret")
+//!     .token(ScopedToken::new("dashboard-secret", TokenScope::ReadOnly))
+//!     .build()?;
+//!
+//! // Runs until the runtime shuts down; drop the future to stop earlier.
+//! server.serve().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Scope
+//!
+//! - The `{prefix}` path segment is accepted but does not isolate anything:
+//!   every prefix sees the same runtime.
+//! - Image endpoints and the WebSocket TTY endpoint are not served. TTY
+//!   executions work through `POST /exec` with `tty: true` and the output,
+//!   input and resize endpoints.
+
+mod auth;
+mod boxes;
+mod error;
+mod exec;
+mod files;
+mod prometheus;
+
+pub use auth::{RestServerAuth, ScopedToken, TokenScope};
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::Router;
+use axum::extract::DefaultBodyLimit;
+use axum::routing::{MethodRouter, delete, get, post, put};
+use hyper_util::rt::{TokioExecutor, TokioIo};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::{GracefulShutdown, Watcher};
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpListener;
+use tokio::task::JoinSet;
+use tokio_rustls::TlsAcceptor;
+use tokio_util::sync::CancellationToken;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::runtime::BoxliteRuntime;
+
+use exec::Executions;
+
+/// Address served when [`RestServerBuilder::bind`] is not called.
+const DEFAULT_BIND: &str = "127.0.0.1:8080";
+
+/// API path prefix when [`RestServerBuilder::prefix`] is not called.
+const DEFAULT_PREFIX: &str = "v1";
+
+/// Time allowed for a client to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time in-flight requests get to finish once shutdown starts.
+///
+/// Output streams of long-running executions are cut off after this.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// State shared by all request handlers.
+struct ServerState {
+    runtime: BoxliteRuntime,
+    auth: RestServerAuth,
+    executions: Executions,
+    /// API path prefix, e.g. `v1`.
+    prefix: String,
+}
+
+impl ServerState {
+    /// URL path of a workspace's resources, e.g. `/v1/default`.
+    fn workspace_path(&self, workspace: &str) -> String {
+        format!("/{}/{}", self.prefix, workspace)
+    }
+}
+
+/// An embeddable REST API server for a [`BoxliteRuntime`].
+///
+/// Created with [`RestServer::builder`]. The listening socket is bound by
+/// [`RestServerBuilder::build`]; requests are served once [`serve`](Self::serve)
+/// is awaited.
+pub struct RestServer {
+    listener: std::net::TcpListener,
+    local_addr: SocketAddr,
+    router: Router,
+    tls: Option<TlsAcceptor>,
+    require_client_cert: bool,
+    shutdown: Option<CancellationToken>,
+}
+
+impl RestServer {
+    /// Start configuring a server.
+    pub fn builder() -> RestServerBuilder {
+        RestServerBuilder::default()
+    }
+
+    /// Address the server is bound to (resolves port 0 to the assigned port).
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Serve requests until the runtime shuts down.
+    ///
+    /// On shutdown the server stops accepting connections and gives in-flight
+    /// requests a grace period to finish. REST-backed runtimes have no
+    /// shutdown signal of their own; drop the future to stop serving them.
+    pub async fn serve(self) -> BoxliteResult<()> {
+        let listener = TcpListener::from_std(self.listener)
+            .map_err(|e| BoxliteError::Network(format!("failed to register listener: {}", e)))?;
+        let shutdown = self.shutdown.unwrap_or_default();
+        let graceful = GracefulShutdown::new();
+        let mut connections = JoinSet::new();
+
+        tracing::info!(
+            addr = %self.local_addr,
+            tls = self.tls.is_some(),
+            "REST server listening"
+        );
+
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                accepted = listener.accept() => {
+                    let (stream, peer) = match accepted {
+                        Ok(conn) => conn,
+                        Err(e) => {
+                            tracing::warn!(error = %e, "Failed to accept connection");
+                            continue;
+                        }
+                    };
+                    let router = self.router.clone();
+                    let watcher = graceful.watcher();
+                    match self.tls.clone() {
+                        Some(acceptor) => {
+                            let require_client_cert = self.require_client_cert;
+                            connections.spawn(async move {
+                                let stream = match tokio::time::timeout(
+                                    TLS_HANDSHAKE_TIMEOUT,
+                                    acceptor.accept(stream),
+                                )
+                                .await
+                                {
+                                    Ok(Ok(stream)) => stream,
+                                    Ok(Err(e)) => {
+                                        tracing::debug!(%peer, error = %e, "TLS handshake failed");
+                                        return;
+                                    }
+                                    Err(_) => {
+                                        tracing::debug!(%peer, "TLS handshake timed out");
+                                        return;
+                                    }
+                                };
+                                let has_client_cert = stream
+                                    .get_ref()
+                                    .1
+                                    .peer_certificates()
+                                    .is_some_and(|certs| !certs.is_empty());
+                                if require_client_cert && !has_client_cert {
+                                    tracing::warn!(%peer, "Refusing connection without a client certificate");
+                                    return;
+                                }
+                                serve_connection(stream, peer, router, watcher).await;
+                            });
+                        }
+                        None => {
+                            connections.spawn(serve_connection(stream, peer, router, watcher));
+                        }
+                    }
+                }
+            }
+        }
+
+        tracing::info!("Runtime shut down; stopping REST server");
+        drop(listener);
+        if tokio::time::timeout(SHUTDOWN_GRACE, graceful.shutdown())
+            .await
+            .is_err()
+        {
+            tracing::warn!(
+                open = connections.len(),
+                "Closing REST connections still open after the grace period"
+            );
+        }
+        connections.shutdown().await;
+        Ok(())
+    }
+}
+
+/// Serve HTTP/1 or HTTP/2 on one connection until it closes.
+async fn serve_connection<I>(io: I, peer: SocketAddr, router: Router, watcher: Watcher)
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let builder = auto::Builder::new(TokioExecutor::new());
+    let conn = builder.serve_connection(TokioIo::new(io), TowerToHyperService::new(router));
+    if let Err(e) = watcher.watch(conn).await {
+        tracing::debug!(%peer, error = %e, "REST connection closed with error");
+    }
+}
+
+/// Builder for [`RestServer`].
+///
+/// Only [`runtime`](Self::runtime) is required. Without
+/// [`auth`](Self::auth), [`token`](Self::token) or
+/// [`client_cert_auth`](Self::client_cert_auth), any client that can reach
+/// the address has full control of the runtime.
+#[derive(Default)]
+pub struct RestServerBuilder {
+    runtime: Option<BoxliteRuntime>,
+    addr: Option<SocketAddr>,
+    auth: RestServerAuth,
+    tls: Option<Arc<rustls::ServerConfig>>,
+    prefix: Option<String>,
+}
+
+impl RestServerBuilder {
+    /// Runtime whose boxes the server manages.
+    pub fn runtime(mut self, runtime: BoxliteRuntime) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Address to listen on (default: `127.0.0.1:8080`). Port 0 picks a free port.
+    pub fn bind(mut self, addr: SocketAddr) -> Self {
+        self.addr = Some(addr);
+        self
+    }
+
+    /// Require `token` as a bearer token on every API request.
+    ///
+    /// See [`RestServerAuth::Token`] for how the bundled client obtains it.
+    pub fn auth(mut self, token: impl Into<String>) -> Self {
+        self.auth = RestServerAuth::Token(token.into());
+        self
+    }
+
+    /// Accept `token` as a bearer token, limited to its scope and selector.
+    ///
+    /// Call repeatedly to configure several tokens. A token set with
+    /// [`auth`](Self::auth) is kept, with [`TokenScope::Full`].
+    pub fn token(mut self, token: ScopedToken) -> Self {
+        self.auth = match self.auth {
+            RestServerAuth::Tokens(mut tokens) => {
+                tokens.push(token);
+                RestServerAuth::Tokens(tokens)
+            }
+            RestServerAuth::Token(shared) => {
+                RestServerAuth::Tokens(vec![ScopedToken::new(shared, TokenScope::Full), token])
+            }
+            RestServerAuth::None | RestServerAuth::ClientCert => {
+                RestServerAuth::Tokens(vec![token])
+            }
+        };
+        self
+    }
+
+    /// Require a client certificate on every connection (mTLS).
+    ///
+    /// Certificates are verified by the client verifier of the
+    /// [`tls`](Self::tls) configuration, which is required.
+    pub fn client_cert_auth(mut self) -> Self {
+        self.auth = RestServerAuth::ClientCert;
+        self
+    }
+
+    /// Serve HTTPS with `config`.
+    ///
+    /// For mTLS, build `config` with a client certificate verifier and call
+    /// [`client_cert_auth`](Self::client_cert_auth).
+    pub fn tls(mut self, config: Arc<rustls::ServerConfig>) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// API path prefix (default: `v1`), matching
+    /// [`BoxliteRestOptions::with_prefix`](crate::BoxliteRestOptions::with_prefix).
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = Some(prefix.into());
+        self
+    }
+
+    /// Validate the configuration and bind the listening socket.
+    pub fn build(self) -> BoxliteResult<RestServer> {
+        let runtime = self
+            .runtime
+            .ok_or_else(|| BoxliteError::Config("RestServer requires a runtime".into()))?;
+        if matches!(self.auth, RestServerAuth::ClientCert) && self.tls.is_none() {
+            return Err(BoxliteError::Config(
+                "client certificate auth requires a TLS configuration".into(),
+            ));
+        }
+        if let RestServerAuth::Tokens(tokens) = &self.auth {
+            for token in tokens {
+                token.validate()?;
+            }
+        }
+        let prefix = self
+            .prefix
+            .unwrap_or_else(|| DEFAULT_PREFIX.to_string())
+            .trim_matches('/')
+            .to_string();
+        if prefix.is_empty() {
+            return Err(BoxliteError::InvalidArgument(
+                "REST API prefix must not be empty".into(),
+            ));
+        }
+
+        let addr = self
+            .addr
+            .unwrap_or_else(|| DEFAULT_BIND.parse().expect("valid default address"));
+        let listener = std::net::TcpListener::bind(addr)
+            .map_err(|e| BoxliteError::Network(format!("failed to bind {}: {}", addr, e)))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| BoxliteError::Network(format!("failed to configure listener: {}", e)))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| BoxliteError::Network(format!("failed to read bound address: {}", e)))?;
+
+        let require_client_cert = matches!(self.auth, RestServerAuth::ClientCert);
+        let shutdown = runtime.shutdown_token();
+        let state = Arc::new(ServerState {
+            runtime,
+            auth: self.auth,
+            executions: Executions::default(),
+            prefix,
+        });
+
+        Ok(RestServer {
+            listener,
+            local_addr,
+            router: router(state),
+            tls: self.tls.map(TlsAcceptor::from),
+            require_client_cert,
+            shutdown,
+        })
+    }
+}
+
+/// Routes of the REST API, nested under the API prefix.
+///
+/// Each route's methods are guarded by the [`TokenScope`] they need:
+/// reads need read-only, executions and file transfers exec, and
+/// everything that changes a box's lifecycle full.
+fn router(state: Arc<ServerState>) -> Router {
+    use TokenScope::{Exec, Full, ReadOnly};
+
+    let guard = |scope: TokenScope, methods: MethodRouter<Arc<ServerState>>| {
+        methods.route_layer(axum::middleware::from_fn_with_state(
+            (state.clone(), scope),
+            auth::authorize,
+        ))
+    };
+
+    let protected = Router::new()
+        .route(
+            "/:prefix/boxes",
+            guard(Full, post(boxes::create)).merge(guard(ReadOnly, get(boxes::list))),
+        )
+        .route(
+            "/:prefix/boxes/:box_id",
+            guard(ReadOnly, get(boxes::get).head(boxes::exists))
+                .merge(guard(Full, delete(boxes::remove))),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/start",
+            guard(Full, post(boxes::start)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/stop",
+            guard(Full, post(boxes::stop)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/metrics",
+            guard(ReadOnly, get(boxes::box_metrics)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/environment",
+            guard(ReadOnly, get(boxes::environment)),
+        )
+        .route(
+            "/:prefix/metrics",
+            guard(ReadOnly, get(boxes::runtime_metrics)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/exec",
+            guard(Exec, post(exec::start)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/executions/:exec_id",
+            guard(Exec, get(exec::status)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/executions/:exec_id/output",
+            guard(Exec, get(exec::output)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/executions/:exec_id/input",
+            guard(Exec, post(exec::input)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/executions/:exec_id/signal",
+            guard(Exec, post(exec::signal)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/executions/:exec_id/resize",
+            guard(Exec, post(exec::resize)),
+        )
+        .route(
+            "/:prefix/boxes/:box_id/files",
+            guard(Exec, put(files::upload).get(files::download))
+                .layer(DefaultBodyLimit::max(files::TRANSFER_MAX_BYTES)),
+        )
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            auth::require_auth,
+        ));
+
+    let api = Router::new()
+        .route("/config", get(boxes::config))
+        .route("/version", get(boxes::version))
+        .route("/oauth/tokens", post(auth::issue_token))
+        .merge(protected);
+
+    Router::new()
+        .nest(&format!("/{}", state.prefix), api)
+        .with_state(state)
+}
@@ -0,0 +1,137 @@
+//! Prometheus text exposition of runtime metrics, served by `GET /metrics`
+//! to scrapers that ask for `text/plain`.
+
+use std::fmt::Write;
+
+use crate::metrics::{ImageUsage, RuntimeMetrics};
+
+/// Content type of the text exposition format.
+pub(super) const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Render `metrics`: the aggregate counters, then the per-image breakdown
+/// with an `image` label.
+pub(super) fn render(metrics: &RuntimeMetrics) -> String {
+    let mut out = String::new();
+    let aggregate = [
+        (
+            "boxlite_boxes_created_total",
+            "counter",
+            "Boxes created",
+            metrics.boxes_created_total(),
+        ),
+        (
+            "boxlite_boxes_failed_total",
+            "counter",
+            "Boxes that failed to start",
+            metrics.boxes_failed_total(),
+        ),
+        (
+            "boxlite_boxes_stopped_total",
+            "counter",
+            "Boxes stopped",
+            metrics.boxes_stopped_total(),
+        ),
+        (
+            "boxlite_running_boxes",
+            "gauge",
+            "Boxes currently running",
+            metrics.num_running_boxes(),
+        ),
+        (
+            "boxlite_commands_executed_total",
+            "counter",
+            "Commands executed across all boxes",
+            metrics.total_commands_executed(),
+        ),
+        (
+            "boxlite_exec_errors_total",
+            "counter",
+            "Command executions that failed",
+            metrics.total_exec_errors(),
+        ),
+    ];
+    for (name, kind, help, value) in aggregate {
+        header(&mut out, name, kind, help);
+        let _ = writeln!(out, "{name} {value}");
+    }
+
+    let per_image = metrics.per_image();
+    let series: [(&str, &str, fn(&ImageUsage) -> f64); 4] = [
+        (
+            "boxlite_image_boxes_created_total",
+            "Boxes created, by image",
+            |u| u.boxes_created as f64,
+        ),
+        (
+            "boxlite_image_commands_executed_total",
+            "Commands executed, by image",
+            |u| u.commands_executed as f64,
+        ),
+        (
+            "boxlite_image_box_seconds_total",
+            "Seconds boxes have been running, by image",
+            |u| u.box_seconds,
+        ),
+        (
+            "boxlite_image_pulled_bytes_total",
+            "Layer bytes downloaded, by image",
+            |u| u.bytes_pulled as f64,
+        ),
+    ];
+    for (name, help, value) in series {
+        header(&mut out, name, "counter", help);
+        for (image, usage) in &per_image {
+            let _ = writeln!(
+                out,
+                "{name}{{image=\"{}\"}} {}",
+                escape_label(image),
+                value(usage)
+            );
+        }
+    }
+    out
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+/// Escape a label value: backslash, double quote and newline.
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::RuntimeMetricsStorage;
+    use std::sync::atomic::Ordering;
+
+    #[test]
+    fn test_render_includes_image_labels() {
+        let storage = RuntimeMetricsStorage::new();
+        storage.boxes_created.store(3, Ordering::Relaxed);
+        storage.per_image.box_created("alpine");
+        storage.per_image.bytes_pulled("alpine", 2048);
+
+        let text = render(&RuntimeMetrics::new(storage));
+        assert!(text.contains("# TYPE boxlite_boxes_created_total counter\n"));
+        assert!(text.contains("\nboxlite_boxes_created_total 3\n"));
+        assert!(text.contains(
+            "boxlite_image_boxes_created_total{image=\"docker.io/library/alpine:latest\"} 1\n"
+        ));
+        assert!(text.contains(
+            "boxlite_image_pulled_bytes_total{image=\"docker.io/library/alpine:latest\"} 2048\n"
+        ));
+    }
+
+    #[test]
+    fn test_escape_label() {
+        assert_eq!(escape_label(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape_label("a\nb"), r"a\nb");
+    }
+}
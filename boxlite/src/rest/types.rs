@@ -1,22 +1,28 @@
 //! Request/response serde structs matching the OpenAPI schema.
 //!
-//! These are wire-format types for the REST API. They are converted
-//! to/from core types (BoxInfo, BoxOptions, etc.) at the boundary.
+//! These are wire-format types for the REST API, shared by the client and
+//! the embedded server. They are converted to/from core types (BoxInfo,
+//! BoxOptions, etc.) at the boundary.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use serde::{Deserialize, Serialize};
 
+use crate::litebox::{EnvEdit, OutputEncoding};
+use crate::metrics::ImageUsage;
+
+use crate::runtime::create_progress::{CreateEvent, CreatePhase};
+
 // ============================================================================
 // Error Model
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ErrorResponse {
     pub error: ErrorModel,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ErrorModel {
     pub message: String,
     #[serde(rename = "type")]
@@ -36,7 +42,7 @@ pub(crate) struct TokenRequest<'a> {
     pub client_secret: &'a str,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct TokenResponse {
     pub access_token: String,
     #[allow(dead_code)]
@@ -48,7 +54,7 @@ pub(crate) struct TokenResponse {
 // Box
 // ============================================================================
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct CreateBoxRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -61,12 +67,24 @@ pub(crate) struct CreateBoxRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub memory_mib: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<crate::runtime::options::BoxPriority>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub disk_size_gb: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub swap_mib: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub working_dir: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub labels: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub entrypoint: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cmd: Option<Vec<String>>,
@@ -77,6 +95,24 @@ pub(crate) struct CreateBoxRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub detach: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub init: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint_script: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capture_core_dumps: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only_rootfs: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub writable_paths: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shutdown_grace_period_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_stop_after_idle_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_cpu_threshold: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network: Option<crate::runtime::options::NetworkMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub security: Option<String>,
 }
 
@@ -104,20 +140,45 @@ impl CreateBoxRequest {
             rootfs_path,
             cpus: options.cpus,
             memory_mib: options.memory_mib,
+            // Omitted unless set, like init
+            priority: (options.priority != Default::default()).then_some(options.priority),
+            cpu_shares: options.cpu_shares,
             disk_size_gb: options.disk_size_gb,
+            swap_mib: options.swap_mib,
             working_dir: options.working_dir.clone(),
             env,
+            timezone: options.timezone.clone(),
+            locale: options.locale.clone(),
+            labels: options.labels.clone(),
             entrypoint: options.entrypoint.clone(),
             cmd: options.cmd.clone(),
             user: options.user.clone(),
             auto_remove: Some(options.auto_remove),
             detach: Some(options.detach),
+            // Omitted unless set, for servers predating the field
+            init: options.init.then_some(true),
+            entrypoint_script: options.entrypoint_script.clone(),
+            // Omitted unless set, like init
+            capture_core_dumps: options.capture_core_dumps.then_some(true),
+            // Omitted unless set, like init
+            read_only_rootfs: options.read_only_rootfs.then_some(true),
+            writable_paths: (!options.writable_paths.is_empty())
+                .then(|| options.writable_paths.clone()),
+            shutdown_grace_period_ms: options
+                .shutdown_grace_period
+                .map(|grace| grace.as_millis() as u64),
+            auto_stop_after_idle_ms: options
+                .auto_stop_after_idle
+                .map(|window| window.as_millis() as u64),
+            idle_cpu_threshold: options.idle_cpu_threshold,
+            // Omitted unless set, like init
+            network: (options.network != Default::default()).then_some(options.network),
             security: None, // TODO: map security preset
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct BoxResponse {
     pub box_id: String,
     pub name: Option<String>,
@@ -125,11 +186,40 @@ pub(crate) struct BoxResponse {
     pub created_at: String,
     pub updated_at: String,
     pub pid: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
     pub image: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_digest: Option<String>,
     pub cpus: u8,
     pub memory_mib: u32,
     #[serde(default)]
+    pub swap_mib: u32,
+    #[serde(default)]
     pub labels: HashMap<String, String>,
+    #[serde(default)]
+    pub network: Option<crate::net::BoxNetwork>,
+    #[serde(default)]
+    pub network_mode: crate::runtime::options::NetworkMode,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub read_only_rootfs: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub writable_paths: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hosts: Vec<crate::net::HostEntry>,
+    /// Absent from servers predating the field; read as the default.
+    #[serde(default)]
+    pub shutdown_grace_period_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_stop_after_idle_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_remaining_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degradations: Vec<crate::runtime::capabilities::Degradation>,
 }
 
 impl BoxResponse {
@@ -139,13 +229,8 @@ impl BoxResponse {
 
         let id = BoxID::parse(&self.box_id).unwrap_or_default();
 
-        let status = match self.status.as_str() {
-            "configured" => BoxStatus::Configured,
-            "running" => BoxStatus::Running,
-            "stopping" => BoxStatus::Stopping,
-            "stopped" => BoxStatus::Stopped,
-            _ => BoxStatus::Unknown,
-        };
+        // Accepts the names of servers predating created/exited/dead too
+        let status = self.status.parse().unwrap_or(BoxStatus::Unknown);
 
         let created_at = chrono::DateTime::parse_from_rfc3339(&self.created_at)
             .map(|dt| dt.with_timezone(&chrono::Utc))
@@ -162,29 +247,81 @@ impl BoxResponse {
             created_at,
             last_updated,
             pid: self.pid,
+            exit_code: self.exit_code,
             image: self.image.clone(),
+            image_digest: self.image_digest.clone(),
             cpus: self.cpus,
             memory_mib: self.memory_mib,
+            swap_mib: self.swap_mib,
             labels: self.labels.clone(),
+            network: self.network.clone(),
+            network_mode: self.network_mode,
+            hosts: self.hosts.clone(),
+            timezone: self.timezone.clone(),
+            locale: self.locale.clone(),
+            read_only_rootfs: self.read_only_rootfs,
+            writable_paths: self.writable_paths.clone(),
+            shutdown_grace_period: self.shutdown_grace_period_ms.map_or(
+                crate::runtime::options::DEFAULT_SHUTDOWN_GRACE_PERIOD,
+                std::time::Duration::from_millis,
+            ),
+            auto_stop_after_idle: self
+                .auto_stop_after_idle_ms
+                .map(std::time::Duration::from_millis),
+            idle_remaining: self.idle_remaining_ms.map(std::time::Duration::from_millis),
+            resource_limits: Default::default(),
+            degradations: self.degradations.clone(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ListBoxesResponse {
     pub boxes: Vec<BoxResponse>,
     #[allow(dead_code)]
     pub next_page_token: Option<String>,
 }
 
+/// One `progress` event of a streamed `POST /boxes`.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CreateProgressEvent {
+    #[serde(flatten)]
+    pub phase: CreatePhase,
+    /// RFC 3339 time the server reported the phase.
+    pub at: String,
+    pub elapsed_ms: u64,
+}
+
+impl CreateProgressEvent {
+    pub fn from_event(event: &CreateEvent) -> Self {
+        Self {
+            phase: event.phase.clone(),
+            at: event.at.to_rfc3339(),
+            elapsed_ms: event.elapsed.as_millis() as u64,
+        }
+    }
+
+    pub fn to_event(&self) -> CreateEvent {
+        let at = chrono::DateTime::parse_from_rfc3339(&self.at)
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+            .unwrap_or_else(|_| chrono::Utc::now());
+
+        CreateEvent {
+            phase: self.phase.clone(),
+            at,
+            elapsed: std::time::Duration::from_millis(self.elapsed_ms),
+        }
+    }
+}
+
 // ============================================================================
 // Execution
 // ============================================================================
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ExecRequest {
     pub command: String,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub env: Option<HashMap<String, String>>,
@@ -194,6 +331,15 @@ pub(crate) struct ExecRequest {
     pub working_dir: Option<String>,
     #[serde(default)]
     pub tty: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub execution_id: Option<String>,
+    #[serde(default)]
+    pub inherit_env: bool,
+    #[serde(default)]
+    pub output_encoding: OutputEncoding,
+    /// Prepends, appends and unsets applied after `env`, in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_edits: Vec<EnvEdit>,
 }
 
 impl ExecRequest {
@@ -211,21 +357,25 @@ impl ExecRequest {
             timeout_seconds,
             working_dir: cmd.working_dir.clone(),
             tty: cmd.tty,
+            execution_id: cmd.execution_id.clone(),
+            inherit_env: cmd.inherit_runtime_env,
+            output_encoding: cmd.output_encoding,
+            env_edits: cmd.env_edits.clone(),
         }
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ExecResponse {
     pub execution_id: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct SignalRequestBody {
     pub signal: i32,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct ResizeRequestBody {
     pub cols: u32,
     pub rows: u32,
@@ -235,7 +385,7 @@ pub(crate) struct ResizeRequestBody {
 // Metrics
 // ============================================================================
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct RuntimeMetricsResponse {
     #[serde(default)]
     pub boxes_created_total: u64,
@@ -250,9 +400,11 @@ pub(crate) struct RuntimeMetricsResponse {
     pub total_commands_executed: u64,
     #[serde(default)]
     pub total_exec_errors: u64,
+    #[serde(default)]
+    pub per_image: BTreeMap<String, ImageUsage>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct BoxMetricsResponse {
     #[serde(default)]
     pub commands_executed_total: u64,
@@ -264,6 +416,8 @@ pub(crate) struct BoxMetricsResponse {
     pub bytes_received_total: u64,
     pub cpu_percent: Option<f32>,
     pub memory_bytes: Option<u64>,
+    #[serde(default)]
+    pub swap_bytes: Option<u64>,
     pub network_bytes_sent: Option<u64>,
     pub network_bytes_received: Option<u64>,
     pub network_tcp_connections: Option<u64>,
@@ -271,7 +425,7 @@ pub(crate) struct BoxMetricsResponse {
     pub boot_timing: Option<BootTimingResponse>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub(crate) struct BootTimingResponse {
     pub total_create_ms: Option<u64>,
     pub guest_boot_ms: Option<u64>,
@@ -295,14 +449,29 @@ mod tests {
             rootfs_path: None,
             cpus: Some(2),
             memory_mib: Some(512),
+            priority: None,
+            cpu_shares: None,
             disk_size_gb: None,
+            swap_mib: None,
             working_dir: None,
             env: None,
+            timezone: None,
+            locale: None,
+            labels: HashMap::new(),
             entrypoint: None,
             cmd: None,
             user: None,
             auto_remove: Some(true),
             detach: None,
+            init: None,
+            entrypoint_script: None,
+            capture_core_dumps: None,
+            read_only_rootfs: None,
+            writable_paths: None,
+            shutdown_grace_period_ms: None,
+            auto_stop_after_idle_ms: None,
+            idle_cpu_threshold: None,
+            network: None,
             security: None,
         };
         let json = serde_json::to_string(&req).unwrap();
@@ -330,6 +499,9 @@ mod tests {
         assert!(req.rootfs_path.is_none());
         assert_eq!(req.cpus, Some(4));
         assert_eq!(req.memory_mib, Some(1024));
+        assert!(req.init.is_none());
+        assert!(req.read_only_rootfs.is_none());
+        assert!(req.writable_paths.is_none());
     }
 
     #[test]
@@ -363,16 +535,90 @@ mod tests {
             created_at: "2024-01-01T00:00:00Z".to_string(),
             updated_at: "2024-01-01T00:01:00Z".to_string(),
             pid: Some(1234),
+            exit_code: None,
             image: "python:3.11".to_string(),
+            image_digest: None,
             cpus: 2,
             memory_mib: 512,
+            swap_mib: 0,
             labels: HashMap::new(),
+            network: None,
+            network_mode: Default::default(),
+            timezone: None,
+            locale: None,
+            read_only_rootfs: false,
+            writable_paths: Vec::new(),
+            hosts: Vec::new(),
+            shutdown_grace_period_ms: None,
+            auto_stop_after_idle_ms: None,
+            idle_remaining_ms: None,
+            degradations: Vec::new(),
         };
         let info = resp.to_box_info();
         assert_eq!(info.name.as_deref(), Some("mybox"));
         assert_eq!(info.image, "python:3.11");
         assert_eq!(info.cpus, 2);
         assert_eq!(info.memory_mib, 512);
+        assert_eq!(
+            info.shutdown_grace_period,
+            crate::runtime::options::DEFAULT_SHUTDOWN_GRACE_PERIOD
+        );
+    }
+
+    #[test]
+    fn test_box_response_status_names() {
+        use crate::litebox::BoxStatus;
+
+        let status_of = |status: &str| {
+            let mut json = serde_json::json!({
+                "box_id": "01J0000000000000000000000A",
+                "name": null,
+                "status": status,
+                "created_at": "2024-01-01T00:00:00Z",
+                "updated_at": "2024-01-01T00:01:00Z",
+                "pid": null,
+                "image": "alpine",
+                "cpus": 1,
+                "memory_mib": 512,
+            });
+            if status == "exited" {
+                json["exit_code"] = 3.into();
+            }
+            let resp: BoxResponse = serde_json::from_value(json).unwrap();
+            let info = resp.to_box_info();
+            (info.status, info.exit_code)
+        };
+
+        assert_eq!(status_of("created"), (BoxStatus::Created, None));
+        assert_eq!(status_of("exited"), (BoxStatus::Exited, Some(3)));
+        assert_eq!(status_of("dead"), (BoxStatus::Dead, None));
+        assert_eq!(status_of("snapshotting"), (BoxStatus::Snapshotting, None));
+        // Older servers
+        assert_eq!(status_of("configured"), (BoxStatus::Created, None));
+        assert_eq!(status_of("stopped"), (BoxStatus::Exited, None));
+        assert_eq!(status_of("bogus"), (BoxStatus::Unknown, None));
+    }
+
+    #[test]
+    fn test_create_progress_event_round_trip() {
+        let event = CreateEvent {
+            phase: CreatePhase::PullingLayer {
+                digest: "sha256:ab".into(),
+                done: 10,
+                total: 20,
+            },
+            at: chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+                .unwrap()
+                .with_timezone(&chrono::Utc),
+            elapsed: std::time::Duration::from_millis(1500),
+        };
+        let json = serde_json::to_value(CreateProgressEvent::from_event(&event)).unwrap();
+        assert_eq!(json["phase"], "pulling_layer");
+        assert_eq!(json["digest"], "sha256:ab");
+        assert_eq!(json["elapsed_ms"], 1500);
+
+        let wire: CreateProgressEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(wire.to_event(), event);
     }
 
     #[test]
@@ -384,11 +630,31 @@ mod tests {
             timeout_seconds: Some(30.0),
             working_dir: Some("/app".to_string()),
             tty: false,
+            execution_id: None,
+            inherit_env: false,
+            output_encoding: OutputEncoding::default(),
+            env_edits: Vec::new(),
         };
         let json = serde_json::to_string(&req).unwrap();
         assert!(json.contains("\"command\":\"python3\""));
         assert!(json.contains("\"timeout_seconds\":30.0"));
         assert!(json.contains("\"working_dir\":\"/app\""));
+        assert!(!json.contains("env_edits"));
+    }
+
+    #[test]
+    fn test_exec_request_env_edits_wire_format() {
+        let command = crate::BoxCommand::new("env")
+            .env_prepend_path("PATH", "/custom/bin", ":")
+            .env_unset("DEBUG");
+        let json = serde_json::to_value(ExecRequest::from_command(&command)).unwrap();
+        assert_eq!(
+            json["env_edits"],
+            serde_json::json!([
+                {"op": "prepend", "key": "PATH", "value": "/custom/bin", "separator": ":"},
+                {"op": "unset", "key": "DEBUG"}
+            ])
+        );
     }
 
     #[test]
@@ -419,5 +685,6 @@ mod tests {
         let resp: RuntimeMetricsResponse = serde_json::from_str(json).unwrap();
         assert_eq!(resp.boxes_created_total, 10);
         assert_eq!(resp.total_commands_executed, 100);
+        assert!(resp.per_image.is_empty());
     }
 }
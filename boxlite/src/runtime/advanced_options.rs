@@ -34,6 +34,23 @@ pub struct SecurityOptions {
     #[serde(default = "default_seccomp_enabled")]
     pub seccomp_enabled: bool,
 
+    /// Run the shim in the namespace sandbox (bwrap) when the jailer is
+    /// enabled (Linux only).
+    ///
+    /// Turned off by the runtime for boxes created on a host without user
+    /// namespaces; seccomp, rlimits and cgroups still apply. See
+    /// `BoxInfo::degradations`.
+    /// Default: true
+    #[serde(default = "default_namespaces_enabled")]
+    pub namespaces_enabled: bool,
+
+    /// Fail box creation instead of degrading isolation when the host lacks
+    /// user namespaces or seccomp (Linux only).
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub require_sandbox: bool,
+
     /// UID to drop to after setup (Linux only).
     ///
     /// - None: Auto-allocate an unprivileged UID
@@ -138,6 +155,40 @@ pub struct ResourceLimits {
     /// Maximum CPU time in seconds (RLIMIT_CPU).
     #[serde(default)]
     pub max_cpu_time: Option<u64>,
+
+    /// Maximum disk read bandwidth in bytes per second (cgroup io.max `rbps`).
+    ///
+    /// Disk I/O limits throttle the box's disk images on the block device
+    /// that holds them. Enforced on Linux only, through the jailer's cgroup;
+    /// requires the jailer and the `io` controller to be delegated (see
+    /// `boxlite doctor`). On macOS any limit throttles the box's disk I/O
+    /// behind other processes' instead, without holding it to the set rates.
+    /// Where the limits are not enforced the box records a degradation, and
+    /// changing them on a running box outside Linux fails with `Unsupported`.
+    #[serde(default)]
+    pub disk_read_bps: Option<u64>,
+
+    /// Maximum disk write bandwidth in bytes per second (cgroup io.max `wbps`).
+    #[serde(default)]
+    pub disk_write_bps: Option<u64>,
+
+    /// Maximum disk read operations per second (cgroup io.max `riops`).
+    #[serde(default)]
+    pub disk_read_iops: Option<u64>,
+
+    /// Maximum disk write operations per second (cgroup io.max `wiops`).
+    #[serde(default)]
+    pub disk_write_iops: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// Whether any disk I/O limit is set.
+    pub fn has_disk_io_limits(&self) -> bool {
+        self.disk_read_bps.is_some()
+            || self.disk_write_bps.is_some()
+            || self.disk_read_iops.is_some()
+            || self.disk_write_iops.is_some()
+    }
 }
 
 // Default value functions for SecurityOptions
@@ -150,6 +201,10 @@ fn default_seccomp_enabled() -> bool {
     false
 }
 
+fn default_namespaces_enabled() -> bool {
+    true
+}
+
 fn default_chroot_base() -> PathBuf {
     PathBuf::from("/srv/boxlite")
 }
@@ -186,6 +241,8 @@ impl Default for SecurityOptions {
         Self {
             jailer_enabled: default_jailer_enabled(),
             seccomp_enabled: default_seccomp_enabled(),
+            namespaces_enabled: default_namespaces_enabled(),
+            require_sandbox: false,
             uid: None,
             gid: None,
             new_pid_ns: false,
@@ -249,6 +306,7 @@ impl SecurityOptions {
                 max_processes: Some(100),
                 max_memory: None,   // Let VM config handle this
                 max_cpu_time: None, // Let VM config handle this
+                ..Default::default()
             },
             ..Default::default()
         }
@@ -358,6 +416,13 @@ impl SecurityOptionsBuilder {
         self
     }
 
+    /// Fail box creation instead of degrading isolation on hosts without
+    /// user namespaces or seccomp (Linux only).
+    pub fn require_sandbox(&mut self, required: bool) -> &mut Self {
+        self.inner.require_sandbox = required;
+        self
+    }
+
     /// Set UID to drop to after setup (Linux only).
     pub fn uid(&mut self, uid: u32) -> &mut Self {
         self.inner.uid = Some(uid);
@@ -521,4 +586,11 @@ pub struct AdvancedBoxOptions {
     /// Defaults to false.
     #[serde(default)]
     pub isolate_mounts: bool,
+
+    /// Hash the contents of every volume into the box's environment report.
+    ///
+    /// Reads all files under each volume on every start, so it is slow for
+    /// large volumes. Defaults to false: volumes are recorded by path only.
+    #[serde(default)]
+    pub report_volume_hashes: bool,
 }
@@ -1,15 +1,36 @@
 //! Runtime backend trait — internal abstraction for local vs REST execution.
 
+use std::net::SocketAddr;
 use std::path::Path;
+use std::time::Duration;
 
 use async_trait::async_trait;
 
+use crate::db::compositions::ComposedMember;
+use crate::db::templates::BoxTemplate;
+use crate::db::trash::TrashedBox;
 use crate::litebox::copy::CopyOptions;
-use crate::litebox::{BoxCommand, Execution, LiteBox};
+use crate::litebox::snapshot_types::{SnapshotMetadata, SnapshotRetention};
+use crate::litebox::{
+    BoxCommand, CapturedOutput, CoreDump, EnvironmentReport, Execution, GuestAgentLog, LiteBox,
+    ServiceInfo, ServicePolicy, StartFailure, TunnelHandle,
+};
 use crate::metrics::{BoxMetrics, RuntimeMetrics};
+use crate::runtime::advanced_options::ResourceLimits;
+use crate::runtime::bulk::BulkResults;
+use crate::runtime::capabilities::RuntimeCapabilities;
+use crate::runtime::create_progress::{CreateObserver, CreatePhase, CreateProgress};
+use crate::runtime::drift::{GetOrCreateOutcome, GetOrCreatePolicy};
 use crate::runtime::options::BoxOptions;
-use crate::runtime::types::BoxInfo;
-use boxlite_shared::errors::BoxliteResult;
+use crate::runtime::orphans::OrphanShim;
+use crate::runtime::portability::{ImportOptions, ImportPlan};
+use crate::runtime::types::{BoxInfo, ListOptions, RemovePlan};
+#[cfg(feature = "url-transfer")]
+use crate::runtime::url_transfer::TransferOptions;
+use crate::runtime::version::VersionInfo;
+use crate::runtime::warm_pool::WarmSelector;
+use crate::vmm::ShimExit;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
 use super::types::BoxID;
 
@@ -24,29 +45,239 @@ use super::types::BoxID;
 pub(crate) trait RuntimeBackend: Send + Sync {
     async fn create(&self, options: BoxOptions, name: Option<String>) -> BoxliteResult<LiteBox>;
 
+    /// Create a box, reporting phases to `observer`.
+    /// Default: plain `create`, then `Done` (backends that cannot observe
+    /// the individual phases).
+    async fn create_with_observer(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: CreateObserver,
+    ) -> BoxliteResult<LiteBox> {
+        let progress = CreateProgress::new(observer);
+        let litebox = self.create(options, name).await?;
+        progress.emit(CreatePhase::Done);
+        Ok(litebox)
+    }
+
     async fn get_or_create(
         &self,
         options: BoxOptions,
         name: Option<String>,
     ) -> BoxliteResult<(LiteBox, bool)>;
 
+    /// `get_or_create` with a policy for an existing box whose options differ.
+    /// Default: plain `get_or_create` for `ReuseAlways` (backends that cannot
+    /// see an existing box's options).
+    async fn get_or_create_with(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        policy: GetOrCreatePolicy,
+    ) -> BoxliteResult<(LiteBox, GetOrCreateOutcome)> {
+        if policy != GetOrCreatePolicy::ReuseAlways {
+            return Err(BoxliteError::Unsupported(
+                "get_or_create policies are not supported by this backend".to_string(),
+            ));
+        }
+        let (litebox, created) = self.get_or_create(options, name).await?;
+        let outcome = if created {
+            GetOrCreateOutcome::Created
+        } else {
+            GetOrCreateOutcome::Reused
+        };
+        Ok((litebox, outcome))
+    }
+
     async fn get(&self, id_or_name: &str) -> BoxliteResult<Option<LiteBox>>;
 
     async fn get_info(&self, id_or_name: &str) -> BoxliteResult<Option<BoxInfo>>;
 
     async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>>;
 
+    /// List boxes with filtering options.
+    /// Default: plain `list_info` (backends without warm pools).
+    async fn list_info_with(&self, _options: ListOptions) -> BoxliteResult<Vec<BoxInfo>> {
+        self.list_info().await
+    }
+
+    /// Take a started box from a warm pool.
+    async fn acquire_warm(&self, _selector: WarmSelector) -> BoxliteResult<LiteBox> {
+        Err(BoxliteError::Unsupported(
+            "warm pools are not supported by this backend".to_string(),
+        ))
+    }
+
     async fn exists(&self, id_or_name: &str) -> BoxliteResult<bool>;
 
     async fn metrics(&self) -> BoxliteResult<RuntimeMetrics>;
 
+    async fn version_info(&self) -> BoxliteResult<VersionInfo>;
+
+    /// Host capabilities probed when the runtime started.
+    async fn capabilities(&self) -> BoxliteResult<RuntimeCapabilities> {
+        Err(BoxliteError::Unsupported(
+            "host capabilities are not available over this backend".to_string(),
+        ))
+    }
+
     async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()>;
 
+    /// Remove a box, bypassing the trash.
+    /// Default: same as `remove` (backends without a trash delete immediately).
+    async fn remove_permanently(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        self.remove(id_or_name, force).await
+    }
+
+    /// Describe what `remove` / `remove_permanently` would do without doing it.
+    async fn plan_remove(
+        &self,
+        _id_or_name: &str,
+        _force: bool,
+        _permanently: bool,
+    ) -> BoxliteResult<RemovePlan> {
+        Err(BoxliteError::Unsupported(
+            "planning a removal is not supported by this backend".to_string(),
+        ))
+    }
+
+    async fn list_trashed(&self) -> BoxliteResult<Vec<TrashedBox>> {
+        Err(trash_unsupported())
+    }
+
+    async fn restore_trashed(&self, _id_or_name: &str) -> BoxliteResult<LiteBox> {
+        Err(trash_unsupported())
+    }
+
+    async fn purge_trashed(&self, _id_or_name: &str) -> BoxliteResult<()> {
+        Err(trash_unsupported())
+    }
+
+    /// Import a box from a `.boxsnap` or `.boxlite` archive on this host.
+    async fn import(
+        &self,
+        _archive_path: &Path,
+        _options: ImportOptions,
+    ) -> BoxliteResult<LiteBox> {
+        Err(BoxliteError::Unsupported(
+            "importing boxes is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Describe what `import` would do without doing it.
+    async fn plan_import(
+        &self,
+        _archive_path: &Path,
+        _options: &ImportOptions,
+    ) -> BoxliteResult<ImportPlan> {
+        Err(BoxliteError::Unsupported(
+            "planning an import is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Import a box from an archive at a URL.
+    #[cfg(feature = "url-transfer")]
+    async fn import_from_url(
+        &self,
+        _url: &str,
+        _options: ImportOptions,
+        _transfer: TransferOptions,
+    ) -> BoxliteResult<LiteBox> {
+        Err(BoxliteError::Unsupported(
+            "importing boxes is not supported by this backend".to_string(),
+        ))
+    }
+
+    async fn register_template(
+        &self,
+        _name: &str,
+        _options: BoxOptions,
+    ) -> BoxliteResult<BoxTemplate> {
+        Err(templates_unsupported())
+    }
+
+    async fn get_template(&self, _name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        Err(templates_unsupported())
+    }
+
+    async fn list_templates(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        Err(templates_unsupported())
+    }
+
+    async fn remove_template(&self, _name: &str) -> BoxliteResult<()> {
+        Err(templates_unsupported())
+    }
+
+    /// Record the members of a composition, replacing earlier records.
+    async fn record_composition(&self, _members: &[ComposedMember]) -> BoxliteResult<()> {
+        Err(compositions_unsupported())
+    }
+
+    async fn list_composition(&self) -> BoxliteResult<Vec<ComposedMember>> {
+        Err(compositions_unsupported())
+    }
+
+    async fn forget_composition(&self, _names: &[String]) -> BoxliteResult<()> {
+        Err(compositions_unsupported())
+    }
+
+    async fn list_orphans(&self) -> BoxliteResult<Vec<OrphanShim>> {
+        Err(orphans_unsupported())
+    }
+
+    async fn kill_orphans(&self, _grace: Duration) -> BoxliteResult<Vec<OrphanShim>> {
+        Err(orphans_unsupported())
+    }
+
+    /// Detach every running box the calling process owns.
+    async fn detach_all(&self) -> BoxliteResult<BulkResults<()>> {
+        Err(ownership_unsupported())
+    }
+
+    /// Adopt every running box started with `detach = false` that no live
+    /// process owns.
+    async fn adopt_all(&self) -> BoxliteResult<BulkResults<()>> {
+        Err(ownership_unsupported())
+    }
+
     async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<()>;
 
     /// Synchronous shutdown for atexit/Drop contexts.
     /// Default no-op (REST backend doesn't manage local processes).
     fn shutdown_sync(&self) {}
+
+    /// Token cancelled when the runtime shuts down.
+    /// Default None (REST backend doesn't own the remote runtime's lifecycle).
+    #[cfg(feature = "rest-server")]
+    fn shutdown_token(&self) -> Option<tokio_util::sync::CancellationToken> {
+        None
+    }
+
+    /// Remote peer identity attached to audit events.
+    /// Default None (local backend has no remote peer).
+    fn audit_peer(&self) -> Option<String> {
+        None
+    }
+}
+
+fn trash_unsupported() -> BoxliteError {
+    BoxliteError::Unsupported("trash is not supported by this backend".to_string())
+}
+
+fn templates_unsupported() -> BoxliteError {
+    BoxliteError::Unsupported("box templates are not supported by this backend".to_string())
+}
+
+fn compositions_unsupported() -> BoxliteError {
+    BoxliteError::Unsupported("box compositions are not supported by this backend".to_string())
+}
+
+fn orphans_unsupported() -> BoxliteError {
+    BoxliteError::Unsupported("orphaned shims are not managed by this backend".to_string())
+}
+
+fn ownership_unsupported() -> BoxliteError {
+    BoxliteError::Unsupported("box ownership is not managed by this backend".to_string())
 }
 
 /// Backend abstraction for individual box operations.
@@ -65,6 +296,35 @@ pub(crate) trait BoxBackend: Send + Sync {
 
     async fn exec(&self, command: BoxCommand) -> BoxliteResult<Execution>;
 
+    /// Register a command template for repeated execution.
+    ///
+    /// Returns None when the backend has no prepared-exec support; callers
+    /// then fall back to a full `exec()` per run.
+    async fn prepare_exec(&self, _command: BoxCommand) -> BoxliteResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Execute a template registered by `prepare_exec`, appending `args`.
+    ///
+    /// Fails with `NotFound` when the guest no longer knows the template
+    /// (e.g. after a restart).
+    async fn exec_prepared(
+        &self,
+        _prepared_id: &str,
+        _args: Vec<String>,
+    ) -> BoxliteResult<Execution> {
+        Err(BoxliteError::Unsupported(
+            "prepared exec is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Drop a template registered by `prepare_exec`.
+    ///
+    /// Unknown IDs are not an error.
+    async fn unprepare_exec(&self, _prepared_id: &str) -> BoxliteResult<()> {
+        Ok(())
+    }
+
     async fn metrics(&self) -> BoxliteResult<BoxMetrics>;
 
     async fn stop(&self) -> BoxliteResult<()>;
@@ -82,6 +342,156 @@ pub(crate) trait BoxBackend: Send + Sync {
         host_dst: &Path,
         opts: CopyOptions,
     ) -> BoxliteResult<()>;
+
+    /// Replace the box's resource limits.
+    async fn update_resource_limits(&self, _limits: ResourceLimits) -> BoxliteResult<()> {
+        Err(BoxliteError::Unsupported(
+            "updating resource limits is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Snapshot retention policy currently attached to the box.
+    fn snapshot_retention(&self) -> Option<SnapshotRetention> {
+        None
+    }
+
+    /// Replace the box's snapshot retention policy.
+    async fn set_snapshot_retention(
+        &self,
+        _retention: Option<SnapshotRetention>,
+    ) -> BoxliteResult<()> {
+        Err(BoxliteError::Unsupported(
+            "snapshot retention is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Options and services a snapshot captures as its metadata.
+    fn snapshot_metadata(&self) -> BoxliteResult<SnapshotMetadata> {
+        Err(BoxliteError::Unsupported(
+            "snapshot metadata is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Put back options and services captured by a snapshot, and persist them.
+    fn restore_metadata(&self, _metadata: SnapshotMetadata) -> BoxliteResult<()> {
+        Err(BoxliteError::Unsupported(
+            "snapshot metadata is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Diagnostics from the most recent failed start.
+    ///
+    /// Backends that don't capture boot diagnostics return None.
+    fn last_start_failure(&self) -> Option<StartFailure> {
+        None
+    }
+
+    /// How the box's shim process last exited abnormally.
+    ///
+    /// Backends without a shim process return None.
+    fn last_exit(&self) -> Option<ShimExit> {
+        None
+    }
+
+    /// Clear the provisioned marker and run the setup commands again.
+    async fn reprovision(&self) -> BoxliteResult<()> {
+        Err(BoxliteError::Unsupported(
+            "reprovisioning is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Make the calling process the owner of the running box.
+    async fn adopt(&self) -> BoxliteResult<()> {
+        Err(BoxliteError::Unsupported(
+            "adopting boxes is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Stop owning the running box, leaving it running.
+    async fn detach(&self) -> BoxliteResult<()> {
+        Err(BoxliteError::Unsupported(
+            "detaching boxes is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Relay TCP connections accepted on `bind` to a port inside the box.
+    async fn tunnel(&self, _guest_port: u16, _bind: SocketAddr) -> BoxliteResult<TunnelHandle> {
+        Err(BoxliteError::Unsupported(
+            "tunnels are not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Tail of the guest kernel log of the running box.
+    async fn guest_dmesg(&self, _tail_lines: usize) -> BoxliteResult<Vec<String>> {
+        Err(BoxliteError::Unsupported(
+            "guest kernel log is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Tail of the guest agent's log of the running box.
+    async fn guest_agent_log(&self, _tail_bytes: usize) -> BoxliteResult<GuestAgentLog> {
+        Err(BoxliteError::Unsupported(
+            "guest agent log is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Core dumps captured in the running box.
+    async fn list_core_dumps(&self) -> BoxliteResult<Vec<CoreDump>> {
+        Err(BoxliteError::Unsupported(
+            "core dump capture is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Flush the running box's filesystems and freeze/thaw its container
+    /// filesystem; how long it was frozen.
+    async fn sync(&self, _freeze_cap: Duration) -> BoxliteResult<Duration> {
+        Err(BoxliteError::Unsupported(
+            "syncing boxes is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Read the captured output of a detached execution.
+    async fn exec_output(&self, _exec_id: &str) -> BoxliteResult<CapturedOutput> {
+        Err(BoxliteError::Unsupported(
+            "exec output capture is not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Environment reports recorded at start, oldest first.
+    async fn environment_reports(&self) -> BoxliteResult<Vec<EnvironmentReport>> {
+        Err(BoxliteError::Unsupported(
+            "environment reports are not supported by this backend".to_string(),
+        ))
+    }
+
+    /// Record a supervised service and start it.
+    async fn spawn_service(
+        &self,
+        _name: &str,
+        _command: BoxCommand,
+        _policy: ServicePolicy,
+    ) -> BoxliteResult<()> {
+        Err(services_unsupported())
+    }
+
+    /// Stop a service until the box next starts.
+    async fn stop_service(&self, _name: &str) -> BoxliteResult<()> {
+        Err(services_unsupported())
+    }
+
+    /// Stop a service and forget it.
+    async fn remove_service(&self, _name: &str) -> BoxliteResult<()> {
+        Err(services_unsupported())
+    }
+
+    /// The box's services with their current runs.
+    async fn list_services(&self) -> BoxliteResult<Vec<ServiceInfo>> {
+        Err(services_unsupported())
+    }
+}
+
+fn services_unsupported() -> BoxliteError {
+    BoxliteError::Unsupported("services are not supported by this backend".to_string())
 }
 
 /// Backend abstraction for execution control (kill, resize).
@@ -0,0 +1,78 @@
+//! Extra guest hosts entries (see `BoxliteOptions::box_dns`).
+//!
+//! Entries are resolved when a box starts. With `box_dns`, starting or
+//! stopping a named box rewrites the hosts files of the other running boxes
+//! through the guest agent.
+
+use std::sync::Arc;
+
+use boxlite_shared::errors::BoxliteResult;
+
+use crate::litebox::BoxState;
+use crate::litebox::config::BoxConfig;
+use crate::net::{self, HostEntry, Peer};
+use crate::runtime::options::NetworkMode;
+
+use super::rt_impl::RuntimeImpl;
+
+fn is_networked(config: &BoxConfig) -> bool {
+    config.options.network != NetworkMode::None
+}
+
+impl RuntimeImpl {
+    /// Extra hosts entries for the guest of `config`, given the boxes
+    /// running now.
+    pub(crate) fn guest_hosts(&self, config: &BoxConfig) -> BoxliteResult<Vec<HostEntry>> {
+        let boxes = if self.box_dns && is_networked(config) {
+            self.box_manager.all_boxes(true)?
+        } else {
+            Vec::new()
+        };
+        let peers = boxes.iter().filter_map(|(other, state)| {
+            let name = other.name.as_deref()?;
+            (other.id != config.id && state.status.is_running()).then_some(Peer {
+                name,
+                networked: is_networked(other),
+            })
+        });
+        Ok(net::resolve_hosts(is_networked(config), peers))
+    }
+
+    /// Rewrite the hosts files of the other running boxes after `changed`
+    /// started or stopped.
+    ///
+    /// Failures are logged: a box that misses an update gets current entries
+    /// on its next start.
+    pub(crate) async fn refresh_box_dns(self: &Arc<Self>, changed: &BoxConfig) {
+        if !self.box_dns || changed.name.is_none() || !is_networked(changed) {
+            return;
+        }
+
+        let boxes = match self.box_manager.all_boxes(true) {
+            Ok(boxes) => boxes,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to list boxes for hosts refresh");
+                return;
+            }
+        };
+        for (config, state) in boxes {
+            if config.id == changed.id || !state.status.is_running() || !is_networked(&config) {
+                continue;
+            }
+            let box_id = config.id.clone();
+            if let Err(e) = self.refresh_hosts_of(config, state).await {
+                tracing::warn!(box_id = %box_id, error = %e, "Failed to refresh box hosts");
+            }
+        }
+    }
+
+    async fn refresh_hosts_of(
+        self: &Arc<Self>,
+        config: BoxConfig,
+        state: BoxState,
+    ) -> BoxliteResult<()> {
+        let hosts = self.guest_hosts(&config)?;
+        let (box_impl, _) = self.get_or_create_box_impl(config, state);
+        box_impl.update_hosts(hosts).await
+    }
+}
@@ -0,0 +1,1151 @@
+//! Image builds from a minimal Containerfile subset.
+//!
+//! [`BoxliteRuntime::build_image`] runs the steps of a [`BuildSpec`] in a
+//! throwaway box created from the base image. After every `RUN` and `COPY`
+//! the box's filesystem is flattened into a single layer and stored as an
+//! image (see `images::built`), recorded under the step's cache key. The key
+//! chains the base image digest, every step so far and the content of the
+//! files `COPY` reads, so a rebuild starts from the last step whose key is
+//! unchanged.
+//!
+//! Flattening runs `tar` inside the box, so the base image needs one
+//! (busybox, alpine, debian and most others have it).
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use oci_spec::image::{Config, ImageConfiguration};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::images::built::{BuiltImageStore, BuiltManifest, normalize_tag};
+use crate::litebox::{BoxCommand, CopyOptions, LiteBox};
+use crate::runtime::core::BoxliteRuntime;
+use crate::runtime::options::{BoxOptions, RootfsSpec};
+use crate::runtime::run_once::collect_stream;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Instructions [`BuildSpec::from_containerfile`] accepts.
+pub const SUPPORTED_INSTRUCTIONS: &[&str] =
+    &["FROM", "RUN", "COPY", "ENV", "WORKDIR", "ENTRYPOINT", "CMD"];
+
+/// Where the box's filesystem is archived while a step is committed.
+const SNAPSHOT_TAR: &str = "/tmp/.boxlite-build-rootfs.tar";
+
+/// Bytes of output kept for the error of a failed internal command.
+const INTERNAL_OUTPUT_BYTES: usize = 4 * 1024;
+
+/// One step of an image build.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "instruction", rename_all = "snake_case")]
+pub enum BuildStep {
+    /// Run a command in the build box, with the environment and working
+    /// directory set by earlier steps. Shell form is `["/bin/sh", "-c", ...]`.
+    Run { command: Vec<String> },
+    /// Copy files or directories from the build context into the directory
+    /// `dest` (relative to the working directory). A directory's contents
+    /// are copied, not the directory itself.
+    Copy { sources: Vec<String>, dest: String },
+    /// Set environment variables for later steps and the image. `$VAR` and
+    /// `${VAR}` in values are replaced with earlier values.
+    Env { vars: Vec<(String, String)> },
+    /// Set the working directory, relative to the previous one. Created in
+    /// the image if missing.
+    Workdir { path: String },
+    /// Set the image entrypoint. Clears the base image's `CMD`, as docker does.
+    Entrypoint { command: Vec<String> },
+    /// Set the image's default command.
+    Cmd { command: Vec<String> },
+}
+
+impl BuildStep {
+    /// `RUN` in shell form.
+    pub fn run(command: impl Into<String>) -> Self {
+        BuildStep::Run {
+            command: shell_form(command.into()),
+        }
+    }
+
+    /// `COPY` of one source.
+    pub fn copy(source: impl Into<String>, dest: impl Into<String>) -> Self {
+        BuildStep::Copy {
+            sources: vec![source.into()],
+            dest: dest.into(),
+        }
+    }
+
+    /// `ENV` of one variable.
+    pub fn env(key: impl Into<String>, value: impl Into<String>) -> Self {
+        BuildStep::Env {
+            vars: vec![(key.into(), value.into())],
+        }
+    }
+
+    /// `WORKDIR`.
+    pub fn workdir(path: impl Into<String>) -> Self {
+        BuildStep::Workdir { path: path.into() }
+    }
+
+    /// Whether the step changes the filesystem (and is cached).
+    fn changes_filesystem(&self) -> bool {
+        matches!(self, BuildStep::Run { .. } | BuildStep::Copy { .. })
+    }
+}
+
+impl std::fmt::Display for BuildStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildStep::Run { command } => write!(f, "RUN {}", command_form(command)),
+            BuildStep::Copy { sources, dest } => write!(f, "COPY {} {}", sources.join(" "), dest),
+            BuildStep::Env { vars } => {
+                let vars: Vec<String> = vars
+                    .iter()
+                    .map(|(k, v)| {
+                        if v.is_empty() || v.contains(char::is_whitespace) {
+                            format!("{}={}", k, serde_json::Value::from(v.as_str()))
+                        } else {
+                            format!("{}={}", k, v)
+                        }
+                    })
+                    .collect();
+                write!(f, "ENV {}", vars.join(" "))
+            }
+            BuildStep::Workdir { path } => write!(f, "WORKDIR {}", path),
+            BuildStep::Entrypoint { command } => write!(f, "ENTRYPOINT {}", command_form(command)),
+            BuildStep::Cmd { command } => write!(f, "CMD {}", command_form(command)),
+        }
+    }
+}
+
+/// What [`BoxliteRuntime::build_image`] builds.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BuildSpec {
+    /// Image the build starts from (the implicit `FROM`). May be an image
+    /// built earlier.
+    pub base_image: String,
+    /// Steps, in order.
+    #[serde(default)]
+    pub steps: Vec<BuildStep>,
+    /// Directory `COPY` sources are relative to (default: the current
+    /// directory). Sources may not leave it.
+    #[serde(default = "default_context")]
+    pub context: PathBuf,
+    /// Run every step, ignoring (but still recording) cached results.
+    #[serde(default)]
+    pub no_cache: bool,
+}
+
+fn default_context() -> PathBuf {
+    PathBuf::from(".")
+}
+
+impl BuildSpec {
+    /// A build of `base_image` with no steps yet.
+    pub fn new(base_image: impl Into<String>) -> Self {
+        Self {
+            base_image: base_image.into(),
+            steps: Vec::new(),
+            context: default_context(),
+            no_cache: false,
+        }
+    }
+
+    /// Add a step.
+    pub fn step(mut self, step: BuildStep) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Set the build context directory.
+    pub fn context(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.context = dir.into();
+        self
+    }
+
+    /// Ignore cached step results.
+    pub fn no_cache(mut self, no_cache: bool) -> Self {
+        self.no_cache = no_cache;
+        self
+    }
+
+    /// Parse a Containerfile using the subset in [`SUPPORTED_INSTRUCTIONS`].
+    ///
+    /// It must start with a single `FROM <image>`. Instructions are
+    /// case-insensitive; lines ending in `\` continue on the next line and
+    /// lines starting with `#` are comments. `RUN`, `ENTRYPOINT` and `CMD`
+    /// take the shell form or a JSON array.
+    ///
+    /// # Errors
+    ///
+    /// `InvalidArgument` naming every unsupported instruction or flag in
+    /// the file along with the supported ones, or the first line that
+    /// cannot be parsed.
+    pub fn from_containerfile(text: &str, context: impl Into<PathBuf>) -> BoxliteResult<Self> {
+        let mut base_image = None;
+        let mut steps = Vec::new();
+        let mut unsupported = Vec::new();
+
+        for (line_no, line) in logical_lines(text) {
+            let (instruction, rest) = line
+                .split_once(char::is_whitespace)
+                .map_or((line.as_str(), ""), |(i, r)| (i, r.trim()));
+            let instruction = instruction.to_ascii_uppercase();
+            let invalid =
+                |msg: &str| BoxliteError::InvalidArgument(format!("line {line_no}: {msg}"));
+
+            if !SUPPORTED_INSTRUCTIONS.contains(&instruction.as_str()) {
+                unsupported.push(format!("line {line_no}: {instruction}"));
+                continue;
+            }
+            if matches!(instruction.as_str(), "FROM" | "RUN" | "COPY") && rest.starts_with("--") {
+                let flag = rest.split_whitespace().next().unwrap_or(rest);
+                unsupported.push(format!("line {line_no}: {instruction} {flag}"));
+                continue;
+            }
+            if rest.is_empty() {
+                return Err(invalid(&format!("{instruction} needs arguments")));
+            }
+
+            if instruction == "FROM" {
+                if base_image.is_some() || !steps.is_empty() {
+                    unsupported.push(format!("line {line_no}: FROM after the first line"));
+                } else if rest.split_whitespace().count() > 1 {
+                    unsupported.push(format!("line {line_no}: FROM ... AS"));
+                } else {
+                    base_image = Some(rest.to_string());
+                }
+                continue;
+            }
+            if base_image.is_none() && unsupported.is_empty() {
+                return Err(invalid("the first instruction must be FROM"));
+            }
+
+            steps.push(match instruction.as_str() {
+                "RUN" => BuildStep::Run {
+                    command: parse_command(rest),
+                },
+                "ENTRYPOINT" => BuildStep::Entrypoint {
+                    command: parse_command(rest),
+                },
+                "CMD" => BuildStep::Cmd {
+                    command: parse_command(rest),
+                },
+                "COPY" => {
+                    let mut args = serde_json::from_str::<Vec<String>>(rest)
+                        .unwrap_or_else(|_| rest.split_whitespace().map(str::to_string).collect());
+                    if args.len() < 2 {
+                        return Err(invalid("COPY needs a source and a destination"));
+                    }
+                    let dest = args.pop().expect("checked above");
+                    BuildStep::Copy {
+                        sources: args,
+                        dest,
+                    }
+                }
+                "ENV" => BuildStep::Env {
+                    vars: parse_env(rest).map_err(|e| invalid(&e))?,
+                },
+                "WORKDIR" => BuildStep::Workdir {
+                    path: rest.to_string(),
+                },
+                _ => unreachable!("checked against SUPPORTED_INSTRUCTIONS"),
+            });
+        }
+
+        if !unsupported.is_empty() {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "unsupported in Containerfile: {}. Supported instructions: {}",
+                unsupported.join(", "),
+                SUPPORTED_INSTRUCTIONS.join(", ")
+            )));
+        }
+        let base_image = base_image.ok_or_else(|| {
+            BoxliteError::InvalidArgument("Containerfile has no FROM instruction".into())
+        })?;
+
+        Ok(Self {
+            base_image,
+            steps,
+            context: context.into(),
+            no_cache: false,
+        })
+    }
+}
+
+/// Progress of [`BoxliteRuntime::build_image_with`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildEvent {
+    /// A step begins. The `FROM` is step 1 of `total`.
+    Step {
+        index: usize,
+        total: usize,
+        /// The step as a Containerfile line.
+        step: String,
+        /// The step's result was taken from the build cache.
+        cached: bool,
+    },
+    /// Output of a `RUN` step, as it arrives.
+    Output { text: String, stderr: bool },
+    /// The box's filesystem is being flattened into an image.
+    Committing,
+}
+
+/// Callback invoked with build progress. Called from the build task, so it
+/// should return quickly.
+pub type BuildObserver = Arc<dyn Fn(&BuildEvent) + Send + Sync>;
+
+/// Result of [`BoxliteRuntime::build_image`].
+#[derive(Clone, Debug)]
+pub struct BuiltImage {
+    /// The tag the image was given, with `:latest` added if it had none.
+    pub reference: String,
+    /// Manifest digest of the image.
+    pub digest: String,
+    /// Filesystem steps taken from the build cache.
+    pub cached_steps: usize,
+    /// Wall-clock time of the build.
+    pub duration: Duration,
+}
+
+impl BoxliteRuntime {
+    /// Build an image from `spec` and tag it `tag`.
+    ///
+    /// The image is stored locally and can be used like a pulled one, e.g.
+    /// `RootfsSpec::Image("local/myimg".into())` or as the base of another
+    /// build. Building the same tag again moves the tag. See
+    /// [`BuildSpec::from_containerfile`] for the supported subset.
+    ///
+    /// # Errors
+    ///
+    /// - `InvalidArgument` for an invalid tag, or a `COPY` source that is
+    ///   missing or outside the context (checked before anything runs)
+    /// - `Execution` if a `RUN` step exits non-zero
+    /// - `Unsupported` for REST runtimes
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use boxlite::{BoxliteRuntime, BuildSpec, BuildStep};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let runtime = BoxliteRuntime::with_defaults()?;
+    /// let spec = BuildSpec::new("alpine:latest")
+    ///     .step(BuildStep::run("apk add --no-cache curl"))
+    ///     .step(BuildStep::env("GREETING", "hello"));
+    /// let image = runtime.build_image("local/curl", spec).await?;
+    /// println!("{} {}", image.reference, image.digest);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn build_image(&self, tag: &str, spec: BuildSpec) -> BoxliteResult<BuiltImage> {
+        self.build_image_with(tag, spec, Arc::new(|_: &BuildEvent| {}))
+            .await
+    }
+
+    /// Build an image, reporting each step and the output of `RUN` steps
+    /// to `observer`.
+    pub async fn build_image_with(
+        &self,
+        tag: &str,
+        spec: BuildSpec,
+        observer: BuildObserver,
+    ) -> BoxliteResult<BuiltImage> {
+        let started = Instant::now();
+        crate::validate::image_reference(tag)?;
+        let store = self.built_images()?;
+
+        // Everything the steps read from the host is checked before the
+        // build starts
+        let context = std::fs::canonicalize(&spec.context).map_err(|e| {
+            BoxliteError::InvalidArgument(format!(
+                "build context {}: {}",
+                spec.context.display(),
+                e
+            ))
+        })?;
+        let mut copy_hashes = Vec::with_capacity(spec.steps.len());
+        for step in &spec.steps {
+            copy_hashes.push(match step {
+                BuildStep::Copy { sources, .. } => Some(hash_sources(&context, sources)?),
+                _ => None,
+            });
+        }
+
+        let total = spec.steps.len() + 1;
+        observer(&BuildEvent::Step {
+            index: 1,
+            total,
+            step: format!("FROM {}", spec.base_image),
+            cached: false,
+        });
+        let base = self.images()?.pull(&spec.base_image).await?;
+        let mut build = Build {
+            runtime: self,
+            store,
+            observer,
+            base_image: spec.base_image.clone(),
+            state: ImageState::from_config(base.load_config().await?),
+            image: None,
+            builder: None,
+            key: chain_key("", &format!("FROM {}", base.manifest_digest())),
+        };
+
+        let result = build
+            .run(&spec, &context, &copy_hashes, total)
+            .await
+            .and_then(|(digest, cached_steps)| {
+                build.store.tag(tag, &digest)?;
+                Ok(BuiltImage {
+                    reference: normalize_tag(tag),
+                    digest,
+                    cached_steps,
+                    duration: started.elapsed(),
+                })
+            });
+        build.remove_builder().await;
+        result
+    }
+
+    /// Best-effort stop + remove of a build box. Problems are logged.
+    async fn cleanup_build_box(&self, litebox: &LiteBox) {
+        let box_id = litebox.id().clone();
+        if let Err(e) = litebox.stop().await {
+            tracing::warn!(box_id = %box_id, error = %e, "build: failed to stop box");
+        }
+        match self.remove(box_id.as_str(), true).await {
+            Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+            Err(e) => tracing::warn!(box_id = %box_id, error = %e, "build: failed to remove box"),
+        }
+    }
+}
+
+/// Image settings the steps change.
+#[derive(Debug, Clone)]
+struct ImageState {
+    config: ImageConfiguration,
+    env: Vec<(String, String)>,
+    workdir: String,
+    /// `WORKDIR`s not yet created in the box.
+    pending_dirs: Vec<String>,
+}
+
+impl ImageState {
+    fn from_config(config: ImageConfiguration) -> Self {
+        let exec = config.config().clone().unwrap_or_default();
+        let env = exec
+            .env()
+            .iter()
+            .flatten()
+            .filter_map(|var| var.split_once('='))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let workdir = exec
+            .working_dir()
+            .clone()
+            .filter(|dir| !dir.is_empty())
+            .unwrap_or_else(|| "/".to_string());
+        Self {
+            config,
+            env,
+            workdir,
+            pending_dirs: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, step: &BuildStep) {
+        match step {
+            BuildStep::Env { vars } => {
+                for (key, value) in vars {
+                    let value = expand_vars(value, &self.env);
+                    self.env.retain(|(k, _)| k != key);
+                    self.env.push((key.clone(), value));
+                }
+            }
+            BuildStep::Workdir { path } => {
+                self.workdir = resolve_in(&self.workdir, &expand_vars(path, &self.env));
+                self.pending_dirs.push(self.workdir.clone());
+            }
+            BuildStep::Entrypoint { command } => {
+                self.update(|exec| {
+                    exec.set_entrypoint(Some(command.clone()));
+                    exec.set_cmd(None);
+                });
+            }
+            BuildStep::Cmd { command } => self.update(|exec| {
+                exec.set_cmd(Some(command.clone()));
+            }),
+            BuildStep::Run { .. } | BuildStep::Copy { .. } => {}
+        }
+    }
+
+    fn update(&mut self, f: impl FnOnce(&mut Config)) {
+        let mut exec = self.config.config().clone().unwrap_or_default();
+        f(&mut exec);
+        self.config.set_config(Some(exec));
+    }
+
+    /// The image config with the current environment and working directory.
+    fn image_config(&self) -> ImageConfiguration {
+        let mut state = self.clone();
+        state.update(|exec| {
+            exec.set_env(Some(
+                self.env.iter().map(|(k, v)| format!("{k}={v}")).collect(),
+            ));
+            exec.set_working_dir(Some(self.workdir.clone()));
+        });
+        let mut config = state.config;
+        config.set_created(Some(chrono::Utc::now().to_rfc3339()));
+        config
+    }
+}
+
+/// A build in progress.
+struct Build<'a> {
+    runtime: &'a BoxliteRuntime,
+    store: BuiltImageStore,
+    observer: BuildObserver,
+    base_image: String,
+    state: ImageState,
+    /// Last committed image; `None` while the build is still at the base.
+    image: Option<BuiltManifest>,
+    /// Box the steps run in, created at the first step not in the cache.
+    builder: Option<LiteBox>,
+    /// Cache key of the steps so far.
+    key: String,
+}
+
+impl Build<'_> {
+    /// Run the steps; returns the image digest and the number of cached
+    /// steps.
+    async fn run(
+        &mut self,
+        spec: &BuildSpec,
+        context: &Path,
+        copy_hashes: &[Option<String>],
+        total: usize,
+    ) -> BoxliteResult<(String, usize)> {
+        let mut cached_steps = 0;
+        for (i, step) in spec.steps.iter().enumerate() {
+            let step_json = serde_json::to_string(step)
+                .map_err(|e| BoxliteError::Internal(format!("failed to encode step: {e}")))?;
+            let copy_hash = copy_hashes[i].as_deref().unwrap_or_default();
+            self.key = chain_key(&self.key, &format!("{step_json}{copy_hash}"));
+
+            if !step.changes_filesystem() {
+                self.report_step(i + 2, total, step, false);
+                self.state.apply(step);
+                continue;
+            }
+
+            // Once a step has run, the box is ahead of anything in the cache
+            if !spec.no_cache
+                && self.builder.is_none()
+                && let Some(hit) = self.store.cached_step(&self.key)?
+            {
+                self.report_step(i + 2, total, step, true);
+                self.state.pending_dirs.clear();
+                self.image = Some(hit);
+                cached_steps += 1;
+                continue;
+            }
+
+            self.report_step(i + 2, total, step, false);
+            self.prepare_builder().await?;
+            match step {
+                BuildStep::Run { command } => self.run_step(i + 2, total, command).await?,
+                BuildStep::Copy { sources, dest } => self.copy_step(context, sources, dest).await?,
+                _ => unreachable!("only filesystem steps get here"),
+            }
+            let image = self.commit().await?;
+            self.store.record_step(&self.key, &image.manifest_digest)?;
+            self.image = Some(image);
+        }
+
+        // The final image needs a layer of its own, with every WORKDIR in it
+        if self.image.is_none() || !self.state.pending_dirs.is_empty() {
+            self.prepare_builder().await?;
+            self.image = Some(self.commit().await?);
+        }
+        let layer = &self.image.as_ref().expect("committed above").layer_digest;
+        let digest = self.store.write_image(layer, self.state.image_config())?;
+        Ok((digest, cached_steps))
+    }
+
+    fn report_step(&self, index: usize, total: usize, step: &BuildStep, cached: bool) {
+        (self.observer)(&BuildEvent::Step {
+            index,
+            total,
+            step: step.to_string(),
+            cached,
+        });
+    }
+
+    /// Create the build box from the last committed image if there is none,
+    /// then create pending `WORKDIR`s.
+    async fn prepare_builder(&mut self) -> BoxliteResult<()> {
+        if self.builder.is_none() {
+            let rootfs = match &self.image {
+                Some(image) => RootfsSpec::RootfsPath(
+                    self.store
+                        .bundle_path(&image.manifest_digest)
+                        .to_string_lossy()
+                        .into_owned(),
+                ),
+                None => RootfsSpec::Image(self.base_image.clone()),
+            };
+            let options = BoxOptions {
+                rootfs,
+                auto_remove: true,
+                detach: false,
+                ..Default::default()
+            };
+            let litebox = self.runtime.create(options, None).await?;
+            self.builder = Some(litebox);
+            self.builder().start().await?;
+        }
+
+        if !self.state.pending_dirs.is_empty() {
+            let mut mkdir = vec!["mkdir".to_string(), "-p".to_string()];
+            mkdir.append(&mut self.state.pending_dirs);
+            self.run_internal(mkdir).await?;
+        }
+        Ok(())
+    }
+
+    fn builder(&self) -> &LiteBox {
+        self.builder.as_ref().expect("build box is created first")
+    }
+
+    async fn run_step(&self, index: usize, total: usize, argv: &[String]) -> BoxliteResult<()> {
+        let (program, args) = argv.split_first().ok_or_else(|| {
+            BoxliteError::InvalidArgument(format!("step {index}/{total}: RUN has no command"))
+        })?;
+        let mut command = BoxCommand::new(program)
+            .args(args)
+            .working_dir(&self.state.workdir);
+        for (key, value) in &self.state.env {
+            command = command.env(key, value);
+        }
+
+        let mut execution = self.builder().exec(command).await?;
+        tokio::join!(
+            forward_output(execution.stdout(), &self.observer, false),
+            forward_output(execution.stderr(), &self.observer, true),
+        );
+
+        let result = execution.wait().await?;
+        if result.exit_code != 0 {
+            return Err(BoxliteError::Execution(format!(
+                "step {index}/{total} `RUN {}` failed with exit code {}",
+                command_form(argv),
+                result.exit_code
+            )));
+        }
+        Ok(())
+    }
+
+    async fn copy_step(&self, context: &Path, sources: &[String], dest: &str) -> BoxliteResult<()> {
+        let dest = resolve_in(&self.state.workdir, &expand_vars(dest, &self.state.env));
+        self.run_internal(vec!["mkdir".into(), "-p".into(), dest.clone()])
+            .await?;
+        for source in sources {
+            let path = resolve_source(context, source)?;
+            let opts = CopyOptions::default().include_parent(false);
+            self.builder().copy_into(&path, &dest, opts).await?;
+        }
+        Ok(())
+    }
+
+    /// Flatten the box's filesystem into an image with the current config.
+    async fn commit(&self) -> BoxliteResult<BuiltManifest> {
+        (self.observer)(&BuildEvent::Committing);
+        let exclude = |path: &str| format!("--exclude=.{path}");
+        self.run_internal(vec![
+            "tar".into(),
+            "-cf".into(),
+            SNAPSHOT_TAR.into(),
+            exclude("/proc"),
+            exclude("/sys"),
+            exclude("/dev"),
+            exclude(SNAPSHOT_TAR),
+            "-C".into(),
+            "/".into(),
+            ".".into(),
+        ])
+        .await?;
+
+        let staged = self
+            .store
+            .staging_dir()?
+            .join(format!("layer-{}.tar", uuid::Uuid::new_v4().simple()));
+        let copied = self
+            .builder()
+            .copy_out(SNAPSHOT_TAR, &staged, CopyOptions::default())
+            .await;
+        let layer = copied.and_then(|()| self.store.add_layer(&staged));
+        let _ = std::fs::remove_file(&staged);
+        self.run_internal(vec!["rm".into(), "-f".into(), SNAPSHOT_TAR.into()])
+            .await?;
+
+        let digest = self.store.write_image(&layer?, self.state.image_config())?;
+        self.store.manifest(&digest)
+    }
+
+    /// Run a command the build needs, failing with its output if it fails.
+    async fn run_internal(&self, argv: Vec<String>) -> BoxliteResult<()> {
+        let (program, args) = argv.split_first().expect("internal commands are not empty");
+        let command = BoxCommand::new(program).args(args);
+        let mut execution = self.builder().exec(command).await?;
+        let (_, stderr) = tokio::join!(
+            collect_stream(execution.stdout(), INTERNAL_OUTPUT_BYTES),
+            collect_stream(execution.stderr(), INTERNAL_OUTPUT_BYTES),
+        );
+        let result = execution.wait().await?;
+        if result.exit_code != 0 {
+            return Err(BoxliteError::Execution(format!(
+                "build: `{}` failed with exit code {}: {}",
+                argv.join(" "),
+                result.exit_code,
+                stderr.text.trim()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn remove_builder(&mut self) {
+        if let Some(litebox) = self.builder.take() {
+            self.runtime.cleanup_build_box(&litebox).await;
+        }
+    }
+}
+
+/// Report each chunk of `stream` as `BuildEvent::Output`.
+async fn forward_output<S>(stream: Option<S>, observer: &BuildObserver, stderr: bool)
+where
+    S: futures::Stream<Item = String> + Unpin,
+{
+    if let Some(mut stream) = stream {
+        while let Some(text) = stream.next().await {
+            observer(&BuildEvent::Output { text, stderr });
+        }
+    }
+}
+
+/// Lines of a Containerfile with continuations joined and comments and
+/// blank lines dropped, with the number of the line each starts on.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') || (trimmed.is_empty() && current.is_none()) {
+            continue;
+        }
+        let (start, mut joined) = current.take().unwrap_or((i + 1, String::new()));
+        match trimmed.strip_suffix('\\') {
+            Some(head) => {
+                joined.push_str(head);
+                joined.push(' ');
+                current = Some((start, joined));
+            }
+            None => {
+                joined.push_str(trimmed);
+                lines.push((start, joined.trim().to_string()));
+            }
+        }
+    }
+    if let Some((start, joined)) = current {
+        lines.push((start, joined.trim().to_string()));
+    }
+    lines
+}
+
+/// A JSON array (exec form) as is, anything else in `/bin/sh -c`.
+fn parse_command(rest: &str) -> Vec<String> {
+    if rest.starts_with('[')
+        && let Ok(argv) = serde_json::from_str::<Vec<String>>(rest)
+    {
+        return argv;
+    }
+    shell_form(rest.to_string())
+}
+
+fn shell_form(command: String) -> Vec<String> {
+    vec!["/bin/sh".to_string(), "-c".to_string(), command]
+}
+
+/// `command` as it would be written in a Containerfile.
+fn command_form(command: &[String]) -> String {
+    match command {
+        [sh, c, script] if sh == "/bin/sh" && c == "-c" => script.clone(),
+        _ => serde_json::to_string(command).unwrap_or_default(),
+    }
+}
+
+/// `KEY=VALUE ...` (values may be quoted) or the legacy `KEY VALUE`.
+fn parse_env(rest: &str) -> Result<Vec<(String, String)>, String> {
+    let first = rest.split_whitespace().next().unwrap_or_default();
+    if !first.contains('=') {
+        let (key, value) = rest
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("ENV {rest} has no value"))?;
+        return Ok(vec![(key.to_string(), value.trim().to_string())]);
+    }
+    split_words(rest)?
+        .into_iter()
+        .map(|word| {
+            word.split_once('=')
+                .filter(|(key, _)| !key.is_empty())
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .ok_or_else(|| format!("ENV expects KEY=VALUE, got '{word}'"))
+        })
+        .collect()
+}
+
+/// Split on whitespace outside quotes, removing the quotes and the
+/// backslashes that escape a character.
+fn split_words(text: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (c, quote) {
+            ('\\', q) if q != Some('\'') => {
+                if let Some(next) = chars.next() {
+                    word.get_or_insert_with(String::new).push(next);
+                }
+            }
+            (q @ ('"' | '\''), None) => {
+                quote = Some(q);
+                word.get_or_insert_with(String::new);
+            }
+            (c, Some(q)) if c == q => quote = None,
+            (c, None) if c.is_whitespace() => words.extend(word.take()),
+            (c, _) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("unterminated quote in '{text}'"));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Replace `$VAR` and `${VAR}` with their values in `env` (empty if unset).
+/// `\$` is a literal `$`.
+fn expand_vars(value: &str, env: &[(String, String)]) -> String {
+    let lookup = |name: &str| {
+        env.iter()
+            .rev()
+            .find(|(k, _)| k == name)
+            .map_or("", |(_, v)| v.as_str())
+    };
+    let mut out = String::new();
+    let mut rest = value;
+    while let Some(pos) = rest.find(['$', '\\']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+        if let Some(after) = tail.strip_prefix("\\$") {
+            out.push('$');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix('\\') {
+            out.push('\\');
+            rest = after;
+        } else if let Some(braced) = tail.strip_prefix("${")
+            && let Some(end) = braced.find('}')
+        {
+            out.push_str(lookup(&braced[..end]));
+            rest = &braced[end + 1..];
+        } else {
+            let name_len = tail[1..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(tail.len() - 1);
+            if name_len == 0 {
+                out.push('$');
+            } else {
+                out.push_str(lookup(&tail[1..=name_len]));
+            }
+            rest = &tail[1 + name_len..];
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `path` resolved against the directory `base` in the box, without `.`
+/// and `..` components.
+fn resolve_in(base: &str, path: &str) -> String {
+    let joined = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("{}/{}", base, path)
+    };
+    let mut parts: Vec<&str> = Vec::new();
+    for part in joined.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+/// The host path of a `COPY` source, which must be inside `context`.
+/// Leading slashes are relative to the context, as in docker.
+fn resolve_source(context: &Path, source: &str) -> BoxliteResult<PathBuf> {
+    let joined = context.join(source.trim_start_matches('/'));
+    let path = std::fs::canonicalize(&joined)
+        .map_err(|e| BoxliteError::InvalidArgument(format!("COPY source '{source}': {e}")))?;
+    if !path.starts_with(context) {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "COPY source '{source}' is outside the build context"
+        )));
+    }
+    Ok(path)
+}
+
+/// Digest of the names, modes and contents of the `COPY` sources.
+fn hash_sources(context: &Path, sources: &[String]) -> BoxliteResult<String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut hasher = Sha256::new();
+    for source in sources {
+        let root = resolve_source(context, source)?;
+        for entry in walkdir::WalkDir::new(&root).sort_by_file_name() {
+            let entry = entry.map_err(|e| {
+                BoxliteError::InvalidArgument(format!("COPY source '{source}': {e}"))
+            })?;
+            let path = entry.path();
+            let read_err =
+                |e: std::io::Error| BoxliteError::Storage(format!("{}: {}", path.display(), e));
+            let meta = entry.metadata().map_err(|e| read_err(e.into()))?;
+            let relative = path.strip_prefix(context).unwrap_or(path);
+            hasher.update(relative.to_string_lossy().as_bytes());
+            hasher.update(meta.permissions().mode().to_le_bytes());
+            if meta.is_symlink() {
+                hasher.update(
+                    std::fs::read_link(path)
+                        .map_err(read_err)?
+                        .to_string_lossy()
+                        .as_bytes(),
+                );
+            } else if meta.is_file() {
+                let mut file = std::fs::File::open(path).map_err(read_err)?;
+                std::io::copy(&mut file, &mut hasher).map_err(read_err)?;
+            }
+            hasher.update([0]);
+        }
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Cache key of a step after the steps keyed by `parent`.
+fn chain_key(parent: &str, step: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(parent.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(step.as_bytes());
+    format!("sha256:{}", hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> BoxliteResult<BuildSpec> {
+        BuildSpec::from_containerfile(text, ".")
+    }
+
+    #[test]
+    fn test_parse_containerfile() {
+        let spec = parse(
+            "# tools\n\
+             FROM alpine:3.20\n\
+             \n\
+             run apk add --no-cache \\\n    curl \\\n    jq\n\
+             ENV APP_HOME=/app MODE=\"dev build\"\n\
+             WORKDIR $APP_HOME\n\
+             COPY src conf.toml ./\n\
+             ENTRYPOINT [\"/app/run\"]\n\
+             CMD serve --verbose\n",
+        )
+        .unwrap();
+        assert_eq!(spec.base_image, "alpine:3.20");
+        assert_eq!(
+            spec.steps,
+            [
+                BuildStep::run("apk add --no-cache  curl  jq"),
+                BuildStep::Env {
+                    vars: vec![
+                        ("APP_HOME".into(), "/app".into()),
+                        ("MODE".into(), "dev build".into()),
+                    ],
+                },
+                BuildStep::workdir("$APP_HOME"),
+                BuildStep::Copy {
+                    sources: vec!["src".into(), "conf.toml".into()],
+                    dest: "./".into(),
+                },
+                BuildStep::Entrypoint {
+                    command: vec!["/app/run".into()],
+                },
+                BuildStep::Cmd {
+                    command: shell_form("serve --verbose".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_instructions_are_listed_upfront() {
+        let err = parse(
+            "FROM alpine\nRUN true\nADD x.tgz /\nUSER app\nCOPY --chown=app a b\nFROM scratch\n",
+        )
+        .unwrap_err();
+        let BoxliteError::InvalidArgument(msg) = err else {
+            panic!("unexpected error: {err}");
+        };
+        for expected in [
+            "line 3: ADD",
+            "line 4: USER",
+            "line 5: COPY --chown=app",
+            "line 6: FROM after the first line",
+            "Supported instructions: FROM, RUN, COPY, ENV, WORKDIR, ENTRYPOINT, CMD",
+        ] {
+            assert!(msg.contains(expected), "{msg}");
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for text in [
+            "RUN true\n",
+            "",
+            "FROM alpine AS build\n",
+            "FROM alpine\nCOPY onlyone\n",
+            "FROM alpine\nENV KEY\n",
+            "FROM alpine\nENV A=\"open\n",
+        ] {
+            assert!(
+                matches!(parse(text), Err(BoxliteError::InvalidArgument(_))),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_step_display_round_trips() {
+        let text = "FROM alpine\nRUN echo hi\nENV A=1 B=\"x y\"\nCMD [\"sh\",\"-c\",\"exit 1\"]\n";
+        let spec = parse(text).unwrap();
+        let lines: Vec<String> = spec.steps.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "RUN echo hi",
+                "ENV A=1 B=\"x y\"",
+                "CMD [\"sh\",\"-c\",\"exit 1\"]"
+            ]
+        );
+        let reparsed = parse(&format!("FROM alpine\n{}\n", lines.join("\n"))).unwrap();
+        assert_eq!(reparsed.steps, spec.steps);
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        let env = vec![
+            ("PATH".to_string(), "/bin".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+        ];
+        assert_eq!(expand_vars("/opt/bin:$PATH", &env), "/opt/bin:/bin");
+        assert_eq!(expand_vars("${HOME}/app", &env), "/root/app");
+        assert_eq!(expand_vars("$UNSET-x", &env), "-x");
+        assert_eq!(expand_vars("\\$HOME costs $", &env), "$HOME costs $");
+    }
+
+    #[test]
+    fn test_image_state_applies_steps() {
+        let mut state = ImageState::from_config(ImageConfiguration::default());
+        assert_eq!(state.workdir, "/");
+        state.apply(&BuildStep::env("BASE", "/srv"));
+        state.apply(&BuildStep::workdir("$BASE/app"));
+        state.apply(&BuildStep::workdir("data"));
+        state.apply(&BuildStep::Cmd {
+            command: vec!["serve".into()],
+        });
+        state.apply(&BuildStep::Entrypoint {
+            command: vec!["/entry".into()],
+        });
+        assert_eq!(state.workdir, "/srv/app/data");
+        assert_eq!(state.pending_dirs, ["/srv/app", "/srv/app/data"]);
+
+        let config = state.image_config();
+        let exec = config.config().as_ref().unwrap();
+        assert_eq!(exec.env().as_deref(), Some(&["BASE=/srv".to_string()][..]));
+        assert_eq!(exec.working_dir().as_deref(), Some("/srv/app/data"));
+        assert_eq!(
+            exec.entrypoint().as_deref(),
+            Some(&["/entry".to_string()][..])
+        );
+        // ENTRYPOINT resets the CMD set before it
+        assert_eq!(exec.cmd(), &None);
+    }
+
+    #[test]
+    fn test_resolve_in() {
+        assert_eq!(resolve_in("/app", "."), "/app");
+        assert_eq!(resolve_in("/app", "./data/"), "/app/data");
+        assert_eq!(resolve_in("/app", "../etc"), "/etc");
+        assert_eq!(resolve_in("/app", "/srv"), "/srv");
+        assert_eq!(resolve_in("/", "app"), "/app");
+    }
+
+    #[test]
+    fn test_copy_sources_stay_in_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = dir.path().join("ctx");
+        std::fs::create_dir_all(context.join("src")).unwrap();
+        std::fs::write(dir.path().join("secret"), "x").unwrap();
+        let context = std::fs::canonicalize(&context).unwrap();
+
+        assert!(resolve_source(&context, "src").is_ok());
+        assert!(resolve_source(&context, "/src").is_ok());
+        for source in ["../secret", "missing"] {
+            assert!(
+                matches!(
+                    resolve_source(&context, source),
+                    Err(BoxliteError::InvalidArgument(_))
+                ),
+                "{source}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_copy_hash_follows_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let context = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir(context.join("src")).unwrap();
+        std::fs::write(context.join("src/a.txt"), "one").unwrap();
+        let sources = vec!["src".to_string()];
+
+        let first = hash_sources(&context, &sources).unwrap();
+        assert_eq!(hash_sources(&context, &sources).unwrap(), first);
+        std::fs::write(context.join("src/a.txt"), "two").unwrap();
+        assert_ne!(hash_sources(&context, &sources).unwrap(), first);
+    }
+
+    #[test]
+    fn test_chain_key_depends_on_every_step() {
+        let base = chain_key("", "FROM sha256:abc");
+        let a = chain_key(&chain_key(&base, "RUN a"), "RUN b");
+        assert_eq!(a, chain_key(&chain_key(&base, "RUN a"), "RUN b"));
+        assert_ne!(a, chain_key(&chain_key(&base, "RUN x"), "RUN b"));
+        assert_ne!(a, chain_key(&base, "RUN b"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Bulk operations over the boxes matching a [`ListFilter`].
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures::StreamExt;
+
+use crate::litebox::{BoxCommand, ExecResult, LiteBox};
+use crate::runtime::core::BoxliteRuntime;
+use crate::runtime::run_once::{DEFAULT_MAX_OUTPUT_BYTES, collect_stream};
+use crate::runtime::types::{BoxID, BoxInfo, BoxStatus, ListFilter, RemovePlan};
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Boxes a bulk operation works on at once.
+pub const BULK_CONCURRENCY: usize = 8;
+
+/// Per-box results of a bulk operation, in list order (newest box first).
+pub type BulkResults<T> = Vec<(BoxID, BoxliteResult<T>)>;
+
+/// Options for [`BoxliteRuntime::stop_matching`].
+#[derive(Debug, Clone, Default)]
+pub struct StopOptions {
+    /// Report a box that hasn't stopped within this long as a `Timeout`
+    /// failure (default: wait). Its stop carries on in the background.
+    pub timeout: Option<Duration>,
+}
+
+impl StopOptions {
+    /// Set the per-box timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Outcome of [`BoxliteRuntime::exec_matching`] in one box.
+#[derive(Debug, Clone)]
+pub struct BulkExecResult {
+    /// Exit status of the command.
+    pub result: ExecResult,
+    /// Captured stdout (up to 1 MiB).
+    pub stdout: String,
+    /// Captured stderr (up to 1 MiB).
+    pub stderr: String,
+    /// Whether stdout exceeded the capture limit.
+    pub stdout_truncated: bool,
+    /// Whether stderr exceeded the capture limit.
+    pub stderr_truncated: bool,
+}
+
+impl BoxliteRuntime {
+    /// Run `op` on every box matching `filter`, [`BULK_CONCURRENCY`] at a time.
+    ///
+    /// A failing box doesn't abort the batch: its error is returned in its
+    /// slot. Only failing to list the boxes fails the call. Idle warm pool
+    /// boxes are never matched.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use boxlite::{BoxliteRuntime, ListFilter};
+    ///
+    /// # async fn example(runtime: BoxliteRuntime) -> boxlite::BoxliteResult<()> {
+    /// let filter = ListFilter::new().label("tenant", "acme");
+    /// let rt = &runtime;
+    /// let results = runtime
+    ///     .for_each_matching(filter, |info| async move {
+    ///         match rt.get(info.id.as_str()).await? {
+    ///             Some(litebox) => litebox.metrics().await.map(Some),
+    ///             None => Ok(None),
+    ///         }
+    ///     })
+    ///     .await?;
+    /// for (id, metrics) in results {
+    ///     println!("{}: {:?}", id, metrics.map(|m| m.map(|m| m.cpu_percent())));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn for_each_matching<F, Fut, T>(
+        &self,
+        filter: ListFilter,
+        op: F,
+    ) -> BoxliteResult<BulkResults<T>>
+    where
+        F: Fn(BoxInfo) -> Fut,
+        Fut: Future<Output = BoxliteResult<T>>,
+    {
+        let matching: Vec<BoxInfo> = self
+            .list_info()
+            .await?
+            .into_iter()
+            .filter(|info| filter.matches(info))
+            .collect();
+
+        let mut results: Vec<(usize, BoxID, BoxliteResult<T>)> =
+            futures::stream::iter(matching.into_iter().enumerate())
+                .map(|(index, info)| {
+                    let id = info.id.clone();
+                    let fut = op(info);
+                    async move { (index, id, fut.await) }
+                })
+                .buffer_unordered(BULK_CONCURRENCY)
+                .collect()
+                .await;
+        results.sort_by_key(|(index, _, _)| *index);
+        Ok(results
+            .into_iter()
+            .map(|(_, id, result)| (id, result))
+            .collect())
+    }
+
+    /// Stop every box matching `filter`.
+    ///
+    /// Stopping an already stopped box succeeds. Boxes with `auto_remove`
+    /// are removed by their stop, as with [`LiteBox::stop`].
+    pub async fn stop_matching(
+        &self,
+        filter: ListFilter,
+        options: StopOptions,
+    ) -> BoxliteResult<BulkResults<()>> {
+        let timeout = options.timeout;
+        self.for_each_matching(filter, |info| async move {
+            self.stop_one(&info.id, timeout).await
+        })
+        .await
+    }
+
+    /// Remove every box matching `filter` (see [`remove`](Self::remove)).
+    pub async fn remove_matching(
+        &self,
+        filter: ListFilter,
+        force: bool,
+    ) -> BoxliteResult<BulkResults<()>> {
+        self.for_each_matching(filter, |info| async move {
+            self.remove(info.id.as_str(), force).await
+        })
+        .await
+    }
+
+    /// Remove every exited box matching `filter`, returning what each
+    /// removal did.
+    ///
+    /// Running boxes and boxes that never started are kept; the filter's
+    /// status is ignored. Boxes go to the trash as with
+    /// [`remove`](Self::remove).
+    pub async fn prune(&self, filter: ListFilter) -> BoxliteResult<BulkResults<RemovePlan>> {
+        self.for_each_matching(prunable(filter), |info| async move {
+            let plan = self.plan_remove(info.id.as_str(), false).await?;
+            self.remove(info.id.as_str(), false).await?;
+            Ok(plan)
+        })
+        .await
+    }
+
+    /// Describe what [`prune`](Self::prune) would do, without changing anything.
+    pub async fn plan_prune(&self, filter: ListFilter) -> BoxliteResult<BulkResults<RemovePlan>> {
+        self.for_each_matching(prunable(filter), |info| async move {
+            self.plan_remove(info.id.as_str(), false).await
+        })
+        .await
+    }
+
+    /// Run `command` in every box matching `filter` and collect its output.
+    ///
+    /// Like [`LiteBox::exec`], this starts stopped boxes; add
+    /// `.status(BoxStatus::Running)` to the filter to skip them. A command
+    /// exiting nonzero is a success here: check `result.exit_code`.
+    pub async fn exec_matching(
+        &self,
+        filter: ListFilter,
+        command: BoxCommand,
+    ) -> BoxliteResult<BulkResults<BulkExecResult>> {
+        let command = &command;
+        self.for_each_matching(filter, |info| async move {
+            self.exec_collect(&info.id, command.clone()).await
+        })
+        .await
+    }
+
+    async fn stop_one(&self, id: &BoxID, timeout: Option<Duration>) -> BoxliteResult<()> {
+        let litebox = self.handle(id).await?;
+        let Some(timeout) = timeout else {
+            return litebox.stop().await;
+        };
+        // Stop in a task so a timeout doesn't cancel it halfway
+        let stop = tokio::spawn(async move { litebox.stop().await });
+        match tokio::time::timeout(timeout, stop).await {
+            Ok(joined) => joined.map_err(|e| {
+                BoxliteError::Internal(format!("stop task of box {} failed: {}", id, e))
+            })?,
+            Err(_) => Err(BoxliteError::Timeout(format!(
+                "box {} did not stop within {:?}",
+                id, timeout
+            ))),
+        }
+    }
+
+    async fn exec_collect(&self, id: &BoxID, command: BoxCommand) -> BoxliteResult<BulkExecResult> {
+        let litebox = self.handle(id).await?;
+        let mut execution = litebox.exec(command).await?;
+        let (stdout, stderr) = tokio::join!(
+            collect_stream(execution.stdout(), DEFAULT_MAX_OUTPUT_BYTES),
+            collect_stream(execution.stderr(), DEFAULT_MAX_OUTPUT_BYTES),
+        );
+        Ok(BulkExecResult {
+            result: execution.wait().await?,
+            stdout_truncated: stdout.truncated,
+            stderr_truncated: stderr.truncated,
+            stdout: stdout.text,
+            stderr: stderr.text,
+        })
+    }
+
+    /// Handle of a listed box, which may have been removed since.
+    async fn handle(&self, id: &BoxID) -> BoxliteResult<LiteBox> {
+        self.get(id.as_str())
+            .await?
+            .ok_or_else(|| BoxliteError::NotFound(id.to_string()))
+    }
+}
+
+/// `filter` narrowed to the boxes `prune` removes.
+fn prunable(filter: ListFilter) -> ListFilter {
+    filter.status(BoxStatus::Exited)
+}
@@ -0,0 +1,598 @@
+//! Host capabilities boxes depend on, probed once per runtime.
+//!
+//! [`RuntimeCapabilities`] is computed by `BoxliteRuntime::new`. Box
+//! creation checks it against the box's [`SecurityOptions`]: a missing
+//! capability either fails the create up front with `Unsupported`, or runs
+//! the box with weaker isolation and records a [`Degradation`] on it.
+//!
+//! | Missing            | Effect on create                                           |
+//! |--------------------|------------------------------------------------------------|
+//! | `kvm`              | `Unsupported` (libkrun has no software emulation fallback) |
+//! | `vsock`            | `Unsupported` (the host cannot reach the guest agent)      |
+//! | `userns`           | jailer runs the shim without namespaces, or `Unsupported` with `require_sandbox` |
+//! | `seccomp`          | seccomp filter skipped, or `Unsupported` with `require_sandbox` |
+//! | `cgroup_delegation`| cgroup limits (memory, CPU, pids, disk I/O) not enforced   |
+//!
+//! `BOXLITE_DISABLE_CAPABILITIES` (comma-separated capability names, or
+//! `all`) forces capabilities off, to exercise degraded hosts.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::runtime::advanced_options::SecurityOptions;
+use crate::runtime::constants::envs;
+use crate::runtime::layout::FilesystemLayout;
+
+/// A host capability boxes depend on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Hardware virtualization: KVM on Linux, Hypervisor.framework on macOS.
+    Kvm,
+    /// Unprivileged user namespaces, for the jailer's bwrap sandbox (Linux).
+    Userns,
+    /// A writable cgroup v2 subtree with the cpu, memory and pids controllers.
+    CgroupDelegation,
+    /// Seccomp syscall filtering (Linux).
+    Seccomp,
+    /// The host end of the guest vsock channel: Unix sockets under the
+    /// BoxLite home, with paths short enough to bind.
+    Vsock,
+}
+
+impl Capability {
+    /// Every capability, in probe order.
+    pub const ALL: [Capability; 5] = [
+        Capability::Kvm,
+        Capability::Userns,
+        Capability::CgroupDelegation,
+        Capability::Seccomp,
+        Capability::Vsock,
+    ];
+
+    /// Name used in `BOXLITE_DISABLE_CAPABILITIES` and reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Capability::Kvm => "kvm",
+            Capability::Userns => "userns",
+            Capability::CgroupDelegation => "cgroup_delegation",
+            Capability::Seccomp => "seccomp",
+            Capability::Vsock => "vsock",
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Capability {
+    type Err = BoxliteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Capability::ALL
+            .into_iter()
+            .find(|c| c.as_str() == s)
+            .ok_or_else(|| BoxliteError::Config(format!("unknown capability: {}", s)))
+    }
+}
+
+/// Whether one capability is available, and why not.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapabilityStatus {
+    pub available: bool,
+    /// What was found, e.g. the reason the capability is missing.
+    pub detail: String,
+}
+
+impl CapabilityStatus {
+    fn available(detail: impl Into<String>) -> Self {
+        Self {
+            available: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn missing(detail: impl Into<String>) -> Self {
+        Self {
+            available: false,
+            detail: detail.into(),
+        }
+    }
+
+    fn from_reason(reason: Option<String>, available: &str) -> Self {
+        match reason {
+            Some(reason) => Self::missing(reason),
+            None => Self::available(available),
+        }
+    }
+}
+
+/// Capabilities of the host a runtime runs on.
+///
+/// Probed once when the runtime is created; see
+/// `BoxliteRuntime::capabilities()`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeCapabilities {
+    pub kvm: CapabilityStatus,
+    pub userns: CapabilityStatus,
+    pub cgroup_delegation: CapabilityStatus,
+    pub seccomp: CapabilityStatus,
+    pub vsock: CapabilityStatus,
+}
+
+/// Isolation a box runs without because the host lacks a capability.
+///
+/// Recorded when the box is created and reported by `BoxInfo::degradations`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Degradation {
+    /// The missing capability.
+    pub capability: Capability,
+    /// What the box runs without.
+    pub effect: String,
+}
+
+impl fmt::Display for Degradation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} unavailable: {}", self.capability, self.effect)
+    }
+}
+
+impl RuntimeCapabilities {
+    /// Probe the host, then turn off capabilities named in
+    /// `BOXLITE_DISABLE_CAPABILITIES`.
+    pub(crate) fn probe(layout: &FilesystemLayout) -> BoxliteResult<Self> {
+        let disabled = std::env::var(envs::BOXLITE_DISABLE_CAPABILITIES).unwrap_or_default();
+        let disabled = parse_disabled(&disabled)?;
+        let mut capabilities = Self {
+            kvm: probe_kvm(),
+            userns: probe_userns(),
+            cgroup_delegation: probe_cgroup_delegation(),
+            seccomp: probe_seccomp(),
+            vsock: probe_vsock(layout),
+        };
+        for capability in disabled {
+            *capabilities.status_mut(capability) = CapabilityStatus::missing(format!(
+                "disabled by {}",
+                envs::BOXLITE_DISABLE_CAPABILITIES
+            ));
+        }
+        Ok(capabilities)
+    }
+
+    /// Status of one capability.
+    pub fn get(&self, capability: Capability) -> &CapabilityStatus {
+        match capability {
+            Capability::Kvm => &self.kvm,
+            Capability::Userns => &self.userns,
+            Capability::CgroupDelegation => &self.cgroup_delegation,
+            Capability::Seccomp => &self.seccomp,
+            Capability::Vsock => &self.vsock,
+        }
+    }
+
+    /// Every capability with its status.
+    pub fn iter(&self) -> impl Iterator<Item = (Capability, &CapabilityStatus)> {
+        Capability::ALL.into_iter().map(|c| (c, self.get(c)))
+    }
+
+    fn status_mut(&mut self, capability: Capability) -> &mut CapabilityStatus {
+        match capability {
+            Capability::Kvm => &mut self.kvm,
+            Capability::Userns => &mut self.userns,
+            Capability::CgroupDelegation => &mut self.cgroup_delegation,
+            Capability::Seccomp => &mut self.seccomp,
+            Capability::Vsock => &mut self.vsock,
+        }
+    }
+
+    /// Check a box about to be created against the host.
+    ///
+    /// Fails with `Unsupported` if the box cannot run here. Otherwise turns
+    /// off the parts of `security` the host cannot provide and returns what
+    /// the box runs without.
+    pub(crate) fn admit(&self, security: &mut SecurityOptions) -> BoxliteResult<Vec<Degradation>> {
+        if !self.kvm.available {
+            return Err(BoxliteError::Unsupported(format!(
+                "cannot create box: hardware virtualization is unavailable ({}). \
+                 The VMM has no software emulation (TCG) fallback.",
+                self.kvm.detail
+            )));
+        }
+        if !self.vsock.available {
+            return Err(BoxliteError::Unsupported(format!(
+                "cannot create box: the guest channel is unavailable ({})",
+                self.vsock.detail
+            )));
+        }
+
+        let mut degradations = Vec::new();
+        // Only the jailer's cgroup holds box disks to the limits; macOS
+        // throttles the shim's disk I/O instead, and a Linux box without
+        // the jailer gets neither
+        if security.resource_limits.has_disk_io_limits() {
+            if cfg!(target_os = "macos") {
+                degradations.push(Degradation {
+                    capability: Capability::CgroupDelegation,
+                    effect: "disk I/O limits only throttle the box's disk I/O behind other \
+                             processes', without holding it to the set rates"
+                        .to_string(),
+                });
+            } else if !cfg!(target_os = "linux") || !security.jailer_enabled {
+                degradations.push(Degradation {
+                    capability: Capability::CgroupDelegation,
+                    effect: "disk I/O limits are not enforced".to_string(),
+                });
+            }
+        }
+        if !security.jailer_enabled || !cfg!(target_os = "linux") {
+            return Ok(degradations);
+        }
+
+        if security.namespaces_enabled && !self.userns.available {
+            self.require_sandbox(security, Capability::Userns)?;
+            security.namespaces_enabled = false;
+            degradations.push(Degradation {
+                capability: Capability::Userns,
+                effect: "the shim runs without the namespace sandbox (bwrap)".to_string(),
+            });
+        }
+        if security.seccomp_enabled && !self.seccomp.available {
+            self.require_sandbox(security, Capability::Seccomp)?;
+            security.seccomp_enabled = false;
+            degradations.push(Degradation {
+                capability: Capability::Seccomp,
+                effect: "the shim runs without a seccomp filter".to_string(),
+            });
+        }
+        if needs_cgroup(security) && !self.cgroup_delegation.available {
+            degradations.push(Degradation {
+                capability: Capability::CgroupDelegation,
+                effect: "memory, CPU, process and disk I/O limits are not enforced".to_string(),
+            });
+        }
+        Ok(degradations)
+    }
+
+    fn require_sandbox(
+        &self,
+        security: &SecurityOptions,
+        capability: Capability,
+    ) -> BoxliteResult<()> {
+        if !security.require_sandbox {
+            return Ok(());
+        }
+        Err(BoxliteError::Unsupported(format!(
+            "cannot create box: SecurityOptions.require_sandbox is set but {} is unavailable ({})",
+            capability,
+            self.get(capability).detail
+        )))
+    }
+}
+
+/// Whether the jailer would put limits for `security` in a cgroup.
+fn needs_cgroup(security: &SecurityOptions) -> bool {
+    let limits = &security.resource_limits;
+    limits.max_memory.is_some()
+        || limits.max_cpu_time.is_some()
+        || limits.max_processes.is_some()
+        || limits.has_disk_io_limits()
+}
+
+/// Parse a `BOXLITE_DISABLE_CAPABILITIES` value.
+fn parse_disabled(value: &str) -> BoxliteResult<HashSet<Capability>> {
+    let mut disabled = HashSet::new();
+    for name in value.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+        if name == "all" {
+            disabled.extend(Capability::ALL);
+        } else {
+            disabled.insert(name.parse().map_err(|_| {
+                BoxliteError::Config(format!(
+                    "{}: unknown capability '{}' (expected {} or all)",
+                    envs::BOXLITE_DISABLE_CAPABILITIES,
+                    name,
+                    Capability::ALL.map(|c| c.as_str()).join(", ")
+                ))
+            })?);
+        }
+    }
+    Ok(disabled)
+}
+
+fn probe_kvm() -> CapabilityStatus {
+    match crate::vmm::host_check::check_virtualization_support() {
+        Ok(support) => CapabilityStatus::available(support.reason),
+        Err(e) => CapabilityStatus::missing(e.to_string()),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn probe_userns() -> CapabilityStatus {
+    match crate::jailer::bwrap::can_create_user_namespace() {
+        Ok(()) => CapabilityStatus::available("bwrap can create user namespaces"),
+        Err(diagnostic) => CapabilityStatus::missing(diagnostic),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_userns() -> CapabilityStatus {
+    CapabilityStatus::missing("user namespaces are Linux-only; not needed on this host")
+}
+
+#[cfg(target_os = "linux")]
+fn probe_cgroup_delegation() -> CapabilityStatus {
+    CapabilityStatus::from_reason(
+        crate::jailer::cgroup::delegation_unavailable_reason(),
+        "cpu, memory and pids controllers are delegated",
+    )
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_cgroup_delegation() -> CapabilityStatus {
+    CapabilityStatus::missing("cgroups are Linux-only")
+}
+
+#[cfg(target_os = "linux")]
+fn probe_seccomp() -> CapabilityStatus {
+    // SAFETY: PR_GET_SECCOMP only reads the calling thread's seccomp mode
+    let mode = unsafe { libc::prctl(libc::PR_GET_SECCOMP) };
+    if mode < 0 {
+        CapabilityStatus::missing(format!(
+            "the kernel does not support seccomp: {}",
+            std::io::Error::last_os_error()
+        ))
+    } else {
+        CapabilityStatus::available("the kernel supports seccomp")
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn probe_seccomp() -> CapabilityStatus {
+    CapabilityStatus::missing("seccomp is Linux-only")
+}
+
+/// Longest Unix socket path the platform accepts (`sun_path` less the NUL).
+#[cfg(target_os = "linux")]
+const MAX_SOCKET_PATH: usize = 107;
+#[cfg(not(target_os = "linux"))]
+const MAX_SOCKET_PATH: usize = 103;
+
+fn probe_vsock(layout: &FilesystemLayout) -> CapabilityStatus {
+    // A box ID is 26 characters; ready.sock is the longest socket name
+    let longest = layout
+        .boxes_dir()
+        .join("0".repeat(26))
+        .join(crate::runtime::layout::dirs::SOCKETS_DIR)
+        .join("ready.sock");
+    let len = longest.as_os_str().len();
+    if len > MAX_SOCKET_PATH {
+        return CapabilityStatus::missing(format!(
+            "box socket paths under {} are {} bytes, over the {} byte limit; \
+             use a shorter home directory",
+            layout.home_dir().display(),
+            len,
+            MAX_SOCKET_PATH
+        ));
+    }
+    CapabilityStatus::from_reason(
+        bind_probe_socket(&layout.temp_dir()).err(),
+        "Unix sockets can be bound under the home directory",
+    )
+}
+
+fn bind_probe_socket(dir: &Path) -> Result<(), String> {
+    let path = dir.join(format!("vsock-probe-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let result = std::os::unix::net::UnixListener::bind(&path)
+        .map(drop)
+        .map_err(|e| format!("cannot bind a Unix socket in {}: {}", dir.display(), e));
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::advanced_options::ResourceLimits;
+    use crate::runtime::layout::FsLayoutConfig;
+
+    fn all_available() -> RuntimeCapabilities {
+        let ok = || CapabilityStatus::available("ok");
+        RuntimeCapabilities {
+            kvm: ok(),
+            userns: ok(),
+            cgroup_delegation: ok(),
+            seccomp: ok(),
+            vsock: ok(),
+        }
+    }
+
+    /// Capabilities with those named like `BOXLITE_DISABLE_CAPABILITIES` off.
+    fn with_disabled(value: &str) -> RuntimeCapabilities {
+        let mut capabilities = all_available();
+        for capability in parse_disabled(value).unwrap() {
+            *capabilities.status_mut(capability) = CapabilityStatus::missing("disabled");
+        }
+        capabilities
+    }
+
+    fn jailed() -> SecurityOptions {
+        SecurityOptions {
+            jailer_enabled: true,
+            seccomp_enabled: true,
+            resource_limits: ResourceLimits {
+                max_processes: Some(100),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_disabled() {
+        assert!(parse_disabled("").unwrap().is_empty());
+        let disabled = parse_disabled("kvm, cgroup_delegation").unwrap();
+        assert_eq!(
+            disabled,
+            HashSet::from([Capability::Kvm, Capability::CgroupDelegation])
+        );
+        assert_eq!(parse_disabled("all").unwrap().len(), Capability::ALL.len());
+
+        let err = parse_disabled("kvm,tpm").unwrap_err().to_string();
+        assert!(err.contains("unknown capability 'tpm'"), "{err}");
+    }
+
+    #[test]
+    fn test_no_kvm_fails_create() {
+        let err = with_disabled("kvm")
+            .admit(&mut SecurityOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, BoxliteError::Unsupported(_)), "{err:?}");
+        assert!(err.to_string().contains("TCG"), "{err}");
+    }
+
+    #[test]
+    fn test_no_vsock_fails_create() {
+        let err = with_disabled("vsock")
+            .admit(&mut SecurityOptions::default())
+            .unwrap_err();
+        assert!(matches!(err, BoxliteError::Unsupported(_)), "{err:?}");
+    }
+
+    #[test]
+    fn test_all_available_changes_nothing() {
+        let mut security = jailed();
+        assert!(all_available().admit(&mut security).unwrap().is_empty());
+        assert!(security.namespaces_enabled);
+        assert!(security.seccomp_enabled);
+    }
+
+    #[test]
+    fn test_jailer_disabled_ignores_sandbox_capabilities() {
+        let mut security = SecurityOptions::default();
+        let degradations = with_disabled("userns,seccomp,cgroup_delegation")
+            .admit(&mut security)
+            .unwrap();
+        assert!(degradations.is_empty());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_no_userns_downgrades_jailer() {
+        let mut security = jailed();
+        let degradations = with_disabled("userns").admit(&mut security).unwrap();
+        assert_eq!(degradations.len(), 1);
+        assert_eq!(degradations[0].capability, Capability::Userns);
+        assert!(!security.namespaces_enabled);
+        // The rest of the jailer still applies
+        assert!(security.jailer_enabled);
+        assert!(security.seccomp_enabled);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_no_seccomp_drops_filter() {
+        let mut security = jailed();
+        let degradations = with_disabled("seccomp").admit(&mut security).unwrap();
+        assert_eq!(degradations[0].capability, Capability::Seccomp);
+        assert!(!security.seccomp_enabled);
+        assert!(security.namespaces_enabled);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_no_cgroup_delegation_only_reports() {
+        let mut security = jailed();
+        let degradations = with_disabled("cgroup_delegation")
+            .admit(&mut security)
+            .unwrap();
+        assert_eq!(degradations[0].capability, Capability::CgroupDelegation);
+
+        // Without limits that need a cgroup, nothing is lost
+        let mut security = SecurityOptions {
+            resource_limits: ResourceLimits::default(),
+            ..jailed()
+        };
+        let degradations = with_disabled("cgroup_delegation")
+            .admit(&mut security)
+            .unwrap();
+        assert!(degradations.is_empty());
+    }
+
+    #[test]
+    fn test_disk_io_limits_without_jailer_reported() {
+        let mut security = SecurityOptions {
+            resource_limits: ResourceLimits {
+                disk_read_iops: Some(100),
+                ..Default::default()
+            },
+            ..SecurityOptions::default()
+        };
+        assert!(!security.jailer_enabled);
+        let degradations = all_available().admit(&mut security).unwrap();
+        assert_eq!(degradations.len(), 1);
+        assert_eq!(degradations[0].capability, Capability::CgroupDelegation);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_disk_io_limits_with_jailer_not_degraded() {
+        let mut security = SecurityOptions {
+            resource_limits: ResourceLimits {
+                disk_read_iops: Some(100),
+                ..Default::default()
+            },
+            ..jailed()
+        };
+        assert!(all_available().admit(&mut security).unwrap().is_empty());
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    #[test]
+    fn test_disk_io_limits_reported_unenforced() {
+        let mut security = SecurityOptions {
+            resource_limits: ResourceLimits {
+                disk_write_bps: Some(1024 * 1024),
+                ..Default::default()
+            },
+            ..SecurityOptions::default()
+        };
+        let degradations = all_available().admit(&mut security).unwrap();
+        assert_eq!(degradations.len(), 1);
+        assert_eq!(degradations[0].capability, Capability::CgroupDelegation);
+        // The limits are kept for inspect and a later move to Linux
+        assert!(security.resource_limits.has_disk_io_limits());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_require_sandbox_fails_instead_of_degrading() {
+        for capability in ["userns", "seccomp"] {
+            let mut security = SecurityOptions {
+                require_sandbox: true,
+                ..jailed()
+            };
+            let err = with_disabled(capability).admit(&mut security).unwrap_err();
+            assert!(matches!(err, BoxliteError::Unsupported(_)), "{err:?}");
+            assert!(err.to_string().contains(capability), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_vsock_probe_rejects_long_home() {
+        let deep = tempfile::tempdir().unwrap();
+        let home = deep.path().join("x".repeat(MAX_SOCKET_PATH));
+        let layout = FilesystemLayout::new(home, FsLayoutConfig::without_bind_mount());
+        let status = probe_vsock(&layout);
+        assert!(!status.available);
+        assert!(status.detail.contains("byte limit"), "{}", status.detail);
+    }
+}
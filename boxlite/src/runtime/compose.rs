@@ -0,0 +1,610 @@
+//! Compositions: sets of boxes brought up and torn down together.
+//!
+//! [`BoxliteRuntime::up`] starts boxes in dependency order, waiting for each
+//! box's readiness probe before starting the boxes that depend on it. The
+//! members and their dependencies are recorded in the `box_composition`
+//! table, so [`BoxliteRuntime::down`] can stop them in reverse order from
+//! another process.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::db::CompositionStore;
+use crate::db::compositions::ComposedMember;
+use crate::litebox::{BoxCommand, LiteBox};
+use crate::runtime::core::BoxliteRuntime;
+use crate::runtime::options::BoxOptions;
+use crate::runtime::rt_impl::RuntimeImpl;
+use crate::runtime::run_once::collect_stream;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Bytes of probe output kept for the error of a probe that never passed.
+const PROBE_OUTPUT_BYTES: usize = 4 * 1024;
+
+/// A box in a composition, see [`BoxliteRuntime::up`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ComposedBox {
+    /// Box name. An existing box with this name is started, not recreated.
+    pub name: String,
+    /// Options the box is created with.
+    #[serde(default)]
+    pub options: BoxOptions,
+    /// Boxes of the same composition that must be up and ready first.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Check that must pass before dependent boxes start (default: none,
+    /// the box is ready once started).
+    #[serde(default)]
+    pub readiness: Option<ReadinessProbe>,
+}
+
+impl ComposedBox {
+    /// A box named `name` created with `options`.
+    pub fn new(name: impl Into<String>, options: BoxOptions) -> Self {
+        Self {
+            name: name.into(),
+            options,
+            depends_on: Vec::new(),
+            readiness: None,
+        }
+    }
+
+    /// Start after the box named `name`.
+    pub fn depends_on(mut self, name: impl Into<String>) -> Self {
+        self.depends_on.push(name.into());
+        self
+    }
+
+    /// Wait for `probe` to pass before starting dependent boxes.
+    pub fn readiness(mut self, probe: ReadinessProbe) -> Self {
+        self.readiness = Some(probe);
+        self
+    }
+}
+
+/// Command run in a box until it exits 0.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReadinessProbe {
+    /// Program and arguments.
+    pub command: Vec<String>,
+    /// Pause between attempts (default: 1 s).
+    #[serde(default = "default_probe_interval")]
+    pub interval: Duration,
+    /// Give up after this long, counted from the first attempt (default: 60 s).
+    #[serde(default = "default_probe_timeout")]
+    pub timeout: Duration,
+}
+
+fn default_probe_interval() -> Duration {
+    Duration::from_secs(1)
+}
+
+fn default_probe_timeout() -> Duration {
+    Duration::from_secs(60)
+}
+
+impl ReadinessProbe {
+    /// Probe running `command` (program, then arguments).
+    pub fn new<I, S>(command: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            command: command.into_iter().map(Into::into).collect(),
+            interval: default_probe_interval(),
+            timeout: default_probe_timeout(),
+        }
+    }
+
+    /// Set the pause between attempts.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Set how long to keep trying.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Options for [`BoxliteRuntime::up_with`].
+#[derive(Debug, Clone)]
+pub struct UpOptions {
+    /// When a box fails, stop the boxes this call started and remove the
+    /// ones it created, and start no more (default: true). Otherwise only
+    /// the boxes depending on the failed one are skipped.
+    pub rollback: bool,
+}
+
+impl Default for UpOptions {
+    fn default() -> Self {
+        Self { rollback: true }
+    }
+}
+
+impl UpOptions {
+    /// Set whether a failure rolls the composition back.
+    pub fn rollback(mut self, rollback: bool) -> Self {
+        self.rollback = rollback;
+        self
+    }
+}
+
+/// What [`BoxliteRuntime::up`] did with one box.
+#[derive(Debug)]
+pub enum UpOutcome {
+    /// Created and started.
+    Created,
+    /// Existed and was started.
+    Started,
+    /// Was already running; left as is.
+    Running,
+    /// Could not be created, started or made ready.
+    Failed(BoxliteError),
+    /// Not attempted: a box it depends on failed, or the composition was
+    /// rolled back first.
+    Skipped,
+    /// Started by this call, then stopped (or removed, if this call created
+    /// it) because another box failed.
+    RolledBack,
+}
+
+/// Result of [`BoxliteRuntime::up`].
+#[derive(Debug)]
+pub struct UpReport {
+    /// Every box with its outcome, in start order.
+    pub boxes: Vec<(String, UpOutcome)>,
+}
+
+impl UpReport {
+    /// Whether every box is up.
+    pub fn success(&self) -> bool {
+        self.boxes.iter().all(|(_, outcome)| {
+            matches!(
+                outcome,
+                UpOutcome::Created | UpOutcome::Started | UpOutcome::Running
+            )
+        })
+    }
+
+    /// The box that failed first, with its error.
+    pub fn failure(&self) -> Option<(&str, &BoxliteError)> {
+        self.boxes.iter().find_map(|(name, outcome)| match outcome {
+            UpOutcome::Failed(e) => Some((name.as_str(), e)),
+            _ => None,
+        })
+    }
+}
+
+/// How to undo bringing a box up.
+enum Undo {
+    Nothing,
+    Stop,
+    Remove,
+}
+
+impl BoxliteRuntime {
+    /// Bring up a set of boxes, with [`UpOptions::default`].
+    ///
+    /// See [`up_with`](Self::up_with).
+    pub async fn up(&self, boxes: Vec<ComposedBox>) -> BoxliteResult<UpReport> {
+        self.up_with(boxes, UpOptions::default()).await
+    }
+
+    /// Create and start `boxes`, each after the boxes it depends on.
+    ///
+    /// A box that already exists is started if needed, not recreated.
+    /// Boxes start one at a time; a box with a readiness probe must pass
+    /// it before the next one starts. A failure rolls back the boxes this
+    /// call brought up unless `options.rollback` is off. The report holds
+    /// each box's outcome; the call itself only fails if the composition
+    /// is invalid (duplicate names, unknown dependencies, a cycle) or
+    /// cannot be recorded.
+    ///
+    /// The members are recorded for [`down`](Self::down) before any box is
+    /// started.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use boxlite::{BoxOptions, BoxliteRuntime, ComposedBox, ReadinessProbe};
+    ///
+    /// # async fn example(runtime: BoxliteRuntime) -> boxlite::BoxliteResult<()> {
+    /// let image = |image: &str| BoxOptions::builder().image(image).build();
+    /// let report = runtime
+    ///     .up(vec![
+    ///         ComposedBox::new("db", image("postgres:16")?)
+    ///             .readiness(ReadinessProbe::new(["pg_isready"])),
+    ///         ComposedBox::new("cache", image("redis:7")?),
+    ///         ComposedBox::new("app", image("myapp:latest")?)
+    ///             .depends_on("db")
+    ///             .depends_on("cache"),
+    ///     ])
+    ///     .await?;
+    /// if let Some((name, err)) = report.failure() {
+    ///     eprintln!("{name} failed: {err}");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn up_with(
+        &self,
+        boxes: Vec<ComposedBox>,
+        options: UpOptions,
+    ) -> BoxliteResult<UpReport> {
+        let order = validate_composition(&boxes)?;
+
+        let composed_at = Utc::now();
+        let members: Vec<ComposedMember> = boxes
+            .iter()
+            .map(|b| ComposedMember {
+                name: b.name.clone(),
+                depends_on: b.depends_on.clone(),
+                composed_at,
+            })
+            .collect();
+        self.backend().record_composition(&members).await?;
+
+        let mut report = Vec::with_capacity(boxes.len());
+        let mut undo = Vec::with_capacity(boxes.len());
+        let mut not_up: HashSet<&str> = HashSet::new();
+        let mut rolling_back = false;
+
+        for index in order {
+            let spec = &boxes[index];
+            if rolling_back || spec.depends_on.iter().any(|d| not_up.contains(d.as_str())) {
+                not_up.insert(&spec.name);
+                report.push((spec.name.clone(), UpOutcome::Skipped));
+                undo.push(Undo::Nothing);
+                continue;
+            }
+
+            let (outcome, how) = self.bring_up(spec).await;
+            if let UpOutcome::Failed(e) = &outcome {
+                tracing::warn!(name = %spec.name, error = %e, "Composed box failed to come up");
+                not_up.insert(&spec.name);
+                rolling_back = options.rollback;
+            }
+            report.push((spec.name.clone(), outcome));
+            undo.push(how);
+        }
+
+        if rolling_back {
+            self.roll_back(&mut report, undo).await;
+        }
+        Ok(UpReport { boxes: report })
+    }
+
+    /// Stop the boxes named `names` and every recorded box that depends on
+    /// them, dependents first.
+    ///
+    /// Order comes from the dependencies recorded by [`up`](Self::up), so
+    /// this works from any process. Names `up` never saw are stopped too,
+    /// after their dependents. Boxes that don't exist are skipped. With
+    /// `remove`, stopped boxes are also removed and forgotten by the
+    /// composition. A failing box doesn't abort the teardown: results are
+    /// per box, in the order they were stopped.
+    pub async fn down(
+        &self,
+        names: &[String],
+        remove: bool,
+    ) -> BoxliteResult<Vec<(String, BoxliteResult<()>)>> {
+        let recorded = self.backend().list_composition().await?;
+
+        // The named boxes, then whatever depends on them
+        let mut targets: Vec<&str> = Vec::new();
+        for name in names {
+            if !targets.contains(&name.as_str()) {
+                targets.push(name);
+            }
+        }
+        let mut next = 0;
+        while next < targets.len() {
+            let target = targets[next];
+            for member in &recorded {
+                if member.depends_on.iter().any(|d| d == target)
+                    && !targets.contains(&member.name.as_str())
+                {
+                    targets.push(&member.name);
+                }
+            }
+            next += 1;
+        }
+
+        // Records from separate `up` calls can disagree; fall back to the
+        // order above if they form a cycle
+        let mut order = dependency_order(&targets, |i| {
+            recorded
+                .iter()
+                .find(|m| m.name == targets[i])
+                .map_or(&[][..], |m| m.depends_on.as_slice())
+        })
+        .unwrap_or_else(|| (0..targets.len()).collect());
+        order.reverse();
+
+        let mut results = Vec::with_capacity(order.len());
+        let mut removed = Vec::new();
+        for index in order {
+            let name = targets[index].to_string();
+            let result = self.take_down(&name, remove).await;
+            if remove && result.is_ok() {
+                removed.push(name.clone());
+            }
+            results.push((name, result));
+        }
+        if !removed.is_empty() {
+            self.backend().forget_composition(&removed).await?;
+        }
+        Ok(results)
+    }
+
+    /// Create or start one box and wait until it is ready. Returns how to
+    /// undo what was done, even on failure.
+    async fn bring_up(&self, spec: &ComposedBox) -> (UpOutcome, Undo) {
+        let (litebox, outcome, undo) = match self.get(&spec.name).await {
+            Ok(Some(litebox)) if litebox.info().status.is_running() => {
+                (litebox, UpOutcome::Running, Undo::Nothing)
+            }
+            Ok(Some(litebox)) => (litebox, UpOutcome::Started, Undo::Stop),
+            Ok(None) => match self
+                .create(spec.options.clone(), Some(spec.name.clone()))
+                .await
+            {
+                Ok(litebox) => (litebox, UpOutcome::Created, Undo::Remove),
+                Err(e) => return (UpOutcome::Failed(e), Undo::Nothing),
+            },
+            Err(e) => return (UpOutcome::Failed(e), Undo::Nothing),
+        };
+
+        let result = async {
+            litebox.start().await?;
+            if let Some(probe) = &spec.readiness {
+                wait_ready(&litebox, probe).await?;
+            }
+            Ok(())
+        }
+        .await;
+        match result {
+            Ok(()) => (outcome, undo),
+            Err(e) => (UpOutcome::Failed(e), undo),
+        }
+    }
+
+    /// Undo the boxes of `report` in reverse start order.
+    async fn roll_back(&self, report: &mut [(String, UpOutcome)], undo: Vec<Undo>) {
+        for ((name, outcome), undo) in report.iter_mut().zip(undo).rev() {
+            let result = match undo {
+                Undo::Nothing => continue,
+                Undo::Stop => match self.get(name).await {
+                    Ok(Some(litebox)) => litebox.stop().await,
+                    Ok(None) => Ok(()),
+                    Err(e) => Err(e),
+                },
+                Undo::Remove => match self.remove(name, true).await {
+                    Ok(()) | Err(BoxliteError::NotFound(_)) => Ok(()),
+                    Err(e) => Err(e),
+                },
+            };
+            match result {
+                Ok(()) => {
+                    if !matches!(outcome, UpOutcome::Failed(_)) {
+                        *outcome = UpOutcome::RolledBack;
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!(name = %name, error = %e, "Failed to roll back composed box");
+                }
+            }
+        }
+    }
+
+    /// Stop (and with `remove`, remove) one box; a missing box is fine.
+    async fn take_down(&self, name: &str, remove: bool) -> BoxliteResult<()> {
+        let Some(litebox) = self.get(name).await? else {
+            return Ok(());
+        };
+        litebox.stop().await?;
+        if remove {
+            // auto_remove boxes are already gone
+            match self.remove(name, false).await {
+                Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RuntimeImpl {
+    fn composition_store(&self) -> CompositionStore {
+        CompositionStore::new(self.box_manager.db())
+    }
+
+    pub(crate) fn record_composition(&self, members: &[ComposedMember]) -> BoxliteResult<()> {
+        self.composition_store().upsert(members)?;
+        tracing::info!(boxes = members.len(), "Recorded box composition");
+        Ok(())
+    }
+
+    pub(crate) fn list_composition(&self) -> BoxliteResult<Vec<ComposedMember>> {
+        self.composition_store().list()
+    }
+
+    pub(crate) fn forget_composition(&self, names: &[String]) -> BoxliteResult<()> {
+        self.composition_store().remove(names)
+    }
+}
+
+/// Run `probe` in `litebox` until it exits 0, failing with `Timeout` once
+/// its timeout has passed.
+async fn wait_ready(litebox: &LiteBox, probe: &ReadinessProbe) -> BoxliteResult<()> {
+    let (program, args) = probe
+        .command
+        .split_first()
+        .expect("validated by validate_composition");
+    let deadline = Instant::now() + probe.timeout;
+
+    loop {
+        let attempt = async {
+            let mut execution = litebox.exec(BoxCommand::new(program).args(args)).await?;
+            let (stdout, stderr) = tokio::join!(
+                collect_stream(execution.stdout(), PROBE_OUTPUT_BYTES),
+                collect_stream(execution.stderr(), PROBE_OUTPUT_BYTES),
+            );
+            let result = execution.wait().await?;
+            Ok::<_, BoxliteError>((result.exit_code, stdout.text, stderr.text))
+        };
+        let last = match tokio::time::timeout_at(deadline.into(), attempt).await {
+            Ok(Ok((0, _, _))) => return Ok(()),
+            Ok(Ok((code, stdout, stderr))) => {
+                let output = if stderr.trim().is_empty() {
+                    stdout
+                } else {
+                    stderr
+                };
+                format!("exited with {}: {}", code, output.trim())
+            }
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "still running".to_string(),
+        };
+
+        if Instant::now() + probe.interval >= deadline {
+            return Err(BoxliteError::Timeout(format!(
+                "box {} not ready after {:?}: readiness probe {:?} {}",
+                litebox.name().unwrap_or(litebox.id().as_str()),
+                probe.timeout,
+                probe.command.join(" "),
+                last
+            )));
+        }
+        tokio::time::sleep(probe.interval).await;
+    }
+}
+
+/// Check a composition and return its start order.
+fn validate_composition(boxes: &[ComposedBox]) -> BoxliteResult<Vec<usize>> {
+    let mut names = HashSet::new();
+    for b in boxes {
+        if b.name.is_empty() {
+            return Err(BoxliteError::InvalidArgument(
+                "composed boxes need a name".to_string(),
+            ));
+        }
+        if !names.insert(b.name.as_str()) {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "box {} appears twice in the composition",
+                b.name
+            )));
+        }
+        if b.readiness.as_ref().is_some_and(|p| p.command.is_empty()) {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "readiness probe of box {} has no command",
+                b.name
+            )));
+        }
+    }
+    for b in boxes {
+        if let Some(missing) = b.depends_on.iter().find(|d| !names.contains(d.as_str())) {
+            return Err(BoxliteError::InvalidArgument(format!(
+                "box {} depends on {}, which is not in the composition",
+                b.name, missing
+            )));
+        }
+    }
+
+    let names: Vec<&str> = boxes.iter().map(|b| b.name.as_str()).collect();
+    dependency_order(&names, |i| &boxes[i].depends_on).ok_or_else(|| {
+        BoxliteError::InvalidArgument("the composition's dependencies form a cycle".to_string())
+    })
+}
+
+/// Indices of `names`, each after the names it depends on, otherwise in the
+/// given order. Dependencies outside `names` are ignored. `None` on a cycle.
+fn dependency_order<'a>(
+    names: &[&str],
+    depends_on: impl Fn(usize) -> &'a [String],
+) -> Option<Vec<usize>> {
+    let mut order: Vec<usize> = Vec::with_capacity(names.len());
+    let mut placed = vec![false; names.len()];
+    while order.len() < names.len() {
+        let ready = (0..names.len()).find(|&i| {
+            !placed[i]
+                && depends_on(i).iter().all(|dep| {
+                    names
+                        .iter()
+                        .position(|n| n == dep)
+                        .is_none_or(|j| placed[j])
+                })
+        })?;
+        placed[ready] = true;
+        order.push(ready);
+    }
+    Some(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn composed(name: &str, depends_on: &[&str]) -> ComposedBox {
+        let mut b = ComposedBox::new(name, BoxOptions::default());
+        b.depends_on = depends_on.iter().map(|d| d.to_string()).collect();
+        b
+    }
+
+    fn names(boxes: &[ComposedBox], order: &[usize]) -> Vec<String> {
+        order.iter().map(|&i| boxes[i].name.clone()).collect()
+    }
+
+    #[test]
+    fn test_start_order_follows_dependencies() {
+        let boxes = vec![
+            composed("app", &["db", "cache"]),
+            composed("cache", &[]),
+            composed("db", &[]),
+            composed("worker", &["db"]),
+        ];
+        let order = validate_composition(&boxes).unwrap();
+        assert_eq!(names(&boxes, &order), ["cache", "db", "app", "worker"]);
+    }
+
+    #[test]
+    fn test_invalid_compositions() {
+        for boxes in [
+            vec![composed("db", &[]), composed("db", &[])],
+            vec![composed("app", &["db"])],
+            vec![composed("a", &["b"]), composed("b", &["a"])],
+            vec![composed("a", &["a"])],
+            vec![composed("", &[])],
+            vec![composed("db", &[]).readiness(ReadinessProbe::new(Vec::<String>::new()))],
+        ] {
+            let err = validate_composition(&boxes).unwrap_err();
+            assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_dependency_order_ignores_outside_names() {
+        let deps = [vec!["db".to_string()], vec!["elsewhere".to_string()]];
+        let order = dependency_order(&["app", "db"], |i| &deps[i]).unwrap();
+        assert_eq!(order, [1, 0]);
+    }
+
+    #[test]
+    fn test_composed_box_defaults() {
+        let b: ComposedBox = serde_json::from_str(
+            r#"{"name": "db", "depends_on": ["net"], "readiness": {"command": ["pg_isready"]}}"#,
+        )
+        .unwrap();
+        assert_eq!(b.depends_on, ["net"]);
+        let probe = b.readiness.unwrap();
+        assert_eq!(probe.interval, Duration::from_secs(1));
+        assert_eq!(probe.timeout, Duration::from_secs(60));
+    }
+}
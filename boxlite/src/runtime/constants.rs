@@ -18,6 +18,10 @@ pub mod guest_paths {
 pub mod envs {
     pub const BOXLITE_HOME: &str = "BOXLITE_HOME";
 
+    /// Host capabilities to treat as missing, comma-separated (`kvm`,
+    /// `userns`, `cgroup_delegation`, `seccomp`, `vsock`) or `all`.
+    pub const BOXLITE_DISABLE_CAPABILITIES: &str = "BOXLITE_DISABLE_CAPABILITIES";
+
     /// REST API base URL (required for REST mode).
     #[cfg(feature = "rest")]
     pub const BOXLITE_REST_URL: &str = "BOXLITE_REST_URL";
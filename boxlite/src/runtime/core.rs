@@ -1,15 +1,31 @@
 //! High-level sandbox runtime structures.
 
+use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
 
+use crate::audit::{AuditSink, AuditedRuntime, JsonlAuditSink};
+use crate::db::templates::BoxTemplate;
+use crate::db::trash::TrashedBox;
 use crate::litebox::LiteBox;
-use crate::metrics::RuntimeMetrics;
+use crate::metrics::{RuntimeMetrics, RuntimeMetricsSnapshot};
+use crate::policy::{CreatePolicy, PolicyRuntime};
 use crate::runtime::backend::RuntimeBackend;
+use crate::runtime::bulk::BulkResults;
+use crate::runtime::capabilities::RuntimeCapabilities;
+use crate::runtime::create_progress::CreateEvent;
+use crate::runtime::drift::{GetOrCreateOutcome, GetOrCreatePolicy};
 use crate::runtime::options::{BoxOptions, BoxliteOptions};
+use crate::runtime::orphans::OrphanShim;
+use crate::runtime::portability::{ImportOptions, ImportPlan};
 use crate::runtime::rt_impl::{LocalRuntime, RuntimeImpl};
 use crate::runtime::signal_handler::install_signal_handler;
-use crate::runtime::types::BoxInfo;
+use crate::runtime::templates::{BoxOptionsPatch, template_not_found};
+use crate::runtime::types::{BoxInfo, ListOptions, RemovePlan};
+#[cfg(feature = "url-transfer")]
+use crate::runtime::url_transfer::TransferOptions;
+use crate::runtime::version::VersionInfo;
+use crate::runtime::warm_pool::WarmSelector;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 // ============================================================================
 // GLOBAL DEFAULT RUNTIME
@@ -71,14 +87,93 @@ impl BoxliteRuntime {
     /// - Another `BoxliteRuntime` is already using the same home directory
     /// - Filesystem initialization fails
     /// - Image API initialization fails
+    /// - The audit directory cannot be created (when `options.audit` is set)
+    /// - boxlite was built without the `local` feature (use `BoxliteRuntime::rest()`)
     pub fn new(options: BoxliteOptions) -> BoxliteResult<Self> {
+        if !cfg!(feature = "local") {
+            return Err(BoxliteError::Unsupported(
+                "Local runtime not compiled in (boxlite built without the `local` feature)"
+                    .to_string(),
+            ));
+        }
+
+        let audit_dir = options
+            .audit
+            .then(|| crate::audit::audit_dir(&options.home_dir));
+
         let local = LocalRuntime(RuntimeImpl::new(options)?);
         let backend_arc = Arc::new(local);
         let image_manager =
             Arc::clone(&backend_arc) as Arc<dyn crate::runtime::images::ImageManager>;
-        Ok(Self {
+        let runtime = Self {
             backend: backend_arc,
             image_manager: Some(image_manager),
+        };
+
+        match audit_dir {
+            Some(dir) => Ok(runtime.with_audit_sink(Arc::new(JsonlAuditSink::new(dir)?))),
+            None => Ok(runtime),
+        }
+    }
+
+    /// Record mutating operations of this runtime and its boxes to `sink`.
+    ///
+    /// Works for any backend; REST runtimes also attach the remote peer to
+    /// each event. Boxes obtained before this call are not audited.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use boxlite::audit::JsonlAuditSink;
+    /// use boxlite::runtime::BoxliteRuntime;
+    ///
+    /// let sink = JsonlAuditSink::new("/var/log/boxlite")?;
+    /// let runtime = BoxliteRuntime::with_defaults()?.with_audit_sink(Arc::new(sink));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_audit_sink(self, sink: Arc<dyn AuditSink>) -> Self {
+        Self {
+            backend: Arc::new(AuditedRuntime::new(self.backend, sink)),
+            image_manager: self.image_manager,
+        }
+    }
+
+    /// Evaluate `policy` before every box this runtime creates.
+    ///
+    /// Requests the policy refuses fail with `BoxliteError::PolicyDenied`.
+    /// Attach the policy before `with_audit_sink()` for denials to show up
+    /// in the audit log.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Unsupported` for REST runtimes, which cannot resolve images
+    /// locally; the server enforces its own policy.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use boxlite::policy::{Severity, SeverityPolicy};
+    /// use boxlite::runtime::BoxliteRuntime;
+    ///
+    /// let policy = SeverityPolicy::new(Severity::High);
+    /// let runtime = BoxliteRuntime::with_defaults()?.with_create_policy(Arc::new(policy))?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn with_create_policy(self, policy: Arc<dyn CreatePolicy>) -> BoxliteResult<Self> {
+        let Some(image_manager) = self.image_manager else {
+            return Err(BoxliteError::Unsupported(
+                "Create policies not supported over REST API".to_string(),
+            ));
+        };
+        Ok(Self {
+            backend: Arc::new(PolicyRuntime::new(
+                self.backend,
+                Arc::clone(&image_manager),
+                policy,
+            )),
+            image_manager: Some(image_manager),
         })
     }
 
@@ -260,11 +355,47 @@ impl BoxliteRuntime {
         self.backend.create(options, name).await
     }
 
+    /// Create a box, reporting each creation phase to `observer`.
+    ///
+    /// Unlike [`create`](Self::create), this resolves and pulls the image
+    /// and builds the shared image and guest disks before returning, so the
+    /// box's first start does not pull or build anything. Each phase is
+    /// reported as a [`CreateEvent`] as it begins, ending with
+    /// [`CreatePhase::Done`](crate::CreatePhase::Done). Remote runtimes
+    /// stream the server's events.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use boxlite::{BoxliteRuntime, BoxOptions, CreatePhase};
+    /// # async fn example(runtime: &BoxliteRuntime) -> boxlite::BoxliteResult<()> {
+    /// let litebox = runtime
+    ///     .create_with_observer(BoxOptions::default(), None, |event| {
+    ///         if let CreatePhase::PullingLayer { digest, done, total } = &event.phase {
+    ///             println!("{digest}: {done}/{total}");
+    ///         }
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_with_observer(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: impl Fn(&CreateEvent) + Send + Sync + 'static,
+    ) -> BoxliteResult<LiteBox> {
+        self.backend
+            .create_with_observer(options, name, Arc::new(observer))
+            .await
+    }
+
     /// Get an existing box by name, or create a new one if it doesn't exist.
     ///
     /// Returns `(LiteBox, true)` if a new box was created, or `(LiteBox, false)`
     /// if an existing box with the given name was found. When an existing box is
-    /// returned, the provided `options` are ignored (no config drift validation).
+    /// returned, the provided `options` are ignored; see
+    /// [`get_or_create_with`](Self::get_or_create_with) to check them.
     pub async fn get_or_create(
         &self,
         options: BoxOptions,
@@ -273,6 +404,45 @@ impl BoxliteRuntime {
         self.backend.get_or_create(options, name).await
     }
 
+    /// Get an existing box by name or create it, deciding with `policy` what
+    /// to do if the existing box was created with other options.
+    ///
+    /// Options are compared after normalizing equivalent values (env order,
+    /// duplicate env keys, port order); labels, `redact_env` and
+    /// `snapshot_retention` don't count. With `RecreateOnMismatch` the old
+    /// box is force-removed, bypassing the trash, and the outcome lists what
+    /// differed; with `FailOnMismatch` the call fails with
+    /// `BoxliteError::ConfigDrift`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use boxlite::{BoxOptions, BoxliteRuntime, GetOrCreateOutcome, GetOrCreatePolicy};
+    ///
+    /// # async fn example(runtime: BoxliteRuntime) -> boxlite::BoxliteResult<()> {
+    /// let options = BoxOptions::builder().image("node:22").build()?;
+    /// let (_litebox, outcome) = runtime
+    ///     .get_or_create_with(
+    ///         options,
+    ///         Some("ci-runner".into()),
+    ///         GetOrCreatePolicy::RecreateOnMismatch,
+    ///     )
+    ///     .await?;
+    /// if let GetOrCreateOutcome::Recreated { changed } = outcome {
+    ///     println!("recreated, changed: {}", changed.join(", "));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_or_create_with(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        policy: GetOrCreatePolicy,
+    ) -> BoxliteResult<(LiteBox, GetOrCreateOutcome)> {
+        self.backend.get_or_create_with(options, name, policy).await
+    }
+
     /// Get a handle to an existing box by ID or name.
     ///
     /// The `id_or_name` parameter can be either:
@@ -288,10 +458,44 @@ impl BoxliteRuntime {
     }
 
     /// List all boxes, sorted by creation time (newest first).
+    ///
+    /// Idle warm pool boxes are left out; see [`list_info_with`](Self::list_info_with).
     pub async fn list_info(&self) -> BoxliteResult<Vec<BoxInfo>> {
         self.backend.list_info().await
     }
 
+    /// List boxes with filtering options, sorted by creation time (newest first).
+    pub async fn list_info_with(&self, options: ListOptions) -> BoxliteResult<Vec<BoxInfo>> {
+        self.backend.list_info_with(options).await
+    }
+
+    /// Take a started box from a warm pool (`BoxliteOptions::warm_pool`).
+    ///
+    /// Returns an idle pool box when there is one and starts a new box
+    /// otherwise; either way the pool is backfilled in the background. The
+    /// box belongs to the caller from then on and never returns to the pool.
+    ///
+    /// # Errors
+    ///
+    /// - `NotFound` if no pool has the selected name
+    /// - `Unsupported` for backends without warm pools (REST)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(runtime: boxlite::BoxliteRuntime) -> boxlite::BoxliteResult<()> {
+    /// use boxlite::WarmSelector;
+    ///
+    /// let sandbox = runtime
+    ///     .acquire_warm(WarmSelector::new("python").env("JOB_ID", "42"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn acquire_warm(&self, selector: WarmSelector) -> BoxliteResult<LiteBox> {
+        self.backend.acquire_warm(selector).await
+    }
+
     /// Check if a box with the given ID or name exists.
     pub async fn exists(&self, id_or_name: &str) -> BoxliteResult<bool> {
         self.backend.exists(id_or_name).await
@@ -302,11 +506,287 @@ impl BoxliteRuntime {
         self.backend.metrics().await
     }
 
-    /// Remove a box completely by ID or name.
+    /// Get an immutable point-in-time copy of runtime-wide metrics.
+    ///
+    /// Diff two snapshots with [`RuntimeMetricsSnapshot::diff`] to measure
+    /// activity between them.
+    pub async fn metrics_snapshot(&self) -> BoxliteResult<RuntimeMetricsSnapshot> {
+        Ok(self.backend.metrics().await?.snapshot())
+    }
+
+    /// Get versions of boxlite and the engine components it runs on
+    /// (libkrun, guest and shim binaries, bwrap, gvproxy).
+    ///
+    /// For a REST runtime these describe the server, not the client.
+    pub async fn version_info(&self) -> BoxliteResult<VersionInfo> {
+        self.backend.version_info().await
+    }
+
+    /// Get the host capabilities probed when the runtime started, and
+    /// which of them are missing.
+    ///
+    /// Boxes created while a capability is missing either fail (KVM, vsock)
+    /// or run with less isolation, recorded in [`BoxInfo::degradations`].
+    pub async fn capabilities(&self) -> BoxliteResult<RuntimeCapabilities> {
+        self.backend.capabilities().await
+    }
+
+    /// Remove a box by ID or name.
+    ///
+    /// With `BoxliteOptions::trash_retention` set, the box is moved to the
+    /// trash and can be brought back with [`restore_trashed`](Self::restore_trashed)
+    /// until the retention expires. Otherwise it is deleted immediately.
     pub async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
         self.backend.remove(id_or_name, force).await
     }
 
+    /// Remove a box by ID or name and delete its disks immediately, bypassing the trash.
+    pub async fn remove_permanently(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        self.backend.remove_permanently(id_or_name, force).await
+    }
+
+    /// Describe what [`remove`](Self::remove) would do, without changing anything.
+    ///
+    /// Fails the same way `remove` would (unknown box, active box without
+    /// `force`). Only supported by the local backend.
+    pub async fn plan_remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<RemovePlan> {
+        self.backend.plan_remove(id_or_name, force, false).await
+    }
+
+    /// Describe what [`remove_permanently`](Self::remove_permanently) would do,
+    /// without changing anything.
+    pub async fn plan_remove_permanently(
+        &self,
+        id_or_name: &str,
+        force: bool,
+    ) -> BoxliteResult<RemovePlan> {
+        self.backend.plan_remove(id_or_name, force, true).await
+    }
+
+    // ========================================================================
+    // TRASH OPERATIONS
+    // ========================================================================
+
+    /// List boxes in the trash, most recently removed first.
+    ///
+    /// Returns `BoxliteError::Unsupported` on a REST runtime.
+    pub async fn list_trashed(&self) -> BoxliteResult<Vec<TrashedBox>> {
+        self.backend.list_trashed().await
+    }
+
+    /// Restore a trashed box by ID, ID prefix or name.
+    ///
+    /// The box comes back with its original ID, name, disks and snapshots, in
+    /// the state it was removed in (force-removed boxes come back stopped).
+    /// Fails with `AlreadyExists` if another box has taken its name meanwhile.
+    pub async fn restore_trashed(&self, id_or_name: &str) -> BoxliteResult<LiteBox> {
+        self.backend.restore_trashed(id_or_name).await
+    }
+
+    /// Permanently delete a trashed box by ID, ID prefix or name.
+    pub async fn purge_trashed(&self, id_or_name: &str) -> BoxliteResult<()> {
+        self.backend.purge_trashed(id_or_name).await
+    }
+
+    // ========================================================================
+    // IMPORT
+    // ========================================================================
+
+    /// Import a box named `name` from a `.boxsnap` or `.boxlite` archive.
+    ///
+    /// Creates a new box with a new ID from the archived disk images. The
+    /// imported box is stopped and can be started normally. Fails with
+    /// `AlreadyExists` if the name is taken; see
+    /// [`import_with`](Self::import_with) for the other conflict modes.
+    pub async fn import(&self, archive_path: &Path, name: &str) -> BoxliteResult<LiteBox> {
+        let options = ImportOptions {
+            name: Some(name.to_string()),
+            ..Default::default()
+        };
+        self.import_with(archive_path, options).await
+    }
+
+    /// Import a box from an archive with explicit [`ImportOptions`].
+    ///
+    /// With `ImportConflict::Overwrite` the existing box of that name is
+    /// removed only once the imported box is stored, so a failed import
+    /// leaves it untouched; it fails with `InvalidState` if that box is
+    /// running. With `ImportConflict::Suffix` the box takes the first free
+    /// name of `{name}-1`, `{name}-2`, ..., readable from the returned
+    /// handle. Returns `BoxliteError::Unsupported` on a REST runtime.
+    pub async fn import_with(
+        &self,
+        archive_path: &Path,
+        options: ImportOptions,
+    ) -> BoxliteResult<LiteBox> {
+        self.backend.import(archive_path, options).await
+    }
+
+    /// Describe what [`import_with`](Self::import_with) would do, without
+    /// extracting the archive or touching any box.
+    ///
+    /// Fails the same way the import would on a taken name, a running box to
+    /// overwrite, a missing archive or too little free disk space. The
+    /// archive's contents are not checked. Returns `BoxliteError::Unsupported`
+    /// on a REST runtime.
+    pub async fn plan_import(
+        &self,
+        archive_path: &Path,
+        options: &ImportOptions,
+    ) -> BoxliteResult<ImportPlan> {
+        self.backend.plan_import(archive_path, options).await
+    }
+
+    /// Import a box from an archive at an `https://`, `http://` or, with
+    /// the `s3` feature, `s3://` URL.
+    ///
+    /// The archive is downloaded to the runtime's temp directory first; a
+    /// dropped connection is resumed where the server supports ranges, and
+    /// a download an earlier call left unfinished is picked up where it
+    /// stopped. With [`TransferOptions::expected_digest`] set, the archive
+    /// must match it before anything is imported. Conflicts are resolved as
+    /// in [`import_with`](Self::import_with), checked before the download
+    /// as well. Fails with `OfflineMode` on an offline runtime and with
+    /// `Unsupported` on a REST runtime.
+    #[cfg(feature = "url-transfer")]
+    pub async fn import_from_url(
+        &self,
+        url: &str,
+        options: ImportOptions,
+        transfer: TransferOptions,
+    ) -> BoxliteResult<LiteBox> {
+        self.backend.import_from_url(url, options, transfer).await
+    }
+
+    // ========================================================================
+    // ORPHANED SHIMS
+    // ========================================================================
+
+    /// List shim processes of this runtime's home whose box is gone: not
+    /// recorded, or recorded with its home directory deleted.
+    ///
+    /// Shims of runtimes with another home are never reported. Returns
+    /// `BoxliteError::Unsupported` on a REST runtime.
+    pub async fn list_orphans(&self) -> BoxliteResult<Vec<OrphanShim>> {
+        self.backend.list_orphans().await
+    }
+
+    /// Kill the shims [`list_orphans`](Self::list_orphans) reports and
+    /// remove their sockets.
+    ///
+    /// Each shim gets SIGTERM, then SIGKILL if it is still running after
+    /// `grace`. Returns the shims that were killed.
+    pub async fn kill_orphans(&self, grace: std::time::Duration) -> BoxliteResult<Vec<OrphanShim>> {
+        self.backend.kill_orphans(grace).await
+    }
+
+    // ========================================================================
+    // BOX OWNERSHIP
+    // ========================================================================
+
+    /// Detach every running box this process owns, so they keep running
+    /// after it exits (see [`LiteBox::detach`]).
+    ///
+    /// Use it before exiting to upgrade the embedding process: a runtime
+    /// opened on the same home afterwards recovers the boxes as running,
+    /// with their executions undisturbed, and takes them back with
+    /// [`adopt_all`](Self::adopt_all). Idle warm pool boxes are left to
+    /// `shutdown`. A box that cannot be detached, such as one with
+    /// `auto_remove`, stays owned and reports its error in its slot.
+    /// Returns `BoxliteError::Unsupported` on a REST runtime.
+    pub async fn detach_all(&self) -> BoxliteResult<BulkResults<()>> {
+        self.backend.detach_all().await
+    }
+
+    /// Adopt every running box started with `detach = false` that no live
+    /// process owns, the reverse of [`detach_all`](Self::detach_all) (see
+    /// [`LiteBox::adopt`]).
+    ///
+    /// Boxes started with `detach = true` are left detached. Returns
+    /// `BoxliteError::Unsupported` on a REST runtime.
+    pub async fn adopt_all(&self) -> BoxliteResult<BulkResults<()>> {
+        self.backend.adopt_all().await
+    }
+
+    // ========================================================================
+    // TEMPLATE OPERATIONS
+    // ========================================================================
+
+    /// Store `options` as a template that boxes can be created from.
+    ///
+    /// The options are validated now, so creating from the template can only
+    /// fail on the overrides passed to
+    /// [`create_from_template`](Self::create_from_template). Fails with
+    /// `AlreadyExists` if the name is taken. Returns
+    /// `BoxliteError::Unsupported` on a REST runtime.
+    pub async fn register_template(
+        &self,
+        name: &str,
+        options: BoxOptions,
+    ) -> BoxliteResult<BoxTemplate> {
+        self.backend.register_template(name, options).await
+    }
+
+    /// Get a template by name.
+    pub async fn get_template(&self, name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        self.backend.get_template(name).await
+    }
+
+    /// List templates, sorted by name.
+    pub async fn list_templates(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        self.backend.list_templates().await
+    }
+
+    /// Remove a template. Boxes created from it are unaffected.
+    pub async fn remove_template(&self, name: &str) -> BoxliteResult<()> {
+        self.backend.remove_template(name).await
+    }
+
+    /// Options [`create_from_template`](Self::create_from_template) would
+    /// create a box with: the template's, with `overrides` applied.
+    pub async fn template_options(
+        &self,
+        template_name: &str,
+        overrides: &BoxOptionsPatch,
+    ) -> BoxliteResult<BoxOptions> {
+        let template = self
+            .get_template(template_name)
+            .await?
+            .ok_or_else(|| template_not_found(template_name))?;
+        let options = overrides.apply(template.options);
+        options.sanitize()?;
+        Ok(options)
+    }
+
+    /// Create a box from a template, with `overrides` applied to its options.
+    ///
+    /// Environment variables and labels in `overrides` are merged into the
+    /// template's by key; other fields replace the template's values. See
+    /// [`BoxOptionsPatch`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use boxlite::{BoxliteRuntime, BoxOptionsPatch};
+    /// # async fn example(runtime: &BoxliteRuntime) -> boxlite::BoxliteResult<()> {
+    /// let mut overrides = BoxOptionsPatch::default();
+    /// overrides.env("TENANT", "acme").label("tenant", "acme");
+    /// let litebox = runtime
+    ///     .create_from_template("web", overrides, Some("web-acme".to_string()))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn create_from_template(
+        &self,
+        template_name: &str,
+        overrides: BoxOptionsPatch,
+        name: Option<String>,
+    ) -> BoxliteResult<LiteBox> {
+        let options = self.template_options(template_name, &overrides).await?;
+        self.create(options, name).await
+    }
+
     // ========================================================================
     // SHUTDOWN OPERATIONS
     // ========================================================================
@@ -314,7 +794,9 @@ impl BoxliteRuntime {
     /// Gracefully shutdown all boxes in this runtime.
     ///
     /// This method stops all running boxes, waiting up to `timeout` seconds
-    /// for each box to stop gracefully before force-killing it.
+    /// for each box to stop gracefully before force-killing it. A box gets
+    /// its `shutdown_grace_period`, shortened when needed so the force kill
+    /// still fits within `timeout`.
     ///
     /// After calling this method, the runtime is permanently shut down and
     /// will return errors for any new operations (like `create()`).
@@ -351,6 +833,27 @@ impl BoxliteRuntime {
         self.backend.shutdown(timeout).await
     }
 
+    /// Backend for runtime operations implemented in sibling modules.
+    pub(super) fn backend(&self) -> &dyn RuntimeBackend {
+        self.backend.as_ref()
+    }
+
+    /// Store of built images, for `build_image` (local runtimes only).
+    pub(super) fn built_images(&self) -> BoxliteResult<crate::images::built::BuiltImageStore> {
+        match &self.image_manager {
+            Some(manager) => Ok(manager.built_images()),
+            None => Err(BoxliteError::Unsupported(
+                "Image builds not supported over REST API".to_string(),
+            )),
+        }
+    }
+
+    /// Token cancelled when this runtime shuts down (None for REST runtimes).
+    #[cfg(feature = "rest-server")]
+    pub(crate) fn shutdown_token(&self) -> Option<tokio_util::sync::CancellationToken> {
+        self.backend.shutdown_token()
+    }
+
     // ========================================================================
     // IMAGE OPERATIONS (via ImageHandle)
     // ========================================================================
@@ -393,6 +896,43 @@ impl BoxliteRuntime {
             )),
         }
     }
+
+    /// Health of the image registries, for diagnosing slow or failing pulls.
+    ///
+    /// Lists `BoxliteOptions::image_registries` in order, then every other
+    /// registry contacted since the runtime was created. A registry that
+    /// failed several requests in a row is skipped by pulls until its
+    /// [`cooldown_until`](crate::RegistryStatus::cooldown_until) passes.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BoxliteError::Unsupported` for REST runtimes.
+    pub fn registry_status(&self) -> BoxliteResult<Vec<crate::RegistryStatus>> {
+        match &self.image_manager {
+            Some(manager) => Ok(manager.registry_status()),
+            None => Err(BoxliteError::Unsupported(
+                "Registry status not available over REST API".to_string(),
+            )),
+        }
+    }
+
+    /// Disk usage of the image disk and guest rootfs caches.
+    ///
+    /// With `BoxliteOptions::image_cache_max_bytes` or
+    /// `rootfs_cache_max_bytes` set, also reports the cap and what was
+    /// evicted to honor it since the runtime was created.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BoxliteError::Unsupported` for REST runtimes.
+    pub fn cache_usage(&self) -> BoxliteResult<crate::CacheUsage> {
+        match &self.image_manager {
+            Some(manager) => manager.cache_usage(),
+            None => Err(BoxliteError::Unsupported(
+                "Cache usage not available over REST API".to_string(),
+            )),
+        }
+    }
 }
 
 // ============================================================================
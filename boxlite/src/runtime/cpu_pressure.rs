@@ -0,0 +1,301 @@
+//! Throttling of low priority boxes while the host is under CPU pressure.
+//!
+//! Enabled by `BoxliteOptions::cpu_pressure`. A background thread samples
+//! the host's CPU pressure from PSI and, once it reaches the policy's
+//! threshold, caps the `cpu.max` of every running `BoxPriority::Low` box
+//! until pressure falls to the release level. Boxes started meanwhile are
+//! capped on the next sample. On release, each box gets its own CPU limit
+//! back. Linux only: the caps live in the cgroups the jailer creates.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use crate::jailer::cgroup::{self, CgroupConfig};
+use crate::metrics::RuntimeMetricsStorage;
+use crate::runtime::options::{BoxPriority, CpuPressurePolicy};
+use crate::runtime::types::BoxID;
+
+use super::rt_impl::RuntimeImpl;
+
+/// Host CPU pressure, as reported by the kernel.
+const PSI_CPU: &str = "/proc/pressure/cpu";
+
+/// `cpu.max` period of throttled boxes, in microseconds.
+const CPU_MAX_PERIOD_US: u64 = 100_000;
+
+/// Smallest quota the kernel accepts in `cpu.max`, in microseconds.
+const CPU_MAX_MIN_QUOTA_US: u64 = 1_000;
+
+/// Granularity at which the monitor thread notices shutdown.
+const MONITOR_POLL: Duration = Duration::from_millis(250);
+
+impl RuntimeImpl {
+    /// Start the thread that throttles low priority boxes under pressure.
+    ///
+    /// A host without PSI logs a warning and leaves boxes alone.
+    pub(crate) fn spawn_pressure_monitor(self: &Arc<Self>, policy: CpuPressurePolicy) {
+        if let Err(e) = read_pressure() {
+            tracing::warn!(
+                error = %e,
+                "Host CPU pressure is unavailable, cpu_pressure policy ignored"
+            );
+            return;
+        }
+
+        let runtime = Arc::downgrade(self);
+        let shutdown = self.shutdown_token.clone();
+        let interval = policy.interval;
+        let mut throttle = Throttle::new(policy, self.runtime_metrics.clone());
+
+        let spawned = std::thread::Builder::new()
+            .name("boxlite-cpu-pressure".to_string())
+            .spawn(move || {
+                'monitor: loop {
+                    // Hold a strong reference only while sampling so the
+                    // thread never keeps the runtime alive
+                    let Some(rt) = runtime.upgrade() else { break };
+                    match read_pressure() {
+                        Ok(pressure) => throttle.update(pressure, || rt.low_priority_boxes()),
+                        Err(e) => tracing::debug!(error = %e, "CPU pressure sample failed"),
+                    }
+                    drop(rt);
+
+                    let mut waited = Duration::ZERO;
+                    while waited < interval {
+                        if shutdown.is_cancelled() || runtime.strong_count() == 0 {
+                            break 'monitor;
+                        }
+                        std::thread::sleep(MONITOR_POLL);
+                        waited += MONITOR_POLL;
+                    }
+                }
+                // Detached boxes outlive the runtime: don't leave them capped
+                throttle.stop();
+            });
+
+        if let Err(e) = spawned {
+            tracing::warn!(error = %e, "Failed to start CPU pressure monitor");
+        }
+    }
+
+    /// Running low priority boxes that have a cgroup, with their own
+    /// `cpu.max`.
+    fn low_priority_boxes(&self) -> Vec<(BoxID, Option<(u64, u64)>)> {
+        let boxes = match self.box_manager.all_boxes(true) {
+            Ok(boxes) => boxes,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to list boxes for CPU throttling");
+                return Vec::new();
+            }
+        };
+        boxes
+            .into_iter()
+            .filter(|(config, state)| {
+                state.status.is_running()
+                    && config.options.priority == BoxPriority::Low
+                    && config.options.advanced.security.jailer_enabled
+            })
+            .map(|(config, _)| {
+                let own = CgroupConfig::from(&config.options.advanced.security.resource_limits);
+                (config.id, own.cpu_max)
+            })
+            .collect()
+    }
+}
+
+/// Throttle state of the monitor thread.
+struct Throttle {
+    policy: CpuPressurePolicy,
+    metrics: RuntimeMetricsStorage,
+    throttled: bool,
+    /// Capped boxes, with the `cpu.max` to give back on release
+    capped: HashMap<BoxID, Option<(u64, u64)>>,
+}
+
+impl Throttle {
+    fn new(policy: CpuPressurePolicy, metrics: RuntimeMetricsStorage) -> Self {
+        Self {
+            policy,
+            metrics,
+            throttled: false,
+            capped: HashMap::new(),
+        }
+    }
+
+    /// Act on a pressure sample; `boxes` lists the boxes to cap.
+    fn update(&mut self, pressure: f64, boxes: impl FnOnce() -> Vec<(BoxID, Option<(u64, u64)>)>) {
+        let throttle = should_throttle(&self.policy, self.throttled, pressure);
+        if throttle && !self.throttled {
+            tracing::info!(
+                pressure,
+                threshold = self.policy.throttle_above,
+                "Host CPU pressure high, throttling low priority boxes"
+            );
+            self.metrics.cpu_throttles.fetch_add(1, Ordering::Relaxed);
+        } else if !throttle && self.throttled {
+            tracing::info!(
+                pressure,
+                threshold = self.policy.release_below,
+                "Host CPU pressure cleared, releasing low priority boxes"
+            );
+            self.release();
+        }
+        self.throttled = throttle;
+
+        if self.throttled {
+            self.cap(boxes());
+        }
+    }
+
+    /// Cap the boxes not capped yet; forget those no longer running.
+    fn cap(&mut self, boxes: Vec<(BoxID, Option<(u64, u64)>)>) {
+        let running: HashSet<&BoxID> = boxes.iter().map(|(id, _)| id).collect();
+        self.capped.retain(|id, _| running.contains(id));
+
+        let limit = throttled_cpu_max(&self.policy);
+        for (id, own) in &boxes {
+            if self.capped.contains_key(id) {
+                continue;
+            }
+            match cgroup::update_cpu_max(id.as_str(), Some(tighter(*own, limit))) {
+                Ok(()) => {
+                    tracing::info!(box_id = %id, "Box CPU throttled");
+                    self.capped.insert(id.clone(), *own);
+                }
+                Err(e) => tracing::debug!(box_id = %id, error = %e, "Box not throttled"),
+            }
+        }
+    }
+
+    /// Give every capped box its own limit back.
+    fn release(&mut self) {
+        self.metrics
+            .cpu_throttle_releases
+            .fetch_add(1, Ordering::Relaxed);
+        for (id, own) in self.capped.drain() {
+            match cgroup::update_cpu_max(id.as_str(), own) {
+                Ok(()) => tracing::info!(box_id = %id, "Box CPU throttle released"),
+                // A box that stopped meanwhile took its cgroup with it
+                Err(e) => tracing::debug!(box_id = %id, error = %e, "Box not released"),
+            }
+        }
+    }
+
+    /// Release the boxes when the runtime goes away.
+    fn stop(&mut self) {
+        if self.throttled {
+            tracing::info!("Runtime stopping, releasing low priority boxes");
+            self.release();
+            self.throttled = false;
+        }
+    }
+}
+
+/// Read the host's CPU pressure, a percentage.
+fn read_pressure() -> std::io::Result<f64> {
+    let psi = std::fs::read_to_string(PSI_CPU)?;
+    parse_some_avg10(&psi).ok_or_else(|| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("no `some avg10` in {}", PSI_CPU),
+        )
+    })
+}
+
+/// `avg10` of the `some` line of a PSI file.
+fn parse_some_avg10(psi: &str) -> Option<f64> {
+    psi.lines()
+        .find(|line| line.starts_with("some "))?
+        .split_whitespace()
+        .find_map(|field| field.strip_prefix("avg10="))?
+        .parse()
+        .ok()
+}
+
+/// Whether boxes should be throttled at `pressure`, given whether they are.
+///
+/// Between the two thresholds the current state holds.
+fn should_throttle(policy: &CpuPressurePolicy, throttled: bool, pressure: f64) -> bool {
+    if throttled {
+        pressure > policy.release_below
+    } else {
+        pressure >= policy.throttle_above
+    }
+}
+
+/// `cpu.max` of a throttled box.
+fn throttled_cpu_max(policy: &CpuPressurePolicy) -> (u64, u64) {
+    let quota = (policy.throttled_cpus * CPU_MAX_PERIOD_US as f64).round() as u64;
+    (quota.max(CPU_MAX_MIN_QUOTA_US), CPU_MAX_PERIOD_US)
+}
+
+/// The stricter of a box's own `cpu.max` and the throttle's.
+fn tighter(own: Option<(u64, u64)>, limit: (u64, u64)) -> (u64, u64) {
+    let cpus = |(quota, period): (u64, u64)| quota as f64 / period as f64;
+    match own {
+        Some(own) if cpus(own) < cpus(limit) => own,
+        _ => limit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_some_avg10() {
+        let psi = "some avg10=63.25 avg60=40.10 avg300=12.00 total=123456\n\
+                   full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n";
+        assert_eq!(parse_some_avg10(psi), Some(63.25));
+        assert_eq!(parse_some_avg10("full avg10=1.00 total=0\n"), None);
+        assert_eq!(parse_some_avg10("some avg10=high total=0\n"), None);
+    }
+
+    #[test]
+    fn test_should_throttle_holds_between_thresholds() {
+        let policy = CpuPressurePolicy::default();
+        let mut throttled = false;
+        let mut states = Vec::new();
+        for pressure in [10.0, 35.0, 50.0, 35.0, 20.5, 20.0, 35.0] {
+            throttled = should_throttle(&policy, throttled, pressure);
+            states.push(throttled);
+        }
+        assert_eq!(states, [false, false, true, true, true, false, false]);
+    }
+
+    #[test]
+    fn test_throttled_cpu_max() {
+        let mut policy = CpuPressurePolicy::default();
+        assert_eq!(throttled_cpu_max(&policy), (50_000, 100_000));
+        policy.throttled_cpus = 2.0;
+        assert_eq!(throttled_cpu_max(&policy), (200_000, 100_000));
+        // Never below what the kernel accepts
+        policy.throttled_cpus = 0.001;
+        assert_eq!(throttled_cpu_max(&policy), (1_000, 100_000));
+    }
+
+    #[test]
+    fn test_tighter_keeps_a_stricter_own_limit() {
+        let limit = (50_000, 100_000);
+        assert_eq!(tighter(None, limit), limit);
+        assert_eq!(tighter(Some((20_000, 100_000)), limit), (20_000, 100_000));
+        assert_eq!(tighter(Some((2_000_000, 1_000_000)), limit), limit);
+    }
+
+    #[test]
+    fn test_transitions_are_counted() {
+        let metrics = RuntimeMetricsStorage::new();
+        let mut throttle = Throttle::new(CpuPressurePolicy::default(), metrics.clone());
+
+        for pressure in [10.0, 60.0, 70.0, 30.0, 5.0, 80.0] {
+            throttle.update(pressure, Vec::new);
+        }
+        assert_eq!(metrics.cpu_throttles.load(Ordering::Relaxed), 2);
+        assert_eq!(metrics.cpu_throttle_releases.load(Ordering::Relaxed), 1);
+
+        throttle.stop();
+        assert_eq!(metrics.cpu_throttle_releases.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,145 @@
+//! Progress reporting for [`BoxliteRuntime::create_with_observer`].
+//!
+//! [`BoxliteRuntime::create_with_observer`]: crate::BoxliteRuntime::create_with_observer
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One phase of box creation.
+///
+/// Phases arrive in the order listed; phases that have nothing to do (an
+/// image that is already pulled, a cached disk) are still reported, and
+/// usually finish immediately. `PullingLayer` repeats for every chunk of
+/// every layer, interleaved across layers that download in parallel.
+/// `LowSpace` is a warning rather than a phase, reported before
+/// `ResolvingImage` when it applies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum CreatePhase {
+    /// Looking up the image locally or in the registry.
+    ResolvingImage,
+    /// Downloading one image layer.
+    PullingLayer {
+        /// Layer digest, e.g. `sha256:...`.
+        digest: String,
+        /// Bytes of the layer on disk.
+        done: u64,
+        /// Layer size from the manifest (0 if unknown).
+        total: u64,
+    },
+    /// Building (or finding cached) the ext4 disk for the image.
+    BuildingImageDisk,
+    /// Preparing the shared guest rootfs that runs the boxlite agent.
+    InjectingGuest,
+    /// Registering the box. Its copy-on-write overlays are created from the
+    /// prepared disks on first start.
+    PreparingOverlays,
+    /// The box exists and its first start will not pull or build anything.
+    Done,
+    /// Free space under the BoxLite home is below
+    /// `BoxliteOptions::low_space_threshold_bytes`. Creation goes on.
+    LowSpace {
+        /// Bytes free on the filesystem.
+        available_bytes: u64,
+        /// The configured threshold.
+        threshold_bytes: u64,
+    },
+}
+
+/// A phase event with its timestamps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CreateEvent {
+    pub phase: CreatePhase,
+    /// Wall-clock time the phase was reported.
+    pub at: DateTime<Utc>,
+    /// Time since the create call started.
+    pub elapsed: Duration,
+}
+
+/// Callback invoked with create progress. Called from runtime tasks, so it
+/// should return quickly.
+pub type CreateObserver = Arc<dyn Fn(&CreateEvent) + Send + Sync>;
+
+/// Stamps phases and forwards them to an observer.
+#[derive(Clone)]
+pub(crate) struct CreateProgress {
+    observer: CreateObserver,
+    started: Instant,
+}
+
+impl CreateProgress {
+    pub(crate) fn new(observer: CreateObserver) -> Self {
+        Self {
+            observer,
+            started: Instant::now(),
+        }
+    }
+
+    pub(crate) fn emit(&self, phase: CreatePhase) {
+        (self.observer)(&CreateEvent {
+            phase,
+            at: Utc::now(),
+            elapsed: self.started.elapsed(),
+        });
+    }
+
+    /// Forward an event produced elsewhere (e.g. by a remote server),
+    /// keeping its timestamps.
+    pub(crate) fn forward(&self, event: &CreateEvent) {
+        (self.observer)(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn phases_serialize_with_a_phase_tag() {
+        let json = serde_json::to_value(CreatePhase::PullingLayer {
+            digest: "sha256:ab".into(),
+            done: 1,
+            total: 2,
+        })
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"phase": "pulling_layer", "digest": "sha256:ab", "done": 1, "total": 2})
+        );
+        assert_eq!(
+            serde_json::to_value(CreatePhase::BuildingImageDisk).unwrap(),
+            serde_json::json!({"phase": "building_image_disk"})
+        );
+        assert_eq!(
+            serde_json::to_value(CreatePhase::LowSpace {
+                available_bytes: 1,
+                threshold_bytes: 2,
+            })
+            .unwrap(),
+            serde_json::json!({"phase": "low_space", "available_bytes": 1, "threshold_bytes": 2})
+        );
+    }
+
+    #[test]
+    fn emit_stamps_elapsed_time() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&seen);
+        let progress = CreateProgress::new(Arc::new(move |event: &CreateEvent| {
+            sink.lock().unwrap().push(event.clone())
+        }));
+
+        progress.emit(CreatePhase::ResolvingImage);
+        std::thread::sleep(Duration::from_millis(5));
+        progress.emit(CreatePhase::Done);
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen[0].phase, CreatePhase::ResolvingImage);
+        assert_eq!(seen[1].phase, CreatePhase::Done);
+        assert!(seen[1].elapsed > seen[0].elapsed);
+        assert!(seen[1].at >= seen[0].at);
+    }
+}
@@ -0,0 +1,193 @@
+//! Free disk space checks.
+//!
+//! Operations that write a lot (image disk builds, snapshots, export,
+//! import, copy into a box) call [`DiskSpace::ensure`] with an estimate of
+//! what they will write before they start, so a full filesystem fails them up
+//! front with `BoxliteError::Storage` instead of halfway through. Estimates
+//! are lower bounds, so a check never refuses an operation that would fit; one
+//! that passes can still hit ENOSPC, and then removes its partial output.
+//!
+//! Each check also compares the free space with
+//! `BoxliteOptions::low_space_threshold_bytes`: below it, a warning is logged
+//! and counted in `RuntimeMetrics::low_space_warnings_total()`.
+
+use std::io;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::metrics::RuntimeMetricsStorage;
+
+/// Room kept on top of every estimate for the database, logs and filesystem
+/// metadata written alongside.
+const RESERVE_BYTES: u64 = 16 * 1024 * 1024;
+
+/// Free space under the low-space threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct LowSpace {
+    pub available_bytes: u64,
+    pub threshold_bytes: u64,
+}
+
+/// Free space checks of one runtime.
+#[derive(Clone, Default)]
+pub(crate) struct DiskSpace {
+    low_space_threshold: Option<u64>,
+    metrics: RuntimeMetricsStorage,
+}
+
+impl DiskSpace {
+    pub(crate) fn new(low_space_threshold: Option<u64>, metrics: RuntimeMetricsStorage) -> Self {
+        Self {
+            low_space_threshold,
+            metrics,
+        }
+    }
+
+    /// Fail with `Storage` unless the filesystem holding `path` has room for
+    /// `required` more bytes to `operation` (e.g. "export box").
+    ///
+    /// Best effort: if the free space cannot be read, the operation goes ahead.
+    pub(crate) fn ensure(&self, path: &Path, required: u64, operation: &str) -> BoxliteResult<()> {
+        let Some(available) = self.available(path) else {
+            return Ok(());
+        };
+        let needed = required.saturating_add(RESERVE_BYTES);
+        if available < needed {
+            return Err(BoxliteError::Storage(format!(
+                "not enough disk space to {} on {}: {} required, {} available",
+                operation,
+                path.display(),
+                format_bytes(needed),
+                format_bytes(available)
+            )));
+        }
+        Ok(())
+    }
+
+    /// The free space of the filesystem holding `path`, if it is below the
+    /// low-space threshold.
+    pub(crate) fn low_space(&self, path: &Path) -> Option<LowSpace> {
+        let available = self.available(path)?;
+        let threshold = self.low_space_threshold?;
+        (available < threshold).then_some(LowSpace {
+            available_bytes: available,
+            threshold_bytes: threshold,
+        })
+    }
+
+    /// Free bytes on the filesystem holding `path`, warning when low.
+    fn available(&self, path: &Path) -> Option<u64> {
+        let available = match available_bytes(path) {
+            Ok(available) => available,
+            Err(e) => {
+                tracing::debug!(path = %path.display(), error = %e, "Cannot read free space");
+                return None;
+            }
+        };
+        if let Some(threshold) = self.low_space_threshold
+            && available < threshold
+        {
+            self.metrics
+                .low_space_warnings
+                .fetch_add(1, Ordering::Relaxed);
+            tracing::warn!(
+                path = %path.display(),
+                available = %format_bytes(available),
+                threshold = %format_bytes(threshold),
+                "Low disk space"
+            );
+        }
+        Some(available)
+    }
+}
+
+/// Whether an I/O error means the filesystem (or the user's quota) is full.
+///
+/// Goes by the error kind, which wrappers such as `tar`'s errors keep.
+pub(crate) fn is_out_of_space(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::StorageFull | io::ErrorKind::QuotaExceeded
+    )
+}
+
+/// Bytes available to unprivileged writers on the filesystem holding `path`,
+/// or holding its nearest existing ancestor.
+fn available_bytes(path: &Path) -> io::Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| io::Error::from(io::ErrorKind::NotFound))?;
+    let stat = nix::sys::statvfs::statvfs(existing)?;
+    Ok((stat.blocks_available() as u64).saturating_mul(stat.fragment_size() as u64))
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const MIB: u64 = 1024 * 1024;
+    const GIB: u64 = MIB * 1024;
+    if bytes >= GIB {
+        format!("{:.1} GiB", bytes as f64 / GIB as f64)
+    } else {
+        format!("{:.1} MiB", bytes as f64 / MIB as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_reports_required_and_available() {
+        let dir = tempfile::tempdir().unwrap();
+        let space = DiskSpace::default();
+        space.ensure(dir.path(), 0, "test").unwrap();
+
+        let err = space
+            .ensure(&dir.path().join("missing/file"), u64::MAX / 2, "export box")
+            .unwrap_err();
+        assert!(matches!(err, BoxliteError::Storage(_)));
+        let message = err.to_string();
+        assert!(
+            message.contains("not enough disk space to export box"),
+            "{message}"
+        );
+        assert!(message.contains("required"), "{message}");
+        assert!(message.contains("available"), "{message}");
+    }
+
+    #[test]
+    fn test_low_space_counts_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let metrics = RuntimeMetricsStorage::new();
+
+        let unset = DiskSpace::new(None, metrics.clone());
+        assert_eq!(unset.low_space(dir.path()), None);
+
+        let space = DiskSpace::new(Some(u64::MAX), metrics.clone());
+        let low = space.low_space(dir.path()).unwrap();
+        assert_eq!(low.threshold_bytes, u64::MAX);
+        space.ensure(dir.path(), 0, "test").unwrap();
+        assert_eq!(metrics.low_space_warnings.load(Ordering::Relaxed), 2);
+
+        let roomy = DiskSpace::new(Some(1), metrics.clone());
+        assert_eq!(roomy.low_space(dir.path()), None);
+        assert_eq!(metrics.low_space_warnings.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_is_out_of_space() {
+        let enospc = io::Error::from_raw_os_error(libc::ENOSPC);
+        assert!(is_out_of_space(&io::Error::new(enospc.kind(), "wrapped")));
+        assert!(is_out_of_space(&enospc));
+        assert!(!is_out_of_space(&io::Error::from_raw_os_error(libc::EIO)));
+        assert!(!is_out_of_space(&io::Error::other("full")));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(16 * 1024 * 1024), "16.0 MiB");
+        assert_eq!(format_bytes(3 * 1024 * 1024 * 1024 / 2), "1.5 GiB");
+    }
+}
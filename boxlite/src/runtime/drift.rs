@@ -0,0 +1,182 @@
+//! What `get_or_create` does when the named box was created with other
+//! options.
+//!
+//! The options are compared field by field as their serialized form, after
+//! dropping the fields that don't change what the box is (labels, exec
+//! output redaction, snapshot retention) and normalizing the ones whose
+//! order doesn't matter (env, ports).
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::runtime::options::BoxOptions;
+
+/// Fields left out of the comparison: they describe or manage the box
+/// without changing it, and some can be changed after creation.
+const IGNORED_FIELDS: &[&str] = &["labels", "redact_env", "snapshot_retention"];
+
+/// What [`BoxliteRuntime::get_or_create_with`](crate::BoxliteRuntime::get_or_create_with)
+/// does with an existing box whose options differ from the requested ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GetOrCreatePolicy {
+    /// Return the existing box as is (default).
+    #[default]
+    ReuseAlways,
+    /// Stop and remove the existing box, then create it again.
+    RecreateOnMismatch,
+    /// Fail with `BoxliteError::ConfigDrift` listing the differing fields.
+    FailOnMismatch,
+}
+
+/// How [`BoxliteRuntime::get_or_create_with`](crate::BoxliteRuntime::get_or_create_with)
+/// got its box.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GetOrCreateOutcome {
+    /// No box had the name; a new one was created.
+    Created,
+    /// The existing box was returned.
+    Reused,
+    /// The existing box differed and was replaced by a new one.
+    Recreated {
+        /// Dotted paths of the options that differed, e.g. `env.PATH`.
+        changed: Vec<String>,
+    },
+}
+
+impl GetOrCreateOutcome {
+    /// Whether a new box was created.
+    pub fn created(&self) -> bool {
+        !matches!(self, GetOrCreateOutcome::Reused)
+    }
+}
+
+/// Dotted paths of the options that differ between an existing box's
+/// `existing` options and `requested`, sorted; empty if they match.
+pub(crate) fn changed_options(existing: &BoxOptions, requested: &BoxOptions) -> Vec<String> {
+    changed_values(&normalize(existing), &normalize(requested))
+}
+
+/// Dotted paths at which `requested` differs from `existing`, sorted.
+pub(crate) fn changed_values(existing: &Value, requested: &Value) -> Vec<String> {
+    let mut changed = Vec::new();
+    diff("", existing, requested, &mut changed);
+    changed
+}
+
+fn normalize(options: &BoxOptions) -> Value {
+    let mut value = serde_json::to_value(options).unwrap_or(Value::Null);
+    let Value::Object(fields) = &mut value else {
+        return value;
+    };
+    for name in IGNORED_FIELDS {
+        fields.remove(*name);
+    }
+    // Later entries win, as they do in the container
+    let env: BTreeMap<&str, &str> = options
+        .env
+        .iter()
+        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .collect();
+    fields.insert(
+        "env".to_string(),
+        serde_json::to_value(env).unwrap_or(Value::Null),
+    );
+    if let Some(Value::Array(ports)) = fields.get_mut("ports") {
+        ports.sort_by_key(|port| port.to_string());
+    }
+    value
+}
+
+fn diff(path: &str, existing: &Value, requested: &Value, changed: &mut Vec<String>) {
+    match (existing, requested) {
+        (Value::Object(a), Value::Object(b)) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                let missing = Value::Null;
+                diff(
+                    &child,
+                    a.get(key).unwrap_or(&missing),
+                    b.get(key).unwrap_or(&missing),
+                    changed,
+                );
+            }
+        }
+        (a, b) if a != b => changed.push(path.to_string()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::RootfsSpec;
+
+    fn options() -> BoxOptions {
+        BoxOptions {
+            rootfs: RootfsSpec::Image("alpine:latest".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_identical_options_match() {
+        assert!(changed_options(&options(), &options()).is_empty());
+    }
+
+    #[test]
+    fn test_reports_dotted_paths() {
+        let requested = BoxOptions {
+            cpus: Some(4),
+            env: vec![("PATH".into(), "/bin".into())],
+            ..options()
+        };
+        assert_eq!(
+            changed_options(&options(), &requested),
+            ["cpus", "env.PATH"]
+        );
+    }
+
+    #[test]
+    fn test_env_order_is_ignored() {
+        let existing = BoxOptions {
+            env: vec![("A".into(), "1".into()), ("B".into(), "2".into())],
+            ..options()
+        };
+        let requested = BoxOptions {
+            env: vec![
+                ("B".into(), "2".into()),
+                ("A".into(), "0".into()),
+                ("A".into(), "1".into()),
+            ],
+            ..options()
+        };
+        assert!(changed_options(&existing, &requested).is_empty());
+    }
+
+    #[test]
+    fn test_informational_fields_are_ignored() {
+        let mut requested = options();
+        requested.labels.insert("team".into(), "ci".into());
+        requested.redact_env.push("TOKEN".into());
+        assert!(changed_options(&options(), &requested).is_empty());
+    }
+
+    #[test]
+    fn test_image_change_is_reported() {
+        let requested = BoxOptions {
+            rootfs: RootfsSpec::Image("alpine:3.20".into()),
+            ..options()
+        };
+        let changed = changed_options(&options(), &requested);
+        assert_eq!(changed.len(), 1);
+        assert!(changed[0].starts_with("rootfs"), "{changed:?}");
+    }
+}
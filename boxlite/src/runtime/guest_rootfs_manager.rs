@@ -2,18 +2,29 @@
 //!
 //! Manages versioned guest rootfs disks: image ext4 + injected boxlite-guest binary.
 //! Old versions persist for existing boxes. GC removes unreferenced entries.
+//!
+//! With `GuestUpdateMode::InPlace`, a new guest binary is re-injected into an
+//! unreferenced cached disk of the same image instead of rebuilding it.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
-use crate::disk::{Disk, DiskFormat, inject_file_into_ext4, read_backing_file_path};
+use crate::disk::cache::{self, CacheCap};
+use crate::disk::{CacheStats, Disk, DiskFormat, inject_file_into_ext4, replace_file_in_ext4};
 use crate::images::{ImageDiskManager, ImageObject};
+use crate::runtime::options::GuestUpdateMode;
 use crate::util;
 
+/// Path of the guest agent inside the guest rootfs.
+const GUEST_BIN_PATH: &str = "boxlite/bin/boxlite-guest";
+
+/// Suffix of the journal written next to a cache entry while it is re-injected.
+const JOURNAL_SUFFIX: &str = ".reinject";
+
 /// Manages versioned guest rootfs disks.
 ///
 /// A guest rootfs = pure image disk + injected `boxlite-guest` binary.
@@ -33,11 +44,27 @@ use crate::util;
 ///
 /// No internal locking is needed.
 ///
+/// # In-place updates
+///
+/// With [`GuestUpdateMode::InPlace`], a cache miss caused only by a new guest
+/// binary re-injects that binary into an existing entry of the same image that
+/// no box references, then renames it to the new version key. A journal file
+/// (`{entry}.ext4.reinject`) exists for the duration of the rewrite; an entry
+/// found with a journal was interrupted mid-write and is discarded.
+///
+/// # Size cap
+///
+/// With a cap set via [`with_cap`](Self::with_cap), least recently used
+/// entries no box is backed by are evicted after each install and on
+/// startup, whatever their guest version.
+///
 /// Cache location: `~/.boxlite/rootfs/`
 pub struct GuestRootfsManager {
     cache_dir: PathBuf,
     temp_dir: PathBuf,
+    update_mode: GuestUpdateMode,
     guest_hash: OnceLock<Result<String, String>>,
+    cap: CacheCap,
 }
 
 impl GuestRootfsManager {
@@ -45,31 +72,76 @@ impl GuestRootfsManager {
         Self {
             cache_dir,
             temp_dir,
+            update_mode: GuestUpdateMode::default(),
             guest_hash: OnceLock::new(),
+            cap: CacheCap::default(),
         }
     }
 
+    /// Cap the cache at `max_bytes`, protecting entries that the boxes under
+    /// `box_roots` are backed by.
+    pub fn with_cap(mut self, max_bytes: Option<u64>, box_roots: Vec<PathBuf>) -> Self {
+        self.cap = CacheCap::new(max_bytes, box_roots);
+        self
+    }
+
+    /// Set how cached entries follow a new guest binary.
+    pub fn with_update_mode(mut self, update_mode: GuestUpdateMode) -> Self {
+        self.update_mode = update_mode;
+        self
+    }
+
     /// Get the cached guest binary hash, computing it once on first access.
+    ///
+    /// In `InPlace` mode the installed binary is always hashed, since it may
+    /// be newer than the one boxlite was compiled against.
     fn cached_guest_hash(&self) -> BoxliteResult<&str> {
         let cached = self
             .guest_hash
-            .get_or_init(|| Self::guest_binary_hash().map_err(|e| e.to_string()));
+            .get_or_init(|| Self::current_guest_hash(self.update_mode).map_err(|e| e.to_string()));
         match cached {
             Ok(hash) => Ok(hash.as_str()),
             Err(msg) => Err(BoxliteError::Storage(msg.clone())),
         }
     }
 
+    /// [`cached_guest_hash`](Self::cached_guest_hash) that hashes on the
+    /// blocking pool the first time, so async callers don't stall their
+    /// worker thread on a cold cache.
+    async fn cached_guest_hash_async(&self) -> BoxliteResult<&str> {
+        if self.guest_hash.get().is_none() {
+            let update_mode = self.update_mode;
+            let hash = tokio::task::spawn_blocking(move || Self::current_guest_hash(update_mode))
+                .await
+                .map_err(|e| BoxliteError::Internal(format!("guest hash task failed: {}", e)))?;
+            // A concurrent caller may have won the race; its hash is as good
+            let _ = self.guest_hash.set(hash.map_err(|e| e.to_string()));
+        }
+        self.cached_guest_hash()
+    }
+
+    /// Hash of the guest binary boxes of `update_mode` are built with.
+    fn current_guest_hash(update_mode: GuestUpdateMode) -> BoxliteResult<String> {
+        match update_mode {
+            GuestUpdateMode::Strict => Self::guest_binary_hash(),
+            GuestUpdateMode::InPlace => util::find_binary("boxlite-guest")
+                .and_then(|guest_bin| util::checksum::sha256_file(&guest_bin)),
+        }
+    }
+
     /// Get or create a versioned guest rootfs disk.
     ///
     /// Stage 1 (via `ImageDiskManager`): ensure pure image ext4 exists.
     /// Stage 2: copy image disk → inject guest binary via debugfs → cache.
+    /// In `InPlace` mode, stage 2 first tries re-injecting an older entry
+    /// of the same image that no box in `boxes_dir` references.
     ///
     /// Returns a persistent `Disk` (won't be cleaned up on drop).
     pub async fn get_or_create(
         &self,
         image: &ImageObject,
         image_disk_mgr: &ImageDiskManager,
+        boxes_dir: &Path,
     ) -> BoxliteResult<Disk> {
         let total_start = std::time::Instant::now();
 
@@ -84,7 +156,7 @@ impl GuestRootfsManager {
         // Stage 2: versioned guest rootfs
         let digest = image.compute_image_digest();
         let hash_start = std::time::Instant::now();
-        let guest_hash = self.cached_guest_hash()?;
+        let guest_hash = self.cached_guest_hash_async().await?;
         tracing::info!(
             elapsed_ms = hash_start.elapsed().as_millis() as u64,
             "get_or_create: cached_guest_hash done"
@@ -100,6 +172,26 @@ impl GuestRootfsManager {
             return Ok(disk);
         }
 
+        if self.update_mode == GuestUpdateMode::InPlace {
+            match self.reinject_stale(&digest, &version_key, boxes_dir) {
+                Ok(Some(disk)) => {
+                    tracing::info!(
+                        version_key = %version_key,
+                        total_ms = total_start.elapsed().as_millis() as u64,
+                        "get_or_create: re-injected stale entry"
+                    );
+                    self.evict_logged();
+                    return Ok(disk);
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!(
+                    version_key = %version_key,
+                    error = %e,
+                    "In-place guest update failed, rebuilding from image disk"
+                ),
+            }
+        }
+
         tracing::info!(
             version_key = %version_key,
             "get_or_create: CACHE MISS — building guest rootfs"
@@ -114,14 +206,174 @@ impl GuestRootfsManager {
             "get_or_create: completed"
         );
 
+        if result.is_ok() {
+            self.evict_logged();
+        }
         result
     }
 
-    /// Look up a cached guest rootfs by version key.
+    /// Evict least recently used entries until the cache fits its cap.
+    ///
+    /// Returns the number of entries removed.
+    pub fn evict(&self) -> BoxliteResult<usize> {
+        Ok(self.cap.enforce("guest rootfs", &self.cache_dir)?.len())
+    }
+
+    fn evict_logged(&self) {
+        if let Err(e) = self.evict() {
+            tracing::warn!("Guest rootfs cache eviction failed: {}", e);
+        }
+    }
+
+    /// Current usage of the cache.
+    pub fn stats(&self) -> BoxliteResult<CacheStats> {
+        self.cap.stats(&self.cache_dir)
+    }
+
+    /// Look up a cached guest rootfs by version key, recording the use.
+    ///
+    /// An entry left behind by an interrupted re-injection is discarded.
     fn find(&self, version_key: &str) -> Option<Disk> {
         let path = self.cache_path(version_key);
-        path.exists()
-            .then(|| Disk::new(path, DiskFormat::Ext4, true))
+        if Self::journal_path(&path).exists() {
+            Self::discard_interrupted(&path);
+            return None;
+        }
+        if !path.exists() {
+            return None;
+        }
+        cache::touch(&path);
+        Some(Disk::new(path, DiskFormat::Ext4, true))
+    }
+
+    /// Re-inject the current guest binary into an older entry of the same
+    /// image and rename it to `version_key`.
+    ///
+    /// Only entries no box references are candidates: existing overlays read
+    /// unmodified clusters from their backing file, so rewriting one under
+    /// them would corrupt their view of the filesystem. Returns `None` when
+    /// there is no candidate.
+    fn reinject_stale(
+        &self,
+        digest: &str,
+        version_key: &str,
+        boxes_dir: &Path,
+    ) -> BoxliteResult<Option<Disk>> {
+        let Some(donor) = self.find_donor(digest, version_key, boxes_dir)? else {
+            return Ok(None);
+        };
+        let target = self.cache_path(version_key);
+        let journal = Self::journal_path(&donor);
+        let start = std::time::Instant::now();
+
+        // Journal first: if we crash past this point the donor may be
+        // half-written, and find()/gc() throw it away.
+        fs::write(&journal, version_key.as_bytes()).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to write re-injection journal {}: {}",
+                journal.display(),
+                e
+            ))
+        })?;
+
+        let result = util::find_binary("boxlite-guest")
+            .and_then(|guest_bin| replace_file_in_ext4(&donor, &guest_bin, GUEST_BIN_PATH))
+            .and_then(|()| {
+                fs::File::open(&donor)
+                    .and_then(|file| file.sync_all())
+                    .and_then(|()| fs::rename(&donor, &target))
+                    .map_err(|e| {
+                        BoxliteError::Storage(format!(
+                            "Failed to install re-injected rootfs {} as {}: {}",
+                            donor.display(),
+                            target.display(),
+                            e
+                        ))
+                    })
+            });
+
+        if let Err(e) = result {
+            Self::discard_interrupted(&donor);
+            return Err(e);
+        }
+        let _ = fs::remove_file(&journal);
+        let _ = cache::remove_entry(&donor);
+        cache::touch(&target);
+
+        tracing::info!(
+            from = %donor.display(),
+            to = %target.display(),
+            elapsed_ms = start.elapsed().as_millis() as u64,
+            "Re-injected guest binary into cached rootfs"
+        );
+        Ok(Some(Disk::new(target, DiskFormat::Ext4, true)))
+    }
+
+    /// Pick the most recently modified unreferenced entry for `digest` built
+    /// with a different guest binary.
+    fn find_donor(
+        &self,
+        digest: &str,
+        version_key: &str,
+        boxes_dir: &Path,
+    ) -> BoxliteResult<Option<PathBuf>> {
+        if !self.cache_dir.exists() {
+            return Ok(None);
+        }
+
+        let image_prefix = format!("{}-", Self::image_prefix(digest));
+        let target = self.cache_path(version_key);
+        let referenced = cache::referenced_backing_files(&[boxes_dir.to_path_buf()])?;
+
+        let entries = fs::read_dir(&self.cache_dir).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to read rootfs cache directory {}: {}",
+                self.cache_dir.display(),
+                e
+            ))
+        })?;
+
+        let donor = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+                name.starts_with(&image_prefix)
+                    && name.ends_with(".ext4")
+                    && *path != target
+                    && !referenced.contains(path)
+                    && !Self::journal_path(path).exists()
+            })
+            .filter_map(|path| {
+                let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+                Some((modified, path))
+            })
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, path)| path);
+
+        Ok(donor)
+    }
+
+    /// Journal path for a cache entry.
+    fn journal_path(entry: &Path) -> PathBuf {
+        let mut name = entry.as_os_str().to_owned();
+        name.push(JOURNAL_SUFFIX);
+        PathBuf::from(name)
+    }
+
+    /// Remove an entry whose re-injection did not complete, then its journal.
+    fn discard_interrupted(entry: &Path) {
+        tracing::warn!(
+            entry = %entry.display(),
+            "Discarding guest rootfs with interrupted re-injection"
+        );
+        if let Err(e) = cache::remove_entry(entry)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove {}: {}", entry.display(), e);
+            return;
+        }
+        let _ = fs::remove_file(Self::journal_path(entry));
     }
 
     /// Build guest rootfs from image disk and atomically install.
@@ -168,11 +420,13 @@ impl GuestRootfsManager {
         // Verify the actual guest binary hash matches what we expected.
         // The compile-time hash (from build.rs) may be stale if the guest
         // binary was rebuilt after boxlite was compiled.
-        let actual_hash = Self::sha256_file(&guest_bin)?;
+        let actual_hash = util::checksum::sha256_file_async(guest_bin.clone()).await?;
         let actual_version_key = Self::version_key(digest, &actual_hash);
 
         if actual_version_key != expected_version_key {
-            if option_env!("BOXLITE_GUEST_HASH").is_some() {
+            if self.update_mode == GuestUpdateMode::Strict
+                && option_env!("BOXLITE_GUEST_HASH").is_some()
+            {
                 // Compile-time hash exists but doesn't match the actual binary.
                 // This means boxlite was compiled against a different guest binary
                 // than what's found at runtime — an inconsistent build.
@@ -194,7 +448,7 @@ impl GuestRootfsManager {
             }
         }
 
-        inject_file_into_ext4(&staged_path, &guest_bin, "boxlite/bin/boxlite-guest")?;
+        inject_file_into_ext4(&staged_path, &guest_bin, GUEST_BIN_PATH)?;
         tracing::info!(
             elapsed_ms = inject_start.elapsed().as_millis() as u64,
             "build_and_install: inject guest binary done"
@@ -245,6 +499,7 @@ impl GuestRootfsManager {
 
         let _ = staged_disk.leak();
 
+        cache::touch(&target);
         tracing::info!("Installed guest rootfs to cache: {}", target.display());
         Ok(Disk::new(target, DiskFormat::Ext4, true))
     }
@@ -288,48 +543,16 @@ impl GuestRootfsManager {
     ///
     /// `current_guest_suffix` identifies current-version entries (e.g. "-8310374f82d7.ext4").
     /// Entries whose filename ends with this suffix are preserved.
+    ///
+    /// In `InPlace` mode, the newest stale entry of each image without a
+    /// current-version entry is kept as a re-injection candidate.
     fn gc_with_suffix(&self, boxes_dir: &Path, current_guest_suffix: &str) -> BoxliteResult<usize> {
         if !self.cache_dir.exists() {
             return Ok(0);
         }
 
-        // Collect all referenced backing file paths from box qcow2 overlays
-        let mut referenced: HashSet<PathBuf> = HashSet::new();
-
-        if boxes_dir.exists() {
-            let entries = fs::read_dir(boxes_dir).map_err(|e| {
-                BoxliteError::Storage(format!(
-                    "Failed to read boxes directory {}: {}",
-                    boxes_dir.display(),
-                    e
-                ))
-            })?;
-
-            for entry in entries {
-                let entry = entry.map_err(|e| {
-                    BoxliteError::Storage(format!("Failed to read box directory entry: {}", e))
-                })?;
-
-                let qcow2_path = entry.path().join("guest-rootfs.qcow2");
-                if !qcow2_path.exists() {
-                    continue;
-                }
-
-                match read_backing_file_path(&qcow2_path) {
-                    Ok(Some(backing_path)) => {
-                        referenced.insert(PathBuf::from(backing_path));
-                    }
-                    Ok(None) => {}
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to read backing file from {}: {}",
-                            qcow2_path.display(),
-                            e
-                        );
-                    }
-                }
-            }
-        }
+        self.recover_interrupted()?;
+        let referenced = cache::referenced_backing_files(&[boxes_dir.to_path_buf()])?;
 
         tracing::info!(
             referenced_count = referenced.len(),
@@ -342,6 +565,8 @@ impl GuestRootfsManager {
         let mut preserved_current = 0;
         let mut preserved_referenced = 0;
         let mut total_entries = 0;
+        let mut stale = Vec::new();
+        let mut current_images = HashSet::new();
 
         let cache_entries = fs::read_dir(&self.cache_dir).map_err(|e| {
             BoxliteError::Storage(format!(
@@ -357,7 +582,7 @@ impl GuestRootfsManager {
             })?;
 
             let path = entry.path();
-            if !path.is_file() {
+            if !path.is_file() || path.extension().is_none_or(|ext| ext != "ext4") {
                 continue;
             }
 
@@ -373,13 +598,29 @@ impl GuestRootfsManager {
             let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
             if filename.ends_with(current_guest_suffix) {
                 preserved_current += 1;
+                current_images.insert(Self::entry_image_prefix(filename).to_string());
                 tracing::debug!("GC: keeping current-version entry: {}", path.display());
                 continue;
             }
 
+            stale.push(path);
+        }
+
+        let donors = if self.update_mode == GuestUpdateMode::InPlace {
+            Self::pick_donors(&stale, &current_images)
+        } else {
+            HashSet::new()
+        };
+
+        for path in stale {
+            if donors.contains(&path) {
+                tracing::debug!("GC: keeping re-injection candidate: {}", path.display());
+                continue;
+            }
+
             // Delete stale entries (old guest version, no box references)
             tracing::info!("GC: removing stale guest rootfs: {}", path.display());
-            if let Err(e) = fs::remove_file(&path) {
+            if let Err(e) = cache::remove_entry(&path) {
                 tracing::warn!("GC: failed to remove {}: {}", path.display(), e);
             } else {
                 removed += 1;
@@ -390,6 +631,7 @@ impl GuestRootfsManager {
             total_entries,
             preserved_current,
             preserved_referenced,
+            preserved_donors = donors.len(),
             removed,
             "gc_with_suffix: summary"
         );
@@ -397,11 +639,57 @@ impl GuestRootfsManager {
         Ok(removed)
     }
 
+    /// Newest stale entry per image that has no current-version entry.
+    fn pick_donors(stale: &[PathBuf], current_images: &HashSet<String>) -> HashSet<PathBuf> {
+        let mut newest: HashMap<&str, (std::time::SystemTime, &PathBuf)> = HashMap::new();
+        for path in stale {
+            let filename = path.file_name().and_then(|f| f.to_str()).unwrap_or("");
+            let image = Self::entry_image_prefix(filename);
+            if current_images.contains(image) {
+                continue;
+            }
+            let Ok(modified) = path.metadata().and_then(|m| m.modified()) else {
+                continue;
+            };
+            match newest.get(image) {
+                Some((best, _)) if *best >= modified => {}
+                _ => {
+                    newest.insert(image, (modified, path));
+                }
+            }
+        }
+        newest.into_values().map(|(_, path)| path.clone()).collect()
+    }
+
+    /// Discard entries whose re-injection was interrupted, and orphan journals.
+    fn recover_interrupted(&self) -> BoxliteResult<()> {
+        let entries = fs::read_dir(&self.cache_dir).map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to read rootfs cache directory {}: {}",
+                self.cache_dir.display(),
+                e
+            ))
+        })?;
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let journal = entry.path();
+            let Some(entry_path) = journal
+                .to_str()
+                .and_then(|p| p.strip_suffix(JOURNAL_SUFFIX))
+                .map(PathBuf::from)
+            else {
+                continue;
+            };
+            Self::discard_interrupted(&entry_path);
+        }
+        Ok(())
+    }
+
     /// Compute SHA256 hash of the boxlite-guest binary.
     ///
     /// Uses compile-time hash (embedded by build.rs) when available,
     /// falling back to runtime computation.
-    fn guest_binary_hash() -> BoxliteResult<String> {
+    pub(crate) fn guest_binary_hash() -> BoxliteResult<String> {
         // Fast path: use compile-time hash embedded by build.rs
         if let Some(hash) = option_env!("BOXLITE_GUEST_HASH") {
             tracing::info!(
@@ -412,51 +700,26 @@ impl GuestRootfsManager {
         }
 
         let guest_bin = util::find_binary("boxlite-guest")?;
-        Self::sha256_file(&guest_bin)
-    }
-
-    /// Compute SHA256 hex digest of a file.
-    fn sha256_file(path: &Path) -> BoxliteResult<String> {
-        use sha2::{Digest, Sha256};
-        use std::io::Read;
-
-        let start = std::time::Instant::now();
-        let mut file = fs::File::open(path).map_err(|e| {
-            BoxliteError::Storage(format!("Failed to open {}: {}", path.display(), e))
-        })?;
-
-        let file_size = file.metadata().map(|m| m.len()).unwrap_or(0);
-
-        let mut hasher = Sha256::new();
-        let mut buffer = vec![0u8; 64 * 1024];
-        loop {
-            let n = file.read(&mut buffer).map_err(|e| {
-                BoxliteError::Storage(format!("Failed to read {}: {}", path.display(), e))
-            })?;
-            if n == 0 {
-                break;
-            }
-            hasher.update(&buffer[..n]);
-        }
-
-        let hash = format!("{:x}", hasher.finalize());
-        tracing::info!(
-            path = %path.display(),
-            size_mb = file_size / (1024 * 1024),
-            elapsed_ms = start.elapsed().as_millis() as u64,
-            hash_prefix = &hash[..12.min(hash.len())],
-            "sha256_file computed"
-        );
-
-        Ok(hash)
+        util::checksum::sha256_file(&guest_bin)
     }
 
     /// Compute the version key from image digest and guest binary hash.
     fn version_key(digest: &str, guest_hash: &str) -> String {
-        let d = digest.strip_prefix("sha256:").unwrap_or(digest);
-        let d = &d[..12.min(d.len())];
         let g = &guest_hash[..12.min(guest_hash.len())];
-        format!("{}-{}", d, g)
+        format!("{}-{}", Self::image_prefix(digest), g)
+    }
+
+    /// Image part of the version key: first 12 hex chars of the digest.
+    fn image_prefix(digest: &str) -> &str {
+        let d = digest.strip_prefix("sha256:").unwrap_or(digest);
+        &d[..12.min(d.len())]
+    }
+
+    /// Image part of a cache entry filename (`{image}-{guest}.ext4`).
+    fn entry_image_prefix(filename: &str) -> &str {
+        filename
+            .rsplit_once('-')
+            .map_or(filename, |(image, _)| image)
     }
 
     /// Compute the cache path for a given version key.
@@ -674,6 +937,116 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_discards_interrupted_entry() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mgr = GuestRootfsManager::new(dir.path().to_path_buf(), dir.path().to_path_buf());
+
+        let entry = dir.path().join("img123-guest1.ext4");
+        std::fs::write(&entry, "half written").unwrap();
+        let journal = GuestRootfsManager::journal_path(&entry);
+        std::fs::write(&journal, "img123-guest2").unwrap();
+
+        assert!(mgr.find("img123-guest1").is_none());
+        assert!(!entry.exists());
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn test_gc_recovers_interrupted_reinjection() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = dir.path().join("rootfs");
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let mgr = GuestRootfsManager::new(cache_dir.clone(), dir.path().to_path_buf());
+
+        // Current-version entry with a journal: interrupted, must not be kept
+        let entry = cache_dir.join("img123-currentguest.ext4");
+        std::fs::write(&entry, "half written").unwrap();
+        let journal = GuestRootfsManager::journal_path(&entry);
+        std::fs::write(&journal, "img123-currentguest").unwrap();
+
+        mgr.gc_with_suffix(&dir.path().join("boxes"), "-currentguest.ext4")
+            .unwrap();
+        assert!(!entry.exists());
+        assert!(!journal.exists());
+    }
+
+    #[test]
+    fn test_gc_in_place_keeps_newest_donor_per_image() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = dir.path().join("rootfs");
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let mgr = GuestRootfsManager::new(cache_dir.clone(), dir.path().to_path_buf())
+            .with_update_mode(GuestUpdateMode::InPlace);
+
+        let older = cache_dir.join("img123-oldguest1.ext4");
+        let newer = cache_dir.join("img123-oldguest2.ext4");
+        std::fs::write(&older, "old").unwrap();
+        std::fs::write(&newer, "newer").unwrap();
+        let past = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        std::fs::File::options()
+            .write(true)
+            .open(&older)
+            .unwrap()
+            .set_modified(past)
+            .unwrap();
+
+        // Image with a current entry needs no donor
+        let current = cache_dir.join("img456-currentguest.ext4");
+        let superseded = cache_dir.join("img456-oldguest1.ext4");
+        std::fs::write(&current, "current").unwrap();
+        std::fs::write(&superseded, "old").unwrap();
+
+        let removed = mgr
+            .gc_with_suffix(&dir.path().join("boxes"), "-currentguest.ext4")
+            .unwrap();
+        assert_eq!(removed, 2);
+        assert!(newer.exists(), "Newest stale entry should be kept as donor");
+        assert!(!older.exists());
+        assert!(current.exists());
+        assert!(!superseded.exists());
+    }
+
+    #[test]
+    fn test_find_donor_matches_image_and_skips_journaled() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mgr = GuestRootfsManager::new(dir.path().to_path_buf(), dir.path().to_path_buf());
+        let digest = "sha256:abcdef1234567890";
+        let boxes_dir = dir.path().join("boxes");
+
+        // Other image: never a donor
+        std::fs::write(dir.path().join("fedcba987654-oldguest.ext4"), "").unwrap();
+        assert_eq!(
+            mgr.find_donor(digest, "abcdef123456-newguest", &boxes_dir)
+                .unwrap(),
+            None
+        );
+
+        let donor = dir.path().join("abcdef123456-oldguest.ext4");
+        std::fs::write(&donor, "").unwrap();
+        assert_eq!(
+            mgr.find_donor(digest, "abcdef123456-newguest", &boxes_dir)
+                .unwrap(),
+            Some(donor.clone())
+        );
+
+        std::fs::write(GuestRootfsManager::journal_path(&donor), "").unwrap();
+        assert_eq!(
+            mgr.find_donor(digest, "abcdef123456-newguest", &boxes_dir)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_entry_image_prefix() {
+        assert_eq!(
+            GuestRootfsManager::entry_image_prefix("abcdef123456-fedcba987654.ext4"),
+            "abcdef123456"
+        );
+        assert_eq!(GuestRootfsManager::entry_image_prefix("nodash"), "nodash");
+    }
+
     #[test]
     fn test_gc_no_cache_dir() {
         let dir = tempfile::TempDir::new().unwrap();
@@ -698,4 +1071,68 @@ mod tests {
             .unwrap();
         assert_eq!(removed, 1);
     }
+
+    #[test]
+    fn test_gc_skips_and_cleans_use_markers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mgr = GuestRootfsManager::new(dir.path().to_path_buf(), dir.path().to_path_buf());
+        let current = dir.path().join("img-currentguest.ext4");
+        let stale = dir.path().join("img-oldguest.ext4");
+        std::fs::write(&current, "").unwrap();
+        std::fs::write(&stale, "").unwrap();
+        cache::touch(&current);
+        cache::touch(&stale);
+
+        let removed = mgr
+            .gc_with_suffix(&dir.path().join("boxes"), "-currentguest.ext4")
+            .unwrap();
+        assert_eq!(removed, 1, "markers are not entries");
+        assert!(dir.path().join("img-currentguest.ext4.used").exists());
+        assert!(!dir.path().join("img-oldguest.ext4.used").exists());
+    }
+
+    #[test]
+    fn test_evict_keeps_referenced_entries() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_dir = dir.path().join("rootfs");
+        let boxes_dir = dir.path().join("boxes");
+        std::fs::create_dir_all(&cache_dir).unwrap();
+        let mgr = GuestRootfsManager::new(cache_dir.clone(), dir.path().to_path_buf())
+            .with_cap(Some(1), vec![boxes_dir.clone()]);
+
+        let two_hours_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(7200);
+        let referenced = cache_dir.join("img123-guest.ext4");
+        let unreferenced = cache_dir.join("img456-guest.ext4");
+        for path in [&referenced, &unreferenced] {
+            std::fs::write(path, vec![1u8; 8192]).unwrap();
+            std::fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(two_hours_ago)
+                .unwrap();
+        }
+
+        // A box is still backed by the referenced entry
+        let box_dir = boxes_dir.join("box-1");
+        std::fs::create_dir_all(&box_dir).unwrap();
+        let backing = referenced.to_str().unwrap().as_bytes();
+        let mut buf = vec![0u8; 1024];
+        buf[0..4].copy_from_slice(&0x514649fbu32.to_be_bytes());
+        buf[4..8].copy_from_slice(&3u32.to_be_bytes());
+        buf[8..16].copy_from_slice(&512u64.to_be_bytes());
+        buf[16..20].copy_from_slice(&(backing.len() as u32).to_be_bytes());
+        buf[512..512 + backing.len()].copy_from_slice(backing);
+        std::fs::write(box_dir.join("guest-rootfs.qcow2"), &buf).unwrap();
+
+        assert_eq!(mgr.evict().unwrap(), 1);
+        assert!(referenced.exists(), "Referenced entry is never evicted");
+        assert!(!unreferenced.exists());
+
+        let stats = mgr.stats().unwrap();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.referenced_entries, 1);
+        assert_eq!(stats.reclaimable_bytes, 0);
+        assert_eq!(stats.evicted_entries, 1);
+    }
 }
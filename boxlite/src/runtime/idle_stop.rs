@@ -0,0 +1,69 @@
+//! Idle auto-stop of the boxes this process owns (see
+//! `BoxOptions::auto_stop_after_idle`).
+//!
+//! Each watched box gets a task that samples its activity and stops it once
+//! idle for its window. Boxes no process owns are watched by their shim.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::runtime::types::BoxID;
+use crate::vmm::{IdlePolicy, IdleTracker, idle};
+
+use super::rt_impl::RuntimeImpl;
+
+impl RuntimeImpl {
+    /// Watch `box_id` while this process owns it, stopping it once idle
+    /// for `policy.window`.
+    ///
+    /// No-op outside a Tokio runtime or when the box is watched already.
+    /// The watch ends when the box stops or is released, when its last
+    /// handle is dropped, or on runtime shutdown.
+    pub(crate) fn watch_idle(self: &Arc<Self>, box_id: &BoxID, policy: IdlePolicy) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        if !self.idle_watches.lock().unwrap().insert(box_id.clone()) {
+            return;
+        }
+
+        let runtime = Arc::downgrade(self);
+        let shutdown = self.shutdown_token.clone();
+        let box_id = box_id.clone();
+        handle.spawn(async move {
+            let mut tracker = IdleTracker::new(policy, Instant::now());
+            loop {
+                tokio::select! {
+                    _ = tokio::time::sleep(policy.sample_interval()) => {}
+                    _ = shutdown.cancelled() => break,
+                }
+                // Only a live handle keeps an owned box running
+                let Some(box_impl) = runtime.upgrade().and_then(|rt| rt.active_box_impl(&box_id))
+                else {
+                    break;
+                };
+                if !box_impl.state.read().status.is_running() || !box_impl.is_owned_here() {
+                    break;
+                }
+
+                let sample = box_impl.idle_sample().await;
+                let now = Instant::now();
+                let remaining = tracker.observe(now, sample.as_ref());
+                let idle = tracker.idle_for(now);
+                if let Err(e) = idle::record_last_active(&box_impl.config.box_home, idle) {
+                    tracing::debug!(box_id = %box_id, error = %e, "Failed to record idle time");
+                }
+                if remaining.is_zero() {
+                    if let Err(e) = box_impl.stop_idle(idle).await {
+                        tracing::warn!(box_id = %box_id, error = %e, "Failed to stop idle box");
+                    }
+                    break;
+                }
+            }
+
+            if let Some(rt) = runtime.upgrade() {
+                rt.idle_watches.lock().unwrap().remove(&box_id);
+            }
+        });
+    }
+}
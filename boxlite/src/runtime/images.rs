@@ -8,7 +8,11 @@ use async_trait::async_trait;
 use std::sync::Arc;
 
 use crate::BoxliteResult;
-use crate::images::ImageObject;
+use crate::disk::CacheUsage;
+use crate::images::built::BuiltImageStore;
+use crate::images::{
+    ImageObject, PullProgress, PullProgressFn, RegistryStatus, VulnerabilityReport,
+};
 use crate::runtime::types::ImageInfo;
 
 /// Internal trait for image management.
@@ -17,11 +21,30 @@ use crate::runtime::types::ImageInfo;
 /// Currently only `LocalRuntime` implements this trait; REST runtime does not.
 #[async_trait]
 pub(crate) trait ImageManager: Send + Sync {
-    /// Pull an image from a registry.
-    async fn pull_image(&self, image_ref: &str) -> BoxliteResult<ImageObject>;
+    /// Pull an image from a registry, optionally reporting layer progress.
+    async fn pull_image(
+        &self,
+        image_ref: &str,
+        progress: Option<PullProgressFn>,
+    ) -> BoxliteResult<ImageObject>;
 
     /// List all locally cached images.
     async fn list_images(&self) -> BoxliteResult<Vec<ImageInfo>>;
+
+    /// Fetch the vulnerability report attached to `image` in its registry.
+    async fn vulnerability_report(
+        &self,
+        image: &ImageObject,
+    ) -> BoxliteResult<Option<VulnerabilityReport>>;
+
+    /// Health of the registries pulls have used or are configured with.
+    fn registry_status(&self) -> Vec<RegistryStatus>;
+
+    /// Usage of the image disk and guest rootfs caches.
+    fn cache_usage(&self) -> BoxliteResult<CacheUsage>;
+
+    /// Store of the images made by `BoxliteRuntime::build_image`.
+    fn built_images(&self) -> BuiltImageStore;
 }
 
 /// Handle for performing image operations.
@@ -81,7 +104,44 @@ impl ImageHandle {
     /// # }
     /// ```
     pub async fn pull(&self, image_ref: &str) -> BoxliteResult<ImageObject> {
-        self.manager.pull_image(image_ref).await
+        self.manager.pull_image(image_ref, None).await
+    }
+
+    /// Pull an image from a registry, reporting layer download progress.
+    ///
+    /// `progress` is called as each layer starts and as its bytes arrive.
+    /// A layer resumed from an interrupted earlier pull reports the reused
+    /// bytes in [`PullProgress::resumed`].
+    ///
+    /// Dropping the returned future cancels the pull cleanly: in-flight
+    /// requests are aborted and partial layers are kept for the next pull.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use boxlite::{Boxlite, Options};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let runtime = Boxlite::new(Options::default())?;
+    /// let images = runtime.images()?;
+    /// let image = images
+    ///     .pull_with_progress("alpine:latest", |p| {
+    ///         if p.resumed > 0 && p.downloaded == p.resumed {
+    ///             println!("{}: resuming at {}%", p.digest, p.resumed_percent().unwrap_or(0));
+    ///         }
+    ///     })
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn pull_with_progress(
+        &self,
+        image_ref: &str,
+        progress: impl Fn(&PullProgress) + Send + Sync + 'static,
+    ) -> BoxliteResult<ImageObject> {
+        self.manager
+            .pull_image(image_ref, Some(std::sync::Arc::new(progress)))
+            .await
     }
 
     /// List all locally cached images.
@@ -0,0 +1,160 @@
+//! Admission of mutating runtime operations around shutdown.
+//!
+//! Every mutating runtime operation holds an [`InFlightGuard`] while it runs.
+//! Shutdown closes admission first, so an operation either got its guard
+//! before shutdown began (and shutdown waits for it, up to the shutdown
+//! timeout) or fails fast with [`BoxliteError::ShuttingDown`]. Operations
+//! still running when the timeout expires are aborted at their next
+//! cancellation point.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+/// Counts mutating operations in flight and gates new ones.
+#[derive(Default)]
+pub(crate) struct InFlightOps {
+    closed: AtomicBool,
+    count: AtomicUsize,
+    idle: Notify,
+    abort: CancellationToken,
+}
+
+impl InFlightOps {
+    /// Admit one `operation` (e.g. "create box"), or fail with
+    /// `ShuttingDown` once admission is closed.
+    pub(crate) fn enter(&self, operation: &str) -> BoxliteResult<InFlightGuard<'_>> {
+        // Count first, then check: `close` sets the flag before reading the
+        // count, so either shutdown sees this operation or it sees the flag.
+        self.count.fetch_add(1, Ordering::SeqCst);
+        let guard = InFlightGuard(self);
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(shutting_down(operation));
+        }
+        Ok(guard)
+    }
+
+    /// Stop admitting operations.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+
+    /// Wait until no operation is in flight, for at most `timeout`
+    /// (None: forever). Returns false on timeout.
+    pub(crate) async fn drain(&self, timeout: Option<Duration>) -> bool {
+        let idle = async {
+            loop {
+                let notified = self.idle.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+                if self.count.load(Ordering::SeqCst) == 0 {
+                    return;
+                }
+                notified.await;
+            }
+        };
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, idle).await.is_ok(),
+            None => {
+                idle.await;
+                true
+            }
+        }
+    }
+
+    /// Cancel operations still in flight at their next cancellation point.
+    pub(crate) fn abort(&self) {
+        self.abort.cancel();
+    }
+}
+
+/// Marks one operation as in flight until dropped.
+pub(crate) struct InFlightGuard<'a>(&'a InFlightOps);
+
+impl InFlightGuard<'_> {
+    /// Run a long step of the operation, failing with `ShuttingDown` if
+    /// shutdown aborts in-flight operations first.
+    pub(crate) async fn cancellable<T>(
+        &self,
+        operation: &str,
+        step: impl Future<Output = BoxliteResult<T>>,
+    ) -> BoxliteResult<T> {
+        tokio::select! {
+            result = step => result,
+            _ = self.0.abort.cancelled() => Err(shutting_down(operation)),
+        }
+    }
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        if self.0.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
+}
+
+fn shutting_down(operation: &str) -> BoxliteError {
+    BoxliteError::ShuttingDown(format!("cannot {}", operation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enter_fails_once_closed() {
+        let ops = InFlightOps::default();
+        assert!(ops.enter("create box").is_ok());
+
+        ops.close();
+        let err = ops.enter("create box").err().unwrap();
+        assert!(matches!(err, BoxliteError::ShuttingDown(ref m) if m == "cannot create box"));
+        assert_eq!(ops.count.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn drain_waits_for_admitted_operations() {
+        let ops = std::sync::Arc::new(InFlightOps::default());
+        let (admitted_tx, admitted_rx) = tokio::sync::oneshot::channel();
+        let op = {
+            let ops = std::sync::Arc::clone(&ops);
+            tokio::spawn(async move {
+                let _guard = ops.enter("remove box").unwrap();
+                admitted_tx.send(()).unwrap();
+                tokio::time::sleep(Duration::from_millis(50)).await;
+            })
+        };
+        admitted_rx.await.unwrap();
+
+        ops.close();
+        assert!(ops.drain(Some(Duration::from_secs(5))).await);
+        assert!(op.is_finished());
+    }
+
+    #[tokio::test]
+    async fn drain_times_out_and_abort_cancels() {
+        let ops = InFlightOps::default();
+        let guard = ops.enter("create box").unwrap();
+
+        ops.close();
+        assert!(!ops.drain(Some(Duration::from_millis(20))).await);
+
+        ops.abort();
+        let result = guard
+            .cancellable("create box", std::future::pending::<BoxliteResult<()>>())
+            .await;
+        assert!(matches!(result, Err(BoxliteError::ShuttingDown(_))));
+    }
+
+    #[tokio::test]
+    async fn drain_returns_immediately_when_idle() {
+        let ops = InFlightOps::default();
+        ops.close();
+        assert!(ops.drain(Some(Duration::ZERO)).await);
+    }
+}
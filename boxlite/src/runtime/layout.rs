@@ -41,6 +41,9 @@ pub mod dirs {
 
     /// Subdirectory for per-entity locks
     pub const LOCKS_DIR: &str = "locks";
+
+    /// Subdirectory for soft-deleted box homes
+    pub const TRASH_DIR: &str = "trash";
 }
 
 /// Configuration for filesystem layout behavior.
@@ -123,6 +126,22 @@ impl FilesystemLayout {
         self.home_dir.join(dirs::BOXES_DIR)
     }
 
+    /// Soft-deleted box homes: ~/.boxlite/trash
+    ///
+    /// Each trashed box's home is moved to `trash/{box_id}` until it is
+    /// restored or purged. Lives under the home dir so the move is a rename.
+    pub fn trash_dir(&self) -> PathBuf {
+        self.home_dir.join(dirs::TRASH_DIR)
+    }
+
+    /// Directories holding box homes: `boxes_dir()` and `trash_dir()`.
+    ///
+    /// Trashed boxes can be restored, so their disks count wherever live
+    /// ones do.
+    pub fn box_roots(&self) -> Vec<PathBuf> {
+        vec![self.boxes_dir(), self.trash_dir()]
+    }
+
     /// Per-entity locks directory: ~/.boxlite/locks
     ///
     /// Contains lock files managed by FileLockManager for multiprocess-safe
@@ -156,6 +175,9 @@ impl FilesystemLayout {
         std::fs::create_dir_all(self.boxes_dir())
             .map_err(|e| BoxliteError::Storage(format!("failed to create boxes dir: {e}")))?;
 
+        std::fs::create_dir_all(self.trash_dir())
+            .map_err(|e| BoxliteError::Storage(format!("failed to create trash dir: {e}")))?;
+
         std::fs::create_dir_all(self.temp_dir())
             .map_err(|e| BoxliteError::Storage(format!("failed to create temp dir: {e}")))?;
 
@@ -287,6 +309,8 @@ impl FilesystemLayout {
 /// │           │   └── work/   # Overlayfs work
 /// │           └── rootfs/     # Final rootfs (overlayfs merged)
 /// ├── shared/             # Guest-visible (ro bind mount → mounts/)
+/// ├── execs/              # Output of detached executions (rw virtio-fs)
+/// │   └── {exec_id}/{stdout,stderr}
 /// ├── logs/               # Per-box logging
 /// │   ├── boxlite-shim.log  # Shim tracing output
 /// │   └── console.log       # Kernel/init output
@@ -351,6 +375,15 @@ impl BoxFilesystemLayout {
         self.sockets_dir().join("net.sock")
     }
 
+    /// Every socket a running box binds under its home.
+    pub fn socket_paths(&self) -> Vec<PathBuf> {
+        vec![
+            self.socket_path(),
+            self.ready_socket_path(),
+            self.net_backend_socket_path(),
+        ]
+    }
+
     // ========================================================================
     // MOUNTS AND SHARED
     // ========================================================================
@@ -393,6 +426,14 @@ impl BoxFilesystemLayout {
         self.box_dir.join(shared_dirs::SHARED)
     }
 
+    /// Detached execution output: ~/.boxlite/boxes/{box_id}/execs
+    ///
+    /// Exposed read-write to the guest, which writes
+    /// `{exec_id}/stdout` and `{exec_id}/stderr` here for detached execs.
+    pub fn execs_dir(&self) -> PathBuf {
+        self.box_dir.join(shared_dirs::EXECS)
+    }
+
     // ========================================================================
     // SNAPSHOTS
     // ========================================================================
@@ -485,6 +526,13 @@ impl BoxFilesystemLayout {
         self.box_dir.join("exit")
     }
 
+    /// Crash diagnostics path: ~/.boxlite/boxes/{box_id}/exit.diagnostics
+    ///
+    /// Written by the shim next to the exit file on panics and fatal signals.
+    pub fn exit_diagnostics_path(&self) -> PathBuf {
+        crate::vmm::crash_diagnostics::diagnostics_path(&self.exit_file_path())
+    }
+
     /// Stderr file path: ~/.boxlite/boxes/{box_id}/shim.stderr
     ///
     /// Captures libkrun stderr output for crash diagnostics.
@@ -515,6 +563,9 @@ impl BoxFilesystemLayout {
         std::fs::create_dir_all(self.mounts_dir())
             .map_err(|e| BoxliteError::Storage(format!("failed to create mounts dir: {e}")))?;
 
+        std::fs::create_dir_all(self.execs_dir())
+            .map_err(|e| BoxliteError::Storage(format!("failed to create execs dir: {e}")))?;
+
         // shared/ is created by create_bind_mount() - don't create it here
         // On Linux: bind mount from mounts/
         // On macOS: symlink to mounts/
@@ -844,6 +895,15 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_box_layout_execs_dir() {
+        let layout = test_box_layout("/home/.boxlite/boxes/mybox");
+        assert_eq!(
+            layout.execs_dir(),
+            PathBuf::from("/home/.boxlite/boxes/mybox/execs")
+        );
+    }
+
     #[test]
     fn test_box_layout_bin_dir() {
         let layout = test_box_layout("/home/.boxlite/boxes/mybox");
@@ -0,0 +1,259 @@
+//! Guest time zone and locale (`BoxOptions::timezone` / `locale`).
+//!
+//! Time zones are IANA names looked up in the tzdb bundled with the runtime,
+//! so they work in images without tzdata; the guest places the zone file at
+//! `/etc/localtime`. [`HOST_TIMEZONE`] stands for the host's current zone,
+//! resolved at each start. Both also become `TZ` / `LANG` defaults of the
+//! container environment, which the box's `env` and each exec's env override.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// `timezone` value that copies the host's current zone.
+pub(crate) const HOST_TIMEZONE: &str = "host";
+
+/// Most suggestions listed for a misspelled name.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Locales suggested for a malformed `locale`.
+const COMMON_LOCALES: &[&str] = &[
+    "C",
+    "C.UTF-8",
+    "POSIX",
+    "de_DE.UTF-8",
+    "en_AU.UTF-8",
+    "en_CA.UTF-8",
+    "en_GB.UTF-8",
+    "en_US.UTF-8",
+    "es_ES.UTF-8",
+    "fr_FR.UTF-8",
+    "it_IT.UTF-8",
+    "ja_JP.UTF-8",
+    "ko_KR.UTF-8",
+    "nl_NL.UTF-8",
+    "pt_BR.UTF-8",
+    "ru_RU.UTF-8",
+    "sv_SE.UTF-8",
+    "zh_CN.UTF-8",
+    "zh_TW.UTF-8",
+];
+
+/// A time zone from the bundled tzdb.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct TimeZone {
+    /// Canonical IANA name, e.g. `Europe/Berlin`.
+    pub name: String,
+    /// The zone's TZif file.
+    pub tzif: &'static [u8],
+}
+
+/// Resolve a `timezone` option: an IANA name (any case) or [`HOST_TIMEZONE`].
+///
+/// Unknown names fail with `InvalidArgument` listing close matches.
+pub(crate) fn resolve_timezone(zone: &str) -> BoxliteResult<TimeZone> {
+    if zone == HOST_TIMEZONE {
+        let host = iana_time_zone::get_timezone().map_err(|e| {
+            BoxliteError::InvalidArgument(format!(
+                "timezone '{}': cannot determine the host's time zone: {}",
+                HOST_TIMEZONE, e
+            ))
+        })?;
+        return lookup_timezone(&host).ok_or_else(|| {
+            BoxliteError::InvalidArgument(format!(
+                "timezone '{}': the host's time zone '{}' is not in the IANA tzdb",
+                HOST_TIMEZONE, host
+            ))
+        });
+    }
+
+    lookup_timezone(zone).ok_or_else(|| {
+        let suggestions = close_matches(zone, tzdb_data::TZ_NAMES.iter().copied(), true);
+        BoxliteError::InvalidArgument(format!(
+            "unknown timezone '{}'{}",
+            zone,
+            did_you_mean(
+                &suggestions,
+                "an IANA name such as Europe/Berlin, or 'host'"
+            )
+        ))
+    })
+}
+
+/// Check a `timezone` option; [`HOST_TIMEZONE`] is only resolved at start.
+pub(crate) fn validate_timezone(zone: &str) -> BoxliteResult<()> {
+    if zone != HOST_TIMEZONE {
+        resolve_timezone(zone)?;
+    }
+    Ok(())
+}
+
+/// Check a `locale` option, e.g. `en_US.UTF-8`, `C.UTF-8` or `POSIX`.
+///
+/// Only the name is checked: the image must ship the locale's data for it
+/// to take effect (glibc); musl images accept any name.
+pub(crate) fn validate_locale(locale: &str) -> BoxliteResult<()> {
+    if is_locale_name(locale) {
+        return Ok(());
+    }
+    let suggestions = close_matches(locale, COMMON_LOCALES.iter().copied(), false);
+    Err(BoxliteError::InvalidArgument(format!(
+        "invalid locale '{}'{}",
+        locale,
+        did_you_mean(&suggestions, "a name like en_US.UTF-8 or C.UTF-8")
+    )))
+}
+
+/// `TZ` and `LANG` defaults for the container environment.
+pub(crate) fn env_defaults(
+    time_zone: Option<&TimeZone>,
+    locale: Option<&str>,
+) -> Vec<(String, String)> {
+    let mut env = Vec::new();
+    if let Some(time_zone) = time_zone {
+        env.push(("TZ".to_string(), time_zone.name.clone()));
+    }
+    if let Some(locale) = locale {
+        env.push(("LANG".to_string(), locale.to_string()));
+    }
+    env
+}
+
+fn lookup_timezone(name: &str) -> Option<TimeZone> {
+    let canonical = tzdb_data::TZ_NAMES
+        .iter()
+        .find(|candidate| candidate.eq_ignore_ascii_case(name))?;
+    let tzif = tzdb_data::find_raw(canonical.as_bytes())?;
+    Some(TimeZone {
+        name: canonical.to_string(),
+        tzif,
+    })
+}
+
+/// `language[_TERRITORY][.codeset][@modifier]`, or `C` / `POSIX`.
+fn is_locale_name(locale: &str) -> bool {
+    let (rest, modifier) = match locale.split_once('@') {
+        Some((rest, modifier)) => (rest, Some(modifier)),
+        None => (locale, None),
+    };
+    let (name, codeset) = match rest.split_once('.') {
+        Some((name, codeset)) => (name, Some(codeset)),
+        None => (rest, None),
+    };
+    let (language, territory) = match name.split_once('_') {
+        Some((language, territory)) => (language, Some(territory)),
+        None => (name, None),
+    };
+
+    let language_ok = matches!(language, "C" | "POSIX")
+        || ((2..=3).contains(&language.len()) && language.bytes().all(|b| b.is_ascii_lowercase()));
+    let territory_ok = territory.is_none_or(|t| {
+        (t.len() == 2 && t.bytes().all(|b| b.is_ascii_uppercase()))
+            || (t.len() == 3 && t.bytes().all(|b| b.is_ascii_digit()))
+    });
+    let codeset_ok = codeset
+        .is_none_or(|c| !c.is_empty() && c.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'));
+    let modifier_ok =
+        modifier.is_none_or(|m| !m.is_empty() && m.bytes().all(|b| b.is_ascii_alphanumeric()));
+
+    language_ok && territory_ok && codeset_ok && modifier_ok
+}
+
+/// Up to [`MAX_SUGGESTIONS`] candidates closest to `input`, ignoring case.
+///
+/// With `by_last_component`, `Berlin` also matches `Europe/Berlin`.
+fn close_matches<'a>(
+    input: &str,
+    candidates: impl Iterator<Item = &'a str>,
+    by_last_component: bool,
+) -> Vec<&'a str> {
+    let input = input.to_ascii_lowercase();
+    let max_distance = (input.len() / 3).max(2);
+    let mut scored: Vec<(usize, &str)> = candidates
+        .filter_map(|candidate| {
+            let lower = candidate.to_ascii_lowercase();
+            let mut distance = strsim::levenshtein(&input, &lower);
+            if by_last_component && let Some((_, last)) = lower.rsplit_once('/') {
+                distance = distance.min(strsim::levenshtein(&input, last));
+            }
+            (distance <= max_distance).then_some((distance, candidate))
+        })
+        .collect();
+    scored.sort();
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+fn did_you_mean(suggestions: &[&str], otherwise: &str) -> String {
+    if suggestions.is_empty() {
+        format!("; expected {}", otherwise)
+    } else {
+        format!("; did you mean {}?", suggestions.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_timezone_canonicalizes_case() {
+        let zone = resolve_timezone("europe/berlin").unwrap();
+        assert_eq!(zone.name, "Europe/Berlin");
+        assert!(zone.tzif.starts_with(b"TZif"));
+        assert_eq!(resolve_timezone("UTC").unwrap().name, "UTC");
+    }
+
+    #[test]
+    fn test_resolve_timezone_suggests_close_matches() {
+        let err = resolve_timezone("Europe/Berln").unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidArgument(_)));
+        assert!(
+            err.to_string().contains("did you mean Europe/Berlin"),
+            "{err}"
+        );
+
+        let err = resolve_timezone("Berlin").unwrap_err().to_string();
+        assert!(err.contains("Europe/Berlin"), "{err}");
+
+        let err = resolve_timezone("Xyzzy/Qwerty").unwrap_err().to_string();
+        assert!(err.contains("expected an IANA name"), "{err}");
+    }
+
+    #[test]
+    fn test_validate_locale() {
+        for ok in [
+            "C",
+            "POSIX",
+            "C.UTF-8",
+            "en_US.UTF-8",
+            "de_DE",
+            "es_419.UTF-8",
+            "sr_RS@latin",
+        ] {
+            assert!(validate_locale(ok).is_ok(), "{ok}");
+        }
+        for bad in ["", "en-US", "EN_us", "en_US.", "en_US.UTF 8", "LANG=C"] {
+            assert!(
+                matches!(validate_locale(bad), Err(BoxliteError::InvalidArgument(_))),
+                "{bad}"
+            );
+        }
+        let err = validate_locale("en_us.UTF-8").unwrap_err().to_string();
+        assert!(err.contains("en_US.UTF-8"), "{err}");
+    }
+
+    #[test]
+    fn test_env_defaults() {
+        let zone = resolve_timezone("Asia/Tokyo").unwrap();
+        assert_eq!(
+            env_defaults(Some(&zone), Some("ja_JP.UTF-8")),
+            vec![
+                ("TZ".to_string(), "Asia/Tokyo".to_string()),
+                ("LANG".to_string(), "ja_JP.UTF-8".to_string()),
+            ]
+        );
+        assert!(env_defaults(None, None).is_empty());
+    }
+}
@@ -2,12 +2,26 @@
 //!
 //! Uses file locking (flock/fcntl) to ensure only one BoxliteRuntime can access
 //! a given BOXLITE_HOME directory at a time.
+//!
+//! The flock alone decides who owns the home: the kernel releases it when
+//! the last fd of the holder closes, so a lock is stale exactly when
+//! `flock` succeeds. The holder also writes its PID and process start time
+//! into the lock file, for diagnostics only. A PID check can't tell that a
+//! lock is free: the holder may live in another PID namespace, or a child may
+//! have inherited the fd after the recorded process exited.
 
 use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
+use crate::util::{is_process_alive, process_start_time};
+
+/// Interval between attempts while waiting for the lock.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// A lock guard that holds an exclusive lock on the runtime directory.
 ///
 /// The lock is automatically released when this guard is dropped,
@@ -19,6 +33,62 @@ pub struct RuntimeLock {
     path: PathBuf,
 }
 
+/// Process recorded in the lock file as holding the lock.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct LockHolder {
+    pid: u32,
+    /// `None` where the start time can't be read.
+    start_time: Option<u64>,
+}
+
+impl LockHolder {
+    fn current() -> Self {
+        let pid = std::process::id();
+        Self {
+            pid,
+            start_time: process_start_time(pid),
+        }
+    }
+
+    /// Parse a `"<pid> <start_time>"` record; `-` for an unknown start time.
+    fn parse(record: &str) -> Option<Self> {
+        let mut fields = record.split_whitespace();
+        let pid = fields.next()?.parse().ok()?;
+        let start_time = match fields.next()? {
+            "-" => None,
+            start => Some(start.parse().ok()?),
+        };
+        Some(Self { pid, start_time })
+    }
+
+    fn record(&self) -> String {
+        match self.start_time {
+            Some(start) => format!("{} {}\n", self.pid, start),
+            None => format!("{} -\n", self.pid),
+        }
+    }
+
+    /// Whether the recorded process still runs, as seen from this PID
+    /// namespace. A start time that can't be compared counts as a match.
+    fn is_alive(&self) -> bool {
+        if !is_process_alive(self.pid) {
+            return false;
+        }
+        match (self.start_time, process_start_time(self.pid)) {
+            (Some(recorded), Some(current)) => recorded == current,
+            _ => true,
+        }
+    }
+}
+
+/// Outcome of one attempt to take the lock.
+enum Attempt {
+    Acquired(RuntimeLock),
+    /// Another open file holds the lock; `None` if its holder wrote no
+    /// readable record.
+    Held(Option<LockHolder>),
+}
+
 impl RuntimeLock {
     /// Attempt to acquire an exclusive lock on the runtime directory.
     ///
@@ -39,18 +109,56 @@ impl RuntimeLock {
     /// # Ok::<(), boxlite_runtime::errors::BoxliteError>(())
     /// ```
     pub fn acquire(home_dir: &Path) -> BoxliteResult<Self> {
+        Self::acquire_waiting(home_dir, None)
+    }
+
+    /// Like [`acquire`](Self::acquire), but while a live runtime holds the
+    /// lock, retry for up to `wait` before failing.
+    pub fn acquire_waiting(home_dir: &Path, wait: Option<Duration>) -> BoxliteResult<Self> {
         // Ensure the directory exists
         std::fs::create_dir_all(home_dir)
             .map_err(|e| BoxliteError::Storage(format!("failed to create home dir: {}", e)))?;
 
         let lock_path = home_dir.join(".lock");
+        let deadline = wait.map(|wait| Instant::now() + wait);
+        let mut logged_wait = false;
+
+        loop {
+            match Self::try_acquire(&lock_path)? {
+                Attempt::Acquired(lock) => return Ok(lock),
+                Attempt::Held(holder) => {
+                    if deadline.is_some_and(|deadline| Instant::now() < deadline) {
+                        if !logged_wait {
+                            tracing::info!(
+                                lock_path = %lock_path.display(),
+                                holder_pid = ?holder.map(|h| h.pid),
+                                "Waiting for runtime lock"
+                            );
+                            logged_wait = true;
+                        }
+                        std::thread::sleep(WAIT_POLL_INTERVAL);
+                        continue;
+                    }
+                    let holder = holder.map(describe_holder).unwrap_or_default();
+                    return Err(BoxliteError::InvalidState(format!(
+                        "Another BoxliteRuntime is already using directory: {}{}\n\
+                         Only one runtime instance can use a BOXLITE_HOME directory at a time.",
+                        home_dir.display(),
+                        holder
+                    )));
+                }
+            }
+        }
+    }
 
+    fn try_acquire(lock_path: &Path) -> BoxliteResult<Attempt> {
         // Open or create the lock file
-        let file = OpenOptions::new()
+        let mut file = OpenOptions::new()
             .create(true)
+            .read(true)
             .write(true)
             .truncate(false)
-            .open(&lock_path)
+            .open(lock_path)
             .map_err(|e| BoxliteError::Storage(format!("failed to open lock file: {}", e)))?;
 
         // Try to acquire exclusive lock (non-blocking)
@@ -63,18 +171,17 @@ impl RuntimeLock {
 
             if result != 0 {
                 let err = std::io::Error::last_os_error();
-                if err.kind() == std::io::ErrorKind::WouldBlock {
-                    return Err(BoxliteError::Internal(format!(
-                        "Another BoxliteRuntime is already using directory: {}\n\
-                         Only one runtime instance can use a BOXLITE_HOME directory at a time.",
-                        home_dir.display()
-                    )));
-                } else {
+                if err.kind() != std::io::ErrorKind::WouldBlock {
                     return Err(BoxliteError::Storage(format!(
                         "failed to acquire lock: {}",
                         err
                     )));
                 }
+
+                // Held by a live fd, whatever the record says
+                let mut record = String::new();
+                let _ = file.read_to_string(&mut record);
+                return Ok(Attempt::Held(LockHolder::parse(&record)));
             }
         }
 
@@ -85,12 +192,25 @@ impl RuntimeLock {
             compile_error!("Windows file locking not yet implemented");
         }
 
+        let mut record = String::new();
+        let _ = file.read_to_string(&mut record);
+        if let Some(previous) = LockHolder::parse(&record) {
+            tracing::debug!(
+                lock_path = %lock_path.display(),
+                previous_pid = previous.pid,
+                "Taking over runtime lock released by its previous holder"
+            );
+        }
+
+        write_record(&mut file, &LockHolder::current())
+            .map_err(|e| BoxliteError::Storage(format!("failed to write lock file: {}", e)))?;
+
         tracing::debug!(lock_path = %lock_path.display(), "Acquired runtime lock");
 
-        Ok(RuntimeLock {
+        Ok(Attempt::Acquired(RuntimeLock {
             file,
-            path: lock_path,
-        })
+            path: lock_path.to_path_buf(),
+        }))
     }
 
     #[allow(dead_code)]
@@ -99,6 +219,28 @@ impl RuntimeLock {
     }
 }
 
+fn write_record(file: &mut File, holder: &LockHolder) -> std::io::Result<()> {
+    file.set_len(0)?;
+    file.rewind()?;
+    file.write_all(holder.record().as_bytes())?;
+    file.sync_data()
+}
+
+/// `" (held by pid N)"` for the lock error, noting a recorded holder that
+/// no longer runs here: the lock then lives on in another PID namespace or
+/// in a process that inherited the fd.
+fn describe_holder(holder: LockHolder) -> String {
+    if holder.is_alive() {
+        format!(" (held by pid {})", holder.pid)
+    } else {
+        format!(
+            " (recorded holder pid {} is not running here; \
+             another process still has the lock file open)",
+            holder.pid
+        )
+    }
+}
+
 impl Drop for RuntimeLock {
     fn drop(&mut self) {
         // Lock is automatically released by OS when file is closed
@@ -199,4 +341,140 @@ mod tests {
 
         assert_eq!(lock.path(), temp_dir.path().join(".lock"));
     }
+
+    /// Hold the lock file at `dir` through a separate open file, as a
+    /// runtime whose flock outlived it would, with `record` as its holder.
+    fn fake_holder(dir: &Path, record: &str) -> File {
+        use std::os::unix::io::AsRawFd;
+
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(dir.join(".lock"))
+            .unwrap();
+        assert_eq!(
+            unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) },
+            0
+        );
+        (&file).write_all(record.as_bytes()).unwrap();
+        file
+    }
+
+    fn dead_pid() -> u32 {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        pid
+    }
+
+    #[test]
+    fn test_holder_record_round_trips() {
+        let holder = LockHolder {
+            pid: 42,
+            start_time: Some(1234),
+        };
+        assert_eq!(LockHolder::parse(&holder.record()), Some(holder));
+        assert_eq!(
+            LockHolder::parse("42 -\n"),
+            Some(LockHolder {
+                pid: 42,
+                start_time: None
+            })
+        );
+        assert_eq!(LockHolder::parse(""), None);
+        assert_eq!(LockHolder::parse("42"), None);
+    }
+
+    #[test]
+    fn test_lock_records_holder() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock = RuntimeLock::acquire(temp_dir.path()).unwrap();
+
+        let record = std::fs::read_to_string(lock.path()).unwrap();
+        assert_eq!(LockHolder::parse(&record), Some(LockHolder::current()));
+    }
+
+    #[test]
+    fn test_held_lock_of_dead_pid_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        // The recorded pid is gone but the fd lives on, e.g. in a child
+        let _holder = fake_holder(temp_dir.path(), &format!("{} 1\n", dead_pid()));
+
+        let err = RuntimeLock::acquire(temp_dir.path()).unwrap_err();
+        assert!(err.to_string().contains("not running here"), "{err}");
+        assert!(temp_dir.path().join(".lock").exists());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_held_lock_of_reused_pid_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let current = LockHolder::current();
+        let reused = LockHolder {
+            pid: current.pid,
+            start_time: current.start_time.map(|start| start + 1),
+        };
+        let _holder = fake_holder(temp_dir.path(), &reused.record());
+
+        assert!(RuntimeLock::acquire(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_takes_over_released_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        // The holder died and the kernel released its flock; only the record is left
+        drop(fake_holder(temp_dir.path(), &format!("{} 1\n", dead_pid())));
+
+        let lock = RuntimeLock::acquire(temp_dir.path()).unwrap();
+        let record = std::fs::read_to_string(lock.path()).unwrap();
+        assert_eq!(LockHolder::parse(&record), Some(LockHolder::current()));
+    }
+
+    #[test]
+    fn test_live_holder_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let _holder = fake_holder(temp_dir.path(), &LockHolder::current().record());
+
+        let err = RuntimeLock::acquire(temp_dir.path()).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("held by pid {}", std::process::id())),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_holder_without_record_is_refused() {
+        let temp_dir = TempDir::new().unwrap();
+        let _holder = fake_holder(temp_dir.path(), "");
+
+        assert!(RuntimeLock::acquire(temp_dir.path()).is_err());
+    }
+
+    #[test]
+    fn test_wait_for_release() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock1 = RuntimeLock::acquire(temp_dir.path()).unwrap();
+        let release = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(300));
+            drop(lock1);
+        });
+
+        let lock2 = RuntimeLock::acquire_waiting(temp_dir.path(), Some(Duration::from_secs(10)));
+        assert!(lock2.is_ok());
+        release.join().unwrap();
+    }
+
+    #[test]
+    fn test_wait_times_out() {
+        let temp_dir = TempDir::new().unwrap();
+        let _lock1 = RuntimeLock::acquire(temp_dir.path()).unwrap();
+
+        let started = Instant::now();
+        let result =
+            RuntimeLock::acquire_waiting(temp_dir.path(), Some(Duration::from_millis(300)));
+        assert!(result.is_err());
+        assert!(started.elapsed() >= Duration::from_millis(300));
+    }
 }
@@ -1,20 +1,55 @@
 pub mod advanced_options;
 pub(crate) mod backend;
+pub mod build;
+pub mod bulk;
+pub mod capabilities;
 pub mod constants;
+pub mod create_progress;
+pub(crate) mod disk_space;
+pub mod drift;
 pub(crate) mod guest_rootfs;
 pub(crate) mod guest_rootfs_manager;
 pub mod images;
+pub(crate) mod in_flight;
 pub mod layout;
+pub(crate) mod locale;
 pub(crate) mod lock;
 pub mod options;
 pub(crate) mod signal_handler;
 pub mod types;
+#[cfg(feature = "url-transfer")]
+pub mod url_transfer;
+pub mod version;
 
+mod box_dns;
+mod compose;
 mod core;
+#[cfg(target_os = "linux")]
+mod cpu_pressure;
+mod idle_stop;
+mod orphans;
+mod ownership;
 pub(crate) mod portability;
+mod reconcile;
 pub(crate) mod rt_impl;
+mod run_once;
+pub(crate) mod seekable;
+pub(crate) mod templates;
+mod trash;
+mod warm_pool;
 
+pub use build::{BuildEvent, BuildObserver, BuildSpec, BuildStep, BuiltImage};
+pub use bulk::{BulkExecResult, BulkResults, StopOptions};
+pub use capabilities::{Capability, CapabilityStatus, Degradation, RuntimeCapabilities};
+pub use compose::{ComposedBox, ReadinessProbe, UpOptions, UpOutcome, UpReport};
 pub use core::BoxliteRuntime;
-pub use portability::ArchiveManifest;
+pub use create_progress::{CreateEvent, CreateObserver, CreatePhase};
+pub use drift::{GetOrCreateOutcome, GetOrCreatePolicy};
 pub use images::ImageHandle;
+pub use orphans::{OrphanReason, OrphanShim};
+pub use portability::{ArchiveEntry, ArchiveManifest, ImportConflict, ImportOptions, ImportPlan};
 pub(crate) use rt_impl::SharedRuntimeImpl;
+pub use run_once::{RunOnceOptions, RunOnceResult};
+pub use templates::BoxOptionsPatch;
+pub use version::VersionInfo;
+pub use warm_pool::WarmSelector;
@@ -2,12 +2,19 @@
 
 use crate::runtime::constants::envs as const_envs;
 use crate::runtime::layout::dirs as const_dirs;
-use boxlite_shared::errors::BoxliteResult;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use dirs::home_dir;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::marker::PhantomData;
 use std::path::PathBuf;
+use std::time::Duration;
+use url::Url;
 
+use crate::litebox::BoxCommand;
+use crate::litebox::snapshot_types::SnapshotRetention;
 use crate::runtime::advanced_options::{AdvancedBoxOptions, SecurityOptions};
+use crate::runtime::locale;
 
 // ============================================================================
 // Runtime Options
@@ -42,6 +49,351 @@ pub struct BoxliteOptions {
     /// ```
     #[serde(default)]
     pub image_registries: Vec<String>,
+    /// Connection settings per registry (default: none).
+    ///
+    /// Keyed by registry as written in `image_registries` or in a fully
+    /// qualified image reference (e.g. `"localhost:5000"`). Registries
+    /// without an entry use HTTPS with the system trust roots and no
+    /// timeout. See [`RegistrySettings`].
+    ///
+    /// A registry that fails several requests in a row is skipped by
+    /// pulls for a short cooldown, see `BoxliteRuntime::registry_status()`.
+    #[serde(default)]
+    pub registry_settings: HashMap<String, RegistrySettings>,
+    /// HTTP caching proxy for blob downloads (default: None).
+    ///
+    /// Layer and config blobs are requested through the proxy, so machines
+    /// on one network share its cache. Manifests and authentication still go
+    /// to the registry directly. When the proxy fails, the blob is fetched
+    /// from the registry instead, with a warning.
+    #[serde(default)]
+    pub blob_cache_proxy: Option<Url>,
+    /// Layer cache shared between machines, e.g. on NFS (default: None).
+    ///
+    /// Pulls copy layers from this directory before asking the registry,
+    /// and publish the layers they download to it. Entries are verified
+    /// against their digest on every read and written with an atomic
+    /// rename, so several machines can use the directory at once. A cache
+    /// that can't be read or written is skipped, with a warning. Hits and
+    /// misses are reported in `PullProgress::cache` and counted in
+    /// `RuntimeMetrics::blob_cache_hits_total()` and
+    /// `blob_cache_misses_total()`.
+    #[serde(default)]
+    pub blob_cache_dir: Option<PathBuf>,
+    /// Air-gap mode: guarantee that the runtime never touches the network.
+    ///
+    /// When true, every registry operation (manifest resolution, blob
+    /// download, auth) and archive URL transfer fails fast with
+    /// `BoxliteError::OfflineMode` naming the host that would have been
+    /// contacted. Cached images, `RootfsPath`
+    /// rootfs and local OCI bundles keep working.
+    #[serde(default)]
+    pub offline: bool,
+    /// Record mutating operations to the audit log (default: false).
+    ///
+    /// When true, create/remove/start/stop/exec/copy/shutdown calls are
+    /// appended as JSON lines to `{home_dir}/audit/audit.jsonl`. Audit
+    /// write failures are logged and never fail the operation.
+    #[serde(default)]
+    pub audit: bool,
+    /// Keep removed boxes in the trash for this long (default: None).
+    ///
+    /// When set, `remove()` moves the box home to `{home_dir}/trash/{box_id}`
+    /// instead of deleting it, so it can be brought back with
+    /// `restore_trashed()`. Entries older than the retention are purged in
+    /// the background. `None` deletes boxes immediately.
+    #[serde(default)]
+    pub trash_retention: Option<Duration>,
+    /// How cached guest rootfs disks follow a new `boxlite-guest` binary
+    /// (default: `Strict`).
+    ///
+    /// See [`GuestUpdateMode`].
+    #[serde(default)]
+    pub guest_update: GuestUpdateMode,
+    /// How long a mutating box operation waits for another one on the same
+    /// box to finish (default: None).
+    ///
+    /// Start, stop, remove, resource limit updates, snapshot, restore,
+    /// export, clone and compact take a per-box lock. `None` fails a conflicting operation right away with
+    /// `BoxliteError::Busy`; `Some(d)` retries for up to `d` first.
+    #[serde(default)]
+    pub lock_wait: Option<Duration>,
+    /// How long `BoxliteRuntime::new()` waits for another runtime to release
+    /// `home_dir` (default: None).
+    ///
+    /// `None` fails right away while a live runtime holds the directory. A
+    /// lock left behind by a runtime that no longer runs is always broken,
+    /// with a warning.
+    #[serde(default)]
+    pub home_lock_wait: Option<Duration>,
+    /// How new boxes provision their disks (default: `Qcow2`).
+    ///
+    /// See [`StorageDriver`]. Existing boxes keep the driver they were
+    /// created with.
+    #[serde(default)]
+    pub storage_driver: StorageDriver,
+    /// Pools of started boxes kept idle for `acquire_warm()` (default: none).
+    ///
+    /// Each pool is filled in the background once the runtime is created
+    /// inside a Tokio runtime, and backfilled after every acquisition. See
+    /// [`WarmPoolSpec`].
+    #[serde(default)]
+    pub warm_pool: Vec<WarmPoolSpec>,
+    /// Cap on the ext4 disks cached per image, in bytes (default: None).
+    ///
+    /// When the cache grows past the cap, the least recently used disks no
+    /// box is backed by are evicted, after each new disk and on startup.
+    /// Disks used in the last few minutes are kept, so the cache may stay
+    /// over a cap that is too small. `None` never evicts.
+    #[serde(default)]
+    pub image_cache_max_bytes: Option<u64>,
+    /// Cap on the cached guest rootfs disks, in bytes (default: None).
+    ///
+    /// Evicted like `image_cache_max_bytes`.
+    #[serde(default)]
+    pub rootfs_cache_max_bytes: Option<u64>,
+    /// Warn when free space under `home_dir` drops below this many bytes
+    /// (default: None).
+    ///
+    /// Checked before image disk builds, snapshots, export, import and
+    /// copies into a box: a low check logs a warning, counts toward
+    /// `RuntimeMetrics::low_space_warnings_total()` and, during
+    /// `create_with_observer()`, is reported as `CreatePhase::LowSpace`.
+    /// Operations that would not fit fail up front either way.
+    #[serde(default)]
+    pub low_space_threshold_bytes: Option<u64>,
+    /// Box templates to register when the runtime is created (default: none).
+    ///
+    /// Each replaces a stored template of the same name, so the config file
+    /// stays authoritative for the templates it defines. See
+    /// `BoxliteRuntime::register_template()`.
+    #[serde(default)]
+    pub templates: Vec<TemplateSpec>,
+    /// Throttle low priority boxes while the host is under CPU pressure
+    /// (default: None).
+    ///
+    /// When set, a background thread samples the host's CPU pressure and,
+    /// past the policy's threshold, caps the CPU of running boxes with
+    /// `BoxPriority::Low` until pressure clears. Each transition is logged
+    /// and counted in `RuntimeMetrics::cpu_throttles_total()` and
+    /// `cpu_throttle_releases_total()`. Linux only, for boxes with the
+    /// jailer's cgroup. See [`CpuPressurePolicy`].
+    #[serde(default)]
+    pub cpu_pressure: Option<CpuPressurePolicy>,
+    /// Let boxes resolve each other by name (default: false).
+    ///
+    /// Every networked box gets `host.boxlite.internal` in its `/etc/hosts`,
+    /// pointing at the host. With `box_dns`, the names of other running,
+    /// networked boxes point there too, so their published ports are
+    /// reachable as `name:host_port`. Running boxes' hosts files are
+    /// rewritten as boxes start and stop.
+    #[serde(default)]
+    pub box_dns: bool,
+    /// Host directories `copy_out` may write under (default: any).
+    ///
+    /// For embedders that fence what the SDK can touch on the host. A copy
+    /// whose destination resolves, symlinks included, outside every listed
+    /// root fails with `BoxliteError::UnsafeCopy` before anything is
+    /// downloaded.
+    #[serde(default)]
+    pub allowed_dest_roots: Vec<PathBuf>,
+}
+
+/// Connection settings for one image registry.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RegistrySettings {
+    /// Connect and read timeout for requests to the registry (default:
+    /// none). A registry that times out counts as failed and the pull moves
+    /// on to the next one.
+    pub timeout: Option<Duration>,
+    /// Talk plain HTTP to the registry instead of HTTPS.
+    pub insecure: bool,
+    /// PEM file with CA certificates to trust for the registry, in addition
+    /// to the system roots. For registries with self-signed certificates.
+    pub ca_cert: Option<PathBuf>,
+}
+
+/// When low priority boxes are throttled, and how hard.
+///
+/// Pressure is the `some avg10` of `/proc/pressure/cpu`: the percentage of
+/// the last 10 seconds in which at least one runnable task waited for a CPU.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CpuPressurePolicy {
+    /// Throttle once pressure reaches this percentage (default: 50).
+    pub throttle_above: f64,
+    /// Lift the throttle once pressure falls to this percentage (default:
+    /// 20). Lower than `throttle_above`, so boxes don't flap around one
+    /// threshold.
+    pub release_below: f64,
+    /// CPUs each throttled box may use, through its `cpu.max` (default: 0.5).
+    pub throttled_cpus: f64,
+    /// How often pressure is sampled (default: 2s).
+    pub interval: Duration,
+}
+
+impl Default for CpuPressurePolicy {
+    fn default() -> Self {
+        Self {
+            throttle_above: 50.0,
+            release_below: 20.0,
+            throttled_cpus: 0.5,
+            interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl CpuPressurePolicy {
+    /// Check that the thresholds are percentages with `release_below` under
+    /// `throttle_above`, and that the CPU cap and interval are positive.
+    pub fn validate(&self) -> BoxliteResult<()> {
+        let percent = 0.0..=100.0;
+        if !percent.contains(&self.throttle_above) || !percent.contains(&self.release_below) {
+            return Err(BoxliteError::Config(format!(
+                "cpu_pressure thresholds must be percentages, got throttle_above={} \
+                 release_below={}",
+                self.throttle_above, self.release_below
+            )));
+        }
+        if self.release_below >= self.throttle_above {
+            return Err(BoxliteError::Config(format!(
+                "cpu_pressure.release_below ({}) must be lower than throttle_above ({})",
+                self.release_below, self.throttle_above
+            )));
+        }
+        if self.throttled_cpus.is_nan() || self.throttled_cpus <= 0.0 {
+            return Err(BoxliteError::Config(format!(
+                "cpu_pressure.throttled_cpus must be positive, got {}",
+                self.throttled_cpus
+            )));
+        }
+        if self.interval.is_zero() {
+            return Err(BoxliteError::Config(
+                "cpu_pressure.interval must not be zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A pool of pre-started boxes, handed out by `BoxliteRuntime::acquire_warm()`.
+///
+/// Idle pool boxes are hidden from `list_info()`, never returned to the pool
+/// once acquired, and removed when the runtime shuts down.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WarmPoolSpec {
+    /// Name `WarmSelector`s refer to; unique per runtime.
+    pub name: String,
+    /// Options every box in the pool is created with.
+    pub options: BoxOptions,
+    /// Number of idle boxes to keep started.
+    pub size: usize,
+}
+
+/// A box template defined in [`BoxliteOptions::templates`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TemplateSpec {
+    /// Name `create_from_template()` refers to; unique per runtime.
+    pub name: String,
+    /// Options boxes created from the template start with.
+    pub options: BoxOptions,
+}
+
+/// Disk provisioning strategy for new boxes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageDriver {
+    /// qcow2 overlays over shared base images. Works on any filesystem.
+    #[default]
+    Qcow2,
+    /// Raw disks cloned from the base images with reflinks (`FICLONE`).
+    ///
+    /// Avoids double copy-on-write on Btrfs and ZFS. Runtime creation fails
+    /// if the boxes directory does not support reflinks.
+    Reflink,
+    /// `Reflink` when the boxes directory is on Btrfs or ZFS and supports
+    /// reflinks, `Qcow2` otherwise.
+    Auto,
+}
+
+/// How cached guest rootfs disks react to a new `boxlite-guest` binary.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GuestUpdateMode {
+    /// Rootfs disks are keyed by the guest binary hash: a new binary builds a
+    /// new disk from the image disk, and a binary that differs from the one
+    /// boxlite was compiled against is an error.
+    #[default]
+    Strict,
+    /// Use whichever guest binary is installed, as long as it speaks a
+    /// supported agent protocol. Cached disks built with an older binary are
+    /// re-injected in place instead of rebuilt, unless a box still uses them.
+    ///
+    /// Meant for large fleets where rebuilding every cached disk on each
+    /// guest update is costly.
+    InPlace,
+}
+
+/// Scheduling priority of a box relative to other boxes on the host.
+///
+/// On Linux it sets the `cpu.weight` of the box's cgroup, which the jailer
+/// creates (`SecurityOptions::jailer_enabled`); on macOS, the QoS class of
+/// the shim process running the VM. `BoxOptions::cpu_shares` overrides the
+/// weight.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoxPriority {
+    /// Weight 25, QoS class utility. Throttled under host CPU pressure when
+    /// `BoxliteOptions::cpu_pressure` is set.
+    Low,
+    /// Weight 100 (the cgroup default), the default QoS class.
+    #[default]
+    Normal,
+    /// Weight 400, QoS class user-initiated.
+    High,
+}
+
+impl BoxPriority {
+    /// cgroup v2 `cpu.weight` of boxes with this priority.
+    pub fn cpu_weight(self) -> u32 {
+        match self {
+            BoxPriority::Low => 25,
+            BoxPriority::Normal => 100,
+            BoxPriority::High => 400,
+        }
+    }
+
+    /// Lowercase name, as in config files.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BoxPriority::Low => "low",
+            BoxPriority::Normal => "normal",
+            BoxPriority::High => "high",
+        }
+    }
+}
+
+impl std::fmt::Display for BoxPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for BoxPriority {
+    type Err = BoxliteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "low" => Ok(BoxPriority::Low),
+            "normal" => Ok(BoxPriority::Normal),
+            "high" => Ok(BoxPriority::High),
+            _ => Err(BoxliteError::Config(format!(
+                "invalid priority '{}' (expected low, normal or high)",
+                s
+            ))),
+        }
+    }
 }
 
 fn default_home_dir() -> PathBuf {
@@ -59,6 +411,24 @@ impl Default for BoxliteOptions {
         Self {
             home_dir: default_home_dir(),
             image_registries: Vec::new(),
+            registry_settings: HashMap::new(),
+            blob_cache_proxy: None,
+            blob_cache_dir: None,
+            offline: false,
+            audit: false,
+            trash_retention: None,
+            guest_update: GuestUpdateMode::Strict,
+            lock_wait: None,
+            home_lock_wait: None,
+            storage_driver: StorageDriver::Qcow2,
+            warm_pool: Vec::new(),
+            image_cache_max_bytes: None,
+            rootfs_cache_max_bytes: None,
+            low_space_threshold_bytes: None,
+            templates: Vec::new(),
+            cpu_pressure: None,
+            box_dns: false,
+            allowed_dest_roots: Vec::new(),
         }
     }
 }
@@ -69,17 +439,72 @@ impl Default for BoxliteOptions {
 pub struct BoxOptions {
     pub cpus: Option<u8>,
     pub memory_mib: Option<u32>,
+    /// Scheduling priority relative to other boxes (default: `Normal`).
+    ///
+    /// See [`BoxPriority`]. With `BoxliteOptions::cpu_pressure` set, low
+    /// priority boxes are also throttled while the host is under CPU
+    /// pressure.
+    #[serde(default)]
+    pub priority: BoxPriority,
+    /// CPU weight of the box, overriding the one `priority` implies
+    /// (default: none).
+    ///
+    /// In cgroup v2 `cpu.weight` units, 1 to 10000 around a default of 100:
+    /// when boxes compete for the host's CPUs, one with 200 gets twice the
+    /// time of one with 100. Linux only, through the cgroup the jailer
+    /// creates (`SecurityOptions::jailer_enabled`).
+    #[serde(default)]
+    pub cpu_shares: Option<u32>,
     /// Disk size in GB for the container rootfs (sparse, grows as needed).
     ///
     /// The actual disk will be at least as large as the base image.
     /// If set, the COW overlay will have this virtual size, allowing
     /// the container to write more data than the base image size.
     pub disk_size_gb: Option<u64>,
+    /// Swap inside the VM in MiB (default: none; 0 also disables it).
+    ///
+    /// The guest creates a swap file of this size on the container disk
+    /// and enables it before the container starts, so workloads slightly
+    /// over `memory_mib` page out instead of being OOM-killed. The file
+    /// takes disk space: with `disk_size_gb` set, the swap must be smaller
+    /// than the disk. Turning swap off removes the file on the next start.
+    #[serde(default)]
+    pub swap_mib: Option<u32>,
     pub working_dir: Option<String>,
     pub env: Vec<(String, String)>,
+    /// Keys of `env` whose values are redacted from exec output.
+    ///
+    /// Wherever such a value appears in the stdout or stderr of a command
+    /// run in the box, the host replaces it with `[REDACTED]` before it
+    /// reaches the output streams, including captured output of detached
+    /// commands. Values a command sets itself are covered too.
+    #[serde(default)]
+    pub redact_env: Vec<String>,
+    /// Time zone of the container (default: the image's, usually UTC).
+    ///
+    /// An IANA name such as `Europe/Berlin`, or `host` for the host's
+    /// current zone, resolved at each start. The zone file comes from the
+    /// tzdb bundled with BoxLite, so images without tzdata work too; it is
+    /// placed at `/etc/localtime` and the name at `/etc/timezone`, and `TZ`
+    /// defaults to the name. `env` or an exec's env can still set `TZ`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Locale of the container, e.g. `en_US.UTF-8` (default: the image's).
+    ///
+    /// Sets the `LANG` default, which `env` or an exec's env can override.
+    /// Glibc images must ship the locale's data for it to take effect.
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// User-defined labels, reported in `BoxInfo::labels`.
+    ///
+    /// Labels don't affect the box itself; they group boxes for listing and
+    /// for bulk operations such as `BoxliteRuntime::stop_matching`.
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
     pub rootfs: RootfsSpec,
     pub volumes: Vec<VolumeSpec>,
-    pub network: NetworkSpec,
+    /// Whether the box gets a network interface (see [`NetworkMode`]).
+    pub network: NetworkMode,
     pub ports: Vec<PortSpec>,
     /// Automatically remove box when stopped.
     ///
@@ -104,105 +529,941 @@ pub struct BoxOptions {
     #[serde(default = "default_detach")]
     pub detach: bool,
 
-    /// Advanced options for expert users (security, mount isolation).
-    ///
-    /// Defaults are secure — most users can ignore this entirely.
-    /// See [`AdvancedBoxOptions`] for details.
-    #[serde(default)]
-    pub advanced: AdvancedBoxOptions,
+    /// Advanced options for expert users (security, mount isolation).
+    ///
+    /// Defaults are secure — most users can ignore this entirely.
+    /// See [`AdvancedBoxOptions`] for details.
+    #[serde(default)]
+    pub advanced: AdvancedBoxOptions,
+
+    /// Override the image's ENTRYPOINT directive.
+    ///
+    /// When set, completely replaces the image's ENTRYPOINT.
+    /// Use with `cmd` to build the full command:
+    ///   Final execution = entrypoint + cmd
+    ///
+    /// Example: For `docker:dind`, bypass the failing entrypoint script:
+    ///   `entrypoint = vec!["dockerd"]`, `cmd = vec!["--iptables=false"]`
+    #[serde(default)]
+    pub entrypoint: Option<Vec<String>>,
+
+    /// Override the image's CMD directive.
+    ///
+    /// The image ENTRYPOINT is preserved; these args replace the image's CMD.
+    /// Final execution = image_entrypoint + cmd.
+    ///
+    /// Example: For `docker:dind` (ENTRYPOINT=["dockerd-entrypoint.sh"]),
+    /// setting `cmd = vec!["--iptables=false"]` produces:
+    /// `["dockerd-entrypoint.sh", "--iptables=false"]`
+    #[serde(default)]
+    pub cmd: Option<Vec<String>>,
+
+    /// Username or UID (format: <name|uid>[:<group|gid>]).
+    /// If None, uses the image's USER directive (defaults to root).
+    #[serde(default)]
+    pub user: Option<String>,
+
+    /// Run the container in its own user namespace.
+    ///
+    /// When None (default), container root is guest root. When set, container
+    /// UIDs/GIDs are remapped to an unprivileged range of the guest, so a
+    /// process escaping the container holds no privileges in the VM.
+    ///
+    /// Tradeoff: the rootfs ownership is shifted into the mapped range at
+    /// start (one-time cost proportional to the number of files), and images
+    /// that need real root capabilities (mounting, raw devices, loading
+    /// modules) will not work. Starting such an image as uid 0 with userns
+    /// enabled fails with a clear error. The shift is recorded in the box
+    /// state; starting the box later with another mapping, or without one,
+    /// fails with `InvalidState`.
+    #[serde(default)]
+    pub userns: Option<UserNsMode>,
+
+    /// Run the entrypoint under a minimal init process (default: false).
+    ///
+    /// When true, the guest agent's built-in init runs as the container's
+    /// PID 1: it reaps orphaned zombie processes and forwards signals
+    /// (SIGTERM on stop) to the entrypoint, like Docker's `--init`. Use it
+    /// for long-running boxes whose entrypoint isn't a proper init. Exec'd
+    /// commands are unaffected.
+    #[serde(default)]
+    pub init: bool,
+
+    /// Shell script that wraps the entrypoint (default: none).
+    ///
+    /// The script is placed read-only at `/dev/boxlite-entrypoint.sh` in the
+    /// container and runs instead of the entrypoint, receiving the original
+    /// entrypoint + cmd as its arguments, so it typically ends with
+    /// `exec "$@"`. It must start with a `#!` line and be at most
+    /// [`MAX_ENTRYPOINT_SCRIPT_BYTES`]. The image is not modified.
+    #[serde(default)]
+    pub entrypoint_script: Option<String>,
+
+    /// Capture core dumps of container processes (default: false).
+    ///
+    /// The guest writes cores into `/var/crash/boxlite` on the container
+    /// disk, each truncated at 256 MiB, keeping the newest 5. List them with
+    /// `LiteBox::list_core_dumps()` and fetch one with
+    /// `LiteBox::fetch_core_dump()`.
+    #[serde(default)]
+    pub capture_core_dumps: bool,
+
+    /// Mount the container's root filesystem read-only (default: false).
+    ///
+    /// Writes outside volumes and tmpfs mounts fail with `EROFS`. `/tmp`,
+    /// `/dev/shm` and `/run` stay writable as tmpfs; add more with
+    /// `writable_paths`.
+    #[serde(default)]
+    pub read_only_rootfs: bool,
+
+    /// Absolute container paths given an empty, writable tmpfs (default:
+    /// none).
+    ///
+    /// The tmpfs hides whatever the image has at the path and is discarded
+    /// on stop. Mostly used with `read_only_rootfs` for caches and spool
+    /// directories.
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+
+    /// How long the box gets to exit after SIGTERM before it is killed
+    /// (default: [`DEFAULT_SHUTDOWN_GRACE_PERIOD`]).
+    ///
+    /// Covers the container's processes handling SIGTERM and the guest
+    /// syncing its disks, on `stop()`, runtime shutdown and parent exit.
+    /// Raise it for databases that flush large caches on shutdown. Must be
+    /// non-zero and at most [`MAX_SHUTDOWN_GRACE_PERIOD`].
+    #[serde(default)]
+    pub shutdown_grace_period: Option<Duration>,
+
+    /// Stop the box after it has been idle this long (default: never).
+    ///
+    /// A box is idle while no exec runs in it and no files are copied in or
+    /// out, and, with `idle_cpu_threshold`, while its VM stays below that
+    /// CPU usage. The stop is graceful, and `LiteBox::last_exit()` reports
+    /// it as `idle-stopped`; the next `exec` starts the box again. The
+    /// runtime that owns the box watches it, the shim watches detached
+    /// boxes. Must be non-zero.
+    #[serde(default)]
+    pub auto_stop_after_idle: Option<Duration>,
+
+    /// CPU usage of the box's VM, in percent of one host CPU, at or above
+    /// which the box counts as busy for `auto_stop_after_idle` (default:
+    /// CPU usage is ignored). Must be positive.
+    #[serde(default)]
+    pub idle_cpu_threshold: Option<f64>,
+
+    /// Snapshot retention policy enforced after each successful snapshot.
+    ///
+    /// When None (default), snapshots accumulate until removed explicitly.
+    /// Can be changed later with `LiteBox::set_snapshot_retention`.
+    #[serde(default)]
+    pub snapshot_retention: Option<SnapshotRetention>,
+
+    /// Commands run once, in order, on the first start (default: none).
+    ///
+    /// They run before `start()` returns, and their output is written to
+    /// `provision.log` in the box directory. Once they have run, the box is
+    /// marked provisioned and restarts skip them; `LiteBox::reprovision()`
+    /// runs them again. Clones of a provisioned box inherit the marker, so a
+    /// box can be provisioned once and cloned for reuse.
+    #[serde(default)]
+    pub setup_commands: Vec<BoxCommand>,
+
+    /// What a failing setup command does to the start (default: fail it).
+    #[serde(default)]
+    pub setup_failure: SetupFailurePolicy,
+}
+
+fn default_auto_remove() -> bool {
+    true
+}
+
+fn default_detach() -> bool {
+    false
+}
+
+impl Default for BoxOptions {
+    fn default() -> Self {
+        Self {
+            cpus: None,
+            memory_mib: None,
+            priority: BoxPriority::default(),
+            cpu_shares: None,
+            disk_size_gb: None,
+            swap_mib: None,
+            working_dir: None,
+            env: Vec::new(),
+            redact_env: Vec::new(),
+            timezone: None,
+            locale: None,
+            labels: HashMap::new(),
+            rootfs: RootfsSpec::default(),
+            volumes: Vec::new(),
+            network: NetworkMode::default(),
+            ports: Vec::new(),
+            auto_remove: default_auto_remove(),
+            detach: default_detach(),
+            advanced: AdvancedBoxOptions::default(),
+            entrypoint: None,
+            cmd: None,
+            user: None,
+            userns: None,
+            init: false,
+            entrypoint_script: None,
+            capture_core_dumps: false,
+            read_only_rootfs: false,
+            writable_paths: Vec::new(),
+            shutdown_grace_period: None,
+            auto_stop_after_idle: None,
+            idle_cpu_threshold: None,
+            snapshot_retention: None,
+            setup_commands: Vec::new(),
+            setup_failure: SetupFailurePolicy::default(),
+        }
+    }
+}
+
+impl BoxOptions {
+    /// Sanitize and validate options.
+    ///
+    /// Validates option combinations:
+    /// - `auto_remove=true` with `detach=true` is invalid (detached boxes need manual lifecycle control)
+    /// - `advanced.isolate_mounts=true` is only supported on Linux
+    /// - `snapshot_retention.max_count` must be at least 1
+    /// - `entrypoint_script` must start with `#!` and fit the size limit
+    /// - `swap_mib` must be smaller than `disk_size_gb`
+    /// - label keys must be non-empty and free of `=`
+    /// - env keys and `ports` must pass [`crate::validate`]
+    /// - `ports` must be empty with `NetworkMode::None`
+    /// - `timezone` must be an IANA zone or `host`, `locale` a locale name
+    /// - `cpu_shares` must be within 1 to 10000
+    /// - `writable_paths` must be absolute, not `/`, and free of `..`
+    /// - `shutdown_grace_period` must be non-zero and at most
+    ///   [`MAX_SHUTDOWN_GRACE_PERIOD`]
+    /// - `auto_stop_after_idle` must be non-zero, `idle_cpu_threshold`
+    ///   positive and only set along with it
+    pub fn sanitize(&self) -> BoxliteResult<()> {
+        // Validate auto_remove + detach combination
+        // A detached box that auto-removes doesn't make practical sense:
+        // - detach=true: box survives parent exit
+        // - auto_remove=true: box removed on stop
+        // This combination is confusing - detached boxes should have manual lifecycle control
+        if self.auto_remove && self.detach {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "auto_remove=true is incompatible with detach=true. \
+                 Detached boxes should use auto_remove=false for manual lifecycle control."
+                    .to_string(),
+            ));
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        if self.advanced.isolate_mounts {
+            return Err(boxlite_shared::errors::BoxliteError::Unsupported(
+                "isolate_mounts is only supported on Linux".to_string(),
+            ));
+        }
+        if let Some(userns) = &self.userns {
+            userns.validate()?;
+        }
+        if let Some(retention) = &self.snapshot_retention {
+            retention.validate()?;
+        }
+        if let Some(script) = &self.entrypoint_script {
+            validate_entrypoint_script(script)?;
+        }
+        validate_swap(self.swap_mib, self.disk_size_gb)?;
+        if let Some(shares) = self.cpu_shares {
+            validate_cpu_shares(shares)?;
+        }
+        for key in self.labels.keys() {
+            validate_label_key(key)?;
+        }
+        for (key, _) in &self.env {
+            crate::validate::env_key(key)?;
+        }
+        for port in &self.ports {
+            crate::validate::port(port)?;
+        }
+        if self.network == NetworkMode::None && !self.ports.is_empty() {
+            return Err(boxlite_shared::errors::BoxliteError::Config(
+                "ports cannot be published with network mode none".to_string(),
+            ));
+        }
+        if let Some(zone) = &self.timezone {
+            locale::validate_timezone(zone)?;
+        }
+        if let Some(name) = &self.locale {
+            locale::validate_locale(name)?;
+        }
+        for path in &self.writable_paths {
+            validate_writable_path(path)?;
+        }
+        if let Some(grace) = self.shutdown_grace_period {
+            validate_shutdown_grace_period(grace)?;
+        }
+        if let Some(window) = self.auto_stop_after_idle {
+            validate_idle_window(window)?;
+        }
+        if let Some(threshold) = self.idle_cpu_threshold {
+            validate_idle_cpu_threshold(threshold)?;
+        }
+        validate_idle_policy(self)?;
+        Ok(())
+    }
+
+    /// Idle auto-stop settings, if the box has `auto_stop_after_idle`.
+    pub(crate) fn idle_policy(&self) -> Option<crate::vmm::IdlePolicy> {
+        self.auto_stop_after_idle
+            .map(|window| crate::vmm::IdlePolicy {
+                window,
+                cpu_threshold: self.idle_cpu_threshold,
+            })
+    }
+
+    /// The shutdown grace period, or the default when unset.
+    pub fn effective_shutdown_grace_period(&self) -> Duration {
+        self.shutdown_grace_period
+            .unwrap_or(DEFAULT_SHUTDOWN_GRACE_PERIOD)
+    }
+
+    /// `cpu.weight` of the box's cgroup, or None to leave the default.
+    pub(crate) fn cpu_weight(&self) -> Option<u32> {
+        match (self.cpu_shares, self.priority) {
+            (Some(shares), _) => Some(shares),
+            (None, BoxPriority::Normal) => None,
+            (None, priority) => Some(priority.cpu_weight()),
+        }
+    }
+
+    /// Set security options (convenience for `advanced.security`).
+    pub fn with_security(mut self, security: SecurityOptions) -> Self {
+        self.advanced.security = security;
+        self
+    }
+
+    /// Start building options with [`BoxOptionsBuilder`].
+    ///
+    /// The rootfs must be chosen with `.image()` or `.rootfs_path()` before
+    /// `.build()` is available.
+    pub fn builder() -> BoxOptionsBuilder<NoRootfs> {
+        BoxOptionsBuilder {
+            options: BoxOptions::default(),
+            problems: Vec::new(),
+            _rootfs: PhantomData,
+        }
+    }
+}
+
+// ============================================================================
+// Box Options Builder (typestate: rootfs required before build)
+// ============================================================================
+
+/// [`BoxOptionsBuilder`] state before a rootfs is chosen.
+#[derive(Debug)]
+pub struct NoRootfs;
+
+/// [`BoxOptionsBuilder`] state once a rootfs is chosen.
+#[derive(Debug)]
+pub struct HasRootfs;
+
+/// Builder for [`BoxOptions`].
+///
+/// `build()` only exists once the rootfs is set, and `image()` /
+/// `rootfs_path()` only before, so a box without a rootfs or with both is
+/// a compile error. Values are validated as they are set (the rules of
+/// [`BoxOptions::sanitize`]), and `build()` reports every problem found,
+/// not just the first.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use boxlite::BoxOptions;
+///
+/// let options = BoxOptions::builder()
+///     .image("python:3.12-slim")
+///     .cpus(2)
+///     .memory_mib(1024)
+///     .env("PYTHONUNBUFFERED", "1")
+///     .volume("/data", "/mnt/data", true)
+///     .build()?;
+/// # Ok::<(), boxlite::BoxliteError>(())
+/// ```
+///
+/// Without a rootfs there is no `build()`:
+///
+/// ```compile_fail
+/// let options = boxlite::BoxOptions::builder().cpus(2).build();
+/// ```
+///
+/// and a second rootfs cannot be set:
+///
+/// ```compile_fail
+/// let builder = boxlite::BoxOptions::builder().image("alpine").rootfs_path("/srv/rootfs");
+/// ```
+#[derive(Debug)]
+#[must_use = "call .build() to get the BoxOptions"]
+pub struct BoxOptionsBuilder<R = NoRootfs> {
+    options: BoxOptions,
+    problems: Vec<String>,
+    _rootfs: PhantomData<R>,
+}
+
+impl BoxOptionsBuilder<NoRootfs> {
+    /// Use the registry image `reference` (e.g. `alpine:latest`) as rootfs.
+    pub fn image(self, reference: impl Into<String>) -> BoxOptionsBuilder<HasRootfs> {
+        let reference = reference.into();
+        let mut builder = self.with_rootfs(RootfsSpec::Image(reference.clone()));
+        if reference.trim().is_empty() {
+            builder.problem("image reference must not be empty");
+        }
+        builder
+    }
+
+    /// Use the prepared rootfs directory at `path` on the host.
+    pub fn rootfs_path(self, path: impl Into<String>) -> BoxOptionsBuilder<HasRootfs> {
+        let path = path.into();
+        let mut builder = self.with_rootfs(RootfsSpec::RootfsPath(path.clone()));
+        if path.trim().is_empty() {
+            builder.problem("rootfs path must not be empty");
+        }
+        builder
+    }
+
+    fn with_rootfs(mut self, rootfs: RootfsSpec) -> BoxOptionsBuilder<HasRootfs> {
+        self.options.rootfs = rootfs;
+        BoxOptionsBuilder {
+            options: self.options,
+            problems: self.problems,
+            _rootfs: PhantomData,
+        }
+    }
+}
+
+impl BoxOptionsBuilder<HasRootfs> {
+    /// Finish, failing with a [`BoxliteError::Config`] that lists every
+    /// problem found.
+    pub fn build(mut self) -> BoxliteResult<BoxOptions> {
+        if let Err(e) = validate_swap(self.options.swap_mib, self.options.disk_size_gb) {
+            self.problem(e);
+        }
+        if self.options.auto_remove && self.options.detach {
+            self.problem(
+                "auto_remove=true is incompatible with detach=true; \
+                 set auto_remove(false) for a detached box",
+            );
+        }
+        if let Err(e) = validate_idle_policy(&self.options) {
+            self.problem(e);
+        }
+
+        match self.problems.len() {
+            0 => Ok(self.options),
+            1 => Err(BoxliteError::Config(self.problems.remove(0))),
+            n => Err(BoxliteError::Config(format!(
+                "{} problems in box options:\n  - {}",
+                n,
+                self.problems.join("\n  - ")
+            ))),
+        }
+    }
+}
+
+impl<R> BoxOptionsBuilder<R> {
+    /// Number of vCPUs.
+    pub fn cpus(mut self, cpus: u8) -> Self {
+        self.options.cpus = Some(cpus);
+        self
+    }
+
+    /// Memory in MiB.
+    pub fn memory_mib(mut self, memory_mib: u32) -> Self {
+        self.options.memory_mib = Some(memory_mib);
+        self
+    }
+
+    /// Scheduling priority (see [`BoxOptions::priority`]).
+    pub fn priority(mut self, priority: BoxPriority) -> Self {
+        self.options.priority = priority;
+        self
+    }
+
+    /// CPU weight, 1 to 10000 (see [`BoxOptions::cpu_shares`]).
+    pub fn cpu_shares(mut self, shares: u32) -> Self {
+        if let Err(e) = validate_cpu_shares(shares) {
+            self.problem(e);
+        }
+        self.options.cpu_shares = Some(shares);
+        self
+    }
+
+    /// Container rootfs disk size in GB (see [`BoxOptions::disk_size_gb`]).
+    pub fn disk_size_gb(mut self, disk_size_gb: u64) -> Self {
+        self.options.disk_size_gb = Some(disk_size_gb);
+        self
+    }
+
+    /// Swap inside the VM in MiB, 0 for none (see [`BoxOptions::swap_mib`]).
+    pub fn swap_mib(mut self, swap_mib: u32) -> Self {
+        self.options.swap_mib = Some(swap_mib);
+        self
+    }
+
+    /// Working directory of the entrypoint and of exec'd commands.
+    pub fn working_dir(mut self, dir: impl Into<String>) -> Self {
+        self.options.working_dir = Some(dir.into());
+        self
+    }
+
+    /// Add an environment variable.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Add several environment variables.
+    pub fn envs<K, V>(mut self, vars: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.options
+            .env
+            .extend(vars.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
+
+    /// Redact the value of environment variable `key` from exec output.
+    pub fn redact_env(mut self, key: impl Into<String>) -> Self {
+        self.options.redact_env.push(key.into());
+        self
+    }
+
+    /// Time zone, an IANA name or `host` (see [`BoxOptions::timezone`]).
+    pub fn timezone(mut self, zone: impl Into<String>) -> Self {
+        let zone = zone.into();
+        if let Err(e) = locale::validate_timezone(&zone) {
+            self.problem(e);
+        }
+        self.options.timezone = Some(zone);
+        self
+    }
+
+    /// Locale, e.g. `en_US.UTF-8` (see [`BoxOptions::locale`]).
+    pub fn locale(mut self, name: impl Into<String>) -> Self {
+        let name = name.into();
+        if let Err(e) = locale::validate_locale(&name) {
+            self.problem(e);
+        }
+        self.options.locale = Some(name);
+        self
+    }
+
+    /// Set label `key` to `value` (see [`BoxOptions::labels`]).
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        let key = key.into();
+        if let Err(e) = validate_label_key(&key) {
+            self.problem(e);
+        }
+        self.options.labels.insert(key, value.into());
+        self
+    }
+
+    /// Mount host directory `host_path` at `guest_path` in the box.
+    pub fn volume(
+        mut self,
+        host_path: impl Into<String>,
+        guest_path: impl Into<String>,
+        read_only: bool,
+    ) -> Self {
+        self.options.volumes.push(VolumeSpec {
+            host_path: host_path.into(),
+            guest_path: guest_path.into(),
+            read_only,
+        });
+        self
+    }
+
+    /// Set the box's network access (default: [`NetworkMode::Default`]).
+    pub fn network(mut self, network: NetworkMode) -> Self {
+        self.options.network = network;
+        self
+    }
+
+    /// Publish a port of the box on the host.
+    pub fn port(mut self, port: PortSpec) -> Self {
+        self.options.ports.push(port);
+        self
+    }
+
+    /// Remove the box when it stops (default: true).
+    pub fn auto_remove(mut self, auto_remove: bool) -> Self {
+        self.options.auto_remove = auto_remove;
+        self
+    }
+
+    /// Keep the box running after this process exits (default: false).
+    pub fn detach(mut self, detach: bool) -> Self {
+        self.options.detach = detach;
+        self
+    }
+
+    /// Advanced options (security, mount isolation, health check).
+    pub fn advanced(mut self, advanced: AdvancedBoxOptions) -> Self {
+        #[cfg(not(target_os = "linux"))]
+        if advanced.isolate_mounts {
+            self.problem("isolate_mounts is only supported on Linux");
+        }
+        self.options.advanced = advanced;
+        self
+    }
+
+    /// Security options (convenience for `advanced.security`).
+    pub fn security(mut self, security: SecurityOptions) -> Self {
+        self.options.advanced.security = security;
+        self
+    }
+
+    /// Replace the image's ENTRYPOINT.
+    pub fn entrypoint<S: Into<String>>(mut self, entrypoint: impl IntoIterator<Item = S>) -> Self {
+        self.options.entrypoint = Some(entrypoint.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Replace the image's CMD.
+    pub fn cmd<S: Into<String>>(mut self, cmd: impl IntoIterator<Item = S>) -> Self {
+        self.options.cmd = Some(cmd.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// User to run as (`<name|uid>[:<group|gid>]`).
+    pub fn user(mut self, user: impl Into<String>) -> Self {
+        self.options.user = Some(user.into());
+        self
+    }
+
+    /// Run the container in its own user namespace.
+    pub fn userns(mut self, userns: UserNsMode) -> Self {
+        if let Err(e) = userns.validate() {
+            self.problem(e);
+        }
+        self.options.userns = Some(userns);
+        self
+    }
+
+    /// Run the entrypoint under a minimal init process.
+    pub fn init(mut self, init: bool) -> Self {
+        self.options.init = init;
+        self
+    }
+
+    /// Shell script wrapping the entrypoint (see [`BoxOptions::entrypoint_script`]).
+    pub fn entrypoint_script(mut self, script: impl Into<String>) -> Self {
+        let script = script.into();
+        if let Err(e) = validate_entrypoint_script(&script) {
+            self.problem(e);
+        }
+        self.options.entrypoint_script = Some(script);
+        self
+    }
+
+    /// Capture core dumps of container processes.
+    pub fn capture_core_dumps(mut self, capture: bool) -> Self {
+        self.options.capture_core_dumps = capture;
+        self
+    }
+
+    /// Mount the container's root filesystem read-only.
+    pub fn read_only_rootfs(mut self, read_only: bool) -> Self {
+        self.options.read_only_rootfs = read_only;
+        self
+    }
+
+    /// Add a container path backed by a writable tmpfs.
+    pub fn writable_path(mut self, path: impl Into<String>) -> Self {
+        let path = path.into();
+        if let Err(e) = validate_writable_path(&path) {
+            self.problem(e);
+        }
+        self.options.writable_paths.push(path);
+        self
+    }
+
+    /// How long the box gets to exit after SIGTERM before it is killed.
+    pub fn shutdown_grace_period(mut self, grace: Duration) -> Self {
+        if let Err(e) = validate_shutdown_grace_period(grace) {
+            self.problem(e);
+        }
+        self.options.shutdown_grace_period = Some(grace);
+        self
+    }
+
+    /// Stop the box after it has been idle this long.
+    pub fn auto_stop_after_idle(mut self, window: Duration) -> Self {
+        if let Err(e) = validate_idle_window(window) {
+            self.problem(e);
+        }
+        self.options.auto_stop_after_idle = Some(window);
+        self
+    }
+
+    /// CPU usage, in percent of one host CPU, at or above which the box
+    /// doesn't count as idle.
+    pub fn idle_cpu_threshold(mut self, percent: f64) -> Self {
+        if let Err(e) = validate_idle_cpu_threshold(percent) {
+            self.problem(e);
+        }
+        self.options.idle_cpu_threshold = Some(percent);
+        self
+    }
+
+    /// Snapshot retention policy.
+    pub fn snapshot_retention(mut self, retention: SnapshotRetention) -> Self {
+        if let Err(e) = retention.validate() {
+            self.problem(e);
+        }
+        self.options.snapshot_retention = Some(retention);
+        self
+    }
+
+    /// Add a command run once on the first start.
+    pub fn setup_command(mut self, command: BoxCommand) -> Self {
+        self.options.setup_commands.push(command);
+        self
+    }
+
+    /// What a failing setup command does to the start.
+    pub fn setup_failure(mut self, policy: SetupFailurePolicy) -> Self {
+        self.options.setup_failure = policy;
+        self
+    }
+
+    /// Record a problem for `build()` to report.
+    fn problem(&mut self, problem: impl Into<BuilderProblem>) {
+        self.problems.push(problem.into().0);
+    }
+}
+
+/// Problem text, without the error kind prefix of a [`BoxliteError`].
+struct BuilderProblem(String);
+
+impl From<&str> for BuilderProblem {
+    fn from(problem: &str) -> Self {
+        Self(problem.to_string())
+    }
+}
+
+impl From<BoxliteError> for BuilderProblem {
+    fn from(err: BoxliteError) -> Self {
+        match err {
+            BoxliteError::Config(msg)
+            | BoxliteError::InvalidArgument(msg)
+            | BoxliteError::Unsupported(msg) => Self(msg),
+            other => Self(other.to_string()),
+        }
+    }
+}
+
+/// Largest accepted [`BoxOptions::entrypoint_script`], in bytes.
+pub const MAX_ENTRYPOINT_SCRIPT_BYTES: usize = 64 * 1024;
+
+fn validate_entrypoint_script(script: &str) -> BoxliteResult<()> {
+    if !script.starts_with("#!") {
+        return Err(BoxliteError::Config(
+            "entrypoint_script must start with a #! line (e.g. #!/bin/sh)".to_string(),
+        ));
+    }
+    if script.len() > MAX_ENTRYPOINT_SCRIPT_BYTES {
+        return Err(BoxliteError::Config(format!(
+            "entrypoint_script is {} bytes, limit is {}",
+            script.len(),
+            MAX_ENTRYPOINT_SCRIPT_BYTES
+        )));
+    }
+    Ok(())
+}
+
+fn validate_swap(swap_mib: Option<u32>, disk_size_gb: Option<u64>) -> BoxliteResult<()> {
+    let (Some(swap_mib), Some(disk_size_gb)) = (swap_mib, disk_size_gb) else {
+        return Ok(());
+    };
+    if swap_mib as u64 >= disk_size_gb.saturating_mul(1024) {
+        return Err(BoxliteError::Config(format!(
+            "swap_mib ({} MiB) must be smaller than disk_size_gb ({} GB), \
+             since the swap file lives on the container disk",
+            swap_mib, disk_size_gb
+        )));
+    }
+    Ok(())
+}
+
+fn validate_cpu_shares(shares: u32) -> BoxliteResult<()> {
+    if !(1..=10000).contains(&shares) {
+        return Err(BoxliteError::Config(format!(
+            "cpu_shares must be within 1 to 10000, got {}",
+            shares
+        )));
+    }
+    Ok(())
+}
+
+fn validate_label_key(key: &str) -> BoxliteResult<()> {
+    if key.is_empty() || key.contains('=') {
+        return Err(BoxliteError::Config(format!(
+            "invalid label key {:?}: must be non-empty and must not contain '='",
+            key
+        )));
+    }
+    Ok(())
+}
+
+/// Shutdown grace period when `BoxOptions::shutdown_grace_period` is unset.
+pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// Longest accepted `BoxOptions::shutdown_grace_period`.
+pub const MAX_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(600);
+
+fn validate_shutdown_grace_period(grace: Duration) -> BoxliteResult<()> {
+    if grace.is_zero() || grace > MAX_SHUTDOWN_GRACE_PERIOD {
+        return Err(BoxliteError::Config(format!(
+            "shutdown_grace_period must be non-zero and at most {}s, got {:?}",
+            MAX_SHUTDOWN_GRACE_PERIOD.as_secs(),
+            grace
+        )));
+    }
+    Ok(())
+}
+
+fn validate_idle_window(window: Duration) -> BoxliteResult<()> {
+    if window.is_zero() {
+        return Err(BoxliteError::Config(
+            "auto_stop_after_idle must be non-zero".to_string(),
+        ));
+    }
+    Ok(())
+}
 
-    /// Override the image's ENTRYPOINT directive.
-    ///
-    /// When set, completely replaces the image's ENTRYPOINT.
-    /// Use with `cmd` to build the full command:
-    ///   Final execution = entrypoint + cmd
-    ///
-    /// Example: For `docker:dind`, bypass the failing entrypoint script:
-    ///   `entrypoint = vec!["dockerd"]`, `cmd = vec!["--iptables=false"]`
-    #[serde(default)]
-    pub entrypoint: Option<Vec<String>>,
+fn validate_idle_cpu_threshold(percent: f64) -> BoxliteResult<()> {
+    if !percent.is_finite() || percent <= 0.0 {
+        return Err(BoxliteError::Config(format!(
+            "idle_cpu_threshold must be a positive percentage, got {}",
+            percent
+        )));
+    }
+    Ok(())
+}
 
-    /// Override the image's CMD directive.
-    ///
-    /// The image ENTRYPOINT is preserved; these args replace the image's CMD.
-    /// Final execution = image_entrypoint + cmd.
-    ///
-    /// Example: For `docker:dind` (ENTRYPOINT=["dockerd-entrypoint.sh"]),
-    /// setting `cmd = vec!["--iptables=false"]` produces:
-    /// `["dockerd-entrypoint.sh", "--iptables=false"]`
-    #[serde(default)]
-    pub cmd: Option<Vec<String>>,
+fn validate_idle_policy(options: &BoxOptions) -> BoxliteResult<()> {
+    if options.idle_cpu_threshold.is_some() && options.auto_stop_after_idle.is_none() {
+        return Err(BoxliteError::Config(
+            "idle_cpu_threshold requires auto_stop_after_idle".to_string(),
+        ));
+    }
+    Ok(())
+}
 
-    /// Username or UID (format: <name|uid>[:<group|gid>]).
-    /// If None, uses the image's USER directive (defaults to root).
-    #[serde(default)]
-    pub user: Option<String>,
+fn validate_writable_path(path: &str) -> BoxliteResult<()> {
+    let invalid = |reason: &str| {
+        Err(BoxliteError::Config(format!(
+            "invalid writable path {:?}: {}",
+            path, reason
+        )))
+    };
+    if !path.starts_with('/') {
+        return invalid("must be absolute");
+    }
+    if path.trim_end_matches('/').is_empty() {
+        return invalid("cannot be the root directory");
+    }
+    if path.split('/').any(|part| part == "..") {
+        return invalid("must not contain '..'");
+    }
+    Ok(())
 }
 
-fn default_auto_remove() -> bool {
-    true
+/// How the start reacts to a failing setup command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SetupFailurePolicy {
+    /// Stop at the failing command and fail the start. The box is not
+    /// marked provisioned.
+    #[default]
+    FailBox,
+    /// Log a warning, run the remaining commands and finish the start.
+    WarnAndContinue,
 }
 
-fn default_detach() -> bool {
-    false
+/// User namespace mode for the container.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum UserNsMode {
+    /// Map container IDs 0..65536 onto an unprivileged subordinate range
+    /// picked by the guest.
+    Auto,
+    /// Explicit ID mappings.
+    Map {
+        uid_map: Vec<IdMapping>,
+        gid_map: Vec<IdMapping>,
+    },
 }
 
-impl Default for BoxOptions {
-    fn default() -> Self {
-        Self {
-            cpus: None,
-            memory_mib: None,
-            disk_size_gb: None,
-            working_dir: None,
-            env: Vec::new(),
-            rootfs: RootfsSpec::default(),
-            volumes: Vec::new(),
-            network: NetworkSpec::default(),
-            ports: Vec::new(),
-            auto_remove: default_auto_remove(),
-            detach: default_detach(),
-            advanced: AdvancedBoxOptions::default(),
-            entrypoint: None,
-            cmd: None,
-            user: None,
-        }
+/// One contiguous range of a user namespace ID mapping.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct IdMapping {
+    /// First ID inside the container.
+    pub container_id: u32,
+    /// First ID in the guest the range maps onto.
+    pub host_id: u32,
+    /// Number of IDs in the range.
+    pub size: u32,
+}
+
+impl UserNsMode {
+    /// Validate explicit mappings (non-empty, non-zero sizes, no overflow,
+    /// no overlapping ranges on either side).
+    pub fn validate(&self) -> BoxliteResult<()> {
+        let UserNsMode::Map { uid_map, gid_map } = self else {
+            return Ok(());
+        };
+        validate_id_map("uid_map", uid_map)?;
+        validate_id_map("gid_map", gid_map)
     }
 }
 
-impl BoxOptions {
-    /// Sanitize and validate options.
-    ///
-    /// Validates option combinations:
-    /// - `auto_remove=true` with `detach=true` is invalid (detached boxes need manual lifecycle control)
-    /// - `advanced.isolate_mounts=true` is only supported on Linux
-    pub fn sanitize(&self) -> BoxliteResult<()> {
-        // Validate auto_remove + detach combination
-        // A detached box that auto-removes doesn't make practical sense:
-        // - detach=true: box survives parent exit
-        // - auto_remove=true: box removed on stop
-        // This combination is confusing - detached boxes should have manual lifecycle control
-        if self.auto_remove && self.detach {
-            return Err(boxlite_shared::errors::BoxliteError::Config(
-                "auto_remove=true is incompatible with detach=true. \
-                 Detached boxes should use auto_remove=false for manual lifecycle control."
-                    .to_string(),
-            ));
-        }
+fn validate_id_map(name: &str, map: &[IdMapping]) -> BoxliteResult<()> {
+    if map.is_empty() {
+        return Err(BoxliteError::Config(format!(
+            "userns {name} must contain at least one range"
+        )));
+    }
 
-        #[cfg(not(target_os = "linux"))]
-        if self.advanced.isolate_mounts {
-            return Err(boxlite_shared::errors::BoxliteError::Unsupported(
-                "isolate_mounts is only supported on Linux".to_string(),
-            ));
+    let mut ranges = Vec::with_capacity(map.len());
+    for m in map {
+        if m.size == 0 {
+            return Err(BoxliteError::Config(format!(
+                "userns {name} range size must be > 0"
+            )));
         }
-        Ok(())
+        let container_end = m.container_id.checked_add(m.size);
+        let host_end = m.host_id.checked_add(m.size);
+        let (Some(container_end), Some(host_end)) = (container_end, host_end) else {
+            return Err(BoxliteError::Config(format!(
+                "userns {name} range {}:{}:{} overflows the ID space",
+                m.container_id, m.host_id, m.size
+            )));
+        };
+        ranges.push(((m.container_id, container_end), (m.host_id, host_end)));
     }
 
-    /// Set security options (convenience for `advanced.security`).
-    pub fn with_security(mut self, security: SecurityOptions) -> Self {
-        self.advanced.security = security;
-        self
+    let overlaps = |a: (u32, u32), b: (u32, u32)| a.0 < b.1 && b.0 < a.1;
+    for (i, a) in ranges.iter().enumerate() {
+        for b in &ranges[i + 1..] {
+            if overlaps(a.0, b.0) || overlaps(a.1, b.1) {
+                return Err(BoxliteError::Config(format!(
+                    "userns {name} ranges must not overlap"
+                )));
+            }
+        }
     }
+    Ok(())
 }
 
 /// How to populate the box root filesystem.
@@ -220,6 +1481,16 @@ impl Default for RootfsSpec {
     }
 }
 
+impl RootfsSpec {
+    /// The image reference, if the rootfs comes from an image.
+    pub(crate) fn image(&self) -> Option<&str> {
+        match self {
+            Self::Image(image) => Some(image),
+            Self::RootfsPath(_) => None,
+        }
+    }
+}
+
 /// Filesystem mount specification.
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
 pub struct VolumeSpec {
@@ -228,13 +1499,21 @@ pub struct VolumeSpec {
     pub read_only: bool,
 }
 
-/// Network isolation options.
-#[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
-pub enum NetworkSpec {
+/// Network access of a box.
+///
+/// Exec, file copy, tunnels and the guest control channel use vsock, so they
+/// work in every mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkMode {
+    /// A virtio-net interface behind the box's own network backend
+    /// (gvproxy), with outbound access and published ports.
     #[default]
-    Isolated,
-    // Host,
-    // Custom(String),
+    #[serde(alias = "Isolated", alias = "isolated")]
+    Default,
+    /// No network backend and no network device: the guest only has
+    /// loopback. `ports` must be empty.
+    None,
 }
 
 #[derive(Clone, Debug, Default, serde::Serialize, serde::Deserialize)]
@@ -371,6 +1650,400 @@ mod tests {
         assert!(opts3.sanitize().is_ok());
     }
 
+    #[test]
+    fn test_sanitize_entrypoint_script() {
+        let with_script = |script: String| BoxOptions {
+            entrypoint_script: Some(script),
+            ..Default::default()
+        };
+
+        assert!(
+            with_script("#!/bin/sh\nulimit -n 4096\nexec \"$@\"\n".into())
+                .sanitize()
+                .is_ok()
+        );
+
+        let err = with_script("ulimit -n 4096\n".into())
+            .sanitize()
+            .unwrap_err();
+        assert!(err.to_string().contains("#!"), "{err}");
+
+        let oversized = format!("#!/bin/sh\n{}", "#".repeat(MAX_ENTRYPOINT_SCRIPT_BYTES));
+        let err = with_script(oversized).sanitize().unwrap_err();
+        assert!(err.to_string().contains("limit"), "{err}");
+    }
+
+    #[test]
+    fn test_sanitize_timezone_and_locale() {
+        let with = |timezone: &str, locale: &str| BoxOptions {
+            timezone: Some(timezone.to_string()),
+            locale: Some(locale.to_string()),
+            ..Default::default()
+        };
+
+        assert!(with("Europe/Berlin", "de_DE.UTF-8").sanitize().is_ok());
+        assert!(with("host", "C.UTF-8").sanitize().is_ok());
+
+        let err = with("Europe/Berln", "C").sanitize().unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err}");
+        assert!(err.to_string().contains("Europe/Berlin"), "{err}");
+        let err = with("UTC", "en-US").sanitize().unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err}");
+
+        let err = BoxOptions::builder()
+            .image("alpine")
+            .timezone("Mars/Olympus_Mons")
+            .locale("english")
+            .build()
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("unknown timezone 'Mars/Olympus_Mons'"),
+            "{err}"
+        );
+        assert!(err.contains("invalid locale 'english'"), "{err}");
+    }
+
+    #[test]
+    fn test_sanitize_swap_against_disk_size() {
+        let with_swap = |swap_mib, disk_size_gb| BoxOptions {
+            swap_mib: Some(swap_mib),
+            disk_size_gb,
+            ..Default::default()
+        };
+
+        // Without a disk size, the guest checks the free space at start
+        assert!(with_swap(4096, None).sanitize().is_ok());
+        assert!(with_swap(1024, Some(2)).sanitize().is_ok());
+        assert!(with_swap(0, Some(1)).sanitize().is_ok());
+
+        let err = with_swap(2048, Some(2)).sanitize().unwrap_err();
+        assert!(err.to_string().contains("disk_size_gb"), "{err}");
+
+        let err = BoxOptions::builder()
+            .image("alpine")
+            .swap_mib(8192)
+            .disk_size_gb(4)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("swap_mib (8192 MiB)"), "{err}");
+    }
+
+    #[test]
+    fn test_cpu_weight_from_priority_and_shares() {
+        let with = |priority, cpu_shares| BoxOptions {
+            priority,
+            cpu_shares,
+            ..Default::default()
+        };
+
+        // Normal priority leaves the cgroup default alone
+        assert_eq!(with(BoxPriority::Normal, None).cpu_weight(), None);
+        assert_eq!(with(BoxPriority::Low, None).cpu_weight(), Some(25));
+        assert_eq!(with(BoxPriority::High, None).cpu_weight(), Some(400));
+        assert_eq!(with(BoxPriority::Low, Some(300)).cpu_weight(), Some(300));
+
+        assert!(with(BoxPriority::Normal, Some(0)).sanitize().is_err());
+        let err = BoxOptions::builder()
+            .image("alpine")
+            .cpu_shares(20000)
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("cpu_shares"), "{err}");
+
+        // Persisted options without the fields keep working
+        let legacy: BoxOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(legacy.priority, BoxPriority::Normal);
+        assert_eq!("low".parse::<BoxPriority>().unwrap(), BoxPriority::Low);
+    }
+
+    #[test]
+    fn test_cpu_pressure_policy_validate() {
+        assert!(CpuPressurePolicy::default().validate().is_ok());
+
+        let inverted = CpuPressurePolicy {
+            throttle_above: 20.0,
+            release_below: 50.0,
+            ..Default::default()
+        };
+        let err = inverted.validate().unwrap_err();
+        assert!(err.to_string().contains("release_below"), "{err}");
+
+        let no_cpu = CpuPressurePolicy {
+            throttled_cpus: 0.0,
+            ..Default::default()
+        };
+        assert!(no_cpu.validate().is_err());
+        let over = CpuPressurePolicy {
+            throttle_above: 150.0,
+            ..Default::default()
+        };
+        assert!(over.validate().is_err());
+    }
+
+    #[test]
+    fn test_network_mode_serde_and_ports() {
+        // Options persisted before NetworkMode still say "Isolated"
+        let legacy: BoxOptions =
+            serde_json::from_str(r#"{"rootfs": {"Image": "alpine"}, "network": "Isolated"}"#)
+                .unwrap();
+        assert_eq!(legacy.network, NetworkMode::Default);
+
+        let none: BoxOptions =
+            serde_json::from_str(r#"{"rootfs": {"Image": "alpine"}, "network": "none"}"#).unwrap();
+        assert_eq!(none.network, NetworkMode::None);
+        assert!(none.sanitize().is_ok());
+        assert_eq!(
+            serde_json::to_value(&none).unwrap()["network"],
+            serde_json::json!("none")
+        );
+
+        let published = BoxOptions {
+            network: NetworkMode::None,
+            ports: vec![PortSpec {
+                guest_port: 80,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let err = published.sanitize().unwrap_err();
+        assert!(err.to_string().contains("network mode none"), "{err}");
+    }
+
+    #[test]
+    fn test_label_keys_are_validated() {
+        let opts = BoxOptions::builder()
+            .image("alpine")
+            .label("tier", "web")
+            .label("tenant", "a=b")
+            .build()
+            .unwrap();
+        assert_eq!(opts.labels["tier"], "web");
+        assert_eq!(opts.labels["tenant"], "a=b");
+        assert!(opts.sanitize().is_ok());
+
+        let err = BoxOptions::builder()
+            .image("alpine")
+            .label("a=b", "c")
+            .build()
+            .unwrap_err();
+        assert!(err.to_string().contains("invalid label key"), "{err}");
+
+        let mut opts = BoxOptions::default();
+        opts.labels.insert(String::new(), "x".into());
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_env_keys_and_ports_are_validated() {
+        let opts = BoxOptions {
+            env: vec![("A=B".into(), "c".into())],
+            ..Default::default()
+        };
+        let err = opts.sanitize().unwrap_err();
+        assert_eq!(crate::validate::error_code(&err), Some("env_key.equals"));
+
+        let mut port = crate::validate::port_spec("8080:80").unwrap();
+        port.guest_port = 0;
+        let opts = BoxOptions {
+            ports: vec![port],
+            ..Default::default()
+        };
+        let err = opts.sanitize().unwrap_err();
+        assert_eq!(crate::validate::error_code(&err), Some("port_spec.range"));
+    }
+
+    #[test]
+    fn test_writable_paths_are_validated() {
+        let opts = BoxOptions::builder()
+            .image("alpine")
+            .read_only_rootfs(true)
+            .writable_path("/var/cache")
+            .build()
+            .unwrap();
+        assert!(opts.read_only_rootfs);
+        assert_eq!(opts.writable_paths, vec!["/var/cache".to_string()]);
+
+        for (path, reason) in [
+            ("var/cache", "absolute"),
+            ("/", "root directory"),
+            ("//", "root directory"),
+            ("/var/../etc", ".."),
+        ] {
+            let err = BoxOptions::builder()
+                .image("alpine")
+                .writable_path(path)
+                .build()
+                .unwrap_err();
+            assert!(err.to_string().contains(reason), "{path}: {err}");
+        }
+
+        let opts = BoxOptions {
+            writable_paths: vec!["relative".into()],
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_auto_stop_after_idle_validation() {
+        let opts = BoxOptions::builder()
+            .image("alpine")
+            .auto_stop_after_idle(Duration::from_secs(7200))
+            .idle_cpu_threshold(5.0)
+            .build()
+            .unwrap();
+        let policy = opts.idle_policy().unwrap();
+        assert_eq!(policy.window, Duration::from_secs(7200));
+        assert_eq!(policy.cpu_threshold, Some(5.0));
+        assert!(opts.sanitize().is_ok());
+        assert!(BoxOptions::default().idle_policy().is_none());
+
+        let builder = BoxOptions::builder()
+            .image("alpine")
+            .auto_stop_after_idle(Duration::ZERO);
+        assert!(builder.build().is_err());
+        for threshold in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let builder = BoxOptions::builder()
+                .image("alpine")
+                .auto_stop_after_idle(Duration::from_secs(60))
+                .idle_cpu_threshold(threshold);
+            assert!(builder.build().is_err(), "{threshold}");
+        }
+
+        // A threshold alone has nothing to apply to
+        let builder = BoxOptions::builder()
+            .image("alpine")
+            .idle_cpu_threshold(5.0);
+        assert!(builder.build().is_err());
+        let opts = BoxOptions {
+            idle_cpu_threshold: Some(5.0),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+        let opts = BoxOptions {
+            auto_stop_after_idle: Some(Duration::ZERO),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_err());
+    }
+
+    #[test]
+    fn test_shutdown_grace_period_is_bounded() {
+        let opts = BoxOptions::default();
+        assert_eq!(
+            opts.effective_shutdown_grace_period(),
+            DEFAULT_SHUTDOWN_GRACE_PERIOD
+        );
+
+        let opts = BoxOptions::builder()
+            .image("alpine")
+            .shutdown_grace_period(Duration::from_secs(30))
+            .build()
+            .unwrap();
+        assert_eq!(opts.shutdown_grace_period, Some(Duration::from_secs(30)));
+        assert_eq!(
+            opts.effective_shutdown_grace_period(),
+            Duration::from_secs(30)
+        );
+
+        for grace in [
+            Duration::ZERO,
+            MAX_SHUTDOWN_GRACE_PERIOD + Duration::from_secs(1),
+        ] {
+            assert!(
+                BoxOptions::builder()
+                    .image("alpine")
+                    .shutdown_grace_period(grace)
+                    .build()
+                    .is_err(),
+                "{grace:?}"
+            );
+            let opts = BoxOptions {
+                shutdown_grace_period: Some(grace),
+                ..Default::default()
+            };
+            assert!(opts.sanitize().is_err(), "{grace:?}");
+        }
+        let opts = BoxOptions {
+            shutdown_grace_period: Some(MAX_SHUTDOWN_GRACE_PERIOD),
+            ..Default::default()
+        };
+        assert!(opts.sanitize().is_ok());
+    }
+
+    #[test]
+    fn test_builder_sets_rootfs_and_fields() {
+        let opts = BoxOptions::builder()
+            .image("alpine:3.20")
+            .cpus(2)
+            .memory_mib(512)
+            .env("A", "1")
+            .envs([("B", "2")])
+            .volume("/host", "/guest", true)
+            .detach(true)
+            .auto_remove(false)
+            .cmd(["sleep", "infinity"])
+            .build()
+            .unwrap();
+        assert!(matches!(opts.rootfs, RootfsSpec::Image(ref r) if r == "alpine:3.20"));
+        assert_eq!(opts.cpus, Some(2));
+        assert_eq!(opts.memory_mib, Some(512));
+        assert_eq!(
+            opts.env,
+            vec![("A".into(), "1".into()), ("B".into(), "2".into())]
+        );
+        assert_eq!(opts.volumes.len(), 1);
+        assert!(opts.volumes[0].read_only);
+        assert!(opts.detach && !opts.auto_remove);
+        assert_eq!(opts.cmd, Some(vec!["sleep".into(), "infinity".into()]));
+        opts.sanitize().unwrap();
+
+        let opts = BoxOptions::builder()
+            .rootfs_path("/srv/rootfs")
+            .build()
+            .unwrap();
+        assert!(matches!(opts.rootfs, RootfsSpec::RootfsPath(ref p) if p == "/srv/rootfs"));
+    }
+
+    #[test]
+    fn test_builder_reports_all_problems() {
+        let err = BoxOptions::builder()
+            .image("")
+            .detach(true)
+            .entrypoint_script("ulimit -n 4096")
+            .snapshot_retention(SnapshotRetention {
+                max_count: Some(0),
+                ..Default::default()
+            })
+            .build()
+            .unwrap_err();
+        let BoxliteError::Config(msg) = err else {
+            panic!("expected Config error, got {err:?}");
+        };
+        assert!(msg.starts_with("4 problems in box options"), "{msg}");
+        for expected in ["image reference", "#!", "max_count", "incompatible"] {
+            assert!(msg.contains(expected), "missing {expected:?} in {msg}");
+        }
+        assert!(!msg.contains("configuration error"), "{msg}");
+    }
+
+    #[test]
+    fn test_builder_single_problem_is_not_a_list() {
+        let err = BoxOptions::builder()
+            .image("alpine")
+            .userns(UserNsMode::Map {
+                uid_map: vec![],
+                gid_map: vec![],
+            })
+            .build()
+            .unwrap_err();
+        let BoxliteError::Config(msg) = err else {
+            panic!("expected Config error, got {err:?}");
+        };
+        assert_eq!(msg, "userns uid_map must contain at least one range");
+    }
+
     // ========================================================================
     // SecurityOptionsBuilder tests
     // ========================================================================
@@ -592,4 +2265,100 @@ mod tests {
         assert!(opts1.resource_limits.max_processes.is_none());
         assert_eq!(opts2.resource_limits.max_processes, Some(50));
     }
+
+    // userns option tests
+
+    fn id_map(container_id: u32, host_id: u32, size: u32) -> IdMapping {
+        IdMapping {
+            container_id,
+            host_id,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_box_options_userns_default_none() {
+        assert!(BoxOptions::default().userns.is_none());
+    }
+
+    #[test]
+    fn test_box_options_userns_serde() {
+        let json = r#"{
+            "rootfs": {"Image": "alpine"},
+            "env": [],
+            "volumes": [],
+            "network": "Isolated",
+            "ports": [],
+            "userns": {"Map": {
+                "uid_map": [{"container_id": 0, "host_id": 100000, "size": 65536}],
+                "gid_map": [{"container_id": 0, "host_id": 100000, "size": 65536}]
+            }}
+        }"#;
+        let opts: BoxOptions = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            opts.userns,
+            Some(UserNsMode::Map {
+                uid_map: vec![id_map(0, 100000, 65536)],
+                gid_map: vec![id_map(0, 100000, 65536)],
+            })
+        );
+        assert!(opts.sanitize().is_ok());
+    }
+
+    #[test]
+    fn test_userns_validate_rejects_bad_maps() {
+        let mode = |uid_map| UserNsMode::Map {
+            uid_map,
+            gid_map: vec![id_map(0, 100000, 65536)],
+        };
+
+        assert!(UserNsMode::Auto.validate().is_ok());
+        assert!(mode(vec![]).validate().is_err());
+        assert!(mode(vec![id_map(0, 100000, 0)]).validate().is_err());
+        assert!(mode(vec![id_map(0, u32::MAX, 2)]).validate().is_err());
+        assert!(
+            mode(vec![id_map(0, 100000, 1000), id_map(500, 200000, 1000)])
+                .validate()
+                .is_err()
+        );
+        assert!(
+            mode(vec![id_map(0, 100000, 1000), id_map(1000, 100500, 1000)])
+                .validate()
+                .is_err()
+        );
+        assert!(
+            mode(vec![id_map(0, 100000, 1000), id_map(1000, 200000, 1000)])
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_box_options_setup_commands_serde() {
+        let opts: BoxOptions = serde_json::from_str(r#"{"rootfs": {"Image": "alpine"}}"#).unwrap();
+        assert!(opts.setup_commands.is_empty());
+        assert_eq!(opts.setup_failure, SetupFailurePolicy::FailBox);
+
+        let json = r#"{
+            "rootfs": {"Image": "alpine"},
+            "setup_commands": [
+                {"command": "apk", "args": ["add", "curl"]},
+                {"command": "true", "timeout": {"secs": 30, "nanos": 0}}
+            ],
+            "setup_failure": "warn_and_continue"
+        }"#;
+        let opts: BoxOptions = serde_json::from_str(json).unwrap();
+        assert_eq!(opts.setup_failure, SetupFailurePolicy::WarnAndContinue);
+        assert_eq!(opts.setup_commands.len(), 2);
+        assert_eq!(opts.setup_commands[0].args, vec!["add", "curl"]);
+        assert_eq!(
+            opts.setup_commands[1].timeout,
+            Some(Duration::from_secs(30))
+        );
+
+        let again: BoxOptions =
+            serde_json::from_str(&serde_json::to_string(&opts).unwrap()).unwrap();
+        assert_eq!(again.setup_commands[0].command, "apk");
+        assert_eq!(again.setup_failure, SetupFailurePolicy::WarnAndContinue);
+    }
 }
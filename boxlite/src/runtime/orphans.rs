@@ -0,0 +1,298 @@
+//! Orphaned shim processes.
+//!
+//! A shim is orphaned when its box is gone: no record in the database, or a
+//! record whose home directory was deleted. Nothing will ever stop such a
+//! shim, so it holds its VM's memory until the host reboots.
+//! `list_orphans()` finds them in the process table and `kill_orphans()`
+//! reclaims them.
+//!
+//! Shims are recognized by their `--config` argument, the JSON
+//! `InstanceSpec` they were spawned with. Its `home_dir` tells the shims of
+//! this runtime from those of runtimes with another `BOXLITE_HOME`.
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use boxlite_shared::errors::BoxliteResult;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::types::BoxID;
+
+use super::rt_impl::RuntimeImpl;
+
+/// How often `kill_orphans()` checks whether a signalled shim has exited.
+const EXIT_POLL: Duration = Duration::from_millis(50);
+
+/// How long `kill_orphans()` waits for a shim to die after SIGKILL.
+const KILL_WAIT: Duration = Duration::from_secs(1);
+
+/// Why a shim counts as orphaned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrphanReason {
+    /// No box with the shim's ID is recorded.
+    NoRecord,
+    /// The box is recorded, but its home directory is gone.
+    HomeMissing,
+}
+
+impl std::fmt::Display for OrphanReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            OrphanReason::NoRecord => "no box record",
+            OrphanReason::HomeMissing => "box home missing",
+        })
+    }
+}
+
+/// A shim process whose box no longer exists.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanShim {
+    pub pid: u32,
+    /// ID of the box the shim was started for.
+    pub box_id: String,
+    /// When the shim started, if the OS reports it.
+    pub started_at: Option<DateTime<Utc>>,
+    /// Resident memory of the shim, VM included.
+    pub rss_bytes: u64,
+    pub reason: OrphanReason,
+}
+
+/// What a shim's `--config` argument says about it.
+#[derive(Debug, PartialEq)]
+struct ShimIdentity {
+    box_id: String,
+    home_dir: PathBuf,
+    sockets: Vec<PathBuf>,
+}
+
+impl RuntimeImpl {
+    /// Shims of this runtime's home whose box no longer exists.
+    pub(crate) fn list_orphans(&self) -> BoxliteResult<Vec<OrphanShim>> {
+        Ok(self
+            .scan_orphans()?
+            .into_iter()
+            .map(|(orphan, _)| orphan)
+            .collect())
+    }
+
+    /// Orphaned shims along with the sockets they were spawned with.
+    fn scan_orphans(&self) -> BoxliteResult<Vec<(OrphanShim, Vec<PathBuf>)>> {
+        let mut sys = sysinfo::System::new();
+        sys.refresh_processes();
+
+        let mut orphans = Vec::new();
+        for (pid, process) in sys.processes() {
+            if !process.name().contains("boxlite-shim") {
+                continue;
+            }
+            let Some(identity) = parse_shim_args(process.cmd()) else {
+                continue;
+            };
+            if identity.home_dir != self.layout.home_dir() {
+                continue;
+            }
+            let Some(reason) = self.orphan_reason(&identity.box_id)? else {
+                continue;
+            };
+            let orphan = OrphanShim {
+                pid: pid.as_u32(),
+                box_id: identity.box_id,
+                started_at: DateTime::from_timestamp(process.start_time() as i64, 0),
+                rss_bytes: process.memory(),
+                reason,
+            };
+            orphans.push((orphan, identity.sockets));
+        }
+        orphans.sort_by_key(|(orphan, _)| orphan.pid);
+        Ok(orphans)
+    }
+
+    /// Kill the orphaned shims: SIGTERM, then SIGKILL after `grace`.
+    ///
+    /// Returns the shims that were killed. Shims that survive SIGKILL (not
+    /// ours to signal) are logged and left out.
+    pub(crate) async fn kill_orphans(&self, grace: Duration) -> BoxliteResult<Vec<OrphanShim>> {
+        let mut killed = Vec::new();
+        for (orphan, sockets) in self.scan_orphans()? {
+            // The PID may have been reused since the scan
+            if !crate::util::is_same_process(orphan.pid, &orphan.box_id) {
+                continue;
+            }
+            if !terminate(orphan.pid, grace).await {
+                tracing::warn!(
+                    box_id = %orphan.box_id,
+                    pid = orphan.pid,
+                    "Failed to kill orphaned shim"
+                );
+                continue;
+            }
+            tracing::info!(box_id = %orphan.box_id, pid = orphan.pid, "Killed orphaned shim");
+            crate::util::remove_stale_sockets(&sockets);
+            killed.push(orphan);
+        }
+        Ok(killed)
+    }
+
+    /// Why the shim of box `box_id` is orphaned, or None if its box exists.
+    fn orphan_reason(&self, box_id: &str) -> BoxliteResult<Option<OrphanReason>> {
+        let Some(id) = BoxID::parse(box_id) else {
+            return Ok(Some(OrphanReason::NoRecord));
+        };
+        // Boxes starting for the first time are not recorded yet
+        if self.is_box_active(&id) {
+            return Ok(None);
+        }
+        Ok(match self.box_manager.box_by_id(&id)? {
+            None => Some(OrphanReason::NoRecord),
+            Some((config, _)) if !config.box_home.exists() => Some(OrphanReason::HomeMissing),
+            Some(_) => None,
+        })
+    }
+}
+
+/// Read the box ID, home and sockets from a shim's command line.
+fn parse_shim_args(args: &[String]) -> Option<ShimIdentity> {
+    let config = args
+        .iter()
+        .position(|arg| arg == "--config")
+        .and_then(|i| args.get(i + 1))?;
+    let spec: serde_json::Value = serde_json::from_str(config).ok()?;
+
+    let box_id = spec.get("box_id")?.as_str()?.to_string();
+    let home_dir = PathBuf::from(spec.get("home_dir")?.as_str()?);
+    let sockets = [
+        &spec["transport"]["Unix"]["socket_path"],
+        &spec["ready_transport"]["Unix"]["socket_path"],
+        &spec["network_config"]["socket_path"],
+    ]
+    .into_iter()
+    .filter_map(|path| path.as_str().map(PathBuf::from))
+    .collect();
+
+    Some(ShimIdentity {
+        box_id,
+        home_dir,
+        sockets,
+    })
+}
+
+/// SIGTERM `pid`, then SIGKILL it if it outlives `grace`. Returns whether it
+/// is gone.
+async fn terminate(pid: u32, grace: Duration) -> bool {
+    unsafe {
+        libc::kill(pid as i32, libc::SIGTERM);
+    }
+    if wait_for_exit(pid, grace).await {
+        return true;
+    }
+    tracing::warn!(
+        pid,
+        "Orphaned shim didn't exit after SIGTERM, force killing"
+    );
+    crate::util::kill_process(pid);
+    wait_for_exit(pid, KILL_WAIT).await
+}
+
+async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if !crate::util::is_process_alive(pid) {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(EXIT_POLL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shim_args(config: serde_json::Value) -> Vec<String> {
+        vec![
+            "/usr/libexec/boxlite-shim".to_string(),
+            "--engine".to_string(),
+            "Libkrun".to_string(),
+            "--config".to_string(),
+            config.to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_parse_shim_args() {
+        let args = shim_args(serde_json::json!({
+            "box_id": "01HZY0000000000000000000AB",
+            "home_dir": "/home/user/.boxlite",
+            "transport": { "Unix": { "socket_path": "/b/sockets/box.sock" } },
+            "ready_transport": { "Unix": { "socket_path": "/b/sockets/ready.sock" } },
+            "network_config": { "port_mappings": [], "socket_path": "/b/sockets/net.sock" },
+        }));
+
+        assert_eq!(
+            parse_shim_args(&args),
+            Some(ShimIdentity {
+                box_id: "01HZY0000000000000000000AB".to_string(),
+                home_dir: PathBuf::from("/home/user/.boxlite"),
+                sockets: vec![
+                    PathBuf::from("/b/sockets/box.sock"),
+                    PathBuf::from("/b/sockets/ready.sock"),
+                    PathBuf::from("/b/sockets/net.sock"),
+                ],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_shim_args_skips_non_unix_transports() {
+        let args = shim_args(serde_json::json!({
+            "box_id": "01HZY0000000000000000000AB",
+            "home_dir": "/home/user/.boxlite",
+            "transport": { "Vsock": { "port": 2695 } },
+            "ready_transport": { "Unix": { "socket_path": "/b/sockets/ready.sock" } },
+            "network_config": null,
+        }));
+
+        let identity = parse_shim_args(&args).unwrap();
+        assert_eq!(
+            identity.sockets,
+            vec![PathBuf::from("/b/sockets/ready.sock")]
+        );
+    }
+
+    #[test]
+    fn test_parse_shim_args_rejects_other_commands() {
+        assert_eq!(parse_shim_args(&["boxlite-shim".to_string()]), None);
+        assert_eq!(
+            parse_shim_args(&["boxlite-shim".to_string(), "--config".to_string()]),
+            None
+        );
+        assert_eq!(
+            parse_shim_args(&shim_args(serde_json::json!({ "home_dir": "/h" }))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_orphan_reason() {
+        let temp_dir = tempfile::TempDir::new_in("/tmp").unwrap();
+        let runtime = RuntimeImpl::new(crate::runtime::options::BoxliteOptions {
+            home_dir: temp_dir.path().to_path_buf(),
+            image_registries: vec![],
+            ..Default::default()
+        })
+        .unwrap();
+
+        assert_eq!(
+            runtime.orphan_reason(BoxID::new().as_str()).unwrap(),
+            Some(OrphanReason::NoRecord)
+        );
+        assert_eq!(
+            runtime.orphan_reason("not-a-box-id").unwrap(),
+            Some(OrphanReason::NoRecord)
+        );
+    }
+}
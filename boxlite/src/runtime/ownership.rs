@@ -0,0 +1,65 @@
+//! Handing the boxes of a process over to a later one.
+//!
+//! Before the embedding process exits for an upgrade, `detach_all` lets go
+//! of every box it owns, so they keep running. The next process recovers
+//! them as running boxes on startup and takes them back with `adopt_all`.
+
+use std::sync::Arc;
+
+use boxlite_shared::errors::BoxliteResult;
+
+use crate::litebox::BoxState;
+use crate::litebox::config::BoxConfig;
+use crate::runtime::bulk::BulkResults;
+
+use super::rt_impl::RuntimeImpl;
+
+impl RuntimeImpl {
+    /// Detach every running box this process owns.
+    pub(crate) async fn detach_all(self: &Arc<Self>) -> BoxliteResult<BulkResults<()>> {
+        let pid = std::process::id();
+        let owned = self
+            .box_manager
+            .all_boxes(true)?
+            .into_iter()
+            .filter(|(_, state)| is_transferable(state) && state.owner_pid == Some(pid));
+
+        let mut results = Vec::new();
+        for (config, state) in owned {
+            let box_id = config.id.clone();
+            let (box_impl, _) = self.get_or_create_box_impl(config, state);
+            results.push((box_id, box_impl.detach().await));
+        }
+        Ok(results)
+    }
+
+    /// Adopt every running box started with `detach = false` that no live
+    /// process owns.
+    pub(crate) async fn adopt_all(self: &Arc<Self>) -> BoxliteResult<BulkResults<()>> {
+        let released = self
+            .box_manager
+            .all_boxes(true)?
+            .into_iter()
+            .filter(|(config, state)| is_transferable(state) && is_unowned(config, state));
+
+        let mut results = Vec::new();
+        for (config, state) in released {
+            let box_id = config.id.clone();
+            let (box_impl, _) = self.get_or_create_box_impl(config, state);
+            results.push((box_id, box_impl.adopt().await));
+        }
+        Ok(results)
+    }
+}
+
+/// Running boxes outside the warm pools, which the runtime drains itself.
+fn is_transferable(state: &BoxState) -> bool {
+    state.status.is_running() && state.warm_pool.is_none()
+}
+
+fn is_unowned(config: &BoxConfig, state: &BoxState) -> bool {
+    !config.options.detach
+        && state
+            .owner_pid
+            .is_none_or(|pid| !crate::util::is_process_alive(pid))
+}
@@ -1,23 +1,37 @@
 //! Box import operations.
 //!
-//! Import recreates a box from a `.boxsnap` or `.boxlite` archive.
+//! Import recreates a box from a `.boxsnap` or `.boxlite` archive, under a
+//! name chosen by [`ImportOptions`].
 //!
 //! Supports two archive formats:
-//! - v2 (`.boxsnap`): tar.zst compressed with SHA-256 checksums
+//! - v2 (`.boxsnap`): tar.zst compressed with SHA-256 checksums, optionally
+//!   in the zstd seekable format with member offsets in the manifest
 //! - v1 (`.boxlite`): plain tar (legacy, backward compatible)
+//!
+//! Seekable archives are valid tar.zst streams, so import reads them like
+//! any other v2 archive; `extract_from_archive` uses the seek table to pull
+//! a single member without decompressing the rest.
 
-use std::path::Path;
+use std::fs::File;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use boxlite_shared::archive::{self, ArchiveLimits};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
+use crate::disk::DiskDriverKind;
 use crate::disk::constants::filenames as disk_filenames;
 use crate::litebox::LiteBox;
 use crate::litebox::config::{BoxConfig, ContainerRuntimeConfig};
+use crate::lock::BoxOperation;
 use crate::runtime::constants::filenames as rt_filenames;
 use crate::runtime::options::{BoxOptions, RootfsSpec};
-use crate::runtime::types::{BoxID, BoxState, BoxStatus, ContainerID};
+use crate::runtime::rt_impl::RuntimeImpl;
+use crate::runtime::seekable::SeekableReader;
+use crate::runtime::types::{BoxID, BoxState, BoxStatus, ContainerID, RemovePlan};
 use crate::vmm::VmmKind;
 
 /// Archive manifest stored as `manifest.json` inside the archive.
@@ -35,14 +49,94 @@ pub struct ArchiveManifest {
     pub guest_disk_checksum: String,
     /// SHA-256 checksum of the container disk.
     pub container_disk_checksum: String,
+    /// Tree digest of the guest rootfs disk (`sha256-tree:` followed by the
+    /// `util::checksum` tree digest), verifiable in parallel. Absent from
+    /// older archives and when there is no guest disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest_disk_tree_checksum: Option<String>,
+    /// Tree digest of the container disk, as for the guest disk.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container_disk_tree_checksum: Option<String>,
     /// Timestamp when the archive was created.
     pub exported_at: String,
+    /// Position of every member in the decompressed tar stream, including
+    /// `manifest.json` itself (seekable archives only).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entries: Vec<ArchiveEntry>,
+}
+
+/// A member of a seekable archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveEntry {
+    /// Path inside the archive (e.g. "disk.qcow2").
+    pub path: String,
+    /// Offset of the member's data in the decompressed tar stream.
+    pub offset: u64,
+    /// Size of the member's data in bytes.
+    pub size: u64,
 }
 
 const MAX_SUPPORTED_VERSION: u32 = 2;
 const MANIFEST_FILENAME: &str = "manifest.json";
+const TAR_BLOCK: u64 = 512;
+/// Leading bytes of a zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xB5, 0x2F, 0xFD];
 
-impl super::BoxliteRuntime {
+/// What [`import_with`](super::BoxliteRuntime::import_with) does when a box
+/// already has the requested name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportConflict {
+    /// Fail with `AlreadyExists`.
+    #[default]
+    Fail,
+    /// Replace the existing box, which must not be running. The existing box
+    /// is only removed once the import has succeeded.
+    Overwrite,
+    /// Import under the first free name of `{name}-1`, `{name}-2`, ...
+    Suffix,
+}
+
+/// Options for [`BoxliteRuntime::import_with`](super::BoxliteRuntime::import_with).
+#[derive(Debug, Clone, Default)]
+pub struct ImportOptions {
+    /// Name of the imported box; `None` leaves it unnamed, like `create`.
+    pub name: Option<String>,
+    /// What to do when a box already has `name`.
+    pub on_conflict: ImportConflict,
+}
+
+/// What importing an archive would do, from
+/// [`BoxliteRuntime::plan_import`](super::BoxliteRuntime::plan_import).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportPlan {
+    /// Name the imported box would take; `None` leaves it unnamed. With
+    /// `ImportConflict::Suffix` this is the first name free right now.
+    pub name: Option<String>,
+    /// Size of the archive. The extracted disks take at least this much.
+    pub archive_bytes: u64,
+    /// Box an `ImportConflict::Overwrite` import would replace. Its disks are
+    /// deleted, never moved to the trash.
+    pub replaces: Option<RemovePlan>,
+}
+
+/// Where an imported box goes, resolved from its name and conflict mode.
+pub(super) enum ImportTarget {
+    Unnamed,
+    Named(String),
+    /// Take the name of the stopped box with this ID, replacing it.
+    Replace(BoxID, String),
+}
+
+impl ImportTarget {
+    fn name(&self) -> Option<&str> {
+        match self {
+            Self::Unnamed => None,
+            Self::Named(name) | Self::Replace(_, name) => Some(name),
+        }
+    }
+}
+
+impl RuntimeImpl {
     /// Import a box from a `.boxsnap` or `.boxlite` archive.
     ///
     /// Creates a new box with a new ID from the archived disk images and
@@ -54,8 +148,14 @@ impl super::BoxliteRuntime {
     /// # Returns
     ///
     /// A LiteBox handle for the newly created box.
-    pub async fn import(&self, archive_path: &Path, name: &str) -> BoxliteResult<LiteBox> {
-        let rt = &self.rt_impl;
+    pub(crate) async fn import(
+        self: &Arc<Self>,
+        archive_path: &Path,
+        options: ImportOptions,
+    ) -> BoxliteResult<LiteBox> {
+        if let Some(name) = &options.name {
+            crate::validate::box_name(name)?;
+        }
 
         if !archive_path.exists() {
             return Err(BoxliteError::NotFound(format!(
@@ -64,12 +164,19 @@ impl super::BoxliteRuntime {
             )));
         }
 
-        // Extract archive to temp directory
-        let temp_dir = tempfile::tempdir_in(rt.layout.temp_dir()).map_err(|e| {
+        // Fail a taken name before the potentially long extraction
+        self.import_target(options.name.as_deref(), options.on_conflict)?;
+
+        // Extracted disks take at least as much as the (compressed) archive
+        let archive_bytes = std::fs::metadata(archive_path).map_or(0, |m| m.len());
+        self.disk_space
+            .ensure(&self.layout.temp_dir(), archive_bytes, "import box")?;
+
+        // Extract archive to temp directory; it is removed on any failure
+        let temp_dir = tempfile::tempdir_in(self.layout.temp_dir()).map_err(|e| {
             BoxliteError::Storage(format!("Failed to create temp directory: {}", e))
         })?;
 
-        // Try zstd decompression first, fall back to plain tar
         extract_archive(archive_path, temp_dir.path())?;
 
         // Read and validate manifest
@@ -100,16 +207,28 @@ impl super::BoxliteRuntime {
             )));
         }
 
+        // Boxes may have come and gone during the extraction
+        let target = self.import_target(options.name.as_deref(), options.on_conflict)?;
+
         // Generate new box identity
         let box_id = BoxID::new();
         let container_id = ContainerID::new();
         let now = Utc::now();
-        let import_name = Some(name.to_string());
 
-        let box_home = rt.layout.boxes_dir().join(box_id.as_str());
-        let socket_path = rt_filenames::unix_socket_path(rt.layout.home_dir(), box_id.as_str());
+        let box_home = self.layout.boxes_dir().join(box_id.as_str());
+        let socket_path = rt_filenames::unix_socket_path(self.layout.home_dir(), box_id.as_str());
         let ready_socket_path = box_home.join("sockets").join("ready.sock");
 
+        // Reconstruct BoxOptions from the image reference.
+        // Imported boxes use default runtime config; disk state is fully preserved.
+        let mut box_options = BoxOptions {
+            rootfs: RootfsSpec::Image(manifest.image),
+            ..Default::default()
+        };
+        let degradations = self
+            .capabilities
+            .admit(&mut box_options.advanced.security)?;
+
         // Create box directory
         std::fs::create_dir_all(&box_home).map_err(|e| {
             BoxliteError::Storage(format!(
@@ -120,116 +239,628 @@ impl super::BoxliteRuntime {
         })?;
 
         // Move disk files into box directory
-        std::fs::rename(
-            &extracted_container,
-            box_home.join(disk_filenames::CONTAINER_DISK),
-        )
-        .map_err(|e| BoxliteError::Storage(format!("Failed to install container disk: {}", e)))?;
-
-        let extracted_guest = temp_dir.path().join(disk_filenames::GUEST_ROOTFS_DISK);
-        if extracted_guest.exists() {
-            std::fs::rename(
-                &extracted_guest,
-                box_home.join(disk_filenames::GUEST_ROOTFS_DISK),
-            )
-            .map_err(|e| {
-                BoxliteError::Storage(format!("Failed to install guest rootfs disk: {}", e))
-            })?;
+        if let Err(e) = install_disks(temp_dir.path(), &box_home) {
+            let _ = std::fs::remove_dir_all(&box_home);
+            return Err(e);
         }
 
-        // Reconstruct BoxOptions from the image reference.
-        // Imported boxes use default runtime config; disk state is fully preserved.
-        let options = BoxOptions {
-            rootfs: RootfsSpec::Image(manifest.image),
-            ..Default::default()
-        };
-
         // Build config for the imported box
         let config = BoxConfig {
             id: box_id.clone(),
-            name: import_name,
+            name: target.name().map(str::to_string),
             created_at: now,
             container: ContainerRuntimeConfig { id: container_id },
-            options,
+            options: box_options,
             engine_kind: VmmKind::Libkrun,
             transport: boxlite_shared::Transport::unix(socket_path),
             box_home,
             ready_socket_path,
+            // Archives always carry standalone qcow2 disks.
+            disk_driver: DiskDriverKind::Qcow2,
+            lineage: None,
+            degradations,
         };
 
         // Create state as Stopped (box has disk state, just needs VM start)
         let mut state = BoxState::new();
-        state.set_status(BoxStatus::Stopped);
+        state.set_status(BoxStatus::Exited);
 
         // Allocate lock
-        let lock_id = rt.lock_manager.allocate()?;
+        let lock_id = match self.lock_manager.allocate() {
+            Ok(lock_id) => lock_id,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&config.box_home);
+                return Err(e);
+            }
+        };
         state.set_lock_id(lock_id);
 
-        // Persist to database
-        if let Err(e) = rt.box_manager.add_box(&config, &state) {
-            let _ = rt.lock_manager.free(lock_id);
+        // Persist to database, swapping out the replaced box in the same
+        // transaction so that one of the two always survives
+        let persisted = match &target {
+            ImportTarget::Replace(old_id, _) => {
+                self.replace_with_import(old_id, &config, &state).await
+            }
+            ImportTarget::Unnamed | ImportTarget::Named(_) => {
+                self.box_manager.add_box(&config, &state)
+            }
+        };
+        if let Err(e) = persisted {
+            let _ = self.lock_manager.free(lock_id);
             let _ = std::fs::remove_dir_all(&config.box_home);
             return Err(e);
         }
 
         tracing::info!(
             box_id = %config.id,
+            name = ?config.name,
             archive = %archive_path.display(),
             "Imported box from archive"
         );
 
         // Return a LiteBox handle
-        rt.get(box_id.as_str()).await?.ok_or_else(|| {
+        self.get(box_id.as_str()).await?.ok_or_else(|| {
             BoxliteError::Internal("Imported box not found after persist".to_string())
         })
     }
+
+    /// Describe what `import` would do, without extracting anything.
+    ///
+    /// Runs the checks `import` makes before extracting: the name, the
+    /// conflict mode and the free disk space. The archive's contents are
+    /// not read, so a corrupt archive still only fails the real import.
+    pub(crate) fn plan_import(
+        &self,
+        archive_path: &Path,
+        options: &ImportOptions,
+    ) -> BoxliteResult<ImportPlan> {
+        if let Some(name) = &options.name {
+            crate::validate::box_name(name)?;
+        }
+
+        let archive_bytes = std::fs::metadata(archive_path)
+            .map_err(|_| {
+                BoxliteError::NotFound(format!("Archive not found: {}", archive_path.display()))
+            })?
+            .len();
+
+        let target = self.import_target(options.name.as_deref(), options.on_conflict)?;
+        self.disk_space
+            .ensure(&self.layout.temp_dir(), archive_bytes, "import box")?;
+
+        let replaces = match &target {
+            ImportTarget::Replace(old_id, _) => {
+                Some(self.plan_remove(old_id.as_str(), false, true)?)
+            }
+            ImportTarget::Unnamed | ImportTarget::Named(_) => None,
+        };
+        Ok(ImportPlan {
+            name: target.name().map(str::to_string),
+            archive_bytes,
+            replaces,
+        })
+    }
+
+    /// Resolve the name an import of `name` takes under `on_conflict`.
+    pub(super) fn import_target(
+        &self,
+        name: Option<&str>,
+        on_conflict: ImportConflict,
+    ) -> BoxliteResult<ImportTarget> {
+        let Some(name) = name else {
+            return Ok(ImportTarget::Unnamed);
+        };
+        let Some((existing, state)) = self.box_manager.lookup_box(name)? else {
+            return Ok(ImportTarget::Named(name.to_string()));
+        };
+
+        match on_conflict {
+            ImportConflict::Fail => Err(BoxliteError::AlreadyExists(format!(
+                "box with name '{}' already exists",
+                name
+            ))),
+            // Only a box of that exact name is replaced, never one whose ID
+            // merely starts with it
+            ImportConflict::Overwrite if existing.name.as_deref() != Some(name) => {
+                Err(BoxliteError::AlreadyExists(format!(
+                    "name '{}' refers to box {}",
+                    name, existing.id
+                )))
+            }
+            ImportConflict::Overwrite if state.status.is_active() => {
+                Err(overwrite_active_error(name, state.status))
+            }
+            ImportConflict::Overwrite => Ok(ImportTarget::Replace(existing.id, name.to_string())),
+            ImportConflict::Suffix => {
+                for n in 1.. {
+                    let candidate = format!("{}-{}", name, n);
+                    crate::validate::box_name(&candidate)?;
+                    if self.box_manager.lookup_box(&candidate)?.is_none() {
+                        return Ok(ImportTarget::Named(candidate));
+                    }
+                }
+                unreachable!("unbounded name suffix search")
+            }
+        }
+    }
+
+    /// Persist an imported box in place of stopped box `old_id`, then free
+    /// what the old box held.
+    async fn replace_with_import(
+        &self,
+        old_id: &BoxID,
+        config: &BoxConfig,
+        state: &BoxState,
+    ) -> BoxliteResult<()> {
+        let _lock = self.lock_box(old_id, BoxOperation::Remove).await?;
+        let (old_config, old_state) = self
+            .box_manager
+            .box_by_id(old_id)?
+            .ok_or_else(|| BoxliteError::NotFound(old_id.to_string()))?;
+        if old_state.status.is_active() {
+            let name = old_config.name.as_deref().unwrap_or(old_id.as_str());
+            return Err(overwrite_active_error(name, old_state.status));
+        }
+
+        self.box_manager.replace_box(old_id, config, state)?;
+        self.release_removed_box(&old_config, &old_state);
+        tracing::info!(box_id = %old_id, replaced_by = %config.id, "Removed box replaced by import");
+        Ok(())
+    }
 }
 
-/// Extract an archive, auto-detecting format (try zstd first, then plain tar).
-fn extract_archive(archive_path: &Path, dest_dir: &Path) -> BoxliteResult<()> {
-    let file = std::fs::File::open(archive_path).map_err(|e| {
+fn overwrite_active_error(name: &str, status: BoxStatus) -> BoxliteError {
+    BoxliteError::InvalidState(format!(
+        "cannot overwrite box '{}' (status: {:?}); stop it first",
+        name, status
+    ))
+}
+
+impl super::BoxliteRuntime {
+    /// Extract a single member of a `.boxsnap` or `.boxlite` archive.
+    ///
+    /// `inner_path` names the member (e.g. `manifest.json`,
+    /// `disk.qcow2`). When `dest` is a directory the member is written into
+    /// it under its own name; otherwise `dest` is the file to write.
+    ///
+    /// Seekable archives (`ExportOptions::seekable`) are read through their
+    /// seek table, decompressing only the frames that hold the member. Other
+    /// archives are streamed up to the member, without extracting the rest.
+    ///
+    /// # Returns
+    ///
+    /// The path of the extracted file.
+    ///
+    /// # Errors
+    ///
+    /// - `NotFound` if the archive or the member does not exist
+    /// - `Storage` if the archive is unreadable or its index is inconsistent
+    pub async fn extract_from_archive(
+        &self,
+        archive_path: &Path,
+        inner_path: &str,
+        dest: &Path,
+    ) -> BoxliteResult<PathBuf> {
+        if !archive_path.exists() {
+            return Err(BoxliteError::NotFound(format!(
+                "Archive not found: {}",
+                archive_path.display()
+            )));
+        }
+
+        let member = inner_path.trim_start_matches("./").trim_start_matches('/');
+        let dest = if dest.is_dir() {
+            let name = Path::new(member).file_name().ok_or_else(|| {
+                BoxliteError::InvalidArgument(format!("Invalid archive member '{}'", inner_path))
+            })?;
+            dest.join(name)
+        } else {
+            dest.to_path_buf()
+        };
+
+        let archive_path = archive_path.to_path_buf();
+        let member = member.to_string();
+        let out_path = dest.clone();
+        tokio::task::spawn_blocking(move || extract_member(&archive_path, &member, &out_path))
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
+
+        Ok(dest)
+    }
+}
+
+/// Copy archive member `member` to `dest`.
+pub(crate) fn extract_member(archive_path: &Path, member: &str, dest: &Path) -> BoxliteResult<()> {
+    let file = open_archive(archive_path)?;
+    match SeekableReader::open(file)
+        .map_err(|e| BoxliteError::Storage(format!("Invalid seekable archive: {}", e)))?
+    {
+        Some(reader) => extract_seekable_member(reader, member, dest),
+        None => extract_streamed_member(open_archive(archive_path)?, member, dest),
+    }
+}
+
+/// Extract a member using the manifest index of a seekable archive.
+fn extract_seekable_member(
+    mut reader: SeekableReader<File>,
+    member: &str,
+    dest: &Path,
+) -> BoxliteResult<()> {
+    let storage =
+        |e: std::io::Error| BoxliteError::Storage(format!("Failed to read archive: {}", e));
+
+    // The manifest is always the first member
+    let mut header = Vec::with_capacity(TAR_BLOCK as usize);
+    reader
+        .read_range(0, TAR_BLOCK, &mut header)
+        .map_err(storage)?;
+    let manifest_size = tar_member_size(&header, MANIFEST_FILENAME)?;
+    let mut manifest_json = Vec::new();
+    reader
+        .read_range(TAR_BLOCK, manifest_size, &mut manifest_json)
+        .map_err(storage)?;
+    let manifest: ArchiveManifest = serde_json::from_slice(&manifest_json)
+        .map_err(|e| BoxliteError::Storage(format!("Invalid manifest: {}", e)))?;
+
+    let entry = manifest
+        .entries
+        .iter()
+        .find(|entry| entry.path == member)
+        .ok_or_else(|| BoxliteError::NotFound(format!("'{}' not found in archive", member)))?;
+
+    // Check the index against the member's own tar header
+    let header_offset = entry.offset.checked_sub(TAR_BLOCK).ok_or_else(|| {
+        BoxliteError::Storage(format!("Invalid archive index entry for '{}'", member))
+    })?;
+    let mut header = Vec::with_capacity(TAR_BLOCK as usize);
+    reader
+        .read_range(header_offset, TAR_BLOCK, &mut header)
+        .map_err(storage)?;
+    if tar_member_size(&header, member)? != entry.size {
+        return Err(BoxliteError::Storage(format!(
+            "Archive index does not match the size of '{}'",
+            member
+        )));
+    }
+
+    write_dest(dest, |out| reader.read_range(entry.offset, entry.size, out))
+}
+
+/// Extract a member by reading the tar stream up to it.
+fn extract_streamed_member(mut file: File, member: &str, dest: &Path) -> BoxliteResult<()> {
+    let mut magic = [0u8; 4];
+    let compressed = file.read_exact(&mut magic).is_ok() && magic == ZSTD_MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+
+    let stream: Box<dyn Read> = if compressed {
+        Box::new(
+            zstd::Decoder::new(file)
+                .map_err(|e| BoxliteError::Storage(format!("Not a zstd archive: {}", e)))?,
+        )
+    } else {
+        Box::new(file)
+    };
+
+    let read_err =
+        |e: std::io::Error| BoxliteError::Storage(format!("Failed to read archive: {}", e));
+    let mut archive = tar::Archive::new(stream);
+    for entry in archive.entries().map_err(read_err)? {
+        let mut entry = entry.map_err(read_err)?;
+        let path = entry.path().map_err(read_err)?;
+        if path.to_string_lossy().trim_start_matches("./") != member {
+            continue;
+        }
+        return write_dest(dest, |out| std::io::copy(&mut entry, out).map(|_| ()));
+    }
+
+    Err(BoxliteError::NotFound(format!(
+        "'{}' not found in archive",
+        member
+    )))
+}
+
+/// Size of the tar member whose header is `header`, checking its name.
+fn tar_member_size(header: &[u8], expected: &str) -> BoxliteResult<u64> {
+    let invalid = || BoxliteError::Storage(format!("Invalid tar header for '{}'", expected));
+    let header = tar::Header::from_byte_slice(header);
+    let path = header.path().map_err(|_| invalid())?;
+    if path.to_string_lossy() != expected {
+        return Err(invalid());
+    }
+    header.entry_size().map_err(|_| invalid())
+}
+
+/// Move the extracted disks from `extracted` into a new box's home.
+fn install_disks(extracted: &Path, box_home: &Path) -> BoxliteResult<()> {
+    std::fs::rename(
+        extracted.join(disk_filenames::CONTAINER_DISK),
+        box_home.join(disk_filenames::CONTAINER_DISK),
+    )
+    .map_err(|e| BoxliteError::Storage(format!("Failed to install container disk: {}", e)))?;
+
+    let extracted_guest = extracted.join(disk_filenames::GUEST_ROOTFS_DISK);
+    if extracted_guest.exists() {
+        std::fs::rename(
+            &extracted_guest,
+            box_home.join(disk_filenames::GUEST_ROOTFS_DISK),
+        )
+        .map_err(|e| {
+            BoxliteError::Storage(format!("Failed to install guest rootfs disk: {}", e))
+        })?;
+    }
+    Ok(())
+}
+
+fn open_archive(archive_path: &Path) -> BoxliteResult<File> {
+    File::open(archive_path).map_err(|e| {
         BoxliteError::Storage(format!(
             "Failed to open archive {}: {}",
             archive_path.display(),
             e
         ))
-    })?;
+    })
+}
 
-    // Try zstd-compressed tar first
-    match try_extract_zstd_tar(file, dest_dir) {
-        Ok(()) => return Ok(()),
-        Err(_) => {
-            // Fall back to plain tar
-            let file = std::fs::File::open(archive_path).map_err(|e| {
-                BoxliteError::Storage(format!(
-                    "Failed to reopen archive {}: {}",
-                    archive_path.display(),
-                    e
-                ))
-            })?;
-            extract_plain_tar(file, dest_dir)?;
-        }
+/// Create `dest` and fill it with `write`, removing it again on failure.
+fn write_dest(
+    dest: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> std::io::Result<()>,
+) -> BoxliteResult<()> {
+    let file = File::create(dest).map_err(|e| {
+        BoxliteError::Storage(format!("Failed to create {}: {}", dest.display(), e))
+    })?;
+    let mut out = BufWriter::new(file);
+    if let Err(e) = write(&mut out).and_then(|()| out.flush()) {
+        drop(out);
+        let _ = std::fs::remove_file(dest);
+        return Err(BoxliteError::Storage(format!(
+            "Failed to extract {}: {}",
+            dest.display(),
+            e
+        )));
     }
-
     Ok(())
 }
 
-/// Try to extract a zstd-compressed tar archive.
-fn try_extract_zstd_tar(file: std::fs::File, dest_dir: &Path) -> BoxliteResult<()> {
-    let decoder = zstd::Decoder::new(file)
-        .map_err(|e| BoxliteError::Storage(format!("Not a zstd archive: {}", e)))?;
-    let mut archive = tar::Archive::new(decoder);
-    archive
-        .unpack(dest_dir)
-        .map_err(|e| BoxliteError::Storage(format!("Failed to extract zstd tar: {}", e)))?;
-    Ok(())
+/// Extract an archive, zstd-compressed or plain tar, refusing unsafe entries.
+fn extract_archive(archive_path: &Path, dest_dir: &Path) -> BoxliteResult<()> {
+    let mut file = open_archive(archive_path)?;
+    let mut magic = [0u8; 4];
+    let compressed = file.read_exact(&mut magic).is_ok() && magic == ZSTD_MAGIC;
+    file.seek(SeekFrom::Start(0))?;
+
+    let stream: Box<dyn Read> = if compressed {
+        Box::new(
+            zstd::Decoder::new(file)
+                .map_err(|e| BoxliteError::Storage(format!("Not a zstd archive: {}", e)))?,
+        )
+    } else {
+        Box::new(file)
+    };
+    archive::unpack(
+        &mut tar::Archive::new(stream),
+        dest_dir,
+        ArchiveLimits::default(),
+    )
 }
 
-/// Extract a plain tar archive.
-fn extract_plain_tar(file: std::fs::File, dest_dir: &Path) -> BoxliteResult<()> {
-    let mut archive = tar::Archive::new(file);
-    archive
-        .unpack(dest_dir)
-        .map_err(|e| BoxliteError::Storage(format!("Failed to extract archive: {}", e)))?;
-    Ok(())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::BoxliteOptions;
+    use crate::runtime::rt_impl::SharedRuntimeImpl;
+
+    fn write_tar_zst(path: &Path, members: &[(&str, &[u8])]) {
+        let encoder = zstd::Encoder::new(File::create(path).unwrap(), 3).unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        for (name, data) in members {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    #[test]
+    fn test_extract_member_from_non_seekable_archive() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("box.boxsnap");
+        write_tar_zst(
+            &archive,
+            &[
+                (MANIFEST_FILENAME, b"{}"),
+                (disk_filenames::CONTAINER_DISK, b"disk"),
+            ],
+        );
+
+        let dest = dir.path().join("out");
+        extract_member(&archive, disk_filenames::CONTAINER_DISK, &dest).unwrap();
+        assert_eq!(std::fs::read(&dest).unwrap(), b"disk");
+
+        let missing = extract_member(&archive, "logs/console.log", &dir.path().join("x"));
+        assert!(matches!(missing, Err(BoxliteError::NotFound(_))));
+        assert!(!dir.path().join("x").exists());
+    }
+
+    #[test]
+    fn test_extract_archive_refuses_symlink_swap() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        std::fs::create_dir(&outside).unwrap();
+
+        let archive = dir.path().join("box.boxsnap");
+        let encoder = zstd::Encoder::new(File::create(&archive).unwrap(), 3).unwrap();
+        let mut builder = tar::Builder::new(encoder);
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "logs", &outside).unwrap();
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_mode(0o644);
+        builder
+            .append_data(&mut header, "logs/console.log", &b"{}"[..])
+            .unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let err = extract_archive(&archive, &dir.path().join("out")).unwrap_err();
+        assert!(matches!(err, BoxliteError::UnsafeArchive(_)), "{err}");
+        assert!(!outside.join("console.log").exists());
+    }
+
+    fn create_test_runtime() -> (SharedRuntimeImpl, tempfile::TempDir) {
+        let temp_dir = tempfile::TempDir::new_in("/tmp").unwrap();
+        let options = BoxliteOptions {
+            home_dir: temp_dir.path().to_path_buf(),
+            image_registries: vec![],
+            ..Default::default()
+        };
+        (RuntimeImpl::new(options).unwrap(), temp_dir)
+    }
+
+    /// Write a v2 archive, with a container disk if `disk` is set.
+    fn write_box_archive(path: &Path, disk: Option<&[u8]>) {
+        let manifest = serde_json::json!({
+            "version": 2,
+            "box_name": "exported",
+            "image": "alpine:latest",
+            "guest_disk_checksum": "",
+            "container_disk_checksum": "",
+            "exported_at": "2026-01-01T00:00:00Z",
+        })
+        .to_string();
+        let mut members = vec![(MANIFEST_FILENAME, manifest.as_bytes())];
+        if let Some(disk) = disk {
+            members.push((disk_filenames::CONTAINER_DISK, disk));
+        }
+        write_tar_zst(path, &members);
+    }
+
+    fn named(name: &str, on_conflict: ImportConflict) -> ImportOptions {
+        ImportOptions {
+            name: Some(name.to_string()),
+            on_conflict,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_conflict_modes() {
+        let (rt, dir) = create_test_runtime();
+        let archive = dir.path().join("box.boxsnap");
+        write_box_archive(&archive, Some(b"disk"));
+
+        let original = rt
+            .import(&archive, named("web", ImportConflict::Fail))
+            .await
+            .unwrap();
+        assert_eq!(original.name(), Some("web"));
+
+        let taken = rt
+            .import(&archive, named("web", ImportConflict::Fail))
+            .await;
+        assert!(matches!(taken, Err(BoxliteError::AlreadyExists(_))));
+
+        for expected in ["web-1", "web-2"] {
+            let suffixed = rt
+                .import(&archive, named("web", ImportConflict::Suffix))
+                .await
+                .unwrap();
+            assert_eq!(suffixed.name(), Some(expected));
+        }
+
+        let unnamed = rt.import(&archive, ImportOptions::default()).await.unwrap();
+        assert_eq!(unnamed.name(), None);
+
+        let (old_config, _) = rt.box_manager.box_by_id(original.id()).unwrap().unwrap();
+        let replacement = rt
+            .import(&archive, named("web", ImportConflict::Overwrite))
+            .await
+            .unwrap();
+        assert_eq!(replacement.name(), Some("web"));
+        assert_ne!(replacement.id(), original.id());
+        assert!(rt.box_manager.box_by_id(original.id()).unwrap().is_none());
+        assert!(!old_config.box_home.exists());
+        assert_eq!(rt.box_manager.all_boxes(false).unwrap().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_plan_import_changes_nothing() {
+        let (rt, dir) = create_test_runtime();
+        let archive = dir.path().join("box.boxsnap");
+        write_box_archive(&archive, Some(b"disk"));
+        let original = rt
+            .import(&archive, named("web", ImportConflict::Fail))
+            .await
+            .unwrap();
+
+        let plan = rt
+            .plan_import(&archive, &named("web", ImportConflict::Overwrite))
+            .unwrap();
+        assert_eq!(plan.name.as_deref(), Some("web"));
+        assert_eq!(
+            plan.archive_bytes,
+            std::fs::metadata(&archive).unwrap().len()
+        );
+        let replaced = plan.replaces.unwrap();
+        assert_eq!(&replaced.id, original.id());
+        assert!(!replaced.to_trash);
+
+        let plan = rt
+            .plan_import(&archive, &named("web", ImportConflict::Suffix))
+            .unwrap();
+        assert_eq!(plan.name.as_deref(), Some("web-1"));
+        assert!(plan.replaces.is_none());
+
+        assert!(matches!(
+            rt.plan_import(&archive, &named("web", ImportConflict::Fail)),
+            Err(BoxliteError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            rt.plan_import(
+                &dir.path().join("missing.boxsnap"),
+                &ImportOptions::default()
+            ),
+            Err(BoxliteError::NotFound(_))
+        ));
+
+        let (kept, _) = rt.box_manager.lookup_box("web").unwrap().unwrap();
+        assert_eq!(&kept.id, original.id());
+        assert_eq!(rt.box_manager.all_boxes(false).unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_failed_overwrite_keeps_original() {
+        let (rt, dir) = create_test_runtime();
+        let archive = dir.path().join("box.boxsnap");
+        write_box_archive(&archive, Some(b"disk"));
+        let original = rt
+            .import(&archive, named("web", ImportConflict::Fail))
+            .await
+            .unwrap();
+        let (config, mut state) = rt.box_manager.box_by_id(original.id()).unwrap().unwrap();
+        let disk = config.box_home.join(disk_filenames::CONTAINER_DISK);
+
+        // The import fails after the original was picked for replacement
+        let broken = dir.path().join("broken.boxsnap");
+        write_box_archive(&broken, None);
+        let result = rt
+            .import(&broken, named("web", ImportConflict::Overwrite))
+            .await;
+        assert!(matches!(result, Err(BoxliteError::Storage(_))));
+        let (kept, _) = rt.box_manager.lookup_box("web").unwrap().unwrap();
+        assert_eq!(&kept.id, original.id());
+        assert_eq!(std::fs::read(&disk).unwrap(), b"disk");
+        assert_eq!(rt.box_manager.all_boxes(false).unwrap().len(), 1);
+
+        // A running box is never overwritten
+        state.set_status(BoxStatus::Running);
+        rt.box_manager.save_box(original.id(), &state).unwrap();
+        let result = rt
+            .import(&archive, named("web", ImportConflict::Overwrite))
+            .await;
+        assert!(matches!(result, Err(BoxliteError::InvalidState(_))));
+        assert!(disk.exists());
+    }
 }
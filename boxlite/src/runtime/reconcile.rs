@@ -0,0 +1,204 @@
+//! Reconciling reported box status with the shim process.
+//!
+//! A runtime's view of a box can lag behind reality: a handle cached here
+//! keeps the state it last saw while the DB record moves on, and a shim
+//! that was killed leaves its box `Running` until something touches it.
+//! `get_info()` and `list_info()` correct what they return against the
+//! newest DB record and the shim's PID and exit files. Nothing is written
+//! back; the operations that act on a box keep their own checks.
+
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::litebox::{BoxState, BoxStatus};
+use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+use crate::runtime::types::BoxInfo;
+use crate::util::{is_process_alive, is_same_process, read_pid_file};
+use crate::vmm::ExitInfo;
+
+use super::rt_impl::RuntimeImpl;
+
+/// What the box directory shows about the box's shim.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Shim {
+    /// A live shim for this box with this PID.
+    Alive(u32),
+    /// No shim, a dead or reused PID, or a shim on its way out.
+    Gone,
+}
+
+impl RuntimeImpl {
+    /// Correct `info` against `persisted`, the box's DB record, and the
+    /// shim process.
+    ///
+    /// A DB record written after `info` (by another process, or through
+    /// another handle) replaces its status and PID, then the shim decides
+    /// between running and stopped.
+    pub(crate) fn reconcile_info(&self, info: &mut BoxInfo, persisted: Option<&BoxState>) {
+        if let Some(state) = persisted
+            && state.last_updated > info.last_updated
+        {
+            info.status = state.status;
+            info.pid = state.pid;
+            info.exit_code = state.exit_code.filter(|_| state.status.is_stopped());
+            info.last_updated = state.last_updated;
+            info.network = state.network.clone();
+        }
+
+        let layout = BoxFilesystemLayout::new(
+            self.layout.boxes_dir().join(info.id.as_str()),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        );
+        let shim = probe_shim(&layout, info.id.as_str());
+        let (status, pid) = reconcile_status(info.status, info.pid, shim);
+        if (status, pid) != (info.status, info.pid) {
+            tracing::debug!(
+                box_id = %info.id,
+                recorded = ?info.status,
+                recorded_pid = ?info.pid,
+                ?status,
+                ?pid,
+                "Reconciled box status with shim"
+            );
+            if info.status.is_running() && status.is_stopped() {
+                info.exit_code = crash_exit_code(&layout);
+            }
+            info.status = status;
+            info.pid = pid;
+        }
+    }
+}
+
+/// Exit code the box's shim recorded on its way out of its last run.
+///
+/// The exit file is only written on crashes and survives restarts, so it
+/// counts only when written after the PID file of the run.
+pub(crate) fn crash_exit_code(layout: &BoxFilesystemLayout) -> Option<i32> {
+    let exit_file = layout.exit_file_path();
+    match (modified(&exit_file), modified(&layout.pid_file_path())) {
+        (Some(exited), Some(started)) if exited > started => {
+            ExitInfo::from_file(&exit_file).map(|info| info.exit_code())
+        }
+        _ => None,
+    }
+}
+
+/// Check the shim recorded in the box's PID file.
+fn probe_shim(layout: &BoxFilesystemLayout, box_id: &str) -> Shim {
+    let pid_file = layout.pid_file_path();
+    let Ok(pid) = read_pid_file(&pid_file) else {
+        return Shim::Gone;
+    };
+    if !is_process_alive(pid) || !is_same_process(pid, box_id) {
+        return Shim::Gone;
+    }
+    // The shim writes its exit file on the way out; one written after the
+    // PID file means this shim is exiting
+    match (modified(&layout.exit_file_path()), modified(&pid_file)) {
+        (Some(exited), Some(started)) if exited > started => Shim::Gone,
+        _ => Shim::Alive(pid),
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Status and PID to report for a box recorded as `status`/`pid`.
+///
+/// Only `Running`, `Exited` and `Created` are corrected: transient states
+/// belong to an operation in progress somewhere, which settles them, and a
+/// `Dead` box stays dead whatever runs.
+fn reconcile_status(status: BoxStatus, pid: Option<u32>, shim: Shim) -> (BoxStatus, Option<u32>) {
+    match (status, shim) {
+        (BoxStatus::Running | BoxStatus::Exited | BoxStatus::Created, Shim::Alive(pid)) => {
+            (BoxStatus::Running, Some(pid))
+        }
+        (BoxStatus::Running, Shim::Gone) => (BoxStatus::Exited, None),
+        (BoxStatus::Exited | BoxStatus::Created, Shim::Gone) => (status, None),
+        _ => (status, pid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_live_shim_means_running() {
+        assert_eq!(
+            reconcile_status(BoxStatus::Exited, None, Shim::Alive(42)),
+            (BoxStatus::Running, Some(42))
+        );
+        assert_eq!(
+            reconcile_status(BoxStatus::Running, Some(41), Shim::Alive(42)),
+            (BoxStatus::Running, Some(42))
+        );
+    }
+
+    #[test]
+    fn test_gone_shim_means_exited() {
+        assert_eq!(
+            reconcile_status(BoxStatus::Running, Some(42), Shim::Gone),
+            (BoxStatus::Exited, None)
+        );
+        assert_eq!(
+            reconcile_status(BoxStatus::Created, None, Shim::Gone),
+            (BoxStatus::Created, None)
+        );
+    }
+
+    #[test]
+    fn test_transient_states_are_left_alone() {
+        assert_eq!(
+            reconcile_status(BoxStatus::Stopping, Some(42), Shim::Gone),
+            (BoxStatus::Stopping, Some(42))
+        );
+        assert_eq!(
+            reconcile_status(BoxStatus::Snapshotting, None, Shim::Alive(42)),
+            (BoxStatus::Snapshotting, None)
+        );
+    }
+
+    #[test]
+    fn test_crash_exit_code_comes_from_exit_file_of_last_run() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let layout = BoxFilesystemLayout::new(
+            dir.path().to_path_buf(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        );
+        let crash = r#"{"exit_code":134,"type":"signal","signal":"SIGABRT"}"#;
+
+        // Exit file without a run to belong to
+        std::fs::write(layout.exit_file_path(), crash).unwrap();
+        assert_eq!(crash_exit_code(&layout), None);
+
+        // Written by the run that started with the PID file
+        std::fs::write(layout.pid_file_path(), "42").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(layout.exit_file_path(), crash).unwrap();
+        assert_eq!(crash_exit_code(&layout), Some(134));
+
+        // Left over from an earlier run
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(layout.pid_file_path(), "43").unwrap();
+        assert_eq!(crash_exit_code(&layout), None);
+    }
+
+    #[test]
+    fn test_probe_without_pid_file_is_gone() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let layout = BoxFilesystemLayout::new(
+            dir.path().to_path_buf(),
+            FsLayoutConfig::without_bind_mount(),
+            false,
+        );
+        assert_eq!(probe_shim(&layout, "box"), Shim::Gone);
+
+        // A PID that is alive but not a shim for this box
+        std::fs::write(layout.pid_file_path(), std::process::id().to_string()).unwrap();
+        assert_eq!(probe_shim(&layout, "box"), Shim::Gone);
+    }
+}
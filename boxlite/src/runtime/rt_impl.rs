@@ -1,23 +1,35 @@
+use crate::db::compositions::ComposedMember;
+use crate::db::templates::BoxTemplate;
 use crate::db::{BoxStore, Database};
+use crate::disk::DiskDriverKind;
 use crate::images::{ImageDiskManager, ImageManager};
 use crate::init_logging_for;
 use crate::litebox::config::BoxConfig;
 use crate::litebox::{BoxManager, LiteBox, SharedBoxImpl};
-use crate::lock::{FileLockManager, LockManager};
+use crate::lock::{BoxOperation, FileLockManager, LockId, LockManager, OperationLock};
 use crate::metrics::{RuntimeMetrics, RuntimeMetricsStorage};
+use crate::runtime::capabilities::RuntimeCapabilities;
 use crate::runtime::constants::filenames;
+use crate::runtime::create_progress::{CreateObserver, CreatePhase, CreateProgress};
+use crate::runtime::disk_space::DiskSpace;
+use crate::runtime::drift::{GetOrCreateOutcome, GetOrCreatePolicy, changed_options};
 use crate::runtime::guest_rootfs::GuestRootfs;
 use crate::runtime::guest_rootfs_manager::GuestRootfsManager;
-use crate::runtime::layout::{FilesystemLayout, FsLayoutConfig};
+use crate::runtime::in_flight::InFlightOps;
+use crate::runtime::layout::{BoxFilesystemLayout, FilesystemLayout, FsLayoutConfig};
 use crate::runtime::lock::RuntimeLock;
 use crate::runtime::options::{BoxOptions, BoxliteOptions};
 use crate::runtime::signal_handler::timeout_to_duration;
-use crate::runtime::types::{BoxID, BoxInfo, BoxState, BoxStatus, ContainerID};
+use crate::runtime::types::{
+    BoxID, BoxInfo, BoxState, BoxStatus, ContainerID, ListOptions, RemovePlan,
+};
+use crate::runtime::warm_pool::{WarmPools, WarmSelector};
 use crate::vmm::VmmKind;
 use boxlite_shared::{BoxliteError, BoxliteResult, Transport};
 use chrono::Utc;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock, Weak};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock, Weak};
 use tokio::sync::OnceCell;
 use tokio_util::sync::CancellationToken;
 
@@ -61,6 +73,10 @@ pub struct RuntimeImpl {
     pub(crate) guest_rootfs: Arc<OnceCell<GuestRootfs>>,
     /// Runtime-wide metrics (AtomicU64 based, lock-free)
     pub(crate) runtime_metrics: RuntimeMetricsStorage,
+    /// Free space checks before space-hungry operations.
+    pub(crate) disk_space: DiskSpace,
+    /// Host capabilities, probed once at startup.
+    pub(crate) capabilities: RuntimeCapabilities,
 
     /// Per-entity lock manager for multiprocess-safe locking.
     ///
@@ -80,6 +96,26 @@ pub struct RuntimeImpl {
     /// Use `.is_cancelled()` for sync checks, `.cancelled()` for async select!.
     /// Child tokens are passed to each box via `.child_token()`.
     pub(crate) shutdown_token: CancellationToken,
+    /// Mutating operations in flight; closed when shutdown begins.
+    pub(crate) in_flight: InFlightOps,
+
+    /// How long removed boxes stay in the trash (None: delete immediately).
+    pub(crate) trash_retention: Option<std::time::Duration>,
+    /// How long mutating box operations wait on the per-box operation lock.
+    pub(crate) lock_wait: Option<std::time::Duration>,
+    /// Whether guests get other running boxes' names in their hosts file.
+    pub(crate) box_dns: bool,
+    /// Whether the runtime refuses network access (`BoxliteOptions::offline`).
+    pub(crate) offline: bool,
+    /// Host directories `copy_out` may write under (empty: any).
+    pub(crate) allowed_dest_roots: Vec<PathBuf>,
+    /// Disk driver new boxes are created with, resolved from
+    /// `BoxliteOptions::storage_driver`.
+    pub(crate) disk_driver: DiskDriverKind,
+    /// Pools of started boxes for `acquire_warm()`.
+    pub(crate) warm_pools: WarmPools,
+    /// Boxes watched for idle auto-stop (`BoxOptions::auto_stop_after_idle`).
+    pub(crate) idle_watches: Mutex<HashSet<BoxID>>,
 }
 
 /// Synchronized state protected by RwLock.
@@ -103,15 +139,6 @@ impl RuntimeImpl {
     ///
     /// Performs all initialization: filesystem setup, locks, managers, and box recovery.
     pub fn new(options: BoxliteOptions) -> BoxliteResult<SharedRuntimeImpl> {
-        let vmm_support = crate::vmm::host_check::check_virtualization_support().map_err(|e| {
-            BoxliteError::Internal(format!("Failed to check virtualization support: {}", e))
-        })?;
-
-        tracing::info!(
-            reason = %vmm_support.reason,
-            "Virtualization support verified"
-        );
-
         // Validate Early: Check preconditions before expensive work
         if !options.home_dir.is_absolute() {
             return Err(BoxliteError::Internal(format!(
@@ -126,6 +153,12 @@ impl RuntimeImpl {
         #[cfg(not(target_os = "linux"))]
         let fs_config = FsLayoutConfig::without_bind_mount();
 
+        let warm_pools = WarmPools::new(&options.warm_pool)?;
+        if let Some(policy) = &options.cpu_pressure {
+            policy.validate()?;
+        }
+        crate::runtime::templates::validate_template_specs(&options.templates)?;
+
         let layout = FilesystemLayout::new(options.home_dir.clone(), fs_config);
 
         layout.prepare().map_err(|e| {
@@ -138,13 +171,14 @@ impl RuntimeImpl {
 
         init_logging_for(&layout)?;
 
-        let runtime_lock = RuntimeLock::acquire(layout.home_dir()).map_err(|e| {
-            BoxliteError::Internal(format!(
-                "Failed to acquire runtime lock at {}: {}",
-                layout.home_dir().display(),
-                e
-            ))
-        })?;
+        let runtime_lock = RuntimeLock::acquire_waiting(layout.home_dir(), options.home_lock_wait)
+            .map_err(|e| {
+                BoxliteError::Internal(format!(
+                    "Failed to acquire runtime lock at {}: {}",
+                    layout.home_dir().display(),
+                    e
+                ))
+            })?;
 
         // Clean temp dir contents to avoid stale files from previous runs
         if let Ok(entries) = std::fs::read_dir(layout.temp_dir()) {
@@ -158,6 +192,16 @@ impl RuntimeImpl {
             }
         }
 
+        // Missing capabilities fail or degrade box creation, not the runtime
+        let capabilities = RuntimeCapabilities::probe(&layout)?;
+        for (capability, status) in capabilities.iter() {
+            if status.available {
+                tracing::info!(%capability, detail = %status.detail, "Host capability available");
+            } else {
+                tracing::warn!(%capability, detail = %status.detail, "Host capability missing");
+            }
+        }
+
         let db = Database::open(&layout.db_dir().join("boxlite.db")).map_err(|e| {
             BoxliteError::Storage(format!(
                 "Failed to initialize database at {}: {}",
@@ -166,16 +210,24 @@ impl RuntimeImpl {
             ))
         })?;
 
-        let image_manager =
-            ImageManager::new(layout.images_dir(), db.clone(), options.image_registries).map_err(
-                |e| {
-                    BoxliteError::Storage(format!(
-                        "Failed to initialize image manager at {}: {}",
-                        layout.images_dir().display(),
-                        e
-                    ))
-                },
-            )?;
+        let runtime_metrics = RuntimeMetricsStorage::new();
+        let image_manager = ImageManager::new(
+            layout.images_dir(),
+            db.clone(),
+            options.image_registries,
+            &options.registry_settings,
+            options.offline,
+            options.blob_cache_proxy.as_ref(),
+            options.blob_cache_dir.clone(),
+        )
+        .map_err(|e| {
+            BoxliteError::Storage(format!(
+                "Failed to initialize image manager at {}: {}",
+                layout.images_dir().display(),
+                e
+            ))
+        })?
+        .with_metrics(runtime_metrics.clone());
 
         let box_store = BoxStore::new(db);
 
@@ -194,10 +246,21 @@ impl RuntimeImpl {
             "Initialized lock manager"
         );
 
+        let disk_driver = DiskDriverKind::resolve(options.storage_driver, &layout.boxes_dir())?;
+        tracing::debug!(driver = %disk_driver, "Resolved storage driver");
+
+        let disk_space = DiskSpace::new(options.low_space_threshold_bytes, runtime_metrics.clone());
+
+        // Trashed boxes can be restored, so their disks' backing files count too
+        let box_roots = layout.box_roots();
         let image_disk_mgr =
-            ImageDiskManager::new(layout.image_layout().disk_images_dir(), layout.temp_dir());
+            ImageDiskManager::new(layout.image_layout().disk_images_dir(), layout.temp_dir())
+                .with_cap(options.image_cache_max_bytes, box_roots.clone())
+                .with_disk_space(disk_space.clone());
         let guest_rootfs_mgr =
-            GuestRootfsManager::new(layout.guest_rootfs_dir(), layout.temp_dir());
+            GuestRootfsManager::new(layout.guest_rootfs_dir(), layout.temp_dir())
+                .with_update_mode(options.guest_update)
+                .with_cap(options.rootfs_cache_max_bytes, box_roots);
 
         let inner = Arc::new(Self {
             sync_state: RwLock::new(SynchronizedState {
@@ -210,16 +273,44 @@ impl RuntimeImpl {
             image_disk_mgr,
             guest_rootfs_mgr,
             guest_rootfs: Arc::new(OnceCell::new()),
-            runtime_metrics: RuntimeMetricsStorage::new(),
+            runtime_metrics,
+            disk_space,
+            capabilities,
             lock_manager,
             _runtime_lock: runtime_lock,
             shutdown_token: CancellationToken::new(),
+            in_flight: InFlightOps::default(),
+            trash_retention: options.trash_retention,
+            lock_wait: options.lock_wait,
+            box_dns: options.box_dns,
+            offline: options.offline,
+            allowed_dest_roots: options.allowed_dest_roots.clone(),
+            disk_driver,
+            warm_pools,
+            idle_watches: Mutex::new(HashSet::new()),
         });
 
         tracing::debug!("initialized runtime");
 
+        inner.import_templates(&options.templates)?;
+
         // Recover boxes from database
         inner.recover_boxes()?;
+        inner.remove_stale_warm_boxes()?;
+
+        if inner.trash_retention.is_some() {
+            inner.spawn_trash_sweeper();
+        }
+        inner.fill_warm_pools();
+
+        #[cfg(target_os = "linux")]
+        if let Some(policy) = options.cpu_pressure {
+            inner.spawn_pressure_monitor(policy);
+        }
+        #[cfg(not(target_os = "linux"))]
+        if options.cpu_pressure.is_some() {
+            tracing::warn!("Host CPU pressure is only monitored on Linux, cpu_pressure ignored");
+        }
 
         Ok(inner)
     }
@@ -243,6 +334,51 @@ impl RuntimeImpl {
         Ok(litebox)
     }
 
+    /// Create a box, preparing everything its first start needs and
+    /// reporting each phase to `observer`.
+    ///
+    /// Unlike [`create`](Self::create), this pulls the image and builds the
+    /// shared image and guest disks up front, so the first start only has to
+    /// create the box's COW overlays.
+    pub async fn create_with_observer(
+        self: &Arc<Self>,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: CreateObserver,
+    ) -> BoxliteResult<LiteBox> {
+        let op = self.in_flight.enter("create box")?;
+
+        // Fail a bad or taken name, a malformed image reference or a host
+        // that cannot run the box before a potentially long pull
+        if let Some(ref name) = name {
+            if self.box_manager.lookup_box(name)?.is_some() {
+                return Err(BoxliteError::AlreadyExists(format!(
+                    "box with name '{}' already exists",
+                    name
+                )));
+            }
+            crate::validate::box_name(name)?;
+        }
+        if let Some(image) = options.rootfs.image() {
+            crate::validate::image_reference(image)?;
+        }
+        self.capabilities
+            .admit(&mut options.advanced.security.clone())?;
+
+        let progress = CreateProgress::new(observer);
+        op.cancellable(
+            "create box",
+            crate::litebox::prepare_shared_rootfs(self, &options.rootfs, &progress),
+        )
+        .await?;
+
+        progress.emit(CreatePhase::PreparingOverlays);
+        let (litebox, _created) = self.create_admitted(options, name, false).await?;
+
+        progress.emit(CreatePhase::Done);
+        Ok(litebox)
+    }
+
     /// Get an existing box by name, or create a new one if it doesn't exist.
     ///
     /// Returns `(LiteBox, true)` if a new box was created, or `(LiteBox, false)`
@@ -256,6 +392,56 @@ impl RuntimeImpl {
         self.create_inner(options, name, true).await
     }
 
+    /// `get_or_create` applying `policy` to an existing box whose options
+    /// differ from `options`.
+    pub async fn get_or_create_with(
+        self: &Arc<Self>,
+        options: BoxOptions,
+        name: Option<String>,
+        policy: GetOrCreatePolicy,
+    ) -> BoxliteResult<(LiteBox, GetOrCreateOutcome)> {
+        let _op = self.in_flight.enter("create box")?;
+
+        if policy != GetOrCreatePolicy::ReuseAlways
+            && let Some(ref box_name) = name
+            && let Some((config, _)) = self.box_manager.lookup_box(box_name)?
+        {
+            // Stored options are admitted ones; admit the request the same way
+            let mut requested = options.clone();
+            self.capabilities.admit(&mut requested.advanced.security)?;
+            let changed = changed_options(&config.options, &requested);
+            if !changed.is_empty() {
+                if policy == GetOrCreatePolicy::FailOnMismatch {
+                    return Err(BoxliteError::ConfigDrift {
+                        name: box_name.clone(),
+                        fields: changed,
+                    });
+                }
+
+                tracing::info!(
+                    box_id = %config.id,
+                    name = %box_name,
+                    changed = %changed.join(", "),
+                    "Recreating box whose options changed"
+                );
+                {
+                    let _lock = self.lock_box(&config.id, BoxOperation::Remove).await?;
+                    self.remove_permanently(config.id.as_str(), true)?;
+                }
+                let (litebox, _) = self.create_admitted(options, name, false).await?;
+                return Ok((litebox, GetOrCreateOutcome::Recreated { changed }));
+            }
+        }
+
+        let (litebox, created) = self.create_admitted(options, name, true).await?;
+        let outcome = if created {
+            GetOrCreateOutcome::Created
+        } else {
+            GetOrCreateOutcome::Reused
+        };
+        Ok((litebox, outcome))
+    }
+
     /// Inner create logic shared by `create()` and `get_or_create()`.
     ///
     /// When `reuse_existing` is false, returns an error if a box with the same
@@ -267,13 +453,28 @@ impl RuntimeImpl {
         name: Option<String>,
         reuse_existing: bool,
     ) -> BoxliteResult<(LiteBox, bool)> {
-        // Check if runtime has been shut down
-        if self.shutdown_token.is_cancelled() {
-            return Err(BoxliteError::Stopped(
-                "Cannot create box: runtime has been shut down".into(),
-            ));
-        }
+        let _op = self.in_flight.enter("create box")?;
+        self.create_admitted(options, name, reuse_existing).await
+    }
 
+    /// `create_inner` for a caller already holding an in-flight guard.
+    async fn create_admitted(
+        self: &Arc<Self>,
+        options: BoxOptions,
+        name: Option<String>,
+        reuse_existing: bool,
+    ) -> BoxliteResult<(LiteBox, bool)> {
+        let (box_impl, created) = self.create_box_impl(options, name, reuse_existing).await?;
+        Ok((LiteBox::new(box_impl), created))
+    }
+
+    /// `create_admitted` returning the shared BoxImpl instead of a handle.
+    pub(crate) async fn create_box_impl(
+        self: &Arc<Self>,
+        options: BoxOptions,
+        name: Option<String>,
+        reuse_existing: bool,
+    ) -> BoxliteResult<(SharedBoxImpl, bool)> {
         // Check DB for existing name — use lookup_box to get full (config, state)
         // so we can build the LiteBox directly without a second lookup
         if let Some(ref name) = name
@@ -281,17 +482,40 @@ impl RuntimeImpl {
         {
             if reuse_existing {
                 let (box_impl, _) = self.get_or_create_box_impl(config, state);
-                return Ok((LiteBox::new(box_impl), false));
+                return Ok((box_impl, false));
             } else {
-                return Err(BoxliteError::InvalidArgument(format!(
+                return Err(BoxliteError::AlreadyExists(format!(
                     "box with name '{}' already exists",
                     name
                 )));
             }
         }
 
+        // Only new boxes are held to the naming rules, so boxes named
+        // before they existed can still be reused
+        if let Some(ref name) = name {
+            crate::validate::box_name(name)?;
+        }
+        // A malformed digest fails now, not at the first start's pull
+        if let Some(image) = options.rootfs.image() {
+            crate::validate::image_reference(image)?;
+        }
+
+        let mut options = options;
+        let degradations = self.capabilities.admit(&mut options.advanced.security)?;
+
         // Initialize box variables with defaults
-        let (config, mut state) = self.init_box_variables(&options, name.clone());
+        let (mut config, mut state) = self.init_box_variables(&options, name.clone());
+        for degradation in &degradations {
+            tracing::warn!(
+                box_id = %config.id,
+                capability = %degradation.capability,
+                detail = %self.capabilities.get(degradation.capability).detail,
+                "Box isolation degraded: {}",
+                degradation.effect
+            );
+        }
+        config.degradations = degradations;
 
         // Allocate lock for this box
         let lock_id = self.lock_manager.allocate()?;
@@ -321,7 +545,7 @@ impl RuntimeImpl {
                 && let Some((config, state)) = self.box_manager.lookup_box(name)?
             {
                 let (box_impl, _) = self.get_or_create_box_impl(config, state);
-                return Ok((LiteBox::new(box_impl), false));
+                return Ok((box_impl, false));
             }
 
             return Err(e);
@@ -337,7 +561,7 @@ impl RuntimeImpl {
         // This also checks in-memory cache for duplicate names
         let (box_impl, inserted) = self.get_or_create_box_impl(config, state);
         if !inserted {
-            return Err(BoxliteError::InvalidArgument(
+            return Err(BoxliteError::AlreadyExists(
                 "box with this name already exists".into(),
             ));
         }
@@ -346,8 +570,11 @@ impl RuntimeImpl {
         self.runtime_metrics
             .boxes_created
             .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        if let Some(image) = options.rootfs.image() {
+            self.runtime_metrics.per_image.box_created(image);
+        }
 
-        Ok((LiteBox::new(box_impl), true))
+        Ok((box_impl, true))
     }
 
     /// Get a handle to an existing box by ID or name.
@@ -406,12 +633,74 @@ impl RuntimeImpl {
         Ok(None)
     }
 
-    /// Remove a box completely by ID or name.
+    /// Remove a box by ID or name.
+    ///
+    /// Moves the box to the trash when `trash_retention` is set.
     pub fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        let box_id = self.resolve_id(id_or_name)?;
+        if self.trash_retention.is_some() {
+            return self.trash_box(&box_id, force);
+        }
+        self.remove_box(&box_id, force)
+    }
+
+    /// Remove a box completely by ID or name, bypassing the trash.
+    pub fn remove_permanently(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
         let box_id = self.resolve_id(id_or_name)?;
         self.remove_box(&box_id, force)
     }
 
+    /// Describe what `remove` (or `remove_permanently`) would do, without
+    /// doing it.
+    pub fn plan_remove(
+        &self,
+        id_or_name: &str,
+        force: bool,
+        permanently: bool,
+    ) -> BoxliteResult<RemovePlan> {
+        let box_id = self.resolve_id(id_or_name)?;
+        let (name, status, box_home, persisted) = match self.box_manager.box_by_id(&box_id)? {
+            Some((config, state)) => {
+                self.ensure_no_dependents(&config, &[config.box_home.clone()])?;
+                (config.name, state.status, config.box_home, true)
+            }
+            None => {
+                let box_impl = self
+                    .sync_state
+                    .read()
+                    .unwrap()
+                    .active_boxes_by_id
+                    .get(&box_id)
+                    .and_then(|weak| weak.upgrade())
+                    .ok_or_else(|| BoxliteError::NotFound(box_id.to_string()))?;
+                let status = box_impl.state.read().status;
+                (
+                    box_impl.config.name.clone(),
+                    status,
+                    box_impl.config.box_home.clone(),
+                    false,
+                )
+            }
+        };
+
+        if status.is_active() && !force {
+            return Err(active_box_error(&box_id, status));
+        }
+
+        // Unpersisted boxes skip the trash, as in trash_box()
+        let to_trash = !permanently && persisted && self.trash_retention.is_some();
+        let disk_bytes = crate::litebox::allocated_size(&box_home);
+        Ok(RemovePlan {
+            id: box_id,
+            name,
+            status,
+            stops_box: status.is_active(),
+            to_trash,
+            disk_bytes,
+            reclaimed_bytes: if to_trash { 0 } else { disk_bytes },
+        })
+    }
+
     // ========================================================================
     // PUBLIC API - QUERY OPERATIONS
     // ========================================================================
@@ -419,46 +708,59 @@ impl RuntimeImpl {
     /// Get information about a specific box by ID or name (without creating a handle).
     ///
     /// Checks in-memory cache first (for boxes not yet persisted), then database.
+    /// The status is reconciled with the newest DB record and the shim
+    /// process, so a box another process started or whose shim died is
+    /// reported as it is.
     pub async fn get_info(self: &Arc<Self>, id_or_name: &str) -> BoxliteResult<Option<BoxInfo>> {
         // Check in-memory cache first (for boxes created but not yet persisted)
-        {
+        let cached = {
             let sync = self.sync_state.read().unwrap();
 
-            // Try as BoxID first
-            if let Some(box_id) = BoxID::parse(id_or_name)
-                && let Some(weak) = sync.active_boxes_by_id.get(&box_id)
-                && let Some(strong) = weak.upgrade()
-            {
-                return Ok(Some(strong.info()));
-            }
-
-            // Try as name
-            if let Some(weak) = sync.active_boxes_by_name.get(id_or_name)
-                && let Some(strong) = weak.upgrade()
-            {
-                return Ok(Some(strong.info()));
-            }
-        }
+            // Try as BoxID first, then as name
+            BoxID::parse(id_or_name)
+                .and_then(|box_id| sync.active_boxes_by_id.get(&box_id))
+                .and_then(Weak::upgrade)
+                .or_else(|| {
+                    sync.active_boxes_by_name
+                        .get(id_or_name)
+                        .and_then(Weak::upgrade)
+                })
+                .map(|strong| strong.info())
+        };
 
-        // Fall back to DB lookup - run on blocking thread pool
+        // DB lookup and reconciliation - run on blocking thread pool
         let this = Arc::clone(self);
         let id_or_name_owned = id_or_name.to_string();
-        let db_result =
-            tokio::task::spawn_blocking(move || this.box_manager.lookup_box(&id_or_name_owned))
-                .await
-                .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))??;
-
-        if let Some((config, state)) = db_result {
-            return Ok(Some(BoxInfo::new(&config, &state)));
-        }
-        Ok(None)
+        tokio::task::spawn_blocking(move || {
+            let key = cached
+                .as_ref()
+                .map_or(id_or_name_owned.as_str(), |info| info.id.as_str());
+            let persisted = this.box_manager.lookup_box(key)?;
+            let mut info = match (cached, &persisted) {
+                (Some(info), _) => info,
+                (None, Some((config, state))) => BoxInfo::new(config, state),
+                (None, None) => return Ok(None),
+            };
+            this.reconcile_info(&mut info, persisted.as_ref().map(|(_, state)| state));
+            Ok(Some(info))
+        })
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?
     }
 
     /// List all boxes, sorted by creation time (newest first).
     ///
     /// Includes both persisted boxes (from database) and in-memory boxes
-    /// (created but not yet persisted).
+    /// (created but not yet persisted). Idle warm pool boxes are left out.
     pub async fn list_info(self: &Arc<Self>) -> BoxliteResult<Vec<BoxInfo>> {
+        self.list_info_with(ListOptions::default()).await
+    }
+
+    /// [`list_info`](Self::list_info) with filtering options.
+    pub async fn list_info_with(
+        self: &Arc<Self>,
+        options: ListOptions,
+    ) -> BoxliteResult<Vec<BoxInfo>> {
         use std::collections::HashSet;
 
         // Get boxes from database - run on blocking thread pool
@@ -470,6 +772,7 @@ impl RuntimeImpl {
         let mut seen_ids: HashSet<BoxID> = db_boxes.iter().map(|(c, _)| c.id.clone()).collect();
         let mut infos: Vec<_> = db_boxes
             .into_iter()
+            .filter(|(_, state)| options.include_warm_pool || state.warm_pool.is_none())
             .map(|(config, state)| BoxInfo::new(&config, &state))
             .collect();
 
@@ -479,6 +782,7 @@ impl RuntimeImpl {
             for (box_id, weak) in &sync.active_boxes_by_id {
                 if !seen_ids.contains(box_id)
                     && let Some(strong) = weak.upgrade()
+                    && (options.include_warm_pool || strong.state.read().warm_pool.is_none())
                 {
                     infos.push(strong.info());
                     seen_ids.insert(box_id.clone());
@@ -486,6 +790,18 @@ impl RuntimeImpl {
             }
         }
 
+        // Reconcile with the shim processes, as in get_info(). The DB
+        // records were just read, so there is nothing newer to consult.
+        let this = Arc::clone(self);
+        let mut infos = tokio::task::spawn_blocking(move || {
+            for info in &mut infos {
+                this.reconcile_info(info, None);
+            }
+            infos
+        })
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))?;
+
         // Sort by creation time (newest first)
         infos.sort_by(|a, b| b.created_at.cmp(&a.created_at));
         Ok(infos)
@@ -539,15 +855,21 @@ impl RuntimeImpl {
     // PUBLIC API - SHUTDOWN
     // ========================================================================
 
-    /// Gracefully shutdown all non-detached boxes in this runtime.
+    /// Gracefully shutdown all boxes this process owns.
     ///
     /// This method:
-    /// 1. Marks the runtime as shut down (no new operations allowed)
+    /// 1. Marks the runtime as shut down (new mutating operations fail with
+    ///    `ShuttingDown`; read-only queries keep working)
     /// 2. Cancels the shutdown token (signals in-flight operations)
-    /// 3. Stops all active non-detached boxes with the given timeout
+    /// 3. Waits up to the timeout for runtime operations already in flight,
+    ///    then aborts the rest
+    /// 4. Removes idle warm pool boxes
+    /// 5. Stops all active boxes this process owns with the given timeout
     ///
-    /// Detached boxes (`detach=true`) are skipped — they are designed to
-    /// survive parent process exit and runtime shutdown.
+    /// A process owns the boxes it started with `detach=false` and those it
+    /// adopted. Detached boxes are skipped — they are designed to survive
+    /// parent process exit and runtime shutdown — and so are boxes owned by
+    /// another process, even when this one holds a handle to them.
     ///
     /// # Arguments
     /// * `timeout` - Seconds before force-kill. None=10s, Some(-1)=infinite
@@ -562,16 +884,30 @@ impl RuntimeImpl {
 
         tracing::info!("Initiating runtime shutdown");
 
-        // Cancel the shutdown token - marks shutdown and signals all in-flight operations
+        // Refuse new mutating operations, then cancel the shutdown token -
+        // marks shutdown and signals all in-flight operations
+        self.in_flight.close();
         self.shutdown_token.cancel();
 
-        // Collect all active non-detached boxes
+        // Convert timeout to duration
+        let timeout_duration = timeout_to_duration(timeout);
+
+        // Let operations admitted before shutdown finish, aborting any still
+        // running when the timeout expires
+        if !self.in_flight.drain(timeout_duration).await {
+            tracing::warn!("Aborting runtime operations still in flight after shutdown timeout");
+            self.in_flight.abort();
+        }
+
+        self.drain_warm_pools();
+
+        // Collect all active boxes this process owns
         let active_boxes: Vec<SharedBoxImpl> = {
             let sync = self.sync_state.read().unwrap();
             sync.active_boxes_by_id
                 .values()
                 .filter_map(|weak| weak.upgrade())
-                .filter(|box_impl| !box_impl.config.options.detach)
+                .filter(|box_impl| box_impl.is_owned_here())
                 .collect()
         };
 
@@ -582,18 +918,20 @@ impl RuntimeImpl {
 
         tracing::info!(count = active_boxes.len(), "Stopping active boxes");
 
-        // Convert timeout to duration
-        let timeout_duration = timeout_to_duration(timeout);
-
-        // Stop all boxes concurrently
+        // Stop all boxes concurrently. A stop waits out any operation in
+        // flight on its box (bounded by the shutdown timeout) rather than
+        // failing as busy. Each box gets its own grace period, cut short so
+        // the force kill still fits in the timeout.
         let stop_futures = active_boxes.iter().map(|box_impl| {
             let box_id = box_impl.id().to_string();
+            let grace = shutdown_grace(box_impl.shutdown_grace_period(), timeout_duration);
             async move {
+                let stop = box_impl.stop_waiting(Some(std::time::Duration::MAX), grace);
                 let result = if let Some(duration) = timeout_duration {
-                    tokio::time::timeout(duration, box_impl.stop()).await
+                    tokio::time::timeout(duration, stop).await
                 } else {
                     // Infinite timeout
-                    Ok(box_impl.stop().await)
+                    Ok(stop.await)
                 };
                 (box_id, result)
             }
@@ -637,12 +975,16 @@ impl RuntimeImpl {
     /// directly and sends SIGTERM to shim processes. The shim's SIGTERM handler
     /// does graceful Guest.Shutdown() RPC (qcow2 flush) before exiting.
     ///
-    /// Detached boxes are skipped (same contract as async `shutdown()`).
+    /// Only boxes this process owns are stopped (same contract as async
+    /// `shutdown()`).
     pub(crate) fn shutdown_sync(&self) {
         if self.shutdown_token.is_cancelled() {
             return;
         }
+        self.in_flight.close();
+        self.in_flight.abort();
         self.shutdown_token.cancel();
+        self.drain_warm_pools();
 
         let boxes = match self.box_manager.all_boxes(true) {
             Ok(b) => b,
@@ -653,7 +995,7 @@ impl RuntimeImpl {
         };
 
         for (config, mut state) in boxes {
-            if state.status != BoxStatus::Running || config.options.detach {
+            if state.status != BoxStatus::Running || state.owner_pid != Some(std::process::id()) {
                 continue;
             }
             let Some(pid) = state.pid else { continue };
@@ -661,7 +1003,7 @@ impl RuntimeImpl {
                 continue;
             }
 
-            tracing::info!(box_id = %config.id, pid, "Auto-stopping non-detached box");
+            tracing::info!(box_id = %config.id, pid, "Auto-stopping owned box");
 
             // SIGTERM triggers shim's graceful shutdown handler (Guest.Shutdown RPC)
             unsafe {
@@ -683,8 +1025,11 @@ impl RuntimeImpl {
                 std::thread::sleep(std::time::Duration::from_millis(50));
             }
 
-            state.mark_stop();
+            state.mark_exited(Some(0));
             let _ = self.box_manager.save_box(&config.id, &state);
+            self.runtime_metrics
+                .per_image
+                .box_stopped(config.id.as_str());
             let pid_file = self
                 .layout
                 .boxes_dir()
@@ -747,73 +1092,14 @@ impl RuntimeImpl {
         tracing::debug!(box_id = %id, force = force, "RuntimeInnerImpl::remove_box called");
 
         // Try to get box from database first
-        if let Some((config, state)) = self.box_manager.box_by_id(id)? {
+        if let Some((config, mut state)) = self.box_manager.box_by_id(id)? {
             // Box exists in database - handle as before
-            let mut state = state;
-            let was_active = state.status.is_active();
-            if was_active {
-                if force {
-                    // Force mode: kill the process directly
-                    if let Some(pid) = state.pid {
-                        tracing::info!(box_id = %id, pid = pid, "Force killing active box");
-                        crate::util::kill_process(pid);
-                    }
-                    // Update status to stopped and save
-                    state.set_status(BoxStatus::Stopped);
-                    state.set_pid(None);
-                    self.box_manager.save_box(id, &state)?;
-                } else {
-                    // Non-force mode: error on active box
-                    return Err(BoxliteError::InvalidState(format!(
-                        "cannot remove active box {} (status: {:?}). Use force=true to stop first",
-                        id, state.status
-                    )));
-                }
-            }
-
-            // Force-removing an active box is semantically a stop operation.
-            if was_active {
-                self.runtime_metrics
-                    .boxes_stopped
-                    .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-            }
+            self.ensure_no_dependents(&config, &[config.box_home.clone()])?;
+            self.stop_for_removal(id, &mut state, force)?;
 
             // Remove from BoxManager (database-first)
             self.box_manager.remove_box(id)?;
-
-            // Free the lock if one was allocated
-            if let Some(lock_id) = state.lock_id {
-                if let Err(e) = self.lock_manager.free(lock_id) {
-                    tracing::warn!(
-                        box_id = %id,
-                        lock_id = %lock_id,
-                        error = %e,
-                        "Failed to free lock for removed box"
-                    );
-                } else {
-                    tracing::debug!(
-                        box_id = %id,
-                        lock_id = %lock_id,
-                        "Freed lock for removed box"
-                    );
-                }
-            }
-
-            // Delete box directory
-            let box_home = config.box_home;
-            if box_home.exists()
-                && let Err(e) = std::fs::remove_dir_all(&box_home)
-            {
-                tracing::warn!(
-                    box_id = %id,
-                    path = %box_home.display(),
-                    error = %e,
-                    "Failed to cleanup box directory"
-                );
-            }
-
-            // Invalidate cache
-            self.invalidate_box_impl(id, config.name.as_deref());
+            self.release_removed_box(&config, &state);
 
             tracing::info!(box_id = %id, "Removed box");
             return Ok(());
@@ -831,15 +1117,14 @@ impl RuntimeImpl {
             // Box exists in-memory only (not yet started/persisted)
             let state = box_impl.state.read();
             if state.status.is_active() && !force {
-                return Err(BoxliteError::InvalidState(format!(
-                    "cannot remove active box {} (status: {:?}). Use force=true to stop first",
-                    id, state.status
-                )));
+                return Err(active_box_error(id, state.status));
             }
             drop(state);
+            box_impl.shutdown_token.cancel();
 
             // Invalidate cache (removes from in-memory maps)
             self.invalidate_box_impl(id, box_impl.config.name.as_deref());
+            remove_box_sockets(id, &box_impl.state.read().sockets);
 
             // Delete box directory if it exists
             let box_home = &box_impl.config.box_home;
@@ -862,6 +1147,199 @@ impl RuntimeImpl {
         Err(BoxliteError::NotFound(id.to_string()))
     }
 
+    /// Refuse to delete or move the disks of `config`'s box, found in
+    /// `homes`, while another box, live or trashed, still uses them as
+    /// backing files, as COW clones and `restore_to_new` boxes do.
+    pub(crate) fn ensure_no_dependents(
+        &self,
+        config: &BoxConfig,
+        homes: &[PathBuf],
+    ) -> BoxliteResult<()> {
+        let roots = self.layout.box_roots();
+        let Some(disk) = crate::litebox::find_dependent_disk(&roots, homes) else {
+            return Ok(());
+        };
+
+        // Name the dependent box, and what it was derived from, when its
+        // lineage records this box as its source
+        let live = self
+            .box_manager
+            .all_boxes(false)?
+            .into_iter()
+            .map(|(derived, _)| (derived.box_home.clone(), derived, false));
+        let trashed = self
+            .trash_store()
+            .list()?
+            .into_iter()
+            .map(|record| (record.trash_dir, record.config, true));
+        let derived = live
+            .chain(trashed)
+            .find(|(home, _, _)| disk.starts_with(home));
+        let source = match derived.as_ref().and_then(|(_, d, _)| d.lineage.as_ref()) {
+            Some(lineage) if lineage.source_box_id == config.id => match &lineage.snapshot {
+                Some(snapshot) => format!("was branched from its snapshot '{}'", snapshot),
+                None => "was cloned from it".to_string(),
+            },
+            _ => "depends on its disks".to_string(),
+        };
+        let dependent = match derived {
+            Some((_, derived, trashed)) => format!(
+                "{}box {}",
+                if trashed { "trashed " } else { "" },
+                derived.name.unwrap_or_else(|| derived.id.to_string())
+            ),
+            None => format!("disk {}", disk.display()),
+        };
+        Err(BoxliteError::InvalidState(format!(
+            "Cannot remove box {}: {} {}. Remove or purge the dependent box first.",
+            config.id, dependent, source
+        )))
+    }
+
+    /// Free what a box deleted from the database still holds: its sockets,
+    /// lock, directory and cached handle.
+    pub(crate) fn release_removed_box(&self, config: &BoxConfig, state: &BoxState) {
+        let id = &config.id;
+
+        // The box is going away, so its sockets go too, even if the
+        // killed shim has not exited yet
+        remove_box_sockets(id, &state.sockets);
+
+        // Free the lock if one was allocated
+        if let Some(lock_id) = state.lock_id {
+            self.free_box_lock(id, lock_id);
+        }
+
+        // Delete box directory
+        let box_home = &config.box_home;
+        if box_home.exists()
+            && let Err(e) = std::fs::remove_dir_all(box_home)
+        {
+            tracing::warn!(
+                box_id = %id,
+                path = %box_home.display(),
+                error = %e,
+                "Failed to cleanup box directory"
+            );
+        }
+
+        // Invalidate cache
+        self.invalidate_box_impl(id, config.name.as_deref());
+    }
+
+    /// Make a persisted box removable: kill it if active and `force` is set.
+    ///
+    /// Errors on an active box without `force`.
+    pub(crate) fn stop_for_removal(
+        &self,
+        id: &BoxID,
+        state: &mut BoxState,
+        force: bool,
+    ) -> BoxliteResult<()> {
+        if !state.status.is_active() {
+            return Ok(());
+        }
+        if !force {
+            return Err(active_box_error(id, state.status));
+        }
+
+        // Force mode: end the operations of open handles and executions
+        // (they report Stopped), then kill the process directly
+        self.cancel_active_box(id);
+        if let Some(pid) = state.pid {
+            tracing::info!(box_id = %id, pid = pid, "Force killing active box");
+            crate::util::kill_process(pid);
+        }
+        // Update status to exited (killed, so no exit code) and save
+        state.mark_exited(None);
+        self.box_manager.save_box(id, state)?;
+
+        // Force-removing an active box is semantically a stop operation.
+        self.runtime_metrics
+            .boxes_stopped
+            .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        self.runtime_metrics.per_image.box_stopped(id.as_str());
+        Ok(())
+    }
+
+    /// Cancel the shutdown token of the box's open `BoxImpl`, if any.
+    fn cancel_active_box(&self, id: &BoxID) {
+        let box_impl = {
+            let sync = self.sync_state.read().unwrap();
+            sync.active_boxes_by_id.get(id).and_then(Weak::upgrade)
+        };
+        if let Some(box_impl) = box_impl {
+            box_impl.shutdown_token.cancel();
+        }
+    }
+
+    /// Free a removed box's lock, logging (not failing) on error.
+    pub(crate) fn free_box_lock(&self, id: &BoxID, lock_id: crate::lock::LockId) {
+        if let Err(e) = self.lock_manager.free(lock_id) {
+            tracing::warn!(
+                box_id = %id,
+                lock_id = %lock_id,
+                error = %e,
+                "Failed to free lock for removed box"
+            );
+        } else {
+            tracing::debug!(
+                box_id = %id,
+                lock_id = %lock_id,
+                "Freed lock for removed box"
+            );
+        }
+    }
+
+    /// Take a box's operation lock for a mutating `operation`.
+    ///
+    /// Fails with `Busy` if another handle holds it longer than
+    /// `BoxliteOptions::lock_wait`.
+    pub(crate) async fn lock_box(
+        &self,
+        id: &BoxID,
+        operation: BoxOperation,
+    ) -> BoxliteResult<OperationLock> {
+        self.lock_box_waiting(id, operation, self.lock_wait).await
+    }
+
+    /// [`lock_box`](Self::lock_box) with an explicit wait (`None`: don't wait).
+    pub(crate) async fn lock_box_waiting(
+        &self,
+        id: &BoxID,
+        operation: BoxOperation,
+        wait: Option<std::time::Duration>,
+    ) -> BoxliteResult<OperationLock> {
+        let lock_id = self.box_lock_id(id)?;
+        let locker = self.lock_manager.retrieve(lock_id)?;
+        let holder_path = self.layout.locks_dir().join(format!("{lock_id}.holder"));
+        let lock = OperationLock::acquire(locker, holder_path, operation, wait).await?;
+
+        // A remove that held the lock before us has freed it along with the box
+        if self.box_lock_id(id).ok() != Some(lock_id) {
+            return Err(BoxliteError::NotFound(id.to_string()));
+        }
+        Ok(lock)
+    }
+
+    /// Lock ID of a box, from the database or, if not persisted, the cache.
+    fn box_lock_id(&self, id: &BoxID) -> BoxliteResult<LockId> {
+        let lock_id = match self.box_manager.box_by_id(id)? {
+            Some((_, state)) => state.lock_id,
+            None => {
+                let box_impl = {
+                    let sync = self.sync_state.read().unwrap();
+                    sync.active_boxes_by_id
+                        .get(id)
+                        .and_then(|weak| weak.upgrade())
+                };
+                let box_impl = box_impl.ok_or_else(|| BoxliteError::NotFound(id.to_string()))?;
+                box_impl.state.read().lock_id
+            }
+        };
+        lock_id.ok_or_else(|| BoxliteError::Internal(format!("box {} is missing lock_id", id)))
+    }
+
     // ========================================================================
     // INTERNAL - INITIALIZATION
     // ========================================================================
@@ -905,6 +1383,9 @@ impl RuntimeImpl {
             transport: Transport::unix(socket_path),
             box_home,
             ready_socket_path,
+            disk_driver: self.disk_driver,
+            lineage: None,
+            degradations: Vec::new(),
         };
 
         // Create initial state (status = Configured)
@@ -937,9 +1418,9 @@ impl RuntimeImpl {
         // - auto_remove=true boxes: these are ephemeral and shouldn't survive restarts
         // - Orphaned active boxes: was Running but directory is missing (crashed mid-operation)
         //
-        // Note: We don't remove Configured or Stopped boxes without directories because:
-        // - Configured boxes: created but never started, no directory yet (this is valid)
-        // - Stopped boxes: might not have a directory if never started
+        // Note: We don't remove Created or Exited boxes without directories because:
+        // - Created boxes: created but never started, no directory yet (this is valid)
+        // - Exited boxes: marked Dead in phase 2, kept so the user sees what was lost
         // - Only Running boxes must have a directory
         let mut boxes_to_remove = Vec::new();
         for (config, state) in &persisted {
@@ -951,7 +1432,6 @@ impl RuntimeImpl {
                 true
             } else if state.status.is_active() && !config.box_home.exists() {
                 // Only remove orphaned boxes that were in an active state
-                // Stopped boxes might not have a directory if never started
                 tracing::warn!(
                     box_id = %config.id,
                     status = ?state.status,
@@ -1010,6 +1490,7 @@ impl RuntimeImpl {
         for (config, mut state) in persisted {
             let box_id = &config.id;
             let original_status = state.status;
+            let original_owner = state.owner_pid;
 
             // Reclaim the lock for this box if one was allocated
             if let Some(lock_id) = state.lock_id {
@@ -1046,15 +1527,38 @@ impl RuntimeImpl {
                             // Process is alive and it's our boxlite-shim - box stays Running
                             state.set_pid(Some(pid));
                             state.set_status(BoxStatus::Running);
+                            // An owner that exited left the box running: it
+                            // was detached, or crashed while detaching it
+                            if state
+                                .owner_pid
+                                .is_some_and(|owner| !is_process_alive(owner))
+                            {
+                                state.set_owner_pid(None);
+                            }
+                            // Runtime before this process recovered the box
+                            // was accounted by the previous one
+                            if let Some(image) = config.options.rootfs.image() {
+                                self.runtime_metrics
+                                    .per_image
+                                    .box_running(box_id.as_str(), image);
+                            }
                             tracing::info!(
                                 box_id = %box_id,
                                 pid = pid,
                                 "Recovered running box from PID file"
                             );
                         } else {
-                            // Process died or PID was reused - clean up and mark as Stopped
+                            // Process died or PID was reused - clean up and mark as Exited,
+                            // with the exit code the shim recorded if it crashed
+                            let exit_code =
+                                super::reconcile::crash_exit_code(&BoxFilesystemLayout::new(
+                                    config.box_home.clone(),
+                                    FsLayoutConfig::without_bind_mount(),
+                                    false,
+                                ));
                             let _ = std::fs::remove_file(&pid_file);
-                            state.mark_stop();
+                            crate::util::remove_stale_sockets(&state.sockets);
+                            state.mark_exited(exit_code);
                             tracing::warn!(
                                 box_id = %box_id,
                                 pid = pid,
@@ -1063,30 +1567,43 @@ impl RuntimeImpl {
                         }
                     }
                     Err(e) => {
-                        // Can't read PID file - clean up and mark as Stopped
+                        // Can't read PID file - clean up and mark as Exited
                         let _ = std::fs::remove_file(&pid_file);
-                        state.mark_stop();
+                        crate::util::remove_stale_sockets(&state.sockets);
+                        state.mark_exited(None);
                         tracing::warn!(
                             box_id = %box_id,
                             error = %e,
-                            "Failed to read PID file, marking as Stopped"
+                            "Failed to read PID file, marking as Exited"
                         );
                     }
                 }
             } else {
                 // No PID file - box was stopped gracefully or never started
-                // Note: Configured boxes won't have a PID file (this is expected)
+                // Note: Created boxes won't have a PID file (this is expected)
                 if state.status == BoxStatus::Running {
-                    state.set_status(BoxStatus::Stopped);
+                    state.mark_exited(None);
                     tracing::warn!(
                         box_id = %box_id,
-                        "Box was Running but no PID file found, marked as Stopped"
+                        "Box was Running but no PID file found, marked as Exited"
                     );
                 }
             }
 
+            // A box that ran before is dead once its storage is gone
+            if state.status.is_stopped()
+                && let Some(missing) = config.missing_storage()
+            {
+                state.mark_dead();
+                tracing::warn!(
+                    box_id = %box_id,
+                    missing = %missing.display(),
+                    "Box storage is missing, marked as Dead"
+                );
+            }
+
             // Save updated state to database if changed
-            if state.status != original_status {
+            if state.status != original_status || state.owner_pid != original_owner {
                 self.box_manager.save_box(box_id, &state)?;
             }
         }
@@ -1096,6 +1613,14 @@ impl RuntimeImpl {
             tracing::warn!("Guest rootfs GC failed: {}", e);
         }
 
+        // Apply the cache caps, which may have shrunk since the last run
+        if let Err(e) = self.guest_rootfs_mgr.evict() {
+            tracing::warn!("Guest rootfs cache eviction failed: {}", e);
+        }
+        if let Err(e) = self.image_disk_mgr.evict() {
+            tracing::warn!("Image disk cache eviction failed: {}", e);
+        }
+
         tracing::info!("Box recovery complete");
         Ok(())
     }
@@ -1188,7 +1713,7 @@ impl RuntimeImpl {
     ///
     /// Checks both by name (if provided) and by ID. This prevents duplicate names
     /// even for boxes not yet persisted to database.
-    fn get_or_create_box_impl(
+    pub(crate) fn get_or_create_box_impl(
         self: &Arc<Self>,
         config: BoxConfig,
         state: BoxState,
@@ -1252,6 +1777,20 @@ impl RuntimeImpl {
         tracing::trace!(box_id = %box_id, name = ?box_name, "Invalidated BoxImpl cache");
     }
 
+    /// The cached BoxImpl for `box_id`, if a handle to it is alive.
+    pub(crate) fn active_box_impl(&self, box_id: &BoxID) -> Option<SharedBoxImpl> {
+        let sync = self.sync_state.read().unwrap();
+        sync.active_boxes_by_id.get(box_id).and_then(Weak::upgrade)
+    }
+
+    /// Whether a live BoxImpl for `box_id` is cached.
+    pub(crate) fn is_box_active(&self, box_id: &BoxID) -> bool {
+        let sync = self.sync_state.read().unwrap();
+        sync.active_boxes_by_id
+            .get(box_id)
+            .is_some_and(|weak| weak.strong_count() > 0)
+    }
+
     /// Acquire coordination lock for multi-step atomic operations.
     ///
     /// Use this when you need atomicity across multiple operations on
@@ -1274,6 +1813,46 @@ impl std::fmt::Debug for RuntimeImpl {
     }
 }
 
+/// Error for removing an active box without `force`.
+/// Time a runtime shutdown keeps for killing a box whose grace period ran
+/// out: the shim's own SIGTERM-to-SIGKILL wait.
+const SHUTDOWN_KILL_RESERVE: std::time::Duration = std::time::Duration::from_secs(2);
+
+/// The grace period a box gets during a runtime shutdown with `timeout`: its
+/// own, but short enough to leave [`SHUTDOWN_KILL_RESERVE`] for the kill.
+fn shutdown_grace(
+    grace: std::time::Duration,
+    timeout: Option<std::time::Duration>,
+) -> std::time::Duration {
+    match timeout {
+        Some(timeout) => grace.min(timeout.saturating_sub(SHUTDOWN_KILL_RESERVE)),
+        None => grace,
+    }
+}
+
+fn active_box_error(id: &BoxID, status: BoxStatus) -> BoxliteError {
+    BoxliteError::InvalidState(format!(
+        "cannot remove active box {} (status: {:?}). Use force=true to stop first",
+        id, status
+    ))
+}
+
+/// Remove the sockets recorded for a removed box, logging (not failing) on error.
+fn remove_box_sockets(id: &BoxID, sockets: &[std::path::PathBuf]) {
+    for socket in sockets {
+        if let Err(e) = std::fs::remove_file(socket)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!(
+                box_id = %id,
+                socket = %socket.display(),
+                error = %e,
+                "Failed to remove box socket"
+            );
+        }
+    }
+}
+
 // ============================================================================
 // LocalRuntime — RuntimeBackend adapter for local VM execution
 // ============================================================================
@@ -1284,6 +1863,14 @@ impl std::fmt::Debug for RuntimeImpl {
 /// Trait methods use `&self`. This newtype holds the Arc as a field to bridge the gap.
 pub(crate) struct LocalRuntime(pub(crate) SharedRuntimeImpl);
 
+impl Drop for LocalRuntime {
+    fn drop(&mut self) {
+        // Idle warm boxes hold the runtime alive; release them so the
+        // runtime's own Drop can run once other handles are gone
+        self.0.drain_warm_pools();
+    }
+}
+
 #[async_trait::async_trait]
 impl super::backend::RuntimeBackend for LocalRuntime {
     async fn create(
@@ -1294,6 +1881,15 @@ impl super::backend::RuntimeBackend for LocalRuntime {
         self.0.create(options, name).await
     }
 
+    async fn create_with_observer(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        observer: CreateObserver,
+    ) -> BoxliteResult<crate::litebox::LiteBox> {
+        self.0.create_with_observer(options, name, observer).await
+    }
+
     async fn get_or_create(
         &self,
         options: BoxOptions,
@@ -1302,6 +1898,15 @@ impl super::backend::RuntimeBackend for LocalRuntime {
         self.0.get_or_create(options, name).await
     }
 
+    async fn get_or_create_with(
+        &self,
+        options: BoxOptions,
+        name: Option<String>,
+        policy: GetOrCreatePolicy,
+    ) -> BoxliteResult<(crate::litebox::LiteBox, GetOrCreateOutcome)> {
+        self.0.get_or_create_with(options, name, policy).await
+    }
+
     async fn get(&self, id_or_name: &str) -> BoxliteResult<Option<crate::litebox::LiteBox>> {
         self.0.get(id_or_name).await
     }
@@ -1314,6 +1919,14 @@ impl super::backend::RuntimeBackend for LocalRuntime {
         self.0.list_info().await
     }
 
+    async fn list_info_with(&self, options: ListOptions) -> BoxliteResult<Vec<BoxInfo>> {
+        self.0.list_info_with(options).await
+    }
+
+    async fn acquire_warm(&self, selector: WarmSelector) -> BoxliteResult<LiteBox> {
+        self.0.acquire_warm(selector).await
+    }
+
     async fn exists(&self, id_or_name: &str) -> BoxliteResult<bool> {
         self.0.exists(id_or_name).await
     }
@@ -1322,8 +1935,137 @@ impl super::backend::RuntimeBackend for LocalRuntime {
         Ok(self.0.metrics().await)
     }
 
+    async fn version_info(&self) -> BoxliteResult<crate::runtime::version::VersionInfo> {
+        tokio::task::spawn_blocking(crate::runtime::version::VersionInfo::collect)
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("spawn_blocking failed: {}", e)))
+    }
+
+    async fn capabilities(&self) -> BoxliteResult<RuntimeCapabilities> {
+        Ok(self.0.capabilities.clone())
+    }
+
     async fn remove(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
-        self.0.remove(id_or_name, force)
+        let _op = self.0.in_flight.enter("remove box")?;
+        let box_id = self.0.resolve_id(id_or_name)?;
+        let _lock = self.0.lock_box(&box_id, BoxOperation::Remove).await?;
+        self.0.remove(box_id.as_str(), force)
+    }
+
+    async fn remove_permanently(&self, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+        let _op = self.0.in_flight.enter("remove box")?;
+        let box_id = self.0.resolve_id(id_or_name)?;
+        let _lock = self.0.lock_box(&box_id, BoxOperation::Remove).await?;
+        self.0.remove_permanently(box_id.as_str(), force)
+    }
+
+    async fn plan_remove(
+        &self,
+        id_or_name: &str,
+        force: bool,
+        permanently: bool,
+    ) -> BoxliteResult<RemovePlan> {
+        self.0.plan_remove(id_or_name, force, permanently)
+    }
+
+    async fn list_trashed(&self) -> BoxliteResult<Vec<crate::db::trash::TrashedBox>> {
+        self.0.list_trashed()
+    }
+
+    async fn register_template(
+        &self,
+        name: &str,
+        options: BoxOptions,
+    ) -> BoxliteResult<BoxTemplate> {
+        let _op = self.0.in_flight.enter("register template")?;
+        self.0.register_template(name, options)
+    }
+
+    async fn get_template(&self, name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        self.0.get_template(name)
+    }
+
+    async fn list_templates(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        self.0.list_templates()
+    }
+
+    async fn remove_template(&self, name: &str) -> BoxliteResult<()> {
+        let _op = self.0.in_flight.enter("remove template")?;
+        self.0.remove_template(name)
+    }
+
+    async fn record_composition(&self, members: &[ComposedMember]) -> BoxliteResult<()> {
+        let _op = self.0.in_flight.enter("record composition")?;
+        self.0.record_composition(members)
+    }
+
+    async fn list_composition(&self) -> BoxliteResult<Vec<ComposedMember>> {
+        self.0.list_composition()
+    }
+
+    async fn forget_composition(&self, names: &[String]) -> BoxliteResult<()> {
+        let _op = self.0.in_flight.enter("forget composition")?;
+        self.0.forget_composition(names)
+    }
+
+    async fn restore_trashed(&self, id_or_name: &str) -> BoxliteResult<crate::litebox::LiteBox> {
+        let _op = self.0.in_flight.enter("restore box")?;
+        self.0.restore_trashed(id_or_name).await
+    }
+
+    async fn purge_trashed(&self, id_or_name: &str) -> BoxliteResult<()> {
+        let _op = self.0.in_flight.enter("purge box")?;
+        self.0.purge_trashed(id_or_name)
+    }
+
+    async fn list_orphans(&self) -> BoxliteResult<Vec<super::orphans::OrphanShim>> {
+        self.0.list_orphans()
+    }
+
+    async fn kill_orphans(
+        &self,
+        grace: std::time::Duration,
+    ) -> BoxliteResult<Vec<super::orphans::OrphanShim>> {
+        let _op = self.0.in_flight.enter("kill orphaned shims")?;
+        self.0.kill_orphans(grace).await
+    }
+
+    async fn detach_all(&self) -> BoxliteResult<super::BulkResults<()>> {
+        let _op = self.0.in_flight.enter("detach boxes")?;
+        self.0.detach_all().await
+    }
+
+    async fn adopt_all(&self) -> BoxliteResult<super::BulkResults<()>> {
+        let _op = self.0.in_flight.enter("adopt boxes")?;
+        self.0.adopt_all().await
+    }
+
+    async fn import(
+        &self,
+        archive_path: &std::path::Path,
+        options: super::ImportOptions,
+    ) -> BoxliteResult<LiteBox> {
+        let _op = self.0.in_flight.enter("import box")?;
+        self.0.import(archive_path, options).await
+    }
+
+    async fn plan_import(
+        &self,
+        archive_path: &std::path::Path,
+        options: &super::ImportOptions,
+    ) -> BoxliteResult<super::ImportPlan> {
+        self.0.plan_import(archive_path, options)
+    }
+
+    #[cfg(feature = "url-transfer")]
+    async fn import_from_url(
+        &self,
+        url: &str,
+        options: super::ImportOptions,
+        transfer: super::url_transfer::TransferOptions,
+    ) -> BoxliteResult<LiteBox> {
+        let _op = self.0.in_flight.enter("import box")?;
+        self.0.import_from_url(url, options, transfer).await
     }
 
     async fn shutdown(&self, timeout: Option<i32>) -> BoxliteResult<()> {
@@ -1333,18 +2075,54 @@ impl super::backend::RuntimeBackend for LocalRuntime {
     fn shutdown_sync(&self) {
         self.0.shutdown_sync();
     }
+
+    #[cfg(feature = "rest-server")]
+    fn shutdown_token(&self) -> Option<CancellationToken> {
+        Some(self.0.shutdown_token.clone())
+    }
 }
 
 // Image operations (separate from RuntimeBackend)
 #[async_trait::async_trait]
 impl super::images::ImageManager for LocalRuntime {
-    async fn pull_image(&self, image_ref: &str) -> BoxliteResult<crate::images::ImageObject> {
-        self.0.image_manager.pull(image_ref).await
+    async fn pull_image(
+        &self,
+        image_ref: &str,
+        progress: Option<crate::images::PullProgressFn>,
+    ) -> BoxliteResult<crate::images::ImageObject> {
+        let op = self.0.in_flight.enter("pull image")?;
+        op.cancellable(
+            "pull image",
+            self.0.image_manager.pull_with_progress(image_ref, progress),
+        )
+        .await
     }
 
     async fn list_images(&self) -> BoxliteResult<Vec<crate::runtime::types::ImageInfo>> {
         self.0.image_manager.list().await
     }
+
+    async fn vulnerability_report(
+        &self,
+        image: &crate::images::ImageObject,
+    ) -> BoxliteResult<Option<crate::images::VulnerabilityReport>> {
+        self.0.image_manager.vulnerability_report(image).await
+    }
+
+    fn registry_status(&self) -> Vec<crate::images::RegistryStatus> {
+        self.0.image_manager.registry_status()
+    }
+
+    fn cache_usage(&self) -> BoxliteResult<crate::disk::CacheUsage> {
+        Ok(crate::disk::CacheUsage {
+            image_disks: self.0.image_disk_mgr.stats()?,
+            guest_rootfs: self.0.guest_rootfs_mgr.stats()?,
+        })
+    }
+
+    fn built_images(&self) -> crate::images::built::BuiltImageStore {
+        self.0.image_manager.built_images().clone()
+    }
 }
 
 // ============================================================================
@@ -1380,6 +2158,7 @@ mod tests {
         let options = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = RuntimeImpl::new(options).expect("Failed to create runtime");
         (runtime, temp_dir)
@@ -1406,14 +2185,19 @@ mod tests {
             },
             box_home: std::path::PathBuf::from("/tmp/test-box"),
             ready_socket_path: std::path::PathBuf::from("/tmp/test-ready.sock"),
+            disk_driver: Default::default(),
+            lineage: None,
+            degradations: Vec::new(),
         }
     }
 
     /// Create a BoxState with Running status and a given PID.
-    fn running_state(pid: u32) -> BoxState {
+    /// Running state as recorded by a start in this process.
+    fn running_state(pid: u32, config: &BoxConfig) -> BoxState {
         let mut state = BoxState::new();
         state.status = BoxStatus::Running;
         state.pid = Some(pid);
+        state.owner_pid = (!config.options.detach).then(std::process::id);
         state
     }
 
@@ -1464,6 +2248,9 @@ mod tests {
             },
             box_home,
             ready_socket_path: std::path::PathBuf::from("/tmp/test-ready.sock"),
+            disk_driver: Default::default(),
+            lineage: None,
+            degradations: Vec::new(),
         }
     }
 
@@ -1537,7 +2324,7 @@ mod tests {
 
         // Insert a non-detached Running box into the DB
         let config = test_box_config(false); // detach=false
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
         runtime
             .box_manager
             .add_box(&config, &state)
@@ -1563,7 +2350,7 @@ mod tests {
             .box_by_id(&config.id)
             .expect("Failed to query box")
             .expect("Box should exist");
-        assert_eq!(updated_state.status, BoxStatus::Stopped);
+        assert_eq!(updated_state.status, BoxStatus::Exited);
         assert!(updated_state.pid.is_none());
     }
 
@@ -1576,7 +2363,7 @@ mod tests {
 
         // Insert a detached Running box into the DB
         let config = test_box_config(true); // detach=true
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
         runtime
             .box_manager
             .add_box(&config, &state)
@@ -1612,7 +2399,7 @@ mod tests {
         // Insert a Stopped box into the DB
         let config = test_box_config(false);
         let mut state = BoxState::new();
-        state.status = BoxStatus::Stopped;
+        state.status = BoxStatus::Exited;
         state.pid = None;
         runtime
             .box_manager
@@ -1628,7 +2415,7 @@ mod tests {
             .box_by_id(&config.id)
             .expect("Failed to query box")
             .expect("Box should exist");
-        assert_eq!(db_state.status, BoxStatus::Stopped);
+        assert_eq!(db_state.status, BoxStatus::Exited);
     }
 
     #[test]
@@ -1639,7 +2426,7 @@ mod tests {
         let dead_pid = 999_999_999u32;
 
         let config = test_box_config(false);
-        let state = running_state(dead_pid);
+        let state = running_state(dead_pid, &config);
         runtime
             .box_manager
             .add_box(&config, &state)
@@ -1667,7 +2454,7 @@ mod tests {
 
         // Non-detached running box
         let config_regular = test_box_config(false);
-        let state_regular = running_state(pid_regular);
+        let state_regular = running_state(pid_regular, &config_regular);
         runtime
             .box_manager
             .add_box(&config_regular, &state_regular)
@@ -1675,7 +2462,7 @@ mod tests {
 
         // Detached running box
         let config_detached = test_box_config(true);
-        let state_detached = running_state(pid_detached);
+        let state_detached = running_state(pid_detached, &config_detached);
         runtime
             .box_manager
             .add_box(&config_detached, &state_detached)
@@ -1684,7 +2471,7 @@ mod tests {
         // Stopped box (should be skipped regardless)
         let config_stopped = test_box_config(false);
         let mut state_stopped = BoxState::new();
-        state_stopped.status = BoxStatus::Stopped;
+        state_stopped.status = BoxStatus::Exited;
         runtime
             .box_manager
             .add_box(&config_stopped, &state_stopped)
@@ -1703,7 +2490,7 @@ mod tests {
             .box_by_id(&config_regular.id)
             .unwrap()
             .unwrap();
-        assert_eq!(db_regular.status, BoxStatus::Stopped);
+        assert_eq!(db_regular.status, BoxStatus::Exited);
 
         // Detached box: still alive, DB unchanged
         assert!(
@@ -1723,7 +2510,7 @@ mod tests {
             .box_by_id(&config_stopped.id)
             .unwrap()
             .unwrap();
-        assert_eq!(db_stopped.status, BoxStatus::Stopped);
+        assert_eq!(db_stopped.status, BoxStatus::Exited);
 
         // Cleanup
         child_regular.kill().ok();
@@ -1732,6 +2519,56 @@ mod tests {
         child_detached.wait().ok();
     }
 
+    #[test]
+    fn test_shutdown_sync_skips_box_owned_by_other_process() {
+        let (runtime, _dir) = create_test_runtime();
+        let (pid, mut child) = spawn_dummy_process();
+
+        // Started with detach=false, but by another process
+        let config = test_box_config(false);
+        let mut state = running_state(pid, &config);
+        state.owner_pid = Some(std::process::id() + 1);
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        runtime.shutdown_sync();
+
+        assert!(
+            crate::util::is_process_alive(pid),
+            "Box owned by another process should survive"
+        );
+        let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
+        assert_eq!(db_state.status, BoxStatus::Running);
+
+        child.kill().ok();
+        child.wait().ok();
+    }
+
+    #[test]
+    fn test_shutdown_sync_stops_adopted_detached_box() {
+        let (runtime, _dir) = create_test_runtime();
+        let (pid, mut child) = spawn_dummy_process();
+
+        // Started detached, then adopted by this process
+        let config = test_box_config(true);
+        let mut state = running_state(pid, &config);
+        state.owner_pid = Some(std::process::id());
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        runtime.shutdown_sync();
+
+        std::thread::sleep(std::time::Duration::from_millis(200));
+        assert!(
+            !crate::util::is_process_alive(pid),
+            "Adopted box should be stopped"
+        );
+        let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
+        assert_eq!(db_state.status, BoxStatus::Exited);
+        assert_eq!(db_state.owner_pid, None);
+
+        child.kill().ok();
+        child.wait().ok();
+    }
+
     // ====================================================================
     // Drop tests
     // ====================================================================
@@ -1744,7 +2581,7 @@ mod tests {
         let (pid, mut child) = spawn_dummy_process();
 
         let config = test_box_config(false);
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
         runtime.box_manager.add_box(&config, &state).unwrap();
 
         // Drop the runtime without calling shutdown
@@ -1770,7 +2607,7 @@ mod tests {
         let (pid, mut child) = spawn_dummy_process();
 
         let config = test_box_config(true); // detached
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
         runtime.box_manager.add_box(&config, &state).unwrap();
 
         // Call shutdown explicitly (async) — skips detached boxes
@@ -1847,7 +2684,7 @@ mod tests {
 
         // Use config with box_home aligned to runtime layout
         let config = test_box_config_in_layout(false, &runtime);
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
 
         // Create the box directory and PID file
         let box_dir = runtime.layout.boxes_dir().join(config.id.as_str());
@@ -1875,7 +2712,7 @@ mod tests {
 
         // DB should be updated to Stopped
         let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
-        assert_eq!(db_state.status, BoxStatus::Stopped);
+        assert_eq!(db_state.status, BoxStatus::Exited);
         assert!(db_state.pid.is_none());
 
         child.kill().ok();
@@ -1922,7 +2759,7 @@ mod tests {
         let (pid, mut child) = spawn_sigterm_ignoring_process();
 
         let config = test_box_config(false);
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
         runtime.box_manager.add_box(&config, &state).unwrap();
 
         let start = std::time::Instant::now();
@@ -1945,7 +2782,7 @@ mod tests {
 
         // DB should be updated to Stopped
         let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
-        assert_eq!(db_state.status, BoxStatus::Stopped);
+        assert_eq!(db_state.status, BoxStatus::Exited);
 
         child.kill().ok();
         child.wait().ok();
@@ -1962,7 +2799,7 @@ mod tests {
         let (pid, mut child) = spawn_dummy_process();
 
         let config = test_box_config(false);
-        let state = running_state(pid);
+        let state = running_state(pid, &config);
         runtime.box_manager.add_box(&config, &state).unwrap();
 
         // Wrap in LocalRuntime (the backend wrapper) and call via trait
@@ -1978,7 +2815,7 @@ mod tests {
 
         // DB should be updated
         let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
-        assert_eq!(db_state.status, BoxStatus::Stopped);
+        assert_eq!(db_state.status, BoxStatus::Exited);
 
         child.kill().ok();
         child.wait().ok();
@@ -1989,7 +2826,7 @@ mod tests {
     // ====================================================================
 
     #[tokio::test]
-    async fn test_create_after_shutdown_returns_stopped() {
+    async fn test_create_after_shutdown_returns_shutting_down() {
         let (runtime, _dir) = create_test_runtime();
 
         // Shutdown the runtime
@@ -2008,14 +2845,247 @@ mod tests {
             .await;
 
         match result {
-            Err(BoxliteError::Stopped(msg)) => {
-                assert!(
-                    msg.contains("shut down"),
-                    "Error should mention 'shut down': {msg}"
-                );
+            Err(BoxliteError::ShuttingDown(msg)) => {
+                assert_eq!(msg, "cannot create box");
             }
-            Err(other) => panic!("Expected Stopped error, got: {other}"),
+            Err(other) => panic!("Expected ShuttingDown error, got: {other}"),
             Ok(_) => panic!("create should fail after shutdown"),
         }
     }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_create_racing_shutdown_succeeds_or_is_refused() {
+        let (runtime, _dir) = create_test_runtime();
+
+        let creates: Vec<_> = (0..16)
+            .map(|i| {
+                let runtime = Arc::clone(&runtime);
+                tokio::spawn(async move {
+                    runtime
+                        .create(
+                            BoxOptions {
+                                rootfs: RootfsSpec::Image("alpine:latest".into()),
+                                auto_remove: false,
+                                ..Default::default()
+                            },
+                            Some(format!("race-{i}")),
+                        )
+                        .await
+                })
+            })
+            .collect();
+        runtime.shutdown(Some(5)).await.unwrap();
+
+        // Every create either finished before shutdown returned or was
+        // refused; none fails any other way or lands after shutdown.
+        let persisted = runtime.box_manager.all_boxes(true).unwrap().len();
+        let mut created = 0;
+        for create in creates {
+            match create.await.unwrap() {
+                Ok(_) => created += 1,
+                Err(BoxliteError::ShuttingDown(_)) => {}
+                Err(other) => panic!("Expected Ok or ShuttingDown, got: {other}"),
+            }
+        }
+        assert_eq!(created, persisted);
+        assert_eq!(
+            runtime.box_manager.all_boxes(true).unwrap().len(),
+            persisted
+        );
+    }
+
+    #[tokio::test]
+    async fn test_remove_after_shutdown_returns_shutting_down() {
+        let (runtime, _dir) = create_test_runtime();
+        let litebox = runtime
+            .create(
+                BoxOptions {
+                    rootfs: RootfsSpec::Image("alpine:latest".into()),
+                    auto_remove: false,
+                    ..Default::default()
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        runtime.shutdown(None).await.unwrap();
+
+        let local = LocalRuntime(Arc::clone(&runtime));
+        let result = local.remove(litebox.id().as_str(), true).await;
+        assert!(matches!(result, Err(BoxliteError::ShuttingDown(_))));
+
+        // Read-only queries keep working
+        assert_eq!(local.list_info().await.unwrap().len(), 1);
+    }
+
+    // ====================================================================
+    // Sockets left behind by a SIGKILLed shim
+    // ====================================================================
+
+    /// Bind `path` in a child process, SIGKILL it and return its PID.
+    ///
+    /// Leaves the socket file behind, as a SIGKILLed shim does.
+    fn sigkill_socket_owner(path: &std::path::Path) -> u32 {
+        use std::os::fd::OwnedFd;
+        use std::os::unix::net::UnixListener;
+
+        let listener = UnixListener::bind(path).expect("Failed to bind socket");
+        let mut child = std::process::Command::new("sleep")
+            .arg("300")
+            .stdin(std::process::Stdio::from(OwnedFd::from(listener)))
+            .spawn()
+            .expect("Failed to spawn socket owner");
+        assert_eq!(
+            crate::util::probe_socket(path),
+            crate::util::SocketState::Live
+        );
+
+        let pid = child.id();
+        child.kill().expect("Failed to kill socket owner");
+        child.wait().ok();
+        pid
+    }
+
+    #[test]
+    fn test_recovery_removes_sockets_of_sigkilled_shim() {
+        let (runtime, _dir) = create_test_runtime();
+        let sockets_dir = TempDir::new_in("/tmp").unwrap();
+        let socket = sockets_dir.path().join("net.sock");
+        let pid = sigkill_socket_owner(&socket);
+
+        let config = test_box_config_in_layout(false, &runtime);
+        let box_dir = runtime.layout.boxes_dir().join(config.id.as_str());
+        std::fs::create_dir_all(&box_dir).unwrap();
+        write_container_disk(&config);
+        std::fs::write(box_dir.join("shim.pid"), pid.to_string()).unwrap();
+        let mut state = running_state(pid, &config);
+        state.set_sockets(vec![socket.clone()]);
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        runtime.recover_boxes().unwrap();
+
+        assert!(!socket.exists(), "Stale socket should be removed");
+        let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
+        assert_eq!(db_state.status, BoxStatus::Exited);
+        assert_eq!(db_state.sockets, vec![socket.clone()]);
+
+        // The next start can bind the same path
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+    }
+
+    fn write_container_disk(config: &BoxConfig) {
+        let disk = config
+            .box_home
+            .join(config.disk_driver.driver().container_disk_name());
+        std::fs::write(disk, b"").unwrap();
+    }
+
+    #[test]
+    fn test_recovery_records_crash_exit_code() {
+        let (runtime, _dir) = create_test_runtime();
+        let config = test_box_config_in_layout(false, &runtime);
+        std::fs::create_dir_all(&config.box_home).unwrap();
+        write_container_disk(&config);
+        // Not a real process: the shim is gone
+        let dead_pid = 999_999_999u32;
+        std::fs::write(config.box_home.join("shim.pid"), dead_pid.to_string()).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(
+            config.box_home.join("exit"),
+            r#"{"exit_code":134,"type":"signal","signal":"SIGABRT"}"#,
+        )
+        .unwrap();
+        let state = running_state(dead_pid, &config);
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        runtime.recover_boxes().unwrap();
+
+        let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
+        assert_eq!(db_state.status, BoxStatus::Exited);
+        assert_eq!(db_state.exit_code, Some(134));
+    }
+
+    #[test]
+    fn test_recovery_marks_box_without_disk_dead() {
+        let (runtime, _dir) = create_test_runtime();
+        let config = test_box_config_in_layout(false, &runtime);
+        std::fs::create_dir_all(&config.box_home).unwrap();
+        let mut state = BoxState::new();
+        state.mark_exited(Some(0));
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        // A created box has no disk yet and stays created
+        let created = test_box_config_in_layout(false, &runtime);
+        runtime
+            .box_manager
+            .add_box(&created, &BoxState::new())
+            .unwrap();
+
+        runtime.recover_boxes().unwrap();
+
+        let (_, db_state) = runtime.box_manager.box_by_id(&config.id).unwrap().unwrap();
+        assert_eq!(db_state.status, BoxStatus::Dead);
+        let (_, db_state) = runtime.box_manager.box_by_id(&created.id).unwrap().unwrap();
+        assert_eq!(db_state.status, BoxStatus::Created);
+    }
+
+    #[test]
+    fn test_recovery_keeps_live_sockets() {
+        let (runtime, _dir) = create_test_runtime();
+        let sockets_dir = TempDir::new_in("/tmp").unwrap();
+        let socket = sockets_dir.path().join("net.sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+
+        // The PID file is unreadable, but someone else still owns the socket
+        let config = test_box_config_in_layout(false, &runtime);
+        let box_dir = runtime.layout.boxes_dir().join(config.id.as_str());
+        std::fs::create_dir_all(&box_dir).unwrap();
+        std::fs::write(box_dir.join("shim.pid"), "garbage").unwrap();
+        let mut state = running_state(u32::MAX, &config);
+        state.set_sockets(vec![socket.clone()]);
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        runtime.recover_boxes().unwrap();
+
+        assert!(socket.exists(), "Live socket should be kept");
+    }
+
+    #[test]
+    fn test_remove_cleans_registered_sockets() {
+        let (runtime, _dir) = create_test_runtime();
+        let sockets_dir = TempDir::new_in("/tmp").unwrap();
+        let socket = sockets_dir.path().join("box.sock");
+        sigkill_socket_owner(&socket);
+
+        // Registered sockets are removed even outside the box home
+        let config = test_box_config(false);
+        let mut state = BoxState::new();
+        state.status = BoxStatus::Exited;
+        state.set_sockets(vec![socket.clone()]);
+        runtime.box_manager.add_box(&config, &state).unwrap();
+
+        runtime.remove_box(&config.id, false).unwrap();
+
+        assert!(!socket.exists(), "Registered socket should be removed");
+    }
+
+    #[test]
+    fn test_shutdown_grace_leaves_room_for_the_kill() {
+        use std::time::Duration;
+
+        let grace = Duration::from_secs(30);
+        assert_eq!(shutdown_grace(grace, None), grace);
+        assert_eq!(
+            shutdown_grace(grace, Some(Duration::from_secs(10))),
+            Duration::from_secs(8)
+        );
+        assert_eq!(
+            shutdown_grace(Duration::from_secs(3), Some(Duration::from_secs(10))),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            shutdown_grace(grace, Some(Duration::from_secs(1))),
+            Duration::ZERO
+        );
+    }
 }
@@ -0,0 +1,340 @@
+//! One-shot box execution: create, run a command, collect output, destroy.
+
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use tokio::task::JoinHandle;
+
+use crate::litebox::{BoxCommand, LiteBox};
+use crate::runtime::core::BoxliteRuntime;
+use crate::runtime::options::BoxOptions;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+/// Default per-stream output capture limit (1 MiB).
+pub(super) const DEFAULT_MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Options for [`BoxliteRuntime::run_once`].
+#[derive(Debug, Clone)]
+pub struct RunOnceOptions {
+    /// Maximum bytes captured per stream (stdout and stderr each).
+    /// Output beyond the limit is drained and discarded (default: 1 MiB).
+    pub max_output_bytes: usize,
+    /// Overall deadline covering create, start, exec and collection.
+    /// Cleanup runs after the deadline and is not counted (default: none).
+    pub timeout: Option<Duration>,
+}
+
+impl Default for RunOnceOptions {
+    fn default() -> Self {
+        Self {
+            max_output_bytes: DEFAULT_MAX_OUTPUT_BYTES,
+            timeout: None,
+        }
+    }
+}
+
+impl RunOnceOptions {
+    /// Set the per-stream output capture limit in bytes.
+    pub fn max_output_bytes(&mut self, bytes: usize) -> &mut Self {
+        self.max_output_bytes = bytes;
+        self
+    }
+
+    /// Set the overall deadline.
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Result of a one-shot run.
+#[derive(Debug, Clone)]
+pub struct RunOnceResult {
+    /// Exit code (0 = success). Negative signal number if killed by a signal.
+    pub exit_code: i32,
+    /// Captured stdout (up to `max_output_bytes`).
+    pub stdout: String,
+    /// Captured stderr (up to `max_output_bytes`).
+    pub stderr: String,
+    /// Whether stdout exceeded the capture limit.
+    pub stdout_truncated: bool,
+    /// Whether stderr exceeded the capture limit.
+    pub stderr_truncated: bool,
+    /// Diagnostic message when the process died unexpectedly.
+    pub error_message: Option<String>,
+    /// Wall-clock time from create to command completion.
+    pub duration: Duration,
+}
+
+impl RunOnceResult {
+    /// Returns true if the exit code was 0.
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+impl BoxliteRuntime {
+    /// Run a single command in a throwaway box.
+    ///
+    /// Creates an unnamed, auto-removing box from `options`, starts it,
+    /// executes `command` while capturing bounded stdout/stderr, then stops
+    /// and removes the box. Cleanup is best-effort and runs on every path,
+    /// including command failure and deadline expiry. A deadline that
+    /// passes while the box is still being created fails with
+    /// `BoxliteError::Timeout` right away; the box is removed in the
+    /// background once creation finishes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use boxlite::{BoxCommand, BoxOptions, BoxliteRuntime, RunOnceOptions};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let runtime = BoxliteRuntime::with_defaults()?;
+    /// let result = runtime
+    ///     .run_once(
+    ///         BoxOptions::default(),
+    ///         BoxCommand::new("echo").arg("hello"),
+    ///         RunOnceOptions::default(),
+    ///     )
+    ///     .await?;
+    /// assert_eq!(result.stdout, "hello\n");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_once(
+        &self,
+        mut options: BoxOptions,
+        command: BoxCommand,
+        opts: RunOnceOptions,
+    ) -> BoxliteResult<RunOnceResult> {
+        let started = Instant::now();
+        let deadline = opts.timeout.map(|t| started + t);
+
+        options.auto_remove = true;
+        options.detach = false;
+
+        let mut create = CreateGuard::spawn(self, options);
+        let litebox = with_deadline(deadline, create.wait()).await?;
+
+        let outcome = with_deadline(
+            deadline,
+            run_and_collect(&litebox, command, opts.max_output_bytes),
+        )
+        .await;
+        let duration = started.elapsed();
+
+        self.cleanup_run_once(&litebox).await;
+
+        let (exit_code, error_message, stdout, stderr) = outcome?;
+        Ok(RunOnceResult {
+            exit_code,
+            error_message,
+            stdout_truncated: stdout.truncated,
+            stderr_truncated: stderr.truncated,
+            stdout: stdout.text,
+            stderr: stderr.text,
+            duration,
+        })
+    }
+
+    /// Best-effort stop + remove. Never fails; problems are logged.
+    async fn cleanup_run_once(&self, litebox: &LiteBox) {
+        let box_id = litebox.id().clone();
+
+        if let Err(e) = litebox.stop().await {
+            tracing::warn!(box_id = %box_id, error = %e, "run_once: failed to stop box");
+        }
+
+        // auto_remove usually removed the box on stop; force-remove covers
+        // the paths where stop failed or never started.
+        match self.remove(box_id.as_str(), true).await {
+            Ok(()) | Err(BoxliteError::NotFound(_)) => {}
+            Err(e) => {
+                tracing::warn!(box_id = %box_id, error = %e, "run_once: failed to remove box");
+            }
+        }
+    }
+}
+
+/// Box creation running on a task of its own, so that a deadline or a
+/// dropped `run_once` future can't abandon a half-created box: if the caller
+/// stops waiting, the box is removed once creation finishes.
+struct CreateGuard {
+    runtime: BoxliteRuntime,
+    task: Option<JoinHandle<BoxliteResult<LiteBox>>>,
+}
+
+impl CreateGuard {
+    fn spawn(runtime: &BoxliteRuntime, options: BoxOptions) -> Self {
+        let creator = runtime.clone();
+        Self {
+            runtime: runtime.clone(),
+            task: Some(tokio::spawn(
+                async move { creator.create(options, None).await },
+            )),
+        }
+    }
+
+    /// Wait for the box. Cancel-safe: dropping this future leaves the task
+    /// to the guard.
+    async fn wait(&mut self) -> BoxliteResult<LiteBox> {
+        let task = self
+            .task
+            .as_mut()
+            .ok_or_else(|| BoxliteError::Internal("run_once box already taken".to_string()))?;
+        let joined = task.await;
+        self.task = None;
+        joined.map_err(|e| BoxliteError::Internal(format!("run_once create task failed: {e}")))?
+    }
+}
+
+impl Drop for CreateGuard {
+    fn drop(&mut self) {
+        let Some(task) = self.task.take() else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            tracing::warn!("run_once: abandoned box creation outside a tokio runtime");
+            return;
+        };
+        let runtime = self.runtime.clone();
+        handle.spawn(async move {
+            if let Ok(Ok(litebox)) = task.await {
+                runtime.cleanup_run_once(&litebox).await;
+            }
+        });
+    }
+}
+
+/// Start the box, execute the command and collect its output.
+async fn run_and_collect(
+    litebox: &LiteBox,
+    command: BoxCommand,
+    max_output_bytes: usize,
+) -> BoxliteResult<(i32, Option<String>, BoundedOutput, BoundedOutput)> {
+    litebox.start().await?;
+
+    let mut execution = litebox.exec(command).await?;
+    let stdout = execution.stdout();
+    let stderr = execution.stderr();
+
+    let (stdout, stderr) = tokio::join!(
+        collect_stream(stdout, max_output_bytes),
+        collect_stream(stderr, max_output_bytes),
+    );
+    let result = execution.wait().await?;
+
+    Ok((result.exit_code, result.error_message, stdout, stderr))
+}
+
+/// Captured output, bounded to a byte limit.
+#[derive(Debug, Default)]
+pub(super) struct BoundedOutput {
+    pub(super) text: String,
+    pub(super) truncated: bool,
+}
+
+impl BoundedOutput {
+    /// Append a chunk, keeping at most `limit` bytes (on a char boundary).
+    fn push(&mut self, chunk: &str, limit: usize) {
+        if self.truncated {
+            return;
+        }
+        let remaining = limit.saturating_sub(self.text.len());
+        if chunk.len() <= remaining {
+            self.text.push_str(chunk);
+            return;
+        }
+        let mut cut = remaining;
+        while !chunk.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        self.text.push_str(&chunk[..cut]);
+        self.truncated = true;
+    }
+}
+
+/// Drain a stream to completion, capturing at most `limit` bytes.
+pub(super) async fn collect_stream<S>(stream: Option<S>, limit: usize) -> BoundedOutput
+where
+    S: futures::Stream<Item = String> + Unpin,
+{
+    let mut output = BoundedOutput::default();
+    if let Some(mut stream) = stream {
+        while let Some(chunk) = stream.next().await {
+            output.push(&chunk, limit);
+        }
+    }
+    output
+}
+
+/// Run `fut`, failing with a timeout error if `deadline` passes first.
+async fn with_deadline<T>(
+    deadline: Option<Instant>,
+    fut: impl std::future::Future<Output = BoxliteResult<T>>,
+) -> BoxliteResult<T> {
+    let Some(deadline) = deadline else {
+        return fut.await;
+    };
+    tokio::time::timeout_at(deadline.into(), fut)
+        .await
+        .map_err(|_| BoxliteError::Timeout("run_once deadline exceeded".to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounded_output_within_limit() {
+        let mut out = BoundedOutput::default();
+        out.push("hello ", 16);
+        out.push("world", 16);
+        assert_eq!(out.text, "hello world");
+        assert!(!out.truncated);
+    }
+
+    #[test]
+    fn test_bounded_output_truncates_at_limit() {
+        let mut out = BoundedOutput::default();
+        out.push("abcdef", 4);
+        out.push("more", 4);
+        assert_eq!(out.text, "abcd");
+        assert!(out.truncated);
+    }
+
+    #[test]
+    fn test_bounded_output_respects_char_boundary() {
+        let mut out = BoundedOutput::default();
+        // "é" is two bytes; a 2-byte limit must not split it after "a"
+        out.push("aé", 2);
+        assert_eq!(out.text, "a");
+        assert!(out.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_collect_stream_drains_past_limit() {
+        let stream = futures::stream::iter(vec!["12".to_string(), "345".to_string()]);
+        let out = collect_stream(Some(stream), 3).await;
+        assert_eq!(out.text, "123");
+        assert!(out.truncated);
+
+        let none: Option<futures::stream::Iter<std::vec::IntoIter<String>>> = None;
+        assert_eq!(collect_stream(none, 3).await.text, "");
+    }
+
+    #[tokio::test]
+    async fn test_with_deadline_expires() {
+        let deadline = Some(Instant::now() + Duration::from_millis(20));
+        let result: BoxliteResult<()> = with_deadline(deadline, async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })
+        .await;
+        assert!(matches!(result, Err(BoxliteError::Timeout(_))));
+
+        let result = with_deadline(None, async { Ok(7) }).await.unwrap();
+        assert_eq!(result, 7);
+    }
+}
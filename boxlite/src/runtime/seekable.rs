@@ -0,0 +1,282 @@
+//! Zstd seekable format for export archives.
+//!
+//! The stream is cut into independent zstd frames of at most
+//! [`FRAME_SIZE`] decompressed bytes, followed by a seek table in a
+//! skippable frame (the layout of zstd's `contrib/seekable_format`):
+//!
+//! ```text
+//! [frame]...[0x184D2A5E][table size][(compressed, decompressed) u32 pairs]
+//!           [frame count u32][descriptor u8][0x8F92EAB1]
+//! ```
+//!
+//! Regular zstd decoders skip the table, so a seekable archive still
+//! imports as a plain `.boxsnap`. With the table, a byte range of the
+//! decompressed stream is read by decompressing only the frames holding it.
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Decompressed size of each frame.
+pub(crate) const FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+const SKIPPABLE_MAGIC: u32 = 0x184D_2A5E;
+const SEEKABLE_MAGIC: u32 = 0x8F92_EAB1;
+const FOOTER_SIZE: u64 = 9;
+const CHECKSUM_FLAG: u8 = 0x80;
+const RESERVED_BITS: u8 = 0x7C;
+
+/// Writer producing a seekable zstd stream.
+///
+/// Call [`finish`](Self::finish) to write the last frame and the seek
+/// table; dropping the writer leaves a stream without them.
+pub(crate) struct SeekableWriter<W: Write> {
+    inner: W,
+    level: i32,
+    buf: Vec<u8>,
+    /// (compressed, decompressed) size of each frame written so far.
+    frames: Vec<(u32, u32)>,
+}
+
+impl<W: Write> SeekableWriter<W> {
+    pub(crate) fn new(inner: W, level: i32) -> Self {
+        Self {
+            inner,
+            level,
+            buf: Vec::with_capacity(FRAME_SIZE),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Write the pending frame and the seek table, returning the inner writer.
+    pub(crate) fn finish(mut self) -> io::Result<W> {
+        self.write_frame()?;
+
+        let entries = self.frames.len() as u32;
+        let table_size = entries * 8 + FOOTER_SIZE as u32;
+        let mut table = Vec::with_capacity(8 + table_size as usize);
+        table.extend_from_slice(&SKIPPABLE_MAGIC.to_le_bytes());
+        table.extend_from_slice(&table_size.to_le_bytes());
+        for (compressed, decompressed) in &self.frames {
+            table.extend_from_slice(&compressed.to_le_bytes());
+            table.extend_from_slice(&decompressed.to_le_bytes());
+        }
+        table.extend_from_slice(&entries.to_le_bytes());
+        table.push(0);
+        table.extend_from_slice(&SEEKABLE_MAGIC.to_le_bytes());
+        self.inner.write_all(&table)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_frame(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let compressed = zstd::bulk::compress(&self.buf, self.level)?;
+        self.inner.write_all(&compressed)?;
+        self.frames
+            .push((compressed.len() as u32, self.buf.len() as u32));
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for SeekableWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(FRAME_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == FRAME_SIZE {
+            self.write_frame()?;
+        }
+        Ok(n)
+    }
+
+    /// Flushes the inner writer without cutting the pending frame short.
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// One frame of a seekable stream.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    compressed_offset: u64,
+    decompressed_offset: u64,
+    compressed_size: u32,
+    decompressed_size: u32,
+}
+
+/// Random access to the decompressed bytes of a seekable zstd stream.
+pub(crate) struct SeekableReader<R: Read + Seek> {
+    inner: R,
+    frames: Vec<Frame>,
+}
+
+impl<R: Read + Seek> SeekableReader<R> {
+    /// Read the seek table at the end of `inner`.
+    ///
+    /// Returns `Ok(None)` when the stream has no seek table, i.e. is not in
+    /// the seekable format.
+    pub(crate) fn open(mut inner: R) -> io::Result<Option<Self>> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        if len < 8 + FOOTER_SIZE {
+            return Ok(None);
+        }
+
+        let mut footer = [0u8; FOOTER_SIZE as usize];
+        inner.seek(SeekFrom::Start(len - FOOTER_SIZE))?;
+        inner.read_exact(&mut footer)?;
+        if u32_at(&footer, 5) != SEEKABLE_MAGIC {
+            return Ok(None);
+        }
+        let count = u32_at(&footer, 0) as u64;
+        let descriptor = footer[4];
+        if descriptor & RESERVED_BITS != 0 {
+            return Err(invalid("reserved seek table descriptor bits set"));
+        }
+        let entry_size: u64 = if descriptor & CHECKSUM_FLAG != 0 {
+            12
+        } else {
+            8
+        };
+
+        let table_size = count * entry_size + FOOTER_SIZE;
+        let table_start = len
+            .checked_sub(8 + table_size)
+            .ok_or_else(|| invalid("seek table larger than the stream"))?;
+        let mut table = vec![0u8; (8 + table_size - FOOTER_SIZE) as usize];
+        inner.seek(SeekFrom::Start(table_start))?;
+        inner.read_exact(&mut table)?;
+        if u32_at(&table, 0) != SKIPPABLE_MAGIC || u32_at(&table, 4) as u64 != table_size {
+            return Err(invalid("malformed seek table header"));
+        }
+
+        let mut frames = Vec::with_capacity(count as usize);
+        let (mut compressed_offset, mut decompressed_offset) = (0u64, 0u64);
+        for entry in table[8..].chunks_exact(entry_size as usize) {
+            let frame = Frame {
+                compressed_offset,
+                decompressed_offset,
+                compressed_size: u32_at(entry, 0),
+                decompressed_size: u32_at(entry, 4),
+            };
+            compressed_offset += frame.compressed_size as u64;
+            decompressed_offset += frame.decompressed_size as u64;
+            frames.push(frame);
+        }
+        if compressed_offset != table_start {
+            return Err(invalid("seek table does not match the stream size"));
+        }
+
+        Ok(Some(Self { inner, frames }))
+    }
+
+    /// Size of the decompressed stream.
+    pub(crate) fn decompressed_len(&self) -> u64 {
+        self.frames
+            .last()
+            .map_or(0, |f| f.decompressed_offset + f.decompressed_size as u64)
+    }
+
+    /// Copy `len` decompressed bytes starting at `offset` to `out`,
+    /// decompressing only the frames that overlap the range.
+    pub(crate) fn read_range<W: Write>(
+        &mut self,
+        offset: u64,
+        len: u64,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let end = offset
+            .checked_add(len)
+            .filter(|end| *end <= self.decompressed_len())
+            .ok_or_else(|| invalid("range beyond the end of the stream"))?;
+        if len == 0 {
+            return Ok(());
+        }
+
+        let first = self
+            .frames
+            .partition_point(|f| f.decompressed_offset + f.decompressed_size as u64 <= offset);
+        let mut compressed = Vec::new();
+        for frame in &self.frames[first..] {
+            if frame.decompressed_offset >= end {
+                break;
+            }
+            compressed.resize(frame.compressed_size as usize, 0);
+            self.inner.seek(SeekFrom::Start(frame.compressed_offset))?;
+            self.inner.read_exact(&mut compressed)?;
+            let data = zstd::bulk::decompress(&compressed, frame.decompressed_size as usize)?;
+            if data.len() != frame.decompressed_size as usize {
+                return Err(invalid("frame size does not match the seek table"));
+            }
+
+            let from = offset.saturating_sub(frame.decompressed_offset) as usize;
+            let to = (end - frame.decompressed_offset).min(data.len() as u64) as usize;
+            out.write_all(&data[from..to])?;
+        }
+        Ok(())
+    }
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("seekable zstd: {}", msg),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn encode(data: &[u8]) -> Vec<u8> {
+        let mut writer = SeekableWriter::new(Vec::new(), 3);
+        writer.write_all(data).unwrap();
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn test_range_across_frame_boundary() {
+        let data = sample(FRAME_SIZE * 2 + 1000);
+        let mut reader = SeekableReader::open(Cursor::new(encode(&data)))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reader.decompressed_len(), data.len() as u64);
+
+        let start = FRAME_SIZE - 10;
+        let mut out = Vec::new();
+        reader
+            .read_range(start as u64, FRAME_SIZE as u64 + 20, &mut out)
+            .unwrap();
+        assert_eq!(out, data[start..start + FRAME_SIZE + 20]);
+    }
+
+    #[test]
+    fn test_plain_zstd_decoder_reads_seekable_stream() {
+        let data = sample(FRAME_SIZE + 1);
+        let decoded = zstd::decode_all(Cursor::new(encode(&data))).unwrap();
+        assert_eq!(decoded, data);
+    }
+
+    #[test]
+    fn test_open_returns_none_without_seek_table() {
+        let plain = zstd::encode_all(Cursor::new(sample(1000)), 3).unwrap();
+        assert!(SeekableReader::open(Cursor::new(plain)).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_range_past_end_is_rejected() {
+        let mut reader = SeekableReader::open(Cursor::new(encode(&sample(100))))
+            .unwrap()
+            .unwrap();
+        assert!(reader.read_range(90, 20, &mut Vec::new()).is_err());
+    }
+}
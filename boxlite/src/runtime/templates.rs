@@ -0,0 +1,343 @@
+//! Box templates: named `BoxOptions` that boxes are created from.
+//!
+//! Templates are stored in the `box_template` table and validated when they
+//! are registered, so `create_from_template()` can only fail on the
+//! overrides it is given. `BoxliteOptions::templates` registers templates
+//! from the config file each time a runtime is created.
+
+use std::collections::HashMap;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::db::TemplateStore;
+use crate::db::templates::BoxTemplate;
+use crate::runtime::options::{
+    BoxOptions, NetworkMode, PortSpec, RootfsSpec, TemplateSpec, VolumeSpec,
+};
+
+use super::rt_impl::RuntimeImpl;
+
+/// Overrides applied to a template's options by `create_from_template()`.
+///
+/// `env` and `labels` are merged into the template's by key. Every other
+/// field that is set replaces the template's value; vectors are replaced
+/// as a whole, not appended to.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoxOptionsPatch {
+    /// Environment variables to set, overriding template entries with the same key.
+    pub env: Vec<(String, String)>,
+    /// Labels to set, overriding template labels with the same key.
+    pub labels: HashMap<String, String>,
+    pub cpus: Option<u8>,
+    pub memory_mib: Option<u32>,
+    pub disk_size_gb: Option<u64>,
+    pub swap_mib: Option<u32>,
+    pub working_dir: Option<String>,
+    pub cmd: Option<Vec<String>>,
+    pub volumes: Option<Vec<VolumeSpec>>,
+    pub ports: Option<Vec<PortSpec>>,
+    pub network: Option<NetworkMode>,
+    pub auto_remove: Option<bool>,
+    pub detach: Option<bool>,
+}
+
+impl BoxOptionsPatch {
+    /// Set an environment variable.
+    pub fn env(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set a label.
+    pub fn label(&mut self, key: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Number of vCPUs.
+    pub fn cpus(&mut self, cpus: u8) -> &mut Self {
+        self.cpus = Some(cpus);
+        self
+    }
+
+    /// Memory in MiB.
+    pub fn memory_mib(&mut self, memory_mib: u32) -> &mut Self {
+        self.memory_mib = Some(memory_mib);
+        self
+    }
+
+    /// Container rootfs disk size in GB.
+    pub fn disk_size_gb(&mut self, disk_size_gb: u64) -> &mut Self {
+        self.disk_size_gb = Some(disk_size_gb);
+        self
+    }
+
+    /// Swap inside the VM in MiB, 0 for none.
+    pub fn swap_mib(&mut self, swap_mib: u32) -> &mut Self {
+        self.swap_mib = Some(swap_mib);
+        self
+    }
+
+    /// Working directory inside the box.
+    pub fn working_dir(&mut self, working_dir: impl Into<String>) -> &mut Self {
+        self.working_dir = Some(working_dir.into());
+        self
+    }
+
+    /// Replace the image CMD.
+    pub fn cmd(&mut self, cmd: Vec<String>) -> &mut Self {
+        self.cmd = Some(cmd);
+        self
+    }
+
+    /// Replace the template's volumes.
+    pub fn volumes(&mut self, volumes: Vec<VolumeSpec>) -> &mut Self {
+        self.volumes = Some(volumes);
+        self
+    }
+
+    /// Replace the template's published ports.
+    pub fn ports(&mut self, ports: Vec<PortSpec>) -> &mut Self {
+        self.ports = Some(ports);
+        self
+    }
+
+    /// Network access of the box.
+    pub fn network(&mut self, network: NetworkMode) -> &mut Self {
+        self.network = Some(network);
+        self
+    }
+
+    /// Remove the box when it stops.
+    pub fn auto_remove(&mut self, auto_remove: bool) -> &mut Self {
+        self.auto_remove = Some(auto_remove);
+        self
+    }
+
+    /// Keep the box running after the creating process exits.
+    pub fn detach(&mut self, detach: bool) -> &mut Self {
+        self.detach = Some(detach);
+        self
+    }
+
+    /// `options` with this patch applied.
+    pub fn apply(&self, mut options: BoxOptions) -> BoxOptions {
+        for (key, value) in &self.env {
+            match options.env.iter_mut().find(|(k, _)| k == key) {
+                Some(entry) => entry.1 = value.clone(),
+                None => options.env.push((key.clone(), value.clone())),
+            }
+        }
+        options.labels.extend(self.labels.clone());
+
+        options.cpus = self.cpus.or(options.cpus);
+        options.memory_mib = self.memory_mib.or(options.memory_mib);
+        options.disk_size_gb = self.disk_size_gb.or(options.disk_size_gb);
+        options.swap_mib = self.swap_mib.or(options.swap_mib);
+        if let Some(working_dir) = &self.working_dir {
+            options.working_dir = Some(working_dir.clone());
+        }
+        if let Some(cmd) = &self.cmd {
+            options.cmd = Some(cmd.clone());
+        }
+        if let Some(volumes) = &self.volumes {
+            options.volumes = volumes.clone();
+        }
+        if let Some(ports) = &self.ports {
+            options.ports = ports.clone();
+        }
+        options.network = self.network.unwrap_or(options.network);
+        options.auto_remove = self.auto_remove.unwrap_or(options.auto_remove);
+        options.detach = self.detach.unwrap_or(options.detach);
+        options
+    }
+}
+
+impl RuntimeImpl {
+    fn template_store(&self) -> TemplateStore {
+        TemplateStore::new(self.box_manager.db())
+    }
+
+    /// Validate and store a new template.
+    pub(crate) fn register_template(
+        &self,
+        name: &str,
+        options: BoxOptions,
+    ) -> BoxliteResult<BoxTemplate> {
+        validate_template(name, &options)?;
+        let template = BoxTemplate {
+            name: name.to_string(),
+            options,
+            created_at: Utc::now(),
+        };
+        self.template_store().insert(&template)?;
+
+        tracing::info!(template = %name, "Registered box template");
+        Ok(template)
+    }
+
+    /// Store the templates of `BoxliteOptions::templates`, replacing any
+    /// with the same name. `specs` must have passed [`validate_template_specs`].
+    pub(crate) fn import_templates(&self, specs: &[TemplateSpec]) -> BoxliteResult<()> {
+        let store = self.template_store();
+        for spec in specs {
+            store.upsert(&BoxTemplate {
+                name: spec.name.clone(),
+                options: spec.options.clone(),
+                created_at: Utc::now(),
+            })?;
+        }
+        if !specs.is_empty() {
+            tracing::debug!(count = specs.len(), "Imported box templates from options");
+        }
+        Ok(())
+    }
+
+    pub(crate) fn get_template(&self, name: &str) -> BoxliteResult<Option<BoxTemplate>> {
+        self.template_store().get(name)
+    }
+
+    pub(crate) fn list_templates(&self) -> BoxliteResult<Vec<BoxTemplate>> {
+        self.template_store().list()
+    }
+
+    /// Delete a template. Boxes created from it are unaffected.
+    pub(crate) fn remove_template(&self, name: &str) -> BoxliteResult<()> {
+        if !self.template_store().remove(name)? {
+            return Err(template_not_found(name));
+        }
+        tracing::info!(template = %name, "Removed box template");
+        Ok(())
+    }
+}
+
+/// Check a template before it is stored.
+pub(crate) fn validate_template(name: &str, options: &BoxOptions) -> BoxliteResult<()> {
+    if name.is_empty() || name.chars().any(char::is_whitespace) {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "invalid template name '{}': must be non-empty and contain no whitespace",
+            name
+        )));
+    }
+    let (RootfsSpec::Image(rootfs) | RootfsSpec::RootfsPath(rootfs)) = &options.rootfs;
+    if rootfs.trim().is_empty() {
+        return Err(BoxliteError::Config(format!(
+            "template '{}' has an empty rootfs",
+            name
+        )));
+    }
+    options.sanitize()
+}
+
+/// Validate `BoxliteOptions::templates` before the runtime is set up.
+pub(crate) fn validate_template_specs(specs: &[TemplateSpec]) -> BoxliteResult<()> {
+    let mut names = std::collections::HashSet::new();
+    for spec in specs {
+        validate_template(&spec.name, &spec.options)?;
+        if !names.insert(spec.name.as_str()) {
+            return Err(BoxliteError::Config(format!(
+                "duplicate template '{}'",
+                spec.name
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn template_not_found(name: &str) -> BoxliteError {
+    BoxliteError::NotFound(format!("template '{}' not found", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template_options() -> BoxOptions {
+        BoxOptions {
+            rootfs: RootfsSpec::Image("nginx:latest".to_string()),
+            cpus: Some(2),
+            memory_mib: Some(512),
+            env: vec![
+                ("TIER".to_string(), "web".to_string()),
+                ("TENANT".to_string(), "none".to_string()),
+            ],
+            labels: HashMap::from([("tier".to_string(), "web".to_string())]),
+            volumes: vec![VolumeSpec {
+                host_path: "/srv/a".to_string(),
+                guest_path: "/data".to_string(),
+                read_only: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_patch_merges_env_and_labels_by_key() {
+        let mut patch = BoxOptionsPatch::default();
+        patch
+            .env("TENANT", "acme")
+            .env("REGION", "eu")
+            .label("tenant", "acme");
+
+        let options = patch.apply(template_options());
+        assert_eq!(
+            options.env,
+            vec![
+                ("TIER".to_string(), "web".to_string()),
+                ("TENANT".to_string(), "acme".to_string()),
+                ("REGION".to_string(), "eu".to_string()),
+            ]
+        );
+        assert_eq!(options.labels.len(), 2);
+        assert_eq!(options.labels["tier"], "web");
+        assert_eq!(options.labels["tenant"], "acme");
+    }
+
+    #[test]
+    fn test_patch_replaces_resources_and_vectors() {
+        let mut patch = BoxOptionsPatch::default();
+        patch.memory_mib(1024).volumes(Vec::new());
+
+        let options = patch.apply(template_options());
+        assert_eq!(options.cpus, Some(2));
+        assert_eq!(options.memory_mib, Some(1024));
+        assert!(options.volumes.is_empty());
+    }
+
+    #[test]
+    fn test_empty_patch_keeps_template() {
+        let options = BoxOptionsPatch::default().apply(template_options());
+        assert_eq!(options.env.len(), 2);
+        assert_eq!(options.volumes.len(), 1);
+        assert!(options.auto_remove);
+    }
+
+    #[test]
+    fn test_validate_template_rejects_bad_names_and_options() {
+        assert!(validate_template("web", &template_options()).is_ok());
+        assert!(validate_template("", &template_options()).is_err());
+        assert!(validate_template("my web", &template_options()).is_err());
+
+        let detached = BoxOptions {
+            detach: true,
+            ..template_options()
+        };
+        assert!(matches!(
+            validate_template("web", &detached),
+            Err(BoxliteError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_template_specs_rejects_duplicates() {
+        let spec = TemplateSpec {
+            name: "web".to_string(),
+            options: template_options(),
+        };
+        assert!(validate_template_specs(std::slice::from_ref(&spec)).is_ok());
+        assert!(validate_template_specs(&[spec.clone(), spec]).is_err());
+    }
+}
@@ -0,0 +1,284 @@
+//! Trash (soft delete) for removed boxes.
+//!
+//! Enabled by `BoxliteOptions::trash_retention`. `remove()` then moves the
+//! box home to `~/.boxlite/trash/{box_id}` and the DB records to the
+//! `box_trash` table instead of deleting them. A background thread purges
+//! entries older than the retention.
+//!
+//! Trashed boxes keep their ID but release their name and lock, so a new box
+//! can take the name right away; restoring fails if the name is taken.
+//!
+//! A box whose disks back another box, live or trashed (a COW clone or a
+//! snapshot branch), can be neither trashed nor purged until that box is gone.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use chrono::Utc;
+
+use crate::db::TrashStore;
+use crate::db::trash::{TrashRecord, TrashedBox};
+use crate::litebox::LiteBox;
+use crate::runtime::types::BoxID;
+
+use super::rt_impl::RuntimeImpl;
+
+/// How often the background sweep looks for expired entries.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Granularity at which the sweep thread notices shutdown.
+const SWEEP_POLL: Duration = Duration::from_secs(1);
+
+impl RuntimeImpl {
+    pub(super) fn trash_store(&self) -> TrashStore {
+        TrashStore::new(self.box_manager.db())
+    }
+
+    /// Move a box to the trash (the `remove()` path when a retention is set).
+    pub(crate) fn trash_box(&self, id: &BoxID, force: bool) -> BoxliteResult<()> {
+        let Some((config, mut state)) = self.box_manager.box_by_id(id)? else {
+            // Not persisted yet: nothing worth keeping
+            return self.remove_box(id, force);
+        };
+
+        // Clones back onto the disks by absolute path, so moving them would
+        // break those boxes
+        self.ensure_no_dependents(&config, &[config.box_home.clone()])?;
+        self.stop_for_removal(id, &mut state, force)?;
+
+        let trash_dir = self.layout.trash_dir().join(id.as_str());
+        if trash_dir.exists() {
+            // Leftover from an interrupted trash or purge
+            std::fs::remove_dir_all(&trash_dir).map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to clear stale trash directory {}: {}",
+                    trash_dir.display(),
+                    e
+                ))
+            })?;
+        }
+        if config.box_home.exists() {
+            std::fs::rename(&config.box_home, &trash_dir).map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to move {} to trash: {}",
+                    config.box_home.display(),
+                    e
+                ))
+            })?;
+        }
+
+        // The lock is re-allocated on restore
+        let lock_id = state.lock_id.take();
+        if let Err(e) = self
+            .trash_store()
+            .trash(&config, &state, &trash_dir, Utc::now())
+        {
+            if trash_dir.exists() {
+                let _ = std::fs::rename(&trash_dir, &config.box_home);
+            }
+            return Err(e);
+        }
+        if let Some(lock_id) = lock_id {
+            self.free_box_lock(id, lock_id);
+        }
+
+        self.invalidate_box_impl(id, config.name.as_deref());
+
+        tracing::info!(
+            box_id = %id,
+            trash_dir = %trash_dir.display(),
+            "Moved box to trash"
+        );
+        Ok(())
+    }
+
+    /// List trashed boxes, most recently trashed first.
+    pub(crate) fn list_trashed(&self) -> BoxliteResult<Vec<TrashedBox>> {
+        Ok(self
+            .trash_store()
+            .list()?
+            .iter()
+            .map(|record| record.summary(self.trash_retention))
+            .collect())
+    }
+
+    /// Move a trashed box back under its original ID, name and home.
+    pub(crate) async fn restore_trashed(
+        self: &Arc<Self>,
+        id_or_name: &str,
+    ) -> BoxliteResult<LiteBox> {
+        let record = self.resolve_trashed(id_or_name)?;
+        let config = &record.config;
+
+        if let Some(name) = &config.name
+            && let Some((live, _)) = self.box_manager.lookup_box(name)?
+            && live.name.as_deref() == Some(name.as_str())
+        {
+            return Err(BoxliteError::AlreadyExists(format!(
+                "cannot restore box {}: name '{}' is now used by box {}",
+                config.id, name, live.id
+            )));
+        }
+        if config.box_home.exists() {
+            return Err(BoxliteError::InvalidState(format!(
+                "cannot restore box {}: {} already exists",
+                config.id,
+                config.box_home.display()
+            )));
+        }
+
+        if record.trash_dir.exists() {
+            std::fs::rename(&record.trash_dir, &config.box_home).map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to move {} out of trash: {}",
+                    record.trash_dir.display(),
+                    e
+                ))
+            })?;
+        }
+
+        let mut state = record.state.clone();
+        let lock_id = self.lock_manager.allocate()?;
+        state.set_lock_id(lock_id);
+
+        if let Err(e) = self.trash_store().restore(&record, &state) {
+            let _ = self.lock_manager.free(lock_id);
+            if config.box_home.exists() {
+                let _ = std::fs::rename(&config.box_home, &record.trash_dir);
+            }
+            return Err(e);
+        }
+
+        tracing::info!(box_id = %config.id, "Restored box from trash");
+
+        self.get(config.id.as_str())
+            .await?
+            .ok_or_else(|| BoxliteError::Internal(format!("restored box {} not found", config.id)))
+    }
+
+    /// Permanently delete a trashed box.
+    pub(crate) fn purge_trashed(&self, id_or_name: &str) -> BoxliteResult<()> {
+        let record = self.resolve_trashed(id_or_name)?;
+        self.purge_record(&record)
+    }
+
+    /// Purge entries older than the retention. Returns how many were purged.
+    pub(crate) fn sweep_trash(&self) -> BoxliteResult<usize> {
+        let Some(retention) = self
+            .trash_retention
+            .and_then(|r| chrono::Duration::from_std(r).ok())
+        else {
+            return Ok(0);
+        };
+
+        let now = Utc::now();
+        let mut purged = 0;
+        for record in self.trash_store().list()? {
+            if record.trashed_at + retention > now {
+                continue;
+            }
+            match self.purge_record(&record) {
+                Ok(()) => purged += 1,
+                Err(e) => tracing::warn!(
+                    box_id = %record.config.id,
+                    error = %e,
+                    "Failed to purge expired trash entry"
+                ),
+            }
+        }
+        Ok(purged)
+    }
+
+    /// Run `sweep_trash` now and then every [`SWEEP_INTERVAL`] until shutdown.
+    pub(crate) fn spawn_trash_sweeper(self: &Arc<Self>) {
+        let runtime = Arc::downgrade(self);
+        let shutdown = self.shutdown_token.clone();
+
+        let spawned = std::thread::Builder::new()
+            .name("boxlite-trash-sweep".to_string())
+            .spawn(move || {
+                'sweep: loop {
+                    // Hold a strong reference only while sweeping so the
+                    // thread never keeps the runtime alive
+                    let Some(rt) = runtime.upgrade() else { break };
+                    match rt.sweep_trash() {
+                        Ok(0) => {}
+                        Ok(purged) => tracing::info!(purged, "Purged expired trash entries"),
+                        Err(e) => tracing::warn!(error = %e, "Trash sweep failed"),
+                    }
+                    drop(rt);
+
+                    let mut waited = Duration::ZERO;
+                    while waited < SWEEP_INTERVAL {
+                        if shutdown.is_cancelled() || runtime.strong_count() == 0 {
+                            break 'sweep;
+                        }
+                        std::thread::sleep(SWEEP_POLL);
+                        waited += SWEEP_POLL;
+                    }
+                }
+            });
+
+        if let Err(e) = spawned {
+            tracing::warn!(error = %e, "Failed to start trash sweeper");
+        }
+    }
+
+    fn purge_record(&self, record: &TrashRecord) -> BoxliteResult<()> {
+        // Disks still name the home the box was moved from
+        self.ensure_no_dependents(
+            &record.config,
+            &[record.config.box_home.clone(), record.trash_dir.clone()],
+        )?;
+        if record.trash_dir.exists() {
+            std::fs::remove_dir_all(&record.trash_dir).map_err(|e| {
+                BoxliteError::Storage(format!(
+                    "Failed to delete {}: {}",
+                    record.trash_dir.display(),
+                    e
+                ))
+            })?;
+        }
+        self.trash_store().remove(record.config.id.as_str())?;
+
+        tracing::info!(box_id = %record.config.id, "Purged box from trash");
+        Ok(())
+    }
+
+    /// Find a trashed box by exact ID, exact name or unique ID prefix.
+    fn resolve_trashed(&self, id_or_name: &str) -> BoxliteResult<TrashRecord> {
+        if let Some(record) = self.trash_store().get(id_or_name)? {
+            return Ok(record);
+        }
+
+        let all = self.trash_store().list()?;
+        let by_name: Vec<_> = all
+            .iter()
+            .filter(|r| r.config.name.as_deref() == Some(id_or_name))
+            .collect();
+        let matches = if by_name.is_empty() {
+            all.iter()
+                .filter(|r| r.config.id.starts_with(id_or_name))
+                .collect()
+        } else {
+            by_name
+        };
+
+        match matches.as_slice() {
+            [] => Err(BoxliteError::NotFound(format!(
+                "no trashed box matches '{}'",
+                id_or_name
+            ))),
+            [record] => Ok((*record).clone()),
+            _ => Err(BoxliteError::InvalidArgument(format!(
+                "multiple trashed boxes match '{}': {:?}",
+                id_or_name,
+                matches
+                    .iter()
+                    .map(|r| r.config.id.as_str())
+                    .collect::<Vec<_>>()
+            ))),
+        }
+    }
+}
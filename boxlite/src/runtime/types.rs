@@ -1,14 +1,16 @@
 //! Core data types for box lifecycle management.
 
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use chrono::{DateTime, Utc};
 use rand::RngCore;
 use rusqlite::ToSql;
 use rusqlite::types::{ToSqlOutput, ValueRef};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::hash::Hash;
+use std::time::Duration;
 
 // Re-export status types from litebox module
 pub use crate::litebox::{BoxState, BoxStatus};
@@ -406,17 +408,101 @@ pub struct BoxInfo {
     /// Process ID of the VMM subprocess (None if not running).
     pub pid: Option<u32>,
 
+    /// Exit code of the last run while the box is Exited: 0 after `stop()`,
+    /// the shim's code after a crash. None when unknown or not Exited.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+
     /// Image reference or rootfs path.
     pub image: String,
 
+    /// Digest the image reference is pinned to (`name@sha256:...`), if any.
+    ///
+    /// The box runs exactly this content: pulls verify it and fail otherwise.
+    #[serde(default)]
+    pub image_digest: Option<String>,
+
     /// Allocated CPU count.
     pub cpus: u8,
 
     /// Allocated memory in MiB.
     pub memory_mib: u32,
 
+    /// Swap inside the VM in MiB (0 = none).
+    #[serde(default)]
+    pub swap_mib: u32,
+
     /// User-defined labels for filtering and organization.
     pub labels: HashMap<String, String>,
+
+    /// Guest network identity (None until the box is first started).
+    pub network: Option<crate::net::BoxNetwork>,
+
+    /// Network access the box was created with.
+    #[serde(default)]
+    pub network_mode: crate::runtime::options::NetworkMode,
+
+    /// Extra `/etc/hosts` entries last written into the guest: the host
+    /// and, with `BoxliteOptions::box_dns`, other boxes' names.
+    #[serde(default)]
+    pub hosts: Vec<crate::net::HostEntry>,
+
+    /// Configured time zone (`host` or an IANA name; None = the image's).
+    #[serde(default)]
+    pub timezone: Option<String>,
+
+    /// Configured locale (None = the image's).
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// Whether the container's root filesystem is mounted read-only.
+    #[serde(default)]
+    pub read_only_rootfs: bool,
+
+    /// Container paths backed by a writable tmpfs.
+    #[serde(default)]
+    pub writable_paths: Vec<String>,
+
+    /// How long the box gets to exit after SIGTERM before it is killed.
+    #[serde(default = "default_shutdown_grace_period")]
+    pub shutdown_grace_period: Duration,
+
+    /// Idle time after which the box is stopped, if any.
+    #[serde(default)]
+    pub auto_stop_after_idle: Option<Duration>,
+
+    /// Idle time left before the box is stopped; set while a box with
+    /// `auto_stop_after_idle` is running.
+    #[serde(default)]
+    pub idle_remaining: Option<Duration>,
+
+    /// Resource limits currently configured for the box.
+    #[serde(default)]
+    pub resource_limits: crate::runtime::advanced_options::ResourceLimits,
+
+    /// Isolation the box runs without because the host lacked a capability.
+    #[serde(default)]
+    pub degradations: Vec<crate::runtime::capabilities::Degradation>,
+}
+
+fn default_shutdown_grace_period() -> Duration {
+    crate::runtime::options::DEFAULT_SHUTDOWN_GRACE_PERIOD
+}
+
+/// Idle time left for a running box, counted from its start until its
+/// idle watcher records activity.
+fn idle_remaining(config: &crate::litebox::config::BoxConfig, window: Duration) -> Duration {
+    use crate::runtime::layout::{BoxFilesystemLayout, FsLayoutConfig};
+
+    let layout = BoxFilesystemLayout::new(
+        config.box_home.clone(),
+        FsLayoutConfig::without_bind_mount(),
+        false,
+    );
+    let started = std::fs::metadata(layout.pid_file_path())
+        .and_then(|m| m.modified())
+        .unwrap_or_else(|_| std::time::SystemTime::now());
+    crate::vmm::idle::remaining(&config.box_home, window, started)
 }
 
 impl BoxInfo {
@@ -431,13 +517,36 @@ impl BoxInfo {
             created_at: config.created_at,
             last_updated: state.last_updated,
             pid: state.pid,
+            exit_code: state.exit_code.filter(|_| state.status.is_stopped()),
             image: match &config.options.rootfs {
                 RootfsSpec::Image(r) => r.clone(),
                 RootfsSpec::RootfsPath(p) => format!("rootfs:{}", p),
             },
+            image_digest: config
+                .options
+                .rootfs
+                .image()
+                .and_then(crate::images::pinned_digest),
             cpus: config.options.cpus.unwrap_or(2),
             memory_mib: config.options.memory_mib.unwrap_or(512),
-            labels: HashMap::new(),
+            swap_mib: config.options.swap_mib.unwrap_or(0),
+            labels: config.options.labels.clone(),
+            network: state.network.clone(),
+            network_mode: config.options.network,
+            hosts: state.hosts.clone(),
+            timezone: config.options.timezone.clone(),
+            locale: config.options.locale.clone(),
+            read_only_rootfs: config.options.read_only_rootfs,
+            writable_paths: config.options.writable_paths.clone(),
+            shutdown_grace_period: config.options.effective_shutdown_grace_period(),
+            auto_stop_after_idle: config.options.auto_stop_after_idle,
+            idle_remaining: config
+                .options
+                .auto_stop_after_idle
+                .filter(|_| state.status.is_running())
+                .map(|window| idle_remaining(config, window)),
+            resource_limits: config.options.advanced.security.resource_limits.clone(),
+            degradations: config.degradations.clone(),
         }
     }
 }
@@ -455,6 +564,135 @@ impl PartialEq for BoxInfo {
     }
 }
 
+/// What removing a box would do, from `BoxliteRuntime::plan_remove`.
+///
+/// Planning changes nothing: it validates the removal the same way and
+/// reports its effects.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RemovePlan {
+    /// Box that would be removed.
+    pub id: BoxID,
+    /// Its name, if any.
+    pub name: Option<String>,
+    /// Its current status.
+    pub status: BoxStatus,
+    /// The box is active and would be killed first (only with `force`).
+    pub stops_box: bool,
+    /// The box would be moved to the trash instead of deleted.
+    pub to_trash: bool,
+    /// Bytes the box's directory (disks, snapshots, logs) occupies on the host.
+    pub disk_bytes: u64,
+    /// Bytes freed by the removal: `disk_bytes`, or 0 when moved to the trash.
+    pub reclaimed_bytes: u64,
+}
+
+/// Filters for `BoxliteRuntime::list_info_with()`.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// Include idle warm pool boxes (hidden by default).
+    pub include_warm_pool: bool,
+}
+
+/// Selects boxes for bulk operations such as `BoxliteRuntime::stop_matching()`.
+///
+/// Every condition set must hold; an empty filter matches every box.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ListFilter {
+    /// Labels the box must carry: `Some(value)` requires that value,
+    /// `None` only the key.
+    pub labels: BTreeMap<String, Option<String>>,
+    /// Status the box must be in.
+    pub status: Option<BoxStatus>,
+    /// Name the box must have.
+    pub name: Option<String>,
+}
+
+impl ListFilter {
+    /// Filter matching every box.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require label `key` with `value`.
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), Some(value.into()));
+        self
+    }
+
+    /// Require label `key` with any value.
+    pub fn has_label(mut self, key: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), None);
+        self
+    }
+
+    /// Require `status`.
+    pub fn status(mut self, status: BoxStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    /// Require the box to be named `name`.
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Build a filter from `key=value` expressions, as taken by the CLI's
+    /// `--filter`: `label=<key>`, `label=<key>=<value>`, `status=<status>`
+    /// and `name=<name>`.
+    pub fn from_exprs<S: AsRef<str>>(exprs: &[S]) -> BoxliteResult<Self> {
+        let mut filter = Self::new();
+        for expr in exprs {
+            let expr = expr.as_ref();
+            let invalid = |reason: &str| {
+                BoxliteError::InvalidArgument(format!("Invalid filter '{}': {}", expr, reason))
+            };
+            let (field, value) = expr
+                .split_once('=')
+                .ok_or_else(|| invalid("expected <field>=<value>"))?;
+            if value.is_empty() {
+                return Err(invalid("empty value"));
+            }
+            filter = match field {
+                "label" => match value.split_once('=') {
+                    Some((key, value)) => filter.label(key, value),
+                    None => filter.has_label(value),
+                },
+                "status" => filter.status(value.parse().map_err(|_| invalid("unknown status"))?),
+                "name" => filter.name(value),
+                _ => return Err(invalid("field must be label, status or name")),
+            };
+        }
+        Ok(filter)
+    }
+
+    /// Whether `info` satisfies every condition.
+    pub fn matches(&self, info: &BoxInfo) -> bool {
+        self.status.is_none_or(|status| info.status == status)
+            && self.matches_identity(info.name.as_deref(), &info.labels)
+    }
+
+    /// Whether a box with `name` and `labels` satisfies the name and label
+    /// conditions (the status is not checked).
+    pub(crate) fn matches_identity(
+        &self,
+        name: Option<&str>,
+        labels: &HashMap<String, String>,
+    ) -> bool {
+        self.name
+            .as_deref()
+            .is_none_or(|expected| name == Some(expected))
+            && self
+                .labels
+                .iter()
+                .all(|(key, value)| match (labels.get(key), value) {
+                    (Some(actual), Some(expected)) => actual == expected,
+                    (Some(_), None) => true,
+                    (None, _) => false,
+                })
+    }
+}
+
 // ============================================================================
 // BOX STATE INFO (Docker-like State object)
 // ============================================================================
@@ -473,6 +711,10 @@ pub struct BoxStateInfo {
 
     /// Process ID of the VMM subprocess (None if not running).
     pub pid: Option<u32>,
+
+    /// Exit code of the last run while the box is Exited.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
 }
 
 impl BoxStateInfo {
@@ -482,6 +724,7 @@ impl BoxStateInfo {
             status: state.status,
             running: state.status.is_running(),
             pid: state.pid,
+            exit_code: state.exit_code.filter(|_| state.status.is_stopped()),
         }
     }
 }
@@ -494,6 +737,7 @@ impl From<&BoxInfo> for BoxStateInfo {
             status: info.status,
             running: info.status.is_running(),
             pid: info.pid,
+            exit_code: info.exit_code,
         }
     }
 }
@@ -617,6 +861,9 @@ mod tests {
             transport: Transport::unix(PathBuf::from("/tmp/boxlite.sock")),
             box_home: PathBuf::from("/tmp/box"),
             ready_socket_path: PathBuf::from("/tmp/ready.sock"),
+            disk_driver: Default::default(),
+            lineage: None,
+            degradations: Vec::new(),
         };
 
         let mut state = BoxState::new();
@@ -630,8 +877,100 @@ mod tests {
         assert_eq!(info.created_at, config.created_at);
         assert_eq!(info.pid, state.pid);
         assert_eq!(info.image, "python:3.11");
+        assert_eq!(info.image_digest, None);
         assert_eq!(info.cpus, 4);
         assert_eq!(info.memory_mib, 1024);
+
+        let digest = "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+        let mut pinned = config.clone();
+        pinned.options.rootfs = RootfsSpec::Image(format!("python:3.11@{digest}"));
+        let info = BoxInfo::new(&pinned, &state);
+        assert_eq!(info.image, format!("python:3.11@{digest}"));
+        assert_eq!(info.image_digest.as_deref(), Some(digest));
+
+        // The exit code shows only while the box is exited
+        state.mark_exited(Some(137));
+        assert_eq!(BoxInfo::new(&config, &state).exit_code, Some(137));
+        state.set_status(BoxStatus::Running);
+        assert_eq!(BoxInfo::new(&config, &state).exit_code, None);
+    }
+
+    #[test]
+    fn test_list_filter_matches() {
+        let mut info = BoxInfo {
+            id: BoxID::new(),
+            name: Some("web-1".to_string()),
+            status: BoxStatus::Running,
+            created_at: Utc::now(),
+            last_updated: Utc::now(),
+            pid: None,
+            exit_code: None,
+            image: "alpine".to_string(),
+            image_digest: None,
+            cpus: 1,
+            memory_mib: 512,
+            swap_mib: 0,
+            labels: HashMap::new(),
+            network: None,
+            network_mode: Default::default(),
+            hosts: Vec::new(),
+            timezone: None,
+            locale: None,
+            read_only_rootfs: false,
+            writable_paths: Vec::new(),
+            shutdown_grace_period: Default::default(),
+            auto_stop_after_idle: None,
+            idle_remaining: None,
+            resource_limits: Default::default(),
+            degradations: Vec::new(),
+        };
+        info.labels.insert("tier".into(), "web".into());
+        info.labels.insert("tenant".into(), "acme".into());
+
+        assert!(ListFilter::new().matches(&info));
+        assert!(ListFilter::new().label("tier", "web").matches(&info));
+        assert!(ListFilter::new().has_label("tenant").matches(&info));
+        assert!(!ListFilter::new().label("tier", "db").matches(&info));
+        assert!(!ListFilter::new().has_label("zone").matches(&info));
+        assert!(
+            ListFilter::new()
+                .label("tier", "web")
+                .status(BoxStatus::Running)
+                .name("web-1")
+                .matches(&info)
+        );
+        assert!(
+            !ListFilter::new()
+                .label("tier", "web")
+                .status(BoxStatus::Exited)
+                .matches(&info)
+        );
+        assert!(!ListFilter::new().name("web-2").matches(&info));
+    }
+
+    #[test]
+    fn test_list_filter_from_exprs() {
+        let filter =
+            ListFilter::from_exprs(&["label=tier=web", "label=tenant", "status=running"]).unwrap();
+        assert_eq!(
+            filter,
+            ListFilter::new()
+                .label("tier", "web")
+                .has_label("tenant")
+                .status(BoxStatus::Running)
+        );
+        // Only the first '=' after the key splits, so values may contain '='
+        let filter = ListFilter::from_exprs(&["label=args=a=b", "name=web-1"]).unwrap();
+        assert_eq!(filter.labels["args"], Some("a=b".to_string()));
+        assert_eq!(filter.name.as_deref(), Some("web-1"));
+
+        for bad in ["tier", "label=", "status=sleepy", "image=alpine"] {
+            let err = ListFilter::from_exprs(&[bad]).unwrap_err();
+            assert!(
+                matches!(err, BoxliteError::InvalidArgument(_)),
+                "{bad}: {err}"
+            );
+        }
     }
 
     #[test]
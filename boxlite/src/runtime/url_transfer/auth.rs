@@ -0,0 +1,180 @@
+//! Credentials for archive transfers.
+
+use std::fmt;
+
+use async_trait::async_trait;
+use boxlite_shared::errors::BoxliteResult;
+use url::Url;
+
+/// Environment variable holding a bearer token for `https://` transfers.
+pub const TOKEN_ENV: &str = "BOXLITE_TRANSFER_TOKEN";
+
+/// Provides the credentials of a transfer.
+///
+/// Called once per upload or download, so a provider can hand out
+/// short-lived tokens.
+#[async_trait]
+pub trait TransferAuth: Send + Sync {
+    /// Credentials for a transfer to or from `url`.
+    async fn credentials(&self, url: &Url) -> BoxliteResult<TransferCredentials>;
+}
+
+/// Credentials of one transfer.
+#[derive(Clone, Default)]
+pub struct TransferCredentials {
+    /// HTTP headers added to every request, e.g. `Authorization`.
+    pub headers: Vec<(String, String)>,
+    /// Signing credentials for `s3://` URLs; `s3://` transfers without them
+    /// are sent unsigned, for public buckets.
+    pub aws: Option<AwsCredentials>,
+}
+
+impl fmt::Debug for TransferCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header_names: Vec<_> = self.headers.iter().map(|(name, _)| name).collect();
+        f.debug_struct("TransferCredentials")
+            .field("headers", &header_names)
+            .field("aws", &self.aws)
+            .finish()
+    }
+}
+
+/// AWS credentials and region for `s3://` URLs.
+#[derive(Clone)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Session token of temporary credentials.
+    pub session_token: Option<String>,
+    /// Region of the bucket, e.g. `eu-west-1`.
+    pub region: String,
+    /// S3-compatible endpoint to use instead of AWS, e.g.
+    /// `https://minio.internal:9000`. Buckets are addressed by path there.
+    pub endpoint: Option<String>,
+}
+
+impl fmt::Debug for AwsCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AwsCredentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("region", &self.region)
+            .field("endpoint", &self.endpoint)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Credentials from the environment: a bearer token from
+/// `BOXLITE_TRANSFER_TOKEN` for `https://` URLs, and the usual
+/// `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY`, `AWS_SESSION_TOKEN`,
+/// `AWS_REGION` and `AWS_ENDPOINT_URL` for `s3://` URLs.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvAuth;
+
+#[async_trait]
+impl TransferAuth for EnvAuth {
+    async fn credentials(&self, url: &Url) -> BoxliteResult<TransferCredentials> {
+        Ok(env_credentials(url, |name| std::env::var(name).ok()))
+    }
+}
+
+fn env_credentials(url: &Url, var: impl Fn(&str) -> Option<String>) -> TransferCredentials {
+    if url.scheme() == "s3" {
+        let aws = var("AWS_ACCESS_KEY_ID")
+            .zip(var("AWS_SECRET_ACCESS_KEY"))
+            .map(|(access_key_id, secret_access_key)| AwsCredentials {
+                access_key_id,
+                secret_access_key,
+                session_token: var("AWS_SESSION_TOKEN"),
+                region: var("AWS_REGION")
+                    .or_else(|| var("AWS_DEFAULT_REGION"))
+                    .unwrap_or_else(|| "us-east-1".to_string()),
+                endpoint: var("AWS_ENDPOINT_URL"),
+            });
+        return TransferCredentials {
+            headers: Vec::new(),
+            aws,
+        };
+    }
+
+    let headers = var(TOKEN_ENV)
+        .map(|token| vec![("Authorization".to_string(), format!("Bearer {}", token))])
+        .unwrap_or_default();
+    TransferCredentials { headers, aws: None }
+}
+
+/// The same credentials for every transfer.
+#[derive(Debug, Clone, Default)]
+pub struct StaticAuth(pub TransferCredentials);
+
+impl StaticAuth {
+    /// `Authorization: Bearer <token>` on every request.
+    pub fn bearer(token: impl fmt::Display) -> Self {
+        Self(TransferCredentials {
+            headers: vec![("Authorization".to_string(), format!("Bearer {}", token))],
+            aws: None,
+        })
+    }
+
+    /// Sign `s3://` requests with `aws`.
+    pub fn aws(aws: AwsCredentials) -> Self {
+        Self(TransferCredentials {
+            headers: Vec::new(),
+            aws: Some(aws),
+        })
+    }
+}
+
+#[async_trait]
+impl TransferAuth for StaticAuth {
+    async fn credentials(&self, _url: &Url) -> BoxliteResult<TransferCredentials> {
+        Ok(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn from_env(url: &str, vars: &[(&str, &str)]) -> TransferCredentials {
+        let vars: HashMap<_, _> = vars.iter().copied().collect();
+        env_credentials(&Url::parse(url).unwrap(), |name| {
+            vars.get(name).map(|v| v.to_string())
+        })
+    }
+
+    #[test]
+    fn test_env_bearer_token() {
+        let creds = from_env("https://example.com/a.boxsnap", &[(TOKEN_ENV, "t0k")]);
+        assert_eq!(
+            creds.headers,
+            vec![("Authorization".to_string(), "Bearer t0k".to_string())]
+        );
+        assert!(creds.aws.is_none());
+
+        let creds = from_env("https://example.com/a.boxsnap", &[]);
+        assert!(creds.headers.is_empty());
+    }
+
+    #[test]
+    fn test_env_aws_credentials() {
+        let vars = [
+            ("AWS_ACCESS_KEY_ID", "AKID"),
+            ("AWS_SECRET_ACCESS_KEY", "secret"),
+            ("AWS_DEFAULT_REGION", "eu-west-1"),
+            (TOKEN_ENV, "ignored"),
+        ];
+        let creds = from_env("s3://bucket/a.boxsnap", &vars);
+        let aws = creds.aws.unwrap();
+        assert_eq!(aws.access_key_id, "AKID");
+        assert_eq!(aws.region, "eu-west-1");
+        assert_eq!(aws.session_token, None);
+        assert!(creds.headers.is_empty());
+
+        // The secret never shows up in logs
+        assert!(!format!("{:?}", aws).contains("secret"));
+
+        let creds = from_env("s3://bucket/a.boxsnap", &[("AWS_ACCESS_KEY_ID", "AKID")]);
+        assert!(creds.aws.is_none());
+    }
+}
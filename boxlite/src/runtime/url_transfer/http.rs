@@ -0,0 +1,218 @@
+//! Archive uploads and downloads over HTTP.
+
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::time::Duration;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use futures::Stream;
+use reqwest::header::{ACCEPT_RANGES, CONTENT_LENGTH, RANGE};
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use url::Url;
+
+use super::{TransferCredentials, TransferOptions, TransferProgress};
+
+/// Times a download is resumed after the connection drops.
+const MAX_RESUMES: u32 = 5;
+
+/// Delay before the first resume; doubles with every further one.
+const RESUME_BACKOFF: Duration = Duration::from_secs(1);
+
+/// Bytes of an error response kept for the error message.
+const ERROR_BODY_LIMIT: usize = 512;
+
+/// A URL and the headers every request to it carries.
+#[derive(Debug, Clone)]
+pub(super) struct Request {
+    pub(super) url: Url,
+    pub(super) headers: Vec<(String, String)>,
+}
+
+impl Request {
+    pub(super) fn new(
+        url: Url,
+        headers: &[(String, String)],
+        credentials: &TransferCredentials,
+    ) -> Self {
+        let headers = headers
+            .iter()
+            .chain(&credentials.headers)
+            .cloned()
+            .collect();
+        Self { url, headers }
+    }
+
+    fn apply(&self, mut builder: RequestBuilder) -> RequestBuilder {
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+        builder
+    }
+}
+
+fn client() -> BoxliteResult<Client> {
+    // No overall timeout: archives take as long as they take
+    Client::builder()
+        .connect_timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| BoxliteError::Config(format!("failed to create HTTP client: {}", e)))
+}
+
+/// Why a download attempt ended early.
+enum Interrupted {
+    /// The connection dropped; `resumable` if the server takes ranges.
+    Connection {
+        error: String,
+        resumable: bool,
+    },
+    Failed(BoxliteError),
+}
+
+impl From<BoxliteError> for Interrupted {
+    fn from(error: BoxliteError) -> Self {
+        Self::Failed(error)
+    }
+}
+
+impl From<std::io::Error> for Interrupted {
+    fn from(error: std::io::Error) -> Self {
+        Self::Failed(error.into())
+    }
+}
+
+/// Download `request` into `path`, continuing from the bytes already there
+/// and resuming up to [`MAX_RESUMES`] times if the connection drops.
+pub(super) async fn download(
+    request: &Request,
+    path: &Path,
+    options: &TransferOptions,
+) -> BoxliteResult<()> {
+    let client = client()?;
+    let mut resumes = 0;
+    loop {
+        let error = match download_once(&client, request, path, options).await {
+            Ok(()) => return Ok(()),
+            Err(Interrupted::Failed(e)) => return Err(e),
+            Err(Interrupted::Connection { error, resumable }) => {
+                if !resumable || resumes == MAX_RESUMES {
+                    return Err(BoxliteError::Network(format!(
+                        "download of {} failed: {}",
+                        request.url, error
+                    )));
+                }
+                error
+            }
+        };
+        let delay = RESUME_BACKOFF * 2u32.pow(resumes);
+        resumes += 1;
+        tracing::warn!(
+            url = %request.url,
+            error = %error,
+            attempt = resumes,
+            "Download interrupted, resuming in {:?}",
+            delay
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn download_once(
+    client: &Client,
+    request: &Request,
+    path: &Path,
+    options: &TransferOptions,
+) -> Result<(), Interrupted> {
+    let offset = std::fs::metadata(path).map_or(0, |m| m.len());
+    let mut builder = request.apply(client.get(request.url.clone()));
+    if offset > 0 {
+        builder = builder.header(RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = builder.send().await.map_err(|e| Interrupted::Connection {
+        error: e.to_string(),
+        // Nothing was received; asking again is safe either way
+        resumable: true,
+    })?;
+
+    let status = response.status();
+    let (mut file, mut done) = match status {
+        StatusCode::PARTIAL_CONTENT => (OpenOptions::new().append(true).open(path)?, offset),
+        // The earlier attempt got everything
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+        status if status.is_success() => (std::fs::File::create(path)?, 0),
+        _ => return Err(status_error(&request.url, response).await.into()),
+    };
+    let resumable = status == StatusCode::PARTIAL_CONTENT
+        || response
+            .headers()
+            .get(ACCEPT_RANGES)
+            .is_some_and(|v| v.as_bytes() == b"bytes");
+    let total = response.content_length().map(|len| done + len);
+    options.report(TransferProgress {
+        bytes_done: done,
+        bytes_total: total,
+    });
+
+    loop {
+        let chunk = response
+            .chunk()
+            .await
+            .map_err(|e| Interrupted::Connection {
+                error: e.to_string(),
+                resumable,
+            })?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        file.write_all(&chunk)?;
+        done += chunk.len() as u64;
+        options.report(TransferProgress {
+            bytes_done: done,
+            bytes_total: total,
+        });
+    }
+    file.flush()?;
+
+    match total {
+        Some(total) if done < total => Err(Interrupted::Connection {
+            error: format!("connection closed after {} of {} bytes", done, total),
+            resumable,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Upload `body` to `request` with a PUT, `len` bytes in all when known
+/// (sent chunked otherwise).
+pub(super) async fn upload<S>(request: &Request, body: S, len: Option<u64>) -> BoxliteResult<()>
+where
+    S: Stream<Item = std::io::Result<Vec<u8>>> + Send + 'static,
+{
+    let client = client()?;
+    let mut builder = request
+        .apply(client.put(request.url.clone()))
+        .body(reqwest::Body::wrap_stream(body));
+    if let Some(len) = len {
+        builder = builder.header(CONTENT_LENGTH, len);
+    }
+    let response = builder
+        .send()
+        .await
+        .map_err(|e| BoxliteError::Network(format!("upload to {} failed: {}", request.url, e)))?;
+    if !response.status().is_success() {
+        return Err(status_error(&request.url, response).await);
+    }
+    Ok(())
+}
+
+/// Error for a response with a failure status, with the start of its body.
+async fn status_error(url: &Url, response: Response) -> BoxliteError {
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    let body: String = body.chars().take(ERROR_BODY_LIMIT).collect();
+    let message = format!("{} returned {}: {}", url, status, body.trim());
+    match status {
+        StatusCode::NOT_FOUND => BoxliteError::NotFound(message),
+        _ => BoxliteError::Network(message),
+    }
+}
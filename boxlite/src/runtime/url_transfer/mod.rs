@@ -0,0 +1,525 @@
+//! Box archives exported to and imported from URLs.
+//!
+//! `https://` (and `http://`) archives are uploaded with a PUT and
+//! downloaded with a GET. With the `s3` feature, `s3://bucket/key` archives
+//! go through the S3 API with SigV4-signed requests. Credentials come from a
+//! [`TransferAuth`] provider, [`EnvAuth`] unless the caller gives another.
+//!
+//! Uploads stream the archive while it is being built, except to S3, which
+//! needs the length up front and gets a staged copy. Downloads land in the
+//! runtime's temp directory, resume with a `Range` request when the server
+//! supports it, and are checked against the caller's expected digest
+//! before the import reads them.
+
+mod auth;
+mod http;
+#[cfg(feature = "s3")]
+mod s3;
+
+use std::fmt;
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use sha2::{Digest, Sha256};
+use tokio::sync::mpsc;
+use url::Url;
+
+use crate::litebox::LiteBox;
+use crate::runtime::portability::ImportOptions;
+use crate::runtime::rt_impl::RuntimeImpl;
+
+pub use auth::{AwsCredentials, EnvAuth, StaticAuth, TransferAuth, TransferCredentials};
+
+/// Size of the chunks an archive is streamed in.
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Chunks buffered between the archive writer and the upload.
+const PIPE_CHUNKS: usize = 8;
+
+/// Directory under the runtime temp dir holding partial downloads.
+const DOWNLOADS_DIR: &str = "downloads";
+
+/// Progress of an archive upload or download.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// Bytes sent or received so far, including those of an earlier,
+    /// interrupted download that was resumed
+    pub bytes_done: u64,
+    /// Size of the archive, when known
+    pub bytes_total: Option<u64>,
+}
+
+/// Callback for [`TransferProgress`]. Called from runtime tasks as each
+/// chunk goes out or comes in, so it should return quickly.
+#[derive(Clone)]
+pub struct TransferObserver(Arc<dyn Fn(&TransferProgress) + Send + Sync>);
+
+impl TransferObserver {
+    pub fn new(observer: impl Fn(&TransferProgress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(observer))
+    }
+
+    pub(crate) fn report(&self, progress: &TransferProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for TransferObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TransferObserver")
+    }
+}
+
+/// Options for [`LiteBox::export_to_url`](crate::LiteBox::export_to_url)
+/// and [`BoxliteRuntime::import_from_url`](crate::BoxliteRuntime::import_from_url).
+#[derive(Clone, Default)]
+pub struct TransferOptions {
+    /// Extra HTTP headers sent with every request (e.g. `x-amz-acl` or a
+    /// `Content-Type` the storage expects).
+    pub headers: Vec<(String, String)>,
+    /// Credentials provider (default: [`EnvAuth`]).
+    pub auth: Option<Arc<dyn TransferAuth>>,
+    /// Expected SHA-256 of a downloaded archive, as `sha256:<hex>` or bare
+    /// hex. The import fails if the archive doesn't match.
+    pub expected_digest: Option<String>,
+    /// Progress callback.
+    pub observer: Option<TransferObserver>,
+}
+
+impl TransferOptions {
+    /// Add an HTTP header sent with every request.
+    pub fn header(&mut self, name: impl Into<String>, value: impl Into<String>) -> &mut Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the credentials provider.
+    pub fn auth(&mut self, auth: Arc<dyn TransferAuth>) -> &mut Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Set the expected SHA-256 of a downloaded archive.
+    pub fn expected_digest(&mut self, digest: impl Into<String>) -> &mut Self {
+        self.expected_digest = Some(digest.into());
+        self
+    }
+
+    /// Set the progress callback.
+    pub fn observer(&mut self, observer: TransferObserver) -> &mut Self {
+        self.observer = Some(observer);
+        self
+    }
+
+    async fn credentials(&self, url: &Url) -> BoxliteResult<TransferCredentials> {
+        match &self.auth {
+            Some(auth) => auth.credentials(url).await,
+            None => EnvAuth.credentials(url).await,
+        }
+    }
+
+    fn report(&self, progress: TransferProgress) {
+        if let Some(observer) = &self.observer {
+            observer.report(&progress);
+        }
+    }
+}
+
+impl fmt::Debug for TransferOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Headers may carry credentials
+        let header_names: Vec<_> = self.headers.iter().map(|(name, _)| name).collect();
+        f.debug_struct("TransferOptions")
+            .field("headers", &header_names)
+            .field("auth", &self.auth.as_ref().map(|_| "TransferAuth"))
+            .field("expected_digest", &self.expected_digest)
+            .field("observer", &self.observer)
+            .finish()
+    }
+}
+
+impl RuntimeImpl {
+    /// Import a box from an archive at `url`.
+    ///
+    /// The archive is downloaded to the temp directory first, resuming a
+    /// partial download an earlier call left behind, and removed once the
+    /// import is done with it.
+    pub(crate) async fn import_from_url(
+        self: &Arc<Self>,
+        url: &str,
+        options: ImportOptions,
+        transfer: TransferOptions,
+    ) -> BoxliteResult<LiteBox> {
+        let url = ArchiveUrl::parse(url)?;
+        self.check_online(&url.host())?;
+        if let Some(name) = &options.name {
+            crate::validate::box_name(name)?;
+        }
+        // Fail a taken name before the potentially long download
+        self.import_target(options.name.as_deref(), options.on_conflict)?;
+
+        let archive = url.download(&self.layout.temp_dir(), &transfer).await?;
+        let result = self.import(&archive, options).await;
+        let _ = std::fs::remove_file(&archive);
+        result
+    }
+
+    /// Fail with `OfflineMode` if the runtime must not contact `host`.
+    pub(crate) fn check_online(&self, host: &str) -> BoxliteResult<()> {
+        if self.offline {
+            tracing::warn!(host = %host, "Blocked archive transfer in offline mode");
+            return Err(BoxliteError::OfflineMode(host.to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Where an archive is uploaded to or downloaded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum ArchiveUrl {
+    Http(Url),
+    #[cfg(feature = "s3")]
+    S3 {
+        url: Url,
+        bucket: String,
+        key: String,
+    },
+}
+
+impl ArchiveUrl {
+    pub(crate) fn parse(s: &str) -> BoxliteResult<Self> {
+        let url = Url::parse(s)
+            .map_err(|e| BoxliteError::InvalidArgument(format!("invalid URL '{}': {}", s, e)))?;
+        match url.scheme() {
+            "http" | "https" => Ok(Self::Http(url)),
+            #[cfg(feature = "s3")]
+            "s3" => {
+                let bucket = url.host_str().unwrap_or_default().to_string();
+                let key = url.path().trim_start_matches('/').to_string();
+                if bucket.is_empty() || key.is_empty() {
+                    return Err(BoxliteError::InvalidArgument(format!(
+                        "expected s3://BUCKET/KEY, got '{}'",
+                        s
+                    )));
+                }
+                Ok(Self::S3 { url, bucket, key })
+            }
+            #[cfg(not(feature = "s3"))]
+            "s3" => Err(BoxliteError::Unsupported(
+                "s3:// URLs need boxlite built with the `s3` feature".to_string(),
+            )),
+            scheme => Err(BoxliteError::InvalidArgument(format!(
+                "unsupported URL scheme '{}' (expected https, http or s3)",
+                scheme
+            ))),
+        }
+    }
+
+    pub(crate) fn url(&self) -> &Url {
+        match self {
+            Self::Http(url) => url,
+            #[cfg(feature = "s3")]
+            Self::S3 { url, .. } => url,
+        }
+    }
+
+    /// Host contacted for this URL, as reported in offline mode.
+    pub(crate) fn host(&self) -> String {
+        match self {
+            Self::Http(url) => url.host_str().unwrap_or_default().to_string(),
+            #[cfg(feature = "s3")]
+            Self::S3 { bucket, .. } => format!("{}.s3.amazonaws.com", bucket),
+        }
+    }
+
+    /// Whether the upload can stream a body of unknown length.
+    pub(crate) fn streams_uploads(&self) -> bool {
+        matches!(self, Self::Http(_))
+    }
+
+    /// Download the archive to a file under `temp_dir`, resuming an earlier
+    /// partial download of the same URL, and verify its digest.
+    ///
+    /// The file is kept when the download fails, for the next attempt to
+    /// resume, and removed when its digest doesn't match.
+    pub(crate) async fn download(
+        &self,
+        temp_dir: &Path,
+        options: &TransferOptions,
+    ) -> BoxliteResult<PathBuf> {
+        let expected = options
+            .expected_digest
+            .as_deref()
+            .map(parse_digest)
+            .transpose()?;
+
+        let dir = temp_dir.join(DOWNLOADS_DIR);
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{}.part", download_id(self.url())));
+
+        let credentials = options.credentials(self.url()).await?;
+        let request = match self {
+            Self::Http(url) => http::Request::new(url.clone(), &options.headers, &credentials),
+            #[cfg(feature = "s3")]
+            Self::S3 { bucket, key, .. } => {
+                s3::Request::new(bucket, key, &options.headers, &credentials)?.get()
+            }
+        };
+        http::download(&request, &path, options).await?;
+
+        if let Some(expected) = expected {
+            let actual = crate::util::checksum::sha256_file_async(path.clone()).await?;
+            if actual != expected {
+                let _ = std::fs::remove_file(&path);
+                return Err(BoxliteError::Storage(format!(
+                    "archive digest mismatch for {}: expected sha256:{}, got sha256:{}",
+                    self.url(),
+                    expected,
+                    actual
+                )));
+            }
+        }
+        Ok(path)
+    }
+
+    /// Upload the archive `write` produces into the [`UploadPipe`] it is
+    /// given, `len` bytes long when known.
+    ///
+    /// A failed upload stops `write` at its next write, and a failed
+    /// `write` aborts the upload; the error is that of whichever failed
+    /// first.
+    pub(crate) async fn upload<F>(
+        &self,
+        len: Option<u64>,
+        options: &TransferOptions,
+        write: impl FnOnce(UploadPipe) -> F,
+    ) -> BoxliteResult<()>
+    where
+        F: Future<Output = BoxliteResult<()>>,
+    {
+        let credentials = options.credentials(self.url()).await?;
+        let request = match self {
+            Self::Http(url) => http::Request::new(url.clone(), &options.headers, &credentials),
+            #[cfg(feature = "s3")]
+            Self::S3 { bucket, key, .. } => {
+                s3::Request::new(bucket, key, &options.headers, &credentials)?.put()
+            }
+        };
+
+        let (pipe, mut rx) = UploadPipe::new();
+        let aborted = Arc::new(AtomicBool::new(false));
+        let observer = options.observer.clone();
+        let body = {
+            let aborted = Arc::clone(&aborted);
+            async_stream::stream! {
+                let mut done = 0u64;
+                loop {
+                    match rx.recv().await {
+                        Some(Piped::Data(chunk)) => {
+                            done += chunk.len() as u64;
+                            if let Some(observer) = &observer {
+                                observer.report(&TransferProgress {
+                                    bytes_done: done,
+                                    bytes_total: len,
+                                });
+                            }
+                            yield Ok(chunk);
+                        }
+                        Some(Piped::End) => break,
+                        // Never let a writer that gave up pass for a
+                        // complete archive
+                        None => {
+                            aborted.store(true, Ordering::SeqCst);
+                            yield Err(std::io::Error::other("archive ended early"));
+                            break;
+                        }
+                    }
+                }
+            }
+        };
+
+        let (written, uploaded) = tokio::join!(write(pipe), http::upload(&request, body, len));
+        if aborted.load(Ordering::SeqCst) {
+            written?;
+        }
+        uploaded?;
+        written
+    }
+
+    /// Upload the file at `path`.
+    pub(crate) async fn upload_file(
+        &self,
+        path: PathBuf,
+        options: &TransferOptions,
+    ) -> BoxliteResult<()> {
+        let len = std::fs::metadata(&path)?.len();
+        self.upload(Some(len), options, |mut pipe| async move {
+            tokio::task::spawn_blocking(move || {
+                let mut file = std::fs::File::open(&path)?;
+                std::io::copy(&mut file, &mut pipe)?;
+                pipe.finish()?;
+                Ok::<_, BoxliteError>(())
+            })
+            .await
+            .map_err(|e| BoxliteError::Internal(format!("upload task failed: {}", e)))?
+        })
+        .await
+    }
+}
+
+/// Name of the partial download of `url`, stable across attempts.
+fn download_id(url: &Url) -> String {
+    hex::encode(Sha256::digest(url.as_str().as_bytes()))
+}
+
+/// Lowercase hex of a `sha256:<hex>` or bare hex digest.
+fn parse_digest(digest: &str) -> BoxliteResult<String> {
+    let hex = digest.strip_prefix("sha256:").unwrap_or(digest);
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "invalid digest '{}', expected sha256:<64 hex digits>",
+            digest
+        )));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
+
+/// What an [`UploadPipe`] hands to its upload.
+enum Piped {
+    Data(Vec<u8>),
+    /// The archive is complete.
+    End,
+}
+
+/// Blocking writer whose bytes go to an upload, in [`CHUNK_SIZE`] chunks.
+///
+/// Writes fail once the upload has ended, which stops the archive being
+/// built for nothing. The upload only completes on [`finish`]; dropping the
+/// pipe without it aborts the upload.
+///
+/// [`finish`]: UploadPipe::finish
+pub(crate) struct UploadPipe {
+    tx: mpsc::Sender<Piped>,
+    buf: Vec<u8>,
+}
+
+impl UploadPipe {
+    fn new() -> (Self, mpsc::Receiver<Piped>) {
+        let (tx, rx) = mpsc::channel(PIPE_CHUNKS);
+        let pipe = Self {
+            tx,
+            buf: Vec::with_capacity(CHUNK_SIZE),
+        };
+        (pipe, rx)
+    }
+
+    /// Send the rest of the archive and complete the upload.
+    pub(crate) fn finish(mut self) -> std::io::Result<()> {
+        self.flush()?;
+        self.send(Piped::End)
+    }
+
+    fn send(&self, piped: Piped) -> std::io::Result<()> {
+        self.tx
+            .blocking_send(piped)
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "upload ended"))
+    }
+}
+
+impl Write for UploadPipe {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        let n = data.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == CHUNK_SIZE {
+            self.flush()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buf, Vec::with_capacity(CHUNK_SIZE));
+        self.send(Piped::Data(chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_archive_url() {
+        let url = ArchiveUrl::parse("https://example.com/boxes/web.boxsnap").unwrap();
+        assert!(url.streams_uploads());
+        assert_eq!(url.host(), "example.com");
+
+        assert!(matches!(
+            ArchiveUrl::parse("ftp://example.com/web.boxsnap"),
+            Err(BoxliteError::InvalidArgument(_))
+        ));
+        assert!(ArchiveUrl::parse("web.boxsnap").is_err());
+    }
+
+    #[cfg(feature = "s3")]
+    #[test]
+    fn test_parse_s3_url() {
+        let url = ArchiveUrl::parse("s3://backups/boxes/web.boxsnap").unwrap();
+        assert!(!url.streams_uploads());
+        assert!(matches!(
+            url,
+            ArchiveUrl::S3 { ref bucket, ref key, .. } if bucket == "backups" && key == "boxes/web.boxsnap"
+        ));
+        assert!(ArchiveUrl::parse("s3://backups").is_err());
+    }
+
+    #[test]
+    fn test_parse_digest() {
+        let hex = "AB".repeat(32);
+        assert_eq!(
+            parse_digest(&format!("sha256:{}", hex)).unwrap(),
+            "ab".repeat(32)
+        );
+        assert_eq!(parse_digest(&hex).unwrap(), "ab".repeat(32));
+        assert!(parse_digest("sha256:abc").is_err());
+        assert!(parse_digest(&"zz".repeat(32)).is_err());
+    }
+
+    fn drain(mut rx: mpsc::Receiver<Piped>) -> (Vec<usize>, bool) {
+        let mut sizes = Vec::new();
+        while let Some(piped) = rx.blocking_recv() {
+            match piped {
+                Piped::Data(chunk) => sizes.push(chunk.len()),
+                Piped::End => return (sizes, true),
+            }
+        }
+        (sizes, false)
+    }
+
+    #[test]
+    fn test_upload_pipe_chunks_writes() {
+        let (mut pipe, rx) = UploadPipe::new();
+        let writer = std::thread::spawn(move || {
+            pipe.write_all(&vec![1u8; CHUNK_SIZE + 10]).unwrap();
+            pipe.finish().unwrap();
+        });
+        assert_eq!(drain(rx), (vec![CHUNK_SIZE, 10], true));
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_upload_pipe_dropped_without_finish() {
+        let (mut pipe, rx) = UploadPipe::new();
+        let writer = std::thread::spawn(move || {
+            pipe.write_all(&vec![1u8; CHUNK_SIZE + 10]).unwrap();
+        });
+        // The buffered tail is never sent, and the end never marked
+        assert_eq!(drain(rx), (vec![CHUNK_SIZE], false));
+        writer.join().unwrap();
+    }
+}
@@ -0,0 +1,189 @@
+//! Version matrix of the engine components a runtime uses.
+//!
+//! Collected by [`BoxliteRuntime::version_info`](crate::BoxliteRuntime::version_info)
+//! to answer "which libkrun / guest / shim is this machine running?" when
+//! debugging across hosts. Every component except the crate version is
+//! best-effort: a missing or unreadable component is `None`, never an error.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::runtime::guest_rootfs_manager::GuestRootfsManager;
+use crate::util::RuntimeBinaryFinder;
+
+/// Versions of boxlite and the engine components it runs on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionInfo {
+    /// boxlite crate version
+    pub version: String,
+    /// Git commit the library was built from
+    #[serde(default)]
+    pub git_commit: Option<String>,
+    /// SHA256 of the boxlite-guest binary
+    #[serde(default)]
+    pub guest_hash: Option<String>,
+    /// Path to the boxlite-shim binary
+    #[serde(default)]
+    pub shim_path: Option<PathBuf>,
+    /// SHA256 of the boxlite-shim binary
+    #[serde(default)]
+    pub shim_hash: Option<String>,
+    /// libkrun version, from the bundled library
+    #[serde(default)]
+    pub libkrun: Option<String>,
+    /// libkrunfw (guest kernel) version, from the bundled library
+    #[serde(default)]
+    pub libkrunfw: Option<String>,
+    /// Bundled bubblewrap version (Linux only)
+    #[serde(default)]
+    pub bwrap: Option<String>,
+    /// libgvproxy version (`gvproxy-backend` feature)
+    #[serde(default)]
+    pub gvproxy: Option<String>,
+    /// Host operating system
+    pub os: String,
+    /// Host CPU architecture
+    pub arch: String,
+}
+
+impl VersionInfo {
+    /// Collect versions for this process.
+    ///
+    /// Blocking: hashes the shim binary and runs `bwrap --version`.
+    pub(crate) fn collect() -> Self {
+        let finder = RuntimeBinaryFinder::from_env();
+        let shim_path = finder.find("boxlite-shim").ok();
+        let shim_hash = shim_path.as_deref().and_then(|path| {
+            crate::util::checksum::sha256_file(path)
+                .inspect_err(|e| tracing::debug!(error = %e, "Failed to hash boxlite-shim"))
+                .ok()
+        });
+
+        Self {
+            version: crate::VERSION.to_string(),
+            git_commit: option_env!("BOXLITE_GIT_COMMIT").map(str::to_string),
+            guest_hash: GuestRootfsManager::guest_binary_hash()
+                .inspect_err(|e| tracing::debug!(error = %e, "Failed to hash boxlite-guest"))
+                .ok(),
+            shim_path,
+            shim_hash,
+            libkrun: find_library_version(finder.search_paths(), "libkrun"),
+            libkrunfw: find_library_version(finder.search_paths(), "libkrunfw"),
+            bwrap: bwrap_version(),
+            gvproxy: gvproxy_version(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn bwrap_version() -> Option<String> {
+    crate::jailer::bwrap::version().filter(|v| !v.is_empty())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bwrap_version() -> Option<String> {
+    None
+}
+
+#[cfg(feature = "gvproxy-backend")]
+fn gvproxy_version() -> Option<String> {
+    crate::net::gvproxy::GvproxyInstance::version()
+        .inspect_err(|e| tracing::debug!(error = %e, "Failed to query gvproxy version"))
+        .ok()
+}
+
+#[cfg(not(feature = "gvproxy-backend"))]
+fn gvproxy_version() -> Option<String> {
+    None
+}
+
+/// Find the bundled `library` in `dirs` and read the version from its file name.
+///
+/// The runtime directory ships only the fully versioned file (symlinks are
+/// dropped when bundling), e.g. `libkrun.so.1.15.1` or `libkrun.1.15.1.dylib`.
+/// The first directory containing the library wins, matching binary lookup.
+fn find_library_version(dirs: &[PathBuf], library: &str) -> Option<String> {
+    dirs.iter().find_map(|dir| library_version_in(dir, library))
+}
+
+fn library_version_in(dir: &Path, library: &str) -> Option<String> {
+    std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            parse_library_version(name.to_str()?, library).map(str::to_string)
+        })
+        // Prefer the most specific name (1.15.1 over 1)
+        .max_by_key(|version| version.split('.').count())
+}
+
+/// Extract the version from a versioned shared library file name.
+///
+/// `libkrun.so.1.15.1` (Linux) and `libkrun.1.15.1.dylib` (macOS) both yield
+/// `1.15.1`. Other libraries sharing the prefix (`libkrunfw.*` for `libkrun`)
+/// don't match.
+fn parse_library_version<'a>(file_name: &'a str, library: &str) -> Option<&'a str> {
+    let rest = file_name.strip_prefix(library)?.strip_prefix('.')?;
+    let version = rest
+        .strip_prefix("so.")
+        .or_else(|| rest.strip_suffix(".dylib"))?;
+
+    let is_version = !version.is_empty()
+        && version
+            .split('.')
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+    is_version.then_some(version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_library_version() {
+        assert_eq!(
+            parse_library_version("libkrun.so.1.15.1", "libkrun"),
+            Some("1.15.1")
+        );
+        assert_eq!(
+            parse_library_version("libkrun.1.15.1.dylib", "libkrun"),
+            Some("1.15.1")
+        );
+        assert_eq!(
+            parse_library_version("libkrunfw.so.4", "libkrunfw"),
+            Some("4")
+        );
+    }
+
+    #[test]
+    fn test_parse_library_version_rejects_other_files() {
+        assert_eq!(parse_library_version("libkrunfw.so.4", "libkrun"), None);
+        assert_eq!(parse_library_version("libkrun.so", "libkrun"), None);
+        assert_eq!(parse_library_version("libkrun.dylib", "libkrun"), None);
+        assert_eq!(parse_library_version("libkrun.so.1.x", "libkrun"), None);
+        assert_eq!(parse_library_version("boxlite-shim", "libkrun"), None);
+    }
+
+    #[test]
+    fn test_find_library_version_prefers_full_version() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("libkrun.so.1"), b"").unwrap();
+        std::fs::write(dir.path().join("libkrun.so.1.15.1"), b"").unwrap();
+        std::fs::write(dir.path().join("libkrunfw.so.4"), b"").unwrap();
+
+        let dirs = vec![PathBuf::from("/nonexistent"), dir.path().to_path_buf()];
+        assert_eq!(
+            find_library_version(&dirs, "libkrun").as_deref(),
+            Some("1.15.1")
+        );
+        assert_eq!(
+            find_library_version(&dirs, "libkrunfw").as_deref(),
+            Some("4")
+        );
+        assert_eq!(find_library_version(&dirs, "libgvproxy"), None);
+    }
+}
@@ -0,0 +1,345 @@
+//! Warm pools of pre-started boxes.
+//!
+//! Configured with `BoxliteOptions::warm_pool`. Each pool keeps `size`
+//! started boxes idle, so `acquire_warm()` only has to update a box's state
+//! before handing it out; a background task then starts a replacement. When
+//! a pool is empty the box is created and started on the spot (a miss).
+//!
+//! Idle boxes carry `BoxState::warm_pool`, which hides them from
+//! `list_info()`. An acquired box is an ordinary box from then on and never
+//! goes back to a pool. The runtime lock guarantees a single process per
+//! home directory, so a pool box found at startup was left behind by an
+//! earlier process and is removed; idle boxes are removed on shutdown.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use parking_lot::Mutex;
+
+use crate::litebox::{LiteBox, SharedBoxImpl};
+use crate::runtime::options::WarmPoolSpec;
+use crate::runtime::types::BoxStatus;
+
+use super::rt_impl::RuntimeImpl;
+
+/// Which warm pool to take a box from, and what to apply to it.
+#[derive(Clone, Debug)]
+pub struct WarmSelector {
+    pool: String,
+    env: Vec<(String, String)>,
+}
+
+impl WarmSelector {
+    /// Select the pool named `pool`.
+    pub fn new(pool: impl Into<String>) -> Self {
+        Self {
+            pool: pool.into(),
+            env: Vec::new(),
+        }
+    }
+
+    /// Set an environment variable for every command run in the acquired box.
+    ///
+    /// The box is already running, so its entrypoint does not see it.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Name of the selected pool.
+    pub fn pool(&self) -> &str {
+        &self.pool
+    }
+}
+
+/// Idle boxes of one pool.
+struct Pool {
+    spec: WarmPoolSpec,
+    idle: Mutex<Idle>,
+    /// A backfill task is running.
+    filling: AtomicBool,
+}
+
+#[derive(Default)]
+struct Idle {
+    boxes: VecDeque<SharedBoxImpl>,
+    /// Drained for good; boxes started afterwards are not kept.
+    closed: bool,
+}
+
+/// The warm pools of a runtime, by name.
+pub(crate) struct WarmPools {
+    pools: HashMap<String, Arc<Pool>>,
+}
+
+impl WarmPools {
+    /// Validate `specs` and set up an empty pool for each.
+    pub(crate) fn new(specs: &[WarmPoolSpec]) -> BoxliteResult<Self> {
+        let mut pools = HashMap::new();
+        for spec in specs {
+            if spec.name.is_empty() {
+                return Err(BoxliteError::Config(
+                    "warm pool name must not be empty".into(),
+                ));
+            }
+            spec.options.sanitize()?;
+            let pool = Pool {
+                spec: spec.clone(),
+                idle: Mutex::new(Idle::default()),
+                filling: AtomicBool::new(false),
+            };
+            if pools.insert(spec.name.clone(), Arc::new(pool)).is_some() {
+                return Err(BoxliteError::Config(format!(
+                    "duplicate warm pool '{}'",
+                    spec.name
+                )));
+            }
+        }
+        Ok(Self { pools })
+    }
+}
+
+impl RuntimeImpl {
+    /// Take a started box from the selected pool, starting one if it is empty.
+    pub async fn acquire_warm(self: &Arc<Self>, selector: WarmSelector) -> BoxliteResult<LiteBox> {
+        let _op = self.in_flight.enter("acquire warm box")?;
+        let started = Instant::now();
+        let pool = self
+            .warm_pools
+            .pools
+            .get(&selector.pool)
+            .cloned()
+            .ok_or_else(|| BoxliteError::NotFound(format!("warm pool '{}'", selector.pool)))?;
+
+        let idle = loop {
+            let Some(box_impl) = pool.idle.lock().boxes.pop_front() else {
+                break None;
+            };
+            if self.is_usable_warm_box(&box_impl) {
+                break Some(box_impl);
+            }
+            tracing::warn!(
+                box_id = %box_impl.id(),
+                pool = %selector.pool,
+                "Discarding warm box that is no longer running"
+            );
+            self.discard_warm_box(&box_impl);
+        };
+        let hit = idle.is_some();
+        let box_impl = match idle {
+            Some(box_impl) => box_impl,
+            None => self.start_warm_box(&pool.spec, false).await?,
+        };
+        if let Err(e) = box_impl.claim_warm(&selector.env) {
+            self.discard_warm_box(&box_impl);
+            return Err(e);
+        }
+        self.fill_warm_pool(&pool);
+
+        let elapsed = started.elapsed();
+        let counter = if hit {
+            &self.runtime_metrics.warm_pool_hits
+        } else {
+            &self.runtime_metrics.warm_pool_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        self.runtime_metrics
+            .warm_acquire_ms
+            .fetch_add(elapsed.as_millis() as u64, Ordering::Relaxed);
+        tracing::info!(
+            box_id = %box_impl.id(),
+            pool = %selector.pool,
+            hit,
+            elapsed_ms = elapsed.as_millis() as u64,
+            "Acquired warm box"
+        );
+
+        Ok(LiteBox::new(box_impl))
+    }
+
+    /// Start filling every pool in the background.
+    pub(crate) fn fill_warm_pools(self: &Arc<Self>) {
+        for pool in self.warm_pools.pools.values() {
+            self.fill_warm_pool(pool);
+        }
+    }
+
+    /// Start boxes in the background until `pool` has `size` idle ones.
+    ///
+    /// No-op outside a Tokio runtime or while the pool is already filling.
+    /// A failed start stops the fill; the next acquisition retries it.
+    fn fill_warm_pool(self: &Arc<Self>, pool: &Arc<Pool>) {
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        if pool.filling.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let weak = Arc::downgrade(self);
+        let pool = Arc::clone(pool);
+        handle.spawn(async move {
+            loop {
+                {
+                    let idle = pool.idle.lock();
+                    if idle.closed || idle.boxes.len() >= pool.spec.size {
+                        break;
+                    }
+                }
+                let Some(runtime) = weak.upgrade() else {
+                    break;
+                };
+                let Ok(_op) = runtime.in_flight.enter("fill warm pool") else {
+                    break;
+                };
+                match runtime.start_warm_box(&pool.spec, true).await {
+                    Ok(box_impl) => {
+                        let mut idle = pool.idle.lock();
+                        if idle.closed {
+                            drop(idle);
+                            runtime.discard_warm_box(&box_impl);
+                            break;
+                        }
+                        idle.boxes.push_back(box_impl);
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            pool = %pool.spec.name,
+                            error = %e,
+                            "Failed to start warm box"
+                        );
+                        break;
+                    }
+                }
+            }
+            pool.filling.store(false, Ordering::Release);
+        });
+    }
+
+    /// Create and start a box for `spec`, marking it idle in the pool first
+    /// when `idle` is set. The box is removed again if it fails to start.
+    async fn start_warm_box(
+        self: &Arc<Self>,
+        spec: &WarmPoolSpec,
+        idle: bool,
+    ) -> BoxliteResult<SharedBoxImpl> {
+        let (box_impl, _) = self
+            .create_box_impl(spec.options.clone(), None, false)
+            .await?;
+        let started = async {
+            if idle {
+                box_impl.mark_warm(&spec.name)?;
+            }
+            box_impl.start().await
+        };
+        if let Err(e) = started.await {
+            self.discard_warm_box(&box_impl);
+            return Err(e);
+        }
+        Ok(box_impl)
+    }
+
+    /// Whether an idle box can still be handed out: it has not been removed
+    /// or stopped behind the pool's back.
+    fn is_usable_warm_box(&self, box_impl: &SharedBoxImpl) -> bool {
+        box_impl.state.read().status == BoxStatus::Running
+            && self
+                .box_manager
+                .box_by_id(box_impl.id())
+                .is_ok_and(|record| record.is_some())
+    }
+
+    fn discard_warm_box(&self, box_impl: &SharedBoxImpl) {
+        if let Err(e) = self.remove_box(box_impl.id(), true) {
+            tracing::warn!(
+                box_id = %box_impl.id(),
+                error = %e,
+                "Failed to remove warm box"
+            );
+        }
+    }
+
+    /// Remove every idle box from the pools and stop backfilling them.
+    ///
+    /// Called on shutdown, and when the last runtime handle is dropped: idle
+    /// boxes hold the runtime alive, so its own `Drop` would never run.
+    pub(crate) fn drain_warm_pools(&self) {
+        for pool in self.warm_pools.pools.values() {
+            let idle: Vec<_> = {
+                let mut idle = pool.idle.lock();
+                idle.closed = true;
+                idle.boxes.drain(..).collect()
+            };
+            for box_impl in idle {
+                tracing::debug!(box_id = %box_impl.id(), pool = %pool.spec.name, "Removing idle warm box");
+                self.discard_warm_box(&box_impl);
+            }
+        }
+    }
+
+    /// Remove pool boxes left behind by an earlier process.
+    pub(crate) fn remove_stale_warm_boxes(&self) -> BoxliteResult<()> {
+        for (config, state) in self.box_manager.all_boxes(true)? {
+            let Some(pool) = state.warm_pool else {
+                continue;
+            };
+            tracing::info!(box_id = %config.id, %pool, "Removing leftover warm pool box");
+            if let Err(e) = self.remove_box(&config.id, true) {
+                tracing::warn!(
+                    box_id = %config.id,
+                    error = %e,
+                    "Failed to remove leftover warm pool box"
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::options::BoxOptions;
+
+    fn spec(name: &str) -> WarmPoolSpec {
+        WarmPoolSpec {
+            name: name.into(),
+            options: BoxOptions::default(),
+            size: 2,
+        }
+    }
+
+    #[test]
+    fn test_warm_pools_reject_duplicate_names() {
+        let result = WarmPools::new(&[spec("py"), spec("py")]);
+        assert!(matches!(result, Err(BoxliteError::Config(_))));
+    }
+
+    #[test]
+    fn test_warm_pools_reject_empty_name() {
+        let result = WarmPools::new(&[spec("")]);
+        assert!(matches!(result, Err(BoxliteError::Config(_))));
+    }
+
+    #[test]
+    fn test_warm_pools_reject_invalid_options() {
+        let mut invalid = spec("py");
+        invalid.options.detach = true;
+        invalid.options.auto_remove = true;
+        let result = WarmPools::new(&[invalid]);
+        assert!(matches!(result, Err(BoxliteError::Config(_))));
+    }
+
+    #[test]
+    fn test_warm_selector_collects_env() {
+        let selector = WarmSelector::new("py").env("A", "1").env("B", "2");
+        assert_eq!(selector.pool(), "py");
+        assert_eq!(
+            selector.env,
+            vec![("A".into(), "1".into()), ("B".into(), "2".into())]
+        );
+    }
+}
@@ -0,0 +1,315 @@
+//! Test harness for code that embeds boxlite.
+//!
+//! Enabled by the `test-util` feature. Bundles the scaffolding boxlite's own
+//! integration tests rely on:
+//!
+//! - [`TestRuntime`]: a runtime with an isolated, short home directory
+//! - [`TestBox`]: a box that is force-removed on drop, including on panic
+//! - [`skip_if_no_virtualization!`](crate::skip_if_no_virtualization): skip
+//!   tests on hosts without KVM / Hypervisor.framework
+//! - [`ExecOutput`]: captured command output with assertion helpers
+//!
+//! ```no_run
+//! use boxlite::testing::TestRuntime;
+//!
+//! #[tokio::test]
+//! async fn echo_works() {
+//!     boxlite::skip_if_no_virtualization!();
+//!
+//!     let rt = TestRuntime::new();
+//!     let bx = rt.alpine().await;
+//!     bx.exec_output("echo", ["hello"]).await.assert_stdout_eq("hello\n");
+//! }
+//! ```
+
+use std::ops::Deref;
+use std::path::Path;
+
+use futures::StreamExt;
+use tempfile::TempDir;
+
+use crate::litebox::{BoxCommand, LiteBox};
+use crate::runtime::BoxliteRuntime;
+use crate::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+
+/// Image used by default for test boxes.
+pub const ALPINE_IMAGE: &str = "alpine:latest";
+
+/// Parent directory for test home directories.
+///
+/// Unix socket paths are limited to ~104 bytes (`SUN_LEN`). Box sockets live
+/// several levels below the home directory, so deep temp dirs (macOS
+/// `/var/folders/...`, nested project checkouts) overflow the limit.
+pub const SHORT_TEMP_ROOT: &str = "/tmp";
+
+/// Create a temporary directory under [`SHORT_TEMP_ROOT`].
+///
+/// Removed when the returned guard is dropped.
+pub fn short_temp_dir() -> TempDir {
+    TempDir::new_in(SHORT_TEMP_ROOT).expect("Failed to create temp dir")
+}
+
+/// Whether the host can run boxes (KVM on Linux, Hypervisor.framework on macOS).
+pub fn virtualization_available() -> bool {
+    crate::vmm::host_check::check_virtualization_support().is_ok()
+}
+
+/// Return early from a test when the host has no hardware virtualization.
+///
+/// Prints the reason to stderr so skipped tests are visible with `--nocapture`.
+#[macro_export]
+macro_rules! skip_if_no_virtualization {
+    () => {
+        if !$crate::testing::virtualization_available() {
+            eprintln!(
+                "skipping {}: hardware virtualization (KVM / Hypervisor.framework) unavailable",
+                module_path!()
+            );
+            return;
+        }
+    };
+}
+
+// ============================================================================
+// TestRuntime
+// ============================================================================
+
+/// Runtime with an isolated home directory, removed on drop.
+///
+/// Dereferences to [`BoxliteRuntime`].
+pub struct TestRuntime {
+    runtime: BoxliteRuntime,
+    // Declared after `runtime` so the runtime shuts down before its home goes away
+    home: TempDir,
+}
+
+impl TestRuntime {
+    /// Create a runtime with default options and no image registries configured.
+    pub fn new() -> Self {
+        Self::with_options(BoxliteOptions {
+            image_registries: vec![],
+            ..Default::default()
+        })
+    }
+
+    /// Create a runtime from `options`, overriding `home_dir` with a fresh short temp dir.
+    pub fn with_options(options: BoxliteOptions) -> Self {
+        let home = short_temp_dir();
+        let options = BoxliteOptions {
+            home_dir: home.path().to_path_buf(),
+            ..options
+        };
+        let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
+        Self { runtime, home }
+    }
+
+    /// The runtime under test.
+    pub fn runtime(&self) -> &BoxliteRuntime {
+        &self.runtime
+    }
+
+    /// Home directory of the runtime.
+    pub fn home_dir(&self) -> &Path {
+        self.home.path()
+    }
+
+    /// Create a box that is force-removed when the returned handle drops.
+    pub async fn create_box(&self, options: BoxOptions) -> TestBox {
+        let litebox = self
+            .runtime
+            .create(options, None)
+            .await
+            .expect("Failed to create box");
+        TestBox::new(self.runtime.clone(), litebox)
+    }
+
+    /// Create an [`ALPINE_IMAGE`] box with default options.
+    pub async fn alpine(&self) -> TestBox {
+        self.create_box(alpine_options()).await
+    }
+}
+
+impl Default for TestRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for TestRuntime {
+    type Target = BoxliteRuntime;
+
+    fn deref(&self) -> &BoxliteRuntime {
+        &self.runtime
+    }
+}
+
+/// Box options for an [`ALPINE_IMAGE`] box that is kept after stop.
+pub fn alpine_options() -> BoxOptions {
+    BoxOptions {
+        rootfs: RootfsSpec::Image(ALPINE_IMAGE.into()),
+        auto_remove: false,
+        ..Default::default()
+    }
+}
+
+// ============================================================================
+// TestBox
+// ============================================================================
+
+/// Box handle that force-removes the box on drop.
+///
+/// Cleanup runs during unwinding as well, so a failed assertion does not leak
+/// a running VM. Dereferences to [`LiteBox`].
+pub struct TestBox {
+    runtime: BoxliteRuntime,
+    litebox: LiteBox,
+}
+
+impl TestBox {
+    /// Take ownership of `litebox` for cleanup on drop.
+    pub fn new(runtime: BoxliteRuntime, litebox: LiteBox) -> Self {
+        Self { runtime, litebox }
+    }
+
+    /// Run `command` with `args` to completion and capture its output.
+    ///
+    /// Panics if the command cannot be started.
+    pub async fn exec_output<I, S>(&self, command: &str, args: I) -> ExecOutput
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.run_output(BoxCommand::new(command).args(args)).await
+    }
+
+    /// Run a prepared [`BoxCommand`] to completion and capture its output.
+    pub async fn run_output(&self, command: BoxCommand) -> ExecOutput {
+        let mut execution = self
+            .litebox
+            .exec(command)
+            .await
+            .expect("Failed to start command");
+
+        let stdout = execution.stdout();
+        let stderr = execution.stderr();
+        let (stdout, stderr) = futures::join!(
+            async {
+                match stdout {
+                    Some(s) => s.collect::<Vec<_>>().await.concat(),
+                    None => String::new(),
+                }
+            },
+            async {
+                match stderr {
+                    Some(s) => s.collect::<Vec<_>>().await.concat(),
+                    None => String::new(),
+                }
+            }
+        );
+
+        let result = execution.wait().await.expect("Failed to wait for command");
+        ExecOutput {
+            exit_code: result.exit_code,
+            stdout,
+            stderr,
+        }
+    }
+}
+
+impl Deref for TestBox {
+    type Target = LiteBox;
+
+    fn deref(&self) -> &LiteBox {
+        &self.litebox
+    }
+}
+
+impl Drop for TestBox {
+    fn drop(&mut self) {
+        // Drop can't await, and may run inside the test's runtime, where
+        // blocking on it would deadlock. Remove on a dedicated thread instead.
+        let runtime = self.runtime.clone();
+        let id = self.litebox.id().to_string();
+        let cleanup = std::thread::spawn(move || {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to build cleanup runtime");
+            rt.block_on(runtime.remove_permanently(&id, true))
+        });
+
+        match cleanup.join() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => eprintln!("TestBox cleanup failed for {}: {}", self.litebox.id(), e),
+            Err(_) => eprintln!("TestBox cleanup panicked for {}", self.litebox.id()),
+        }
+    }
+}
+
+// ============================================================================
+// ExecOutput
+// ============================================================================
+
+/// Captured result of a command run in a [`TestBox`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExecOutput {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl ExecOutput {
+    /// Assert the command exited with code 0.
+    #[track_caller]
+    pub fn assert_success(&self) -> &Self {
+        assert_eq!(
+            self.exit_code, 0,
+            "command failed\nstdout: {}\nstderr: {}",
+            self.stdout, self.stderr
+        );
+        self
+    }
+
+    /// Assert the command exited with `code`.
+    #[track_caller]
+    pub fn assert_exit_code(&self, code: i32) -> &Self {
+        assert_eq!(
+            self.exit_code, code,
+            "unexpected exit code\nstdout: {}\nstderr: {}",
+            self.stdout, self.stderr
+        );
+        self
+    }
+
+    /// Assert the command succeeded and stdout equals `expected`.
+    #[track_caller]
+    pub fn assert_stdout_eq(&self, expected: &str) -> &Self {
+        self.assert_success();
+        assert_eq!(self.stdout, expected, "stderr: {}", self.stderr);
+        self
+    }
+
+    /// Assert stdout contains `needle`.
+    #[track_caller]
+    pub fn assert_stdout_contains(&self, needle: &str) -> &Self {
+        assert!(
+            self.stdout.contains(needle),
+            "stdout does not contain {:?}\nstdout: {}",
+            needle,
+            self.stdout
+        );
+        self
+    }
+
+    /// Assert stderr contains `needle`.
+    #[track_caller]
+    pub fn assert_stderr_contains(&self, needle: &str) -> &Self {
+        assert!(
+            self.stderr.contains(needle),
+            "stderr does not contain {:?}\nstderr: {}",
+            needle,
+            self.stderr
+        );
+        self
+    }
+}
@@ -128,6 +128,11 @@ impl RuntimeBinaryFinder {
         builder.build()
     }
 
+    /// Directories searched, in priority order.
+    pub fn search_paths(&self) -> &[PathBuf] {
+        &self.search_paths
+    }
+
     /// Find a binary by name, searching all configured paths.
     pub fn find(&self, binary_name: &str) -> BoxliteResult<PathBuf> {
         for search_path in &self.search_paths {
@@ -0,0 +1,217 @@
+//! SHA-256 checksums of large files (disk images, guest binaries).
+//!
+//! Two digests are available:
+//!
+//! - the plain SHA-256 of the file, streamed with a large buffer. Inherently
+//!   sequential, so a multi-GB disk takes seconds whatever the core count.
+//! - a tree digest: the SHA-256 of every [`TREE_SEGMENT_SIZE`] segment,
+//!   hashed in parallel, then the SHA-256 of those segment digests
+//!   concatenated in order. Verifying it scales with the cores available.
+//!
+//! Both block; async callers use [`file_digests_async`] or
+//! [`sha256_file_async`], which run on the blocking pool.
+
+use std::fs::File;
+use std::io::Read;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+
+/// Read buffer of the streaming hashes.
+const READ_BUFFER_SIZE: usize = 1024 * 1024;
+
+/// Size of the segments hashed independently by the tree digest.
+///
+/// Part of the digest format: changing it changes every tree digest.
+pub const TREE_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Upper bound on the threads hashing segments, so a large export leaves
+/// cores to the boxes running next to it.
+const MAX_HASH_THREADS: usize = 4;
+
+/// Plain and tree digests of one file, both lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDigests {
+    pub sha256: String,
+    pub tree_sha256: String,
+}
+
+/// SHA-256 of `path` as lowercase hex.
+pub fn sha256_file(path: &Path) -> BoxliteResult<String> {
+    let mut file = open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| read_error(path, e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Tree digest of `path` as lowercase hex (see the module docs).
+pub fn tree_sha256_file(path: &Path) -> BoxliteResult<String> {
+    tree_sha256_file_with(path, TREE_SEGMENT_SIZE)
+}
+
+fn tree_sha256_file_with(path: &Path, segment_size: u64) -> BoxliteResult<String> {
+    let file = open(path)?;
+    let len = file.metadata().map_err(|e| read_error(path, e))?.len();
+    let segments = len.div_ceil(segment_size);
+
+    let digests = hash_pool().install(|| {
+        (0..segments)
+            .into_par_iter()
+            .map(|i| {
+                let start = i * segment_size;
+                let end = (start + segment_size).min(len);
+                hash_segment(&file, start, end).map_err(|e| read_error(path, e))
+            })
+            .collect::<BoxliteResult<Vec<_>>>()
+    })?;
+
+    let mut root = Sha256::new();
+    for digest in &digests {
+        root.update(digest);
+    }
+    Ok(hex::encode(root.finalize()))
+}
+
+/// Plain and tree digests of `path`, computed side by side.
+pub fn file_digests(path: &Path) -> BoxliteResult<FileDigests> {
+    let (sha256, tree_sha256) = rayon::join(|| sha256_file(path), || tree_sha256_file(path));
+    Ok(FileDigests {
+        sha256: sha256?,
+        tree_sha256: tree_sha256?,
+    })
+}
+
+/// [`sha256_file`] on the blocking pool.
+pub async fn sha256_file_async(path: PathBuf) -> BoxliteResult<String> {
+    tokio::task::spawn_blocking(move || sha256_file(&path))
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("checksum task failed: {}", e)))?
+}
+
+/// [`file_digests`] on the blocking pool.
+pub async fn file_digests_async(path: PathBuf) -> BoxliteResult<FileDigests> {
+    tokio::task::spawn_blocking(move || file_digests(&path))
+        .await
+        .map_err(|e| BoxliteError::Internal(format!("checksum task failed: {}", e)))?
+}
+
+fn hash_segment(file: &File, start: u64, end: u64) -> std::io::Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; READ_BUFFER_SIZE.min((end - start) as usize)];
+    let mut offset = start;
+    while offset < end {
+        let want = buf.len().min((end - offset) as usize);
+        let n = file.read_at(&mut buf[..want], offset)?;
+        if n == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        hasher.update(&buf[..n]);
+        offset += n as u64;
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Thread pool of the tree digest, separate from rayon's global pool that
+/// layer extraction uses.
+fn hash_pool() -> &'static rayon::ThreadPool {
+    static POOL: OnceLock<rayon::ThreadPool> = OnceLock::new();
+    POOL.get_or_init(|| {
+        let threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_HASH_THREADS);
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .thread_name(|i| format!("boxlite-hash-{}", i))
+            .build()
+            .expect("failed to build checksum thread pool")
+    })
+}
+
+fn open(path: &Path) -> BoxliteResult<File> {
+    File::open(path).map_err(|e| {
+        BoxliteError::Storage(format!(
+            "Failed to open {} for checksum: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+fn read_error(path: &Path, e: std::io::Error) -> BoxliteError {
+    BoxliteError::Storage(format!(
+        "Failed to read {} for checksum: {}",
+        path.display(),
+        e
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha256_hex(data: &[u8]) -> String {
+        hex::encode(Sha256::digest(data))
+    }
+
+    #[test]
+    fn test_sha256_file_matches_one_shot_digest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+        let data: Vec<u8> = (0..3 * READ_BUFFER_SIZE as u32 + 17)
+            .map(|i| (i % 251) as u8)
+            .collect();
+        std::fs::write(&path, &data).unwrap();
+
+        assert_eq!(sha256_file(&path).unwrap(), sha256_hex(&data));
+    }
+
+    #[test]
+    fn test_tree_digest_combines_segment_digests() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data");
+        let data: Vec<u8> = (0..2 * READ_BUFFER_SIZE as u32 + 5)
+            .map(|i| (i % 241) as u8)
+            .collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let segment_size = READ_BUFFER_SIZE as u64 + 3;
+        let mut root = Sha256::new();
+        for segment in data.chunks(segment_size as usize) {
+            root.update(Sha256::digest(segment));
+        }
+        assert_eq!(
+            tree_sha256_file_with(&path, segment_size).unwrap(),
+            hex::encode(root.finalize())
+        );
+
+        let digests = file_digests(&path).unwrap();
+        assert_eq!(digests.sha256, sha256_hex(&data));
+        assert_eq!(digests.tree_sha256, tree_sha256_file(&path).unwrap());
+    }
+
+    #[test]
+    fn test_tree_digest_of_empty_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("empty");
+        std::fs::write(&path, b"").unwrap();
+
+        assert_eq!(tree_sha256_file(&path).unwrap(), sha256_hex(b""));
+    }
+
+    #[test]
+    fn test_missing_file_is_a_storage_error() {
+        let err = sha256_file(Path::new("/nonexistent/boxlite-checksum")).unwrap_err();
+        assert!(matches!(err, BoxliteError::Storage(_)));
+    }
+}
@@ -1,5 +1,7 @@
 mod binary_finder;
+pub mod checksum;
 pub mod process;
+mod socket;
 
 pub use binary_finder::{RuntimeBinaryFinder, find_binary};
 
@@ -13,8 +15,10 @@ use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{EnvFilter, fmt};
 
 pub use process::{
-    ProcessExit, ProcessMonitor, is_process_alive, is_same_process, kill_process, read_pid_file,
+    ProcessExit, ProcessMonitor, is_process_alive, is_same_process, kill_process,
+    process_start_time, read_pid_file,
 };
+pub use socket::{SocketState, probe_socket, remove_stale_sockets};
 
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 unsafe extern "C" {
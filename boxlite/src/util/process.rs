@@ -244,6 +244,53 @@ fn is_process_zombie_macos(pid: u32) -> bool {
     info.pbi_status == libc::SZOMB
 }
 
+/// Start time of a process, for telling it apart from a later process that
+/// reuses its PID.
+///
+/// The value is only comparable with others from this function on the same
+/// host: clock ticks since boot on Linux, microseconds since the epoch on
+/// macOS.
+///
+/// # Returns
+/// * `Some(start)` - Process exists and its start time could be read
+/// * `None` - Process does not exist, or the platform is not supported
+pub fn process_start_time(pid: u32) -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        // Field 22 of /proc/{pid}/stat; the command name (field 2) may
+        // contain spaces, so count from its closing parenthesis
+        let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        let fields = &stat[stat.rfind(')')? + 1..];
+        fields.split_whitespace().nth(19)?.parse().ok()
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut info = std::mem::MaybeUninit::<libc::proc_bsdinfo>::uninit();
+        let expected_size = std::mem::size_of::<libc::proc_bsdinfo>() as i32;
+        let bytes = unsafe {
+            libc::proc_pidinfo(
+                pid as i32,
+                libc::PROC_PIDTBSDINFO,
+                0,
+                info.as_mut_ptr().cast(),
+                expected_size,
+            )
+        };
+        if bytes != expected_size {
+            return None;
+        }
+        let info = unsafe { info.assume_init() };
+        Some(info.pbi_start_tvsec * 1_000_000 + info.pbi_start_tvusec)
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "macos")))]
+    {
+        let _ = pid;
+        None
+    }
+}
+
 /// Verify that a PID belongs to a boxlite-shim process for the given box.
 ///
 /// This prevents PID reuse attacks where a PID is recycled for a different process.
@@ -380,6 +427,15 @@ mod tests {
         panic!("Exited child remained reported as alive while still existing");
     }
 
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_process_start_time() {
+        let start = process_start_time(std::process::id());
+        assert!(start.is_some());
+        assert_eq!(process_start_time(std::process::id()), start);
+        assert_eq!(process_start_time(999999999), None);
+    }
+
     #[test]
     fn test_is_same_process_current() {
         let current_pid = std::process::id();
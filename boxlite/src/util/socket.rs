@@ -0,0 +1,165 @@
+//! Stale Unix socket detection.
+//!
+//! A shim killed with SIGKILL never unlinks its socket files (`box.sock`,
+//! the gvproxy `net.sock`), and binding the same path again fails with
+//! "address in use". Before starting a box, its sockets are probed: a
+//! socket file nobody listens on any more is stale and removed, while one
+//! a live process still owns is left alone.
+
+use std::io::ErrorKind;
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixDatagram, UnixStream};
+use std::path::{Path, PathBuf};
+
+/// What a probe found at a socket path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocketState {
+    /// Nothing at the path.
+    Missing,
+    /// A live process accepts connections on the socket.
+    Live,
+    /// A leftover file with no process behind it.
+    Stale,
+}
+
+/// Probe whether a live process owns the socket at `path`.
+///
+/// Connecting to a socket file whose owner died fails with
+/// `ECONNREFUSED`. Stream sockets are tried first, then datagram sockets
+/// (gvproxy uses `SOCK_DGRAM` on macOS). Errors that say nothing about
+/// the owner, such as permission denied, count as live so that nothing
+/// in use is ever removed.
+pub fn probe_socket(path: &Path) -> SocketState {
+    let metadata = match std::fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return SocketState::Missing,
+        Err(_) => return SocketState::Live,
+    };
+    if !metadata.file_type().is_socket() {
+        // Nothing can listen on a regular file left at a socket path
+        return SocketState::Stale;
+    }
+
+    match UnixStream::connect(path) {
+        Ok(_) => SocketState::Live,
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => SocketState::Stale,
+        Err(e) if e.raw_os_error() == Some(libc::EPROTOTYPE) => probe_datagram(path),
+        Err(_) => SocketState::Live,
+    }
+}
+
+fn probe_datagram(path: &Path) -> SocketState {
+    let Ok(socket) = UnixDatagram::unbound() else {
+        return SocketState::Live;
+    };
+    match socket.connect(path) {
+        Ok(()) => SocketState::Live,
+        Err(e) if e.kind() == ErrorKind::ConnectionRefused => SocketState::Stale,
+        Err(_) => SocketState::Live,
+    }
+}
+
+/// Remove the stale sockets among `paths`.
+///
+/// Returns the paths still owned by a live process, which are kept.
+pub fn remove_stale_sockets(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut live = Vec::new();
+    for path in paths {
+        match probe_socket(path) {
+            SocketState::Missing => {}
+            SocketState::Live => live.push(path.clone()),
+            SocketState::Stale => {
+                tracing::warn!(socket = %path.display(), "Removing stale socket");
+                if let Err(e) = std::fs::remove_file(path)
+                    && e.kind() != ErrorKind::NotFound
+                {
+                    tracing::warn!(
+                        socket = %path.display(),
+                        error = %e,
+                        "Failed to remove stale socket"
+                    );
+                }
+            }
+        }
+    }
+    live
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::fd::OwnedFd;
+    use std::os::unix::net::UnixListener;
+    use std::process::Stdio;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_probe_missing_socket() {
+        let dir = TempDir::new_in("/tmp").unwrap();
+        assert_eq!(
+            probe_socket(&dir.path().join("none.sock")),
+            SocketState::Missing
+        );
+    }
+
+    #[test]
+    fn test_live_stream_socket_is_kept() {
+        let dir = TempDir::new_in("/tmp").unwrap();
+        let path = dir.path().join("box.sock");
+        let _listener = UnixListener::bind(&path).unwrap();
+
+        assert_eq!(probe_socket(&path), SocketState::Live);
+        assert_eq!(
+            remove_stale_sockets(std::slice::from_ref(&path)),
+            [path.clone()]
+        );
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_stale_sockets_are_removed() {
+        let dir = TempDir::new_in("/tmp").unwrap();
+        let stream = dir.path().join("box.sock");
+        let datagram = dir.path().join("net.sock");
+        let file = dir.path().join("ready.sock");
+
+        // Closing without unlinking leaves the same files as a SIGKILL
+        drop(UnixListener::bind(&stream).unwrap());
+        drop(UnixDatagram::bind(&datagram).unwrap());
+        std::fs::write(&file, b"").unwrap();
+
+        let paths = [stream, datagram, file];
+        for path in &paths {
+            assert_eq!(probe_socket(path), SocketState::Stale, "{}", path.display());
+        }
+        assert!(remove_stale_sockets(&paths).is_empty());
+        assert!(paths.iter().all(|path| !path.exists()));
+
+        // The paths can be bound again
+        let _listener = UnixListener::bind(&paths[0]).unwrap();
+        let _socket = UnixDatagram::bind(&paths[1]).unwrap();
+    }
+
+    #[test]
+    fn test_socket_of_sigkilled_process_is_removed() {
+        let dir = TempDir::new_in("/tmp").unwrap();
+        let path = dir.path().join("net.sock");
+
+        // A child owns the socket, like the shim owning gvproxy's. Our
+        // copy of the fd is closed once the command is spawned.
+        let listener = UnixListener::bind(&path).unwrap();
+        let mut child = std::process::Command::new("sleep")
+            .arg("300")
+            .stdin(Stdio::from(OwnedFd::from(listener)))
+            .spawn()
+            .unwrap();
+        assert_eq!(probe_socket(&path), SocketState::Live);
+
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(probe_socket(&path), SocketState::Stale);
+        assert!(remove_stale_sockets(std::slice::from_ref(&path)).is_empty());
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,565 @@
+//! Canonical validators for values that cross an API boundary.
+//!
+//! The Rust API, the REST server, the CLI and the C and Java bindings check
+//! guest paths, host paths, box names, image references, env keys and port
+//! specs here, so every SDK accepts and rejects the same inputs. Values are
+//! never trimmed: surrounding whitespace is part of the value.
+//!
+//! Failures are [`BoxliteError::InvalidArgument`] whose message ends with the
+//! stable code of the broken rule in brackets, e.g.
+//! `guest path "tmp" must be absolute (start with '/') [guest_path.relative]`.
+//! [`error_code`] extracts it. [`RULES`] lists every rule; the table in
+//! `docs/reference/validation.md` is [`rules_table`]'s output.
+
+use std::path::PathBuf;
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+
+use crate::runtime::options::{PortProtocol, PortSpec};
+use crate::runtime::types::BoxID;
+
+/// Longest accepted box name, in bytes.
+pub const MAX_BOX_NAME_LEN: usize = 128;
+
+/// A rule a validator enforces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rule {
+    /// Stable identifier, `<value>.<rule>`, carried by errors.
+    pub code: &'static str,
+    /// What the rule requires, in one sentence.
+    pub requirement: &'static str,
+}
+
+/// Every rule, grouped by the validator that enforces it.
+pub const RULES: &[Rule] = &[
+    Rule {
+        code: "guest_path.empty",
+        requirement: "A guest path must not be empty.",
+    },
+    Rule {
+        code: "guest_path.nul",
+        requirement: "A guest path must not contain NUL bytes.",
+    },
+    Rule {
+        code: "guest_path.backslash",
+        requirement: "A guest path must use `/` as its only separator; `\\` is rejected on every host.",
+    },
+    Rule {
+        code: "guest_path.relative",
+        requirement: "A guest path must be absolute (start with `/`).",
+    },
+    Rule {
+        code: "guest_path.parent",
+        requirement: "A guest path must not contain a `..` component.",
+    },
+    Rule {
+        code: "host_path.empty",
+        requirement: "A host path must not be empty.",
+    },
+    Rule {
+        code: "host_path.nul",
+        requirement: "A host path must not contain NUL bytes.",
+    },
+    Rule {
+        code: "box_name.empty",
+        requirement: "A box name must not be empty.",
+    },
+    Rule {
+        code: "box_name.too_long",
+        requirement: "A box name must be at most 128 bytes long.",
+    },
+    Rule {
+        code: "box_name.charset",
+        requirement: "A box name must start with an ASCII letter or digit and contain only ASCII letters, digits, `_`, `.` and `-`.",
+    },
+    Rule {
+        code: "box_name.looks_like_id",
+        requirement: "A box name must not have the form of a box ID (a 26-character ULID).",
+    },
+    Rule {
+        code: "image_reference.empty",
+        requirement: "An image reference must not be empty.",
+    },
+    Rule {
+        code: "image_reference.invalid",
+        requirement: "An image reference must parse as `[registry/]repository[:tag][@digest]`, with a well-formed digest.",
+    },
+    Rule {
+        code: "env_key.empty",
+        requirement: "An environment variable name must not be empty.",
+    },
+    Rule {
+        code: "env_key.equals",
+        requirement: "An environment variable name must not contain `=`.",
+    },
+    Rule {
+        code: "env_key.nul",
+        requirement: "An environment variable name must not contain NUL bytes.",
+    },
+    Rule {
+        code: "port_spec.syntax",
+        requirement: "A port spec must have the form `[hostPort:]guestPort[/protocol]`.",
+    },
+    Rule {
+        code: "port_spec.protocol",
+        requirement: "A port spec protocol must be `tcp` or `udp`, in any case.",
+    },
+    Rule {
+        code: "port_spec.range",
+        requirement: "Ports must be within 1 to 65535; omit the host port, or set it to 0 in a structured port mapping, to have one assigned.",
+    },
+];
+
+/// The code of the rule `err` reports, if it came from this module.
+pub fn error_code(err: &BoxliteError) -> Option<&'static str> {
+    let BoxliteError::InvalidArgument(message) = err else {
+        return None;
+    };
+    let (_, code) = message.strip_suffix(']')?.rsplit_once(" [")?;
+    RULES
+        .iter()
+        .map(|rule| rule.code)
+        .find(|known| *known == code)
+}
+
+/// [`RULES`] as a markdown table.
+pub fn rules_table() -> String {
+    let mut table = String::from("| Code | Requirement |\n|------|-------------|\n");
+    for rule in RULES {
+        table.push_str(&format!("| `{}` | {} |\n", rule.code, rule.requirement));
+    }
+    table
+}
+
+fn reject(code: &'static str, message: String) -> BoxliteError {
+    debug_assert!(RULES.iter().any(|rule| rule.code == code), "{code}");
+    BoxliteError::InvalidArgument(format!("{message} [{code}]"))
+}
+
+/// Validate a path inside the box.
+///
+/// Guest paths are unix-style regardless of the host OS: absolute, `/` as
+/// the only separator, and no `..` components.
+pub fn guest_path(path: &str) -> BoxliteResult<()> {
+    if path.is_empty() {
+        return Err(reject(
+            "guest_path.empty",
+            "guest path cannot be empty".into(),
+        ));
+    }
+    if path.contains('\0') {
+        return Err(reject(
+            "guest_path.nul",
+            format!("guest path {:?} contains a NUL byte", path),
+        ));
+    }
+    if let Some(component) = path.split('/').find(|c| c.contains('\\')) {
+        return Err(reject(
+            "guest_path.backslash",
+            format!(
+                "guest path {:?} contains a backslash in component {:?}; guest paths must use '/'",
+                path, component
+            ),
+        ));
+    }
+    if !path.starts_with('/') {
+        return Err(reject(
+            "guest_path.relative",
+            format!("guest path {:?} must be absolute (start with '/')", path),
+        ));
+    }
+    if path.split('/').any(|c| c == "..") {
+        return Err(reject(
+            "guest_path.parent",
+            format!("guest path {:?} contains disallowed component \"..\"", path),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate a path on the host and convert it to a native `PathBuf`, see
+/// [`normalize_host_path`](crate::litebox::normalize_host_path).
+pub fn host_path(path: &str) -> BoxliteResult<PathBuf> {
+    if path.is_empty() {
+        return Err(reject(
+            "host_path.empty",
+            "host path cannot be empty".into(),
+        ));
+    }
+    if path.contains('\0') {
+        return Err(reject(
+            "host_path.nul",
+            format!("host path {:?} contains a NUL byte", path),
+        ));
+    }
+    Ok(crate::litebox::normalize_host_path(path))
+}
+
+/// Validate the name of a new box.
+pub fn box_name(name: &str) -> BoxliteResult<()> {
+    if name.is_empty() {
+        return Err(reject("box_name.empty", "box name cannot be empty".into()));
+    }
+    if name.len() > MAX_BOX_NAME_LEN {
+        return Err(reject(
+            "box_name.too_long",
+            format!(
+                "box name {:?} is longer than {} bytes",
+                name, MAX_BOX_NAME_LEN
+            ),
+        ));
+    }
+    let mut chars = name.chars();
+    let starts_alphanumeric = chars.next().is_some_and(|c| c.is_ascii_alphanumeric());
+    if !starts_alphanumeric || !chars.all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c)) {
+        return Err(reject(
+            "box_name.charset",
+            format!(
+                "box name {:?} must start with a letter or digit and contain only letters, digits, '_', '.' and '-'",
+                name
+            ),
+        ));
+    }
+    // IDs are looked up before names, so such a name could shadow a box
+    if BoxID::is_valid(name) {
+        return Err(reject(
+            "box_name.looks_like_id",
+            format!("box name {:?} has the form of a box ID", name),
+        ));
+    }
+    Ok(())
+}
+
+/// Validate an image reference, including the digest of a pinned one.
+pub fn image_reference(reference: &str) -> BoxliteResult<()> {
+    if reference.is_empty() {
+        return Err(reject(
+            "image_reference.empty",
+            "image reference cannot be empty".into(),
+        ));
+    }
+    crate::images::parse_reference(reference)
+        .map(|_| ())
+        .map_err(|e| {
+            reject(
+                "image_reference.invalid",
+                format!("invalid image reference '{reference}': {e}"),
+            )
+        })
+}
+
+/// Validate the name of an environment variable.
+pub fn env_key(key: &str) -> BoxliteResult<()> {
+    if key.is_empty() {
+        return Err(reject("env_key.empty", "env key cannot be empty".into()));
+    }
+    if key.contains('=') {
+        return Err(reject(
+            "env_key.equals",
+            format!("env key {:?} contains '='", key),
+        ));
+    }
+    if key.contains('\0') {
+        return Err(reject(
+            "env_key.nul",
+            format!("env key {:?} contains a NUL byte", key),
+        ));
+    }
+    Ok(())
+}
+
+/// Parse a port spec: `[hostPort:]guestPort[/tcp|udp]`.
+///
+/// - `guestPort` → host port assigned at start
+/// - `hostPort:guestPort` → fixed host port
+///
+/// The protocol defaults to TCP.
+pub fn port_spec(spec: &str) -> BoxliteResult<PortSpec> {
+    let (ports, protocol) = match spec.split_once('/') {
+        Some((ports, protocol)) if protocol.eq_ignore_ascii_case("tcp") => {
+            (ports, PortProtocol::Tcp)
+        }
+        Some((ports, protocol)) if protocol.eq_ignore_ascii_case("udp") => {
+            (ports, PortProtocol::Udp)
+        }
+        Some((_, protocol)) => {
+            return Err(reject(
+                "port_spec.protocol",
+                format!(
+                    "invalid protocol {:?} in port spec {:?}; use tcp or udp",
+                    protocol, spec
+                ),
+            ));
+        }
+        None => (spec, PortProtocol::Tcp),
+    };
+    let (host_port, guest_port) = match ports.split_once(':') {
+        Some((host, guest)) => (Some(port_number(spec, host)?), port_number(spec, guest)?),
+        None => (None, port_number(spec, ports)?),
+    };
+    Ok(PortSpec {
+        host_port,
+        guest_port,
+        protocol,
+        host_ip: None,
+    })
+}
+
+/// Validate a structured port mapping. A host port of 0 means "assign one".
+pub fn port(spec: &PortSpec) -> BoxliteResult<()> {
+    if spec.guest_port == 0 {
+        return Err(reject(
+            "port_spec.range",
+            "guest port must be within 1 to 65535, got 0".into(),
+        ));
+    }
+    Ok(())
+}
+
+fn port_number(spec: &str, text: &str) -> BoxliteResult<u16> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(reject(
+            "port_spec.syntax",
+            format!(
+                "invalid port spec {:?}; use [hostPort:]guestPort[/tcp|udp]",
+                spec
+            ),
+        ));
+    }
+    match text.parse::<u16>() {
+        Ok(port) if port != 0 => Ok(port),
+        _ => Err(reject(
+            "port_spec.range",
+            format!(
+                "port {} in port spec {:?} must be within 1 to 65535",
+                text, spec
+            ),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "sha256:1111111111111111111111111111111111111111111111111111111111111111";
+
+    fn code(result: BoxliteResult<impl std::fmt::Debug>) -> &'static str {
+        let err = result.unwrap_err();
+        error_code(&err).unwrap_or_else(|| panic!("no rule code in {err}"))
+    }
+
+    #[test]
+    fn test_rule_codes_are_unique() {
+        let mut codes: Vec<&str> = RULES.iter().map(|rule| rule.code).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), RULES.len());
+    }
+
+    #[test]
+    fn test_error_code() {
+        assert_eq!(code(guest_path("tmp")), "guest_path.relative");
+        assert_eq!(
+            error_code(&BoxliteError::InvalidArgument("bad [x.y]".into())),
+            None
+        );
+        assert_eq!(
+            error_code(&BoxliteError::Config("[env_key.empty]".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_docs_match_rules() {
+        let docs = include_str!("../../docs/reference/validation.md");
+        assert!(
+            docs.contains(&rules_table()),
+            "docs/reference/validation.md is out of date; paste rules_table() output:\n{}",
+            rules_table()
+        );
+    }
+
+    /// Every path of up to three components drawn from `PARTS`, with and
+    /// without a leading `/`, is accepted exactly when the rules allow it.
+    #[test]
+    fn test_guest_path_property() {
+        const PARTS: &[&str] = &["", "a", ".", "..", "..a", "a b", "a\\b", "a\0"];
+        let mut paths = vec![String::new()];
+        for _ in 0..3 {
+            let mut longer = Vec::new();
+            for path in &paths {
+                for part in PARTS {
+                    longer.push(format!("{path}/{part}"));
+                }
+            }
+            paths.extend(longer);
+        }
+        let relative: Vec<String> = paths
+            .iter()
+            .map(|p| p.trim_start_matches('/').to_string())
+            .collect();
+        paths.extend(relative);
+
+        for path in &paths {
+            let expected = if path.is_empty() {
+                Some("guest_path.empty")
+            } else if path.contains('\0') {
+                Some("guest_path.nul")
+            } else if path.contains('\\') {
+                Some("guest_path.backslash")
+            } else if !path.starts_with('/') {
+                Some("guest_path.relative")
+            } else if path.split('/').any(|c| c == "..") {
+                Some("guest_path.parent")
+            } else {
+                None
+            };
+            match expected {
+                None => assert!(guest_path(path).is_ok(), "{path:?}"),
+                Some(expected) => assert_eq!(code(guest_path(path)), expected, "{path:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_host_path() {
+        assert!(host_path("relative/dir").is_ok());
+        assert!(host_path(" ").is_ok());
+        assert_eq!(code(host_path("")), "host_path.empty");
+        assert_eq!(code(host_path("/tmp\0x")), "host_path.nul");
+    }
+
+    /// Every ASCII character is accepted as the first and as a later
+    /// character of a box name exactly when the rules allow it.
+    #[test]
+    fn test_box_name_property() {
+        for byte in 0u8..128 {
+            let c = byte as char;
+            let alphanumeric = c.is_ascii_alphanumeric();
+
+            let first = c.to_string();
+            assert_eq!(box_name(&first).is_ok(), alphanumeric, "{first:?}");
+
+            let later = format!("a{c}");
+            assert_eq!(
+                box_name(&later).is_ok(),
+                alphanumeric || "_.-".contains(c),
+                "{later:?}"
+            );
+        }
+        assert_eq!(code(box_name("")), "box_name.empty");
+        assert_eq!(code(box_name("é")), "box_name.charset");
+        assert_eq!(code(box_name(" web")), "box_name.charset");
+        assert!(box_name(&"a".repeat(MAX_BOX_NAME_LEN)).is_ok());
+        assert_eq!(
+            code(box_name(&"a".repeat(MAX_BOX_NAME_LEN + 1))),
+            "box_name.too_long"
+        );
+        assert_eq!(
+            code(box_name(BoxID::new().as_str())),
+            "box_name.looks_like_id"
+        );
+    }
+
+    #[test]
+    fn test_image_reference_rejects_bad_digest() {
+        assert!(image_reference(&format!("alpine@{DIGEST}")).is_ok());
+        assert!(image_reference("alpine:latest").is_ok());
+        assert!(image_reference("ghcr.io/foo/bar:v1").is_ok());
+
+        assert_eq!(code(image_reference("")), "image_reference.empty");
+        // Truncated digest
+        assert_eq!(
+            code(image_reference("alpine@sha256:1234")),
+            "image_reference.invalid"
+        );
+        // Malformed digest
+        assert!(image_reference("alpine@sha256:XYZ").is_err());
+    }
+
+    /// Every ASCII character is accepted inside an env key exactly when the
+    /// rules allow it.
+    #[test]
+    fn test_env_key_property() {
+        for byte in 0u8..128 {
+            let key = format!("A{}B", byte as char);
+            let expected = match byte {
+                b'=' => Some("env_key.equals"),
+                0 => Some("env_key.nul"),
+                _ => None,
+            };
+            match expected {
+                None => assert!(env_key(&key).is_ok(), "{key:?}"),
+                Some(expected) => assert_eq!(code(env_key(&key)), expected, "{key:?}"),
+            }
+        }
+        assert_eq!(code(env_key("")), "env_key.empty");
+    }
+
+    #[test]
+    fn test_port_spec_forms() {
+        let spec = port_spec("8080:80/udp").unwrap();
+        assert_eq!(spec.host_port, Some(8080));
+        assert_eq!(spec.guest_port, 80);
+        assert_eq!(spec.protocol, PortProtocol::Udp);
+
+        let spec = port_spec("443/TCP").unwrap();
+        assert_eq!(spec.host_port, None);
+        assert_eq!(spec.guest_port, 443);
+        assert_eq!(spec.protocol, PortProtocol::Tcp);
+    }
+
+    /// Every combination of host port, guest port and protocol drawn from a
+    /// sample of valid and invalid values is classified by the rules.
+    #[test]
+    fn test_port_spec_property() {
+        const PORTS: &[(&str, Option<&str>)] = &[
+            ("1", None),
+            ("80", None),
+            ("65535", None),
+            ("0", Some("port_spec.range")),
+            ("65536", Some("port_spec.range")),
+            ("", Some("port_spec.syntax")),
+            ("8o", Some("port_spec.syntax")),
+            (" 80", Some("port_spec.syntax")),
+            ("-1", Some("port_spec.syntax")),
+        ];
+        const PROTOCOLS: &[(&str, Option<&str>)] = &[
+            ("", None),
+            ("/tcp", None),
+            ("/Udp", None),
+            ("/sctp", Some("port_spec.protocol")),
+            ("/", Some("port_spec.protocol")),
+        ];
+
+        for (guest, guest_error) in PORTS {
+            for (protocol, protocol_error) in PROTOCOLS {
+                let spec = format!("{guest}{protocol}");
+                match protocol_error.or(*guest_error) {
+                    None => assert!(port_spec(&spec).is_ok(), "{spec:?}"),
+                    Some(expected) => assert_eq!(code(port_spec(&spec)), expected, "{spec:?}"),
+                }
+                for (host, host_error) in PORTS {
+                    let spec = format!("{host}:{guest}{protocol}");
+                    match protocol_error.or(*host_error).or(*guest_error) {
+                        None => assert!(port_spec(&spec).is_ok(), "{spec:?}"),
+                        Some(expected) => {
+                            assert_eq!(code(port_spec(&spec)), expected, "{spec:?}")
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq!(code(port_spec("1:2:3")), "port_spec.syntax");
+    }
+
+    #[test]
+    fn test_structured_port() {
+        let mut spec = port_spec("80").unwrap();
+        spec.host_port = Some(0);
+        assert!(port(&spec).is_ok());
+        spec.guest_port = 0;
+        assert_eq!(code(port(&spec)), "port_spec.range");
+    }
+}
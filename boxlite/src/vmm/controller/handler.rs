@@ -1,6 +1,7 @@
 //! VmmHandler - Runtime operations on a running VM.
 
 use super::VmmMetrics;
+use super::watchdog::Keepalive;
 use boxlite_shared::BoxliteResult;
 
 /// Trait for runtime operations on a running VM.
@@ -27,4 +28,9 @@ pub trait VmmHandler: Send {
 
     /// Get the process ID of the running VM.
     fn pid(&self) -> u32;
+
+    /// Tie the VM's lifetime to `keepalive`: once it is dropped (with the
+    /// handler, or when this process exits) the VM shuts down, as if it
+    /// had been spawned here with `detach = false`.
+    fn set_keepalive(&mut self, keepalive: Keepalive);
 }
@@ -35,6 +35,7 @@ pub struct ShimHandler {
     /// POLLHUP to the shim and triggering graceful shutdown.
     /// Defense-in-depth: even if `stop()` is never called, dropping the
     /// handler closes this, triggering shim cleanup automatically.
+    /// Attached handlers get one when the box is adopted.
     #[allow(dead_code)]
     keepalive: Option<watchdog::Keepalive>,
     /// Shared System instance for CPU metrics calculation across calls.
@@ -151,10 +152,17 @@ impl VmmHandlerTrait for ShimHandler {
                 // result == 0 means still running
 
                 if start.elapsed().as_millis() > GRACEFUL_SHUTDOWN_TIMEOUT_MS as u128 {
-                    // Timeout - force kill
+                    // Timeout - force kill, and wait for the exit as the
+                    // spawned path does so a restart never races the old shim
                     unsafe {
                         libc::kill(self.pid as i32, libc::SIGKILL);
                     }
+                    let killed = std::time::Instant::now();
+                    while crate::util::is_process_alive(self.pid)
+                        && killed.elapsed().as_millis() < GRACEFUL_SHUTDOWN_TIMEOUT_MS as u128
+                    {
+                        std::thread::sleep(std::time::Duration::from_millis(50));
+                    }
                     return Ok(());
                 }
 
@@ -196,6 +204,10 @@ impl VmmHandlerTrait for ShimHandler {
     fn is_running(&self) -> bool {
         crate::util::is_process_alive(self.pid)
     }
+
+    fn set_keepalive(&mut self, keepalive: watchdog::Keepalive) {
+        self.keepalive = Some(keepalive);
+    }
 }
 
 // ============================================================================
@@ -215,6 +227,8 @@ pub struct ShimController {
     options: crate::runtime::options::BoxOptions,
     /// Box filesystem layout (provides paths for stderr, sockets, etc.)
     layout: BoxFilesystemLayout,
+    /// Whether the pre-spawn setup already ran (see [`Self::prepare`]).
+    prepared: bool,
 }
 
 impl ShimController {
@@ -251,8 +265,37 @@ impl ShimController {
             box_id,
             options,
             layout,
+            prepared: false,
         })
     }
+
+    /// Run the pre-spawn setup ahead of [`VmmController::start`].
+    ///
+    /// Sandbox preflight, cgroup creation and the shim copy depend only on
+    /// the box layout, so box start runs them while the rootfs is built.
+    /// Blocking: call from a blocking context.
+    pub fn prepare(&mut self) -> BoxliteResult<()> {
+        let started = Instant::now();
+        self.spawner().prepare()?;
+        self.prepared = true;
+        tracing::debug!(
+            box_id = %self.box_id,
+            duration_ms = started.elapsed().as_millis(),
+            "Shim spawn prepared"
+        );
+        Ok(())
+    }
+
+    fn spawner(&self) -> ShimSpawner<'_> {
+        ShimSpawner::new(
+            &self.binary_path,
+            self.engine_type,
+            &self.layout,
+            self.box_id.as_str(),
+            &self.options,
+        )
+        .with_prepared(self.prepared)
+    }
 }
 
 #[async_trait::async_trait]
@@ -283,31 +326,42 @@ impl VmmController for ShimController {
             // VM configuration
             cpus: config.cpus,
             memory_mib: config.memory_mib,
+            priority: config.priority,
             fs_shares: config.fs_shares.clone(),
             block_devices: config.block_devices.clone(),
             guest_entrypoint,
             transport: config.transport.clone(),
             ready_transport: config.ready_transport.clone(),
             guest_rootfs: config.guest_rootfs.clone(),
+            network_mode: config.network_mode,
             network_config: config.network_config.clone(), // Pass port mappings to subprocess (shim creates gvproxy)
             network_backend_endpoint: None, // Will be populated by shim (not serialized)
             home_dir: config.home_dir.clone(),
             console_output: config.console_output.clone(),
             exit_file: config.exit_file.clone(),
             detach: config.detach,
+            shutdown_grace_period: config.shutdown_grace_period,
+            idle_policy: config.idle_policy,
         };
 
         // Serialize the config for passing to subprocess
         let config_json = serde_json::to_string(&serializable_config)
             .map_err(|e| BoxliteError::Engine(format!("Failed to serialize config: {}", e)))?;
 
-        // Clean up stale socket file if it exists (defense in depth)
-        // Only relevant for Unix sockets
+        // A SIGKILLed shim leaves its sockets (box.sock, gvproxy's net.sock)
+        // behind, and the new shim would fail to bind them. Remove the stale
+        // ones; a socket that is still live means another shim owns the box.
+        let mut sockets = self.layout.socket_paths();
         if let boxlite_shared::Transport::Unix { socket_path } = &config.transport
-            && socket_path.exists()
+            && !sockets.contains(socket_path)
         {
-            tracing::warn!("Removing stale Unix socket: {}", socket_path.display());
-            let _ = std::fs::remove_file(socket_path);
+            sockets.push(socket_path.clone());
+        }
+        if let Some(live) = crate::util::remove_stale_sockets(&sockets).first() {
+            return Err(BoxliteError::InvalidState(format!(
+                "socket {} is in use by another process; is the box already running?",
+                live.display()
+            )));
         }
 
         // Spawn Box subprocess with piped stdio
@@ -321,14 +375,7 @@ impl VmmController for ShimController {
 
         // Measure subprocess spawn time
         let shim_spawn_start = Instant::now();
-        let spawner = ShimSpawner::new(
-            &self.binary_path,
-            self.engine_type,
-            &self.layout,
-            self.box_id.as_str(),
-            &self.options,
-        );
-        let spawned = spawner.spawn(&config_json, config.detach)?;
+        let spawned = self.spawner().spawn(&config_json, config.detach)?;
         // spawn_duration: time to create Box subprocess
         let shim_spawn_duration = shim_spawn_start.elapsed();
 
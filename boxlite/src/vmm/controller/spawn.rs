@@ -5,12 +5,14 @@ use std::{
     process::{Child, Stdio},
 };
 
-use crate::jailer::{Jail, JailerBuilder};
+use crate::jailer::{Jail, JailerBuilder, shim_copy};
 use crate::runtime::layout::BoxFilesystemLayout;
 use crate::runtime::options::BoxOptions;
+#[cfg(feature = "local")]
 use crate::util::configure_library_env;
 use crate::vmm::VmmKind;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+#[cfg(feature = "local")]
 use libkrun_sys::krun_create_ctx;
 
 use super::watchdog;
@@ -41,6 +43,8 @@ pub struct ShimSpawner<'a> {
     layout: &'a BoxFilesystemLayout,
     box_id: &'a str,
     options: &'a BoxOptions,
+    /// Whether [`prepare()`](Self::prepare) already ran for this box.
+    prepared: bool,
 }
 
 impl<'a> ShimSpawner<'a> {
@@ -57,9 +61,39 @@ impl<'a> ShimSpawner<'a> {
             layout,
             box_id,
             options,
+            prepared: false,
         }
     }
 
+    /// Skip the pre-spawn setup in [`spawn()`](Self::spawn), done earlier
+    /// by [`prepare()`](Self::prepare).
+    pub fn with_prepared(mut self, prepared: bool) -> Self {
+        self.prepared = prepared;
+        self
+    }
+
+    /// Pre-spawn setup that needs only the box layout, not the VM config.
+    ///
+    /// Runs the sandbox preflight and cgroup creation, and copies the shim
+    /// into the box, so box start can do it while the rootfs is still being
+    /// built. `spawn()` then only builds and launches the command.
+    pub fn prepare(&self) -> BoxliteResult<()> {
+        let jail = JailerBuilder::new()
+            .with_box_id(self.box_id)
+            .with_layout(self.layout.clone())
+            .with_security(self.options.advanced.security.clone())
+            .with_volumes(self.options.volumes.clone())
+            .with_cpu_weight(self.options.cpu_weight())
+            .build()?;
+        jail.prepare()?;
+
+        // Warm the shim copy; the copy in `command()` is then a no-op
+        if self.options.advanced.security.jailer_enabled {
+            shim_copy::copy_shim_to_box(self.binary_path, self.layout.root())?;
+        }
+        Ok(())
+    }
+
     /// Spawn the shim subprocess with jailer isolation and optional watchdog.
     ///
     /// When `detach` is false, creates a watchdog pipe so the shim detects
@@ -81,7 +115,8 @@ impl<'a> ShimSpawner<'a> {
             .with_box_id(self.box_id)
             .with_layout(self.layout.clone())
             .with_security(self.options.advanced.security.clone())
-            .with_volumes(self.options.volumes.clone());
+            .with_volumes(self.options.volumes.clone())
+            .with_cpu_weight(self.options.cpu_weight());
 
         if let Some(ref setup) = child_setup {
             builder = builder.with_preserved_fd(setup.raw_fd(), watchdog::PIPE_FD);
@@ -90,7 +125,9 @@ impl<'a> ShimSpawner<'a> {
         let jail = builder.build()?;
 
         // 3. Setup pre-spawn isolation (cgroups on Linux, no-op on macOS)
-        jail.prepare()?;
+        if !self.prepared {
+            jail.prepare()?;
+        }
 
         // 4. Build isolated command (includes pre_exec hook)
         let shim_args = self.build_shim_args(config_json);
@@ -141,6 +178,7 @@ impl<'a> ShimSpawner<'a> {
         }
 
         // Set library search paths for bundled dependencies
+        #[cfg(feature = "local")]
         configure_library_env(cmd, krun_create_ctx as *const libc::c_void);
     }
 
@@ -7,14 +7,38 @@
 //! This is zero-latency, tamper-proof (kernel FDs), and works across
 //! PID/mount namespaces — the gold standard used by s6, containerd-shim,
 //! runc, crun, and conmon.
+//!
+//! A process that did not spawn the shim can take over the same role with a
+//! named pipe: it creates [`OWNER_FIFO`] in the box directory, holds the write
+//! end, and sends [`OWNER_SIGNAL`]. The shim opens the read end, removes the
+//! FIFO to acknowledge, and watches it like the inherited pipe.
+//!
+//! The owner can also let go of the box: it creates [`RELEASE_FILE`] in the
+//! box directory and sends [`OWNER_SIGNAL`]. The shim stops acting on the
+//! hangup of every pipe it watches so far, and removes the file to
+//! acknowledge. A shim that predates releases finds no FIFO, logs the failed
+//! adoption and keeps its watchdog.
 
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::ffi::CString;
 use std::os::fd::{FromRawFd, OwnedFd, RawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
 
 /// Well-known FD for the watchdog pipe in the shim process.
 /// Pre-exec dup2s the inherited pipe read end to this position.
 pub const PIPE_FD: i32 = 3;
 
+/// Name of the adoption FIFO, in the box directory.
+pub const OWNER_FIFO: &str = "owner.fifo";
+
+/// Name of the release request file, in the box directory.
+pub const RELEASE_FILE: &str = "owner.release";
+
+/// Signal asking the shim to act on an ownership request: drop its watchdog
+/// if [`RELEASE_FILE`] exists, else open [`OWNER_FIFO`] and watch it.
+pub const OWNER_SIGNAL: i32 = libc::SIGUSR1;
+
 /// Parent-side keepalive handle.
 ///
 /// While this exists, the shim's watchdog thread blocks on poll().
@@ -75,6 +99,93 @@ pub fn create() -> BoxliteResult<(Keepalive, ChildSetup)> {
     ))
 }
 
+/// Create the adoption FIFO at `path` and hold its write end.
+///
+/// Any stale FIFO at `path` is replaced. The returned keepalive works like the
+/// one from [`create`] once the shim has opened the read end (see
+/// [`open_owner_fifo`]).
+pub fn create_owner_fifo(path: &Path) -> BoxliteResult<Keepalive> {
+    let fifo_err = |what: &str| {
+        BoxliteError::Engine(format!(
+            "Failed to {} owner FIFO {}: {}",
+            what,
+            path.display(),
+            std::io::Error::last_os_error()
+        ))
+    };
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| BoxliteError::Engine(format!("Invalid FIFO path {}", path.display())))?;
+
+    let _ = std::fs::remove_file(path);
+    // SAFETY: c_path is a valid NUL-terminated path.
+    if unsafe { libc::mkfifo(c_path.as_ptr(), 0o600) } != 0 {
+        return Err(fifo_err("create"));
+    }
+
+    // A non-blocking write open needs a reader. Hold one just long enough
+    // to open the write end; only the shim's reader watches for hangup.
+    // SAFETY: c_path is a valid NUL-terminated path.
+    let reader = unsafe {
+        libc::open(
+            c_path.as_ptr(),
+            libc::O_RDONLY | libc::O_NONBLOCK | libc::O_CLOEXEC,
+        )
+    };
+    if reader < 0 {
+        return Err(fifo_err("open"));
+    }
+    // SAFETY: reader is a valid FD we own.
+    let _reader = unsafe { OwnedFd::from_raw_fd(reader) };
+    // SAFETY: c_path is a valid NUL-terminated path.
+    let writer = unsafe {
+        libc::open(
+            c_path.as_ptr(),
+            libc::O_WRONLY | libc::O_NONBLOCK | libc::O_CLOEXEC,
+        )
+    };
+    if writer < 0 {
+        return Err(fifo_err("open"));
+    }
+
+    Ok(Keepalive {
+        // SAFETY: writer is a valid write-end FD we own.
+        _pipe_write: unsafe { OwnedFd::from_raw_fd(writer) },
+    })
+}
+
+/// Open the read end of the adoption FIFO at `path` (shim side).
+///
+/// Removes the FIFO once open, which tells the adopting process the shim
+/// is now watching it.
+pub fn open_owner_fifo(path: &Path) -> BoxliteResult<OwnedFd> {
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| BoxliteError::Engine(format!("Invalid FIFO path {}", path.display())))?;
+    // SAFETY: c_path is a valid NUL-terminated path.
+    let fd = unsafe {
+        libc::open(
+            c_path.as_ptr(),
+            libc::O_RDONLY | libc::O_NONBLOCK | libc::O_CLOEXEC,
+        )
+    };
+    if fd < 0 {
+        return Err(BoxliteError::Engine(format!(
+            "Failed to open owner FIFO {}: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        )));
+    }
+    // SAFETY: fd is a valid FD we own.
+    let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+    std::fs::remove_file(path).map_err(|e| {
+        BoxliteError::Engine(format!(
+            "Failed to remove owner FIFO {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    Ok(fd)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -144,4 +255,39 @@ mod tests {
 
         drop(child_setup);
     }
+
+    #[test]
+    fn test_owner_fifo_hangup_on_keepalive_drop() {
+        use std::os::fd::AsRawFd;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(OWNER_FIFO);
+
+        let keepalive = create_owner_fifo(&path).expect("FIFO creation should succeed");
+        let reader = open_owner_fifo(&path).expect("FIFO open should succeed");
+        assert!(!path.exists(), "shim side removes the FIFO once open");
+
+        let mut pollfd = libc::pollfd {
+            fd: reader.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // Writer still held: nothing to report
+        assert_eq!(unsafe { libc::poll(&mut pollfd, 1, 50) }, 0);
+
+        drop(keepalive);
+        let ret = unsafe { libc::poll(&mut pollfd, 1, 100) };
+        assert_eq!(ret, 1, "poll should report the hangup");
+        assert_ne!(pollfd.revents & libc::POLLHUP, 0);
+    }
+
+    #[test]
+    fn test_create_owner_fifo_replaces_stale_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(OWNER_FIFO);
+        std::fs::write(&path, b"stale").unwrap();
+
+        let _keepalive = create_owner_fifo(&path).expect("stale file should be replaced");
+        let _reader = open_owner_fifo(&path).unwrap();
+    }
 }
@@ -0,0 +1,451 @@
+//! Crash diagnostics written by the shim next to its exit file.
+//!
+//! During normal operation the shim appends the names of the operations it
+//! performs (lifecycle steps, its guest RPCs) to an [`OperationLog`], a
+//! fixed-size ring buffer that costs a few atomic stores per entry. When the
+//! shim panics or receives a fatal signal, a [`DiagnosticsCollector`]
+//! snapshots the ring buffer and the open fd count and writes them as JSON
+//! to [`diagnostics_path`]. The host reads them back as [`CrashDiagnostics`].
+//!
+//! Collection never allocates and only uses async-signal-safe syscalls
+//! (`fcntl`, `getrlimit`, `open`, `write`), so it can run in a signal
+//! handler. The output is bounded by [`MAX_DIAGNOSTICS_BYTES`].
+//!
+//! ## File Format (JSON)
+//!
+//! ```json
+//! {"recent_operations":[{"name":"instance.enter","at_ms":1714564800123}],
+//!  "vcpu_states":null,"open_fds":42,"truncated":false}
+//! ```
+
+use std::ffi::CStr;
+use std::fmt::{self, Write as _};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicPtr, AtomicU64, AtomicUsize, Ordering, fence};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of recent operations an [`OperationLog`] keeps.
+pub const OPERATION_LOG_CAPACITY: usize = 32;
+
+/// Upper bound on the size of a diagnostics file.
+pub const MAX_DIAGNOSTICS_BYTES: usize = 4096;
+
+/// Highest fd probed when counting open fds.
+const MAX_PROBED_FD: libc::c_int = 65536;
+
+/// Path of the diagnostics file that accompanies `exit_file`.
+pub fn diagnostics_path(exit_file: &Path) -> PathBuf {
+    let mut name = exit_file.as_os_str().to_owned();
+    name.push(".diagnostics");
+    PathBuf::from(name)
+}
+
+/// Diagnostics the shim recorded when it crashed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CrashDiagnostics {
+    /// Most recent shim operations, oldest first.
+    pub recent_operations: Vec<RecordedOperation>,
+    /// vCPU states reported by the VMM. `None` when the engine can't report
+    /// them, which is currently always the case for libkrun.
+    pub vcpu_states: Option<Vec<String>>,
+    /// Open file descriptors in the shim at the time of the crash.
+    pub open_fds: Option<u64>,
+    /// Whether operations were dropped to stay within the size limit.
+    pub truncated: bool,
+}
+
+impl CrashDiagnostics {
+    /// Parse diagnostics from a JSON file.
+    ///
+    /// Returns `None` if the file doesn't exist, is empty, or is invalid.
+    pub fn from_file(path: &Path) -> Option<Self> {
+        let content = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+/// One entry of an [`OperationLog`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedOperation {
+    pub name: String,
+    /// Wall-clock time of the operation, in milliseconds since the epoch.
+    pub at_ms: u64,
+}
+
+struct Slot {
+    /// Sequence number of the entry, 0 while it is being written.
+    seq: AtomicU64,
+    name_ptr: AtomicPtr<u8>,
+    name_len: AtomicUsize,
+    at_ms: AtomicU64,
+}
+
+impl Slot {
+    const fn new() -> Self {
+        Self {
+            seq: AtomicU64::new(0),
+            name_ptr: AtomicPtr::new(std::ptr::null_mut()),
+            name_len: AtomicUsize::new(0),
+            at_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+/// Lock-free ring buffer of the last [`OPERATION_LOG_CAPACITY`] operations.
+///
+/// Meant to live in a `static`. Each slot is a small seqlock, so a reader
+/// in a signal handler never sees a half-written entry.
+pub struct OperationLog {
+    next: AtomicU64,
+    slots: [Slot; OPERATION_LOG_CAPACITY],
+}
+
+impl OperationLog {
+    pub const fn new() -> Self {
+        Self {
+            next: AtomicU64::new(0),
+            slots: [const { Slot::new() }; OPERATION_LOG_CAPACITY],
+        }
+    }
+
+    /// Append an operation, overwriting the oldest one when full.
+    pub fn record(&self, name: &'static str) {
+        let at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let seq = self.next.fetch_add(1, Ordering::Relaxed) + 1;
+        let slot = &self.slots[(seq % OPERATION_LOG_CAPACITY as u64) as usize];
+
+        slot.seq.store(0, Ordering::Relaxed);
+        fence(Ordering::Release);
+        slot.name_ptr
+            .store(name.as_ptr().cast_mut(), Ordering::Relaxed);
+        slot.name_len.store(name.len(), Ordering::Relaxed);
+        slot.at_ms.store(at_ms, Ordering::Relaxed);
+        slot.seq.store(seq, Ordering::Release);
+    }
+
+    /// Consistent entries, oldest first, without allocating.
+    fn snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot {
+            entries: [(0, "", 0); OPERATION_LOG_CAPACITY],
+            len: 0,
+        };
+        for slot in &self.slots {
+            let seq = slot.seq.load(Ordering::Acquire);
+            let ptr = slot.name_ptr.load(Ordering::Relaxed);
+            let len = slot.name_len.load(Ordering::Relaxed);
+            let at_ms = slot.at_ms.load(Ordering::Relaxed);
+            fence(Ordering::Acquire);
+            if seq == 0 || ptr.is_null() || slot.seq.load(Ordering::Relaxed) != seq {
+                continue;
+            }
+            // SAFETY: the seqlock check guarantees ptr and len were stored
+            // together by `record`, from a `&'static str`.
+            let name =
+                unsafe { std::str::from_utf8_unchecked(std::slice::from_raw_parts(ptr, len)) };
+            snapshot.entries[snapshot.len] = (seq, name, at_ms);
+            snapshot.len += 1;
+        }
+        snapshot.entries[..snapshot.len].sort_unstable_by_key(|(seq, _, _)| *seq);
+        snapshot
+    }
+}
+
+impl Default for OperationLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Snapshot {
+    entries: [(u64, &'static str, u64); OPERATION_LOG_CAPACITY],
+    len: usize,
+}
+
+/// Collects crash diagnostics from an [`OperationLog`] and the process.
+#[derive(Clone, Copy)]
+pub struct DiagnosticsCollector {
+    operations: &'static OperationLog,
+}
+
+impl DiagnosticsCollector {
+    pub fn new(operations: &'static OperationLog) -> Self {
+        Self { operations }
+    }
+
+    /// Render the diagnostics as JSON into `buf`, returning the length.
+    ///
+    /// Async-signal-safe. The oldest operations are dropped (and
+    /// `truncated` set) when they don't fit.
+    pub fn render(&self, buf: &mut [u8]) -> usize {
+        let snapshot = self.operations.snapshot();
+        let entries = &snapshot.entries[..snapshot.len];
+
+        let mut tail = [0u8; 96];
+        let mut tail_buf = JsonBuf::new(&mut tail);
+        let _ = write!(tail_buf, "],\"vcpu_states\":null,\"open_fds\":");
+        let _ = match count_open_fds() {
+            Some(count) => write!(tail_buf, "{count}"),
+            None => write!(tail_buf, "null"),
+        };
+        let _ = write!(tail_buf, ",\"truncated\":");
+        let tail_len = tail_buf.len;
+
+        // Newest entries that fit, with room left for the tail
+        let reserve = tail_len + "false}".len();
+        let head = "{\"recent_operations\":[";
+        if buf.len() < head.len() + reserve {
+            return 0;
+        }
+        let mut budget = buf.len().saturating_sub(head.len() + reserve);
+        let mut first = entries.len();
+        while first > 0 {
+            let size = entry_json_len(entries[first - 1]) + usize::from(first < entries.len());
+            if size > budget {
+                break;
+            }
+            budget -= size;
+            first -= 1;
+        }
+
+        let mut out = JsonBuf::new(buf);
+        if !out.push(head.as_bytes()) {
+            return 0;
+        }
+        for (i, &entry) in entries[first..].iter().enumerate() {
+            if i > 0 {
+                out.push(b",");
+            }
+            write_entry(&mut out, entry);
+        }
+        out.push(&tail[..tail_len]);
+        out.push(if first > 0 { b"true}" } else { b"false}" });
+        out.len
+    }
+
+    /// Write the diagnostics to `path`, truncating it first.
+    ///
+    /// Async-signal-safe: the path is a pre-built C string and the JSON is
+    /// rendered on the stack.
+    pub fn write_to(&self, path: &CStr) {
+        let mut buf = [0u8; MAX_DIAGNOSTICS_BYTES];
+        let len = self.render(&mut buf);
+        write_file(path, &buf[..len]);
+    }
+}
+
+/// Write `data` to `path` with raw syscalls (async-signal-safe).
+pub fn write_file(path: &CStr, data: &[u8]) {
+    unsafe {
+        let fd = libc::open(
+            path.as_ptr(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC | libc::O_CLOEXEC,
+            0o644,
+        );
+        if fd < 0 {
+            return;
+        }
+        let mut written = 0;
+        while written < data.len() {
+            let n = libc::write(fd, data[written..].as_ptr().cast(), data.len() - written);
+            if n <= 0 {
+                break;
+            }
+            written += n as usize;
+        }
+        libc::close(fd);
+    }
+}
+
+/// Count open fds by probing each one with `fcntl` (async-signal-safe).
+fn count_open_fds() -> Option<u64> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        return None;
+    }
+    let max = limit.rlim_cur.min(MAX_PROBED_FD as libc::rlim_t) as libc::c_int;
+    let open = (0..max)
+        .filter(|&fd| unsafe { libc::fcntl(fd, libc::F_GETFD) } != -1)
+        .count();
+    Some(open as u64)
+}
+
+fn write_entry(out: &mut JsonBuf<'_>, (_, name, at_ms): (u64, &str, u64)) {
+    out.push(b"{\"name\":\"");
+    for &byte in name.as_bytes() {
+        write_escaped(out, byte);
+    }
+    let _ = write!(out, "\",\"at_ms\":{at_ms}}}");
+}
+
+/// Length of an entry as written by [`write_entry`].
+fn entry_json_len(entry: (u64, &str, u64)) -> usize {
+    let mut scratch = [0u8; 512];
+    let mut counter = JsonBuf::new(&mut scratch);
+    write_entry(&mut counter, entry);
+    if counter.overflowed {
+        usize::MAX / 2
+    } else {
+        counter.len
+    }
+}
+
+fn write_escaped(out: &mut JsonBuf<'_>, byte: u8) {
+    match byte {
+        b'"' => {
+            out.push(b"\\\"");
+        }
+        b'\\' => {
+            out.push(b"\\\\");
+        }
+        0x00..=0x1f => {
+            let _ = write!(out, "\\u{:04x}", byte);
+        }
+        _ => {
+            out.push(&[byte]);
+        }
+    }
+}
+
+/// Fixed-capacity byte buffer. Writes that don't fit are dropped whole.
+struct JsonBuf<'a> {
+    buf: &'a mut [u8],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<'a> JsonBuf<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self {
+            buf,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) -> bool {
+        let end = self.len + bytes.len();
+        if end > self.buf.len() {
+            self.overflowed = true;
+            return false;
+        }
+        self.buf[self.len..end].copy_from_slice(bytes);
+        self.len = end;
+        true
+    }
+}
+
+impl fmt::Write for JsonBuf<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.push(s.as_bytes()) {
+            Ok(())
+        } else {
+            Err(fmt::Error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(collector: &DiagnosticsCollector, buf: &mut [u8]) -> CrashDiagnostics {
+        let len = collector.render(buf);
+        serde_json::from_slice(&buf[..len]).unwrap()
+    }
+
+    fn names(diagnostics: &CrashDiagnostics) -> Vec<&str> {
+        diagnostics
+            .recent_operations
+            .iter()
+            .map(|op| op.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_render_lists_operations_oldest_first() {
+        static LOG: OperationLog = OperationLog::new();
+        LOG.record("engine.create");
+        LOG.record("instance.enter");
+
+        let collector = DiagnosticsCollector::new(&LOG);
+        let diagnostics = parse(&collector, &mut [0u8; MAX_DIAGNOSTICS_BYTES]);
+        assert_eq!(names(&diagnostics), ["engine.create", "instance.enter"]);
+        assert!(diagnostics.recent_operations[0].at_ms > 0);
+        assert!(diagnostics.open_fds.unwrap() >= 3);
+        assert_eq!(diagnostics.vcpu_states, None);
+        assert!(!diagnostics.truncated);
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_newest_operations() {
+        static LOG: OperationLog = OperationLog::new();
+        const NAMES: [&str; 3] = ["a", "b", "c"];
+        for i in 0..OPERATION_LOG_CAPACITY + 5 {
+            LOG.record(NAMES[i % NAMES.len()]);
+        }
+
+        let diagnostics = parse(
+            &DiagnosticsCollector::new(&LOG),
+            &mut [0u8; MAX_DIAGNOSTICS_BYTES],
+        );
+        assert_eq!(diagnostics.recent_operations.len(), OPERATION_LOG_CAPACITY);
+        // The oldest surviving entry is number 5 of the sequence
+        assert_eq!(
+            diagnostics.recent_operations[0].name,
+            NAMES[5 % NAMES.len()]
+        );
+    }
+
+    #[test]
+    fn test_render_is_bounded_and_valid_when_truncated() {
+        static LOG: OperationLog = OperationLog::new();
+        for _ in 0..OPERATION_LOG_CAPACITY {
+            LOG.record("guest.shutdown");
+        }
+        LOG.record("needs \"escaping\"\n");
+
+        let mut buf = [0u8; 256];
+        let diagnostics = parse(&DiagnosticsCollector::new(&LOG), &mut buf);
+        assert!(diagnostics.truncated);
+        assert!(!diagnostics.recent_operations.is_empty());
+        assert_eq!(
+            diagnostics.recent_operations.last().unwrap().name,
+            "needs \"escaping\"\n"
+        );
+    }
+
+    #[test]
+    fn test_write_to_and_read_back() {
+        static LOG: OperationLog = OperationLog::new();
+        LOG.record("shim.start");
+
+        let dir = tempfile::tempdir().unwrap();
+        let exit_file = dir.path().join("exit");
+        let path = diagnostics_path(&exit_file);
+        assert_eq!(path, dir.path().join("exit.diagnostics"));
+
+        // Stale content is replaced
+        std::fs::write(&path, "x".repeat(MAX_DIAGNOSTICS_BYTES * 2)).unwrap();
+        let c_path = std::ffi::CString::new(path.to_str().unwrap()).unwrap();
+        DiagnosticsCollector::new(&LOG).write_to(&c_path);
+
+        let diagnostics = CrashDiagnostics::from_file(&path).unwrap();
+        assert_eq!(names(&diagnostics), ["shim.start"]);
+        assert!(std::fs::metadata(&path).unwrap().len() <= MAX_DIAGNOSTICS_BYTES as u64);
+    }
+
+    #[test]
+    fn test_from_file_empty_is_none() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exit.diagnostics");
+        std::fs::write(&path, "").unwrap();
+        assert!(CrashDiagnostics::from_file(&path).is_none());
+    }
+}
@@ -1,7 +1,8 @@
-//! Exit information for shim process crashes.
+//! Exit information for shim process crashes and idle stops.
 //!
 //! Provides structured JSON format for crash diagnostics.
-//! Written by shim signal/panic handlers, read by guest_connect.
+//! Written by shim signal/panic handlers and by idle auto-stop, read by
+//! guest_connect.
 //!
 //! ## Exit File Format (JSON)
 //!
@@ -15,17 +16,27 @@
 //! {"exit_code":101,"type":"panic","message":"panic message","location":"file.rs:42:5"}
 //! ```
 //!
+//! Idle auto-stop (see [`super::idle`]):
+//! ```json
+//! {"exit_code":0,"type":"idle-stopped","idle_secs":7200}
+//! ```
+//!
 //! Note: Stderr content is captured separately in shim.stderr file (not embedded here).
+//! Crash diagnostics go to a separate file next to the exit file, see
+//! [`super::crash_diagnostics`].
 
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 
+use super::crash_diagnostics::{CrashDiagnostics, diagnostics_path};
+
 /// Exit information written to the exit file as JSON.
 ///
-/// Three variants for different exit types:
+/// Four variants for different exit types:
 /// - `Signal`: Process killed by signal (SIGABRT, SIGSEGV, etc.)
 /// - `Panic`: Rust panic occurred
 /// - `Error`: Normal error returned from instance.enter()
+/// - `IdleStopped`: Box stopped by `BoxOptions::auto_stop_after_idle`
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum ExitInfo {
@@ -39,6 +50,9 @@ pub enum ExitInfo {
     },
     /// Normal error returned from shim (e.g., instance.enter() failed).
     Error { exit_code: i32, message: String },
+    /// Box stopped gracefully after `idle_secs` without activity.
+    #[serde(rename = "idle-stopped")]
+    IdleStopped { exit_code: i32, idle_secs: u64 },
 }
 
 impl ExitInfo {
@@ -56,6 +70,7 @@ impl ExitInfo {
             ExitInfo::Signal { exit_code, .. } => *exit_code,
             ExitInfo::Panic { exit_code, .. } => *exit_code,
             ExitInfo::Error { exit_code, .. } => *exit_code,
+            ExitInfo::IdleStopped { exit_code, .. } => *exit_code,
         }
     }
 
@@ -63,7 +78,7 @@ impl ExitInfo {
     pub fn signal_name(&self) -> Option<&str> {
         match self {
             ExitInfo::Signal { signal, .. } => Some(signal),
-            ExitInfo::Panic { .. } | ExitInfo::Error { .. } => None,
+            ExitInfo::Panic { .. } | ExitInfo::Error { .. } | ExitInfo::IdleStopped { .. } => None,
         }
     }
 
@@ -71,7 +86,7 @@ impl ExitInfo {
     pub fn panic_message(&self) -> Option<&str> {
         match self {
             ExitInfo::Panic { message, .. } => Some(message),
-            ExitInfo::Signal { .. } | ExitInfo::Error { .. } => None,
+            ExitInfo::Signal { .. } | ExitInfo::Error { .. } | ExitInfo::IdleStopped { .. } => None,
         }
     }
 
@@ -79,7 +94,15 @@ impl ExitInfo {
     pub fn error_message(&self) -> Option<&str> {
         match self {
             ExitInfo::Error { message, .. } => Some(message),
-            ExitInfo::Signal { .. } | ExitInfo::Panic { .. } => None,
+            ExitInfo::Signal { .. } | ExitInfo::Panic { .. } | ExitInfo::IdleStopped { .. } => None,
+        }
+    }
+
+    /// Get how long the box was idle if it was stopped for idleness.
+    pub fn idle_secs(&self) -> Option<u64> {
+        match self {
+            ExitInfo::IdleStopped { idle_secs, .. } => Some(*idle_secs),
+            ExitInfo::Signal { .. } | ExitInfo::Panic { .. } | ExitInfo::Error { .. } => None,
         }
     }
 
@@ -97,6 +120,33 @@ impl ExitInfo {
     pub fn is_error(&self) -> bool {
         matches!(self, ExitInfo::Error { .. })
     }
+
+    /// Check if the box was stopped for idleness.
+    pub fn is_idle_stopped(&self) -> bool {
+        matches!(self, ExitInfo::IdleStopped { .. })
+    }
+}
+
+/// How the shim last exited, with its crash diagnostics when it recorded any.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShimExit {
+    #[serde(flatten)]
+    pub info: ExitInfo,
+    /// Written on panics and fatal signals; `None` for normal errors.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub diagnostics: Option<CrashDiagnostics>,
+}
+
+impl ShimExit {
+    /// Read the exit file and the diagnostics file next to it.
+    ///
+    /// Returns `None` if the exit file doesn't exist or JSON is invalid.
+    pub fn from_exit_file(exit_file: &Path) -> Option<Self> {
+        Some(Self {
+            info: ExitInfo::from_file(exit_file)?,
+            diagnostics: CrashDiagnostics::from_file(&diagnostics_path(exit_file)),
+        })
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +258,50 @@ mod tests {
         assert_eq!(info.error_message(), Some("test error"));
         assert!(info.is_error());
     }
+
+    #[test]
+    fn test_idle_stopped_serialization() {
+        let info = ExitInfo::IdleStopped {
+            exit_code: 0,
+            idle_secs: 7200,
+        };
+
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(json.contains(r#""type":"idle-stopped""#));
+        assert!(json.contains(r#""idle_secs":7200"#));
+
+        let parsed: ExitInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.exit_code(), 0);
+        assert_eq!(parsed.idle_secs(), Some(7200));
+        assert!(parsed.is_idle_stopped());
+        assert!(parsed.error_message().is_none());
+    }
+
+    #[test]
+    fn test_shim_exit_includes_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exit");
+        std::fs::write(
+            &path,
+            r#"{"type":"panic","exit_code":101,"message":"boom","location":"main.rs:1:1"}"#,
+        )
+        .unwrap();
+        assert!(
+            ShimExit::from_exit_file(&path)
+                .unwrap()
+                .diagnostics
+                .is_none()
+        );
+
+        std::fs::write(
+            diagnostics_path(&path),
+            r#"{"recent_operations":[{"name":"instance.enter","at_ms":1}],"vcpu_states":null,"open_fds":12,"truncated":false}"#,
+        )
+        .unwrap();
+        let exit = ShimExit::from_exit_file(&path).unwrap();
+        assert_eq!(exit.info.panic_message(), Some("boom"));
+        let diagnostics = exit.diagnostics.unwrap();
+        assert_eq!(diagnostics.recent_operations[0].name, "instance.enter");
+        assert_eq!(diagnostics.open_fds, Some(12));
+    }
 }
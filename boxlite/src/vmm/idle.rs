@@ -0,0 +1,268 @@
+//! Idle tracking for `BoxOptions::auto_stop_after_idle`.
+//!
+//! A box is idle while no exec runs in it, no files are copied in or out
+//! and, with an idle CPU threshold, its VM process uses less CPU than that.
+//! The process that owns a box watches it; the shim watches boxes no
+//! process owns (detached or released ones), so they stop without the
+//! process that created them. Either way, a box idle for the whole window
+//! gets a graceful stop, and its exit file records
+//! [`ExitInfo::IdleStopped`].
+//!
+//! The watcher records the last activity it saw in [`IDLE_FILE`] in the box
+//! directory, from which inspect reports the idle time left.
+
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use super::ExitInfo;
+
+/// Last activity record in the box directory.
+pub const IDLE_FILE: &str = "idle";
+
+/// Bounds of the time between two samples.
+const MIN_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_SAMPLE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// When a box counts as idle, and for how long before it is stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct IdlePolicy {
+    /// Idle time after which the box is stopped.
+    pub window: Duration,
+    /// CPU usage of the VM process, in percent of one host CPU, at or above
+    /// which the box is busy. `None` ignores CPU usage.
+    pub cpu_threshold: Option<f64>,
+}
+
+impl IdlePolicy {
+    /// Time between two samples: a tenth of the window, within 1s to 30s.
+    pub fn sample_interval(&self) -> Duration {
+        (self.window / 10).clamp(MIN_SAMPLE_INTERVAL, MAX_SAMPLE_INTERVAL)
+    }
+}
+
+/// What a box was doing when sampled.
+#[derive(Debug, Clone, Copy)]
+pub struct IdleSample {
+    /// Executions still running in the guest.
+    pub running_execs: u32,
+    /// Time since the guest last started or finished an exec or a file
+    /// transfer.
+    pub since_activity: Duration,
+    /// CPU usage of the VM process, in percent of one host CPU.
+    pub cpu_percent: Option<f64>,
+}
+
+/// Last activity of a box, folded from samples.
+#[derive(Debug)]
+pub struct IdleTracker {
+    policy: IdlePolicy,
+    last_active: Instant,
+}
+
+impl IdleTracker {
+    /// Start tracking at `now`, which counts as activity.
+    pub fn new(policy: IdlePolicy, now: Instant) -> Self {
+        Self {
+            policy,
+            last_active: now,
+        }
+    }
+
+    /// Fold in what the box was doing at `now` and return the idle time
+    /// left before it should stop. A failed sample (`None`) counts as
+    /// activity.
+    pub fn observe(&mut self, now: Instant, sample: Option<&IdleSample>) -> Duration {
+        let active_at = match sample {
+            Some(sample) if self.is_idle(sample) => now.checked_sub(sample.since_activity),
+            _ => Some(now),
+        };
+        if let Some(at) = active_at {
+            self.last_active = self.last_active.max(at);
+        }
+        self.remaining(now)
+    }
+
+    /// Time the box has been idle at `now`.
+    pub fn idle_for(&self, now: Instant) -> Duration {
+        now.saturating_duration_since(self.last_active)
+    }
+
+    /// Idle time left at `now` before the box should stop.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        self.policy.window.saturating_sub(self.idle_for(now))
+    }
+
+    fn is_idle(&self, sample: &IdleSample) -> bool {
+        let cpu_busy = match (self.policy.cpu_threshold, sample.cpu_percent) {
+            (Some(threshold), Some(cpu)) => cpu >= threshold,
+            // Without a reading, CPU usage can't prove the box busy
+            _ => false,
+        };
+        sample.running_execs == 0 && !cpu_busy
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct IdleRecord {
+    /// Unix time of the last activity, in milliseconds.
+    last_active_ms: u64,
+}
+
+/// Record the last activity of the box in `box_dir`, `idle` before now.
+pub fn record_last_active(box_dir: &Path, idle: Duration) -> std::io::Result<()> {
+    let at = SystemTime::now()
+        .checked_sub(idle)
+        .unwrap_or(UNIX_EPOCH)
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let record = IdleRecord {
+        last_active_ms: at.as_millis() as u64,
+    };
+    std::fs::write(box_dir.join(IDLE_FILE), serde_json::to_vec(&record)?)
+}
+
+/// Idle time left for the box in `box_dir` that started at `started`,
+/// from the last activity its watcher recorded.
+///
+/// A record older than `started` belongs to an earlier run and is ignored.
+pub fn remaining(box_dir: &Path, window: Duration, started: SystemTime) -> Duration {
+    let recorded = std::fs::read(box_dir.join(IDLE_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_slice::<IdleRecord>(&data).ok())
+        .map(|record| UNIX_EPOCH + Duration::from_millis(record.last_active_ms));
+    let last_active = recorded.map_or(started, |at| at.max(started));
+    let idle = SystemTime::now()
+        .duration_since(last_active)
+        .unwrap_or_default();
+    window.saturating_sub(idle)
+}
+
+/// Record in `exit_file` that the box was stopped after `idle` without
+/// activity.
+pub fn record_idle_stop(exit_file: &Path, idle: Duration) -> std::io::Result<()> {
+    let info = ExitInfo::IdleStopped {
+        exit_code: 0,
+        idle_secs: idle.as_secs(),
+    };
+    std::fs::write(exit_file, serde_json::to_vec(&info)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(window_secs: u64, cpu_threshold: Option<f64>) -> IdlePolicy {
+        IdlePolicy {
+            window: Duration::from_secs(window_secs),
+            cpu_threshold,
+        }
+    }
+
+    fn sample(running_execs: u32, since_secs: u64, cpu_percent: Option<f64>) -> IdleSample {
+        IdleSample {
+            running_execs,
+            since_activity: Duration::from_secs(since_secs),
+            cpu_percent,
+        }
+    }
+
+    #[test]
+    fn test_tracker_counts_down_while_idle() {
+        let start = Instant::now();
+        let mut tracker = IdleTracker::new(policy(60, None), start);
+
+        let now = start + Duration::from_secs(20);
+        let left = tracker.observe(now, Some(&sample(0, 100, None)));
+        assert_eq!(left, Duration::from_secs(40));
+
+        let now = start + Duration::from_secs(60);
+        assert_eq!(
+            tracker.observe(now, Some(&sample(0, 140, None))),
+            Duration::ZERO
+        );
+        assert_eq!(tracker.idle_for(now), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_tracker_resets_on_activity() {
+        let start = Instant::now();
+        let mut tracker = IdleTracker::new(policy(60, None), start);
+
+        // An exec started 5s before the sample
+        let now = start + Duration::from_secs(50);
+        assert_eq!(
+            tracker.observe(now, Some(&sample(0, 5, None))),
+            Duration::from_secs(55)
+        );
+
+        // A running exec keeps the box busy
+        let now = start + Duration::from_secs(200);
+        assert_eq!(
+            tracker.observe(now, Some(&sample(1, 190, None))),
+            Duration::from_secs(60)
+        );
+
+        // So does a failed sample
+        let now = start + Duration::from_secs(400);
+        assert_eq!(tracker.observe(now, None), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_tracker_cpu_threshold() {
+        let start = Instant::now();
+        let now = start + Duration::from_secs(30);
+
+        let mut tracker = IdleTracker::new(policy(60, Some(5.0)), start);
+        let left = tracker.observe(now, Some(&sample(0, 100, Some(12.5))));
+        assert_eq!(left, Duration::from_secs(60));
+
+        let mut tracker = IdleTracker::new(policy(60, Some(5.0)), start);
+        let left = tracker.observe(now, Some(&sample(0, 100, Some(1.0))));
+        assert_eq!(left, Duration::from_secs(30));
+
+        // Without a threshold, CPU usage doesn't matter
+        let mut tracker = IdleTracker::new(policy(60, None), start);
+        let left = tracker.observe(now, Some(&sample(0, 100, Some(400.0))));
+        assert_eq!(left, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_sample_interval_is_bounded() {
+        assert_eq!(policy(5, None).sample_interval(), MIN_SAMPLE_INTERVAL);
+        assert_eq!(policy(60, None).sample_interval(), Duration::from_secs(6));
+        assert_eq!(policy(7200, None).sample_interval(), MAX_SAMPLE_INTERVAL);
+    }
+
+    #[test]
+    fn test_remaining_from_record() {
+        let dir = tempfile::tempdir().unwrap();
+        let window = Duration::from_secs(3600);
+        let started = SystemTime::now() - Duration::from_secs(600);
+
+        // No record yet: idle since the start
+        let left = remaining(dir.path(), window, started);
+        assert!(left <= Duration::from_secs(3000) && left > Duration::from_secs(2990));
+
+        record_last_active(dir.path(), Duration::from_secs(60)).unwrap();
+        let left = remaining(dir.path(), window, started);
+        assert!(left <= Duration::from_secs(3540) && left > Duration::from_secs(3530));
+
+        // A record from an earlier run is ignored
+        let left = remaining(dir.path(), window, SystemTime::now());
+        assert!(left > Duration::from_secs(3590));
+    }
+
+    #[test]
+    fn test_record_idle_stop() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("exit");
+        record_idle_stop(&path, Duration::from_secs(7200)).unwrap();
+
+        let info = ExitInfo::from_file(&path).unwrap();
+        assert!(info.is_idle_stopped());
+        assert_eq!(info.exit_code(), 0);
+        assert_eq!(info.idle_secs(), Some(7200));
+    }
+}
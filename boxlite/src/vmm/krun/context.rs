@@ -12,11 +12,11 @@ use crate::vmm::krun::check_status;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use libkrun_sys::{
     krun_add_disk2, krun_add_net_unixgram, krun_add_net_unixstream, krun_add_virtiofs,
-    krun_add_vsock_port2, krun_create_ctx, krun_free_ctx, krun_init_log, krun_set_console_output,
-    krun_set_env, krun_set_exec, krun_set_gpu_options, krun_set_kernel, krun_set_nested_virt,
-    krun_set_port_map, krun_set_rlimits, krun_set_root, krun_set_root_disk_remount,
-    krun_set_vm_config, krun_set_workdir, krun_setgid, krun_setuid, krun_split_irqchip,
-    krun_start_enter,
+    krun_add_vsock, krun_add_vsock_port2, krun_create_ctx, krun_disable_implicit_vsock,
+    krun_free_ctx, krun_init_log, krun_set_console_output, krun_set_env, krun_set_exec,
+    krun_set_gpu_options, krun_set_kernel, krun_set_nested_virt, krun_set_port_map,
+    krun_set_rlimits, krun_set_root, krun_set_root_disk_remount, krun_set_vm_config,
+    krun_set_workdir, krun_setgid, krun_setuid, krun_split_irqchip, krun_start_enter,
 };
 
 /// Thin wrapper that owns a libkrun context.
@@ -433,6 +433,18 @@ impl KrunContext {
         })
     }
 
+    /// Replace the implicit vsock device with one without TSI, so the guest
+    /// gets no network through vsock. Vsock ports keep working.
+    ///
+    /// Must be called before any `add_vsock_port`.
+    pub unsafe fn disable_tsi(&self) -> BoxliteResult<()> {
+        tracing::debug!("Disabling TSI networking");
+        check_status("krun_disable_implicit_vsock", unsafe {
+            krun_disable_implicit_vsock(self.ctx_id)
+        })?;
+        check_status("krun_add_vsock", unsafe { krun_add_vsock(self.ctx_id, 0) })
+    }
+
     /// Configure vsock port with Unix socket bridge.
     ///
     /// # Arguments
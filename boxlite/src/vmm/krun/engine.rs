@@ -2,6 +2,7 @@
 
 use super::context::KrunContext;
 use crate::runtime::constants::network;
+use crate::runtime::options::NetworkMode;
 use crate::vmm::{InstanceSpec, Vmm, VmmConfig, VmmInstance, engine::VmmInstanceImpl};
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 
@@ -244,7 +245,11 @@ impl Vmm for Krun {
             ctx.set_vm_config(config.cpus.unwrap_or(4), config.memory_mib.unwrap_or(4096))?;
 
             // Configure net from connection info passed by parent process
-            if let Some(connection) = &config.network_backend_endpoint {
+            if config.network_mode == NetworkMode::None {
+                // No backend and no TSI: the guest only has loopback
+                tracing::info!("Network mode none: disabling TSI networking");
+                ctx.disable_tsi()?;
+            } else if let Some(connection) = &config.network_backend_endpoint {
                 tracing::info!(connection = ?connection, "Configuring network connection");
 
                 match connection {
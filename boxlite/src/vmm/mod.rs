@@ -6,18 +6,25 @@ use std::path::PathBuf;
 use std::str::FromStr;
 
 pub mod controller;
+pub mod crash_diagnostics;
 pub mod engine;
 pub mod exit_info;
 pub mod factory;
 pub mod host_check;
+pub mod idle;
+#[cfg(feature = "local")]
 pub mod krun;
 pub mod registry;
 
 use crate::jailer::SecurityOptions;
 use crate::runtime::guest_rootfs::GuestRootfs;
+pub use crash_diagnostics::{
+    CrashDiagnostics, DiagnosticsCollector, OperationLog, RecordedOperation,
+};
 pub use engine::{Vmm, VmmConfig, VmmInstance};
-pub use exit_info::ExitInfo;
+pub use exit_info::{ExitInfo, ShimExit};
 pub use factory::VmmFactory;
+pub use idle::{IdlePolicy, IdleSample, IdleTracker};
 pub use registry::create_engine;
 
 /// Available sandbox engine implementations.
@@ -152,6 +159,9 @@ pub struct InstanceSpec {
     pub security: SecurityOptions,
     pub cpus: Option<u8>,
     pub memory_mib: Option<u32>,
+    /// Scheduling priority; the shim takes the matching QoS class on macOS.
+    #[serde(default)]
+    pub priority: crate::runtime::options::BoxPriority,
     /// Filesystem shares from host to guest
     pub fs_shares: FsShares,
     /// Block device attachments via virtio-blk
@@ -164,6 +174,10 @@ pub struct InstanceSpec {
     pub ready_transport: boxlite_shared::Transport,
     /// Resolved guest rootfs path and assembly strategy
     pub guest_rootfs: GuestRootfs,
+    /// Network access of the box. With `NetworkMode::None`, `network_config`
+    /// is None and the engine also turns off its implicit TSI networking.
+    #[serde(default)]
+    pub network_mode: crate::runtime::options::NetworkMode,
     /// Network configuration (port mappings) passed to shim subprocess.
     /// The shim creates the network backend (gvproxy) from this config,
     /// ensuring networking survives detach operations.
@@ -181,6 +195,17 @@ pub struct InstanceSpec {
     /// Whether the box should continue running when the parent process exits.
     /// When false, the shim detects parent death via watchdog pipe POLLHUP.
     pub detach: bool,
+    /// How long the shim's SIGTERM handler waits for the guest to shut down
+    /// before the box is killed.
+    #[serde(default = "default_shutdown_grace_period")]
+    pub shutdown_grace_period: std::time::Duration,
+    /// Idle auto-stop the shim applies while no host process owns the box.
+    #[serde(default)]
+    pub idle_policy: Option<IdlePolicy>,
+}
+
+fn default_shutdown_grace_period() -> std::time::Duration {
+    crate::runtime::options::DEFAULT_SHUTDOWN_GRACE_PERIOD
 }
 
 /// Entrypoint configuration that the guest should run.
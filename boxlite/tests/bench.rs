@@ -0,0 +1,84 @@
+//! Integration tests for the `boxlite::bench` harness.
+
+use std::time::Duration;
+
+use boxlite::BoxliteRuntime;
+use boxlite::bench::{self, BOX_NAME_PREFIX, BenchOptions, Scenario};
+use boxlite::testing::TestRuntime;
+
+fn small_run() -> BenchOptions {
+    BenchOptions {
+        iterations: 2,
+        copy_files: 20,
+        copy_file_size: 512,
+        ..Default::default()
+    }
+}
+
+async fn bench_boxes(runtime: &BoxliteRuntime) -> usize {
+    runtime
+        .list_info()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|info| {
+            info.name
+                .as_deref()
+                .is_some_and(|name| name.starts_with(BOX_NAME_PREFIX))
+        })
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn every_scenario_reports_and_removes_its_boxes() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let report = bench::run_until(&rt, &small_run(), &Scenario::ALL, std::future::pending())
+        .await
+        .unwrap();
+
+    assert!(!report.interrupted);
+    let operations: Vec<_> = report
+        .results
+        .iter()
+        .map(|r| r.operation.as_str())
+        .collect();
+    assert_eq!(
+        operations,
+        [
+            "cold_start",
+            "exec",
+            "copy_in",
+            "snapshot_create",
+            "snapshot_restore"
+        ]
+    );
+    for result in &report.results {
+        assert_eq!(result.stats.samples, 2);
+        assert!(result.stats.p50_ms <= result.stats.p95_ms);
+        assert!(result.stats.p95_ms <= result.stats.max_ms);
+    }
+    assert!(report.results[2].throughput_bytes_per_sec.is_some());
+    assert_eq!(bench_boxes(&rt).await, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn interrupted_run_removes_its_boxes() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    // Stop as soon as the first box exists
+    let stop = async {
+        while bench_boxes(&rt).await == 0 {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    };
+    let report = bench::run_until(&rt, &small_run(), &[Scenario::WarmExec], stop)
+        .await
+        .unwrap();
+
+    assert!(report.interrupted);
+    assert!(report.results.is_empty());
+    assert_eq!(bench_boxes(&rt).await, 0);
+}
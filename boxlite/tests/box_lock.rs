@@ -0,0 +1,86 @@
+//! Integration tests for the per-box operation lock.
+//!
+//! Two runtimes cannot share a home (see `runtime.rs`), so these race
+//! handles of one runtime. The lock is a file lock, so handles in separate
+//! processes contend the same way.
+
+use std::time::Duration;
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions, BoxStatus, BoxliteError, BoxliteOptions};
+
+/// A mutating call made while another handle is starting the box fails
+/// with `Busy` naming the start, instead of blocking or interleaving.
+#[tokio::test(flavor = "multi_thread")]
+async fn operation_during_start_is_busy() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.alpine().await;
+
+    let starting = rt.get(bx.id().as_str()).await.unwrap().unwrap();
+    let start = tokio::spawn(async move { starting.start().await });
+
+    // Probe with a harmless mutating call until it runs into the start
+    let mut busy = None;
+    while !start.is_finished() {
+        match bx.snapshot().remove("missing").await {
+            Err(BoxliteError::Busy { operation, pid }) => {
+                busy = Some((operation, pid));
+                break;
+            }
+            _ => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    }
+    start.await.unwrap().unwrap();
+
+    let (operation, pid) = busy.expect("start finished before the probe saw its lock");
+    assert_eq!(operation, "start");
+    assert_eq!(pid, std::process::id());
+}
+
+/// With `lock_wait`, a racing stop and start serialize and leave the box in
+/// a consistent, restartable state.
+#[tokio::test(flavor = "multi_thread")]
+async fn racing_stop_and_start_serialize_with_lock_wait() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::with_options(BoxliteOptions {
+        image_registries: vec![],
+        lock_wait: Some(Duration::from_secs(120)),
+        ..Default::default()
+    });
+    let bx = rt
+        .create_box(BoxOptions {
+            auto_remove: false,
+            ..alpine_options()
+        })
+        .await;
+    let id = bx.id().to_string();
+
+    // Create the disks, then leave the box stopped
+    bx.start().await.unwrap();
+    bx.stop().await.unwrap();
+
+    for _ in 0..3 {
+        let starter = rt.get(&id).await.unwrap().unwrap();
+        let stopper = rt.get(&id).await.unwrap().unwrap();
+        let (started, stopped) = tokio::join!(starter.start(), stopper.stop());
+        started.unwrap();
+        stopped.unwrap();
+
+        let info = rt.get_info(&id).await.unwrap().unwrap();
+        assert!(
+            matches!(info.status, BoxStatus::Running | BoxStatus::Exited),
+            "unexpected status {:?}",
+            info.status
+        );
+
+        // Whichever order the race took, the box still starts and runs
+        let fresh = rt.get(&id).await.unwrap().unwrap();
+        fresh.start().await.unwrap();
+        let mut execution = fresh.exec(BoxCommand::new("true")).await.unwrap();
+        assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+        fresh.stop().await.unwrap();
+    }
+}
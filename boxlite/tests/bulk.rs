@@ -0,0 +1,140 @@
+//! Integration tests for bulk operations over labeled boxes
+//! (`stop_matching`, `remove_matching`, `exec_matching`, `prune`).
+
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxID, BoxStatus, BoxliteError, ListFilter, StopOptions};
+
+async fn create_labeled(rt: &TestRuntime, name: &str, tier: &str) -> TestBox {
+    let mut options = alpine_options();
+    options.labels.insert("tier".into(), tier.into());
+    let bx = rt
+        .runtime()
+        .create(options, Some(name.to_string()))
+        .await
+        .unwrap();
+    bx.start().await.unwrap();
+    TestBox::new(rt.runtime().clone(), bx)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exec_matching_reports_each_box_without_aborting() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let web1 = create_labeled(&rt, "web-1", "web").await;
+    let web2 = create_labeled(&rt, "web-2", "web").await;
+    let _db = create_labeled(&rt, "db", "db").await;
+    assert_eq!(web1.info().labels["tier"], "web");
+
+    let web = ListFilter::new().label("tier", "web");
+    let results = rt
+        .runtime()
+        .exec_matching(web.clone(), BoxCommand::new("hostname"))
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    for (_, result) in &results {
+        let output = result.as_ref().unwrap();
+        assert!(output.result.success());
+        assert!(!output.stdout.is_empty());
+    }
+
+    // A command failing in one box is reported in its slot only
+    web1.exec_output("touch", ["/marker"]).await;
+    let results = rt
+        .runtime()
+        .exec_matching(web, BoxCommand::new("test").args(["-f", "/marker"]))
+        .await
+        .unwrap();
+    let exit_code = |id: &BoxID| {
+        let (_, result) = results.iter().find(|(bid, _)| bid == id).unwrap();
+        result.as_ref().unwrap().result.exit_code
+    };
+    assert_eq!(exit_code(web1.id()), 0);
+    assert_eq!(exit_code(web2.id()), 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stop_and_remove_matching_leave_other_boxes() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let _web1 = create_labeled(&rt, "web-1", "web").await;
+    let _web2 = create_labeled(&rt, "web-2", "web").await;
+    let db = create_labeled(&rt, "db", "db").await;
+
+    let web = ListFilter::new().label("tier", "web");
+    let results = rt
+        .runtime()
+        .stop_matching(web.clone(), StopOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|(_, r)| r.is_ok()));
+
+    let running = rt
+        .runtime()
+        .list_info()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|info| info.status == BoxStatus::Running)
+        .map(|info| info.id)
+        .collect::<Vec<_>>();
+    assert_eq!(running, vec![db.id().clone()]);
+
+    let results = rt.runtime().remove_matching(web, false).await.unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|(_, r)| r.is_ok()));
+    assert!(rt.runtime().get("web-1").await.unwrap().is_none());
+
+    // Removing a running box without force fails for that box alone
+    let results = rt
+        .runtime()
+        .remove_matching(ListFilter::new().has_label("tier"), false)
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(matches!(results[0].1, Err(BoxliteError::InvalidState(_))));
+    assert!(rt.runtime().exists("db").await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn prune_removes_only_exited_boxes() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let web = create_labeled(&rt, "web", "web").await;
+    let _db = create_labeled(&rt, "db", "db").await;
+    web.stop().await.unwrap();
+
+    let plans = rt.runtime().plan_prune(ListFilter::new()).await.unwrap();
+    assert_eq!(plans.len(), 1);
+    assert_eq!(&plans[0].0, web.id());
+    assert!(plans[0].1.as_ref().unwrap().disk_bytes > 0);
+    assert!(rt.runtime().exists("web").await.unwrap());
+
+    let results = rt.runtime().prune(ListFilter::new()).await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(results[0].1.is_ok());
+    assert!(!rt.runtime().exists("web").await.unwrap());
+    assert!(rt.runtime().exists("db").await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn empty_match_is_not_an_error() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let _bx = rt.create_box(alpine_options()).await;
+
+    let results = rt
+        .runtime()
+        .stop_matching(
+            ListFilter::new().label("tier", "none"),
+            StopOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert!(results.is_empty());
+}
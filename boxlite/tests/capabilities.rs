@@ -0,0 +1,90 @@
+//! Integration tests for the host capability probe: each capability is
+//! forced off with `BOXLITE_DISABLE_CAPABILITIES` and the box either fails
+//! at create or runs with the degradation recorded.
+//!
+//! The override is read when a runtime starts, so the whole matrix runs in
+//! one test: setting the variable is only sound with no other test running.
+
+use boxlite::runtime::advanced_options::{ResourceLimits, SecurityOptions};
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{BoxOptions, BoxliteError, Capability};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn each_disabled_capability_degrades_predictably() {
+    boxlite::skip_if_no_virtualization!();
+
+    // No KVM and no vsock: the box cannot run at all
+    for capability in ["kvm", "vsock"] {
+        let rt = runtime_without(capability);
+        let missing = rt.capabilities().await.unwrap();
+        let status = missing.get(capability.parse().unwrap());
+        assert!(!status.available);
+        assert!(status.detail.contains("BOXLITE_DISABLE_CAPABILITIES"));
+
+        let err = rt.create(alpine_options(), None).await.unwrap_err();
+        assert!(matches!(err, BoxliteError::Unsupported(_)), "{err:?}");
+        assert!(rt.list_info().await.unwrap().is_empty());
+    }
+
+    // The jailer, and so its degradations, only exist on Linux
+    if !cfg!(target_os = "linux") {
+        return;
+    }
+
+    // No user namespaces: the shim runs without bwrap, and still works
+    let rt = runtime_without("userns");
+    let bx = rt.create_box(jailed(SecurityOptions::default())).await;
+    assert_degraded(&bx, Capability::Userns);
+    bx.start().await.unwrap();
+    bx.exec_output("echo", ["hi"])
+        .await
+        .assert_stdout_eq("hi\n");
+
+    let mut strict = SecurityOptions::default();
+    strict.require_sandbox = true;
+    let err = rt.create(jailed(strict), None).await.unwrap_err();
+    assert!(err.to_string().contains("require_sandbox"), "{err}");
+
+    // No seccomp: the filter is dropped
+    let rt = runtime_without("seccomp");
+    let mut security = SecurityOptions::default();
+    security.seccomp_enabled = true;
+    let bx = rt.create_box(jailed(security)).await;
+    assert_degraded(&bx, Capability::Seccomp);
+
+    // No cgroup delegation: only boxes with limits are affected
+    let rt = runtime_without("cgroup_delegation");
+    let bx = rt.create_box(jailed(SecurityOptions::default())).await;
+    assert!(bx.info().degradations.is_empty());
+    let mut security = SecurityOptions::default();
+    security.resource_limits = ResourceLimits {
+        max_memory: Some(256 * 1024 * 1024),
+        ..Default::default()
+    };
+    let bx = rt.create_box(jailed(security)).await;
+    assert_degraded(&bx, Capability::CgroupDelegation);
+
+    // SAFETY: see the module docs; no other test shares this process
+    unsafe { std::env::remove_var("BOXLITE_DISABLE_CAPABILITIES") };
+}
+
+fn runtime_without(capability: &str) -> TestRuntime {
+    // SAFETY: see the module docs; no other test shares this process
+    unsafe { std::env::set_var("BOXLITE_DISABLE_CAPABILITIES", capability) };
+    TestRuntime::new()
+}
+
+fn jailed(mut security: SecurityOptions) -> BoxOptions {
+    security.jailer_enabled = true;
+    let mut options = alpine_options();
+    options.advanced.security = security;
+    options
+}
+
+fn assert_degraded(bx: &TestBox, capability: Capability) {
+    let degradations = bx.info().degradations;
+    assert!(
+        degradations.iter().any(|d| d.capability == capability),
+        "{degradations:?}"
+    );
+}
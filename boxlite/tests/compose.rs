@@ -0,0 +1,89 @@
+//! Integration tests for compositions: dependency order, readiness probes,
+//! rollback and teardown through `up` / `down`.
+
+use std::time::Duration;
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxOptions, ComposedBox, ReadinessProbe, UpOptions, UpOutcome};
+
+fn sleeper() -> BoxOptions {
+    let mut options = alpine_options();
+    options.cmd = Some(vec!["sleep".into(), "300".into()]);
+    options
+}
+
+fn names(report: &boxlite::UpReport) -> Vec<&str> {
+    report.boxes.iter().map(|(name, _)| name.as_str()).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn up_and_down_follow_dependencies() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let report = rt
+        .up(vec![
+            ComposedBox::new("app", sleeper()).depends_on("db"),
+            ComposedBox::new("db", sleeper()).readiness(ReadinessProbe::new(["true"])),
+        ])
+        .await
+        .unwrap();
+    assert!(report.success(), "{report:?}");
+    assert_eq!(names(&report), ["db", "app"]);
+    assert!(matches!(report.boxes[0].1, UpOutcome::Created));
+
+    // Already up: nothing is recreated
+    let again = rt
+        .up(vec![
+            ComposedBox::new("app", sleeper()).depends_on("db"),
+            ComposedBox::new("db", sleeper()),
+        ])
+        .await
+        .unwrap();
+    assert!(matches!(again.boxes[0].1, UpOutcome::Running));
+
+    // Naming only the dependency takes its dependents down too
+    let results = rt.down(&["db".to_string()], true).await.unwrap();
+    let order: Vec<&str> = results.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(order, ["app", "db"]);
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+    assert!(!rt.exists("app").await.unwrap());
+    assert!(!rt.exists("db").await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_readiness_rolls_back() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let never_ready = ReadinessProbe::new(["false"])
+        .interval(Duration::from_millis(200))
+        .timeout(Duration::from_secs(2));
+    let boxes = vec![
+        ComposedBox::new("db", sleeper()),
+        ComposedBox::new("cache", sleeper()).readiness(never_ready),
+        ComposedBox::new("app", sleeper()).depends_on("cache"),
+    ];
+
+    let report = rt.up(boxes.clone()).await.unwrap();
+    assert!(!report.success());
+    let (failed, err) = report.failure().unwrap();
+    assert_eq!(failed, "cache");
+    assert!(matches!(err, boxlite::BoxliteError::Timeout(_)), "{err}");
+    assert!(matches!(report.boxes[0].1, UpOutcome::RolledBack));
+    assert!(matches!(report.boxes[2].1, UpOutcome::Skipped));
+    assert!(!rt.exists("db").await.unwrap());
+
+    // Without rollback, boxes that don't depend on the failed one stay up
+    let report = rt
+        .up_with(boxes, UpOptions::default().rollback(false))
+        .await
+        .unwrap();
+    assert!(matches!(report.boxes[0].1, UpOutcome::Created));
+    assert!(matches!(report.boxes[2].1, UpOutcome::Skipped));
+    let db = rt.get("db").await.unwrap().unwrap();
+    assert!(db.info().status.is_running());
+
+    let names = ["db", "cache", "app"].map(String::from);
+    rt.down(&names, true).await.unwrap();
+}
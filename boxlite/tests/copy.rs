@@ -0,0 +1,227 @@
+//! Integration tests for file ownership in copy_into / copy_out, and for
+//! where copy_out may write on the host.
+
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{BoxOptions, BoxliteError, BoxliteOptions, CopyOptions, CopyOwnership};
+
+/// Box running as a non-root user that is not in the image's /etc/passwd.
+async fn box_as_user_1000(rt: &TestRuntime) -> TestBox {
+    rt.create_box(BoxOptions {
+        user: Some("1000:1000".into()),
+        ..alpine_options()
+    })
+    .await
+}
+
+/// Host tree `upload/{top.txt, sub/inner.txt, sub/deeper/leaf.txt}`.
+fn nested_tree(root: &Path) -> std::path::PathBuf {
+    let upload = root.join("upload");
+    std::fs::create_dir_all(upload.join("sub/deeper")).unwrap();
+    std::fs::write(upload.join("top.txt"), "top").unwrap();
+    std::fs::write(upload.join("sub/inner.txt"), "inner").unwrap();
+    std::fs::write(upload.join("sub/deeper/leaf.txt"), "leaf").unwrap();
+    upload
+}
+
+/// `uid:gid` of each path inside the box, one per line.
+async fn owners(bx: &TestBox, paths: &[&str]) -> Vec<String> {
+    let mut args = vec!["-c", "%u:%g"];
+    args.extend_from_slice(paths);
+    let output = bx.exec_output("stat", args).await;
+    output.assert_success();
+    output.stdout.lines().map(str::to_string).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn copy_into_defaults_to_container_user() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = box_as_user_1000(&rt).await;
+    let host = tempfile::tempdir().unwrap();
+    let upload = nested_tree(host.path());
+
+    bx.copy_into(&upload, "/tmp/app/data", CopyOptions::default())
+        .await
+        .unwrap();
+
+    let paths = [
+        "/tmp/app",
+        "/tmp/app/data",
+        "/tmp/app/data/upload",
+        "/tmp/app/data/upload/top.txt",
+        "/tmp/app/data/upload/sub",
+        "/tmp/app/data/upload/sub/deeper",
+        "/tmp/app/data/upload/sub/deeper/leaf.txt",
+    ];
+    for owner in owners(&bx, &paths).await {
+        assert_eq!(owner, "1000:1000");
+    }
+
+    // The box user can write into the copied tree without a chown
+    bx.exec_output(
+        "sh",
+        ["-c", "echo more >> /tmp/app/data/upload/sub/inner.txt"],
+    )
+    .await
+    .assert_success();
+    bx.exec_output("touch", ["/tmp/app/data/upload/sub/deeper/new.txt"])
+        .await
+        .assert_success();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn copy_into_explicit_owner() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = box_as_user_1000(&rt).await;
+    let host = tempfile::tempdir().unwrap();
+    let upload = nested_tree(host.path());
+
+    let opts = CopyOptions::default()
+        .include_parent(false)
+        .ownership(CopyOwnership::Explicit {
+            uid: 1234,
+            gid: 5678,
+        });
+    bx.copy_into(&upload, "/tmp/explicit", opts).await.unwrap();
+
+    for owner in owners(
+        &bx,
+        &["/tmp/explicit/sub", "/tmp/explicit/sub/deeper/leaf.txt"],
+    )
+    .await
+    {
+        assert_eq!(owner, "1234:5678");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn copy_into_preserve_host_keeps_host_ids() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = box_as_user_1000(&rt).await;
+    let host = tempfile::tempdir().unwrap();
+    let upload = nested_tree(host.path());
+    let meta = std::fs::metadata(upload.join("sub/deeper/leaf.txt")).unwrap();
+
+    let opts = CopyOptions::default()
+        .include_parent(false)
+        .ownership(CopyOwnership::PreserveHost);
+    bx.copy_into(&upload, "/tmp/preserved", opts).await.unwrap();
+
+    let owner = owners(&bx, &["/tmp/preserved/sub/deeper/leaf.txt"]).await;
+    assert_eq!(owner, vec![format!("{}:{}", meta.uid(), meta.gid())]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn copy_out_chowns_to_caller_by_default() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = box_as_user_1000(&rt).await;
+    bx.exec_output(
+        "sh",
+        [
+            "-c",
+            "mkdir -p /tmp/out/sub && echo hi > /tmp/out/sub/f.txt",
+        ],
+    )
+    .await
+    .assert_success();
+
+    let host = tempfile::tempdir().unwrap();
+    let dest = host.path().join("dest");
+    bx.copy_out("/tmp/out", &dest, CopyOptions::default())
+        .await
+        .unwrap();
+
+    let caller = std::fs::metadata(host.path()).unwrap();
+    let meta = std::fs::metadata(dest.join("out/sub/f.txt")).unwrap();
+    assert_eq!((meta.uid(), meta.gid()), (caller.uid(), caller.gid()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn copy_out_stays_under_destination() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.alpine().await;
+    // A tree with a symlink of its own, copied as a link
+    bx.exec_output(
+        "sh",
+        [
+            "-c",
+            "mkdir -p /tmp/out/sub && echo hi > /tmp/out/sub/f.txt && ln -s /etc /tmp/out/etc",
+        ],
+    )
+    .await
+    .assert_success();
+
+    // The destination already holds a link out of it where the tree goes
+    let host = tempfile::tempdir().unwrap();
+    let outside = host.path().join("outside");
+    std::fs::create_dir(&outside).unwrap();
+    let dest = host.path().join("dest");
+    std::fs::create_dir_all(dest.join("out")).unwrap();
+    std::os::unix::fs::symlink(&outside, dest.join("out/sub")).unwrap();
+
+    let err = bx
+        .copy_out("/tmp/out", &dest, CopyOptions::default())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, BoxliteError::UnsafeCopy(reason) if reason.contains("out/sub/f.txt")),
+        "{err}"
+    );
+    assert_eq!(std::fs::read_dir(&outside).unwrap().count(), 0);
+
+    let fresh = host.path().join("fresh");
+    bx.copy_out("/tmp/out", &fresh, CopyOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(fresh.join("out/sub/f.txt")).unwrap(),
+        "hi\n"
+    );
+    assert_eq!(
+        std::fs::read_link(fresh.join("out/etc")).unwrap(),
+        Path::new("/etc")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn copy_out_respects_allowed_dest_roots() {
+    boxlite::skip_if_no_virtualization!();
+
+    let host = tempfile::tempdir().unwrap();
+    let allowed = host.path().join("allowed");
+    std::fs::create_dir(&allowed).unwrap();
+    std::os::unix::fs::symlink(host.path(), allowed.join("up")).unwrap();
+    let rt = TestRuntime::with_options(BoxliteOptions {
+        image_registries: vec![],
+        allowed_dest_roots: vec![allowed.clone()],
+        ..Default::default()
+    });
+    let bx = rt.alpine().await;
+
+    for dest in [host.path().join("elsewhere"), allowed.join("up/elsewhere")] {
+        let err = bx
+            .copy_out("/etc/alpine-release", &dest, CopyOptions::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, BoxliteError::UnsafeCopy(_)), "{err}");
+        assert!(!dest.exists());
+    }
+
+    let dest = allowed.join("copied/alpine-release");
+    bx.copy_out("/etc/alpine-release", &dest, CopyOptions::default())
+        .await
+        .unwrap();
+    assert!(dest.is_file());
+}
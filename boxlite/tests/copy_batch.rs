@@ -0,0 +1,135 @@
+//! Integration tests for batched `copy_into`: a directory streamed as one
+//! archive the guest unpacks as it arrives.
+//!
+//! The 50k file benchmark is ignored by default; run it with
+//! `cargo test --test copy_batch -- --ignored --nocapture`.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{CopyOptions, CopyProgress};
+
+/// Host tree of `dirs` directories holding `per_dir` tiny files each.
+fn tiny_files(root: &Path, dirs: usize, per_dir: usize) -> PathBuf {
+    let src = root.join("src");
+    for d in 0..dirs {
+        let dir = src.join(format!("d{d}"));
+        std::fs::create_dir_all(&dir).unwrap();
+        for f in 0..per_dir {
+            std::fs::write(dir.join(format!("f{f}.js")), format!("{d}/{f}")).unwrap();
+        }
+    }
+    src
+}
+
+/// `find` listing of `path` inside the box, with owners, sorted.
+async fn listing(bx: &TestBox, path: &str) -> Vec<String> {
+    let script = format!("cd {path} && find . -exec stat -c '%n %u:%g %s' {{}} + | sort");
+    let output = bx.exec_output("sh", ["-c", &script]).await;
+    output.assert_success();
+    output.stdout.lines().map(str::to_string).collect()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn batched_copy_matches_unbatched() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.alpine().await;
+    let host = tempfile::tempdir().unwrap();
+    let src = tiny_files(host.path(), 3, 5);
+
+    for (dest, batch) in [("/root/plain", false), ("/root/batched", true)] {
+        let opts = CopyOptions::default().include_parent(false).batch(batch);
+        bx.copy_into(&src, dest, opts).await.unwrap();
+    }
+    assert_eq!(
+        listing(&bx, "/root/plain").await,
+        listing(&bx, "/root/batched").await
+    );
+
+    // overwrite=false refuses a non-empty destination, as without batching
+    let opts = CopyOptions::default().batch(true).no_overwrite();
+    let err = bx.copy_into(&src, "/root/batched", opts).await.unwrap_err();
+    assert!(err.to_string().contains("overwrite=false"), "{err}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn many_files_are_batched_with_aggregate_progress() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.alpine().await;
+    let host = tempfile::tempdir().unwrap();
+    // Past the threshold, so batched without asking
+    let src = tiny_files(host.path(), 10, 200);
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&reports);
+    let opts = CopyOptions::default().on_progress(move |p| seen.lock().unwrap().push(*p));
+    bx.copy_into(&src, "/root/app", opts).await.unwrap();
+
+    let reports: Vec<CopyProgress> = reports.lock().unwrap().clone();
+    let last = reports.last().expect("no progress reported");
+    assert_eq!(last.files_sent, 2000);
+    assert_eq!(last.files_total, 2000);
+    assert_eq!(last.bytes_sent, last.bytes_total);
+    assert!(reports.len() < 100, "{} reports", reports.len());
+
+    bx.exec_output("sh", ["-c", "find /root/app/src -type f | wc -l"])
+        .await
+        .assert_stdout_eq("2000\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "benchmark: copies 50k files"]
+async fn batched_copy_of_50k_files_is_an_order_of_magnitude_faster() {
+    boxlite::skip_if_no_virtualization!();
+
+    const DIRS: usize = 100;
+    const PER_DIR: usize = 500;
+    /// Directories copied file by file; the rest is extrapolated
+    const SAMPLED_DIRS: usize = 2;
+
+    let rt = TestRuntime::new();
+    let bx = rt.alpine().await;
+    let host = tempfile::tempdir().unwrap();
+    let src = tiny_files(host.path(), DIRS, PER_DIR);
+
+    // One copy_into per file, the way a caller without batching sends a tree
+    let started = Instant::now();
+    for d in 0..SAMPLED_DIRS {
+        let dest = format!("/root/per-file/d{d}/");
+        for f in 0..PER_DIR {
+            let file = src.join(format!("d{d}/f{f}.js"));
+            bx.copy_into(&file, &dest, CopyOptions::default())
+                .await
+                .unwrap();
+        }
+    }
+    let per_file = started.elapsed().mul_f64((DIRS / SAMPLED_DIRS) as f64);
+
+    let started = Instant::now();
+    bx.copy_into(&src, "/root/batched", CopyOptions::default().batch(true))
+        .await
+        .unwrap();
+    let batched = started.elapsed();
+
+    bx.exec_output("sh", ["-c", "find /root/batched -type f | wc -l"])
+        .await
+        .assert_stdout_eq(&format!("{}\n", DIRS * PER_DIR));
+
+    eprintln!(
+        "{} files: per-file ~{:.1?} (extrapolated), batched {:.1?}",
+        DIRS * PER_DIR,
+        per_file,
+        batched
+    );
+    assert!(
+        per_file >= batched * 10,
+        "batched {batched:?} is not 10x faster than per-file {per_file:?}"
+    );
+    assert!(batched < Duration::from_secs(120), "{batched:?}");
+}
@@ -0,0 +1,86 @@
+//! Integration tests for core dump capture (`BoxOptions::capture_core_dumps`).
+
+use std::time::Duration;
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions, CoreDump, LiteBox};
+
+/// Kill a shell with SIGSEGV, which dumps core.
+async fn crash(bx: &LiteBox) {
+    let command = BoxCommand::new("sh").args(["-c", "kill -SEGV $$"]);
+    let mut execution = bx.exec(command).await.unwrap();
+    execution.wait().await.unwrap();
+}
+
+/// The handler runs asynchronously to the crash; wait for `count` dumps.
+async fn wait_for_cores(bx: &LiteBox, count: usize) -> Vec<CoreDump> {
+    for _ in 0..100 {
+        let cores = bx.list_core_dumps().await.unwrap();
+        if cores.len() >= count {
+            return cores;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("expected {count} core dumps");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn crash_leaves_fetchable_core_dump() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            capture_core_dumps: true,
+            ..alpine_options()
+        })
+        .await;
+    bx.start().await.unwrap();
+    crash(&bx).await;
+
+    let cores = wait_for_cores(&bx, 1).await;
+    let core = &cores[0];
+    assert_eq!(core.signal, 11);
+    assert!(core.name.starts_with("core.sh."), "got {core:?}");
+    assert!(core.size_bytes > 0);
+
+    let host = tempfile::tempdir().unwrap();
+    bx.fetch_core_dump(&core.name, host.path()).await.unwrap();
+    let fetched = std::fs::metadata(host.path().join(&core.name)).unwrap();
+    assert_eq!(fetched.len(), core.size_bytes);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn oldest_core_dumps_are_rotated_out() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            capture_core_dumps: true,
+            ..alpine_options()
+        })
+        .await;
+    bx.start().await.unwrap();
+
+    for crashed in 1..=6 {
+        crash(&bx).await;
+        wait_for_cores(&bx, crashed.min(5)).await;
+    }
+    let cores = bx.list_core_dumps().await.unwrap();
+    assert_eq!(cores.len(), 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_core_dumps_without_capture() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    bx.start().await.unwrap();
+    crash(&bx).await;
+
+    assert!(bx.list_core_dumps().await.unwrap().is_empty());
+    let err = bx.fetch_core_dump("../etc/passwd", ".").await.unwrap_err();
+    assert!(matches!(err, boxlite::BoxliteError::InvalidArgument(_)));
+}
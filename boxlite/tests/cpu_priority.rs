@@ -0,0 +1,83 @@
+//! Integration tests for box CPU priority: two CPU-burning boxes pinned to
+//! one host CPU share it by the `cpu.weight` their priorities imply.
+//!
+//! Weights live in the cgroups the jailer creates, so the test is skipped on
+//! hosts without cgroup delegation.
+#![cfg(target_os = "linux")]
+
+use boxlite::runtime::advanced_options::SecurityOptions;
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{BoxOptions, BoxPriority, Capability};
+
+/// How long each box burns CPU.
+const BURN_SECS: u32 = 10;
+
+/// The host CPU both boxes are pinned to.
+const SHARED_CPU: usize = 0;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn high_priority_box_outruns_low_priority_box() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let capabilities = rt.capabilities().await.unwrap();
+    let delegation = capabilities.get(Capability::CgroupDelegation);
+    if !delegation.available {
+        eprintln!("skipping: no cgroup delegation ({})", delegation.detail);
+        return;
+    }
+
+    let low = rt.create_box(burner(BoxPriority::Low)).await;
+    let high = rt.create_box(burner(BoxPriority::High)).await;
+    for bx in [&low, &high] {
+        bx.start().await.unwrap();
+        pin_to_shared_cpu(bx);
+    }
+
+    let (low_loops, high_loops) = tokio::join!(burn(&low), burn(&high));
+    eprintln!("loops in {BURN_SECS}s: low {low_loops}, high {high_loops}");
+    // Weights 25 and 400: the high priority box should get most of the CPU
+    assert!(
+        high_loops > 3 * low_loops,
+        "high {high_loops} vs low {low_loops}"
+    );
+}
+
+fn burner(priority: BoxPriority) -> BoxOptions {
+    let mut options = alpine_options();
+    options.cpus = Some(1);
+    options.priority = priority;
+    options.advanced.security = SecurityOptions {
+        jailer_enabled: true,
+        ..Default::default()
+    };
+    options
+}
+
+/// Count loop iterations for `BURN_SECS`.
+async fn burn(bx: &TestBox) -> u64 {
+    let script = format!(
+        "end=$(($(date +%s) + {BURN_SECS})); n=0; \
+         while [ $(date +%s) -lt $end ]; do n=$((n + 1)); done; echo $n"
+    );
+    let output = bx.exec_output("sh", ["-c", &script]).await;
+    output.assert_success();
+    output.stdout.trim().parse().unwrap()
+}
+
+/// Pin every thread of the box's shim, vCPU threads included, to
+/// `SHARED_CPU`, so the boxes compete for it.
+fn pin_to_shared_cpu(bx: &TestBox) {
+    let pid = bx.info().pid.expect("running box has a pid");
+    let tasks = std::fs::read_dir(format!("/proc/{pid}/task")).unwrap();
+    for task in tasks {
+        let tid: libc::pid_t = task.unwrap().file_name().to_str().unwrap().parse().unwrap();
+        // SAFETY: cpu_set_t is plain data; sched_setaffinity only reads it
+        let rc = unsafe {
+            let mut set: libc::cpu_set_t = std::mem::zeroed();
+            libc::CPU_SET(SHARED_CPU, &mut set);
+            libc::sched_setaffinity(tid, std::mem::size_of::<libc::cpu_set_t>(), &set)
+        };
+        assert_eq!(rc, 0, "sched_setaffinity({tid})");
+    }
+}
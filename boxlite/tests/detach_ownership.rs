@@ -0,0 +1,363 @@
+//! Integration tests for box ownership across host process restarts.
+//!
+//! A box is owned by the process that started it with `detach=false`, or by
+//! the process that adopted it with `LiteBox::adopt()`; it stops when its
+//! owner exits. A detached box has no owner and outlives every process.
+//! A restart is simulated by dropping the runtime and opening a new one on
+//! the same home directory.
+//!
+//! | detach | after restart | stop()  | exec()            | runtime drop        |
+//! |--------|---------------|---------|-------------------|---------------------|
+//! | false  | box stopped   | no-op   | restarts, owned   | stops the box       |
+//! | true   | box running   | stops   | runs, not owned   | leaves it running   |
+//! | true   | + adopt()     | stops   | runs, owned       | stops the box       |
+//!
+//! `detach_all()` turns a process's owned boxes into unowned ones before it
+//! exits, and `adopt_all()` makes the next process their owner.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use boxlite::litebox::BoxCommand;
+use boxlite::runtime::options::{BoxOptions, BoxliteOptions};
+use boxlite::runtime::types::BoxStatus;
+use boxlite::testing::{alpine_options, short_temp_dir};
+use boxlite::util::is_process_alive;
+use boxlite::{BoxliteError, BoxliteRuntime};
+
+// ============================================================================
+// TEST FIXTURES
+// ============================================================================
+
+fn open_runtime(home: &Path) -> BoxliteRuntime {
+    BoxliteRuntime::new(BoxliteOptions {
+        home_dir: home.to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    })
+    .expect("Failed to create runtime")
+}
+
+/// Start a box in `runtime` and return its ID and shim PID.
+async fn start_box(runtime: &BoxliteRuntime, detach: bool) -> (String, u32) {
+    let handle = runtime
+        .create(
+            BoxOptions {
+                detach,
+                ..alpine_options()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+    let pid = handle.info().pid.expect("Running box should have a PID");
+    (handle.id().to_string(), pid)
+}
+
+/// Wait up to 10s for `pid` to exit.
+async fn wait_for_exit(pid: u32) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if !is_process_alive(pid) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+async fn exec_ok(runtime: &BoxliteRuntime, box_id: &str) {
+    let handle = runtime.get(box_id).await.unwrap().unwrap();
+    let mut execution = handle
+        .exec(BoxCommand::new("echo").args(["hi"]))
+        .await
+        .unwrap();
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+}
+
+// ============================================================================
+// detach=false
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn owned_box_is_stopped_after_restart() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    let pid = {
+        let runtime = open_runtime(home.path());
+        start_box(&runtime, false).await.1
+    };
+    assert!(
+        wait_for_exit(pid).await,
+        "Owned box should stop with its owner"
+    );
+
+    let runtime = open_runtime(home.path());
+    let info = runtime.list_info().await.unwrap().pop().unwrap();
+    assert_eq!(info.status, BoxStatus::Exited);
+
+    // stop() is a no-op, exec() starts it again under the new process
+    let handle = runtime.get(info.id.as_str()).await.unwrap().unwrap();
+    handle.stop().await.unwrap();
+    exec_ok(&runtime, info.id.as_str()).await;
+    let new_pid = runtime
+        .get_info(info.id.as_str())
+        .await
+        .unwrap()
+        .unwrap()
+        .pid
+        .unwrap();
+    drop(handle);
+
+    drop(runtime);
+    assert!(
+        wait_for_exit(new_pid).await,
+        "Box restarted by exec() should be owned by the new runtime"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn adopt_is_noop_for_owner() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+    let runtime = open_runtime(home.path());
+
+    let (box_id, pid) = start_box(&runtime, false).await;
+    let handle = runtime.get(&box_id).await.unwrap().unwrap();
+    handle.adopt().await.unwrap();
+    assert!(is_process_alive(pid));
+
+    drop(handle);
+    drop(runtime);
+    assert!(wait_for_exit(pid).await);
+}
+
+// ============================================================================
+// detach=true
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn detached_box_stops_from_new_process() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    let (box_id, pid) = {
+        let runtime = open_runtime(home.path());
+        start_box(&runtime, true).await
+    };
+    assert!(
+        is_process_alive(pid),
+        "Detached box should outlive its runtime"
+    );
+
+    let runtime = open_runtime(home.path());
+    let handle = runtime.get(&box_id).await.unwrap().unwrap();
+    handle.stop().await.unwrap();
+
+    assert!(!is_process_alive(pid), "stop() should wait for the shim");
+    let info = runtime.get_info(&box_id).await.unwrap().unwrap();
+    assert_eq!(info.status, BoxStatus::Exited);
+    runtime.remove(&box_id, false).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn detached_box_execs_from_new_process_without_taking_ownership() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    let (box_id, pid) = {
+        let runtime = open_runtime(home.path());
+        start_box(&runtime, true).await
+    };
+
+    {
+        let runtime = open_runtime(home.path());
+        exec_ok(&runtime, &box_id).await;
+        runtime.shutdown(None).await.unwrap();
+    }
+    assert!(
+        is_process_alive(pid),
+        "exec() from a new process should not make it the owner"
+    );
+
+    let runtime = open_runtime(home.path());
+    runtime.remove(&box_id, true).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn adopted_box_stops_with_adopting_process() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    let (box_id, pid) = {
+        let runtime = open_runtime(home.path());
+        start_box(&runtime, true).await
+    };
+
+    {
+        let runtime = open_runtime(home.path());
+        let handle = runtime.get(&box_id).await.unwrap().unwrap();
+        handle.adopt().await.unwrap();
+        exec_ok(&runtime, &box_id).await;
+    }
+    assert!(
+        wait_for_exit(pid).await,
+        "Adopted box should stop when the adopting runtime goes away"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_stops_only_adopted_boxes() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    let ((adopted_id, adopted_pid), (other_id, other_pid)) = {
+        let runtime = open_runtime(home.path());
+        (
+            start_box(&runtime, true).await,
+            start_box(&runtime, true).await,
+        )
+    };
+
+    let runtime = open_runtime(home.path());
+    let adopted = runtime.get(&adopted_id).await.unwrap().unwrap();
+    let other = runtime.get(&other_id).await.unwrap().unwrap();
+    adopted.adopt().await.unwrap();
+    exec_ok(&runtime, &other_id).await;
+
+    runtime.shutdown(None).await.unwrap();
+    assert!(!is_process_alive(adopted_pid), "Adopted box should stop");
+    assert!(is_process_alive(other_pid), "Unadopted box should survive");
+
+    drop((adopted, other));
+    drop(runtime);
+    let runtime = open_runtime(home.path());
+    runtime.remove(&other_id, true).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn adopt_requires_running_box() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+    let runtime = open_runtime(home.path());
+
+    let handle = runtime
+        .create(
+            BoxOptions {
+                detach: true,
+                ..alpine_options()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    let result = handle.adopt().await;
+    assert!(
+        matches!(result, Err(BoxliteError::InvalidState(_))),
+        "adopt() on a box that never started should fail, got {result:?}"
+    );
+}
+
+// ============================================================================
+// detach_all / adopt_all
+// ============================================================================
+
+/// Wait up to 20s for the captured stdout of `exec_id` to contain `needle`.
+async fn wait_for_output(runtime: &BoxliteRuntime, box_id: &str, exec_id: &str, needle: &str) {
+    let handle = runtime.get(box_id).await.unwrap().unwrap();
+    let deadline = Instant::now() + Duration::from_secs(20);
+    loop {
+        let stdout = handle.exec_output(exec_id).await.unwrap().stdout;
+        if stdout.contains(needle) {
+            return;
+        }
+        assert!(Instant::now() < deadline, "no {needle:?} in {stdout:?}");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn boxes_survive_restart_through_detach_all() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    // The old process starts an owned box running a long command, then
+    // hands the box off before it exits
+    let (box_id, pid, exec_id) = {
+        let runtime = open_runtime(home.path());
+        let (box_id, pid) = start_box(&runtime, false).await;
+        let handle = runtime.get(&box_id).await.unwrap().unwrap();
+        let execution = handle
+            .exec(
+                BoxCommand::new("sh")
+                    .args(["-c", "echo started; sleep 3; echo finished"])
+                    .detach(true),
+            )
+            .await
+            .unwrap();
+        let exec_id = execution.id().clone();
+
+        let results = runtime.detach_all().await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.as_str(), box_id);
+        results[0].1.as_ref().unwrap();
+        (box_id, pid, exec_id)
+    };
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(
+        is_process_alive(pid),
+        "Detached box should outlive its runtime"
+    );
+
+    // The new process finds it running and takes it back
+    let runtime = open_runtime(home.path());
+    let info = runtime.get_info(&box_id).await.unwrap().unwrap();
+    assert_eq!(info.status, BoxStatus::Running);
+    assert_eq!(info.pid, Some(pid));
+    let results = runtime.adopt_all().await.unwrap();
+    assert_eq!(results.len(), 1);
+    results[0].1.as_ref().unwrap();
+
+    // The command kept running across the restart
+    wait_for_output(&runtime, &box_id, &exec_id, "finished").await;
+    exec_ok(&runtime, &box_id).await;
+
+    drop(runtime);
+    assert!(
+        wait_for_exit(pid).await,
+        "Box adopted back should stop with its new owner"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn detach_all_skips_auto_remove_and_adopt_all_skips_detached() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+    let runtime = open_runtime(home.path());
+
+    let ephemeral = runtime
+        .create(
+            BoxOptions {
+                auto_remove: true,
+                ..alpine_options()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    ephemeral.start().await.unwrap();
+    let results = runtime.detach_all().await.unwrap();
+    assert_eq!(results.len(), 1);
+    assert!(
+        matches!(results[0].1, Err(BoxliteError::InvalidState(_))),
+        "auto_remove box should stay owned, got {:?}",
+        results[0].1
+    );
+    ephemeral.stop().await.unwrap();
+
+    let (detached_id, _) = start_box(&runtime, true).await;
+    assert!(runtime.adopt_all().await.unwrap().is_empty());
+    runtime.remove(&detached_id, true).await.unwrap();
+}
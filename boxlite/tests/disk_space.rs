@@ -0,0 +1,127 @@
+//! Integration tests for free space checks and `low_space_threshold_bytes`.
+//!
+//! The full-disk test mounts a small tmpfs as the BoxLite home and is
+//! skipped unless run as root.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use boxlite::testing::{TestRuntime, alpine_options, short_temp_dir};
+use boxlite::{BoxliteError, BoxliteOptions, BoxliteRuntime, CopyOptions, CreatePhase};
+
+/// Size of the tmpfs home: enough for alpine, short of the files below.
+const HOME_SIZE: &str = "512m";
+/// Size of the sparse files that cannot fit in the home.
+const TOO_BIG: u64 = 1024 * 1024 * 1024;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn low_space_is_reported_during_create() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::with_options(BoxliteOptions {
+        image_registries: vec![],
+        low_space_threshold_bytes: Some(u64::MAX),
+        ..Default::default()
+    });
+
+    let phases = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&phases);
+    let litebox = rt
+        .create_with_observer(alpine_options(), None, move |event| {
+            seen.lock().unwrap().push(event.phase.clone())
+        })
+        .await
+        .unwrap();
+
+    let phases = phases.lock().unwrap();
+    assert!(
+        matches!(
+            phases[0],
+            CreatePhase::LowSpace {
+                threshold_bytes: u64::MAX,
+                ..
+            }
+        ),
+        "{phases:?}"
+    );
+    assert_eq!(phases[1], CreatePhase::ResolvingImage);
+    assert_eq!(phases.last(), Some(&CreatePhase::Done));
+    assert!(rt.metrics().await.unwrap().low_space_warnings_total() >= 1);
+
+    rt.remove(litebox.id().as_str(), true).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn full_home_fails_import_and_copy_up_front() {
+    boxlite::skip_if_no_virtualization!();
+    if unsafe { libc::geteuid() } != 0 {
+        eprintln!("Skipping: mounting a tmpfs home needs root");
+        return;
+    }
+
+    let home = short_temp_dir();
+    let mounted = Command::new("mount")
+        .args(["-t", "tmpfs", "-o"])
+        .arg(format!("size={HOME_SIZE}"))
+        .arg("tmpfs")
+        .arg(home.path())
+        .status()
+        .unwrap();
+    assert!(mounted.success());
+    let _unmount = Unmount(home.path());
+
+    let rt = BoxliteRuntime::new(BoxliteOptions {
+        home_dir: home.path().to_path_buf(),
+        ..Default::default()
+    })
+    .unwrap();
+    let bx = rt.create(alpine_options(), None).await.unwrap();
+    bx.start().await.unwrap();
+
+    // Sparse files the size checks count in full
+    let outside = short_temp_dir();
+    let big = outside.path().join("big");
+    std::fs::File::create(&big)
+        .unwrap()
+        .set_len(TOO_BIG)
+        .unwrap();
+
+    let err = bx
+        .copy_into(&big, "/root/big", CopyOptions::default())
+        .await
+        .unwrap_err();
+    assert_storage_error(&err, "copy into box");
+
+    let err = rt.import(&big, "imported").await.unwrap_err();
+    assert_storage_error(&err, "import box");
+
+    // Nothing was staged
+    let leftovers: Vec<_> = std::fs::read_dir(home.path().join("tmp"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    assert!(leftovers.is_empty(), "{leftovers:?}");
+
+    rt.remove(bx.id().as_str(), true).await.unwrap();
+    rt.shutdown(None).await.unwrap();
+}
+
+fn assert_storage_error(err: &BoxliteError, operation: &str) {
+    assert!(matches!(err, BoxliteError::Storage(_)), "{err:?}");
+    let message = err.to_string();
+    assert!(
+        message.contains(&format!("not enough disk space to {operation}")),
+        "{message}"
+    );
+    assert!(message.contains("required"), "{message}");
+}
+
+/// Unmounts the tmpfs home when the test ends, including on panic.
+struct Unmount<'a>(&'a Path);
+
+impl Drop for Unmount<'_> {
+    fn drop(&mut self) {
+        let _ = Command::new("umount").arg("-l").arg(self.0).status();
+    }
+}
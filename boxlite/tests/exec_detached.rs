@@ -0,0 +1,56 @@
+//! Integration tests for output capture of detached executions.
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxliteError, LiteBox};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn detached_output_is_readable_by_id() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let mut execution = bx
+        .exec(
+            BoxCommand::new("sh")
+                .args(["-c", "echo out; echo err >&2"])
+                .detach(true),
+        )
+        .await
+        .unwrap();
+    let exec_id = execution.id().clone();
+    let paths = execution.output_paths().cloned().unwrap();
+    assert!(paths.stdout.ends_with(format!("execs/{exec_id}/stdout")));
+
+    // Nobody reads the streams; the guest drains them into the files
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+    drop(execution);
+
+    // A fresh handle, as a later process would have
+    let handle = rt.get(bx.id().as_str()).await.unwrap().unwrap();
+    let output = handle.exec_output(&exec_id).await.unwrap();
+    assert_eq!(output.stdout, "out\n");
+    assert_eq!(output.stderr, "err\n");
+    assert_eq!(std::fs::read_to_string(&paths.stdout).unwrap(), "out\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn attached_exec_is_not_captured() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let mut execution = bx.exec(BoxCommand::new("true")).await.unwrap();
+    assert!(execution.output_paths().is_none());
+    execution.wait().await.unwrap();
+
+    let err = LiteBox::exec_output(&bx, execution.id()).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::NotFound(_)), "got {err:?}");
+
+    let err = LiteBox::exec_output(&bx, "../escape").await.unwrap_err();
+    assert!(
+        matches!(err, BoxliteError::InvalidArgument(_)),
+        "got {err:?}"
+    );
+}
@@ -0,0 +1,95 @@
+//! Integration tests for how an exec's env is built from its layers
+//! (image, box, command sets and command edits).
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions};
+
+/// `PATH` of the alpine image, under every layer below.
+const IMAGE_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+fn printenv(keys: &str) -> BoxCommand {
+    BoxCommand::new("sh").args([
+        "-c",
+        &format!("for k in {keys}; do eval \"echo $k=\\${{$k-UNSET}}\"; done"),
+    ])
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn env_layers_combine_in_order() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            env: vec![
+                ("MODE".into(), "box".into()),
+                ("LIBS".into(), "/box/lib".into()),
+                ("DEBUG".into(), "1".into()),
+            ],
+            ..alpine_options()
+        })
+        .await;
+
+    // Nothing on the command: image and box env
+    bx.run_output(printenv("PATH MODE DEBUG"))
+        .await
+        .assert_success()
+        .assert_stdout_eq(&format!("PATH={IMAGE_PATH}\nMODE=box\nDEBUG=1\n"));
+
+    // A set replaces the value, an edit merges with the one in the box
+    bx.run_output(
+        printenv("PATH MODE LIBS DEBUG NEW")
+            .env_prepend_path("PATH", "/custom/bin", ":")
+            .env_set("MODE", "exec")
+            .env_append_path("LIBS", "/exec/lib", ":")
+            .env_unset("DEBUG")
+            .env_append_path("NEW", "x", ":"),
+    )
+    .await
+    .assert_success()
+    .assert_stdout_eq(&format!(
+        "PATH=/custom/bin:{IMAGE_PATH}\nMODE=exec\nLIBS=/box/lib:/exec/lib\nDEBUG=UNSET\nNEW=x\n"
+    ));
+
+    // An edit applies on top of the command's own set
+    bx.run_output(printenv("PATH").env_set("PATH", "/bin").env_prepend_path(
+        "PATH",
+        "/custom/bin",
+        ":",
+    ))
+    .await
+    .assert_success()
+    .assert_stdout_eq("PATH=/custom/bin:/bin\n");
+
+    // A later set drops the earlier edits of its key
+    bx.run_output(
+        printenv("PATH")
+            .env_prepend_path("PATH", "/custom/bin", ":")
+            .env_set("PATH", "/bin"),
+    )
+    .await
+    .assert_success()
+    .assert_stdout_eq("PATH=/bin\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn env_edits_apply_to_inherited_env() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            env: vec![("PATH".into(), "/box/bin:/usr/bin:/bin".into())],
+            ..alpine_options()
+        })
+        .await;
+
+    bx.run_output(printenv("PATH").inherit_runtime_env(true).env_prepend_path(
+        "PATH",
+        "/custom/bin",
+        ":",
+    ))
+    .await
+    .assert_success()
+    .assert_stdout_eq("PATH=/custom/bin:/box/bin:/usr/bin:/bin\n");
+}
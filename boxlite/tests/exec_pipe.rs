@@ -0,0 +1,115 @@
+//! Integration tests for `Execution::pipe_*`: output and input copied by the
+//! runtime, with slow sinks and sources holding the guest process back.
+
+use std::time::Duration;
+
+use boxlite::BoxCommand;
+use boxlite::testing::{TestRuntime, alpine_options};
+use tokio::io::AsyncReadExt;
+
+/// More than every buffer between the process and a pipe holds.
+const LARGE: u64 = 32 * 1024 * 1024;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipe_stdout_composes_with_wait() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let mut execution = bx.exec(BoxCommand::new("echo").arg("hello")).await.unwrap();
+    let (sink, mut reader) = tokio::io::duplex(1024);
+    let copied = execution.pipe_stdout(sink);
+    let status = execution.wait().await.unwrap();
+
+    assert_eq!(copied.await.unwrap().unwrap(), 6);
+    assert_eq!(status.exit_code, 0);
+    let mut out = String::new();
+    reader.read_to_string(&mut out).await.unwrap();
+    assert_eq!(out, "hello\n");
+
+    let again = execution.pipe_stdout(tokio::io::sink()).await.unwrap();
+    assert!(again.unwrap_err().to_string().contains("already taken"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn slow_sink_holds_the_process_back() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let script = format!("head -c {LARGE} /dev/zero; touch /tmp/done");
+    let mut execution = bx
+        .exec(BoxCommand::new("sh").args(["-c", &script]))
+        .await
+        .unwrap();
+    // Nothing reads the other end yet, so the sink takes 64 KiB and stalls
+    let (sink, mut reader) = tokio::io::duplex(64 * 1024);
+    let copied = execution.pipe_stdout(sink);
+
+    tokio::time::sleep(Duration::from_secs(3)).await;
+    assert!(!copied.is_finished());
+    bx.exec_output("test", ["-e", "/tmp/done"])
+        .await
+        .assert_exit_code(1);
+
+    let drained = tokio::io::copy(&mut reader, &mut tokio::io::sink())
+        .await
+        .unwrap();
+    assert_eq!(drained, LARGE);
+    assert_eq!(copied.await.unwrap().unwrap(), LARGE);
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+    bx.exec_output("test", ["-e", "/tmp/done"])
+        .await
+        .assert_success();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipe_combined_interleaves_both_streams() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let mut execution = bx
+        .exec(BoxCommand::new("sh").args(["-c", "echo out; echo err >&2"]))
+        .await
+        .unwrap();
+    let (sink, mut reader) = tokio::io::duplex(1024);
+    let copied = execution.pipe_combined(sink);
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+    assert_eq!(copied.await.unwrap().unwrap(), 8);
+
+    let mut out = String::new();
+    reader.read_to_string(&mut out).await.unwrap();
+    assert!(out.contains("out\n") && out.contains("err\n"), "{out:?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pipe_stdin_waits_for_the_process() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    // The process reads nothing for the first seconds
+    let mut execution = bx
+        .exec(BoxCommand::new("sh").args(["-c", "sleep 3; wc -c"]))
+        .await
+        .unwrap();
+    let source = tokio::io::repeat(b'x').take(LARGE);
+    let copied = execution.pipe_stdin(source);
+    let (sink, mut reader) = tokio::io::duplex(1024);
+    let output = execution.pipe_stdout(sink);
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    assert!(!copied.is_finished(), "stdin was read ahead of the process");
+
+    assert_eq!(copied.await.unwrap().unwrap(), LARGE);
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+    output.await.unwrap().unwrap();
+    let mut out = String::new();
+    reader.read_to_string(&mut out).await.unwrap();
+    assert_eq!(out.trim(), LARGE.to_string());
+}
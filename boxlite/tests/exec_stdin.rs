@@ -0,0 +1,95 @@
+//! Integration tests for piped (non-TTY) exec stdin: EOF reaches the
+//! process, and a process that stops reading does not break the writer.
+
+use boxlite::BoxCommand;
+use boxlite::testing::{TestRuntime, alpine_options};
+use futures::StreamExt;
+
+async fn stdout_of(execution: &mut boxlite::Execution) -> String {
+    let mut out = String::new();
+    if let Some(mut stdout) = execution.stdout() {
+        while let Some(chunk) = stdout.next().await {
+            out.push_str(&chunk);
+        }
+    }
+    out
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn finite_input_reaches_eof() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    // Without the close, both commands would wait for more input forever
+    let cases = [
+        (BoxCommand::new("cat"), "hello\nworld\n"),
+        (BoxCommand::new("wc").arg("-c"), "12\n"),
+    ];
+    for (command, expected) in cases {
+        let mut execution = bx.exec(command).await.unwrap();
+        let mut stdin = execution.stdin().unwrap();
+        stdin.write_all(b"hello\n").await.unwrap();
+        stdin.write_all(b"world\n").await.unwrap();
+        stdin.close();
+
+        let out = tokio::time::timeout(
+            std::time::Duration::from_secs(30),
+            stdout_of(&mut execution),
+        )
+        .await
+        .expect("process never saw EOF");
+        assert_eq!(out.trim_start(), expected);
+        assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dropping_stdin_closes_it() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let mut execution = bx.exec(BoxCommand::new("cat")).await.unwrap();
+    let mut stdin = execution.stdin().unwrap();
+    stdin.write_all(b"bye\n").await.unwrap();
+    drop(stdin);
+
+    let out = tokio::time::timeout(
+        std::time::Duration::from_secs(30),
+        stdout_of(&mut execution),
+    )
+    .await
+    .expect("process never saw EOF");
+    assert_eq!(out, "bye\n");
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn process_closing_stdin_first_stops_writes() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let mut execution = bx
+        .exec(BoxCommand::new("head").args(["-c", "5"]))
+        .await
+        .unwrap();
+    let mut stdin = execution.stdin().unwrap();
+
+    // Keep writing past what `head` reads; writes eventually fail once the
+    // process is gone, but the execution itself is unaffected
+    let chunk = vec![b'x'; 64 * 1024];
+    for _ in 0..64 {
+        if stdin.write_all(&chunk).await.is_err() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    assert_eq!(stdout_of(&mut execution).await, "xxxxx");
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+}
@@ -28,6 +28,7 @@ impl TestContext {
         let options = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         Self {
@@ -615,9 +616,9 @@ async fn test_metrics_returns_stopped_error() {
     let _ = ctx.runtime.remove(handle.id().as_str(), true).await;
 }
 
-/// Test that create() returns Stopped error after runtime.shutdown().
+/// Test that create() returns ShuttingDown error after runtime.shutdown().
 #[tokio::test]
-async fn test_create_after_shutdown_returns_stopped() {
+async fn test_create_after_shutdown_returns_shutting_down() {
     let ctx = TestContext::new();
 
     // Shutdown runtime
@@ -626,20 +627,67 @@ async fn test_create_after_shutdown_returns_stopped() {
     // Attempt to create box after shutdown
     let result = ctx.runtime.create(default_box_options(), None).await;
 
-    println!("=== test_create_after_shutdown_returns_stopped ===");
+    println!("=== test_create_after_shutdown_returns_shutting_down ===");
     match &result {
-        Err(BoxliteError::Stopped(msg)) => {
-            println!("✓ Got expected Stopped error: {}", msg);
+        Err(BoxliteError::ShuttingDown(msg)) => {
+            println!("✓ Got expected ShuttingDown error: {}", msg);
         }
         Err(e) => {
-            panic!("Expected Stopped error, got: {:?}", e);
+            panic!("Expected ShuttingDown error, got: {:?}", e);
         }
         Ok(_) => {
             panic!("Expected error, but create succeeded");
         }
     }
 
-    assert!(matches!(result, Err(BoxliteError::Stopped(_))));
+    assert!(matches!(result, Err(BoxliteError::ShuttingDown(_))));
+}
+
+/// Test that exec() on a box handle after runtime.shutdown() returns
+/// ShuttingDown rather than a generic Stopped error.
+#[tokio::test]
+async fn test_exec_after_shutdown_returns_shutting_down() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    ctx.runtime.shutdown(Some(5)).await.unwrap();
+
+    let result = handle.exec(BoxCommand::new("echo").arg("late")).await;
+    assert!(matches!(result, Err(BoxliteError::ShuttingDown(_))));
+}
+
+/// Test that exec() racing runtime.shutdown() either starts before the
+/// shutdown or fails with ShuttingDown, never with another error.
+#[tokio::test(flavor = "multi_thread")]
+async fn test_exec_racing_shutdown_has_deterministic_outcome() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+    let handle = std::sync::Arc::new(handle);
+
+    let execs: Vec<_> = (0..8)
+        .map(|_| {
+            let handle = std::sync::Arc::clone(&handle);
+            tokio::spawn(async move { handle.exec(BoxCommand::new("sleep").arg("1")).await })
+        })
+        .collect();
+    ctx.runtime.shutdown(Some(5)).await.unwrap();
+
+    for exec in execs {
+        match exec.await.unwrap() {
+            Ok(_) | Err(BoxliteError::ShuttingDown(_)) => {}
+            Err(e) => panic!("Expected Ok or ShuttingDown, got: {:?}", e),
+        }
+    }
 }
 
 /// Test that wait() returns promptly when box is stopped.
@@ -1008,3 +1056,184 @@ async fn test_exec_then_shutdown_sequential() {
     let shutdown_result = ctx.runtime.shutdown(Some(5)).await;
     assert!(shutdown_result.is_ok());
 }
+
+// ============================================================================
+// HANDLE LIFETIME TESTS
+// ============================================================================
+// An Execution holds its own reference to the box: dropping the LiteBox
+// handle does not affect it. Stopping or removing the box, or shutting down
+// the runtime, ends it with a Stopped error on its next operation.
+
+/// Dropping the box handle mid-exec leaves the execution working.
+#[tokio::test]
+async fn test_drop_box_then_read_stdout() {
+    use futures::StreamExt;
+
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+    let box_id = handle.id().clone();
+
+    let mut execution = handle
+        .exec(BoxCommand::new("sh").args(["-c", "sleep 1; echo done"]))
+        .await
+        .unwrap();
+    drop(handle);
+
+    let mut stdout = execution.stdout().unwrap();
+    let mut output = String::new();
+    while let Some(chunk) = stdout.next().await {
+        output.push_str(&chunk);
+    }
+    assert_eq!(output, "done\n");
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+
+    // The box itself is still running
+    let info = ctx
+        .runtime
+        .get_info(box_id.as_str())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(info.status.is_active(), "{:?}", info.status);
+
+    let _ = ctx.runtime.remove(box_id.as_str(), true).await;
+}
+
+/// Stopping the box mid-exec ends the streams and fails the next
+/// operations with Stopped.
+#[tokio::test]
+async fn test_stop_box_mid_exec_reports_stopped() {
+    use futures::StreamExt;
+
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    let mut execution = handle
+        .exec(BoxCommand::new("sleep").arg("3600"))
+        .await
+        .unwrap();
+    let mut stdout = execution.stdout().unwrap();
+    let mut stdin = execution.stdin().unwrap();
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    handle.stop().await.unwrap();
+
+    let end = tokio::time::timeout(Duration::from_secs(5), stdout.next()).await;
+    assert!(matches!(end, Ok(None)), "stdout should end after stop");
+    let result = tokio::time::timeout(Duration::from_secs(5), execution.wait())
+        .await
+        .expect("wait() should return after stop");
+    assert!(
+        matches!(result, Err(BoxliteError::Stopped(_))),
+        "{result:?}"
+    );
+    assert!(matches!(
+        execution.kill().await,
+        Err(BoxliteError::Stopped(_))
+    ));
+    assert!(matches!(
+        stdin.write(b"input").await,
+        Err(BoxliteError::Stopped(_))
+    ));
+
+    let _ = ctx.runtime.remove(handle.id().as_str(), true).await;
+}
+
+/// A result received before the stop is still returned.
+#[tokio::test]
+async fn test_stop_after_exit_keeps_result() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    let mut execution = handle
+        .exec(BoxCommand::new("sh").args(["-c", "exit 3"]))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    handle.stop().await.unwrap();
+
+    assert_eq!(execution.wait().await.unwrap().exit_code, 3);
+
+    let _ = ctx.runtime.remove(handle.id().as_str(), true).await;
+}
+
+/// Force-removing the box mid-exec fails the execution with Stopped, even
+/// after the handle it came from was dropped.
+#[tokio::test]
+async fn test_remove_box_mid_exec_reports_stopped() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+    let box_id = handle.id().clone();
+
+    let mut execution = handle
+        .exec(BoxCommand::new("sleep").arg("3600"))
+        .await
+        .unwrap();
+    drop(handle);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    ctx.runtime.remove(box_id.as_str(), true).await.unwrap();
+
+    let result = tokio::time::timeout(Duration::from_secs(5), execution.wait())
+        .await
+        .expect("wait() should return after remove");
+    assert!(
+        matches!(result, Err(BoxliteError::Stopped(_))),
+        "{result:?}"
+    );
+}
+
+/// Shutting down the runtime with live executions fails them with Stopped.
+#[tokio::test]
+async fn test_runtime_shutdown_with_live_executions_reports_stopped() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+
+    let mut executions = Vec::new();
+    for _ in 0..2 {
+        let execution = handle
+            .exec(BoxCommand::new("sleep").arg("3600"))
+            .await
+            .unwrap();
+        executions.push(execution);
+    }
+    drop(handle);
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    ctx.runtime.shutdown(Some(5)).await.unwrap();
+
+    for mut execution in executions {
+        let result = tokio::time::timeout(Duration::from_secs(5), execution.wait())
+            .await
+            .expect("wait() should return after shutdown");
+        assert!(
+            matches!(result, Err(BoxliteError::Stopped(_))),
+            "{result:?}"
+        );
+    }
+}
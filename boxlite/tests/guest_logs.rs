@@ -0,0 +1,67 @@
+//! Integration tests for reading the guest kernel and agent logs.
+
+use boxlite::runtime::types::BoxStatus;
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxliteError};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dmesg_returns_bounded_kernel_log() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    bx.start().await.unwrap();
+
+    let all = bx.guest_dmesg(10_000).await.unwrap();
+    assert!(
+        all.iter().any(|line| line.contains("Linux version")),
+        "got {all:?}"
+    );
+    assert!(all.iter().all(|line| !line.starts_with('<')));
+
+    let tail = bx.guest_dmesg(5).await.unwrap();
+    assert_eq!(tail.len(), 5);
+    assert_eq!(tail, all[all.len() - 5..]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn agent_log_returns_bounded_tail() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    let mut execution = bx.exec(BoxCommand::new("true")).await.unwrap();
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+
+    let log = bx.guest_agent_log(1024 * 1024).await.unwrap();
+    assert!(
+        log.text.contains("BoxLite Guest Agent starting"),
+        "got {log:?}"
+    );
+
+    let tail = bx.guest_agent_log(64).await.unwrap();
+    assert!(tail.text.chars().count() <= 64);
+    assert!(tail.truncated);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn logs_require_running_box() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    // Never started: reading the log must not boot the box
+    let err = bx.guest_dmesg(10).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidState(_)), "got {err:?}");
+    assert_eq!(bx.info().status, BoxStatus::Created);
+
+    bx.start().await.unwrap();
+    bx.stop().await.unwrap();
+    let stopped = rt.get(bx.id().as_str()).await.unwrap().unwrap();
+    let err = stopped.guest_agent_log(1024).await.unwrap_err();
+    match err {
+        BoxliteError::InvalidState(msg) => assert!(msg.contains("console.log"), "got {msg}"),
+        other => panic!("expected InvalidState, got {other:?}"),
+    }
+}
@@ -0,0 +1,64 @@
+//! Integration tests for `BoxOptions::auto_stop_after_idle`.
+
+use std::time::{Duration, Instant};
+
+use boxlite::BoxOptions;
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+
+const WINDOW: Duration = Duration::from_secs(3);
+
+fn auto_stopping() -> BoxOptions {
+    BoxOptions {
+        auto_stop_after_idle: Some(WINDOW),
+        ..alpine_options()
+    }
+}
+
+/// Wait for the box to stop, returning how long that took.
+async fn wait_stopped(bx: &TestBox, timeout: Duration) -> Duration {
+    let started = Instant::now();
+    while bx.info().status.is_running() {
+        assert!(started.elapsed() < timeout, "box still running");
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+    started.elapsed()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn idle_box_is_stopped_and_restarted_by_exec() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(auto_stopping()).await;
+    bx.start().await.unwrap();
+    let info = bx.info();
+    assert_eq!(info.auto_stop_after_idle, Some(WINDOW));
+    assert!(info.idle_remaining.is_some_and(|left| left <= WINDOW));
+
+    wait_stopped(&bx, Duration::from_secs(30)).await;
+    let exit = bx.last_exit().expect("exit file");
+    assert!(exit.info.is_idle_stopped(), "{exit:?}");
+    assert_eq!(exit.info.exit_code(), 0);
+    assert!(bx.info().idle_remaining.is_none());
+
+    // The next exec starts the box again
+    bx.exec_output("true", Vec::<String>::new())
+        .await
+        .assert_success();
+    assert!(bx.info().status.is_running());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn running_exec_keeps_box_alive() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(auto_stopping()).await;
+    bx.start().await.unwrap();
+
+    // Busy for twice the window, then idle for one window
+    bx.exec_output("sleep", ["6"]).await.assert_success();
+    assert!(bx.info().status.is_running());
+    let idle = wait_stopped(&bx, Duration::from_secs(30)).await;
+    assert!(idle >= Duration::from_secs(1), "stopped after {idle:?}");
+}
@@ -0,0 +1,54 @@
+//! Integration tests for images pinned by digest: a box created from
+//! `name@digest` or `name:tag@digest` runs that content and records the digest.
+
+use boxlite::BoxliteError;
+use boxlite::runtime::options::RootfsSpec;
+use boxlite::testing::{ALPINE_IMAGE, TestRuntime, alpine_options};
+
+#[tokio::test]
+async fn pinned_digest_is_pulled_and_recorded() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let image = rt.images().unwrap().pull(ALPINE_IMAGE).await.unwrap();
+    let digest = image.manifest_digest().to_string();
+
+    // The tag is ignored: the digest wins
+    for reference in [
+        format!("alpine@{digest}"),
+        format!("alpine:no-such-tag@{digest}"),
+    ] {
+        let mut options = alpine_options();
+        options.rootfs = RootfsSpec::Image(reference.clone());
+        let bx = rt.create_box(options).await;
+
+        bx.exec_output("echo", ["pinned"])
+            .await
+            .assert_success()
+            .assert_stdout_contains("pinned");
+        let info = bx.info();
+        assert_eq!(info.image, reference);
+        assert_eq!(info.image_digest.as_deref(), Some(digest.as_str()));
+    }
+}
+
+#[tokio::test]
+async fn tagged_image_has_no_digest() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.alpine().await;
+    assert_eq!(bx.info().image_digest, None);
+}
+
+#[tokio::test]
+async fn malformed_digest_fails_at_create() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let mut options = alpine_options();
+    options.rootfs = RootfsSpec::Image("alpine@sha256:1234".into());
+
+    let err = rt.create(options, None).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err}");
+}
@@ -0,0 +1,141 @@
+//! Integration tests for `get_info` / `list_info` reporting a box as it is,
+//! not as this runtime last saw it.
+//!
+//! The cross-process test re-runs this test binary as process A to start a
+//! detached box; the test process (B) then opens its own runtime on the same
+//! home directory.
+
+use std::path::Path;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use boxlite::BoxliteRuntime;
+use boxlite::runtime::options::{BoxOptions, BoxliteOptions};
+use boxlite::runtime::types::BoxStatus;
+use boxlite::testing::{alpine_options, short_temp_dir};
+use boxlite::util::{is_process_alive, kill_process};
+
+/// Home directory the child process starts its box in.
+const CHILD_HOME_ENV: &str = "BOXLITE_TEST_RECONCILE_HOME";
+
+// ============================================================================
+// TEST FIXTURES
+// ============================================================================
+
+fn open_runtime(home: &Path) -> BoxliteRuntime {
+    BoxliteRuntime::new(BoxliteOptions {
+        home_dir: home.to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    })
+    .expect("Failed to create runtime")
+}
+
+async fn start_detached(runtime: &BoxliteRuntime) -> (String, u32) {
+    let handle = runtime
+        .create(
+            BoxOptions {
+                detach: true,
+                auto_remove: false,
+                ..alpine_options()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    handle.start().await.unwrap();
+    let pid = handle.info().pid.expect("Running box should have a PID");
+    (handle.id().to_string(), pid)
+}
+
+/// Wait up to 10s for `pid` to exit.
+async fn wait_for_exit(pid: u32) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if !is_process_alive(pid) {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+/// Process A: start a detached box in `$BOXLITE_TEST_RECONCILE_HOME` and
+/// print its ID and shim PID. Only runs when spawned by
+/// `detached_box_from_other_process_reports_running`.
+#[tokio::test(flavor = "multi_thread")]
+#[ignore = "helper process for detached_box_from_other_process_reports_running"]
+async fn child_starts_detached_box() {
+    let Ok(home) = std::env::var(CHILD_HOME_ENV) else {
+        return;
+    };
+    let runtime = open_runtime(Path::new(&home));
+    let (box_id, pid) = start_detached(&runtime).await;
+    println!("BOX {} {}", box_id, pid);
+}
+
+// ============================================================================
+// TESTS
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn detached_box_from_other_process_reports_running() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    let output = Command::new(std::env::current_exe().unwrap())
+        .args([
+            "child_starts_detached_box",
+            "--exact",
+            "--ignored",
+            "--nocapture",
+        ])
+        .env(CHILD_HOME_ENV, home.path())
+        .output()
+        .expect("Failed to run child process");
+    assert!(output.status.success(), "child process failed: {output:?}");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("BOX "))
+        .expect("child process should print the box");
+    let (box_id, pid) = line.split_once(' ').unwrap();
+    let pid: u32 = pid.parse().unwrap();
+
+    let runtime = open_runtime(home.path());
+    let info = runtime.get_info(box_id).await.unwrap().unwrap();
+    assert_eq!(info.status, BoxStatus::Running);
+    assert_eq!(info.pid, Some(pid));
+
+    let listed = runtime.list_info().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].status, BoxStatus::Running);
+    assert_eq!(listed[0].pid, Some(pid));
+
+    runtime.remove(box_id, true).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn killed_shim_reports_stopped_immediately() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+    let runtime = open_runtime(home.path());
+
+    let (box_id, pid) = start_detached(&runtime).await;
+    // Keep a handle cached in this runtime, with its in-memory state
+    let handle = runtime.get(&box_id).await.unwrap().unwrap();
+
+    kill_process(pid);
+    assert!(wait_for_exit(pid).await);
+
+    let info = runtime.get_info(&box_id).await.unwrap().unwrap();
+    assert_eq!(info.status, BoxStatus::Exited);
+    assert_eq!(info.pid, None);
+
+    let listed = runtime.list_info().await.unwrap();
+    assert_eq!(listed[0].status, BoxStatus::Exited);
+    assert_eq!(listed[0].pid, None);
+
+    drop(handle);
+    runtime.remove(&box_id, true).await.unwrap();
+}
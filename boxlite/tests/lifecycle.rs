@@ -21,6 +21,7 @@ impl TestContext {
         let options = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         Self {
@@ -262,7 +263,7 @@ async fn get_info_returns_box_metadata() {
     assert_eq!(info.id, box_id);
     assert_eq!(
         info.status,
-        BoxStatus::Configured,
+        BoxStatus::Created,
         "Expected Configured after create(), got {:?}",
         info.status
     );
@@ -493,7 +494,7 @@ async fn stop_marks_box_as_stopped() {
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(info.status, BoxStatus::Stopped);
+    assert_eq!(info.status, BoxStatus::Exited);
 
     // Cleanup
     ctx.runtime.remove(box_id.as_str(), false).await.unwrap();
@@ -527,7 +528,7 @@ async fn litebox_info_returns_correct_metadata() {
         .unwrap()
         .expect("info should be available");
     assert_eq!(info.id, box_id);
-    assert_eq!(info.status, BoxStatus::Configured);
+    assert_eq!(info.status, BoxStatus::Created);
     assert_eq!(info.cpus, 2); // Default value
     assert_eq!(info.memory_mib, 512); // Default value
 
@@ -595,6 +596,7 @@ async fn boxes_persist_across_runtime_restart() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         let litebox = runtime
@@ -623,6 +625,7 @@ async fn boxes_persist_across_runtime_restart() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -632,7 +635,7 @@ async fn boxes_persist_across_runtime_restart() {
 
         // Status should be Stopped
         let status = &boxes[0].status;
-        assert_eq!(status, &BoxStatus::Stopped);
+        assert_eq!(status, &BoxStatus::Exited);
 
         // Cleanup
         runtime.remove(box_id.as_str(), false).await.unwrap();
@@ -653,6 +656,7 @@ async fn multiple_boxes_persist_and_recover_without_lock_errors() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -711,6 +715,7 @@ async fn multiple_boxes_persist_and_recover_without_lock_errors() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime after restart");
 
@@ -732,7 +737,7 @@ async fn multiple_boxes_persist_and_recover_without_lock_errors() {
         for info in &boxes {
             assert_eq!(
                 info.status,
-                BoxStatus::Stopped,
+                BoxStatus::Exited,
                 "Recovered box should be stopped"
             );
         }
@@ -820,7 +825,7 @@ async fn auto_remove_false_preserves_box_on_stop() {
         .await
         .unwrap()
         .unwrap();
-    assert_eq!(info.status, BoxStatus::Stopped);
+    assert_eq!(info.status, BoxStatus::Exited);
 
     // Cleanup manually
     ctx.runtime.remove(box_id.as_str(), false).await.unwrap();
@@ -886,6 +891,7 @@ async fn recovery_removes_auto_remove_true_boxes() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -935,6 +941,7 @@ async fn recovery_removes_auto_remove_true_boxes() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime after restart");
 
@@ -974,6 +981,7 @@ async fn recovery_removes_orphaned_stopped_boxes_without_directory() {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
 
@@ -1008,6 +1016,7 @@ async fn recovery_removes_orphaned_stopped_boxes_without_directory() {
         let options = BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime after restart");
 
@@ -1025,7 +1034,7 @@ async fn recovery_removes_orphaned_stopped_boxes_without_directory() {
         );
         assert_eq!(
             boxes[0].status,
-            BoxStatus::Stopped,
+            BoxStatus::Exited,
             "Box should remain in Stopped status"
         );
 
@@ -0,0 +1,102 @@
+//! Integration tests for `LiteBox::mount_readonly()`.
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p boxlite --features fuse --test mount -- --nocapture
+//! ```
+
+#![cfg(feature = "fuse")]
+
+use boxlite::BoxliteError;
+use boxlite::testing::{TestRuntime, alpine_options};
+
+/// FUSE must be usable by the test user, on top of virtualization.
+fn fuse_available() -> bool {
+    !cfg!(target_os = "linux") || std::path::Path::new("/dev/fuse").exists()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mount_reads_stopped_box_files() {
+    boxlite::skip_if_no_virtualization!();
+    if !fuse_available() {
+        eprintln!("Skipping: /dev/fuse is not available");
+        return;
+    }
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    bx.exec_output(
+        "sh",
+        [
+            "-c",
+            "mkdir -p /root/data && echo hello > /root/data/greeting",
+        ],
+    )
+    .await
+    .assert_success();
+    let release = bx.exec_output("cat", ["/etc/alpine-release"]).await;
+    release.assert_success();
+    bx.stop().await.unwrap();
+
+    let mountpoint = tempfile::tempdir().unwrap();
+    let mut handle = bx.mount_readonly(mountpoint.path()).await.unwrap();
+    let root = handle.mountpoint().to_path_buf();
+
+    // Image files and files written by the box are both visible
+    assert_eq!(
+        std::fs::read_to_string(root.join("etc/alpine-release")).unwrap(),
+        release.stdout
+    );
+    assert_eq!(
+        std::fs::read_to_string(root.join("root/data/greeting")).unwrap(),
+        "hello\n"
+    );
+    let etc: Vec<_> = std::fs::read_dir(root.join("etc"))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert!(etc.iter().any(|name| name == "os-release"), "{etc:?}");
+    assert!(std::fs::symlink_metadata(root.join("bin/sh")).is_ok());
+
+    // Writes are rejected
+    assert!(std::fs::write(root.join("root/data/greeting"), "changed").is_err());
+    assert!(std::fs::write(root.join("root/new"), "new").is_err());
+    assert!(std::fs::remove_file(root.join("etc/alpine-release")).is_err());
+
+    // The box can't start while mounted
+    match bx.start().await {
+        Err(BoxliteError::Busy { operation, .. }) => assert_eq!(operation, "mount"),
+        other => panic!("expected Busy, got {other:?}"),
+    }
+
+    handle.unmount().unwrap();
+    assert!(!root.join("etc").exists());
+    drop(handle);
+
+    bx.start().await.unwrap();
+    bx.exec_output("cat", ["/root/data/greeting"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("hello\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn mount_rejects_running_box_and_bad_mountpoint() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    bx.exec_output("true", Vec::<String>::new())
+        .await
+        .assert_success();
+
+    let mountpoint = tempfile::tempdir().unwrap();
+    let err = bx.mount_readonly(mountpoint.path()).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidState(_)), "{err:?}");
+
+    bx.stop().await.unwrap();
+    let file = mountpoint.path().join("file");
+    std::fs::write(&file, "").unwrap();
+    let err = bx.mount_readonly(&file).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err:?}");
+}
@@ -0,0 +1,55 @@
+//! Integration tests for boxes created with `NetworkMode::None`.
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxOptions, CopyOptions, NetworkMode};
+
+fn no_network_options() -> BoxOptions {
+    BoxOptions {
+        network: NetworkMode::None,
+        ..alpine_options()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_network_box_cannot_reach_outside() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(no_network_options()).await;
+
+    bx.exec_output("echo", ["still here"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("still here\n");
+
+    let wget = bx
+        .exec_output("wget", ["-q", "-T", "5", "-O", "-", "http://example.com"])
+        .await;
+    assert_ne!(wget.exit_code, 0, "wget should fail without a network");
+
+    // Loopback is the only interface
+    let links = bx.exec_output("ls", ["/sys/class/net"]).await;
+    links.assert_success().assert_stdout_eq("lo\n");
+
+    let info = rt.get_info(bx.id().as_str()).await.unwrap().unwrap();
+    assert_eq!(info.network_mode, NetworkMode::None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn no_network_box_copies_files() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(no_network_options()).await;
+    let host = tempfile::tempdir().unwrap();
+    let file = host.path().join("hello.txt");
+    std::fs::write(&file, "hello\n").unwrap();
+
+    bx.copy_into(&file, "/tmp/hello.txt", CopyOptions::default())
+        .await
+        .unwrap();
+    bx.exec_output("cat", ["/tmp/hello.txt"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("hello\n");
+}
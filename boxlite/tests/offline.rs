@@ -0,0 +1,73 @@
+//! Integration tests for offline (air-gap) mode.
+
+use boxlite::runtime::constants::images::INIT_ROOTFS;
+use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+use boxlite::{BoxCommand, BoxliteError, BoxliteRuntime};
+use tempfile::TempDir;
+
+fn runtime_options(home: &TempDir, offline: bool) -> BoxliteOptions {
+    BoxliteOptions {
+        home_dir: home.path().to_path_buf(),
+        offline,
+        ..Default::default()
+    }
+}
+
+/// Warm the image cache with an online runtime, then release the home lock.
+///
+/// The guest init rootfs image is pulled too, since booting any box needs it.
+async fn warm_cache(home: &TempDir, image: &str) {
+    let runtime = BoxliteRuntime::new(runtime_options(home, false)).unwrap();
+    let images = runtime.images().unwrap();
+    images.pull(INIT_ROOTFS).await.unwrap();
+    images.pull(image).await.unwrap();
+    runtime.shutdown(None).await.unwrap();
+}
+
+#[tokio::test]
+async fn offline_create_from_cached_image_succeeds() {
+    let home = TempDir::new_in("/tmp").unwrap();
+    warm_cache(&home, "alpine:latest").await;
+
+    let runtime = BoxliteRuntime::new(runtime_options(&home, true)).unwrap();
+    let litebox = runtime
+        .create(
+            BoxOptions {
+                rootfs: RootfsSpec::Image("alpine:latest".into()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    let mut execution = litebox
+        .exec(BoxCommand::new("echo").arg("offline"))
+        .await
+        .unwrap();
+    let result = execution.wait().await.unwrap();
+    assert_eq!(result.exit_code, 0);
+
+    litebox.stop().await.unwrap();
+    runtime.shutdown(None).await.unwrap();
+}
+
+#[tokio::test]
+async fn offline_pull_of_uncached_image_fails_with_offline_error() {
+    let home = TempDir::new_in("/tmp").unwrap();
+    let runtime = BoxliteRuntime::new(runtime_options(&home, true)).unwrap();
+
+    let err = runtime
+        .images()
+        .unwrap()
+        .pull("busybox:1.36")
+        .await
+        .unwrap_err();
+
+    match err {
+        BoxliteError::OfflineMode(msg) => {
+            assert!(msg.contains("docker.io"), "should name the host: {msg}");
+        }
+        other => panic!("expected OfflineMode, got {other:?}"),
+    }
+}
@@ -35,6 +35,7 @@ impl TestContext {
         let options = BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
         Self {
@@ -331,6 +332,7 @@ async fn detached_box_survives_runtime_drop() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -370,6 +372,7 @@ async fn detached_box_survives_runtime_drop() {
     let runtime = BoxliteRuntime::new(BoxliteOptions {
         home_dir,
         image_registries: vec![],
+        ..Default::default()
     })
     .unwrap();
     runtime.remove(&box_id, true).await.unwrap();
@@ -392,6 +395,7 @@ async fn non_detached_box_exits_on_runtime_drop() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -460,6 +464,7 @@ async fn detached_box_recoverable_after_restart() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -485,6 +490,7 @@ async fn detached_box_recoverable_after_restart() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -511,7 +517,7 @@ async fn detached_box_recoverable_after_restart() {
             .await
             .unwrap()
             .expect("Box should exist");
-        assert_eq!(info.status, BoxStatus::Stopped);
+        assert_eq!(info.status, BoxStatus::Exited);
 
         // Cleanup
         runtime.remove(&box_id, false).await.unwrap();
@@ -578,6 +584,7 @@ async fn recovery_with_live_process() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -606,6 +613,7 @@ async fn recovery_with_live_process() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -635,6 +643,7 @@ async fn recovery_with_dead_process() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -671,6 +680,7 @@ async fn recovery_with_dead_process() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -682,8 +692,8 @@ async fn recovery_with_dead_process() {
 
         assert_eq!(
             info.status,
-            BoxStatus::Stopped,
-            "Dead process should be marked Stopped"
+            BoxStatus::Exited,
+            "Dead process should be marked Exited"
         );
         assert!(info.pid.is_none(), "Stopped box should have no PID");
 
@@ -710,6 +720,7 @@ async fn recovery_with_missing_pid_file() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -739,6 +750,7 @@ async fn recovery_with_missing_pid_file() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -750,7 +762,7 @@ async fn recovery_with_missing_pid_file() {
 
         assert_eq!(
             info.status,
-            BoxStatus::Stopped,
+            BoxStatus::Exited,
             "Missing PID file should result in Stopped status"
         );
 
@@ -770,6 +782,7 @@ async fn recovery_with_corrupted_pid_file() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -799,6 +812,7 @@ async fn recovery_with_corrupted_pid_file() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -810,7 +824,7 @@ async fn recovery_with_corrupted_pid_file() {
 
         assert_eq!(
             info.status,
-            BoxStatus::Stopped,
+            BoxStatus::Exited,
             "Corrupted PID file should result in Stopped status"
         );
 
@@ -834,6 +848,7 @@ async fn recovery_preserves_stopped_boxes() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir: home_dir.clone(),
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -865,6 +880,7 @@ async fn recovery_preserves_stopped_boxes() {
         let runtime = BoxliteRuntime::new(BoxliteOptions {
             home_dir,
             image_registries: vec![],
+            ..Default::default()
         })
         .unwrap();
 
@@ -874,7 +890,7 @@ async fn recovery_preserves_stopped_boxes() {
             .unwrap()
             .expect("Box should exist");
 
-        assert_eq!(info.status, BoxStatus::Stopped);
+        assert_eq!(info.status, BoxStatus::Exited);
         assert!(info.pid.is_none());
 
         // Cleanup
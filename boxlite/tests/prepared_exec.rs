@@ -0,0 +1,135 @@
+//! Integration tests for prepared commands (`LiteBox::prepare_exec`).
+
+use boxlite::BoxCommand;
+use boxlite::BoxliteRuntime;
+use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
+use futures::StreamExt;
+use tempfile::TempDir;
+
+// ============================================================================
+// TEST FIXTURES
+// ============================================================================
+
+/// Test context with isolated runtime and automatic cleanup.
+struct TestContext {
+    runtime: BoxliteRuntime,
+    _temp_dir: TempDir,
+}
+
+impl TestContext {
+    fn new() -> Self {
+        // Use /tmp directly to avoid macOS's long temp paths that exceed SUN_LEN
+        let temp_dir = TempDir::new_in("/tmp").expect("Failed to create temp dir");
+        let options = BoxliteOptions {
+            home_dir: temp_dir.path().to_path_buf(),
+            image_registries: vec![],
+            ..Default::default()
+        };
+        let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
+        Self {
+            runtime,
+            _temp_dir: temp_dir,
+        }
+    }
+}
+
+fn default_box_options() -> BoxOptions {
+    BoxOptions {
+        rootfs: RootfsSpec::Image("alpine:latest".into()),
+        auto_remove: false,
+        ..Default::default()
+    }
+}
+
+async fn stdout_of(mut execution: boxlite::Execution) -> String {
+    let mut out = String::new();
+    if let Some(mut stdout) = execution.stdout() {
+        while let Some(chunk) = stdout.next().await {
+            out.push_str(&chunk);
+        }
+    }
+    execution.wait().await.unwrap();
+    out
+}
+
+// ============================================================================
+// PREPARED EXEC TESTS
+// ============================================================================
+
+#[tokio::test]
+async fn prepared_exec_appends_args_per_run() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+
+    let prepared = handle
+        .prepare_exec(
+            BoxCommand::new("sh")
+                .args(["-c", "echo \"$GREETING $1\"", "sh"])
+                .env("GREETING", "job"),
+        )
+        .await
+        .unwrap();
+
+    for job in ["a", "b", "c"] {
+        let execution = prepared.run([job]).await.unwrap();
+        assert_eq!(stdout_of(execution).await, format!("job {job}\n"));
+    }
+
+    handle.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn prepare_exec_rejects_missing_working_dir() {
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+
+    let result = handle
+        .prepare_exec(BoxCommand::new("pwd").working_dir("/does/not/exist"))
+        .await;
+    assert!(result.is_err());
+
+    handle.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn prepared_templates_are_capped_and_freed_on_drop() {
+    use boxlite::BoxliteError;
+    use boxlite_shared::constants::prepared::MAX_TEMPLATES;
+
+    let ctx = TestContext::new();
+    let handle = ctx
+        .runtime
+        .create(default_box_options(), None)
+        .await
+        .unwrap();
+
+    let mut held = Vec::with_capacity(MAX_TEMPLATES);
+    for _ in 0..MAX_TEMPLATES {
+        held.push(handle.prepare_exec(BoxCommand::new("true")).await.unwrap());
+    }
+    let result = handle.prepare_exec(BoxCommand::new("true")).await;
+    assert!(matches!(result, Err(BoxliteError::InvalidState(_))));
+
+    // Dropping a handle unregisters its template in the background
+    held.pop();
+    let mut freed = false;
+    for _ in 0..50 {
+        if handle.prepare_exec(BoxCommand::new("true")).await.is_ok() {
+            freed = true;
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(freed, "dropped template was not unregistered");
+
+    drop(held);
+    handle.stop().await.unwrap();
+}
@@ -0,0 +1,118 @@
+//! Integration tests for one-time provisioning via `BoxOptions::setup_commands`.
+
+use std::path::PathBuf;
+
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions, BoxliteError, LiteBox, SetupFailurePolicy};
+use futures::StreamExt;
+
+/// Setup command that appends a line to `/root/runs` on every run.
+fn counting_command() -> BoxCommand {
+    BoxCommand::new("sh").args(["-c", "echo run >> /root/runs; echo provisioned"])
+}
+
+fn provision_log(rt: &TestRuntime, bx: &TestBox) -> PathBuf {
+    rt.home_dir()
+        .join("boxes")
+        .join(bx.id().as_str())
+        .join("provision.log")
+}
+
+/// Number of times the counting command has run.
+async fn runs(litebox: &LiteBox) -> usize {
+    let mut execution = litebox
+        .exec(BoxCommand::new("cat").arg("/root/runs"))
+        .await
+        .unwrap();
+    let stdout = execution
+        .stdout()
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await
+        .concat();
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+    stdout.lines().count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn setup_commands_run_once_across_restarts() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            setup_commands: vec![counting_command()],
+            ..alpine_options()
+        })
+        .await;
+
+    bx.start().await.unwrap();
+    assert_eq!(runs(&bx).await, 1);
+    let log = std::fs::read_to_string(provision_log(&rt, &bx)).unwrap();
+    assert!(log.contains("provisioned"), "log: {log}");
+
+    // stop() invalidates the handle; `bx` still removes the box on drop
+    bx.stop().await.unwrap();
+    let restarted = rt.get(bx.id().as_str()).await.unwrap().unwrap();
+    restarted.start().await.unwrap();
+    assert_eq!(runs(&restarted).await, 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reprovision_reruns_setup_commands() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            setup_commands: vec![counting_command()],
+            ..alpine_options()
+        })
+        .await;
+
+    bx.start().await.unwrap();
+    bx.reprovision().await.unwrap();
+    assert_eq!(runs(&bx).await, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failing_setup_command_fails_start() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            setup_commands: vec![
+                BoxCommand::new("sh").args(["-c", "echo broken >&2; exit 3"]),
+                counting_command(),
+            ],
+            ..alpine_options()
+        })
+        .await;
+
+    let err = bx.start().await.unwrap_err();
+    assert!(matches!(err, BoxliteError::Execution(_)), "got {err:?}");
+    assert!(err.to_string().contains("exited with code 3"), "got {err}");
+
+    // The run stopped at the failing command
+    let log = std::fs::read_to_string(provision_log(&rt, &bx)).unwrap();
+    assert!(log.contains("broken"), "log: {log}");
+    assert!(!log.contains("provisioned"), "log: {log}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn warn_and_continue_finishes_start() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            setup_commands: vec![BoxCommand::new("false"), counting_command()],
+            setup_failure: SetupFailurePolicy::WarnAndContinue,
+            ..alpine_options()
+        })
+        .await;
+
+    bx.start().await.unwrap();
+    assert_eq!(runs(&bx).await, 1);
+}
@@ -0,0 +1,58 @@
+//! Integration tests for `BoxOptions::read_only_rootfs` and
+//! `BoxOptions::writable_paths`.
+
+use boxlite::BoxOptions;
+use boxlite::testing::{TestRuntime, alpine_options};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_only_rootfs_keeps_tmpfs_paths_writable() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            read_only_rootfs: true,
+            writable_paths: vec!["/var/cache".into()],
+            ..alpine_options()
+        })
+        .await;
+
+    bx.exec_output("touch", ["/etc/boxlite"])
+        .await
+        .assert_exit_code(1)
+        .assert_stderr_contains("Read-only file system");
+
+    for dir in ["/tmp", "/run", "/var/cache"] {
+        let script = format!("echo ok > {dir}/f && cat {dir}/f");
+        bx.exec_output("sh", ["-c", script.as_str()])
+            .await
+            .assert_success()
+            .assert_stdout_eq("ok\n");
+    }
+
+    let info = bx.info();
+    assert!(info.read_only_rootfs);
+    assert_eq!(info.writable_paths, vec!["/var/cache".to_string()]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writable_path_hides_image_contents() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            writable_paths: vec!["/etc/apk/".into()],
+            ..alpine_options()
+        })
+        .await;
+
+    // The tmpfs starts empty, and the rest of the rootfs stays writable
+    bx.exec_output("ls", ["-A", "/etc/apk"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("");
+    bx.exec_output("touch", ["/etc/boxlite"])
+        .await
+        .assert_success();
+}
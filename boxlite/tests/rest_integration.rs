@@ -150,7 +150,7 @@ async fn test_rest_start_stop_box() {
         .await
         .expect("get_info failed")
         .expect("box not found");
-    assert_eq!(info.status.to_string(), "stopped");
+    assert_eq!(info.status.to_string(), "exited");
 
     rt.remove(&id_str, true).await.ok();
 }
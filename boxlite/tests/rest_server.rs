@@ -0,0 +1,281 @@
+//! Compatibility tests: the REST client backend against the embedded server.
+//!
+//! Each test serves a local runtime with `RestServer` on a free port and
+//! drives it through `BoxliteRuntime::rest`.
+//!
+//! Run with:
+//! ```bash
+//! cargo test -p boxlite --features rest-server --test rest_server -- --nocapture
+//! ```
+
+#![cfg(feature = "rest-server")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use boxlite::litebox::CopyOptions;
+use boxlite::rest::server::{RestServer, RestServerBuilder, ScopedToken, TokenScope};
+use boxlite::testing::{TestBox, TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxliteError, BoxliteRestOptions, BoxliteRuntime, ListFilter};
+
+const TOKEN: &str = "test-token";
+const READER: &str = "reader-token";
+const OPERATOR: &str = "operator-token";
+
+/// Serve `runtime` on a free port with the shared token.
+fn serve(runtime: &BoxliteRuntime) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    serve_with(runtime, |builder| builder.auth(TOKEN))
+}
+
+/// Serve `runtime` on a free port with the shared token (full scope), a
+/// read-only token and an exec token.
+fn serve_scoped(runtime: &BoxliteRuntime) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    serve_with(runtime, |builder| {
+        builder
+            .auth(TOKEN)
+            .token(ScopedToken::new(READER, TokenScope::ReadOnly))
+            .token(ScopedToken::new(OPERATOR, TokenScope::Exec))
+    })
+}
+
+fn serve_with(
+    runtime: &BoxliteRuntime,
+    configure: impl FnOnce(RestServerBuilder) -> RestServerBuilder,
+) -> (SocketAddr, tokio::task::JoinHandle<()>) {
+    let builder = RestServer::builder()
+        .runtime(runtime.clone())
+        .bind("127.0.0.1:0".parse().unwrap());
+    let server = configure(builder).build().expect("Failed to build server");
+    let addr = server.local_addr();
+    let handle = tokio::spawn(async move {
+        server.serve().await.expect("Server failed");
+    });
+    (addr, handle)
+}
+
+/// REST-backed runtime talking to the server at `addr`.
+fn client(addr: SocketAddr, secret: &str) -> BoxliteRuntime {
+    BoxliteRuntime::rest(
+        BoxliteRestOptions::new(format!("http://{}", addr))
+            .with_credentials("test-client".into(), secret.into()),
+    )
+    .expect("Failed to create REST runtime")
+}
+
+// ============================================================================
+// AUTH AND DISCOVERY (no VM needed)
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn wrong_secret_is_rejected() {
+    let rt = TestRuntime::new();
+    let (addr, _server) = serve(&rt);
+
+    let err = client(addr, "wrong").list_info().await.unwrap_err();
+    assert!(matches!(err, BoxliteError::Config(_)), "got {err:?}");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn missing_box_is_not_found() {
+    let rt = TestRuntime::new();
+    let (addr, _server) = serve(&rt);
+    let remote = client(addr, TOKEN);
+
+    assert!(remote.list_info().await.unwrap().is_empty());
+    assert!(remote.get_info("no-such-box").await.unwrap().is_none());
+    assert!(!remote.exists("no-such-box").await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scopes_are_enforced_per_endpoint_class() {
+    use reqwest::Method;
+
+    let rt = TestRuntime::new();
+    let (addr, _server) = serve_scoped(&rt);
+    let http = reqwest::Client::new();
+    let base = format!("http://{}/v1/default", addr);
+
+    // Requests the scope allows get past authorization and then fail
+    // because the box does not exist or the body is not valid JSON.
+    let requests = [
+        (Method::GET, "/boxes", TokenScope::ReadOnly),
+        (Method::GET, "/boxes/missing", TokenScope::ReadOnly),
+        (Method::GET, "/boxes/missing/metrics", TokenScope::ReadOnly),
+        (Method::GET, "/metrics", TokenScope::ReadOnly),
+        (Method::POST, "/boxes/missing/exec", TokenScope::Exec),
+        (
+            Method::GET,
+            "/boxes/missing/files?path=/etc",
+            TokenScope::Exec,
+        ),
+        (Method::POST, "/boxes", TokenScope::Full),
+        (Method::POST, "/boxes/missing/stop", TokenScope::Full),
+        (Method::DELETE, "/boxes/missing", TokenScope::Full),
+    ];
+    for (token, scope) in [
+        (READER, TokenScope::ReadOnly),
+        (OPERATOR, TokenScope::Exec),
+        (TOKEN, TokenScope::Full),
+    ] {
+        for (method, path, required) in &requests {
+            let status = http
+                .request(method.clone(), format!("{}{}", base, path))
+                .bearer_auth(token)
+                .header("content-type", "application/json")
+                .body("not json")
+                .send()
+                .await
+                .unwrap()
+                .status();
+            if scope >= *required {
+                assert!(
+                    status != 401 && status != 403,
+                    "{scope} token: {method} {path} refused with {status}"
+                );
+            } else {
+                assert_eq!(status, 403, "{scope} token: {method} {path}");
+            }
+        }
+    }
+
+    let status = http
+        .get(format!("{}/boxes", base))
+        .bearer_auth("unknown-token")
+        .send()
+        .await
+        .unwrap()
+        .status();
+    assert_eq!(status, 401);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn read_only_client_gets_permission_denied() {
+    let rt = TestRuntime::new();
+    let (addr, _server) = serve_scoped(&rt);
+    let remote = client(addr, READER);
+
+    assert!(remote.list_info().await.unwrap().is_empty());
+    let err = remote.remove("missing", true).await.unwrap_err();
+    assert!(
+        matches!(err, BoxliteError::PermissionDenied(_)),
+        "got {err:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn serve_returns_after_runtime_shutdown() {
+    let rt = TestRuntime::new();
+    let (_addr, server) = serve(&rt);
+
+    rt.shutdown(None).await.unwrap();
+    tokio::time::timeout(Duration::from_secs(15), server)
+        .await
+        .expect("Server still running after runtime shutdown")
+        .unwrap();
+}
+
+// ============================================================================
+// BOX ROUND TRIP
+// ============================================================================
+
+#[tokio::test(flavor = "multi_thread")]
+async fn create_exec_copy_remove() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let (addr, _server) = serve(&rt);
+    let remote = client(addr, TOKEN);
+
+    let litebox = remote
+        .create(alpine_options(), Some("rest-server-roundtrip".into()))
+        .await
+        .expect("Failed to create box");
+    let id = litebox.id().to_string();
+    assert!(rt.exists(&id).await.unwrap(), "box not visible locally");
+    // Clean up through the local runtime, in case the server is at fault
+    let bx = TestBox::new(rt.runtime().clone(), litebox);
+
+    bx.run_output(BoxCommand::new("sh").args(["-c", "echo out; echo err >&2; exit 3"]))
+        .await
+        .assert_exit_code(3)
+        .assert_stdout_eq("out\n")
+        .assert_stderr_contains("err");
+
+    let host = tempfile::tempdir().unwrap();
+    let upload = host.path().join("upload");
+    std::fs::create_dir(&upload).unwrap();
+    std::fs::write(upload.join("hello.txt"), "hello from host").unwrap();
+    bx.copy_into(&upload, "/root/uploaded", CopyOptions::default())
+        .await
+        .expect("copy_into failed");
+    bx.exec_output("cat", ["/root/uploaded/hello.txt"])
+        .await
+        .assert_stdout_eq("hello from host");
+
+    let download = host.path().join("download");
+    bx.copy_out("/root/uploaded", &download, CopyOptions::default())
+        .await
+        .expect("copy_out failed");
+    assert_eq!(
+        std::fs::read_to_string(download.join("uploaded/hello.txt")).unwrap(),
+        "hello from host"
+    );
+
+    remote
+        .remove(&id, true)
+        .await
+        .expect("Failed to remove box");
+    assert!(!rt.exists(&id).await.unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn selector_limits_token_to_matching_boxes() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let (addr, _server) = serve_with(&rt, |builder| {
+        builder.token(
+            ScopedToken::new(OPERATOR, TokenScope::Full)
+                .selector(ListFilter::new().label("team", "web")),
+        )
+    });
+    let remote = client(addr, OPERATOR);
+
+    let mut web = alpine_options();
+    web.labels.insert("team".into(), "web".into());
+    let web = rt
+        .create(web, Some("rest-scoped-web".into()))
+        .await
+        .expect("Failed to create box");
+    let web = TestBox::new(rt.runtime().clone(), web);
+    let mut db = alpine_options();
+    db.labels.insert("team".into(), "db".into());
+    let db = rt
+        .create(db, Some("rest-scoped-db".into()))
+        .await
+        .expect("Failed to create box");
+    let db = TestBox::new(rt.runtime().clone(), db);
+
+    let listed: Vec<_> = remote
+        .list_info()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|info| info.id)
+        .collect();
+    assert_eq!(listed, [web.id().clone()]);
+    assert!(remote.get_info("rest-scoped-web").await.unwrap().is_some());
+    assert!(remote.get_info("rest-scoped-db").await.unwrap().is_none());
+    let err = remote.remove("rest-scoped-db", true).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::NotFound(_)), "got {err:?}");
+    assert!(rt.exists(&db.id().to_string()).await.unwrap());
+
+    let err = remote
+        .create(alpine_options(), Some("rest-scoped-unlabeled".into()))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, BoxliteError::PermissionDenied(_)),
+        "got {err:?}"
+    );
+}
@@ -14,6 +14,7 @@ fn test_runtime_prevents_concurrent_access() {
     let config1 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let runtime1 = BoxliteRuntime::new(config1).unwrap();
 
@@ -21,6 +22,7 @@ fn test_runtime_prevents_concurrent_access() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let result = BoxliteRuntime::new(config2);
     assert!(result.is_err());
@@ -36,6 +38,7 @@ fn test_runtime_prevents_concurrent_access() {
     let config3 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime2 = BoxliteRuntime::new(config3).unwrap();
 }
@@ -49,6 +52,7 @@ fn test_runtime_lock_released_on_drop() {
         let config = BoxliteOptions {
             home_dir: temp_dir.path().to_path_buf(),
             image_registries: vec![],
+            ..Default::default()
         };
         let _runtime = BoxliteRuntime::new(config).unwrap();
     } // Lock released here
@@ -57,6 +61,7 @@ fn test_runtime_lock_released_on_drop() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime2 = BoxliteRuntime::new(config2).unwrap();
 }
@@ -70,6 +75,7 @@ fn test_runtime_lock_across_threads() {
     let config1 = BoxliteOptions {
         home_dir: dir_path.clone(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime1 = BoxliteRuntime::new(config1).unwrap();
 
@@ -79,6 +85,7 @@ fn test_runtime_lock_across_threads() {
         let config = BoxliteOptions {
             home_dir: dir_clone,
             image_registries: vec![],
+            ..Default::default()
         };
         BoxliteRuntime::new(config)
     });
@@ -96,6 +103,7 @@ fn test_different_home_dirs_independent() {
     let config1 = BoxliteOptions {
         home_dir: temp_dir1.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime1 = BoxliteRuntime::new(config1).unwrap();
 
@@ -103,6 +111,7 @@ fn test_different_home_dirs_independent() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir2.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime2 = BoxliteRuntime::new(config2).unwrap();
 
@@ -118,6 +127,7 @@ fn test_lock_file_created() {
     let config = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let _runtime = BoxliteRuntime::new(config).unwrap();
 
@@ -133,6 +143,7 @@ fn test_lock_survives_short_operations() {
     let config1 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let runtime = BoxliteRuntime::new(config1).unwrap();
 
@@ -143,6 +154,7 @@ fn test_lock_survives_short_operations() {
     let config2 = BoxliteOptions {
         home_dir: temp_dir.path().to_path_buf(),
         image_registries: vec![],
+        ..Default::default()
     };
     let result = BoxliteRuntime::new(config2);
     assert!(result.is_err());
@@ -0,0 +1,58 @@
+//! Integration tests for per-exec scratch directories.
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions, BoxliteError, ScratchSpec};
+
+/// Write a file named `name` to the scratch directory, wait until the other
+/// exec has done the same, then list the scratch directory.
+fn writer(name: &str) -> BoxCommand {
+    let script = format!(
+        "echo {name} > \"$BOXLITE_SCRATCH_DIR/{name}\" && touch /tmp/ready-{name} && \
+         while [ $(ls /tmp | grep -c '^ready-') -lt 2 ]; do sleep 0.1; done && \
+         ls \"$BOXLITE_SCRATCH_DIR\""
+    );
+    BoxCommand::new("sh")
+        .args(["-c", &script])
+        .scratch_dir(ScratchSpec::new(16, "/scratch"))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_execs_do_not_share_scratch() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let (a, b) = futures::join!(bx.run_output(writer("a")), bx.run_output(writer("b")));
+    a.assert_success().assert_stdout_eq("a\n");
+    b.assert_success().assert_stdout_eq("b\n");
+
+    // Freed with the execs: nothing mounted, nothing left behind
+    bx.exec_output(
+        "sh",
+        [
+            "-c",
+            "ls -A /scratch; grep -c ' /scratch ' /proc/mounts || true",
+        ],
+    )
+    .await
+    .assert_success()
+    .assert_stdout_eq("0\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scratch_larger_than_memory_is_rejected() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            memory_mib: Some(512),
+            ..alpine_options()
+        })
+        .await;
+
+    let command = BoxCommand::new("true").scratch_dir(ScratchSpec::new(1024, "/scratch"));
+    let err = bx.exec(command).await.err().expect("exec should fail");
+    assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err}");
+}
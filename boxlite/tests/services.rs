@@ -0,0 +1,133 @@
+//! Integration tests for supervised box services: restart policies, output
+//! capture and dependency ordering across box restarts.
+
+use std::time::Duration;
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{
+    BoxCommand, BoxliteError, LiteBox, RestartPolicy, ServiceInfo, ServicePolicy, ServiceStatus,
+};
+
+async fn service(bx: &LiteBox, name: &str) -> ServiceInfo {
+    bx.list_services()
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|s| s.name == name)
+        .unwrap_or_else(|| panic!("service {name} not listed"))
+}
+
+/// Poll `name` until `check` holds, for at most 10 s.
+async fn wait_for(bx: &LiteBox, name: &str, check: impl Fn(&ServiceInfo) -> bool) -> ServiceInfo {
+    for _ in 0..100 {
+        let info = service(bx, name).await;
+        if check(&info) {
+            return info;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("service {name}: {:?}", service(bx, name).await);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn always_restarts_a_killed_service() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let policy = ServicePolicy {
+        restart: RestartPolicy::Always,
+        ..Default::default()
+    };
+    bx.spawn_service("sleeper", BoxCommand::new("sleep").arg("infinity"), policy)
+        .await
+        .unwrap();
+    let first = wait_for(&bx, "sleeper", |s| s.status == ServiceStatus::Running).await;
+    let pid = first.pid.unwrap();
+
+    bx.exec_output("kill", ["-9", &pid.to_string()])
+        .await
+        .assert_success();
+    let restarted = wait_for(&bx, "sleeper", |s| {
+        s.status == ServiceStatus::Running && s.restarts == 1
+    })
+    .await;
+    assert_ne!(restarted.pid, Some(pid));
+    assert_ne!(restarted.execution_id, first.execution_id);
+
+    bx.stop_service("sleeper").await.unwrap();
+    assert_eq!(service(&bx, "sleeper").await.status, ServiceStatus::Stopped);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exited_service_keeps_its_output() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let command = BoxCommand::new("sh").args(["-c", "echo ready; exit 3"]);
+    bx.spawn_service("oneshot", command, ServicePolicy::default())
+        .await
+        .unwrap();
+    let info = wait_for(&bx, "oneshot", |s| s.status == ServiceStatus::Exited).await;
+    assert_eq!(info.exit_code, Some(3));
+    assert_eq!(info.restarts, 0);
+
+    let output = LiteBox::exec_output(&bx, &info.execution_id.unwrap())
+        .await
+        .unwrap();
+    assert_eq!(output.stdout, "ready\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn services_start_in_order_with_the_box() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let always = |depends_on: &[&str]| ServicePolicy {
+        restart: RestartPolicy::Always,
+        depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+    };
+    bx.spawn_service("db", BoxCommand::new("sleep").arg("infinity"), always(&[]))
+        .await
+        .unwrap();
+    bx.spawn_service(
+        "web",
+        BoxCommand::new("sleep").arg("infinity"),
+        always(&["db"]),
+    )
+    .await
+    .unwrap();
+
+    let err = bx.remove_service("db").await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidState(_)), "{err}");
+    let err = bx
+        .spawn_service("db", BoxCommand::new("true"), always(&[]))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BoxliteError::AlreadyExists(_)), "{err}");
+
+    bx.stop().await.unwrap();
+    let stopped = bx.list_services().await.unwrap();
+    assert!(stopped.iter().all(|s| s.status == ServiceStatus::Stopped));
+
+    bx.start().await.unwrap();
+    let names: Vec<_> = bx
+        .list_services()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.name)
+        .collect();
+    assert_eq!(names, ["db", "web"]);
+    wait_for(&bx, "db", |s| s.status == ServiceStatus::Running).await;
+    wait_for(&bx, "web", |s| s.status == ServiceStatus::Running).await;
+
+    bx.remove_service("web").await.unwrap();
+    bx.remove_service("db").await.unwrap();
+    assert!(bx.list_services().await.unwrap().is_empty());
+}
@@ -7,37 +7,11 @@
 //! Test categories:
 //! - Async shutdown: idempotency, token cancellation
 //! - Shutdown across runtimes: independent isolation
+//! - Operations after and during shutdown: fail fast with `ShuttingDown`
 
-use boxlite::BoxliteRuntime;
 use boxlite::runtime::options::{BoxOptions, BoxliteOptions, RootfsSpec};
-use tempfile::TempDir;
-
-// ============================================================================
-// TEST FIXTURES
-// ============================================================================
-
-/// Test context with isolated runtime and automatic cleanup.
-struct TestContext {
-    runtime: BoxliteRuntime,
-    _temp_dir: TempDir,
-}
-
-impl TestContext {
-    fn new() -> Self {
-        // Use /tmp directly to avoid macOS's long temp paths that exceed SUN_LEN
-        // for Unix socket paths (limited to ~104 chars)
-        let temp_dir = TempDir::new_in("/tmp").expect("Failed to create temp dir");
-        let options = BoxliteOptions {
-            home_dir: temp_dir.path().to_path_buf(),
-            image_registries: vec![],
-        };
-        let runtime = BoxliteRuntime::new(options).expect("Failed to create runtime");
-        Self {
-            runtime,
-            _temp_dir: temp_dir,
-        }
-    }
-}
+use boxlite::testing::{TestRuntime, short_temp_dir};
+use boxlite::{BoxliteError, BoxliteRuntime};
 
 // ============================================================================
 // SHUTDOWN IDEMPOTENCY
@@ -46,30 +20,30 @@ impl TestContext {
 /// Calling shutdown() twice should succeed (second call is no-op).
 #[tokio::test]
 async fn shutdown_is_idempotent() {
-    let ctx = TestContext::new();
+    let rt = TestRuntime::new();
 
-    let result1 = ctx.runtime.shutdown(None).await;
+    let result1 = rt.shutdown(None).await;
     assert!(result1.is_ok());
 
-    let result2 = ctx.runtime.shutdown(None).await;
+    let result2 = rt.shutdown(None).await;
     assert!(result2.is_ok());
 }
 
 /// Shutdown with explicit timeout should succeed.
 #[tokio::test]
 async fn shutdown_with_timeout() {
-    let ctx = TestContext::new();
+    let rt = TestRuntime::new();
 
-    let result = ctx.runtime.shutdown(Some(5)).await;
+    let result = rt.shutdown(Some(5)).await;
     assert!(result.is_ok());
 }
 
 /// Shutdown with no boxes should complete immediately.
 #[tokio::test]
 async fn shutdown_empty_runtime() {
-    let ctx = TestContext::new();
+    let rt = TestRuntime::new();
 
-    let result = ctx.runtime.shutdown(None).await;
+    let result = rt.shutdown(None).await;
     assert!(result.is_ok());
 }
 
@@ -80,14 +54,14 @@ async fn shutdown_empty_runtime() {
 /// Shutting down one runtime should not affect another.
 #[tokio::test]
 async fn shutdown_does_not_affect_other_runtimes() {
-    let ctx1 = TestContext::new();
-    let ctx2 = TestContext::new();
+    let rt1 = TestRuntime::new();
+    let rt2 = TestRuntime::new();
 
     // Shutdown runtime 1
-    ctx1.runtime.shutdown(None).await.unwrap();
+    rt1.shutdown(None).await.unwrap();
 
     // Runtime 2 should still be operational (list_info works)
-    let result = ctx2.runtime.list_info().await;
+    let result = rt2.list_info().await;
     assert!(result.is_ok());
     assert!(result.unwrap().is_empty());
 }
@@ -96,12 +70,12 @@ async fn shutdown_does_not_affect_other_runtimes() {
 /// Only box creation/start should fail.
 #[tokio::test]
 async fn read_operations_work_after_shutdown() {
-    let ctx = TestContext::new();
+    let rt = TestRuntime::new();
 
-    ctx.runtime.shutdown(None).await.unwrap();
+    rt.shutdown(None).await.unwrap();
 
     // list_info is a read-only query — should still work
-    let result = ctx.runtime.list_info().await;
+    let result = rt.list_info().await;
     assert!(result.is_ok());
     assert!(result.unwrap().is_empty());
 }
@@ -113,7 +87,7 @@ async fn read_operations_work_after_shutdown() {
 /// Runtime drop releases the lock, allowing a new runtime on the same directory.
 #[test]
 fn drop_releases_lock() {
-    let temp_dir = TempDir::new_in("/tmp").expect("Failed to create temp dir");
+    let temp_dir = short_temp_dir();
     let dir_path = temp_dir.path().to_path_buf();
 
     // Create and drop a runtime
@@ -121,6 +95,7 @@ fn drop_releases_lock() {
         let options = BoxliteOptions {
             home_dir: dir_path.clone(),
             image_registries: vec![],
+            ..Default::default()
         };
         let _rt = BoxliteRuntime::new(options).unwrap();
     } // Drop fires here
@@ -129,6 +104,7 @@ fn drop_releases_lock() {
     let options2 = BoxliteOptions {
         home_dir: dir_path,
         image_registries: vec![],
+        ..Default::default()
     };
     let _rt2 = BoxliteRuntime::new(options2).unwrap();
 }
@@ -137,14 +113,14 @@ fn drop_releases_lock() {
 /// Both see the same shutdown token, so double-shutdown via clone is safe.
 #[tokio::test]
 async fn cloned_runtime_shares_shutdown_state() {
-    let ctx = TestContext::new();
-    let clone = ctx.runtime.clone();
+    let rt = TestRuntime::new();
+    let clone = rt.runtime().clone();
 
     // Shutdown via clone
     clone.shutdown(None).await.unwrap();
 
     // Second shutdown via original should be a no-op
-    let result = ctx.runtime.shutdown(None).await;
+    let result = rt.shutdown(None).await;
     assert!(
         result.is_ok(),
         "Second shutdown via original should succeed as no-op"
@@ -159,20 +135,20 @@ async fn cloned_runtime_shares_shutdown_state() {
 #[tokio::test]
 async fn shutdown_timeout_edge_values() {
     // Some(0) — zero timeout
-    let ctx = TestContext::new();
-    assert!(ctx.runtime.shutdown(Some(0)).await.is_ok());
+    let rt = TestRuntime::new();
+    assert!(rt.shutdown(Some(0)).await.is_ok());
 
     // Some(-1) — infinite timeout
-    let ctx = TestContext::new();
-    assert!(ctx.runtime.shutdown(Some(-1)).await.is_ok());
+    let rt = TestRuntime::new();
+    assert!(rt.shutdown(Some(-1)).await.is_ok());
 
     // Some(30) — explicit 30s
-    let ctx = TestContext::new();
-    assert!(ctx.runtime.shutdown(Some(30)).await.is_ok());
+    let rt = TestRuntime::new();
+    assert!(rt.shutdown(Some(30)).await.is_ok());
 
     // Some(-5) — negative value
-    let ctx = TestContext::new();
-    assert!(ctx.runtime.shutdown(Some(-5)).await.is_ok());
+    let rt = TestRuntime::new();
+    assert!(rt.shutdown(Some(-5)).await.is_ok());
 }
 
 // ============================================================================
@@ -182,12 +158,12 @@ async fn shutdown_timeout_edge_values() {
 /// Multiple concurrent shutdown() calls should all succeed without panic or deadlock.
 #[tokio::test]
 async fn concurrent_shutdown_is_safe() {
-    let ctx = TestContext::new();
+    let rt = TestRuntime::new();
 
     // Clone runtime 4 times and call shutdown concurrently
     let handles: Vec<_> = (0..4)
         .map(|_| {
-            let rt = ctx.runtime.clone();
+            let rt = rt.runtime().clone();
             tokio::spawn(async move { rt.shutdown(None).await })
         })
         .collect();
@@ -208,12 +184,11 @@ async fn concurrent_shutdown_is_safe() {
 /// Creating a box after shutdown should fail with a clear error.
 #[tokio::test]
 async fn create_after_shutdown_is_rejected() {
-    let ctx = TestContext::new();
+    let rt = TestRuntime::new();
 
-    ctx.runtime.shutdown(None).await.unwrap();
+    rt.shutdown(None).await.unwrap();
 
-    let result = ctx
-        .runtime
+    let result = rt
         .create(
             BoxOptions {
                 rootfs: RootfsSpec::Image("test:latest".into()),
@@ -224,13 +199,72 @@ async fn create_after_shutdown_is_rejected() {
         .await;
 
     match result {
-        Err(e) => {
-            let err_msg = e.to_string();
-            assert!(
-                err_msg.contains("shut down"),
-                "Error should mention 'shut down': {err_msg}"
-            );
-        }
+        Err(BoxliteError::ShuttingDown(msg)) => assert_eq!(msg, "cannot create box"),
+        Err(other) => panic!("Expected ShuttingDown error, got: {other}"),
         Ok(_) => panic!("create should fail after shutdown"),
     }
 }
+
+/// Removing a box after shutdown fails fast; reading it still works.
+#[tokio::test]
+async fn remove_after_shutdown_is_rejected() {
+    let rt = TestRuntime::new();
+    let litebox = rt
+        .create(
+            BoxOptions {
+                rootfs: RootfsSpec::Image("test:latest".into()),
+                auto_remove: false,
+                ..Default::default()
+            },
+            Some("kept".into()),
+        )
+        .await
+        .unwrap();
+
+    rt.shutdown(None).await.unwrap();
+
+    let result = rt.remove(litebox.id().as_str(), true).await;
+    assert!(matches!(result, Err(BoxliteError::ShuttingDown(_))));
+
+    let info = rt.get_info("kept").await.unwrap();
+    assert!(
+        info.is_some(),
+        "box should still be readable after shutdown"
+    );
+}
+
+/// Creates racing a shutdown either complete before it returns or fail
+/// with `ShuttingDown`.
+#[tokio::test(flavor = "multi_thread")]
+async fn create_racing_shutdown_has_deterministic_outcome() {
+    let rt = TestRuntime::new();
+
+    let creates: Vec<_> = (0..16)
+        .map(|i| {
+            let rt = rt.runtime().clone();
+            tokio::spawn(async move {
+                rt.create(
+                    BoxOptions {
+                        rootfs: RootfsSpec::Image("test:latest".into()),
+                        auto_remove: false,
+                        ..Default::default()
+                    },
+                    Some(format!("race-{i}")),
+                )
+                .await
+            })
+        })
+        .collect();
+    rt.shutdown(Some(5)).await.unwrap();
+    let listed = rt.list_info().await.unwrap().len();
+
+    let mut created = 0;
+    for create in creates {
+        match create.await.unwrap() {
+            Ok(_) => created += 1,
+            Err(BoxliteError::ShuttingDown(_)) => {}
+            Err(other) => panic!("Expected Ok or ShuttingDown, got: {other}"),
+        }
+    }
+    assert_eq!(created, listed);
+}
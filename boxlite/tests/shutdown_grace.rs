@@ -0,0 +1,68 @@
+//! Integration tests for `BoxOptions::shutdown_grace_period`.
+
+use std::time::{Duration, Instant};
+
+use boxlite::BoxOptions;
+use boxlite::testing::{TestRuntime, alpine_options};
+
+/// How long the entrypoint takes to handle SIGTERM.
+const HANDLER_SECS: u64 = 4;
+
+/// Box whose entrypoint traps SIGTERM and leaves `/done` behind
+/// [`HANDLER_SECS`] later.
+fn slow_to_stop(grace: Option<Duration>) -> BoxOptions {
+    let script =
+        format!("trap 'sleep {HANDLER_SECS}; touch /done; exit 0' TERM; while :; do sleep 1; done");
+    BoxOptions {
+        entrypoint: Some(vec!["sh".into(), "-c".into(), script]),
+        shutdown_grace_period: grace,
+        ..alpine_options()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stop_waits_for_sigterm_handler_within_grace_period() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(slow_to_stop(Some(Duration::from_secs(10))))
+        .await;
+    bx.start().await.unwrap();
+    assert_eq!(bx.info().shutdown_grace_period, Duration::from_secs(10));
+
+    let started = Instant::now();
+    bx.stop().await.unwrap();
+    let elapsed = started.elapsed();
+    assert!(
+        elapsed >= Duration::from_secs(HANDLER_SECS),
+        "stop returned after {elapsed:?}, before the handler finished"
+    );
+
+    // The handler ran to completion, so its marker made it to disk
+    bx.start().await.unwrap();
+    bx.exec_output("test", ["-f", "/done"])
+        .await
+        .assert_success();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn default_grace_period_kills_slow_sigterm_handler() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(slow_to_stop(None)).await;
+    bx.start().await.unwrap();
+    assert_eq!(
+        bx.info().shutdown_grace_period,
+        boxlite::runtime::options::DEFAULT_SHUTDOWN_GRACE_PERIOD
+    );
+
+    bx.stop().await.unwrap();
+
+    // Killed at 3s, before the handler got to write its marker
+    bx.start().await.unwrap();
+    bx.exec_output("test", ["-f", "/done"])
+        .await
+        .assert_exit_code(1);
+}
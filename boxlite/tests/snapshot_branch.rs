@@ -0,0 +1,128 @@
+//! Integration tests for branching a snapshot into a new box
+//! (`SnapshotHandle::restore_to_new`).
+
+use std::time::Duration;
+
+use boxlite::testing::{TestBox, TestRuntime};
+use boxlite::{BoxStatus, BoxliteError, BoxliteOptions, SnapshotOptions};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn restore_to_new_works_while_source_runs() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let source = rt.alpine().await;
+    source.exec_output("touch", ["/marker"]).await;
+    source.stop().await.unwrap();
+    source
+        .snapshot()
+        .create("with-marker", SnapshotOptions::default())
+        .await
+        .unwrap();
+
+    // Changes made after the snapshot don't reach the branch
+    source.start().await.unwrap();
+    source.exec_output("rm", ["/marker"]).await;
+    assert_eq!(source.info().status, BoxStatus::Running);
+
+    let branched = source
+        .snapshot()
+        .restore_to_new("with-marker", "branched")
+        .await
+        .unwrap();
+    let branched = TestBox::new(rt.runtime().clone(), branched);
+    assert_eq!(branched.info().status, BoxStatus::Exited);
+
+    branched.start().await.unwrap();
+
+    let output = branched.exec_output("test", ["-f", "/marker"]).await;
+    assert_eq!(output.exit_code, 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_with_branched_box_cannot_be_removed() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let source = rt.alpine().await;
+    source.stop().await.unwrap();
+    source
+        .snapshot()
+        .create("base", SnapshotOptions::default())
+        .await
+        .unwrap();
+    let branched = source
+        .snapshot()
+        .restore_to_new("base", "branched")
+        .await
+        .unwrap();
+    let branched = TestBox::new(rt.runtime().clone(), branched);
+
+    // The branch's disks back onto the source's snapshot
+    let err = rt
+        .runtime()
+        .remove_permanently(source.id().as_str(), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidState(_)), "{err}");
+    assert!(err.to_string().contains("branched"), "{err}");
+
+    // Once the branch is gone the source can go too
+    drop(branched);
+    rt.runtime()
+        .plan_remove_permanently(source.id().as_str(), true)
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_with_branched_box_cannot_be_trashed() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::with_options(BoxliteOptions {
+        image_registries: vec![],
+        trash_retention: Some(Duration::from_secs(3600)),
+        ..Default::default()
+    });
+    let source = rt.alpine().await;
+    source.stop().await.unwrap();
+    source
+        .snapshot()
+        .create("base", SnapshotOptions::default())
+        .await
+        .unwrap();
+    let branched = source
+        .snapshot()
+        .restore_to_new("base", "branched")
+        .await
+        .unwrap();
+
+    // Moving the source would break the branch's backing files
+    let err = rt
+        .runtime()
+        .remove(source.id().as_str(), true)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidState(_)), "{err}");
+
+    // A trashed branch still counts: it can be restored
+    rt.runtime()
+        .remove(branched.id().as_str(), true)
+        .await
+        .unwrap();
+    let err = rt
+        .runtime()
+        .remove(source.id().as_str(), true)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("trashed box branched"), "{err}");
+
+    rt.runtime()
+        .purge_trashed(branched.id().as_str())
+        .await
+        .unwrap();
+    rt.runtime()
+        .plan_remove(source.id().as_str(), true)
+        .await
+        .unwrap();
+}
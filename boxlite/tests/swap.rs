@@ -0,0 +1,96 @@
+//! Integration tests for swap inside the VM (`BoxOptions::swap_mib`).
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions, BoxliteError, LiteBox};
+use futures::StreamExt;
+
+const MEMORY_MIB: u32 = 256;
+
+/// Allocate and touch slightly more than the VM's memory: `dd` fills a
+/// buffer of one block size with zeros.
+async fn allocate_over_memory(bx: &LiteBox) -> i32 {
+    let block = format!("bs={}M", MEMORY_MIB + 32);
+    let command =
+        BoxCommand::new("dd").args(["if=/dev/zero", "of=/dev/null", "count=1", block.as_str()]);
+    let mut execution = bx.exec(command).await.unwrap();
+    execution.wait().await.unwrap().exit_code
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn allocation_over_memory_needs_swap() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let without = rt
+        .create_box(BoxOptions {
+            memory_mib: Some(MEMORY_MIB),
+            ..alpine_options()
+        })
+        .await;
+    assert_ne!(allocate_over_memory(&without).await, 0, "expected OOM kill");
+    assert_eq!(without.metrics().await.unwrap().swap_bytes, None);
+
+    let with = rt
+        .create_box(BoxOptions {
+            memory_mib: Some(MEMORY_MIB),
+            swap_mib: Some(512),
+            disk_size_gb: Some(2),
+            ..alpine_options()
+        })
+        .await;
+    assert_eq!(allocate_over_memory(&with).await, 0);
+    assert_eq!(with.info().swap_mib, 512);
+    assert!(with.metrics().await.unwrap().swap_bytes.is_some());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn swap_file_is_reused_across_restarts() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            swap_mib: Some(64),
+            disk_size_gb: Some(1),
+            ..alpine_options()
+        })
+        .await;
+    bx.start().await.unwrap();
+    assert!(proc_swaps(&bx).await.contains(".boxlite-swap"));
+
+    bx.stop().await.unwrap();
+    bx.start().await.unwrap();
+    assert!(proc_swaps(&bx).await.contains(".boxlite-swap"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn swap_larger_than_disk_is_rejected() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            swap_mib: Some(4096),
+            disk_size_gb: Some(2),
+            ..alpine_options()
+        })
+        .await;
+    let err = bx.start().await.unwrap_err();
+    assert!(matches!(err, BoxliteError::Config(_)), "got {err:?}");
+    assert!(err.to_string().contains("disk_size_gb"), "got {err}");
+}
+
+async fn proc_swaps(bx: &LiteBox) -> String {
+    let mut execution = bx
+        .exec(BoxCommand::new("cat").arg("/proc/swaps"))
+        .await
+        .unwrap();
+    let mut out = String::new();
+    if let Some(mut stdout) = execution.stdout() {
+        while let Some(chunk) = stdout.next().await {
+            out.push_str(&chunk);
+        }
+    }
+    execution.wait().await.unwrap();
+    out
+}
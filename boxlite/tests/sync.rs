@@ -0,0 +1,49 @@
+//! Integration tests for `LiteBox::sync()`.
+
+use std::time::Duration;
+
+use boxlite::BoxliteError;
+use boxlite::runtime::types::BoxStatus;
+use boxlite::testing::{TestRuntime, alpine_options};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sync_freezes_and_thaws_container_disk() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    bx.exec_output("sh", ["-c", "echo data > /root/file"])
+        .await
+        .assert_success();
+
+    let frozen = bx.sync(Duration::from_millis(500)).await.unwrap();
+    assert!(frozen < Duration::from_millis(500), "frozen for {frozen:?}");
+
+    // Thawed: the container can write again
+    bx.exec_output("sh", ["-c", "echo more >> /root/file && cat /root/file"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("data\nmore\n");
+
+    // Only flush
+    assert_eq!(bx.sync(Duration::ZERO).await.unwrap(), Duration::ZERO);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sync_rejects_bad_cap_and_stopped_box() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let err = bx.sync(Duration::from_secs(60)).await.unwrap_err();
+    assert!(
+        matches!(err, BoxliteError::InvalidArgument(_)),
+        "got {err:?}"
+    );
+
+    // Never started: syncing must not boot the box
+    let err = bx.sync(Duration::from_millis(500)).await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidState(_)), "got {err:?}");
+    assert_eq!(bx.info().status, BoxStatus::Created);
+}
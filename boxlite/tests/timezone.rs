@@ -0,0 +1,94 @@
+//! Integration tests for `BoxOptions::timezone` and `BoxOptions::locale`.
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxOptions, BoxliteError};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn timezone_and_locale_apply_to_container() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            timezone: Some("Asia/Tokyo".into()),
+            locale: Some("C.UTF-8".into()),
+            ..alpine_options()
+        })
+        .await;
+
+    // alpine ships no tzdata: the zone comes from the bundled tzdb
+    bx.exec_output("date", ["+%Z %z"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("JST +0900\n");
+    bx.exec_output("cat", ["/etc/timezone"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("Asia/Tokyo\n");
+    bx.exec_output("sh", ["-c", "echo \"$TZ $LANG\""])
+        .await
+        .assert_success()
+        .assert_stdout_eq("Asia/Tokyo C.UTF-8\n");
+
+    // /etc/localtime alone is enough once TZ is cleared
+    bx.exec_output("sh", ["-c", "unset TZ; date +%Z"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("JST\n");
+
+    // An exec's env overrides the defaults
+    bx.run_output(
+        BoxCommand::new("sh")
+            .args(["-c", "echo \"$LANG\"; date +%Z"])
+            .env("TZ", "UTC")
+            .env("LANG", "POSIX"),
+    )
+    .await
+    .assert_success()
+    .assert_stdout_eq("POSIX\nUTC\n");
+
+    let info = bx.info();
+    assert_eq!(info.timezone.as_deref(), Some("Asia/Tokyo"));
+    assert_eq!(info.locale.as_deref(), Some("C.UTF-8"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn box_env_overrides_timezone_default() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            timezone: Some("europe/berlin".into()),
+            env: vec![("TZ".into(), "UTC".into())],
+            ..alpine_options()
+        })
+        .await;
+
+    // The name is canonicalized for the zone files
+    bx.exec_output("cat", ["/etc/timezone"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("Europe/Berlin\n");
+    bx.exec_output("date", ["+%Z"])
+        .await
+        .assert_success()
+        .assert_stdout_eq("UTC\n");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_timezone_is_rejected() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt
+        .create_box(BoxOptions {
+            timezone: Some("Europe/Berln".into()),
+            ..alpine_options()
+        })
+        .await;
+
+    let err = bx.start().await.unwrap_err();
+    assert!(matches!(err, BoxliteError::InvalidArgument(_)), "{err:?}");
+    assert!(err.to_string().contains("Europe/Berlin"), "{err}");
+}
@@ -0,0 +1,102 @@
+//! Integration tests for host-to-guest TCP tunnels.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use boxlite::testing::{TestRuntime, alpine_options};
+use boxlite::{BoxCommand, BoxliteError};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Serve `/www/index.html` on the box's loopback with busybox httpd.
+async fn start_httpd(bx: &boxlite::LiteBox) {
+    let mut execution = bx
+        .exec(BoxCommand::new("sh").args([
+            "-c",
+            "mkdir -p /www && echo hello > /www/index.html && httpd -p 127.0.0.1:8080 -h /www",
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+}
+
+async fn get(addr: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /index.html HTTP/1.0\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tunnel_relays_concurrent_connections() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+    start_httpd(&bx).await;
+
+    let tunnel = bx.tunnel(8080).await.unwrap();
+    assert!(tunnel.local_addr().ip().is_loopback());
+    assert_eq!(tunnel.guest_port(), 8080);
+
+    let addr = tunnel.local_addr();
+    let responses = futures::future::join_all((0..8).map(|_| get(addr))).await;
+    for response in responses {
+        assert!(response.starts_with("HTTP/1.0 200"), "got {response:?}");
+        assert!(response.ends_with("hello\n"), "got {response:?}");
+    }
+
+    drop(tunnel);
+    assert!(
+        TcpStream::connect(addr).await.is_err(),
+        "Dropping the handle should close the listener"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tunnel_to_closed_port_closes_connection() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let tunnel = bx.tunnel(9).await.unwrap();
+    let mut stream = TcpStream::connect(tunnel.local_addr()).await.unwrap();
+    let mut buf = Vec::new();
+    let read = tokio::time::timeout(Duration::from_secs(10), stream.read_to_end(&mut buf))
+        .await
+        .expect("Refused guest connection should close the host side");
+    assert!(read.is_err() || buf.is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tunnel_closes_when_box_stops() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let tunnel = bx.tunnel(8080).await.unwrap();
+    bx.stop().await.unwrap();
+    tokio::time::timeout(Duration::from_secs(10), tunnel.closed())
+        .await
+        .expect("Tunnel should close when the box stops");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tunnel_rejects_port_zero() {
+    boxlite::skip_if_no_virtualization!();
+
+    let rt = TestRuntime::new();
+    let bx = rt.create_box(alpine_options()).await;
+
+    let err = bx.tunnel(0).await.unwrap_err();
+    assert!(
+        matches!(err, BoxliteError::InvalidArgument(_)),
+        "got {err:?}"
+    );
+}
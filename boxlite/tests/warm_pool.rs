@@ -0,0 +1,168 @@
+//! Integration tests for runtime warm pools.
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use boxlite::runtime::types::BoxStatus;
+use boxlite::testing::{alpine_options, short_temp_dir};
+use boxlite::{
+    BoxCommand, BoxInfo, BoxliteError, BoxliteOptions, BoxliteRuntime, ListOptions, WarmPoolSpec,
+    WarmSelector,
+};
+
+fn open_runtime(home: &Path, size: usize) -> BoxliteRuntime {
+    BoxliteRuntime::new(BoxliteOptions {
+        home_dir: home.to_path_buf(),
+        image_registries: vec![],
+        warm_pool: vec![WarmPoolSpec {
+            name: "alpine".into(),
+            options: alpine_options(),
+            size,
+        }],
+        ..Default::default()
+    })
+    .expect("Failed to create runtime")
+}
+
+/// Boxes only listed with `include_warm_pool`.
+async fn idle_boxes(runtime: &BoxliteRuntime) -> Vec<BoxInfo> {
+    let listed: Vec<_> = runtime
+        .list_info()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|info| info.id)
+        .collect();
+    runtime
+        .list_info_with(ListOptions {
+            include_warm_pool: true,
+        })
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|info| !listed.contains(&info.id))
+        .collect()
+}
+
+/// Wait up to 60s for the pool to hold `count` started boxes.
+async fn wait_for_idle(runtime: &BoxliteRuntime, count: usize) {
+    let deadline = Instant::now() + Duration::from_secs(60);
+    loop {
+        let running = idle_boxes(runtime)
+            .await
+            .into_iter()
+            .filter(|info| info.status == BoxStatus::Running)
+            .count();
+        if running >= count {
+            return;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "Pool did not fill to {count} boxes"
+        );
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn acquire_hits_filled_pool_and_backfills() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+    let runtime = open_runtime(home.path(), 1);
+    wait_for_idle(&runtime, 1).await;
+    assert!(
+        runtime.list_info().await.unwrap().is_empty(),
+        "Idle pool boxes should be hidden from list_info"
+    );
+
+    let sandbox = runtime
+        .acquire_warm(WarmSelector::new("alpine").env("JOB_ID", "42"))
+        .await
+        .unwrap();
+    assert_eq!(sandbox.info().status, BoxStatus::Running);
+    let listed = runtime.list_info().await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].id, *sandbox.id());
+
+    let mut execution = sandbox
+        .exec(BoxCommand::new("sh").args(["-c", "test \"$JOB_ID\" = 42"]))
+        .await
+        .unwrap();
+    assert_eq!(execution.wait().await.unwrap().exit_code, 0);
+
+    let metrics = runtime.metrics().await.unwrap();
+    assert_eq!(metrics.warm_pool_hits_total(), 1);
+    assert_eq!(metrics.warm_pool_misses_total(), 0);
+    assert!(metrics.warm_acquire_avg_ms().is_some());
+
+    // The acquired box is replaced, not returned
+    wait_for_idle(&runtime, 1).await;
+    sandbox.stop().await.unwrap();
+    assert_eq!(idle_boxes(&runtime).await.len(), 1);
+
+    runtime.shutdown(None).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn acquire_from_empty_pool_is_a_miss() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    // Created outside a Tokio runtime, so nothing fills the pool up front
+    let runtime = {
+        let home = home.path().to_path_buf();
+        std::thread::spawn(move || open_runtime(&home, 1))
+            .join()
+            .unwrap()
+    };
+    assert!(idle_boxes(&runtime).await.is_empty());
+
+    let sandbox = runtime
+        .acquire_warm(WarmSelector::new("alpine"))
+        .await
+        .unwrap();
+    assert_eq!(sandbox.info().status, BoxStatus::Running);
+    let metrics = runtime.metrics().await.unwrap();
+    assert_eq!(metrics.warm_pool_hits_total(), 0);
+    assert_eq!(metrics.warm_pool_misses_total(), 1);
+
+    runtime.shutdown(None).await.unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unknown_pool_is_not_found() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+    let runtime = open_runtime(home.path(), 0);
+
+    let result = runtime.acquire_warm(WarmSelector::new("missing")).await;
+    assert!(matches!(result, Err(BoxliteError::NotFound(_))));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn shutdown_removes_idle_boxes() {
+    boxlite::skip_if_no_virtualization!();
+    let home = short_temp_dir();
+
+    {
+        let runtime = open_runtime(home.path(), 2);
+        wait_for_idle(&runtime, 2).await;
+        runtime.shutdown(None).await.unwrap();
+        assert!(idle_boxes(&runtime).await.is_empty());
+    }
+
+    // Nothing is left for the next runtime on the same home
+    let runtime = BoxliteRuntime::new(BoxliteOptions {
+        home_dir: home.path().to_path_buf(),
+        image_registries: vec![],
+        ..Default::default()
+    })
+    .unwrap();
+    let all = runtime
+        .list_info_with(ListOptions {
+            include_warm_pool: true,
+        })
+        .await
+        .unwrap();
+    assert!(all.is_empty(), "got {all:?}");
+}
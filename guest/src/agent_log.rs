@@ -0,0 +1,100 @@
+//! In-memory copy of the agent's own log.
+//!
+//! The agent logs to stderr, which ends up in the host-side console log
+//! interleaved with kernel output. Tracing output is also kept here, in a
+//! ring buffer of `MAX_AGENT_LOG_BYTES`, so the host can fetch the agent log
+//! of a running box over `Guest.AgentLog`.
+
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+use boxlite_shared::constants::guest_logs::MAX_AGENT_LOG_BYTES;
+
+static LOG: Mutex<Ring> = Mutex::new(Ring::new());
+
+/// Writer for `tracing_subscriber::fmt().with_writer(agent_log::writer)`.
+pub fn writer() -> AgentLogWriter {
+    AgentLogWriter
+}
+
+/// Writes to stderr and appends what was written to the ring buffer.
+pub struct AgentLogWriter;
+
+impl Write for AgentLogWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = io::stderr().write(buf)?;
+        lock().append(&buf[..n], MAX_AGENT_LOG_BYTES as usize);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stderr().flush()
+    }
+}
+
+/// The last `max_bytes` bytes of the log, and whether earlier output was
+/// cut off (by `max_bytes` or by the buffer capacity).
+pub fn tail(max_bytes: usize) -> (Vec<u8>, bool) {
+    lock().tail(max_bytes)
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Ring> {
+    LOG.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+struct Ring {
+    data: VecDeque<u8>,
+    /// Output has been dropped to stay within the capacity.
+    overflowed: bool,
+}
+
+impl Ring {
+    const fn new() -> Self {
+        Self {
+            data: VecDeque::new(),
+            overflowed: false,
+        }
+    }
+
+    fn append(&mut self, bytes: &[u8], capacity: usize) {
+        let excess = (self.data.len() + bytes.len()).saturating_sub(capacity);
+        if excess == 0 {
+            self.data.extend(bytes);
+            return;
+        }
+        self.overflowed = true;
+        let dropped = excess.min(self.data.len());
+        self.data.drain(..dropped);
+        self.data.extend(&bytes[excess - dropped..]);
+    }
+
+    fn tail(&self, max_bytes: usize) -> (Vec<u8>, bool) {
+        let skip = self.data.len().saturating_sub(max_bytes);
+        let data = self.data.iter().skip(skip).copied().collect();
+        (data, skip > 0 || self.overflowed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_within_capacity() {
+        let mut ring = Ring::new();
+        ring.append(b"hello ", 16);
+        ring.append(b"world", 16);
+        assert_eq!(ring.tail(100), (b"hello world".to_vec(), false));
+        assert_eq!(ring.tail(5), (b"world".to_vec(), true));
+    }
+
+    #[test]
+    fn test_append_drops_oldest_bytes_past_capacity() {
+        let mut ring = Ring::new();
+        ring.append(b"0123456789", 8);
+        assert_eq!(ring.tail(100), (b"23456789".to_vec(), true));
+        ring.append(b"ab", 8);
+        assert_eq!(ring.tail(100), (b"456789ab".to_vec(), true));
+    }
+}
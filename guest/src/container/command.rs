@@ -4,8 +4,13 @@
 //! following the `std::process::Command` pattern.
 
 use super::capabilities::capability_names;
+use super::spec::SCRATCH_HELPER_PATH;
+use crate::scratch;
+use crate::service::exec::env as exec_env;
 use crate::service::exec::exec_handle::{ExecHandle, PtyConfig};
+use boxlite_shared::constants::container::SCRATCH_DIR_ENV;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use boxlite_shared::EnvEdit;
 use libcontainer::container::builder::ContainerBuilder;
 use libcontainer::syscall::syscall::SyscallType;
 use nix::unistd::Pid;
@@ -60,6 +65,9 @@ pub struct ContainerCommand {
 
     /// PTY configuration (set via with_pty())
     pty_config: Option<PtyConfig>,
+
+    /// Scratch tmpfs size (MiB) and mount point (set via scratch_dir())
+    scratch: Option<(u32, String)>,
 }
 
 impl ContainerCommand {
@@ -81,6 +89,7 @@ impl ContainerCommand {
             cwd: None,
             console_socket: None,
             pty_config: None,
+            scratch: None,
             id,
             state_root,
         }
@@ -190,6 +199,14 @@ impl ContainerCommand {
         self
     }
 
+    /// Apply env edits (prepend, append, unset) on top of the env so far
+    ///
+    /// Call after [`envs`](Self::envs): edits see the values set there.
+    pub fn env_edits(mut self, edits: &[EnvEdit]) -> Self {
+        exec_env::apply(&mut self.env, edits);
+        self
+    }
+
     /// Set working directory
     ///
     /// # Example
@@ -207,6 +224,26 @@ impl ContainerCommand {
         self
     }
 
+    /// Run the process as `uid:gid` instead of the container's default user
+    pub fn user(mut self, uid: u32, gid: u32) -> Self {
+        self.user = (uid, gid);
+        self
+    }
+
+    /// Give the process a private tmpfs of `size_mib` at `mount_at`
+    ///
+    /// The process is started through the scratch helper (see
+    /// `crate::scratch`), which mounts the tmpfs in a mount namespace of the
+    /// process's own and then execs the program as the container user.
+    /// [`SCRATCH_DIR_ENV`] is set to `mount_at`.
+    pub fn scratch_dir(mut self, size_mib: u32, mount_at: impl Into<String>) -> Self {
+        let mount_at = mount_at.into();
+        self.env
+            .insert(SCRATCH_DIR_ENV.to_string(), mount_at.clone());
+        self.scratch = Some((size_mib, mount_at));
+        self
+    }
+
     /// Spawn the process
     ///
     /// Creates a tenant process in the container with stdin/stdout/stderr pipes.
@@ -247,14 +284,17 @@ impl ContainerCommand {
 
     /// Spawn process with pipes (standard mode).
     async fn spawn_with_pipes(self) -> BoxliteResult<ExecHandle> {
-        use nix::unistd::pipe;
+        use nix::fcntl::OFlag;
+        use nix::unistd::pipe2;
 
-        // Create pipes for I/O
-        let (stdin_read, stdin_write) = pipe()
+        // Create pipes for I/O. Close-on-exec keeps the agent's ends out of
+        // other processes it starts, which would otherwise hold stdin open
+        // past EOF; the container side is dup'ed onto 0-2 before exec.
+        let (stdin_read, stdin_write) = pipe2(OFlag::O_CLOEXEC)
             .map_err(|e| BoxliteError::Internal(format!("Failed to create stdin pipe: {}", e)))?;
-        let (stdout_read, stdout_write) = pipe()
+        let (stdout_read, stdout_write) = pipe2(OFlag::O_CLOEXEC)
             .map_err(|e| BoxliteError::Internal(format!("Failed to create stdout pipe: {}", e)))?;
-        let (stderr_read, stderr_write) = pipe()
+        let (stderr_read, stderr_write) = pipe2(OFlag::O_CLOEXEC)
             .map_err(|e| BoxliteError::Internal(format!("Failed to create stderr pipe: {}", e)))?;
 
         tracing::debug!(container_id = %self.id, "Spawning with pipes");
@@ -307,6 +347,21 @@ impl ContainerCommand {
         let mut container_args = vec![program.clone()];
         container_args.extend_from_slice(self.args.as_slice());
 
+        // The helper mounts as root and drops to the user before exec
+        let (container_args, (uid, gid)) = match &self.scratch {
+            Some((size_mib, mount_at)) => (
+                scratch::wrap(
+                    SCRATCH_HELPER_PATH,
+                    *size_mib,
+                    mount_at,
+                    self.user,
+                    &container_args,
+                ),
+                (0, 0),
+            ),
+            None => (container_args, self.user),
+        };
+
         // Build container
         let mut builder = ContainerBuilder::new(self.id.to_string(), SyscallType::default())
             .with_root_path(self.state_root.clone())
@@ -353,8 +408,6 @@ impl ContainerCommand {
             );
         }
 
-        let (uid, gid) = self.user;
-
         let pid = builder
             .as_tenant()
             .with_capabilities(capability_names())
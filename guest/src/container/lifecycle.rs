@@ -6,10 +6,13 @@
 use super::command::ContainerCommand;
 use super::spec::UserMount;
 use super::stdio::ContainerStdio;
-use super::{kill, spec, start};
+use super::userns::{self, UserNsConfig};
+use super::{kill, runtime_env, spec, start};
+use crate::core_dump;
 use crate::layout::GuestLayout;
 use crate::service::exec::InitHealthCheck;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use boxlite_shared::{HostEntry, TimeZone};
 use libcontainer::container::Container as LibContainer;
 use libcontainer::signal::Signal;
 use std::collections::HashMap;
@@ -45,9 +48,13 @@ pub struct Container {
     id: String,
     state_root: PathBuf,
     bundle_path: PathBuf,
+    /// Container root filesystem (guest path).
+    rootfs: PathBuf,
     env: HashMap<String, String>,
     /// Resolved (uid, gid) from image USER directive, propagated to exec commands.
     user: (u32, u32),
+    /// User namespace mappings, if the container runs in one.
+    userns: Option<UserNsConfig>,
     /// Stdio pipes that keep init process alive.
     /// Dropping this closes pipes → init gets EOF → init exits.
     #[allow(dead_code)]
@@ -75,6 +82,18 @@ impl Container {
     /// - `env`: Environment variables in "KEY=VALUE" format
     /// - `workdir`: Working directory inside container
     /// - `user_mounts`: Bind mounts from guest VM paths into container
+    /// - `userns`: User namespace mappings (None = share the guest's)
+    /// - `init`: Run the entrypoint under the built-in init, which reaps
+    ///   zombies and forwards signals (like `docker run --init`)
+    /// - `entrypoint_script`: Script run in place of the entrypoint, with
+    ///   the entrypoint as its arguments
+    /// - `capture_core_dumps`: Route core dumps of container processes into
+    ///   `core_dumps::DIR` (see `crate::core_dump`)
+    /// - `time_zone`: Zone file and name placed at `/etc/localtime` and
+    ///   `/etc/timezone` (None = the image's own)
+    /// - `read_only_rootfs`: Mount the rootfs read-only, with a tmpfs at `/run`
+    /// - `writable_paths`: Container paths given a tmpfs
+    /// - `extra_hosts`: Entries appended to the generated `/etc/hosts`
     ///
     /// # Errors
     ///
@@ -82,6 +101,8 @@ impl Container {
     /// - Failed to create container directory
     /// - Failed to create or start container
     /// - Init process exited immediately
+    /// - Container user not mapped by `userns`
+    #[allow(clippy::too_many_arguments)]
     pub fn start(
         container_id: &str,
         rootfs: impl AsRef<Path>,
@@ -90,6 +111,14 @@ impl Container {
         workdir: impl AsRef<Path>,
         user: &str,
         user_mounts: Vec<UserMount>,
+        userns: Option<UserNsConfig>,
+        init: bool,
+        entrypoint_script: Option<&str>,
+        capture_core_dumps: bool,
+        time_zone: Option<&TimeZone>,
+        read_only_rootfs: bool,
+        writable_paths: &[String],
+        extra_hosts: &[HostEntry],
     ) -> BoxliteResult<Self> {
         let rootfs = rootfs.as_ref();
         let workdir = workdir.as_ref();
@@ -120,6 +149,22 @@ impl Container {
             .ok_or_else(|| BoxliteError::Internal("Invalid rootfs path".to_string()))?;
         let (uid, gid) = spec::resolve_user(rootfs_str, user)?;
 
+        // With a user namespace, the rootfs must be owned by mapped IDs
+        if let Some(config) = &userns {
+            config.check_user(uid, gid)?;
+            userns::shift_rootfs(rootfs, config)?;
+        }
+
+        // The agent binary doubles as the init (see crate::init) and the
+        // scratch helper (see crate::scratch)
+        let agent_binary = std::env::current_exe()
+            .map_err(|e| BoxliteError::Internal(format!("Failed to locate agent binary: {}", e)))?;
+        let init_binary = init.then_some(agent_binary.as_path());
+
+        if capture_core_dumps {
+            core_dump::enable(rootfs)?;
+        }
+
         // Create OCI bundle at /run/boxlite/containers/{cid}/
         // create_oci_bundle creates bundle_root/{cid}/, so pass containers_dir
         let bundle_path = start::create_oci_bundle(
@@ -132,6 +177,15 @@ impl Container {
             gid,
             &layout.containers_dir(),
             &user_mounts,
+            userns.as_ref(),
+            init_binary,
+            Some(&agent_binary),
+            entrypoint_script,
+            capture_core_dumps,
+            time_zone,
+            read_only_rootfs,
+            writable_paths,
+            extra_hosts,
         )?;
 
         // Create stdio pipes before container creation.
@@ -146,8 +200,10 @@ impl Container {
             id: container_id.to_string(),
             state_root,
             bundle_path,
+            rootfs: rootfs.to_path_buf(),
             env: env_map,
             user: (uid, gid),
+            userns,
             stdio,
             is_shutdown: std::sync::atomic::AtomicBool::new(false),
         })
@@ -237,6 +293,56 @@ impl Container {
         )
     }
 
+    /// Create a command builder starting from the init process's current
+    /// environment and working directory instead of the start env.
+    ///
+    /// Used for execs with `inherit_runtime_env` (see [`runtime_env`]).
+    pub fn cmd_from_init(&self) -> BoxliteResult<ContainerCommand> {
+        let pid = LibContainer::load(self.container_state_path())
+            .ok()
+            .and_then(|container| container.pid())
+            .ok_or_else(|| {
+                BoxliteError::Internal(format!("Container {} has no init process", self.id))
+            })?;
+        let (env, cwd) = runtime_env::read(pid.as_raw())?;
+
+        Ok(
+            ContainerCommand::new(self.id.clone(), self.state_root.clone(), env, self.user)
+                .current_dir(cwd),
+        )
+    }
+
+    /// Environment inherited by exec commands (image env + box env).
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// Resolved (uid, gid) of the container's default user.
+    pub fn user(&self) -> (u32, u32) {
+        self.user
+    }
+
+    /// On-disk owner for files the container should see as `uid:gid`.
+    ///
+    /// Identity without a user namespace; `None` when the namespace does
+    /// not map the IDs.
+    pub fn file_owner(&self, uid: u32, gid: u32) -> Option<(u32, u32)> {
+        match &self.userns {
+            Some(config) => Some((config.map_uid(uid)?, config.map_gid(gid)?)),
+            None => Some((uid, gid)),
+        }
+    }
+
+    /// Replace the extra entries of the container's `/etc/hosts`.
+    pub fn update_hosts(&self, entries: &[HostEntry]) -> BoxliteResult<()> {
+        start::write_hosts_file(&self.bundle_path, entries).map(|_| ())
+    }
+
+    /// Whether `path` (absolute, container view) is a directory in the rootfs.
+    pub fn has_dir(&self, path: &str) -> bool {
+        self.rootfs.join(path.trim_start_matches('/')).is_dir()
+    }
+
     /// Drain init process stdout and stderr.
     ///
     /// Reads all available data from the init process pipes using non-blocking I/O.
@@ -275,6 +381,12 @@ impl Container {
         // Drain init process output before building diagnostics
         let (init_stdout, init_stderr) = self.drain_init_output();
 
+        // Echo early stderr to the console so the host can attach it to
+        // start-failure diagnostics
+        for line in init_stderr.lines() {
+            eprintln!("{} {}", boxlite_shared::boot::CONTAINER_STDERR_MARKER, line);
+        }
+
         // Try to load container state from libcontainer
         let mut result = match LibContainer::load(container_state_path.clone()) {
             Ok(libcontainer) => {
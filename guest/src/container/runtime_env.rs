@@ -0,0 +1,85 @@
+//! Live environment of the container init process.
+//!
+//! Execs with `inherit_runtime_env` start from what the init process has
+//! now rather than from the container's start env, like `docker exec`:
+//! `/proc/<pid>/environ` (the env it was last exec'd with, so an entrypoint
+//! that exports variables and then `exec`s is picked up) and its cwd.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Environment and working directory (container view) of process `pid`.
+pub(super) fn read(pid: i32) -> BoxliteResult<(HashMap<String, String>, String)> {
+    let proc_dir = Path::new("/proc").join(pid.to_string());
+    let read_err = |what: &str, e: std::io::Error| {
+        BoxliteError::Internal(format!(
+            "Failed to read {what} of container init process {pid}: {e}"
+        ))
+    };
+
+    let environ =
+        std::fs::read(proc_dir.join("environ")).map_err(|e| read_err("environment", e))?;
+    let root = std::fs::read_link(proc_dir.join("root")).map_err(|e| read_err("root", e))?;
+    let cwd =
+        std::fs::read_link(proc_dir.join("cwd")).map_err(|e| read_err("working directory", e))?;
+
+    Ok((parse_environ(&environ), container_path(&root, &cwd)))
+}
+
+/// Parse a NUL-separated `KEY=VALUE` block; entries without `=` are skipped.
+fn parse_environ(environ: &[u8]) -> HashMap<String, String> {
+    environ
+        .split(|b| *b == 0)
+        .filter_map(|entry| {
+            let entry = String::from_utf8_lossy(entry);
+            let (key, value) = entry.split_once('=')?;
+            (!key.is_empty()).then(|| (key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// `cwd` as the container sees it, given the process's `root` as seen
+/// from the guest (`/` when the guest cannot see past the container's root).
+fn container_path(root: &Path, cwd: &Path) -> String {
+    match cwd.strip_prefix(root) {
+        Ok(rel) => Path::new("/").join(rel).to_string_lossy().into_owned(),
+        Err(_) => cwd.to_string_lossy().into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_environ() {
+        let env = parse_environ(b"PATH=/bin\0SDK_HOME=/opt/sdk\0EMPTY=\0A=b=c\0junk\0\0");
+        assert_eq!(env.len(), 4);
+        assert_eq!(env["PATH"], "/bin");
+        assert_eq!(env["SDK_HOME"], "/opt/sdk");
+        assert_eq!(env["EMPTY"], "");
+        assert_eq!(env["A"], "b=c");
+    }
+
+    #[test]
+    fn test_container_path() {
+        let rootfs = Path::new("/run/boxlite/containers/c1/rootfs");
+        assert_eq!(container_path(rootfs, &rootfs.join("srv/app")), "/srv/app");
+        assert_eq!(container_path(rootfs, rootfs), "/");
+        assert_eq!(container_path(Path::new("/"), Path::new("/tmp")), "/tmp");
+    }
+
+    #[test]
+    fn test_read_own_process() {
+        let (env, cwd) = read(std::process::id() as i32).unwrap();
+        assert_eq!(env.get("PATH"), std::env::var("PATH").ok().as_ref());
+        assert_eq!(
+            cwd,
+            std::env::current_dir()
+                .unwrap()
+                .to_string_lossy()
+                .into_owned()
+        );
+    }
+}
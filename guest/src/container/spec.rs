@@ -3,6 +3,9 @@
 //! Creates OCI-compliant runtime specifications following the runtime-spec standard.
 
 use super::capabilities::all_capabilities;
+use super::userns::UserNsConfig;
+use boxlite_shared::constants::container::ENTRYPOINT_SCRIPT_PATH;
+use boxlite_shared::constants::core_dumps;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use std::path::Path;
 
@@ -12,6 +15,19 @@ use oci_spec::runtime::{
     RootBuilder, Spec, SpecBuilder, UserBuilder,
 };
 
+/// Container path the guest agent is bind-mounted at to act as init.
+///
+/// `/dev` is a per-container tmpfs, so nothing is created in the image rootfs.
+/// The file name is what makes the agent run as init (see `crate::init`).
+pub const INIT_PATH: &str = "/dev/boxlite-init";
+
+/// Container path the guest agent is bind-mounted at to set up exec
+/// scratch directories (see `crate::scratch`).
+pub const SCRATCH_HELPER_PATH: &str = "/dev/boxlite-scratch";
+
+/// Where images keep their tzdb; `TZ=<name>` resolves to a file in it.
+pub const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
 /// User-specified bind mount for container
 #[derive(Debug, Clone)]
 pub struct UserMount {
@@ -29,11 +45,25 @@ pub struct UserMount {
 /// - Standard mounts (/proc, /dev, /sys, etc.)
 /// - User-specified bind mounts (volumes)
 /// - Default capabilities (matching runc defaults)
-/// - Standard namespaces (pid, ipc, uts, mount)
+/// - Standard namespaces (pid, ipc, uts, mount, plus user when `userns` is set)
 /// - UID/GID mappings for user namespace
 /// - Configurable user (resolved uid/gid)
 /// - Resource limits (rlimits)
 /// - No new privileges disabled (allows sudo)
+/// - With `init_binary`, that binary bind-mounted at [`INIT_PATH`] as PID 1,
+///   running the entrypoint as its child (like `docker run --init`)
+/// - With `scratch_helper`, that binary bind-mounted at
+///   [`SCRATCH_HELPER_PATH`] for execs with a scratch directory
+/// - With `entrypoint_script`, that file bind-mounted at
+///   [`ENTRYPOINT_SCRIPT_PATH`] and run with the entrypoint as its arguments
+/// - With `capture_core_dumps`, RLIMIT_CORE raised to [`core_dumps::MAX_BYTES`]
+///   (the agent routes the dumps, see `crate::core_dump`)
+/// - With `time_zone`, the bundle's `localtime` and `timezone` files
+///   bind-mounted over `/etc/localtime` and `/etc/timezone`, and at the zone's
+///   path under [`ZONEINFO_DIR`] when the image lacks it, so `TZ` resolves
+/// - With `read_only_rootfs`, the root mounted read-only and `/run` given a
+///   tmpfs (`/tmp` and `/dev` always have one)
+/// - A tmpfs at each of `writable_paths`
 ///
 /// NOTE: Cgroups are disabled for performance (~105ms savings on container startup).
 /// Since we're inside a VM with single-tenant isolation, cgroup resource limits
@@ -50,10 +80,23 @@ pub fn create_oci_spec(
     gid: u32,
     bundle_path: &Path,
     user_mounts: &[UserMount],
+    userns: Option<&UserNsConfig>,
+    init_binary: Option<&Path>,
+    scratch_helper: Option<&Path>,
+    entrypoint_script: Option<&Path>,
+    capture_core_dumps: bool,
+    time_zone: Option<&str>,
+    read_only_rootfs: bool,
+    writable_paths: &[String],
 ) -> BoxliteResult<Spec> {
     let caps = build_default_capabilities()?;
-    let namespaces = build_default_namespaces()?;
+    let namespaces = build_default_namespaces(userns.is_some())?;
     let mut mounts = build_standard_mounts(bundle_path)?;
+    if let Some(name) = time_zone {
+        mounts.extend(build_time_zone_mounts(rootfs, bundle_path, name)?);
+    }
+    // Before user mounts, so volumes under a writable path stay visible
+    mounts.extend(build_writable_mounts(read_only_rootfs, writable_paths)?);
 
     // Add user-specified bind mounts
     for user_mount in user_mounts {
@@ -86,9 +129,28 @@ pub fn create_oci_spec(
         );
     }
 
-    let process = build_process_spec(entrypoint, env, workdir, uid, gid, caps)?;
-    let root = build_root_spec(rootfs)?;
-    let linux = build_linux_spec(container_id, namespaces)?;
+    let args = match entrypoint_script {
+        Some(script) => {
+            mounts.push(build_entrypoint_script_mount(script)?);
+            wrap_with_script(entrypoint)
+        }
+        None => entrypoint.to_vec(),
+    };
+    let args = match init_binary {
+        Some(init_binary) => {
+            mounts.push(build_init_mount(init_binary)?);
+            wrap_with_init(&args)
+        }
+        None => args,
+    };
+
+    if let Some(helper) = scratch_helper {
+        mounts.push(build_scratch_helper_mount(helper)?);
+    }
+
+    let process = build_process_spec(&args, env, workdir, uid, gid, caps, capture_core_dumps)?;
+    let root = build_root_spec(rootfs, read_only_rootfs)?;
+    let linux = build_linux_spec(container_id, namespaces, userns)?;
 
     SpecBuilder::default()
         .version("1.0.2")
@@ -101,6 +163,152 @@ pub fn create_oci_spec(
         .map_err(|e| BoxliteError::Internal(format!("Failed to build OCI spec: {}", e)))
 }
 
+/// Prefix `entrypoint` with the init, which runs it as its child.
+fn wrap_with_init(entrypoint: &[String]) -> Vec<String> {
+    [INIT_PATH.to_string(), "--".to_string()]
+        .into_iter()
+        .chain(entrypoint.iter().cloned())
+        .collect()
+}
+
+/// Read-only bind mount of the init binary at [`INIT_PATH`].
+fn build_init_mount(init_binary: &Path) -> BoxliteResult<Mount> {
+    let source = init_binary
+        .to_str()
+        .ok_or_else(|| BoxliteError::Internal("Invalid init binary path".to_string()))?;
+
+    MountBuilder::default()
+        .destination(INIT_PATH)
+        .typ("bind")
+        .source(source)
+        .options(vec!["bind".to_string(), "ro".to_string()])
+        .build()
+        .map_err(|e| BoxliteError::Internal(format!("Failed to build init mount: {}", e)))
+}
+
+/// Read-only bind mount of the scratch helper at [`SCRATCH_HELPER_PATH`].
+fn build_scratch_helper_mount(helper: &Path) -> BoxliteResult<Mount> {
+    let source = helper
+        .to_str()
+        .ok_or_else(|| BoxliteError::Internal("Invalid scratch helper path".to_string()))?;
+
+    MountBuilder::default()
+        .destination(SCRATCH_HELPER_PATH)
+        .typ("bind")
+        .source(source)
+        .options(vec!["bind".to_string(), "ro".to_string()])
+        .build()
+        .map_err(|e| BoxliteError::Internal(format!("Failed to build scratch helper mount: {}", e)))
+}
+
+/// Prefix `entrypoint` with the wrapper script, which receives it as `"$@"`.
+fn wrap_with_script(entrypoint: &[String]) -> Vec<String> {
+    std::iter::once(ENTRYPOINT_SCRIPT_PATH.to_string())
+        .chain(entrypoint.iter().cloned())
+        .collect()
+}
+
+/// Read-only bind mount of the wrapper script at [`ENTRYPOINT_SCRIPT_PATH`].
+fn build_entrypoint_script_mount(script: &Path) -> BoxliteResult<Mount> {
+    let source = script
+        .to_str()
+        .ok_or_else(|| BoxliteError::Internal("Invalid entrypoint script path".to_string()))?;
+
+    MountBuilder::default()
+        .destination(ENTRYPOINT_SCRIPT_PATH)
+        .typ("bind")
+        .source(source)
+        .options(vec!["bind".to_string(), "ro".to_string()])
+        .build()
+        .map_err(|e| {
+            BoxliteError::Internal(format!("Failed to build entrypoint script mount: {}", e))
+        })
+}
+
+/// Mounts placing the bundle's time zone files, written for zone `name`.
+fn build_time_zone_mounts(
+    rootfs: &str,
+    bundle_path: &Path,
+    name: &str,
+) -> BoxliteResult<Vec<Mount>> {
+    let zone_path = format!("{}/{}", ZONEINFO_DIR, name);
+    let mut mounts = vec![
+        build_bundle_file_mount(bundle_path, "localtime", "/etc/localtime")?,
+        build_bundle_file_mount(bundle_path, "timezone", "/etc/timezone")?,
+    ];
+    // Images without tzdata (alpine) couldn't resolve TZ otherwise
+    if !Path::new(rootfs)
+        .join(zone_path.trim_start_matches('/'))
+        .exists()
+    {
+        mounts.push(build_bundle_file_mount(
+            bundle_path,
+            "localtime",
+            &zone_path,
+        )?);
+    }
+    Ok(mounts)
+}
+
+/// Tmpfs mounts keeping `/run` (with a read-only rootfs) and
+/// `writable_paths` writable. Paths that already get a tmpfs are skipped.
+fn build_writable_mounts(
+    read_only_rootfs: bool,
+    writable_paths: &[String],
+) -> BoxliteResult<Vec<Mount>> {
+    let mut mounts = Vec::new();
+    let mut seen = vec!["/tmp"];
+    if read_only_rootfs {
+        mounts.push(build_tmpfs_mount("/run", "mode=755")?);
+        seen.push("/run");
+    }
+    for path in writable_paths {
+        let path = path.trim_end_matches('/');
+        if !seen.contains(&path) {
+            mounts.push(build_tmpfs_mount(path, "mode=1777")?);
+            seen.push(path);
+        }
+    }
+    Ok(mounts)
+}
+
+/// Empty tmpfs at `destination`, like the one at `/tmp`.
+fn build_tmpfs_mount(destination: &str, mode: &str) -> BoxliteResult<Mount> {
+    MountBuilder::default()
+        .destination(destination)
+        .typ("tmpfs")
+        .source("tmpfs")
+        .options(vec![
+            "nosuid".to_string(),
+            "nodev".to_string(),
+            mode.to_string(),
+        ])
+        .build()
+        .map_err(|e| {
+            BoxliteError::Internal(format!("Failed to build {} mount: {}", destination, e))
+        })
+}
+
+/// Read-only bind mount of `file` in the bundle at `destination`.
+fn build_bundle_file_mount(
+    bundle_path: &Path,
+    file: &str,
+    destination: &str,
+) -> BoxliteResult<Mount> {
+    let source = bundle_path.join(file);
+    MountBuilder::default()
+        .destination(destination)
+        .typ("bind")
+        .source(source.to_str().ok_or_else(|| {
+            BoxliteError::Internal(format!("Invalid {} path: {}", file, source.display()))
+        })?)
+        .options(vec!["bind".to_string(), "ro".to_string()])
+        .build()
+        .map_err(|e| {
+            BoxliteError::Internal(format!("Failed to build {} mount: {}", destination, e))
+        })
+}
+
 // ====================
 // User Resolution
 // ====================
@@ -278,8 +486,11 @@ fn build_default_capabilities() -> BoxliteResult<oci_spec::runtime::LinuxCapabil
 }
 
 /// Build default namespaces for container isolation
-fn build_default_namespaces() -> BoxliteResult<Vec<oci_spec::runtime::LinuxNamespace>> {
-    Ok(vec![
+///
+/// The user namespace is opt-in (`userns`): it remaps container IDs to an
+/// unprivileged guest range at the cost of a one-time rootfs ownership shift.
+fn build_default_namespaces(userns: bool) -> BoxliteResult<Vec<oci_spec::runtime::LinuxNamespace>> {
+    let mut namespaces = vec![
         build_namespace(LinuxNamespaceType::Pid)?,
         build_namespace(LinuxNamespaceType::Ipc)?,
         build_namespace(LinuxNamespaceType::Uts)?,
@@ -289,8 +500,11 @@ fn build_default_namespaces() -> BoxliteResult<Vec<oci_spec::runtime::LinuxNames
         // Since we're inside a VM with single-tenant isolation, cgroup namespace provides
         // minimal additional security benefit. Re-enable if resource limits are needed.
         // build_namespace(LinuxNamespaceType::Cgroup)?,
-        // build_namespace(LinuxNamespaceType::User)?,
-    ])
+    ];
+    if userns {
+        namespaces.push(build_namespace(LinuxNamespaceType::User)?);
+    }
+    Ok(namespaces)
 }
 
 /// Build a single namespace specification
@@ -309,6 +523,7 @@ fn build_process_spec(
     uid: u32,
     gid: u32,
     caps: oci_spec::runtime::LinuxCapabilities,
+    capture_core_dumps: bool,
 ) -> BoxliteResult<oci_spec::runtime::Process> {
     let user = UserBuilder::default()
         .uid(uid)
@@ -320,13 +535,25 @@ fn build_process_spec(
     // Set NOFILE to 1048576 to match Docker's defaults
     // This allows applications to open many files/connections (databases, web servers, etc.)
     #[allow(unused)]
-    let rlimits = vec![PosixRlimitBuilder::default()
+    let mut rlimits = vec![PosixRlimitBuilder::default()
         .typ(PosixRlimitType::RlimitNofile)
         .hard(1024u64 * 1024u64)
         .soft(1024u64 * 1024u64)
         .build()
         .map_err(|e| BoxliteError::Internal(format!("Failed to build rlimit: {}", e)))?];
 
+    // Let processes dump core; the handler truncates at the same size
+    if capture_core_dumps {
+        rlimits.push(
+            PosixRlimitBuilder::default()
+                .typ(PosixRlimitType::RlimitCore)
+                .hard(core_dumps::MAX_BYTES)
+                .soft(core_dumps::MAX_BYTES)
+                .build()
+                .map_err(|e| BoxliteError::Internal(format!("Failed to build rlimit: {}", e)))?,
+        );
+    }
+
     ProcessBuilder::default()
         .terminal(false)
         .user(user)
@@ -341,10 +568,10 @@ fn build_process_spec(
 }
 
 /// Build root filesystem specification
-fn build_root_spec(rootfs: &str) -> BoxliteResult<oci_spec::runtime::Root> {
+fn build_root_spec(rootfs: &str, read_only: bool) -> BoxliteResult<oci_spec::runtime::Root> {
     RootBuilder::default()
         .path(rootfs)
-        .readonly(false)
+        .readonly(read_only)
         .build()
         .map_err(|e| BoxliteError::Internal(format!("Failed to build root spec: {}", e)))
 }
@@ -353,22 +580,15 @@ fn build_root_spec(rootfs: &str) -> BoxliteResult<oci_spec::runtime::Root> {
 fn build_linux_spec(
     container_id: &str,
     namespaces: Vec<oci_spec::runtime::LinuxNamespace>,
+    userns: Option<&UserNsConfig>,
 ) -> BoxliteResult<oci_spec::runtime::Linux> {
-    // UID/GID mappings for user namespace
-    // Map full range of UIDs/GIDs to allow non-root users (nginx=33, etc.)
-    let uid_mappings = vec![LinuxIdMappingBuilder::default()
-        .host_id(0u32)
-        .container_id(0u32)
-        .size(65536u32)  // Map 0-65535 to cover all common users
-        .build()
-        .map_err(|e| BoxliteError::Internal(format!("Failed to build UID mapping: {}", e)))?];
-
-    let gid_mappings = vec![LinuxIdMappingBuilder::default()
-        .host_id(0u32)
-        .container_id(0u32)
-        .size(65536u32)  // Map 0-65535 to cover all common groups
-        .build()
-        .map_err(|e| BoxliteError::Internal(format!("Failed to build GID mapping: {}", e)))?];
+    let (uid_mappings, gid_mappings) = match userns {
+        Some(config) => (config.oci_uid_mappings()?, config.oci_gid_mappings()?),
+        None => (
+            build_identity_mapping("UID")?,
+            build_identity_mapping("GID")?,
+        ),
+    };
 
     // Masked paths for security (hide sensitive /proc and /sys entries)
     #[allow(unused)]
@@ -411,6 +631,20 @@ fn build_linux_spec(
         .map_err(|e| BoxliteError::Internal(format!("Failed to build linux spec: {}", e)))
 }
 
+/// Identity mapping, used when the user namespace is disabled
+///
+/// Map full range of UIDs/GIDs to allow non-root users (nginx=33, etc.)
+fn build_identity_mapping(kind: &str) -> BoxliteResult<Vec<oci_spec::runtime::LinuxIdMapping>> {
+    Ok(vec![LinuxIdMappingBuilder::default()
+        .host_id(0u32)
+        .container_id(0u32)
+        .size(65536u32)  // Map 0-65535 to cover all common users and groups
+        .build()
+        .map_err(|e| {
+            BoxliteError::Internal(format!("Failed to build {} mapping: {}", kind, e))
+        })?])
+}
+
 /// Build standard mounts for container filesystem
 fn build_standard_mounts(bundle_path: &Path) -> BoxliteResult<Vec<Mount>> {
     let mut mounts = vec![
@@ -960,4 +1194,484 @@ mod tests {
         let err = resolve_user(r, "short").unwrap_err().to_string();
         assert!(err.contains("User 'short' not found"), "got: {}", err);
     }
+
+    // ==================
+    // User namespace
+    // ==================
+
+    fn userns_auto() -> UserNsConfig {
+        UserNsConfig::from_proto(boxlite_shared::UserNamespace::default())
+    }
+
+    fn has_user_ns(linux: &oci_spec::runtime::Linux) -> bool {
+        linux
+            .namespaces()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|ns| ns.typ() == LinuxNamespaceType::User)
+    }
+
+    #[test]
+    fn test_namespaces_without_userns() {
+        let namespaces = build_default_namespaces(false).unwrap();
+        assert_eq!(namespaces.len(), 4);
+        assert!(!namespaces
+            .iter()
+            .any(|ns| ns.typ() == LinuxNamespaceType::User));
+    }
+
+    #[test]
+    fn test_linux_spec_identity_mapping_without_userns() {
+        let linux = build_linux_spec("c1", build_default_namespaces(false).unwrap(), None).unwrap();
+        assert!(!has_user_ns(&linux));
+
+        let uid = &linux.uid_mappings().as_ref().unwrap()[0];
+        assert_eq!(
+            (uid.container_id(), uid.host_id(), uid.size()),
+            (0, 0, 65536)
+        );
+    }
+
+    #[test]
+    fn test_linux_spec_with_userns_auto() {
+        let config = userns_auto();
+        let linux =
+            build_linux_spec("c1", build_default_namespaces(true).unwrap(), Some(&config)).unwrap();
+        assert!(has_user_ns(&linux));
+
+        let uid = &linux.uid_mappings().as_ref().unwrap()[0];
+        let gid = &linux.gid_mappings().as_ref().unwrap()[0];
+        assert_eq!(
+            (uid.container_id(), uid.host_id(), uid.size()),
+            (0, 100000, 65536)
+        );
+        assert_eq!(
+            (gid.container_id(), gid.host_id(), gid.size()),
+            (0, 100000, 65536)
+        );
+    }
+
+    #[test]
+    fn test_linux_spec_with_userns_explicit_map() {
+        let config = UserNsConfig::from_proto(boxlite_shared::UserNamespace {
+            uid_map: vec![
+                boxlite_shared::IdMapping {
+                    container_id: 0,
+                    host_id: 200000,
+                    size: 1000,
+                },
+                boxlite_shared::IdMapping {
+                    container_id: 1000,
+                    host_id: 1000,
+                    size: 1,
+                },
+            ],
+            gid_map: vec![boxlite_shared::IdMapping {
+                container_id: 0,
+                host_id: 200000,
+                size: 65536,
+            }],
+        });
+        let linux =
+            build_linux_spec("c1", build_default_namespaces(true).unwrap(), Some(&config)).unwrap();
+
+        let uid = linux.uid_mappings().as_ref().unwrap();
+        assert_eq!(uid.len(), 2);
+        assert_eq!(
+            (uid[1].container_id(), uid[1].host_id(), uid[1].size()),
+            (1000, 1000, 1)
+        );
+    }
+
+    #[test]
+    fn test_create_oci_spec_with_userns() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let config = userns_auto();
+        let spec = create_oci_spec(
+            "c1",
+            rootfs.path().to_str().unwrap(),
+            &["sh".to_string()],
+            &[],
+            "/",
+            33,
+            33,
+            bundle.path(),
+            &[],
+            Some(&config),
+            None,
+            None,
+            None,
+            false,
+            None,
+            false,
+            &[],
+        )
+        .unwrap();
+
+        let linux = spec.linux().as_ref().unwrap();
+        assert!(has_user_ns(linux));
+        // Process user stays container-side; the runtime applies the mapping
+        let user = spec.process().as_ref().unwrap().user();
+        assert_eq!((user.uid(), user.gid()), (33, 33));
+    }
+
+    #[test]
+    fn test_create_oci_spec_with_init() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let spec = create_oci_spec(
+            "c1",
+            rootfs.path().to_str().unwrap(),
+            &["sh".to_string(), "-c".to_string(), "sleep 1".to_string()],
+            &[],
+            "/",
+            0,
+            0,
+            bundle.path(),
+            &[],
+            None,
+            Some(Path::new("/boxlite/bin/boxlite-guest")),
+            None,
+            None,
+            false,
+            None,
+            false,
+            &[],
+        )
+        .unwrap();
+
+        let args = spec.process().as_ref().unwrap().args().clone().unwrap();
+        assert_eq!(args, vec![INIT_PATH, "--", "sh", "-c", "sleep 1"]);
+
+        let mounts = spec.mounts().as_ref().unwrap();
+        let init_mount = mounts
+            .iter()
+            .find(|m| m.destination() == Path::new(INIT_PATH))
+            .expect("init binary should be bind-mounted");
+        assert_eq!(
+            init_mount.source().as_deref(),
+            Some(Path::new("/boxlite/bin/boxlite-guest"))
+        );
+        // Must come after the /dev tmpfs it is mounted into
+        let dev = mounts
+            .iter()
+            .position(|m| m.destination() == Path::new("/dev"))
+            .unwrap();
+        let init = mounts
+            .iter()
+            .position(|m| m.destination() == Path::new(INIT_PATH))
+            .unwrap();
+        assert!(dev < init);
+    }
+
+    #[test]
+    fn test_init_path_runs_agent_as_init() {
+        let name = Path::new(INIT_PATH).file_name().unwrap();
+        assert_eq!(name, crate::init::NAME);
+    }
+
+    #[test]
+    fn test_create_oci_spec_with_scratch_helper() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let spec = create_oci_spec(
+            "c1",
+            rootfs.path().to_str().unwrap(),
+            &["sh".to_string()],
+            &[],
+            "/",
+            0,
+            0,
+            bundle.path(),
+            &[],
+            None,
+            None,
+            Some(Path::new("/boxlite/bin/boxlite-guest")),
+            None,
+            false,
+            None,
+            false,
+            &[],
+        )
+        .unwrap();
+
+        // The entrypoint is not wrapped; only execs use the helper
+        let args = spec.process().as_ref().unwrap().args().clone().unwrap();
+        assert_eq!(args, vec!["sh"]);
+        let helper = spec
+            .mounts()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find(|m| m.destination() == Path::new(SCRATCH_HELPER_PATH))
+            .expect("scratch helper should be bind-mounted");
+        assert_eq!(
+            helper.source().as_deref(),
+            Some(Path::new("/boxlite/bin/boxlite-guest"))
+        );
+        let name = Path::new(SCRATCH_HELPER_PATH).file_name().unwrap();
+        assert_eq!(name, crate::scratch::NAME);
+    }
+
+    #[test]
+    fn test_create_oci_spec_without_init_keeps_entrypoint() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let spec = create_oci_spec(
+            "c1",
+            rootfs.path().to_str().unwrap(),
+            &["sh".to_string()],
+            &[],
+            "/",
+            0,
+            0,
+            bundle.path(),
+            &[],
+            None,
+            None,
+            None,
+            None,
+            false,
+            None,
+            false,
+            &[],
+        )
+        .unwrap();
+
+        let args = spec.process().as_ref().unwrap().args().clone().unwrap();
+        assert_eq!(args, vec!["sh"]);
+        assert!(!spec
+            .mounts()
+            .as_ref()
+            .unwrap()
+            .iter()
+            .any(|m| m.destination() == Path::new(INIT_PATH)));
+    }
+
+    #[test]
+    fn test_create_oci_spec_with_entrypoint_script() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let script = bundle.path().join("entrypoint.sh");
+        let spec = create_oci_spec(
+            "c1",
+            rootfs.path().to_str().unwrap(),
+            &["nginx".to_string(), "-g".to_string()],
+            &[],
+            "/",
+            0,
+            0,
+            bundle.path(),
+            &[],
+            None,
+            Some(Path::new("/boxlite/bin/boxlite-guest")),
+            None,
+            Some(&script),
+            false,
+            None,
+            false,
+            &[],
+        )
+        .unwrap();
+
+        // The init runs the script, which gets the entrypoint as "$@"
+        let args = spec.process().as_ref().unwrap().args().clone().unwrap();
+        assert_eq!(
+            args,
+            vec![INIT_PATH, "--", ENTRYPOINT_SCRIPT_PATH, "nginx", "-g"]
+        );
+
+        let mounts = spec.mounts().as_ref().unwrap();
+        let script_mount = mounts
+            .iter()
+            .find(|m| m.destination() == Path::new(ENTRYPOINT_SCRIPT_PATH))
+            .expect("entrypoint script should be bind-mounted");
+        assert_eq!(script_mount.source().as_deref(), Some(script.as_path()));
+        assert!(script_mount
+            .options()
+            .as_ref()
+            .unwrap()
+            .contains(&"ro".to_string()));
+    }
+
+    #[test]
+    fn test_create_oci_spec_core_rlimit_only_when_capturing() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let core_limit = |capture: bool| {
+            let spec = create_oci_spec(
+                "c1",
+                rootfs.path().to_str().unwrap(),
+                &["sh".to_string()],
+                &[],
+                "/",
+                0,
+                0,
+                bundle.path(),
+                &[],
+                None,
+                None,
+                None,
+                None,
+                capture,
+                None,
+                false,
+                &[],
+            )
+            .unwrap();
+            spec.process()
+                .as_ref()
+                .unwrap()
+                .rlimits()
+                .as_ref()
+                .unwrap()
+                .iter()
+                .find(|r| r.typ() == PosixRlimitType::RlimitCore)
+                .map(|r| (r.soft(), r.hard()))
+        };
+
+        assert_eq!(core_limit(false), None);
+        assert_eq!(
+            core_limit(true),
+            Some((core_dumps::MAX_BYTES, core_dumps::MAX_BYTES))
+        );
+    }
+
+    #[test]
+    fn test_create_oci_spec_time_zone_mounts() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let localtime = bundle.path().join("localtime");
+        let zone_destinations = |rootfs: &Path| {
+            let spec = create_oci_spec(
+                "c1",
+                rootfs.to_str().unwrap(),
+                &["sh".to_string()],
+                &[],
+                "/",
+                0,
+                0,
+                bundle.path(),
+                &[],
+                None,
+                None,
+                None,
+                None,
+                false,
+                Some("Europe/Berlin"),
+                false,
+                &[],
+            )
+            .unwrap();
+            spec.mounts()
+                .as_ref()
+                .unwrap()
+                .iter()
+                .filter(|m| m.source().as_deref() == Some(localtime.as_path()))
+                .map(|m| m.destination().clone())
+                .collect::<Vec<_>>()
+        };
+
+        // No tzdata in the image: the zone file is placed for TZ too
+        assert_eq!(
+            zone_destinations(rootfs.path()),
+            vec![
+                Path::new("/etc/localtime").to_path_buf(),
+                Path::new("/usr/share/zoneinfo/Europe/Berlin").to_path_buf(),
+            ]
+        );
+
+        let zoneinfo = rootfs.path().join("usr/share/zoneinfo/Europe");
+        fs::create_dir_all(&zoneinfo).unwrap();
+        fs::write(zoneinfo.join("Berlin"), "TZif").unwrap();
+        assert_eq!(
+            zone_destinations(rootfs.path()),
+            vec![Path::new("/etc/localtime").to_path_buf()]
+        );
+    }
+
+    #[test]
+    fn test_create_oci_spec_read_only_rootfs() {
+        let rootfs = make_test_rootfs();
+        let bundle = tempfile::tempdir().unwrap();
+        let spec_with = |read_only: bool, writable: &[String]| {
+            create_oci_spec(
+                "c1",
+                rootfs.path().to_str().unwrap(),
+                &["sh".to_string()],
+                &[],
+                "/",
+                0,
+                0,
+                bundle.path(),
+                &[UserMount {
+                    source: "/run/boxlite/shared/v".to_string(),
+                    destination: "/var/cache/data".to_string(),
+                    read_only: false,
+                }],
+                None,
+                None,
+                None,
+                None,
+                false,
+                None,
+                read_only,
+                writable,
+            )
+            .unwrap()
+        };
+        let tmpfs_destinations = |spec: &Spec| {
+            spec.mounts()
+                .as_ref()
+                .unwrap()
+                .iter()
+                .filter(|m| m.typ().as_deref() == Some("tmpfs"))
+                .map(|m| m.destination().to_string_lossy().into_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let spec = spec_with(false, &[]);
+        assert_eq!(spec.root().as_ref().unwrap().readonly(), Some(false));
+        assert_eq!(tmpfs_destinations(&spec), ["/dev", "/dev/shm", "/tmp"]);
+
+        let writable = ["/var/cache/".to_string(), "/tmp".to_string()];
+        let spec = spec_with(true, &writable);
+        assert_eq!(spec.root().as_ref().unwrap().readonly(), Some(true));
+        assert_eq!(
+            tmpfs_destinations(&spec),
+            ["/dev", "/dev/shm", "/tmp", "/run", "/var/cache"]
+        );
+        // The volume under /var/cache is mounted over its tmpfs
+        let mounts = spec.mounts().as_ref().unwrap();
+        let position = |destination: &str| {
+            mounts
+                .iter()
+                .position(|m| m.destination() == Path::new(destination))
+                .unwrap()
+        };
+        assert!(position("/var/cache") < position("/var/cache/data"));
+    }
+
+    #[test]
+    fn test_userns_rejects_unmapped_resolved_user() {
+        let rootfs = make_test_rootfs();
+        let r = rootfs.path().to_str().unwrap();
+        let config = UserNsConfig::from_proto(boxlite_shared::UserNamespace {
+            uid_map: vec![boxlite_shared::IdMapping {
+                container_id: 0,
+                host_id: 100000,
+                size: 100,
+            }],
+            gid_map: vec![],
+        });
+
+        let (uid, gid) = resolve_user(r, "www-data").unwrap();
+        assert!(config.check_user(uid, gid).is_ok());
+
+        let (uid, gid) = resolve_user(r, "nobody").unwrap();
+        let err = config.check_user(uid, gid).unwrap_err().to_string();
+        assert!(err.contains("not mapped"), "got: {}", err);
+    }
 }
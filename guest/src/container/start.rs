@@ -4,7 +4,9 @@
 //! Separated from container.rs to group by lifecycle phase (Prepare → Execute).
 
 use super::spec;
+use super::userns::UserNsConfig;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use boxlite_shared::{HostEntry, TimeZone};
 use libcontainer::container::builder::ContainerBuilder;
 use libcontainer::container::Container as LibContainer;
 use libcontainer::syscall::syscall::SyscallType;
@@ -44,28 +46,15 @@ pub(crate) fn validate_container_inputs(
 pub(crate) fn create_container_etc_files(
     bundle_path: &Path,
     _container_id: &str,
+    extra_hosts: &[HostEntry],
 ) -> BoxliteResult<()> {
-    const DEFAULT_HOSTNAME: &str = "boxlite";
-
     // Create /etc/hostname
     let hostname_path = bundle_path.join("hostname");
     fs::write(&hostname_path, format!("{}\n", DEFAULT_HOSTNAME))
         .map_err(|e| BoxliteError::Internal(format!("Failed to create hostname file: {}", e)))?;
 
     // Create /etc/hosts with localhost and hostname entries
-    let hosts_path = bundle_path.join("hosts");
-    let hosts_content = format!(
-        "127.0.0.1\tlocalhost\n\
-         ::1\t\tlocalhost ip6-localhost ip6-loopback\n\
-         fe00::0\t\tip6-localnet\n\
-         ff00::0\t\tip6-mcastprefix\n\
-         ff02::1\t\tip6-allnodes\n\
-         ff02::2\t\tip6-allrouters\n\
-         127.0.1.1\t{}\n",
-        DEFAULT_HOSTNAME
-    );
-    fs::write(&hosts_path, hosts_content)
-        .map_err(|e| BoxliteError::Internal(format!("Failed to create hosts file: {}", e)))?;
+    let hosts_path = write_hosts_file(bundle_path, extra_hosts)?;
 
     // Create /etc/resolv.conf with gateway as DNS server
     let resolv_conf_path = bundle_path.join("resolv.conf");
@@ -86,6 +75,79 @@ pub(crate) fn create_container_etc_files(
     Ok(())
 }
 
+/// Hostname of every container
+const DEFAULT_HOSTNAME: &str = "boxlite";
+
+/// Write the bundle's hosts file: the base entries, then `extra_hosts`.
+///
+/// The file is truncated and rewritten in place rather than replaced, so a
+/// running container's bind mount at /etc/hosts sees the new content.
+pub(crate) fn write_hosts_file(
+    bundle_path: &Path,
+    extra_hosts: &[HostEntry],
+) -> BoxliteResult<PathBuf> {
+    let hosts_path = bundle_path.join("hosts");
+    fs::write(&hosts_path, hosts_content(extra_hosts))
+        .map_err(|e| BoxliteError::Internal(format!("Failed to create hosts file: {}", e)))?;
+    Ok(hosts_path)
+}
+
+fn hosts_content(extra_hosts: &[HostEntry]) -> String {
+    let mut content = format!(
+        "127.0.0.1\tlocalhost\n\
+         ::1\t\tlocalhost ip6-localhost ip6-loopback\n\
+         fe00::0\t\tip6-localnet\n\
+         ff00::0\t\tip6-mcastprefix\n\
+         ff02::1\t\tip6-allnodes\n\
+         ff02::2\t\tip6-allrouters\n\
+         127.0.1.1\t{}\n",
+        DEFAULT_HOSTNAME
+    );
+    for entry in extra_hosts.iter().filter(|e| !e.names.is_empty()) {
+        content.push_str(&format!("{}\t{}\n", entry.ip, entry.names.join(" ")));
+    }
+    content
+}
+
+/// Create the localtime and timezone files for /etc/localtime and /etc/timezone
+fn create_time_zone_files(bundle_path: &Path, time_zone: &TimeZone) -> BoxliteResult<()> {
+    // The name also becomes a path under the image's zoneinfo directory
+    let name = &time_zone.name;
+    if name.is_empty() || name.starts_with('/') || name.split('/').any(|c| c == "..") {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "Invalid time zone name: {:?}",
+            name
+        )));
+    }
+
+    fs::write(bundle_path.join("localtime"), &time_zone.tzif)
+        .map_err(|e| BoxliteError::Internal(format!("Failed to create localtime file: {}", e)))?;
+    fs::write(bundle_path.join("timezone"), format!("{}\n", name))
+        .map_err(|e| BoxliteError::Internal(format!("Failed to create timezone file: {}", e)))?;
+
+    tracing::debug!(time_zone = %name, "Created time zone files");
+    Ok(())
+}
+
+/// Write the entrypoint wrapper script into the bundle, executable by all.
+///
+/// Returns its path, to be bind-mounted read-only into the container.
+fn write_entrypoint_script(bundle_path: &Path, script: &str) -> BoxliteResult<PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = bundle_path.join("entrypoint.sh");
+    fs::write(&path, script).map_err(|e| {
+        BoxliteError::Internal(format!("Failed to create entrypoint script: {}", e))
+    })?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).map_err(|e| {
+        BoxliteError::Internal(format!(
+            "Failed to make entrypoint script executable: {}",
+            e
+        ))
+    })?;
+    Ok(path)
+}
+
 /// Create OCI bundle (config.json + rootfs reference)
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn create_oci_bundle(
@@ -98,6 +160,15 @@ pub(crate) fn create_oci_bundle(
     gid: u32,
     bundle_root: &Path,
     user_mounts: &[spec::UserMount],
+    userns: Option<&UserNsConfig>,
+    init_binary: Option<&Path>,
+    scratch_helper: Option<&Path>,
+    entrypoint_script: Option<&str>,
+    capture_core_dumps: bool,
+    time_zone: Option<&TimeZone>,
+    read_only_rootfs: bool,
+    writable_paths: &[String],
+    extra_hosts: &[HostEntry],
 ) -> BoxliteResult<PathBuf> {
     let bundle_path = bundle_root.join(container_id);
 
@@ -111,7 +182,14 @@ pub(crate) fn create_oci_bundle(
 
     // Create /etc/hosts, /etc/hostname and /etc/resolv.conf files
     // These will be bind-mounted into the container to provide hostname and DNS resolution
-    create_container_etc_files(&bundle_path, container_id)?;
+    create_container_etc_files(&bundle_path, container_id, extra_hosts)?;
+    if let Some(time_zone) = time_zone {
+        create_time_zone_files(&bundle_path, time_zone)?;
+    }
+
+    let entrypoint_script = entrypoint_script
+        .map(|script| write_entrypoint_script(&bundle_path, script))
+        .transpose()?;
 
     let spec = spec::create_oci_spec(
         container_id,
@@ -127,6 +205,14 @@ pub(crate) fn create_oci_bundle(
         gid,
         &bundle_path,
         user_mounts,
+        userns,
+        init_binary,
+        scratch_helper,
+        entrypoint_script.as_deref(),
+        capture_core_dumps,
+        time_zone.map(|tz| tz.name.as_str()),
+        read_only_rootfs,
+        writable_paths,
     )?;
     let config_path = bundle_path.join("config.json");
 
@@ -142,6 +228,14 @@ pub(crate) fn create_oci_bundle(
         container_id,
         bundle_path = %bundle_path.display(),
         user_mounts_count = user_mounts.len(),
+        userns = userns.is_some(),
+        init = init_binary.is_some(),
+        entrypoint_script = entrypoint_script.is_some(),
+        capture_core_dumps,
+        time_zone = ?time_zone.map(|tz| &tz.name),
+        read_only_rootfs,
+        writable_paths = ?writable_paths,
+        extra_hosts = extra_hosts.len(),
         "Created OCI bundle"
     );
 
@@ -278,3 +372,38 @@ pub(crate) fn load_container_status(
 
     Ok(container.status())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::MetadataExt;
+
+    #[test]
+    fn test_write_hosts_file_rewrites_in_place() {
+        let bundle = tempfile::tempdir().unwrap();
+        let path = write_hosts_file(bundle.path(), &[]).unwrap();
+        let inode = fs::metadata(&path).unwrap().ino();
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .ends_with("127.0.1.1\tboxlite\n"));
+
+        let entries = [
+            HostEntry {
+                ip: "192.168.127.254".to_string(),
+                names: vec!["host.boxlite.internal".to_string()],
+            },
+            HostEntry {
+                ip: "192.168.127.254".to_string(),
+                names: vec!["web".to_string(), "api".to_string()],
+            },
+        ];
+        write_hosts_file(bundle.path(), &entries).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("127.0.0.1\tlocalhost\n"));
+        assert!(
+            content.ends_with("192.168.127.254\thost.boxlite.internal\n192.168.127.254\tweb api\n")
+        );
+        // The bind mount at /etc/hosts follows the inode
+        assert_eq!(fs::metadata(&path).unwrap().ino(), inode);
+    }
+}
@@ -0,0 +1,402 @@
+//! User namespace support
+//!
+//! When enabled, the container gets its own user namespace and container
+//! UIDs/GIDs are mapped onto an unprivileged range of the guest. Root inside
+//! the container is then an ordinary user in the VM.
+//!
+//! The rootfs is stored with container-side ownership (what the image layers
+//! contain), so before the first start it is shifted into the mapped range
+//! with `lchown`. This is a one-time walk over the rootfs; later starts detect
+//! the shift from the owner of the rootfs directory and skip it. Idmapped
+//! mounts would avoid the walk but need kernel support the guest kernel does
+//! not guarantee.
+//!
+//! Tradeoffs: the first start pays for the ownership walk, volumes are not
+//! shifted (their files show up as `nobody` unless owned by a mapped ID), and
+//! images that need real root capabilities in the VM (mounting filesystems,
+//! raw device access, loading modules) do not work.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use boxlite_shared::UserNamespace;
+use oci_spec::runtime::{LinuxIdMapping, LinuxIdMappingBuilder};
+use std::fs;
+use std::os::unix::fs::{lchown, MetadataExt, PermissionsExt};
+use std::path::Path;
+
+/// First guest ID of the range used by auto mode.
+const AUTO_HOST_ID: u32 = 100_000;
+
+/// Number of IDs mapped by auto mode (covers all common users).
+const AUTO_SIZE: u32 = 65_536;
+
+/// Hint appended to container start failures when userns is enabled.
+pub const ROOT_REQUIRED_HINT: &str = "The container runs in a user namespace: uid 0 inside \
+     has no privileges in the VM. Images that need real root (mounting filesystems, device \
+     access, loading modules) are not supported with userns; disable it for this box.";
+
+/// One contiguous ID range: `container_id..+size` maps to `host_id..+size`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRange {
+    pub container_id: u32,
+    pub host_id: u32,
+    pub size: u32,
+}
+
+impl IdRange {
+    fn map(&self, id: u32) -> Option<u32> {
+        let offset = id.checked_sub(self.container_id)?;
+        if offset < self.size {
+            self.host_id.checked_add(offset)
+        } else {
+            None
+        }
+    }
+}
+
+/// User namespace ID mappings for a container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UserNsConfig {
+    pub uid_map: Vec<IdRange>,
+    pub gid_map: Vec<IdRange>,
+}
+
+impl UserNsConfig {
+    /// Build from the proto message. An empty map selects the auto range.
+    pub fn from_proto(userns: UserNamespace) -> Self {
+        let convert = |map: Vec<boxlite_shared::IdMapping>| {
+            if map.is_empty() {
+                return vec![auto_range()];
+            }
+            map.into_iter()
+                .map(|m| IdRange {
+                    container_id: m.container_id,
+                    host_id: m.host_id,
+                    size: m.size,
+                })
+                .collect()
+        };
+
+        Self {
+            uid_map: convert(userns.uid_map),
+            gid_map: convert(userns.gid_map),
+        }
+    }
+
+    /// Map a container UID to the guest UID.
+    pub fn map_uid(&self, uid: u32) -> Option<u32> {
+        self.uid_map.iter().find_map(|r| r.map(uid))
+    }
+
+    /// Map a container GID to the guest GID.
+    pub fn map_gid(&self, gid: u32) -> Option<u32> {
+        self.gid_map.iter().find_map(|r| r.map(gid))
+    }
+
+    /// Check that the container user is representable in the namespace.
+    ///
+    /// Container root must always be mapped: the rootfs is root-owned and
+    /// the shift is keyed on it.
+    pub fn check_user(&self, uid: u32, gid: u32) -> BoxliteResult<()> {
+        if self.map_uid(0).is_none() || self.map_gid(0).is_none() {
+            return Err(BoxliteError::Config(
+                "userns mapping must include container uid 0 and gid 0 \
+                 (the image rootfs is owned by root)"
+                    .to_string(),
+            ));
+        }
+        if self.map_uid(uid).is_none() {
+            return Err(BoxliteError::Config(format!(
+                "container user uid {} is not mapped by the userns uid_map",
+                uid
+            )));
+        }
+        if self.map_gid(gid).is_none() {
+            return Err(BoxliteError::Config(format!(
+                "container user gid {} is not mapped by the userns gid_map",
+                gid
+            )));
+        }
+        Ok(())
+    }
+
+    /// OCI UID mappings.
+    pub fn oci_uid_mappings(&self) -> BoxliteResult<Vec<LinuxIdMapping>> {
+        build_oci_mappings(&self.uid_map, "UID")
+    }
+
+    /// OCI GID mappings.
+    pub fn oci_gid_mappings(&self) -> BoxliteResult<Vec<LinuxIdMapping>> {
+        build_oci_mappings(&self.gid_map, "GID")
+    }
+}
+
+fn auto_range() -> IdRange {
+    IdRange {
+        container_id: 0,
+        host_id: AUTO_HOST_ID,
+        size: AUTO_SIZE,
+    }
+}
+
+fn build_oci_mappings(ranges: &[IdRange], kind: &str) -> BoxliteResult<Vec<LinuxIdMapping>> {
+    ranges
+        .iter()
+        .map(|r| {
+            LinuxIdMappingBuilder::default()
+                .container_id(r.container_id)
+                .host_id(r.host_id)
+                .size(r.size)
+                .build()
+                .map_err(|e| {
+                    BoxliteError::Internal(format!("Failed to build {} mapping: {}", kind, e))
+                })
+        })
+        .collect()
+}
+
+/// Shift rootfs ownership into the mapped range.
+///
+/// Skipped when the rootfs directory is already owned by mapped root. A
+/// rootfs owned by anyone else was shifted for a different mapping; shifting
+/// it again would corrupt ownership, so that is an error.
+///
+/// Does not cross mount points. IDs outside the mapping are left untouched.
+pub fn shift_rootfs(rootfs: &Path, config: &UserNsConfig) -> BoxliteResult<()> {
+    let meta = fs::symlink_metadata(rootfs).map_err(|e| {
+        BoxliteError::Internal(format!("Failed to stat rootfs {}: {}", rootfs.display(), e))
+    })?;
+
+    // check_user() guarantees root is mapped
+    let root_uid = config.map_uid(0).unwrap_or(0);
+    let root_gid = config.map_gid(0).unwrap_or(0);
+    if meta.uid() == root_uid && meta.gid() == root_gid {
+        tracing::debug!(rootfs = %rootfs.display(), "Rootfs already shifted for userns");
+        return Ok(());
+    }
+    if meta.uid() != 0 || meta.gid() != 0 {
+        return Err(BoxliteError::Config(format!(
+            "rootfs is owned by {}:{}, expected 0:0 or {}:{}; it was prepared for a \
+             different userns mapping, which cannot be changed on an existing box",
+            meta.uid(),
+            meta.gid(),
+            root_uid,
+            root_gid
+        )));
+    }
+
+    let start = std::time::Instant::now();
+    let mut shifted = 0usize;
+    shift_tree(rootfs, meta.dev(), config, &mut shifted)?;
+
+    tracing::info!(
+        rootfs = %rootfs.display(),
+        shifted,
+        elapsed_ms = start.elapsed().as_millis() as u64,
+        "Shifted rootfs ownership for userns"
+    );
+    Ok(())
+}
+
+fn shift_tree(
+    path: &Path,
+    dev: u64,
+    config: &UserNsConfig,
+    shifted: &mut usize,
+) -> BoxliteResult<()> {
+    let meta = fs::symlink_metadata(path)
+        .map_err(|e| BoxliteError::Internal(format!("Failed to stat {}: {}", path.display(), e)))?;
+    if meta.dev() != dev {
+        return Ok(());
+    }
+
+    if meta.is_dir() {
+        let entries = fs::read_dir(path).map_err(|e| {
+            BoxliteError::Internal(format!("Failed to read {}: {}", path.display(), e))
+        })?;
+        for entry in entries {
+            let entry = entry.map_err(|e| {
+                BoxliteError::Internal(format!("Failed to read {}: {}", path.display(), e))
+            })?;
+            shift_tree(&entry.path(), dev, config, shifted)?;
+        }
+    }
+
+    // Shift the directory after its children so the rootfs itself, which
+    // marks a completed shift, is changed last.
+    shift_entry(path, &meta, config, shifted)
+}
+
+fn shift_entry(
+    path: &Path,
+    meta: &fs::Metadata,
+    config: &UserNsConfig,
+    shifted: &mut usize,
+) -> BoxliteResult<()> {
+    let uid = config.map_uid(meta.uid()).unwrap_or(meta.uid());
+    let gid = config.map_gid(meta.gid()).unwrap_or(meta.gid());
+    if uid == meta.uid() && gid == meta.gid() {
+        return Ok(());
+    }
+
+    lchown(path, Some(uid), Some(gid)).map_err(|e| {
+        BoxliteError::Internal(format!("Failed to chown {}: {}", path.display(), e))
+    })?;
+
+    // chown clears setuid/setgid on regular files; restore them
+    let mode = meta.mode();
+    if meta.is_file() && mode & 0o6000 != 0 {
+        fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o7777)).map_err(|e| {
+            BoxliteError::Internal(format!(
+                "Failed to restore mode of {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    }
+
+    *shifted += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boxlite_shared::IdMapping;
+
+    fn range(container_id: u32, host_id: u32, size: u32) -> IdRange {
+        IdRange {
+            container_id,
+            host_id,
+            size,
+        }
+    }
+
+    #[test]
+    fn test_from_proto_empty_is_auto() {
+        let config = UserNsConfig::from_proto(UserNamespace::default());
+        assert_eq!(config.uid_map, vec![range(0, AUTO_HOST_ID, AUTO_SIZE)]);
+        assert_eq!(config.gid_map, vec![range(0, AUTO_HOST_ID, AUTO_SIZE)]);
+    }
+
+    #[test]
+    fn test_from_proto_explicit() {
+        let config = UserNsConfig::from_proto(UserNamespace {
+            uid_map: vec![IdMapping {
+                container_id: 0,
+                host_id: 200000,
+                size: 1000,
+            }],
+            gid_map: vec![],
+        });
+        assert_eq!(config.uid_map, vec![range(0, 200000, 1000)]);
+        assert_eq!(config.gid_map, vec![range(0, AUTO_HOST_ID, AUTO_SIZE)]);
+    }
+
+    #[test]
+    fn test_map_ids() {
+        let config = UserNsConfig {
+            uid_map: vec![range(0, 100000, 1000), range(1000, 300000, 10)],
+            gid_map: vec![range(0, 100000, 65536)],
+        };
+        assert_eq!(config.map_uid(0), Some(100000));
+        assert_eq!(config.map_uid(999), Some(100999));
+        assert_eq!(config.map_uid(1005), Some(300005));
+        assert_eq!(config.map_uid(1010), None);
+        assert_eq!(config.map_gid(65535), Some(165535));
+        assert_eq!(config.map_gid(65536), None);
+    }
+
+    #[test]
+    fn test_check_user() {
+        let config = UserNsConfig {
+            uid_map: vec![range(0, 100000, 1000)],
+            gid_map: vec![range(0, 100000, 1000)],
+        };
+        assert!(config.check_user(0, 0).is_ok());
+        assert!(config.check_user(33, 33).is_ok());
+
+        let err = config.check_user(5000, 0).unwrap_err().to_string();
+        assert!(err.contains("uid 5000"), "{}", err);
+        let err = config.check_user(0, 5000).unwrap_err().to_string();
+        assert!(err.contains("gid 5000"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_user_requires_root_mapping() {
+        let config = UserNsConfig {
+            uid_map: vec![range(1000, 101000, 1000)],
+            gid_map: vec![range(0, 100000, 65536)],
+        };
+        let err = config.check_user(1000, 1000).unwrap_err().to_string();
+        assert!(err.contains("uid 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_oci_mappings() {
+        let config = UserNsConfig::from_proto(UserNamespace::default());
+        let uid = config.oci_uid_mappings().unwrap();
+        assert_eq!(uid.len(), 1);
+        assert_eq!(uid[0].container_id(), 0);
+        assert_eq!(uid[0].host_id(), AUTO_HOST_ID);
+        assert_eq!(uid[0].size(), AUTO_SIZE);
+    }
+
+    fn running_as_root() -> bool {
+        unsafe { nix::libc::geteuid() == 0 }
+    }
+
+    #[test]
+    fn test_shift_rootfs_shifts_once() {
+        if !running_as_root() {
+            eprintln!("skipping: chown requires root");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let rootfs = dir.path();
+        fs::create_dir_all(rootfs.join("home/app")).unwrap();
+        fs::write(rootfs.join("etc-file"), "x").unwrap();
+        fs::write(rootfs.join("home/app/suid"), "x").unwrap();
+        fs::set_permissions(
+            rootfs.join("home/app/suid"),
+            fs::Permissions::from_mode(0o4755),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink("etc-file", rootfs.join("link")).unwrap();
+        lchown(rootfs, Some(0), Some(0)).unwrap();
+        lchown(rootfs.join("home/app"), Some(1000), Some(1000)).unwrap();
+
+        let config = UserNsConfig::from_proto(UserNamespace::default());
+        shift_rootfs(rootfs, &config).unwrap();
+
+        let owner = |p: &str| {
+            let m = fs::symlink_metadata(rootfs.join(p)).unwrap();
+            (m.uid(), m.gid())
+        };
+        assert_eq!(owner(""), (100000, 100000));
+        assert_eq!(owner("etc-file"), (100000, 100000));
+        assert_eq!(owner("link"), (100000, 100000));
+        assert_eq!(owner("home/app"), (101000, 101000));
+        let mode = fs::metadata(rootfs.join("home/app/suid")).unwrap().mode();
+        assert_eq!(mode & 0o7777, 0o4755);
+
+        // Second start is a no-op
+        shift_rootfs(rootfs, &config).unwrap();
+        assert_eq!(owner("home/app"), (101000, 101000));
+    }
+
+    #[test]
+    fn test_shift_rootfs_rejects_different_mapping() {
+        if !running_as_root() {
+            eprintln!("skipping: chown requires root");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        lchown(dir.path(), Some(200000), Some(200000)).unwrap();
+
+        let config = UserNsConfig::from_proto(UserNamespace::default());
+        let err = shift_rootfs(dir.path(), &config).unwrap_err().to_string();
+        assert!(err.contains("different userns mapping"), "{}", err);
+    }
+}
@@ -0,0 +1,302 @@
+//! Core dump capture (`BoxOptions::capture_core_dumps`).
+//!
+//! `kernel.core_pattern` pipes core dumps to the agent binary, which the
+//! kernel starts in the guest's root namespaces as
+//! `boxlite-guest --core-dump <global pid> <pid> <signal> <time> <comm>`.
+//! The handler writes the dump into [`core_dumps::DIR`] under the crashing
+//! process's root, truncated to [`core_dumps::MAX_BYTES`], then removes the
+//! oldest dumps past [`core_dumps::MAX_COUNT`].
+//!
+//! Files are named `core.<comm>.<pid>.<signal>.<time>`, so listing them
+//! needs nothing but the directory.
+//!
+//! The core pattern is global to the guest kernel. A box runs a single
+//! container, so it is set when that container asks for capture.
+
+use boxlite_shared::constants::core_dumps;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+
+/// First argument the agent is started with to act as the handler.
+pub const HANDLER_FLAG: &str = "--core-dump";
+
+/// Longest `kernel.core_pattern` the kernel accepts.
+const MAX_PATTERN_LEN: usize = 127;
+
+/// Dumps the kernel keeps the crashing process around for, so the handler
+/// can resolve `/proc/<pid>/root`.
+const PIPE_LIMIT: &str = "4";
+
+/// A core file in [`core_dumps::DIR`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoreFile {
+    pub name: String,
+    pub pid: u32,
+    pub signal: i32,
+    pub time_unix: i64,
+    pub size_bytes: u64,
+}
+
+impl From<CoreFile> for boxlite_shared::CoreDump {
+    fn from(core: CoreFile) -> Self {
+        Self {
+            name: core.name,
+            pid: core.pid,
+            signal: core.signal,
+            size_bytes: core.size_bytes,
+            time_unix: core.time_unix,
+        }
+    }
+}
+
+/// Route core dumps to the handler and create the core directory in
+/// `rootfs`.
+pub fn enable(rootfs: &Path) -> BoxliteResult<()> {
+    let dir = dir_in(rootfs);
+    fs::create_dir_all(&dir)
+        .and_then(|()| check_within(rootfs, &dir))
+        .map_err(|e| {
+            BoxliteError::Internal(format!(
+                "Failed to create core dump directory {}: {}",
+                core_dumps::DIR,
+                e
+            ))
+        })?;
+
+    let exe = std::env::current_exe().map_err(|e| {
+        BoxliteError::Internal(format!("Failed to locate core dump handler: {}", e))
+    })?;
+    let pattern = format!("|{} {} %P %p %s %t %e", exe.display(), HANDLER_FLAG);
+    if pattern.len() > MAX_PATTERN_LEN {
+        return Err(BoxliteError::Internal(format!(
+            "Core pattern {:?} is longer than {} bytes",
+            pattern, MAX_PATTERN_LEN
+        )));
+    }
+
+    let sysctl = |path: &str, value: &str| {
+        fs::write(path, value)
+            .map_err(|e| BoxliteError::Internal(format!("Failed to set {}: {}", path, e)))
+    };
+    sysctl("/proc/sys/kernel/core_pattern", &pattern)?;
+    sysctl("/proc/sys/kernel/core_pipe_limit", PIPE_LIMIT)?;
+    tracing::info!(pattern = %pattern, "Core dump capture enabled");
+    Ok(())
+}
+
+/// Whether this process was started by the kernel as the core handler.
+pub fn invoked_as_handler() -> bool {
+    std::env::args_os()
+        .nth(1)
+        .is_some_and(|arg| arg == HANDLER_FLAG)
+}
+
+/// Store the dump piped on stdin and exit.
+///
+/// The kernel gives the handler no stderr; failures go to the kernel log.
+pub fn run() -> ! {
+    let args: Vec<OsString> = std::env::args_os().skip(2).collect();
+    let code = match handle(&args, &mut io::stdin().lock()) {
+        Ok(()) => 0,
+        Err(e) => {
+            if let Ok(mut kmsg) = fs::OpenOptions::new().write(true).open("/dev/kmsg") {
+                let _ = writeln!(kmsg, "boxlite-guest: core dump not saved: {}", e);
+            }
+            1
+        }
+    };
+    std::process::exit(code)
+}
+
+/// Core files in `dir`, oldest first. A missing directory has none.
+pub fn list(dir: &Path) -> io::Result<Vec<CoreFile>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut cores = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let Some((pid, signal, time_unix)) = parse_name(&name) else {
+            continue;
+        };
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        cores.push(CoreFile {
+            name,
+            pid,
+            signal,
+            time_unix,
+            size_bytes: metadata.size(),
+        });
+    }
+    cores.sort_by(|a, b| (a.time_unix, &a.name).cmp(&(b.time_unix, &b.name)));
+    Ok(cores)
+}
+
+/// `core_dumps::DIR` under `root`.
+pub fn dir_in(root: &Path) -> PathBuf {
+    root.join(core_dumps::DIR.trim_start_matches('/'))
+}
+
+fn handle(args: &[OsString], core: &mut impl Read) -> io::Result<()> {
+    let [global_pid, pid, signal, time, comm @ ..] = args else {
+        return Err(invalid(format!("unexpected arguments {:?}", args)));
+    };
+    let arg = |value: &OsString| value.to_string_lossy().into_owned();
+    let pid: u32 = arg(pid).parse().map_err(|_| invalid("bad pid"))?;
+    let signal: i32 = arg(signal).parse().map_err(|_| invalid("bad signal"))?;
+    let time: i64 = arg(time).parse().map_err(|_| invalid("bad time"))?;
+    let comm = comm.iter().map(arg).collect::<Vec<_>>().join(" ");
+
+    // The directory was created at container start; one that now leads
+    // out of the container (e.g. replaced by a symlink) is not used
+    let root = fs::canonicalize(format!("/proc/{}/root", arg(global_pid)))?;
+    let dir = dir_in(&root);
+    check_within(&root, &dir)?;
+
+    let name = file_name(&comm, pid, signal, time);
+    let partial = dir.join(format!(".{}.partial", name));
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .custom_flags(nix::libc::O_NOFOLLOW)
+        .open(&partial)?;
+    let written = io::copy(&mut core.take(core_dumps::MAX_BYTES), &mut file);
+    drop(file);
+    if let Err(e) = written {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, dir.join(&name))?;
+
+    rotate(&dir, core_dumps::MAX_COUNT)
+}
+
+/// Remove the oldest core files in `dir` until at most `keep` are left.
+fn rotate(dir: &Path, keep: usize) -> io::Result<()> {
+    let cores = list(dir)?;
+    let excess = cores.len().saturating_sub(keep);
+    for core in &cores[..excess] {
+        fs::remove_file(dir.join(&core.name))?;
+    }
+    Ok(())
+}
+
+/// `core.<comm>.<pid>.<signal>.<time>`, with `comm` limited to characters
+/// safe in a file name.
+fn file_name(comm: &str, pid: u32, signal: i32, time: i64) -> String {
+    let comm: String = comm
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let comm = if comm.is_empty() { "unknown" } else { &comm };
+    format!("core.{}.{}.{}.{}", comm, pid, signal, time)
+}
+
+/// `(pid, signal, time)` of a name made by [`file_name`].
+fn parse_name(name: &str) -> Option<(u32, i32, i64)> {
+    let mut fields = name.rsplitn(4, '.');
+    let time = fields.next()?.parse().ok()?;
+    let signal = fields.next()?.parse().ok()?;
+    let pid = fields.next()?.parse().ok()?;
+    let rest = fields.next()?;
+    rest.strip_prefix("core.")?;
+    Some((pid, signal, time))
+}
+
+/// Fail unless `dir` exists and resolves to a directory under `root`.
+fn check_within(root: &Path, dir: &Path) -> io::Result<()> {
+    let root = fs::canonicalize(root)?;
+    let resolved = fs::canonicalize(dir)?;
+    if resolved.starts_with(&root) && resolved.is_dir() {
+        Ok(())
+    } else {
+        Err(invalid(format!(
+            "{} resolves outside the container root",
+            dir.display()
+        )))
+    }
+}
+
+fn invalid(msg: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_round_trips() {
+        let name = file_name("my app/v1.2", 42, 11, 1_700_000_000);
+        assert_eq!(name, "core.my_app_v1.2.42.11.1700000000");
+        assert_eq!(parse_name(&name), Some((42, 11, 1_700_000_000)));
+
+        assert_eq!(file_name("", 1, 6, 0), "core.unknown.1.6.0");
+        assert_eq!(parse_name("core.1.6"), None);
+        assert_eq!(parse_name("notes.txt"), None);
+        assert_eq!(parse_name(".core.sh.1.6.0.partial"), None);
+    }
+
+    #[test]
+    fn test_list_and_rotate_drop_oldest() {
+        let dir = tempfile::tempdir().unwrap();
+        for (comm, time) in [("a", 30), ("b", 10), ("c", 20)] {
+            fs::write(dir.path().join(file_name(comm, 7, 11, time)), b"core").unwrap();
+        }
+        fs::write(dir.path().join("README"), b"not a core").unwrap();
+
+        let cores = list(dir.path()).unwrap();
+        let times: Vec<i64> = cores.iter().map(|c| c.time_unix).collect();
+        assert_eq!(times, vec![10, 20, 30]);
+        assert_eq!(cores[0].size_bytes, 4);
+
+        rotate(dir.path(), 2).unwrap();
+        let names: Vec<String> = list(dir.path())
+            .unwrap()
+            .into_iter()
+            .map(|c| c.name)
+            .collect();
+        assert_eq!(names, vec!["core.c.7.11.20", "core.a.7.11.30"]);
+        assert!(dir.path().join("README").exists());
+    }
+
+    #[test]
+    fn test_list_missing_dir_is_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list(&dir.path().join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_within_rejects_escaping_symlink() {
+        let root = tempfile::tempdir().unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        let dir = dir_in(root.path());
+        fs::create_dir_all(dir.parent().unwrap()).unwrap();
+        std::os::unix::fs::symlink(outside.path(), &dir).unwrap();
+        assert!(check_within(root.path(), &dir).is_err());
+
+        fs::remove_file(&dir).unwrap();
+        fs::create_dir(&dir).unwrap();
+        check_within(root.path(), &dir).unwrap();
+    }
+}
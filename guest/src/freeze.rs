@@ -0,0 +1,218 @@
+//! Filesystem flush for `Guest.Sync` (`LiteBox::sync()`).
+//!
+//! `sync(2)` writes dirty data out, then a FIFREEZE/FITHAW pair on the
+//! container filesystem checkpoints its journal, so the disk image on the
+//! host is consistent without a journal replay. Writes to the container
+//! block while it is frozen.
+//!
+//! The freeze runs on its own thread, which thaws as soon as FIFREEZE
+//! returns. The caller only waits up to the freeze cap, so neither a slow
+//! freeze nor a dropped RPC or dead host holds the filesystem frozen, and
+//! a second freeze is refused until the first has thawed. A failed thaw is
+//! retried, last through a freshly opened descriptor of the mount, before
+//! the freeze is released.
+
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use std::fs::File;
+use std::io;
+use std::os::unix::io::AsRawFd;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// `_IOWR('X', 119, int)`
+const FIFREEZE: u32 = 0xC004_5877;
+/// `_IOWR('X', 120, int)`
+const FITHAW: u32 = 0xC004_5878;
+
+/// FITHAW attempts per descriptor before giving up on it.
+const THAW_ATTEMPTS: u32 = 5;
+/// Pause before the first FITHAW retry; doubled after each failure.
+const THAW_BACKOFF: Duration = Duration::from_millis(10);
+
+/// Set while a freeze thread owns the container filesystem.
+static FREEZING: AtomicBool = AtomicBool::new(false);
+
+/// Ownership of [`FREEZING`]; released on drop.
+struct FreezeGuard;
+
+impl FreezeGuard {
+    fn acquire() -> BoxliteResult<Self> {
+        if FREEZING.swap(true, Ordering::SeqCst) {
+            return Err(BoxliteError::InvalidState(
+                "a filesystem freeze is already in progress".to_string(),
+            ));
+        }
+        Ok(Self)
+    }
+}
+
+impl Drop for FreezeGuard {
+    fn drop(&mut self) {
+        FREEZING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Flush all filesystems, then freeze and thaw the one mounted at `mount`.
+///
+/// Returns how long `mount` was frozen. A zero `cap` only flushes. Fails
+/// with `InvalidState` while another freeze is in progress, and with
+/// `Timeout` if the freeze did not complete within `cap`; the filesystem
+/// is thawed as soon as it does.
+pub fn sync_and_freeze(mount: &Path, cap: Duration) -> BoxliteResult<Duration> {
+    unsafe {
+        nix::libc::sync();
+    }
+    if cap.is_zero() {
+        return Ok(Duration::ZERO);
+    }
+
+    let guard = FreezeGuard::acquire()?;
+    let file = File::open(mount).map_err(|e| {
+        BoxliteError::Internal(format!("Failed to open {}: {}", mount.display(), e))
+    })?;
+
+    let (tx, rx) = mpsc::channel();
+    let mount_path = mount.to_path_buf();
+    std::thread::spawn(move || {
+        let result = freeze_and_thaw(&file, &mount_path);
+        drop(guard);
+        if let Ok(frozen) = &result {
+            tracing::debug!(mount = %mount_path.display(), ?frozen, "Froze and thawed filesystem");
+        }
+        // The caller may have given up waiting
+        let _ = tx.send(result);
+    });
+
+    match rx.recv_timeout(cap) {
+        Ok(result) => result.map_err(|e| {
+            BoxliteError::Internal(format!("Failed to freeze {}: {}", mount.display(), e))
+        }),
+        Err(_) => {
+            tracing::warn!(
+                mount = %mount.display(),
+                ?cap,
+                "Filesystem freeze exceeded its cap; it thaws once the freeze completes"
+            );
+            Err(BoxliteError::Timeout(format!(
+                "freezing {} took longer than {:?}",
+                mount.display(),
+                cap
+            )))
+        }
+    }
+}
+
+/// FIFREEZE then FITHAW; how long the filesystem stayed frozen.
+///
+/// A failing FITHAW is retried on `file`, then on a new descriptor of
+/// `mount`, so a transient error doesn't leave the filesystem frozen.
+fn freeze_and_thaw(file: &File, mount: &Path) -> io::Result<Duration> {
+    let started = Instant::now();
+    if unsafe { nix::libc::ioctl(file.as_raw_fd(), FIFREEZE as _, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let thawed = thaw_with_retry(|| thaw(file)).or_else(|e| {
+        tracing::warn!(error = %e, "Thaw failed, retrying through a new descriptor");
+        let reopened = File::open(mount)?;
+        thaw_with_retry(|| thaw(&reopened))
+    });
+    if let Err(e) = thawed {
+        tracing::error!(error = %e, mount = %mount.display(), "Failed to thaw filesystem");
+        return Err(e);
+    }
+    Ok(started.elapsed())
+}
+
+fn thaw(file: &File) -> io::Result<()> {
+    if unsafe { nix::libc::ioctl(file.as_raw_fd(), FITHAW as _, 0) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Call `thaw` up to [`THAW_ATTEMPTS`] times with backoff. EINVAL means the
+/// filesystem is no longer frozen, which counts as thawed.
+fn thaw_with_retry(mut thaw: impl FnMut() -> io::Result<()>) -> io::Result<()> {
+    let mut backoff = THAW_BACKOFF;
+    let mut attempt = 1;
+    loop {
+        match thaw() {
+            Ok(()) => return Ok(()),
+            Err(e) if e.raw_os_error() == Some(nix::libc::EINVAL) && attempt > 1 => return Ok(()),
+            Err(e) if attempt >= THAW_ATTEMPTS => return Err(e),
+            Err(e) => {
+                tracing::warn!(error = %e, attempt, "Failed to thaw filesystem, retrying");
+                std::thread::sleep(backoff);
+                backoff *= 2;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_second_freeze_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let held = FreezeGuard::acquire().unwrap();
+
+        let result = sync_and_freeze(dir.path(), Duration::from_secs(1));
+        assert!(matches!(result, Err(BoxliteError::InvalidState(_))));
+
+        // A flush-only sync doesn't need the freeze
+        assert_eq!(
+            sync_and_freeze(dir.path(), Duration::ZERO).unwrap(),
+            Duration::ZERO
+        );
+
+        drop(held);
+        drop(FreezeGuard::acquire().unwrap());
+    }
+
+    #[test]
+    fn test_thaw_retries_transient_failures() {
+        let mut calls = 0;
+        let result = thaw_with_retry(|| {
+            calls += 1;
+            if calls < 3 {
+                Err(io::Error::from_raw_os_error(nix::libc::EBUSY))
+            } else {
+                Ok(())
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(calls, 3);
+    }
+
+    #[test]
+    fn test_thaw_gives_up_after_bounded_attempts() {
+        let mut calls = 0;
+        let result = thaw_with_retry(|| {
+            calls += 1;
+            Err(io::Error::from_raw_os_error(nix::libc::EIO))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, THAW_ATTEMPTS);
+    }
+
+    #[test]
+    fn test_thaw_einval_on_retry_means_thawed() {
+        let mut calls = 0;
+        let result = thaw_with_retry(|| {
+            calls += 1;
+            if calls == 1 {
+                Err(io::Error::from_raw_os_error(nix::libc::EIO))
+            } else {
+                Err(io::Error::from_raw_os_error(nix::libc::EINVAL))
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(calls, 2);
+    }
+}
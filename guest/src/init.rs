@@ -0,0 +1,159 @@
+//! Built-in container init (`BoxOptions::init`).
+//!
+//! The agent binary is bind-mounted into the container at
+//! `/dev/boxlite-init` and, when started under that name, runs as the
+//! container's PID 1 instead of the agent, like `docker run --init`:
+//!
+//! - spawns the entrypoint (`boxlite-init -- <entrypoint...>`) as its child
+//! - forwards every catchable signal to it (SIGTERM on graceful stop)
+//! - reaps all children, including orphans reparented to it
+//! - exits with the entrypoint's status (128 + signal if it was killed)
+//!
+//! It is single-threaded and blocks the forwarded signals, taking them with
+//! `sigwait`: a blocked signal is queued even for PID 1, whose default
+//! dispositions the kernel would otherwise drop.
+
+use nix::errno::Errno;
+use nix::sys::signal::{kill, SigSet, SigmaskHow, Signal};
+use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
+use nix::unistd::Pid;
+use std::ffi::OsString;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+
+/// File name the agent binary is run under to act as init.
+pub const NAME: &str = "boxlite-init";
+
+/// Signals that stay unblocked: faults raised by init itself, not meant
+/// for the child. SIGKILL and SIGSTOP can't be blocked anyway.
+const SYNCHRONOUS: &[Signal] = &[
+    Signal::SIGFPE,
+    Signal::SIGILL,
+    Signal::SIGSEGV,
+    Signal::SIGBUS,
+    Signal::SIGABRT,
+    Signal::SIGTRAP,
+    Signal::SIGSYS,
+];
+
+/// Whether this process was started as the container init.
+pub fn invoked_as_init() -> bool {
+    std::env::args_os()
+        .next()
+        .is_some_and(|arg0| Path::new(&arg0).file_name() == Some(NAME.as_ref()))
+}
+
+/// Run as init and exit with the entrypoint's status.
+pub fn run() -> ! {
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    let command = match args.split_first() {
+        Some((first, rest)) if first == "--" => rest,
+        _ => &args[..],
+    };
+
+    let code = match supervise(command) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("{}: {}", NAME, e);
+            e.exit_code()
+        }
+    };
+    std::process::exit(code)
+}
+
+#[derive(Debug)]
+enum InitError {
+    NoCommand,
+    Spawn(OsString, std::io::Error),
+    Signals(Errno),
+}
+
+impl InitError {
+    /// Shell conventions: 127 = not found, 126 = not executable.
+    fn exit_code(&self) -> i32 {
+        match self {
+            InitError::Spawn(_, e) if e.kind() == std::io::ErrorKind::NotFound => 127,
+            InitError::Spawn(..) => 126,
+            InitError::NoCommand | InitError::Signals(_) => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitError::NoCommand => write!(f, "no command given"),
+            InitError::Spawn(program, e) => {
+                write!(f, "failed to start {}: {}", program.to_string_lossy(), e)
+            }
+            InitError::Signals(e) => write!(f, "failed to block signals: {}", e),
+        }
+    }
+}
+
+/// Spawn `command`, then forward signals and reap until it exits.
+fn supervise(command: &[OsString]) -> Result<i32, InitError> {
+    let (program, args) = command.split_first().ok_or(InitError::NoCommand)?;
+
+    // Only matters when not PID 1 (e.g. tests): orphans reparent to us
+    let _ = nix::sys::prctl::set_child_subreaper(true);
+
+    let mut forwarded = SigSet::all();
+    for signal in SYNCHRONOUS {
+        forwarded.remove(*signal);
+    }
+    // Block before spawning so no signal is lost in between
+    nix::sys::signal::sigprocmask(SigmaskHow::SIG_BLOCK, Some(&forwarded), None)
+        .map_err(InitError::Signals)?;
+
+    let mut command = Command::new(program);
+    command.args(args);
+    // SAFETY: sigprocmask is async-signal-safe. The mask survives exec, so
+    // without this the entrypoint would never see the forwarded signals.
+    unsafe {
+        command.pre_exec(|| {
+            nix::sys::signal::sigprocmask(SigmaskHow::SIG_SETMASK, Some(&SigSet::empty()), None)
+                .map_err(std::io::Error::from)
+        });
+    }
+    let child = command
+        .spawn()
+        .map_err(|e| InitError::Spawn(program.clone(), e))?;
+    let child = Pid::from_raw(child.id() as i32);
+
+    loop {
+        match forwarded.wait() {
+            Ok(Signal::SIGCHLD) => {}
+            Ok(signal) => {
+                if let Err(e) = kill(child, signal) {
+                    eprintln!("{}: failed to forward {}: {}", NAME, signal, e);
+                }
+            }
+            Err(e) => eprintln!("{}: sigwait failed: {}", NAME, e),
+        }
+
+        if let Some(code) = reap(child) {
+            return Ok(code);
+        }
+    }
+}
+
+/// Reap every exited child. Returns the exit code once `child` has exited.
+fn reap(child: Pid) -> Option<i32> {
+    let mut code = None;
+    loop {
+        match waitpid(None, Some(WaitPidFlag::WNOHANG)) {
+            Ok(WaitStatus::Exited(pid, status)) if pid == child => code = Some(status),
+            Ok(WaitStatus::Signaled(pid, signal, _)) if pid == child => {
+                code = Some(128 + signal as i32)
+            }
+            Ok(WaitStatus::StillAlive) | Err(Errno::ECHILD) => return code,
+            Ok(_) | Err(Errno::EINTR) => {}
+            Err(e) => {
+                eprintln!("{}: waitpid failed: {}", NAME, e);
+                return code;
+            }
+        }
+    }
+}
@@ -66,6 +66,7 @@ impl ContainerLayout {
 /// │   └── containers/{cid}/
 /// │       ├── overlayfs/{upper,work}  # overlayfs writable layer
 /// │       └── rootfs/                 # merged rootfs mount point
+/// ├── execs/{exec_id}/                # detached exec output (virtio-fs mount)
 /// └── containers/                     # OCI containers
 ///     └── {cid}/
 ///         ├── config.json             # OCI bundle config
@@ -118,6 +119,13 @@ impl GuestLayout {
         self.shared.base()
     }
 
+    /// Detached exec output: /run/boxlite/execs (virtio-fs mount from host)
+    ///
+    /// Each captured execution writes to execs/{exec_id}/{stdout,stderr}.
+    pub fn execs_dir(&self) -> PathBuf {
+        self.base.join(dirs::EXECS)
+    }
+
     // ========================================================================
     // CONTAINER LAYOUT
     // ========================================================================
@@ -199,6 +207,7 @@ mod tests {
         let layout = GuestLayout::new();
         assert_eq!(layout.base().to_str().unwrap(), "/run/boxlite");
         assert_eq!(layout.shared_dir().to_str().unwrap(), "/run/boxlite/shared");
+        assert_eq!(layout.execs_dir().to_str().unwrap(), "/run/boxlite/execs");
     }
 
     #[test]
@@ -3,9 +3,17 @@
 #[cfg(not(target_os = "linux"))]
 compile_error!("BoxLite guest is Linux-only; build with a Linux target");
 
+#[cfg(target_os = "linux")]
+mod agent_log;
 #[cfg(target_os = "linux")]
 mod container;
 #[cfg(target_os = "linux")]
+mod core_dump;
+#[cfg(target_os = "linux")]
+mod freeze;
+#[cfg(target_os = "linux")]
+mod init;
+#[cfg(target_os = "linux")]
 mod layout;
 #[cfg(target_os = "linux")]
 mod mounts;
@@ -14,9 +22,13 @@ mod network;
 #[cfg(target_os = "linux")]
 mod overlayfs;
 #[cfg(target_os = "linux")]
+mod scratch;
+#[cfg(target_os = "linux")]
 mod service;
 #[cfg(target_os = "linux")]
 mod storage;
+#[cfg(target_os = "linux")]
+mod swap;
 
 #[cfg(target_os = "linux")]
 use boxlite_shared::errors::BoxliteResult;
@@ -51,10 +63,31 @@ struct GuestArgs {
     notify: Option<String>,
 }
 
+#[cfg(target_os = "linux")]
+fn main() -> BoxliteResult<()> {
+    // Bind-mounted into containers as their PID 1 (BoxOptions::init)
+    if init::invoked_as_init() {
+        init::run();
+    }
+    // Bind-mounted into containers to set up exec scratch directories
+    if scratch::invoked_as_scratch() {
+        scratch::run();
+    }
+    // Started by the kernel through kernel.core_pattern (capture_core_dumps)
+    if core_dump::invoked_as_handler() {
+        core_dump::run();
+    }
+    agent_main()
+}
+
 #[cfg(target_os = "linux")]
 #[tokio::main]
-async fn main() -> BoxliteResult<()> {
+async fn agent_main() -> BoxliteResult<()> {
     // Early diagnostic - visible even if tracing fails
+    eprintln!(
+        "{}",
+        boxlite_shared::boot::BootPhase::KernelHandoff.marker_line()
+    );
     eprintln!("[BOOT] BoxLite guest agent starting");
 
     // Set panic hook to ensure we see panics
@@ -69,7 +102,7 @@ async fn main() -> BoxliteResult<()> {
     // Default to "info" level if RUST_LOG is not set (for visibility)
     if let Err(e) = tracing_subscriber::fmt()
         .with_target(true) // Show module names
-        .with_writer(std::io::stderr)
+        .with_writer(agent_log::writer) // stderr, plus a copy for Guest.AgentLog
         .with_env_filter(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("info")),
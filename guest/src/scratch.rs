@@ -0,0 +1,237 @@
+//! Per-exec scratch directories (`BoxCommand::scratch_dir`).
+//!
+//! The agent binary is bind-mounted into every container at
+//! `/dev/boxlite-scratch` and, when started under that name, sets up a
+//! scratch directory and then execs the real command:
+//!
+//! - unshares a mount namespace, so the mount is seen by this exec only
+//! - mounts a tmpfs of the requested size at the requested path, owned by
+//!   the exec's user
+//! - drops from root to the exec's user and group
+//! - execs the command, which keeps the namespace (and the tmpfs) alive
+//!
+//! Nothing has to be torn down: the kernel frees the tmpfs with the mount
+//! namespace, once the command and every process it started have exited.
+//!
+//! Invocation: `boxlite-scratch <size_mib> <mount_at> <uid> <gid> -- <command...>`.
+
+use nix::mount::{mount, MsFlags};
+use nix::sched::{unshare, CloneFlags};
+use nix::unistd::{setgid, setgroups, setuid, Gid, Uid};
+use std::ffi::OsString;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File name the agent binary is run under to set up a scratch directory.
+pub const NAME: &str = "boxlite-scratch";
+
+/// Whether this process was started as the scratch helper.
+pub fn invoked_as_scratch() -> bool {
+    std::env::args_os()
+        .next()
+        .is_some_and(|arg0| Path::new(&arg0).file_name() == Some(NAME.as_ref()))
+}
+
+/// Arguments that make the helper at `helper_path` run `command` as
+/// `user` with a `size_mib` tmpfs at `mount_at`.
+pub fn wrap(
+    helper_path: &str,
+    size_mib: u32,
+    mount_at: &str,
+    user: (u32, u32),
+    command: &[String],
+) -> Vec<String> {
+    [
+        helper_path.to_string(),
+        size_mib.to_string(),
+        mount_at.to_string(),
+        user.0.to_string(),
+        user.1.to_string(),
+        "--".to_string(),
+    ]
+    .into_iter()
+    .chain(command.iter().cloned())
+    .collect()
+}
+
+/// Set up the scratch directory and exec the command; exits on failure.
+pub fn run() -> ! {
+    let args: Vec<OsString> = std::env::args_os().skip(1).collect();
+    let e = match parse(&args) {
+        Ok((scratch, command)) => match scratch.setup() {
+            Ok(()) => exec(command),
+            Err(e) => e,
+        },
+        Err(e) => e,
+    };
+    eprintln!("{}: {}", NAME, e);
+    std::process::exit(e.exit_code())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Scratch {
+    size_mib: u32,
+    mount_at: PathBuf,
+    uid: u32,
+    gid: u32,
+}
+
+#[derive(Debug)]
+enum ScratchError {
+    Usage(String),
+    Setup(&'static str, std::io::Error),
+    Exec(OsString, std::io::Error),
+}
+
+impl ScratchError {
+    /// Shell conventions: 127 = not found, 126 = not executable.
+    fn exit_code(&self) -> i32 {
+        match self {
+            ScratchError::Exec(_, e) if e.kind() == std::io::ErrorKind::NotFound => 127,
+            ScratchError::Exec(..) => 126,
+            ScratchError::Usage(_) | ScratchError::Setup(..) => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for ScratchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScratchError::Usage(msg) => write!(f, "{}", msg),
+            ScratchError::Setup(what, e) => write!(f, "failed to {}: {}", what, e),
+            ScratchError::Exec(program, e) => {
+                write!(f, "failed to start {}: {}", program.to_string_lossy(), e)
+            }
+        }
+    }
+}
+
+/// Split the arguments written by [`wrap`] into the spec and the command.
+fn parse(args: &[OsString]) -> Result<(Scratch, &[OsString]), ScratchError> {
+    let usage = || {
+        ScratchError::Usage(
+            "usage: boxlite-scratch <size_mib> <mount_at> <uid> <gid> -- <command...>".into(),
+        )
+    };
+    let separator = args.iter().position(|a| a == "--").ok_or_else(usage)?;
+    let (spec, command) = (&args[..separator], &args[separator + 1..]);
+    let [size_mib, mount_at, uid, gid] = spec else {
+        return Err(usage());
+    };
+    if command.is_empty() {
+        return Err(usage());
+    }
+
+    let number = |arg: &OsString| arg.to_str().and_then(|s| s.parse::<u32>().ok());
+    let scratch = Scratch {
+        size_mib: number(size_mib).ok_or_else(usage)?,
+        mount_at: PathBuf::from(mount_at),
+        uid: number(uid).ok_or_else(usage)?,
+        gid: number(gid).ok_or_else(usage)?,
+    };
+    Ok((scratch, command))
+}
+
+impl Scratch {
+    /// Mount the tmpfs in a new mount namespace and become the exec's user.
+    fn setup(&self) -> Result<(), ScratchError> {
+        let err = |what: &'static str| move |e: nix::Error| ScratchError::Setup(what, e.into());
+
+        unshare(CloneFlags::CLONE_NEWNS).map_err(err("unshare mount namespace"))?;
+        // Keep the tmpfs from propagating back to the container's namespace
+        mount(
+            None::<&str>,
+            "/",
+            None::<&str>,
+            MsFlags::MS_REC | MsFlags::MS_PRIVATE,
+            None::<&str>,
+        )
+        .map_err(err("make mounts private"))?;
+
+        std::fs::create_dir_all(&self.mount_at)
+            .map_err(|e| ScratchError::Setup("create mount point", e))?;
+        let options = format!(
+            "size={}m,mode=0700,uid={},gid={}",
+            self.size_mib, self.uid, self.gid
+        );
+        mount(
+            Some("tmpfs"),
+            &self.mount_at,
+            Some("tmpfs"),
+            MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+            Some(options.as_str()),
+        )
+        .map_err(err("mount scratch tmpfs"))?;
+
+        // Started as root for the mount; the command runs as the exec's user
+        if (self.uid, self.gid) != (0, 0) {
+            setgroups(&[]).map_err(err("clear supplementary groups"))?;
+            setgid(Gid::from_raw(self.gid)).map_err(err("set group"))?;
+            setuid(Uid::from_raw(self.uid)).map_err(err("set user"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Replace this process with `command`; returns only on failure.
+fn exec(command: &[OsString]) -> ScratchError {
+    let (program, args) = command
+        .split_first()
+        .expect("parse() rejects an empty command");
+    let e = Command::new(program).args(args).exec();
+    ScratchError::Exec(program.clone(), e)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn os_args(args: &[String]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_parse_wrapped_args() {
+        let command = vec!["sh".to_string(), "-c".to_string(), "ls -- /".to_string()];
+        let wrapped = wrap(
+            "/dev/boxlite-scratch",
+            256,
+            "/scratch",
+            (1000, 100),
+            &command,
+        );
+        assert_eq!(wrapped[0], "/dev/boxlite-scratch");
+
+        let args = os_args(&wrapped[1..]);
+        let (scratch, parsed) = parse(&args).unwrap();
+        assert_eq!(
+            scratch,
+            Scratch {
+                size_mib: 256,
+                mount_at: PathBuf::from("/scratch"),
+                uid: 1000,
+                gid: 100,
+            }
+        );
+        assert_eq!(parsed, os_args(&command).as_slice());
+    }
+
+    #[test]
+    fn test_parse_rejects_malformed_args() {
+        for args in [
+            vec![],
+            vec!["256", "/scratch", "0", "0"],
+            vec!["256", "/scratch", "0", "0", "--"],
+            vec!["256", "/scratch", "0", "--", "sh"],
+            vec!["big", "/scratch", "0", "0", "--", "sh"],
+            vec!["256", "/scratch", "-1", "0", "--", "sh"],
+        ] {
+            let args: Vec<OsString> = args.into_iter().map(OsString::from).collect();
+            assert!(
+                matches!(parse(&args), Err(ScratchError::Usage(_))),
+                "{args:?}"
+            );
+        }
+    }
+}
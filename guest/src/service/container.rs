@@ -1,20 +1,25 @@
 #![cfg(target_os = "linux")]
 //! Container service implementation.
 //!
-//! Handles OCI container lifecycle (Init RPC).
+//! Handles OCI container lifecycle (Init RPC), lists captured core dumps and
+//! rewrites the container's hosts file.
 
 use std::path::Path;
 
 use crate::service::server::GuestServer;
+use boxlite_shared::boot::BootPhase;
 use boxlite_shared::{
     container_init_response, rootfs_init, Container as ContainerService, ContainerInitError,
-    ContainerInitRequest, ContainerInitResponse, ContainerInitSuccess, Filesystem, RootfsInit,
+    ContainerInitRequest, ContainerInitResponse, ContainerInitSuccess, Filesystem,
+    ListCoreDumpsRequest, ListCoreDumpsResponse, RootfsInit, UpdateHostsRequest,
+    UpdateHostsResponse,
 };
 use nix::mount::{mount, MsFlags};
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info};
 
-use crate::container::{Container, UserMount};
+use crate::container::{Container, UserMount, UserNsConfig, ROOT_REQUIRED_HINT};
+use crate::core_dump;
 use crate::layout::GuestLayout;
 use crate::storage::block_device::BlockDeviceMount;
 
@@ -171,6 +176,16 @@ impl ContainerService for GuestServer {
             }));
         }
 
+        // Swap goes on the container disk, so only now that it is mounted
+        if let Err(e) = crate::swap::configure(&shared_rootfs, init_req.swap_mib) {
+            error!("{}", e);
+            return Ok(Response::new(ContainerInitResponse {
+                result: Some(container_init_response::Result::Error(ContainerInitError {
+                    reason: e.to_string(),
+                })),
+            }));
+        }
+
         // Bind mount shared rootfs to bundle rootfs
         if let Err(e) = mount(
             Some(shared_rootfs.as_path()),
@@ -187,6 +202,8 @@ impl ContainerService for GuestServer {
             }));
         }
 
+        eprintln!("{}", BootPhase::RootfsMounted.marker_line());
+
         // Convert proto BindMount to UserMount for OCI spec
         // Construct full source path from convention: /run/boxlite/shared/containers/{id}/volumes/{name}
         let guest_layout = boxlite_shared::layout::SharedGuestLayout::new("/run/boxlite/shared");
@@ -205,6 +222,14 @@ impl ContainerService for GuestServer {
             })
             .collect();
 
+        let userns = init_req.userns.map(UserNsConfig::from_proto);
+        // Appended to start failures: userns is a common cause for root images
+        let userns_hint = if userns.is_some() {
+            format!(" {}", ROOT_REQUIRED_HINT)
+        } else {
+            String::new()
+        };
+
         debug!(
             entrypoint = ?config.entrypoint,
             workdir = %config.workdir,
@@ -213,6 +238,15 @@ impl ContainerService for GuestServer {
             bundle_rootfs = %bundle_rootfs.display(),
             container_id = %container_id,
             user_mounts_count = user_mounts.len(),
+            userns = ?userns,
+            init = init_req.init,
+            entrypoint_script = init_req.entrypoint_script.is_some(),
+            capture_core_dumps = init_req.capture_core_dumps,
+            swap_mib = init_req.swap_mib,
+            time_zone = ?init_req.time_zone.as_ref().map(|tz| &tz.name),
+            read_only_rootfs = init_req.read_only_rootfs,
+            writable_paths = ?init_req.writable_paths,
+            extra_hosts = init_req.extra_hosts.len(),
             "Container configuration"
         );
 
@@ -232,8 +266,17 @@ impl ContainerService for GuestServer {
             &config.workdir,
             &config.user,
             user_mounts,
+            userns,
+            init_req.init,
+            init_req.entrypoint_script.as_deref(),
+            init_req.capture_core_dumps,
+            init_req.time_zone.as_ref(),
+            init_req.read_only_rootfs,
+            &init_req.writable_paths,
+            &init_req.extra_hosts,
         ) {
             Ok(mut container) => {
+                eprintln!("{}", BootPhase::ContainerSpawned.marker_line());
                 debug!(container_id = %container_id, "Container started, checking if init process is running");
                 // Verify container init process is running
                 if !container.is_running() {
@@ -248,8 +291,8 @@ impl ContainerService for GuestServer {
                     return Ok(Response::new(ContainerInitResponse {
                         result: Some(container_init_response::Result::Error(ContainerInitError {
                             reason: format!(
-                                "Container init process exited immediately. {}",
-                                diagnostics
+                                "Container init process exited immediately. {}{}",
+                                diagnostics, userns_hint
                             ),
                         })),
                     }));
@@ -276,10 +319,60 @@ impl ContainerService for GuestServer {
                 error!("Failed to start container: {}", e);
                 Ok(Response::new(ContainerInitResponse {
                     result: Some(container_init_response::Result::Error(ContainerInitError {
-                        reason: format!("Failed to start container: {}", e),
+                        reason: format!("Failed to start container: {}{}", e, userns_hint),
                     })),
                 }))
             }
         }
     }
+
+    async fn list_core_dumps(
+        &self,
+        request: Request<ListCoreDumpsRequest>,
+    ) -> Result<Response<ListCoreDumpsResponse>, Status> {
+        let container_id = request.into_inner().container_id;
+        if container_id.is_empty() {
+            return Err(Status::invalid_argument("container_id is required"));
+        }
+
+        let rootfs = self.layout.shared().container(&container_id).rootfs_dir();
+        let dir = core_dump::dir_in(&rootfs);
+        let cores = tokio::task::spawn_blocking(move || core_dump::list(&dir))
+            .await
+            .map_err(|e| Status::internal(format!("core dump listing task failed: {}", e)))?
+            .map_err(|e| Status::internal(format!("Failed to list core dumps: {}", e)))?;
+
+        Ok(Response::new(ListCoreDumpsResponse {
+            dumps: cores.into_iter().map(Into::into).collect(),
+        }))
+    }
+
+    async fn update_hosts(
+        &self,
+        request: Request<UpdateHostsRequest>,
+    ) -> Result<Response<UpdateHostsResponse>, Status> {
+        let req = request.into_inner();
+        let container = self
+            .containers
+            .lock()
+            .await
+            .get(&req.container_id)
+            .cloned()
+            .ok_or_else(|| {
+                Status::not_found(format!("container {} not found", req.container_id))
+            })?;
+
+        container
+            .lock()
+            .await
+            .update_hosts(&req.entries)
+            .map_err(|e| Status::internal(format!("Failed to update hosts: {}", e)))?;
+
+        debug!(
+            container_id = %req.container_id,
+            entries = req.entries.len(),
+            "Updated hosts file"
+        );
+        Ok(Response::new(UpdateHostsResponse {}))
+    }
 }
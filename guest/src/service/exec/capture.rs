@@ -0,0 +1,151 @@
+//! Output capture for detached executions.
+//!
+//! When `ExecRequest.capture` is set, stdout/stderr are drained from spawn
+//! time into `execs/{exec_id}/{stdout,stderr}`, a virtio-fs share of the
+//! host's `{box_home}/execs`. The output therefore outlives the client that
+//! started the execution. An attached client still gets the live stream,
+//! best-effort: chunks nobody reads in time are dropped from the stream,
+//! never from the files.
+
+use std::path::{Component, Path, PathBuf};
+
+use boxlite_shared::{exec_output, ExecOutput};
+use futures::{Stream, StreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tonic::Status;
+use tracing::warn;
+
+/// Capture directory of one execution: `{execs_dir}/{exec_id}`.
+///
+/// Rejects IDs that are not a single plain path component.
+pub(super) fn capture_dir(execs_dir: &Path, exec_id: &str) -> Result<PathBuf, String> {
+    let mut components = Path::new(exec_id).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(_)), None) => Ok(execs_dir.join(exec_id)),
+        _ => Err(format!("invalid execution id for capture: {:?}", exec_id)),
+    }
+}
+
+/// Capture file for one output stream, capped at `max_bytes`.
+pub(super) struct CaptureFile {
+    path: PathBuf,
+    /// None once writing failed; the stream is still drained.
+    file: Option<tokio::fs::File>,
+    remaining: u64,
+}
+
+impl CaptureFile {
+    pub(super) async fn create(path: PathBuf, max_bytes: u64) -> std::io::Result<Self> {
+        let file = tokio::fs::File::create(&path).await?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            remaining: max_bytes,
+        })
+    }
+
+    /// Append as much of `chunk` as the cap allows.
+    async fn write(&mut self, chunk: &[u8]) {
+        let len = chunk.len().min(self.remaining as usize);
+        if len == 0 {
+            return;
+        }
+        let Some(file) = self.file.as_mut() else {
+            return;
+        };
+
+        match file.write_all(&chunk[..len]).await {
+            Ok(()) => self.remaining -= len as u64,
+            Err(e) => {
+                warn!(path = %self.path.display(), error = %e, "Output capture failed");
+                self.file = None;
+            }
+        }
+    }
+
+    async fn finish(mut self) {
+        if let Some(mut file) = self.file.take() {
+            if let Err(e) = file.flush().await {
+                warn!(path = %self.path.display(), error = %e, "Output capture failed");
+            }
+        }
+    }
+}
+
+/// Drain `stream` into `file`, forwarding chunks to `tx` while there is room.
+pub(super) async fn tee<S>(
+    mut stream: S,
+    mut file: CaptureFile,
+    tx: mpsc::Sender<Result<ExecOutput, Status>>,
+    event: fn(Vec<u8>) -> exec_output::Event,
+) where
+    S: Stream<Item = Vec<u8>> + Unpin,
+{
+    while let Some(chunk) = stream.next().await {
+        file.write(&chunk).await;
+        // Full (nobody reading yet) or closed (client gone): the file has it
+        let _ = tx.try_send(Ok(ExecOutput {
+            event: Some(event(chunk)),
+        }));
+    }
+    file.finish().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boxlite_shared::Stdout;
+
+    #[test]
+    fn test_capture_dir_rejects_paths() {
+        let execs = Path::new("/run/boxlite/execs");
+        assert_eq!(
+            capture_dir(execs, "abc-123").unwrap(),
+            PathBuf::from("/run/boxlite/execs/abc-123")
+        );
+        for bad in ["", "..", ".", "a/b", "/abs", "../escape"] {
+            assert!(capture_dir(execs, bad).is_err(), "accepted {:?}", bad);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tee_caps_file_and_forwards() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stdout");
+        let file = CaptureFile::create(path.clone(), 5).await.unwrap();
+        let (tx, mut rx) = mpsc::channel(8);
+        let chunks = futures::stream::iter(vec![b"abc".to_vec(), b"defg".to_vec()]);
+
+        tee(chunks, file, tx, |data| {
+            exec_output::Event::Stdout(Stdout { data })
+        })
+        .await;
+
+        assert_eq!(std::fs::read(&path).unwrap(), b"abcde");
+        let mut forwarded = Vec::new();
+        while let Some(Ok(msg)) = rx.recv().await {
+            if let Some(exec_output::Event::Stdout(out)) = msg.event {
+                forwarded.extend(out.data);
+            }
+        }
+        assert_eq!(forwarded, b"abcdefg");
+    }
+
+    #[tokio::test]
+    async fn test_tee_keeps_writing_without_reader() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stderr");
+        let file = CaptureFile::create(path.clone(), 1024).await.unwrap();
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+        let chunks = futures::stream::iter(vec![b"one\n".to_vec(), b"two\n".to_vec()]);
+
+        tee(chunks, file, tx, |data| {
+            exec_output::Event::Stdout(Stdout { data })
+        })
+        .await;
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "one\ntwo\n");
+    }
+}
@@ -0,0 +1,153 @@
+//! Env edits of an exec (`ExecRequest.env_edits`).
+//!
+//! Edits apply after the base env (the container's start env or the init
+//! process's live one) and `ExecRequest.env` have been merged, in request
+//! order, so a prepend sees the value the process would otherwise get.
+
+use boxlite_shared::env_edit::Edit;
+use boxlite_shared::EnvEdit;
+use std::collections::HashMap;
+
+/// Apply `edits` to `env` in order.
+///
+/// Prepending or appending to an unset or empty variable sets it to the
+/// value alone, without a separator. Edits without an operation (sent by
+/// a newer host) are ignored.
+pub(crate) fn apply(env: &mut HashMap<String, String>, edits: &[EnvEdit]) {
+    for edit in edits {
+        match &edit.edit {
+            Some(Edit::Prepend(path)) => {
+                let merged = match env.get(&edit.key).filter(|v| !v.is_empty()) {
+                    Some(current) => format!("{}{}{}", path.value, path.separator, current),
+                    None => path.value.clone(),
+                };
+                env.insert(edit.key.clone(), merged);
+            }
+            Some(Edit::Append(path)) => {
+                let merged = match env.get(&edit.key).filter(|v| !v.is_empty()) {
+                    Some(current) => format!("{}{}{}", current, path.separator, path.value),
+                    None => path.value.clone(),
+                };
+                env.insert(edit.key.clone(), merged);
+            }
+            Some(Edit::Unset(_)) => {
+                env.remove(&edit.key);
+            }
+            None => {}
+        }
+    }
+}
+
+/// Env of a process the guest executor spawns directly: the agent's own
+/// env under the request's.
+pub(crate) fn guest_env(
+    env: &HashMap<String, String>,
+    edits: &[EnvEdit],
+) -> HashMap<String, String> {
+    let mut merged: HashMap<String, String> = std::env::vars().collect();
+    merged.extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+    apply(&mut merged, edits);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use boxlite_shared::PathEdit;
+
+    fn prepend(key: &str, value: &str) -> EnvEdit {
+        EnvEdit {
+            key: key.to_string(),
+            edit: Some(Edit::Prepend(PathEdit {
+                value: value.to_string(),
+                separator: ":".to_string(),
+            })),
+        }
+    }
+
+    fn append(key: &str, value: &str) -> EnvEdit {
+        EnvEdit {
+            key: key.to_string(),
+            edit: Some(Edit::Append(PathEdit {
+                value: value.to_string(),
+                separator: ":".to_string(),
+            })),
+        }
+    }
+
+    fn unset(key: &str) -> EnvEdit {
+        EnvEdit {
+            key: key.to_string(),
+            edit: Some(Edit::Unset(true)),
+        }
+    }
+
+    fn base() -> HashMap<String, String> {
+        HashMap::from([
+            ("PATH".to_string(), "/usr/bin:/bin".to_string()),
+            ("HOME".to_string(), "/root".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ])
+    }
+
+    #[test]
+    fn test_prepend_and_append_merge_with_base() {
+        let mut env = base();
+        apply(
+            &mut env,
+            &[prepend("PATH", "/custom/bin"), append("PATH", "/opt/bin")],
+        );
+        assert_eq!(env["PATH"], "/custom/bin:/usr/bin:/bin:/opt/bin");
+        assert_eq!(env["HOME"], "/root");
+    }
+
+    #[test]
+    fn test_merge_into_missing_or_empty_sets_value() {
+        let mut env = base();
+        apply(
+            &mut env,
+            &[prepend("LD_LIBRARY_PATH", "/lib64"), append("EMPTY", "/x")],
+        );
+        assert_eq!(env["LD_LIBRARY_PATH"], "/lib64");
+        assert_eq!(env["EMPTY"], "/x");
+    }
+
+    #[test]
+    fn test_edits_apply_in_order() {
+        let mut env = base();
+        apply(&mut env, &[unset("PATH"), prepend("PATH", "/a")]);
+        assert_eq!(env["PATH"], "/a");
+
+        let mut env = base();
+        apply(&mut env, &[prepend("PATH", "/a"), unset("PATH")]);
+        assert!(!env.contains_key("PATH"));
+    }
+
+    #[test]
+    fn test_custom_separator() {
+        let mut env = HashMap::from([("FLAGS".to_string(), "-O2".to_string())]);
+        let edit = EnvEdit {
+            key: "FLAGS".to_string(),
+            edit: Some(Edit::Append(PathEdit {
+                value: "-g".to_string(),
+                separator: " ".to_string(),
+            })),
+        };
+        apply(&mut env, &[edit]);
+        assert_eq!(env["FLAGS"], "-O2 -g");
+    }
+
+    #[test]
+    fn test_guest_env_layers_request_over_agent_env() {
+        let request = HashMap::from([("HOME".to_string(), "/work".to_string())]);
+        let env = guest_env(&request, &[prepend("PATH", "/custom/bin")]);
+        assert_eq!(env["HOME"], "/work");
+        let agent_path = std::env::var("PATH").unwrap_or_default();
+        let expected = if agent_path.is_empty() {
+            "/custom/bin".to_string()
+        } else {
+            format!("/custom/bin:{}", agent_path)
+        };
+        assert_eq!(env["PATH"], expected);
+    }
+}
@@ -33,12 +33,19 @@ impl ExecStdin {
     ///
     /// # Errors
     ///
-    /// - I/O error (pipe closed, etc.)
-    pub async fn write_all(&mut self, data: &[u8]) -> BoxliteResult<()> {
-        self.inner
-            .write_all(data)
-            .await
-            .map_err(|e| BoxliteError::Internal(format!("Failed to write to stdin: {}", e)))
+    /// - I/O error; `BrokenPipe` once the process has closed its stdin
+    pub async fn write_all(&mut self, data: &[u8]) -> std::io::Result<()> {
+        self.inner.write_all(data).await
+    }
+
+    /// Close stdin, so the process reads EOF.
+    ///
+    /// Waits for pending writes first: the file writes in the background,
+    /// and dropping it mid-write would close the fd only afterwards.
+    pub async fn close(mut self) {
+        if let Err(e) = self.inner.flush().await {
+            tracing::debug!("stdin flush before close failed: {}", e);
+        }
     }
 }
 
@@ -5,10 +5,13 @@
 //! - GuestExecutor: runs commands directly on guest
 
 use crate::container::Container;
+use crate::service::exec::env;
 use crate::service::exec::exec_handle::{ExecHandle, PtyConfig};
 use async_trait::async_trait;
 use boxlite_shared::errors::{BoxliteError, BoxliteResult};
 use boxlite_shared::ExecRequest;
+use nix::fcntl::OFlag;
+use nix::unistd::pipe2;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
@@ -22,11 +25,23 @@ pub trait Executor: Send + Sync {
 /// Executes commands inside OCI container.
 pub struct ContainerExecutor {
     container: Arc<Mutex<Container>>,
+    /// (uid, gid) resolved ahead of time (prepared templates); None uses
+    /// the container's current user.
+    user: Option<(u32, u32)>,
 }
 
 impl ContainerExecutor {
     pub fn new(container: Arc<Mutex<Container>>) -> Self {
-        Self { container }
+        Self {
+            container,
+            user: None,
+        }
+    }
+
+    /// Run as an already resolved (uid, gid).
+    pub fn with_user(mut self, user: Option<(u32, u32)>) -> Self {
+        self.user = user;
+        self
     }
 
     /// Get a clone of the container reference for status checking.
@@ -42,16 +57,30 @@ impl Executor for ContainerExecutor {
         let cmd = {
             let container = self.container.lock().await;
 
-            let mut cmd = container
-                .cmd()
+            // The init process's live env and cwd, under the request's own
+            let base = if req.inherit_runtime_env {
+                container.cmd_from_init()?
+            } else {
+                container.cmd()
+            };
+            let mut cmd = base
                 .program(&req.program)
                 .args(&req.args)
-                .envs(req.env.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+                .envs(req.env.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+                .env_edits(&req.env_edits);
+
+            if let Some((uid, gid)) = self.user {
+                cmd = cmd.user(uid, gid);
+            }
 
             if !req.workdir.is_empty() {
                 cmd = cmd.current_dir(&req.workdir);
             }
 
+            if let Some(scratch) = &req.scratch {
+                cmd = cmd.scratch_dir(scratch.size_mib, &scratch.mount_at);
+            }
+
             if let Some(tty) = &req.tty {
                 cmd = cmd.with_pty(PtyConfig {
                     rows: tty.rows as u16,
@@ -74,6 +103,11 @@ pub struct GuestExecutor;
 #[async_trait]
 impl Executor for GuestExecutor {
     async fn spawn(&self, req: &ExecRequest) -> BoxliteResult<ExecHandle> {
+        if req.scratch.is_some() {
+            return Err(BoxliteError::Unsupported(
+                "scratch directories are only available in containers".to_string(),
+            ));
+        }
         if let Some(tty) = &req.tty {
             let config = PtyConfig {
                 rows: tty.rows as u16,
@@ -97,20 +131,21 @@ fn spawn_with_pipes(req: &ExecRequest) -> BoxliteResult<ExecHandle> {
     let mut cmd = Command::new(&req.program);
     cmd.args(&req.args);
 
-    for (k, v) in &req.env {
-        cmd.env(k, v);
-    }
+    cmd.env_clear()
+        .envs(env::guest_env(&req.env, &req.env_edits));
 
     if !req.workdir.is_empty() {
         cmd.current_dir(&req.workdir);
     }
 
-    // Create pipes for stdin/stdout/stderr
-    let (stdin_read, stdin_write) = nix::unistd::pipe()
+    // Create pipes for stdin/stdout/stderr. Close-on-exec keeps our ends out
+    // of this and every other child: a process holding the write end of its
+    // own stdin never reads EOF.
+    let (stdin_read, stdin_write) = pipe2(OFlag::O_CLOEXEC)
         .map_err(|e| BoxliteError::Internal(format!("Failed to create stdin pipe: {}", e)))?;
-    let (stdout_read, stdout_write) = nix::unistd::pipe()
+    let (stdout_read, stdout_write) = pipe2(OFlag::O_CLOEXEC)
         .map_err(|e| BoxliteError::Internal(format!("Failed to create stdout pipe: {}", e)))?;
-    let (stderr_read, stderr_write) = nix::unistd::pipe()
+    let (stderr_read, stderr_write) = pipe2(OFlag::O_CLOEXEC)
         .map_err(|e| BoxliteError::Internal(format!("Failed to create stderr pipe: {}", e)))?;
 
     // Configure command to use our pipes.
@@ -170,9 +205,8 @@ fn spawn_with_pty(req: &ExecRequest, config: PtyConfig) -> BoxliteResult<ExecHan
     let mut cmd = Command::new(&req.program);
     cmd.args(&req.args);
 
-    for (k, v) in &req.env {
-        cmd.env(k, v);
-    }
+    cmd.env_clear()
+        .envs(env::guest_env(&req.env, &req.env_edits));
 
     if !req.workdir.is_empty() {
         cmd.current_dir(&req.workdir);
@@ -12,15 +12,21 @@
 //! - **Executor Layer** (executor.rs): Process spawning abstraction
 //! - **Lifecycle Layer** (timeout.rs): Process management
 //! - **State Layer** (registry.rs, state.rs): Execution state
+//! - **Capture Layer** (capture.rs): Output files of detached executions
+//! - **Env Layer** (env.rs): Env edits merged with the current values
+//! - **Prepared Layer** (prepared.rs): Registered command templates
 //! - **Types** (types.rs): Shared types
 //!
 //! Each file has a single, clear responsibility.
 
+mod capture;
+pub(crate) mod env;
 #[cfg(target_os = "linux")]
 pub mod exec_handle;
 pub(in crate::service) mod executor;
+pub(in crate::service) mod prepared;
 pub(in crate::service) mod registry;
-mod state;
+pub(in crate::service) mod state;
 mod timeout;
 
 // Re-export trait so container module can implement it
@@ -29,9 +35,11 @@ pub(crate) use state::InitHealthCheck;
 use crate::service::exec::executor::{ContainerExecutor, GuestExecutor};
 use crate::service::server::GuestServer;
 use boxlite_shared::{
-    constants::executor as executor_const, AttachRequest, ExecError, ExecOutput, ExecRequest,
-    ExecResponse, ExecStdin, Execution, KillRequest, KillResponse, ResizeTtyRequest,
-    ResizeTtyResponse, SendInputAck, WaitRequest, WaitResponse,
+    constants::executor as executor_const, constants::prepared as prepared_const, AttachRequest,
+    ExecError, ExecOutput, ExecPreparedRequest, ExecRequest, ExecResponse, ExecStdin, Execution,
+    KillRequest, KillResponse, PrepareRequest, PrepareResponse, ResizeTtyRequest,
+    ResizeTtyResponse, SendInputAck, UnprepareRequest, UnprepareResponse, WaitRequest,
+    WaitResponse,
 };
 use futures::stream::Stream;
 use std::pin::Pin;
@@ -64,6 +72,7 @@ impl Execution for GuestServer {
         }
 
         // Spawn execution
+        self.activity.touch();
         match spawn_execution(self, execution_id, req).await {
             Ok(resp) => Ok(Response::new(resp)),
             Err(err_resp) => Ok(Response::new(err_resp)),
@@ -143,7 +152,9 @@ impl Execution for GuestServer {
             .ok_or_else(|| Status::not_found(format!("Execution not found: {}", exec_id)))?;
 
         // Wait for process to exit
-        let exit_status = state.wait_process().await?;
+        let exit_status = state.wait_process().await;
+        self.activity.touch();
+        let exit_status = exit_status?;
 
         let (exit_code, signal, error_message) = match exit_status {
             ExitStatus::Code(code) => {
@@ -288,18 +299,176 @@ impl Execution for GuestServer {
             }
         }
     }
+
+    async fn prepare(
+        &self,
+        request: Request<PrepareRequest>,
+    ) -> Result<Response<PrepareResponse>, Status> {
+        let mut template = request
+            .into_inner()
+            .template
+            .ok_or_else(|| Status::invalid_argument("template is required"))?;
+
+        info!(program = %template.program, "prepare request");
+
+        if template.program.is_empty() {
+            return Ok(Response::new(prepare_error(
+                "invalid_template",
+                "program is required",
+            )));
+        }
+        template.execution_id = None;
+
+        let user = match resolve_template(self, &mut template).await {
+            Ok(user) => user,
+            Err(detail) => {
+                return Ok(Response::new(prepare_error("invalid_template", &detail)));
+            }
+        };
+
+        let Some(prepared_id) = self
+            .prepared
+            .register(prepared::PreparedTemplate {
+                request: template,
+                user,
+            })
+            .await
+        else {
+            return Ok(Response::new(prepare_error(
+                prepared_const::LIMIT_REASON,
+                &format!(
+                    "Too many prepared commands (limit {})",
+                    prepared_const::MAX_TEMPLATES
+                ),
+            )));
+        };
+        debug!(prepared_id = %prepared_id, "command prepared");
+
+        Ok(Response::new(PrepareResponse {
+            prepared_id,
+            error: None,
+        }))
+    }
+
+    async fn exec_prepared(
+        &self,
+        request: Request<ExecPreparedRequest>,
+    ) -> Result<Response<ExecResponse>, Status> {
+        let req = request.into_inner();
+        let execution_id = req
+            .execution_id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+
+        debug!(
+            execution_id = %execution_id,
+            prepared_id = %req.prepared_id,
+            "exec_prepared request"
+        );
+
+        let Some(template) = self.prepared.get(&req.prepared_id).await else {
+            return Ok(Response::new(error_response(
+                execution_id,
+                prepared_const::NOT_FOUND_REASON,
+                &format!("Prepared command not found: {}", req.prepared_id),
+            )));
+        };
+
+        if self.registry.exists(&execution_id).await {
+            return Ok(Response::new(error_response(
+                execution_id,
+                "execution_exists",
+                "Execution already exists",
+            )));
+        }
+
+        let exec_req = prepared::build_request(&template.request, req);
+        self.activity.touch();
+        match spawn_execution_as(self, execution_id, exec_req, template.user).await {
+            Ok(resp) => Ok(Response::new(resp)),
+            Err(err_resp) => Ok(Response::new(err_resp)),
+        }
+    }
+
+    async fn unprepare(
+        &self,
+        request: Request<UnprepareRequest>,
+    ) -> Result<Response<UnprepareResponse>, Status> {
+        let prepared_id = request.into_inner().prepared_id;
+        let removed = self.prepared.remove(&prepared_id).await;
+        debug!(prepared_id = %prepared_id, removed, "unprepare request");
+
+        Ok(Response::new(UnprepareResponse { removed }))
+    }
+}
+
+/// Resolve a template once so invocations skip the work: merge the
+/// container env into the template env, validate the working directory and
+/// resolve the container user.
+///
+/// Returns the (uid, gid) container templates run as; None for the guest
+/// executor.
+async fn resolve_template(
+    server: &GuestServer,
+    template: &mut ExecRequest,
+) -> Result<Option<(u32, u32)>, String> {
+    let executor_value = template.env.get(executor_const::ENV_VAR).cloned();
+    let container_id = executor_value
+        .as_deref()
+        .and_then(|s| s.strip_prefix(executor_const::CONTAINER_KEY))
+        .and_then(|rest| rest.strip_prefix('='));
+
+    let Some(container_id) = container_id else {
+        // Guest executor: env is inherited as-is, workdir is a guest path
+        if !template.workdir.is_empty() && !std::path::Path::new(&template.workdir).is_dir() {
+            return Err(format!("Working directory not found: {}", template.workdir));
+        }
+        return Ok(None);
+    };
+
+    let container_arc = server
+        .containers
+        .lock()
+        .await
+        .get(container_id)
+        .cloned()
+        .ok_or_else(|| format!("Container not found: {}", container_id))?;
+    let container = container_arc.lock().await;
+
+    if !template.workdir.is_empty() && !container.has_dir(&template.workdir) {
+        return Err(format!("Working directory not found: {}", template.workdir));
+    }
+
+    // Inheriting templates read the init process's env at each invocation
+    if !template.inherit_runtime_env {
+        let mut env = container.env().clone();
+        env.extend(template.env.drain());
+        template.env = env;
+    }
+    Ok(Some(container.user()))
 }
 
 /// Spawn execution (orchestrates full lifecycle).
-async fn spawn_execution(
+pub(in crate::service) async fn spawn_execution(
     server: &GuestServer,
     execution_id: String,
     req: ExecRequest,
+) -> Result<ExecResponse, ExecResponse> {
+    spawn_execution_as(server, execution_id, req, None).await
+}
+
+/// Spawn execution as an already resolved container user (None: the
+/// container's default user).
+async fn spawn_execution_as(
+    server: &GuestServer,
+    execution_id: String,
+    req: ExecRequest,
+    user: Option<(u32, u32)>,
 ) -> Result<ExecResponse, ExecResponse> {
     let started_at_ms = now_ms();
 
     // Step 1: Spawn process using executor selected by BOXLITE_EXECUTOR env var
-    let (child, container_ref) = spawn_with_executor(server, &req, &execution_id).await?;
+    let (child, container_ref) = spawn_with_executor(server, &req, &execution_id, user).await?;
 
     let pid = child.pid().as_raw() as u32;
 
@@ -312,6 +481,21 @@ async fn spawn_execution(
         }
         None => state::ExecutionState::new(child),
     };
+    // Captured executions drain their output from the start, so it reaches
+    // the files even if no client ever attaches
+    if let Some(capture) = &req.capture {
+        let started = match capture::capture_dir(&server.layout.execs_dir(), &execution_id) {
+            Ok(dir) => state
+                .start_capture(&dir, capture.max_bytes)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = started {
+            warn!(execution_id = %execution_id, error = %e, "Output capture not started");
+        }
+    }
+
     server
         .registry
         .register(execution_id.clone(), state.clone())
@@ -346,6 +530,16 @@ fn error_response(id: String, reason: &str, detail: &str) -> ExecResponse {
     }
 }
 
+fn prepare_error(reason: &str, detail: &str) -> PrepareResponse {
+    PrepareResponse {
+        prepared_id: String::new(),
+        error: Some(ExecError {
+            reason: reason.to_string(),
+            detail: detail.to_string(),
+        }),
+    }
+}
+
 fn spawn_error(exec_id: &str, err: String) -> ExecResponse {
     ExecResponse {
         execution_id: exec_id.to_string(),
@@ -379,6 +573,7 @@ async fn spawn_with_executor(
     server: &GuestServer,
     req: &ExecRequest,
     execution_id: &str,
+    user: Option<(u32, u32)>,
 ) -> Result<
     (
         exec_handle::ExecHandle,
@@ -427,7 +622,7 @@ async fn spawn_with_executor(
                     )
                 })?
             };
-            let executor = ContainerExecutor::new(container_arc);
+            let executor = ContainerExecutor::new(container_arc).with_user(user);
             let container_ref = executor.container_ref();
             let handle = match executor.spawn(req).await {
                 Ok(h) => h,
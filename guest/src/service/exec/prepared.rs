@@ -0,0 +1,169 @@
+//! Prepared command registry.
+//!
+//! Stores command templates registered via `Execution.Prepare` so repeated
+//! executions only carry the per-invocation delta (extra args). Templates
+//! are held in memory until `Execution.Unprepare` drops them or the guest
+//! agent restarts; clients detect the latter through the
+//! `prepared_not_found` error and re-register. At most
+//! [`MAX_TEMPLATES`](prepared_const::MAX_TEMPLATES) are held at once.
+
+use boxlite_shared::constants::prepared as prepared_const;
+use boxlite_shared::{ExecPreparedRequest, ExecRequest};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+/// A registered template, resolved once at prepare time.
+pub(crate) struct PreparedTemplate {
+    pub request: ExecRequest,
+    /// Container user (uid, gid) the template runs as; None for the guest
+    /// executor.
+    pub user: Option<(u32, u32)>,
+}
+
+/// Registry of prepared command templates.
+#[derive(Clone)]
+pub(crate) struct PreparedRegistry {
+    templates: Arc<Mutex<HashMap<String, Arc<PreparedTemplate>>>>,
+    capacity: usize,
+}
+
+impl PreparedRegistry {
+    /// Create empty registry.
+    pub fn new() -> Self {
+        Self::with_capacity(prepared_const::MAX_TEMPLATES)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            templates: Arc::new(Mutex::new(HashMap::new())),
+            capacity,
+        }
+    }
+
+    /// Store a template and return its ID, or None when the registry is full.
+    pub async fn register(&self, template: PreparedTemplate) -> Option<String> {
+        let mut templates = self.templates.lock().await;
+        if templates.len() >= self.capacity {
+            return None;
+        }
+        let prepared_id = uuid::Uuid::new_v4().to_string();
+        templates.insert(prepared_id.clone(), Arc::new(template));
+        Some(prepared_id)
+    }
+
+    /// Get a template by ID.
+    pub async fn get(&self, prepared_id: &str) -> Option<Arc<PreparedTemplate>> {
+        self.templates.lock().await.get(prepared_id).cloned()
+    }
+
+    /// Drop a template; returns whether it was registered.
+    pub async fn remove(&self, prepared_id: &str) -> bool {
+        self.templates.lock().await.remove(prepared_id).is_some()
+    }
+}
+
+/// Build the full exec request for one invocation of a template.
+///
+/// Extra args are appended to the template's args; everything else
+/// (program, env and env edits, workdir, timeout, tty, env inheritance,
+/// scratch directory) comes from the template.
+pub(crate) fn build_request(template: &ExecRequest, req: ExecPreparedRequest) -> ExecRequest {
+    let mut args = template.args.clone();
+    args.extend(req.args);
+
+    ExecRequest {
+        execution_id: req.execution_id,
+        program: template.program.clone(),
+        args,
+        env: template.env.clone(),
+        workdir: template.workdir.clone(),
+        timeout_ms: template.timeout_ms,
+        tty: template.tty.clone(),
+        capture: template.capture,
+        inherit_runtime_env: template.inherit_runtime_env,
+        scratch: template.scratch.clone(),
+        env_edits: template.env_edits.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template() -> ExecRequest {
+        ExecRequest {
+            execution_id: None,
+            program: "python".to_string(),
+            args: vec!["worker.py".to_string()],
+            env: HashMap::from([("MODE".to_string(), "batch".to_string())]),
+            workdir: "/app".to_string(),
+            timeout_ms: 500,
+            tty: None,
+            capture: None,
+            inherit_runtime_env: false,
+            scratch: None,
+            env_edits: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_build_request_appends_args() {
+        let req = build_request(
+            &template(),
+            ExecPreparedRequest {
+                prepared_id: "p1".to_string(),
+                execution_id: Some("e1".to_string()),
+                args: vec!["--job".to_string(), "42".to_string()],
+            },
+        );
+
+        assert_eq!(req.program, "python");
+        assert_eq!(req.args, vec!["worker.py", "--job", "42"]);
+        assert_eq!(req.env["MODE"], "batch");
+        assert_eq!(req.workdir, "/app");
+        assert_eq!(req.timeout_ms, 500);
+        assert_eq!(req.execution_id.as_deref(), Some("e1"));
+    }
+
+    fn prepared() -> PreparedTemplate {
+        PreparedTemplate {
+            request: template(),
+            user: Some((1000, 1000)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_register_and_get() {
+        let registry = PreparedRegistry::new();
+        let id = registry.register(prepared()).await.unwrap();
+
+        let stored = registry.get(&id).await.unwrap();
+        assert_eq!(stored.request.program, "python");
+        assert_eq!(stored.user, Some((1000, 1000)));
+        assert!(registry.get("missing").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_registry_remove() {
+        let registry = PreparedRegistry::new();
+        let id = registry.register(prepared()).await.unwrap();
+
+        assert!(registry.remove(&id).await);
+        assert!(registry.get(&id).await.is_none());
+        assert!(!registry.remove(&id).await);
+    }
+
+    #[tokio::test]
+    async fn test_registry_refuses_past_capacity() {
+        let registry = PreparedRegistry::with_capacity(2);
+        let first = registry.register(prepared()).await.unwrap();
+        registry.register(prepared()).await.unwrap();
+
+        assert!(registry.register(prepared()).await.is_none());
+
+        // Removing a template frees its slot
+        registry.remove(&first).await;
+        assert!(registry.register(prepared()).await.is_some());
+    }
+}
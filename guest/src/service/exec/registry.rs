@@ -43,6 +43,24 @@ impl ExecutionRegistry {
         self.executions.lock().await.insert(exec_id, state);
     }
 
+    /// Number of executions whose process is still alive, leaving out the
+    /// ids `skip` matches.
+    pub async fn count_running(&self, skip: impl Fn(&str) -> bool) -> u32 {
+        let executions = self.executions.lock().await;
+        let mut running = 0;
+        for (exec_id, state) in executions.iter() {
+            if skip(exec_id) {
+                continue;
+            }
+            if let Some(pid) = state.get_pid().await {
+                if kill(Pid::from_raw(pid as i32), None).is_ok() {
+                    running += 1;
+                }
+            }
+        }
+        running
+    }
+
     /// Gracefully shutdown all running executions.
     ///
     /// Sends SIGTERM first, waits for exit with timeout, then SIGKILL if needed.
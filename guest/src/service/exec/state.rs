@@ -1,6 +1,8 @@
+use crate::service::exec::capture::{self, CaptureFile};
 use crate::service::exec::exec_handle::ExecHandle;
 use boxlite_shared::ExecOutput;
 use std::os::unix::io::AsRawFd;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 use tokio::task::JoinHandle;
@@ -25,8 +27,10 @@ pub(crate) trait InitHealthCheck: Send + Sync {
 struct Inner {
     /// The process handle (owns pid, pty_controller, stdin, stdout, stderr)
     handle: Option<ExecHandle>,
-    /// Stdout/stderr forwarding tasks (set on attach)
+    /// Stdout/stderr forwarding tasks (set on attach, or at spawn when captured)
     output_tasks: Vec<JoinHandle<()>>,
+    /// Live output of a captured execution, handed out on attach
+    captured_output: Option<mpsc::Receiver<Result<ExecOutput, Status>>>,
     /// Timeout flag
     #[allow(dead_code)] // Will be used for timeout handling
     timed_out: bool,
@@ -50,6 +54,7 @@ impl ExecutionState {
         let inner = Inner {
             handle: Some(handle),
             output_tasks: Vec::new(),
+            captured_output: None,
             timed_out: false,
             init_health: None,
         };
@@ -70,6 +75,7 @@ impl ExecutionState {
         let inner = Inner {
             handle: Some(handle),
             output_tasks: Vec::new(),
+            captured_output: None,
             timed_out: false,
             init_health: Some(init_health),
         };
@@ -123,29 +129,30 @@ impl ExecutionState {
 
         // Spawn forwarding task
         let task = tokio::spawn(async move {
-            // Write first message data
-            if !first.data.is_empty() {
-                stdin
-                    .write_all(&first.data)
-                    .await
-                    .map_err(|e| Status::internal(format!("Stdin write failed: {}", e)))?;
-            }
-            if first.close {
-                return Ok(());
-            }
-
-            // Forward remaining messages
-            while let Some(msg) = stream.message().await? {
+            let mut next = Some(first);
+            while let Some(msg) = next {
                 if !msg.data.is_empty() {
-                    stdin
-                        .write_all(&msg.data)
-                        .await
-                        .map_err(|e| Status::internal(format!("Stdin write failed: {}", e)))?;
+                    match stdin.write_all(&msg.data).await {
+                        Ok(()) => {}
+                        // The process closed its stdin (or exited); the rest
+                        // of the input has nowhere to go
+                        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                            tracing::debug!("process closed stdin, dropping further input");
+                            return Ok(());
+                        }
+                        Err(e) => {
+                            return Err(Status::internal(format!("Stdin write failed: {}", e)));
+                        }
+                    }
                 }
                 if msg.close {
                     break;
                 }
+                next = stream.message().await?;
             }
+
+            // Explicit EOF from the host, or the host went away
+            stdin.close().await;
             Ok(())
         });
 
@@ -198,6 +205,48 @@ impl ExecutionState {
         }
     }
 
+    /// Start teeing stdout/stderr into `dir/{stdout,stderr}`.
+    ///
+    /// Output is drained from now on, whether or not a client attaches;
+    /// a later `attach()` receives the live stream.
+    pub(super) async fn start_capture(&self, dir: &Path, max_bytes: u64) -> std::io::Result<()> {
+        use boxlite_shared::{exec_output, Stderr, Stdout};
+
+        tokio::fs::create_dir_all(dir).await?;
+        let stdout_file = CaptureFile::create(dir.join("stdout"), max_bytes).await?;
+        let stderr_file = CaptureFile::create(dir.join("stderr"), max_bytes).await?;
+
+        let mut inner = self.inner.lock().await;
+        let handle = inner
+            .handle
+            .as_mut()
+            .ok_or_else(|| std::io::Error::other("handle not available"))?;
+        let (stdout, stderr) = (handle.stdout(), handle.stderr());
+
+        let (tx, rx) = mpsc::channel(100);
+        let mut tasks = Vec::new();
+        if let Some(stdout) = stdout {
+            tasks.push(tokio::spawn(capture::tee(
+                stdout,
+                stdout_file,
+                tx.clone(),
+                |data| exec_output::Event::Stdout(Stdout { data }),
+            )));
+        }
+        if let Some(stderr) = stderr {
+            tasks.push(tokio::spawn(capture::tee(
+                stderr,
+                stderr_file,
+                tx,
+                |data| exec_output::Event::Stderr(Stderr { data }),
+            )));
+        }
+
+        inner.output_tasks = tasks;
+        inner.captured_output = Some(rx);
+        Ok(())
+    }
+
     /// Attach to execution output.
     ///
     /// Takes stdout/stderr from handle and starts forwarding tasks.
@@ -215,6 +264,11 @@ impl ExecutionState {
         let (stdout, stderr) = {
             let mut inner = self.inner.lock().await;
 
+            // Captured executions are already being drained
+            if let Some(rx) = inner.captured_output.take() {
+                return Ok(rx);
+            }
+
             if !inner.output_tasks.is_empty() {
                 return Err(Status::already_exists("Already attached"));
             }
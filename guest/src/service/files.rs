@@ -5,10 +5,13 @@
 //! running inside the guest.
 
 use crate::service::server::GuestServer;
+use boxlite_shared::archive::{self, parse_unsafe_archive, ArchiveGuard, EntryAction};
 use boxlite_shared::{
-    files_server::Files, DownloadChunk, DownloadRequest, UploadChunk, UploadResponse,
+    files_server::Files, ArchiveLimits, DownloadChunk, DownloadRequest, UploadChunk,
+    UploadOwnership, UploadResponse,
 };
-use std::path::{Path, PathBuf};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::sync::mpsc;
@@ -18,6 +21,7 @@ use tracing::info;
 
 const CHUNK_SIZE: usize = 1 << 20; // 1 MiB
 const MAX_UPLOAD_BYTES: u64 = 512 * 1024 * 1024; // 512 MiB safety cap
+const STREAMED_CHUNKS_IN_FLIGHT: usize = 4;
 
 #[tonic::async_trait]
 impl Files for GuestServer {
@@ -25,6 +29,7 @@ impl Files for GuestServer {
         &self,
         request: Request<Streaming<UploadChunk>>,
     ) -> Result<Response<UploadResponse>, Status> {
+        let _transfer = self.activity.transfer();
         let mut stream = request.into_inner();
 
         // First chunk must carry dest_path (and optional container_id)
@@ -50,6 +55,21 @@ impl Files for GuestServer {
         // Overwrite / mkdir flags
         let mkdir_parents = first.mkdir_parents;
         let overwrite = first.overwrite;
+        let owner = self.upload_owner(&container_id, &first).await?;
+        let limits: ArchiveLimits = first.limits.map(Into::into).unwrap_or_default();
+
+        if first.streamed {
+            return upload_streamed(
+                first,
+                stream,
+                dest_root,
+                dest_path,
+                container_id,
+                owner,
+                limits,
+            )
+            .await;
+        }
 
         // Temp file to hold tar stream
         let temp_path =
@@ -100,7 +120,7 @@ impl Files for GuestServer {
                 ExtractionMode::FileToFile => {
                     if let Some(parent) = dest.parent() {
                         if mkdir_parents {
-                            std::fs::create_dir_all(parent)
+                            create_dirs(parent, owner)
                                 .map_err(|e| format!("failed to create parent dir: {}", e))?;
                         } else if !parent.exists() {
                             return Err(format!(
@@ -115,41 +135,43 @@ impl Files for GuestServer {
                     let tar_file = std::fs::File::open(&temp_clone)
                         .map_err(|e| format!("open temp: {}", e))?;
                     let mut archive = tar::Archive::new(tar_file);
+                    archive.set_preserve_ownerships(owner == UploadOwner::Preserve);
                     let mut entries = archive
                         .entries()
                         .map_err(|e| format!("read entries: {}", e))?;
                     if let Some(entry) = entries.next() {
                         let mut entry = entry.map_err(|e| format!("read entry: {}", e))?;
-                        entry
-                            .unpack(&dest)
-                            .map_err(|e| format!("unpack file: {}", e))?;
-                    }
-                }
-                ExtractionMode::IntoDirectory => {
-                    if !dest.exists() {
-                        if mkdir_parents {
-                            std::fs::create_dir_all(&dest)
-                                .map_err(|e| format!("failed to create destination: {}", e))?;
-                        } else {
-                            return Err(format!("destination {} does not exist", dest_path_clone));
+                        let action = ArchiveGuard::new(limits)
+                            .check(&entry)
+                            .map_err(|e| e.to_string())?;
+                        if action == EntryAction::Unpack {
+                            entry
+                                .unpack(&dest)
+                                .map_err(|e| format!("unpack file: {}", e))?;
                         }
                     }
-                    if !overwrite && dest.read_dir().ok().and_then(|mut r| r.next()).is_some() {
-                        return Err("destination exists and overwrite=false".into());
+                    if let UploadOwner::Chown(uid, gid) = owner {
+                        std::os::unix::fs::lchown(&dest, Some(uid), Some(gid))
+                            .map_err(|e| format!("chown {}: {}", dest.display(), e))?;
                     }
+                }
+                ExtractionMode::IntoDirectory => {
+                    prepare_dest_dir(&dest, &dest_path_clone, mkdir_parents, overwrite, owner)?;
                     let tar_file = std::fs::File::open(&temp_clone)
                         .map_err(|e| format!("open temp: {}", e))?;
                     let mut archive = tar::Archive::new(tar_file);
-                    archive
-                        .unpack(&dest)
-                        .map_err(|e| format!("extract failed: {}", e))?;
+                    archive.set_preserve_ownerships(owner == UploadOwner::Preserve);
+                    archive::unpack(&mut archive, &dest, limits).map_err(|e| e.to_string())?;
+                    if let UploadOwner::Chown(uid, gid) = owner {
+                        chown_entries(&temp_clone, &dest, uid, gid)?;
+                    }
                 }
             }
             Ok(())
         })
         .await
         .map_err(|e| Status::internal(format!("task join error: {}", e)))?
-        .map_err(Status::internal)?;
+        .map_err(upload_status)?;
 
         let _ = tokio::fs::remove_file(&temp_path).await;
 
@@ -172,6 +194,7 @@ impl Files for GuestServer {
         &self,
         request: Request<DownloadRequest>,
     ) -> Result<Response<Self::DownloadStream>, Status> {
+        let transfer = self.activity.transfer();
         let req = request.into_inner();
         if req.src_path.is_empty() {
             return Err(Status::invalid_argument("src_path is required"));
@@ -229,6 +252,7 @@ impl Files for GuestServer {
         // Stream file contents
         let (tx, rx) = mpsc::channel::<Result<DownloadChunk, Status>>(4);
         tokio::spawn(async move {
+            let _transfer = transfer;
             let mut file = match File::open(&temp_path).await {
                 Ok(f) => f,
                 Err(e) => {
@@ -295,6 +319,39 @@ impl GuestServer {
         Err("container_id required when multiple containers present".into())
     }
 
+    /// Resolve the requested ownership to on-disk IDs for `container_id`.
+    #[allow(clippy::result_large_err)]
+    async fn upload_owner(
+        &self,
+        container_id: &str,
+        chunk: &UploadChunk,
+    ) -> Result<UploadOwner, Status> {
+        let ownership = chunk.ownership();
+        if ownership == UploadOwnership::Preserve {
+            return Ok(UploadOwner::Preserve);
+        }
+
+        let container = self
+            .containers
+            .lock()
+            .await
+            .get(container_id)
+            .cloned()
+            .ok_or_else(|| Status::not_found(format!("container {} not found", container_id)))?;
+        let container = container.lock().await;
+        let (uid, gid) = match ownership {
+            UploadOwnership::Explicit => (chunk.uid, chunk.gid),
+            _ => container.user(),
+        };
+        let (uid, gid) = container.file_owner(uid, gid).ok_or_else(|| {
+            Status::invalid_argument(format!(
+                "owner {}:{} is not mapped by the container's user namespace",
+                uid, gid
+            ))
+        })?;
+        Ok(UploadOwner::Chown(uid, gid))
+    }
+
     #[allow(clippy::result_large_err)]
     fn container_rootfs(&self, container_id: &str, path: &str) -> Result<PathBuf, Status> {
         let guest_layout = self.layout.shared().container(container_id);
@@ -318,6 +375,249 @@ impl GuestServer {
     }
 }
 
+/// Unpack an upload as its chunks arrive, always into a directory.
+///
+/// Nothing is staged, so the upload size cap does not apply: the archive
+/// only ever takes the space of its unpacked entries, within `limits`.
+#[allow(clippy::too_many_arguments)]
+async fn upload_streamed(
+    first: UploadChunk,
+    mut stream: Streaming<UploadChunk>,
+    dest_root: PathBuf,
+    dest_path: String,
+    container_id: String,
+    owner: UploadOwner,
+    limits: ArchiveLimits,
+) -> Result<Response<UploadResponse>, Status> {
+    let (mkdir_parents, overwrite) = (first.mkdir_parents, first.overwrite);
+    let (tx, rx) = mpsc::channel::<Vec<u8>>(STREAMED_CHUNKS_IN_FLIGHT);
+    let dest = dest_root.clone();
+    let unpacker = tokio::task::spawn_blocking(move || -> Result<(), String> {
+        prepare_dest_dir(&dest, &dest_path, mkdir_parents, overwrite, owner)?;
+        unpack_stream(ChunkReader::new(rx), &dest, owner, limits)
+    });
+
+    // A failed send means the unpacker stopped early; its result says why
+    let mut total = first.data.len() as u64;
+    let mut stopped = !first.data.is_empty() && tx.send(first.data).await.is_err();
+    while !stopped {
+        let Some(chunk) = stream.message().await? else {
+            break;
+        };
+        total += chunk.data.len() as u64;
+        stopped = tx.send(chunk.data).await.is_err();
+    }
+    drop(tx);
+
+    unpacker
+        .await
+        .map_err(|e| Status::internal(format!("task join error: {}", e)))?
+        .map_err(upload_status)?;
+
+    info!(
+        dest = %dest_root.display(),
+        bytes = total,
+        container_id = %container_id,
+        "streamed upload completed"
+    );
+
+    Ok(Response::new(UploadResponse {
+        success: true,
+        error: None,
+    }))
+}
+
+/// Blocking reader over the chunks of a streamed upload.
+struct ChunkReader {
+    rx: mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl ChunkReader {
+    fn new(rx: mpsc::Receiver<Vec<u8>>) -> Self {
+        Self {
+            rx,
+            chunk: Vec::new(),
+            pos: 0,
+        }
+    }
+}
+
+impl Read for ChunkReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.rx.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// Create `dest` if allowed, and refuse a non-empty one without `overwrite`.
+fn prepare_dest_dir(
+    dest: &Path,
+    dest_path: &str,
+    mkdir_parents: bool,
+    overwrite: bool,
+    owner: UploadOwner,
+) -> Result<(), String> {
+    if !dest.exists() {
+        if mkdir_parents {
+            create_dirs(dest, owner).map_err(|e| format!("failed to create destination: {}", e))?;
+        } else {
+            return Err(format!("destination {} does not exist", dest_path));
+        }
+    }
+    if !overwrite && dest.read_dir().ok().and_then(|mut r| r.next()).is_some() {
+        return Err("destination exists and overwrite=false".into());
+    }
+    Ok(())
+}
+
+/// Status for a failed upload: an unsafe archive is the caller's fault.
+fn upload_status(message: String) -> Status {
+    if parse_unsafe_archive(&message).is_some() {
+        Status::invalid_argument(message)
+    } else {
+        Status::internal(message)
+    }
+}
+
+/// Unpack the archive read from `reader` into the directory `dest` in one
+/// pass, like `archive::unpack`: directories are finished last so their
+/// permissions do not block the entries inside them.
+fn unpack_stream(
+    reader: impl Read,
+    dest: &Path,
+    owner: UploadOwner,
+    limits: ArchiveLimits,
+) -> Result<(), String> {
+    let dest_canon = dest
+        .canonicalize()
+        .map_err(|e| format!("resolve {}: {}", dest.display(), e))?;
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_ownerships(owner == UploadOwner::Preserve);
+
+    let mut guard = ArchiveGuard::new(limits);
+    let mut dirs = Vec::new();
+    for entry in archive
+        .entries()
+        .map_err(|e| format!("read entries: {}", e))?
+    {
+        let mut entry = entry.map_err(|e| format!("read entry: {}", e))?;
+        if guard.check(&entry).map_err(|e| e.to_string())? == EntryAction::Skip {
+            continue;
+        }
+        if entry.header().entry_type() == tar::EntryType::Directory {
+            dirs.push(entry);
+            continue;
+        }
+        unpack_entry(&mut entry, dest, &dest_canon, owner)?;
+    }
+    for mut dir in dirs {
+        unpack_entry(&mut dir, dest, &dest_canon, owner)?;
+    }
+    Ok(())
+}
+
+fn unpack_entry<R: Read>(
+    entry: &mut tar::Entry<'_, R>,
+    dest: &Path,
+    dest_canon: &Path,
+    owner: UploadOwner,
+) -> Result<(), String> {
+    let path = entry
+        .path()
+        .map_err(|e| format!("entry path: {}", e))?
+        .into_owned();
+    entry
+        .unpack_in(dest)
+        .map_err(|e| format!("extract {}: {}", path.display(), e))?;
+    if let UploadOwner::Chown(uid, gid) = owner {
+        chown_entry(dest_canon, &path, uid, gid)?;
+    }
+    Ok(())
+}
+
+/// Ownership applied to uploaded entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UploadOwner {
+    /// Keep the uid/gid recorded in the archive.
+    Preserve,
+    /// Chown extracted entries (and directories the upload creates) to on-disk IDs.
+    Chown(u32, u32),
+}
+
+/// `create_dir_all`, chowning the directories it creates.
+fn create_dirs(path: &Path, owner: UploadOwner) -> std::io::Result<()> {
+    let created: Vec<&Path> = path.ancestors().take_while(|p| !p.exists()).collect();
+    std::fs::create_dir_all(path)?;
+    if let UploadOwner::Chown(uid, gid) = owner {
+        for dir in created {
+            std::os::unix::fs::lchown(dir, Some(uid), Some(gid))?;
+        }
+    }
+    Ok(())
+}
+
+/// Chown the entries of the archive at `tar_path`, already unpacked into `dest`.
+///
+/// Skips entries `unpack` refuses (`..`), entries naming `dest` itself,
+/// entries whose parent resolves outside `dest` through a symlink, and
+/// entries that were not unpacked (devices).
+fn chown_entries(tar_path: &Path, dest: &Path, uid: u32, gid: u32) -> Result<(), String> {
+    let dest = dest
+        .canonicalize()
+        .map_err(|e| format!("resolve {}: {}", dest.display(), e))?;
+    let tar_file = std::fs::File::open(tar_path).map_err(|e| format!("open temp: {}", e))?;
+    let mut archive = tar::Archive::new(tar_file);
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("read entries: {}", e))?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("read entry: {}", e))?;
+        let path = entry.path().map_err(|e| format!("entry path: {}", e))?;
+        chown_entry(&dest, &path, uid, gid)?;
+    }
+    Ok(())
+}
+
+/// Chown the entry at archive path `path`, unpacked into the canonical `dest`.
+/// Skips the same entries as [`chown_entries`].
+fn chown_entry(dest: &Path, path: &Path, uid: u32, gid: u32) -> Result<(), String> {
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Ok(());
+    }
+    let rel: PathBuf = path
+        .components()
+        .filter(|c| matches!(c, Component::Normal(_)))
+        .collect();
+    if rel.as_os_str().is_empty() {
+        return Ok(());
+    }
+
+    let target = dest.join(&rel);
+    let inside = target
+        .parent()
+        .and_then(|parent| parent.canonicalize().ok())
+        .is_some_and(|parent| parent.starts_with(dest));
+    if !inside || std::fs::symlink_metadata(&target).is_err() {
+        return Ok(());
+    }
+    std::os::unix::fs::lchown(&target, Some(uid), Some(gid))
+        .map_err(|e| format!("chown {}: {}", target.display(), e))
+}
+
 /// Whether to extract as a single file or into a directory.
 enum ExtractionMode {
     /// Destination is a file path — extract the single tar entry directly to it.
@@ -408,3 +708,214 @@ fn append_dir_recursive(
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+
+    fn running_as_root() -> bool {
+        unsafe { nix::libc::geteuid() == 0 }
+    }
+
+    fn owner(path: &Path) -> (u32, u32) {
+        let m = fs::symlink_metadata(path).unwrap();
+        (m.uid(), m.gid())
+    }
+
+    /// Archive of `./`, `app/`, `app/src/` and `app/src/main.py`.
+    fn nested_tar(dir: &Path) -> PathBuf {
+        let src = dir.join("src");
+        fs::create_dir_all(src.join("app/src")).unwrap();
+        fs::write(src.join("app/src/main.py"), "print()").unwrap();
+
+        let tar_path = dir.join("upload.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+        builder.append_dir(".", &src).unwrap();
+        builder.append_dir_all("app", src.join("app")).unwrap();
+        builder.finish().unwrap();
+        tar_path
+    }
+
+    #[test]
+    fn test_chown_entries_nested_dirs() {
+        if !running_as_root() {
+            eprintln!("skipping: chown requires root");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let tar_path = nested_tar(dir.path());
+        let dest = dir.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+        tar::Archive::new(fs::File::open(&tar_path).unwrap())
+            .unpack(&dest)
+            .unwrap();
+
+        chown_entries(&tar_path, &dest, 1000, 1000).unwrap();
+
+        assert_eq!(owner(&dest.join("app")), (1000, 1000));
+        assert_eq!(owner(&dest.join("app/src")), (1000, 1000));
+        assert_eq!(owner(&dest.join("app/src/main.py")), (1000, 1000));
+        // The destination itself belongs to whoever created it
+        assert_eq!(owner(&dest), (0, 0));
+    }
+
+    #[test]
+    fn test_chown_entries_skips_symlinked_parent() {
+        if !running_as_root() {
+            eprintln!("skipping: chown requires root");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        fs::write(outside.join("main.py"), "x").unwrap();
+
+        // A pre-existing symlink in the destination points outside it
+        let dest = dir.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+        std::os::unix::fs::symlink(&outside, dest.join("app")).unwrap();
+
+        let tar_path = dir.path().join("upload.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(1);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, "app/main.py", &b"y"[..])
+            .unwrap();
+        builder.finish().unwrap();
+
+        chown_entries(&tar_path, &dest, 1000, 1000).unwrap();
+        assert_eq!(owner(&outside.join("main.py")), (0, 0));
+    }
+
+    /// Feed `tar_path` to a [`ChunkReader`] in small chunks from another thread.
+    fn chunked(tar_path: &Path) -> ChunkReader {
+        let data = fs::read(tar_path).unwrap();
+        let (tx, rx) = mpsc::channel(STREAMED_CHUNKS_IN_FLIGHT);
+        std::thread::spawn(move || {
+            for chunk in data.chunks(100) {
+                tx.blocking_send(chunk.to_vec()).unwrap();
+            }
+        });
+        ChunkReader::new(rx)
+    }
+
+    #[test]
+    fn test_unpack_stream_nested_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let tar_path = nested_tar(dir.path());
+        let dest = dir.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+
+        unpack_stream(
+            chunked(&tar_path),
+            &dest,
+            UploadOwner::Preserve,
+            ArchiveLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dest.join("app/src/main.py")).unwrap(),
+            "print()"
+        );
+    }
+
+    #[test]
+    fn test_unpack_stream_rejects_symlink_swap() {
+        let dir = tempfile::tempdir().unwrap();
+        let outside = dir.path().join("outside");
+        fs::create_dir(&outside).unwrap();
+        let dest = dir.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+
+        let tar_path = dir.path().join("upload.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+        let mut link = tar::Header::new_gnu();
+        link.set_entry_type(tar::EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "app", &outside).unwrap();
+        let mut file = tar::Header::new_gnu();
+        file.set_size(1);
+        file.set_mode(0o644);
+        builder
+            .append_data(&mut file, "app/planted", &b"x"[..])
+            .unwrap();
+        builder.finish().unwrap();
+
+        let err = unpack_stream(
+            chunked(&tar_path),
+            &dest,
+            UploadOwner::Preserve,
+            ArchiveLimits::default(),
+        )
+        .unwrap_err();
+        assert!(!outside.join("planted").exists());
+        assert_eq!(upload_status(err).code(), tonic::Code::InvalidArgument);
+    }
+
+    #[test]
+    fn test_unpack_stream_read_only_dir_is_finished_last() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir_all(src.join("ro")).unwrap();
+        fs::write(src.join("ro/file"), "x").unwrap();
+        let tar_path = dir.path().join("upload.tar");
+        let mut builder = tar::Builder::new(fs::File::create(&tar_path).unwrap());
+        builder.append_dir_all("ro", src.join("ro")).unwrap();
+        builder.finish().unwrap();
+        // Mark the archived directory read-only after the fact
+        let mut data = fs::read(&tar_path).unwrap();
+        let mut header = tar::Header::from_byte_slice(&data[..512]).clone();
+        header.set_mode(0o555);
+        header.set_cksum();
+        data[..512].copy_from_slice(header.as_bytes());
+        fs::write(&tar_path, data).unwrap();
+
+        let dest = dir.path().join("dest");
+        fs::create_dir(&dest).unwrap();
+        unpack_stream(
+            chunked(&tar_path),
+            &dest,
+            UploadOwner::Preserve,
+            ArchiveLimits::default(),
+        )
+        .unwrap();
+
+        assert_eq!(fs::read_to_string(dest.join("ro/file")).unwrap(), "x");
+        assert_eq!(fs::metadata(dest.join("ro")).unwrap().mode() & 0o777, 0o555);
+    }
+
+    #[test]
+    fn test_create_dirs_chowns_only_created() {
+        if !running_as_root() {
+            eprintln!("skipping: chown requires root");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a/b/c");
+        create_dirs(&path, UploadOwner::Chown(1000, 1000)).unwrap();
+
+        assert_eq!(owner(dir.path()), (0, 0));
+        assert_eq!(owner(&dir.path().join("a")), (1000, 1000));
+        assert_eq!(owner(&dir.path().join("a/b")), (1000, 1000));
+        assert_eq!(owner(&path), (1000, 1000));
+    }
+
+    #[test]
+    fn test_create_dirs_preserve_leaves_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a/b");
+        create_dirs(&path, UploadOwner::Preserve).unwrap();
+
+        assert!(path.is_dir());
+        assert_eq!(owner(&path), owner(dir.path()));
+    }
+}
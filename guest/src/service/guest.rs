@@ -1,13 +1,20 @@
 //! Guest service implementation.
 //!
-//! Handles guest initialization and management (Init, Ping, Shutdown RPCs).
+//! Handles guest initialization and management (Init, Ping, Shutdown RPCs)
+//! and serves the kernel and agent logs (Dmesg, AgentLog RPCs), swap
+//! usage (SwapUsage RPC), filesystem flushes (Sync RPC) and exec and file
+//! transfer activity (Activity RPC).
 
 use crate::service::server::GuestServer;
 use boxlite_shared::{
-    guest_init_response, Guest as GuestService, GuestInitError, GuestInitRequest,
+    constants::{agent_protocol, freeze, guest_logs},
+    errors::BoxliteError,
+    guest_init_response, ActivityRequest, ActivityResponse, AgentLogRequest, AgentLogResponse,
+    DmesgRequest, DmesgResponse, Guest as GuestService, GuestInitError, GuestInitRequest,
     GuestInitResponse, GuestInitSuccess, PingRequest, PingResponse, ShutdownRequest,
-    ShutdownResponse,
+    ShutdownResponse, SwapUsageRequest, SwapUsageResponse, SyncRequest, SyncResponse,
 };
+use std::time::{Duration, Instant};
 use tonic::{Request, Response, Status};
 use tracing::{debug, error, info};
 
@@ -82,28 +89,36 @@ impl GuestService for GuestServer {
         debug!("Received ping request");
         Ok(Response::new(PingResponse {
             version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol_version: agent_protocol::VERSION,
         }))
     }
 
     async fn shutdown(
         &self,
-        _request: Request<ShutdownRequest>,
+        request: Request<ShutdownRequest>,
     ) -> Result<Response<ShutdownResponse>, Status> {
-        info!("Received shutdown request - graceful shutdown starting");
+        let started = Instant::now();
+        let (window_ms, exec_timeout_ms) = shutdown_window(request.into_inner().timeout_ms);
+        info!(
+            window_ms,
+            "Received shutdown request - graceful shutdown starting"
+        );
 
-        // Step 1: Gracefully shutdown all running executions
-        const EXEC_SHUTDOWN_TIMEOUT_MS: u64 = 1000;
+        // Step 1: Gracefully shutdown all running executions; services
+        // are not restarted once their processes go
+        self.services.stop_all().await;
         info!("Stopping running executions...");
-        self.registry.shutdown_all(EXEC_SHUTDOWN_TIMEOUT_MS).await;
+        self.registry.shutdown_all(exec_timeout_ms).await;
 
-        // Step 2: Gracefully shutdown all containers
-        const CONTAINER_SHUTDOWN_TIMEOUT_MS: u64 = 2000;
-        info!("Stopping containers...");
+        // Step 2: Gracefully shutdown all containers, in what is left of
+        // the window
+        let container_timeout_ms = window_ms.saturating_sub(started.elapsed().as_millis() as u64);
+        info!(container_timeout_ms, "Stopping containers...");
         let containers = self.containers.lock().await;
         for (container_id, container_arc) in containers.iter() {
             info!(container_id = %container_id, "Shutting down container");
             let container = container_arc.lock().await;
-            if let Err(e) = container.shutdown(CONTAINER_SHUTDOWN_TIMEOUT_MS) {
+            if let Err(e) = container.shutdown(container_timeout_ms) {
                 error!(container_id = %container_id, error = %e, "Failed to shutdown container");
             }
         }
@@ -119,4 +134,180 @@ impl GuestService for GuestServer {
         info!("Graceful shutdown complete");
         Ok(Response::new(ShutdownResponse {}))
     }
+
+    async fn dmesg(
+        &self,
+        request: Request<DmesgRequest>,
+    ) -> Result<Response<DmesgResponse>, Status> {
+        let tail = request
+            .into_inner()
+            .tail_lines
+            .min(guest_logs::MAX_DMESG_LINES);
+        debug!(tail, "Received dmesg request");
+
+        let log = tokio::task::spawn_blocking(read_kernel_log)
+            .await
+            .map_err(|e| Status::internal(format!("dmesg task failed: {}", e)))?
+            .map_err(|e| Status::internal(format!("Failed to read kernel log: {}", e)))?;
+        Ok(Response::new(DmesgResponse {
+            lines: tail_kernel_log(&log, tail as usize),
+        }))
+    }
+
+    async fn agent_log(
+        &self,
+        request: Request<AgentLogRequest>,
+    ) -> Result<Response<AgentLogResponse>, Status> {
+        let tail = request
+            .into_inner()
+            .tail_bytes
+            .min(guest_logs::MAX_AGENT_LOG_BYTES);
+        debug!(tail, "Received agent log request");
+
+        let (data, truncated) = crate::agent_log::tail(tail as usize);
+        Ok(Response::new(AgentLogResponse { data, truncated }))
+    }
+
+    async fn swap_usage(
+        &self,
+        _request: Request<SwapUsageRequest>,
+    ) -> Result<Response<SwapUsageResponse>, Status> {
+        let (total_bytes, used_bytes) = crate::swap::usage()
+            .map_err(|e| Status::internal(format!("Failed to read swap usage: {}", e)))?;
+        Ok(Response::new(SwapUsageResponse {
+            total_bytes,
+            used_bytes,
+        }))
+    }
+
+    async fn sync(&self, request: Request<SyncRequest>) -> Result<Response<SyncResponse>, Status> {
+        let req = request.into_inner();
+        if req.container_id.is_empty() {
+            return Err(Status::invalid_argument("container_id is required"));
+        }
+        let cap = Duration::from_millis(req.freeze_ms.min(freeze::MAX_CAP_MS) as u64);
+        info!(container_id = %req.container_id, ?cap, "Received sync request");
+
+        // The freeze thread thaws on its own, so dropping this RPC is safe
+        let rootfs = self
+            .layout
+            .shared()
+            .container(&req.container_id)
+            .rootfs_dir();
+        let frozen =
+            tokio::task::spawn_blocking(move || crate::freeze::sync_and_freeze(&rootfs, cap))
+                .await
+                .map_err(|e| Status::internal(format!("sync task failed: {}", e)))?
+                .map_err(|e| match e {
+                    BoxliteError::InvalidState(msg) => Status::failed_precondition(msg),
+                    BoxliteError::Timeout(msg) => Status::deadline_exceeded(msg),
+                    e => Status::internal(e.to_string()),
+                })?;
+
+        Ok(Response::new(SyncResponse {
+            frozen_micros: frozen.as_micros() as u64,
+        }))
+    }
+
+    async fn activity(
+        &self,
+        _request: Request<ActivityRequest>,
+    ) -> Result<Response<ActivityResponse>, Status> {
+        // Supervised services are the box's workload, not activity on it
+        let running_execs = self
+            .registry
+            .count_running(crate::service::supervisor::is_service_run)
+            .await;
+        Ok(Response::new(ActivityResponse {
+            running_execs,
+            idle_ms: self.activity.idle().as_millis() as u64,
+        }))
+    }
+}
+
+/// Shutdown window when the request leaves `timeout_ms` at 0.
+const DEFAULT_SHUTDOWN_TIMEOUT_MS: u64 = 3000;
+
+/// Longest time execs get to exit, out of the shutdown window.
+const EXEC_SHUTDOWN_TIMEOUT_MS: u64 = 1000;
+
+/// The shutdown window requested as `timeout_ms`, and the part of it execs
+/// get to exit: a third, at most 1s. Containers get the rest.
+fn shutdown_window(timeout_ms: u64) -> (u64, u64) {
+    let window = if timeout_ms == 0 {
+        DEFAULT_SHUTDOWN_TIMEOUT_MS
+    } else {
+        timeout_ms
+    };
+    (window, (window / 3).min(EXEC_SHUTDOWN_TIMEOUT_MS))
+}
+
+/// Read the whole kernel ring buffer with klogctl(2), as `dmesg` does.
+fn read_kernel_log() -> std::io::Result<String> {
+    const SYSLOG_ACTION_READ_ALL: nix::libc::c_int = 3;
+    const SYSLOG_ACTION_SIZE_BUFFER: nix::libc::c_int = 10;
+
+    let size = unsafe { nix::libc::klogctl(SYSLOG_ACTION_SIZE_BUFFER, std::ptr::null_mut(), 0) };
+    if size < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let mut buf = vec![0u8; size as usize];
+    let read = unsafe { nix::libc::klogctl(SYSLOG_ACTION_READ_ALL, buf.as_mut_ptr().cast(), size) };
+    if read < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    buf.truncate(read as usize);
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// Last `lines` lines of a klogctl dump, without the `<level>` prefixes.
+fn tail_kernel_log(log: &str, lines: usize) -> Vec<String> {
+    let all: Vec<&str> = log.lines().collect();
+    all[all.len().saturating_sub(lines)..]
+        .iter()
+        .map(|line| strip_level(line).to_string())
+        .collect()
+}
+
+fn strip_level(line: &str) -> &str {
+    line.strip_prefix('<')
+        .and_then(|rest| rest.split_once('>'))
+        .filter(|(level, _)| !level.is_empty() && level.bytes().all(|b| b.is_ascii_digit()))
+        .map_or(line, |(_, message)| message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_kernel_log_strips_levels() {
+        let log = "<6>[    0.000000] Linux version 6.1\n<3>[    1.5] Out of memory: Killed process 42\n<4>[    2.0] EXT4-fs warning\n";
+        assert_eq!(
+            tail_kernel_log(log, 2),
+            vec![
+                "[    1.5] Out of memory: Killed process 42",
+                "[    2.0] EXT4-fs warning",
+            ]
+        );
+        assert_eq!(tail_kernel_log(log, 100).len(), 3);
+        assert!(tail_kernel_log(log, 0).is_empty());
+    }
+
+    #[test]
+    fn test_shutdown_window() {
+        // The default keeps the 3s, 1s of it for execs, the agent always used
+        assert_eq!(shutdown_window(0), (3000, 1000));
+        assert_eq!(shutdown_window(3000), (3000, 1000));
+        assert_eq!(shutdown_window(60_000), (60_000, 1000));
+        assert_eq!(shutdown_window(600), (600, 200));
+        assert_eq!(shutdown_window(1), (1, 0));
+    }
+
+    #[test]
+    fn test_strip_level_keeps_unprefixed_lines() {
+        assert_eq!(strip_level("<12>message"), "message");
+        assert_eq!(strip_level("<x>message"), "<x>message");
+        assert_eq!(strip_level("plain"), "plain");
+    }
 }
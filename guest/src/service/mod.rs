@@ -4,9 +4,13 @@
 //! - `guest`: Guest initialization and management (Init, Ping, Shutdown RPCs)
 //! - `container`: Container lifecycle (Init RPC)
 //! - `execution`: Command execution (Exec, Wait, Kill RPCs)
+//! - `tunnel`: TCP relay to guest loopback services (Relay RPC)
+//! - `supervisor`: Supervised long-running services (StartService, StopService RPCs)
 
 mod container;
 pub(crate) mod exec;
 pub(crate) mod files;
 mod guest;
 pub(crate) mod server;
+pub(crate) mod supervisor;
+mod tunnel;
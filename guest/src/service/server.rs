@@ -1,9 +1,13 @@
 use crate::container::Container;
 use crate::layout::GuestLayout;
+use crate::service::exec::prepared::PreparedRegistry;
 use crate::service::exec::registry::ExecutionRegistry;
+use crate::service::supervisor::ServiceRegistry;
+use boxlite_shared::boot::BootPhase;
 use boxlite_shared::{BoxliteResult, Transport};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 use tonic::transport::Server;
 use tracing::{info, warn};
@@ -17,12 +21,72 @@ pub(crate) struct GuestInitState {
     pub initialized: bool,
 }
 
+/// When the host last ran a command or moved files (Guest.Activity).
+#[derive(Clone)]
+pub(crate) struct ActivityClock(Arc<std::sync::Mutex<Activity>>);
+
+struct Activity {
+    last: Instant,
+    /// Uploads and downloads in flight
+    transfers: u32,
+}
+
+impl ActivityClock {
+    fn new() -> Self {
+        Self(Arc::new(std::sync::Mutex::new(Activity {
+            last: Instant::now(),
+            transfers: 0,
+        })))
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Activity> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record activity now.
+    pub fn touch(&self) {
+        self.lock().last = Instant::now();
+    }
+
+    /// Mark a file transfer in flight until the guard drops.
+    pub fn transfer(&self) -> TransferGuard {
+        let mut activity = self.lock();
+        activity.transfers += 1;
+        activity.last = Instant::now();
+        TransferGuard(self.clone())
+    }
+
+    /// Time since the last activity; zero while a transfer is in flight.
+    pub fn idle(&self) -> Duration {
+        let activity = self.lock();
+        if activity.transfers > 0 {
+            Duration::ZERO
+        } else {
+            activity.last.elapsed()
+        }
+    }
+}
+
+/// A file transfer in flight (see [`ActivityClock::transfer`]).
+pub(crate) struct TransferGuard(ActivityClock);
+
+impl Drop for TransferGuard {
+    fn drop(&mut self) {
+        let mut activity = self.0.lock();
+        activity.transfers -= 1;
+        activity.last = Instant::now();
+    }
+}
+
 /// Guest agent server.
 ///
 /// Implements three gRPC services:
 /// - Guest: Agent initialization and management
 /// - Container: OCI container lifecycle
 /// - Execution: Command execution with bidirectional streaming
+///
+/// Clones share all state; the supervisor keeps one per service task.
+#[derive(Clone)]
 pub(crate) struct GuestServer {
     /// Guest filesystem layout
     pub layout: GuestLayout,
@@ -35,6 +99,15 @@ pub(crate) struct GuestServer {
 
     /// Execution registry for tracking running executions
     pub registry: ExecutionRegistry,
+
+    /// Prepared command templates (lost on agent restart)
+    pub prepared: PreparedRegistry,
+
+    /// Supervised services (lost on agent restart)
+    pub services: ServiceRegistry,
+
+    /// Last exec or file transfer, for the host's idle auto-stop
+    pub activity: ActivityClock,
 }
 
 impl GuestServer {
@@ -48,6 +121,9 @@ impl GuestServer {
             init_state: Arc::new(Mutex::new(GuestInitState::default())),
             containers: Arc::new(Mutex::new(HashMap::new())),
             registry: ExecutionRegistry::new(),
+            prepared: PreparedRegistry::new(),
+            services: ServiceRegistry::new(),
+            activity: ActivityClock::new(),
         }
     }
 
@@ -78,7 +154,9 @@ impl GuestServer {
             .add_service(boxlite_shared::ContainerServer::from_arc(server.clone()))
             .add_service(boxlite_shared::GuestServer::from_arc(server.clone()))
             .add_service(boxlite_shared::ExecutionServer::from_arc(server.clone()))
-            .add_service(boxlite_shared::FilesServer::from_arc(server.clone()));
+            .add_service(boxlite_shared::FilesServer::from_arc(server.clone()))
+            .add_service(boxlite_shared::TunnelServer::from_arc(server.clone()))
+            .add_service(boxlite_shared::SupervisorServer::from_arc(server.clone()));
 
         match transport {
             Transport::Vsock { port } => {
@@ -185,6 +263,9 @@ impl GuestServer {
 ///
 /// The connection itself is the signal - no data needs to be sent.
 async fn notify_host_ready(notify_uri: Option<String>) -> BoxliteResult<()> {
+    // Listener is bound; record the phase before the host can race ahead
+    eprintln!("{}", BootPhase::AgentStarted.marker_line());
+
     let uri = match notify_uri {
         Some(uri) => uri,
         None => {
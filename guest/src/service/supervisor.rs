@@ -0,0 +1,346 @@
+//! Supervisor service implementation.
+//!
+//! Runs long-lived services next to the container entrypoint. Each run of a
+//! service is an ordinary execution (`service-<name>-<run>`) in the exec
+//! registry, with its output captured like a detached exec. A task per
+//! service waits for the run to exit and starts the next one as the
+//! service's restart policy says.
+
+use crate::service::exec::exec_handle::ExitStatus;
+use crate::service::exec::spawn_execution;
+use crate::service::exec::state::ExecutionState;
+use crate::service::server::GuestServer;
+use boxlite_shared::{
+    ExecError, ExecRequest, ListServicesRequest, ListServicesResponse, OutputCapture,
+    RestartPolicy, ServiceState, ServiceStatus, StartServiceRequest, StartServiceResponse,
+    StopServiceRequest, StopServiceResponse, Supervisor,
+};
+use nix::sys::signal::Signal;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
+use tokio::task::JoinHandle;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+/// Delay before the first restart; doubles per restart up to `MAX_BACKOFF`.
+const MIN_BACKOFF: Duration = Duration::from_millis(100);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// A run lasting this long resets the backoff.
+const STABLE_RUN: Duration = Duration::from_secs(10);
+/// Per-stream output cap when the request sets none.
+const OUTPUT_MAX_BYTES: u64 = 16 * 1024 * 1024;
+
+/// A supervised service.
+struct Service {
+    status: Arc<Mutex<ServiceStatus>>,
+    /// Set to `true` to stop the service; no run starts after it.
+    stop: watch::Sender<bool>,
+    /// Supervision task; `None` once stopped or if the first run failed.
+    task: Option<JoinHandle<()>>,
+}
+
+impl Service {
+    fn is_active(&self) -> bool {
+        self.task.as_ref().is_some_and(|task| !task.is_finished())
+    }
+}
+
+/// Registry of supervised services.
+#[derive(Clone)]
+pub(crate) struct ServiceRegistry {
+    services: Arc<Mutex<HashMap<String, Service>>>,
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self {
+            services: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Stop restarting every service; their processes are left to the
+    /// execution shutdown that follows.
+    pub async fn stop_all(&self) {
+        for service in self.services.lock().await.values() {
+            service.stop.send_replace(true);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Supervisor for GuestServer {
+    async fn start_service(
+        &self,
+        request: Request<StartServiceRequest>,
+    ) -> Result<Response<StartServiceResponse>, Status> {
+        let req = request.into_inner();
+        let restart = req.restart();
+        let name = req.name;
+        let mut command = req
+            .command
+            .ok_or_else(|| Status::invalid_argument("command is required"))?;
+        info!(service = %name, program = %command.program, ?restart, "start_service request");
+
+        if name.is_empty() || command.program.is_empty() {
+            return Ok(Response::new(start_error(
+                "invalid_service",
+                "name and program are required",
+            )));
+        }
+        // The output has to be drained whether or not anyone attaches
+        command.capture.get_or_insert(OutputCapture {
+            max_bytes: OUTPUT_MAX_BYTES,
+        });
+
+        // Held across the first run so concurrent starts of a name serialize
+        let mut services = self.services.services.lock().await;
+        if services.get(&name).is_some_and(Service::is_active) {
+            return Ok(Response::new(start_error(
+                "service_exists",
+                &format!("Service is already running: {}", name),
+            )));
+        }
+
+        let status = Arc::new(Mutex::new(ServiceStatus {
+            name: name.clone(),
+            ..Default::default()
+        }));
+        let (stop, stop_rx) = watch::channel(false);
+        let run = first_free_run(self, &name).await;
+
+        // The first run is started here so spawn failures reach the caller
+        let (task, error) = match start_run(self, &name, run, &command, &status).await {
+            Ok(execution) => {
+                let task = tokio::spawn(supervise(
+                    self.clone(),
+                    name.clone(),
+                    command,
+                    restart,
+                    status.clone(),
+                    run,
+                    execution,
+                    stop_rx,
+                ));
+                (Some(task), None)
+            }
+            Err(error) => (None, Some(error)),
+        };
+        services.insert(name, Service { status, stop, task });
+
+        Ok(Response::new(StartServiceResponse { error }))
+    }
+
+    async fn stop_service(
+        &self,
+        request: Request<StopServiceRequest>,
+    ) -> Result<Response<StopServiceResponse>, Status> {
+        let req = request.into_inner();
+        info!(service = %req.name, timeout_ms = req.timeout_ms, "stop_service request");
+
+        let (status, task) = {
+            let mut services = self.services.services.lock().await;
+            let Some(service) = services.get_mut(&req.name) else {
+                return Ok(Response::new(StopServiceResponse {
+                    error: Some(ExecError {
+                        reason: "service_not_found".to_string(),
+                        detail: format!("Service not found: {}", req.name),
+                    }),
+                }));
+            };
+            service.stop.send_replace(true);
+            (service.status.clone(), service.task.take())
+        };
+
+        if let Some(mut task) = task {
+            let execution_id = status.lock().await.execution_id.clone();
+            let execution = self.registry.get(&execution_id).await;
+            if let Some(execution) = &execution {
+                execution.kill(Signal::SIGTERM).await;
+            }
+
+            let grace = Duration::from_millis(req.timeout_ms);
+            if tokio::time::timeout(grace, &mut task).await.is_err() {
+                warn!(service = %req.name, "Service didn't stop gracefully, sending SIGKILL");
+                if let Some(execution) = &execution {
+                    execution.kill(Signal::SIGKILL).await;
+                }
+                let _ = task.await;
+            }
+        }
+
+        let mut status = status.lock().await;
+        status.state = ServiceState::Stopped.into();
+        status.pid = 0;
+        Ok(Response::new(StopServiceResponse { error: None }))
+    }
+
+    async fn list_services(
+        &self,
+        _request: Request<ListServicesRequest>,
+    ) -> Result<Response<ListServicesResponse>, Status> {
+        let statuses: Vec<_> = self
+            .services
+            .services
+            .lock()
+            .await
+            .values()
+            .map(|service| service.status.clone())
+            .collect();
+
+        let mut services = Vec::with_capacity(statuses.len());
+        for status in statuses {
+            services.push(status.lock().await.clone());
+        }
+        services.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Response::new(ListServicesResponse { services }))
+    }
+}
+
+/// Wait for each run of a service and restart it per `restart` until the
+/// policy says no or the service is stopped.
+#[allow(clippy::too_many_arguments)]
+async fn supervise(
+    server: GuestServer,
+    name: String,
+    command: ExecRequest,
+    restart: RestartPolicy,
+    status: Arc<Mutex<ServiceStatus>>,
+    mut run: u32,
+    mut execution: ExecutionState,
+    mut stop: watch::Receiver<bool>,
+) {
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        let started = Instant::now();
+        let exit = execution.wait_process().await;
+        let failed = !matches!(exit, Ok(ExitStatus::Code(0)));
+        let stopping = *stop.borrow();
+        let restarting = !stopping
+            && match restart {
+                RestartPolicy::No => false,
+                RestartPolicy::OnFailure => failed,
+                RestartPolicy::Always => true,
+            };
+
+        {
+            let mut status = status.lock().await;
+            status.pid = 0;
+            status.exit_code = match exit {
+                Ok(ExitStatus::Code(code)) => Some(code),
+                _ => None,
+            };
+            status.state = match (stopping, restarting) {
+                (true, _) => ServiceState::Stopped,
+                (false, true) => ServiceState::Restarting,
+                (false, false) => ServiceState::Exited,
+            }
+            .into();
+            info!(
+                service = %name,
+                execution_id = %status.execution_id,
+                exit = ?exit,
+                restarting,
+                "service run ended"
+            );
+        }
+        if !restarting {
+            return;
+        }
+
+        if started.elapsed() >= STABLE_RUN {
+            backoff = MIN_BACKOFF;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = stop.changed() => {
+                status.lock().await.state = ServiceState::Stopped.into();
+                return;
+            }
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+
+        run += 1;
+        execution = match start_run(&server, &name, run, &command, &status).await {
+            Ok(execution) => execution,
+            Err(_) => return,
+        };
+        status.lock().await.restarts += 1;
+
+        // Stopped while the run was being spawned: it is this run that ends
+        if *stop.borrow() {
+            execution.kill(Signal::SIGTERM).await;
+        }
+    }
+}
+
+/// Spawn run `run` of a service and record it in `status`.
+async fn start_run(
+    server: &GuestServer,
+    name: &str,
+    run: u32,
+    command: &ExecRequest,
+    status: &Mutex<ServiceStatus>,
+) -> Result<ExecutionState, ExecError> {
+    let execution_id = run_id(name, run);
+    let mut req = command.clone();
+    req.execution_id = Some(execution_id.clone());
+
+    let result = spawn_execution(server, execution_id.clone(), req).await;
+    let mut status = status.lock().await;
+    status.execution_id = execution_id.clone();
+    status.exit_code = None;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(response) => {
+            let error = response.error.unwrap_or_default();
+            warn!(service = %name, error = %error.detail, "Service failed to spawn");
+            status.state = ServiceState::Failed.into();
+            status.pid = 0;
+            status.error = error.detail.clone();
+            return Err(error);
+        }
+    };
+
+    let execution = server
+        .registry
+        .get(&execution_id)
+        .await
+        .ok_or_else(|| ExecError {
+            reason: "spawn_failed".to_string(),
+            detail: format!("Execution not registered: {}", execution_id),
+        })?;
+    status.state = ServiceState::Running.into();
+    status.pid = response.pid;
+    status.error.clear();
+    Ok(execution)
+}
+
+/// Run number after the runs of earlier starts of the same name.
+async fn first_free_run(server: &GuestServer, name: &str) -> u32 {
+    let mut run = 0;
+    while server.registry.exists(&run_id(name, run)).await {
+        run += 1;
+    }
+    run
+}
+
+fn run_id(name: &str, run: u32) -> String {
+    format!("service-{}-{}", name, run)
+}
+
+/// Whether `execution_id` is a run of a supervised service.
+pub(crate) fn is_service_run(execution_id: &str) -> bool {
+    execution_id.starts_with("service-")
+}
+
+fn start_error(reason: &str, detail: &str) -> StartServiceResponse {
+    StartServiceResponse {
+        error: Some(ExecError {
+            reason: reason.to_string(),
+            detail: detail.to_string(),
+        }),
+    }
+}
@@ -0,0 +1,217 @@
+//! Tunnel service implementation.
+//!
+//! Relays TCP connections from the host to services listening on the guest
+//! loopback interface. Containers share the guest network namespace, so this
+//! is the container's `127.0.0.1` as well.
+
+use crate::service::server::GuestServer;
+use boxlite_shared::{tunnel_frame::Kind, Tunnel, TunnelClose, TunnelFrame};
+use std::collections::HashMap;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, info};
+
+const READ_BUF_SIZE: usize = 32 * 1024;
+/// Frames buffered towards a single connection before the stream backs off.
+const CONN_QUEUE: usize = 64;
+/// Frames buffered towards the host, shared by all connections.
+const OUTBOUND_QUEUE: usize = 256;
+
+type Outbound = mpsc::Sender<Result<TunnelFrame, Status>>;
+
+/// A relayed connection, as seen by the dispatcher.
+struct Conn {
+    /// Data from the host; `None` once the host has closed its side.
+    to_guest: Option<mpsc::Sender<Vec<u8>>>,
+    task: JoinHandle<()>,
+}
+
+#[tonic::async_trait]
+impl Tunnel for GuestServer {
+    type RelayStream = ReceiverStream<Result<TunnelFrame, Status>>;
+
+    async fn relay(
+        &self,
+        request: Request<Streaming<TunnelFrame>>,
+    ) -> Result<Response<Self::RelayStream>, Status> {
+        info!("tunnel relay opened");
+        let (tx, rx) = mpsc::channel(OUTBOUND_QUEUE);
+        tokio::spawn(dispatch(request.into_inner(), tx));
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// Route frames from the host to their connections until the stream ends.
+async fn dispatch(mut inbound: Streaming<TunnelFrame>, outbound: Outbound) {
+    let mut conns: HashMap<u64, Conn> = HashMap::new();
+
+    while let Ok(Some(frame)) = inbound.message().await {
+        let conn_id = frame.conn_id;
+        match frame.kind {
+            Some(Kind::Open(open)) => {
+                conns.retain(|_, conn| !conn.task.is_finished());
+                let Ok(port) = u16::try_from(open.port) else {
+                    let error = format!("invalid port {}", open.port);
+                    let _ = outbound.send(Ok(close_frame(conn_id, Some(error)))).await;
+                    continue;
+                };
+                let (tx, rx) = mpsc::channel(CONN_QUEUE);
+                let task = tokio::spawn(relay_conn(conn_id, port, rx, outbound.clone()));
+                conns.insert(
+                    conn_id,
+                    Conn {
+                        to_guest: Some(tx),
+                        task,
+                    },
+                );
+            }
+            Some(Kind::Data(data)) => {
+                if let Some(tx) = conns.get(&conn_id).and_then(|c| c.to_guest.as_ref()) {
+                    let _ = tx.send(data).await;
+                }
+            }
+            Some(Kind::Close(close)) => {
+                if close.error.is_some() {
+                    if let Some(conn) = conns.remove(&conn_id) {
+                        conn.task.abort();
+                    }
+                } else if let Some(conn) = conns.get_mut(&conn_id) {
+                    conn.to_guest = None;
+                }
+            }
+            None => {}
+        }
+    }
+
+    debug!(connections = conns.len(), "tunnel relay closed");
+    for conn in conns.into_values() {
+        conn.task.abort();
+    }
+}
+
+/// Connect to `127.0.0.1:port` and pump bytes both ways.
+///
+/// Each direction closes independently; an I/O error on either aborts the
+/// connection and is reported to the host.
+async fn relay_conn(
+    conn_id: u64,
+    port: u16,
+    mut from_host: mpsc::Receiver<Vec<u8>>,
+    outbound: Outbound,
+) {
+    let stream = match TcpStream::connect(("127.0.0.1", port)).await {
+        Ok(stream) => stream,
+        Err(e) => {
+            debug!(conn_id, port, error = %e, "tunnel connect failed");
+            let _ = outbound
+                .send(Ok(close_frame(conn_id, Some(e.to_string()))))
+                .await;
+            return;
+        }
+    };
+    let (mut reader, mut writer) = stream.into_split();
+
+    let to_host = async {
+        let mut buf = vec![0u8; READ_BUF_SIZE];
+        loop {
+            let n = reader.read(&mut buf).await?;
+            let frame = if n == 0 {
+                close_frame(conn_id, None)
+            } else {
+                data_frame(conn_id, buf[..n].to_vec())
+            };
+            if outbound.send(Ok(frame)).await.is_err() {
+                return Err(std::io::Error::other("tunnel stream closed"));
+            }
+            if n == 0 {
+                return Ok(());
+            }
+        }
+    };
+    let to_guest = async {
+        while let Some(data) = from_host.recv().await {
+            writer.write_all(&data).await?;
+        }
+        writer.shutdown().await
+    };
+
+    if let Err(e) = tokio::try_join!(to_host, to_guest) {
+        let _ = outbound
+            .send(Ok(close_frame(conn_id, Some(e.to_string()))))
+            .await;
+    }
+}
+
+fn data_frame(conn_id: u64, data: Vec<u8>) -> TunnelFrame {
+    TunnelFrame {
+        conn_id,
+        kind: Some(Kind::Data(data)),
+    }
+}
+
+fn close_frame(conn_id: u64, error: Option<String>) -> TunnelFrame {
+    TunnelFrame {
+        conn_id,
+        kind: Some(Kind::Close(TunnelClose { error })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    async fn next_kind(rx: &mut mpsc::Receiver<Result<TunnelFrame, Status>>) -> Kind {
+        let frame = rx.recv().await.unwrap().unwrap();
+        assert_eq!(frame.conn_id, 7);
+        frame.kind.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_relay_conn_round_trip_and_half_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).await.unwrap();
+            socket.write_all(b"pong").await.unwrap();
+            received
+        });
+
+        let (to_conn, from_host) = mpsc::channel(CONN_QUEUE);
+        let (outbound, mut rx) = mpsc::channel(OUTBOUND_QUEUE);
+        let task = tokio::spawn(relay_conn(7, port, from_host, outbound));
+
+        to_conn.send(b"ping".to_vec()).await.unwrap();
+        drop(to_conn);
+
+        assert!(matches!(next_kind(&mut rx).await, Kind::Data(d) if d == b"pong"));
+        assert!(matches!(
+            next_kind(&mut rx).await,
+            Kind::Close(TunnelClose { error: None })
+        ));
+        assert_eq!(server.await.unwrap(), b"ping");
+        task.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_relay_conn_reports_refused_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let (_to_conn, from_host) = mpsc::channel(CONN_QUEUE);
+        let (outbound, mut rx) = mpsc::channel(OUTBOUND_QUEUE);
+        relay_conn(7, port, from_host, outbound).await;
+
+        assert!(matches!(
+            next_kind(&mut rx).await,
+            Kind::Close(TunnelClose { error: Some(_) })
+        ));
+    }
+}
@@ -6,7 +6,7 @@ use std::path::{Path, PathBuf};
 
 use boxlite_shared::constants::mount_tags;
 use boxlite_shared::errors::BoxliteResult;
-use boxlite_shared::layout::GUEST_BASE;
+use boxlite_shared::layout::{dirs, GUEST_BASE};
 use boxlite_shared::{volume, Filesystem, Volume};
 
 use super::block_device::BlockDeviceMount;
@@ -16,6 +16,7 @@ use super::virtiofs::VirtiofsMount;
 ///
 /// System volumes use well-known paths:
 /// - SHARED → /run/boxlite/shared
+/// - EXECS → /run/boxlite/execs
 /// - LAYERS (with container_id) → /run/boxlite/shared/containers/{container_id}/layers
 /// - User volumes (with container_id) → /run/boxlite/shared/containers/{container_id}/volumes/{tag}
 fn resolve_mount_point(tag: &str, mount_point: &str, container_id: &str) -> PathBuf {
@@ -26,6 +27,7 @@ fn resolve_mount_point(tag: &str, mount_point: &str, container_id: &str) -> Path
     // Guest determines path based on tag
     match tag {
         mount_tags::SHARED => PathBuf::from(GUEST_BASE).join("shared"),
+        mount_tags::EXECS => PathBuf::from(GUEST_BASE).join(dirs::EXECS),
         mount_tags::LAYERS => {
             if container_id.is_empty() {
                 // Legacy path (shouldn't happen in convention-based mode)
@@ -0,0 +1,215 @@
+//! Swap inside the VM (`BoxOptions::swap_mib`).
+//!
+//! The swap file lives on the container disk at [`swap::FILE`], so it
+//! survives restarts and is reused when the size is unchanged. It is
+//! allocated up front (swap files must not have holes), given a swap
+//! header like `mkswap` writes, and enabled before the container starts.
+//! A box whose swap was turned off gets the leftover file removed, giving
+//! the space back to the container.
+
+use boxlite_shared::constants::swap;
+use boxlite_shared::errors::{BoxliteError, BoxliteResult};
+use nix::errno::Errno;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{FileExt, OpenOptionsExt};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+
+/// Signature `mkswap` writes at the end of the first page.
+const SWAP_MAGIC: &[u8] = b"SWAPSPACE2";
+
+/// Offset of the v1 swap header, after the boot block.
+const HEADER_OFFSET: usize = 1024;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Create (or reuse) a swap file of `size_mib` in `rootfs` and enable it.
+///
+/// `size_mib == 0` removes a swap file left by an earlier start instead.
+pub fn configure(rootfs: &Path, size_mib: u32) -> BoxliteResult<()> {
+    let path = file_in(rootfs);
+    if size_mib == 0 {
+        return match fs::remove_file(&path) {
+            Ok(()) => {
+                tracing::info!(path = %path.display(), "Removed swap file of an earlier start");
+                Ok(())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(BoxliteError::Internal(format!(
+                "Failed to remove swap file {}: {}",
+                swap::FILE,
+                e
+            ))),
+        };
+    }
+
+    let size = size_mib as u64 * MIB;
+    let page_size = page_size();
+    if size < 10 * page_size as u64 {
+        return Err(BoxliteError::Config(format!(
+            "swap_mib {} is too small for a swap file",
+            size_mib
+        )));
+    }
+
+    prepare_file(rootfs, &path, size, page_size).map_err(|e| {
+        BoxliteError::Internal(format!(
+            "Failed to create {} MiB swap file {}: {}",
+            size_mib,
+            swap::FILE,
+            e
+        ))
+    })?;
+    enable(&path).map_err(|e| {
+        BoxliteError::Unsupported(format!(
+            "Failed to enable swap file {} (swap needs an ext4 container disk and a guest \
+             kernel with swap support): {}",
+            swap::FILE,
+            e
+        ))
+    })?;
+
+    tracing::info!(path = %path.display(), size_mib, "Swap enabled");
+    Ok(())
+}
+
+/// Swap size and usage of the guest in bytes, from `/proc/meminfo`.
+pub fn usage() -> io::Result<(u64, u64)> {
+    let meminfo = fs::read_to_string("/proc/meminfo")?;
+    parse_meminfo(&meminfo).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "SwapTotal/SwapFree missing from /proc/meminfo",
+        )
+    })
+}
+
+/// `swap::FILE` under `root`.
+fn file_in(root: &Path) -> PathBuf {
+    root.join(swap::FILE.trim_start_matches('/'))
+}
+
+/// Allocate `path` at `size` bytes and write the swap header.
+fn prepare_file(rootfs: &Path, path: &Path, size: u64, page_size: usize) -> io::Result<()> {
+    let existing = match fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() == size => size,
+        Ok(_) => {
+            // Resized, or not a regular file: start over
+            fs::remove_file(path)?;
+            0
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e),
+    };
+
+    let needed = size - existing;
+    let stat = nix::sys::statvfs::statvfs(rootfs)?;
+    let free = stat.blocks_available() as u64 * stat.fragment_size() as u64;
+    if needed > free {
+        return Err(io::Error::other(format!(
+            "needs {} MiB but the container disk has {} MiB free; raise disk_size_gb",
+            needed / MIB,
+            free / MIB
+        )));
+    }
+
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .mode(0o600)
+        .custom_flags(nix::libc::O_NOFOLLOW)
+        .open(path)?;
+    let ret = unsafe { nix::libc::fallocate(file.as_raw_fd(), 0, 0, size as nix::libc::off_t) };
+    Errno::result(ret)?;
+    file.write_all_at(&header(page_size, size), 0)?;
+    file.sync_all()
+}
+
+/// First page of a swap file of `size` bytes, as `mkswap` writes it.
+fn header(page_size: usize, size: u64) -> Vec<u8> {
+    let last_page = (size / page_size as u64 - 1) as u32;
+    let mut page = vec![0u8; page_size];
+    let fields = [1u32, last_page, 0];
+    for (i, value) in fields.iter().enumerate() {
+        let at = HEADER_OFFSET + i * 4;
+        page[at..at + 4].copy_from_slice(&value.to_ne_bytes());
+    }
+    let uuid_at = HEADER_OFFSET + fields.len() * 4;
+    page[uuid_at..uuid_at + 16].copy_from_slice(uuid::Uuid::new_v4().as_bytes());
+    page[page_size - SWAP_MAGIC.len()..].copy_from_slice(SWAP_MAGIC);
+    page
+}
+
+fn enable(path: &Path) -> io::Result<()> {
+    let path = CString::new(path.as_os_str().as_bytes())?;
+    let ret = unsafe { nix::libc::swapon(path.as_ptr(), 0) };
+    Errno::result(ret)?;
+    Ok(())
+}
+
+fn page_size() -> usize {
+    match unsafe { nix::libc::sysconf(nix::libc::_SC_PAGESIZE) } {
+        size if size > 0 => size as usize,
+        _ => 4096,
+    }
+}
+
+/// `(SwapTotal, SwapTotal - SwapFree)` in bytes.
+fn parse_meminfo(meminfo: &str) -> Option<(u64, u64)> {
+    let field = |name: &str| {
+        meminfo.lines().find_map(|line| {
+            let value = line.strip_prefix(name)?.strip_prefix(':')?;
+            let kib: u64 = value.trim().trim_end_matches("kB").trim().parse().ok()?;
+            Some(kib * 1024)
+        })
+    };
+    let total = field("SwapTotal")?;
+    let free = field("SwapFree")?;
+    Some((total, total.saturating_sub(free)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_layout() {
+        let page = header(4096, 64 * MIB);
+        assert_eq!(page.len(), 4096);
+        assert_eq!(&page[4096 - 10..], SWAP_MAGIC);
+        let word = |at: usize| u32::from_ne_bytes(page[at..at + 4].try_into().unwrap());
+        assert_eq!(word(1024), 1);
+        assert_eq!(word(1028), 64 * 256 - 1);
+        assert_eq!(word(1032), 0);
+        assert!(page[..1024].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_parse_meminfo() {
+        let meminfo = "MemTotal:         498176 kB\n\
+                       SwapCached:            0 kB\n\
+                       SwapTotal:        524284 kB\n\
+                       SwapFree:         520188 kB\n";
+        assert_eq!(
+            parse_meminfo(meminfo),
+            Some((524284 * 1024, (524284 - 520188) * 1024))
+        );
+        assert_eq!(parse_meminfo("MemTotal: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_zero_size_removes_leftover_file() {
+        let root = tempfile::tempdir().unwrap();
+        let path = file_in(root.path());
+        fs::write(&path, b"old swap").unwrap();
+
+        configure(root.path(), 0).unwrap();
+        assert!(!path.exists());
+        // Nothing to remove is fine too
+        configure(root.path(), 0).unwrap();
+    }
+}
@@ -0,0 +1,92 @@
+//! Built-in container init: zombie reaping, signal forwarding, exit status.
+//!
+//! Runs the agent binary under the init name, as the container would. It is
+//! not PID 1 here, so it relies on being a child subreaper to adopt orphans.
+
+#![cfg(target_os = "linux")]
+
+use std::path::Path;
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use nix::sys::signal::{kill, Signal};
+use nix::unistd::Pid;
+use tempfile::TempDir;
+
+/// Spawn `sh -c script` under the init, started through a `boxlite-init` symlink.
+fn spawn_init(dir: &TempDir, script: &str) -> Child {
+    let init = dir.path().join("boxlite-init");
+    if !init.exists() {
+        std::os::unix::fs::symlink(env!("CARGO_BIN_EXE_boxlite-guest"), &init).unwrap();
+    }
+    Command::new(&init)
+        .args(["--", "sh", "-c", script])
+        .spawn()
+        .expect("failed to spawn init")
+}
+
+/// PIDs of zombie processes whose parent is `parent`.
+fn zombie_children(parent: u32) -> Vec<u32> {
+    std::fs::read_dir("/proc")
+        .unwrap()
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter(|pid| {
+            let Ok(stat) =
+                std::fs::read_to_string(Path::new("/proc").join(pid.to_string()).join("stat"))
+            else {
+                return false;
+            };
+            // "pid (comm) state ppid ...": comm may contain spaces and parens
+            let Some((_, rest)) = stat.rsplit_once(')') else {
+                return false;
+            };
+            let mut fields = rest.split_whitespace();
+            let state = fields.next();
+            let ppid = fields.next().and_then(|p| p.parse::<u32>().ok());
+            state == Some("Z") && ppid == Some(parent)
+        })
+        .collect()
+}
+
+#[test]
+fn init_reaps_orphaned_children() {
+    let dir = TempDir::new().unwrap();
+    // Each subshell backgrounds a short sleep and exits at once, orphaning it
+    let mut init = spawn_init(
+        &dir,
+        "for i in 1 2 3 4 5 6 7 8; do (sleep 0.1 &); done; sleep 2",
+    );
+
+    // The orphans have exited by now; unreaped, they'd be zombies of init
+    std::thread::sleep(Duration::from_secs(1));
+    let zombies = zombie_children(init.id());
+
+    let _ = init.kill();
+    let _ = init.wait();
+    assert!(zombies.is_empty(), "unreaped zombies: {:?}", zombies);
+}
+
+#[test]
+fn init_forwards_sigterm_to_entrypoint() {
+    let dir = TempDir::new().unwrap();
+    let mut init = spawn_init(&dir, "trap 'exit 42' TERM; sleep 10 & wait");
+
+    // Give sh time to install the trap
+    std::thread::sleep(Duration::from_millis(500));
+    kill(Pid::from_raw(init.id() as i32), Signal::SIGTERM).unwrap();
+
+    let status = init.wait().unwrap();
+    assert_eq!(status.code(), Some(42));
+}
+
+#[test]
+fn init_exits_with_entrypoint_status() {
+    let dir = TempDir::new().unwrap();
+
+    let status = spawn_init(&dir, "exit 3").wait().unwrap();
+    assert_eq!(status.code(), Some(3));
+
+    // Killed by a signal: 128 + signal number
+    let status = spawn_init(&dir, "kill -KILL $$").wait().unwrap();
+    assert_eq!(status.code(), Some(128 + Signal::SIGKILL as i32));
+}
@@ -32,6 +32,7 @@ import io
 import json
 import logging
 import os
+import platform
 import tarfile
 import tempfile
 import time
@@ -107,6 +108,7 @@ class CreateBoxRequest(BaseModel):
     network: Optional[str] = "isolated"
     auto_remove: Optional[bool] = True
     detach: Optional[bool] = False
+    init: Optional[bool] = False
     security: Optional[str] = None
 
 
@@ -168,6 +170,7 @@ ERROR_MAP = [
     ("already exists:", 409, "AlreadyExistsError"),
     ("invalid state:", 409, "InvalidStateError"),
     ("stopped:", 409, "StoppedError"),
+    ("busy:", 409, "BusyError"),
     ("invalid argument:", 400, "InvalidArgumentError"),
     ("configuration error:", 400, "ConfigError"),
     ("unsupported:", 400, "UnsupportedError"),
@@ -314,6 +317,8 @@ def build_box_options(req: CreateBoxRequest) -> boxlite.BoxOptions:
         kwargs["auto_remove"] = req.auto_remove
     if req.detach is not None:
         kwargs["detach"] = req.detach
+    if req.init:
+        kwargs["init"] = req.init
     if req.volumes:
         kwargs["volumes"] = [
             (v["host_path"], v["guest_path"], v.get("read_only", False))
@@ -430,6 +435,24 @@ async def get_config():
     }
 
 
+@app.get("/v1/version")
+async def get_version():
+    # The Python SDK only exposes its own version; engine components are unknown.
+    return {
+        "version": boxlite.__version__,
+        "git_commit": None,
+        "guest_hash": None,
+        "shim_path": None,
+        "shim_hash": None,
+        "libkrun": None,
+        "libkrunfw": None,
+        "bwrap": None,
+        "gvproxy": None,
+        "os": platform.system().lower(),
+        "arch": platform.machine(),
+    }
+
+
 @app.post("/v1/oauth/tokens")
 async def get_token(request: Request):
     body = await request.form()
@@ -0,0 +1,512 @@
+//! JSON shapes for the handle-based APIs.
+//!
+//! Shared by the JNI bridge and the `bl_*` C API. Field names are camelCase,
+//! matching the Java SDK's serialized DTOs.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use boxlite::{
+    BoxCommand, BoxInfo, BoxOptions, BoxliteError, BoxliteOptions, BoxliteResult, CopyOptions,
+    ExecResult, GetOrCreatePolicy, OutputEncoding, RootfsSpec, RunOnceOptions, RunOnceResult,
+    RuntimeMetricsSnapshot, ScratchSpec, VersionInfo,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::json::status_to_string;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeOptionsDto {
+    pub home_dir: Option<String>,
+    #[serde(default)]
+    pub image_registries: Vec<String>,
+    #[serde(default)]
+    pub offline: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxOptionsDto {
+    pub image: Option<String>,
+    pub rootfs_path: Option<String>,
+    pub cpus: Option<u8>,
+    pub memory_mib: Option<u32>,
+    pub disk_size_gb: Option<u64>,
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub auto_remove: Option<bool>,
+    pub detach: Option<bool>,
+    pub entrypoint: Option<Vec<String>>,
+    pub cmd: Option<Vec<String>>,
+    pub user: Option<String>,
+}
+
+/// The extra field `runtime_get_or_create` reads from its `BoxOptionsDto`
+/// JSON (snake_case policy name, default `reuse_always`).
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetOrCreateDto {
+    #[serde(default)]
+    pub get_or_create_policy: GetOrCreatePolicy,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CopyOptionsDto {
+    #[serde(default = "default_true")]
+    pub recursive: bool,
+    #[serde(default = "default_true")]
+    pub overwrite: bool,
+    #[serde(default)]
+    pub follow_symlinks: bool,
+    #[serde(default = "default_true")]
+    pub include_parent: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecCommandDto {
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub timeout_millis: Option<u64>,
+    pub working_dir: Option<String>,
+    #[serde(default)]
+    pub tty: bool,
+    pub scratch: Option<ScratchDto>,
+    #[serde(default)]
+    pub output_encoding: OutputEncoding,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScratchDto {
+    pub size_mib: u32,
+    pub mount_at: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOnceOptionsDto {
+    pub max_output_bytes: Option<usize>,
+    pub timeout_millis: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeMetricsDto {
+    pub snapshot_id: u64,
+    pub taken_at_millis: i64,
+    pub reset_epoch: u64,
+    pub boxes_created_total: u64,
+    pub boxes_failed_total: u64,
+    pub boxes_stopped_total: u64,
+    pub num_running_boxes: u64,
+    pub total_commands_executed: u64,
+    pub total_exec_errors: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionInfoDto {
+    pub version: String,
+    pub git_commit: Option<String>,
+    pub guest_hash: Option<String>,
+    pub shim_path: Option<String>,
+    pub shim_hash: Option<String>,
+    pub libkrun: Option<String>,
+    pub libkrunfw: Option<String>,
+    pub bwrap: Option<String>,
+    pub gvproxy: Option<String>,
+    pub os: String,
+    pub arch: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecResultDto {
+    pub exit_code: i32,
+    pub error_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunOnceResultDto {
+    pub exit_code: i32,
+    pub stdout: String,
+    pub stderr: String,
+    pub stdout_truncated: bool,
+    pub stderr_truncated: bool,
+    pub error_message: Option<String>,
+    pub duration_millis: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxStateInfoDto {
+    pub status: String,
+    pub running: bool,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoxInfoDto {
+    pub id: String,
+    pub name: Option<String>,
+    pub state: BoxStateInfoDto,
+    pub created_at: String,
+    pub image: String,
+    pub cpus: u8,
+    pub memory_mib: u32,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Parse a JSON argument, naming it in the error.
+pub fn parse_json<T: DeserializeOwned>(json: &str, arg_name: &str) -> BoxliteResult<T> {
+    serde_json::from_str(json)
+        .map_err(|e| BoxliteError::Config(format!("Failed to parse {arg_name} JSON: {e}")))
+}
+
+pub fn serialize_json<T: Serialize>(value: &T) -> BoxliteResult<String> {
+    serde_json::to_string(value)
+        .map_err(|e| BoxliteError::Internal(format!("Failed to serialize JSON response: {e}")))
+}
+
+impl From<RuntimeOptionsDto> for BoxliteOptions {
+    fn from(dto: RuntimeOptionsDto) -> Self {
+        let mut options = BoxliteOptions::default();
+        if let Some(home_dir) = dto.home_dir
+            && !home_dir.trim().is_empty()
+        {
+            options.home_dir = PathBuf::from(home_dir);
+        }
+        options.image_registries = dto.image_registries;
+        options.offline = dto.offline;
+        options
+    }
+}
+
+impl TryFrom<BoxOptionsDto> for BoxOptions {
+    type Error = BoxliteError;
+
+    fn try_from(dto: BoxOptionsDto) -> BoxliteResult<Self> {
+        let mut options = BoxOptions::default();
+        let image = dto.image.filter(|v| !v.trim().is_empty());
+        let rootfs_path = dto.rootfs_path.filter(|v| !v.trim().is_empty());
+
+        if image.is_some() && rootfs_path.is_some() {
+            return Err(BoxliteError::Config(
+                "BoxOptions requires exactly one of image or rootfsPath".to_string(),
+            ));
+        }
+
+        if let Some(image) = image {
+            options.rootfs = RootfsSpec::Image(image);
+        } else if let Some(rootfs_path) = rootfs_path {
+            options.rootfs = RootfsSpec::RootfsPath(rootfs_path);
+        }
+
+        options.cpus = dto.cpus;
+        options.memory_mib = dto.memory_mib;
+        options.disk_size_gb = dto.disk_size_gb;
+        options.working_dir = dto.working_dir;
+        options.env = dto.env.into_iter().collect();
+        options.auto_remove = dto.auto_remove.unwrap_or(options.auto_remove);
+        options.detach = dto.detach.unwrap_or(options.detach);
+        // Java defaults currently serialize empty lists for entrypoint/cmd.
+        // Treat empty vectors as "not set" so we don't override image config
+        // with an invalid empty entrypoint.
+        options.entrypoint = dto.entrypoint.filter(|value| !value.is_empty());
+        options.cmd = dto.cmd.filter(|value| !value.is_empty());
+        options.user = dto.user;
+        options.sanitize()?;
+        Ok(options)
+    }
+}
+
+impl From<CopyOptionsDto> for CopyOptions {
+    fn from(dto: CopyOptionsDto) -> Self {
+        CopyOptions {
+            recursive: dto.recursive,
+            overwrite: dto.overwrite,
+            follow_symlinks: dto.follow_symlinks,
+            include_parent: dto.include_parent,
+            ..Default::default()
+        }
+    }
+}
+
+impl TryFrom<ExecCommandDto> for BoxCommand {
+    type Error = BoxliteError;
+
+    fn try_from(dto: ExecCommandDto) -> BoxliteResult<Self> {
+        if dto.command.trim().is_empty() {
+            return Err(BoxliteError::InvalidArgument(
+                "command must not be null or blank".to_string(),
+            ));
+        }
+        if let Some(timeout_millis) = dto.timeout_millis
+            && timeout_millis == 0
+        {
+            return Err(BoxliteError::InvalidArgument(
+                "timeoutMillis must be > 0 when provided".to_string(),
+            ));
+        }
+
+        let mut command = BoxCommand::new(dto.command)
+            .args(dto.args)
+            .tty(dto.tty)
+            .output_encoding(dto.output_encoding);
+        if !dto.env.is_empty() {
+            for (key, value) in dto.env {
+                command = command.env(key, value);
+            }
+        }
+        if let Some(timeout_millis) = dto.timeout_millis {
+            command = command.timeout(Duration::from_millis(timeout_millis));
+        }
+        if let Some(working_dir) = dto.working_dir
+            && !working_dir.trim().is_empty()
+        {
+            command = command.working_dir(working_dir);
+        }
+        if let Some(scratch) = dto.scratch {
+            command = command.scratch_dir(ScratchSpec::new(scratch.size_mib, scratch.mount_at));
+        }
+        Ok(command)
+    }
+}
+
+impl From<RunOnceOptionsDto> for RunOnceOptions {
+    fn from(dto: RunOnceOptionsDto) -> Self {
+        let mut options = RunOnceOptions::default();
+        if let Some(max_output_bytes) = dto.max_output_bytes {
+            options.max_output_bytes(max_output_bytes);
+        }
+        if let Some(timeout_millis) = dto.timeout_millis {
+            options.timeout(Duration::from_millis(timeout_millis));
+        }
+        options
+    }
+}
+
+impl From<BoxInfo> for BoxInfoDto {
+    fn from(info: BoxInfo) -> Self {
+        BoxInfoDto {
+            id: info.id.to_string(),
+            name: info.name,
+            state: BoxStateInfoDto {
+                status: status_to_string(info.status).to_string(),
+                running: info.status.is_running(),
+                pid: info.pid,
+                exit_code: info.exit_code,
+            },
+            created_at: info.created_at.to_rfc3339(),
+            image: info.image,
+            cpus: info.cpus,
+            memory_mib: info.memory_mib,
+        }
+    }
+}
+
+impl From<RuntimeMetricsSnapshot> for RuntimeMetricsDto {
+    fn from(snapshot: RuntimeMetricsSnapshot) -> Self {
+        RuntimeMetricsDto {
+            snapshot_id: snapshot.sequence,
+            taken_at_millis: snapshot.taken_at.timestamp_millis(),
+            reset_epoch: snapshot.reset_epoch,
+            boxes_created_total: snapshot.boxes_created_total,
+            boxes_failed_total: snapshot.boxes_failed_total,
+            boxes_stopped_total: snapshot.boxes_stopped_total,
+            num_running_boxes: snapshot.num_running_boxes,
+            total_commands_executed: snapshot.total_commands_executed,
+            total_exec_errors: snapshot.total_exec_errors,
+        }
+    }
+}
+
+impl From<VersionInfo> for VersionInfoDto {
+    fn from(info: VersionInfo) -> Self {
+        VersionInfoDto {
+            version: info.version,
+            git_commit: info.git_commit,
+            guest_hash: info.guest_hash,
+            shim_path: info.shim_path.map(|p| p.to_string_lossy().into_owned()),
+            shim_hash: info.shim_hash,
+            libkrun: info.libkrun,
+            libkrunfw: info.libkrunfw,
+            bwrap: info.bwrap,
+            gvproxy: info.gvproxy,
+            os: info.os,
+            arch: info.arch,
+        }
+    }
+}
+
+impl From<ExecResult> for ExecResultDto {
+    fn from(result: ExecResult) -> Self {
+        ExecResultDto {
+            exit_code: result.exit_code,
+            error_message: result.error_message,
+        }
+    }
+}
+
+impl From<RunOnceResult> for RunOnceResultDto {
+    fn from(result: RunOnceResult) -> Self {
+        RunOnceResultDto {
+            exit_code: result.exit_code,
+            stdout: result.stdout,
+            stderr: result.stderr,
+            stdout_truncated: result.stdout_truncated,
+            stderr_truncated: result.stderr_truncated,
+            error_message: result.error_message,
+            duration_millis: result.duration.as_millis() as u64,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_entrypoint_and_cmd_do_not_override_image_defaults() {
+        let options = BoxOptions::try_from(BoxOptionsDto {
+            image: Some("alpine:latest".to_string()),
+            entrypoint: Some(Vec::new()),
+            cmd: Some(Vec::new()),
+            ..Default::default()
+        })
+        .expect("conversion should succeed");
+
+        assert!(
+            options.entrypoint.is_none(),
+            "empty entrypoint should map to None"
+        );
+        assert!(options.cmd.is_none(), "empty cmd should map to None");
+    }
+
+    #[test]
+    fn non_empty_entrypoint_and_cmd_are_preserved() {
+        let options = BoxOptions::try_from(BoxOptionsDto {
+            image: Some("alpine:latest".to_string()),
+            entrypoint: Some(vec!["/bin/sh".to_string()]),
+            cmd: Some(vec!["-lc".to_string(), "echo hi".to_string()]),
+            ..Default::default()
+        })
+        .expect("conversion should succeed");
+
+        assert_eq!(
+            options.entrypoint,
+            Some(vec!["/bin/sh".to_string()]),
+            "non-empty entrypoint should be passed through"
+        );
+        assert_eq!(
+            options.cmd,
+            Some(vec!["-lc".to_string(), "echo hi".to_string()]),
+            "non-empty cmd should be passed through"
+        );
+        assert!(
+            matches!(options.rootfs, RootfsSpec::Image(_)),
+            "image input should still map to image rootfs"
+        );
+    }
+
+    #[test]
+    fn get_or_create_policy_rides_along_box_options() {
+        let json = r#"{"image":"alpine:latest","getOrCreatePolicy":"fail_on_mismatch"}"#;
+        let dto: GetOrCreateDto = parse_json(json, "boxOptionsJson").unwrap();
+        assert_eq!(dto.get_or_create_policy, GetOrCreatePolicy::FailOnMismatch);
+        assert!(parse_json::<BoxOptionsDto>(json, "boxOptionsJson").is_ok());
+
+        let dto: GetOrCreateDto = parse_json(r#"{"image":"alpine"}"#, "boxOptionsJson").unwrap();
+        assert_eq!(dto.get_or_create_policy, GetOrCreatePolicy::ReuseAlways);
+    }
+
+    #[test]
+    fn exec_command_accepts_supported_fields() {
+        let mut env = HashMap::new();
+        env.insert("FOO".to_string(), "bar".to_string());
+
+        let result = BoxCommand::try_from(ExecCommandDto {
+            command: "sh".to_string(),
+            args: vec!["-lc".to_string(), "echo hi".to_string()],
+            env,
+            timeout_millis: Some(1_500),
+            working_dir: Some("/workspace".to_string()),
+            tty: true,
+            scratch: Some(ScratchDto {
+                size_mib: 64,
+                mount_at: "/scratch".to_string(),
+            }),
+            output_encoding: OutputEncoding::Raw,
+        });
+
+        assert!(
+            result.is_ok(),
+            "valid command with args/env/timeout/workingDir/tty/scratch should convert successfully"
+        );
+    }
+
+    #[test]
+    fn exec_command_rejects_blank_command() {
+        let result = BoxCommand::try_from(ExecCommandDto {
+            command: "   ".to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            timeout_millis: None,
+            working_dir: None,
+            tty: false,
+            scratch: None,
+            output_encoding: OutputEncoding::default(),
+        });
+
+        assert!(
+            matches!(result, Err(BoxliteError::InvalidArgument(_))),
+            "blank command should fail with InvalidArgument"
+        );
+    }
+
+    #[test]
+    fn exec_command_parses_scratch() {
+        let dto: ExecCommandDto = parse_json(
+            r#"{"command":"sh","scratch":{"sizeMib":256,"mountAt":"/scratch"}}"#,
+            "execCommandJson",
+        )
+        .unwrap();
+        let scratch = dto.scratch.expect("scratch should be parsed");
+        assert_eq!(scratch.size_mib, 256);
+        assert_eq!(scratch.mount_at, "/scratch");
+    }
+
+    #[test]
+    fn exec_command_parses_output_encoding() {
+        let dto: ExecCommandDto = parse_json(
+            r#"{"command":"cat","outputEncoding":"raw"}"#,
+            "execCommandJson",
+        )
+        .unwrap();
+        assert_eq!(dto.output_encoding, OutputEncoding::Raw);
+
+        let dto: ExecCommandDto = parse_json(r#"{"command":"cat"}"#, "execCommandJson").unwrap();
+        assert_eq!(dto.output_encoding, OutputEncoding::Utf8Lossy);
+    }
+
+    #[test]
+    fn parse_json_names_the_argument() {
+        let err = parse_json::<ExecCommandDto>("{", "execCommandJson").unwrap_err();
+        assert!(matches!(err, BoxliteError::Config(ref msg) if msg.contains("execCommandJson")));
+    }
+}
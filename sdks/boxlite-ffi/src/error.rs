@@ -59,6 +59,26 @@ pub enum BoxliteErrorCode {
     Metadata = 18,
     /// Unsupported engine error
     UnsupportedEngine = 19,
+    /// Network access refused in offline mode
+    OfflineMode = 20,
+    /// Operation exceeded its deadline
+    Timeout = 21,
+    /// Refused by a create policy
+    PolicyDenied = 22,
+    /// Another operation on the box is in progress
+    Busy = 23,
+    /// The runtime is shutting down
+    ShuttingDown = 24,
+    /// A copy stopped at its deadline before finishing
+    PartialTransfer = 25,
+    /// An archive was refused as unsafe to unpack
+    UnsafeArchive = 26,
+    /// A named box exists with different options
+    ConfigDrift = 27,
+    /// Credentials do not allow the operation
+    PermissionDenied = 28,
+    /// A copy out would write outside its host destination
+    UnsafeCopy = 29,
 }
 
 /// Extended error information for C API.
@@ -104,6 +124,16 @@ pub fn error_to_code(err: &BoxliteError) -> BoxliteErrorCode {
         BoxliteError::Rpc(_) => BoxliteErrorCode::Rpc,
         BoxliteError::RpcTransport(_) => BoxliteErrorCode::RpcTransport,
         BoxliteError::MetadataError(_) => BoxliteErrorCode::Metadata,
+        BoxliteError::OfflineMode(_) => BoxliteErrorCode::OfflineMode,
+        BoxliteError::Timeout(_) => BoxliteErrorCode::Timeout,
+        BoxliteError::PolicyDenied(_) => BoxliteErrorCode::PolicyDenied,
+        BoxliteError::Busy { .. } => BoxliteErrorCode::Busy,
+        BoxliteError::ShuttingDown(_) => BoxliteErrorCode::ShuttingDown,
+        BoxliteError::PartialTransfer(_) => BoxliteErrorCode::PartialTransfer,
+        BoxliteError::UnsafeArchive(_) => BoxliteErrorCode::UnsafeArchive,
+        BoxliteError::ConfigDrift { .. } => BoxliteErrorCode::ConfigDrift,
+        BoxliteError::PermissionDenied(_) => BoxliteErrorCode::PermissionDenied,
+        BoxliteError::UnsafeCopy(_) => BoxliteErrorCode::UnsafeCopy,
     }
 }
 
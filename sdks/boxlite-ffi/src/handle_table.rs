@@ -0,0 +1,186 @@
+//! Sharded handle tables for the handle-based APIs (JNI bridge, `bl_*` C API).
+//!
+//! Caller threads hit the tables concurrently and most calls go on to block on
+//! the Tokio runtime. To keep one slow call from stalling the others:
+//!
+//! - Each operation locks a single shard for one map operation and never hands
+//!   a guard to the caller, so no table lock can span a `block_on` or a call
+//!   back into the embedding language.
+//! - Removed values are dropped after the shard lock is released; dropping a
+//!   runtime, box or execution may block.
+//!
+//! Debug builds also count live shard guards per thread, and
+//! [`assert_no_table_guards`] panics if one is alive when entering the runtime.
+
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+const SHARDS: usize = 16;
+
+thread_local! {
+    static LIVE_GUARDS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Panic (debug builds only) if this thread holds a handle table guard.
+pub fn assert_no_table_guards(context: &str) {
+    if cfg!(debug_assertions) {
+        let live = LIVE_GUARDS.with(Cell::get);
+        assert!(
+            live == 0,
+            "{context} entered with {live} handle table guard(s) alive on this thread"
+        );
+    }
+}
+
+/// Map from handle to entry, sharded by handle.
+pub struct HandleTable<T> {
+    shards: [Mutex<HashMap<i64, T>>; SHARDS],
+}
+
+impl<T: Clone> HandleTable<T> {
+    pub fn new() -> Self {
+        Self {
+            shards: std::array::from_fn(|_| Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn insert(&self, handle: i64, value: T) {
+        let previous = self.shard(handle).insert(handle, value);
+        drop(previous);
+    }
+
+    /// Clone the entry for `handle`.
+    pub fn get(&self, handle: i64) -> Option<T> {
+        self.shard(handle).get(&handle).cloned()
+    }
+
+    pub fn contains(&self, handle: i64) -> bool {
+        self.shard(handle).contains_key(&handle)
+    }
+
+    pub fn remove(&self, handle: i64) -> Option<T> {
+        self.shard(handle).remove(&handle)
+    }
+
+    /// Remove every entry matching `predicate` and return them.
+    ///
+    /// Shards are visited one at a time; the caller drops the returned
+    /// entries with no lock held.
+    pub fn remove_where(&self, mut predicate: impl FnMut(&T) -> bool) -> Vec<T> {
+        let mut removed = Vec::new();
+        for index in 0..SHARDS {
+            let mut shard = self.lock_shard(index);
+            let handles: Vec<i64> = shard
+                .iter()
+                .filter(|(_, value)| predicate(value))
+                .map(|(handle, _)| *handle)
+                .collect();
+            removed.extend(handles.iter().filter_map(|h| shard.remove(h)));
+        }
+        removed
+    }
+
+    fn shard(&self, handle: i64) -> ShardGuard<'_, T> {
+        self.lock_shard(handle.rem_euclid(SHARDS as i64) as usize)
+    }
+
+    fn lock_shard(&self, index: usize) -> ShardGuard<'_, T> {
+        // Critical sections are single map operations and cannot leave a
+        // shard half-updated, so a poisoned lock is still consistent.
+        let guard = self.shards[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        ShardGuard::new(guard)
+    }
+}
+
+impl<T: Clone> Default for HandleTable<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Shard lock guard, counted in [`LIVE_GUARDS`] in debug builds.
+struct ShardGuard<'a, T> {
+    guard: MutexGuard<'a, HashMap<i64, T>>,
+}
+
+impl<'a, T> ShardGuard<'a, T> {
+    fn new(guard: MutexGuard<'a, HashMap<i64, T>>) -> Self {
+        if cfg!(debug_assertions) {
+            LIVE_GUARDS.with(|live| live.set(live.get() + 1));
+        }
+        Self { guard }
+    }
+}
+
+impl<T> Drop for ShardGuard<'_, T> {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            LIVE_GUARDS.with(|live| live.set(live.get() - 1));
+        }
+    }
+}
+
+impl<T> Deref for ShardGuard<'_, T> {
+    type Target = HashMap<i64, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for ShardGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.guard
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let table = HandleTable::new();
+        table.insert(1, "a");
+        table.insert(17, "b");
+        assert_eq!(table.get(1), Some("a"));
+        assert!(table.contains(17));
+        assert_eq!(table.remove(1), Some("a"));
+        assert_eq!(table.get(1), None);
+        assert_eq!(table.remove(1), None);
+    }
+
+    #[test]
+    fn remove_where_spans_shards() {
+        let table = HandleTable::new();
+        for handle in 1..=100 {
+            table.insert(handle, handle % 2);
+        }
+        let removed = table.remove_where(|value| *value == 0);
+        assert_eq!(removed.len(), 50);
+        assert!(!table.contains(2));
+        assert!(table.contains(3));
+    }
+
+    #[test]
+    fn no_guard_outlives_an_operation() {
+        let table = HandleTable::new();
+        table.insert(1, 1);
+        let _ = table.get(1);
+        table.remove_where(|_| true);
+        assert_no_table_guards("test");
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "handle table guard")]
+    fn held_guard_is_detected() {
+        let table: HandleTable<i32> = HandleTable::new();
+        let _guard = table.shard(1);
+        assert_no_table_guards("test");
+    }
+}
@@ -0,0 +1,836 @@
+//! Handle registry for the handle-based APIs.
+//!
+//! Runtimes, boxes, executions and prepared execs are kept in process-wide
+//! [`HandleTable`]s and referred to by positive integer handles, so a stale
+//! or double-freed handle is an error instead of a dangling pointer. Every
+//! dependent handle records the runtime it came from; freeing a runtime drops
+//! them all.
+//!
+//! Handles to a box and to its executions are independent, as `LiteBox` and
+//! `Execution` are: freeing or invalidating a box handle leaves the
+//! execution handles started through it working. Stopping or removing the
+//! box makes their next call fail with `Stopped`.
+//!
+//! All blocking calls enter the shared Tokio runtime through [`block_on`].
+
+use std::future::Future;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock};
+use std::time::Duration;
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use boxlite::{
+    BoxliteError, BoxliteResult, BoxliteRuntime, ExecOutputBytes, ExecStderr, ExecStdin,
+    ExecStdout, Execution, InvalidUtf8, LiteBox, PreparedExec,
+};
+use futures::{Stream, StreamExt};
+use tokio::sync::Mutex as AsyncMutex;
+
+use crate::handle_table::{HandleTable, assert_no_table_guards};
+
+static NEXT_HANDLE: AtomicI64 = AtomicI64::new(1);
+
+/// Tokio runtime shared by all handle-based calls.
+pub static TOKIO: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to build Tokio runtime for BoxLite FFI")
+});
+
+static RUNTIMES: LazyLock<HandleTable<Arc<BoxliteRuntime>>> = LazyLock::new(HandleTable::new);
+static BOXES: LazyLock<HandleTable<BoxHandleEntry>> = LazyLock::new(HandleTable::new);
+static EXECUTIONS: LazyLock<HandleTable<ExecutionHandleEntry>> = LazyLock::new(HandleTable::new);
+static PREPARED_EXECS: LazyLock<HandleTable<PreparedExecHandleEntry>> =
+    LazyLock::new(HandleTable::new);
+
+#[derive(Clone)]
+pub struct BoxHandleEntry {
+    pub runtime_handle: i64,
+    pub handle: Arc<LiteBox>,
+}
+
+#[derive(Clone)]
+pub struct ExecutionHandleEntry {
+    pub runtime_handle: i64,
+    pub id: String,
+    // Clones share state, so calls clone it instead of locking
+    pub execution: Execution,
+    pub stdin: Arc<AsyncMutex<Option<ExecStdin>>>,
+    pub stdout: Arc<AsyncMutex<Option<OutputReader>>>,
+    pub stderr: Arc<AsyncMutex<Option<OutputReader>>>,
+}
+
+/// Stdout or stderr of an execution, as text or as raw bytes depending on
+/// the command's output encoding.
+pub enum ExecOutput {
+    Stdout(ExecStdout),
+    Stderr(ExecStderr),
+    Bytes(ExecOutputBytes),
+}
+
+impl ExecOutput {
+    fn stdout(execution: &mut Execution) -> Option<Self> {
+        match execution.stdout() {
+            Some(stdout) => Some(Self::Stdout(stdout)),
+            None => execution.stdout_bytes().map(Self::Bytes),
+        }
+    }
+
+    fn stderr(execution: &mut Execution) -> Option<Self> {
+        match execution.stderr() {
+            Some(stderr) => Some(Self::Stderr(stderr)),
+            None => execution.stderr_bytes().map(Self::Bytes),
+        }
+    }
+}
+
+impl Stream for ExecOutput {
+    type Item = Vec<u8>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        match self.get_mut() {
+            Self::Stdout(stdout) => stdout
+                .poll_next_unpin(cx)
+                .map(|c| c.map(String::into_bytes)),
+            Self::Stderr(stderr) => stderr
+                .poll_next_unpin(cx)
+                .map(|c| c.map(String::into_bytes)),
+            Self::Bytes(bytes) => bytes.poll_next_unpin(cx),
+        }
+    }
+}
+
+/// Output an [`OutputReader`] reads from.
+pub trait OutputSource: Stream<Item = Vec<u8>> + Unpin {
+    /// Whether the output is raw bytes rather than decoded text.
+    fn is_raw(&self) -> bool;
+
+    /// The invalid sequence that ended strictly decoded output early.
+    fn decode_error(&self) -> Option<InvalidUtf8>;
+}
+
+impl OutputSource for ExecOutput {
+    fn is_raw(&self) -> bool {
+        matches!(self, Self::Bytes(_))
+    }
+
+    fn decode_error(&self) -> Option<InvalidUtf8> {
+        match self {
+            Self::Stdout(stdout) => stdout.decode_error(),
+            Self::Stderr(stderr) => stderr.decode_error(),
+            Self::Bytes(_) => None,
+        }
+    }
+}
+
+/// Stdout or stderr of an execution, read a line at a time (as text or as
+/// bytes) or into a caller's buffer.
+///
+/// `read_into` keeps whatever did not fit in the buffer for the next call;
+/// the line reads hand that remainder out first, so they can be mixed.
+/// Raw output has no text reads. Strictly decoded output that hit an
+/// invalid sequence fails with `Execution` where it would end.
+pub struct OutputReader<S = ExecOutput> {
+    stream: S,
+    name: &'static str,
+    pending: Vec<u8>,
+}
+
+impl<S: OutputSource> OutputReader<S> {
+    pub fn new(stream: S, name: &'static str) -> Self {
+        Self {
+            stream,
+            name,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Next line of output, or `None` at end of stream.
+    pub async fn next_line(&mut self) -> BoxliteResult<Option<String>> {
+        if self.stream.is_raw() {
+            return Err(BoxliteError::InvalidState(format!(
+                "{} uses the raw output encoding; read it as bytes",
+                self.name
+            )));
+        }
+        // Decoded chunks are valid UTF-8; only a remainder cut by
+        // `read_into` may end inside a character
+        let line = self.next_line_bytes().await?;
+        Ok(line.map(|line| String::from_utf8_lossy(&line).into_owned()))
+    }
+
+    /// Next line of output as bytes, or `None` at end of stream.
+    pub async fn next_line_bytes(&mut self) -> BoxliteResult<Option<Vec<u8>>> {
+        if !self.pending.is_empty() {
+            return Ok(Some(std::mem::take(&mut self.pending)));
+        }
+        match self.stream.next().await {
+            Some(chunk) => Ok(Some(chunk)),
+            None => self.end(),
+        }
+    }
+
+    /// Copy up to `buf.len()` bytes of output into `buf`, waiting for
+    /// output if none is buffered. Returns `None` at end of stream.
+    pub async fn read_into(&mut self, buf: &mut [u8]) -> BoxliteResult<Option<usize>> {
+        if buf.is_empty() {
+            return Ok(Some(0));
+        }
+        while self.pending.is_empty() {
+            match self.stream.next().await {
+                Some(chunk) => self.pending = chunk,
+                None => return self.end(),
+            }
+        }
+        let len = buf.len().min(self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+        Ok(Some(len))
+    }
+
+    fn end<T>(&self) -> BoxliteResult<Option<T>> {
+        match self.stream.decode_error() {
+            Some(err) => Err(BoxliteError::Execution(format!("{}: {err}", self.name))),
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct PreparedExecHandleEntry {
+    pub runtime_handle: i64,
+    pub prepared: Arc<PreparedExec>,
+}
+
+fn allocate_handle() -> i64 {
+    NEXT_HANDLE.fetch_add(1, Ordering::Relaxed)
+}
+
+fn check_handle(handle: i64, kind: &str) -> BoxliteResult<i64> {
+    if handle <= 0 {
+        return Err(BoxliteError::InvalidState(format!(
+            "{kind} handle must be positive, got {handle}"
+        )));
+    }
+    Ok(handle)
+}
+
+pub fn insert_runtime_handle(runtime: BoxliteRuntime) -> BoxliteResult<i64> {
+    let handle = allocate_handle();
+    RUNTIMES.insert(handle, Arc::new(runtime));
+    Ok(handle)
+}
+
+pub fn get_runtime(runtime_handle: i64) -> BoxliteResult<Arc<BoxliteRuntime>> {
+    let handle = check_handle(runtime_handle, "runtime")?;
+    RUNTIMES.get(handle).ok_or_else(|| {
+        BoxliteError::InvalidState(format!("runtime handle {runtime_handle} is not active"))
+    })
+}
+
+/// Free a runtime handle and every box, execution and prepared exec handle
+/// created through it.
+pub fn remove_runtime(runtime_handle: i64) -> BoxliteResult<()> {
+    let handle = check_handle(runtime_handle, "runtime")?;
+    let Some(runtime) = RUNTIMES.remove(handle) else {
+        return Err(BoxliteError::InvalidState(format!(
+            "runtime handle {runtime_handle} is not active"
+        )));
+    };
+
+    // Drop dependent handles before the runtime itself, with no table locked
+    let boxes = BOXES.remove_where(|entry| entry.runtime_handle == handle);
+    let executions = EXECUTIONS.remove_where(|entry| entry.runtime_handle == handle);
+    let prepared = PREPARED_EXECS.remove_where(|entry| entry.runtime_handle == handle);
+    let _rt = TOKIO.enter();
+    drop((prepared, executions, boxes));
+    drop(runtime);
+    Ok(())
+}
+
+fn ensure_runtime_active(runtime_handle: i64, what: &str) -> BoxliteResult<()> {
+    if RUNTIMES.contains(runtime_handle) {
+        Ok(())
+    } else {
+        Err(BoxliteError::InvalidState(format!(
+            "{what} belongs to a closed runtime"
+        )))
+    }
+}
+
+pub fn insert_box_handle(runtime_handle: i64, handle: LiteBox) -> BoxliteResult<i64> {
+    let box_handle = allocate_handle();
+    let entry = BoxHandleEntry {
+        runtime_handle,
+        handle: Arc::new(handle),
+    };
+    BOXES.insert(box_handle, entry);
+    Ok(box_handle)
+}
+
+pub fn get_box_entry(box_handle: i64) -> BoxliteResult<BoxHandleEntry> {
+    let handle = check_handle(box_handle, "box")?;
+    let entry = BOXES.get(handle).ok_or_else(|| {
+        BoxliteError::InvalidState(format!("box handle {box_handle} is not active"))
+    })?;
+    ensure_runtime_active(entry.runtime_handle, &format!("box handle {box_handle}"))?;
+    Ok(entry)
+}
+
+/// Free a box handle. Executions started through it are not affected.
+pub fn remove_box_handle(box_handle: i64) -> BoxliteResult<()> {
+    let handle = check_handle(box_handle, "box")?;
+    BOXES.remove(handle);
+    Ok(())
+}
+
+/// Drop the box handles of `runtime_handle` that refer to `id_or_name`.
+///
+/// Called once the box is removed. Its execution handles are kept: the
+/// output they already received can still be read, and their other calls
+/// fail with `Stopped`.
+pub fn invalidate_box_handles_for(runtime_handle: i64, id_or_name: &str) {
+    let removed = BOXES.remove_where(|entry| {
+        entry.runtime_handle == runtime_handle
+            && (entry.handle.id().as_str() == id_or_name || entry.handle.name() == Some(id_or_name))
+    });
+    drop(removed);
+}
+
+pub fn insert_execution_handle(
+    runtime_handle: i64,
+    mut execution: Execution,
+) -> BoxliteResult<i64> {
+    let handle = allocate_handle();
+    let id = execution.id().to_owned();
+    let stdin = execution.stdin();
+    let stdout = ExecOutput::stdout(&mut execution);
+    let stderr = ExecOutput::stderr(&mut execution);
+    let entry = ExecutionHandleEntry {
+        runtime_handle,
+        id,
+        execution,
+        stdin: Arc::new(AsyncMutex::new(stdin)),
+        stdout: Arc::new(AsyncMutex::new(
+            stdout.map(|stdout| OutputReader::new(stdout, "stdout")),
+        )),
+        stderr: Arc::new(AsyncMutex::new(
+            stderr.map(|stderr| OutputReader::new(stderr, "stderr")),
+        )),
+    };
+    EXECUTIONS.insert(handle, entry);
+    Ok(handle)
+}
+
+pub fn get_execution_entry(execution_handle: i64) -> BoxliteResult<ExecutionHandleEntry> {
+    let handle = check_handle(execution_handle, "execution")?;
+    let entry = EXECUTIONS.get(handle).ok_or_else(|| {
+        BoxliteError::InvalidState(format!("execution handle {execution_handle} is not active"))
+    })?;
+    ensure_runtime_active(
+        entry.runtime_handle,
+        &format!("execution handle {execution_handle}"),
+    )?;
+    Ok(entry)
+}
+
+pub fn remove_execution_handle(execution_handle: i64) -> BoxliteResult<()> {
+    let handle = check_handle(execution_handle, "execution")?;
+    EXECUTIONS.remove(handle);
+    Ok(())
+}
+
+pub fn insert_prepared_exec_handle(
+    runtime_handle: i64,
+    prepared: PreparedExec,
+) -> BoxliteResult<i64> {
+    let handle = allocate_handle();
+    let entry = PreparedExecHandleEntry {
+        runtime_handle,
+        prepared: Arc::new(prepared),
+    };
+    PREPARED_EXECS.insert(handle, entry);
+    Ok(handle)
+}
+
+pub fn get_prepared_exec_entry(prepared_handle: i64) -> BoxliteResult<PreparedExecHandleEntry> {
+    let handle = check_handle(prepared_handle, "prepared exec")?;
+    let entry = PREPARED_EXECS.get(handle).ok_or_else(|| {
+        BoxliteError::InvalidState(format!(
+            "prepared exec handle {prepared_handle} is not active"
+        ))
+    })?;
+    ensure_runtime_active(
+        entry.runtime_handle,
+        &format!("prepared exec handle {prepared_handle}"),
+    )?;
+    Ok(entry)
+}
+
+pub fn remove_prepared_exec_handle(prepared_handle: i64) -> BoxliteResult<()> {
+    let handle = check_handle(prepared_handle, "prepared exec")?;
+    let entry = PREPARED_EXECS.remove(handle);
+    // Dropping the last reference unregisters the template on the runtime
+    let _rt = TOKIO.enter();
+    drop(entry);
+    Ok(())
+}
+
+/// Convert a `callTimeoutMillis` argument into a deadline. `0` means no deadline.
+pub fn parse_call_timeout(call_timeout_millis: i64) -> BoxliteResult<Option<Duration>> {
+    match call_timeout_millis {
+        0 => Ok(None),
+        millis if millis < 0 => Err(BoxliteError::InvalidArgument(format!(
+            "callTimeoutMillis must be >= 0, got {millis}"
+        ))),
+        millis => Ok(Some(Duration::from_millis(millis as u64))),
+    }
+}
+
+async fn with_call_timeout<T>(
+    timeout: Option<Duration>,
+    operation: &str,
+    future: impl Future<Output = BoxliteResult<T>>,
+) -> BoxliteResult<T> {
+    let Some(timeout) = timeout else {
+        return future.await;
+    };
+
+    match tokio::time::timeout(timeout, future).await {
+        Ok(result) => result,
+        Err(_) => Err(BoxliteError::Timeout(format!(
+            "{operation} did not complete within {}ms",
+            timeout.as_millis()
+        ))),
+    }
+}
+
+/// Block the calling thread on `future`.
+///
+/// All blocking calls enter the runtime through here. Debug builds panic if
+/// the thread still holds a handle table guard.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    assert_no_table_guards("block_on");
+    TOKIO.block_on(future)
+}
+
+/// Block on `future`, dropping it when the deadline passes.
+///
+/// Use for operations that are safe to abandon midway.
+pub fn block_on_cancellable<T>(
+    timeout: Option<Duration>,
+    operation: &str,
+    future: impl Future<Output = BoxliteResult<T>>,
+) -> BoxliteResult<T> {
+    block_on(with_call_timeout(timeout, operation, future))
+}
+
+/// Run `future` as a detached task and block until it finishes or the deadline passes.
+///
+/// On timeout only the caller is released; the task keeps running to
+/// completion in the background. Use for operations that must not be torn
+/// down halfway (VM boot, shutdown).
+pub fn block_on_detached<T, F>(
+    timeout: Option<Duration>,
+    operation: &str,
+    future: F,
+) -> BoxliteResult<T>
+where
+    T: Send + 'static,
+    F: Future<Output = BoxliteResult<T>> + Send + 'static,
+{
+    let task = TOKIO.spawn(future);
+    block_on(with_call_timeout(timeout, operation, async {
+        task.await
+            .map_err(|e| BoxliteError::Internal(format!("{operation} task failed: {e}")))?
+    }))
+}
+
+/// Run `future` as a detached task and block until it finishes or the deadline passes.
+///
+/// On timeout the task keeps running, and a value it yields afterwards goes
+/// to `release` instead of the caller. Use for operations that create
+/// something the caller would otherwise have to clean up (a started
+/// process).
+pub fn block_on_releasing<T, F, R, RF>(
+    timeout: Option<Duration>,
+    operation: &str,
+    future: F,
+    release: R,
+) -> BoxliteResult<T>
+where
+    T: Send + 'static,
+    F: Future<Output = BoxliteResult<T>> + Send + 'static,
+    R: FnOnce(T) -> RF + Send + 'static,
+    RF: Future<Output = ()> + Send + 'static,
+{
+    let mut task = TOKIO.spawn(future);
+    let joined = |result: Result<BoxliteResult<T>, tokio::task::JoinError>| {
+        result.map_err(|e| BoxliteError::Internal(format!("{operation} task failed: {e}")))?
+    };
+    block_on(async {
+        let Some(timeout) = timeout else {
+            return joined(task.await);
+        };
+        match tokio::time::timeout(timeout, &mut task).await {
+            Ok(result) => joined(result),
+            Err(_) => {
+                TOKIO.spawn(async move {
+                    if let Ok(Ok(value)) = task.await {
+                        release(value).await;
+                    }
+                });
+                Err(BoxliteError::Timeout(format!(
+                    "{operation} did not complete within {}ms",
+                    timeout.as_millis()
+                )))
+            }
+        }
+    })
+}
+
+/// Run `future` as a detached task and pass its result to `on_complete`.
+///
+/// Returns immediately. `on_complete` runs on a runtime worker thread.
+pub fn spawn_with_completion<T, F, C>(future: F, on_complete: C)
+where
+    T: Send + 'static,
+    F: Future<Output = BoxliteResult<T>> + Send + 'static,
+    C: FnOnce(BoxliteResult<T>) + Send + 'static,
+{
+    TOKIO.spawn(async move { on_complete(future.await) });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::sync::mpsc;
+    use std::time::Instant;
+
+    /// Stand-in for a hung guest: completes after `delay` and records that it ran to the end.
+    async fn slow_operation(delay: Duration, finished: Arc<AtomicBool>) -> BoxliteResult<u32> {
+        tokio::time::sleep(delay).await;
+        finished.store(true, Ordering::SeqCst);
+        Ok(7)
+    }
+
+    #[test]
+    fn call_timeout_zero_means_no_deadline() {
+        assert_eq!(parse_call_timeout(0).unwrap(), None);
+        assert_eq!(
+            parse_call_timeout(1500).unwrap(),
+            Some(Duration::from_millis(1500))
+        );
+    }
+
+    #[test]
+    fn call_timeout_rejects_negative_values() {
+        let err = parse_call_timeout(-1).expect_err("negative timeout must fail");
+        assert!(matches!(err, BoxliteError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn cancellable_call_returns_result_within_deadline() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let value = block_on_cancellable(
+            Some(Duration::from_secs(5)),
+            "fast op",
+            slow_operation(Duration::from_millis(10), finished.clone()),
+        )
+        .unwrap();
+        assert_eq!(value, 7);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn cancellable_call_times_out_and_drops_operation() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let err = block_on_cancellable(
+            Some(Duration::from_millis(20)),
+            "slow op",
+            slow_operation(Duration::from_millis(200), finished.clone()),
+        )
+        .expect_err("slow op must time out");
+        assert!(matches!(err, BoxliteError::Timeout(ref msg) if msg.contains("slow op")));
+
+        std::thread::sleep(Duration::from_millis(300));
+        assert!(
+            !finished.load(Ordering::SeqCst),
+            "operation should be cancelled"
+        );
+    }
+
+    #[test]
+    fn detached_call_times_out_but_operation_completes() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let err = block_on_detached(
+            Some(Duration::from_millis(20)),
+            "slow op",
+            slow_operation(Duration::from_millis(100), finished.clone()),
+        )
+        .expect_err("slow op must time out");
+        assert!(matches!(err, BoxliteError::Timeout(_)));
+        assert!(!finished.load(Ordering::SeqCst));
+
+        block_on(tokio::time::sleep(Duration::from_millis(300)));
+        assert!(
+            finished.load(Ordering::SeqCst),
+            "operation should keep running"
+        );
+    }
+
+    #[test]
+    fn no_deadline_waits_for_completion() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let value = block_on_detached(
+            None,
+            "slow op",
+            slow_operation(Duration::from_millis(50), finished.clone()),
+        )
+        .unwrap();
+        assert_eq!(value, 7);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    /// Stand-in for a started process: records whether it was killed.
+    struct MockExecution {
+        killed: Arc<AtomicBool>,
+    }
+
+    /// Stand-in for a slow exec backend: spawns the process after `delay`.
+    async fn slow_exec(delay: Duration, killed: Arc<AtomicBool>) -> BoxliteResult<MockExecution> {
+        tokio::time::sleep(delay).await;
+        Ok(MockExecution { killed })
+    }
+
+    async fn kill(execution: MockExecution) {
+        execution.killed.store(true, Ordering::SeqCst);
+    }
+
+    #[test]
+    fn releasing_call_returns_value_within_deadline() {
+        let killed = Arc::new(AtomicBool::new(false));
+        let execution = block_on_releasing(
+            Some(Duration::from_secs(5)),
+            "box exec",
+            slow_exec(Duration::from_millis(10), killed.clone()),
+            kill,
+        )
+        .unwrap();
+        assert!(!execution.killed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn releasing_call_kills_execution_started_after_timeout() {
+        let killed = Arc::new(AtomicBool::new(false));
+        let err = block_on_releasing(
+            Some(Duration::from_millis(20)),
+            "box exec",
+            slow_exec(Duration::from_millis(100), killed.clone()),
+            kill,
+        )
+        .err()
+        .expect("slow exec must time out");
+        assert!(matches!(err, BoxliteError::Timeout(ref msg) if msg.contains("box exec")));
+        assert!(!killed.load(Ordering::SeqCst));
+
+        block_on(tokio::time::sleep(Duration::from_millis(300)));
+        assert!(
+            killed.load(Ordering::SeqCst),
+            "execution started after the timeout should be killed"
+        );
+    }
+
+    #[test]
+    fn releasing_call_without_deadline_waits() {
+        let killed = Arc::new(AtomicBool::new(false));
+        let execution = block_on_releasing(
+            None,
+            "box exec",
+            slow_exec(Duration::from_millis(50), killed.clone()),
+            kill,
+        )
+        .unwrap();
+        assert!(!execution.killed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn completion_receives_result() {
+        let finished = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        spawn_with_completion(
+            slow_operation(Duration::from_millis(10), finished.clone()),
+            move |result| tx.send(result).unwrap(),
+        );
+        let result = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(result.unwrap(), 7);
+        assert!(finished.load(Ordering::SeqCst));
+    }
+
+    /// Chunks ending in an optional decode error, for reader tests.
+    struct TestOutput {
+        chunks: futures::stream::Iter<std::vec::IntoIter<Vec<u8>>>,
+        raw: bool,
+        error: Option<InvalidUtf8>,
+    }
+
+    impl TestOutput {
+        fn new(chunks: &[&[u8]], raw: bool, error: Option<InvalidUtf8>) -> Self {
+            let chunks: Vec<Vec<u8>> = chunks.iter().map(|c| c.to_vec()).collect();
+            Self {
+                chunks: futures::stream::iter(chunks),
+                raw,
+                error,
+            }
+        }
+    }
+
+    impl Stream for TestOutput {
+        type Item = Vec<u8>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Vec<u8>>> {
+            self.chunks.poll_next_unpin(cx)
+        }
+    }
+
+    impl OutputSource for TestOutput {
+        fn is_raw(&self) -> bool {
+            self.raw
+        }
+
+        fn decode_error(&self) -> Option<InvalidUtf8> {
+            self.error.clone()
+        }
+    }
+
+    #[test]
+    fn stdout_reader_splits_chunks_across_reads() {
+        let stream = TestOutput::new(&[b"hello\n", b"world\n"], false, None);
+        let mut reader = OutputReader::new(stream, "stdout");
+        let mut buf = [0u8; 4];
+        block_on(async {
+            assert_eq!(reader.read_into(&mut buf).await.unwrap(), Some(4));
+            assert_eq!(&buf, b"hell");
+            assert_eq!(reader.read_into(&mut buf).await.unwrap(), Some(2));
+            assert_eq!(&buf[..2], b"o\n");
+            assert_eq!(reader.read_into(&mut buf).await.unwrap(), Some(4));
+            // The rest of a partly read chunk comes out as a line
+            assert_eq!(reader.next_line().await.unwrap().as_deref(), Some("d\n"));
+            assert_eq!(reader.read_into(&mut buf).await.unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn raw_output_reads_only_as_bytes() {
+        let stream = TestOutput::new(&[b"caf\xe9\n", b"\xff"], true, None);
+        let mut reader = OutputReader::new(stream, "stdout");
+        block_on(async {
+            let err = reader.next_line().await.unwrap_err();
+            assert!(matches!(err, BoxliteError::InvalidState(ref msg) if msg.contains("raw")));
+            let line = reader.next_line_bytes().await.unwrap();
+            assert_eq!(line.as_deref(), Some(&b"caf\xe9\n"[..]));
+            let line = reader.next_line_bytes().await.unwrap();
+            assert_eq!(line.as_deref(), Some(&b"\xff"[..]));
+            assert_eq!(reader.next_line_bytes().await.unwrap(), None);
+        });
+    }
+
+    #[test]
+    fn strict_decode_error_fails_at_end_of_stream() {
+        let error = InvalidUtf8 {
+            offset: 5,
+            bytes: vec![0xe9],
+        };
+        let stream = TestOutput::new(&[b"gr\xc3\xbcn"], false, Some(error));
+        let mut reader = OutputReader::new(stream, "stderr");
+        block_on(async {
+            assert_eq!(reader.next_line().await.unwrap().as_deref(), Some("grün"));
+            let err = reader.next_line().await.unwrap_err();
+            assert!(
+                matches!(err, BoxliteError::Execution(ref msg) if msg.starts_with("stderr: invalid UTF-8 at byte 5"))
+            );
+        });
+    }
+
+    #[test]
+    fn stale_handles_are_rejected() {
+        let err = get_box_entry(0).err().unwrap();
+        assert!(matches!(err, BoxliteError::InvalidState(ref msg) if msg.contains("positive")));
+
+        let err = get_execution_entry(i64::MAX).err().unwrap();
+        assert!(matches!(err, BoxliteError::InvalidState(ref msg) if msg.contains("not active")));
+    }
+
+    /// 64 threads churn create/exec/free cycles through a shared table, each
+    /// entering the runtime between table operations, while a sweeper removes
+    /// entries concurrently (like closing a runtime). Must finish promptly and
+    /// never trip the held-guard assertion in `block_on`.
+    #[test]
+    fn handle_table_survives_concurrent_create_exec_free() {
+        const THREADS: usize = 64;
+        const CYCLES: usize = 200;
+
+        let table: Arc<HandleTable<Arc<AtomicUsize>>> = Arc::new(HandleTable::new());
+        let next_handle = Arc::new(AtomicI64::new(1));
+        let execs = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicBool::new(false));
+        let started = Instant::now();
+
+        let sweeper = {
+            let table = table.clone();
+            let done = done.clone();
+            std::thread::spawn(move || {
+                while !done.load(Ordering::SeqCst) {
+                    let removed = table.remove_where(|runs| runs.load(Ordering::SeqCst) > 1);
+                    drop(removed);
+                    std::thread::yield_now();
+                }
+            })
+        };
+
+        let workers: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let table = table.clone();
+                let next_handle = next_handle.clone();
+                let execs = execs.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..CYCLES {
+                        // create
+                        let handle = next_handle.fetch_add(1, Ordering::Relaxed);
+                        table.insert(handle, Arc::new(AtomicUsize::new(0)));
+
+                        // exec: look up, then block on the runtime without a table lock
+                        for _ in 0..3 {
+                            let Some(runs) = table.get(handle) else { break };
+                            block_on(async {
+                                tokio::task::yield_now().await;
+                                runs.fetch_add(1, Ordering::SeqCst);
+                            });
+                            execs.fetch_add(1, Ordering::Relaxed);
+                        }
+
+                        // free (the sweeper may have beaten us to it)
+                        let _ = table.remove(handle);
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().expect("worker panicked");
+        }
+        done.store(true, Ordering::SeqCst);
+        sweeper.join().expect("sweeper panicked");
+
+        assert!(execs.load(Ordering::Relaxed) >= THREADS * CYCLES);
+        assert!(
+            started.elapsed() < Duration::from_secs(30),
+            "handle table churn stalled: {:?}",
+            started.elapsed()
+        );
+    }
+}
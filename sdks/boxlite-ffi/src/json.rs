@@ -6,13 +6,7 @@ use boxlite::runtime::types::{BoxInfo, BoxStatus};
 
 /// Convert BoxStatus to string representation
 pub fn status_to_string(status: BoxStatus) -> &'static str {
-    match status {
-        BoxStatus::Unknown => "unknown",
-        BoxStatus::Configured => "configured",
-        BoxStatus::Running => "running",
-        BoxStatus::Stopping => "stopping",
-        BoxStatus::Stopped => "stopped",
-    }
+    status.as_str()
 }
 
 /// Convert BoxInfo to JSON with nested state structure
@@ -23,11 +17,13 @@ pub fn box_info_to_json(info: &BoxInfo) -> serde_json::Value {
         "state": {
             "status": status_to_string(info.status),
             "running": info.status.is_running(),
-            "pid": info.pid
+            "pid": info.pid,
+            "exit_code": info.exit_code
         },
         "created_at": info.created_at.to_rfc3339(),
         "image": info.image,
         "cpus": info.cpus,
-        "memory_mib": info.memory_mib
+        "memory_mib": info.memory_mib,
+        "network": info.network
     })
 }
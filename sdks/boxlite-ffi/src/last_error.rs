@@ -0,0 +1,114 @@
+//! Thread-local last error for the `bl_*` C API.
+//!
+//! Every `bl_*` call clears the slot on entry and fills it on failure, so
+//! `bl_last_error()` always describes the most recent call on the calling
+//! thread. The message stays valid until that thread's next `bl_*` call.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::os::raw::c_char;
+use std::ptr;
+
+use boxlite::{BoxliteError, BoxliteResult};
+
+use crate::error::{BoxliteErrorCode, error_to_code};
+
+struct LastError {
+    code: BoxliteErrorCode,
+    message: CString,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<LastError>> = const { RefCell::new(None) };
+}
+
+pub fn clear_last_error() {
+    LAST_ERROR.with(|slot| slot.borrow_mut().take());
+}
+
+/// Record `err` as this thread's last error and return its code.
+pub fn set_last_error(err: &BoxliteError) -> BoxliteErrorCode {
+    let code = error_to_code(err);
+    let message =
+        CString::new(err.to_string().replace('\0', " ")).expect("NUL bytes were replaced");
+    LAST_ERROR.with(|slot| *slot.borrow_mut() = Some(LastError { code, message }));
+    code
+}
+
+/// Code of this thread's last error, `Ok` if the last call succeeded.
+pub fn last_error_code() -> BoxliteErrorCode {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(BoxliteErrorCode::Ok, |e| e.code)
+    })
+}
+
+/// Message of this thread's last error, NULL if the last call succeeded.
+///
+/// Owned by the thread-local slot; valid until the next `bl_*` call on
+/// this thread.
+pub fn last_error_message() -> *const c_char {
+    LAST_ERROR.with(|slot| {
+        slot.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |e| e.message.as_ptr())
+    })
+}
+
+/// Run one `bl_*` call: clear the last error, run `f`, and on failure record
+/// the error and return `on_error`.
+pub fn with_last_error<T>(on_error: T, f: impl FnOnce() -> BoxliteResult<T>) -> T {
+    clear_last_error();
+    match f() {
+        Ok(value) => value,
+        Err(err) => {
+            set_last_error(&err);
+            on_error
+        }
+    }
+}
+
+/// [`with_last_error`] for calls that only report a status code.
+pub fn status(f: impl FnOnce() -> BoxliteResult<()>) -> BoxliteErrorCode {
+    clear_last_error();
+    match f() {
+        Ok(()) => BoxliteErrorCode::Ok,
+        Err(err) => set_last_error(&err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn failure_sets_and_success_clears() {
+        let value = with_last_error(0, || Err(BoxliteError::NotFound("box x".into())));
+        assert_eq!(value, 0);
+        assert_eq!(last_error_code(), BoxliteErrorCode::NotFound);
+        let message = unsafe { CStr::from_ptr(last_error_message()) };
+        assert!(message.to_str().unwrap().contains("box x"));
+
+        assert_eq!(status(|| Ok(())), BoxliteErrorCode::Ok);
+        assert_eq!(last_error_code(), BoxliteErrorCode::Ok);
+        assert!(last_error_message().is_null());
+    }
+
+    #[test]
+    fn last_error_is_per_thread() {
+        set_last_error(&BoxliteError::Internal("main".into()));
+        std::thread::spawn(|| assert!(last_error_message().is_null()))
+            .join()
+            .unwrap();
+        assert_eq!(last_error_code(), BoxliteErrorCode::Internal);
+    }
+
+    #[test]
+    fn nul_in_message_is_replaced() {
+        set_last_error(&BoxliteError::Internal("a\0b".into()));
+        let message = unsafe { CStr::from_ptr(last_error_message()) };
+        assert!(message.to_str().unwrap().ends_with("a b"));
+    }
+}
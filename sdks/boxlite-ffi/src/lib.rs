@@ -6,9 +6,15 @@
 //! - JSON serialization helpers
 //! - Runtime management (Tokio + BoxliteRuntime)
 //! - Core FFI operations implementation
+//! - Handle-based operations shared by the JNI bridge and the `bl_*` C API
 
+pub mod dto;
 pub mod error;
+pub mod handle_table;
+pub mod handles;
 pub mod json;
+pub mod last_error;
+pub mod natives;
 pub mod ops;
 pub mod runner;
 pub mod runtime;
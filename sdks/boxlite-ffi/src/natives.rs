@@ -0,0 +1,511 @@
+//! Handle-based operations shared by the JNI bridge and the `bl_*` C API.
+//!
+//! Inputs and outputs are plain Rust values plus the JSON shapes in
+//! [`crate::dto`]; each binding only converts its own argument types and
+//! maps errors (exceptions for Java, `bl_last_error()` for C).
+//!
+//! Long operations also come as `*_future` functions. They validate handles
+//! and arguments up front and return a `'static` future, so a binding can
+//! either block on it or hand it to [`crate::handles::spawn_with_completion`].
+
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use boxlite::{
+    BoxCommand, BoxOptions, BoxliteError, BoxliteOptions, BoxliteResult, BoxliteRuntime,
+    GetOrCreateOutcome, validate,
+};
+
+use crate::dto::{
+    BoxInfoDto, BoxOptionsDto, CopyOptionsDto, ExecCommandDto, ExecResultDto, GetOrCreateDto,
+    RunOnceOptionsDto, RunOnceResultDto, RuntimeMetricsDto, RuntimeOptionsDto, VersionInfoDto,
+    parse_json, serialize_json,
+};
+use crate::handles::{
+    block_on, block_on_cancellable, block_on_detached, block_on_releasing, get_box_entry,
+    get_execution_entry, get_prepared_exec_entry, get_runtime, insert_box_handle,
+    insert_execution_handle, insert_prepared_exec_handle, insert_runtime_handle,
+    invalidate_box_handles_for,
+};
+
+/// Version of the `bl_*` C ABI. Bumped on any incompatible change to a
+/// function signature or JSON shape.
+pub const ABI_VERSION: u32 = 1;
+
+// ============================================================================
+// Runtime
+// ============================================================================
+
+pub fn runtime_new(options_json: &str) -> BoxliteResult<i64> {
+    let dto: RuntimeOptionsDto = parse_json(options_json, "optionsJson")?;
+    let runtime = BoxliteRuntime::new(BoxliteOptions::from(dto))?;
+    insert_runtime_handle(runtime)
+}
+
+pub fn runtime_default() -> BoxliteResult<i64> {
+    if BoxliteRuntime::try_default_runtime().is_none()
+        && let Err(err) = BoxliteRuntime::init_default_runtime(BoxliteOptions::default())
+        && BoxliteRuntime::try_default_runtime().is_none()
+    {
+        return Err(err);
+    }
+
+    let Some(runtime) = BoxliteRuntime::try_default_runtime() else {
+        return Err(BoxliteError::Internal(
+            "Default runtime is not initialized".into(),
+        ));
+    };
+    insert_runtime_handle(runtime.clone())
+}
+
+pub fn runtime_init_default(options_json: &str) -> BoxliteResult<()> {
+    let dto: RuntimeOptionsDto = parse_json(options_json, "optionsJson")?;
+    BoxliteRuntime::init_default_runtime(BoxliteOptions::from(dto))
+}
+
+/// Create a box; resolves to the new box handle.
+pub fn runtime_create_future(
+    runtime_handle: i64,
+    box_options_json: &str,
+    name: Option<String>,
+) -> BoxliteResult<impl Future<Output = BoxliteResult<i64>> + Send + 'static> {
+    let runtime = get_runtime(runtime_handle)?;
+    let dto: BoxOptionsDto = parse_json(box_options_json, "boxOptionsJson")?;
+    let options = BoxOptions::try_from(dto)?;
+    Ok(async move {
+        let handle = runtime.create(options, name).await?;
+        insert_box_handle(runtime_handle, handle)
+    })
+}
+
+pub fn runtime_create(
+    runtime_handle: i64,
+    box_options_json: &str,
+    name: Option<String>,
+) -> BoxliteResult<i64> {
+    block_on(runtime_create_future(
+        runtime_handle,
+        box_options_json,
+        name,
+    )?)
+}
+
+/// Returns the box handle and how it was obtained.
+///
+/// The options JSON may carry a `getOrCreatePolicy` field, see
+/// [`GetOrCreateDto`].
+pub fn runtime_get_or_create(
+    runtime_handle: i64,
+    box_options_json: &str,
+    name: Option<String>,
+) -> BoxliteResult<(i64, GetOrCreateOutcome)> {
+    let runtime = get_runtime(runtime_handle)?;
+    let dto: BoxOptionsDto = parse_json(box_options_json, "boxOptionsJson")?;
+    let policy =
+        parse_json::<GetOrCreateDto>(box_options_json, "boxOptionsJson")?.get_or_create_policy;
+    let options = BoxOptions::try_from(dto)?;
+    let (handle, outcome) = block_on(runtime.get_or_create_with(options, name, policy))?;
+    Ok((insert_box_handle(runtime_handle, handle)?, outcome))
+}
+
+pub fn runtime_get(runtime_handle: i64, id_or_name: &str) -> BoxliteResult<Option<i64>> {
+    let runtime = get_runtime(runtime_handle)?;
+    match block_on(runtime.get(id_or_name))? {
+        Some(handle) => insert_box_handle(runtime_handle, handle).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn runtime_get_info(runtime_handle: i64, id_or_name: &str) -> BoxliteResult<Option<String>> {
+    let runtime = get_runtime(runtime_handle)?;
+    match block_on(runtime.get_info(id_or_name))? {
+        Some(info) => serialize_json(&BoxInfoDto::from(info)).map(Some),
+        None => Ok(None),
+    }
+}
+
+pub fn runtime_list_info(runtime_handle: i64) -> BoxliteResult<String> {
+    let runtime = get_runtime(runtime_handle)?;
+    let infos = block_on(runtime.list_info())?;
+    let mapped = infos.into_iter().map(BoxInfoDto::from).collect::<Vec<_>>();
+    serialize_json(&mapped)
+}
+
+/// Remove a box and invalidate this runtime's handles to it.
+pub fn runtime_remove(runtime_handle: i64, id_or_name: &str, force: bool) -> BoxliteResult<()> {
+    let runtime = get_runtime(runtime_handle)?;
+    block_on(runtime.remove(id_or_name, force))?;
+    invalidate_box_handles_for(runtime_handle, id_or_name);
+    Ok(())
+}
+
+pub fn runtime_metrics(runtime_handle: i64) -> BoxliteResult<String> {
+    let runtime = get_runtime(runtime_handle)?;
+    let snapshot = block_on(runtime.metrics_snapshot())?;
+    serialize_json(&RuntimeMetricsDto::from(snapshot))
+}
+
+pub fn runtime_version_info(runtime_handle: i64) -> BoxliteResult<String> {
+    let runtime = get_runtime(runtime_handle)?;
+    let info = block_on(runtime.version_info())?;
+    serialize_json(&VersionInfoDto::from(info))
+}
+
+pub fn runtime_shutdown(
+    runtime_handle: i64,
+    timeout_seconds: Option<i32>,
+    call_timeout: Option<Duration>,
+) -> BoxliteResult<()> {
+    let runtime = get_runtime(runtime_handle)?;
+    // Detached: shutdown keeps stopping boxes after the caller gives up.
+    block_on_detached(call_timeout, "runtime shutdown", async move {
+        runtime.shutdown(timeout_seconds).await
+    })
+}
+
+pub fn runtime_run_once(
+    runtime_handle: i64,
+    box_options_json: &str,
+    exec_command_json: &str,
+    run_once_options_json: &str,
+) -> BoxliteResult<String> {
+    let runtime = get_runtime(runtime_handle)?;
+    let box_options: BoxOptionsDto = parse_json(box_options_json, "boxOptionsJson")?;
+    let command: ExecCommandDto = parse_json(exec_command_json, "execCommandJson")?;
+    let run_once_options: RunOnceOptionsDto =
+        parse_json(run_once_options_json, "runOnceOptionsJson")?;
+    let result = block_on(runtime.run_once(
+        BoxOptions::try_from(box_options)?,
+        BoxCommand::try_from(command)?,
+        run_once_options.into(),
+    ))?;
+    serialize_json(&RunOnceResultDto::from(result))
+}
+
+// ============================================================================
+// Box
+// ============================================================================
+
+pub fn box_id(box_handle: i64) -> BoxliteResult<String> {
+    Ok(get_box_entry(box_handle)?.handle.id().to_string())
+}
+
+pub fn box_name(box_handle: i64) -> BoxliteResult<Option<String>> {
+    Ok(get_box_entry(box_handle)?
+        .handle
+        .name()
+        .map(ToOwned::to_owned))
+}
+
+pub fn box_info(box_handle: i64) -> BoxliteResult<String> {
+    let entry = get_box_entry(box_handle)?;
+    serialize_json(&BoxInfoDto::from(entry.handle.info()))
+}
+
+pub fn box_start_future(
+    box_handle: i64,
+) -> BoxliteResult<impl Future<Output = BoxliteResult<()>> + Send + 'static> {
+    let entry = get_box_entry(box_handle)?;
+    Ok(async move { entry.handle.start().await })
+}
+
+pub fn box_start(box_handle: i64, call_timeout: Option<Duration>) -> BoxliteResult<()> {
+    // Detached: the VM keeps booting after a timeout; start is idempotent,
+    // so calling it again resumes waiting on the same box.
+    block_on_detached(call_timeout, "box start", box_start_future(box_handle)?)
+}
+
+pub fn box_stop_future(
+    box_handle: i64,
+) -> BoxliteResult<impl Future<Output = BoxliteResult<()>> + Send + 'static> {
+    let entry = get_box_entry(box_handle)?;
+    Ok(async move { entry.handle.stop().await })
+}
+
+pub fn box_stop(box_handle: i64, call_timeout: Option<Duration>) -> BoxliteResult<()> {
+    // Detached: the stop runs to completion; stop is idempotent and safe to retry.
+    block_on_detached(call_timeout, "box stop", box_stop_future(box_handle)?)
+}
+
+/// Start a command; resolves to the new execution handle.
+pub fn box_exec_future(
+    box_handle: i64,
+    exec_command_json: &str,
+) -> BoxliteResult<impl Future<Output = BoxliteResult<i64>> + Send + 'static> {
+    let entry = get_box_entry(box_handle)?;
+    let dto: ExecCommandDto = parse_json(exec_command_json, "execCommandJson")?;
+    let command = BoxCommand::try_from(dto)?;
+    Ok(async move {
+        let execution = entry.handle.exec(command).await?;
+        insert_execution_handle(entry.runtime_handle, execution)
+    })
+}
+
+pub fn box_exec(
+    box_handle: i64,
+    exec_command_json: &str,
+    call_timeout: Option<Duration>,
+) -> BoxliteResult<i64> {
+    let entry = get_box_entry(box_handle)?;
+    let dto: ExecCommandDto = parse_json(exec_command_json, "execCommandJson")?;
+    let command = BoxCommand::try_from(dto)?;
+    let runtime_handle = entry.runtime_handle;
+    // Timed out: the exec keeps going, and a process the guest spawns after
+    // the deadline is killed, since no handle to it is returned.
+    let execution = block_on_releasing(
+        call_timeout,
+        "box exec",
+        async move { entry.handle.exec(command).await },
+        |mut execution| async move {
+            // Best effort: a process that already exited needs no kill
+            let _ = execution.kill().await;
+        },
+    )?;
+    insert_execution_handle(runtime_handle, execution)
+}
+
+pub fn box_prepare_exec(box_handle: i64, exec_command_json: &str) -> BoxliteResult<i64> {
+    let entry = get_box_entry(box_handle)?;
+    let dto: ExecCommandDto = parse_json(exec_command_json, "execCommandJson")?;
+    let prepared = block_on(entry.handle.prepare_exec(BoxCommand::try_from(dto)?))?;
+    insert_prepared_exec_handle(entry.runtime_handle, prepared)
+}
+
+pub fn prepared_exec_run(prepared_handle: i64, args_json: &str) -> BoxliteResult<i64> {
+    let entry = get_prepared_exec_entry(prepared_handle)?;
+    let args: Vec<String> = parse_json(args_json, "argsJson")?;
+    let execution = block_on(entry.prepared.run(args))?;
+    insert_execution_handle(entry.runtime_handle, execution)
+}
+
+/// Check a guest path argument, see [`validate::guest_path`].
+pub fn check_container_path(path: &str, arg_name: &str) -> BoxliteResult<()> {
+    name_argument(validate::guest_path(path), arg_name)
+}
+
+/// Check a host path argument and convert it to a native path, see
+/// [`validate::host_path`].
+pub fn check_host_path(path: &str, arg_name: &str) -> BoxliteResult<PathBuf> {
+    name_argument(validate::host_path(path), arg_name)
+}
+
+/// Prefix an invalid-argument error with the argument's name.
+fn name_argument<T>(result: BoxliteResult<T>, arg_name: &str) -> BoxliteResult<T> {
+    result.map_err(|e| match e {
+        BoxliteError::InvalidArgument(msg) => {
+            BoxliteError::InvalidArgument(format!("Invalid {arg_name}: {msg}"))
+        }
+        other => other,
+    })
+}
+
+pub fn box_copy_in(
+    box_handle: i64,
+    host_path: &str,
+    container_dest: &str,
+    copy_options_json: &str,
+    call_timeout: Option<Duration>,
+) -> BoxliteResult<()> {
+    let entry = get_box_entry(box_handle)?;
+    let host_path = check_host_path(host_path, "hostPath")?;
+    check_container_path(container_dest, "containerDest")?;
+    let copy_options: CopyOptionsDto = parse_json(copy_options_json, "copyOptionsJson")?;
+    // Cancelled: the transfer stops and may leave a partial copy at the destination.
+    block_on_cancellable(
+        call_timeout,
+        "copy in",
+        entry
+            .handle
+            .copy_into(&host_path, container_dest, copy_options.into()),
+    )
+}
+
+pub fn box_copy_out(
+    box_handle: i64,
+    container_src: &str,
+    host_dest: &str,
+    copy_options_json: &str,
+    call_timeout: Option<Duration>,
+) -> BoxliteResult<()> {
+    let entry = get_box_entry(box_handle)?;
+    check_container_path(container_src, "containerSrc")?;
+    let host_dest = check_host_path(host_dest, "hostDest")?;
+    let copy_options: CopyOptionsDto = parse_json(copy_options_json, "copyOptionsJson")?;
+    // Cancelled: the transfer stops and may leave a partial copy at the destination.
+    block_on_cancellable(
+        call_timeout,
+        "copy out",
+        entry
+            .handle
+            .copy_out(container_src, &host_dest, copy_options.into()),
+    )
+}
+
+// ============================================================================
+// Execution
+// ============================================================================
+
+pub fn execution_id(execution_handle: i64) -> BoxliteResult<String> {
+    Ok(get_execution_entry(execution_handle)?.id)
+}
+
+pub fn execution_stdin_write(execution_handle: i64, data: &[u8]) -> BoxliteResult<()> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stdin_guard = entry.stdin.lock().await;
+        let stdin = stdin_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stdin is not available for this execution".to_string())
+        })?;
+        stdin.write(data).await
+    })
+}
+
+pub fn execution_stdin_close(execution_handle: i64) -> BoxliteResult<()> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stdin_guard = entry.stdin.lock().await;
+        let stdin = stdin_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stdin is not available for this execution".to_string())
+        })?;
+        stdin.close();
+        Ok(())
+    })
+}
+
+/// Next stdout line, or `None` at end of stream.
+pub fn execution_stdout_next_line(execution_handle: i64) -> BoxliteResult<Option<String>> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stdout_guard = entry.stdout.lock().await;
+        let stdout = stdout_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stdout is not available for this execution".to_string())
+        })?;
+        stdout.next_line().await
+    })
+}
+
+/// Next stdout line as bytes, or `None` at end of stream. Works with every
+/// output encoding.
+pub fn execution_stdout_next_line_bytes(execution_handle: i64) -> BoxliteResult<Option<Vec<u8>>> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stdout_guard = entry.stdout.lock().await;
+        let stdout = stdout_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stdout is not available for this execution".to_string())
+        })?;
+        stdout.next_line_bytes().await
+    })
+}
+
+/// Copy up to `buf.len()` bytes of stdout into `buf`; the byte count, or
+/// `None` at end of stream.
+pub fn execution_stdout_read_into(
+    execution_handle: i64,
+    buf: &mut [u8],
+) -> BoxliteResult<Option<usize>> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stdout_guard = entry.stdout.lock().await;
+        let stdout = stdout_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stdout is not available for this execution".to_string())
+        })?;
+        stdout.read_into(buf).await
+    })
+}
+
+/// Next stderr line, or `None` at end of stream.
+pub fn execution_stderr_next_line(execution_handle: i64) -> BoxliteResult<Option<String>> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stderr_guard = entry.stderr.lock().await;
+        let stderr = stderr_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stderr is not available for this execution".to_string())
+        })?;
+        stderr.next_line().await
+    })
+}
+
+/// Next stderr line as bytes, like [`execution_stdout_next_line_bytes`].
+pub fn execution_stderr_next_line_bytes(execution_handle: i64) -> BoxliteResult<Option<Vec<u8>>> {
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(async {
+        let mut stderr_guard = entry.stderr.lock().await;
+        let stderr = stderr_guard.as_mut().ok_or_else(|| {
+            BoxliteError::InvalidState("stderr is not available for this execution".to_string())
+        })?;
+        stderr.next_line_bytes().await
+    })
+}
+
+/// Wait for the execution to exit; resolves to the result JSON.
+pub fn execution_wait_future(
+    execution_handle: i64,
+) -> BoxliteResult<impl Future<Output = BoxliteResult<String>> + Send + 'static> {
+    let mut execution = get_execution_entry(execution_handle)?.execution;
+    Ok(async move {
+        let result = execution.wait().await?;
+        serialize_json(&ExecResultDto::from(result))
+    })
+}
+
+pub fn execution_wait(
+    execution_handle: i64,
+    call_timeout: Option<Duration>,
+) -> BoxliteResult<String> {
+    // Cancelled: only the wait is abandoned; the process keeps running and
+    // wait can be called again.
+    block_on_cancellable(
+        call_timeout,
+        "execution wait",
+        execution_wait_future(execution_handle)?,
+    )
+}
+
+pub fn execution_kill(execution_handle: i64) -> BoxliteResult<()> {
+    let mut execution = get_execution_entry(execution_handle)?.execution;
+    block_on(execution.kill())
+}
+
+pub fn execution_resize_tty(execution_handle: i64, rows: i32, cols: i32) -> BoxliteResult<()> {
+    if rows <= 0 || cols <= 0 {
+        return Err(BoxliteError::InvalidArgument(
+            "rows and cols must both be > 0".to_string(),
+        ));
+    }
+    let entry = get_execution_entry(execution_handle)?;
+    block_on(entry.execution.resize_tty(rows as u32, cols as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resize_tty_validates_before_lookup() {
+        let err = execution_resize_tty(i64::MAX, 0, 80).unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidArgument(_)));
+    }
+
+    #[test]
+    fn exec_on_stale_box_handle_fails_before_parsing() {
+        let err = box_exec(i64::MAX, "not json", None).unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidState(ref msg) if msg.contains("not active")));
+    }
+
+    #[test]
+    fn container_path_error_names_the_argument() {
+        let err = check_container_path("relative/path", "containerDest").unwrap_err();
+        assert!(
+            matches!(err, BoxliteError::InvalidArgument(ref msg) if msg.contains("containerDest"))
+        );
+        assert_eq!(validate::error_code(&err), Some("guest_path.relative"));
+    }
+
+    #[test]
+    fn host_path_error_names_the_argument() {
+        let err = check_host_path("", "hostDest").unwrap_err();
+        assert!(matches!(err, BoxliteError::InvalidArgument(ref msg) if msg.contains("hostDest")));
+        assert_eq!(validate::error_code(&err), Some("host_path.empty"));
+    }
+}
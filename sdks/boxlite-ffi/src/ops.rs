@@ -1372,10 +1372,11 @@ mod tests {
     #[test]
     fn test_status_to_string_mapping() {
         assert_eq!(status_to_string(BoxStatus::Unknown), "unknown");
-        assert_eq!(status_to_string(BoxStatus::Configured), "configured");
+        assert_eq!(status_to_string(BoxStatus::Created), "created");
         assert_eq!(status_to_string(BoxStatus::Running), "running");
         assert_eq!(status_to_string(BoxStatus::Stopping), "stopping");
-        assert_eq!(status_to_string(BoxStatus::Stopped), "stopped");
+        assert_eq!(status_to_string(BoxStatus::Exited), "exited");
+        assert_eq!(status_to_string(BoxStatus::Dead), "dead");
     }
 
     #[test]
@@ -54,6 +54,26 @@ typedef enum BoxliteErrorCode {
   Metadata = 18,
   // Unsupported engine error
   UnsupportedEngine = 19,
+  // Network access refused in offline mode
+  OfflineMode = 20,
+  // Operation exceeded its deadline
+  Timeout = 21,
+  // Refused by a create policy
+  PolicyDenied = 22,
+  // Another operation on the box is in progress
+  Busy = 23,
+  // The runtime is shutting down
+  ShuttingDown = 24,
+  // A copy stopped at its deadline before finishing
+  PartialTransfer = 25,
+  // An archive was refused as unsafe to unpack
+  UnsafeArchive = 26,
+  // A named box exists with different options
+  ConfigDrift = 27,
+  // Credentials do not allow the operation
+  PermissionDenied = 28,
+  // A copy out would write outside its host destination
+  UnsafeCopy = 29,
 } BoxliteErrorCode;
 
 // Opaque handle to a running box
@@ -93,6 +113,20 @@ typedef struct ExecResult {
 
 typedef struct ExecResult CBoxliteExecResult;
 
+// Handle to a runtime, box or execution. `0` means "no handle".
+typedef int64_t BlHandle;
+
+// Completion callback for `bl_*_async` calls.
+//
+// On success `code` is `Ok`, `handle` is the new handle (if the call
+// creates one) and `payload` is the result JSON (if the call returns one),
+// otherwise NULL. On failure `payload` is the error message. `payload` is
+// only valid for the duration of the callback.
+typedef void (*BlCompletion)(void *user_data,
+                             enum BoxliteErrorCode code,
+                             BlHandle handle,
+                             const char *payload);
+
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
@@ -450,6 +484,153 @@ void boxlite_free_string(char *s);
 // * `error` - Pointer to `CBoxliteError` to free.
 void boxlite_error_free(CBoxliteError *error);
 
+// Version of the `bl_*` ABI. Bindings should refuse to load a library
+// whose version differs from the one they were built against.
+uint32_t bl_abi_version(void);
+
+// Message of the last failed `bl_*` call on this thread, or NULL if the
+// last call succeeded.
+//
+// Owned by the library; valid until the next `bl_*` call on this thread.
+const char *bl_last_error(void);
+
+// Error code of the last `bl_*` call on this thread.
+enum BoxliteErrorCode bl_last_error_code(void);
+
+// Free a string returned by a `bl_*` function. NULL is ignored.
+void bl_string_free(char *s);
+
+// Create a runtime.
+//
+// # Arguments
+// * `options_json` - Runtime options, e.g. `{"homeDir": "/tmp/bl"}`. NULL uses defaults.
+//
+// # Returns
+// The runtime handle, or `0` on error.
+BlHandle bl_runtime_new(const char *options_json);
+
+// Free a runtime handle and every box and execution handle created
+// through it.
+enum BoxliteErrorCode bl_runtime_free(BlHandle runtime);
+
+// Create a box.
+//
+// # Arguments
+// * `runtime` - Runtime handle.
+// * `box_options_json` - Box options, e.g. `{"image": "alpine:latest"}`.
+// * `name` - Optional box name, or NULL.
+//
+// # Returns
+// The box handle, or `0` on error.
+BlHandle bl_runtime_create(BlHandle runtime, const char *box_options_json, const char *name);
+
+// Asynchronous `bl_runtime_create`. The callback receives the box handle.
+enum BoxliteErrorCode bl_runtime_create_async(BlHandle runtime,
+                                              const char *box_options_json,
+                                              const char *name,
+                                              BlCompletion callback,
+                                              void *user_data);
+
+// Look up a box by ID or name.
+//
+// # Returns
+// The box handle, or `0` with a `NotFound` error if no such box exists.
+BlHandle bl_runtime_get(BlHandle runtime, const char *id_or_name);
+
+// List all boxes as a JSON array of box info objects.
+//
+// # Returns
+// A JSON string to free with `bl_string_free`, or NULL on error.
+char *bl_runtime_list_info(BlHandle runtime);
+
+// Remove a box. Handles to it from this runtime become invalid; its
+// execution handles stay valid and fail with `Stopped` from then on.
+enum BoxliteErrorCode bl_runtime_remove(BlHandle runtime, const char *id_or_name, bool force);
+
+// Stop all boxes of a runtime.
+//
+// # Arguments
+// * `timeout_secs` - Per-box stop timeout. `0` uses the default, `-1` waits forever.
+enum BoxliteErrorCode bl_runtime_shutdown(BlHandle runtime, int timeout_secs);
+
+// Free a box handle. The box itself is not stopped or removed, and
+// executions started through the handle keep working.
+enum BoxliteErrorCode bl_box_free(BlHandle box_handle);
+
+// Box ID. Free with `bl_string_free`; NULL on error.
+char *bl_box_id(BlHandle box_handle);
+
+// Box info as JSON. Free with `bl_string_free`; NULL on error.
+char *bl_box_info(BlHandle box_handle);
+
+// Start a box and wait until it is running.
+enum BoxliteErrorCode bl_box_start(BlHandle box_handle);
+
+// Asynchronous `bl_box_start`.
+enum BoxliteErrorCode bl_box_start_async(BlHandle box_handle,
+                                         BlCompletion callback,
+                                         void *user_data);
+
+// Stop a box.
+enum BoxliteErrorCode bl_box_stop(BlHandle box_handle);
+
+// Asynchronous `bl_box_stop`.
+enum BoxliteErrorCode bl_box_stop_async(BlHandle box_handle,
+                                        BlCompletion callback,
+                                        void *user_data);
+
+// Start a command in a box.
+//
+// # Arguments
+// * `box_handle` - Box handle.
+// * `exec_command_json` - Command, e.g. `{"command": "ls", "args": ["-l"]}`.
+//
+// # Returns
+// The execution handle, or `0` on error.
+BlHandle bl_box_exec(BlHandle box_handle, const char *exec_command_json);
+
+// Asynchronous `bl_box_exec`. The callback receives the execution handle.
+enum BoxliteErrorCode bl_box_exec_async(BlHandle box_handle,
+                                        const char *exec_command_json,
+                                        BlCompletion callback,
+                                        void *user_data);
+
+// Free an execution handle. The process is not killed.
+enum BoxliteErrorCode bl_execution_free(BlHandle execution);
+
+// Write `len` bytes from `data` to the process's stdin.
+enum BoxliteErrorCode bl_execution_stdin_write(BlHandle execution,
+                                               const uint8_t *data,
+                                               size_t len);
+
+// Close the process's stdin.
+enum BoxliteErrorCode bl_execution_stdin_close(BlHandle execution);
+
+// Next line of stdout, blocking until one is available.
+//
+// # Returns
+// The line (free with `bl_string_free`), or NULL at end of stream. On
+// error also NULL, with `bl_last_error_code()` set.
+char *bl_execution_stdout_next_line(BlHandle execution);
+
+// Next line of stderr. Same contract as `bl_execution_stdout_next_line`.
+char *bl_execution_stderr_next_line(BlHandle execution);
+
+// Wait for the process to exit.
+//
+// # Returns
+// Result JSON such as `{"exitCode": 0, "errorMessage": null}` (free with
+// `bl_string_free`), or NULL on error.
+char *bl_execution_wait(BlHandle execution);
+
+// Asynchronous `bl_execution_wait`. The callback receives the result JSON.
+enum BoxliteErrorCode bl_execution_wait_async(BlHandle execution,
+                                              BlCompletion callback,
+                                              void *user_data);
+
+// Kill the process.
+enum BoxliteErrorCode bl_execution_kill(BlHandle execution);
+
 #ifdef __cplusplus
 }  // extern "C"
 #endif  // __cplusplus
@@ -0,0 +1,501 @@
+//! Handle-based `bl_*` C API
+//!
+//! The same operations as the Java SDK, for bindings that want plain integer
+//! handles, JSON in/out and asynchronous completion callbacks (Python-style
+//! async SDKs, Go, Swift, ...).
+//!
+//! Conventions:
+//! - Objects are referred to by positive `BlHandle`s; `0` is never valid. A
+//!   freed or stale handle is reported as an error, never dereferenced.
+//! - Failing calls return `0`/NULL or an error code and record a message
+//!   readable with `bl_last_error()` on the same thread.
+//! - Strings returned by the library must be freed with `bl_string_free`.
+//! - `*_async` calls return immediately and report through a `BlCompletion`
+//!   callback, invoked exactly once on a library worker thread.
+
+#![allow(unsafe_op_in_unsafe_fn)]
+#![allow(clippy::missing_safety_doc)]
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+
+use boxlite::{BoxliteError, BoxliteResult};
+use boxlite_ffi::error::{BoxliteErrorCode, error_to_code};
+use boxlite_ffi::handles::{
+    remove_box_handle, remove_execution_handle, remove_runtime, spawn_with_completion,
+};
+use boxlite_ffi::last_error::{last_error_code, last_error_message, status, with_last_error};
+use boxlite_ffi::natives;
+use boxlite_ffi::string::alloc_c_string;
+
+/// Handle to a runtime, box or execution. `0` means "no handle".
+pub type BlHandle = i64;
+
+/// Completion callback for `bl_*_async` calls.
+///
+/// On success `code` is `Ok`, `handle` is the new handle (if the call
+/// creates one) and `payload` is the result JSON (if the call returns one),
+/// otherwise NULL. On failure `payload` is the error message. `payload` is
+/// only valid for the duration of the callback.
+pub type BlCompletion = Option<
+    extern "C" fn(
+        user_data: *mut c_void,
+        code: BoxliteErrorCode,
+        handle: BlHandle,
+        payload: *const c_char,
+    ),
+>;
+
+/// `user_data` is owned by the caller, who guarantees it may be used from
+/// the worker thread that runs the callback.
+struct UserData(*mut c_void);
+
+unsafe impl Send for UserData {}
+
+/// Result of an async call, reduced to what the callback receives.
+enum Completion {
+    Unit,
+    Handle(BlHandle),
+    Json(String),
+}
+
+impl From<()> for Completion {
+    fn from(_: ()) -> Self {
+        Completion::Unit
+    }
+}
+
+impl From<i64> for Completion {
+    fn from(handle: i64) -> Self {
+        Completion::Handle(handle)
+    }
+}
+
+impl From<String> for Completion {
+    fn from(json: String) -> Self {
+        Completion::Json(json)
+    }
+}
+
+fn deliver(callback: BlCompletion, user_data: UserData, result: BoxliteResult<Completion>) {
+    let Some(callback) = callback else {
+        return;
+    };
+    let (code, handle, payload) = match result {
+        Ok(Completion::Unit) => (BoxliteErrorCode::Ok, 0, None),
+        Ok(Completion::Handle(handle)) => (BoxliteErrorCode::Ok, handle, None),
+        Ok(Completion::Json(json)) => (BoxliteErrorCode::Ok, 0, Some(json)),
+        Err(err) => (error_to_code(&err), 0, Some(err.to_string())),
+    };
+    let payload = payload.map(|text| CString::new(text.replace('\0', " ")).unwrap_or_default());
+    callback(
+        user_data.0,
+        code,
+        handle,
+        payload.as_ref().map_or(ptr::null(), |text| text.as_ptr()),
+    );
+}
+
+/// Validate up front, then run the future on the shared runtime and report
+/// through `callback`. Validation errors are returned synchronously and the
+/// callback is not invoked.
+fn start_async<T, F>(
+    callback: BlCompletion,
+    user_data: *mut c_void,
+    future: BoxliteResult<F>,
+) -> BoxliteErrorCode
+where
+    T: Into<Completion> + Send + 'static,
+    F: std::future::Future<Output = BoxliteResult<T>> + Send + 'static,
+{
+    let user_data = UserData(user_data);
+    status(|| {
+        let future = future?;
+        spawn_with_completion(future, move |result| {
+            deliver(callback, user_data, result.map(Into::into))
+        });
+        Ok(())
+    })
+}
+
+unsafe fn required_str<'a>(ptr: *const c_char, arg_name: &str) -> BoxliteResult<&'a str> {
+    if ptr.is_null() {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "{arg_name} must not be NULL"
+        )));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|e| BoxliteError::InvalidArgument(format!("Invalid {arg_name}: {e}")))
+}
+
+unsafe fn optional_string(ptr: *const c_char, arg_name: &str) -> BoxliteResult<Option<String>> {
+    if ptr.is_null() {
+        return Ok(None);
+    }
+    Ok(Some(required_str(ptr, arg_name)?.to_owned()))
+}
+
+unsafe fn json_or_empty<'a>(ptr: *const c_char, arg_name: &str) -> BoxliteResult<&'a str> {
+    if ptr.is_null() {
+        Ok("{}")
+    } else {
+        required_str(ptr, arg_name)
+    }
+}
+
+fn to_c_string(value: String) -> BoxliteResult<*mut c_char> {
+    let ptr = alloc_c_string(&value);
+    if ptr.is_null() {
+        return Err(BoxliteError::Internal(
+            "Result string contains a NUL byte".to_string(),
+        ));
+    }
+    Ok(ptr)
+}
+
+// ============================================================================
+// Library
+// ============================================================================
+
+/// Version of the `bl_*` ABI. Bindings should refuse to load a library
+/// whose version differs from the one they were built against.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_abi_version() -> u32 {
+    natives::ABI_VERSION
+}
+
+/// Message of the last failed `bl_*` call on this thread, or NULL if the
+/// last call succeeded.
+///
+/// Owned by the library; valid until the next `bl_*` call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_last_error() -> *const c_char {
+    last_error_message()
+}
+
+/// Error code of the last `bl_*` call on this thread.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_last_error_code() -> BoxliteErrorCode {
+    last_error_code()
+}
+
+/// Free a string returned by a `bl_*` function. NULL is ignored.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_string_free(s: *mut c_char) {
+    boxlite_ffi::ops::string_free(s)
+}
+
+// ============================================================================
+// Runtime
+// ============================================================================
+
+/// Create a runtime.
+///
+/// # Arguments
+/// * `options_json` - Runtime options, e.g. `{"homeDir": "/tmp/bl"}`. NULL uses defaults.
+///
+/// # Returns
+/// The runtime handle, or `0` on error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_runtime_new(options_json: *const c_char) -> BlHandle {
+    with_last_error(0, || {
+        natives::runtime_new(json_or_empty(options_json, "options_json")?)
+    })
+}
+
+/// Free a runtime handle and every box and execution handle created
+/// through it.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_runtime_free(runtime: BlHandle) -> BoxliteErrorCode {
+    status(|| remove_runtime(runtime))
+}
+
+/// Create a box.
+///
+/// # Arguments
+/// * `runtime` - Runtime handle.
+/// * `box_options_json` - Box options, e.g. `{"image": "alpine:latest"}`.
+/// * `name` - Optional box name, or NULL.
+///
+/// # Returns
+/// The box handle, or `0` on error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_runtime_create(
+    runtime: BlHandle,
+    box_options_json: *const c_char,
+    name: *const c_char,
+) -> BlHandle {
+    with_last_error(0, || {
+        natives::runtime_create(
+            runtime,
+            required_str(box_options_json, "box_options_json")?,
+            optional_string(name, "name")?,
+        )
+    })
+}
+
+/// Asynchronous `bl_runtime_create`. The callback receives the box handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_runtime_create_async(
+    runtime: BlHandle,
+    box_options_json: *const c_char,
+    name: *const c_char,
+    callback: BlCompletion,
+    user_data: *mut c_void,
+) -> BoxliteErrorCode {
+    let future = (|| {
+        natives::runtime_create_future(
+            runtime,
+            required_str(box_options_json, "box_options_json")?,
+            optional_string(name, "name")?,
+        )
+    })();
+    start_async(callback, user_data, future)
+}
+
+/// Look up a box by ID or name.
+///
+/// # Returns
+/// The box handle, or `0` with a `NotFound` error if no such box exists.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_runtime_get(runtime: BlHandle, id_or_name: *const c_char) -> BlHandle {
+    with_last_error(0, || {
+        let id_or_name = required_str(id_or_name, "id_or_name")?;
+        natives::runtime_get(runtime, id_or_name)?
+            .ok_or_else(|| BoxliteError::NotFound(format!("box {id_or_name}")))
+    })
+}
+
+/// List all boxes as a JSON array of box info objects.
+///
+/// # Returns
+/// A JSON string to free with `bl_string_free`, or NULL on error.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_runtime_list_info(runtime: BlHandle) -> *mut c_char {
+    with_last_error(ptr::null_mut(), || {
+        to_c_string(natives::runtime_list_info(runtime)?)
+    })
+}
+
+/// Remove a box. Handles to it from this runtime become invalid; its
+/// execution handles stay valid and fail with `Stopped` from then on.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_runtime_remove(
+    runtime: BlHandle,
+    id_or_name: *const c_char,
+    force: bool,
+) -> BoxliteErrorCode {
+    status(|| natives::runtime_remove(runtime, required_str(id_or_name, "id_or_name")?, force))
+}
+
+/// Stop all boxes of a runtime.
+///
+/// # Arguments
+/// * `timeout_secs` - Per-box stop timeout. `0` uses the default, `-1` waits forever.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_runtime_shutdown(runtime: BlHandle, timeout_secs: c_int) -> BoxliteErrorCode {
+    let timeout = if timeout_secs == 0 {
+        None
+    } else {
+        Some(timeout_secs)
+    };
+    status(|| natives::runtime_shutdown(runtime, timeout, None))
+}
+
+// ============================================================================
+// Box
+// ============================================================================
+
+/// Free a box handle. The box itself is not stopped or removed, and
+/// executions started through the handle keep working.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_free(box_handle: BlHandle) -> BoxliteErrorCode {
+    status(|| remove_box_handle(box_handle))
+}
+
+/// Box ID. Free with `bl_string_free`; NULL on error.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_id(box_handle: BlHandle) -> *mut c_char {
+    with_last_error(ptr::null_mut(), || {
+        to_c_string(natives::box_id(box_handle)?)
+    })
+}
+
+/// Box info as JSON. Free with `bl_string_free`; NULL on error.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_info(box_handle: BlHandle) -> *mut c_char {
+    with_last_error(ptr::null_mut(), || {
+        to_c_string(natives::box_info(box_handle)?)
+    })
+}
+
+/// Start a box and wait until it is running.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_start(box_handle: BlHandle) -> BoxliteErrorCode {
+    status(|| natives::box_start(box_handle, None))
+}
+
+/// Asynchronous `bl_box_start`.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_start_async(
+    box_handle: BlHandle,
+    callback: BlCompletion,
+    user_data: *mut c_void,
+) -> BoxliteErrorCode {
+    start_async(callback, user_data, natives::box_start_future(box_handle))
+}
+
+/// Stop a box.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_stop(box_handle: BlHandle) -> BoxliteErrorCode {
+    status(|| natives::box_stop(box_handle, None))
+}
+
+/// Asynchronous `bl_box_stop`.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_box_stop_async(
+    box_handle: BlHandle,
+    callback: BlCompletion,
+    user_data: *mut c_void,
+) -> BoxliteErrorCode {
+    start_async(callback, user_data, natives::box_stop_future(box_handle))
+}
+
+/// Start a command in a box.
+///
+/// # Arguments
+/// * `box_handle` - Box handle.
+/// * `exec_command_json` - Command, e.g. `{"command": "ls", "args": ["-l"]}`.
+///
+/// # Returns
+/// The execution handle, or `0` on error.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_box_exec(
+    box_handle: BlHandle,
+    exec_command_json: *const c_char,
+) -> BlHandle {
+    with_last_error(0, || {
+        natives::box_exec(
+            box_handle,
+            required_str(exec_command_json, "exec_command_json")?,
+            None,
+        )
+    })
+}
+
+/// Asynchronous `bl_box_exec`. The callback receives the execution handle.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_box_exec_async(
+    box_handle: BlHandle,
+    exec_command_json: *const c_char,
+    callback: BlCompletion,
+    user_data: *mut c_void,
+) -> BoxliteErrorCode {
+    let future = (|| {
+        natives::box_exec_future(
+            box_handle,
+            required_str(exec_command_json, "exec_command_json")?,
+        )
+    })();
+    start_async(callback, user_data, future)
+}
+
+// ============================================================================
+// Execution
+// ============================================================================
+
+/// Free an execution handle. The process is not killed.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_free(execution: BlHandle) -> BoxliteErrorCode {
+    status(|| remove_execution_handle(execution))
+}
+
+/// Write `len` bytes from `data` to the process's stdin.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn bl_execution_stdin_write(
+    execution: BlHandle,
+    data: *const u8,
+    len: usize,
+) -> BoxliteErrorCode {
+    status(|| {
+        if data.is_null() && len > 0 {
+            return Err(BoxliteError::InvalidArgument(
+                "data must not be NULL".to_string(),
+            ));
+        }
+        let bytes = if len == 0 {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(data, len)
+        };
+        natives::execution_stdin_write(execution, bytes)
+    })
+}
+
+/// Close the process's stdin.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_stdin_close(execution: BlHandle) -> BoxliteErrorCode {
+    status(|| natives::execution_stdin_close(execution))
+}
+
+/// Next line of stdout, blocking until one is available.
+///
+/// # Returns
+/// The line (free with `bl_string_free`), or NULL at end of stream. On
+/// error also NULL, with `bl_last_error_code()` set.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_stdout_next_line(execution: BlHandle) -> *mut c_char {
+    with_last_error(
+        ptr::null_mut(),
+        || match natives::execution_stdout_next_line(execution)? {
+            Some(line) => to_c_string(line),
+            None => Ok(ptr::null_mut()),
+        },
+    )
+}
+
+/// Next line of stderr. Same contract as `bl_execution_stdout_next_line`.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_stderr_next_line(execution: BlHandle) -> *mut c_char {
+    with_last_error(
+        ptr::null_mut(),
+        || match natives::execution_stderr_next_line(execution)? {
+            Some(line) => to_c_string(line),
+            None => Ok(ptr::null_mut()),
+        },
+    )
+}
+
+/// Wait for the process to exit.
+///
+/// # Returns
+/// Result JSON such as `{"exitCode": 0, "errorMessage": null}` (free with
+/// `bl_string_free`), or NULL on error.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_wait(execution: BlHandle) -> *mut c_char {
+    with_last_error(ptr::null_mut(), || {
+        to_c_string(natives::execution_wait(execution, None)?)
+    })
+}
+
+/// Asynchronous `bl_execution_wait`. The callback receives the result JSON.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_wait_async(
+    execution: BlHandle,
+    callback: BlCompletion,
+    user_data: *mut c_void,
+) -> BoxliteErrorCode {
+    start_async(
+        callback,
+        user_data,
+        natives::execution_wait_future(execution),
+    )
+}
+
+/// Kill the process.
+#[unsafe(no_mangle)]
+pub extern "C" fn bl_execution_kill(execution: BlHandle) -> BoxliteErrorCode {
+    status(|| natives::execution_kill(execution))
+}
@@ -3,7 +3,9 @@
 //! This crate provides C FFI bindings for the BoxLite runtime,
 //! building the C shared library and static library artifacts.
 
+pub mod bl;
 pub mod ffi;
 
 // Re-export all FFI symbols
+pub use bl::*;
 pub use ffi::*;
@@ -0,0 +1,91 @@
+/**
+ * BoxLite C SDK - Handle API Tests
+ *
+ * Tests the handle-based bl_* API: ABI version, last error, handle lifecycle
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <assert.h>
+#include "boxlite.h"
+
+void test_abi_version() {
+    printf("\nTEST: ABI version\n");
+
+    assert(bl_abi_version() == 1);
+
+    printf("  ✓ ABI version is %u\n", bl_abi_version());
+}
+
+void test_stale_handle_sets_last_error() {
+    printf("\nTEST: Stale handle sets last error\n");
+
+    char* id = bl_box_id(123456789);
+    assert(id == NULL);
+    assert(bl_last_error_code() == InvalidState);
+    assert(bl_last_error() != NULL);
+    assert(strstr(bl_last_error(), "not active") != NULL);
+    printf("  ✓ Stale handle error: %s\n", bl_last_error());
+
+    assert(bl_box_id(0) == NULL);
+    assert(bl_last_error_code() == InvalidState);
+    printf("  ✓ Zero handle rejected\n");
+}
+
+void test_runtime_lifecycle() {
+    printf("\nTEST: Runtime new/free\n");
+
+    BlHandle runtime = bl_runtime_new("{\"homeDir\": \"/tmp/boxlite-test-bl-api\"}");
+    assert(runtime > 0);
+    assert(bl_last_error() == NULL);
+    printf("  ✓ Runtime created: %lld\n", (long long)runtime);
+
+    char* list = bl_runtime_list_info(runtime);
+    assert(list != NULL);
+    assert(list[0] == '[');
+    bl_string_free(list);
+    printf("  ✓ Listed boxes\n");
+
+    assert(bl_runtime_free(runtime) == Ok);
+    assert(bl_runtime_free(runtime) == InvalidState);
+    assert(bl_runtime_list_info(runtime) == NULL);
+    printf("  ✓ Freed runtime handle is rejected\n");
+}
+
+void test_invalid_arguments() {
+    printf("\nTEST: Invalid arguments\n");
+
+    assert(bl_runtime_new("{invalid}") == 0);
+    assert(bl_last_error_code() == Config);
+    printf("  ✓ Invalid JSON: %s\n", bl_last_error());
+
+    BlHandle runtime = bl_runtime_new("{\"homeDir\": \"/tmp/boxlite-test-bl-api\"}");
+    assert(runtime > 0);
+    assert(bl_runtime_create(runtime, NULL, NULL) == 0);
+    assert(bl_last_error_code() == InvalidArgument);
+    printf("  ✓ NULL options: %s\n", bl_last_error());
+
+    assert(bl_runtime_get(runtime, "no-such-box") == 0);
+    assert(bl_last_error_code() == NotFound);
+    printf("  ✓ Missing box: %s\n", bl_last_error());
+
+    bl_runtime_free(runtime);
+}
+
+int main() {
+    printf("═══════════════════════════════════════\n");
+    printf("  BoxLite C SDK - Handle API Tests\n");
+    printf("═══════════════════════════════════════\n");
+
+    test_abi_version();
+    test_stale_handle_sets_last_error();
+    test_runtime_lifecycle();
+    test_invalid_arguments();
+
+    printf("\n═══════════════════════════════════════\n");
+    printf("  ✅ ALL TESTS PASSED (%d tests)\n", 4);
+    printf("═══════════════════════════════════════\n");
+
+    return 0;
+}
@@ -1,159 +1,38 @@
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicI64, Ordering};
-use std::sync::{Arc, Mutex, MutexGuard};
-use std::time::Duration;
-
-use boxlite::{
-    BoxCommand, BoxInfo, BoxOptions, BoxStatus, BoxliteError, BoxliteOptions, BoxliteResult,
-    BoxliteRuntime, CopyOptions, ExecResult, ExecStderr, ExecStdin, ExecStdout, Execution, LiteBox,
-    RootfsSpec,
+//! JNI bridge for the Java SDK.
+//!
+//! Handles, JSON shapes and the operations themselves live in
+//! [`boxlite_ffi::natives`], shared with the `bl_*` C API; this crate only
+//! converts JNI arguments and turns errors into Java exceptions.
+
+use boxlite::{BoxliteError, BoxliteResult, GetOrCreateOutcome};
+use boxlite_ffi::handles::{
+    parse_call_timeout, remove_box_handle, remove_execution_handle, remove_prepared_exec_handle,
+    remove_runtime,
 };
-use futures::StreamExt;
+use boxlite_ffi::natives;
 use jni::JNIEnv;
-use jni::objects::{JByteArray, JClass, JObject, JString};
-use jni::sys::{jboolean, jint, jlong, jlongArray, jstring};
-use once_cell::sync::Lazy;
-use serde::de::DeserializeOwned;
-use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex as AsyncMutex;
-
-const ABI_VERSION: jint = 2;
-
-static NEXT_HANDLE: AtomicI64 = AtomicI64::new(1);
-static TOKIO: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()
-        .expect("Failed to build Tokio runtime for Java JNI bridge")
-});
-static RUNTIMES: Lazy<Mutex<HashMap<i64, Arc<BoxliteRuntime>>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
-static BOXES: Lazy<Mutex<HashMap<i64, BoxHandleEntry>>> = Lazy::new(|| Mutex::new(HashMap::new()));
-static EXECUTIONS: Lazy<Mutex<HashMap<i64, ExecutionHandleEntry>>> =
-    Lazy::new(|| Mutex::new(HashMap::new()));
-
-#[derive(Clone)]
-struct BoxHandleEntry {
-    runtime_handle: i64,
-    handle: Arc<LiteBox>,
-}
-
-#[derive(Clone)]
-struct ExecutionHandleEntry {
-    runtime_handle: i64,
-    id: String,
-    execution: Arc<AsyncMutex<Execution>>,
-    stdin: Arc<AsyncMutex<Option<ExecStdin>>>,
-    stdout: Arc<AsyncMutex<Option<ExecStdout>>>,
-    stderr: Arc<AsyncMutex<Option<ExecStderr>>>,
-}
+use jni::objects::{JByteArray, JByteBuffer, JClass, JObject, JString};
+use jni::sys::{jboolean, jbyteArray, jint, jlong, jlongArray, jstring};
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaRuntimeOptions {
-    home_dir: Option<String>,
-    #[serde(default)]
-    image_registries: Vec<String>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaBoxOptions {
-    image: Option<String>,
-    rootfs_path: Option<String>,
-    cpus: Option<u8>,
-    memory_mib: Option<u32>,
-    disk_size_gb: Option<u64>,
-    working_dir: Option<String>,
-    #[serde(default)]
-    env: HashMap<String, String>,
-    auto_remove: Option<bool>,
-    detach: Option<bool>,
-    entrypoint: Option<Vec<String>>,
-    cmd: Option<Vec<String>>,
-    user: Option<String>,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaCopyOptions {
-    #[serde(default = "default_true")]
-    recursive: bool,
-    #[serde(default = "default_true")]
-    overwrite: bool,
-    #[serde(default)]
-    follow_symlinks: bool,
-    #[serde(default = "default_true")]
-    include_parent: bool,
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaExecCommand {
-    command: String,
-    #[serde(default)]
-    args: Vec<String>,
-    #[serde(default)]
-    env: HashMap<String, String>,
-    timeout_millis: Option<u64>,
-    working_dir: Option<String>,
-    #[serde(default)]
-    tty: bool,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaRuntimeMetrics {
-    boxes_created_total: u64,
-    boxes_failed_total: u64,
-    boxes_stopped_total: u64,
-    num_running_boxes: u64,
-    total_commands_executed: u64,
-    total_exec_errors: u64,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaExecResult {
-    exit_code: i32,
-    error_message: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaBoxStateInfo {
-    status: String,
-    running: bool,
-    pid: Option<u32>,
-}
-
-#[derive(Debug, Serialize)]
-#[serde(rename_all = "camelCase")]
-struct JavaBoxInfo {
-    id: String,
-    name: Option<String>,
-    state: JavaBoxStateInfo,
-    created_at: String,
-    image: String,
-    cpus: u8,
-    memory_mib: u32,
-}
-
-fn default_true() -> bool {
-    true
-}
+const ABI_VERSION: jint = 5;
 
 fn throw_boxlite_error(env: &mut JNIEnv<'_>, err: BoxliteError) {
     let class = match &err {
         BoxliteError::NotFound(_) => "io/boxlite/NotFoundException",
         BoxliteError::AlreadyExists(_) => "io/boxlite/AlreadyExistsException",
-        BoxliteError::InvalidState(_) | BoxliteError::Stopped(_) => {
-            "io/boxlite/InvalidStateException"
-        }
+        BoxliteError::InvalidState(_)
+        | BoxliteError::Stopped(_)
+        | BoxliteError::ShuttingDown(_)
+        | BoxliteError::Busy { .. }
+        | BoxliteError::ConfigDrift { .. } => "io/boxlite/InvalidStateException",
         BoxliteError::Config(_)
         | BoxliteError::InvalidArgument(_)
-        | BoxliteError::Unsupported(_) => "io/boxlite/ConfigException",
+        | BoxliteError::Unsupported(_)
+        | BoxliteError::UnsafeArchive(_)
+        | BoxliteError::UnsafeCopy(_) => "io/boxlite/ConfigException",
+        BoxliteError::Timeout(_) | BoxliteError::PartialTransfer(_) => {
+            "io/boxlite/TimeoutException"
+        }
         _ => "io/boxlite/InternalException",
     };
     let _ = env.throw_new(class, err.to_string());
@@ -163,220 +42,6 @@ fn throw_internal(env: &mut JNIEnv<'_>, message: impl AsRef<str>) {
     let _ = env.throw_new("io/boxlite/InternalException", message.as_ref());
 }
 
-fn status_to_string(status: BoxStatus) -> String {
-    match status {
-        BoxStatus::Unknown => "unknown",
-        BoxStatus::Configured => "configured",
-        BoxStatus::Running => "running",
-        BoxStatus::Stopping => "stopping",
-        BoxStatus::Stopped => "stopped",
-    }
-    .to_string()
-}
-
-fn box_info_to_java(info: BoxInfo) -> JavaBoxInfo {
-    JavaBoxInfo {
-        id: info.id.to_string(),
-        name: info.name,
-        state: JavaBoxStateInfo {
-            status: status_to_string(info.status),
-            running: info.status.is_running(),
-            pid: info.pid,
-        },
-        created_at: info.created_at.to_rfc3339(),
-        image: info.image,
-        cpus: info.cpus,
-        memory_mib: info.memory_mib,
-    }
-}
-
-fn runtime_metrics_to_java(metrics: boxlite::RuntimeMetrics) -> JavaRuntimeMetrics {
-    JavaRuntimeMetrics {
-        boxes_created_total: metrics.boxes_created_total(),
-        boxes_failed_total: metrics.boxes_failed_total(),
-        boxes_stopped_total: metrics.boxes_stopped_total(),
-        num_running_boxes: metrics.num_running_boxes(),
-        total_commands_executed: metrics.total_commands_executed(),
-        total_exec_errors: metrics.total_exec_errors(),
-    }
-}
-
-fn allocate_handle() -> i64 {
-    NEXT_HANDLE.fetch_add(1, Ordering::Relaxed)
-}
-
-fn runtime_handle_from_jlong(handle: jlong) -> BoxliteResult<i64> {
-    if handle <= 0 {
-        return Err(BoxliteError::InvalidState(format!(
-            "runtime handle must be positive, got {handle}"
-        )));
-    }
-    Ok(handle)
-}
-
-fn box_handle_from_jlong(handle: jlong) -> BoxliteResult<i64> {
-    if handle <= 0 {
-        return Err(BoxliteError::InvalidState(format!(
-            "box handle must be positive, got {handle}"
-        )));
-    }
-    Ok(handle)
-}
-
-fn lock_runtimes() -> BoxliteResult<MutexGuard<'static, HashMap<i64, Arc<BoxliteRuntime>>>> {
-    RUNTIMES
-        .lock()
-        .map_err(|e| BoxliteError::Internal(format!("runtime handle table lock poisoned: {e}")))
-}
-
-fn lock_boxes() -> BoxliteResult<MutexGuard<'static, HashMap<i64, BoxHandleEntry>>> {
-    BOXES
-        .lock()
-        .map_err(|e| BoxliteError::Internal(format!("box handle table lock poisoned: {e}")))
-}
-
-fn lock_executions() -> BoxliteResult<MutexGuard<'static, HashMap<i64, ExecutionHandleEntry>>> {
-    EXECUTIONS
-        .lock()
-        .map_err(|e| BoxliteError::Internal(format!("execution handle table lock poisoned: {e}")))
-}
-
-fn insert_runtime_handle(runtime: BoxliteRuntime) -> BoxliteResult<i64> {
-    let native_handle = allocate_handle();
-    lock_runtimes()?.insert(native_handle, Arc::new(runtime));
-    Ok(native_handle)
-}
-
-fn get_runtime(runtime_handle: jlong) -> BoxliteResult<Arc<BoxliteRuntime>> {
-    let native_handle = runtime_handle_from_jlong(runtime_handle)?;
-    lock_runtimes()?
-        .get(&native_handle)
-        .cloned()
-        .ok_or_else(|| {
-            BoxliteError::InvalidState(format!("runtime handle {runtime_handle} is not active"))
-        })
-}
-
-fn remove_runtime(runtime_handle: jlong) -> BoxliteResult<()> {
-    let native_handle = runtime_handle_from_jlong(runtime_handle)?;
-    let removed = lock_runtimes()?.remove(&native_handle);
-    if removed.is_none() {
-        return Err(BoxliteError::InvalidState(format!(
-            "runtime handle {runtime_handle} is not active"
-        )));
-    }
-
-    let mut boxes = lock_boxes()?;
-    boxes.retain(|_, entry| entry.runtime_handle != native_handle);
-
-    let mut executions = lock_executions()?;
-    executions.retain(|_, entry| entry.runtime_handle != native_handle);
-    Ok(())
-}
-
-fn insert_box_handle(runtime_handle: i64, handle: LiteBox) -> BoxliteResult<i64> {
-    let native_handle = allocate_handle();
-    let entry = BoxHandleEntry {
-        runtime_handle,
-        handle: Arc::new(handle),
-    };
-    lock_boxes()?.insert(native_handle, entry);
-    Ok(native_handle)
-}
-
-fn get_box_entry(box_handle: jlong) -> BoxliteResult<BoxHandleEntry> {
-    let native_handle = box_handle_from_jlong(box_handle)?;
-    let entry = lock_boxes()?.get(&native_handle).cloned().ok_or_else(|| {
-        BoxliteError::InvalidState(format!("box handle {box_handle} is not active"))
-    })?;
-
-    if !lock_runtimes()?.contains_key(&entry.runtime_handle) {
-        return Err(BoxliteError::InvalidState(format!(
-            "box handle {box_handle} belongs to a closed runtime"
-        )));
-    }
-
-    Ok(entry)
-}
-
-fn remove_box_handle(box_handle: jlong) -> BoxliteResult<()> {
-    let native_handle = box_handle_from_jlong(box_handle)?;
-    lock_boxes()?.remove(&native_handle);
-    Ok(())
-}
-
-fn insert_execution_handle(runtime_handle: i64, mut execution: Execution) -> BoxliteResult<i64> {
-    let native_handle = allocate_handle();
-    let id = execution.id().to_owned();
-    let stdin = execution.stdin();
-    let stdout = execution.stdout();
-    let stderr = execution.stderr();
-    let entry = ExecutionHandleEntry {
-        runtime_handle,
-        id,
-        execution: Arc::new(AsyncMutex::new(execution)),
-        stdin: Arc::new(AsyncMutex::new(stdin)),
-        stdout: Arc::new(AsyncMutex::new(stdout)),
-        stderr: Arc::new(AsyncMutex::new(stderr)),
-    };
-    lock_executions()?.insert(native_handle, entry);
-    Ok(native_handle)
-}
-
-fn execution_handle_from_jlong(handle: jlong) -> BoxliteResult<i64> {
-    if handle <= 0 {
-        return Err(BoxliteError::InvalidState(format!(
-            "execution handle must be positive, got {handle}"
-        )));
-    }
-    Ok(handle)
-}
-
-fn get_execution_entry(execution_handle: jlong) -> BoxliteResult<ExecutionHandleEntry> {
-    let native_handle = execution_handle_from_jlong(execution_handle)?;
-    let entry = lock_executions()?
-        .get(&native_handle)
-        .cloned()
-        .ok_or_else(|| {
-            BoxliteError::InvalidState(format!("execution handle {execution_handle} is not active"))
-        })?;
-
-    if !lock_runtimes()?.contains_key(&entry.runtime_handle) {
-        return Err(BoxliteError::InvalidState(format!(
-            "execution handle {execution_handle} belongs to a closed runtime"
-        )));
-    }
-
-    Ok(entry)
-}
-
-fn clone_execution(entry: &ExecutionHandleEntry) -> Execution {
-    TOKIO.block_on(async {
-        let execution = entry.execution.lock().await;
-        execution.clone()
-    })
-}
-
-fn remove_execution_handle(execution_handle: jlong) -> BoxliteResult<()> {
-    let native_handle = execution_handle_from_jlong(execution_handle)?;
-    lock_executions()?.remove(&native_handle);
-    Ok(())
-}
-
-fn invalidate_box_handles_for(runtime_handle: i64, id_or_name: &str) -> BoxliteResult<()> {
-    let mut boxes = lock_boxes()?;
-    boxes.retain(|_, entry| {
-        if entry.runtime_handle != runtime_handle {
-            return true;
-        }
-
-        let id = entry.handle.id().to_string();
-        let name = entry.handle.name().map(ToOwned::to_owned);
-        !(id == id_or_name || name.as_deref() == Some(id_or_name))
-    });
-    Ok(())
-}
-
 fn read_optional_string(
     env: &mut JNIEnv<'_>,
     value: JString<'_>,
@@ -408,6 +73,19 @@ fn read_required_string(
     })
 }
 
+/// Read a path argument as given, blank included: only null is rejected here,
+/// the rest is left to `boxlite::validate` so every SDK applies the same rules.
+fn read_path(env: &mut JNIEnv<'_>, value: JString<'_>, arg_name: &str) -> BoxliteResult<String> {
+    if value.is_null() {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "{arg_name} must not be null"
+        )));
+    }
+    env.get_string(&value)
+        .map(Into::into)
+        .map_err(|e| BoxliteError::InvalidArgument(format!("Invalid {arg_name}: {e}")))
+}
+
 fn read_required_bytes(
     env: &mut JNIEnv<'_>,
     value: JByteArray<'_>,
@@ -422,14 +100,38 @@ fn read_required_bytes(
         .map_err(|e| BoxliteError::InvalidArgument(format!("Invalid {arg_name}: {e}")))
 }
 
-fn parse_json_from_string<T: DeserializeOwned>(
+/// Locate `len` bytes at `offset` in a direct `ByteBuffer`, checking that the
+/// range lies within its capacity.
+fn read_direct_buffer_region(
     env: &mut JNIEnv<'_>,
-    value: JString<'_>,
+    buffer: &JByteBuffer<'_>,
+    offset: jint,
+    len: jint,
     arg_name: &str,
-) -> BoxliteResult<T> {
-    let json = read_required_string(env, value, arg_name)?;
-    serde_json::from_str(&json)
-        .map_err(|e| BoxliteError::Config(format!("Failed to parse {arg_name} JSON: {e}")))
+) -> BoxliteResult<(*mut u8, usize)> {
+    if buffer.is_null() {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "{arg_name} must not be null"
+        )));
+    }
+    let not_direct =
+        |_| BoxliteError::InvalidArgument(format!("{arg_name} must be a direct ByteBuffer"));
+    let address = env.get_direct_buffer_address(buffer).map_err(not_direct)?;
+    let capacity = env.get_direct_buffer_capacity(buffer).map_err(not_direct)?;
+
+    let (Ok(offset), Ok(len)) = (usize::try_from(offset), usize::try_from(len)) else {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "{arg_name} offset and length must not be negative"
+        )));
+    };
+    if offset + len > capacity {
+        return Err(BoxliteError::InvalidArgument(format!(
+            "{arg_name} range {offset}..{} exceeds its capacity {capacity}",
+            offset + len
+        )));
+    }
+    // SAFETY: offset <= capacity, so the result stays within the buffer
+    Ok((unsafe { address.add(offset) }, len))
 }
 
 fn parse_timeout_seconds(
@@ -452,103 +154,6 @@ fn parse_timeout_seconds(
     Ok(Some(timeout))
 }
 
-fn java_runtime_options_to_native(dto: JavaRuntimeOptions) -> BoxliteOptions {
-    let mut options = BoxliteOptions::default();
-    if let Some(home_dir) = dto.home_dir
-        && !home_dir.trim().is_empty()
-    {
-        options.home_dir = PathBuf::from(home_dir);
-    }
-    options.image_registries = dto.image_registries;
-    options
-}
-
-fn java_box_options_to_native(dto: JavaBoxOptions) -> BoxliteResult<BoxOptions> {
-    let mut options = BoxOptions::default();
-    let image = dto.image.filter(|v| !v.trim().is_empty());
-    let rootfs_path = dto.rootfs_path.filter(|v| !v.trim().is_empty());
-
-    if image.is_some() && rootfs_path.is_some() {
-        return Err(BoxliteError::Config(
-            "BoxOptions requires exactly one of image or rootfsPath".to_string(),
-        ));
-    }
-
-    if let Some(image) = image {
-        options.rootfs = RootfsSpec::Image(image);
-    } else if let Some(rootfs_path) = rootfs_path {
-        options.rootfs = RootfsSpec::RootfsPath(rootfs_path);
-    }
-
-    options.cpus = dto.cpus;
-    options.memory_mib = dto.memory_mib;
-    options.disk_size_gb = dto.disk_size_gb;
-    options.working_dir = dto.working_dir;
-    options.env = dto.env.into_iter().collect();
-    options.auto_remove = dto.auto_remove.unwrap_or(options.auto_remove);
-    options.detach = dto.detach.unwrap_or(options.detach);
-    // Java defaults currently serialize empty lists for entrypoint/cmd.
-    // Treat empty vectors as "not set" so we don't override image config
-    // with an invalid empty entrypoint.
-    options.entrypoint = dto.entrypoint.filter(|value| !value.is_empty());
-    options.cmd = dto.cmd.filter(|value| !value.is_empty());
-    options.user = dto.user;
-    options.sanitize()?;
-    Ok(options)
-}
-
-fn java_copy_options_to_native(dto: JavaCopyOptions) -> CopyOptions {
-    CopyOptions {
-        recursive: dto.recursive,
-        overwrite: dto.overwrite,
-        follow_symlinks: dto.follow_symlinks,
-        include_parent: dto.include_parent,
-    }
-}
-
-fn java_exec_command_to_native(dto: JavaExecCommand) -> BoxliteResult<BoxCommand> {
-    if dto.command.trim().is_empty() {
-        return Err(BoxliteError::InvalidArgument(
-            "command must not be null or blank".to_string(),
-        ));
-    }
-    if let Some(timeout_millis) = dto.timeout_millis
-        && timeout_millis == 0
-    {
-        return Err(BoxliteError::InvalidArgument(
-            "timeoutMillis must be > 0 when provided".to_string(),
-        ));
-    }
-
-    let mut command = BoxCommand::new(dto.command).args(dto.args).tty(dto.tty);
-    if !dto.env.is_empty() {
-        for (key, value) in dto.env {
-            command = command.env(key, value);
-        }
-    }
-    if let Some(timeout_millis) = dto.timeout_millis {
-        command = command.timeout(Duration::from_millis(timeout_millis));
-    }
-    if let Some(working_dir) = dto.working_dir
-        && !working_dir.trim().is_empty()
-    {
-        command = command.working_dir(working_dir);
-    }
-    Ok(command)
-}
-
-fn exec_result_to_java(result: ExecResult) -> JavaExecResult {
-    JavaExecResult {
-        exit_code: result.exit_code,
-        error_message: result.error_message,
-    }
-}
-
-fn serialize_json<T: Serialize>(value: &T) -> BoxliteResult<String> {
-    serde_json::to_string(value)
-        .map_err(|e| BoxliteError::Internal(format!("Failed to serialize JSON response: {e}")))
-}
-
 fn to_jstring(env: &mut JNIEnv<'_>, value: &str) -> jstring {
     match env.new_string(value) {
         Ok(value) => value.into_raw(),
@@ -576,21 +181,14 @@ fn to_jlong_array(env: &mut JNIEnv<'_>, values: &[jlong]) -> jlongArray {
     array.into_raw()
 }
 
-fn get_default_runtime_clone() -> BoxliteResult<BoxliteRuntime> {
-    if BoxliteRuntime::try_default_runtime().is_none()
-        && let Err(err) = BoxliteRuntime::init_default_runtime(BoxliteOptions::default())
-        && BoxliteRuntime::try_default_runtime().is_none()
-    {
-        return Err(err);
-    }
-
-    if BoxliteRuntime::try_default_runtime().is_none() {
-        return Err(BoxliteError::Internal(
-            "Default runtime is not initialized".into(),
-        ));
+fn to_jbyte_array(env: &mut JNIEnv<'_>, values: &[u8]) -> jbyteArray {
+    match env.byte_array_from_slice(values) {
+        Ok(array) => array.into_raw(),
+        Err(e) => {
+            throw_internal(env, format!("Failed to create byte[]: {e}"));
+            std::ptr::null_mut()
+        }
     }
-
-    Ok(BoxliteRuntime::default_runtime().clone())
 }
 
 #[unsafe(no_mangle)]
@@ -608,10 +206,8 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeNew(
     options_json: JString<'_>,
 ) -> jlong {
     let result: BoxliteResult<i64> = (|| {
-        let dto: JavaRuntimeOptions =
-            parse_json_from_string(&mut env, options_json, "optionsJson")?;
-        let runtime = BoxliteRuntime::new(java_runtime_options_to_native(dto))?;
-        insert_runtime_handle(runtime)
+        let options_json = read_required_string(&mut env, options_json, "optionsJson")?;
+        natives::runtime_new(&options_json)
     })();
 
     match result {
@@ -628,12 +224,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeDefaul
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
 ) -> jlong {
-    let result: BoxliteResult<i64> = (|| {
-        let runtime = get_default_runtime_clone()?;
-        insert_runtime_handle(runtime)
-    })();
-
-    match result {
+    match natives::runtime_default() {
         Ok(handle) => handle as jlong,
         Err(err) => {
             throw_boxlite_error(&mut env, err);
@@ -649,9 +240,8 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeInitDe
     options_json: JString<'_>,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let dto: JavaRuntimeOptions =
-            parse_json_from_string(&mut env, options_json, "optionsJson")?;
-        BoxliteRuntime::init_default_runtime(java_runtime_options_to_native(dto))
+        let options_json = read_required_string(&mut env, options_json, "optionsJson")?;
+        natives::runtime_init_default(&options_json)
     })();
 
     if let Err(err) = result {
@@ -683,14 +273,9 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeCreate
     name: JString<'_>,
 ) -> jlong {
     let result: BoxliteResult<i64> = (|| {
-        let native_runtime_handle = runtime_handle_from_jlong(runtime_handle)?;
-        let runtime = get_runtime(runtime_handle)?;
-        let dto: JavaBoxOptions =
-            parse_json_from_string(&mut env, box_options_json, "boxOptionsJson")?;
-        let options = java_box_options_to_native(dto)?;
+        let box_options_json = read_required_string(&mut env, box_options_json, "boxOptionsJson")?;
         let name = read_optional_string(&mut env, name, "name")?;
-        let handle = TOKIO.block_on(runtime.create(options, name))?;
-        insert_box_handle(native_runtime_handle, handle)
+        natives::runtime_create(runtime_handle, &box_options_json, name)
     })();
 
     match result {
@@ -711,15 +296,17 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeGetOrC
     name: JString<'_>,
 ) -> jlongArray {
     let result: BoxliteResult<[jlong; 2]> = (|| {
-        let native_runtime_handle = runtime_handle_from_jlong(runtime_handle)?;
-        let runtime = get_runtime(runtime_handle)?;
-        let dto: JavaBoxOptions =
-            parse_json_from_string(&mut env, box_options_json, "boxOptionsJson")?;
-        let options = java_box_options_to_native(dto)?;
+        let box_options_json = read_required_string(&mut env, box_options_json, "boxOptionsJson")?;
         let name = read_optional_string(&mut env, name, "name")?;
-        let (handle, created) = TOKIO.block_on(runtime.get_or_create(options, name))?;
-        let box_handle = insert_box_handle(native_runtime_handle, handle)?;
-        Ok([box_handle as jlong, if created { 1 } else { 0 }])
+        let (box_handle, outcome) =
+            natives::runtime_get_or_create(runtime_handle, &box_options_json, name)?;
+        // 0 = reused, 1 = created, 2 = recreated
+        let status = match outcome {
+            GetOrCreateOutcome::Reused => 0,
+            GetOrCreateOutcome::Created => 1,
+            GetOrCreateOutcome::Recreated { .. } => 2,
+        };
+        Ok([box_handle as jlong, status])
     })();
 
     match result {
@@ -738,18 +325,13 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeGet(
     runtime_handle: jlong,
     id_or_name: JString<'_>,
 ) -> jlong {
-    let result: BoxliteResult<jlong> = (|| {
-        let native_runtime_handle = runtime_handle_from_jlong(runtime_handle)?;
-        let runtime = get_runtime(runtime_handle)?;
+    let result: BoxliteResult<Option<i64>> = (|| {
         let id_or_name = read_required_string(&mut env, id_or_name, "idOrName")?;
-        match TOKIO.block_on(runtime.get(&id_or_name))? {
-            Some(handle) => Ok(insert_box_handle(native_runtime_handle, handle)? as jlong),
-            None => Ok(0),
-        }
+        natives::runtime_get(runtime_handle, &id_or_name)
     })();
 
     match result {
-        Ok(handle) => handle,
+        Ok(handle) => handle.unwrap_or(0) as jlong,
         Err(err) => {
             throw_boxlite_error(&mut env, err);
             0
@@ -765,13 +347,8 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeGetInf
     id_or_name: JString<'_>,
 ) -> jstring {
     let result: BoxliteResult<Option<String>> = (|| {
-        let runtime = get_runtime(runtime_handle)?;
         let id_or_name = read_required_string(&mut env, id_or_name, "idOrName")?;
-        let info = TOKIO.block_on(runtime.get_info(&id_or_name))?;
-        match info {
-            Some(info) => serialize_json(&box_info_to_java(info)).map(Some),
-            None => Ok(None),
-        }
+        natives::runtime_get_info(runtime_handle, &id_or_name)
     })();
 
     match result {
@@ -790,14 +367,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeListIn
     _class: JClass<'_>,
     runtime_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<String> = (|| {
-        let runtime = get_runtime(runtime_handle)?;
-        let infos = TOKIO.block_on(runtime.list_info())?;
-        let mapped = infos.into_iter().map(box_info_to_java).collect::<Vec<_>>();
-        serialize_json(&mapped)
-    })();
-
-    match result {
+    match natives::runtime_list_info(runtime_handle) {
         Ok(json) => to_jstring(&mut env, &json),
         Err(err) => {
             throw_boxlite_error(&mut env, err);
@@ -815,11 +385,8 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeRemove
     force: jboolean,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let native_runtime_handle = runtime_handle_from_jlong(runtime_handle)?;
-        let runtime = get_runtime(runtime_handle)?;
         let id_or_name = read_required_string(&mut env, id_or_name, "idOrName")?;
-        TOKIO.block_on(runtime.remove(&id_or_name, force != 0))?;
-        invalidate_box_handles_for(native_runtime_handle, &id_or_name)
+        natives::runtime_remove(runtime_handle, &id_or_name, force != 0)
     })();
 
     if let Err(err) = result {
@@ -833,13 +400,22 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeMetric
     _class: JClass<'_>,
     runtime_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<String> = (|| {
-        let runtime = get_runtime(runtime_handle)?;
-        let metrics = TOKIO.block_on(runtime.metrics());
-        serialize_json(&runtime_metrics_to_java(metrics))
-    })();
+    match natives::runtime_metrics(runtime_handle) {
+        Ok(json) => to_jstring(&mut env, &json),
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            std::ptr::null_mut()
+        }
+    }
+}
 
-    match result {
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeVersionInfo(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    runtime_handle: jlong,
+) -> jstring {
+    match natives::runtime_version_info(runtime_handle) {
         Ok(json) => to_jstring(&mut env, &json),
         Err(err) => {
             throw_boxlite_error(&mut env, err);
@@ -854,11 +430,12 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeShutdo
     _class: JClass<'_>,
     runtime_handle: jlong,
     timeout_seconds: JObject<'_>,
+    call_timeout_millis: jlong,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let runtime = get_runtime(runtime_handle)?;
         let timeout_seconds = parse_timeout_seconds(&mut env, timeout_seconds)?;
-        TOKIO.block_on(runtime.shutdown(timeout_seconds))
+        let call_timeout = parse_call_timeout(call_timeout_millis)?;
+        natives::runtime_shutdown(runtime_handle, timeout_seconds, call_timeout)
     })();
 
     if let Err(err) = result {
@@ -866,6 +443,38 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeShutdo
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeRuntimeRunOnce(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    runtime_handle: jlong,
+    box_options_json: JString<'_>,
+    exec_command_json: JString<'_>,
+    run_once_options_json: JString<'_>,
+) -> jstring {
+    let result: BoxliteResult<String> = (|| {
+        let box_options_json = read_required_string(&mut env, box_options_json, "boxOptionsJson")?;
+        let exec_command_json =
+            read_required_string(&mut env, exec_command_json, "execCommandJson")?;
+        let run_once_options_json =
+            read_required_string(&mut env, run_once_options_json, "runOnceOptionsJson")?;
+        natives::runtime_run_once(
+            runtime_handle,
+            &box_options_json,
+            &exec_command_json,
+            &run_once_options_json,
+        )
+    })();
+
+    match result {
+        Ok(json) => to_jstring(&mut env, &json),
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxFree(
     mut env: JNIEnv<'_>,
@@ -887,12 +496,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxId(
     _class: JClass<'_>,
     box_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<String> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        Ok(entry.handle.id().to_string())
-    })();
-
-    match result {
+    match natives::box_id(box_handle) {
         Ok(value) => to_jstring(&mut env, &value),
         Err(err) => {
             throw_boxlite_error(&mut env, err);
@@ -907,12 +511,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxName(
     _class: JClass<'_>,
     box_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<Option<String>> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        Ok(entry.handle.name().map(ToOwned::to_owned))
-    })();
-
-    match result {
+    match natives::box_name(box_handle) {
         Ok(Some(value)) => to_jstring(&mut env, &value),
         Ok(None) => std::ptr::null_mut(),
         Err(err) => {
@@ -928,13 +527,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxInfo(
     _class: JClass<'_>,
     box_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<String> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        let info = entry.handle.info();
-        serialize_json(&box_info_to_java(info))
-    })();
-
-    match result {
+    match natives::box_info(box_handle) {
         Ok(json) => to_jstring(&mut env, &json),
         Err(err) => {
             throw_boxlite_error(&mut env, err);
@@ -948,11 +541,10 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxStart(
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
     box_handle: jlong,
+    call_timeout_millis: jlong,
 ) {
-    let result: BoxliteResult<()> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        TOKIO.block_on(entry.handle.start())
-    })();
+    let result = parse_call_timeout(call_timeout_millis)
+        .and_then(|call_timeout| natives::box_start(box_handle, call_timeout));
 
     if let Err(err) = result {
         throw_boxlite_error(&mut env, err);
@@ -964,11 +556,10 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxStop(
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
     box_handle: jlong,
+    call_timeout_millis: jlong,
 ) {
-    let result: BoxliteResult<()> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        TOKIO.block_on(entry.handle.stop())
-    })();
+    let result = parse_call_timeout(call_timeout_millis)
+        .and_then(|call_timeout| natives::box_stop(box_handle, call_timeout));
 
     if let Err(err) = result {
         throw_boxlite_error(&mut env, err);
@@ -981,14 +572,13 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxExec(
     _class: JClass<'_>,
     box_handle: jlong,
     exec_command_json: JString<'_>,
+    call_timeout_millis: jlong,
 ) -> jlong {
     let result: BoxliteResult<i64> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        let dto: JavaExecCommand =
-            parse_json_from_string(&mut env, exec_command_json, "execCommandJson")?;
-        let command = java_exec_command_to_native(dto)?;
-        let execution = TOKIO.block_on(entry.handle.exec(command))?;
-        insert_execution_handle(entry.runtime_handle, execution)
+        let exec_command_json =
+            read_required_string(&mut env, exec_command_json, "execCommandJson")?;
+        let call_timeout = parse_call_timeout(call_timeout_millis)?;
+        natives::box_exec(box_handle, &exec_command_json, call_timeout)
     })();
 
     match result {
@@ -1000,6 +590,64 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxExec(
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxPrepareExec(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    box_handle: jlong,
+    exec_command_json: JString<'_>,
+) -> jlong {
+    let result: BoxliteResult<i64> = (|| {
+        let exec_command_json =
+            read_required_string(&mut env, exec_command_json, "execCommandJson")?;
+        natives::box_prepare_exec(box_handle, &exec_command_json)
+    })();
+
+    match result {
+        Ok(handle) => handle as jlong,
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            0
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativePreparedExecRun(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    prepared_handle: jlong,
+    args_json: JString<'_>,
+) -> jlong {
+    let result: BoxliteResult<i64> = (|| {
+        let args_json = read_required_string(&mut env, args_json, "argsJson")?;
+        natives::prepared_exec_run(prepared_handle, &args_json)
+    })();
+
+    match result {
+        Ok(handle) => handle as jlong,
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            0
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativePreparedExecFree(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    prepared_handle: jlong,
+) {
+    if prepared_handle <= 0 {
+        return;
+    }
+
+    if let Err(err) = remove_prepared_exec_handle(prepared_handle) {
+        throw_boxlite_error(&mut env, err);
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxCopyIn(
     mut env: JNIEnv<'_>,
@@ -1008,18 +656,21 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxCopyIn(
     host_path: JString<'_>,
     container_dest: JString<'_>,
     copy_options_json: JString<'_>,
+    call_timeout_millis: jlong,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        let host_path = read_required_string(&mut env, host_path, "hostPath")?;
-        let container_dest = read_required_string(&mut env, container_dest, "containerDest")?;
-        let copy_options: JavaCopyOptions =
-            parse_json_from_string(&mut env, copy_options_json, "copyOptionsJson")?;
-        TOKIO.block_on(entry.handle.copy_into(
-            Path::new(&host_path),
+        let host_path = read_path(&mut env, host_path, "hostPath")?;
+        let container_dest = read_path(&mut env, container_dest, "containerDest")?;
+        let copy_options_json =
+            read_required_string(&mut env, copy_options_json, "copyOptionsJson")?;
+        let call_timeout = parse_call_timeout(call_timeout_millis)?;
+        natives::box_copy_in(
+            box_handle,
+            &host_path,
             &container_dest,
-            java_copy_options_to_native(copy_options),
-        ))
+            &copy_options_json,
+            call_timeout,
+        )
     })();
 
     if let Err(err) = result {
@@ -1048,12 +699,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionId(
     _class: JClass<'_>,
     execution_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<String> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
-        Ok(entry.id)
-    })();
-
-    match result {
+    match natives::execution_id(execution_handle) {
         Ok(value) => to_jstring(&mut env, &value),
         Err(err) => {
             throw_boxlite_error(&mut env, err);
@@ -1070,13 +716,8 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdi
     data: JByteArray<'_>,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
         let bytes = read_required_bytes(&mut env, data, "data")?;
-        let mut stdin_guard = TOKIO.block_on(entry.stdin.lock());
-        let stdin = stdin_guard.as_mut().ok_or_else(|| {
-            BoxliteError::InvalidState("stdin is not available for this execution".to_string())
-        })?;
-        TOKIO.block_on(stdin.write(&bytes))
+        natives::execution_stdin_write(execution_handle, &bytes)
     })();
 
     if let Err(err) = result {
@@ -1085,19 +726,20 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdi
 }
 
 #[unsafe(no_mangle)]
-pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdinClose(
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdinWriteDirect(
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
     execution_handle: jlong,
+    buffer: JByteBuffer<'_>,
+    offset: jint,
+    len: jint,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
-        let mut stdin_guard = TOKIO.block_on(entry.stdin.lock());
-        let stdin = stdin_guard.as_mut().ok_or_else(|| {
-            BoxliteError::InvalidState("stdin is not available for this execution".to_string())
-        })?;
-        stdin.close();
-        Ok(())
+        let (address, len) = read_direct_buffer_region(&mut env, &buffer, offset, len, "buffer")?;
+        // SAFETY: the region was bounds-checked and `buffer` stays referenced
+        // for the duration of this call
+        let data = unsafe { std::slice::from_raw_parts(address, len) };
+        natives::execution_stdin_write(execution_handle, data)
     })();
 
     if let Err(err) = result {
@@ -1105,22 +747,24 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdi
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdinClose(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    execution_handle: jlong,
+) {
+    if let Err(err) = natives::execution_stdin_close(execution_handle) {
+        throw_boxlite_error(&mut env, err);
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdoutNextLine(
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
     execution_handle: jlong,
 ) -> jstring {
-    let result: BoxliteResult<Option<String>> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
-        let mut stdout_guard = TOKIO.block_on(entry.stdout.lock());
-        let stdout = stdout_guard.as_mut().ok_or_else(|| {
-            BoxliteError::InvalidState("stdout is not available for this execution".to_string())
-        })?;
-        Ok(TOKIO.block_on(stdout.next()))
-    })();
-
-    match result {
+    match natives::execution_stdout_next_line(execution_handle) {
         Ok(Some(line)) => to_jstring(&mut env, &line),
         Ok(None) => std::ptr::null_mut(),
         Err(err) => {
@@ -1131,21 +775,57 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdo
 }
 
 #[unsafe(no_mangle)]
-pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStderrNextLine(
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdoutNextLineBytes(
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
     execution_handle: jlong,
-) -> jstring {
-    let result: BoxliteResult<Option<String>> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
-        let mut stderr_guard = TOKIO.block_on(entry.stderr.lock());
-        let stderr = stderr_guard.as_mut().ok_or_else(|| {
-            BoxliteError::InvalidState("stderr is not available for this execution".to_string())
-        })?;
-        Ok(TOKIO.block_on(stderr.next()))
+) -> jbyteArray {
+    match natives::execution_stdout_next_line_bytes(execution_handle) {
+        Ok(Some(line)) => to_jbyte_array(&mut env, &line),
+        Ok(None) => std::ptr::null_mut(),
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Fill the start of a direct `ByteBuffer` with up to `max_len` bytes of
+/// stdout; the byte count, or -1 at end of stream.
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStdoutReadInto(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    execution_handle: jlong,
+    buffer: JByteBuffer<'_>,
+    max_len: jint,
+) -> jint {
+    let result: BoxliteResult<Option<usize>> = (|| {
+        let (address, len) = read_direct_buffer_region(&mut env, &buffer, 0, max_len, "buffer")?;
+        // SAFETY: the region was bounds-checked and `buffer` stays referenced
+        // for the duration of this call
+        let data = unsafe { std::slice::from_raw_parts_mut(address, len) };
+        natives::execution_stdout_read_into(execution_handle, data)
     })();
 
     match result {
+        // At most max_len, so it fits a jint
+        Ok(Some(read)) => read as jint,
+        Ok(None) => -1,
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            -1
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStderrNextLine(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    execution_handle: jlong,
+) -> jstring {
+    match natives::execution_stderr_next_line(execution_handle) {
         Ok(Some(line)) => to_jstring(&mut env, &line),
         Ok(None) => std::ptr::null_mut(),
         Err(err) => {
@@ -1155,18 +835,31 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStde
     }
 }
 
+#[unsafe(no_mangle)]
+pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionStderrNextLineBytes(
+    mut env: JNIEnv<'_>,
+    _class: JClass<'_>,
+    execution_handle: jlong,
+) -> jbyteArray {
+    match natives::execution_stderr_next_line_bytes(execution_handle) {
+        Ok(Some(line)) => to_jbyte_array(&mut env, &line),
+        Ok(None) => std::ptr::null_mut(),
+        Err(err) => {
+            throw_boxlite_error(&mut env, err);
+            std::ptr::null_mut()
+        }
+    }
+}
+
 #[unsafe(no_mangle)]
 pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionWait(
     mut env: JNIEnv<'_>,
     _class: JClass<'_>,
     execution_handle: jlong,
+    call_timeout_millis: jlong,
 ) -> jstring {
-    let result: BoxliteResult<String> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
-        let mut execution = clone_execution(&entry);
-        let result = TOKIO.block_on(execution.wait())?;
-        serialize_json(&exec_result_to_java(result))
-    })();
+    let result = parse_call_timeout(call_timeout_millis)
+        .and_then(|call_timeout| natives::execution_wait(execution_handle, call_timeout));
 
     match result {
         Ok(json) => to_jstring(&mut env, &json),
@@ -1183,13 +876,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionKill
     _class: JClass<'_>,
     execution_handle: jlong,
 ) {
-    let result: BoxliteResult<()> = (|| {
-        let entry = get_execution_entry(execution_handle)?;
-        let mut execution = clone_execution(&entry);
-        TOKIO.block_on(execution.kill())
-    })();
-
-    if let Err(err) = result {
+    if let Err(err) = natives::execution_kill(execution_handle) {
         throw_boxlite_error(&mut env, err);
     }
 }
@@ -1202,18 +889,7 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeExecutionResi
     rows: jint,
     cols: jint,
 ) {
-    let result: BoxliteResult<()> = (|| {
-        if rows <= 0 || cols <= 0 {
-            return Err(BoxliteError::InvalidArgument(
-                "rows and cols must both be > 0".to_string(),
-            ));
-        }
-        let entry = get_execution_entry(execution_handle)?;
-        let execution = TOKIO.block_on(entry.execution.lock());
-        TOKIO.block_on(execution.resize_tty(rows as u32, cols as u32))
-    })();
-
-    if let Err(err) = result {
+    if let Err(err) = natives::execution_resize_tty(execution_handle, rows, cols) {
         throw_boxlite_error(&mut env, err);
     }
 }
@@ -1226,18 +902,21 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeBoxCopyOut(
     container_src: JString<'_>,
     host_dest: JString<'_>,
     copy_options_json: JString<'_>,
+    call_timeout_millis: jlong,
 ) {
     let result: BoxliteResult<()> = (|| {
-        let entry = get_box_entry(box_handle)?;
-        let container_src = read_required_string(&mut env, container_src, "containerSrc")?;
-        let host_dest = read_required_string(&mut env, host_dest, "hostDest")?;
-        let copy_options: JavaCopyOptions =
-            parse_json_from_string(&mut env, copy_options_json, "copyOptionsJson")?;
-        TOKIO.block_on(entry.handle.copy_out(
+        let container_src = read_path(&mut env, container_src, "containerSrc")?;
+        let host_dest = read_path(&mut env, host_dest, "hostDest")?;
+        let copy_options_json =
+            read_required_string(&mut env, copy_options_json, "copyOptionsJson")?;
+        let call_timeout = parse_call_timeout(call_timeout_millis)?;
+        natives::box_copy_out(
+            box_handle,
             &container_src,
-            Path::new(&host_dest),
-            java_copy_options_to_native(copy_options),
-        ))
+            &host_dest,
+            &copy_options_json,
+            call_timeout,
+        )
     })();
 
     if let Err(err) = result {
@@ -1252,108 +931,3 @@ pub extern "system" fn Java_io_boxlite_loader_NativeBindings_nativeAbiVersion(
 ) -> jint {
     ABI_VERSION
 }
-
-#[cfg(test)]
-mod tests {
-    use super::{
-        JavaBoxOptions, JavaExecCommand, java_box_options_to_native, java_exec_command_to_native,
-    };
-    use boxlite::{BoxliteError, RootfsSpec};
-    use std::collections::HashMap;
-
-    #[test]
-    fn java_empty_entrypoint_and_cmd_do_not_override_image_defaults() {
-        let options = java_box_options_to_native(JavaBoxOptions {
-            image: Some("alpine:latest".to_string()),
-            rootfs_path: None,
-            cpus: None,
-            memory_mib: None,
-            disk_size_gb: None,
-            working_dir: None,
-            env: HashMap::new(),
-            auto_remove: None,
-            detach: None,
-            entrypoint: Some(Vec::new()),
-            cmd: Some(Vec::new()),
-            user: None,
-        })
-        .expect("conversion should succeed");
-
-        assert!(
-            options.entrypoint.is_none(),
-            "empty Java entrypoint should map to None"
-        );
-        assert!(options.cmd.is_none(), "empty Java cmd should map to None");
-    }
-
-    #[test]
-    fn java_non_empty_entrypoint_and_cmd_are_preserved() {
-        let options = java_box_options_to_native(JavaBoxOptions {
-            image: Some("alpine:latest".to_string()),
-            rootfs_path: None,
-            cpus: None,
-            memory_mib: None,
-            disk_size_gb: None,
-            working_dir: None,
-            env: HashMap::new(),
-            auto_remove: None,
-            detach: None,
-            entrypoint: Some(vec!["/bin/sh".to_string()]),
-            cmd: Some(vec!["-lc".to_string(), "echo hi".to_string()]),
-            user: None,
-        })
-        .expect("conversion should succeed");
-
-        assert_eq!(
-            options.entrypoint,
-            Some(vec!["/bin/sh".to_string()]),
-            "non-empty Java entrypoint should be passed through"
-        );
-        assert_eq!(
-            options.cmd,
-            Some(vec!["-lc".to_string(), "echo hi".to_string()]),
-            "non-empty Java cmd should be passed through"
-        );
-        assert!(
-            matches!(options.rootfs, RootfsSpec::Image(_)),
-            "image input should still map to image rootfs"
-        );
-    }
-
-    #[test]
-    fn java_exec_command_accepts_supported_fields() {
-        let mut env = HashMap::new();
-        env.insert("FOO".to_string(), "bar".to_string());
-
-        let result = java_exec_command_to_native(JavaExecCommand {
-            command: "sh".to_string(),
-            args: vec!["-lc".to_string(), "echo hi".to_string()],
-            env,
-            timeout_millis: Some(1_500),
-            working_dir: Some("/workspace".to_string()),
-            tty: true,
-        });
-
-        assert!(
-            result.is_ok(),
-            "valid command with args/env/timeout/workingDir/tty should convert successfully"
-        );
-    }
-
-    #[test]
-    fn java_exec_command_rejects_blank_command() {
-        let result = java_exec_command_to_native(JavaExecCommand {
-            command: "   ".to_string(),
-            args: Vec::new(),
-            env: HashMap::new(),
-            timeout_millis: None,
-            working_dir: None,
-            tty: false,
-        });
-
-        assert!(
-            matches!(result, Err(BoxliteError::InvalidArgument(_))),
-            "blank command should fail with InvalidArgument"
-        );
-    }
-}
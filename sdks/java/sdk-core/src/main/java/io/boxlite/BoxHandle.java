@@ -3,6 +3,7 @@ package io.boxlite;
 import io.boxlite.loader.NativeBindings;
 import java.lang.ref.Cleaner;
 import java.nio.file.Path;
+import java.time.Duration;
 import java.util.Optional;
 import java.util.concurrent.CompletableFuture;
 import java.util.concurrent.atomic.AtomicLong;
@@ -60,9 +61,21 @@ public final class BoxHandle implements AutoCloseable {
      * @return 异步完成信号。
      */
     public CompletableFuture<Void> start() {
+        return start(null);
+    }
+
+    /**
+     * 启动盒子，最长等待 {@code timeout}。
+     *
+     * <p>超时后抛出 {@link TimeoutException}，虚拟机仍在后台继续启动；再次调用 {@code start} 会等待同一次启动。
+     *
+     * @param timeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步完成信号。
+     */
+    public CompletableFuture<Void> start(Duration timeout) {
         return runtime.async(() -> {
             runtime.requireNativeHandle();
-            NativeBindings.boxStart(state.requireNativeHandle());
+            NativeBindings.boxStart(state.requireNativeHandle(), BoxliteRuntime.callTimeoutMillis(timeout));
             return null;
         });
     }
@@ -73,9 +86,21 @@ public final class BoxHandle implements AutoCloseable {
      * @return 异步完成信号。
      */
     public CompletableFuture<Void> stop() {
+        return stop(null);
+    }
+
+    /**
+     * 停止盒子，最长等待 {@code timeout}。
+     *
+     * <p>超时后抛出 {@link TimeoutException}，停止流程仍在后台继续执行，可安全重试。
+     *
+     * @param timeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步完成信号。
+     */
+    public CompletableFuture<Void> stop(Duration timeout) {
         return runtime.async(() -> {
             runtime.requireNativeHandle();
-            NativeBindings.boxStop(state.requireNativeHandle());
+            NativeBindings.boxStop(state.requireNativeHandle(), BoxliteRuntime.callTimeoutMillis(timeout));
             return null;
         });
     }
@@ -87,6 +112,19 @@ public final class BoxHandle implements AutoCloseable {
      * @return 异步返回执行句柄。
      */
     public CompletableFuture<ExecutionHandle> exec(ExecCommand command) {
+        return exec(command, null);
+    }
+
+    /**
+     * 在盒子内执行命令，最长等待 {@code timeout} 获取执行句柄。
+     *
+     * <p>超时后抛出 {@link TimeoutException}；若命令在超时后才在盒子内启动，会被自动终止，不会遗留无法管理的进程。
+     *
+     * @param command 执行选项。
+     * @param timeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步返回执行句柄。
+     */
+    public CompletableFuture<ExecutionHandle> exec(ExecCommand command, Duration timeout) {
         return runtime.async(() -> {
             runtime.requireNativeHandle();
             if (command == null) {
@@ -95,7 +133,8 @@ public final class BoxHandle implements AutoCloseable {
 
             long execHandle = NativeBindings.boxExec(
                 state.requireNativeHandle(),
-                JsonSupport.write(command)
+                JsonSupport.write(command),
+                BoxliteRuntime.callTimeoutMillis(timeout)
             );
             if (execHandle == 0L) {
                 throw new InternalException("Native boxExec returned invalid handle 0");
@@ -104,22 +143,68 @@ public final class BoxHandle implements AutoCloseable {
         });
     }
 
+    /**
+     * 预注册命令模板，供重复执行时只发送追加参数。
+     *
+     * <p>盒子重启后模板会在下次运行时透明地重新注册。
+     *
+     * @param command 命令模板。
+     * @return 异步返回预注册命令句柄。
+     */
+    public CompletableFuture<PreparedExecHandle> prepareExec(ExecCommand command) {
+        return runtime.async(() -> {
+            runtime.requireNativeHandle();
+            if (command == null) {
+                throw new ConfigException("command must not be null");
+            }
+
+            long preparedHandle = NativeBindings.boxPrepareExec(
+                state.requireNativeHandle(),
+                JsonSupport.write(command)
+            );
+            if (preparedHandle == 0L) {
+                throw new InternalException("Native boxPrepareExec returned invalid handle 0");
+            }
+            return new PreparedExecHandle(runtime, preparedHandle);
+        });
+    }
+
     /**
      * 将宿主机内容复制到盒子内。
      *
      * @param hostPath 宿主机源路径。
-     * @param containerDest 盒子内目标路径。
+     * @param containerDest 盒子内目标路径，须为绝对路径，规则见 docs/reference/validation.md。
      * @param options 复制选项，传 {@code null} 等价于 {@link CopyOptions#defaults()}。
      * @return 异步完成信号。
      */
     public CompletableFuture<Void> copyIn(Path hostPath, String containerDest, CopyOptions options) {
+        return copyIn(hostPath, containerDest, options, null);
+    }
+
+    /**
+     * 将宿主机内容复制到盒子内，最长等待 {@code timeout}。
+     *
+     * <p>超时后抛出 {@link TimeoutException} 并中止复制；目标路径可能残留部分内容。
+     *
+     * @param hostPath 宿主机源路径。
+     * @param containerDest 盒子内目标路径，须为绝对路径，规则见 docs/reference/validation.md。
+     * @param options 复制选项，传 {@code null} 等价于 {@link CopyOptions#defaults()}。
+     * @param timeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步完成信号。
+     */
+    public CompletableFuture<Void> copyIn(
+        Path hostPath,
+        String containerDest,
+        CopyOptions options,
+        Duration timeout
+    ) {
         return runtime.async(() -> {
             runtime.requireNativeHandle();
             if (hostPath == null) {
                 throw new ConfigException("hostPath must not be null");
             }
-            if (containerDest == null || containerDest.isBlank()) {
-                throw new ConfigException("containerDest must not be null or blank");
+            if (containerDest == null) {
+                throw new ConfigException("containerDest must not be null");
             }
 
             CopyOptions resolvedOptions = options == null ? CopyOptions.defaults() : options;
@@ -127,7 +212,8 @@ public final class BoxHandle implements AutoCloseable {
                 state.requireNativeHandle(),
                 hostPath.toString(),
                 containerDest,
-                JsonSupport.write(resolvedOptions)
+                JsonSupport.write(resolvedOptions),
+                BoxliteRuntime.callTimeoutMillis(timeout)
             );
             return null;
         });
@@ -136,16 +222,36 @@ public final class BoxHandle implements AutoCloseable {
     /**
      * 将盒子内内容复制到宿主机。
      *
-     * @param containerSrc 盒子内源路径。
+     * @param containerSrc 盒子内源路径，须为绝对路径，规则见 docs/reference/validation.md。
      * @param hostDest 宿主机目标路径。
      * @param options 复制选项，传 {@code null} 等价于 {@link CopyOptions#defaults()}。
      * @return 异步完成信号。
      */
     public CompletableFuture<Void> copyOut(String containerSrc, Path hostDest, CopyOptions options) {
+        return copyOut(containerSrc, hostDest, options, null);
+    }
+
+    /**
+     * 将盒子内内容复制到宿主机，最长等待 {@code timeout}。
+     *
+     * <p>超时后抛出 {@link TimeoutException} 并中止复制；目标路径可能残留部分内容。
+     *
+     * @param containerSrc 盒子内源路径，须为绝对路径，规则见 docs/reference/validation.md。
+     * @param hostDest 宿主机目标路径。
+     * @param options 复制选项，传 {@code null} 等价于 {@link CopyOptions#defaults()}。
+     * @param timeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步完成信号。
+     */
+    public CompletableFuture<Void> copyOut(
+        String containerSrc,
+        Path hostDest,
+        CopyOptions options,
+        Duration timeout
+    ) {
         return runtime.async(() -> {
             runtime.requireNativeHandle();
-            if (containerSrc == null || containerSrc.isBlank()) {
-                throw new ConfigException("containerSrc must not be null or blank");
+            if (containerSrc == null) {
+                throw new ConfigException("containerSrc must not be null");
             }
             if (hostDest == null) {
                 throw new ConfigException("hostDest must not be null");
@@ -156,7 +262,8 @@ public final class BoxHandle implements AutoCloseable {
                 state.requireNativeHandle(),
                 containerSrc,
                 hostDest.toString(),
-                JsonSupport.write(resolvedOptions)
+                JsonSupport.write(resolvedOptions),
+                BoxliteRuntime.callTimeoutMillis(timeout)
             );
             return null;
         });
@@ -9,6 +9,7 @@ public final class BoxStateInfo {
     private final String status;
     private final boolean running;
     private final Integer pid;
+    private final Integer exitCode;
 
     /**
      * 可反序列化的盒子状态模型。
@@ -16,16 +17,19 @@ public final class BoxStateInfo {
      * @param status 状态文本。
      * @param running 盒子当前是否运行中。
      * @param pid 可选进程 ID。
+     * @param exitCode 可选的上次运行退出码（仅 exited 状态）。
      */
     @JsonCreator
     public BoxStateInfo(
         @JsonProperty("status") String status,
         @JsonProperty("running") boolean running,
-        @JsonProperty("pid") Integer pid
+        @JsonProperty("pid") Integer pid,
+        @JsonProperty("exitCode") Integer exitCode
     ) {
         this.status = Objects.requireNonNull(status, "status must not be null");
         this.running = running;
         this.pid = pid;
+        this.exitCode = exitCode;
     }
 
     /**
@@ -54,4 +58,13 @@ public final class BoxStateInfo {
     public Integer pid() {
         return pid;
     }
+
+    /**
+     * 返回上次运行的退出码。
+     *
+     * @return 状态为 {@code exited} 且退出码已知时返回退出码，否则返回 {@code null}。
+     */
+    public Integer exitCode() {
+        return exitCode;
+    }
 }
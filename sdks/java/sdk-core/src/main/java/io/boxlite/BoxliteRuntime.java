@@ -1,8 +1,10 @@
 package io.boxlite;
 
+import com.fasterxml.jackson.annotation.JsonUnwrapped;
 import io.boxlite.loader.NativeBindings;
 import java.lang.ref.Cleaner;
 import java.nio.file.Path;
+import java.time.Duration;
 import java.util.List;
 import java.util.Optional;
 import java.util.concurrent.CompletableFuture;
@@ -113,20 +115,74 @@ public final class BoxliteRuntime implements AutoCloseable {
         return create(options, null);
     }
 
+    /**
+     * 在一次性盒子中执行单条命令。
+     *
+     * <p>创建、启动、执行、收集输出与清理在一次原生调用内完成；无论成功与否都会停止并删除盒子。
+     *
+     * @param options 盒子选项，传 {@code null} 等价于 {@link BoxOptions#defaults()}。
+     * @param command 执行选项。
+     * @param runOnceOptions 一次性运行选项，传 {@code null} 等价于 {@link RunOnceOptions#defaults()}。
+     * @return 异步返回运行结果。
+     */
+    public CompletableFuture<RunOnceResult> runOnce(
+        BoxOptions options,
+        ExecCommand command,
+        RunOnceOptions runOnceOptions
+    ) {
+        BoxOptions resolvedOptions = options == null ? BoxOptions.defaults() : options;
+        RunOnceOptions resolvedRunOnceOptions =
+            runOnceOptions == null ? RunOnceOptions.defaults() : runOnceOptions;
+        return async(() -> {
+            if (command == null) {
+                throw new ConfigException("command must not be null");
+            }
+
+            String json = NativeBindings.runtimeRunOnce(
+                requireNativeHandle(),
+                JsonSupport.write(resolvedOptions),
+                JsonSupport.write(command),
+                JsonSupport.write(resolvedRunOnceOptions)
+            );
+            return JsonSupport.read(json, RunOnceResult.class);
+        });
+    }
+
     /**
      * 按名称查找盒子，不存在时创建。
      *
+     * <p>已有盒子直接返回，不比较选项。
+     *
      * @param options 需要创建时使用的盒子选项。
      * @param name 用于查找的盒子名称，可为 {@code null}。
      * @return 异步返回盒子句柄及是否新建标记。
      */
     public CompletableFuture<GetOrCreateResult> getOrCreate(BoxOptions options, String name) {
+        return getOrCreate(options, name, GetOrCreatePolicy.REUSE_ALWAYS);
+    }
+
+    /**
+     * 按名称查找盒子，不存在时创建；已有盒子的选项不同时按 {@code policy} 处理。
+     *
+     * <p>比较时忽略标签，并且不区分环境变量的顺序。
+     *
+     * @param options 需要创建时使用的盒子选项。
+     * @param name 用于查找的盒子名称，可为 {@code null}。
+     * @param policy 选项不同时的处理方式，{@code null} 表示 {@link GetOrCreatePolicy#REUSE_ALWAYS}。
+     * @return 异步返回盒子句柄及新建、重建标记。
+     */
+    public CompletableFuture<GetOrCreateResult> getOrCreate(
+        BoxOptions options,
+        String name,
+        GetOrCreatePolicy policy
+    ) {
         BoxOptions resolvedOptions = options == null ? BoxOptions.defaults() : options;
+        GetOrCreatePolicy resolvedPolicy = policy == null ? GetOrCreatePolicy.REUSE_ALWAYS : policy;
         return async(() -> {
             long runtimeHandle = requireNativeHandle();
             long[] nativeResult = NativeBindings.runtimeGetOrCreate(
                 runtimeHandle,
-                JsonSupport.write(resolvedOptions),
+                JsonSupport.write(new GetOrCreatePayload(resolvedOptions, resolvedPolicy)),
                 name
             );
 
@@ -135,8 +191,10 @@ public final class BoxliteRuntime implements AutoCloseable {
             }
 
             long boxHandle = requireValidNativeHandle(nativeResult[0], "runtimeGetOrCreate");
+            // 0 = 复用，1 = 新建，2 = 重建
             boolean created = nativeResult[1] != 0L;
-            return new GetOrCreateResult(new BoxHandle(this, boxHandle), created);
+            boolean recreated = nativeResult[1] == 2L;
+            return new GetOrCreateResult(new BoxHandle(this, boxHandle), created, recreated);
         });
     }
 
@@ -233,6 +291,18 @@ public final class BoxliteRuntime implements AutoCloseable {
         });
     }
 
+    /**
+     * 读取 boxlite 及其引擎组件（libkrun、guest/shim 二进制、bwrap、gvproxy）的版本信息。
+     *
+     * @return 异步返回版本信息。
+     */
+    public CompletableFuture<VersionInfo> versionInfo() {
+        return async(() -> {
+            String json = NativeBindings.runtimeVersionInfo(requireNativeHandle());
+            return JsonSupport.read(json, VersionInfo.class);
+        });
+    }
+
     /**
      * 关闭运行时。
      *
@@ -240,8 +310,25 @@ public final class BoxliteRuntime implements AutoCloseable {
      * @return 异步完成信号。
      */
     public CompletableFuture<Void> shutdown(Integer timeoutSeconds) {
+        return shutdown(timeoutSeconds, null);
+    }
+
+    /**
+     * 关闭运行时，并限制调用方的最长等待时间。
+     *
+     * <p>超时后抛出 {@link TimeoutException}，关闭流程仍在后台继续执行。
+     *
+     * @param timeoutSeconds 可选优雅关闭超时（秒），传 {@code null} 使用默认值。
+     * @param callTimeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步完成信号。
+     */
+    public CompletableFuture<Void> shutdown(Integer timeoutSeconds, Duration callTimeout) {
         return async(() -> {
-            NativeBindings.runtimeShutdown(requireNativeHandle(), timeoutSeconds);
+            NativeBindings.runtimeShutdown(
+                requireNativeHandle(),
+                timeoutSeconds,
+                callTimeoutMillis(callTimeout)
+            );
             return null;
         });
     }
@@ -267,6 +354,21 @@ public final class BoxliteRuntime implements AutoCloseable {
         return CompletableFuture.supplyAsync(supplier, EXECUTOR);
     }
 
+    static long callTimeoutMillis(Duration timeout) {
+        if (timeout == null || timeout.isZero()) {
+            return 0L;
+        }
+        if (timeout.isNegative()) {
+            throw new ConfigException("timeout must not be negative");
+        }
+        // Round sub-millisecond timeouts up so they are not mistaken for "no timeout".
+        try {
+            return Math.max(1L, timeout.toMillis());
+        } catch (ArithmeticException overflow) {
+            return Long.MAX_VALUE;
+        }
+    }
+
     private static BoxliteRuntime fromNativeHandle(long handle, String operation, boolean ownsNativeHandle) {
         return new BoxliteRuntime(requireValidNativeHandle(handle, operation), ownsNativeHandle);
     }
@@ -290,10 +392,13 @@ public final class BoxliteRuntime implements AutoCloseable {
         if (homeDir != null) {
             normalizedHomeDir = homeDir.toAbsolutePath().normalize().toString();
         }
-        return new RuntimePayload(normalizedHomeDir, options.imageRegistries());
+        return new RuntimePayload(normalizedHomeDir, options.imageRegistries(), options.offline());
+    }
+
+    private record RuntimePayload(String homeDir, List<String> imageRegistries, boolean offline) {
     }
 
-    private record RuntimePayload(String homeDir, List<String> imageRegistries) {
+    private record GetOrCreatePayload(@JsonUnwrapped BoxOptions options, GetOrCreatePolicy getOrCreatePolicy) {
     }
 
     private static final class RuntimeState implements Runnable {
@@ -14,6 +14,8 @@ public final class ExecCommand {
     private final Long timeoutMillis;
     private final String workingDir;
     private final boolean tty;
+    private final Scratch scratch;
+    private final OutputEncoding outputEncoding;
 
     private ExecCommand(Builder builder) {
         this.command = builder.command;
@@ -22,6 +24,8 @@ public final class ExecCommand {
         this.timeoutMillis = builder.timeoutMillis;
         this.workingDir = builder.workingDir;
         this.tty = builder.tty;
+        this.scratch = builder.scratch;
+        this.outputEncoding = builder.outputEncoding;
     }
 
     /**
@@ -88,6 +92,53 @@ public final class ExecCommand {
         return tty;
     }
 
+    /**
+     * 返回可选的临时目录配置。
+     *
+     * @return 临时目录配置，或 {@code null}。
+     */
+    public Scratch scratch() {
+        return scratch;
+    }
+
+    /**
+     * 返回输出解码方式。
+     *
+     * @return 输出解码方式，默认 {@link OutputEncoding#UTF8_LOSSY}。
+     */
+    public OutputEncoding outputEncoding() {
+        return outputEncoding;
+    }
+
+    /** 仅对单次执行可见的 tmpfs 临时目录，执行结束后释放。 */
+    public static final class Scratch {
+        private final int sizeMib;
+        private final String mountAt;
+
+        private Scratch(int sizeMib, String mountAt) {
+            this.sizeMib = sizeMib;
+            this.mountAt = mountAt;
+        }
+
+        /**
+         * 返回 tmpfs 大小上限。
+         *
+         * @return 大小（MiB），不超过 box 内存。
+         */
+        public int sizeMib() {
+            return sizeMib;
+        }
+
+        /**
+         * 返回容器内的挂载路径。
+         *
+         * @return 绝对路径，同时通过 {@code BOXLITE_SCRATCH_DIR} 暴露给命令。
+         */
+        public String mountAt() {
+            return mountAt;
+        }
+    }
+
     /** {@link ExecCommand} 的构建器。 */
     public static final class Builder {
         private final String command;
@@ -96,6 +147,8 @@ public final class ExecCommand {
         private Long timeoutMillis;
         private String workingDir;
         private boolean tty;
+        private Scratch scratch;
+        private OutputEncoding outputEncoding = OutputEncoding.UTF8_LOSSY;
 
         private Builder(String command) {
             if (command == null || command.isBlank()) {
@@ -193,6 +246,36 @@ public final class ExecCommand {
             return this;
         }
 
+        /**
+         * 为本次执行挂载私有的 tmpfs 临时目录。
+         *
+         * @param sizeMib 大小（MiB），必须 {@code > 0} 且不超过 box 内存。
+         * @param mountAt 容器内的绝对挂载路径。
+         * @return 当前构建器。
+         */
+        public Builder scratch(int sizeMib, String mountAt) {
+            if (sizeMib <= 0) {
+                throw new ConfigException("scratch sizeMib must be > 0");
+            }
+            if (mountAt == null || !mountAt.startsWith("/")) {
+                throw new ConfigException("scratch mountAt must be an absolute path");
+            }
+            this.scratch = new Scratch(sizeMib, mountAt);
+            return this;
+        }
+
+        /**
+         * 设置标准输出和标准错误的解码方式。
+         *
+         * @param outputEncoding 解码方式；{@link OutputEncoding#RAW} 时只能按字节读取输出。
+         * @return 当前构建器。
+         */
+        public Builder outputEncoding(OutputEncoding outputEncoding) {
+            Objects.requireNonNull(outputEncoding, "outputEncoding must not be null");
+            this.outputEncoding = outputEncoding;
+            return this;
+        }
+
         /**
          * 构建不可变执行命令。
          *
@@ -2,6 +2,8 @@ package io.boxlite;
 
 import io.boxlite.loader.NativeBindings;
 import java.lang.ref.Cleaner;
+import java.nio.ByteBuffer;
+import java.time.Duration;
 import java.util.Optional;
 import java.util.concurrent.CompletableFuture;
 import java.util.concurrent.atomic.AtomicLong;
@@ -48,6 +50,26 @@ public final class ExecutionHandle implements AutoCloseable {
         });
     }
 
+    /**
+     * 将直接缓冲区中 {@code position} 到 {@code limit} 之间的字节写入进程标准输入，不经过 Java 堆复制。
+     *
+     * <p>写入成功后缓冲区的 {@code position} 前移到 {@code limit}。
+     *
+     * @param buffer 直接缓冲区（{@link ByteBuffer#allocateDirect(int)}）。
+     * @return 异步完成信号。
+     */
+    public CompletableFuture<Void> stdinWrite(ByteBuffer buffer) {
+        return runtime.async(() -> {
+            runtime.requireNativeHandle();
+            requireDirect(buffer);
+            int position = buffer.position();
+            int length = buffer.remaining();
+            NativeBindings.executionStdinWriteDirect(state.requireNativeHandle(), buffer, position, length);
+            buffer.position(position + length);
+            return null;
+        });
+    }
+
     /**
      * 关闭进程标准输入。
      *
@@ -64,6 +86,10 @@ public final class ExecutionHandle implements AutoCloseable {
     /**
      * 读取标准输出的下一行。
      *
+     * <p>命令使用 {@link OutputEncoding#RAW} 时抛出 {@link InvalidStateException}，请改用
+     * {@link #stdoutNextLineBytes()}；使用 {@link OutputEncoding#STRICT} 时，遇到无效 UTF-8
+     * 的位置读取失败。
+     *
      * @return 异步返回下一行；流结束时为空。
      */
     public CompletableFuture<Optional<String>> stdoutNextLine() {
@@ -75,7 +101,47 @@ public final class ExecutionHandle implements AutoCloseable {
     }
 
     /**
-     * 读取标准错误的下一行。
+     * 按原始字节读取标准输出的下一行，适用于任何输出解码方式。
+     *
+     * @return 异步返回下一行的字节；流结束时为空。
+     */
+    public CompletableFuture<Optional<byte[]>> stdoutNextLineBytes() {
+        return runtime.async(() -> {
+            runtime.requireNativeHandle();
+            byte[] line = NativeBindings.executionStdoutNextLineBytes(state.requireNativeHandle());
+            return Optional.ofNullable(line);
+        });
+    }
+
+    /**
+     * 将标准输出读入直接缓冲区，从 {@code position} 开始最多写到 {@code limit}。
+     *
+     * <p>没有可读数据时等待输出；读到的字节数不超过 {@code remaining()}，缓冲区的
+     * {@code position} 随之前移。与 {@link #stdoutNextLine()} 可交替使用。
+     *
+     * @param buffer 直接缓冲区（{@link ByteBuffer#allocateDirect(int)}）。
+     * @return 异步返回读取的字节数；流结束时为 {@code -1}。
+     */
+    public CompletableFuture<Integer> stdoutReadInto(ByteBuffer buffer) {
+        return runtime.async(() -> {
+            runtime.requireNativeHandle();
+            requireDirect(buffer);
+            // The native side fills from the start of the buffer it is given
+            ByteBuffer target = buffer.position() == 0 ? buffer : buffer.slice();
+            int read = NativeBindings.executionStdoutReadInto(
+                state.requireNativeHandle(),
+                target,
+                buffer.remaining()
+            );
+            if (read > 0) {
+                buffer.position(buffer.position() + read);
+            }
+            return read;
+        });
+    }
+
+    /**
+     * 读取标准错误的下一行，输出解码方式的限制同 {@link #stdoutNextLine()}。
      *
      * @return 异步返回下一行；流结束时为空。
      */
@@ -87,15 +153,43 @@ public final class ExecutionHandle implements AutoCloseable {
         });
     }
 
+    /**
+     * 按原始字节读取标准错误的下一行，适用于任何输出解码方式。
+     *
+     * @return 异步返回下一行的字节；流结束时为空。
+     */
+    public CompletableFuture<Optional<byte[]>> stderrNextLineBytes() {
+        return runtime.async(() -> {
+            runtime.requireNativeHandle();
+            byte[] line = NativeBindings.executionStderrNextLineBytes(state.requireNativeHandle());
+            return Optional.ofNullable(line);
+        });
+    }
+
     /**
      * 等待进程结束。
      *
      * @return 异步返回执行结果。
      */
     public CompletableFuture<ExecResult> waitFor() {
+        return waitFor(null);
+    }
+
+    /**
+     * 等待进程结束，最长等待 {@code timeout}。
+     *
+     * <p>超时后抛出 {@link TimeoutException}；进程不受影响，可再次调用本方法继续等待。
+     *
+     * @param timeout 调用超时，传 {@code null} 或零表示无限等待。
+     * @return 异步返回执行结果。
+     */
+    public CompletableFuture<ExecResult> waitFor(Duration timeout) {
         return runtime.async(() -> {
             runtime.requireNativeHandle();
-            String json = NativeBindings.executionWait(state.requireNativeHandle());
+            String json = NativeBindings.executionWait(
+                state.requireNativeHandle(),
+                BoxliteRuntime.callTimeoutMillis(timeout)
+            );
             return JsonSupport.read(json, ExecResult.class);
         });
     }
@@ -131,6 +225,15 @@ public final class ExecutionHandle implements AutoCloseable {
         });
     }
 
+    private static void requireDirect(ByteBuffer buffer) {
+        if (buffer == null) {
+            throw new ConfigException("buffer must not be null");
+        }
+        if (!buffer.isDirect()) {
+            throw new ConfigException("buffer must be a direct ByteBuffer");
+        }
+    }
+
     /** 释放原生执行句柄，可重复调用。 */
     @Override
     public void close() {
@@ -0,0 +1,29 @@
+package io.boxlite;
+
+import com.fasterxml.jackson.annotation.JsonValue;
+
+/** 同名盒子已存在且选项不同时 {@link BoxliteRuntime#getOrCreate} 的处理方式。 */
+public enum GetOrCreatePolicy {
+    /** 始终返回已有盒子（默认）。 */
+    REUSE_ALWAYS("reuse_always"),
+    /** 停止并删除已有盒子，再按新选项创建。 */
+    RECREATE_ON_MISMATCH("recreate_on_mismatch"),
+    /** 抛出 {@link InvalidStateException}，消息中列出不同的选项。 */
+    FAIL_ON_MISMATCH("fail_on_mismatch");
+
+    private final String wireName;
+
+    GetOrCreatePolicy(String wireName) {
+        this.wireName = wireName;
+    }
+
+    /**
+     * 返回传给原生层的策略名。
+     *
+     * @return 策略名，例如 {@code recreate_on_mismatch}。
+     */
+    @JsonValue
+    public String wireName() {
+        return wireName;
+    }
+}
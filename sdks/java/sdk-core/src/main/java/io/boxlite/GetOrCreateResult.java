@@ -6,6 +6,7 @@ import java.util.Objects;
 public final class GetOrCreateResult {
     private final BoxHandle box;
     private final boolean created;
+    private final boolean recreated;
 
     /**
      * 创建 get-or-create 结果对象。
@@ -14,8 +15,20 @@ public final class GetOrCreateResult {
      * @param created 盒子是否为新创建。
      */
     public GetOrCreateResult(BoxHandle box, boolean created) {
+        this(box, created, false);
+    }
+
+    /**
+     * 创建 get-or-create 结果对象。
+     *
+     * @param box 返回的盒子句柄。
+     * @param created 盒子是否为新创建。
+     * @param recreated 盒子是否因选项不同而重建。
+     */
+    public GetOrCreateResult(BoxHandle box, boolean created, boolean recreated) {
         this.box = Objects.requireNonNull(box, "box must not be null");
-        this.created = created;
+        this.created = created || recreated;
+        this.recreated = recreated;
     }
 
     /**
@@ -35,4 +48,15 @@ public final class GetOrCreateResult {
     public boolean created() {
         return created;
     }
+
+    /**
+     * 返回重建状态。
+     *
+     * <p>重建的盒子同时也是新创建的。
+     *
+     * @return 已有盒子因选项不同被删除并重建时为 {@code true}。
+     */
+    public boolean recreated() {
+        return recreated;
+    }
 }
@@ -9,10 +9,12 @@ import java.util.Objects;
 public final class Options {
     private final Path homeDir;
     private final List<String> imageRegistries;
+    private final boolean offline;
 
     private Options(Builder builder) {
         this.homeDir = builder.homeDir;
         this.imageRegistries = List.copyOf(builder.imageRegistries);
+        this.offline = builder.offline;
     }
 
     /**
@@ -51,10 +53,20 @@ public final class Options {
         return imageRegistries;
     }
 
+    /**
+     * 返回是否启用离线（气隙）模式。
+     *
+     * @return 为 {@code true} 时禁止任何镜像仓库访问，仅使用本地缓存。
+     */
+    public boolean offline() {
+        return offline;
+    }
+
     /** {@link Options} 的构建器。 */
     public static final class Builder {
         private Path homeDir;
         private List<String> imageRegistries = List.of();
+        private boolean offline;
 
         private Builder() {
         }
@@ -97,6 +109,17 @@ public final class Options {
             return this;
         }
 
+        /**
+         * 设置离线（气隙）模式。
+         *
+         * @param offline 为 {@code true} 时禁止任何网络访问。
+         * @return 当前构建器。
+         */
+        public Builder offline(boolean offline) {
+            this.offline = offline;
+            return this;
+        }
+
         /**
          * 构建不可变运行时选项。
          *
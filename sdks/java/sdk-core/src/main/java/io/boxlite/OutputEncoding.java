@@ -0,0 +1,29 @@
+package io.boxlite;
+
+import com.fasterxml.jackson.annotation.JsonValue;
+
+/** 命令标准输出和标准错误的解码方式，见 {@link ExecCommand.Builder#outputEncoding}。 */
+public enum OutputEncoding {
+    /** 按 UTF-8 解码，无效字节替换为 U+FFFD（默认）。 */
+    UTF8_LOSSY("utf8_lossy"),
+    /** 按 UTF-8 解码，遇到第一个无效序列时读取失败，异常消息给出其字节位置。 */
+    STRICT("strict"),
+    /** 不解码，只能通过 {@link ExecutionHandle#stdoutNextLineBytes()} 等字节接口读取。 */
+    RAW("raw");
+
+    private final String wireName;
+
+    OutputEncoding(String wireName) {
+        this.wireName = wireName;
+    }
+
+    /**
+     * 返回传给原生层的编码名。
+     *
+     * @return 编码名，例如 {@code utf8_lossy}。
+     */
+    @JsonValue
+    public String wireName() {
+        return wireName;
+    }
+}
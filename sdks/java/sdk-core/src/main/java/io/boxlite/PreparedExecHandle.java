@@ -0,0 +1,80 @@
+package io.boxlite;
+
+import io.boxlite.loader.NativeBindings;
+import java.lang.ref.Cleaner;
+import java.util.List;
+import java.util.concurrent.CompletableFuture;
+import java.util.concurrent.atomic.AtomicLong;
+
+/** 预注册命令句柄，重复执行同一命令模板。 */
+public final class PreparedExecHandle implements AutoCloseable {
+    private static final Cleaner CLEANER = Cleaner.create();
+
+    private final BoxliteRuntime runtime;
+    private final PreparedState state;
+    private final Cleaner.Cleanable cleanable;
+
+    PreparedExecHandle(BoxliteRuntime runtime, long nativeHandle) {
+        this.runtime = runtime;
+        this.state = new PreparedState(nativeHandle);
+        this.cleanable = CLEANER.register(this, state);
+    }
+
+    /**
+     * 以追加参数运行命令模板。
+     *
+     * @param args 追加到模板参数之后的参数，传 {@code null} 等价于空列表。
+     * @return 异步返回执行句柄。
+     */
+    public CompletableFuture<ExecutionHandle> run(List<String> args) {
+        return runtime.async(() -> {
+            runtime.requireNativeHandle();
+            List<String> resolvedArgs = args == null ? List.of() : List.copyOf(args);
+
+            long execHandle = NativeBindings.preparedExecRun(
+                state.requireNativeHandle(),
+                JsonSupport.write(resolvedArgs)
+            );
+            if (execHandle == 0L) {
+                throw new InternalException("Native preparedExecRun returned invalid handle 0");
+            }
+            return new ExecutionHandle(runtime, execHandle);
+        });
+    }
+
+    /** 释放原生预注册命令句柄，可重复调用。 */
+    @Override
+    public void close() {
+        cleanable.clean();
+    }
+
+    private static final class PreparedState implements Runnable {
+        private final AtomicLong handle;
+
+        private PreparedState(long handle) {
+            this.handle = new AtomicLong(handle);
+        }
+
+        private long requireNativeHandle() {
+            long value = handle.get();
+            if (value == 0L) {
+                throw new InvalidStateException("Prepared exec handle is closed");
+            }
+            return value;
+        }
+
+        @Override
+        public void run() {
+            long value = handle.getAndSet(0L);
+            if (value == 0L) {
+                return;
+            }
+
+            try {
+                NativeBindings.preparedExecFree(value);
+            } catch (RuntimeException ignored) {
+                // Cleaner fallback must not fail on GC threads.
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+package io.boxlite;
+
+/** {@link BoxliteRuntime#runOnce} 的一次性运行选项。 */
+public final class RunOnceOptions {
+    private final Long maxOutputBytes;
+    private final Long timeoutMillis;
+
+    private RunOnceOptions(Builder builder) {
+        this.maxOutputBytes = builder.maxOutputBytes;
+        this.timeoutMillis = builder.timeoutMillis;
+    }
+
+    /**
+     * 返回默认一次性运行选项。
+     *
+     * @return 默认选项对象。
+     */
+    public static RunOnceOptions defaults() {
+        return builder().build();
+    }
+
+    /**
+     * 创建一次性运行选项构建器。
+     *
+     * @return 构建器实例。
+     */
+    public static Builder builder() {
+        return new Builder();
+    }
+
+    /**
+     * 返回单个输出流的捕获上限。
+     *
+     * @return 字节数，或 {@code null} 表示使用原生默认值（1 MiB）。
+     */
+    public Long maxOutputBytes() {
+        return maxOutputBytes;
+    }
+
+    /**
+     * 返回整体超时时间。
+     *
+     * @return 超时毫秒数，或 {@code null} 表示不限制。
+     */
+    public Long timeoutMillis() {
+        return timeoutMillis;
+    }
+
+    /** {@link RunOnceOptions} 的构建器。 */
+    public static final class Builder {
+        private Long maxOutputBytes;
+        private Long timeoutMillis;
+
+        private Builder() {
+        }
+
+        /**
+         * 设置 stdout 与 stderr 各自的捕获上限，超出部分被丢弃。
+         *
+         * @param maxOutputBytes 字节数；提供时必须 {@code >= 0}。
+         * @return 当前构建器。
+         */
+        public Builder maxOutputBytes(Long maxOutputBytes) {
+            if (maxOutputBytes != null && maxOutputBytes < 0) {
+                throw new ConfigException("maxOutputBytes must be >= 0 when provided");
+            }
+            this.maxOutputBytes = maxOutputBytes;
+            return this;
+        }
+
+        /**
+         * 设置覆盖创建、启动、执行与输出收集的整体超时。
+         *
+         * @param timeoutMillis 超时时间（毫秒）；提供时必须 {@code > 0}。
+         * @return 当前构建器。
+         */
+        public Builder timeoutMillis(Long timeoutMillis) {
+            if (timeoutMillis != null && timeoutMillis <= 0) {
+                throw new ConfigException("timeoutMillis must be > 0 when provided");
+            }
+            this.timeoutMillis = timeoutMillis;
+            return this;
+        }
+
+        /**
+         * 构建不可变一次性运行选项。
+         *
+         * @return 选项对象。
+         */
+        public RunOnceOptions build() {
+            return new RunOnceOptions(this);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+package io.boxlite;
+
+import com.fasterxml.jackson.annotation.JsonCreator;
+import com.fasterxml.jackson.annotation.JsonProperty;
+
+/** 通过 {@link BoxliteRuntime#runOnce} 得到的一次性运行结果。 */
+public final class RunOnceResult {
+    private final int exitCode;
+    private final String stdout;
+    private final String stderr;
+    private final boolean stdoutTruncated;
+    private final boolean stderrTruncated;
+    private final String errorMessage;
+    private final long durationMillis;
+
+    /**
+     * 可反序列化的一次性运行结果模型。
+     *
+     * @param exitCode 进程退出码。
+     * @param stdout 捕获的标准输出。
+     * @param stderr 捕获的标准错误。
+     * @param stdoutTruncated 标准输出是否超出捕获上限。
+     * @param stderrTruncated 标准错误是否超出捕获上限。
+     * @param errorMessage 来自原生层的可选错误信息。
+     * @param durationMillis 从创建盒子到命令结束的耗时（毫秒）。
+     */
+    @JsonCreator
+    public RunOnceResult(
+        @JsonProperty("exitCode") int exitCode,
+        @JsonProperty("stdout") String stdout,
+        @JsonProperty("stderr") String stderr,
+        @JsonProperty("stdoutTruncated") boolean stdoutTruncated,
+        @JsonProperty("stderrTruncated") boolean stderrTruncated,
+        @JsonProperty("errorMessage") String errorMessage,
+        @JsonProperty("durationMillis") long durationMillis
+    ) {
+        this.exitCode = exitCode;
+        this.stdout = stdout == null ? "" : stdout;
+        this.stderr = stderr == null ? "" : stderr;
+        this.stdoutTruncated = stdoutTruncated;
+        this.stderrTruncated = stderrTruncated;
+        this.errorMessage = errorMessage;
+        this.durationMillis = durationMillis;
+    }
+
+    /**
+     * 返回进程退出码。
+     *
+     * @return 退出码。
+     */
+    public int exitCode() {
+        return exitCode;
+    }
+
+    /**
+     * 返回捕获的标准输出。
+     *
+     * @return 标准输出文本。
+     */
+    public String stdout() {
+        return stdout;
+    }
+
+    /**
+     * 返回捕获的标准错误。
+     *
+     * @return 标准错误文本。
+     */
+    public String stderr() {
+        return stderr;
+    }
+
+    /**
+     * 返回标准输出是否被截断。
+     *
+     * @return 超出捕获上限时返回 {@code true}。
+     */
+    public boolean stdoutTruncated() {
+        return stdoutTruncated;
+    }
+
+    /**
+     * 返回标准错误是否被截断。
+     *
+     * @return 超出捕获上限时返回 {@code true}。
+     */
+    public boolean stderrTruncated() {
+        return stderrTruncated;
+    }
+
+    /**
+     * 返回可选错误信息。
+     *
+     * @return 原生侧错误信息，或 {@code null}。
+     */
+    public String errorMessage() {
+        return errorMessage;
+    }
+
+    /**
+     * 返回运行耗时。
+     *
+     * @return 耗时毫秒数。
+     */
+    public long durationMillis() {
+        return durationMillis;
+    }
+
+    /**
+     * 返回执行是否成功。
+     *
+     * @return 当 {@link #exitCode()} 为 0 时返回 {@code true}。
+     */
+    public boolean success() {
+        return exitCode == 0;
+    }
+}
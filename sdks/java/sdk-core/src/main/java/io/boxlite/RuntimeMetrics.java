@@ -5,6 +5,9 @@ import com.fasterxml.jackson.annotation.JsonProperty;
 
 /** 运行时全局指标快照。 */
 public final class RuntimeMetrics {
+    private final long snapshotId;
+    private final long takenAtMillis;
+    private final long resetEpoch;
     private final long boxesCreatedTotal;
     private final long boxesFailedTotal;
     private final long boxesStoppedTotal;
@@ -15,6 +18,9 @@ public final class RuntimeMetrics {
     /**
      * 可反序列化的运行时指标模型。
      *
+     * @param snapshotId 快照序号（进程内单调递增）。
+     * @param takenAtMillis 快照时间（Unix 毫秒）。
+     * @param resetEpoch 截至该快照的计数器重置次数。
      * @param boxesCreatedTotal 累计创建盒子数。
      * @param boxesFailedTotal 累计失败盒子操作数。
      * @param boxesStoppedTotal 累计停止盒子数。
@@ -24,6 +30,9 @@ public final class RuntimeMetrics {
      */
     @JsonCreator
     public RuntimeMetrics(
+        @JsonProperty("snapshotId") long snapshotId,
+        @JsonProperty("takenAtMillis") long takenAtMillis,
+        @JsonProperty("resetEpoch") long resetEpoch,
         @JsonProperty("boxesCreatedTotal") long boxesCreatedTotal,
         @JsonProperty("boxesFailedTotal") long boxesFailedTotal,
         @JsonProperty("boxesStoppedTotal") long boxesStoppedTotal,
@@ -31,6 +40,9 @@ public final class RuntimeMetrics {
         @JsonProperty("totalCommandsExecuted") long totalCommandsExecuted,
         @JsonProperty("totalExecErrors") long totalExecErrors
     ) {
+        this.snapshotId = snapshotId;
+        this.takenAtMillis = takenAtMillis;
+        this.resetEpoch = resetEpoch;
         this.boxesCreatedTotal = boxesCreatedTotal;
         this.boxesFailedTotal = boxesFailedTotal;
         this.boxesStoppedTotal = boxesStoppedTotal;
@@ -39,6 +51,33 @@ public final class RuntimeMetrics {
         this.totalExecErrors = totalExecErrors;
     }
 
+    /**
+     * 返回快照序号，较晚的快照序号更大。
+     *
+     * @return 快照序号。
+     */
+    public long snapshotId() {
+        return snapshotId;
+    }
+
+    /**
+     * 返回快照时间。
+     *
+     * @return Unix 毫秒时间戳。
+     */
+    public long takenAtMillis() {
+        return takenAtMillis;
+    }
+
+    /**
+     * 返回截至该快照的计数器重置次数。
+     *
+     * @return 重置次数。
+     */
+    public long resetEpoch() {
+        return resetEpoch;
+    }
+
     /**
      * 返回运行时启动以来累计创建盒子数。
      *
@@ -92,4 +131,35 @@ public final class RuntimeMetrics {
     public long totalExecErrors() {
         return totalExecErrors;
     }
+
+    /**
+     * 计算从较早快照到当前快照的增量。
+     *
+     * <p>若两次快照之间计数器被重置，增量取重置后的累计值（真实值的下界）。
+     *
+     * @param earlier 较早的指标快照。
+     * @return 指标增量。
+     */
+    public RuntimeMetricsDelta diff(RuntimeMetrics earlier) {
+        boolean countersReset = resetEpoch != earlier.resetEpoch;
+        return new RuntimeMetricsDelta(
+            earlier.snapshotId,
+            snapshotId,
+            takenAtMillis - earlier.takenAtMillis,
+            countersReset,
+            delta(countersReset, boxesCreatedTotal, earlier.boxesCreatedTotal),
+            delta(countersReset, boxesFailedTotal, earlier.boxesFailedTotal),
+            delta(countersReset, boxesStoppedTotal, earlier.boxesStoppedTotal),
+            delta(countersReset, totalCommandsExecuted, earlier.totalCommandsExecuted),
+            delta(countersReset, totalExecErrors, earlier.totalExecErrors),
+            numRunningBoxes - earlier.numRunningBoxes
+        );
+    }
+
+    private static long delta(boolean countersReset, long later, long before) {
+        if (countersReset) {
+            return later;
+        }
+        return Math.max(0, later - before);
+    }
 }
@@ -0,0 +1,129 @@
+package io.boxlite;
+
+/** 两次运行时指标快照之间的增量。 */
+public final class RuntimeMetricsDelta {
+    private final long fromSnapshotId;
+    private final long toSnapshotId;
+    private final long elapsedMillis;
+    private final boolean countersReset;
+    private final long boxesCreated;
+    private final long boxesFailed;
+    private final long boxesStopped;
+    private final long commandsExecuted;
+    private final long execErrors;
+    private final long runningBoxesChange;
+
+    RuntimeMetricsDelta(
+        long fromSnapshotId,
+        long toSnapshotId,
+        long elapsedMillis,
+        boolean countersReset,
+        long boxesCreated,
+        long boxesFailed,
+        long boxesStopped,
+        long commandsExecuted,
+        long execErrors,
+        long runningBoxesChange
+    ) {
+        this.fromSnapshotId = fromSnapshotId;
+        this.toSnapshotId = toSnapshotId;
+        this.elapsedMillis = elapsedMillis;
+        this.countersReset = countersReset;
+        this.boxesCreated = boxesCreated;
+        this.boxesFailed = boxesFailed;
+        this.boxesStopped = boxesStopped;
+        this.commandsExecuted = commandsExecuted;
+        this.execErrors = execErrors;
+        this.runningBoxesChange = runningBoxesChange;
+    }
+
+    /**
+     * 返回较早快照的序号。
+     *
+     * @return 起始快照序号。
+     */
+    public long fromSnapshotId() {
+        return fromSnapshotId;
+    }
+
+    /**
+     * 返回较晚快照的序号。
+     *
+     * @return 结束快照序号。
+     */
+    public long toSnapshotId() {
+        return toSnapshotId;
+    }
+
+    /**
+     * 返回两次快照之间的时间间隔。
+     *
+     * @return 间隔毫秒数。
+     */
+    public long elapsedMillis() {
+        return elapsedMillis;
+    }
+
+    /**
+     * 返回两次快照之间计数器是否被重置。
+     *
+     * @return 发生重置时为 {@code true}。
+     */
+    public boolean countersReset() {
+        return countersReset;
+    }
+
+    /**
+     * 返回期间新建盒子数。
+     *
+     * @return 创建增量。
+     */
+    public long boxesCreated() {
+        return boxesCreated;
+    }
+
+    /**
+     * 返回期间失败盒子操作数。
+     *
+     * @return 失败增量。
+     */
+    public long boxesFailed() {
+        return boxesFailed;
+    }
+
+    /**
+     * 返回期间停止盒子数。
+     *
+     * @return 停止增量。
+     */
+    public long boxesStopped() {
+        return boxesStopped;
+    }
+
+    /**
+     * 返回期间执行命令数。
+     *
+     * @return 命令增量。
+     */
+    public long commandsExecuted() {
+        return commandsExecuted;
+    }
+
+    /**
+     * 返回期间命令执行错误数。
+     *
+     * @return 执行错误增量。
+     */
+    public long execErrors() {
+        return execErrors;
+    }
+
+    /**
+     * 返回运行中盒子数的变化量，可能为负。
+     *
+     * @return 运行中盒子数变化。
+     */
+    public long runningBoxesChange() {
+        return runningBoxesChange;
+    }
+}
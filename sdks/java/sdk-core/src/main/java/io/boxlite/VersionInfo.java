@@ -0,0 +1,160 @@
+package io.boxlite;
+
+import com.fasterxml.jackson.annotation.JsonCreator;
+import com.fasterxml.jackson.annotation.JsonProperty;
+
+/** boxlite 及其引擎组件的版本信息，用于跨机器排查问题。未知组件为 {@code null}。 */
+public final class VersionInfo {
+    private final String version;
+    private final String gitCommit;
+    private final String guestHash;
+    private final String shimPath;
+    private final String shimHash;
+    private final String libkrun;
+    private final String libkrunfw;
+    private final String bwrap;
+    private final String gvproxy;
+    private final String os;
+    private final String arch;
+
+    /**
+     * 可反序列化的版本信息模型。
+     *
+     * @param version boxlite 版本号。
+     * @param gitCommit 构建时的 git 提交。
+     * @param guestHash boxlite-guest 二进制的 SHA256。
+     * @param shimPath boxlite-shim 二进制路径。
+     * @param shimHash boxlite-shim 二进制的 SHA256。
+     * @param libkrun libkrun 版本。
+     * @param libkrunfw libkrunfw（guest 内核）版本。
+     * @param bwrap 内置 bubblewrap 版本（仅 Linux）。
+     * @param gvproxy gvproxy 版本。
+     * @param os 宿主机操作系统。
+     * @param arch 宿主机 CPU 架构。
+     */
+    @JsonCreator
+    public VersionInfo(
+        @JsonProperty("version") String version,
+        @JsonProperty("gitCommit") String gitCommit,
+        @JsonProperty("guestHash") String guestHash,
+        @JsonProperty("shimPath") String shimPath,
+        @JsonProperty("shimHash") String shimHash,
+        @JsonProperty("libkrun") String libkrun,
+        @JsonProperty("libkrunfw") String libkrunfw,
+        @JsonProperty("bwrap") String bwrap,
+        @JsonProperty("gvproxy") String gvproxy,
+        @JsonProperty("os") String os,
+        @JsonProperty("arch") String arch
+    ) {
+        this.version = version;
+        this.gitCommit = gitCommit;
+        this.guestHash = guestHash;
+        this.shimPath = shimPath;
+        this.shimHash = shimHash;
+        this.libkrun = libkrun;
+        this.libkrunfw = libkrunfw;
+        this.bwrap = bwrap;
+        this.gvproxy = gvproxy;
+        this.os = os;
+        this.arch = arch;
+    }
+
+    /**
+     * 返回boxlite 版本号。
+     *
+     * @return boxlite 版本号。
+     */
+    public String version() {
+        return version;
+    }
+
+    /**
+     * 返回构建时的 git 提交。
+     *
+     * @return git 提交哈希，或 {@code null}。
+     */
+    public String gitCommit() {
+        return gitCommit;
+    }
+
+    /**
+     * 返回boxlite-guest 二进制的 SHA256。
+     *
+     * @return SHA256 十六进制摘要，或 {@code null}。
+     */
+    public String guestHash() {
+        return guestHash;
+    }
+
+    /**
+     * 返回boxlite-shim 二进制路径。
+     *
+     * @return shim 路径，或 {@code null}。
+     */
+    public String shimPath() {
+        return shimPath;
+    }
+
+    /**
+     * 返回boxlite-shim 二进制的 SHA256。
+     *
+     * @return SHA256 十六进制摘要，或 {@code null}。
+     */
+    public String shimHash() {
+        return shimHash;
+    }
+
+    /**
+     * 返回libkrun 版本。
+     *
+     * @return libkrun 版本，或 {@code null}。
+     */
+    public String libkrun() {
+        return libkrun;
+    }
+
+    /**
+     * 返回libkrunfw（guest 内核）版本。
+     *
+     * @return libkrunfw 版本，或 {@code null}。
+     */
+    public String libkrunfw() {
+        return libkrunfw;
+    }
+
+    /**
+     * 返回内置 bubblewrap 版本（仅 Linux）。
+     *
+     * @return bwrap 版本，或 {@code null}。
+     */
+    public String bwrap() {
+        return bwrap;
+    }
+
+    /**
+     * 返回gvproxy 版本。
+     *
+     * @return gvproxy 版本，或 {@code null}。
+     */
+    public String gvproxy() {
+        return gvproxy;
+    }
+
+    /**
+     * 返回宿主机操作系统。
+     *
+     * @return 操作系统名称。
+     */
+    public String os() {
+        return os;
+    }
+
+    /**
+     * 返回宿主机 CPU 架构。
+     *
+     * @return CPU 架构名称。
+     */
+    public String arch() {
+        return arch;
+    }
+}
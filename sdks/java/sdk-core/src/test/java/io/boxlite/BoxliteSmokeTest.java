@@ -9,14 +9,19 @@ import static org.junit.jupiter.api.Assertions.assertTrue;
 import static org.junit.jupiter.api.Assertions.fail;
 
 import java.io.IOException;
+import java.nio.ByteBuffer;
 import java.nio.charset.StandardCharsets;
 import java.nio.file.Files;
 import java.nio.file.Path;
+import java.util.ArrayList;
 import java.util.Comparator;
 import java.util.List;
 import java.util.Locale;
 import java.util.concurrent.CompletableFuture;
 import java.util.concurrent.CompletionException;
+import java.util.concurrent.ExecutorService;
+import java.util.concurrent.Executors;
+import java.util.concurrent.Future;
 import java.util.concurrent.TimeUnit;
 import java.util.Optional;
 import java.util.UUID;
@@ -27,6 +32,9 @@ import org.junit.jupiter.api.io.TempDir;
 
 class BoxliteSmokeTest {
     private static final int LOG_TAIL_LINES = 120;
+    private static final int STRESS_THREADS = 64;
+    private static final int STRESS_CYCLES = 5;
+    private static final int THROUGHPUT_BYTES = 16 * 1024 * 1024;
     private static final Path SHARED_RUNTIME_HOME = Path.of(
         System.getProperty("user.home"),
         ".boxlite"
@@ -112,6 +120,9 @@ class BoxliteSmokeTest {
 
             RuntimeMetrics metrics = runtime.metrics().join();
             assertNotNull(metrics, "Runtime metrics should be available");
+            RuntimeMetrics later = runtime.metrics().join();
+            assertTrue(later.snapshotId() > metrics.snapshotId(), "Snapshot ids should increase");
+            assertEquals(0, later.diff(metrics).boxesCreated(), "No boxes created between snapshots");
 
             first.box().close();
             second.box().close();
@@ -119,6 +130,37 @@ class BoxliteSmokeTest {
         }
     }
 
+    @Test
+    void getOrCreatePolicyHandlesChangedOptions() {
+        try (TestRuntime fixture = newRuntimeForTest("case-get-or-create-policy")) {
+            BoxliteRuntime runtime = fixture.runtime();
+            String name = "java-goc-policy-" + UUID.randomUUID();
+            BoxOptions changed = BoxOptions.builder().putEnv("MODE", "ci").build();
+
+            GetOrCreateResult first = runtime.getOrCreate(BoxOptions.defaults(), name).join();
+            String firstId = first.box().id();
+            RuntimeException failure = joinFailure(
+                runtime.getOrCreate(changed, name, GetOrCreatePolicy.FAIL_ON_MISMATCH)
+            );
+            assertInstanceOf(InvalidStateException.class, failure);
+            assertTrue(failure.getMessage().contains("env.MODE"), failure.getMessage());
+
+            GetOrCreateResult recreated =
+                runtime.getOrCreate(changed, name, GetOrCreatePolicy.RECREATE_ON_MISMATCH).join();
+            assertTrue(recreated.recreated(), "Changed options should recreate the box");
+            assertTrue(recreated.created(), "A recreated box is a new box");
+            assertFalse(firstId.equals(recreated.box().id()), "Expected a new box id");
+
+            GetOrCreateResult reused =
+                runtime.getOrCreate(changed, name, GetOrCreatePolicy.FAIL_ON_MISMATCH).join();
+            assertFalse(reused.created(), "Matching options should reuse the box");
+
+            first.box().close();
+            recreated.box().close();
+            reused.box().close();
+        }
+    }
+
     @Test
     void boxCanStartStopAndBeReattached() throws Exception {
         runVmTest("case-start-stop", runtime -> {
@@ -226,6 +268,89 @@ class BoxliteSmokeTest {
         });
     }
 
+    @Test
+    void execDirectBufferRoundTripWorks() throws Exception {
+        runVmTest("case-exec-direct", runtime -> {
+            BoxOptions options = BoxOptions.builder()
+                .autoRemove(false)
+                .build();
+            BoxHandle box = runtime.create(options, "java-exec-direct-" + UUID.randomUUID()).join();
+            ExecutionHandle exec = box.exec(ExecCommand.builder("cat").build()).join();
+
+            ByteBuffer input = ByteBuffer.allocateDirect(64);
+            input.put("hello-direct\n".getBytes(StandardCharsets.UTF_8)).flip();
+            exec.stdinWrite(input).join();
+            assertFalse(input.hasRemaining(), "write should consume the buffer");
+            exec.stdinClose().join();
+
+            ByteBuffer output = ByteBuffer.allocateDirect(64);
+            while (exec.stdoutReadInto(output).join() >= 0) {
+                // Keep reading until EOF
+            }
+            output.flip();
+            assertEquals("hello-direct\n", StandardCharsets.UTF_8.decode(output).toString());
+
+            RuntimeException heap = joinFailure(exec.stdoutReadInto(ByteBuffer.allocate(16)));
+            assertInstanceOf(ConfigException.class, heap);
+
+            assertTrue(exec.waitFor().join().success(), "cat should exit successfully");
+            exec.close();
+            runtime.remove(box.id(), true).join();
+            box.close();
+        });
+    }
+
+    @Test
+    void directBufferReadsOutpaceLineReads() throws Exception {
+        runVmTest("case-exec-throughput", runtime -> {
+            BoxOptions options = BoxOptions.builder()
+                .autoRemove(false)
+                .build();
+            BoxHandle box = runtime.create(options, "java-exec-throughput-" + UUID.randomUUID()).join();
+            ExecCommand command = ExecCommand.builder("sh")
+                .addArg("-c")
+                .addArg("yes 0123456789abcdef | head -c " + THROUGHPUT_BYTES)
+                .build();
+
+            ExecutionHandle lines = box.exec(command).join();
+            long lineStart = System.nanoTime();
+            long lineBytes = 0;
+            for (Optional<String> line = lines.stdoutNextLine().join();
+                line.isPresent();
+                line = lines.stdoutNextLine().join()) {
+                lineBytes += line.get().getBytes(StandardCharsets.UTF_8).length;
+            }
+            long lineNanos = System.nanoTime() - lineStart;
+            assertTrue(lines.waitFor().join().success(), "line reader command should succeed");
+            lines.close();
+
+            ExecutionHandle direct = box.exec(command).join();
+            ByteBuffer buffer = ByteBuffer.allocateDirect(256 * 1024);
+            long directStart = System.nanoTime();
+            long directBytes = 0;
+            for (int read = direct.stdoutReadInto(buffer).join();
+                read >= 0;
+                read = direct.stdoutReadInto(buffer.clear()).join()) {
+                directBytes += read;
+            }
+            long directNanos = System.nanoTime() - directStart;
+            assertTrue(direct.waitFor().join().success(), "direct reader command should succeed");
+            direct.close();
+
+            assertEquals(THROUGHPUT_BYTES, lineBytes);
+            assertEquals(THROUGHPUT_BYTES, directBytes);
+            System.out.printf(
+                Locale.ROOT,
+                "[boxlite-java-test] stdout throughput: lines %.1f MiB/s, direct buffer %.1f MiB/s%n",
+                mibPerSecond(lineBytes, lineNanos),
+                mibPerSecond(directBytes, directNanos)
+            );
+
+            runtime.remove(box.id(), true).join();
+            box.close();
+        });
+    }
+
     @Test
     void execCanBeKilled() throws Exception {
         runVmTest("case-exec-kill", runtime -> {
@@ -280,6 +405,51 @@ class BoxliteSmokeTest {
         });
     }
 
+    @Test
+    void concurrentCreateExecFreeCyclesDoNotStall() throws Exception {
+        runVmTest("case-jni-stress", runtime -> {
+            BoxOptions options = BoxOptions.builder()
+                .autoRemove(false)
+                .build();
+            BoxHandle box = runtime.create(options, "java-jni-stress-" + UUID.randomUUID()).join();
+            String boxId = box.id();
+
+            ExecutorService pool = Executors.newFixedThreadPool(STRESS_THREADS);
+            try {
+                List<Future<?>> workers = new ArrayList<>();
+                for (int t = 0; t < STRESS_THREADS; t++) {
+                    int worker = t;
+                    workers.add(pool.submit(() -> {
+                        for (int cycle = 0; cycle < STRESS_CYCLES; cycle++) {
+                            String expected = worker + "-" + cycle;
+                            BoxHandle handle = runtime.get(boxId).join().orElseThrow();
+                            ExecutionHandle exec = handle.exec(
+                                ExecCommand.builder("echo").addArg(expected).build()
+                            ).join();
+
+                            String line = exec.stdoutNextLine().join().orElseThrow();
+                            ExecResult result = exec.waitFor().join();
+                            assertEquals(expected, line.strip());
+                            assertTrue(result.success(), "echo should succeed");
+
+                            exec.close();
+                            handle.close();
+                        }
+                        return null;
+                    }));
+                }
+                for (Future<?> worker : workers) {
+                    worker.get(5, TimeUnit.MINUTES);
+                }
+            } finally {
+                pool.shutdownNow();
+            }
+
+            runtime.remove(boxId, true).join();
+            box.close();
+        });
+    }
+
     @Test
     void runtimeRemoveMissingBoxThrowsNotFound() {
         try (TestRuntime fixture = newRuntimeForTest("case-remove-missing")) {
@@ -330,6 +500,10 @@ class BoxliteSmokeTest {
         return new TestRuntime(Boxlite.newRuntime(options), SHARED_RUNTIME_HOME);
     }
 
+    private static double mibPerSecond(long bytes, long nanos) {
+        return bytes / (1024.0 * 1024.0) / (nanos / 1_000_000_000.0);
+    }
+
     private static RuntimeException joinFailure(java.util.concurrent.CompletableFuture<?> future) {
         try {
             future.join();
@@ -0,0 +1,30 @@
+package io.boxlite;
+
+import static org.junit.jupiter.api.Assertions.assertEquals;
+import static org.junit.jupiter.api.Assertions.assertThrows;
+
+import java.time.Duration;
+import org.junit.jupiter.api.Test;
+
+class CallTimeoutTest {
+    @Test
+    void nullAndZeroMeanNoTimeout() {
+        assertEquals(0L, BoxliteRuntime.callTimeoutMillis(null));
+        assertEquals(0L, BoxliteRuntime.callTimeoutMillis(Duration.ZERO));
+    }
+
+    @Test
+    void convertsToMillis() {
+        assertEquals(1_500L, BoxliteRuntime.callTimeoutMillis(Duration.ofMillis(1_500)));
+    }
+
+    @Test
+    void roundsSubMillisecondUp() {
+        assertEquals(1L, BoxliteRuntime.callTimeoutMillis(Duration.ofNanos(10)));
+    }
+
+    @Test
+    void rejectsNegativeTimeout() {
+        assertThrows(ConfigException.class, () -> BoxliteRuntime.callTimeoutMillis(Duration.ofSeconds(-1)));
+    }
+}
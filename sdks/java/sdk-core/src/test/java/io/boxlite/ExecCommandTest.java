@@ -2,6 +2,7 @@ package io.boxlite;
 
 import static org.junit.jupiter.api.Assertions.assertEquals;
 import static org.junit.jupiter.api.Assertions.assertFalse;
+import static org.junit.jupiter.api.Assertions.assertNull;
 import static org.junit.jupiter.api.Assertions.assertThrows;
 import static org.junit.jupiter.api.Assertions.assertTrue;
 
@@ -18,6 +19,7 @@ class ExecCommandTest {
             .workingDir("/workspace")
             .timeoutMillis(1_234L)
             .tty(true)
+            .scratch(256, "/scratch")
             .build();
 
         assertEquals("sh", command.command());
@@ -26,6 +28,8 @@ class ExecCommandTest {
         assertEquals("/workspace", command.workingDir());
         assertEquals(1_234L, command.timeoutMillis());
         assertTrue(command.tty());
+        assertEquals(256, command.scratch().sizeMib());
+        assertEquals("/scratch", command.scratch().mountAt());
     }
 
     @Test
@@ -41,6 +45,34 @@ class ExecCommandTest {
         );
     }
 
+    @Test
+    void builderRejectsInvalidScratch() {
+        assertThrows(
+            ConfigException.class,
+            () -> ExecCommand.builder("echo").scratch(0, "/scratch")
+        );
+        assertThrows(
+            ConfigException.class,
+            () -> ExecCommand.builder("echo").scratch(64, "scratch")
+        );
+    }
+
+    @Test
+    void scratchSerializesAsNativeDto() {
+        String json = JsonSupport.write(ExecCommand.builder("echo").scratch(64, "/scratch").build());
+        Map<?, ?> scratch = (Map<?, ?>) JsonSupport.read(json, Map.class).get("scratch");
+
+        assertEquals(64, scratch.get("sizeMib"));
+        assertEquals("/scratch", scratch.get("mountAt"));
+    }
+
+    @Test
+    void outputEncodingSerializesAsWireName() {
+        String json = JsonSupport.write(ExecCommand.builder("cat").outputEncoding(OutputEncoding.RAW).build());
+
+        assertEquals("raw", JsonSupport.read(json, Map.class).get("outputEncoding"));
+    }
+
     @Test
     void builderAllowsUnsetOptionalFields() {
         ExecCommand command = ExecCommand.builder("echo")
@@ -51,5 +83,7 @@ class ExecCommandTest {
         assertEquals("echo", command.command());
         assertEquals(java.util.List.of("ok"), command.args());
         assertFalse(command.tty());
+        assertNull(command.scratch());
+        assertEquals(OutputEncoding.UTF8_LOSSY, command.outputEncoding());
     }
 }
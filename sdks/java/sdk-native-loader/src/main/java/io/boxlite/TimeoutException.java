@@ -0,0 +1,13 @@
+package io.boxlite;
+
+/** 当原生调用超过调用方指定的超时时间时抛出的异常。 */
+public final class TimeoutException extends BoxliteException {
+    /**
+     * 使用超时错误信息创建异常。
+     *
+     * @param message 错误信息。
+     */
+    public TimeoutException(String message) {
+        super(message);
+    }
+}
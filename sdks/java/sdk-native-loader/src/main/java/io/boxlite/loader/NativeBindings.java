@@ -1,10 +1,15 @@
 package io.boxlite.loader;
 
 import io.boxlite.BoxliteException;
+import java.nio.ByteBuffer;
 
-/** JNI bridge for runtime and box lifecycle operations. */
+/**
+ * JNI bridge for runtime and box lifecycle operations.
+ *
+ * <p>Long-running calls take a {@code callTimeoutMillis} argument; {@code 0} waits indefinitely.
+ */
 public final class NativeBindings {
-    private static final int EXPECTED_ABI_VERSION = 2;
+    private static final int EXPECTED_ABI_VERSION = 5;
 
     static {
         NativeLoader.load();
@@ -67,8 +72,21 @@ public final class NativeBindings {
         return nativeRuntimeMetrics(runtimeHandle);
     }
 
-    public static void runtimeShutdown(long runtimeHandle, Integer timeoutSeconds) {
-        nativeRuntimeShutdown(runtimeHandle, timeoutSeconds);
+    public static String runtimeVersionInfo(long runtimeHandle) {
+        return nativeRuntimeVersionInfo(runtimeHandle);
+    }
+
+    public static void runtimeShutdown(long runtimeHandle, Integer timeoutSeconds, long callTimeoutMillis) {
+        nativeRuntimeShutdown(runtimeHandle, timeoutSeconds, callTimeoutMillis);
+    }
+
+    public static String runtimeRunOnce(
+        long runtimeHandle,
+        String boxOptionsJson,
+        String execCommandJson,
+        String runOnceOptionsJson
+    ) {
+        return nativeRuntimeRunOnce(runtimeHandle, boxOptionsJson, execCommandJson, runOnceOptionsJson);
     }
 
     public static void boxFree(long boxHandle) {
@@ -87,34 +105,48 @@ public final class NativeBindings {
         return nativeBoxInfo(boxHandle);
     }
 
-    public static void boxStart(long boxHandle) {
-        nativeBoxStart(boxHandle);
+    public static void boxStart(long boxHandle, long callTimeoutMillis) {
+        nativeBoxStart(boxHandle, callTimeoutMillis);
+    }
+
+    public static void boxStop(long boxHandle, long callTimeoutMillis) {
+        nativeBoxStop(boxHandle, callTimeoutMillis);
+    }
+
+    public static long boxExec(long boxHandle, String execCommandJson, long callTimeoutMillis) {
+        return nativeBoxExec(boxHandle, execCommandJson, callTimeoutMillis);
     }
 
-    public static void boxStop(long boxHandle) {
-        nativeBoxStop(boxHandle);
+    public static long boxPrepareExec(long boxHandle, String execCommandJson) {
+        return nativeBoxPrepareExec(boxHandle, execCommandJson);
     }
 
-    public static long boxExec(long boxHandle, String execCommandJson) {
-        return nativeBoxExec(boxHandle, execCommandJson);
+    public static long preparedExecRun(long preparedHandle, String argsJson) {
+        return nativePreparedExecRun(preparedHandle, argsJson);
+    }
+
+    public static void preparedExecFree(long preparedHandle) {
+        nativePreparedExecFree(preparedHandle);
     }
 
     public static void boxCopyIn(
         long boxHandle,
         String hostPath,
         String containerDest,
-        String copyOptionsJson
+        String copyOptionsJson,
+        long callTimeoutMillis
     ) {
-        nativeBoxCopyIn(boxHandle, hostPath, containerDest, copyOptionsJson);
+        nativeBoxCopyIn(boxHandle, hostPath, containerDest, copyOptionsJson, callTimeoutMillis);
     }
 
     public static void boxCopyOut(
         long boxHandle,
         String containerSrc,
         String hostDest,
-        String copyOptionsJson
+        String copyOptionsJson,
+        long callTimeoutMillis
     ) {
-        nativeBoxCopyOut(boxHandle, containerSrc, hostDest, copyOptionsJson);
+        nativeBoxCopyOut(boxHandle, containerSrc, hostDest, copyOptionsJson, callTimeoutMillis);
     }
 
     public static void executionFree(long executionHandle) {
@@ -129,6 +161,15 @@ public final class NativeBindings {
         nativeExecutionStdinWrite(executionHandle, data);
     }
 
+    public static void executionStdinWriteDirect(
+        long executionHandle,
+        ByteBuffer buffer,
+        int offset,
+        int length
+    ) {
+        nativeExecutionStdinWriteDirect(executionHandle, buffer, offset, length);
+    }
+
     public static void executionStdinClose(long executionHandle) {
         nativeExecutionStdinClose(executionHandle);
     }
@@ -137,12 +178,24 @@ public final class NativeBindings {
         return nativeExecutionStdoutNextLine(executionHandle);
     }
 
+    public static byte[] executionStdoutNextLineBytes(long executionHandle) {
+        return nativeExecutionStdoutNextLineBytes(executionHandle);
+    }
+
+    public static int executionStdoutReadInto(long executionHandle, ByteBuffer buffer, int maxLength) {
+        return nativeExecutionStdoutReadInto(executionHandle, buffer, maxLength);
+    }
+
     public static String executionStderrNextLine(long executionHandle) {
         return nativeExecutionStderrNextLine(executionHandle);
     }
 
-    public static String executionWait(long executionHandle) {
-        return nativeExecutionWait(executionHandle);
+    public static byte[] executionStderrNextLineBytes(long executionHandle) {
+        return nativeExecutionStderrNextLineBytes(executionHandle);
+    }
+
+    public static String executionWait(long executionHandle, long callTimeoutMillis) {
+        return nativeExecutionWait(executionHandle, callTimeoutMillis);
     }
 
     public static void executionKill(long executionHandle) {
@@ -181,7 +234,20 @@ public final class NativeBindings {
 
     private static native String nativeRuntimeMetrics(long runtimeHandle);
 
-    private static native void nativeRuntimeShutdown(long runtimeHandle, Integer timeoutSeconds);
+    private static native String nativeRuntimeVersionInfo(long runtimeHandle);
+
+    private static native void nativeRuntimeShutdown(
+        long runtimeHandle,
+        Integer timeoutSeconds,
+        long callTimeoutMillis
+    );
+
+    private static native String nativeRuntimeRunOnce(
+        long runtimeHandle,
+        String boxOptionsJson,
+        String execCommandJson,
+        String runOnceOptionsJson
+    );
 
     private static native void nativeBoxFree(long boxHandle);
 
@@ -191,24 +257,32 @@ public final class NativeBindings {
 
     private static native String nativeBoxInfo(long boxHandle);
 
-    private static native void nativeBoxStart(long boxHandle);
+    private static native void nativeBoxStart(long boxHandle, long callTimeoutMillis);
+
+    private static native void nativeBoxStop(long boxHandle, long callTimeoutMillis);
 
-    private static native void nativeBoxStop(long boxHandle);
+    private static native long nativeBoxExec(long boxHandle, String execCommandJson, long callTimeoutMillis);
 
-    private static native long nativeBoxExec(long boxHandle, String execCommandJson);
+    private static native long nativeBoxPrepareExec(long boxHandle, String execCommandJson);
+
+    private static native long nativePreparedExecRun(long preparedHandle, String argsJson);
+
+    private static native void nativePreparedExecFree(long preparedHandle);
 
     private static native void nativeBoxCopyIn(
         long boxHandle,
         String hostPath,
         String containerDest,
-        String copyOptionsJson
+        String copyOptionsJson,
+        long callTimeoutMillis
     );
 
     private static native void nativeBoxCopyOut(
         long boxHandle,
         String containerSrc,
         String hostDest,
-        String copyOptionsJson
+        String copyOptionsJson,
+        long callTimeoutMillis
     );
 
     private static native void nativeExecutionFree(long executionHandle);
@@ -217,13 +291,30 @@ public final class NativeBindings {
 
     private static native void nativeExecutionStdinWrite(long executionHandle, byte[] data);
 
+    private static native void nativeExecutionStdinWriteDirect(
+        long executionHandle,
+        ByteBuffer buffer,
+        int offset,
+        int length
+    );
+
     private static native void nativeExecutionStdinClose(long executionHandle);
 
     private static native String nativeExecutionStdoutNextLine(long executionHandle);
 
+    private static native byte[] nativeExecutionStdoutNextLineBytes(long executionHandle);
+
+    private static native int nativeExecutionStdoutReadInto(
+        long executionHandle,
+        ByteBuffer buffer,
+        int maxLength
+    );
+
     private static native String nativeExecutionStderrNextLine(long executionHandle);
 
-    private static native String nativeExecutionWait(long executionHandle);
+    private static native byte[] nativeExecutionStderrNextLineBytes(long executionHandle);
+
+    private static native String nativeExecutionWait(long executionHandle, long callTimeoutMillis);
 
     private static native void nativeExecutionKill(long executionHandle);
 
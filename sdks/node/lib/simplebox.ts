@@ -139,6 +139,9 @@ export interface SimpleBoxOptions {
   /** Run box in detached mode (survives parent process exit, default: false) */
   detach?: boolean;
 
+  /** Run the entrypoint under a minimal init that reaps zombies and forwards signals (default: false) */
+  init?: boolean;
+
   /** Working directory inside container */
   workingDir?: string;
 
@@ -279,6 +282,7 @@ export class SimpleBox {
       diskSizeGb: options.diskSizeGb,
       autoRemove: options.autoRemove ?? true,
       detach: options.detach ?? false,
+      init: options.init ?? false,
       workingDir: options.workingDir,
       env: options.env
         ? Object.entries(options.env).map(([key, value]) => ({ key, value }))
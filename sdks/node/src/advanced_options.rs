@@ -83,6 +83,7 @@ impl From<JsSecurityOptions> for SecurityOptions {
             max_processes: coerce_optional_u64_limit(js_opts.max_processes),
             max_memory: coerce_optional_u64_limit(js_opts.max_memory),
             max_cpu_time: coerce_optional_u64_limit(js_opts.max_cpu_time),
+            ..Default::default()
         };
 
         opts
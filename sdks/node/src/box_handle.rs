@@ -196,7 +196,11 @@ impl JsBox {
         let opts = into_copy_options(options);
 
         self.handle
-            .copy_into(std::path::Path::new(&host_path), &container_dest, opts)
+            .copy_into(
+                &boxlite::normalize_host_path(&host_path),
+                &container_dest,
+                opts,
+            )
             .await
             .map_err(map_err)
     }
@@ -212,7 +216,11 @@ impl JsBox {
         let opts = into_copy_options(options);
 
         self.handle
-            .copy_out(&container_src, std::path::Path::new(&host_dest), opts)
+            .copy_out(
+                &container_src,
+                &boxlite::normalize_host_path(&host_dest),
+                opts,
+            )
             .await
             .map_err(map_err)
     }
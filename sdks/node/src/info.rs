@@ -12,7 +12,7 @@ use napi_derive::napi;
 #[napi(object)]
 #[derive(Clone, Debug)]
 pub struct JsBoxStateInfo {
-    /// Current lifecycle status ("configured", "running", "stopped", etc.)
+    /// Current lifecycle status ("created", "running", "exited", "dead", etc.)
     pub status: String,
 
     /// Whether the box is currently running
@@ -20,18 +20,23 @@ pub struct JsBoxStateInfo {
 
     /// Process ID of the VMM subprocess (undefined if not running)
     pub pid: Option<u32>,
+
+    /// Exit code of the last run (undefined unless exited)
+    pub exit_code: Option<i32>,
 }
 
 fn status_to_string(status: BoxStatus) -> String {
     match status {
         BoxStatus::Unknown => "unknown",
-        BoxStatus::Configured => "configured",
+        BoxStatus::Created => "created",
         BoxStatus::Running => "running",
         BoxStatus::Stopping => "stopping",
-        BoxStatus::Stopped => "stopped",
+        BoxStatus::Exited => "exited",
+        BoxStatus::Dead => "dead",
         BoxStatus::Snapshotting => "snapshotting",
         BoxStatus::Restoring => "restoring",
         BoxStatus::Exporting => "exporting",
+        BoxStatus::Compacting => "compacting",
     }
     .to_string()
 }
@@ -75,6 +80,7 @@ impl From<BoxInfo> for JsBoxInfo {
             status: status_to_string(info.status),
             running: info.status.is_running(),
             pid: info.pid,
+            exit_code: info.exit_code,
         };
 
         Self {
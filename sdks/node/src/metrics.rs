@@ -60,6 +60,8 @@ pub struct JsBoxMetrics {
     pub cpu_percent: Option<f64>,
     /// Memory usage in bytes
     pub memory_bytes: Option<f64>,
+    /// Swap in use inside the VM in bytes (None without swap)
+    pub swap_bytes: Option<f64>,
 
     // Network metrics
     /// Network bytes sent (host to guest)
@@ -102,6 +104,7 @@ impl From<BoxMetrics> for JsBoxMetrics {
             // Resource usage
             cpu_percent: m.cpu_percent.map(|v| v as f64),
             memory_bytes: m.memory_bytes.map(|v| v as f64),
+            swap_bytes: m.swap_bytes.map(|v| v as f64),
 
             // Network metrics (convert u64 to f64 for JavaScript)
             network_bytes_sent: m.network_bytes_sent.map(|v| v as f64),
@@ -1,9 +1,10 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 use boxlite::runtime::advanced_options::{AdvancedBoxOptions, SecurityOptions};
 use boxlite::runtime::constants::images;
 use boxlite::runtime::options::{
-    BoxOptions, BoxliteOptions, NetworkSpec, PortProtocol, PortSpec, RootfsSpec, VolumeSpec,
+    BoxOptions, BoxliteOptions, NetworkMode, PortProtocol, PortSpec, RootfsSpec, VolumeSpec,
 };
 use napi_derive::napi;
 
@@ -21,6 +22,8 @@ pub struct JsOptions {
     /// Tried in order; first successful pull wins.
     /// Example: ["ghcr.io", "quay.io", "docker.io"]
     pub image_registries: Option<Vec<String>>,
+    /// Air-gap mode: refuse all registry access, use only cached images.
+    pub offline: Option<bool>,
 }
 
 impl From<JsOptions> for BoxliteOptions {
@@ -35,6 +38,10 @@ impl From<JsOptions> for BoxliteOptions {
             config.image_registries = registries;
         }
 
+        if let Some(offline) = js_opts.offline {
+            config.offline = offline;
+        }
+
         config
     }
 }
@@ -60,16 +67,28 @@ pub struct JsBoxOptions {
     /// Disk size in GB for container rootfs (sparse, grows as needed)
     pub disk_size_gb: Option<f64>,
 
+    /// Swap inside the VM in MiB, on the container disk (default: none)
+    pub swap_mib: Option<u32>,
+
     /// Working directory inside container (default: /root)
     pub working_dir: Option<String>,
 
     /// Environment variables as array of {key, value} objects
     pub env: Option<Vec<JsEnvVar>>,
 
+    /// Time zone: an IANA name like "Europe/Berlin", or "host" (default: the image's)
+    pub timezone: Option<String>,
+
+    /// Locale, e.g. "en_US.UTF-8", setting LANG (default: the image's)
+    pub locale: Option<String>,
+
+    /// Labels for grouping boxes, e.g. `{ tier: "web" }`
+    pub labels: Option<HashMap<String, String>>,
+
     /// Volume mounts as array of volume specs
     pub volumes: Option<Vec<JsVolumeSpec>>,
 
-    /// Network mode ("isolated" - only option currently)
+    /// Network mode: "default" or "none" (loopback only, no ports)
     pub network: Option<String>,
 
     /// Port mappings as array of port specs
@@ -98,6 +117,10 @@ pub struct JsBoxOptions {
     /// If None, uses the image's USER directive (defaults to root).
     pub user: Option<String>,
 
+    /// Run the entrypoint under a minimal init that reaps zombie processes
+    /// and forwards signals, like `docker run --init` (default: false).
+    pub init: Option<bool>,
+
     /// Security isolation options for the box.
     pub security: Option<JsSecurityOptions>,
 }
@@ -184,10 +207,10 @@ impl From<JsBoxOptions> for BoxOptions {
             .map(VolumeSpec::from)
             .collect();
 
-        // Convert network spec
+        // Convert network mode
         let network = match js_opts.network.as_deref() {
-            Some(s) if s.eq_ignore_ascii_case("isolated") => NetworkSpec::Isolated,
-            _ => NetworkSpec::Isolated,
+            Some(s) if s.eq_ignore_ascii_case("none") => NetworkMode::None,
+            _ => NetworkMode::Default,
         };
 
         // Convert ports
@@ -226,9 +249,16 @@ impl From<JsBoxOptions> for BoxOptions {
         BoxOptions {
             cpus: js_opts.cpus,
             memory_mib: js_opts.memory_mib,
+            priority: Default::default(),
+            cpu_shares: None,
             disk_size_gb: js_opts.disk_size_gb.map(|v| v as u64),
+            swap_mib: js_opts.swap_mib,
             working_dir: js_opts.working_dir,
             env,
+            redact_env: Vec::new(),
+            timezone: js_opts.timezone,
+            locale: js_opts.locale,
+            labels: js_opts.labels.unwrap_or_default(),
             rootfs,
             volumes,
             network,
@@ -242,6 +272,18 @@ impl From<JsBoxOptions> for BoxOptions {
             entrypoint: js_opts.entrypoint,
             cmd: js_opts.cmd,
             user: js_opts.user,
+            userns: None,
+            init: js_opts.init.unwrap_or(false),
+            entrypoint_script: None,
+            capture_core_dumps: false,
+            read_only_rootfs: false,
+            writable_paths: Vec::new(),
+            shutdown_grace_period: None,
+            auto_stop_after_idle: None,
+            idle_cpu_threshold: None,
+            snapshot_retention: None,
+            setup_commands: Vec::new(),
+            setup_failure: Default::default(),
         }
     }
 }
@@ -181,6 +181,7 @@ impl From<PySecurityOptions> for SecurityOptions {
                 max_processes: py_opts.max_processes,
                 max_memory: py_opts.max_memory,
                 max_cpu_time: py_opts.max_cpu_time,
+                ..Default::default()
             },
             ..Default::default()
         }
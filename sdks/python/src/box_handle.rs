@@ -156,7 +156,11 @@ impl PyBox {
                 copy_options.map_or_else(boxlite::CopyOptions::default, Into::into);
 
             handle
-                .copy_into(std::path::Path::new(&host_path), &container_dest, opts)
+                .copy_into(
+                    &boxlite::normalize_host_path(&host_path),
+                    &container_dest,
+                    opts,
+                )
                 .await
                 .map_err(map_err)?;
             Ok(())
@@ -178,7 +182,11 @@ impl PyBox {
                 copy_options.map_or_else(boxlite::CopyOptions::default, Into::into);
 
             handle
-                .copy_out(&container_src, std::path::Path::new(&host_dest), opts)
+                .copy_out(
+                    &container_src,
+                    &boxlite::normalize_host_path(&host_dest),
+                    opts,
+                )
                 .await
                 .map_err(map_err)?;
             Ok(())
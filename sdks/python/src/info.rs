@@ -14,6 +14,8 @@ pub struct PyBoxStateInfo {
     pub(crate) running: bool,
     #[pyo3(get)]
     pub(crate) pid: Option<u32>,
+    #[pyo3(get)]
+    pub(crate) exit_code: Option<i32>,
 }
 
 #[pymethods]
@@ -22,7 +24,8 @@ impl PyBoxStateInfo {
         serde_json::to_string_pretty(&serde_json::json!({
             "status": self.status,
             "running": self.running,
-            "pid": self.pid
+            "pid": self.pid,
+            "exit_code": self.exit_code
         }))
         .unwrap_or_default()
     }
@@ -31,13 +34,15 @@ impl PyBoxStateInfo {
 fn status_to_string(status: BoxStatus) -> String {
     match status {
         BoxStatus::Unknown => "unknown",
-        BoxStatus::Configured => "configured",
+        BoxStatus::Created => "created",
         BoxStatus::Running => "running",
         BoxStatus::Stopping => "stopping",
-        BoxStatus::Stopped => "stopped",
+        BoxStatus::Exited => "exited",
+        BoxStatus::Dead => "dead",
         BoxStatus::Snapshotting => "snapshotting",
         BoxStatus::Restoring => "restoring",
         BoxStatus::Exporting => "exporting",
+        BoxStatus::Compacting => "compacting",
     }
     .to_string()
 }
@@ -48,6 +53,7 @@ impl From<BoxStateInfo> for PyBoxStateInfo {
             status: status_to_string(info.status),
             running: info.running,
             pid: info.pid,
+            exit_code: info.exit_code,
         }
     }
 }
@@ -84,7 +90,8 @@ impl PyBoxInfo {
             "state": {
                 "status": self.state.status,
                 "running": self.state.running,
-                "pid": self.state.pid
+                "pid": self.state.pid,
+                "exit_code": self.state.exit_code
             },
             "image": self.image,
             "cpus": self.cpus,
@@ -101,6 +108,7 @@ impl From<BoxInfo> for PyBoxInfo {
             status: status_to_string(info.status),
             running: info.status.is_running(),
             pid: info.pid,
+            exit_code: info.exit_code,
         };
 
         PyBoxInfo {
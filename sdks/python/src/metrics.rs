@@ -62,6 +62,8 @@ pub(crate) struct PyBoxMetrics {
     #[pyo3(get)]
     pub(crate) memory_bytes: Option<u64>,
     #[pyo3(get)]
+    pub(crate) swap_bytes: Option<u64>,
+    #[pyo3(get)]
     pub(crate) network_bytes_sent: Option<u64>,
     #[pyo3(get)]
     pub(crate) network_bytes_received: Option<u64>,
@@ -110,6 +112,7 @@ impl From<BoxMetrics> for PyBoxMetrics {
             guest_boot_duration_ms: metrics.guest_boot_duration_ms(),
             cpu_percent: metrics.cpu_percent(),
             memory_bytes: metrics.memory_bytes(),
+            swap_bytes: metrics.swap_bytes(),
             network_bytes_sent: metrics.network_bytes_sent(),
             network_bytes_received: metrics.network_bytes_received(),
             network_tcp_connections: metrics.network_tcp_connections(),
@@ -5,7 +5,7 @@ use boxlite::CopyOptions;
 use boxlite::runtime::advanced_options::SecurityOptions;
 use boxlite::runtime::constants::images;
 use boxlite::runtime::options::{
-    BoxOptions, BoxliteOptions, NetworkSpec, PortProtocol, PortSpec, RootfsSpec, VolumeSpec,
+    BoxOptions, BoxliteOptions, NetworkMode, PortProtocol, PortSpec, RootfsSpec, VolumeSpec,
 };
 use pyo3::exceptions::PyRuntimeError;
 use pyo3::prelude::*;
@@ -22,23 +22,27 @@ pub(crate) struct PyOptions {
     /// Tried in order; first successful pull wins.
     #[pyo3(get, set)]
     pub(crate) image_registries: Vec<String>,
+    /// Air-gap mode: refuse all registry access, use only cached images.
+    #[pyo3(get, set)]
+    pub(crate) offline: bool,
 }
 
 #[pymethods]
 impl PyOptions {
     #[new]
-    #[pyo3(signature = (home_dir=None, image_registries=vec![]))]
-    fn new(home_dir: Option<String>, image_registries: Vec<String>) -> Self {
+    #[pyo3(signature = (home_dir=None, image_registries=vec![], offline=false))]
+    fn new(home_dir: Option<String>, image_registries: Vec<String>, offline: bool) -> Self {
         Self {
             home_dir,
             image_registries,
+            offline,
         }
     }
 
     fn __repr__(&self) -> String {
         format!(
-            "Options(home_dir={:?}, image_registries={:?})",
-            self.home_dir, self.image_registries
+            "Options(home_dir={:?}, image_registries={:?}, offline={})",
+            self.home_dir, self.image_registries, self.offline
         )
     }
 }
@@ -52,6 +56,7 @@ impl From<PyOptions> for BoxliteOptions {
         }
 
         config.image_registries = py_opts.image_registries;
+        config.offline = py_opts.offline;
 
         config
     }
@@ -102,6 +107,7 @@ impl From<PyCopyOptions> for CopyOptions {
             overwrite: opt.overwrite,
             follow_symlinks: opt.follow_symlinks,
             include_parent: opt.include_parent,
+            ..Default::default()
         }
     }
 }
@@ -151,6 +157,10 @@ pub(crate) struct PyBoxOptions {
     /// Advanced options for expert users (security, mount isolation).
     #[pyo3(get, set)]
     pub(crate) advanced: Option<PyAdvancedBoxOptions>,
+    /// Run the entrypoint under a minimal init that reaps zombie processes
+    /// and forwards signals, like `docker run --init`.
+    #[pyo3(get, set)]
+    pub(crate) init: Option<bool>,
 }
 
 #[pymethods]
@@ -173,6 +183,7 @@ impl PyBoxOptions {
         cmd=None,
         user=None,
         advanced=None,
+        init=None,
     ))]
     #[allow(clippy::too_many_arguments)]
     fn new(
@@ -192,6 +203,7 @@ impl PyBoxOptions {
         cmd: Option<Vec<String>>,
         user: Option<String>,
         advanced: Option<PyAdvancedBoxOptions>,
+        init: Option<bool>,
     ) -> Self {
         Self {
             image,
@@ -210,6 +222,7 @@ impl PyBoxOptions {
             cmd,
             user,
             advanced,
+            init,
         }
     }
 
@@ -230,10 +243,8 @@ impl From<PyBoxOptions> for BoxOptions {
         let volumes = py_opts.volumes.into_iter().map(VolumeSpec::from).collect();
 
         let network = match py_opts.network {
-            // Some(ref s) if s.eq_ignore_ascii_case("host") => NetworkSpec::Host,
-            Some(ref s) if s.eq_ignore_ascii_case("isolated") => NetworkSpec::Isolated,
-            // Some(s) if !s.is_empty() => NetworkSpec::Custom(s),
-            _ => NetworkSpec::Isolated,
+            Some(ref s) if s.eq_ignore_ascii_case("none") => NetworkMode::None,
+            _ => NetworkMode::Default,
         };
 
         let ports = py_opts.ports.into_iter().map(PortSpec::from).collect();
@@ -263,6 +274,7 @@ impl From<PyBoxOptions> for BoxOptions {
             entrypoint: py_opts.entrypoint,
             cmd: py_opts.cmd,
             user: py_opts.user,
+            init: py_opts.init.unwrap_or(false),
             ..Default::default()
         };
 
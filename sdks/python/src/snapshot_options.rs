@@ -13,17 +13,29 @@ pub(crate) struct PySnapshotOptions {
     pub quiesce_timeout_secs: u64,
     #[pyo3(get, set)]
     pub stop_on_quiesce_fail: bool,
+    #[pyo3(get, set)]
+    pub metadata: bool,
+    #[pyo3(get, set)]
+    pub metadata_only: bool,
 }
 
 #[pymethods]
 impl PySnapshotOptions {
     #[new]
-    #[pyo3(signature = (quiesce=true, quiesce_timeout_secs=30, stop_on_quiesce_fail=true))]
-    fn new(quiesce: bool, quiesce_timeout_secs: u64, stop_on_quiesce_fail: bool) -> Self {
+    #[pyo3(signature = (quiesce=true, quiesce_timeout_secs=30, stop_on_quiesce_fail=true, metadata=false, metadata_only=false))]
+    fn new(
+        quiesce: bool,
+        quiesce_timeout_secs: u64,
+        stop_on_quiesce_fail: bool,
+        metadata: bool,
+        metadata_only: bool,
+    ) -> Self {
         Self {
             quiesce,
             quiesce_timeout_secs,
             stop_on_quiesce_fail,
+            metadata,
+            metadata_only,
         }
     }
 }
@@ -34,6 +46,9 @@ impl From<PySnapshotOptions> for SnapshotOptions {
             quiesce: py.quiesce,
             quiesce_timeout_secs: py.quiesce_timeout_secs,
             stop_on_quiesce_fail: py.stop_on_quiesce_fail,
+            retention: None,
+            metadata: py.metadata,
+            metadata_only: py.metadata_only,
         }
     }
 }
@@ -48,17 +63,20 @@ pub(crate) struct PyExportOptions {
     pub compression_level: i32,
     #[pyo3(get, set)]
     pub include_metadata: bool,
+    #[pyo3(get, set)]
+    pub seekable: bool,
 }
 
 #[pymethods]
 impl PyExportOptions {
     #[new]
-    #[pyo3(signature = (compress=true, compression_level=3, include_metadata=true))]
-    fn new(compress: bool, compression_level: i32, include_metadata: bool) -> Self {
+    #[pyo3(signature = (compress=true, compression_level=3, include_metadata=true, seekable=false))]
+    fn new(compress: bool, compression_level: i32, include_metadata: bool, seekable: bool) -> Self {
         Self {
             compress,
             compression_level,
             include_metadata,
+            seekable,
         }
     }
 }
@@ -69,6 +87,7 @@ impl From<PyExportOptions> for ExportOptions {
             compress: py.compress,
             compression_level: py.compression_level,
             include_metadata: py.include_metadata,
+            seekable: py.seekable,
         }
     }
 }
@@ -2,7 +2,7 @@
 
 use std::sync::Arc;
 
-use boxlite::{LiteBox, SnapshotInfo, SnapshotOptions};
+use boxlite::{LiteBox, RestoreOptions, SnapshotInfo, SnapshotOptions};
 use pyo3::prelude::*;
 
 use crate::snapshot_options::PySnapshotOptions;
@@ -27,6 +27,10 @@ pub(crate) struct PySnapshotInfo {
     pub container_disk_bytes: u64,
     #[pyo3(get)]
     pub size_bytes: u64,
+    #[pyo3(get)]
+    pub has_metadata: bool,
+    #[pyo3(get)]
+    pub metadata_only: bool,
 }
 
 #[pymethods]
@@ -50,6 +54,8 @@ impl From<SnapshotInfo> for PySnapshotInfo {
             guest_disk_bytes: r.guest_disk_bytes,
             container_disk_bytes: r.container_disk_bytes,
             size_bytes: r.size_bytes,
+            has_metadata: r.has_metadata,
+            metadata_only: r.metadata_only,
         }
     }
 }
@@ -114,11 +120,24 @@ impl PySnapshotHandle {
         })
     }
 
-    /// Restore the box's disks from a snapshot.
-    fn restore<'py>(&self, py: Python<'py>, name: String) -> PyResult<Bound<'py, PyAny>> {
+    /// Restore the box's disks from a snapshot, and with `restore_metadata`
+    /// the options and services captured with it.
+    #[pyo3(signature = (name, restore_metadata=false))]
+    fn restore<'py>(
+        &self,
+        py: Python<'py>,
+        name: String,
+        restore_metadata: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let handle = Arc::clone(&self.handle);
+        let mut opts = RestoreOptions::default();
+        opts.restore_metadata(restore_metadata);
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            handle.snapshot().restore(&name).await.map_err(map_err)?;
+            handle
+                .snapshot()
+                .restore(&name, opts)
+                .await
+                .map_err(map_err)?;
             Ok(())
         })
     }
@@ -109,7 +109,7 @@ class TestBoxManagement:
         info = runtime.get_info(box.id)
         assert info is not None
         assert info.id == box.id
-        assert info.state.status in {"configured", "running"}
+        assert info.state.status in {"created", "running"}
         assert info.image == "python:3.11"
         assert info.cpus == 4
         assert info.memory_mib == 1024
@@ -126,11 +126,11 @@ class TestBoxManagement:
         boxes = [runtime.create_box() for _ in range(2)]
         running = runtime.list()
         ids = {box.id for box in boxes}
-        # Boxes may be in configured or running state
+        # Boxes may be in created or running state
         active_ids = {
             info.id
             for info in running
-            if info.state.status in {"configured", "running"}
+            if info.state.status in {"created", "running"}
         }
         assert ids.issubset(active_ids)
 
@@ -154,7 +154,7 @@ class TestBoxManagement:
         info = runtime.get_info(box.id)
         assert info is not None
         assert info.id == box.id
-        assert info.state.status in {"configured", "running"}
+        assert info.state.status in {"created", "running"}
 
     def test_get_box_info_nonexistent(self, runtime):
         assert runtime.get_info("nonexistent-id-12345678901") is None
@@ -164,11 +164,11 @@ class TestBoxManagement:
         opts = boxlite.BoxOptions(image="alpine:latest", auto_remove=False)
         box = runtime._runtime.create(opts)
         runtime._boxes.append(box)
-        assert runtime.get_info(box.id).state.status in {"configured", "running"}
+        assert runtime.get_info(box.id).state.status in {"created", "running"}
         box.stop()
         state_after_shutdown = runtime.get_info(box.id)
         assert state_after_shutdown is not None
-        assert state_after_shutdown.state.status == "stopped"
+        assert state_after_shutdown.state.status == "exited"
         runtime.remove(box.id)
         runtime.forget(box)
         assert runtime.get_info(box.id) is None
@@ -238,12 +238,12 @@ class TestBoxInfoObject:
             "memory_mib": info.memory_mib,
         }
         assert data["id"] == box.id
-        assert data["state"] in {"configured", "running"}  # May be configured initially
+        assert data["state"] in {"created", "running"}  # May be created initially
 
     def test_box_info_state_values(self, runtime):
         box = runtime.create_box()
         info = runtime.get_info(box.id)
-        assert info.state.status in {"configured", "running", "stopped", "failed"}
+        assert info.state.status in {"created", "running", "exited", "dead"}
 
 
 if __name__ == "__main__":
@@ -94,7 +94,7 @@ class TestAutoRemoveBehavior:
         # Box should still exist
         info = runtime.get_info(box_id)
         assert info is not None
-        assert info.state.status == "stopped"
+        assert info.state.status == "exited"
 
         # Cleanup
         runtime.remove(box_id)
@@ -195,7 +195,7 @@ class TestCombinedOptions:
         # Box still exists
         info = runtime.get_info(box_id)
         assert info is not None
-        assert info.state.status == "stopped"
+        assert info.state.status == "exited"
 
         # Can get new handle
         box2 = runtime.get(box_id)
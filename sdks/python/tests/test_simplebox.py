@@ -44,7 +44,7 @@ class TestSimpleBoxBasic:
             assert info is not None
             assert info.id == box.id
             # info.state is a BoxStateInfo object with status, pid, running fields
-            assert info.state.status in {"configured", "running"}
+            assert info.state.status in {"created", "running"}
 
 
 class TestSimpleBoxExec: